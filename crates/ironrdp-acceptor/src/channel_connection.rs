@@ -5,11 +5,16 @@ use ironrdp_core::WriteBuf;
 use ironrdp_pdu::{self as pdu, x224::X224};
 use pdu::mcs;
 
+// T.125 `Result` enumeration values.
+const RESULT_SUCCESSFUL: u8 = 0;
+const RESULT_NOT_ADMITTED: u8 = 6;
+
 #[derive(Debug)]
 pub struct ChannelConnectionSequence {
     state: ChannelConnectionState,
     user_channel_id: u16,
     channel_ids: Option<HashSet<u16>>,
+    denied_channel_ids: HashSet<u16>,
 }
 
 #[derive(Default, Debug)]
@@ -138,8 +143,15 @@ impl Sequence for ChannelConnectionSequence {
             }
 
             ChannelConnectionState::SendChannelJoinConfirm { remaining, channel_id } => {
+                let result = if self.denied_channel_ids.contains(&channel_id) {
+                    warn!(channel_id, "Channel join denied");
+                    RESULT_NOT_ADMITTED
+                } else {
+                    RESULT_SUCCESSFUL
+                };
+
                 let channel_confirm = mcs::ChannelJoinConfirm {
-                    result: 0,
+                    result,
                     initiator_id: self.user_channel_id,
                     requested_channel_id: channel_id,
                     channel_id,
@@ -178,14 +190,23 @@ impl ChannelConnectionSequence {
                     .chain(other_channels)
                     .collect(),
             ),
+            denied_channel_ids: HashSet::new(),
         }
     }
 
+    /// Channels for which the MCS Channel Join Request is answered with a `rt-not-admitted` result.
+    #[must_use]
+    pub fn with_denied_channels(mut self, denied_channel_ids: HashSet<u16>) -> Self {
+        self.denied_channel_ids = denied_channel_ids;
+        self
+    }
+
     pub fn skip_channel_join(user_channel_id: u16) -> Self {
         Self {
             state: ChannelConnectionState::WaitErectDomainRequest,
             user_channel_id,
             channel_ids: None,
+            denied_channel_ids: HashSet::new(),
         }
     }
 
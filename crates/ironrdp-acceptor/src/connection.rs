@@ -1,27 +1,46 @@
 use std::any::TypeId;
+use std::collections::HashSet;
 use std::mem;
 
+use ironrdp_connector::sspi::AuthIdentity;
 use ironrdp_connector::{
-    encode_x224_packet, reason_err, ConnectorError, ConnectorErrorExt, ConnectorResult, DesktopSize, Sequence, State,
-    Written,
+    encode_x224_packet, reason_err, ConnectorError, ConnectorErrorExt, ConnectorErrorKind, ConnectorResult,
+    DesktopSize, Sequence, State, Written,
 };
 use ironrdp_core::decode;
-use ironrdp_core::WriteBuf;
+use ironrdp_core::{DecodeLimits, DecodeOptions, WriteBuf};
 use ironrdp_pdu as pdu;
 use ironrdp_pdu::x224::X224;
 use ironrdp_svc::{StaticChannelSet, SvcServerProcessor};
+use pdu::gcc::EncryptionMethod;
+use pdu::pcb::{self, PreconnectionBlob};
+use pdu::rdp::autodetect::{
+    AutoDetectPhase, AutoDetectRequest, AutoDetectRequestPdu, AutoDetectResponse, AutoDetectResponsePdu,
+};
 use pdu::rdp::capability_sets::CapabilitySet;
 use pdu::rdp::headers::ShareControlPdu;
+use pdu::rdp::server_error_info::{ErrorInfo, ServerSetErrorInfoPdu};
 use pdu::rdp::server_license::{LicensePdu, LicensingErrorMessage};
+use pdu::rdp::standard_security::{
+    proprietary_server_certificate, SecurityExchangePdu, StandardSecurity, CLIENT_RANDOM_LEN, SERVER_RANDOM_LEN,
+};
 use pdu::{gcc, mcs, nego, rdp};
+use rand_core::{OsRng, RngCore as _};
 
 use super::channel_connection::ChannelConnectionSequence;
 use super::finalization::FinalizationSequence;
+use super::hooks::AcceptorHooks;
+use super::licensing::{LicenseProvider, LicensingConfig, LicensingSequence};
+use super::policy::CapabilityPolicy;
+use super::standard_security::{SecurityLayer, StandardSecurityConfig};
 use crate::util::{self, wrap_share_data};
 
 const IO_CHANNEL_ID: u16 = 1003;
 const USER_CHANNEL_ID: u16 = 1002;
 
+/// Size of the payload sent to the client for the connect-time bandwidth measurement.
+const AUTO_DETECT_PAYLOAD_SIZE: usize = 16 * 1024;
+
 pub struct Acceptor {
     state: AcceptorState,
     security: nego::SecurityProtocol,
@@ -31,13 +50,40 @@ pub struct Acceptor {
     server_capabilities: Vec<CapabilitySet>,
     static_channels: StaticChannelSet,
     saved_for_reactivation: AcceptorState,
+    hooks: Option<Box<dyn AcceptorHooks>>,
+    policy: CapabilityPolicy,
+    auto_detect: bool,
+    auto_detected_bandwidth: Option<u32>,
+    standard_security: Option<StandardSecurityConfig>,
+    /// Encryption method and server random sent in the Server Security Data, until the security exchange
+    pending_security_exchange: Option<(EncryptionMethod, [u8; SERVER_RANDOM_LEN])>,
+    security_layer: Option<SecurityLayer>,
+    preconnection_blob: Option<PreconnectionBlob>,
+    decode_limits: DecodeLimits,
+    /// Taken when the licensing exchange starts, it is not repeated on reactivation
+    licensing: Option<(LicensingConfig, Box<dyn LicenseProvider>)>,
 }
 
 #[derive(Debug)]
 pub struct AcceptorResult {
     pub static_channels: StaticChannelSet,
+    /// Client capability sets, constrained by the [`CapabilityPolicy`]
     pub capabilities: Vec<CapabilitySet>,
+    pub desktop_size: DesktopSize,
+    /// Early capabilities advertised by the client in the Client Core Data
+    pub early_capability: Option<gcc::ClientEarlyCapabilityFlags>,
+    /// Bandwidth measured during the connect-time auto-detection, in kilobits per second
+    pub auto_detected_bandwidth: Option<u32>,
+    /// Encryption of the session frames, when Standard RDP Security is used
+    ///
+    /// All the frames exchanged after the connection sequence, including the unmatched ones,
+    /// are encrypted and must go through this layer. [`Self::input_events`] are already decrypted.
+    pub security_layer: Option<SecurityLayer>,
+    /// Preconnection PDU sent by the client before the X.224 Connection Request, if any
+    pub preconnection_blob: Option<PreconnectionBlob>,
     pub input_events: Vec<Vec<u8>>,
+    /// Bitmap keys advertised by the client in the Persistent Key List PDUs, for each cache cell
+    pub persistent_keys: Vec<Vec<u64>>,
     pub user_channel_id: u16,
     pub io_channel_id: u16,
 }
@@ -53,10 +99,22 @@ impl Acceptor {
             server_capabilities: capabilities,
             static_channels: StaticChannelSet::new(),
             saved_for_reactivation: Default::default(),
+            hooks: None,
+            policy: CapabilityPolicy::default(),
+            auto_detect: false,
+            auto_detected_bandwidth: None,
+            standard_security: None,
+            pending_security_exchange: None,
+            security_layer: None,
+            preconnection_blob: None,
+            decode_limits: DecodeLimits::default(),
+            licensing: None,
         }
     }
 
     pub fn new_deactivation_reactivation(mut consumed: Acceptor, desktop_size: DesktopSize) -> Self {
+        let desktop_size = consumed.policy.clamp_desktop_size(desktop_size);
+
         let AcceptorState::CapabilitiesSendServer {
             early_capability,
             channels,
@@ -88,7 +146,70 @@ impl Acceptor {
             server_capabilities: consumed.server_capabilities,
             static_channels: StaticChannelSet::new(),
             saved_for_reactivation,
+            hooks: consumed.hooks,
+            policy: consumed.policy,
+            auto_detect: consumed.auto_detect,
+            auto_detected_bandwidth: None,
+            standard_security: consumed.standard_security,
+            pending_security_exchange: None,
+            security_layer: consumed.security_layer,
+            preconnection_blob: consumed.preconnection_blob,
+            decode_limits: consumed.decode_limits,
+            licensing: None,
+        }
+    }
+
+    /// Installs the authentication and authorization hooks consulted during the connection sequence.
+    pub fn set_hooks(&mut self, hooks: Box<dyn AcceptorHooks>) {
+        self.hooks = Some(hooks);
+    }
+
+    /// Constrains the capabilities negotiated with the client.
+    pub fn set_capability_policy(&mut self, policy: CapabilityPolicy) {
+        policy.apply(&mut self.server_capabilities);
+        self.desktop_size = policy.clamp_desktop_size(self.desktop_size);
+        self.policy = policy;
+    }
+
+    /// Enables the connect-time network auto-detection.
+    ///
+    /// The bandwidth is only measured if the client advertises support for the network characteristics
+    /// detection, and the result is reported in [`AcceptorResult::auto_detected_bandwidth`].
+    pub fn set_auto_detect(&mut self, enabled: bool) {
+        self.auto_detect = enabled;
+    }
+
+    /// Enables Standard RDP Security, for legacy clients connecting without TLS.
+    ///
+    /// This is only taken into account when the acceptor is created for the standard RDP security protocol.
+    /// Standard RDP Security is weak, and should be restricted to lab or embedded use cases.
+    pub fn set_standard_security(&mut self, config: StandardSecurityConfig) {
+        if !self.security.is_empty() {
+            warn!(security = ?self.security, "Standard RDP Security ignored for enhanced security protocols");
+            return;
         }
+
+        self.standard_security = Some(config);
+    }
+
+    /// Restores the security layer of a session going through a deactivation-reactivation sequence.
+    ///
+    /// The layer is carried over by [`Acceptor::new_deactivation_reactivation`] if still owned by the consumed acceptor,
+    /// this is only required once it was handed over by [`AcceptorResult::security_layer`].
+    pub fn set_security_layer(&mut self, layer: SecurityLayer) {
+        self.security_layer = Some(layer);
+    }
+
+    /// Enables the licensing exchange, the client licenses being issued and validated by `provider`.
+    ///
+    /// Otherwise, the licensing exchange is skipped by telling the client it already holds a valid license.
+    pub fn set_licensing(&mut self, config: LicensingConfig, provider: Box<dyn LicenseProvider>) {
+        self.licensing = Some((config, provider));
+    }
+
+    /// Bounds the resources claimed by the PDUs received from the client.
+    pub fn set_decode_limits(&mut self, limits: DecodeLimits) {
+        self.decode_limits = limits;
     }
 
     pub fn attach_static_channel<T>(&mut self, channel: T)
@@ -109,6 +230,14 @@ impl Acceptor {
         }
     }
 
+    /// Returns the Preconnection PDU sent by the client, once received
+    ///
+    /// Brokers and multi-tenant servers may use the PCB ID or string to route the connection
+    /// before it goes any further, e.g.: right after the X.224 Connection Request is received.
+    pub fn preconnection_blob(&self) -> Option<&PreconnectionBlob> {
+        self.preconnection_blob.as_ref()
+    }
+
     pub fn reached_security_upgrade(&self) -> Option<nego::SecurityProtocol> {
         match self.state {
             AcceptorState::SecurityUpgrade { .. } => Some(self.security),
@@ -116,16 +245,65 @@ impl Acceptor {
         }
     }
 
+    pub fn mark_security_upgrade_as_done(&mut self) {
+        assert!(self.reached_security_upgrade().is_some());
+        self.step(&[], &mut WriteBuf::new()).expect("transition to next state");
+        debug_assert!(self.reached_security_upgrade().is_none());
+    }
+
+    pub(crate) fn security(&self) -> nego::SecurityProtocol {
+        self.security
+    }
+
+    pub fn should_perform_credssp(&self) -> bool {
+        matches!(self.state, AcceptorState::Credssp { .. })
+    }
+
+    /// Ends the CredSSP sequence, once the client is authenticated as `identity`
+    ///
+    /// The [`AcceptorHooks::on_nla_complete`] hook decides whether the authenticated user is allowed to connect.
+    /// Returns `false` when it is not, the acceptor then fails with an
+    /// [`AccessDenied`](ConnectorErrorKind::AccessDenied) error.
+    pub fn mark_credssp_as_done(&mut self, identity: &AuthIdentity) -> bool {
+        let AcceptorState::Credssp { requested_protocol } = self.state else {
+            panic!("invalid acceptor state");
+        };
+
+        let allowed = self
+            .hooks
+            .as_mut()
+            .map_or(true, |hooks| hooks.on_nla_complete(identity));
+
+        self.state = if allowed {
+            AcceptorState::BasicSettingsWaitInitial { requested_protocol }
+        } else {
+            warn!(username = identity.username.account_name(), "Authenticated user denied");
+            AcceptorState::AccessDenied
+        };
+
+        allowed
+    }
+
     pub fn get_result(&mut self) -> Option<AcceptorResult> {
         match mem::take(&mut self.state) {
             AcceptorState::Accepted {
                 channels: _channels, // TODO: what about ChannelDef?
                 client_capabilities,
                 input_events,
+                persistent_keys,
             } => Some(AcceptorResult {
+                early_capability: match self.saved_for_reactivation {
+                    AcceptorState::CapabilitiesSendServer { early_capability, .. } => early_capability,
+                    _ => None,
+                },
                 static_channels: mem::take(&mut self.static_channels),
                 capabilities: client_capabilities,
+                desktop_size: self.desktop_size,
+                auto_detected_bandwidth: self.auto_detected_bandwidth,
+                security_layer: self.security_layer.take(),
+                preconnection_blob: self.preconnection_blob.clone(),
                 input_events,
+                persistent_keys,
                 user_channel_id: self.user_channel_id,
                 io_channel_id: self.io_channel_id,
             }),
@@ -146,9 +324,15 @@ pub enum AcceptorState {
     InitiationSendConfirm {
         requested_protocol: nego::SecurityProtocol,
     },
+    InitiationSendFailure {
+        code: nego::FailureCode,
+    },
     SecurityUpgrade {
         requested_protocol: nego::SecurityProtocol,
     },
+    Credssp {
+        requested_protocol: nego::SecurityProtocol,
+    },
     BasicSettingsWaitInitial {
         requested_protocol: nego::SecurityProtocol,
     },
@@ -156,6 +340,7 @@ pub enum AcceptorState {
         requested_protocol: nego::SecurityProtocol,
         early_capability: Option<gcc::ClientEarlyCapabilityFlags>,
         channels: Vec<(u16, Option<gcc::ChannelDef>)>,
+        denied_channels: HashSet<u16>,
     },
     ChannelConnection {
         early_capability: Option<gcc::ClientEarlyCapabilityFlags>,
@@ -170,10 +355,26 @@ pub enum AcceptorState {
         early_capability: Option<gcc::ClientEarlyCapabilityFlags>,
         channels: Vec<(u16, gcc::ChannelDef)>,
     },
+    SecureSettingsSendDenial {
+        error_info: ErrorInfo,
+    },
+    AutoDetectSendRequests {
+        early_capability: Option<gcc::ClientEarlyCapabilityFlags>,
+        channels: Vec<(u16, gcc::ChannelDef)>,
+    },
+    AutoDetectWaitResponse {
+        early_capability: Option<gcc::ClientEarlyCapabilityFlags>,
+        channels: Vec<(u16, gcc::ChannelDef)>,
+    },
     LicensingExchange {
         early_capability: Option<gcc::ClientEarlyCapabilityFlags>,
         channels: Vec<(u16, gcc::ChannelDef)>,
     },
+    LicenseIssuance {
+        early_capability: Option<gcc::ClientEarlyCapabilityFlags>,
+        channels: Vec<(u16, gcc::ChannelDef)>,
+        licensing: LicensingSequence,
+    },
     CapabilitiesSendServer {
         early_capability: Option<gcc::ClientEarlyCapabilityFlags>,
         channels: Vec<(u16, gcc::ChannelDef)>,
@@ -193,7 +394,9 @@ pub enum AcceptorState {
         channels: Vec<(u16, gcc::ChannelDef)>,
         client_capabilities: Vec<CapabilitySet>,
         input_events: Vec<Vec<u8>>,
+        persistent_keys: Vec<Vec<u64>>,
     },
+    AccessDenied,
 }
 
 impl State for AcceptorState {
@@ -202,18 +405,25 @@ impl State for AcceptorState {
             Self::Consumed => "Consumed",
             Self::InitiationWaitRequest => "InitiationWaitRequest",
             Self::InitiationSendConfirm { .. } => "InitiationSendConfirm",
+            Self::InitiationSendFailure { .. } => "InitiationSendFailure",
             Self::SecurityUpgrade { .. } => "SecurityUpgrade",
+            Self::Credssp { .. } => "Credssp",
             Self::BasicSettingsWaitInitial { .. } => "BasicSettingsWaitInitial",
             Self::BasicSettingsSendResponse { .. } => "BasicSettingsSendResponse",
             Self::ChannelConnection { .. } => "ChannelConnection",
             Self::RdpSecurityCommencement { .. } => "RdpSecurityCommencement",
             Self::SecureSettingsExchange { .. } => "SecureSettingsExchange",
+            Self::SecureSettingsSendDenial { .. } => "SecureSettingsSendDenial",
+            Self::AutoDetectSendRequests { .. } => "AutoDetectSendRequests",
+            Self::AutoDetectWaitResponse { .. } => "AutoDetectWaitResponse",
             Self::LicensingExchange { .. } => "LicensingExchange",
+            Self::LicenseIssuance { licensing, .. } => licensing.state().name(),
             Self::CapabilitiesSendServer { .. } => "CapabilitiesSendServer",
             Self::MonitorLayoutSend { .. } => "MonitorLayoutSend",
             Self::CapabilitiesWaitConfirm { .. } => "CapabilitiesWaitConfirm",
             Self::ConnectionFinalization { .. } => "ConnectionFinalization",
             Self::Accepted { .. } => "Connected",
+            Self::AccessDenied => "AccessDenied",
         }
     }
 
@@ -230,20 +440,31 @@ impl Sequence for Acceptor {
     fn next_pdu_hint(&self) -> Option<&dyn pdu::PduHint> {
         match &self.state {
             AcceptorState::Consumed => None,
+            AcceptorState::InitiationWaitRequest if self.preconnection_blob.is_none() => Some(&pcb::PCB_OR_X224_HINT),
             AcceptorState::InitiationWaitRequest => Some(&pdu::X224_HINT),
             AcceptorState::InitiationSendConfirm { .. } => None,
+            AcceptorState::InitiationSendFailure { .. } => None,
             AcceptorState::SecurityUpgrade { .. } => None,
+            AcceptorState::Credssp { .. } => None,
             AcceptorState::BasicSettingsWaitInitial { .. } => Some(&pdu::X224_HINT),
             AcceptorState::BasicSettingsSendResponse { .. } => None,
             AcceptorState::ChannelConnection { connection, .. } => connection.next_pdu_hint(),
+            AcceptorState::RdpSecurityCommencement { .. } if self.pending_security_exchange.is_some() => {
+                Some(&pdu::X224_HINT)
+            }
             AcceptorState::RdpSecurityCommencement { .. } => None,
             AcceptorState::SecureSettingsExchange { .. } => Some(&pdu::X224_HINT),
+            AcceptorState::SecureSettingsSendDenial { .. } => None,
+            AcceptorState::AutoDetectSendRequests { .. } => None,
+            AcceptorState::AutoDetectWaitResponse { .. } => Some(&pdu::X224_HINT),
             AcceptorState::LicensingExchange { .. } => None,
+            AcceptorState::LicenseIssuance { licensing, .. } => licensing.next_pdu_hint(),
             AcceptorState::CapabilitiesSendServer { .. } => None,
             AcceptorState::MonitorLayoutSend { .. } => None,
             AcceptorState::CapabilitiesWaitConfirm { .. } => Some(&pdu::X224_HINT),
             AcceptorState::ConnectionFinalization { finalization, .. } => finalization.next_pdu_hint(),
             AcceptorState::Accepted { .. } => None,
+            AcceptorState::AccessDenied => None,
         }
     }
 
@@ -252,7 +473,92 @@ impl Sequence for Acceptor {
     }
 
     fn step(&mut self, input: &[u8], output: &mut WriteBuf) -> ConnectorResult<Written> {
+        let options = DecodeOptions::default().with_limits(self.decode_limits);
+        ironrdp_core::with_decode_options(options, || self.step_impl(input, output))
+    }
+}
+
+impl Acceptor {
+    fn step_impl(&mut self, input: &[u8], output: &mut WriteBuf) -> ConnectorResult<Written> {
+        let Some(layer) = self.security_layer.as_mut() else {
+            return self.step_plain(input, output);
+        };
+
+        let input = if input.is_empty() {
+            Vec::new()
+        } else {
+            layer.decrypt_frame(input)?
+        };
+
+        // Licensing PDUs are not encrypted, unless the client advertises otherwise in its Client Info PDU.
+        let encrypt = !matches!(
+            self.state,
+            AcceptorState::LicensingExchange { .. } | AcceptorState::LicenseIssuance { .. }
+        );
+        let has_security_header = matches!(self.state, AcceptorState::AutoDetectSendRequests { .. });
+
+        let mut buf = WriteBuf::new();
+        if self.step_plain(&input, &mut buf)?.is_nothing() {
+            return Ok(Written::Nothing);
+        }
+
+        let layer = self
+            .security_layer
+            .as_mut()
+            .ok_or_else(|| ConnectorError::general("security layer is missing"))?;
+
+        let mut written = 0usize;
+        let mut frames = buf.filled();
+
+        while !frames.is_empty() {
+            let length = pdu::find_size(frames)
+                .map_err(ConnectorError::decode)?
+                .ok_or_else(|| ConnectorError::general("incomplete frame"))?
+                .length;
+            let (frame, rest) = frames.split_at(length);
+            frames = rest;
+
+            let frame = if encrypt {
+                layer.encrypt_frame(frame, has_security_header)?
+            } else {
+                frame.to_vec()
+            };
+
+            output.write_slice(&frame);
+            #[allow(clippy::arithmetic_side_effects)] // Bounded by the size of the output buffer.
+            {
+                written += frame.len();
+            }
+        }
+
+        Written::from_size(written)
+    }
+
+    fn step_plain(&mut self, input: &[u8], output: &mut WriteBuf) -> ConnectorResult<Written> {
         let (written, next_state) = match mem::take(&mut self.state) {
+            AcceptorState::InitiationWaitRequest
+                if self.preconnection_blob.is_none() && pcb::is_preconnection_blob(input) == Some(true) =>
+            {
+                let blob = decode::<PreconnectionBlob>(input).map_err(ConnectorError::decode)?;
+
+                debug!(message = ?blob, "Received");
+
+                let allowed = self
+                    .hooks
+                    .as_mut()
+                    .map_or(true, |hooks| hooks.on_preconnection_blob(&blob));
+                self.preconnection_blob = Some(blob);
+
+                let next_state = if allowed {
+                    AcceptorState::InitiationWaitRequest
+                } else {
+                    warn!("Preconnection PDU denied");
+                    AcceptorState::AccessDenied
+                };
+
+                (Written::Nothing, next_state)
+            }
+
             AcceptorState::InitiationWaitRequest => {
                 let connection_request = decode::<X224<nego::ConnectionRequest>>(input)
                     .map_err(ConnectorError::decode)
@@ -260,12 +566,19 @@ impl Sequence for Acceptor {
 
                 debug!(message = ?connection_request, "Received");
 
-                (
-                    Written::Nothing,
-                    AcceptorState::InitiationSendConfirm {
+                let decision = match self.hooks.as_mut() {
+                    Some(hooks) => hooks.on_connection_request(&connection_request),
+                    None => Ok(()),
+                };
+
+                let next_state = match decision {
+                    Ok(()) => AcceptorState::InitiationSendConfirm {
                         requested_protocol: connection_request.protocol,
                     },
-                )
+                    Err(code) => AcceptorState::InitiationSendFailure { code },
+                };
+
+                (Written::Nothing, next_state)
             }
 
             AcceptorState::InitiationSendConfirm { requested_protocol } => {
@@ -285,10 +598,38 @@ impl Sequence for Acceptor {
                 )
             }
 
-            AcceptorState::SecurityUpgrade { requested_protocol } => (
-                Written::Nothing,
-                AcceptorState::BasicSettingsWaitInitial { requested_protocol },
-            ),
+            AcceptorState::InitiationSendFailure { code } => {
+                let connection_confirm = nego::ConnectionConfirm::Failure { code };
+
+                warn!(message = ?connection_confirm, "Connection request denied");
+
+                let written =
+                    ironrdp_core::encode_buf(&X224(connection_confirm), output).map_err(ConnectorError::encode)?;
+
+                (Written::from_size(written)?, AcceptorState::AccessDenied)
+            }
+
+            AcceptorState::SecurityUpgrade { requested_protocol } => {
+                let next_state = if self
+                    .security
+                    .intersects(nego::SecurityProtocol::HYBRID_EX | nego::SecurityProtocol::HYBRID)
+                {
+                    AcceptorState::Credssp { requested_protocol }
+                } else {
+                    AcceptorState::BasicSettingsWaitInitial { requested_protocol }
+                };
+
+                (Written::Nothing, next_state)
+            }
+
+            AcceptorState::Credssp { requested_protocol } => {
+                // Stepping over this state would skip the client authentication.
+                self.state = AcceptorState::Credssp { requested_protocol };
+                return Err(reason_err!(
+                    "Credssp",
+                    "CredSSP must be performed with `accept_credssp`"
+                ));
+            }
 
             AcceptorState::BasicSettingsWaitInitial { requested_protocol } => {
                 let x224_payload = decode::<X224<pdu::x224::X224Data<'_>>>(input)
@@ -306,30 +647,59 @@ impl Sequence for Acceptor {
                     .optional_data
                     .early_capability_flags;
 
-                let joined: Vec<_> = settings_initial
+                if !self.policy.accepts_early_capability(early_capability) {
+                    return Err(reason_err!(
+                        "BasicSettingsWaitInitial",
+                        "client early capabilities rejected by policy: {early_capability:?}",
+                    ));
+                }
+
+                if let Some(config) = &self.standard_security {
+                    let client_methods = settings_initial
+                        .conference_create_request
+                        .gcc_blocks
+                        .security
+                        .encryption_methods;
+
+                    let method = config.select_method(client_methods).ok_or_else(|| {
+                        reason_err!(
+                            "BasicSettingsWaitInitial",
+                            "no common encryption method with the client: {client_methods:?}",
+                        )
+                    })?;
+
+                    let mut server_random = [0u8; SERVER_RANDOM_LEN];
+                    OsRng.fill_bytes(&mut server_random);
+
+                    debug!(?method, "Standard RDP Security negotiated");
+
+                    self.pending_security_exchange = Some((method, server_random));
+                }
+
+                let requested_channels = settings_initial
                     .conference_create_request
                     .gcc_blocks
                     .network
-                    .map(|network| {
-                        network
-                            .channels
-                            .into_iter()
-                            .map(|c| {
-                                self.static_channels
-                                    .get_by_channel_name(&c.name)
-                                    .map(|(type_id, _)| (type_id, c))
-                            })
-                            .collect()
-                    })
+                    .map(|network| network.channels)
                     .unwrap_or_default();
 
+                let mut denied_channels = HashSet::new();
+
                 #[allow(clippy::arithmetic_side_effects)] // IO channel ID is not big enough for overflowing.
-                let channels = joined
+                let channels = requested_channels
                     .into_iter()
                     .enumerate()
-                    .map(|(i, channel)| {
+                    .map(|(i, c)| {
                         let channel_id = u16::try_from(i).unwrap() + self.io_channel_id + 1;
-                        if let Some((type_id, c)) = channel {
+
+                        let is_allowed = self.hooks.as_mut().map_or(true, |hooks| hooks.on_channel_join(&c));
+                        if !is_allowed {
+                            debug!(channel_name = ?c.name, channel_id, "Channel denied by hooks");
+                            denied_channels.insert(channel_id);
+                            return (channel_id, None);
+                        }
+
+                        if let Some((type_id, _)) = self.static_channels.get_by_channel_name(&c.name) {
                             self.static_channels.attach_channel_id(type_id, channel_id);
                             (channel_id, Some(c))
                         } else {
@@ -344,6 +714,7 @@ impl Sequence for Acceptor {
                         requested_protocol,
                         early_capability,
                         channels,
+                        denied_channels,
                     },
                 )
             }
@@ -352,17 +723,31 @@ impl Sequence for Acceptor {
                 requested_protocol,
                 early_capability,
                 channels,
+                denied_channels,
             } => {
                 let channel_ids: Vec<u16> = channels.iter().map(|&(i, _)| i).collect();
 
                 let skip_channel_join = early_capability
                     .is_some_and(|client| client.contains(gcc::ClientEarlyCapabilityFlags::SUPPORT_SKIP_CHANNELJOIN));
 
+                let security = match (&self.standard_security, self.pending_security_exchange) {
+                    (Some(config), Some((method, server_random))) => gcc::ServerSecurityData {
+                        encryption_method: method,
+                        encryption_level: gcc::EncryptionLevel::ClientCompatible,
+                        server_random: Some(server_random),
+                        server_cert: proprietary_server_certificate(&config.server_key, &config.signing_key)
+                            .and_then(|certificate| ironrdp_core::encode_vec(&certificate))
+                            .map_err(ConnectorError::encode)?,
+                    },
+                    _ => gcc::ServerSecurityData::no_security(),
+                };
+
                 let server_blocks = create_gcc_blocks(
                     self.io_channel_id,
                     channel_ids.clone(),
                     requested_protocol,
                     skip_channel_join,
+                    security,
                 );
 
                 let settings_response = mcs::ConnectResponse {
@@ -388,6 +773,7 @@ impl Sequence for Acceptor {
                             ChannelConnectionSequence::skip_channel_join(self.user_channel_id)
                         } else {
                             ChannelConnectionSequence::new(self.user_channel_id, self.io_channel_id, channel_ids)
+                                .with_denied_channels(denied_channels)
                         },
                     },
                 )
@@ -419,13 +805,38 @@ impl Sequence for Acceptor {
                 early_capability,
                 channels,
                 ..
-            } => (
-                Written::Nothing,
-                AcceptorState::SecureSettingsExchange {
-                    early_capability,
-                    channels,
-                },
-            ),
+            } => {
+                if let Some((method, server_random)) = self.pending_security_exchange.take() {
+                    let config = self
+                        .standard_security
+                        .as_ref()
+                        .ok_or_else(|| ConnectorError::general("standard security is not enabled"))?;
+
+                    let data: X224<mcs::SendDataRequest<'_>> = decode(input).map_err(ConnectorError::decode)?;
+                    let exchange: SecurityExchangePdu =
+                        decode(data.0.user_data.as_ref()).map_err(ConnectorError::decode)?;
+
+                    debug!(message = ?exchange, "Received");
+
+                    let decrypted = config.server_key.decrypt(&exchange.encrypted_client_random);
+                    let client_random: [u8; CLIENT_RANDOM_LEN] = decrypted
+                        .get(..CLIENT_RANDOM_LEN)
+                        .and_then(|random| random.try_into().ok())
+                        .ok_or_else(|| ConnectorError::general("invalid encrypted client random"))?;
+
+                    let cipher = StandardSecurity::server(&client_random, &server_random, method)
+                        .map_err(ConnectorError::decode)?;
+                    self.security_layer = Some(SecurityLayer::new(cipher));
+                }
+
+                (
+                    Written::Nothing,
+                    AcceptorState::SecureSettingsExchange {
+                        early_capability,
+                        channels,
+                    },
+                )
+            }
 
             AcceptorState::SecureSettingsExchange {
                 early_capability,
@@ -438,6 +849,115 @@ impl Sequence for Acceptor {
 
                 debug!(message = ?client_info, "Received");
 
+                let decision = match self.hooks.as_mut() {
+                    Some(hooks) => hooks.on_client_info(&client_info.client_info),
+                    None => Ok(()),
+                };
+
+                let auto_detect_flag = gcc::ClientEarlyCapabilityFlags::SUPPORT_NET_CHAR_AUTODETECT;
+                let next_state = match decision {
+                    Ok(()) if self.auto_detect && early_capability.is_some_and(|c| c.contains(auto_detect_flag)) => {
+                        AcceptorState::AutoDetectSendRequests {
+                            early_capability,
+                            channels,
+                        }
+                    }
+                    Ok(()) => AcceptorState::LicensingExchange {
+                        early_capability,
+                        channels,
+                    },
+                    Err(error_info) => AcceptorState::SecureSettingsSendDenial { error_info },
+                };
+
+                (Written::Nothing, next_state)
+            }
+
+            AcceptorState::SecureSettingsSendDenial { error_info } => {
+                let set_error_info = rdp::headers::ShareDataPdu::ServerSetErrorInfo(ServerSetErrorInfoPdu(error_info));
+
+                warn!(message = ?set_error_info, "Client info denied");
+
+                let share_data = wrap_share_data(set_error_info, self.io_channel_id);
+
+                let error_info_written =
+                    util::encode_send_data_indication(self.user_channel_id, self.io_channel_id, &share_data, output)?;
+
+                let ultimatum = mcs::DisconnectProviderUltimatum::from_reason(mcs::DisconnectReason::ProviderInitiated);
+
+                debug!(message = ?ultimatum, "Send");
+
+                let ultimatum_written =
+                    ironrdp_core::encode_buf(&X224(ultimatum), output).map_err(ConnectorError::encode)?;
+
+                #[allow(clippy::arithmetic_side_effects)] // Both PDUs are a few bytes long.
+                let written = error_info_written + ultimatum_written;
+
+                (Written::from_size(written)?, AcceptorState::AccessDenied)
+            }
+
+            AcceptorState::AutoDetectSendRequests {
+                early_capability,
+                channels,
+            } => {
+                let start = AutoDetectRequestPdu(AutoDetectRequest::BandwidthMeasureStart {
+                    sequence_number: 0,
+                    phase: AutoDetectPhase::ConnectTime,
+                });
+
+                debug!(message = ?start, "Send");
+
+                let start_written =
+                    util::encode_send_data_indication(self.user_channel_id, self.io_channel_id, &start, output)?;
+
+                // The payload is only there to be measured, and is carried by the stop request directly.
+                let stop = AutoDetectRequestPdu(AutoDetectRequest::BandwidthMeasureStop {
+                    sequence_number: 1,
+                    phase: AutoDetectPhase::ConnectTime,
+                    payload: vec![0; AUTO_DETECT_PAYLOAD_SIZE],
+                });
+
+                debug!(sequence_number = 1, "Send bandwidth measure stop");
+
+                let stop_written =
+                    util::encode_send_data_indication(self.user_channel_id, self.io_channel_id, &stop, output)?;
+
+                #[allow(clippy::arithmetic_side_effects)] // Both PDUs are at most a few kilobytes long.
+                let written = start_written + stop_written;
+
+                (
+                    Written::from_size(written)?,
+                    AcceptorState::AutoDetectWaitResponse {
+                        early_capability,
+                        channels,
+                    },
+                )
+            }
+
+            AcceptorState::AutoDetectWaitResponse {
+                early_capability,
+                channels,
+            } => {
+                let data: X224<mcs::SendDataRequest<'_>> = decode(input).map_err(ConnectorError::decode)?;
+                let response: AutoDetectResponsePdu =
+                    decode(data.0.user_data.as_ref()).map_err(ConnectorError::decode)?;
+
+                debug!(message = ?response, "Received");
+
+                let AutoDetectResponse::BandwidthMeasureResults {
+                    time_delta, byte_count, ..
+                } = response.0
+                else {
+                    return Err(ConnectorError::general("expected bandwidth measure results"));
+                };
+
+                // Bytes per millisecond multiplied by 8 gives kilobits per second.
+                self.auto_detected_bandwidth = u64::from(byte_count)
+                    .checked_mul(8)
+                    .and_then(|bits| bits.checked_div(u64::from(time_delta)))
+                    .map(|bandwidth| u32::try_from(bandwidth).unwrap_or(u32::MAX));
+
+                debug!(bandwidth = ?self.auto_detected_bandwidth, "Connect-time auto-detection completed");
+
                 (
                     Written::Nothing,
                     AcceptorState::LicensingExchange {
@@ -447,6 +967,25 @@ impl Sequence for Acceptor {
                 )
             }
 
+            AcceptorState::LicensingExchange {
+                early_capability,
+                channels,
+            } if self.licensing.is_some() => {
+                let (config, provider) = self.licensing.take().expect("licensing is enabled");
+                let mut licensing = LicensingSequence::new(config, provider, self.user_channel_id, self.io_channel_id);
+
+                let written = licensing.step(input, output)?;
+
+                (
+                    written,
+                    AcceptorState::LicenseIssuance {
+                        early_capability,
+                        channels,
+                        licensing,
+                    },
+                )
+            }
+
             AcceptorState::LicensingExchange {
                 early_capability,
                 channels,
@@ -474,6 +1013,36 @@ impl Sequence for Acceptor {
                 )
             }
 
+            AcceptorState::LicenseIssuance {
+                early_capability,
+                channels,
+                mut licensing,
+            } => {
+                let written = licensing.step(input, output)?;
+
+                let next_state = if !licensing.state().is_terminal() {
+                    AcceptorState::LicenseIssuance {
+                        early_capability,
+                        channels,
+                        licensing,
+                    }
+                } else if licensing.is_licensed() {
+                    self.saved_for_reactivation = AcceptorState::CapabilitiesSendServer {
+                        early_capability,
+                        channels: channels.clone(),
+                    };
+
+                    AcceptorState::CapabilitiesSendServer {
+                        early_capability,
+                        channels,
+                    }
+                } else {
+                    AcceptorState::AccessDenied
+                };
+
+                (written, next_state)
+            }
+
             AcceptorState::CapabilitiesSendServer {
                 early_capability,
                 channels,
@@ -550,12 +1119,15 @@ impl Sequence for Acceptor {
                             return Err(ConnectorError::general("expected client confirm active"));
                         };
 
+                        let mut client_capabilities = confirm.pdu.capability_sets;
+                        self.policy.apply(&mut client_capabilities);
+
                         (
                             Written::Nothing,
                             AcceptorState::ConnectionFinalization {
                                 channels,
                                 finalization: FinalizationSequence::new(self.user_channel_id, self.io_channel_id),
-                                client_capabilities: confirm.pdu.capability_sets,
+                                client_capabilities,
                             },
                         )
                     }
@@ -583,6 +1155,7 @@ impl Sequence for Acceptor {
                         channels,
                         client_capabilities,
                         input_events: finalization.input_events,
+                        persistent_keys: finalization.persistent_keys,
                     }
                 } else {
                     AcceptorState::ConnectionFinalization {
@@ -595,6 +1168,10 @@ impl Sequence for Acceptor {
                 (written, state)
             }
 
+            AcceptorState::AccessDenied => {
+                return Err(ConnectorError::new("access denied", ConnectorErrorKind::AccessDenied));
+            }
+
             _ => unreachable!(),
         };
 
@@ -608,6 +1185,7 @@ fn create_gcc_blocks(
     channel_ids: Vec<u16>,
     requested: nego::SecurityProtocol,
     skip_channel_join: bool,
+    security: gcc::ServerSecurityData,
 ) -> gcc::ServerGccBlocks {
     gcc::ServerGccBlocks {
         core: gcc::ServerCoreData {
@@ -618,7 +1196,7 @@ fn create_gcc_blocks(
                     .then_some(gcc::ServerEarlyCapabilityFlags::SKIP_CHANNELJOIN_SUPPORTED),
             },
         },
-        security: gcc::ServerSecurityData::no_security(),
+        security,
         network: gcc::ServerNetworkData {
             channel_ids,
             io_channel,
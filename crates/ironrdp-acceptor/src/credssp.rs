@@ -0,0 +1,134 @@
+use ironrdp_async::{Framed, FramedRead, FramedWrite};
+use ironrdp_connector::sspi::credssp::{
+    ClientMode, CredSspServer, CredentialsProxy, EarlyUserAuthResult, ServerState, TsRequest,
+};
+use ironrdp_connector::sspi::generator::GeneratorState;
+use ironrdp_connector::sspi::{self, AuthIdentity, NegotiateConfig};
+use ironrdp_connector::{custom_err, ConnectorError, ConnectorErrorKind, ConnectorResult};
+use ironrdp_core::other_err;
+use ironrdp_pdu::{nego, PduHint};
+
+use crate::Acceptor;
+
+#[derive(Clone, Copy, Debug)]
+struct TsRequestHint;
+
+impl PduHint for TsRequestHint {
+    fn find_size(&self, bytes: &[u8]) -> ironrdp_core::DecodeResult<Option<(bool, usize)>> {
+        match TsRequest::read_length(bytes) {
+            Ok(length) => Ok(Some((true, length))),
+            Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => Ok(None),
+            Err(e) => Err(other_err!("TsRequestHint", source: e)),
+        }
+    }
+}
+
+/// Authenticates the client with CredSSP, when NLA is used
+///
+/// `credentials` provides the credentials of the users allowed to connect, and `public_key` is the public key of the
+/// certificate presented during the TLS upgrade. Network requests are not supported: the client is authenticated
+/// with NTLM.
+///
+/// Must be called after the security upgrade, once [`Acceptor::mark_security_upgrade_as_done`] is called, when
+/// [`Acceptor::should_perform_credssp`] returns `true`. Once the client is authenticated,
+/// [`AcceptorHooks::on_nla_complete`](crate::AcceptorHooks::on_nla_complete) is called with its identity.
+pub async fn accept_credssp<S, C>(
+    framed: &mut Framed<S>,
+    acceptor: &mut Acceptor,
+    credentials: C,
+    public_key: Vec<u8>,
+    computer_name: String,
+) -> ConnectorResult<()>
+where
+    S: FramedRead + FramedWrite,
+    C: CredentialsProxy<AuthenticationData = AuthIdentity>,
+{
+    assert!(acceptor.should_perform_credssp());
+
+    let mut server = CredSspServer::new(
+        public_key,
+        credentials,
+        ClientMode::Negotiate(NegotiateConfig {
+            protocol_config: Box::<sspi::ntlm::NtlmConfig>::default(),
+            package_list: None,
+            client_computer_name: computer_name,
+        }),
+    )
+    .map_err(|e| ConnectorError::new("CredSSP", ConnectorErrorKind::Credssp(e)))?;
+
+    let identity = loop {
+        let pdu = framed
+            .read_by_hint(&TsRequestHint, None)
+            .await
+            .map_err(|e| custom_err!("read frame by hint", e))?;
+
+        let ts_request = TsRequest::from_buffer(&pdu).map_err(|e| custom_err!("TsRequest", e))?;
+
+        debug!(message = ?ts_request, "Received");
+
+        let result = {
+            let mut generator = server.process(ts_request);
+            let mut state = generator.start();
+
+            loop {
+                match state {
+                    GeneratorState::Suspended(_) => {
+                        state = generator.resume(Err(sspi::Error::new(
+                            sspi::ErrorKind::UnsupportedFunction,
+                            "network requests are not supported",
+                        )));
+                    }
+                    GeneratorState::Completed(result) => break result,
+                }
+            }
+        }; // drop generator
+
+        match result {
+            Ok(ServerState::ReplyNeeded(ts_request)) => write_ts_request(framed, &ts_request).await?,
+            Ok(ServerState::Finished(identity)) => break identity,
+            Err(error) => {
+                // The TS Request reports the error code to the client.
+                write_ts_request(framed, &error.ts_request).await?;
+                return Err(ConnectorError::new("CredSSP", ConnectorErrorKind::Credssp(error.error)));
+            }
+        }
+    };
+
+    let allowed = acceptor.mark_credssp_as_done(&identity);
+
+    if acceptor.security().contains(nego::SecurityProtocol::HYBRID_EX) {
+        let result = if allowed {
+            EarlyUserAuthResult::Success
+        } else {
+            EarlyUserAuthResult::AccessDenied
+        };
+
+        debug!(message = ?result, "Send");
+
+        let mut pdu = Vec::new();
+        result
+            .to_buffer(&mut pdu)
+            .map_err(|e| custom_err!("EarlyUserAuthResult", e))?;
+        framed.write_all(&pdu).await.map_err(|e| custom_err!("write all", e))?;
+    }
+
+    if allowed {
+        Ok(())
+    } else {
+        Err(ConnectorError::new("CredSSP", ConnectorErrorKind::AccessDenied))
+    }
+}
+
+async fn write_ts_request<S>(framed: &mut Framed<S>, ts_request: &TsRequest) -> ConnectorResult<()>
+where
+    S: FramedWrite,
+{
+    debug!(message = ?ts_request, "Send");
+
+    let mut pdu = vec![0; usize::from(ts_request.buffer_len())];
+    ts_request
+        .encode_ts_request(&mut pdu)
+        .map_err(|e| custom_err!("TsRequest", e))?;
+
+    framed.write_all(&pdu).await.map_err(|e| custom_err!("write all", e))
+}
@@ -2,6 +2,7 @@ use ironrdp_connector::{ConnectorError, ConnectorErrorExt, ConnectorResult, Sequ
 use ironrdp_core::WriteBuf;
 use ironrdp_pdu::{self as pdu, x224::X224};
 use pdu::rdp;
+use pdu::rdp::finalization_messages::{PersistentKeyListFlags, PersistentKeyListPdu, PERSISTENT_KEY_LIST_CELLS};
 
 use crate::util::{self, wrap_share_data};
 
@@ -12,6 +13,7 @@ pub struct FinalizationSequence {
     io_channel_id: u16,
 
     pub input_events: Vec<Vec<u8>>,
+    pub persistent_keys: Vec<Vec<u64>>,
 }
 
 #[derive(Default, Debug)]
@@ -104,12 +106,29 @@ impl Sequence for FinalizationSequence {
             }
 
             FinalizationState::WaitFontList => match decode_font_list(input) {
-                Ok(font_list) => {
+                Ok(FontListOrKeys::FontList(font_list)) => {
                     debug!(message = ?font_list, "Received");
 
                     (Written::Nothing, FinalizationState::SendSynchronizeConfirm)
                 }
 
+                Ok(FontListOrKeys::PersistentKeyList(key_list)) => {
+                    debug!(message = ?key_list, "Received");
+
+                    // The Persistent Key List PDUs are sent between the Control (Request Control) PDU
+                    // and the Font List PDU, the keys of each cell being spread over several PDUs.
+                    if key_list.flags.contains(PersistentKeyListFlags::FIRST) {
+                        self.persistent_keys.clear();
+                    }
+
+                    self.persistent_keys.resize_with(PERSISTENT_KEY_LIST_CELLS, Vec::new);
+                    for (cell, keys) in self.persistent_keys.iter_mut().zip(key_list.keys) {
+                        cell.extend(keys);
+                    }
+
+                    (Written::Nothing, FinalizationState::WaitFontList)
+                }
+
                 Err(()) => {
                     self.input_events.push(input.to_vec());
 
@@ -186,6 +205,7 @@ impl FinalizationSequence {
             user_channel_id,
             io_channel_id,
             input_events: Vec::new(),
+            persistent_keys: Vec::new(),
         }
     }
 
@@ -227,7 +247,12 @@ fn decode_share_control(input: &[u8]) -> ConnectorResult<rdp::headers::ShareCont
     Ok(share_control)
 }
 
-fn decode_font_list(input: &[u8]) -> Result<rdp::finalization_messages::FontPdu, ()> {
+enum FontListOrKeys {
+    FontList(rdp::finalization_messages::FontPdu),
+    PersistentKeyList(PersistentKeyListPdu),
+}
+
+fn decode_font_list(input: &[u8]) -> Result<FontListOrKeys, ()> {
     use pdu::rdp::headers::{ShareControlPdu, ShareDataPdu};
 
     let share_control = decode_share_control(input).map_err(|_| ())?;
@@ -236,9 +261,9 @@ fn decode_font_list(input: &[u8]) -> Result<rdp::finalization_messages::FontPdu,
         return Err(());
     };
 
-    let ShareDataPdu::FontList(font_pdu) = data_pdu.share_data_pdu else {
-        return Err(());
-    };
-
-    Ok(font_pdu)
+    match data_pdu.share_data_pdu {
+        ShareDataPdu::FontList(font_pdu) => Ok(FontListOrKeys::FontList(font_pdu)),
+        ShareDataPdu::BitmapCachePersistentList(key_list) => Ok(FontListOrKeys::PersistentKeyList(key_list)),
+        _ => Err(()),
+    }
 }
@@ -0,0 +1,63 @@
+use ironrdp_connector::sspi::AuthIdentity;
+use ironrdp_pdu::rdp::server_error_info::ErrorInfo;
+use ironrdp_pdu::{gcc, nego, pcb, rdp};
+
+/// Authentication and authorization callbacks invoked by the [`Acceptor`](crate::Acceptor)
+///
+/// Each callback is invoked at a specific point of the connection sequence, and may reject the
+/// connection (or a part of it). The acceptor then sends the appropriate protocol-level denial
+/// to the client before failing with an [`AccessDenied`](ironrdp_connector::ConnectorErrorKind::AccessDenied) error.
+///
+/// All methods have a default implementation accepting everything.
+pub trait AcceptorHooks: Send {
+    /// Called when a Preconnection PDU is received, before the X.224 Connection Request
+    ///
+    /// The PCB ID and string identify the RDP source the client intends to reach, as used by Hyper-V and
+    /// connection brokers. No response is defined by the protocol: returning `false` drops the connection.
+    fn on_preconnection_blob(&mut self, blob: &pcb::PreconnectionBlob) -> bool {
+        let _ = blob;
+        true
+    }
+
+    /// Called when the X.224 Connection Request is received
+    ///
+    /// This is the right place to enforce the allowed security protocols, or to filter by routing token or cookie.
+    /// Returning an error sends a RDP Negotiation Failure with the provided code.
+    fn on_connection_request(&mut self, request: &nego::ConnectionRequest) -> Result<(), nego::FailureCode> {
+        let _ = request;
+        Ok(())
+    }
+
+    /// Called when the client is authenticated by the CredSSP sequence, when NLA is used
+    ///
+    /// This is the identity to base authorization decisions on. Returning `false` denies the connection: when
+    /// `HYBRID_EX` is used, an Early User Authorization Result PDU with an `ACCESS_DENIED` result is sent to the
+    /// client, otherwise the connection is dropped.
+    fn on_nla_complete(&mut self, identity: &AuthIdentity) -> bool {
+        let _ = identity;
+        true
+    }
+
+    /// Called when the client credentials are received in the Client Info PDU
+    ///
+    /// These credentials are claimed by the client and are not verified by the acceptor. In particular, when NLA is
+    /// used, nothing ties them to the user authenticated by [`Self::on_nla_complete`]: a client may authenticate as
+    /// one user and claim another one here. Compare them with the authenticated identity before relying on them.
+    /// Returning an error sends a Set Error Info PDU with the provided code, followed by a
+    /// Disconnect Provider Ultimatum.
+    fn on_client_info(&mut self, client_info: &rdp::ClientInfo) -> Result<(), ErrorInfo> {
+        let _ = client_info;
+        Ok(())
+    }
+
+    /// Called for each static virtual channel requested by the client in the MCS Connect Initial PDU
+    ///
+    /// Returning `false` denies the channel: the MCS Channel Join Confirm for this channel is sent with
+    /// a `rt-not-admitted` result, and no static channel processor is bound to it.
+    fn on_channel_join(&mut self, channel: &gcc::ChannelDef) -> bool {
+        let _ = channel;
+        true
+    }
+}
+
+ironrdp_core::assert_obj_safe!(AcceptorHooks);
@@ -8,14 +8,26 @@ use ironrdp_core::WriteBuf;
 
 mod channel_connection;
 mod connection;
+mod credssp;
 mod finalization;
+mod hooks;
+mod licensing;
+mod policy;
+mod standard_security;
 mod util;
 
 pub use ironrdp_connector::DesktopSize;
 
 pub use self::channel_connection::{ChannelConnectionSequence, ChannelConnectionState};
 pub use self::connection::{Acceptor, AcceptorResult, AcceptorState};
+pub use self::credssp::accept_credssp;
 pub use self::finalization::{FinalizationSequence, FinalizationState};
+pub use self::hooks::AcceptorHooks;
+pub use self::licensing::{
+    ClientLicensing, LicenseProvider, LicenseRequest, LicensingConfig, LicensingSequence, LicensingState,
+};
+pub use self::policy::{CapabilityPolicy, CodecKind};
+pub use self::standard_security::{SecurityLayer, StandardSecurityConfig};
 
 pub enum BeginResult<S>
 where
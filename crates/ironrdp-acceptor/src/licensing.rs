@@ -0,0 +1,335 @@
+use core::fmt;
+
+use ironrdp_connector::{ConnectorError, ConnectorErrorExt, ConnectorResult, Sequence, State, Written};
+use ironrdp_core::{decode, WriteBuf};
+use ironrdp_pdu::rdp::server_license::{
+    LicenseEncryptionData, LicenseErrorCode, LicensePdu, LicensingErrorMessage, LicensingStateTransition,
+    NewLicenseInformation, ProductInfo, Scope, ServerLicenseRequest, ServerPlatformChallenge, ServerUpgradeLicense,
+    RANDOM_NUMBER_SIZE,
+};
+use ironrdp_pdu::rdp::standard_security::{proprietary_server_certificate, RsaPrivateKey};
+use ironrdp_pdu::x224::X224;
+use ironrdp_pdu::{self as pdu, mcs};
+use rand_core::{OsRng, RngCore as _};
+
+use crate::util;
+
+const PLATFORM_CHALLENGE_SIZE: usize = 16;
+
+/// Server licensing settings (MS-RDPELE)
+///
+/// Without these settings, the acceptor skips the licensing exchange by sending a STATUS_VALID_CLIENT error
+/// message right away, which is enough for most clients.
+#[derive(Debug, Clone)]
+pub struct LicensingConfig {
+    /// RSA key pair used for exchanging the premaster secret
+    pub server_key: RsaPrivateKey,
+    /// Key signing the server proprietary certificate
+    pub signing_key: RsaPrivateKey,
+    pub company_name: String,
+    pub product_id: String,
+    pub product_version: u32,
+    /// Scope of the issued licenses, usually the name of the licensing domain
+    pub scope: String,
+}
+
+/// Client license requested in a Client New License Request PDU, or after a presented license was rejected
+#[derive(Debug)]
+pub struct LicenseRequest<'a> {
+    /// Unknown when the request follows a rejected license
+    pub username: Option<&'a str>,
+    /// Unknown when the request follows a rejected license
+    pub machine_name: Option<&'a str>,
+    /// Hardware identification of the client, to which the license is bound
+    pub hardware_id: &'a [u8],
+}
+
+/// Issues and validates the client licenses during the licensing exchange
+pub trait LicenseProvider: Send {
+    /// Called when the client presents a license previously issued to it
+    ///
+    /// Returning `false` issues a new license instead.
+    fn validate_license(&mut self, license: &[u8], hardware_id: &[u8]) -> bool;
+
+    /// Called when the client needs a new license
+    ///
+    /// Returning `None` denies the license: a licensing error is sent to the client, and the connection fails.
+    fn issue_license(&mut self, request: &LicenseRequest<'_>) -> Option<Vec<u8>>;
+}
+
+ironrdp_core::assert_obj_safe!(LicenseProvider);
+
+pub struct LicensingSequence {
+    state: LicensingState,
+    config: LicensingConfig,
+    provider: Box<dyn LicenseProvider>,
+    user_channel_id: u16,
+    io_channel_id: u16,
+}
+
+#[derive(Default, Debug)]
+pub enum LicensingState {
+    #[default]
+    Consumed,
+
+    SendRequest,
+    WaitRequest {
+        server_random: [u8; RANDOM_NUMBER_SIZE],
+    },
+    WaitChallengeResponse {
+        encryption_data: LicenseEncryptionData,
+        challenge: [u8; PLATFORM_CHALLENGE_SIZE],
+        client: ClientLicensing,
+    },
+
+    Licensed,
+    Denied,
+}
+
+/// What the client asked for in its first licensing message
+#[derive(Debug)]
+pub enum ClientLicensing {
+    NewLicense { username: String, machine_name: String },
+    PresentedLicense { license: Vec<u8> },
+}
+
+impl State for LicensingState {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Consumed => "Consumed",
+            Self::SendRequest => "SendRequest",
+            Self::WaitRequest { .. } => "WaitRequest",
+            Self::WaitChallengeResponse { .. } => "WaitChallengeResponse",
+            Self::Licensed => "Licensed",
+            Self::Denied => "Denied",
+        }
+    }
+
+    fn is_terminal(&self) -> bool {
+        matches!(self, Self::Licensed | Self::Denied)
+    }
+
+    fn as_any(&self) -> &dyn core::any::Any {
+        self
+    }
+}
+
+impl LicensingSequence {
+    pub fn new(
+        config: LicensingConfig,
+        provider: Box<dyn LicenseProvider>,
+        user_channel_id: u16,
+        io_channel_id: u16,
+    ) -> Self {
+        Self {
+            state: LicensingState::SendRequest,
+            config,
+            provider,
+            user_channel_id,
+            io_channel_id,
+        }
+    }
+
+    pub fn is_licensed(&self) -> bool {
+        matches!(self.state, LicensingState::Licensed)
+    }
+
+    fn send(&self, license: LicensePdu, output: &mut WriteBuf) -> ConnectorResult<Written> {
+        debug!(message = ?license, "Send");
+
+        let written = util::encode_send_data_indication(self.user_channel_id, self.io_channel_id, &license, output)?;
+
+        Written::from_size(written)
+    }
+
+    fn send_error(&self, error_code: LicenseErrorCode, output: &mut WriteBuf) -> ConnectorResult<Written> {
+        let error = LicensingErrorMessage::new(error_code, LicensingStateTransition::TotalAbort)
+            .map_err(ConnectorError::encode)?;
+
+        warn!(error_code = ?error.error_code, "Licensing denied");
+
+        self.send(error.into(), output)
+    }
+}
+
+impl fmt::Debug for LicensingSequence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LicensingSequence")
+            .field("state", &self.state)
+            .field("config", &self.config)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Sequence for LicensingSequence {
+    fn next_pdu_hint(&self) -> Option<&dyn pdu::PduHint> {
+        match self.state {
+            LicensingState::Consumed => None,
+            LicensingState::SendRequest => None,
+            LicensingState::WaitRequest { .. } => Some(&pdu::X224_HINT),
+            LicensingState::WaitChallengeResponse { .. } => Some(&pdu::X224_HINT),
+            LicensingState::Licensed => None,
+            LicensingState::Denied => None,
+        }
+    }
+
+    fn state(&self) -> &dyn State {
+        &self.state
+    }
+
+    fn step(&mut self, input: &[u8], output: &mut WriteBuf) -> ConnectorResult<Written> {
+        let (written, next_state) = match core::mem::take(&mut self.state) {
+            LicensingState::SendRequest => {
+                let mut server_random = [0u8; RANDOM_NUMBER_SIZE];
+                OsRng.fill_bytes(&mut server_random);
+
+                let certificate = proprietary_server_certificate(&self.config.server_key, &self.config.signing_key)
+                    .map_err(ConnectorError::encode)?;
+
+                let request = ServerLicenseRequest::new(
+                    &server_random,
+                    ProductInfo {
+                        version: self.config.product_version,
+                        company_name: self.config.company_name.clone(),
+                        product_id: self.config.product_id.clone(),
+                    },
+                    certificate,
+                    vec![Scope(self.config.scope.clone())],
+                )
+                .map_err(ConnectorError::encode)?;
+
+                (
+                    self.send(request.into(), output)?,
+                    LicensingState::WaitRequest { server_random },
+                )
+            }
+
+            LicensingState::WaitRequest { server_random } => {
+                let license = decode_license_pdu(input)?;
+
+                debug!(message = ?license, "Received");
+
+                let (encryption_data, client) = match license {
+                    LicensePdu::ClientNewLicenseRequest(request) => (
+                        request.encryption_data(&server_random, &self.config.server_key),
+                        ClientLicensing::NewLicense {
+                            username: request.client_username,
+                            machine_name: request.client_machine_name,
+                        },
+                    ),
+                    LicensePdu::ClientLicenseInfo(license_info) => (
+                        license_info.encryption_data(&server_random, &self.config.server_key),
+                        ClientLicensing::PresentedLicense {
+                            license: license_info.license_info,
+                        },
+                    ),
+                    _ => return Err(ConnectorError::general("expected a license request")),
+                };
+
+                let mut challenge = [0u8; PLATFORM_CHALLENGE_SIZE];
+                OsRng.fill_bytes(&mut challenge);
+
+                let platform_challenge =
+                    ServerPlatformChallenge::new(&challenge, &encryption_data).map_err(ConnectorError::encode)?;
+
+                (
+                    self.send(platform_challenge.into(), output)?,
+                    LicensingState::WaitChallengeResponse {
+                        encryption_data,
+                        challenge,
+                        client,
+                    },
+                )
+            }
+
+            LicensingState::WaitChallengeResponse {
+                encryption_data,
+                challenge,
+                client,
+            } => {
+                let license = decode_license_pdu(input)?;
+
+                debug!(message = ?license, "Received");
+
+                let LicensePdu::ClientPlatformChallengeResponse(response) = license else {
+                    return Err(ConnectorError::general("expected a platform challenge response"));
+                };
+
+                let hardware_id = match response.verify(&challenge, &encryption_data) {
+                    Ok(hardware_id) => hardware_id,
+                    Err(error) => {
+                        warn!(%error, "Invalid platform challenge response");
+
+                        let written = self.send_error(LicenseErrorCode::InvalidMac, output)?;
+                        self.state = LicensingState::Denied;
+
+                        return Ok(written);
+                    }
+                };
+
+                let request = match &client {
+                    ClientLicensing::PresentedLicense { license } => {
+                        if self.provider.validate_license(license, &hardware_id) {
+                            info!("Client license validated");
+
+                            let valid_client =
+                                LicensingErrorMessage::new_valid_client().map_err(ConnectorError::encode)?;
+                            self.state = LicensingState::Licensed;
+
+                            return self.send(valid_client.into(), output);
+                        }
+
+                        info!("Client license rejected, issuing a new license");
+
+                        LicenseRequest {
+                            username: None,
+                            machine_name: None,
+                            hardware_id: &hardware_id,
+                        }
+                    }
+                    ClientLicensing::NewLicense { username, machine_name } => LicenseRequest {
+                        username: Some(username.as_str()),
+                        machine_name: Some(machine_name.as_str()),
+                        hardware_id: &hardware_id,
+                    },
+                };
+
+                match self.provider.issue_license(&request) {
+                    Some(license_info) => {
+                        let new_license = NewLicenseInformation {
+                            version: self.config.product_version,
+                            scope: self.config.scope.clone(),
+                            company_name: self.config.company_name.clone(),
+                            product_id: self.config.product_id.clone(),
+                            license_info,
+                        };
+
+                        let new_license = ServerUpgradeLicense::new_license(&new_license, &encryption_data)
+                            .map_err(ConnectorError::encode)?;
+
+                        info!("Client license issued");
+
+                        (self.send(new_license.into(), output)?, LicensingState::Licensed)
+                    }
+                    None => (
+                        self.send_error(LicenseErrorCode::NoLicense, output)?,
+                        LicensingState::Denied,
+                    ),
+                }
+            }
+
+            LicensingState::Consumed | LicensingState::Licensed | LicensingState::Denied => {
+                return Err(ConnectorError::general("licensing sequence already completed"));
+            }
+        };
+
+        self.state = next_state;
+        Ok(written)
+    }
+}
+
+fn decode_license_pdu(input: &[u8]) -> ConnectorResult<LicensePdu> {
+    let data = decode::<X224<mcs::SendDataRequest<'_>>>(input).map_err(ConnectorError::decode)?;
+
+    decode::<LicensePdu>(data.0.user_data.as_ref()).map_err(ConnectorError::decode)
+}
@@ -0,0 +1,98 @@
+use ironrdp_connector::DesktopSize;
+use ironrdp_pdu::gcc;
+use ironrdp_pdu::rdp::capability_sets::{CapabilitySet, CodecProperty, GeneralExtraFlags, InputFlags};
+
+/// Bitmap codecs which can be denied by a [`CapabilityPolicy`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CodecKind {
+    NsCodec,
+    RemoteFx,
+    ImageRemoteFx,
+}
+
+impl CodecKind {
+    fn matches(self, property: &CodecProperty) -> bool {
+        matches!(
+            (self, property),
+            (Self::NsCodec, CodecProperty::NsCodec(_))
+                | (Self::RemoteFx, CodecProperty::RemoteFx(_))
+                | (Self::ImageRemoteFx, CodecProperty::ImageRemoteFx(_))
+        )
+    }
+}
+
+/// Constraints applied on the capabilities negotiated by the [`Acceptor`](crate::Acceptor)
+///
+/// The policy is applied on both the capability sets advertised by the server in the Demand Active PDU,
+/// and the capability sets confirmed by the client. The constrained client capability sets are the ones
+/// returned in the [`AcceptorResult`](crate::AcceptorResult), so the session can be built upon them directly.
+#[derive(Debug, Clone, Default)]
+pub struct CapabilityPolicy {
+    /// Bitmap codecs removed from the Bitmap Codecs capability sets
+    pub denied_codecs: Vec<CodecKind>,
+    /// Maximum desktop size
+    pub max_desktop_size: Option<DesktopSize>,
+    /// Maximum color depth, in bits per pixel
+    pub max_color_depth: Option<u16>,
+    /// Do not advertise nor accept fast-path input and output
+    pub disable_fast_path: bool,
+    /// Reject clients not supporting the Graphics Pipeline Extension (MS-RDPEGFX)
+    pub require_egfx: bool,
+}
+
+impl CapabilityPolicy {
+    /// Applies the policy on a list of capability sets
+    pub fn apply(&self, capabilities: &mut [CapabilitySet]) {
+        for capability in capabilities.iter_mut() {
+            match capability {
+                CapabilitySet::General(general) if self.disable_fast_path => {
+                    general.extra_flags.remove(GeneralExtraFlags::FASTPATH_OUTPUT_SUPPORTED);
+                }
+                CapabilitySet::Input(input) if self.disable_fast_path => {
+                    input
+                        .input_flags
+                        .remove(InputFlags::FASTPATH_INPUT | InputFlags::FASTPATH_INPUT_2);
+                }
+                CapabilitySet::Bitmap(bitmap) => {
+                    let desktop_size = self.clamp_desktop_size(DesktopSize {
+                        width: bitmap.desktop_width,
+                        height: bitmap.desktop_height,
+                    });
+                    bitmap.desktop_width = desktop_size.width;
+                    bitmap.desktop_height = desktop_size.height;
+
+                    if let Some(max_color_depth) = self.max_color_depth {
+                        bitmap.pref_bits_per_pix = bitmap.pref_bits_per_pix.min(max_color_depth);
+                    }
+                }
+                CapabilitySet::BitmapCodecs(codecs) => {
+                    codecs
+                        .0
+                        .retain(|codec| !self.denied_codecs.iter().any(|kind| kind.matches(&codec.property)));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Returns the desktop size, reduced to the maximum allowed size if necessary
+    pub fn clamp_desktop_size(&self, desktop_size: DesktopSize) -> DesktopSize {
+        match self.max_desktop_size {
+            Some(max) => DesktopSize {
+                width: desktop_size.width.min(max.width),
+                height: desktop_size.height.min(max.height),
+            },
+            None => desktop_size,
+        }
+    }
+
+    /// Returns whether the client early capabilities satisfy the policy
+    pub fn accepts_early_capability(&self, early_capability: Option<gcc::ClientEarlyCapabilityFlags>) -> bool {
+        if self.require_egfx {
+            early_capability
+                .is_some_and(|flags| flags.contains(gcc::ClientEarlyCapabilityFlags::SUPPORT_DYN_VC_GFX_PROTOCOL))
+        } else {
+            true
+        }
+    }
+}
@@ -0,0 +1,244 @@
+use std::borrow::Cow;
+
+use ironrdp_connector::{reason_err, ConnectorError, ConnectorErrorExt, ConnectorResult};
+use ironrdp_core::{decode, encode_vec};
+use ironrdp_pdu::gcc::EncryptionMethod;
+use ironrdp_pdu::mcs;
+use ironrdp_pdu::rdp::headers::BasicSecurityHeaderFlags;
+use ironrdp_pdu::rdp::standard_security::{RsaPrivateKey, StandardSecurity, DATA_SIGNATURE_LEN};
+use ironrdp_pdu::x224::X224;
+use ironrdp_pdu::Action;
+
+const FASTPATH_SECURE_CHECKSUM: u8 = 0x1;
+const FASTPATH_ENCRYPTED: u8 = 0x2;
+
+/// Standard RDP Security settings, for legacy clients unable to negotiate TLS
+///
+/// Standard RDP Security relies on RC4 and a RSA key exchange which are not considered secure anymore.
+/// It is only used when explicitly enabled, and the acceptor is created for the standard RDP security protocol
+/// (an empty [`SecurityProtocol`](ironrdp_pdu::nego::SecurityProtocol)).
+#[derive(Debug, Clone)]
+pub struct StandardSecurityConfig {
+    /// RSA key pair used for exchanging the client random
+    pub server_key: RsaPrivateKey,
+    /// Key signing the server proprietary certificate
+    ///
+    /// Clients verify the certificate with the Terminal Services signing key, see [MS-RDPBCGR] 5.3.3.1.1.
+    pub signing_key: RsaPrivateKey,
+    /// Encryption methods accepted by the server, the strongest one supported by the client is selected
+    pub encryption_methods: EncryptionMethod,
+}
+
+impl StandardSecurityConfig {
+    /// Returns the strongest encryption method supported by both the server and the client
+    pub(crate) fn select_method(&self, client_methods: EncryptionMethod) -> Option<EncryptionMethod> {
+        let common = self.encryption_methods & client_methods;
+
+        [
+            EncryptionMethod::BIT_128,
+            EncryptionMethod::BIT_56,
+            EncryptionMethod::BIT_40,
+        ]
+        .into_iter()
+        .find(|method| common.contains(*method))
+    }
+}
+
+/// Encryption of the frames exchanged with a client using Standard RDP Security
+///
+/// Decrypted frames follow the same conventions as with Enhanced RDP Security: the security header is removed,
+/// unless it carries other flags than the encryption ones (e.g.: Client Info PDU, auto-detect PDUs).
+#[derive(Debug)]
+pub struct SecurityLayer {
+    cipher: StandardSecurity,
+}
+
+impl SecurityLayer {
+    pub(crate) fn new(cipher: StandardSecurity) -> Self {
+        Self { cipher }
+    }
+
+    pub fn encryption_method(&self) -> EncryptionMethod {
+        self.cipher.method()
+    }
+
+    /// Decrypts a slow-path or fast-path frame received from the client
+    pub fn decrypt_frame(&mut self, frame: &[u8]) -> ConnectorResult<Vec<u8>> {
+        let Some(&header) = frame.first() else {
+            return Ok(Vec::new());
+        };
+
+        match Action::from_fp_output_header(header) {
+            Ok(Action::X224) => {
+                let message = decode::<X224<mcs::McsMessage<'_>>>(frame).map_err(ConnectorError::decode)?;
+
+                let mcs::McsMessage::SendDataRequest(mut request) = message.0 else {
+                    return Ok(frame.to_vec());
+                };
+                request.user_data = Cow::Owned(self.decrypt_user_data(&request.user_data)?);
+
+                encode_vec(&X224(request)).map_err(ConnectorError::encode)
+            }
+            Ok(Action::FastPath) => {
+                let (data_offset, _) = read_fast_path_length(frame)?;
+                let flags = header >> 6;
+
+                if flags & FASTPATH_ENCRYPTED == 0 {
+                    return Ok(frame.to_vec());
+                }
+
+                let (signature, data) = split_signature(&frame[data_offset..])?;
+                let data = self
+                    .cipher
+                    .decrypt(data, signature, flags & FASTPATH_SECURE_CHECKSUM != 0)
+                    .map_err(ConnectorError::decode)?;
+
+                fast_path_frame(header & 0x3f, &[], &data)
+            }
+            Err(_) => Err(reason_err!("decrypt", "unknown frame action: {header:#04x}")),
+        }
+    }
+
+    /// Encrypts a slow-path or fast-path frame sent to the client
+    ///
+    /// `has_security_header` indicates whether the user data of a slow-path frame already starts with a basic
+    /// security header, such as the auto-detect PDUs.
+    pub fn encrypt_frame(&mut self, frame: &[u8], has_security_header: bool) -> ConnectorResult<Vec<u8>> {
+        let Some(&header) = frame.first() else {
+            return Ok(Vec::new());
+        };
+
+        match Action::from_fp_output_header(header) {
+            Ok(Action::X224) => {
+                let message = decode::<X224<mcs::McsMessage<'_>>>(frame).map_err(ConnectorError::decode)?;
+
+                let mcs::McsMessage::SendDataIndication(mut indication) = message.0 else {
+                    return Ok(frame.to_vec());
+                };
+                indication.user_data = Cow::Owned(self.encrypt_user_data(&indication.user_data, has_security_header)?);
+
+                encode_vec(&X224(indication)).map_err(ConnectorError::encode)
+            }
+            Ok(Action::FastPath) => {
+                let (data_offset, _) = read_fast_path_length(frame)?;
+                let (signature, data) = self.cipher.encrypt(&frame[data_offset..], false);
+
+                fast_path_frame((header & 0x3f) | (FASTPATH_ENCRYPTED << 6), &signature, &data)
+            }
+            Err(_) => Err(reason_err!("encrypt", "unknown frame action: {header:#04x}")),
+        }
+    }
+
+    fn decrypt_user_data(&mut self, user_data: &[u8]) -> ConnectorResult<Vec<u8>> {
+        let (flags, data) = split_security_header(user_data)?;
+
+        let data = if flags.contains(BasicSecurityHeaderFlags::ENCRYPT) {
+            let (signature, data) = split_signature(data)?;
+            self.cipher
+                .decrypt(
+                    data,
+                    signature,
+                    flags.contains(BasicSecurityHeaderFlags::SECURE_CHECKSUM),
+                )
+                .map_err(ConnectorError::decode)?
+        } else {
+            data.to_vec()
+        };
+
+        let flags = flags - BasicSecurityHeaderFlags::ENCRYPT - BasicSecurityHeaderFlags::SECURE_CHECKSUM;
+        if flags.is_empty() {
+            Ok(data)
+        } else {
+            Ok(with_security_header(flags, &[], &data))
+        }
+    }
+
+    fn encrypt_user_data(&mut self, user_data: &[u8], has_security_header: bool) -> ConnectorResult<Vec<u8>> {
+        let (flags, data) = if has_security_header {
+            split_security_header(user_data)?
+        } else {
+            (BasicSecurityHeaderFlags::empty(), user_data)
+        };
+
+        let (signature, data) = self.cipher.encrypt(data, false);
+
+        Ok(with_security_header(
+            flags | BasicSecurityHeaderFlags::ENCRYPT,
+            &signature,
+            &data,
+        ))
+    }
+}
+
+fn split_security_header(user_data: &[u8]) -> ConnectorResult<(BasicSecurityHeaderFlags, &[u8])> {
+    let (Some(&low), Some(&high)) = (user_data.first(), user_data.get(1)) else {
+        return Err(reason_err!("security header", "missing basic security header"));
+    };
+    let flags = BasicSecurityHeaderFlags::from_bits_truncate(u16::from_le_bytes([low, high]));
+
+    // The flagsHi field is unused.
+    let data = user_data
+        .get(4..)
+        .ok_or_else(|| reason_err!("security header", "missing basic security header"))?;
+
+    Ok((flags, data))
+}
+
+fn split_signature(data: &[u8]) -> ConnectorResult<(&[u8], &[u8])> {
+    if data.len() < DATA_SIGNATURE_LEN {
+        return Err(reason_err!("decrypt", "missing data signature"));
+    }
+
+    Ok(data.split_at(DATA_SIGNATURE_LEN))
+}
+
+fn with_security_header(flags: BasicSecurityHeaderFlags, signature: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut user_data = Vec::new();
+    user_data.extend_from_slice(&flags.bits().to_le_bytes());
+    user_data.extend_from_slice(&[0, 0]);
+    user_data.extend_from_slice(signature);
+    user_data.extend_from_slice(data);
+    user_data
+}
+
+/// Returns the offset of the data following the length field of a fast-path frame, and the frame length
+fn read_fast_path_length(frame: &[u8]) -> ConnectorResult<(usize, usize)> {
+    let too_short = || reason_err!("fast-path", "frame too short");
+
+    let first = *frame.get(1).ok_or_else(too_short)?;
+    let (offset, length) = if first & 0x80 != 0 {
+        let second = *frame.get(2).ok_or_else(too_short)?;
+        (3, usize::from(u16::from_be_bytes([first & 0x7f, second])))
+    } else {
+        (2, usize::from(first))
+    };
+
+    if length != frame.len() {
+        return Err(reason_err!("fast-path", "unexpected frame length"));
+    }
+
+    Ok((offset, length))
+}
+
+#[allow(clippy::arithmetic_side_effects)] // The payload length is checked against the maximum frame length first.
+fn fast_path_frame(header: u8, signature: &[u8], data: &[u8]) -> ConnectorResult<Vec<u8>> {
+    const MAX_FRAME_LEN: usize = 0x7fff;
+
+    let payload_len = signature.len() + data.len();
+    if payload_len > MAX_FRAME_LEN - 3 {
+        return Err(reason_err!("fast-path", "frame too long"));
+    }
+
+    let mut frame = Vec::new();
+    frame.push(header);
+    if payload_len + 2 <= 0x7f {
+        frame.push(u8::try_from(payload_len + 2).expect("checked above"));
+    } else {
+        let length = u16::try_from(payload_len + 3).expect("checked above");
+        frame.extend_from_slice(&(length | 0x8000).to_be_bytes());
+    }
+    frame.extend_from_slice(signature);
+    frame.extend_from_slice(data);
+
+    Ok(frame)
+}
@@ -0,0 +1,327 @@
+use std::collections::BTreeMap;
+use std::io;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use ironrdp_pdu::mcs::McsMessage;
+use ironrdp_pdu::x224::X224;
+use ironrdp_pdu::Action;
+
+/// Receiver of the frames exchanged over a connection.
+///
+/// A sink is installed on a [`Framed`](crate::Framed) with [`Framed::set_capture`](crate::Framed::set_capture).
+/// Since the frames are captured above the TLS layer, the decrypted traffic is captured.
+pub trait CaptureSink: Send {
+    /// Captures a complete frame received from the peer.
+    fn received(&mut self, frame: &[u8]) -> io::Result<()>;
+
+    /// Captures a frame sent to the peer.
+    fn sent(&mut self, frame: &[u8]) -> io::Result<()>;
+
+    /// Associates a name with a MCS channel, for annotating the frames sent over it.
+    fn register_channel(&mut self, _channel_id: u16, _name: &str) {}
+}
+
+/// Side of the connection the traffic is captured from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CaptureEndpoint {
+    Client,
+    Server,
+}
+
+/// Link type for raw IPv4 packets, without link-layer header.
+const LINKTYPE_RAW: u16 = 101;
+
+const IPV4_HEADER_LEN: u16 = 20;
+const TCP_HEADER_LEN: u16 = 20;
+
+/// Maximum payload of a synthesized TCP segment, so that the IPv4 total length fits in 16 bits.
+const MAX_SEGMENT_LEN: usize = 65535 - 20 - 20;
+
+// Documentation addresses (RFC 5737), the actual addresses being irrelevant for the analysis.
+const CLIENT_ADDR: [u8; 4] = [192, 0, 2, 1];
+const SERVER_ADDR: [u8; 4] = [192, 0, 2, 2];
+const CLIENT_PORT: u16 = 49152;
+const SERVER_PORT: u16 = 3389;
+
+/// [`CaptureSink`] writing the frames in the pcapng format.
+///
+/// Each frame is written as a synthesized TCP segment between port 49152 (client) and port 3389 (server),
+/// so that Wireshark applies its RDP dissector. A comment describing the direction, the kind of frame and
+/// the MCS channel (when applicable) is attached to each packet.
+///
+/// Each packet is flushed as soon as written, so that the capture is usable even if the connection
+/// is abruptly terminated.
+pub struct PcapngCapture<W> {
+    writer: W,
+    endpoint: CaptureEndpoint,
+    client_seq: u32,
+    server_seq: u32,
+    channels: BTreeMap<u16, String>,
+}
+
+impl<W> PcapngCapture<W>
+where
+    W: io::Write + Send,
+{
+    /// Writes the pcapng header into `writer`, and returns a sink writing the captured frames after it.
+    pub fn new(mut writer: W, endpoint: CaptureEndpoint) -> io::Result<Self> {
+        writer.write_all(&section_header_block())?;
+        writer.write_all(&interface_description_block())?;
+        writer.flush()?;
+
+        Ok(Self {
+            writer,
+            endpoint,
+            client_seq: 1,
+            server_seq: 1,
+            channels: BTreeMap::new(),
+        })
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    fn write_frame(&mut self, from_client: bool, frame: &[u8]) -> io::Result<()> {
+        let comment = self.describe(from_client, frame);
+
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| u64::try_from(elapsed.as_micros()).unwrap_or(u64::MAX))
+            .unwrap_or(0);
+
+        for segment in frame.chunks(MAX_SEGMENT_LEN) {
+            let segment_len = u32::try_from(segment.len()).expect("segment length fits in 16 bits");
+
+            let packet = if from_client {
+                let packet = tcp_packet(true, self.client_seq, self.server_seq, segment);
+                self.client_seq = self.client_seq.wrapping_add(segment_len);
+                packet
+            } else {
+                let packet = tcp_packet(false, self.server_seq, self.client_seq, segment);
+                self.server_seq = self.server_seq.wrapping_add(segment_len);
+                packet
+            };
+
+            self.writer
+                .write_all(&enhanced_packet_block(timestamp, &packet, &comment))?;
+        }
+
+        self.writer.flush()
+    }
+
+    fn describe(&self, from_client: bool, frame: &[u8]) -> String {
+        let mut comment = String::from(if from_client {
+            "client → server"
+        } else {
+            "server → client"
+        });
+
+        match ironrdp_pdu::find_size(frame) {
+            Ok(Some(info)) if info.action == Action::FastPath => comment.push_str(", fast-path"),
+            Ok(Some(_)) => {
+                comment.push_str(", X.224");
+
+                let channel_id = match ironrdp_core::decode::<X224<McsMessage<'_>>>(frame) {
+                    Ok(X224(McsMessage::SendDataRequest(msg))) => Some(msg.channel_id),
+                    Ok(X224(McsMessage::SendDataIndication(msg))) => Some(msg.channel_id),
+                    _ => None,
+                };
+
+                if let Some(channel_id) = channel_id {
+                    comment.push_str(", MCS channel ");
+                    comment.push_str(&channel_id.to_string());
+
+                    if let Some(name) = self.channels.get(&channel_id) {
+                        comment.push_str(" (");
+                        comment.push_str(name);
+                        comment.push(')');
+                    }
+                }
+            }
+            // Not a TPKT or fast-path frame (e.g.: CredSSP).
+            Ok(None) | Err(_) => {}
+        }
+
+        comment
+    }
+}
+
+impl<W> CaptureSink for PcapngCapture<W>
+where
+    W: io::Write + Send,
+{
+    fn received(&mut self, frame: &[u8]) -> io::Result<()> {
+        let from_client = self.endpoint == CaptureEndpoint::Server;
+        self.write_frame(from_client, frame)
+    }
+
+    fn sent(&mut self, frame: &[u8]) -> io::Result<()> {
+        let from_client = self.endpoint == CaptureEndpoint::Client;
+        self.write_frame(from_client, frame)
+    }
+
+    fn register_channel(&mut self, channel_id: u16, name: &str) {
+        self.channels.insert(channel_id, name.to_owned());
+    }
+}
+
+fn section_header_block() -> Vec<u8> {
+    let mut body = Vec::new();
+    body.extend_from_slice(&0x1A2B_3C4Du32.to_le_bytes()); // byte-order magic
+    body.extend_from_slice(&1u16.to_le_bytes()); // major version
+    body.extend_from_slice(&0u16.to_le_bytes()); // minor version
+    body.extend_from_slice(&(-1i64).to_le_bytes()); // section length, unspecified
+    push_option(&mut body, 4, b"IronRDP"); // shb_userappl
+    push_end_of_options(&mut body);
+
+    block(0x0A0D_0D0A, &body)
+}
+
+fn interface_description_block() -> Vec<u8> {
+    let mut body = Vec::new();
+    body.extend_from_slice(&LINKTYPE_RAW.to_le_bytes());
+    body.extend_from_slice(&0u16.to_le_bytes()); // reserved
+    body.extend_from_slice(&0u32.to_le_bytes()); // snap length, unlimited
+    push_option(&mut body, 2, b"rdp"); // if_name
+    push_end_of_options(&mut body);
+
+    block(0x0000_0001, &body)
+}
+
+fn enhanced_packet_block(timestamp: u64, packet: &[u8], comment: &str) -> Vec<u8> {
+    let packet_len = u32::try_from(packet.len()).expect("packet length fits in 16 bits");
+
+    // The high 32 bits of the timestamp come first.
+    let timestamp = timestamp.to_le_bytes();
+
+    let mut body = Vec::new();
+    body.extend_from_slice(&0u32.to_le_bytes()); // interface ID
+    body.extend_from_slice(&timestamp[4..8]);
+    body.extend_from_slice(&timestamp[0..4]);
+    body.extend_from_slice(&packet_len.to_le_bytes()); // captured length
+    body.extend_from_slice(&packet_len.to_le_bytes()); // original length
+    body.extend_from_slice(packet);
+    pad(&mut body);
+    push_option(&mut body, 1, comment.as_bytes()); // opt_comment
+    push_end_of_options(&mut body);
+
+    block(0x0000_0006, &body)
+}
+
+fn block(block_type: u32, body: &[u8]) -> Vec<u8> {
+    // Block type, body, and the total length repeated before and after the body.
+    let total_len = body.len().checked_add(12).expect("block length overflow");
+    let total_len = u32::try_from(total_len).expect("block length fits in 32 bits");
+
+    let mut block = Vec::new();
+    block.extend_from_slice(&block_type.to_le_bytes());
+    block.extend_from_slice(&total_len.to_le_bytes());
+    block.extend_from_slice(body);
+    block.extend_from_slice(&total_len.to_le_bytes());
+    block
+}
+
+fn push_option(body: &mut Vec<u8>, code: u16, value: &[u8]) {
+    // Longer values are truncated, which is acceptable for the informative options written here.
+    let value = &value[..value.len().min(usize::from(u16::MAX))];
+    let value_len = u16::try_from(value.len()).expect("option length fits in 16 bits");
+
+    body.extend_from_slice(&code.to_le_bytes());
+    body.extend_from_slice(&value_len.to_le_bytes());
+    body.extend_from_slice(value);
+    pad(body);
+}
+
+fn push_end_of_options(body: &mut Vec<u8>) {
+    body.extend_from_slice(&[0; 4]);
+}
+
+/// Pads to a 32-bit boundary, as required for the block fields.
+fn pad(body: &mut Vec<u8>) {
+    while body.len() % 4 != 0 {
+        body.push(0);
+    }
+}
+
+fn tcp_packet(from_client: bool, seq: u32, ack: u32, payload: &[u8]) -> Vec<u8> {
+    let (src_addr, dst_addr, src_port, dst_port) = if from_client {
+        (CLIENT_ADDR, SERVER_ADDR, CLIENT_PORT, SERVER_PORT)
+    } else {
+        (SERVER_ADDR, CLIENT_ADDR, SERVER_PORT, CLIENT_PORT)
+    };
+
+    let tcp_len = u16::try_from(payload.len())
+        .ok()
+        .and_then(|payload_len| payload_len.checked_add(TCP_HEADER_LEN))
+        .expect("segment length fits in 16 bits");
+    let total_len = tcp_len
+        .checked_add(IPV4_HEADER_LEN)
+        .expect("segment length fits in 16 bits");
+
+    let mut packet = Vec::with_capacity(usize::from(total_len));
+
+    // IPv4 header
+    packet.push(0x45); // version 4, header length of 5 words
+    packet.push(0); // DSCP / ECN
+    packet.extend_from_slice(&total_len.to_be_bytes());
+    packet.extend_from_slice(&0u16.to_be_bytes()); // identification
+    packet.extend_from_slice(&0x4000u16.to_be_bytes()); // don't fragment
+    packet.push(64); // TTL
+    packet.push(6); // TCP
+    packet.extend_from_slice(&0u16.to_be_bytes()); // checksum, computed below
+    packet.extend_from_slice(&src_addr);
+    packet.extend_from_slice(&dst_addr);
+
+    let ip_checksum = checksum(0, &packet);
+    packet[10..12].copy_from_slice(&ip_checksum.to_be_bytes());
+
+    // TCP header
+    packet.extend_from_slice(&src_port.to_be_bytes());
+    packet.extend_from_slice(&dst_port.to_be_bytes());
+    packet.extend_from_slice(&seq.to_be_bytes());
+    packet.extend_from_slice(&ack.to_be_bytes());
+    packet.push(0x50); // header length of 5 words
+    packet.push(0x18); // PSH | ACK
+    packet.extend_from_slice(&u16::MAX.to_be_bytes()); // window
+    packet.extend_from_slice(&0u16.to_be_bytes()); // checksum, computed below
+    packet.extend_from_slice(&0u16.to_be_bytes()); // urgent pointer
+    packet.extend_from_slice(payload);
+
+    // Pseudo-header: addresses, protocol and TCP length.
+    let mut pseudo_header = [0; 12];
+    pseudo_header[0..4].copy_from_slice(&src_addr);
+    pseudo_header[4..8].copy_from_slice(&dst_addr);
+    pseudo_header[9] = 6;
+    pseudo_header[10..12].copy_from_slice(&tcp_len.to_be_bytes());
+
+    let tcp_checksum = checksum(sum_words(0, &pseudo_header), &packet[20..]);
+    packet[36..38].copy_from_slice(&tcp_checksum.to_be_bytes());
+
+    packet
+}
+
+/// Internet checksum (RFC 1071) of `data`, continuing from the partial sum `initial`.
+fn checksum(initial: u32, data: &[u8]) -> u16 {
+    let mut sum = sum_words(initial, data);
+
+    while sum > 0xFFFF {
+        sum = (sum & 0xFFFF).wrapping_add(sum >> 16);
+    }
+
+    !u16::try_from(sum).expect("folded into 16 bits")
+}
+
+fn sum_words(initial: u32, data: &[u8]) -> u32 {
+    data.chunks(2).fold(initial, |sum, word| {
+        let word = match *word {
+            [high, low] => u16::from_be_bytes([high, low]),
+            [high] => u16::from_be_bytes([high, 0]),
+            _ => unreachable!("chunks of at most 2 bytes"),
+        };
+
+        // Folding the carry immediately, so that the sum never overflows.
+        let sum = sum.wrapping_add(u32::from(word));
+        (sum & 0xFFFF).wrapping_add(sum >> 16)
+    })
+}
@@ -132,6 +132,7 @@ where
         server_name,
         server_public_key,
         kerberos_config,
+        connector.config.restricted_admin,
     )?;
 
     loop {
@@ -5,6 +5,8 @@ use ironrdp_connector::{ConnectorResult, Sequence, Written};
 use ironrdp_core::WriteBuf;
 use ironrdp_pdu::PduHint;
 
+use crate::CaptureSink;
+
 // TODO: investigate if we could use static async fn / return position impl trait in traits when stabilized:
 // https://github.com/rust-lang/rust/issues/91611
 
@@ -55,12 +57,38 @@ pub trait StreamWrapper: Sized {
 pub struct Framed<S> {
     stream: S,
     buf: BytesMut,
+    capture: Option<Box<dyn CaptureSink>>,
 }
 
 impl<S> Framed<S> {
     pub fn peek(&self) -> &[u8] {
         &self.buf
     }
+
+    /// Installs a sink capturing the frames read and written from now on, returning the previous one.
+    ///
+    /// If the sink fails, the error is logged and the capture is stopped, without interrupting the connection.
+    pub fn set_capture(&mut self, capture: Box<dyn CaptureSink>) -> Option<Box<dyn CaptureSink>> {
+        self.capture.replace(capture)
+    }
+
+    /// Removes the capture sink, if any.
+    pub fn take_capture(&mut self) -> Option<Box<dyn CaptureSink>> {
+        self.capture.take()
+    }
+
+    pub fn capture_mut(&mut self) -> Option<&mut (dyn CaptureSink + 'static)> {
+        self.capture.as_deref_mut()
+    }
+
+    fn capture_with(&mut self, f: impl FnOnce(&mut dyn CaptureSink) -> io::Result<()>) {
+        if let Some(capture) = self.capture.as_deref_mut() {
+            if let Err(error) = f(capture) {
+                warn!(%error, "Failed to capture a frame; capture is stopped");
+                self.capture = None;
+            }
+        }
+    }
 }
 
 impl<S> Framed<S>
@@ -75,6 +103,7 @@ where
         Self {
             stream: S::from_inner(stream),
             buf: leftover,
+            capture: None,
         }
     }
 
@@ -112,7 +141,9 @@ where
     pub async fn read_exact(&mut self, length: usize) -> io::Result<BytesMut> {
         loop {
             if self.buf.len() >= length {
-                return Ok(self.buf.split_to(length));
+                let frame = self.buf.split_to(length);
+                self.capture_with(|capture| capture.received(&frame));
+                return Ok(frame);
             } else {
                 self.buf
                     .reserve(length.checked_sub(self.buf.len()).expect("length > self.buf.len()"));
@@ -223,7 +254,9 @@ where
     /// partially written, but future calls to `write_all` will start over
     /// from the beginning of the buffer.
     pub async fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
-        self.stream.write_all(buf).await
+        self.stream.write_all(buf).await?;
+        self.capture_with(|capture| capture.sent(buf));
+        Ok(())
     }
 }
 
@@ -3,6 +3,7 @@ extern crate tracing;
 
 pub use bytes;
 
+mod capture;
 mod connector;
 mod framed;
 mod session;
@@ -13,6 +14,7 @@ use std::pin::Pin;
 use ironrdp_connector::sspi::generator::NetworkRequest;
 use ironrdp_connector::ConnectorResult;
 
+pub use self::capture::*;
 pub use self::connector::*;
 pub use self::framed::*;
 // pub use self::session::*;
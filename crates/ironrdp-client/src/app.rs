@@ -10,12 +10,23 @@ use winit::application::ApplicationHandler;
 use winit::dpi::{LogicalPosition, PhysicalSize};
 use winit::event::{self, WindowEvent};
 use winit::event_loop::{ActiveEventLoop, ControlFlow, EventLoop};
-use winit::keyboard::ModifiersKeyState;
+use winit::keyboard::{KeyCode, ModifiersKeyState, ModifiersState, PhysicalKey};
+use winit::monitor::MonitorHandle;
 use winit::platform::scancode::PhysicalKeyExtScancode;
-use winit::window::{Window, WindowAttributes};
+use winit::window::{Fullscreen, Window, WindowAttributes};
 
+use crate::config::Config;
+use crate::hotkey::Hotkey;
+use crate::keyboard_grab::KeyboardGrab;
+use crate::monitors::{MonitorGeometry, MonitorLayout, MonitorSelection};
 use crate::rdp::{RdpInputEvent, RdpOutputEvent};
 
+/// Delay after the last resize of the window before the new size is sent to the server
+///
+/// While the window edge is dragged, a resize event is received for each intermediate size: only the final size
+/// is sent, as each resolution change makes the server execute a deactivation-reactivation sequence.
+const RESIZE_DEBOUNCE: Duration = Duration::from_millis(500);
+
 type WindowSurface = (Arc<Window>, softbuffer::Surface<DisplayHandle<'static>, Arc<Window>>);
 
 pub struct App {
@@ -27,12 +38,33 @@ pub struct App {
     input_database: ironrdp::input::Database,
     last_size: Option<PhysicalSize<u32>>,
     resize_timeout: Option<Instant>,
+    modifiers: ModifiersState,
+    /// Monitors covered by the window in fullscreen mode
+    fullscreen_selection: MonitorSelection,
+    /// Whether the window is created in fullscreen mode
+    start_fullscreen: bool,
+    /// Layout of the monitors covered by the window, when in fullscreen mode
+    fullscreen_layout: Option<MonitorLayout>,
+    /// Size of the window to restore when leaving fullscreen mode
+    windowed_size: Option<PhysicalSize<u32>>,
+    /// Last layout sent to the RDP client
+    sent_layout: Option<MonitorLayout>,
+    /// Last scale factor sent to the RDP client
+    sent_scale_factor: Option<u32>,
+    /// Keyboard grab, with the hotkey toggling it
+    keyboard_grab: Option<(KeyboardGrab, Hotkey)>,
+    /// Whether the keyboard grab is toggled on
+    keyboard_grabbed: bool,
+    focused: bool,
+    screenshot_hotkey: Hotkey,
+    recording_hotkey: Hotkey,
 }
 
 impl App {
     pub fn new(
         event_loop: &EventLoop<RdpOutputEvent>,
         input_event_sender: &mpsc::UnboundedSender<RdpInputEvent>,
+        config: &Config,
     ) -> anyhow::Result<Self> {
         // SAFETY: We drop the softbuffer context right before the event loop is stopped, thus making this safe.
         // FIXME: This is not a sufficient proof and the API is actually unsound as-is.
@@ -42,6 +74,17 @@ impl App {
         let context = softbuffer::Context::new(display_handle)
             .map_err(|e| anyhow::anyhow!("unable to initialize softbuffer context: {e}"))?;
 
+        let keyboard_grab =
+            config
+                .keyboard_grab
+                .and_then(|hotkey| match KeyboardGrab::new(event_loop.create_proxy()) {
+                    Ok(grab) => Some((grab, hotkey)),
+                    Err(error) => {
+                        warn!("The keyboard can’t be grabbed: {error:#}");
+                        None
+                    }
+                });
+
         let input_database = ironrdp::input::Database::new();
         Ok(Self {
             input_event_sender: input_event_sender.clone(),
@@ -52,9 +95,93 @@ impl App {
             input_database,
             last_size: None,
             resize_timeout: None,
+            modifiers: ModifiersState::empty(),
+            start_fullscreen: config.fullscreen.is_some(),
+            fullscreen_selection: config.fullscreen.clone().unwrap_or(MonitorSelection::Current),
+            fullscreen_layout: None,
+            windowed_size: None,
+            sent_layout: None,
+            sent_scale_factor: None,
+            keyboard_grabbed: keyboard_grab.is_some(),
+            keyboard_grab,
+            focused: false,
+            screenshot_hotkey: config.screenshot_hotkey,
+            recording_hotkey: config.recording_hotkey,
         })
     }
 
+    /// Grabs the keyboard when the grab is toggled on and the window has the focus
+    fn update_keyboard_grab(&mut self) {
+        let Some((grab, _)) = &self.keyboard_grab else {
+            return;
+        };
+
+        grab.set_active(self.keyboard_grabbed && self.focused);
+    }
+
+    fn toggle_keyboard_grab(&mut self) {
+        self.keyboard_grabbed = !self.keyboard_grabbed;
+        info!(grabbed = self.keyboard_grabbed, "Keyboard grab toggled");
+
+        // The keys pressed with the hotkey are held down on the remote.
+        let input_events = self.input_database.release_all();
+        send_fast_path_events(&self.input_event_sender, input_events);
+
+        self.update_keyboard_grab();
+    }
+
+    /// Covers the selected monitors with the window, returning the resulting layout
+    fn enter_fullscreen(&mut self, event_loop: &ActiveEventLoop) -> anyhow::Result<MonitorLayout> {
+        let Some((window, _)) = self.window.as_ref() else {
+            anyhow::bail!("no window");
+        };
+
+        let available: Vec<MonitorHandle> = event_loop.available_monitors().collect();
+        let monitors =
+            self.fullscreen_selection
+                .select(&available, window.current_monitor(), event_loop.primary_monitor())?;
+        let geometries: Vec<MonitorGeometry> = monitors.iter().map(MonitorGeometry::from).collect();
+        let layout = MonitorLayout::new(&geometries)?;
+
+        self.windowed_size = Some(window.inner_size());
+
+        if let [monitor] = monitors.as_slice() {
+            window.set_fullscreen(Some(Fullscreen::Borderless(Some(monitor.clone()))));
+        } else {
+            // A fullscreen window covers a single monitor, a borderless window is laid over the monitors instead.
+            window.set_decorations(false);
+            window.set_outer_position(layout.position);
+            let _ = window.request_inner_size(layout.size());
+        }
+
+        self.fullscreen_layout = Some(layout.clone());
+
+        Ok(layout)
+    }
+
+    fn exit_fullscreen(&mut self) {
+        let Some((window, _)) = self.window.as_ref() else {
+            return;
+        };
+
+        self.fullscreen_layout = None;
+
+        window.set_fullscreen(None);
+        window.set_decorations(true);
+
+        if let Some(size) = self.windowed_size.take() {
+            let _ = window.request_inner_size(size);
+        }
+    }
+
+    fn toggle_fullscreen(&mut self, event_loop: &ActiveEventLoop) {
+        if self.fullscreen_layout.is_some() {
+            self.exit_fullscreen();
+        } else if let Err(error) = self.enter_fullscreen(event_loop) {
+            error!("Failed to enter fullscreen mode: {error:#}");
+        }
+    }
+
     fn send_resize_event(&mut self) {
         let Some(size) = self.last_size.take() else {
             return;
@@ -62,11 +189,34 @@ impl App {
         let Some((window, _)) = self.window.as_mut() else {
             return;
         };
+
+        // In fullscreen mode, each monitor is advertised with its own size and scale factor.
+        if let Some(layout) = &self.fullscreen_layout {
+            if self.sent_layout.as_ref() != Some(layout) {
+                let _ = self
+                    .input_event_sender
+                    .send(RdpInputEvent::MonitorLayout(layout.clone()));
+                self.sent_layout = Some(layout.clone());
+            }
+            return;
+        }
+
+        self.sent_layout = None;
+
         let scale_factor = (window.scale_factor() * 100.0) as u32;
+        let width = u16::try_from(size.width).unwrap_or(u16::MAX);
+        let height = u16::try_from(size.height).unwrap_or(u16::MAX);
+
+        // E.g.: the window was resized back to its original size before the end of the debounce delay.
+        if (width, height) == self.buffer_size && self.sent_scale_factor == Some(scale_factor) {
+            return;
+        }
+
+        self.sent_scale_factor = Some(scale_factor);
 
         let _ = self.input_event_sender.send(RdpInputEvent::Resize {
-            width: u16::try_from(size.width).unwrap(),
-            height: u16::try_from(size.height).unwrap(),
+            width,
+            height,
             scale_factor,
             // TODO: it should be possible to get the physical size here, however winit doesn't make it straightforward.
             // FreeRDP does it based on DPI reading grabbed via [`SDL_GetDisplayDPI`](https://wiki.libsdl.org/SDL2/SDL_GetDisplayDPI):
@@ -108,6 +258,24 @@ impl ApplicationHandler<RdpOutputEvent> for App {
                 let window = Arc::new(window);
                 let surface = softbuffer::Surface::new(&self.context, Arc::clone(&window)).expect("surface");
                 self.window = Some((window, surface));
+
+                if self.start_fullscreen {
+                    self.start_fullscreen = false;
+
+                    match self.enter_fullscreen(event_loop) {
+                        Ok(layout) => {
+                            // The RDP client waits for the initial layout before connecting.
+                            let _ = self
+                                .input_event_sender
+                                .send(RdpInputEvent::MonitorLayout(layout.clone()));
+                            self.sent_layout = Some(layout);
+                        }
+                        Err(error) => {
+                            eprintln!("Failed to enter fullscreen mode: {error:#}");
+                            event_loop.exit();
+                        }
+                    }
+                }
             }
             Err(error) => {
                 error!(%error, "Failed to create window");
@@ -126,8 +294,13 @@ impl ApplicationHandler<RdpOutputEvent> for App {
 
         match event {
             WindowEvent::Resized(size) => {
+                // The window is minimized, there is nothing to display.
+                if size.width == 0 || size.height == 0 {
+                    return;
+                }
+
                 self.last_size = Some(size);
-                self.resize_timeout = Some(Instant::now() + Duration::from_secs(1));
+                self.resize_timeout = Some(Instant::now() + RESIZE_DEBOUNCE);
             }
             WindowEvent::CloseRequested => {
                 if self.input_event_sender.send(RdpInputEvent::Close).is_err() {
@@ -157,6 +330,48 @@ impl ApplicationHandler<RdpOutputEvent> for App {
             // TODO(#376): Implement unicode input in native client
             // }
             WindowEvent::KeyboardInput { event, .. } => {
+                // Ctrl+Alt+Enter toggles fullscreen mode, and is not sent to the server.
+                if event.physical_key == PhysicalKey::Code(KeyCode::Enter)
+                    && self.modifiers.control_key()
+                    && self.modifiers.alt_key()
+                {
+                    if event.state == event::ElementState::Pressed && !event.repeat {
+                        self.toggle_fullscreen(event_loop);
+                    }
+
+                    return;
+                }
+
+                if let PhysicalKey::Code(key) = event.physical_key {
+                    let pressed = event.state == event::ElementState::Pressed && !event.repeat;
+
+                    if let Some((_, hotkey)) = &self.keyboard_grab {
+                        if hotkey.matches(self.modifiers, key) {
+                            if pressed {
+                                self.toggle_keyboard_grab();
+                            }
+
+                            return;
+                        }
+                    }
+
+                    if self.screenshot_hotkey.matches(self.modifiers, key) {
+                        if pressed {
+                            let _ = self.input_event_sender.send(RdpInputEvent::Screenshot);
+                        }
+
+                        return;
+                    }
+
+                    if self.recording_hotkey.matches(self.modifiers, key) {
+                        if pressed {
+                            let _ = self.input_event_sender.send(RdpInputEvent::ToggleRecording);
+                        }
+
+                        return;
+                    }
+                }
+
                 if let Some(scancode) = event.physical_key.to_scancode() {
                     let scancode = ironrdp::input::Scancode::from_u16(u16::try_from(scancode).unwrap());
 
@@ -195,6 +410,8 @@ impl ApplicationHandler<RdpOutputEvent> for App {
                 let input_events = self.input_database.apply(operations);
 
                 send_fast_path_events(&self.input_event_sender, input_events);
+
+                self.modifiers = state.state();
             }
             WindowEvent::CursorMoved { position, .. } => {
                 let win_size = window.inner_size();
@@ -282,12 +499,23 @@ impl ApplicationHandler<RdpOutputEvent> for App {
             WindowEvent::RedrawRequested => {
                 self.draw();
             }
+            WindowEvent::ScaleFactorChanged { .. } => {
+                // The window moved to a monitor with another DPI: the new scale factor is sent to the server,
+                // even if the size of the window is unchanged.
+                if self.fullscreen_layout.is_none() {
+                    self.last_size = Some(window.inner_size());
+                    self.resize_timeout = Some(Instant::now() + RESIZE_DEBOUNCE);
+                }
+            }
+            WindowEvent::Focused(focused) => {
+                self.focused = focused;
+                self.update_keyboard_grab();
+            }
             WindowEvent::ActivationTokenDone { .. }
             | WindowEvent::Moved(_)
             | WindowEvent::Destroyed
             | WindowEvent::HoveredFile(_)
             | WindowEvent::HoveredFileCancelled
-            | WindowEvent::Focused(_)
             | WindowEvent::Ime(_)
             | WindowEvent::CursorEntered { .. }
             | WindowEvent::CursorLeft { .. }
@@ -298,7 +526,6 @@ impl ApplicationHandler<RdpOutputEvent> for App {
             | WindowEvent::TouchpadPressure { .. }
             | WindowEvent::AxisMotion { .. }
             | WindowEvent::Touch(_)
-            | WindowEvent::ScaleFactorChanged { .. }
             | WindowEvent::ThemeChanged(_)
             | WindowEvent::Occluded(_) => {
                 // ignore
@@ -357,6 +584,17 @@ impl ApplicationHandler<RdpOutputEvent> for App {
                     error!(?error, "Failed to set cursor position");
                 }
             }
+            RdpOutputEvent::GrabbedKey { scancode, pressed } => {
+                let operation = if pressed {
+                    ironrdp::input::Operation::KeyPressed(scancode)
+                } else {
+                    ironrdp::input::Operation::KeyReleased(scancode)
+                };
+
+                let input_events = self.input_database.apply(std::iter::once(operation));
+
+                send_fast_path_events(&self.input_event_sender, input_events);
+            }
         }
     }
 }
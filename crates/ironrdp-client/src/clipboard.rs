@@ -1,24 +1,130 @@
-use ironrdp::cliprdr::backend::{ClipboardMessage, ClipboardMessageProxy};
+use ironrdp::cliprdr::backend::{ClipboardMessage, ClipboardMessageProxy, CliprdrBackend, CliprdrBackendFactory};
+use ironrdp::cliprdr::pdu::{
+    ClipboardFormat, ClipboardGeneralCapabilityFlags, FileContentsRequest, FileContentsResponse, FormatDataRequest,
+    FormatDataResponse, LockDataId,
+};
+use ironrdp_core::impl_as_any;
 use tokio::sync::mpsc;
 
+use crate::config::ClipboardDirection;
 use crate::rdp::RdpInputEvent;
 
 /// Shim for sending and receiving CLIPRDR events as `RdpInputEvent`
+///
+/// The messages are filtered according to the clipboard direction: when copying from the local clipboard is not
+/// allowed, an empty format list is advertised to the remote, and its data requests are rejected.
 #[derive(Clone, Debug)]
 pub struct ClientClipboardMessageProxy {
     tx: mpsc::UnboundedSender<RdpInputEvent>,
+    direction: ClipboardDirection,
 }
 
 impl ClientClipboardMessageProxy {
-    pub fn new(tx: mpsc::UnboundedSender<RdpInputEvent>) -> Self {
-        Self { tx }
+    pub fn new(tx: mpsc::UnboundedSender<RdpInputEvent>, direction: ClipboardDirection) -> Self {
+        Self { tx, direction }
     }
 }
 
 impl ClipboardMessageProxy for ClientClipboardMessageProxy {
     fn send_clipboard_message(&self, message: ClipboardMessage) {
+        let message = match message {
+            ClipboardMessage::SendInitiateCopy(_) if !self.direction.allows_local_to_remote() => {
+                // The format list is still sent, as it is part of the channel initialization.
+                ClipboardMessage::SendInitiateCopy(Vec::new())
+            }
+            ClipboardMessage::SendFormatData(_) if !self.direction.allows_local_to_remote() => {
+                ClipboardMessage::SendFormatData(FormatDataResponse::new_error())
+            }
+            ClipboardMessage::SendInitiatePaste(_) if !self.direction.allows_remote_to_local() => return,
+            message => message,
+        };
+
         if self.tx.send(RdpInputEvent::Clipboard(message)).is_err() {
             error!("Failed to send os clipboard message, receiver is closed");
         }
     }
 }
+
+/// Wraps a backend factory, so that the backends ignore the content copied on the remote when pasting it locally
+/// is not allowed
+pub struct DirectionalBackendFactory {
+    inner: Box<dyn CliprdrBackendFactory + Send>,
+    direction: ClipboardDirection,
+}
+
+impl DirectionalBackendFactory {
+    pub fn new(inner: Box<dyn CliprdrBackendFactory + Send>, direction: ClipboardDirection) -> Self {
+        Self { inner, direction }
+    }
+}
+
+impl CliprdrBackendFactory for DirectionalBackendFactory {
+    fn build_cliprdr_backend(&self) -> Box<dyn CliprdrBackend> {
+        Box::new(DirectionalBackend {
+            inner: self.inner.build_cliprdr_backend(),
+            direction: self.direction,
+        })
+    }
+}
+
+#[derive(Debug)]
+struct DirectionalBackend {
+    inner: Box<dyn CliprdrBackend>,
+    direction: ClipboardDirection,
+}
+
+impl_as_any!(DirectionalBackend);
+
+impl CliprdrBackend for DirectionalBackend {
+    fn temporary_directory(&self) -> &str {
+        self.inner.temporary_directory()
+    }
+
+    fn client_capabilities(&self) -> ClipboardGeneralCapabilityFlags {
+        self.inner.client_capabilities()
+    }
+
+    fn on_request_format_list(&mut self) {
+        self.inner.on_request_format_list()
+    }
+
+    fn on_format_list_received(&mut self) {
+        self.inner.on_format_list_received()
+    }
+
+    fn on_process_negotiated_capabilities(&mut self, capabilities: ClipboardGeneralCapabilityFlags) {
+        self.inner.on_process_negotiated_capabilities(capabilities)
+    }
+
+    fn on_remote_copy(&mut self, available_formats: &[ClipboardFormat]) {
+        if self.direction.allows_remote_to_local() {
+            self.inner.on_remote_copy(available_formats)
+        } else {
+            self.inner.on_remote_copy(&[])
+        }
+    }
+
+    fn on_format_data_request(&mut self, request: FormatDataRequest) {
+        self.inner.on_format_data_request(request)
+    }
+
+    fn on_format_data_response(&mut self, response: FormatDataResponse<'_>) {
+        self.inner.on_format_data_response(response)
+    }
+
+    fn on_file_contents_request(&mut self, request: FileContentsRequest) {
+        self.inner.on_file_contents_request(request)
+    }
+
+    fn on_file_contents_response(&mut self, response: FileContentsResponse<'_>) {
+        self.inner.on_file_contents_response(response)
+    }
+
+    fn on_lock(&mut self, data_id: LockDataId) {
+        self.inner.on_lock(data_id)
+    }
+
+    fn on_unlock(&mut self, data_id: LockDataId) {
+        self.inner.on_unlock(data_id)
+    }
+}
@@ -1,24 +1,164 @@
 use std::io;
 use std::num::ParseIntError;
+use std::path::PathBuf;
 use std::str::FromStr;
 
 use anyhow::Context as _;
 use clap::clap_derive::ValueEnum;
 use clap::Parser;
-use ironrdp::connector::{self, Credentials};
+use ironrdp::connector::credssp::KerberosConfig;
+use ironrdp::connector::{self, Credentials, SmartCardIdentity};
+use ironrdp::core::DecodeMode;
 use ironrdp::pdu::rdp::capability_sets::MajorPlatformType;
 use ironrdp::pdu::rdp::client_info::PerformanceFlags;
+use ironrdp::svc::wire_log::WireLogging;
+use ironrdp_tls::CertificateVerification;
 use tap::prelude::*;
 
-const DEFAULT_WIDTH: u16 = 1920;
-const DEFAULT_HEIGHT: u16 = 1080;
+use crate::hotkey::Hotkey;
+use crate::monitors::MonitorSelection;
+use crate::profile::Profile;
+
+const DEFAULT_WIDTH: u16 = 1024;
+const DEFAULT_HEIGHT: u16 = 768;
 
 #[derive(Clone, Debug)]
 pub struct Config {
     pub log_file: Option<String>,
+    pub wire_logging: WireLogging,
+    /// File into which the traffic is captured, when recording from the start of the session
+    pub capture_file: Option<PathBuf>,
+    /// Directory in which the screenshots and recordings taken with the hotkeys are saved
+    pub output_dir: PathBuf,
+    pub screenshot_hotkey: Hotkey,
+    pub recording_hotkey: Hotkey,
     pub destination: Destination,
     pub connector: connector::Config,
     pub clipboard_type: ClipboardType,
+    pub clipboard_direction: ClipboardDirection,
+    pub sound: bool,
+    /// Monitors covered by the window at startup, when starting in fullscreen mode
+    pub fullscreen: Option<MonitorSelection>,
+    pub shared_drives: Vec<SharedDrive>,
+    pub shared_printer: Option<SharedPrinter>,
+    /// Hotkey toggling the keyboard grab, when the keyboard grab is enabled
+    pub keyboard_grab: Option<Hotkey>,
+    /// Kerberos settings, when Kerberos is preferred over NTLM
+    pub kerberos_config: Option<KerberosConfig>,
+    /// Expected fingerprint of the server certificate
+    pub cert_fingerprint: Option<CertificateFingerprint>,
+    pub certificate_verification: CertificateVerification,
+    /// Directory in which the client licenses issued by the server are stored
+    pub license_cache_dir: Option<PathBuf>,
+}
+
+/// SHA-256 fingerprint of a certificate
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CertificateFingerprint(pub [u8; 32]);
+
+impl CertificateFingerprint {
+    /// Computes the fingerprint of a DER-encoded certificate
+    pub fn of(certificate: &[u8]) -> Self {
+        use sha2::Digest as _;
+
+        Self(sha2::Sha256::digest(certificate).into())
+    }
+}
+
+impl FromStr for CertificateFingerprint {
+    type Err = anyhow::Error;
+
+    /// Parses hexadecimal digits, optionally separated by colons (as printed by `openssl x509 -fingerprint`)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let digits = s.replace(':', "");
+
+        anyhow::ensure!(
+            digits.len() == 64 && digits.is_ascii(),
+            "expected a SHA-256 fingerprint (64 hexadecimal digits)"
+        );
+
+        let mut fingerprint = [0; 32];
+
+        for (byte, chunk) in fingerprint.iter_mut().zip(digits.as_bytes().chunks(2)) {
+            *byte = std::str::from_utf8(chunk)
+                .ok()
+                .and_then(|chunk| u8::from_str_radix(chunk, 16).ok())
+                .context("invalid hexadecimal digit")?;
+        }
+
+        Ok(Self(fingerprint))
+    }
+}
+
+impl core::fmt::Display for CertificateFingerprint {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        for (idx, byte) in self.0.iter().enumerate() {
+            if idx != 0 {
+                write!(f, ":")?;
+            }
+            write!(f, "{byte:02X}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Local directory shared with the server as a drive
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SharedDrive {
+    pub path: PathBuf,
+    /// Name of the drive, as displayed on the server
+    pub name: String,
+    pub read_only: bool,
+}
+
+impl FromStr for SharedDrive {
+    type Err = anyhow::Error;
+
+    /// Parses `PATH[:NAME][:ro]`
+    ///
+    /// The drive is named after the last component of the path when no name is given.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (s, read_only) = match s.strip_suffix(":ro") {
+            Some(s) => (s, true),
+            None => (s, false),
+        };
+
+        // A name can’t contain a path separator, so that `C:\Users` is not split.
+        let (path, name) = match s.rsplit_once(':') {
+            Some((path, name)) if !path.is_empty() && !name.contains(['/', '\\']) => (path, Some(name)),
+            _ => (s, None),
+        };
+
+        anyhow::ensure!(!path.is_empty(), "empty drive path");
+
+        let path = PathBuf::from(path);
+
+        let name = match name {
+            Some(name) => name.to_owned(),
+            None => path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_else(|| "root".to_owned()),
+        };
+
+        anyhow::ensure!(!name.is_empty(), "empty drive name");
+
+        Ok(Self { path, name, read_only })
+    }
+}
+
+/// Printer shared with the server
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SharedPrinter {
+    /// Local printer on which the documents are printed, the default one when not set
+    pub local_printer: Option<String>,
+}
+
+impl SharedPrinter {
+    /// Name of the printer, as displayed on the server
+    pub fn name(&self) -> &str {
+        self.local_printer.as_deref().unwrap_or("IronRDP")
+    }
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
@@ -27,9 +167,48 @@ pub enum ClipboardType {
     Stub,
     #[cfg(windows)]
     Windows,
+    /// Text and images, on all platforms
+    Portable,
     None,
 }
 
+/// Directions in which the clipboard content is shared
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+pub enum ClipboardDirection {
+    Both,
+    /// Copy locally and paste on the remote only
+    LocalToRemote,
+    /// Copy on the remote and paste locally only
+    RemoteToLocal,
+}
+
+impl ClipboardDirection {
+    pub fn allows_local_to_remote(self) -> bool {
+        matches!(self, Self::Both | Self::LocalToRemote)
+    }
+
+    pub fn allows_remote_to_local(self) -> bool {
+        matches!(self, Self::Both | Self::RemoteToLocal)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+enum WireLoggingArg {
+    Disabled,
+    Metadata,
+    Payload,
+}
+
+impl From<WireLoggingArg> for WireLogging {
+    fn from(arg: WireLoggingArg) -> Self {
+        match arg {
+            WireLoggingArg::Disabled => WireLogging::Disabled,
+            WireLoggingArg::Metadata => WireLogging::Metadata,
+            WireLoggingArg::Payload => WireLogging::Payload,
+        }
+    }
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
 enum KeyboardType {
     IbmPcXt,
@@ -142,10 +321,44 @@ impl From<&Destination> for connector::ServerName {
 #[clap(author = "Devolutions", about = "Devolutions-IronRDP client")]
 #[clap(version, long_about = None)]
 struct Args {
+    /// A connection profile, in TOML or in the `.rdp` format of mstsc
+    ///
+    /// The command line arguments take precedence over the settings of the profile.
+    #[clap(short, long, value_parser)]
+    config: Option<PathBuf>,
+
     /// A file with IronRDP client logs
     #[clap(short, long, value_parser)]
     log_file: Option<String>,
 
+    /// Log the PDUs exchanged over the virtual channels, with the `ironrdp::wire` target
+    ///
+    /// The payloads may contain sensitive data (clipboard content, transferred files…), and are only logged
+    /// with `payload`.
+    #[clap(long, value_enum, default_value_t = WireLoggingArg::Disabled)]
+    wire_logging: WireLoggingArg,
+
+    /// A pcapng file into which the decrypted RDP traffic is captured, for analysis with Wireshark
+    ///
+    /// The capture contains the whole session, including the credentials and the clipboard content.
+    /// It can be stopped with the recording hotkey.
+    #[clap(long, value_parser)]
+    capture_file: Option<PathBuf>,
+
+    /// The directory in which the screenshots and the recordings taken with the hotkeys are saved
+    #[clap(long, value_parser, default_value = ".")]
+    output_dir: PathBuf,
+
+    /// The hotkey saving a screenshot of the session, as PNG
+    #[clap(long, value_parser, default_value = "Ctrl+Alt+P")]
+    screenshot_hotkey: Hotkey,
+
+    /// The hotkey starting or stopping the recording of the session
+    ///
+    /// The decrypted RDP traffic is recorded as pcapng, like with `--capture-file`.
+    #[clap(long, value_parser, default_value = "Ctrl+Alt+R")]
+    recording_hotkey: Hotkey,
+
     /// An address on which the client will connect.
     destination: Option<Destination>,
 
@@ -193,6 +406,18 @@ struct Args {
     #[clap(long)]
     color_depth: Option<u32>,
 
+    /// Do not advertise the RemoteFX codec to the server
+    #[clap(long)]
+    no_remotefx: bool,
+
+    /// The initial width of the desktop
+    #[clap(long)]
+    width: Option<u16>,
+
+    /// The initial height of the desktop
+    #[clap(long)]
+    height: Option<u16>,
+
     /// Ignore mouse pointer messages sent by the server. Increases performance when enabled, as the
     /// client could skip costly software rendering of the pointer with alpha blending
     #[clap(long)]
@@ -210,6 +435,83 @@ struct Args {
     #[clap(long)]
     autologon: bool,
 
+    /// Request the Restricted Admin mode, in which the credentials are not delegated to the server
+    ///
+    /// The server must support it, and the user must be an administrator of the server.
+    #[clap(long, conflicts_with = "no_credssp")]
+    restricted_admin: bool,
+
+    /// Authenticate with a smart card, whose PIN is given with `--password`
+    ///
+    /// Unless `--smartcard-certificate` is given, the smart card is accessed through the system.
+    /// Smart card authentication relies on Kerberos.
+    #[clap(long, conflicts_with = "no_credssp")]
+    smartcard: bool,
+
+    /// Emulate a smart card holding this DER-encoded X.509 certificate
+    #[clap(long, value_parser, requires_all = ["smartcard", "smartcard_private_key"])]
+    smartcard_certificate: Option<PathBuf>,
+
+    /// The DER-encoded RSA private key of the emulated smart card
+    #[clap(long, value_parser, requires = "smartcard_certificate")]
+    smartcard_private_key: Option<PathBuf>,
+
+    /// The reader name of the emulated smart card
+    #[clap(
+        long,
+        requires = "smartcard_certificate",
+        default_value = "IronRDP Smart Card Reader"
+    )]
+    smartcard_reader: String,
+
+    /// The key container name of the emulated smart card
+    #[clap(long, requires = "smartcard_certificate", default_value = "IronRDP")]
+    smartcard_container: String,
+
+    /// The cryptographic service provider (CSP) name of the emulated smart card
+    #[clap(
+        long,
+        requires = "smartcard_certificate",
+        default_value = "Microsoft Base Smart Card Crypto Provider"
+    )]
+    smartcard_csp: String,
+
+    /// Prefer Kerberos over NTLM for CredSSP authentication
+    ///
+    /// The KDC is located using DNS, unless `--kdc-proxy-url` is given.
+    #[clap(long, conflicts_with = "no_credssp")]
+    kerberos: bool,
+
+    /// A KDC proxy through which Kerberos messages are sent (implies `--kerberos`)
+    #[clap(long, value_parser, conflicts_with = "no_credssp")]
+    kdc_proxy_url: Option<url::Url>,
+
+    /// Only connect to a server whose certificate has this SHA-256 fingerprint
+    ///
+    /// The fingerprint is given as hexadecimal digits, optionally separated by colons.
+    /// The fingerprint of the server certificate is logged at the `info` level.
+    #[clap(long, value_parser, value_name = "SHA256")]
+    cert_fingerprint: Option<CertificateFingerprint>,
+
+    /// Verify the server certificate against the certificate store of the system
+    ///
+    /// Requires the client to be built with the `native-tls` feature, in which case the platform TLS stack (Schannel
+    /// on Windows, Secure Transport on macOS) applies the trust and revocation policy of the system.
+    #[clap(long)]
+    verify_server_certificate: bool,
+
+    /// Store the client licenses issued by the server in this directory, and present them on the next connections
+    ///
+    /// Without this option, a new license is requested on each connection.
+    #[clap(long, value_parser)]
+    license_cache_dir: Option<PathBuf>,
+
+    /// Reject PDUs with benign protocol violations (non-zero padding, unknown capability sets…)
+    ///
+    /// By default, such violations are tolerated and logged as warnings.
+    #[clap(long)]
+    strict_decoding: bool,
+
     /// Disable TLS + Graphical login (legacy authentication method)
     ///
     /// Disabling this in order to enforce usage of CredSSP (NLA) is recommended.
@@ -224,16 +526,75 @@ struct Args {
     no_credssp: bool,
 
     /// The clipboard type
-    #[clap(long, value_enum, value_parser, default_value_t = ClipboardType::Default)]
-    clipboard_type: ClipboardType,
+    ///
+    /// Defaults to `none` when the clipboard is disabled by the profile, and to `default` otherwise.
+    /// The `default` clipboard is `windows` on Windows, and `portable` on the other platforms.
+    #[clap(long, value_enum, value_parser)]
+    clipboard_type: Option<ClipboardType>,
+
+    /// The directions in which the clipboard content is shared
+    #[clap(long, value_enum, default_value_t = ClipboardDirection::Both)]
+    clipboard_direction: ClipboardDirection,
+
+    /// Do not share the clipboard, same as `--clipboard-type none`
+    #[clap(long, conflicts_with = "clipboard_type")]
+    no_clipboard: bool,
+
+    /// Do not play the sound of the remote session
+    #[clap(long)]
+    no_sound: bool,
+
+    /// Start in fullscreen mode, on the current monitor
+    ///
+    /// Fullscreen mode is toggled with Ctrl+Alt+Enter.
+    #[clap(long)]
+    fullscreen: bool,
+
+    /// Start in fullscreen mode, spanning the selected monitors
+    ///
+    /// Either `all`, or the comma-separated indices of the monitors, such as `0,2`.
+    /// The first selected monitor is the primary monitor of the remote session.
+    #[clap(long, value_parser)]
+    monitors: Option<MonitorSelection>,
+
+    /// Send the system shortcuts (Alt+Tab, the Windows key, the media keys…) to the server (Windows only)
+    ///
+    /// The keyboard is grabbed while the window has the focus, and the grab is toggled with `--grab-hotkey`.
+    #[clap(long)]
+    grab_keyboard: bool,
+
+    /// The hotkey toggling the keyboard grab, such as `Ctrl+Alt+Home`
+    #[clap(long, value_parser, requires = "grab_keyboard", default_value = "Ctrl+Alt+Home")]
+    grab_hotkey: Hotkey,
+
+    /// Share a local directory with the server, as `PATH[:NAME][:ro]`
+    ///
+    /// The drive is named after the directory unless NAME is given, and is read-only with the `:ro` suffix.
+    /// This option can be repeated to share several directories.
+    #[clap(long, value_parser, value_name = "PATH[:NAME][:ro]")]
+    share_drive: Vec<SharedDrive>,
+
+    /// Share a local printer with the server, the default printer unless LOCAL_PRINTER is given
+    ///
+    /// The documents are printed with `lpr`.
+    #[clap(long, value_name = "LOCAL_PRINTER", num_args = 0..=1, default_missing_value = "")]
+    share_printer: Option<String>,
 }
 
 impl Config {
     pub fn parse_args() -> anyhow::Result<Self> {
         let args = Args::parse();
 
+        let profile = if let Some(path) = &args.config {
+            Profile::load(path)?
+        } else {
+            Profile::default()
+        };
+
         let destination = if let Some(destination) = args.destination {
             destination
+        } else if let Some(destination) = profile.destination {
+            Destination::new(destination).context("invalid destination in profile")?
         } else {
             inquire::Text::new("Server address:")
                 .prompt()
@@ -241,22 +602,67 @@ impl Config {
                 .pipe(Destination::new)?
         };
 
-        let username = if let Some(username) = args.username {
-            username
+        let password_from_env = profile
+            .password_env
+            .map(|var| std::env::var(&var).with_context(|| format!("couldn’t read the password from ${var}")))
+            .transpose()?;
+
+        let credentials = if args.smartcard {
+            let pin = if let Some(pin) = args.password.or(password_from_env) {
+                pin
+            } else {
+                inquire::Password::new("PIN:")
+                    .without_confirmation()
+                    .prompt()
+                    .context("PIN prompt")?
+            };
+
+            let config = if let (Some(certificate), Some(private_key)) =
+                (args.smartcard_certificate, args.smartcard_private_key)
+            {
+                Some(Box::new(SmartCardIdentity {
+                    certificate: std::fs::read(&certificate)
+                        .with_context(|| format!("couldn’t read {}", certificate.display()))?,
+                    reader_name: args.smartcard_reader,
+                    container_name: args.smartcard_container,
+                    csp_name: args.smartcard_csp,
+                    private_key: std::fs::read(&private_key)
+                        .with_context(|| format!("couldn’t read {}", private_key.display()))?,
+                }))
+            } else {
+                None
+            };
+
+            Credentials::SmartCard { pin, config }
         } else {
-            inquire::Text::new("Username:").prompt().context("Username prompt")?
+            let username = if let Some(username) = args.username.or(profile.username) {
+                username
+            } else {
+                inquire::Text::new("Username:").prompt().context("Username prompt")?
+            };
+
+            let password = if let Some(password) = args.password.or(password_from_env) {
+                password
+            } else {
+                inquire::Password::new("Password:")
+                    .without_confirmation()
+                    .prompt()
+                    .context("Password prompt")?
+            };
+
+            Credentials::UsernamePassword { username, password }
         };
 
-        let password = if let Some(password) = args.password {
-            password
+        let color_depth = args.color_depth.or(profile.color_depth);
+        let remotefx = if args.no_remotefx {
+            Some(false)
         } else {
-            inquire::Password::new("Password:")
-                .without_confirmation()
-                .prompt()
-                .context("Password prompt")?
+            profile.remotefx
         };
 
-        let bitmap = if let Some(color_depth) = args.color_depth {
+        let bitmap = if color_depth.is_some() || remotefx.is_some() {
+            let color_depth = color_depth.unwrap_or(32);
+
             if color_depth != 16 && color_depth != 32 {
                 anyhow::bail!("Invalid color depth. Only 16 and 32 bit color depths are supported.");
             }
@@ -264,29 +670,80 @@ impl Config {
             Some(connector::BitmapConfig {
                 color_depth,
                 lossy_compression: true,
+                remotefx: remotefx.unwrap_or(true),
             })
         } else {
             None
         };
 
-        let clipboard_type = if args.clipboard_type == ClipboardType::Default {
+        let fullscreen = if let Some(monitors) = args.monitors {
+            Some(monitors)
+        } else if let Some(monitors) = profile.monitors {
+            Some(monitors.parse().context("invalid monitors in profile")?)
+        } else if args.fullscreen || profile.fullscreen == Some(true) {
+            Some(MonitorSelection::Current)
+        } else {
+            None
+        };
+
+        let clipboard_type = args
+            .clipboard_type
+            .unwrap_or(if args.no_clipboard || profile.clipboard == Some(false) {
+                ClipboardType::None
+            } else {
+                ClipboardType::Default
+            });
+
+        let clipboard_type = if clipboard_type == ClipboardType::Default {
             #[cfg(windows)]
             {
                 ClipboardType::Windows
             }
             #[cfg(not(windows))]
             {
-                ClipboardType::None
+                ClipboardType::Portable
             }
         } else {
-            args.clipboard_type
+            clipboard_type
+        };
+
+        let shared_drives = args
+            .share_drive
+            .into_iter()
+            .map(|drive| {
+                let path = std::fs::canonicalize(&drive.path)
+                    .with_context(|| format!("couldn’t share {}", drive.path.display()))?;
+                anyhow::ensure!(path.is_dir(), "couldn’t share {}: not a directory", path.display());
+                Ok(SharedDrive { path, ..drive })
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        let shared_printer = args.share_printer.map(|local_printer| SharedPrinter {
+            local_printer: Some(local_printer).filter(|local_printer| !local_printer.is_empty()),
+        });
+
+        if cfg!(not(any(target_os = "macos", target_os = "linux")))
+            && (!shared_drives.is_empty() || shared_printer.is_some())
+        {
+            anyhow::bail!("drive and printer sharing is only supported on macOS and Linux");
+        }
+
+        let client_name = whoami::fallible::hostname().unwrap_or_else(|_| "ironrdp".to_owned());
+
+        let kerberos_config = if args.kerberos || args.smartcard || args.kdc_proxy_url.is_some() {
+            Some(KerberosConfig {
+                kdc_proxy_url: args.kdc_proxy_url,
+                hostname: Some(client_name.clone()),
+            })
+        } else {
+            None
         };
 
         let connector = connector::Config {
-            credentials: Credentials::UsernamePassword { username, password },
-            domain: args.domain,
-            enable_tls: !args.no_tls,
-            enable_credssp: !args.no_credssp,
+            credentials,
+            domain: args.domain.or(profile.domain),
+            enable_tls: !args.no_tls && profile.tls.unwrap_or(true),
+            enable_credssp: !args.no_credssp && profile.credssp.unwrap_or(true),
             keyboard_type: KeyboardType::parse(args.keyboard_type),
             keyboard_subtype: args.keyboard_subtype,
             keyboard_layout: 0, // the server SHOULD use the default active input locale identifier
@@ -294,8 +751,8 @@ impl Config {
             ime_file_name: args.ime_file_name,
             dig_product_id: args.dig_product_id,
             desktop_size: connector::DesktopSize {
-                width: DEFAULT_WIDTH,
-                height: DEFAULT_HEIGHT,
+                width: args.width.or(profile.width).unwrap_or(DEFAULT_WIDTH),
+                height: args.height.or(profile.height).unwrap_or(DEFAULT_HEIGHT),
             },
             desktop_scale_factor: 0, // Default to 0 per FreeRDP
             bitmap,
@@ -304,7 +761,7 @@ impl Config {
                 .unwrap_or(0)
                 .pipe(u32::try_from)
                 .unwrap(),
-            client_name: whoami::fallible::hostname().unwrap_or_else(|_| "ironrdp".to_owned()),
+            client_name,
             // NOTE: hardcode this value like in freerdp
             // https://github.com/FreeRDP/FreeRDP/blob/4e24b966c86fdf494a782f0dfcfc43a057a2ea60/libfreerdp/core/settings.c#LL49C34-L49C70
             client_dir: "C:\\Windows\\System32\\mstscax.dll".to_owned(),
@@ -317,16 +774,45 @@ impl Config {
                 _ => MajorPlatformType::UNSPECIFIED,
             },
             no_server_pointer: args.no_server_pointer,
-            autologon: args.autologon,
+            autologon: args.autologon || profile.autologon.unwrap_or(false),
+            restricted_admin: args.restricted_admin,
+            auto_reconnect: None,
+            decode_mode: if args.strict_decoding {
+                DecodeMode::Strict
+            } else {
+                DecodeMode::Lenient
+            },
+            decode_limits: Default::default(),
+            monitors: Vec::new(),
+            persistent_bitmap_cache: Vec::new(),
             pointer_software_rendering: true,
             performance_flags: PerformanceFlags::default(),
         };
 
         Ok(Self {
             log_file: args.log_file,
+            wire_logging: args.wire_logging.into(),
+            capture_file: args.capture_file,
+            output_dir: args.output_dir,
+            screenshot_hotkey: args.screenshot_hotkey,
+            recording_hotkey: args.recording_hotkey,
             destination,
             connector,
             clipboard_type,
+            clipboard_direction: args.clipboard_direction,
+            sound: !args.no_sound && profile.sound.unwrap_or(true),
+            fullscreen,
+            shared_drives,
+            shared_printer,
+            keyboard_grab: args.grab_keyboard.then_some(args.grab_hotkey),
+            kerberos_config,
+            cert_fingerprint: args.cert_fingerprint,
+            certificate_verification: if args.verify_server_certificate {
+                CertificateVerification::System
+            } else {
+                CertificateVerification::Disabled
+            },
+            license_cache_dir: args.license_cache_dir,
         })
     }
 }
@@ -0,0 +1,138 @@
+//! Hotkeys handled by the client, such as the keyboard grab toggle
+
+use std::str::FromStr;
+
+use winit::keyboard::{KeyCode, ModifiersState};
+
+/// Key combination handled locally, and not sent to the server
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Hotkey {
+    pub modifiers: ModifiersState,
+    pub key: KeyCode,
+}
+
+impl Hotkey {
+    pub fn matches(&self, modifiers: ModifiersState, key: KeyCode) -> bool {
+        self.key == key && modifiers.contains(self.modifiers)
+    }
+}
+
+impl FromStr for Hotkey {
+    type Err = anyhow::Error;
+
+    /// Parses a key preceded by modifiers, such as `Ctrl+Alt+Home`
+    ///
+    /// The modifiers are `Ctrl`, `Alt` and `Shift`. The Windows key is not allowed, as it may be grabbed.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts: Vec<&str> = s.split('+').map(str::trim).collect();
+
+        let key = parts.pop().unwrap_or_default();
+        let key = parse_key(key).ok_or_else(|| anyhow::anyhow!("unknown key: {key}"))?;
+
+        let mut modifiers = ModifiersState::empty();
+
+        for modifier in parts {
+            modifiers |= match modifier.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => ModifiersState::CONTROL,
+                "alt" => ModifiersState::ALT,
+                "shift" => ModifiersState::SHIFT,
+                _ => anyhow::bail!("unknown modifier: {modifier}"),
+            };
+        }
+
+        anyhow::ensure!(!modifiers.is_empty(), "the hotkey requires at least one modifier");
+
+        Ok(Self { modifiers, key })
+    }
+}
+
+fn parse_key(key: &str) -> Option<KeyCode> {
+    const LETTERS: [KeyCode; 26] = [
+        KeyCode::KeyA,
+        KeyCode::KeyB,
+        KeyCode::KeyC,
+        KeyCode::KeyD,
+        KeyCode::KeyE,
+        KeyCode::KeyF,
+        KeyCode::KeyG,
+        KeyCode::KeyH,
+        KeyCode::KeyI,
+        KeyCode::KeyJ,
+        KeyCode::KeyK,
+        KeyCode::KeyL,
+        KeyCode::KeyM,
+        KeyCode::KeyN,
+        KeyCode::KeyO,
+        KeyCode::KeyP,
+        KeyCode::KeyQ,
+        KeyCode::KeyR,
+        KeyCode::KeyS,
+        KeyCode::KeyT,
+        KeyCode::KeyU,
+        KeyCode::KeyV,
+        KeyCode::KeyW,
+        KeyCode::KeyX,
+        KeyCode::KeyY,
+        KeyCode::KeyZ,
+    ];
+
+    const DIGITS: [KeyCode; 10] = [
+        KeyCode::Digit0,
+        KeyCode::Digit1,
+        KeyCode::Digit2,
+        KeyCode::Digit3,
+        KeyCode::Digit4,
+        KeyCode::Digit5,
+        KeyCode::Digit6,
+        KeyCode::Digit7,
+        KeyCode::Digit8,
+        KeyCode::Digit9,
+    ];
+
+    const FUNCTION_KEYS: [KeyCode; 12] = [
+        KeyCode::F1,
+        KeyCode::F2,
+        KeyCode::F3,
+        KeyCode::F4,
+        KeyCode::F5,
+        KeyCode::F6,
+        KeyCode::F7,
+        KeyCode::F8,
+        KeyCode::F9,
+        KeyCode::F10,
+        KeyCode::F11,
+        KeyCode::F12,
+    ];
+
+    let key = key.to_ascii_lowercase();
+
+    if let [c] = key.as_bytes() {
+        return match c {
+            b'a'..=b'z' => LETTERS.get(usize::from(c - b'a')).copied(),
+            b'0'..=b'9' => DIGITS.get(usize::from(c - b'0')).copied(),
+            _ => None,
+        };
+    }
+
+    if let Some(n) = key.strip_prefix('f').and_then(|n| n.parse::<usize>().ok()) {
+        return FUNCTION_KEYS.get(n.checked_sub(1)?).copied();
+    }
+
+    let key = match key.as_str() {
+        "home" => KeyCode::Home,
+        "end" => KeyCode::End,
+        "insert" => KeyCode::Insert,
+        "delete" => KeyCode::Delete,
+        "pageup" => KeyCode::PageUp,
+        "pagedown" => KeyCode::PageDown,
+        "enter" => KeyCode::Enter,
+        "space" => KeyCode::Space,
+        "backspace" => KeyCode::Backspace,
+        "escape" | "esc" => KeyCode::Escape,
+        "pause" => KeyCode::Pause,
+        "scrolllock" => KeyCode::ScrollLock,
+        _ => return None,
+    };
+
+    Some(key)
+}
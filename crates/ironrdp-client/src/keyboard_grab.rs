@@ -0,0 +1,162 @@
+//! Keyboard grab
+//!
+//! While the keyboard is grabbed, the system shortcuts (Alt+Tab, the Windows key, the media keys…) are sent to the
+//! remote session instead of being handled locally. The grab is toggled with a [`Hotkey`], and is only effective
+//! while the window has the focus.
+//!
+//! The grab is only supported on Windows, where a low-level keyboard hook intercepts the keys before the system
+//! handles them. Ctrl+Alt+Del and Win+L are never intercepted.
+//!
+//! [`Hotkey`]: crate::hotkey::Hotkey
+
+use winit::event_loop::EventLoopProxy;
+
+use crate::rdp::RdpOutputEvent;
+
+/// Intercepts the system shortcuts while it is active
+///
+/// The intercepted keys are sent to the event loop as [`RdpOutputEvent::GrabbedKey`].
+pub struct KeyboardGrab {
+    #[cfg(windows)]
+    hook: windows::Win32::UI::WindowsAndMessaging::HHOOK,
+}
+
+#[cfg(windows)]
+impl KeyboardGrab {
+    /// Installs the keyboard hook, inactive until [`KeyboardGrab::set_active`] is called
+    ///
+    /// Must be called from the thread running the event loop.
+    pub fn new(event_loop_proxy: EventLoopProxy<RdpOutputEvent>) -> anyhow::Result<Self> {
+        use windows::Win32::Foundation::HINSTANCE;
+        use windows::Win32::System::LibraryLoader::GetModuleHandleW;
+        use windows::Win32::UI::WindowsAndMessaging::{SetWindowsHookExW, WH_KEYBOARD_LL};
+
+        let mut proxy = hook::PROXY.lock().map_err(|_| anyhow::anyhow!("poisoned lock"))?;
+        anyhow::ensure!(proxy.is_none(), "the keyboard is already grabbed");
+
+        // SAFETY: low-level WinAPI call
+        let instance = unsafe { GetModuleHandleW(None)? };
+
+        // SAFETY: `keyboard_proc` is a valid hook procedure, which stays valid for the lifetime of the program.
+        let hook =
+            unsafe { SetWindowsHookExW(WH_KEYBOARD_LL, Some(hook::keyboard_proc), HINSTANCE::from(instance), 0)? };
+
+        *proxy = Some(event_loop_proxy);
+
+        Ok(Self { hook })
+    }
+
+    /// Starts or stops intercepting the system shortcuts
+    pub fn set_active(&self, active: bool) {
+        hook::ACTIVE.store(active, std::sync::atomic::Ordering::Relaxed);
+    }
+}
+
+#[cfg(windows)]
+impl Drop for KeyboardGrab {
+    fn drop(&mut self) {
+        use windows::Win32::UI::WindowsAndMessaging::UnhookWindowsHookEx;
+
+        self.set_active(false);
+
+        // SAFETY: `self.hook` was returned by `SetWindowsHookExW`, and is only unhooked here.
+        if let Err(error) = unsafe { UnhookWindowsHookEx(self.hook) } {
+            warn!(%error, "Failed to remove the keyboard hook");
+        }
+
+        if let Ok(mut proxy) = hook::PROXY.lock() {
+            *proxy = None;
+        }
+    }
+}
+
+#[cfg(not(windows))]
+impl KeyboardGrab {
+    pub fn new(event_loop_proxy: EventLoopProxy<RdpOutputEvent>) -> anyhow::Result<Self> {
+        let _ = event_loop_proxy;
+        anyhow::bail!("keyboard grab is only supported on Windows")
+    }
+
+    pub fn set_active(&self, active: bool) {
+        let _ = active;
+    }
+}
+
+#[cfg(windows)]
+mod hook {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Mutex;
+
+    use windows::Win32::Foundation::{LPARAM, LRESULT, WPARAM};
+    use windows::Win32::UI::Input::KeyboardAndMouse::{
+        GetAsyncKeyState, VIRTUAL_KEY, VK_APPS, VK_BROWSER_BACK, VK_CONTROL, VK_ESCAPE, VK_F4, VK_LAUNCH_APP2, VK_LWIN,
+        VK_RWIN, VK_SNAPSHOT, VK_TAB,
+    };
+    use windows::Win32::UI::WindowsAndMessaging::{
+        CallNextHookEx, HC_ACTION, HHOOK, KBDLLHOOKSTRUCT, LLKHF_ALTDOWN, LLKHF_EXTENDED, LLKHF_UP,
+    };
+    use winit::event_loop::EventLoopProxy;
+
+    use crate::rdp::RdpOutputEvent;
+
+    pub(super) static PROXY: Mutex<Option<EventLoopProxy<RdpOutputEvent>>> = Mutex::new(None);
+    pub(super) static ACTIVE: AtomicBool = AtomicBool::new(false);
+
+    /// Low-level keyboard hook procedure
+    ///
+    /// SAFETY: This function should only be used for the `WH_KEYBOARD_LL` hook.
+    pub(super) unsafe extern "system" fn keyboard_proc(code: i32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+        // `as` conversion is fine for constants
+        if code == HC_ACTION as i32 && ACTIVE.load(Ordering::Relaxed) {
+            // SAFETY: for `WH_KEYBOARD_LL` hooks, `lparam` points to a `KBDLLHOOKSTRUCT` when `code` is `HC_ACTION`.
+            let event = unsafe { &*(lparam.0 as *const KBDLLHOOKSTRUCT) };
+
+            if is_system_shortcut(event) && forward(event) {
+                // The key is not handled locally.
+                return LRESULT(1);
+            }
+        }
+
+        // SAFETY: the event is passed to the next hook, as required.
+        unsafe { CallNextHookEx(HHOOK::default(), code, wparam, lparam) }
+    }
+
+    fn is_system_shortcut(event: &KBDLLHOOKSTRUCT) -> bool {
+        let Ok(vk) = u16::try_from(event.vkCode).map(VIRTUAL_KEY) else {
+            return false;
+        };
+
+        let alt_down = event.flags.contains(LLKHF_ALTDOWN);
+
+        // SAFETY: `GetAsyncKeyState` is always safe to call.
+        let ctrl_down = unsafe { GetAsyncKeyState(i32::from(VK_CONTROL.0)) } < 0;
+
+        match vk {
+            VK_LWIN | VK_RWIN | VK_APPS | VK_SNAPSHOT => true,
+            VK_TAB | VK_F4 => alt_down,
+            VK_ESCAPE => alt_down || ctrl_down,
+            // Browser, volume, media and application launch keys.
+            VIRTUAL_KEY(code) => (VK_BROWSER_BACK.0..=VK_LAUNCH_APP2.0).contains(&code),
+        }
+    }
+
+    /// Returns `false` when the key couldn’t be forwarded, in which case it’s handled locally
+    fn forward(event: &KBDLLHOOKSTRUCT) -> bool {
+        let Ok(scancode) = u8::try_from(event.scanCode) else {
+            return false;
+        };
+
+        let scancode = ironrdp::input::Scancode::from_u8(event.flags.contains(LLKHF_EXTENDED), scancode);
+        let pressed = !event.flags.contains(LLKHF_UP);
+
+        let Ok(proxy) = PROXY.lock() else {
+            return false;
+        };
+
+        proxy.as_ref().is_some_and(|proxy| {
+            proxy
+                .send_event(RdpOutputEvent::GrabbedKey { scancode, pressed })
+                .is_ok()
+        })
+    }
+}
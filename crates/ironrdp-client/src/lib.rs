@@ -13,5 +13,11 @@ extern crate tracing;
 pub mod app;
 pub mod clipboard;
 pub mod config;
+pub mod hotkey;
+pub mod keyboard_grab;
+pub mod license_cache;
+pub mod monitors;
 pub mod network_client;
+pub mod profile;
 pub mod rdp;
+pub mod recording;
@@ -0,0 +1,66 @@
+//! Storage of the client licenses
+//!
+//! The licenses issued by the license servers are stored as files in a directory, one file per product and scope, so
+//! that they are presented again on the next connections instead of requesting new ones.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use ironrdp::connector::{custom_err, ConnectorResult, LicenseCache, LicenseKey};
+use sha2::{Digest as _, Sha256};
+
+#[derive(Debug)]
+pub struct FileLicenseCache {
+    dir: PathBuf,
+}
+
+impl FileLicenseCache {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// The files are named after a hash of the key, which contains arbitrary strings sent by the server
+    fn license_path(&self, key: &LicenseKey) -> PathBuf {
+        let mut hasher = Sha256::new();
+
+        for field in [&key.scope, &key.company_name, &key.product_id] {
+            hasher.update(field.as_bytes());
+            hasher.update([0]);
+        }
+
+        let name: String = hasher.finalize().iter().map(|byte| format!("{byte:02x}")).collect();
+
+        self.dir.join(format!("{name}.license"))
+    }
+}
+
+impl LicenseCache for FileLicenseCache {
+    fn get_license(&self, key: &LicenseKey) -> ConnectorResult<Option<Vec<u8>>> {
+        match fs::read(self.license_path(key)) {
+            Ok(license) => Ok(Some(license)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(custom_err!("read license", e)),
+        }
+    }
+
+    fn store_license(&self, key: &LicenseKey, license: &[u8]) -> ConnectorResult<()> {
+        fs::create_dir_all(&self.dir).map_err(|e| custom_err!("create license directory", e))?;
+        write_atomically(&self.license_path(key), license).map_err(|e| custom_err!("write license", e))
+    }
+
+    fn remove_license(&self, key: &LicenseKey) -> ConnectorResult<()> {
+        match fs::remove_file(self.license_path(key)) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(custom_err!("remove license", e)),
+        }
+    }
+}
+
+/// Writes into a temporary file renamed afterwards, so that an interrupted write never leaves a truncated license
+fn write_atomically(path: &Path, data: &[u8]) -> io::Result<()> {
+    let tmp_path = path.with_extension("tmp");
+    fs::write(&tmp_path, data)?;
+    fs::rename(&tmp_path, path)
+}
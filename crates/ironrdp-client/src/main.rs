@@ -4,7 +4,9 @@
 extern crate tracing;
 
 use anyhow::Context as _;
+use ironrdp::cliprdr::backend::CliprdrBackendFactory;
 use ironrdp_client::app::App;
+use ironrdp_client::clipboard::DirectionalBackendFactory;
 use ironrdp_client::config::{ClipboardType, Config};
 use ironrdp_client::rdp::{RdpClient, RdpInputEvent, RdpOutputEvent};
 use tokio::runtime;
@@ -14,28 +16,27 @@ fn main() -> anyhow::Result<()> {
     let mut config = Config::parse_args().context("CLI arguments parsing")?;
 
     setup_logging(config.log_file.as_deref()).context("unable to initialize logging")?;
+    ironrdp::svc::wire_log::set_wire_logging(config.wire_logging);
 
     debug!("Initialize App");
     let event_loop = EventLoop::<RdpOutputEvent>::with_user_event().build()?;
     let event_loop_proxy = event_loop.create_proxy();
     let (input_event_sender, input_event_receiver) = RdpInputEvent::create_channel();
-    let mut app = App::new(&event_loop, &input_event_sender).context("unable to initialize App")?;
+    let mut app = App::new(&event_loop, &input_event_sender, &config).context("unable to initialize App")?;
 
-    // TODO: get window size & scale factor from GUI/App
-    let window_size = (1024, 768);
+    // TODO: get scale factor from GUI/App
     config.connector.desktop_scale_factor = 0;
-    config.connector.desktop_size.width = u16::try_from(window_size.0).unwrap();
-    config.connector.desktop_size.height = u16::try_from(window_size.1).unwrap();
 
     let rt = runtime::Builder::new_multi_thread()
         .enable_all()
         .build()
         .context("unable to create tokio runtime")?;
 
-    // NOTE: we need to keep `win_clipboard` alive, otherwise it will be dropped before IronRDP
-    // starts and clipboard functionality will not be available.
+    // NOTE: we need to keep the clipboard (`win_clipboard` or `portable_clipboard`) alive, otherwise it will be
+    // dropped before IronRDP starts and clipboard functionality will not be available.
     #[cfg(windows)]
     let _win_clipboard;
+    let _portable_clipboard;
 
     let cliprdr_factory = match config.clipboard_type {
         ClipboardType::Stub => {
@@ -50,15 +51,42 @@ fn main() -> anyhow::Result<()> {
             use ironrdp_client::clipboard::ClientClipboardMessageProxy;
             use ironrdp_cliprdr_native::WinClipboard;
 
-            let cliprdr = WinClipboard::new(ClientClipboardMessageProxy::new(input_event_sender))?;
+            let cliprdr = WinClipboard::new(ClientClipboardMessageProxy::new(
+                input_event_sender,
+                config.clipboard_direction,
+            ))?;
 
             let factory = cliprdr.backend_factory();
             _win_clipboard = cliprdr;
             Some(factory)
         }
+        ClipboardType::Portable => {
+            use ironrdp_client::clipboard::ClientClipboardMessageProxy;
+            use ironrdp_cliprdr_native::PortableClipboard;
+
+            match PortableClipboard::new(ClientClipboardMessageProxy::new(
+                input_event_sender,
+                config.clipboard_direction,
+            )) {
+                Ok(cliprdr) => {
+                    let factory = cliprdr.backend_factory();
+                    _portable_clipboard = cliprdr;
+                    Some(factory)
+                }
+                Err(error) => {
+                    // E.g.: no display server to hold the clipboard.
+                    warn!(%error, "Clipboard is not available");
+                    None
+                }
+            }
+        }
         _ => None,
     };
 
+    let cliprdr_factory = cliprdr_factory.map(|factory| -> Box<dyn CliprdrBackendFactory + Send> {
+        Box::new(DirectionalBackendFactory::new(factory, config.clipboard_direction))
+    });
+
     let client = RdpClient {
         config,
         event_loop_proxy,
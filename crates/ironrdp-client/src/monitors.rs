@@ -0,0 +1,249 @@
+//! Fullscreen mode, optionally spanning several physical monitors
+//!
+//! The selected monitors are advertised to the server as the monitor layout of the session (in the GCC
+//! monitor data at connection time, and over the Display Control Virtual Channel afterwards), so that the
+//! remote desktop is laid out the same way as the local one.
+
+use std::str::FromStr;
+
+use anyhow::Context as _;
+use ironrdp::connector::{self, MonitorConfig};
+use ironrdp::core::EncodeResult;
+use ironrdp::displaycontrol::pdu::{DeviceScaleFactor, MonitorLayoutEntry};
+use ironrdp::pdu::gcc::{self, ExtendedMonitorInfo, MonitorFlags, MonitorOrientation};
+use winit::dpi::{PhysicalPosition, PhysicalSize};
+use winit::monitor::MonitorHandle;
+
+/// Monitors covered by the fullscreen window
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MonitorSelection {
+    /// The monitor on which the window is displayed, or the primary monitor at startup
+    Current,
+    /// All the monitors
+    All,
+    /// The monitors at the given indices, in the order in which they are enumerated by the system
+    Indices(Vec<usize>),
+}
+
+impl FromStr for MonitorSelection {
+    type Err = anyhow::Error;
+
+    /// Parses `current`, `all`, or a comma-separated list of indices such as `0,2`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "current" => Ok(Self::Current),
+            "all" => Ok(Self::All),
+            indices => {
+                let indices = indices
+                    .split(',')
+                    .map(|index| index.trim().parse::<usize>())
+                    .collect::<Result<Vec<_>, _>>()
+                    .with_context(|| format!("invalid monitor selection `{s}`"))?;
+
+                anyhow::ensure!(!indices.is_empty(), "empty monitor selection");
+
+                Ok(Self::Indices(indices))
+            }
+        }
+    }
+}
+
+impl MonitorSelection {
+    /// Returns the selected monitors, among the `available` ones
+    ///
+    /// The primary monitor of the session is the first returned one: the primary monitor of the system when all
+    /// the monitors are selected, and the first listed one otherwise.
+    pub fn select(
+        &self,
+        available: &[MonitorHandle],
+        current: Option<MonitorHandle>,
+        primary: Option<MonitorHandle>,
+    ) -> anyhow::Result<Vec<MonitorHandle>> {
+        let selected = match self {
+            Self::Current => current
+                .or(primary)
+                .or_else(|| available.first().cloned())
+                .into_iter()
+                .collect(),
+            Self::All => {
+                let mut selected = available.to_vec();
+
+                if let Some(position) = selected.iter().position(|monitor| Some(monitor) == primary.as_ref()) {
+                    selected[..=position].rotate_right(1);
+                }
+
+                selected
+            }
+            Self::Indices(indices) => {
+                let mut selected = Vec::with_capacity(indices.len());
+
+                for &index in indices {
+                    let monitor = available.get(index).with_context(|| {
+                        format!("no monitor at index {index}, only {} monitor(s) found", available.len())
+                    })?;
+
+                    if !selected.contains(monitor) {
+                        selected.push(monitor.clone());
+                    }
+                }
+
+                selected
+            }
+        };
+
+        anyhow::ensure!(!selected.is_empty(), "no monitor found");
+
+        Ok(selected)
+    }
+}
+
+/// Geometry of a physical monitor, in physical pixels
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MonitorGeometry {
+    pub position: PhysicalPosition<i32>,
+    pub size: PhysicalSize<u32>,
+    pub scale_factor: f64,
+}
+
+impl From<&MonitorHandle> for MonitorGeometry {
+    fn from(monitor: &MonitorHandle) -> Self {
+        Self {
+            position: monitor.position(),
+            size: monitor.size(),
+            scale_factor: monitor.scale_factor(),
+        }
+    }
+}
+
+/// Layout of the remote desktop over the selected monitors
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MonitorLayout {
+    /// Position of the top-left corner of the remote desktop on the local screens
+    pub position: PhysicalPosition<i32>,
+    /// Size of the bounding box of the monitors
+    pub desktop_size: connector::DesktopSize,
+    /// The monitors, relatively to the primary one, which is at (0, 0)
+    pub monitors: Vec<MonitorConfig>,
+}
+
+impl MonitorLayout {
+    /// Lays out the remote desktop over `monitors`
+    ///
+    /// The first monitor is the primary monitor of the session. Each monitor is advertised with its own scale
+    /// factor, so that the server renders at the DPI of the monitor.
+    pub fn new(monitors: &[MonitorGeometry]) -> anyhow::Result<Self> {
+        let primary = monitors.first().context("no monitor selected")?;
+
+        let left = monitors
+            .iter()
+            .map(|monitor| monitor.position.x)
+            .min()
+            .unwrap_or_default();
+        let top = monitors
+            .iter()
+            .map(|monitor| monitor.position.y)
+            .min()
+            .unwrap_or_default();
+        let right = monitors.iter().map(right_edge).max().unwrap_or_default();
+        let bottom = monitors.iter().map(bottom_edge).max().unwrap_or_default();
+
+        let desktop_size = connector::DesktopSize {
+            width: u16::try_from(right - left).context("desktop too wide")?,
+            height: u16::try_from(bottom - top).context("desktop too high")?,
+        };
+
+        let monitors = monitors
+            .iter()
+            .enumerate()
+            .map(|(index, monitor)| {
+                let left = monitor.position.x - primary.position.x;
+                let top = monitor.position.y - primary.position.y;
+
+                MonitorConfig {
+                    monitor: gcc::Monitor {
+                        left,
+                        top,
+                        // The right and bottom edges are inclusive.
+                        right: left + monitor.size.width as i32 - 1,
+                        bottom: top + monitor.size.height as i32 - 1,
+                        flags: if index == 0 {
+                            MonitorFlags::PRIMARY
+                        } else {
+                            MonitorFlags::empty()
+                        },
+                    },
+                    attributes: ExtendedMonitorInfo::from_dpi(
+                        0,
+                        0,
+                        MonitorOrientation::Landscape,
+                        (monitor.scale_factor * f64::from(ExtendedMonitorInfo::DEFAULT_DPI)).round() as u32,
+                    ),
+                }
+            })
+            .collect();
+
+        Ok(Self {
+            position: PhysicalPosition::new(left, top),
+            desktop_size,
+            monitors,
+        })
+    }
+
+    pub fn size(&self) -> PhysicalSize<u32> {
+        PhysicalSize::new(u32::from(self.desktop_size.width), u32::from(self.desktop_size.height))
+    }
+
+    /// Desktop scale factor of the primary monitor, in percent
+    pub fn desktop_scale_factor(&self) -> u32 {
+        self.monitors
+            .first()
+            .map(|monitor| monitor.attributes.desktop_scale_factor)
+            .unwrap_or(100)
+    }
+
+    /// Applies the layout to the configuration used for the next connections
+    pub fn apply(&self, config: &mut connector::Config) {
+        config.desktop_size = self.desktop_size;
+        config.desktop_scale_factor = self.desktop_scale_factor();
+        config.monitors = self.monitors.clone();
+    }
+
+    /// Returns the layout as sent over the Display Control Virtual Channel
+    pub fn to_display_control(&self) -> EncodeResult<Vec<MonitorLayoutEntry>> {
+        self.monitors
+            .iter()
+            .map(|monitor| {
+                let width = monitor.monitor.right.abs_diff(monitor.monitor.left) + 1;
+                let height = monitor.monitor.bottom.abs_diff(monitor.monitor.top) + 1;
+                let (width, height) = MonitorLayoutEntry::adjust_display_size(width, height);
+
+                let entry = if monitor.monitor.flags.contains(MonitorFlags::PRIMARY) {
+                    MonitorLayoutEntry::new_primary(width, height)?
+                } else {
+                    MonitorLayoutEntry::new_secondary(width, height)?
+                };
+
+                let device_scale_factor = match monitor.attributes.device_scale_factor {
+                    180 => DeviceScaleFactor::Scale180Percent,
+                    140 => DeviceScaleFactor::Scale140Percent,
+                    _ => DeviceScaleFactor::Scale100Percent,
+                };
+
+                let entry = entry
+                    .with_position(monitor.monitor.left, monitor.monitor.top)?
+                    .with_desktop_scale_factor(monitor.attributes.desktop_scale_factor)?
+                    .with_device_scale_factor(device_scale_factor);
+
+                Ok(entry)
+            })
+            .collect()
+    }
+}
+
+fn right_edge(monitor: &MonitorGeometry) -> i32 {
+    monitor.position.x + monitor.size.width as i32
+}
+
+fn bottom_edge(monitor: &MonitorGeometry) -> i32 {
+    monitor.position.y + monitor.size.height as i32
+}
@@ -0,0 +1,202 @@
+//! Connection profiles
+//!
+//! A profile holds the settings of a connection, so that they don’t need to be repeated on the command line.
+//! Profiles are written in TOML:
+//!
+//! ```toml
+//! destination = "rdp.example.com:3389"
+//! username = "alice"
+//! domain = "EXAMPLE"
+//! password-env = "RDP_PASSWORD"
+//! width = 1920
+//! height = 1080
+//! color-depth = 32
+//! remotefx = false
+//! clipboard = true
+//! sound = false
+//! monitors = "0,1"
+//! ```
+//!
+//! For migrating from mstsc, the `.rdp` files are also supported, although only a subset of their settings
+//! is understood (see [`Profile::from_rdp_file`]).
+
+use std::path::Path;
+
+use anyhow::Context as _;
+use serde::Deserialize;
+
+/// Settings of a connection, all of them optional
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Profile {
+    /// Address of the server, with an optional port
+    pub destination: Option<String>,
+    pub username: Option<String>,
+    pub domain: Option<String>,
+    /// Name of the environment variable holding the password
+    ///
+    /// The password itself is never read from a profile.
+    pub password_env: Option<String>,
+    /// Initial width of the desktop
+    pub width: Option<u16>,
+    /// Initial height of the desktop
+    pub height: Option<u16>,
+    pub color_depth: Option<u32>,
+    /// Advertise the RemoteFX codec to the server
+    pub remotefx: Option<bool>,
+    /// Redirect the clipboard
+    pub clipboard: Option<bool>,
+    /// Play the sound of the remote session
+    pub sound: Option<bool>,
+    /// Start in fullscreen mode, on the current monitor
+    pub fullscreen: Option<bool>,
+    /// Start in fullscreen mode, spanning the selected monitors (`all`, or indices such as `0,1`)
+    pub monitors: Option<String>,
+    /// Enable TLS + Graphical login (legacy authentication method)
+    pub tls: Option<bool>,
+    /// Enable TLS + Network Level Authentication (NLA) using CredSSP
+    pub credssp: Option<bool>,
+    pub autologon: Option<bool>,
+}
+
+impl Profile {
+    /// Loads a profile from a file
+    ///
+    /// Files with the `.rdp` extension are parsed with [`Profile::from_rdp_file`], other files as TOML.
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let content = std::fs::read(path).with_context(|| format!("couldn’t read {}", path.display()))?;
+
+        let is_rdp_file = path
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("rdp"));
+
+        if is_rdp_file {
+            let content = decode_rdp_file(&content).with_context(|| format!("invalid .rdp file {}", path.display()))?;
+            Self::from_rdp_file(&content).with_context(|| format!("invalid .rdp file {}", path.display()))
+        } else {
+            let content = String::from_utf8(content).with_context(|| format!("invalid profile {}", path.display()))?;
+            toml::from_str(&content).with_context(|| format!("invalid profile {}", path.display()))
+        }
+    }
+
+    /// Parses the content of a `.rdp` file, as written by mstsc
+    ///
+    /// The following settings are supported, the other ones are ignored:
+    ///
+    /// - `full address` and `server port`
+    /// - `username` (optionally as `DOMAIN\username`) and `domain`
+    /// - `desktopwidth` and `desktopheight`
+    /// - `session bpp`
+    /// - `redirectclipboard`
+    /// - `audiomode` (sound is only played when set to 0, “play on this computer”)
+    /// - `enablecredsspsupport`
+    /// - `screen mode id` (fullscreen when set to 2), `use multimon` and `selectedmonitors` (interpreted as
+    ///   the indices of the monitors)
+    pub fn from_rdp_file(content: &str) -> anyhow::Result<Self> {
+        let mut profile = Self::default();
+        let mut server_port = None;
+        let mut use_multimon = false;
+        let mut selected_monitors = None;
+
+        for (line_number, line) in (1..).zip(content.lines()) {
+            let line = line.trim();
+
+            if line.is_empty() {
+                continue;
+            }
+
+            let mut parts = line.splitn(3, ':');
+
+            let (Some(key), Some(ty), Some(value)) = (parts.next(), parts.next(), parts.next()) else {
+                anyhow::bail!("line {line_number}: expected `name:type:value`");
+            };
+
+            let key = key.trim().to_ascii_lowercase();
+
+            let integer = || -> anyhow::Result<i64> {
+                anyhow::ensure!(ty == "i", "line {line_number}: `{key}` is not an integer setting");
+                value
+                    .trim()
+                    .parse()
+                    .with_context(|| format!("line {line_number}: invalid integer for `{key}`"))
+            };
+
+            let string = || -> anyhow::Result<String> {
+                anyhow::ensure!(ty == "s", "line {line_number}: `{key}` is not a string setting");
+                Ok(value.trim().to_owned())
+            };
+
+            match key.as_str() {
+                "full address" => profile.destination = Some(string()?).filter(|address| !address.is_empty()),
+                "server port" => server_port = Some(integer()?),
+                "username" => {
+                    let username = string()?;
+
+                    if let Some((domain, username)) = username.split_once('\\') {
+                        profile.domain = Some(domain.to_owned());
+                        profile.username = Some(username.to_owned());
+                    } else if !username.is_empty() {
+                        profile.username = Some(username);
+                    }
+                }
+                "domain" => {
+                    if let Some(domain) = Some(string()?).filter(|domain| !domain.is_empty()) {
+                        profile.domain = Some(domain);
+                    }
+                }
+                "desktopwidth" => profile.width = Some(u16::try_from(integer()?).context("invalid desktop width")?),
+                "desktopheight" => profile.height = Some(u16::try_from(integer()?).context("invalid desktop height")?),
+                "session bpp" => {
+                    profile.color_depth = Some(u32::try_from(integer()?).context("invalid color depth")?);
+                }
+                "redirectclipboard" => profile.clipboard = Some(integer()? != 0),
+                "audiomode" => profile.sound = Some(integer()? == 0),
+                "enablecredsspsupport" => profile.credssp = Some(integer()? != 0),
+                "screen mode id" => profile.fullscreen = Some(integer()? == 2),
+                "use multimon" => use_multimon = integer()? != 0,
+                "selectedmonitors" => selected_monitors = Some(string()?).filter(|monitors| !monitors.is_empty()),
+                _ => {}
+            }
+        }
+
+        // The port is only applied when the address doesn’t already include one.
+        if let (Some(destination), Some(port)) = (&mut profile.destination, server_port) {
+            let has_port = destination
+                .rsplit_once(':')
+                .is_some_and(|(host, _)| !host.contains(':') || host.ends_with(']'));
+
+            if !has_port {
+                *destination = if destination.contains(':') {
+                    // Bare IPv6 address
+                    format!("[{destination}]:{port}")
+                } else {
+                    format!("{destination}:{port}")
+                };
+            }
+        }
+
+        // Without a selection, mstsc spans all the monitors.
+        if use_multimon && profile.fullscreen != Some(false) {
+            profile.monitors = Some(selected_monitors.unwrap_or_else(|| "all".to_owned()));
+        }
+
+        Ok(profile)
+    }
+}
+
+/// mstsc writes `.rdp` files in UTF-16LE, with a byte order mark
+fn decode_rdp_file(content: &[u8]) -> anyhow::Result<String> {
+    if let Some(content) = content.strip_prefix(&[0xFF, 0xFE]) {
+        anyhow::ensure!(content.len() % 2 == 0, "truncated UTF-16 content");
+
+        let units: Vec<u16> = content
+            .chunks_exact(2)
+            .map(|unit| u16::from_le_bytes([unit[0], unit[1]]))
+            .collect();
+
+        String::from_utf16(&units).context("invalid UTF-16 content")
+    } else {
+        let content = content.strip_prefix(&[0xEF, 0xBB, 0xBF]).unwrap_or(content);
+        String::from_utf8(content.to_vec()).context("invalid UTF-8 content")
+    }
+}
@@ -1,12 +1,19 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
 use ironrdp::cliprdr::backend::{ClipboardMessage, CliprdrBackendFactory};
 use ironrdp::connector::connection_activation::ConnectionActivationState;
 use ironrdp::connector::{ConnectionResult, ConnectorResult};
 use ironrdp::displaycontrol::client::DisplayControlClient;
 use ironrdp::displaycontrol::pdu::MonitorLayoutEntry;
 use ironrdp::graphics::image_processing::PixelFormat;
+use ironrdp::input::Scancode;
 use ironrdp::pdu::input::fast_path::FastPathInputEvent;
 use ironrdp::session::image::DecodedImage;
-use ironrdp::session::{fast_path, ActiveStage, ActiveStageOutput, GracefulDisconnectReason, SessionResult};
+use ironrdp::session::{
+    fast_path, ActiveStage, ActiveStageOutput, GracefulDisconnectReason, SessionError, SessionErrorExt as _,
+    SessionResult,
+};
 use ironrdp::{cliprdr, connector, rdpdr, rdpsnd, session};
 use ironrdp_core::WriteBuf;
 use ironrdp_rdpsnd_native::cpal;
@@ -17,7 +24,10 @@ use tokio::net::TcpStream;
 use tokio::sync::mpsc;
 use winit::event_loop::EventLoopProxy;
 
-use crate::config::Config;
+use crate::config::{CertificateFingerprint, Config, SharedDrive};
+use crate::license_cache::FileLicenseCache;
+use crate::monitors::MonitorLayout;
+use crate::recording;
 
 #[derive(Debug)]
 pub enum RdpOutputEvent {
@@ -27,6 +37,7 @@ pub enum RdpOutputEvent {
     PointerHidden,
     PointerPosition { x: u16, y: u16 },
     Terminated(SessionResult<GracefulDisconnectReason>),
+    GrabbedKey { scancode: Scancode, pressed: bool },
 }
 
 #[derive(Debug)]
@@ -38,9 +49,15 @@ pub enum RdpInputEvent {
         /// The physical size of the display in millimeters (width, height).
         physical_size: Option<(u32, u32)>,
     },
+    /// The window spans several monitors, laid out as given
+    MonitorLayout(MonitorLayout),
     FastPath(SmallVec<[FastPathInputEvent; 2]>),
     Close,
     Clipboard(ClipboardMessage),
+    /// Saves a screenshot of the session
+    Screenshot,
+    /// Starts or stops the recording of the session
+    ToggleRecording,
 }
 
 impl RdpInputEvent {
@@ -58,6 +75,21 @@ pub struct RdpClient {
 
 impl RdpClient {
     pub async fn run(mut self) {
+        if self.config.fullscreen.is_some() {
+            // The monitors are only known once the window is created.
+            loop {
+                match self.input_event_receiver.recv().await {
+                    Some(RdpInputEvent::MonitorLayout(layout)) => {
+                        debug!(?layout, "Initial monitor layout");
+                        layout.apply(&mut self.config.connector);
+                        break;
+                    }
+                    Some(RdpInputEvent::Close) | None => return,
+                    Some(_) => {}
+                }
+            }
+        }
+
         loop {
             let (connection_result, framed) = match connect(&self.config, self.cliprdr_factory.as_deref()).await {
                 Ok(result) => result,
@@ -72,12 +104,18 @@ impl RdpClient {
                 connection_result,
                 &self.event_loop_proxy,
                 &mut self.input_event_receiver,
+                &mut self.config.capture_file,
+                &self.config.output_dir,
             )
             .await
             {
                 Ok(RdpControlFlow::ReconnectWithNewSize { width, height }) => {
                     self.config.connector.desktop_size.width = width;
                     self.config.connector.desktop_size.height = height;
+                    self.config.connector.monitors.clear();
+                }
+                Ok(RdpControlFlow::ReconnectWithNewLayout(layout)) => {
+                    layout.apply(&mut self.config.connector);
                 }
                 Ok(RdpControlFlow::TerminatedGracefully(reason)) => {
                     let _ = self.event_loop_proxy.send_event(RdpOutputEvent::Terminated(Ok(reason)));
@@ -94,11 +132,71 @@ impl RdpClient {
 
 enum RdpControlFlow {
     ReconnectWithNewSize { width: u16, height: u16 },
+    ReconnectWithNewLayout(MonitorLayout),
     TerminatedGracefully(GracefulDisconnectReason),
 }
 
 type UpgradedFramed = ironrdp_tokio::TokioFramed<ironrdp_tls::TlsStream<TcpStream>>;
 
+const SMARTCARD_DEVICE_ID: u32 = 0;
+const PRINTER_DEVICE_ID: u32 = 1;
+const FIRST_DRIVE_DEVICE_ID: u32 = 2;
+
+fn build_rdpdr(config: &Config) -> rdpdr::Rdpdr {
+    let drives: Vec<(u32, &SharedDrive)> = (FIRST_DRIVE_DEVICE_ID..).zip(&config.shared_drives).collect();
+
+    let rdpdr = rdpdr::Rdpdr::new(build_rdpdr_backend(config, &drives), "IronRDP".to_owned())
+        .with_smartcard(SMARTCARD_DEVICE_ID);
+
+    let rdpdr = if drives.is_empty() {
+        rdpdr
+    } else {
+        rdpdr.with_drives(Some(
+            drives
+                .iter()
+                .map(|(device_id, drive)| (*device_id, drive.name.clone()))
+                .collect(),
+        ))
+    };
+
+    if let Some(printer) = &config.shared_printer {
+        rdpdr.with_printer(PRINTER_DEVICE_ID, printer.name().to_owned())
+    } else {
+        rdpdr
+    }
+}
+
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+fn build_rdpdr_backend(config: &Config, drives: &[(u32, &SharedDrive)]) -> Box<dyn rdpdr::RdpdrBackend> {
+    use ironrdp_rdpdr_native::backend::NixRdpdrBackend;
+
+    if drives.is_empty() && config.shared_printer.is_none() {
+        return Box::new(NoopRdpdrBackend {});
+    }
+
+    let mut backend = NixRdpdrBackend::default();
+
+    for (device_id, drive) in drives {
+        backend = backend.with_drive(*device_id, drive.path.to_string_lossy().into_owned(), drive.read_only);
+    }
+
+    if let Some(local_printer) = config
+        .shared_printer
+        .as_ref()
+        .and_then(|printer| printer.local_printer.clone())
+    {
+        backend = backend.with_local_printer(local_printer);
+    }
+
+    Box::new(backend)
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+fn build_rdpdr_backend(_: &Config, _: &[(u32, &SharedDrive)]) -> Box<dyn rdpdr::RdpdrBackend> {
+    // Sharing is rejected when parsing the configuration on the other platforms.
+    Box::new(NoopRdpdrBackend {})
+}
+
 async fn connect(
     config: &Config,
     cliprdr_factory: Option<&(dyn CliprdrBackendFactory + Send)>,
@@ -120,8 +218,11 @@ async fn connect(
         .with_static_channel(
             ironrdp::dvc::DrdynvcClient::new().with_dynamic_channel(DisplayControlClient::new(|_| Ok(Vec::new()))),
         )
-        .with_static_channel(rdpsnd::client::Rdpsnd::new(Box::new(cpal::RdpsndBackend::new())))
-        .with_static_channel(rdpdr::Rdpdr::new(Box::new(NoopRdpdrBackend {}), "IronRDP".to_owned()).with_smartcard(0));
+        .with_static_channel(build_rdpdr(config));
+
+    if config.sound {
+        connector.attach_static_channel(rdpsnd::client::Rdpsnd::new(Box::new(cpal::RdpsndBackend::new())));
+    }
 
     if let Some(builder) = cliprdr_factory {
         let backend = builder.build_cliprdr_backend();
@@ -131,6 +232,10 @@ async fn connect(
         connector.attach_static_channel(cliprdr);
     }
 
+    if let Some(dir) = &config.license_cache_dir {
+        connector.attach_license_cache(Arc::new(FileLicenseCache::new(dir.clone())));
+    }
+
     let should_upgrade = ironrdp_tokio::connect_begin(&mut framed, &mut connector).await?;
 
     debug!("TLS upgrade");
@@ -138,14 +243,47 @@ async fn connect(
     // Ensure there is no leftover
     let initial_stream = framed.into_inner_no_leftover();
 
-    let (upgraded_stream, server_public_key) = ironrdp_tls::upgrade(initial_stream, config.destination.name())
-        .await
-        .map_err(|e| connector::custom_err!("TLS upgrade", e))?;
+    let (upgraded_stream, server_public_key) = ironrdp_tls::upgrade_with(
+        initial_stream,
+        config.destination.name(),
+        config.certificate_verification,
+    )
+    .await
+    .map_err(|e| connector::custom_err!("TLS upgrade", e))?;
+
+    let certificate =
+        ironrdp_tls::peer_certificate(&upgraded_stream).map_err(|e| connector::custom_err!("TLS upgrade", e))?;
+    let fingerprint = CertificateFingerprint::of(&certificate);
+
+    info!(%fingerprint, "Server certificate");
+
+    if let Some(expected) = config.cert_fingerprint {
+        if fingerprint != expected {
+            return Err(connector::reason_err!(
+                "TLS upgrade",
+                "the server certificate fingerprint is {fingerprint}, expected {expected}"
+            ));
+        }
+    }
+
+    connector.attach_tls_info(connector::TlsInfo {
+        protocol_version: ironrdp_tls::protocol_version(&upgraded_stream),
+        cipher_suite: ironrdp_tls::cipher_suite(&upgraded_stream),
+        server_certificate_chain: ironrdp_tls::peer_certificate_chain(&upgraded_stream)
+            .map_err(|e| connector::custom_err!("TLS upgrade", e))?,
+    });
 
     let upgraded = ironrdp_tokio::mark_as_upgraded(should_upgrade, &mut connector);
 
     let mut upgraded_framed = ironrdp_tokio::TokioFramed::new(upgraded_stream);
 
+    if let Some(capture_file) = &config.capture_file {
+        let capture =
+            recording::open_capture(capture_file).map_err(|e| connector::custom_err!("open capture file", e))?;
+
+        upgraded_framed.set_capture(Box::new(capture));
+    }
+
     let mut network_client = crate::network_client::ReqwestNetworkClient::new();
     let connection_result = ironrdp_tokio::connect_finalize(
         upgraded,
@@ -154,12 +292,27 @@ async fn connect(
         (&config.destination).into(),
         server_public_key,
         Some(&mut network_client),
-        None,
+        config.kerberos_config.clone(),
     )
     .await?;
 
     debug!(?connection_result);
 
+    let security_info = &connection_result.security_info;
+    let tls_info = security_info.tls.as_ref();
+
+    info!(
+        security_protocol = ?security_info.security_protocol,
+        nla = security_info.nla,
+        tls_version = tls_info.and_then(|tls| tls.protocol_version.as_deref()),
+        cipher_suite = tls_info.and_then(|tls| tls.cipher_suite.as_deref()),
+        "Connection secured"
+    );
+
+    if let Some(capture) = upgraded_framed.capture_mut() {
+        recording::register_channels(capture, &recording::channel_names(&connection_result));
+    }
+
     Ok((connection_result, upgraded_framed))
 }
 
@@ -168,6 +321,8 @@ async fn active_session(
     connection_result: ConnectionResult,
     event_loop_proxy: &EventLoopProxy<RdpOutputEvent>,
     input_event_receiver: &mut mpsc::UnboundedReceiver<RdpInputEvent>,
+    capture_file: &mut Option<PathBuf>,
+    output_dir: &Path,
 ) -> SessionResult<RdpControlFlow> {
     let mut image = DecodedImage::new(
         PixelFormat::RgbA32,
@@ -175,6 +330,8 @@ async fn active_session(
         connection_result.desktop_size.height,
     );
 
+    let channel_names = recording::channel_names(&connection_result);
+
     let mut active_stage = ActiveStage::new(connection_result);
 
     let disconnect_reason = 'outer: loop {
@@ -201,6 +358,16 @@ async fn active_session(
                             return Ok(RdpControlFlow::ReconnectWithNewSize { width: width.try_into().unwrap(), height: height.try_into().unwrap() })
                         }
                     },
+                    RdpInputEvent::MonitorLayout(layout) => {
+                        trace!(?layout, "Monitor layout event");
+                        let monitors = layout.to_display_control().map_err(SessionError::encode)?;
+                        if let Some(response_frame) = active_stage.encode_monitor_layout(&monitors) {
+                            vec![ActiveStageOutput::ResponseFrame(response_frame?)]
+                        } else {
+                            debug!("Reconnecting with new monitor layout");
+                            return Ok(RdpControlFlow::ReconnectWithNewLayout(layout))
+                        }
+                    },
                     RdpInputEvent::FastPath(events) => {
                         trace!(?events);
                         active_stage.process_fastpath_input(&mut image, &events)?
@@ -208,6 +375,40 @@ async fn active_session(
                     RdpInputEvent::Close => {
                         active_stage.graceful_shutdown()?
                     }
+                    RdpInputEvent::Screenshot => {
+                        let path = recording::output_path(output_dir, "png");
+                        let (width, height, data) = (image.width(), image.height(), image.data().to_vec());
+
+                        tokio::task::spawn_blocking(move || {
+                            match recording::save_screenshot(&path, width, height, &data) {
+                                Ok(()) => info!(path = %path.display(), "Screenshot saved"),
+                                Err(error) => error!("Failed to save the screenshot: {error:#}"),
+                            }
+                        });
+
+                        Vec::new()
+                    }
+                    RdpInputEvent::ToggleRecording => {
+                        if framed.take_capture().is_some() {
+                            info!("Recording stopped");
+                            *capture_file = None;
+                        } else {
+                            let path = recording::output_path(output_dir, "pcapng");
+
+                            match recording::open_capture(&path) {
+                                Ok(mut capture) => {
+                                    recording::register_channels(&mut capture, &channel_names);
+                                    framed.set_capture(Box::new(capture));
+                                    info!(path = %path.display(), "Recording started");
+                                    // The recording goes on after reconnecting.
+                                    *capture_file = Some(path);
+                                }
+                                Err(error) => error!(%error, path = %path.display(), "Failed to start the recording"),
+                            }
+                        }
+
+                        Vec::new()
+                    }
                     RdpInputEvent::Clipboard(event) => {
                         if let Some(cliprdr) = active_stage.get_svc_processor::<cliprdr::CliprdrClient>() {
                             if let Some(svc_messages) = match event {
@@ -251,24 +452,7 @@ async fn active_session(
                     .await
                     .map_err(|e| session::custom_err!("write response", e))?,
                 ActiveStageOutput::GraphicsUpdate(_region) => {
-                    let buffer: Vec<u32> = image
-                        .data()
-                        .chunks_exact(4)
-                        .map(|pixel| {
-                            let r = pixel[0];
-                            let g = pixel[1];
-                            let b = pixel[2];
-                            u32::from_be_bytes([0, r, g, b])
-                        })
-                        .collect();
-
-                    event_loop_proxy
-                        .send_event(RdpOutputEvent::Image {
-                            buffer,
-                            width: image.width(),
-                            height: image.height(),
-                        })
-                        .map_err(|e| session::custom_err!("event_loop_proxy", e))?;
+                    send_image(&image, event_loop_proxy)?;
                 }
                 ActiveStageOutput::PointerDefault => {
                     event_loop_proxy
@@ -311,6 +495,7 @@ async fn active_session(
                             desktop_size,
                             no_server_pointer,
                             pointer_software_rendering,
+                            ..
                         } = connection_activation.state
                         {
                             debug!(?desktop_size, "Deactivation-Reactivation Sequence completed");
@@ -327,10 +512,16 @@ async fn active_session(
                                 .build(),
                             );
                             active_stage.set_no_server_pointer(no_server_pointer);
+                            // Re-layout the window for the new desktop size right away, instead of waiting for
+                            // the first graphics update.
+                            send_image(&image, event_loop_proxy)?;
                             break 'activation_seq;
                         }
                     }
                 }
+                ActiveStageOutput::MonitorLayout(monitors) => {
+                    debug!(?monitors, "Server changed the monitor layout");
+                }
                 ActiveStageOutput::Terminate(reason) => break 'outer reason,
             }
         }
@@ -338,3 +529,24 @@ async fn active_session(
 
     Ok(RdpControlFlow::TerminatedGracefully(disconnect_reason))
 }
+
+fn send_image(image: &DecodedImage, event_loop_proxy: &EventLoopProxy<RdpOutputEvent>) -> SessionResult<()> {
+    let buffer: Vec<u32> = image
+        .data()
+        .chunks_exact(4)
+        .map(|pixel| {
+            let r = pixel[0];
+            let g = pixel[1];
+            let b = pixel[2];
+            u32::from_be_bytes([0, r, g, b])
+        })
+        .collect();
+
+    event_loop_proxy
+        .send_event(RdpOutputEvent::Image {
+            buffer,
+            width: image.width(),
+            height: image.height(),
+        })
+        .map_err(|e| session::custom_err!("event_loop_proxy", e))
+}
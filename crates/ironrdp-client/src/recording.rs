@@ -0,0 +1,71 @@
+//! Screenshots and recordings of the session
+//!
+//! A recording is a capture of the decrypted RDP traffic, in the pcapng format (see
+//! [`PcapngCapture`](ironrdp_tokio::PcapngCapture)).
+
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufWriter};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::Context as _;
+use ironrdp::connector::ConnectionResult;
+use ironrdp_tokio::{CaptureEndpoint, CaptureSink, PcapngCapture};
+
+/// Returns a path in `dir` for a new file with the given extension, named after the current time
+pub fn output_path(dir: &Path, extension: &str) -> PathBuf {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis())
+        .unwrap_or_default();
+
+    dir.join(format!("ironrdp-{timestamp}.{extension}"))
+}
+
+/// Saves RGBA pixels as PNG
+pub fn save_screenshot(path: &Path, width: u16, height: u16, data: &[u8]) -> anyhow::Result<()> {
+    let file = File::create(path).with_context(|| format!("couldn’t create {}", path.display()))?;
+
+    let mut encoder = png::Encoder::new(BufWriter::new(file), u32::from(width), u32::from(height));
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+
+    let mut writer = encoder.write_header().context("write PNG header")?;
+    writer.write_image_data(data).context("write PNG data")?;
+    writer.finish().context("finish PNG")?;
+
+    Ok(())
+}
+
+/// Opens a pcapng capture
+///
+/// Existing files are appended to, so that the sessions established when reconnecting are captured into the same
+/// file.
+pub fn open_capture(path: &Path) -> io::Result<PcapngCapture<BufWriter<File>>> {
+    let file = OpenOptions::new().create(true).append(true).open(path)?;
+    PcapngCapture::new(BufWriter::new(file), CaptureEndpoint::Client)
+}
+
+/// Returns the names of the MCS channels, for annotating the captured frames
+pub fn channel_names(connection_result: &ConnectionResult) -> Vec<(u16, String)> {
+    let mut names = vec![
+        (connection_result.io_channel_id, "I/O".to_owned()),
+        (connection_result.user_channel_id, "user".to_owned()),
+    ];
+
+    for (type_id, channel) in connection_result.static_channels.iter() {
+        let channel_id = connection_result.static_channels.get_channel_id_by_type_id(type_id);
+
+        if let (Some(channel_id), Some(name)) = (channel_id, channel.channel_name().as_str()) {
+            names.push((channel_id, name.to_owned()));
+        }
+    }
+
+    names
+}
+
+pub fn register_channels(capture: &mut dyn CaptureSink, channel_names: &[(u16, String)]) {
+    for (channel_id, name) in channel_names {
+        capture.register_channel(*channel_id, name);
+    }
+}
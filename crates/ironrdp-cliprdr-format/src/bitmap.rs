@@ -608,8 +608,40 @@ fn encode_png(ctx: &PngEncoderContext) -> Result<Vec<u8>, BitmapError> {
     Ok(output)
 }
 
+/// Image with 8-bit RGBA samples, stored top-down
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RgbaImage {
+    pub width: u16,
+    pub height: u16,
+    pub data: Vec<u8>,
+}
+
 /// Converts `CF_DIB` to PNG.
 pub fn dib_to_png(input: &[u8]) -> Result<Vec<u8>, BitmapError> {
+    let png_ctx = decode_dib(input)?;
+    encode_png(&png_ctx)
+}
+
+/// Converts `CF_DIB` to an RGBA image.
+pub fn dib_to_rgba(input: &[u8]) -> Result<RgbaImage, BitmapError> {
+    let ctx = decode_dib(input)?;
+
+    // DIBv1 has no alpha channel, the image is opaque.
+    let data = ctx
+        .bitmap
+        .chunks_exact(3)
+        .flat_map(|pixel| [pixel[0], pixel[1], pixel[2], 0xFF])
+        .collect();
+
+    Ok(RgbaImage {
+        width: ctx.width,
+        height: ctx.height,
+        data,
+    })
+}
+
+/// Decodes `CF_DIB` into a top-down RGB bitmap.
+fn decode_dib(input: &[u8]) -> Result<PngEncoderContext, BitmapError> {
     let mut src = ReadCursor::new(input);
     let header = BitmapInfoHeader::decode(&mut src).map_err(BitmapError::Decode)?;
 
@@ -624,8 +656,7 @@ pub fn dib_to_png(input: &[u8]) -> Result<Vec<u8>, BitmapError> {
         return Err(BitmapError::Unsupported("unsupported compression"));
     }
 
-    let png_ctx = bgra_to_top_down_rgba(&header, src.remaining(), false)?;
-    encode_png(&png_ctx)
+    bgra_to_top_down_rgba(&header, src.remaining(), false)
 }
 
 /// Converts `CF_DIB` to PNG.
@@ -640,12 +671,13 @@ pub fn dibv5_to_png(input: &[u8]) -> Result<Vec<u8>, BitmapError> {
 }
 
 fn top_down_rgba_to_bottom_up_bgra(
-    info: png::OutputInfo,
+    width: u32,
+    height: u32,
+    no_alpha: bool,
     src_bitmap: &[u8],
 ) -> Result<(BitmapInfoHeader, Vec<u8>), BitmapError> {
-    let no_alpha = info.color_type != png::ColorType::Rgba;
-    let width = u16::try_from(info.width).map_err(|_| BitmapError::WidthTooBig)?;
-    let height = u16::try_from(info.height).map_err(|_| BitmapError::HeightTooBig)?;
+    let width = u16::try_from(width).map_err(|_| BitmapError::WidthTooBig)?;
+    let height = u16::try_from(height).map_err(|_| BitmapError::HeightTooBig)?;
 
     #[allow(clippy::arithmetic_side_effects)] // width * 4 <= 10_000 * 4 < u32::MAX
     let stride = usize::from(width) * 4;
@@ -726,8 +758,34 @@ pub fn png_to_cf_dib(input: &[u8]) -> Result<Vec<u8>, BitmapError> {
     // and one in the body of this function.
 
     let (png_info, rgba_bytes) = decode_png(input)?;
-    let (header, bgra_bytes) = top_down_rgba_to_bottom_up_bgra(png_info, &rgba_bytes)?;
+    let (header, bgra_bytes) = top_down_rgba_to_bottom_up_bgra(
+        png_info.width,
+        png_info.height,
+        png_info.color_type != png::ColorType::Rgba,
+        &rgba_bytes,
+    )?;
+
+    encode_dib(&header, &bgra_bytes)
+}
+
+/// Converts an RGBA image to `CF_DIB` format.
+pub fn rgba_to_cf_dib(image: &RgbaImage) -> Result<Vec<u8>, BitmapError> {
+    let expected_len = usize::from(image.width)
+        .checked_mul(usize::from(image.height))
+        .and_then(|len| len.checked_mul(4))
+        .ok_or(BitmapError::InvalidSize)?;
+
+    // Prevent allocation of huge buffers.
+    ensure(expected_len <= MAX_BUFFER_SIZE).ok_or(BitmapError::BufferTooBig)?;
+    ensure(image.data.len() == expected_len).ok_or(BitmapError::InvalidSize)?;
 
+    let (header, bgra_bytes) =
+        top_down_rgba_to_bottom_up_bgra(u32::from(image.width), u32::from(image.height), false, &image.data)?;
+
+    encode_dib(&header, &bgra_bytes)
+}
+
+fn encode_dib(header: &BitmapInfoHeader, bgra_bytes: &[u8]) -> Result<Vec<u8>, BitmapError> {
     let output_len = header
         .size()
         .checked_add(bgra_bytes.len())
@@ -739,7 +797,7 @@ pub fn png_to_cf_dib(input: &[u8]) -> Result<Vec<u8>, BitmapError> {
     {
         let mut dst = WriteCursor::new(&mut output);
         header.encode(&mut dst).map_err(BitmapError::Encode)?;
-        dst.write_slice(&bgra_bytes);
+        dst.write_slice(bgra_bytes);
     }
 
     Ok(output)
@@ -752,7 +810,12 @@ pub fn png_to_cf_dibv5(input: &[u8]) -> Result<Vec<u8>, BitmapError> {
     // and one in the body of this function.
 
     let (png_info, rgba_bytes) = decode_png(input)?;
-    let (header_v1, bgra_bytes) = top_down_rgba_to_bottom_up_bgra(png_info, &rgba_bytes)?;
+    let (header_v1, bgra_bytes) = top_down_rgba_to_bottom_up_bgra(
+        png_info.width,
+        png_info.height,
+        png_info.color_type != png::ColorType::Rgba,
+        &rgba_bytes,
+    )?;
 
     let header = BitmapV5Header {
         v1: header_v1,
@@ -14,5 +14,10 @@ mod windows;
 #[cfg(windows)]
 pub use crate::windows::{WinClipboard, WinCliprdrError, WinCliprdrResult, HWND};
 
+#[cfg(feature = "portable")]
+mod portable;
+#[cfg(feature = "portable")]
+pub use crate::portable::{PortableClipboard, PortableCliprdrBackend, PortableCliprdrError, PortableCliprdrResult};
+
 mod stub;
 pub use crate::stub::{StubClipboard, StubCliprdrBackend};
@@ -0,0 +1,367 @@
+use std::borrow::Cow;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash as _, Hasher as _};
+use std::sync::mpsc as mpsc_sync;
+use std::thread;
+use std::time::Duration;
+
+use arboard::ImageData;
+use ironrdp_cliprdr::backend::{ClipboardMessage, ClipboardMessageProxy, CliprdrBackend, CliprdrBackendFactory};
+use ironrdp_cliprdr::pdu::{
+    ClipboardFormat, ClipboardFormatId, ClipboardGeneralCapabilityFlags, FileContentsRequest, FileContentsResponse,
+    FormatDataRequest, FormatDataResponse, LockDataId, OwnedFormatDataResponse,
+};
+use ironrdp_cliprdr_format::bitmap::{dib_to_rgba, rgba_to_cf_dib, BitmapError, RgbaImage};
+use ironrdp_core::{impl_as_any, DecodeError, IntoOwned};
+use thiserror::Error;
+use tracing::{debug, error};
+
+/// Interval at which the OS clipboard is checked for changes.
+///
+/// The clipboard libraries of macOS and Linux do not notify of clipboard changes.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+pub type PortableCliprdrResult<T> = Result<T, PortableCliprdrError>;
+
+#[derive(Debug, Error)]
+pub enum PortableCliprdrError {
+    #[error("failed to access the clipboard")]
+    Clipboard(#[from] arboard::Error),
+
+    #[error("failed to convert the clipboard image")]
+    Bitmap(#[from] BitmapError),
+
+    #[error("failed to decode remote clipboard data")]
+    Decode(DecodeError),
+
+    #[error("failed to spawn the clipboard thread")]
+    Spawn(#[from] std::io::Error),
+
+    #[error("clipboard thread stopped unexpectedly")]
+    ThreadStopped,
+}
+
+/// Sent from the clipboard backend to the clipboard thread
+#[derive(Debug)]
+enum BackendEvent {
+    RemoteFormatList(Vec<ClipboardFormat>),
+    FormatDataRequest(FormatDataRequest),
+    FormatDataResponse(OwnedFormatDataResponse),
+    RemoteRequestsFormatList,
+}
+
+/// Portable RDP client clipboard implementation, for text and images.
+///
+/// The OS clipboard is accessed from a dedicated thread, running as long as the [`PortableClipboard`] or one of
+/// the backends built by its factory is alive. The clipboard is polled for changes, and the data copied on the
+/// remote is immediately pasted into the OS clipboard, as delayed rendering is not supported on all platforms.
+pub struct PortableClipboard {
+    backend_tx: mpsc_sync::Sender<BackendEvent>,
+}
+
+impl PortableClipboard {
+    /// Creates new clipboard instance.
+    pub fn new(message_proxy: impl ClipboardMessageProxy + 'static) -> PortableCliprdrResult<Self> {
+        let (backend_tx, backend_rx) = mpsc_sync::channel();
+        let (init_tx, init_rx) = mpsc_sync::sync_channel(1);
+
+        // The clipboard is not thread safe on all platforms, hence created on the thread using it.
+        thread::Builder::new()
+            .name("ironrdp-clipboard".to_owned())
+            .spawn(move || match arboard::Clipboard::new() {
+                Ok(clipboard) => {
+                    let _ = init_tx.send(Ok(()));
+                    ClipboardThread::new(clipboard, message_proxy).run(backend_rx);
+                }
+                Err(error) => {
+                    let _ = init_tx.send(Err(error));
+                }
+            })?;
+
+        init_rx.recv().map_err(|_| PortableCliprdrError::ThreadStopped)??;
+
+        Ok(Self { backend_tx })
+    }
+
+    /// Returns clipboard backend factory suitable for making backend instances for `CLIPRDR` SVC.
+    pub fn backend_factory(&self) -> Box<dyn CliprdrBackendFactory + Send> {
+        Box::new(PortableCliprdrBackendFactory {
+            tx: self.backend_tx.clone(),
+        })
+    }
+}
+
+struct PortableCliprdrBackendFactory {
+    tx: mpsc_sync::Sender<BackendEvent>,
+}
+
+impl CliprdrBackendFactory for PortableCliprdrBackendFactory {
+    fn build_cliprdr_backend(&self) -> Box<dyn CliprdrBackend> {
+        Box::new(PortableCliprdrBackend { tx: self.tx.clone() })
+    }
+}
+
+#[derive(Debug)]
+pub struct PortableCliprdrBackend {
+    tx: mpsc_sync::Sender<BackendEvent>,
+}
+
+impl_as_any!(PortableCliprdrBackend);
+
+impl PortableCliprdrBackend {
+    fn send_event(&self, event: BackendEvent) {
+        if self.tx.send(event).is_err() {
+            error!("Clipboard thread is stopped");
+        }
+    }
+}
+
+impl CliprdrBackend for PortableCliprdrBackend {
+    fn temporary_directory(&self) -> &str {
+        ".cliprdr"
+    }
+
+    fn client_capabilities(&self) -> ClipboardGeneralCapabilityFlags {
+        // No additional capabilities yet
+        ClipboardGeneralCapabilityFlags::empty()
+    }
+
+    fn on_process_negotiated_capabilities(&mut self, capabilities: ClipboardGeneralCapabilityFlags) {
+        debug!(?capabilities);
+    }
+
+    fn on_remote_copy(&mut self, available_formats: &[ClipboardFormat]) {
+        self.send_event(BackendEvent::RemoteFormatList(available_formats.to_vec()));
+    }
+
+    fn on_format_data_request(&mut self, request: FormatDataRequest) {
+        self.send_event(BackendEvent::FormatDataRequest(request));
+    }
+
+    fn on_format_data_response(&mut self, response: FormatDataResponse<'_>) {
+        self.send_event(BackendEvent::FormatDataResponse(response.into_owned()));
+    }
+
+    fn on_file_contents_request(&mut self, _request: FileContentsRequest) {
+        // File transfer not implemented yet
+    }
+
+    fn on_file_contents_response(&mut self, _response: FileContentsResponse<'_>) {
+        // File transfer not implemented yet
+    }
+
+    fn on_lock(&mut self, _data_id: LockDataId) {
+        // File transfer not implemented yet
+    }
+
+    fn on_unlock(&mut self, _data_id: LockDataId) {
+        // File transfer not implemented yet
+    }
+
+    fn on_request_format_list(&mut self) {
+        self.send_event(BackendEvent::RemoteRequestsFormatList);
+    }
+}
+
+/// Content of the OS clipboard, in the formats supported by this backend
+struct ClipboardContent {
+    text: Option<String>,
+    image: Option<ImageData<'static>>,
+}
+
+impl ClipboardContent {
+    fn formats(&self) -> Vec<ClipboardFormat> {
+        let mut formats = Vec::new();
+
+        if self.text.is_some() {
+            formats.push(ClipboardFormat::new(ClipboardFormatId::CF_UNICODETEXT));
+        }
+
+        if self.image.is_some() {
+            formats.push(ClipboardFormat::new(ClipboardFormatId::CF_DIB));
+        }
+
+        formats
+    }
+
+    /// Fingerprint used to detect clipboard changes, without keeping a copy of the content
+    fn fingerprint(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+
+        self.text.hash(&mut hasher);
+
+        if let Some(image) = &self.image {
+            image.width.hash(&mut hasher);
+            image.height.hash(&mut hasher);
+            image.bytes.hash(&mut hasher);
+        }
+
+        hasher.finish()
+    }
+}
+
+struct ClipboardThread<P> {
+    clipboard: arboard::Clipboard,
+    message_proxy: P,
+    /// Fingerprint of the last content advertised to the remote, or received from it
+    last_fingerprint: Option<u64>,
+    /// Format requested to the remote, of which the data is pending
+    pending_paste: Option<ClipboardFormatId>,
+    /// Whether the `CLIPRDR` channel requested the initial format list, after which the changes are advertised
+    is_ready: bool,
+}
+
+impl<P: ClipboardMessageProxy> ClipboardThread<P> {
+    fn new(clipboard: arboard::Clipboard, message_proxy: P) -> Self {
+        Self {
+            clipboard,
+            message_proxy,
+            last_fingerprint: None,
+            pending_paste: None,
+            is_ready: false,
+        }
+    }
+
+    fn run(mut self, backend_rx: mpsc_sync::Receiver<BackendEvent>) {
+        loop {
+            let result = match backend_rx.recv_timeout(POLL_INTERVAL) {
+                Ok(event) => self.process_event(event),
+                Err(mpsc_sync::RecvTimeoutError::Timeout) => self.poll(),
+                Err(mpsc_sync::RecvTimeoutError::Disconnected) => break,
+            };
+
+            if let Err(error) = result {
+                self.message_proxy
+                    .send_clipboard_message(ClipboardMessage::Error(Box::new(error)));
+            }
+        }
+
+        debug!("Clipboard thread stopped");
+    }
+
+    fn read_content(&mut self) -> ClipboardContent {
+        // Both fail when the clipboard is empty, or holds data in another format.
+        ClipboardContent {
+            text: self.clipboard.get_text().ok(),
+            image: self.clipboard.get_image().ok(),
+        }
+    }
+
+    fn advertise(&mut self, content: &ClipboardContent) {
+        self.last_fingerprint = Some(content.fingerprint());
+        self.message_proxy
+            .send_clipboard_message(ClipboardMessage::SendInitiateCopy(content.formats()));
+    }
+
+    fn poll(&mut self) -> PortableCliprdrResult<()> {
+        if !self.is_ready {
+            return Ok(());
+        }
+
+        let content = self.read_content();
+
+        if self.last_fingerprint != Some(content.fingerprint()) {
+            self.advertise(&content);
+        }
+
+        Ok(())
+    }
+
+    fn process_event(&mut self, event: BackendEvent) -> PortableCliprdrResult<()> {
+        match event {
+            BackendEvent::RemoteRequestsFormatList => {
+                self.is_ready = true;
+                let content = self.read_content();
+                self.advertise(&content);
+            }
+            BackendEvent::RemoteFormatList(formats) => {
+                let requested_format = [ClipboardFormatId::CF_UNICODETEXT, ClipboardFormatId::CF_DIB]
+                    .into_iter()
+                    .find(|id| formats.iter().any(|format| format.id() == *id));
+
+                self.pending_paste = requested_format;
+
+                if let Some(format) = requested_format {
+                    self.message_proxy
+                        .send_clipboard_message(ClipboardMessage::SendInitiatePaste(format));
+                }
+            }
+            BackendEvent::FormatDataRequest(request) => {
+                let response = match self.format_data(request.format) {
+                    Ok(Some(response)) => response,
+                    Ok(None) => FormatDataResponse::new_error(),
+                    Err(error) => {
+                        self.message_proxy
+                            .send_clipboard_message(ClipboardMessage::SendFormatData(FormatDataResponse::new_error()));
+                        return Err(error);
+                    }
+                };
+
+                self.message_proxy
+                    .send_clipboard_message(ClipboardMessage::SendFormatData(response));
+            }
+            BackendEvent::FormatDataResponse(response) => {
+                let Some(format) = self.pending_paste.take() else {
+                    debug!("Ignored an unexpected format data response");
+                    return Ok(());
+                };
+
+                if response.is_error() {
+                    debug!(?format, "Remote failed to send the clipboard data");
+                    return Ok(());
+                }
+
+                self.paste(format, &response)?;
+
+                // The pasted content is not advertised back to the remote.
+                let content = self.read_content();
+                self.last_fingerprint = Some(content.fingerprint());
+            }
+        }
+
+        Ok(())
+    }
+
+    fn format_data(&mut self, format: ClipboardFormatId) -> PortableCliprdrResult<Option<OwnedFormatDataResponse>> {
+        let response = match format {
+            ClipboardFormatId::CF_UNICODETEXT => {
+                Some(FormatDataResponse::new_unicode_string(&self.clipboard.get_text()?))
+            }
+            ClipboardFormatId::CF_DIB => {
+                let image = self.clipboard.get_image()?;
+
+                let image = RgbaImage {
+                    width: u16::try_from(image.width).map_err(|_| BitmapError::WidthTooBig)?,
+                    height: u16::try_from(image.height).map_err(|_| BitmapError::HeightTooBig)?,
+                    data: image.bytes.into_owned(),
+                };
+
+                Some(FormatDataResponse::new_data(rgba_to_cf_dib(&image)?))
+            }
+            _ => None,
+        };
+
+        Ok(response)
+    }
+
+    fn paste(&mut self, format: ClipboardFormatId, response: &OwnedFormatDataResponse) -> PortableCliprdrResult<()> {
+        match format {
+            ClipboardFormatId::CF_UNICODETEXT => {
+                let text = response.to_unicode_string().map_err(PortableCliprdrError::Decode)?;
+                self.clipboard.set_text(text)?;
+            }
+            ClipboardFormatId::CF_DIB => {
+                let image = dib_to_rgba(response.data())?;
+
+                self.clipboard.set_image(ImageData {
+                    width: usize::from(image.width),
+                    height: usize::from(image.height),
+                    bytes: Cow::Owned(image.data),
+                })?;
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+}
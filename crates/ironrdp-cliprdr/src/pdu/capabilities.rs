@@ -4,7 +4,7 @@ use ironrdp_core::{
     EncodeResult, ReadCursor, WriteCursor,
 };
 use ironrdp_core::{Decode, Encode};
-use ironrdp_pdu::{impl_pdu_pod, read_padding, write_padding};
+use ironrdp_pdu::{impl_pdu_pod, read_zeroed_padding, write_padding};
 
 use crate::pdu::PartialHeader;
 
@@ -91,7 +91,7 @@ impl<'de> Decode<'de> for Capabilities {
 
         ensure_fixed_part_size!(in: src);
         let capabilities_count = src.read_u16();
-        read_padding!(src, 2);
+        read_zeroed_padding!(src, 2)?;
 
         let mut capabilities = Vec::with_capacity(usize::from(capabilities_count));
 
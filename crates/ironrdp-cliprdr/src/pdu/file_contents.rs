@@ -2,7 +2,8 @@ use std::borrow::Cow;
 
 use bitflags::bitflags;
 use ironrdp_core::{
-    cast_int, ensure_size, invalid_field_err, DecodeResult, EncodeResult, IntoOwned, ReadCursor, WriteCursor,
+    cast_int, decode_limits, ensure_size, invalid_field_err, DecodeResult, EncodeResult, IntoOwned, ReadCursor,
+    WriteCursor,
 };
 use ironrdp_core::{Decode, Encode};
 use ironrdp_pdu::impl_pdu_borrowing;
@@ -147,6 +148,13 @@ impl<'de> Decode<'de> for FileContentsResponse<'de> {
 
         let data_size = header.data_length() - Self::FIXED_PART_SIZE;
 
+        if data_size > decode_limits().max_clipboard_data_size {
+            return Err(invalid_field_err!(
+                "requestedFileContentsData",
+                "file contents size exceeds the decode limit"
+            ));
+        }
+
         let stream_id = src.read_u32();
         let data = src.read_slice(data_size);
 
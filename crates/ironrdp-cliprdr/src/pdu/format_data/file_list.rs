@@ -1,6 +1,7 @@
 use bitflags::bitflags;
 use ironrdp_core::{
-    cast_length, ensure_fixed_part_size, Decode, DecodeResult, Encode, EncodeResult, ReadCursor, WriteCursor,
+    cast_length, decode_limits, ensure_fixed_part_size, ensure_size, invalid_field_err, Decode, DecodeResult, Encode,
+    EncodeResult, ReadCursor, WriteCursor,
 };
 use ironrdp_pdu::utils::{combine_u64, decode_string, encode_string, split_u64, CharacterSet};
 use ironrdp_pdu::{impl_pdu_pod, write_padding};
@@ -137,7 +138,13 @@ impl<'de> Decode<'de> for FileDescriptor {
         let file_size = if flags.contains(ClipboardFileFlags::FILE_SIZE) {
             let size_hi = src.read_u32();
             let size_lo = src.read_u32();
-            Some(combine_u64(size_lo, size_hi))
+            let file_size = combine_u64(size_lo, size_hi);
+
+            if file_size > decode_limits().max_file_size {
+                return Err(invalid_field_err!("fileSize", "file size exceeds the decode limit"));
+            }
+
+            Some(file_size)
         } else {
             let _ = src.read_u64();
             None
@@ -195,7 +202,10 @@ impl Encode for PackedFileList {
 impl<'de> Decode<'de> for PackedFileList {
     fn decode(src: &mut ReadCursor<'de>) -> DecodeResult<Self> {
         ensure_fixed_part_size!(in: src);
-        let file_count = cast_length!(Self::NAME, "cItems", src.read_u32())?;
+        let file_count: usize = cast_length!(Self::NAME, "cItems", src.read_u32())?;
+
+        // Checked upfront so that the file count does not drive the allocation below.
+        ensure_size!(in: src, size: file_count.saturating_mul(FileDescriptor::SIZE));
 
         let mut files = Vec::with_capacity(file_count);
         for _ in 0..file_count {
@@ -10,8 +10,10 @@ pub use self::palette::*;
 use std::borrow::Cow;
 
 use ironrdp_core::cast_int;
+use ironrdp_core::decode_limits;
 use ironrdp_core::ensure_fixed_part_size;
 use ironrdp_core::ensure_size;
+use ironrdp_core::invalid_field_err;
 use ironrdp_core::DecodeResult;
 use ironrdp_core::EncodeResult;
 use ironrdp_core::IntoOwned;
@@ -205,6 +207,14 @@ impl<'de> Decode<'de> for FormatDataResponse<'de> {
         let is_error = header.message_flags.contains(ClipboardPduFlags::RESPONSE_FAIL);
 
         ensure_size!(in: src, size: header.data_length());
+
+        if header.data_length() > decode_limits().max_clipboard_data_size {
+            return Err(invalid_field_err!(
+                "requestedFormatData",
+                "clipboard data size exceeds the decode limit"
+            ));
+        }
+
         let data = src.read_slice(header.data_length());
 
         Ok(Self {
@@ -4,7 +4,10 @@ use std::mem;
 use ironrdp_core::WriteBuf;
 use ironrdp_pdu::{mcs, x224::X224, PduHint};
 
-use crate::{ConnectorError, ConnectorErrorExt as _, ConnectorResult, Sequence, State, Written};
+use crate::{
+    map_decode_error, ConnectorError, ConnectorErrorExt as _, ConnectorErrorKind, ConnectorResult, Sequence, State,
+    Written,
+};
 
 #[derive(Default, Debug)]
 #[non_exhaustive]
@@ -133,7 +136,7 @@ impl Sequence for ChannelConnectionSequence {
 
             ChannelConnectionState::WaitAttachUserConfirm => {
                 let attach_user_confirm = ironrdp_core::decode::<X224<mcs::AttachUserConfirm>>(input)
-                    .map_err(ConnectorError::decode)
+                    .map_err(|e| map_decode_error(input, e))
                     .map(|p| p.0)?;
 
                 let user_channel_id = attach_user_confirm.initiator_id;
@@ -199,7 +202,7 @@ impl Sequence for ChannelConnectionSequence {
                 mut remaining_channel_ids,
             } => {
                 let channel_join_confirm = ironrdp_core::decode::<X224<mcs::ChannelJoinConfirm>>(input)
-                    .map_err(ConnectorError::decode)
+                    .map_err(|e| map_decode_error(input, e))
                     .map(|p| p.0)?;
 
                 debug!(message = ?channel_join_confirm, "Received");
@@ -214,21 +217,28 @@ impl Sequence for ChannelConnectionSequence {
                 let is_expected = remaining_channel_ids.remove(&channel_join_confirm.requested_channel_id);
 
                 if !is_expected {
-                    return Err(reason_err!(
-                        "ChannelJoinConfirm",
-                        "unexpected requested_channel_id in MCS Channel Join Confirm: got {}, expected one of: {:?}",
+                    warn!(
                         channel_join_confirm.requested_channel_id,
-                        remaining_channel_ids,
+                        ?remaining_channel_ids,
+                        "Unexpected requested_channel_id in MCS Channel Join Confirm",
+                    );
+
+                    return Err(ConnectorError::new(
+                        "ChannelJoinConfirm",
+                        ConnectorErrorKind::UnexpectedChannelJoin {
+                            channel_id: channel_join_confirm.requested_channel_id,
+                        },
                     ));
                 }
 
                 if channel_join_confirm.requested_channel_id != channel_join_confirm.channel_id {
                     // We could handle that gracefully by updating the StaticChannelSet, but it doesn’t seem to ever happen.
-                    return Err(reason_err!(
+                    return Err(ConnectorError::new(
                         "ChannelJoinConfirm",
-                        "a channel was joined with a different channel ID than requested: requested {}, got {}",
-                        channel_join_confirm.requested_channel_id,
-                        channel_join_confirm.channel_id,
+                        ConnectorErrorKind::ChannelIdMismatch {
+                            requested: channel_join_confirm.requested_channel_id,
+                            joined: channel_join_confirm.channel_id,
+                        },
                     ));
                 }
 
@@ -1,8 +1,9 @@
 use std::borrow::Cow;
 use std::mem;
 use std::net::SocketAddr;
+use std::sync::Arc;
 
-use ironrdp_core::{decode, encode_vec, Encode, WriteBuf};
+use ironrdp_core::{decode, encode_vec, DecodeLimits, DecodeMode, Encode, WriteBuf};
 use ironrdp_pdu::rdp::client_info::{OptionalSystemTime, TimezoneInfo};
 use ironrdp_pdu::x224::X224;
 use ironrdp_pdu::{gcc, mcs, nego, rdp, PduHint};
@@ -10,10 +11,10 @@ use ironrdp_svc::{StaticChannelSet, StaticVirtualChannel, SvcClientProcessor};
 
 use crate::channel_connection::{ChannelConnectionSequence, ChannelConnectionState};
 use crate::connection_activation::{ConnectionActivationSequence, ConnectionActivationState};
-use crate::license_exchange::LicenseExchangeSequence;
+use crate::license_exchange::{LicenseCache, LicenseExchangeSequence};
 use crate::{
-    encode_x224_packet, Config, ConnectorError, ConnectorErrorExt as _, ConnectorResult, DesktopSize, Sequence, State,
-    Written,
+    encode_x224_packet, map_decode_error, Config, ConnectorError, ConnectorErrorExt as _, ConnectorErrorKind,
+    ConnectorResult, DesktopSize, Sequence, State, Written,
 };
 
 #[derive(Debug)]
@@ -23,9 +24,56 @@ pub struct ConnectionResult {
     pub user_channel_id: u16,
     pub static_channels: StaticChannelSet,
     pub desktop_size: DesktopSize,
+    /// Monitor layout sent by the server during the capabilities exchange, if any
+    #[cfg_attr(feature = "arbitrary", arbitrary(default))]
+    pub monitor_layout: Option<Vec<gcc::Monitor>>,
     pub no_server_pointer: bool,
     pub pointer_software_rendering: bool,
+    #[cfg_attr(feature = "arbitrary", arbitrary(default))]
+    pub decode_mode: DecodeMode,
+    #[cfg_attr(feature = "arbitrary", arbitrary(default))]
+    pub decode_limits: DecodeLimits,
     pub connection_activation: ConnectionActivationSequence,
+    /// How the connection is secured, e.g.: for displaying it to the user
+    pub security_info: SecurityInfo,
+}
+
+/// Security properties of an established connection
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct SecurityInfo {
+    /// Security protocol selected by the server during the connection initiation
+    pub security_protocol: nego::SecurityProtocol,
+    /// Whether the user was authenticated before the session was established (Network Level Authentication)
+    ///
+    /// This is the case when CredSSP succeeded.
+    pub nla: bool,
+    /// Details of the TLS connection, when provided with [`ClientConnector::attach_tls_info`]
+    pub tls: Option<TlsInfo>,
+}
+
+impl Default for SecurityInfo {
+    fn default() -> Self {
+        Self {
+            security_protocol: nego::SecurityProtocol::empty(),
+            nla: false,
+            tls: None,
+        }
+    }
+}
+
+/// Details of the TLS connection, as reported by the TLS backend
+///
+/// The fields a backend is not able to report are left empty.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct TlsInfo {
+    /// Negotiated protocol version, e.g.: `TLSv1_3`
+    pub protocol_version: Option<String>,
+    /// Negotiated cipher suite, e.g.: `TLS13_AES_256_GCM_SHA384`
+    pub cipher_suite: Option<String>,
+    /// DER-encoded certificates presented by the server, starting with the certificate of the server itself
+    pub server_certificate_chain: Vec<Vec<u8>>,
 }
 
 #[derive(Default, Debug)]
@@ -124,6 +172,11 @@ pub struct ClientConnector {
     pub state: ClientConnectorState,
     pub server_addr: Option<SocketAddr>,
     pub static_channels: StaticChannelSet,
+    /// Security properties gathered along the connection sequence, moved into the [`ConnectionResult`]
+    pub security_info: SecurityInfo,
+    /// Storage for the client licenses, presented again on the next connections
+    #[cfg_attr(feature = "arbitrary", arbitrary(default))]
+    pub license_cache: Option<Arc<dyn LicenseCache>>,
 }
 
 impl ClientConnector {
@@ -133,6 +186,8 @@ impl ClientConnector {
             state: ClientConnectorState::ConnectionInitiationSendRequest,
             server_addr: None,
             static_channels: StaticChannelSet::new(),
+            security_info: SecurityInfo::default(),
+            license_cache: None,
         }
     }
 
@@ -164,6 +219,23 @@ impl ClientConnector {
         self.static_channels.insert(channel);
     }
 
+    #[must_use]
+    pub fn with_license_cache(mut self, license_cache: Arc<dyn LicenseCache>) -> Self {
+        self.license_cache = Some(license_cache);
+        self
+    }
+
+    pub fn attach_license_cache(&mut self, license_cache: Arc<dyn LicenseCache>) {
+        self.license_cache = Some(license_cache);
+    }
+
+    /// Records the details of the TLS connection established during the security upgrade
+    ///
+    /// They are reported in [`ConnectionResult::security_info`].
+    pub fn attach_tls_info(&mut self, tls_info: TlsInfo) {
+        self.security_info.tls = Some(tls_info);
+    }
+
     pub fn should_perform_security_upgrade(&self) -> bool {
         matches!(self.state, ClientConnectorState::EnhancedSecurityUpgrade { .. })
     }
@@ -216,6 +288,15 @@ impl Sequence for ClientConnector {
     }
 
     fn step(&mut self, input: &[u8], output: &mut WriteBuf) -> ConnectorResult<Written> {
+        ironrdp_core::with_decode_options(
+            crate::decode_options(self.config.decode_mode, self.config.decode_limits),
+            || self.step_impl(input, output),
+        )
+    }
+}
+
+impl ClientConnector {
+    fn step_impl(&mut self, input: &[u8], output: &mut WriteBuf) -> ConnectorResult<Written> {
         let (written, next_state) = match mem::take(&mut self.state) {
             // Invalid state
             ClientConnectorState::Consumed => {
@@ -246,15 +327,26 @@ impl Sequence for ClientConnector {
                     security_protocol.insert(nego::SecurityProtocol::HYBRID | nego::SecurityProtocol::HYBRID_EX);
                 }
 
+                if self.config.restricted_admin && !self.config.enable_credssp {
+                    return Err(reason_err!("Initiation", "Restricted Admin mode requires CredSSP"));
+                }
+
                 if security_protocol.is_standard_rdp_security() {
-                    return Err(reason_err!("Initiation", "standard RDP security is not supported",));
+                    return Err(ConnectorError::new(
+                        "Initiation",
+                        ConnectorErrorKind::StandardSecurityNotSupported,
+                    ));
                 }
 
                 let connection_request = nego::ConnectionRequest {
                     nego_data: Some(nego::NegoRequestData::cookie(
                         self.config.credentials.username().to_owned(),
                     )),
-                    flags: nego::RequestFlags::empty(),
+                    flags: if self.config.restricted_admin {
+                        nego::RequestFlags::RESTRICTED_ADMIN_MODE_REQUIRED
+                    } else {
+                        nego::RequestFlags::empty()
+                    },
                     protocol: security_protocol,
                 };
 
@@ -272,7 +364,7 @@ impl Sequence for ClientConnector {
             }
             ClientConnectorState::ConnectionInitiationWaitConfirm { requested_protocol } => {
                 let connection_confirm = decode::<X224<nego::ConnectionConfirm>>(input)
-                    .map_err(ConnectorError::decode)
+                    .map_err(|e| map_decode_error(input, e))
                     .map(|p| p.0)?;
 
                 debug!(message = ?connection_confirm, "Received");
@@ -281,19 +373,32 @@ impl Sequence for ClientConnector {
                     nego::ConnectionConfirm::Response { flags, protocol } => (flags, protocol),
                     nego::ConnectionConfirm::Failure { code } => {
                         error!(?code, "Received connection failure code");
-                        return Err(reason_err!("Initiation", "{code}"));
+                        return Err(ConnectorError::new("Initiation", ConnectorErrorKind::Negotiation(code)));
                     }
                 };
 
                 info!(?selected_protocol, ?flags, "Server confirmed connection");
 
                 if !selected_protocol.intersects(requested_protocol) {
+                    return Err(ConnectorError::new(
+                        "Initiation",
+                        ConnectorErrorKind::SecurityProtocolMismatch {
+                            requested: requested_protocol,
+                            selected: selected_protocol,
+                        },
+                    ));
+                }
+
+                if self.config.restricted_admin && !flags.contains(nego::ResponseFlags::RESTRICTED_ADMIN_MODE_SUPPORTED)
+                {
                     return Err(reason_err!(
                         "Initiation",
-                        "client advertised {requested_protocol}, but server selected {selected_protocol}",
+                        "Restricted Admin mode is not supported by the server"
                     ));
                 }
 
+                self.security_info.security_protocol = selected_protocol;
+
                 (
                     Written::Nothing,
                     ClientConnectorState::EnhancedSecurityUpgrade { selected_protocol },
@@ -318,10 +423,15 @@ impl Sequence for ClientConnector {
             }
 
             //== CredSSP ==//
-            ClientConnectorState::Credssp { selected_protocol } => (
-                Written::Nothing,
-                ClientConnectorState::BasicSettingsExchangeSendInitial { selected_protocol },
-            ),
+            ClientConnectorState::Credssp { selected_protocol } => {
+                // The CredSSP sequence is performed by the user code, this state is left once it succeeded.
+                self.security_info.nla = true;
+
+                (
+                    Written::Nothing,
+                    ClientConnectorState::BasicSettingsExchangeSendInitial { selected_protocol },
+                )
+            }
 
             //== Basic Settings Exchange ==//
             // Exchange basic settings including Core Data, Security Data and Network Data.
@@ -346,8 +456,8 @@ impl Sequence for ClientConnector {
                 let x224_payload = decode::<X224<crate::x224::X224Data<'_>>>(input)
                     .map_err(ConnectorError::decode)
                     .map(|p| p.0)?;
-                let connect_response =
-                    decode::<mcs::ConnectResponse>(x224_payload.data.as_ref()).map_err(ConnectorError::decode)?;
+                let connect_response = decode::<mcs::ConnectResponse>(x224_payload.data.as_ref())
+                    .map_err(|e| map_decode_error(input, e))?;
 
                 debug!(message = ?connect_response, "Received");
 
@@ -478,6 +588,7 @@ impl Sequence for ClientConnector {
                         io_channel_id,
                         self.config.credentials.username().to_owned(),
                         self.config.domain.clone(),
+                        self.license_cache.clone(),
                     ),
                 },
             ),
@@ -557,6 +668,7 @@ impl Sequence for ClientConnector {
                             io_channel_id,
                             user_channel_id,
                             desktop_size,
+                            monitor_layout,
                             no_server_pointer,
                             pointer_software_rendering,
                         } => ClientConnectorState::Connected {
@@ -565,9 +677,13 @@ impl Sequence for ClientConnector {
                                 user_channel_id,
                                 static_channels: mem::take(&mut self.static_channels),
                                 desktop_size,
+                                monitor_layout,
                                 no_server_pointer,
                                 pointer_software_rendering,
+                                decode_mode: self.config.decode_mode,
+                                decode_limits: self.config.decode_limits,
                                 connection_activation,
+                                security_info: mem::take(&mut self.security_info),
                             },
                         },
                         _ => return Err(general_err!("invalid state (this is a bug)")),
@@ -629,6 +745,20 @@ fn create_gcc_blocks<'a>(
         .map(ironrdp_svc::make_channel_definition)
         .collect::<Vec<_>>();
 
+    let (monitor, monitor_extended) = if config.monitors.is_empty() {
+        (None, None)
+    } else {
+        let monitor = ClientMonitorData {
+            monitors: config.monitors.iter().map(|m| m.monitor.clone()).collect(),
+        };
+
+        let monitor_extended = ClientMonitorExtendedData {
+            extended_monitors_info: config.monitors.iter().map(|m| m.attributes.clone()).collect(),
+        };
+
+        (Some(monitor), Some(monitor_extended))
+    };
+
     ClientGccBlocks {
         core: ClientCoreData {
             version: RdpVersion::V5_PLUS,
@@ -653,7 +783,8 @@ fn create_gcc_blocks<'a>(
                     let mut early_capability_flags = ClientEarlyCapabilityFlags::VALID_CONNECTION_TYPE
                         | ClientEarlyCapabilityFlags::SUPPORT_ERR_INFO_PDU
                         | ClientEarlyCapabilityFlags::STRONG_ASYMMETRIC_KEYS
-                        | ClientEarlyCapabilityFlags::SUPPORT_SKIP_CHANNELJOIN;
+                        | ClientEarlyCapabilityFlags::SUPPORT_SKIP_CHANNELJOIN
+                        | ClientEarlyCapabilityFlags::SUPPORT_MONITOR_LAYOUT_PDU;
 
                     // TODO(#136): support for ClientEarlyCapabilityFlags::SUPPORT_STATUS_INFO_PDU
 
@@ -692,12 +823,12 @@ fn create_gcc_blocks<'a>(
         },
         // TODO(#139): support for Some(ClientClusterData { flags: RedirectionFlags::REDIRECTION_SUPPORTED, redirection_version: RedirectionVersion::V4, redirected_session_id: 0, }),
         cluster: None,
-        monitor: None,
+        monitor,
         // TODO(#140): support for Client Message Channel Data (https://learn.microsoft.com/en-us/openspecs/windows_protocols/ms-rdpbcgr/f50e791c-de03-4b25-b17e-e914c9020bc3)
         message_channel: None,
         // TODO(#140): support for Some(MultiTransportChannelData { flags: MultiTransportFlags::empty(), })
         multi_transport_channel: None,
-        monitor_extended: None,
+        monitor_extended,
     }
 }
 
@@ -707,6 +838,7 @@ fn create_client_info_pdu(config: &Config, routing_addr: &SocketAddr) -> rdp::Cl
         ExtendedClientOptionalInfo,
     };
     use ironrdp_pdu::rdp::headers::{BasicSecurityHeader, BasicSecurityHeaderFlags};
+    use ironrdp_pdu::rdp::standard_security::CLIENT_RANDOM_LEN;
     use ironrdp_pdu::rdp::ClientInfoPdu;
 
     let security_header = BasicSecurityHeader {
@@ -733,10 +865,36 @@ fn create_client_info_pdu(config: &Config, routing_addr: &SocketAddr) -> rdp::Cl
         flags |= ClientInfoFlags::PASSWORD_IS_SC_PIN;
     }
 
+    let optional_data = ExtendedClientOptionalInfo::builder()
+        .timezone(TimezoneInfo {
+            bias: 0,
+            standard_name: String::new(),
+            standard_date: OptionalSystemTime(None),
+            standard_bias: 0,
+            daylight_name: String::new(),
+            daylight_date: OptionalSystemTime(None),
+            daylight_bias: 0,
+        })
+        .session_id(0)
+        .performance_flags(config.performance_flags);
+
+    let optional_data = match &config.auto_reconnect {
+        // Enhanced RDP Security is always used, so the client random is zeroed.
+        Some(auto_reconnect) => optional_data
+            .reconnect_cookie(auto_reconnect.client_cookie(&[0; CLIENT_RANDOM_LEN]))
+            .build(),
+        None => optional_data.build(),
+    };
+
     let client_info = ClientInfo {
         credentials: Credentials {
             username: config.credentials.username().to_owned(),
-            password: config.credentials.secret().to_owned(),
+            // In Restricted Admin mode, the credentials are not sent to the server.
+            password: if config.restricted_admin {
+                String::new()
+            } else {
+                config.credentials.secret().to_owned()
+            },
             domain: config.domain.clone(),
         },
         code_page: 0, // ignored if the keyboardLayout field of the Client Core Data is set to zero
@@ -751,19 +909,7 @@ fn create_client_info_pdu(config: &Config, routing_addr: &SocketAddr) -> rdp::Cl
             },
             address: routing_addr.ip().to_string(),
             dir: config.client_dir.clone(),
-            optional_data: ExtendedClientOptionalInfo::builder()
-                .timezone(TimezoneInfo {
-                    bias: 0,
-                    standard_name: String::new(),
-                    standard_date: OptionalSystemTime(None),
-                    standard_bias: 0,
-                    daylight_name: String::new(),
-                    daylight_date: OptionalSystemTime(None),
-                    daylight_bias: 0,
-                })
-                .session_id(0)
-                .performance_flags(config.performance_flags)
-                .build(),
+            optional_data,
         },
     };
 
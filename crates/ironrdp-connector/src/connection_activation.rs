@@ -1,8 +1,13 @@
 use std::mem;
 
+use ironrdp_pdu::gcc;
+use ironrdp_pdu::rdp::finalization_messages::{PersistentKeyListPdu, PERSISTENT_KEY_LIST_CELLS};
 use ironrdp_pdu::rdp::{self, capability_sets::CapabilitySet};
 
-use crate::{legacy, Config, ConnectionFinalizationSequence, ConnectorResult, DesktopSize, Sequence, State, Written};
+use crate::{
+    legacy, Config, ConnectionFinalizationSequence, ConnectorError, ConnectorErrorExt as _, ConnectorResult,
+    DesktopSize, Sequence, State, Written,
+};
 
 /// Represents the Capability Exchange and Connection Finalization phases
 /// of the connection sequence (section [1.3.1.1]).
@@ -20,6 +25,8 @@ use crate::{legacy, Config, ConnectionFinalizationSequence, ConnectorResult, Des
 pub struct ConnectionActivationSequence {
     pub state: ConnectionActivationState,
     config: Config,
+    /// Whether this is a Deactivation-Reactivation Sequence, during which the persistent keys are not sent again
+    reactivation: bool,
 }
 
 impl ConnectionActivationSequence {
@@ -30,6 +37,7 @@ impl ConnectionActivationSequence {
                 user_channel_id,
             },
             config,
+            reactivation: false,
         }
     }
 
@@ -39,6 +47,8 @@ impl ConnectionActivationSequence {
     }
 
     fn reset(mut self) -> Self {
+        self.reactivation = true;
+
         match &self.state {
             ConnectionActivationState::CapabilitiesExchange {
                 io_channel_id,
@@ -150,8 +160,15 @@ impl Sequence for ConnectionActivationSequence {
                         height: self.config.desktop_size.height,
                     });
 
+                let persistent_bitmap_cache = persistent_bitmap_cache(&self.config, &capability_sets)?;
+
+                let persistent_key_list = match &persistent_bitmap_cache {
+                    Some(_) if !self.reactivation => create_persistent_key_list(&self.config)?,
+                    _ => Vec::new(),
+                };
+
                 let client_confirm_active = rdp::headers::ShareControlPdu::ClientConfirmActive(
-                    create_client_confirm_active(&self.config, capability_sets, desktop_size),
+                    create_client_confirm_active(&self.config, capability_sets, desktop_size, persistent_bitmap_cache)?,
                 );
 
                 debug!(message = ?client_confirm_active, "Send");
@@ -170,7 +187,8 @@ impl Sequence for ConnectionActivationSequence {
                         io_channel_id,
                         user_channel_id,
                         desktop_size,
-                        connection_finalization: ConnectionFinalizationSequence::new(io_channel_id, user_channel_id),
+                        connection_finalization: ConnectionFinalizationSequence::new(io_channel_id, user_channel_id)
+                            .with_persistent_key_list(persistent_key_list),
                     },
                 )
             }
@@ -196,6 +214,7 @@ impl Sequence for ConnectionActivationSequence {
                         io_channel_id,
                         user_channel_id,
                         desktop_size,
+                        monitor_layout: connection_finalization.monitor_layout,
                         no_server_pointer: self.config.no_server_pointer,
                         pointer_software_rendering: self.config.pointer_software_rendering,
                     }
@@ -229,6 +248,8 @@ pub enum ConnectionActivationState {
         io_channel_id: u16,
         user_channel_id: u16,
         desktop_size: DesktopSize,
+        /// Monitor layout sent by the server during the capabilities exchange, if any
+        monitor_layout: Option<Vec<gcc::Monitor>>,
         no_server_pointer: bool,
         pointer_software_rendering: bool,
     },
@@ -255,21 +276,83 @@ impl State for ConnectionActivationState {
 
 const DEFAULT_POINTER_CACHE_SIZE: u16 = 32;
 
+/// Returns the Revision 2 Bitmap Cache Capability Set advertising the persistent bitmap cache cells
+///
+/// The persistent bitmap cache is only used when the server sends the Bitmap Cache Host Support Capability Set.
+fn persistent_bitmap_cache(
+    config: &Config,
+    server_capability_sets: &[CapabilitySet],
+) -> ConnectorResult<Option<rdp::capability_sets::BitmapCacheRev2>> {
+    use ironrdp_pdu::rdp::capability_sets::{BitmapCacheRev2, CacheFlags, CellInfo};
+
+    if config.persistent_bitmap_cache.is_empty() {
+        return Ok(None);
+    }
+
+    if !server_capability_sets
+        .iter()
+        .any(|capability_set| matches!(capability_set, CapabilitySet::BitmapCacheHostSupport(_)))
+    {
+        debug!("Persistent bitmap cache not supported by the server");
+        return Ok(None);
+    }
+
+    if config.persistent_bitmap_cache.len() > PERSISTENT_KEY_LIST_CELLS {
+        return Err(general_err!("too many persistent bitmap cache cells"));
+    }
+
+    let mut cache_cell_info = [CellInfo::default(); PERSISTENT_KEY_LIST_CELLS];
+
+    for (cell_info, cell) in cache_cell_info.iter_mut().zip(&config.persistent_bitmap_cache) {
+        if u32::try_from(cell.keys.len()).map_or(true, |keys| keys > cell.entries) {
+            return Err(general_err!("more persistent keys than bitmap cache cell entries"));
+        }
+
+        *cell_info = CellInfo {
+            num_entries: cell.entries,
+            is_cache_persistent: true,
+        };
+    }
+
+    Ok(Some(BitmapCacheRev2 {
+        cache_flags: CacheFlags::PERSISTENT_KEYS_EXPECTED_FLAG,
+        num_cell_caches: u8::try_from(config.persistent_bitmap_cache.len())
+            .map_err(|_| general_err!("too many persistent bitmap cache cells"))?,
+        cache_cell_info,
+    }))
+}
+
+/// Returns the Persistent Key List PDUs, or none if there are no keys to send
+fn create_persistent_key_list(config: &Config) -> ConnectorResult<Vec<PersistentKeyListPdu>> {
+    if config.persistent_bitmap_cache.iter().all(|cell| cell.keys.is_empty()) {
+        return Ok(Vec::new());
+    }
+
+    let keys = config
+        .persistent_bitmap_cache
+        .iter()
+        .map(|cell| cell.keys.clone())
+        .collect::<Vec<_>>();
+
+    PersistentKeyListPdu::from_keys(&keys).map_err(ConnectorError::encode)
+}
+
 fn create_client_confirm_active(
     config: &Config,
-    mut server_capability_sets: Vec<CapabilitySet>,
+    server_capability_sets: Vec<CapabilitySet>,
     desktop_size: DesktopSize,
-) -> rdp::capability_sets::ClientConfirmActive {
+    persistent_bitmap_cache: Option<rdp::capability_sets::BitmapCacheRev2>,
+) -> ConnectorResult<rdp::capability_sets::ClientConfirmActive> {
     use ironrdp_pdu::rdp::capability_sets::*;
 
-    server_capability_sets.retain(|capability_set| matches!(capability_set, CapabilitySet::MultiFragmentUpdate(_)));
-
     let lossy_bitmap_compression = config
         .bitmap
         .as_ref()
         .map(|bitmap| bitmap.lossy_compression)
         .unwrap_or(false);
 
+    let remotefx = config.bitmap.as_ref().map_or(true, |bitmap| bitmap.remotefx);
+
     let drawing_flags = if lossy_bitmap_compression {
         BitmapDrawingFlags::ALLOW_SKIP_ALPHA
             | BitmapDrawingFlags::ALLOW_DYNAMIC_COLOR_FIDELITY
@@ -278,49 +361,62 @@ fn create_client_confirm_active(
         BitmapDrawingFlags::ALLOW_SKIP_ALPHA
     };
 
-    server_capability_sets.extend_from_slice(&[
-        CapabilitySet::General(General {
+    let multifragment_update = server_capability_sets
+        .iter()
+        .find_map(|capability_set| match capability_set {
+            CapabilitySet::MultiFragmentUpdate(multifragment_update) => Some(multifragment_update.clone()),
+            _ => None,
+        })
+        .unwrap_or(MultifragmentUpdate {
+            max_request_size: 8 * 1024 * 1024, // 8 MB
+        });
+
+    let capability_sets = CapabilitySetsBuilder::client()
+        .general(General {
             major_platform_type: config.platform,
             extra_flags: GeneralExtraFlags::FASTPATH_OUTPUT_SUPPORTED | GeneralExtraFlags::NO_BITMAP_COMPRESSION_HDR,
             ..Default::default()
-        }),
-        CapabilitySet::Bitmap(Bitmap {
+        })
+        .bitmap(Bitmap {
             pref_bits_per_pix: 32,
             desktop_width: desktop_size.width,
             desktop_height: desktop_size.height,
             // This is required to be true in order for the Microsoft::Windows::RDS::DisplayControl DVC to work.
             desktop_resize_flag: true,
             drawing_flags,
-        }),
-        CapabilitySet::Order(Order::new(
+        })
+        .order(Order::new(
             OrderFlags::NEGOTIATE_ORDER_SUPPORT | OrderFlags::ZERO_BOUNDS_DELTAS_SUPPORT,
             OrderSupportExFlags::empty(),
             0,
             0,
-        )),
-        CapabilitySet::BitmapCache(BitmapCache {
-            caches: [CacheEntry {
-                entries: 0,
-                max_cell_size: 0,
-            }; BITMAP_CACHE_ENTRIES_NUM],
-        }),
-        CapabilitySet::Input(Input {
+        ))
+        .with(match persistent_bitmap_cache {
+            Some(bitmap_cache) => CapabilitySet::BitmapCacheRev2(bitmap_cache),
+            None => CapabilitySet::BitmapCache(BitmapCache {
+                caches: [CacheEntry {
+                    entries: 0,
+                    max_cell_size: 0,
+                }; BITMAP_CACHE_ENTRIES_NUM],
+            }),
+        })
+        .input(Input {
             input_flags: InputFlags::all(),
             keyboard_layout: 0,
             keyboard_type: Some(config.keyboard_type),
             keyboard_subtype: config.keyboard_subtype,
             keyboard_function_key: config.keyboard_functional_keys_count,
             keyboard_ime_filename: config.ime_file_name.clone(),
-        }),
-        CapabilitySet::Pointer(Pointer {
+        })
+        .pointer(Pointer {
             // Pointer cache should be set to non-zero value to enable client-side pointer rendering.
             color_pointer_cache_size: DEFAULT_POINTER_CACHE_SIZE,
             pointer_cache_size: DEFAULT_POINTER_CACHE_SIZE,
-        }),
-        CapabilitySet::Brush(Brush {
+        })
+        .brush(Brush {
             support_level: SupportLevel::Default,
-        }),
-        CapabilitySet::GlyphCache(GlyphCache {
+        })
+        .glyph_cache(GlyphCache {
             glyph_cache: [CacheDefinition {
                 entries: 0,
                 max_cell_size: 0,
@@ -330,62 +426,65 @@ fn create_client_confirm_active(
                 max_cell_size: 0,
             },
             glyph_support_level: GlyphSupportLevel::None,
-        }),
-        CapabilitySet::OffscreenBitmapCache(OffscreenBitmapCache {
+        })
+        .offscreen_bitmap_cache(OffscreenBitmapCache {
             is_supported: false,
             cache_size: 0,
             cache_entries: 0,
-        }),
-        CapabilitySet::VirtualChannel(VirtualChannel {
+        })
+        .virtual_channel(VirtualChannel {
             flags: VirtualChannelFlags::NO_COMPRESSION,
             chunk_size: Some(0), // ignored
-        }),
-        CapabilitySet::Sound(Sound {
+        })
+        .sound(Sound {
             flags: SoundFlags::empty(),
-        }),
-        CapabilitySet::LargePointer(LargePointer {
+        })
+        .large_pointer(LargePointer {
             // Setting `LargePointerSupportFlags::UP_TO_384X384_PIXELS` allows server to send
             // `TS_FP_LARGEPOINTERATTRIBUTE` update messages, which are required for client-side
             // rendering of pointers bigger than 96x96 pixels.
             // `LargePointerSupportFlags::UP_TO_96X96_PIXELS` is needed for proper cursor behavior
             // in Windows 2019 and older
             flags: LargePointerSupportFlags::UP_TO_96X96_PIXELS | LargePointerSupportFlags::UP_TO_384X384_PIXELS,
-        }),
-        CapabilitySet::SurfaceCommands(SurfaceCommands {
+        })
+        .surface_commands(SurfaceCommands {
             flags: CmdFlags::SET_SURFACE_BITS | CmdFlags::STREAM_SURFACE_BITS | CmdFlags::FRAME_MARKER,
-        }),
-        CapabilitySet::BitmapCodecs(BitmapCodecs(vec![Codec {
-            id: 0x03, // RemoteFX
-            property: CodecProperty::RemoteFx(RemoteFxContainer::ClientContainer(RfxClientCapsContainer {
-                capture_flags: CaptureFlags::empty(),
-                caps_data: RfxCaps(RfxCapset(vec![RfxICap {
-                    flags: RfxICapFlags::empty(),
-                    entropy_bits: EntropyBits::Rlgr3,
-                }])),
-            })),
-        }])),
-        CapabilitySet::FrameAcknowledge(FrameAcknowledge {
+        })
+        .bitmap_codecs(BitmapCodecs(if remotefx {
+            vec![Codec {
+                id: 0x03, // RemoteFX
+                property: CodecProperty::RemoteFx(RemoteFxContainer::ClientContainer(RfxClientCapsContainer {
+                    capture_flags: CaptureFlags::empty(),
+                    caps_data: RfxCaps(RfxCapset(vec![RfxICap {
+                        flags: RfxICapFlags::empty(),
+                        entropy_bits: EntropyBits::Rlgr3,
+                    }])),
+                })),
+            }]
+        } else {
+            Vec::new()
+        }))
+        .frame_acknowledge(FrameAcknowledge {
             // FIXME(#447): Revert this to 2 per FreeRDP.
             // This is a temporary hack to fix a resize bug, see:
             // https://github.com/Devolutions/IronRDP/issues/447
             max_unacknowledged_frame_count: 20,
-        }),
-    ]);
+        })
+        .multifragment_update(multifragment_update)
+        .build()
+        .map_err(|e| custom_err!("ClientConfirmActive", e))?;
 
-    if !server_capability_sets
-        .iter()
-        .any(|c| matches!(&c, CapabilitySet::MultiFragmentUpdate(_)))
-    {
-        server_capability_sets.push(CapabilitySet::MultiFragmentUpdate(MultifragmentUpdate {
-            max_request_size: 8 * 1024 * 1024, // 8 MB
-        }));
+    let capabilities_diff = diff(&capability_sets, &server_capability_sets);
+
+    for (requested, negotiated) in &capabilities_diff.changed {
+        debug!(?requested, ?negotiated, "Capability set differs from the server one");
     }
 
-    ClientConfirmActive {
+    Ok(ClientConfirmActive {
         originator_id: SERVER_CHANNEL_ID,
         pdu: DemandActive {
             source_descriptor: "IRONRDP".to_owned(),
-            capability_sets: server_capability_sets,
+            capability_sets,
         },
-    }
+    })
 }
@@ -1,12 +1,14 @@
 use std::mem;
 
 use ironrdp_core::WriteBuf;
+use ironrdp_pdu::gcc;
 use ironrdp_pdu::rdp::capability_sets::SERVER_CHANNEL_ID;
+use ironrdp_pdu::rdp::finalization_messages::PersistentKeyListPdu;
 use ironrdp_pdu::rdp::headers::ShareDataPdu;
 use ironrdp_pdu::rdp::{finalization_messages, server_error_info};
 use ironrdp_pdu::PduHint;
 
-use crate::{legacy, ConnectorResult, Sequence, State, Written};
+use crate::{legacy, ConnectorError, ConnectorErrorKind, ConnectorResult, Sequence, State, Written};
 
 #[derive(Default, Debug, Clone)]
 #[non_exhaustive]
@@ -18,6 +20,7 @@ pub enum ConnectionFinalizationState {
     SendSynchronize,
     SendControlCooperate,
     SendRequestControl,
+    SendPersistentKeyList,
     SendFontList,
 
     WaitForResponse,
@@ -32,6 +35,7 @@ impl State for ConnectionFinalizationState {
             Self::SendSynchronize => "SendSynchronize",
             Self::SendControlCooperate => "SendControlCooperate",
             Self::SendRequestControl => "SendRequestControl",
+            Self::SendPersistentKeyList => "SendPersistentKeyList",
             Self::SendFontList => "SendFontList",
             Self::WaitForResponse => "WaitForResponse",
             Self::Finished => "Finished",
@@ -53,6 +57,12 @@ pub struct ConnectionFinalizationSequence {
     pub state: ConnectionFinalizationState,
     pub io_channel_id: u16,
     pub user_channel_id: u16,
+    /// Monitor layout sent by the server, if any
+    #[cfg_attr(feature = "arbitrary", arbitrary(default))]
+    pub monitor_layout: Option<Vec<gcc::Monitor>>,
+    /// Persistent Key List PDUs sent after the Control (Request Control) PDU
+    #[cfg_attr(feature = "arbitrary", arbitrary(default))]
+    pub persistent_key_list: Vec<PersistentKeyListPdu>,
 }
 
 impl ConnectionFinalizationSequence {
@@ -61,8 +71,17 @@ impl ConnectionFinalizationSequence {
             state: ConnectionFinalizationState::SendSynchronize,
             io_channel_id,
             user_channel_id,
+            monitor_layout: None,
+            persistent_key_list: Vec::new(),
         }
     }
+
+    /// Sets the Persistent Key List PDUs advertising the bitmaps cached during previous connections
+    #[must_use]
+    pub fn with_persistent_key_list(mut self, persistent_key_list: Vec<PersistentKeyListPdu>) -> Self {
+        self.persistent_key_list = persistent_key_list;
+        self
+    }
 }
 
 impl Sequence for ConnectionFinalizationSequence {
@@ -72,6 +91,7 @@ impl Sequence for ConnectionFinalizationSequence {
             ConnectionFinalizationState::SendSynchronize => None,
             ConnectionFinalizationState::SendControlCooperate => None,
             ConnectionFinalizationState::SendRequestControl => None,
+            ConnectionFinalizationState::SendPersistentKeyList => None,
             ConnectionFinalizationState::SendFontList => None,
             ConnectionFinalizationState::WaitForResponse => Some(&ironrdp_pdu::X224_HINT),
             ConnectionFinalizationState::Finished => None,
@@ -133,6 +153,29 @@ impl Sequence for ConnectionFinalizationSequence {
 
                 let written = legacy::encode_share_data(self.user_channel_id, self.io_channel_id, 0, message, output)?;
 
+                let next_state = if self.persistent_key_list.is_empty() {
+                    ConnectionFinalizationState::SendFontList
+                } else {
+                    ConnectionFinalizationState::SendPersistentKeyList
+                };
+
+                (Written::from_size(written)?, next_state)
+            }
+
+            ConnectionFinalizationState::SendPersistentKeyList => {
+                let mut written = 0usize;
+
+                for pdu in mem::take(&mut self.persistent_key_list) {
+                    let message = ShareDataPdu::BitmapCachePersistentList(pdu);
+
+                    debug!(?message, "Send");
+
+                    let size = legacy::encode_share_data(self.user_channel_id, self.io_channel_id, 0, message, output)?;
+                    written = written
+                        .checked_add(size)
+                        .ok_or_else(|| general_err!("persistent key list too large"))?;
+                }
+
                 (Written::from_size(written)?, ConnectionFinalizationState::SendFontList)
             }
 
@@ -202,14 +245,22 @@ impl Sequence for ConnectionFinalizationSequence {
                                 server_error_info::ProtocolIndependentCode::None,
                             ) => ConnectionFinalizationState::WaitForResponse,
                             _ => {
-                                return Err(reason_err!(
+                                return Err(ConnectorError::new(
                                     "ServerSetErrorInfo",
-                                    "server returned error info: {}",
-                                    error_info.description()
+                                    ConnectorErrorKind::ServerErrorInfo(error_info),
                                 ));
                             }
                         }
                     }
+                    ShareDataPdu::MonitorLayout(monitor_layout) => {
+                        // https://learn.microsoft.com/en-us/openspecs/windows_protocols/ms-rdpbcgr/023f1e69-cfe8-4ee6-9ee0-7e759fb4e4ee
+                        //
+                        // The Demand Active PDU is optionally followed by a Monitor Layout PDU
+                        // when the client advertised SUPPORT_MONITOR_LAYOUT_PDU.
+                        debug!(monitors = ?monitor_layout.monitors, "Server Monitor Layout");
+                        self.monitor_layout = Some(monitor_layout.monitors);
+                        ConnectionFinalizationState::WaitForResponse
+                    }
                     ShareDataPdu::FontMap(_) => {
                         // https://learn.microsoft.com/en-us/openspecs/windows_protocols/ms-rdpbcgr/023f1e69-cfe8-4ee6-9ee0-7e759fb4e4ee
                         //
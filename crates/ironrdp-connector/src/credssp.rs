@@ -96,6 +96,7 @@ impl CredsspSequence {
         server_name: ServerName,
         server_public_key: Vec<u8>,
         kerberos_config: Option<KerberosConfig>,
+        restricted_admin: bool,
     ) -> ConnectorResult<(Self, credssp::TsRequest)> {
         let credentials: sspi::Credentials = match &credentials {
             Credentials::UsernamePassword { username, password } => {
@@ -149,7 +150,12 @@ impl CredsspSequence {
         let client = CredSspClient::new(
             server_public_key,
             credentials,
-            credssp::CredSspMode::WithCredentials,
+            // In Restricted Admin mode, the user is authenticated but the credentials are not delegated.
+            if restricted_admin {
+                credssp::CredSspMode::CredentialLess
+            } else {
+                credssp::CredSspMode::WithCredentials
+            },
             credssp::ClientMode::Negotiate(sspi::NegotiateConfig {
                 protocol_config: credssp_config,
                 package_list: None,
@@ -6,7 +6,7 @@ use ironrdp_pdu::rdp;
 use ironrdp_pdu::rdp::headers::ServerDeactivateAll;
 use ironrdp_pdu::x224::X224;
 
-use crate::{ConnectorError, ConnectorErrorExt as _, ConnectorResult};
+use crate::{ConnectorError, ConnectorErrorExt as _, ConnectorErrorKind, ConnectorResult};
 
 pub fn encode_send_data_request<T>(
     initiator_id: u16,
@@ -65,15 +65,13 @@ pub fn decode_send_data_indication(src: &[u8]) -> ConnectorResult<SendDataIndica
                 user_data,
             })
         }
-        McsMessage::DisconnectProviderUltimatum(msg) => Err(reason_err!(
+        McsMessage::DisconnectProviderUltimatum(msg) => Err(ConnectorError::new(
             "decode_send_data_indication",
-            "received disconnect provider ultimatum: {:?}",
-            msg.reason
+            ConnectorErrorKind::Disconnected(msg.reason),
         )),
-        _ => Err(reason_err!(
+        _ => Err(ConnectorError::new(
             "decode_send_data_indication",
-            "unexpected MCS message: {}",
-            ironrdp_core::name(&mcs_msg)
+            ConnectorErrorKind::UnexpectedPdu(ironrdp_core::name(&mcs_msg)),
         )),
     }
 }
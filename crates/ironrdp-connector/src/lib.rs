@@ -21,20 +21,26 @@ use core::any::Any;
 use core::fmt;
 
 pub use channel_connection::{ChannelConnectionSequence, ChannelConnectionState};
-pub use connection::{encode_send_data_request, ClientConnector, ClientConnectorState, ConnectionResult};
+pub use connection::{
+    encode_send_data_request, ClientConnector, ClientConnectorState, ConnectionResult, SecurityInfo, TlsInfo,
+};
 pub use connection_finalization::{ConnectionFinalizationSequence, ConnectionFinalizationState};
-use ironrdp_core::WriteBuf;
 use ironrdp_core::{encode_buf, encode_vec, Encode};
+use ironrdp_core::{DecodeLimits, DecodeMode, DecodeOptions, WriteBuf};
+use ironrdp_error::ErrorCode;
 use ironrdp_pdu::rdp::capability_sets;
 use ironrdp_pdu::rdp::client_info::PerformanceFlags;
+use ironrdp_pdu::rdp::server_error_info::ErrorInfo;
+use ironrdp_pdu::rdp::session_info::ServerAutoReconnect;
 use ironrdp_pdu::x224::X224;
-use ironrdp_pdu::{gcc, x224, PduHint};
-pub use license_exchange::{LicenseExchangeSequence, LicenseExchangeState};
+use ironrdp_pdu::{gcc, mcs, nego, x224, PduHint};
+pub use license_exchange::{LicenseCache, LicenseExchangeSequence, LicenseExchangeState, LicenseKey};
 pub use server_name::ServerName;
 pub use sspi;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DesktopSize {
     pub width: u16,
     pub height: u16,
@@ -42,12 +48,38 @@ pub struct DesktopSize {
 
 #[derive(Debug, Clone, Copy)]
 #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BitmapConfig {
     pub lossy_compression: bool,
     pub color_depth: u32,
+    /// Advertise the RemoteFX codec to the server
+    ///
+    /// The session processor must be built with RemoteFX support (`rfx` feature of `ironrdp-session`).
+    pub remotefx: bool,
+}
+
+/// Monitor advertised to the server
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MonitorConfig {
+    /// Position of the monitor in the virtual desktop, and whether it is the primary monitor
+    pub monitor: gcc::Monitor,
+    /// Physical dimensions, orientation and scale factors of the monitor
+    pub attributes: gcc::ExtendedMonitorInfo,
+}
+
+/// Bitmap cache cell persisted by the client across connections
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PersistentCacheCell {
+    /// Number of entries of the cell
+    pub entries: u32,
+    /// Keys of the bitmaps cached in the cell during previous connections
+    pub keys: Vec<u64>,
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SmartCardIdentity {
     /// DER-encoded X509 certificate
     pub certificate: Vec<u8>,
@@ -61,7 +93,12 @@ pub struct SmartCardIdentity {
     pub private_key: Vec<u8>,
 }
 
+/// Credentials used to authenticate the user
+///
+/// Beware that, with the `serde` feature, the password or PIN is serialized in clear text along with the rest
+/// of the [`Config`].
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Credentials {
     UsernamePassword {
         username: String,
@@ -91,6 +128,7 @@ impl Credentials {
 
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Config {
     /// The initial desktop size to request
     pub desktop_size: DesktopSize,
@@ -163,10 +201,43 @@ pub struct Config {
     pub platform: capability_sets::MajorPlatformType,
     /// If true, the INFO_AUTOLOGON flag is set in the [`ClientInfoPdu`](ironrdp_pdu::rdp::ClientInfoPdu)
     pub autologon: bool,
+    /// Requests the Restricted Admin mode
+    ///
+    /// The user is authenticated with CredSSP, but the credentials are not delegated to the server,
+    /// which can't reuse them. This requires CredSSP, and the connection fails if the server doesn't support it.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub restricted_admin: bool,
+    /// Auto-reconnect cookie received from the server during a previous connection
+    ///
+    /// When set, the client auto-reconnect cookie is sent in the [`ClientInfoPdu`](ironrdp_pdu::rdp::ClientInfoPdu),
+    /// and the server may reconnect to the existing session without requiring the credentials again.
+    pub auto_reconnect: Option<ServerAutoReconnect>,
+    /// How benign protocol violations in the PDUs received from the server are handled
+    #[cfg_attr(feature = "arbitrary", arbitrary(default))]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub decode_mode: DecodeMode,
+    /// Bounds on the resources claimed by the PDUs received from the server
+    #[cfg_attr(feature = "arbitrary", arbitrary(default))]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub decode_limits: DecodeLimits,
+    /// Monitors making up the virtual desktop
+    ///
+    /// When empty, a single monitor of `desktop_size` is assumed and no monitor data is sent to the server.
+    #[cfg_attr(feature = "arbitrary", arbitrary(default))]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub monitors: Vec<MonitorConfig>,
+    /// Bitmap cache cells persisted across connections, at most 5
+    ///
+    /// When not empty and the server supports the Revision 2 Bitmap Cache, the cells are advertised as persistent
+    /// and the keys of the bitmaps cached during previous connections are sent in Persistent Key List PDUs.
+    #[cfg_attr(feature = "arbitrary", arbitrary(default))]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub persistent_bitmap_cache: Vec<PersistentCacheCell>,
 
     // FIXME(@CBenoit): these are client-only options, not part of the connector.
     pub no_server_pointer: bool,
     pub pointer_software_rendering: bool,
+    #[cfg_attr(feature = "serde", serde(default))]
     pub performance_flags: PerformanceFlags,
 }
 
@@ -255,8 +326,94 @@ pub enum ConnectorErrorKind {
     Credssp(sspi::Error),
     Reason(String),
     AccessDenied,
+    /// The server refused the security protocols requested in the X.224 Connection Request
+    Negotiation(nego::FailureCode),
+    /// The server sent an MCS Disconnect Provider Ultimatum
+    Disconnected(mcs::DisconnectReason),
     General,
     Custom,
+    /// The server sent a Set Error Info PDU during the connection sequence
+    ServerErrorInfo(ErrorInfo),
+    /// Standard RDP Security was requested, which is not supported
+    StandardSecurityNotSupported,
+    /// The server selected a security protocol which was not requested
+    SecurityProtocolMismatch {
+        requested: nego::SecurityProtocol,
+        selected: nego::SecurityProtocol,
+    },
+    /// The server confirmed joining a channel which was not requested
+    UnexpectedChannelJoin {
+        channel_id: u16,
+    },
+    /// The server joined a channel with a different ID than requested
+    ChannelIdMismatch {
+        requested: u16,
+        joined: u16,
+    },
+    /// A PDU was received when another one was expected
+    UnexpectedPdu(&'static str),
+}
+
+impl ConnectorErrorKind {
+    /// Returns the error info code matching this error, if any.
+    ///
+    /// This is the code a server would send in a Set Error Info PDU before disconnecting because of this error,
+    /// and the code received by the client for [`ConnectorErrorKind::ServerErrorInfo`].
+    ///
+    /// | Error kind              | Error info                         |
+    /// |-------------------------|------------------------------------|
+    /// | `ServerErrorInfo(info)` | `info`                             |
+    /// | `AccessDenied`          | `ERRINFO_SERVER_DENIED_CONNECTION` |
+    /// | `UnexpectedChannelJoin` | `ERRINFO_INVALIDCHANNELID`         |
+    /// | `ChannelIdMismatch`     | `ERRINFO_INVALIDCHANNELID`         |
+    /// | `UnexpectedPdu`         | `ERRINFO_UNKNOWNPDUTYPE`           |
+    pub fn error_info(&self) -> Option<ErrorInfo> {
+        use ironrdp_pdu::rdp::server_error_info::{ProtocolIndependentCode, RdpSpecificCode};
+
+        match self {
+            ConnectorErrorKind::ServerErrorInfo(info) => Some(*info),
+            ConnectorErrorKind::AccessDenied => Some(ErrorInfo::ProtocolIndependentCode(
+                ProtocolIndependentCode::ServerDeniedConnection,
+            )),
+            ConnectorErrorKind::UnexpectedChannelJoin { .. } | ConnectorErrorKind::ChannelIdMismatch { .. } => {
+                Some(ErrorInfo::RdpSpecificCode(RdpSpecificCode::InvalidChannelId))
+            }
+            ConnectorErrorKind::UnexpectedPdu(_) => Some(ErrorInfo::RdpSpecificCode(RdpSpecificCode::UnknownPduType)),
+            ConnectorErrorKind::Encode(_)
+            | ConnectorErrorKind::Decode(_)
+            | ConnectorErrorKind::Credssp(_)
+            | ConnectorErrorKind::Reason(_)
+            | ConnectorErrorKind::Negotiation(_)
+            | ConnectorErrorKind::Disconnected(_)
+            | ConnectorErrorKind::General
+            | ConnectorErrorKind::Custom
+            | ConnectorErrorKind::StandardSecurityNotSupported
+            | ConnectorErrorKind::SecurityProtocolMismatch { .. } => None,
+        }
+    }
+}
+
+/// Connector error codes are in the `0x200` range.
+impl ErrorCode for ConnectorErrorKind {
+    fn code(&self) -> u32 {
+        match self {
+            ConnectorErrorKind::Encode(_) => 0x201,
+            ConnectorErrorKind::Decode(_) => 0x202,
+            ConnectorErrorKind::Credssp(_) => 0x203,
+            ConnectorErrorKind::Reason(_) => 0x204,
+            ConnectorErrorKind::AccessDenied => 0x205,
+            ConnectorErrorKind::Negotiation(_) => 0x206,
+            ConnectorErrorKind::Disconnected(_) => 0x207,
+            ConnectorErrorKind::General => 0x208,
+            ConnectorErrorKind::Custom => 0x209,
+            ConnectorErrorKind::ServerErrorInfo(_) => 0x20A,
+            ConnectorErrorKind::StandardSecurityNotSupported => 0x20B,
+            ConnectorErrorKind::SecurityProtocolMismatch { .. } => 0x20C,
+            ConnectorErrorKind::UnexpectedChannelJoin { .. } => 0x20D,
+            ConnectorErrorKind::ChannelIdMismatch { .. } => 0x20E,
+            ConnectorErrorKind::UnexpectedPdu(_) => 0x20F,
+        }
+    }
 }
 
 impl fmt::Display for ConnectorErrorKind {
@@ -267,8 +424,25 @@ impl fmt::Display for ConnectorErrorKind {
             ConnectorErrorKind::Credssp(_) => write!(f, "CredSSP"),
             ConnectorErrorKind::Reason(description) => write!(f, "reason: {description}"),
             ConnectorErrorKind::AccessDenied => write!(f, "access denied"),
+            ConnectorErrorKind::Negotiation(code) => write_negotiation_failure(f, *code),
+            ConnectorErrorKind::Disconnected(reason) => write!(f, "disconnected by server: {reason}"),
             ConnectorErrorKind::General => write!(f, "general error"),
             ConnectorErrorKind::Custom => write!(f, "custom error"),
+            ConnectorErrorKind::ServerErrorInfo(info) => {
+                write!(f, "server returned error info: {}", info.description())
+            }
+            ConnectorErrorKind::StandardSecurityNotSupported => write!(f, "standard RDP security is not supported"),
+            ConnectorErrorKind::SecurityProtocolMismatch { requested, selected } => {
+                write!(f, "client advertised {requested}, but server selected {selected}")
+            }
+            ConnectorErrorKind::UnexpectedChannelJoin { channel_id } => {
+                write!(f, "unexpected channel joined: ID {channel_id}")
+            }
+            ConnectorErrorKind::ChannelIdMismatch { requested, joined } => write!(
+                f,
+                "a channel was joined with a different channel ID than requested: requested {requested}, got {joined}"
+            ),
+            ConnectorErrorKind::UnexpectedPdu(name) => write!(f, "unexpected PDU: {name}"),
         }
     }
 }
@@ -281,12 +455,46 @@ impl std::error::Error for ConnectorErrorKind {
             ConnectorErrorKind::Credssp(e) => Some(e),
             ConnectorErrorKind::Reason(_) => None,
             ConnectorErrorKind::AccessDenied => None,
+            ConnectorErrorKind::Negotiation(_) => None,
+            ConnectorErrorKind::Disconnected(_) => None,
             ConnectorErrorKind::Custom => None,
             ConnectorErrorKind::General => None,
+            ConnectorErrorKind::ServerErrorInfo(_) => None,
+            ConnectorErrorKind::StandardSecurityNotSupported => None,
+            ConnectorErrorKind::SecurityProtocolMismatch { .. } => None,
+            ConnectorErrorKind::UnexpectedChannelJoin { .. } => None,
+            ConnectorErrorKind::ChannelIdMismatch { .. } => None,
+            ConnectorErrorKind::UnexpectedPdu(_) => None,
         }
     }
 }
 
+fn write_negotiation_failure(f: &mut fmt::Formatter<'_>, code: nego::FailureCode) -> fmt::Result {
+    use nego::FailureCode;
+
+    let hint = match code {
+        FailureCode::SSL_REQUIRED_BY_SERVER => "server requires TLS, which must be enabled on the client",
+        FailureCode::HYBRID_REQUIRED_BY_SERVER => {
+            "server requires Network Level Authentication (NLA), which must be enabled on the client"
+        }
+        FailureCode::SSL_NOT_ALLOWED_BY_SERVER => {
+            "server only supports Standard RDP Security, which is not supported by the client"
+        }
+        FailureCode::SSL_CERT_NOT_ON_SERVER => {
+            "server has no valid TLS certificate, the server certificate configuration must be fixed"
+        }
+        FailureCode::SSL_WITH_USER_AUTH_REQUIRED_BY_SERVER => {
+            "server requires TLS client certificate authentication, which is not supported by the client"
+        }
+        FailureCode::INCONSISTENT_FLAGS => {
+            "requested security protocols are inconsistent with the security protocol already in use"
+        }
+        _ => return write!(f, "negotiation failure: {code}"),
+    };
+
+    write!(f, "negotiation failure: {hint}")
+}
+
 pub type ConnectorError = ironrdp_error::Error<ConnectorErrorKind>;
 
 pub trait ConnectorErrorExt {
@@ -363,3 +571,18 @@ where
 
     Ok(written)
 }
+
+/// Maps a decoding error, reporting the MCS Disconnect Provider Ultimatum sent by the server instead of the expected PDU, if any
+pub(crate) fn map_decode_error(input: &[u8], error: ironrdp_core::DecodeError) -> ConnectorError {
+    match ironrdp_core::decode::<X224<mcs::DisconnectProviderUltimatum>>(input) {
+        Ok(X224(ultimatum)) => ConnectorError::new("MCS", ConnectorErrorKind::Disconnected(ultimatum.reason)),
+        Err(_) => ConnectorError::decode(error),
+    }
+}
+
+/// Decoding options for the given mode and limits, logging the tolerated protocol violations
+pub fn decode_options(mode: DecodeMode, limits: DecodeLimits) -> DecodeOptions {
+    DecodeOptions::new(mode)
+        .with_limits(limits)
+        .with_warning_callback(|warning| warn!(%warning, "Tolerated protocol violation"))
+}
@@ -1,5 +1,6 @@
 use core::fmt;
 use std::mem;
+use std::sync::Arc;
 
 use ironrdp_core::WriteBuf;
 use ironrdp_pdu::rdp::server_license::{self, LicensePdu, ServerLicenseError};
@@ -9,6 +10,30 @@ use rand_core::{OsRng, RngCore as _};
 use super::legacy;
 use crate::{encode_send_data_request, ConnectorResult, ConnectorResultExt as _, Sequence, State, Written};
 
+/// Identifies the product and scope a client license is issued for
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct LicenseKey {
+    pub scope: String,
+    pub company_name: String,
+    pub product_id: String,
+}
+
+/// Persistent storage for the client licenses issued by the license servers
+///
+/// The stored licenses are presented on the next connections instead of requesting new ones, so that the per-device
+/// client access licenses are honored. Errors are logged, and never abort the license exchange.
+pub trait LicenseCache: Send + Sync + fmt::Debug {
+    /// Returns the license previously issued for `key`, if any
+    fn get_license(&self, key: &LicenseKey) -> ConnectorResult<Option<Vec<u8>>>;
+
+    /// Stores a license issued by the server, replacing any license previously stored for `key`
+    fn store_license(&self, key: &LicenseKey, license: &[u8]) -> ConnectorResult<()>;
+
+    /// Removes a license rejected by the server
+    fn remove_license(&self, key: &LicenseKey) -> ConnectorResult<()>;
+}
+
 #[derive(Default, Debug)]
 #[non_exhaustive]
 #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
@@ -58,15 +83,78 @@ pub struct LicenseExchangeSequence {
     pub io_channel_id: u16,
     pub username: String,
     pub domain: Option<String>,
+    #[cfg_attr(feature = "arbitrary", arbitrary(default))]
+    pub license_cache: Option<Arc<dyn LicenseCache>>,
+    /// The cached license presented to the server, if any
+    presented_license: Option<LicenseKey>,
 }
 
 impl LicenseExchangeSequence {
-    pub fn new(io_channel_id: u16, username: String, domain: Option<String>) -> Self {
+    pub fn new(
+        io_channel_id: u16,
+        username: String,
+        domain: Option<String>,
+        license_cache: Option<Arc<dyn LicenseCache>>,
+    ) -> Self {
         Self {
             state: LicenseExchangeState::NewLicenseRequest,
             io_channel_id,
             username,
             domain,
+            license_cache,
+            presented_license: None,
+        }
+    }
+
+    /// Looks up the cache for a license matching one of the scopes of the request
+    fn find_cached_license(
+        &self,
+        license_request: &server_license::ServerLicenseRequest,
+    ) -> Option<(LicenseKey, Vec<u8>)> {
+        let cache = self.license_cache.as_ref()?;
+
+        license_request.scope_list.iter().find_map(|scope| {
+            let key = LicenseKey {
+                scope: scope.0.clone(),
+                company_name: license_request.product_info.company_name.clone(),
+                product_id: license_request.product_info.product_id.clone(),
+            };
+
+            match cache.get_license(&key) {
+                Ok(license) => license.map(|license| (key, license)),
+                Err(error) => {
+                    warn!(%error, ?key, "Failed to look up the license cache");
+                    None
+                }
+            }
+        })
+    }
+
+    /// Removes the presented license from the cache after the server rejected it
+    fn forget_presented_license(&mut self) {
+        if let (Some(cache), Some(key)) = (&self.license_cache, self.presented_license.take()) {
+            warn!(?key, "The cached license was rejected by the server");
+
+            if let Err(error) = cache.remove_license(&key) {
+                warn!(%error, ?key, "Failed to remove the license from the cache");
+            }
+        }
+    }
+
+    fn store_license(&self, new_license: server_license::NewLicenseInformation) {
+        let Some(cache) = &self.license_cache else {
+            return;
+        };
+
+        let key = LicenseKey {
+            scope: new_license.scope,
+            company_name: new_license.company_name,
+            product_id: new_license.product_id,
+        };
+
+        match cache.store_license(&key, &new_license.license_info) {
+            Ok(()) => debug!(?key, "License stored in the cache"),
+            Err(error) => warn!(%error, ?key, "Failed to store the license in the cache"),
         }
     }
 }
@@ -108,24 +196,51 @@ impl Sequence for LicenseExchangeSequence {
                         let mut premaster_secret = [0u8; server_license::PREMASTER_SECRET_SIZE];
                         OsRng.fill_bytes(&mut premaster_secret);
 
-                        match server_license::ClientNewLicenseRequest::from_server_license_request(
-                            &license_request,
-                            &client_random,
-                            &premaster_secret,
-                            &self.username,
-                            self.domain.as_deref().unwrap_or(""),
-                        ) {
-                            Ok((new_license_request, encryption_data)) => {
-                                trace!(?encryption_data, "Successfully generated Client New License Request");
-                                info!(message = ?new_license_request, "Send");
+                        let hostname = self.domain.as_deref().unwrap_or("");
+
+                        let cached_license = self.find_cached_license(&license_request);
+
+                        let result = match &cached_license {
+                            Some((key, license)) => {
+                                debug!(?key, "Presenting the cached license");
+
+                                server_license::ClientLicenseInfo::from_server_license_request(
+                                    &license_request,
+                                    &client_random,
+                                    &premaster_secret,
+                                    license,
+                                    hostname,
+                                )
+                                .map(|(license_info, encryption_data)| {
+                                    (LicensePdu::from(license_info), encryption_data)
+                                })
+                            }
+                            None => server_license::ClientNewLicenseRequest::from_server_license_request(
+                                &license_request,
+                                &client_random,
+                                &premaster_secret,
+                                &self.username,
+                                hostname,
+                            )
+                            .map(|(new_license_request, encryption_data)| {
+                                (LicensePdu::from(new_license_request), encryption_data)
+                            }),
+                        };
+
+                        match result {
+                            Ok((license_pdu, encryption_data)) => {
+                                trace!(?encryption_data, "Successfully generated the license request");
+                                info!(message = ?license_pdu, "Send");
 
                                 let written = encode_send_data_request::<LicensePdu>(
                                     send_data_indication_ctx.initiator_id,
                                     send_data_indication_ctx.channel_id,
-                                    &new_license_request.into(),
+                                    &license_pdu,
                                     output,
                                 )?;
 
+                                self.presented_license = cached_license.map(|(key, _)| key);
+
                                 (
                                     Written::from_size(written)?,
                                     LicenseExchangeState::PlatformChallenge { encryption_data },
@@ -153,7 +268,7 @@ impl Sequence for LicenseExchangeSequence {
                                     );
                                 }
 
-                                return Err(custom_err!("ClientNewLicenseRequest", error));
+                                return Err(custom_err!("license request", error));
                             }
                         }
                     }
@@ -210,6 +325,8 @@ impl Sequence for LicenseExchangeSequence {
                     }
                     LicensePdu::LicensingErrorMessage(error_message) => {
                         if error_message.error_code != server_license::LicenseErrorCode::StatusValidClient {
+                            self.forget_presented_license();
+
                             return Err(custom_err!(
                                 "LicensingErrorMessage",
                                 ServerLicenseError::from(error_message)
@@ -243,9 +360,18 @@ impl Sequence for LicenseExchangeSequence {
                             .map_err(|e| custom_err!("license verification", e))?;
 
                         debug!("License verified with success");
+
+                        if self.license_cache.is_some() {
+                            match upgrade_license.new_license_info(&encryption_data) {
+                                Ok(new_license) => self.store_license(new_license),
+                                Err(error) => warn!(%error, "Failed to decode the issued license"),
+                            }
+                        }
                     }
                     LicensePdu::LicensingErrorMessage(error_message) => {
                         if error_message.error_code != server_license::LicenseErrorCode::StatusValidClient {
+                            self.forget_presented_license();
+
                             return Err(custom_err!(
                                 "LicensingErrorMessage",
                                 ServerLicenseError::from(error_message)
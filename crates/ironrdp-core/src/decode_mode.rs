@@ -0,0 +1,219 @@
+#[cfg(feature = "alloc")]
+use alloc::sync::Arc;
+use core::fmt;
+
+use crate::{invalid_field_err, DecodeResult};
+
+/// How decoders handle the benign protocol violations found in the wild
+///
+/// Several servers send slightly malformed PDUs (non-zero padding, strings longer than advertised, unknown
+/// capability sets…) which can safely be decoded anyway.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DecodeMode {
+    /// Benign violations are tolerated, and reported as [`DecodeWarning`]s
+    #[default]
+    Lenient,
+    /// Benign violations are decoding errors
+    Strict,
+}
+
+/// Benign protocol violation tolerated in [`DecodeMode::Lenient`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecodeWarning {
+    /// Context in which the violation was found
+    pub context: &'static str,
+    /// Name of the offending field
+    pub field: &'static str,
+    /// Description of the violation
+    pub reason: &'static str,
+}
+
+impl fmt::Display for DecodeWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "[{}] tolerated invalid `{}`: {}",
+            self.context, self.field, self.reason
+        )
+    }
+}
+
+/// Bounds on the resources a decoded PDU may claim
+///
+/// Decoders reject the PDUs exceeding these limits instead of allocating on behalf of the peer. The defaults are
+/// generous enough for any well-behaved peer; servers and proxies exposed to untrusted peers may tighten them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct DecodeLimits {
+    /// Maximum size of a PDU, including the PDUs reassembled from chunks or fragments
+    pub max_pdu_size: usize,
+    /// Maximum number of capability sets in a Demand Active or Confirm Active PDU
+    pub max_capability_sets: usize,
+    /// Maximum number of static virtual channels
+    pub max_channel_count: usize,
+    /// Maximum number of monitors in a monitor layout
+    pub max_monitor_count: usize,
+    /// Maximum size of the clipboard data and file contents
+    pub max_clipboard_data_size: usize,
+    /// Maximum size of a file advertised over the clipboard
+    pub max_file_size: u64,
+}
+
+impl DecodeLimits {
+    /// The default limits
+    pub const DEFAULT: Self = Self {
+        max_pdu_size: 64 * 1024 * 1024,
+        max_capability_sets: 256,
+        max_channel_count: 31,
+        max_monitor_count: 1024,
+        max_clipboard_data_size: 64 * 1024 * 1024,
+        max_file_size: u64::MAX,
+    };
+}
+
+impl Default for DecodeLimits {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// Callback receiving the [`DecodeWarning`]s
+#[cfg(feature = "alloc")]
+pub type DecodeWarningCallback = Arc<dyn Fn(&DecodeWarning) + Send + Sync>;
+
+/// Options applied to the decoding operations performed by [`with_decode_options`]
+#[cfg(feature = "alloc")]
+#[derive(Clone, Default)]
+pub struct DecodeOptions {
+    /// How benign protocol violations are handled
+    pub mode: DecodeMode,
+    /// Bounds on the resources claimed by the decoded PDUs
+    pub limits: DecodeLimits,
+    /// Called for each violation tolerated in [`DecodeMode::Lenient`]
+    pub on_warning: Option<DecodeWarningCallback>,
+}
+
+#[cfg(feature = "alloc")]
+impl DecodeOptions {
+    /// Options for the given mode, without warning callback
+    pub fn new(mode: DecodeMode) -> Self {
+        Self {
+            mode,
+            limits: DecodeLimits::DEFAULT,
+            on_warning: None,
+        }
+    }
+
+    /// Sets the bounds on the resources claimed by the decoded PDUs
+    #[must_use]
+    pub fn with_limits(mut self, limits: DecodeLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Sets the callback receiving the tolerated violations
+    #[must_use]
+    pub fn with_warning_callback(mut self, callback: impl Fn(&DecodeWarning) + Send + Sync + 'static) -> Self {
+        self.on_warning = Some(Arc::new(callback));
+        self
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl fmt::Debug for DecodeOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DecodeOptions")
+            .field("mode", &self.mode)
+            .field("limits", &self.limits)
+            .field("on_warning", &self.on_warning.as_ref().map(|_| "<callback>"))
+            .finish()
+    }
+}
+
+#[cfg(feature = "std")]
+std::thread_local! {
+    static CURRENT_OPTIONS: core::cell::RefCell<DecodeOptions> = core::cell::RefCell::new(DecodeOptions::default());
+}
+
+/// Runs `f` with the given decoding options on the current thread
+///
+/// The previous options are restored afterwards, even if `f` panics.
+#[cfg(feature = "std")]
+pub fn with_decode_options<R>(options: DecodeOptions, f: impl FnOnce() -> R) -> R {
+    struct RestoreGuard(Option<DecodeOptions>);
+
+    impl Drop for RestoreGuard {
+        fn drop(&mut self) {
+            if let Some(previous) = self.0.take() {
+                CURRENT_OPTIONS.with(|current| *current.borrow_mut() = previous);
+            }
+        }
+    }
+
+    let previous = CURRENT_OPTIONS.with(|current| current.replace(options));
+    let _guard = RestoreGuard(Some(previous));
+
+    f()
+}
+
+/// Returns the decoding mode of the current thread
+///
+/// Without the `std` feature, decoding is always lenient.
+pub fn decode_mode() -> DecodeMode {
+    #[cfg(feature = "std")]
+    {
+        CURRENT_OPTIONS.with(|current| current.borrow().mode)
+    }
+
+    #[cfg(not(feature = "std"))]
+    {
+        DecodeMode::Lenient
+    }
+}
+
+/// Returns the decoding limits of the current thread
+///
+/// Without the `std` feature, the default limits always apply.
+pub fn decode_limits() -> DecodeLimits {
+    #[cfg(feature = "std")]
+    {
+        CURRENT_OPTIONS.with(|current| current.borrow().limits)
+    }
+
+    #[cfg(not(feature = "std"))]
+    {
+        DecodeLimits::DEFAULT
+    }
+}
+
+/// Handles a benign protocol violation according to the current [`DecodeMode`]
+///
+/// Returns an "invalid field" error in strict mode, and reports a [`DecodeWarning`] otherwise.
+pub fn tolerate_violation(context: &'static str, field: &'static str, reason: &'static str) -> DecodeResult<()> {
+    match decode_mode() {
+        DecodeMode::Strict => Err(invalid_field_err(context, field, reason)),
+        DecodeMode::Lenient => {
+            report_warning(&DecodeWarning { context, field, reason });
+            Ok(())
+        }
+    }
+}
+
+fn report_warning(warning: &DecodeWarning) {
+    #[cfg(feature = "std")]
+    {
+        // The callback is cloned out so it may decode PDUs itself.
+        let callback = CURRENT_OPTIONS.with(|current| current.borrow().on_warning.clone());
+
+        if let Some(callback) = callback {
+            callback(warning);
+        }
+    }
+
+    #[cfg(not(feature = "std"))]
+    {
+        let _ = warning;
+    }
+}
@@ -13,19 +13,25 @@ mod macros;
 mod as_any;
 mod cursor;
 mod decode;
+mod decode_mode;
 mod encode;
 mod error;
 mod into_owned;
 #[cfg(feature = "alloc")]
 mod write_buf;
+#[cfg(feature = "alloc")]
+mod write_buf_pool;
 
 // Flat API hierarchy of common traits and types
 
 pub use self::as_any::*;
 pub use self::cursor::*;
 pub use self::decode::*;
+pub use self::decode_mode::*;
 pub use self::encode::*;
 pub use self::error::*;
 pub use self::into_owned::*;
 #[cfg(feature = "alloc")]
 pub use self::write_buf::*;
+#[cfg(feature = "alloc")]
+pub use self::write_buf_pool::*;
@@ -391,3 +391,34 @@ macro_rules! cast_int {
         $crate::cast_int!($crate::function!(), $field, $len)
     }};
 }
+
+/// Handles a benign protocol violation according to the current [`DecodeMode`](crate::DecodeMode).
+///
+/// Expands to a call to [`tolerate_violation`](crate::tolerate_violation), which returns an error
+/// in strict mode and reports a warning in lenient mode.
+///
+/// # Examples
+///
+/// ```
+/// use ironrdp_core::{tolerate_violation, DecodeResult};
+///
+/// fn check_padding(padding: u8) -> DecodeResult<()> {
+///     if padding != 0 {
+///         tolerate_violation!("padding", "not zeroed")?;
+///     }
+///     Ok(())
+/// }
+/// ```
+///
+/// # Note
+///
+/// If the context is not provided, it will use the current function name.
+#[macro_export]
+macro_rules! tolerate_violation {
+    ( $context:expr, $field:expr , $reason:expr $(,)? ) => {{
+        $crate::tolerate_violation($context, $field, $reason)
+    }};
+    ( $field:expr , $reason:expr $(,)? ) => {{
+        $crate::tolerate_violation!($crate::function!(), $field, $reason)
+    }};
+}
@@ -0,0 +1,74 @@
+use alloc::vec::Vec;
+
+use crate::WriteBuf;
+
+/// Default maximum number of buffers kept around by a [`WriteBufPool`].
+const DEFAULT_MAX_POOLED: usize = 16;
+
+/// Pool of reusable [`WriteBuf`]s, for encoding messages without allocating a new buffer each time.
+///
+/// Ownership rules:
+///
+/// - A buffer obtained with [`WriteBufPool::checkout`] is owned by the caller, and is always empty.
+/// - A buffer is reused only once returned with [`WriteBufPool::checkin`]. Dropping it instead is fine,
+///   and simply releases its memory.
+/// - Only the filled region of a reused buffer is meaningful: [`WriteBuf::into_inner`] may return
+///   stale bytes past [`WriteBuf::filled_len`].
+/// - Buffers are cleared when returned to the pool, which shrinks the ones that grew big,
+///   so that the memory retained by the pool stays bounded.
+///
+/// The pool is not synchronized: it is meant to be owned by a single encoder (e.g.: a channel processor
+/// or a connection task).
+pub struct WriteBufPool {
+    free: Vec<WriteBuf>,
+    max_pooled: usize,
+}
+
+impl WriteBufPool {
+    /// Constructs a new, empty pool, keeping at most 16 buffers around.
+    pub const fn new() -> Self {
+        Self::with_max_pooled(DEFAULT_MAX_POOLED)
+    }
+
+    /// Constructs a new, empty pool, keeping at most `max_pooled` buffers around.
+    pub const fn with_max_pooled(max_pooled: usize) -> Self {
+        Self {
+            free: Vec::new(),
+            max_pooled,
+        }
+    }
+
+    /// Takes an empty buffer from the pool, or allocates a new one if the pool is empty.
+    pub fn checkout(&mut self) -> WriteBuf {
+        self.free.pop().unwrap_or_default()
+    }
+
+    /// Returns a buffer to the pool, so that its memory is reused by a later [`WriteBufPool::checkout`].
+    ///
+    /// The buffer is dropped if the pool is full.
+    pub fn checkin(&mut self, mut buf: WriteBuf) {
+        if self.free.len() < self.max_pooled {
+            buf.clear();
+            self.free.push(buf);
+        }
+    }
+
+    /// Runs `f` with a scratch buffer from the pool, returning the buffer to the pool afterwards.
+    pub fn with_scratch<T>(&mut self, f: impl FnOnce(&mut WriteBuf) -> T) -> T {
+        let mut buf = self.checkout();
+        let result = f(&mut buf);
+        self.checkin(buf);
+        result
+    }
+
+    /// Returns the number of buffers currently available in the pool.
+    pub fn available(&self) -> usize {
+        self.free.len()
+    }
+}
+
+impl Default for WriteBufPool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
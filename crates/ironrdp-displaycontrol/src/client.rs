@@ -1,5 +1,5 @@
 use crate::{
-    pdu::{DisplayControlCapabilities, DisplayControlMonitorLayout, DisplayControlPdu},
+    pdu::{DisplayControlCapabilities, DisplayControlMonitorLayout, DisplayControlPdu, MonitorLayoutEntry},
     CHANNEL_NAME,
 };
 use ironrdp_core::{impl_as_any, Decode, EncodeResult, ReadCursor};
@@ -63,6 +63,19 @@ impl DisplayControlClient {
         debug!(?pdu, "Sending monitor layout");
         encode_dvc_messages(channel_id, vec![Box::new(pdu)], ChannelFlags::empty())
     }
+
+    /// Builds a [`DisplayControlPdu::MonitorLayout`] with the given `monitors`, and wraps it as an [`SvcMessage`].
+    ///
+    /// Exactly one of the `monitors` must be primary.
+    pub fn encode_monitor_layout(
+        &self,
+        channel_id: u32,
+        monitors: &[MonitorLayoutEntry],
+    ) -> EncodeResult<Vec<SvcMessage>> {
+        let pdu: DisplayControlPdu = DisplayControlMonitorLayout::new(monitors)?.into();
+        debug!(?pdu, "Sending monitor layout");
+        encode_dvc_messages(channel_id, vec![Box::new(pdu)], ChannelFlags::empty())
+    }
 }
 
 impl_as_any!(DisplayControlClient);
@@ -3,7 +3,8 @@
 //! [1]: https://learn.microsoft.com/en-us/openspecs/windows_protocols/ms-rdpedisp/d2954508-f487-48bc-8731-39743e0854a9
 
 use ironrdp_core::{
-    ensure_fixed_part_size, invalid_field_err, Decode, DecodeResult, Encode, EncodeResult, ReadCursor, WriteCursor,
+    decode_limits, ensure_fixed_part_size, invalid_field_err, Decode, DecodeResult, Encode, EncodeResult, ReadCursor,
+    WriteCursor,
 };
 use ironrdp_dvc::DvcEncode;
 use tracing::warn;
@@ -336,6 +337,13 @@ impl<'de> Decode<'de> for DisplayControlMonitorLayout {
             return Err(invalid_field_err!("NumMonitors", "Too many monitors"));
         }
 
+        if usize::try_from(num_monitors).unwrap() > decode_limits().max_monitor_count {
+            return Err(invalid_field_err!(
+                "NumMonitors",
+                "Monitor count exceeds the decode limit"
+            ));
+        }
+
         let mut monitors = Vec::with_capacity(usize::try_from(num_monitors).unwrap());
         for _ in 0..num_monitors {
             let monitor = MonitorLayoutEntry::decode(src)?;
@@ -169,6 +169,6 @@ impl SvcProcessor for DrdynvcClient {
 
 impl SvcClientProcessor for DrdynvcClient {}
 
-fn decode_dvc_message(user_data: &[u8]) -> DecodeResult<DrdynvcServerPdu> {
+fn decode_dvc_message(user_data: &[u8]) -> DecodeResult<DrdynvcServerPdu<'_>> {
     DrdynvcServerPdu::decode(&mut ReadCursor::new(user_data))
 }
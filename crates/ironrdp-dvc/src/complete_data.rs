@@ -1,6 +1,7 @@
+use alloc::borrow::Cow;
 use alloc::vec::Vec;
 use core::cmp;
-use ironrdp_core::{cast_length, invalid_field_err, DecodeResult};
+use ironrdp_core::{cast_length, decode_limits, invalid_field_err, DecodeResult};
 
 use crate::pdu::{DataFirstPdu, DataPdu, DrdynvcDataPdu};
 
@@ -18,16 +19,30 @@ impl CompleteData {
         }
     }
 
-    pub(crate) fn process_data(&mut self, pdu: DrdynvcDataPdu) -> DecodeResult<Option<Vec<u8>>> {
+    /// Returns the complete message once all its fragments are received
+    ///
+    /// Messages which are not fragmented are returned as is, without copying them.
+    pub(crate) fn process_data<'a>(&mut self, pdu: DrdynvcDataPdu<'a>) -> DecodeResult<Option<Cow<'a, [u8]>>> {
         match pdu {
             DrdynvcDataPdu::DataFirst(data_first) => self.process_data_first_pdu(data_first),
             DrdynvcDataPdu::Data(data) => self.process_data_pdu(data),
         }
     }
 
-    fn process_data_first_pdu(&mut self, data_first: DataFirstPdu) -> DecodeResult<Option<Vec<u8>>> {
+    fn process_data_first_pdu<'a>(&mut self, data_first: DataFirstPdu<'a>) -> DecodeResult<Option<Cow<'a, [u8]>>> {
         let total_data_size: DecodeResult<_> = cast_length!("DataFirstPdu::length", data_first.length);
         let total_data_size = total_data_size?;
+
+        if total_data_size > decode_limits().max_pdu_size {
+            self.total_size = 0;
+            self.data.clear();
+
+            return Err(invalid_field_err!(
+                "DataFirstPdu::length",
+                "PDU size exceeds the decode limit"
+            ));
+        }
+
         if self.total_size != 0 || !self.data.is_empty() {
             error!("Incomplete DVC message, it will be skipped");
 
@@ -38,13 +53,13 @@ impl CompleteData {
             Ok(Some(data_first.data))
         } else {
             self.total_size = total_data_size;
-            self.data = data_first.data;
+            self.data = data_first.data.into_owned();
 
             Ok(None)
         }
     }
 
-    fn process_data_pdu(&mut self, mut data: DataPdu) -> DecodeResult<Option<Vec<u8>>> {
+    fn process_data_pdu<'a>(&mut self, data: DataPdu<'a>) -> DecodeResult<Option<Cow<'a, [u8]>>> {
         if self.total_size == 0 && self.data.is_empty() {
             // message is not fragmented
             return Ok(Some(data.data));
@@ -56,14 +71,14 @@ impl CompleteData {
                 match actual_data_length.cmp(&(self.total_size)) {
                     cmp::Ordering::Less => {
                         // this is one of the fragmented messages, just append it
-                        self.data.append(&mut data.data);
+                        self.data.extend_from_slice(&data.data);
                         Ok(None)
                     }
                     cmp::Ordering::Equal => {
                         // this is the last fragmented message, need to return the whole reassembled message
                         self.total_size = 0;
-                        self.data.append(&mut data.data);
-                        Ok(Some(self.data.drain(..).collect()))
+                        self.data.extend_from_slice(&data.data);
+                        Ok(Some(Cow::Owned(core::mem::take(&mut self.data))))
                     }
                     cmp::Ordering::Greater => {
                         error!("Actual DVC message size is grater than expected total DVC message size");
@@ -18,6 +18,7 @@ use alloc::vec::Vec;
 pub use ironrdp_pdu;
 use ironrdp_core::{assert_obj_safe, cast_length, encode_vec, other_err, AsAny, Encode, EncodeResult};
 use ironrdp_pdu::{decode_err, pdu_other_err, PduResult};
+use ironrdp_svc::wire_log::ChannelWireLog;
 use ironrdp_svc::{self, SvcMessage};
 
 mod complete_data;
@@ -31,6 +32,9 @@ pub use server::*;
 
 pub mod pdu;
 
+#[cfg(feature = "testing")]
+pub mod testing;
+
 /// Represents a message that, when encoded, forms a complete PDU for a given dynamic virtual channel.
 /// This means a message that is ready to be wrapped in [`dvc::CommonPdu::DataFirst`] and [`dvc::CommonPdu::Data`] PDUs
 /// (being split into multiple of such PDUs if necessary).
@@ -102,6 +106,7 @@ pub fn encode_dvc_messages(
 pub struct DynamicVirtualChannel {
     channel_processor: Box<dyn DvcProcessor + Send>,
     complete_data: CompleteData,
+    wire_log: ChannelWireLog,
     /// The channel ID assigned by the server.
     ///
     /// This field is `None` until the server assigns a channel ID.
@@ -113,6 +118,7 @@ impl DynamicVirtualChannel {
         Self {
             channel_processor: Box::new(handler),
             complete_data: CompleteData::new(),
+            wire_log: ChannelWireLog::new(),
             channel_id: None,
         }
     }
@@ -131,22 +137,40 @@ impl DynamicVirtualChannel {
 
     fn start(&mut self) -> PduResult<Vec<DvcMessage>> {
         if let Some(channel_id) = self.channel_id {
-            self.channel_processor.start(channel_id)
+            let _span = debug_span!("dvc", channel = self.channel_name(), channel_id).entered();
+
+            let messages = self.channel_processor.start(channel_id)?;
+            self.log_sent(&messages);
+
+            Ok(messages)
         } else {
             Err(pdu_other_err!("DynamicVirtualChannel::start", "channel ID not set"))
         }
     }
 
-    fn process(&mut self, pdu: DrdynvcDataPdu) -> PduResult<Vec<DvcMessage>> {
+    fn process(&mut self, pdu: DrdynvcDataPdu<'_>) -> PduResult<Vec<DvcMessage>> {
         let channel_id = pdu.channel_id();
+        let _span = debug_span!("dvc", channel = self.channel_name(), channel_id).entered();
+
         let complete_data = self.complete_data.process_data(pdu).map_err(|e| decode_err!(e))?;
         if let Some(complete_data) = complete_data {
-            self.channel_processor.process(channel_id, &complete_data)
+            self.wire_log
+                .received(self.channel_processor.channel_name(), &complete_data);
+            let messages = self.channel_processor.process(channel_id, &complete_data)?;
+            self.log_sent(&messages);
+            Ok(messages)
         } else {
             Ok(Vec::new())
         }
     }
 
+    fn log_sent(&mut self, messages: &[DvcMessage]) {
+        for message in messages {
+            self.wire_log
+                .sent(self.channel_processor.channel_name(), message.as_ref());
+        }
+    }
+
     fn channel_name(&self) -> &str {
         self.channel_processor.channel_name()
     }
@@ -1,10 +1,11 @@
+use alloc::borrow::Cow;
 use alloc::format;
 use core::fmt;
 
-use crate::{DynamicChannelId, String, Vec};
+use crate::{DynamicChannelId, String};
 use ironrdp_core::{
     cast_length, ensure_fixed_part_size, ensure_size, invalid_field_err, unsupported_value_err, Decode, DecodeError,
-    DecodeResult, Encode, EncodeResult, ReadCursor, WriteCursor,
+    DecodeResult, Encode, EncodeResult, IntoOwned, ReadCursor, WriteCursor,
 };
 use ironrdp_pdu::utils::{
     checked_sum, encoded_str_len, read_string_from_cursor, strict_sum, write_string_to_cursor, CharacterSet,
@@ -12,13 +13,15 @@ use ironrdp_pdu::utils::{
 use ironrdp_svc::SvcEncode;
 
 /// Dynamic Virtual Channel PDU's that are sent by both client and server.
+///
+/// The data is borrowed from the decoded buffer, use [`IntoOwned::into_owned`] to detach it.
 #[derive(Debug, PartialEq)]
-pub enum DrdynvcDataPdu {
-    DataFirst(DataFirstPdu),
-    Data(DataPdu),
+pub enum DrdynvcDataPdu<'a> {
+    DataFirst(DataFirstPdu<'a>),
+    Data(DataPdu<'a>),
 }
 
-impl DrdynvcDataPdu {
+impl DrdynvcDataPdu<'_> {
     /// Maximum size of the `data` field in `DrdynvcDataPdu`.
     pub const MAX_DATA_SIZE: usize = 1590;
 
@@ -30,7 +33,18 @@ impl DrdynvcDataPdu {
     }
 }
 
-impl Encode for DrdynvcDataPdu {
+impl IntoOwned for DrdynvcDataPdu<'_> {
+    type Owned = DrdynvcDataPdu<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        match self {
+            DrdynvcDataPdu::DataFirst(pdu) => DrdynvcDataPdu::DataFirst(pdu.into_owned()),
+            DrdynvcDataPdu::Data(pdu) => DrdynvcDataPdu::Data(pdu.into_owned()),
+        }
+    }
+}
+
+impl Encode for DrdynvcDataPdu<'_> {
     fn encode(&self, dst: &mut WriteCursor<'_>) -> EncodeResult<()> {
         match self {
             DrdynvcDataPdu::DataFirst(pdu) => pdu.encode(dst),
@@ -55,14 +69,27 @@ impl Encode for DrdynvcDataPdu {
 
 /// Dynamic Virtual Channel PDU's that are sent by the client.
 #[derive(Debug, PartialEq)]
-pub enum DrdynvcClientPdu {
+pub enum DrdynvcClientPdu<'a> {
     Capabilities(CapabilitiesResponsePdu),
     Create(CreateResponsePdu),
     Close(ClosePdu),
-    Data(DrdynvcDataPdu),
+    Data(DrdynvcDataPdu<'a>),
+}
+
+impl IntoOwned for DrdynvcClientPdu<'_> {
+    type Owned = DrdynvcClientPdu<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        match self {
+            DrdynvcClientPdu::Capabilities(pdu) => DrdynvcClientPdu::Capabilities(pdu),
+            DrdynvcClientPdu::Create(pdu) => DrdynvcClientPdu::Create(pdu),
+            DrdynvcClientPdu::Close(pdu) => DrdynvcClientPdu::Close(pdu),
+            DrdynvcClientPdu::Data(pdu) => DrdynvcClientPdu::Data(pdu.into_owned()),
+        }
+    }
 }
 
-impl Encode for DrdynvcClientPdu {
+impl Encode for DrdynvcClientPdu<'_> {
     fn encode(&self, dst: &mut WriteCursor<'_>) -> EncodeResult<()> {
         match self {
             DrdynvcClientPdu::Capabilities(pdu) => pdu.encode(dst),
@@ -91,8 +118,8 @@ impl Encode for DrdynvcClientPdu {
     }
 }
 
-impl Decode<'_> for DrdynvcClientPdu {
-    fn decode(src: &mut ReadCursor<'_>) -> DecodeResult<Self> {
+impl<'de> Decode<'de> for DrdynvcClientPdu<'de> {
+    fn decode(src: &mut ReadCursor<'de>) -> DecodeResult<Self> {
         let header = Header::decode(src)?;
         match header.cmd {
             Cmd::Create => Ok(Self::Create(CreateResponsePdu::decode(header, src)?)),
@@ -109,14 +136,27 @@ impl Decode<'_> for DrdynvcClientPdu {
 
 /// Dynamic Virtual Channel PDU's that are sent by the server.
 #[derive(Debug, PartialEq)]
-pub enum DrdynvcServerPdu {
+pub enum DrdynvcServerPdu<'a> {
     Capabilities(CapabilitiesRequestPdu),
     Create(CreateRequestPdu),
     Close(ClosePdu),
-    Data(DrdynvcDataPdu),
+    Data(DrdynvcDataPdu<'a>),
 }
 
-impl Encode for DrdynvcServerPdu {
+impl IntoOwned for DrdynvcServerPdu<'_> {
+    type Owned = DrdynvcServerPdu<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        match self {
+            DrdynvcServerPdu::Capabilities(pdu) => DrdynvcServerPdu::Capabilities(pdu),
+            DrdynvcServerPdu::Create(pdu) => DrdynvcServerPdu::Create(pdu),
+            DrdynvcServerPdu::Close(pdu) => DrdynvcServerPdu::Close(pdu),
+            DrdynvcServerPdu::Data(pdu) => DrdynvcServerPdu::Data(pdu.into_owned()),
+        }
+    }
+}
+
+impl Encode for DrdynvcServerPdu<'_> {
     fn encode(&self, dst: &mut WriteCursor<'_>) -> EncodeResult<()> {
         match self {
             DrdynvcServerPdu::Data(pdu) => pdu.encode(dst),
@@ -145,8 +185,8 @@ impl Encode for DrdynvcServerPdu {
     }
 }
 
-impl Decode<'_> for DrdynvcServerPdu {
-    fn decode(src: &mut ReadCursor<'_>) -> DecodeResult<Self> {
+impl<'de> Decode<'de> for DrdynvcServerPdu<'de> {
+    fn decode(src: &mut ReadCursor<'de>) -> DecodeResult<Self> {
         let header = Header::decode(src)?;
         match header.cmd {
             Cmd::Create => Ok(Self::Create(CreateRequestPdu::decode(header, src)?)),
@@ -162,9 +202,9 @@ impl Decode<'_> for DrdynvcServerPdu {
 }
 
 // Dynamic virtual channel PDU's are sent over a static virtual channel, so they are `SvcEncode`.
-impl SvcEncode for DrdynvcDataPdu {}
-impl SvcEncode for DrdynvcClientPdu {}
-impl SvcEncode for DrdynvcServerPdu {}
+impl SvcEncode for DrdynvcDataPdu<'_> {}
+impl SvcEncode for DrdynvcClientPdu<'_> {}
+impl SvcEncode for DrdynvcServerPdu<'_> {}
 
 /// [2.2] Message Syntax
 ///
@@ -279,29 +319,42 @@ impl From<Cmd> for String {
 ///
 /// [2.2.3.1]: https://learn.microsoft.com/en-us/openspecs/windows_protocols/ms-rdpedyc/69377767-56a6-4ab8-996b-7758676e9261
 #[derive(Debug, PartialEq)]
-pub struct DataFirstPdu {
+pub struct DataFirstPdu<'a> {
     header: Header,
     pub channel_id: DynamicChannelId,
     /// Length is the *total* length of the data to be sent, including the length
     /// of the data that will be sent by subsequent DVC_DATA PDUs.
     pub length: u32,
     /// Data is just the data to be sent in this PDU.
-    pub data: Vec<u8>,
+    pub data: Cow<'a, [u8]>,
 }
 
-impl DataFirstPdu {
+impl IntoOwned for DataFirstPdu<'_> {
+    type Owned = DataFirstPdu<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        DataFirstPdu {
+            header: self.header,
+            channel_id: self.channel_id,
+            length: self.length,
+            data: Cow::Owned(self.data.into_owned()),
+        }
+    }
+}
+
+impl<'a> DataFirstPdu<'a> {
     /// Create a new `DataFirstPdu` with the given `channel_id`, `length`, and `data`.
     ///
     /// `length` is the *total* length of the data to be sent, including the length
     /// of the data that will be sent by subsequent `DataPdu`s.
     ///
     /// `data` is just the data to be sent in this PDU.
-    pub fn new(channel_id: DynamicChannelId, total_length: u32, data: Vec<u8>) -> Self {
+    pub fn new(channel_id: DynamicChannelId, total_length: u32, data: impl Into<Cow<'a, [u8]>>) -> Self {
         Self {
             header: Header::new(channel_id, total_length, Cmd::DataFirst),
             channel_id,
             length: total_length,
-            data,
+            data: data.into(),
         }
     }
 
@@ -321,12 +374,12 @@ impl DataFirstPdu {
         }
     }
 
-    fn decode(header: Header, src: &mut ReadCursor<'_>) -> DecodeResult<Self> {
+    fn decode(header: Header, src: &mut ReadCursor<'a>) -> DecodeResult<Self> {
         let fixed_part_size = checked_sum(&[header.cb_id.size_of_val(), header.sp.size_of_val()])?;
         ensure_size!(in: src, size: fixed_part_size);
         let channel_id = header.cb_id.decode_val(src)?;
         let length = header.sp.decode_val(src)?;
-        let data = src.read_remaining().to_vec();
+        let data = Cow::Borrowed(src.read_remaining());
         Ok(Self {
             header,
             channel_id,
@@ -431,25 +484,37 @@ impl From<FieldType> for u8 {
 ///
 /// [2.2.3.2]: https://learn.microsoft.com/en-us/openspecs/windows_protocols/ms-rdpedyc/15b59886-db44-47f1-8da3-47c8fcd82803
 #[derive(Debug, PartialEq)]
-pub struct DataPdu {
+pub struct DataPdu<'a> {
     header: Header,
     pub channel_id: DynamicChannelId,
-    pub data: Vec<u8>,
+    pub data: Cow<'a, [u8]>,
+}
+
+impl IntoOwned for DataPdu<'_> {
+    type Owned = DataPdu<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        DataPdu {
+            header: self.header,
+            channel_id: self.channel_id,
+            data: Cow::Owned(self.data.into_owned()),
+        }
+    }
 }
 
-impl DataPdu {
-    pub fn new(channel_id: DynamicChannelId, data: Vec<u8>) -> Self {
+impl<'a> DataPdu<'a> {
+    pub fn new(channel_id: DynamicChannelId, data: impl Into<Cow<'a, [u8]>>) -> Self {
         Self {
             header: Header::new(channel_id, 0, Cmd::Data),
             channel_id,
-            data,
+            data: data.into(),
         }
     }
 
-    fn decode(header: Header, src: &mut ReadCursor<'_>) -> DecodeResult<Self> {
+    fn decode(header: Header, src: &mut ReadCursor<'a>) -> DecodeResult<Self> {
         ensure_size!(in: src, size: header.cb_id.size_of_val());
         let channel_id = header.cb_id.decode_val(src)?;
-        let data = src.read_remaining().to_vec();
+        let data = Cow::Borrowed(src.read_remaining());
         Ok(Self {
             header,
             channel_id,
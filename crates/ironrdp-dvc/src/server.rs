@@ -1,7 +1,7 @@
 use crate::pdu::{
-    CapabilitiesRequestPdu, CapsVersion, CreateRequestPdu, CreationStatus, DrdynvcClientPdu, DrdynvcServerPdu,
+    CapabilitiesRequestPdu, CapsVersion, ClosePdu, CreateRequestPdu, CreationStatus, DrdynvcClientPdu, DrdynvcServerPdu,
 };
-use crate::{encode_dvc_messages, CompleteData, DvcProcessor};
+use crate::{encode_dvc_messages, CompleteData, DvcMessage, DvcProcessor, DynamicChannelId};
 use alloc::boxed::Box;
 use alloc::vec::Vec;
 use core::fmt;
@@ -9,6 +9,7 @@ use ironrdp_core::Decode as _;
 use ironrdp_core::{cast_length, impl_as_any, DecodeResult};
 use ironrdp_core::{invalid_field_err, ReadCursor};
 use ironrdp_pdu::{self as pdu, decode_err, encode_err, pdu_other_err};
+use ironrdp_svc::wire_log::ChannelWireLog;
 use ironrdp_svc::{ChannelFlags, CompressionCondition, SvcMessage, SvcProcessor, SvcServerProcessor};
 use pdu::gcc::ChannelName;
 use pdu::PduResult;
@@ -28,6 +29,7 @@ struct DynamicChannel {
     state: ChannelState,
     processor: Box<dyn DvcProcessor>,
     complete_data: CompleteData,
+    wire_log: ChannelWireLog,
 }
 
 impl DynamicChannel {
@@ -39,6 +41,13 @@ impl DynamicChannel {
             state: ChannelState::Closed,
             processor: Box::new(processor),
             complete_data: CompleteData::new(),
+            wire_log: ChannelWireLog::new(),
+        }
+    }
+
+    fn log_sent(&mut self, messages: &[DvcMessage]) {
+        for message in messages {
+            self.wire_log.sent(self.processor.channel_name(), message.as_ref());
         }
     }
 }
@@ -47,8 +56,12 @@ impl DynamicChannel {
 /// It adds support for dynamic virtual channels (DVC).
 pub struct DrdynvcServer {
     dynamic_channels: Slab<DynamicChannel>,
+    on_channel_created: Option<OnChannelCreated>,
 }
 
+/// Callback invoked with the channel name, the channel ID and the status of each dynamic channel creation
+type OnChannelCreated = Box<dyn FnMut(&str, DynamicChannelId, CreationStatus) + Send>;
+
 impl fmt::Debug for DrdynvcServer {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "DrdynvcServer([")?;
@@ -70,6 +83,7 @@ impl DrdynvcServer {
     pub fn new() -> Self {
         Self {
             dynamic_channels: Slab::new(),
+            on_channel_created: None,
         }
     }
 
@@ -84,14 +98,54 @@ impl DrdynvcServer {
         self
     }
 
-    fn channel_by_id(&mut self, id: u32) -> DecodeResult<&mut DynamicChannel> {
-        let id = cast_length!("DRDYNVC", "", id)?;
+    /// Registers a callback invoked each time the client answers a dynamic channel creation request
+    #[must_use]
+    pub fn with_creation_callback<F>(mut self, callback: F) -> Self
+    where
+        F: FnMut(&str, DynamicChannelId, CreationStatus) + Send + 'static,
+    {
+        self.on_channel_created = Some(Box::new(callback));
+        self
+    }
+
+    /// Closes all the opened dynamic channels
+    ///
+    /// Returns the Close Request PDUs to send to the client. The channels are considered closed once the client
+    /// acknowledged them, see [`DrdynvcServer::has_opened_channels`].
+    pub fn close_all(&mut self) -> PduResult<Vec<SvcMessage>> {
+        let mut resp = Vec::new();
+
+        for (id, c) in self.dynamic_channels.iter_mut() {
+            if c.state != ChannelState::Opened {
+                continue;
+            }
+            let channel_id = id
+                .try_into()
+                .map_err(|e| pdu_other_err!("invalid channel id", source: e))?;
+            c.processor.close(channel_id);
+            resp.push(as_svc_msg_with_flag(DrdynvcServerPdu::Close(ClosePdu::new(
+                channel_id,
+            )))?);
+        }
+
+        Ok(resp)
+    }
+
+    /// Returns whether some dynamic channels are opened, or waiting for the client to close them
+    pub fn has_opened_channels(&self) -> bool {
         self.dynamic_channels
-            .get_mut(id)
-            .ok_or_else(|| invalid_field_err!("DRDYNVC", "", "invalid channel id"))
+            .iter()
+            .any(|(_, c)| c.state == ChannelState::Opened)
     }
 }
 
+fn channel_by_id(dynamic_channels: &mut Slab<DynamicChannel>, id: u32) -> DecodeResult<&mut DynamicChannel> {
+    let id = cast_length!("DRDYNVC", "", id)?;
+    dynamic_channels
+        .get_mut(id)
+        .ok_or_else(|| invalid_field_err!("DRDYNVC", "", "invalid channel id"))
+}
+
 impl_as_any!(DrdynvcServer);
 
 impl Default for DrdynvcServer {
@@ -139,21 +193,26 @@ impl SvcProcessor for DrdynvcServer {
             DrdynvcClientPdu::Create(create_resp) => {
                 debug!("Got DVC Create Response PDU: {create_resp:?}");
                 let id = create_resp.channel_id;
-                let c = self.channel_by_id(id).map_err(|e| decode_err!(e))?;
+                let c = channel_by_id(&mut self.dynamic_channels, id).map_err(|e| decode_err!(e))?;
                 if c.state != ChannelState::Creation {
                     return Err(pdu_other_err!("invalid channel state"));
                 }
+                if let Some(on_channel_created) = self.on_channel_created.as_mut() {
+                    on_channel_created(c.processor.channel_name(), id, create_resp.creation_status);
+                }
                 if create_resp.creation_status != CreationStatus::OK {
                     c.state = ChannelState::CreationFailed(create_resp.creation_status.into());
                     return Ok(resp);
                 }
                 c.state = ChannelState::Opened;
+                let _span = debug_span!("dvc", channel = c.processor.channel_name(), channel_id = id).entered();
                 let msg = c.processor.start(create_resp.channel_id)?;
+                c.log_sent(&msg);
                 resp.extend(encode_dvc_messages(id, msg, ChannelFlags::SHOW_PROTOCOL).map_err(|e| encode_err!(e))?);
             }
             DrdynvcClientPdu::Close(close_resp) => {
                 debug!("Got DVC Close Response PDU: {close_resp:?}");
-                let c = self.channel_by_id(close_resp.channel_id).map_err(|e| decode_err!(e))?;
+                let c = channel_by_id(&mut self.dynamic_channels, close_resp.channel_id).map_err(|e| decode_err!(e))?;
                 if c.state != ChannelState::Opened {
                     return Err(pdu_other_err!("invalid channel state"));
                 }
@@ -161,12 +220,15 @@ impl SvcProcessor for DrdynvcServer {
             }
             DrdynvcClientPdu::Data(data) => {
                 let channel_id = data.channel_id();
-                let c = self.channel_by_id(channel_id).map_err(|e| decode_err!(e))?;
+                let c = channel_by_id(&mut self.dynamic_channels, channel_id).map_err(|e| decode_err!(e))?;
                 if c.state != ChannelState::Opened {
                     return Err(pdu_other_err!("invalid channel state"));
                 }
+                let _span = debug_span!("dvc", channel = c.processor.channel_name(), channel_id).entered();
                 if let Some(complete) = c.complete_data.process_data(data).map_err(|e| decode_err!(e))? {
+                    c.wire_log.received(c.processor.channel_name(), &complete);
                     let msg = c.processor.process(channel_id, &complete)?;
+                    c.log_sent(&msg);
                     resp.extend(
                         encode_dvc_messages(channel_id, msg, ChannelFlags::SHOW_PROTOCOL)
                             .map_err(|e| encode_err!(e))?,
@@ -181,10 +243,10 @@ impl SvcProcessor for DrdynvcServer {
 
 impl SvcServerProcessor for DrdynvcServer {}
 
-fn decode_dvc_message(user_data: &[u8]) -> DecodeResult<DrdynvcClientPdu> {
+fn decode_dvc_message(user_data: &[u8]) -> DecodeResult<DrdynvcClientPdu<'_>> {
     DrdynvcClientPdu::decode(&mut ReadCursor::new(user_data))
 }
 
-fn as_svc_msg_with_flag(pdu: DrdynvcServerPdu) -> PduResult<SvcMessage> {
+fn as_svc_msg_with_flag(pdu: DrdynvcServerPdu<'static>) -> PduResult<SvcMessage> {
     Ok(SvcMessage::from(pdu).with_flags(ChannelFlags::SHOW_PROTOCOL))
 }
@@ -0,0 +1,163 @@
+//! Utilities for testing dynamic virtual channel processors hermetically.
+//!
+//! [`DvcMockEndpoint`] carries the PDUs of a [`MockPeer`] script to a [`DvcProcessor`], as DRDYNVC data PDUs
+//! fragmented and reassembled as over the wire.
+//!
+//! ```ignore
+//! let mut processor = MyDvcProcessor::new();
+//!
+//! MockPeer::new()
+//!     .send(&MyPdu::Request)
+//!     .expect(&MyPdu::Response)
+//!     .run(&mut DvcMockEndpoint::new(&mut processor, 1))?;
+//! ```
+
+use alloc::boxed::Box;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use ironrdp_core::{decode, ensure_size, Encode, EncodeResult, WriteCursor};
+use ironrdp_svc::{ChannelFlags, ChunkProcessor, SvcMessage, CHANNEL_CHUNK_LENGTH};
+
+pub use ironrdp_svc::testing::{MockEndpoint, MockError, MockPeer, MockResult};
+
+use crate::complete_data::CompleteData;
+use crate::pdu::{DrdynvcClientPdu, DrdynvcServerPdu};
+use crate::{encode_dvc_messages, DvcEncode, DvcMessage, DvcProcessor, DynamicChannelId};
+
+/// In-memory transport to a dynamic virtual channel processor under test.
+///
+/// The channel is considered open, with the given channel ID: [`DvcMockEndpoint`] starts the processor
+/// as [`DrdynvcClient`](crate::DrdynvcClient) does once the channel is created, without the creation handshake.
+pub struct DvcMockEndpoint<'a> {
+    processor: &'a mut dyn DvcProcessor,
+    channel_id: DynamicChannelId,
+    processor_side: Side,
+    peer_side: Side,
+}
+
+impl<'a> DvcMockEndpoint<'a> {
+    pub fn new(processor: &'a mut dyn DvcProcessor, channel_id: DynamicChannelId) -> Self {
+        Self {
+            processor,
+            channel_id,
+            processor_side: Side::new(),
+            peer_side: Side::new(),
+        }
+    }
+
+    fn to_peer(&mut self, messages: Vec<DvcMessage>) -> MockResult<Vec<Vec<u8>>> {
+        let messages =
+            encode_dvc_messages(self.channel_id, messages, ChannelFlags::empty()).map_err(MockError::Encode)?;
+
+        let mut pdus = Vec::new();
+
+        for data in self.peer_side.receive(messages)? {
+            // Data PDUs are encoded the same way in both directions.
+            match decode::<DrdynvcClientPdu<'_>>(&data).map_err(MockError::Decode)? {
+                DrdynvcClientPdu::Data(pdu) => {
+                    if let Some(complete) = self
+                        .peer_side
+                        .complete_data
+                        .process_data(pdu)
+                        .map_err(MockError::Decode)?
+                    {
+                        pdus.push(complete.into_owned());
+                    }
+                }
+                pdu => warn!(?pdu, "Ignored a non-data DRDYNVC PDU"),
+            }
+        }
+
+        Ok(pdus)
+    }
+}
+
+impl MockEndpoint for DvcMockEndpoint<'_> {
+    fn start(&mut self) -> MockResult<Vec<Vec<u8>>> {
+        let messages = self.processor.start(self.channel_id).map_err(MockError::Channel)?;
+        self.to_peer(messages)
+    }
+
+    fn deliver(&mut self, pdu: &[u8]) -> MockResult<Vec<Vec<u8>>> {
+        let messages: Vec<DvcMessage> = vec![Box::new(RawPdu(pdu.to_vec()))];
+        let messages =
+            encode_dvc_messages(self.channel_id, messages, ChannelFlags::empty()).map_err(MockError::Encode)?;
+
+        let mut pdus = Vec::new();
+
+        for data in self.processor_side.receive(messages)? {
+            let DrdynvcServerPdu::Data(pdu) = decode::<DrdynvcServerPdu<'_>>(&data).map_err(MockError::Decode)? else {
+                unreachable!("only data PDUs are sent by the peer");
+            };
+
+            if let Some(complete) = self
+                .processor_side
+                .complete_data
+                .process_data(pdu)
+                .map_err(MockError::Decode)?
+            {
+                let messages = self
+                    .processor
+                    .process(self.channel_id, &complete)
+                    .map_err(MockError::Channel)?;
+                pdus.extend(self.to_peer(messages)?);
+            }
+        }
+
+        Ok(pdus)
+    }
+}
+
+/// Receiving side of the mock transport, reassembling the static channel chunks then the DVC fragments.
+struct Side {
+    chunk_processor: ChunkProcessor,
+    complete_data: CompleteData,
+}
+
+impl Side {
+    fn new() -> Self {
+        Self {
+            chunk_processor: ChunkProcessor::new(),
+            complete_data: CompleteData::new(),
+        }
+    }
+
+    /// Returns the DRDYNVC PDUs carried by `messages`.
+    fn receive(&mut self, messages: Vec<SvcMessage>) -> MockResult<Vec<Vec<u8>>> {
+        let mut pdus = Vec::new();
+
+        for chunk in ChunkProcessor::chunkify(messages, CHANNEL_CHUNK_LENGTH).map_err(MockError::Encode)? {
+            if let Some(pdu) = self
+                .chunk_processor
+                .dechunkify(chunk.filled())
+                .map_err(MockError::Decode)?
+            {
+                pdus.push(pdu);
+            }
+        }
+
+        Ok(pdus)
+    }
+}
+
+/// PDU sent by the peer, already encoded.
+struct RawPdu(Vec<u8>);
+
+impl Encode for RawPdu {
+    fn encode(&self, dst: &mut WriteCursor<'_>) -> EncodeResult<()> {
+        ensure_size!(in: dst, size: self.size());
+        dst.write_slice(&self.0);
+        Ok(())
+    }
+
+    fn name(&self) -> &'static str {
+        "RawPdu"
+    }
+
+    fn size(&self) -> usize {
+        self.0.len()
+    }
+}
+
+impl DvcEncode for RawPdu {}
@@ -19,6 +19,14 @@ pub trait Source: fmt::Display + fmt::Debug + Send + Sync + 'static {}
 #[cfg(not(feature = "std"))]
 impl<T> Source for T where T: fmt::Display + fmt::Debug + Send + Sync + 'static {}
 
+/// Stable numeric code identifying an error kind.
+///
+/// Codes are part of the public API: a code is never reused for another kind of error, so that embedders can
+/// branch on errors or look up localized messages without matching on the error kinds themselves.
+pub trait ErrorCode {
+    fn code(&self) -> u32;
+}
+
 #[derive(Debug)]
 pub struct Error<Kind> {
     pub context: &'static str,
@@ -81,6 +89,14 @@ impl<Kind> Error<Kind> {
     pub fn report(&self) -> ErrorReport<'_, Kind> {
         ErrorReport(self)
     }
+
+    /// Returns the stable code of the error kind.
+    pub fn code(&self) -> u32
+    where
+        Kind: ErrorCode,
+    {
+        self.kind.code()
+    }
 }
 
 impl<Kind> fmt::Display for Error<Kind>
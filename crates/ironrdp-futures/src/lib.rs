@@ -1,11 +1,18 @@
+#![doc = include_str!("../README.md")]
+
 #[rustfmt::skip] // do not re-order this pub use
 pub use ironrdp_async::*;
 
+use std::future::poll_fn;
 use std::io;
 use std::pin::Pin;
+use std::task::Poll;
 
 use bytes::BytesMut;
-use futures_util::io::{AsyncRead, AsyncWrite};
+use futures_io::{AsyncRead, AsyncWrite};
+
+/// Number of bytes the internal buffer is extended by on each read.
+const READ_CHUNK_SIZE: usize = 8 * 1024;
 
 pub type FuturesFramed<S> = Framed<FuturesStream<S>>;
 
@@ -37,21 +44,13 @@ impl<S> FramedRead for FuturesStream<S>
 where
     S: Send + Sync + Unpin + AsyncRead,
 {
-    type ReadFut<'read> = Pin<Box<dyn std::future::Future<Output = io::Result<usize>> + Send + Sync + 'read>>
+    type ReadFut<'read>
+        = Pin<Box<dyn std::future::Future<Output = io::Result<usize>> + Send + Sync + 'read>>
     where
         Self: 'read;
 
     fn read<'a>(&'a mut self, buf: &'a mut BytesMut) -> Self::ReadFut<'a> {
-        use futures_util::io::AsyncReadExt as _;
-
-        Box::pin(async {
-            // NOTE(perf): tokio implementation is more efficient
-            let mut read_bytes = [0u8; 1024];
-            let len = self.inner.read(&mut read_bytes).await?;
-            buf.extend_from_slice(&read_bytes[..len]);
-
-            Ok(len)
-        })
+        Box::pin(read_buf(&mut self.inner, buf))
     }
 }
 
@@ -59,19 +58,13 @@ impl<S> FramedWrite for FuturesStream<S>
 where
     S: Send + Sync + Unpin + AsyncWrite,
 {
-    type WriteAllFut<'write> = Pin<Box<dyn std::future::Future<Output = io::Result<()>> + Send + Sync + 'write>>
+    type WriteAllFut<'write>
+        = Pin<Box<dyn std::future::Future<Output = io::Result<()>> + Send + Sync + 'write>>
     where
         Self: 'write;
 
     fn write_all<'a>(&'a mut self, buf: &'a [u8]) -> Self::WriteAllFut<'a> {
-        use futures_util::io::AsyncWriteExt as _;
-
-        Box::pin(async {
-            self.inner.write_all(buf).await?;
-            self.inner.flush().await?;
-
-            Ok(())
-        })
+        Box::pin(write_all_and_flush(&mut self.inner, buf))
     }
 }
 
@@ -105,21 +98,13 @@ impl<S> FramedRead for LocalFuturesStream<S>
 where
     S: Unpin + AsyncRead,
 {
-    type ReadFut<'read> = Pin<Box<dyn std::future::Future<Output = io::Result<usize>> + 'read>>
+    type ReadFut<'read>
+        = Pin<Box<dyn std::future::Future<Output = io::Result<usize>> + 'read>>
     where
         Self: 'read;
 
     fn read<'a>(&'a mut self, buf: &'a mut BytesMut) -> Self::ReadFut<'a> {
-        use futures_util::io::AsyncReadExt as _;
-
-        Box::pin(async {
-            // NOTE(perf): tokio implementation is more efficient
-            let mut read_bytes = [0u8; 1024];
-            let len = self.inner.read(&mut read_bytes[..]).await?;
-            buf.extend_from_slice(&read_bytes[..len]);
-
-            Ok(len)
-        })
+        Box::pin(read_buf(&mut self.inner, buf))
     }
 }
 
@@ -127,18 +112,53 @@ impl<S> FramedWrite for LocalFuturesStream<S>
 where
     S: Unpin + AsyncWrite,
 {
-    type WriteAllFut<'write> = Pin<Box<dyn std::future::Future<Output = io::Result<()>> + 'write>>
+    type WriteAllFut<'write>
+        = Pin<Box<dyn std::future::Future<Output = io::Result<()>> + 'write>>
     where
         Self: 'write;
 
     fn write_all<'a>(&'a mut self, buf: &'a [u8]) -> Self::WriteAllFut<'a> {
-        use futures_util::io::AsyncWriteExt as _;
+        Box::pin(write_all_and_flush(&mut self.inner, buf))
+    }
+}
 
-        Box::pin(async {
-            self.inner.write_all(buf).await?;
-            self.inner.flush().await?;
+/// Reads directly into the spare capacity of `buf`, similarly to tokio’s `read_buf`.
+///
+/// The buffer is restored to its filled length before returning from each poll, keeping this cancel safe.
+async fn read_buf<S>(stream: &mut S, buf: &mut BytesMut) -> io::Result<usize>
+where
+    S: Unpin + AsyncRead,
+{
+    poll_fn(|cx| {
+        let filled = buf.len();
+        buf.resize(filled.saturating_add(READ_CHUNK_SIZE), 0);
+
+        let result = Pin::new(&mut *stream).poll_read(cx, &mut buf[filled..]);
+
+        let read = match &result {
+            Poll::Ready(Ok(len)) => *len,
+            _ => 0,
+        };
+        buf.truncate(filled.saturating_add(read));
 
-            Ok(())
-        })
+        result
+    })
+    .await
+}
+
+async fn write_all_and_flush<S>(stream: &mut S, mut buf: &[u8]) -> io::Result<()>
+where
+    S: Unpin + AsyncWrite,
+{
+    while !buf.is_empty() {
+        let len = poll_fn(|cx| Pin::new(&mut *stream).poll_write(cx, buf)).await?;
+
+        if len == 0 {
+            return Err(io::Error::from(io::ErrorKind::WriteZero));
+        }
+
+        buf = &buf[len..];
     }
+
+    poll_fn(|cx| Pin::new(&mut *stream).poll_flush(cx)).await
 }
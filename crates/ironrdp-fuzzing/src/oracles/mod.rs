@@ -30,6 +30,9 @@ pub fn pdu_decode(data: &[u8]) {
     let _ = decode::<headers::ShareControlHeader>(data);
     let _ = decode::<pcb::PreconnectionBlob>(data);
     let _ = decode::<server_error_info::ServerSetErrorInfoPdu>(data);
+    let _ = decode::<autodetect::AutoDetectRequestPdu>(data);
+    let _ = decode::<autodetect::AutoDetectResponsePdu>(data);
+    let _ = decode::<standard_security::SecurityExchangePdu>(data);
 
     let _ = decode::<gcc::ClientGccBlocks>(data);
     let _ = decode::<gcc::ServerGccBlocks>(data);
@@ -6,6 +6,23 @@ use crate::image_processing::PixelFormat;
 const ALPHA: u8 = 255;
 
 pub fn ycbcr_to_bgra(input: YCbCrBuffer<'_>, mut output: &mut [u8]) -> io::Result<()> {
+    #[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+    let input = {
+        let converted = wasm_simd::ycbcr_to_bgra(&input, output);
+        output = &mut output[converted * 4..];
+
+        // The remaining pixels are converted below.
+        YCbCrBuffer {
+            y: &input.y[converted..],
+            cb: &input.cb[converted..],
+            cr: &input.cr[converted..],
+        }
+    };
+
+    ycbcr_to_bgra_scalar(input, output)
+}
+
+fn ycbcr_to_bgra_scalar(input: YCbCrBuffer<'_>, mut output: &mut [u8]) -> io::Result<()> {
     for ycbcr in input {
         let pixel = Rgb::from(ycbcr);
 
@@ -268,3 +285,109 @@ impl From<Rgb> for YCbCr {
         }
     }
 }
+
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+mod wasm_simd {
+    use core::arch::wasm32::*;
+
+    use super::YCbCrBuffer;
+
+    /// SIMD version of the `YCbCr` to `Rgb` conversion, converting 4 pixels at a time
+    ///
+    /// Returns the number of converted pixels, the remaining ones (less than 4) are left to the caller.
+    pub(super) fn ycbcr_to_bgra(input: &YCbCrBuffer<'_>, output: &mut [u8]) -> usize {
+        // Same fixed-point factors as the scalar version.
+        const DIVISOR: f32 = (1 << 16) as f32;
+
+        let cr_r_factor = i32x4_splat((1.402_525 * DIVISOR) as i32);
+        let cb_g_factor = i32x4_splat((0.343_730 * DIVISOR) as i32);
+        let cr_g_factor = i32x4_splat((0.714_401 * DIVISOR) as i32);
+        let cb_b_factor = i32x4_splat((1.769_905 * DIVISOR) as i32);
+        let cr_b_factor = i32x4_splat((0.000_013 * DIVISOR) as i32);
+        let y_offset = i32x4_splat(4096);
+        let alpha = u32x4_splat(u32::from(super::ALPHA) << 24);
+
+        let count = input
+            .y
+            .len()
+            .min(input.cb.len())
+            .min(input.cr.len())
+            .min(output.len() / 4);
+        let count = count - count % 4;
+
+        for i in (0..count).step_by(4) {
+            let y = load(&input.y[i..i + 4]);
+            let cb = load(&input.cb[i..i + 4]);
+            let cr = load(&input.cr[i..i + 4]);
+
+            let yy = i32x4_shl(i32x4_add(y, y_offset), 16);
+            let r = i32x4_add(yy, i32x4_mul(cr, cr_r_factor));
+            let g = i32x4_sub(i32x4_sub(yy, i32x4_mul(cb, cb_g_factor)), i32x4_mul(cr, cr_g_factor));
+            let b = i32x4_add(i32x4_add(yy, i32x4_mul(cb, cb_b_factor)), i32x4_mul(cb, cr_b_factor));
+
+            // Little-endian BGRA pixels.
+            let pixels = v128_or(
+                v128_or(clip(b), i32x4_shl(clip(g), 8)),
+                v128_or(i32x4_shl(clip(r), 16), alpha),
+            );
+
+            let destination = &mut output[i * 4..i * 4 + 16];
+            // SAFETY: `destination` is 16 bytes long, which is the size of the written vector.
+            unsafe { v128_store(destination.as_mut_ptr().cast(), pixels) };
+        }
+
+        count
+    }
+
+    fn load(values: &[i16]) -> v128 {
+        assert_eq!(values.len(), 4);
+        // SAFETY: `values` holds 4 `i16`, which is what is read.
+        unsafe { i32x4_load_extend_i16x4(values.as_ptr()) }
+    }
+
+    fn clip(value: v128) -> v128 {
+        i32x4_max(i32x4_min(i32x4_shr(value, 21), i32x4_splat(255)), i32x4_splat(0))
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::super::{ycbcr_to_bgra_scalar, YCbCrBuffer};
+
+        /// Deterministic samples covering the whole `i16` range, so that both clipping bounds are hit.
+        fn samples(seed: u32, len: usize) -> Vec<i16> {
+            let mut state = seed;
+
+            (0..len)
+                .map(|_| {
+                    state ^= state << 13;
+                    state ^= state >> 17;
+                    state ^= state << 5;
+                    state as i16
+                })
+                .collect()
+        }
+
+        #[test]
+        fn simd_ycbcr_to_bgra_matches_scalar() {
+            // Not a multiple of 4, so the scalar tail is exercised as well.
+            for len in [0, 1, 3, 4, 7, 64, 4099] {
+                let y = samples(1, len);
+                let cb = samples(2, len);
+                let cr = samples(3, len);
+                let input = || YCbCrBuffer {
+                    y: &y,
+                    cb: &cb,
+                    cr: &cr,
+                };
+
+                let mut expected = vec![0; len * 4];
+                ycbcr_to_bgra_scalar(input(), &mut expected).unwrap();
+
+                let mut actual = vec![0; len * 4];
+                super::super::ycbcr_to_bgra(input(), &mut actual).unwrap();
+
+                assert_eq!(actual, expected, "length {len}");
+            }
+        }
+    }
+}
@@ -104,6 +104,10 @@ pub fn decode(buffer: &mut [i16], temp_buffer: &mut [i16]) {
 
 fn decode_block(buffer: &mut [i16], temp_buffer: &mut [i16], subband_width: usize) {
     inverse_horizontal(buffer, temp_buffer, subband_width);
+
+    #[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+    wasm_simd::inverse_vertical(buffer, temp_buffer, subband_width);
+    #[cfg(not(all(target_arch = "wasm32", target_feature = "simd128")))]
     inverse_vertical(buffer, temp_buffer, subband_width);
 }
 
@@ -153,6 +157,7 @@ fn inverse_horizontal(mut buffer: &[i16], temp_buffer: &mut [i16], subband_width
     }
 }
 
+#[cfg_attr(all(target_arch = "wasm32", target_feature = "simd128"), allow(dead_code))] // Replaced by the SIMD version
 fn inverse_vertical(mut buffer: &mut [i16], mut temp_buffer: &[i16], subband_width: usize) {
     let total_width = subband_width * 2;
 
@@ -186,3 +191,106 @@ fn inverse_vertical(mut buffer: &mut [i16], mut temp_buffer: &[i16], subband_wid
         buffer = &mut buffer[1..];
     }
 }
+
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+mod wasm_simd {
+    use core::arch::wasm32::*;
+
+    /// SIMD version of `inverse_vertical`
+    ///
+    /// Columns are independent, so 4 of them are processed at a time. Values are widened to 32 bits, like in the
+    /// scalar version, and truncated back to 16 bits when stored.
+    pub(super) fn inverse_vertical(buffer: &mut [i16], temp_buffer: &[i16], subband_width: usize) {
+        let total_width = subband_width * 2;
+        let one = i32x4_splat(1);
+
+        for column in (0..total_width).step_by(4) {
+            let row = |index: usize| column + index * total_width;
+
+            let first = i32x4_sub(
+                load(temp_buffer, row(0)),
+                i32x4_shr(i32x4_add(i32x4_shl(load(temp_buffer, row(subband_width)), 1), one), 1),
+            );
+            store(buffer, row(0), first);
+            let mut previous_even = wrap(first);
+
+            for n in 1..subband_width {
+                let l = load(temp_buffer, row(n));
+                let lh = load(temp_buffer, row(subband_width - 1 + n));
+                let h = load(temp_buffer, row(subband_width + n));
+
+                // Even coefficients
+                let even = i32x4_sub(l, i32x4_shr(i32x4_add(i32x4_add(lh, h), one), 1));
+                store(buffer, row(2 * n), even);
+                let even = wrap(even);
+
+                // Odd coefficients
+                let odd = i32x4_add(shl1(lh), i32x4_shr(i32x4_add(previous_even, even), 1));
+                store(buffer, row(2 * n - 1), odd);
+
+                previous_even = even;
+            }
+
+            let last_h = load(temp_buffer, row(2 * subband_width - 1));
+            let odd = i32x4_add(shl1(last_h), i32x4_shr(i32x4_add(previous_even, previous_even), 1));
+            store(buffer, row(2 * subband_width - 1), odd);
+        }
+    }
+
+    fn load(slice: &[i16], index: usize) -> v128 {
+        let values = &slice[index..index + 4];
+        // SAFETY: `values` holds 4 `i16`, which is what is read.
+        unsafe { i32x4_load_extend_i16x4(values.as_ptr()) }
+    }
+
+    fn store(slice: &mut [i16], index: usize, value: v128) {
+        let values = &mut slice[index..index + 4];
+        // Keep the lower 16 bits of each lane, like the `as i16` conversions.
+        let packed = i8x16_shuffle::<0, 1, 4, 5, 8, 9, 12, 13, 0, 1, 4, 5, 8, 9, 12, 13>(value, value);
+        // SAFETY: `values` holds 4 `i16` (8 bytes), which is what is written.
+        unsafe { v128_store64_lane::<0>(packed, values.as_mut_ptr().cast()) };
+    }
+
+    /// Truncates each lane to 16 bits, and sign-extends it back
+    fn wrap(value: v128) -> v128 {
+        i32x4_shr(i32x4_shl(value, 16), 16)
+    }
+
+    /// Equivalent of the 16-bit `value << 1`, sign-extended back to 32 bits
+    fn shl1(value: v128) -> v128 {
+        i32x4_shr(i32x4_shl(value, 17), 16)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        /// Deterministic samples covering the whole `i16` range, so that the 16-bit wrapping is exercised.
+        fn samples(seed: u32, len: usize) -> Vec<i16> {
+            let mut state = seed;
+
+            (0..len)
+                .map(|_| {
+                    state ^= state << 13;
+                    state ^= state >> 17;
+                    state ^= state << 5;
+                    state as i16
+                })
+                .collect()
+        }
+
+        #[test]
+        fn simd_inverse_vertical_matches_scalar() {
+            for subband_width in [8, 16, 32] {
+                let len = subband_width * subband_width * 4;
+                let temp_buffer = samples(u32::try_from(subband_width).unwrap(), len);
+
+                let mut expected = vec![0; len];
+                super::super::inverse_vertical(&mut expected, &temp_buffer, subband_width);
+
+                let mut actual = vec![0; len];
+                super::inverse_vertical(&mut actual, &temp_buffer, subband_width);
+
+                assert_eq!(actual, expected, "subband width {subband_width}");
+            }
+        }
+    }
+}
@@ -11,6 +11,7 @@ pub mod pointer;
 pub mod quantization;
 pub mod rdp6;
 pub mod rectangle_processing;
+pub mod rfx_encoder;
 pub mod rle;
 pub mod rlgr;
 pub mod subband_reconstruction;
@@ -18,6 +19,14 @@ pub mod zgfx;
 
 mod utils;
 
+/// Whether the codec hot loops (color conversion, DWT) use SIMD instructions
+///
+/// SIMD128 is used on `wasm32` when the `simd128` target feature is enabled at build time
+/// (e.g.: `RUSTFLAGS="-C target-feature=+simd128"`). This is decided at build time: WASM has no way to fall back
+/// from instructions the host does not support, so the selection at runtime is done by loading either a SIMD or a
+/// scalar build of the module (see the `ironrdp-web` README).
+pub const SIMD_ACCELERATION: bool = cfg!(all(target_arch = "wasm32", target_feature = "simd128"));
+
 pub fn rfx_encode_component(
     input: &mut [i16],
     output: &mut [u8],
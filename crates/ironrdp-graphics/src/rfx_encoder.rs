@@ -0,0 +1,293 @@
+//! RemoteFX encoder
+//!
+//! [`RfxEncoder`] produces the RemoteFX messages of an image, as specified by MS-RDPRFX. The image is split into
+//! 64x64 tiles, and each tile covered by the encoded region is converted to YCbCr, transformed (DWT), quantized and
+//! entropy coded (RLGR). The tiles are then wrapped into the region, tile set and frame messages.
+//!
+//! The header messages (sync, context, channels and codec versions) are only sent with the first frame, and whenever
+//! the size of the image changes, as the decoder keeps track of them.
+
+use ironrdp_pdu::codecs::rfx::{
+    self, EntropyAlgorithm, OperatingMode, Quant, RfxChannel, RfxChannelHeight, RfxChannelWidth, RfxError, RfxRectangle,
+};
+use ironrdp_pdu::PduBufferParsing;
+use thiserror::Error;
+
+use crate::color_conversion::to_64x64_ycbcr_tile;
+use crate::image_processing::PixelFormat;
+use crate::rfx_encode_component;
+use crate::rlgr::RlgrError;
+
+const TILE_SIZE: u16 = 64;
+const TILE_PIXELS: usize = TILE_SIZE as usize * TILE_SIZE as usize;
+// Worst case size of an entropy coded tile component, RLGR output being far smaller for actual images.
+const COMPONENT_CAPACITY: usize = TILE_PIXELS * 4;
+
+#[derive(Debug, Error)]
+pub enum RfxEncoderError {
+    #[error("invalid source image: {0}")]
+    InvalidImage(&'static str),
+    #[error("rectangle out of the image bounds: {0:?}")]
+    InvalidRectangle(RfxRectangle),
+    #[error(transparent)]
+    Rlgr(#[from] RlgrError),
+    #[error("RFX message encoding error: {0}")]
+    Rfx(RfxError),
+}
+
+/// Image to encode, `stride` being the number of bytes between the start of two consecutive lines
+#[derive(Clone, Copy)]
+pub struct RfxSourceImage<'a> {
+    pub data: &'a [u8],
+    pub width: u16,
+    pub height: u16,
+    pub stride: usize,
+    pub format: PixelFormat,
+}
+
+impl std::fmt::Debug for RfxSourceImage<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RfxSourceImage")
+            .field("width", &self.width)
+            .field("height", &self.height)
+            .field("stride", &self.stride)
+            .field("format", &self.format)
+            .field("data_len", &self.data.len())
+            .finish()
+    }
+}
+
+impl RfxSourceImage<'_> {
+    fn validate(&self) -> Result<(), RfxEncoderError> {
+        if self.width == 0 || self.height == 0 {
+            return Err(RfxEncoderError::InvalidImage("empty image"));
+        }
+
+        // The channel dimensions are signed 16-bit integers.
+        if i16::try_from(self.width).is_err() || i16::try_from(self.height).is_err() {
+            return Err(RfxEncoderError::InvalidImage("image too large"));
+        }
+
+        let line_length = usize::from(self.width) * usize::from(self.format.bytes_per_pixel());
+
+        if self.stride < line_length {
+            return Err(RfxEncoderError::InvalidImage("stride shorter than a line"));
+        }
+
+        if self.data.len() < (usize::from(self.height) - 1) * self.stride + line_length {
+            return Err(RfxEncoderError::InvalidImage("data shorter than the image"));
+        }
+
+        Ok(())
+    }
+}
+
+/// Encodes images into RemoteFX frames
+#[derive(Debug)]
+pub struct RfxEncoder {
+    entropy_algorithm: EntropyAlgorithm,
+    quant: Quant,
+    frame_index: u32,
+    channel: Option<(u16, u16)>,
+    component: Vec<u8>,
+}
+
+impl RfxEncoder {
+    pub fn new(entropy_algorithm: EntropyAlgorithm) -> Self {
+        Self {
+            entropy_algorithm,
+            quant: Quant::default(),
+            frame_index: 0,
+            channel: None,
+            component: vec![0; COMPONENT_CAPACITY],
+        }
+    }
+
+    pub fn entropy_algorithm(&self) -> EntropyAlgorithm {
+        self.entropy_algorithm
+    }
+
+    pub fn quant(&self) -> &Quant {
+        &self.quant
+    }
+
+    /// Sets the quantization values of the next frames
+    ///
+    /// Values range from 6 to 15, higher values giving a higher compression rate and a lower quality.
+    pub fn set_quant(&mut self, quant: Quant) {
+        self.quant = quant;
+    }
+
+    /// Sends the header messages again with the next frame, for a decoder starting over
+    pub fn reset(&mut self) {
+        self.channel = None;
+    }
+
+    /// Encodes the whole image into a frame
+    pub fn encode(&mut self, image: &RfxSourceImage<'_>) -> Result<Vec<u8>, RfxEncoderError> {
+        let rectangle = RfxRectangle {
+            x: 0,
+            y: 0,
+            width: image.width,
+            height: image.height,
+        };
+
+        self.encode_region(image, &[rectangle])
+    }
+
+    /// Encodes the parts of the image covered by `rectangles` into a frame
+    ///
+    /// Only the tiles intersecting the rectangles are encoded, the decoder clipping them to the rectangles.
+    pub fn encode_region(
+        &mut self,
+        image: &RfxSourceImage<'_>,
+        rectangles: &[RfxRectangle],
+    ) -> Result<Vec<u8>, RfxEncoderError> {
+        image.validate()?;
+
+        if let Some(rectangle) = rectangles.iter().find(|r| !is_within(r, image)) {
+            return Err(RfxEncoderError::InvalidRectangle(rectangle.clone()));
+        }
+
+        let mut output = Vec::new();
+
+        let channel = (image.width, image.height);
+        if self.channel != Some(channel) {
+            self.encode_headers(image, &mut output)?;
+        }
+
+        let tiles_x = image.width.div_ceil(TILE_SIZE);
+        let tiles_y = image.height.div_ceil(TILE_SIZE);
+        let bpp = usize::from(image.format.bytes_per_pixel());
+
+        let mut ycbcr = [[0i16; TILE_PIXELS], [0i16; TILE_PIXELS], [0i16; TILE_PIXELS]];
+        let mut encoded = Vec::new();
+        let mut encoded_tiles = Vec::new();
+
+        for tile_y in 0..tiles_y {
+            for tile_x in 0..tiles_x {
+                if !rectangles.iter().any(|r| intersects_tile(r, tile_x, tile_y)) {
+                    continue;
+                }
+
+                let x = usize::from(tile_x * TILE_SIZE);
+                let y = usize::from(tile_y * TILE_SIZE);
+                let tile_width = (usize::from(image.width) - x).min(usize::from(TILE_SIZE));
+                let tile_height = (usize::from(image.height) - y).min(usize::from(TILE_SIZE));
+
+                let [y_buffer, cb_buffer, cr_buffer] = &mut ycbcr;
+                to_64x64_ycbcr_tile(
+                    &image.data[y * image.stride + x * bpp..],
+                    tile_width,
+                    tile_height,
+                    image.stride,
+                    image.format,
+                    y_buffer,
+                    cb_buffer,
+                    cr_buffer,
+                );
+
+                let mut ranges = [0..0, 0..0, 0..0];
+                for (component, range) in ycbcr.iter_mut().zip(ranges.iter_mut()) {
+                    let length =
+                        rfx_encode_component(component, &mut self.component, &self.quant, self.entropy_algorithm)?;
+                    *range = encoded.len()..encoded.len() + length;
+                    encoded.extend_from_slice(&self.component[..length]);
+                }
+
+                encoded_tiles.push((tile_x, tile_y, ranges));
+            }
+        }
+
+        let tiles = encoded_tiles
+            .into_iter()
+            .map(|(x, y, [y_range, cb_range, cr_range])| rfx::Tile {
+                y_quant_index: 0,
+                cb_quant_index: 0,
+                cr_quant_index: 0,
+                x,
+                y,
+                y_data: &encoded[y_range],
+                cb_data: &encoded[cb_range],
+                cr_data: &encoded[cr_range],
+            })
+            .collect();
+
+        append(
+            &mut output,
+            &rfx::FrameBeginPdu {
+                index: self.frame_index,
+                number_of_regions: 1,
+            },
+        )?;
+        append(
+            &mut output,
+            &rfx::RegionPdu {
+                rectangles: rectangles.to_vec(),
+            },
+        )?;
+        append(
+            &mut output,
+            &rfx::TileSetPdu {
+                entropy_algorithm: self.entropy_algorithm,
+                quants: vec![self.quant.clone()],
+                tiles,
+            },
+        )?;
+        append(&mut output, &rfx::FrameEndPdu)?;
+
+        self.channel = Some(channel);
+        self.frame_index = self.frame_index.wrapping_add(1);
+
+        Ok(output)
+    }
+
+    fn encode_headers(&self, image: &RfxSourceImage<'_>, output: &mut Vec<u8>) -> Result<(), RfxEncoderError> {
+        // Dimensions were checked when validating the image.
+        let channels = rfx::ChannelsPdu(vec![RfxChannel {
+            width: RfxChannelWidth::new(image.width as i16),
+            height: RfxChannelHeight::new(image.height as i16),
+        }]);
+        let context = rfx::ContextPdu {
+            flags: OperatingMode::IMAGE_MODE,
+            entropy_algorithm: self.entropy_algorithm,
+        };
+
+        append(output, &rfx::SyncPdu)?;
+        append(output, &rfx::Headers::Context(context))?;
+        append(output, &rfx::Headers::Channels(channels))?;
+        append(output, &rfx::Headers::CodecVersions(rfx::CodecVersionsPdu))?;
+
+        Ok(())
+    }
+}
+
+fn append<'a>(
+    output: &mut Vec<u8>,
+    message: &impl PduBufferParsing<'a, Error = RfxError>,
+) -> Result<(), RfxEncoderError> {
+    let start = output.len();
+    output.resize(start + message.buffer_length(), 0);
+
+    let mut buffer = &mut output[start..];
+    message.to_buffer_consume(&mut buffer).map_err(RfxEncoderError::Rfx)
+}
+
+fn is_within(rectangle: &RfxRectangle, image: &RfxSourceImage<'_>) -> bool {
+    rectangle.width != 0
+        && rectangle.height != 0
+        && u32::from(rectangle.x) + u32::from(rectangle.width) <= u32::from(image.width)
+        && u32::from(rectangle.y) + u32::from(rectangle.height) <= u32::from(image.height)
+}
+
+fn intersects_tile(rectangle: &RfxRectangle, tile_x: u16, tile_y: u16) -> bool {
+    let left = u32::from(tile_x) * u32::from(TILE_SIZE);
+    let top = u32::from(tile_y) * u32::from(TILE_SIZE);
+    let right = left + u32::from(TILE_SIZE);
+    let bottom = top + u32::from(TILE_SIZE);
+
+    u32::from(rectangle.x) < right
+        && u32::from(rectangle.x) + u32::from(rectangle.width) > left
+        && u32::from(rectangle.y) < bottom
+        && u32::from(rectangle.y) + u32::from(rectangle.height) > top
+}
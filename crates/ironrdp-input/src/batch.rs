@@ -0,0 +1,185 @@
+//! Batching of input events
+//!
+//! High-frequency pointer devices produce many mouse moves, each of them costing a whole fast-path input PDU when
+//! sent right away. [`InputBatcher`] holds the events for a short interval and sends them in as few PDUs as the
+//! configured limits allow, keeping only the last of successive mouse moves.
+
+use std::time::{Duration, Instant};
+
+use ironrdp_core::{encode_vec, Encode as _, EncodeResult};
+use ironrdp_pdu::input::fast_path::{FastPathInput, FastPathInputEvent};
+use ironrdp_pdu::input::mouse::PointerFlags;
+use ironrdp_pdu::input::mouse_rel::PointerRelFlags;
+
+/// Maximum number of events in a fast-path input PDU
+pub const MAX_EVENTS_PER_PDU: usize = 255;
+
+/// Maximum size of a fast-path input PDU, as limited by its length field
+pub const MAX_PDU_SIZE: usize = 0x7FFF;
+
+/// Largest fast-path input header: action byte, two-byte length and event count
+const MAX_HEADER_SIZE: usize = 4;
+
+/// Accumulates input events, typically returned by [`Database::apply`](crate::Database::apply), in order to send
+/// them in batches.
+///
+/// Only mouse moves directly following each other are coalesced: button, key and wheel events are kept, in order.
+#[derive(Debug, Clone)]
+pub struct InputBatcher {
+    interval: Duration,
+    max_events: usize,
+    max_pdu_size: usize,
+    pending: Vec<FastPathInputEvent>,
+    batch_start: Option<Instant>,
+}
+
+impl InputBatcher {
+    /// Creates a batcher holding the events for at most `interval`.
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            max_events: MAX_EVENTS_PER_PDU,
+            max_pdu_size: MAX_PDU_SIZE,
+            pending: Vec::new(),
+            batch_start: None,
+        }
+    }
+
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    pub fn set_interval(&mut self, interval: Duration) {
+        self.interval = interval;
+    }
+
+    pub fn max_events(&self) -> usize {
+        self.max_events
+    }
+
+    /// Limits the number of events per PDU, in the range 1 to [`MAX_EVENTS_PER_PDU`].
+    pub fn set_max_events(&mut self, max_events: usize) {
+        self.max_events = max_events.clamp(1, MAX_EVENTS_PER_PDU);
+    }
+
+    pub fn max_pdu_size(&self) -> usize {
+        self.max_pdu_size
+    }
+
+    /// Limits the size of the PDUs, up to [`MAX_PDU_SIZE`].
+    ///
+    /// A PDU always holds at least one event, even if it exceeds the limit.
+    pub fn set_max_pdu_size(&mut self, max_pdu_size: usize) {
+        self.max_pdu_size = max_pdu_size.min(MAX_PDU_SIZE);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Queues events, `now` being the current time.
+    pub fn push(&mut self, events: impl IntoIterator<Item = FastPathInputEvent>, now: Instant) {
+        for event in events {
+            if let Some(last) = self.pending.last_mut() {
+                if let Some(coalesced) = coalesce(last, &event) {
+                    *last = coalesced;
+                    continue;
+                }
+            }
+
+            self.pending.push(event);
+        }
+
+        if !self.pending.is_empty() {
+            self.batch_start.get_or_insert(now);
+        }
+    }
+
+    /// Returns the instant at which the pending events are due, if any.
+    ///
+    /// The events are due right away when they don't fit in a single PDU anymore.
+    pub fn deadline(&self) -> Option<Instant> {
+        let batch_start = self.batch_start?;
+
+        if self.pending.len() >= self.max_events || self.first_pdu_len() < self.pending.len() {
+            Some(batch_start)
+        } else {
+            Some(batch_start.checked_add(self.interval).unwrap_or(batch_start))
+        }
+    }
+
+    /// Returns the pending events as a single PDU, if they are due at `now`.
+    pub fn poll(&mut self, now: Instant) -> Option<FastPathInput> {
+        if self.deadline()? <= now {
+            self.flush()
+        } else {
+            None
+        }
+    }
+
+    /// Returns the pending events as a single PDU, regardless of the batching interval.
+    ///
+    /// The events which don't fit in a single PDU are kept pending, and due right away.
+    pub fn flush(&mut self) -> Option<FastPathInput> {
+        if self.pending.is_empty() {
+            return None;
+        }
+
+        let len = self.first_pdu_len();
+
+        let events = if len < self.pending.len() {
+            self.pending.drain(..len).collect()
+        } else {
+            self.batch_start = None;
+            std::mem::take(&mut self.pending)
+        };
+
+        Some(FastPathInput(events))
+    }
+
+    /// Returns all the pending events, as many PDUs as needed.
+    pub fn flush_all(&mut self) -> Vec<FastPathInput> {
+        std::iter::from_fn(|| self.flush()).collect()
+    }
+
+    /// Returns all the pending events, as many encoded PDUs as needed.
+    pub fn flush_encoded(&mut self) -> EncodeResult<Vec<Vec<u8>>> {
+        self.flush_all().iter().map(encode_vec).collect()
+    }
+
+    /// Returns the number of pending events fitting in the next PDU.
+    fn first_pdu_len(&self) -> usize {
+        let mut pdu_size = MAX_HEADER_SIZE;
+
+        let len = self
+            .pending
+            .iter()
+            .take(self.max_events)
+            .take_while(|event| {
+                pdu_size = pdu_size.saturating_add(event.size());
+                pdu_size <= self.max_pdu_size
+            })
+            .count();
+
+        len.max(1).min(self.pending.len())
+    }
+}
+
+fn coalesce(last: &FastPathInputEvent, event: &FastPathInputEvent) -> Option<FastPathInputEvent> {
+    match (last, event) {
+        (FastPathInputEvent::MouseEvent(last), FastPathInputEvent::MouseEvent(event))
+            if last.flags == PointerFlags::MOVE && event.flags == PointerFlags::MOVE =>
+        {
+            Some(FastPathInputEvent::MouseEvent(event.clone()))
+        }
+        (FastPathInputEvent::MouseEventRel(last), FastPathInputEvent::MouseEventRel(event))
+            if last.flags == PointerRelFlags::MOVE && event.flags == PointerRelFlags::MOVE =>
+        {
+            let mut coalesced = event.clone();
+            coalesced.x_delta = last.x_delta.checked_add(event.x_delta)?;
+            coalesced.y_delta = last.y_delta.checked_add(event.y_delta)?;
+            Some(FastPathInputEvent::MouseEventRel(coalesced))
+        }
+        _ => None,
+    }
+}
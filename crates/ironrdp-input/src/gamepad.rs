@@ -0,0 +1,218 @@
+//! Game controller input
+//!
+//! RDP has no standard channel for game controllers. [`GamepadStatePdu`] carries the state of a controller using the
+//! layout of the XInput `XINPUT_STATE` structure, prefixed with the index of the controller. It is meant to be sent
+//! over a dynamic virtual channel opened by the server-side component which injects the controllers.
+
+use std::collections::BTreeMap;
+
+use bitflags::bitflags;
+use ironrdp_core::{ensure_fixed_part_size, Encode, EncodeResult, WriteCursor};
+use smallvec::SmallVec;
+
+use crate::Operation;
+
+/// Maximum number of controllers, as supported by XInput
+pub const MAX_GAMEPADS: u8 = 4;
+
+bitflags! {
+    /// XINPUT_GAMEPAD, wButtons field
+    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct GamepadButtons: u16 {
+        const DPAD_UP = 0x0001;
+        const DPAD_DOWN = 0x0002;
+        const DPAD_LEFT = 0x0004;
+        const DPAD_RIGHT = 0x0008;
+        const START = 0x0010;
+        const BACK = 0x0020;
+        const LEFT_THUMB = 0x0040;
+        const RIGHT_THUMB = 0x0080;
+        const LEFT_SHOULDER = 0x0100;
+        const RIGHT_SHOULDER = 0x0200;
+        const A = 0x1000;
+        const B = 0x2000;
+        const X = 0x4000;
+        const Y = 0x8000;
+    }
+}
+
+/// Analog controls of a game controller
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GamepadAxis {
+    LeftStickX,
+    LeftStickY,
+    RightStickX,
+    RightStickY,
+    /// In the range 0 to 255
+    LeftTrigger,
+    /// In the range 0 to 255
+    RightTrigger,
+}
+
+/// XINPUT_GAMEPAD
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct GamepadState {
+    pub buttons: GamepadButtons,
+    pub left_trigger: u8,
+    pub right_trigger: u8,
+    pub left_stick_x: i16,
+    pub left_stick_y: i16,
+    pub right_stick_x: i16,
+    pub right_stick_y: i16,
+}
+
+impl GamepadState {
+    const NAME: &'static str = "XINPUT_GAMEPAD";
+
+    const FIXED_PART_SIZE: usize = 2 /* wButtons */ + 1 /* bLeftTrigger */ + 1 /* bRightTrigger */ + 2 * 4 /* sThumb* */;
+
+    fn set_axis(&mut self, axis: GamepadAxis, value: i16) {
+        let trigger_value = || u8::try_from(value.max(0)).unwrap_or(u8::MAX);
+
+        match axis {
+            GamepadAxis::LeftStickX => self.left_stick_x = value,
+            GamepadAxis::LeftStickY => self.left_stick_y = value,
+            GamepadAxis::RightStickX => self.right_stick_x = value,
+            GamepadAxis::RightStickY => self.right_stick_y = value,
+            GamepadAxis::LeftTrigger => self.left_trigger = trigger_value(),
+            GamepadAxis::RightTrigger => self.right_trigger = trigger_value(),
+        }
+    }
+}
+
+impl Encode for GamepadState {
+    fn encode(&self, dst: &mut WriteCursor<'_>) -> EncodeResult<()> {
+        ensure_fixed_part_size!(in: dst);
+
+        dst.write_u16(self.buttons.bits());
+        dst.write_u8(self.left_trigger);
+        dst.write_u8(self.right_trigger);
+        dst.write_i16(self.left_stick_x);
+        dst.write_i16(self.left_stick_y);
+        dst.write_i16(self.right_stick_x);
+        dst.write_i16(self.right_stick_y);
+
+        Ok(())
+    }
+
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    fn size(&self) -> usize {
+        Self::FIXED_PART_SIZE
+    }
+}
+
+/// State of a game controller: index of the controller, followed by XINPUT_STATE
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct GamepadStatePdu {
+    pub gamepad: u8,
+    /// Incremented each time the state of the controller changes
+    pub packet_number: u32,
+    pub state: GamepadState,
+}
+
+impl GamepadStatePdu {
+    const NAME: &'static str = "GamepadStatePdu";
+
+    const FIXED_PART_SIZE: usize = 1 /* gamepad */ + 4 /* dwPacketNumber */ + GamepadState::FIXED_PART_SIZE;
+}
+
+impl Encode for GamepadStatePdu {
+    fn encode(&self, dst: &mut WriteCursor<'_>) -> EncodeResult<()> {
+        ensure_fixed_part_size!(in: dst);
+
+        dst.write_u8(self.gamepad);
+        dst.write_u32(self.packet_number);
+        self.state.encode(dst)
+    }
+
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    fn size(&self) -> usize {
+        Self::FIXED_PART_SIZE
+    }
+}
+
+/// Builds controller state PDUs out of gamepad operations, keeping track of the state of each controller.
+#[derive(Debug, Clone, Default)]
+pub struct GamepadEncoder {
+    gamepads: BTreeMap<u8, (u32, GamepadState)>,
+}
+
+impl GamepadEncoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn state(&self, gamepad: u8) -> GamepadState {
+        self.gamepads.get(&gamepad).map(|(_, state)| *state).unwrap_or_default()
+    }
+
+    /// Applies a transaction (list of operations) and returns the state of each controller it changed.
+    ///
+    /// Operations other than gamepad ones, those which would cause no state change, and those targeting a controller
+    /// index out of range are ignored.
+    pub fn apply(&mut self, transaction: impl IntoIterator<Item = Operation>) -> SmallVec<[GamepadStatePdu; 1]> {
+        let mut changed = BTreeMap::new();
+
+        for operation in transaction {
+            let gamepad = match operation {
+                Operation::GamepadButtonPressed { gamepad, .. }
+                | Operation::GamepadButtonReleased { gamepad, .. }
+                | Operation::GamepadAxisMoved { gamepad, .. } => gamepad,
+                _ => continue,
+            };
+
+            if gamepad >= MAX_GAMEPADS {
+                continue;
+            }
+
+            let state = changed.entry(gamepad).or_insert_with(|| self.state(gamepad));
+
+            match operation {
+                Operation::GamepadButtonPressed { buttons, .. } => state.buttons.insert(buttons),
+                Operation::GamepadButtonReleased { buttons, .. } => state.buttons.remove(buttons),
+                Operation::GamepadAxisMoved { axis, value, .. } => state.set_axis(axis, value),
+                _ => {}
+            }
+        }
+
+        changed
+            .into_iter()
+            .filter_map(|(gamepad, state)| self.update(gamepad, state))
+            .collect()
+    }
+
+    /// Resets all the controllers to their neutral state. Returns the states to send.
+    pub fn reset_all(&mut self) -> SmallVec<[GamepadStatePdu; 1]> {
+        let gamepads: Vec<u8> = self.gamepads.keys().copied().collect();
+
+        gamepads
+            .into_iter()
+            .filter_map(|gamepad| self.update(gamepad, GamepadState::default()))
+            .collect()
+    }
+
+    fn update(&mut self, gamepad: u8, state: GamepadState) -> Option<GamepadStatePdu> {
+        let (packet_number, current) = self.gamepads.entry(gamepad).or_default();
+
+        if *current == state {
+            return None;
+        }
+
+        *packet_number = packet_number.wrapping_add(1);
+        *current = state;
+
+        Some(GamepadStatePdu {
+            gamepad,
+            packet_number: *packet_number,
+            state,
+        })
+    }
+}
@@ -0,0 +1,136 @@
+//! Input latency instrumentation
+//!
+//! Operations applied with [`Database::apply_timed`](crate::Database::apply_timed) carry the instant at which the
+//! user produced them. A [`LatencyTracker`] remembers the instants of the events sent to the server, and records the
+//! elapsed time into a [`LatencyMetrics`] sink once the server acknowledges a frame.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use ironrdp_pdu::input::fast_path::FastPathInputEvent;
+
+use crate::Operation;
+
+/// An operation, along with the instant at which it originated, if known.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimedOperation {
+    pub operation: Operation,
+    pub timestamp: Option<Instant>,
+}
+
+impl TimedOperation {
+    pub fn new(operation: Operation, timestamp: Instant) -> Self {
+        Self {
+            operation,
+            timestamp: Some(timestamp),
+        }
+    }
+}
+
+impl From<Operation> for TimedOperation {
+    fn from(operation: Operation) -> Self {
+        Self {
+            operation,
+            timestamp: None,
+        }
+    }
+}
+
+/// An RDP input event, along with the instant at which the operation producing it originated, if known.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimedEvent {
+    pub event: FastPathInputEvent,
+    pub timestamp: Option<Instant>,
+}
+
+/// Sink for input latency measurements
+pub trait LatencyMetrics {
+    fn record_input_latency(&mut self, latency: Duration);
+}
+
+/// Upper bounds of the [`LatencyHistogram`] buckets, in milliseconds
+pub const BUCKET_BOUNDS_MS: [u64; 10] = [1, 2, 5, 10, 20, 50, 100, 200, 500, 1000];
+
+/// Aggregates latency measurements into buckets.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LatencyHistogram {
+    /// One bucket per bound, plus one for the measurements above the last bound
+    buckets: [u64; BUCKET_BOUNDS_MS.len() + 1],
+    count: u64,
+    sum: Duration,
+    max: Duration,
+}
+
+impl LatencyHistogram {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    pub fn max(&self) -> Duration {
+        self.max
+    }
+
+    pub fn mean(&self) -> Option<Duration> {
+        let count = u32::try_from(self.count).ok().filter(|count| *count != 0)?;
+        self.sum.checked_div(count)
+    }
+
+    /// Returns the number of measurements of each bucket, along with its upper bound.
+    ///
+    /// The last bucket has no upper bound.
+    pub fn buckets(&self) -> impl Iterator<Item = (Option<Duration>, u64)> + '_ {
+        let bounds = BUCKET_BOUNDS_MS.iter().map(|ms| Some(Duration::from_millis(*ms)));
+        bounds.chain([None]).zip(self.buckets.iter().copied())
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+impl LatencyMetrics for LatencyHistogram {
+    fn record_input_latency(&mut self, latency: Duration) {
+        let idx = BUCKET_BOUNDS_MS
+            .iter()
+            .position(|ms| latency <= Duration::from_millis(*ms))
+            .unwrap_or(BUCKET_BOUNDS_MS.len());
+
+        self.buckets[idx] = self.buckets[idx].saturating_add(1);
+        self.count = self.count.saturating_add(1);
+        self.sum = self.sum.saturating_add(latency);
+        self.max = self.max.max(latency);
+    }
+}
+
+/// Correlates the input events sent to the server with the frame acknowledgements.
+#[derive(Debug, Clone, Default)]
+pub struct LatencyTracker {
+    pending: VecDeque<Instant>,
+}
+
+impl LatencyTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Remembers the origination instants of events sent to the server. Events without timestamp are ignored.
+    pub fn sent<'a>(&mut self, events: impl IntoIterator<Item = &'a TimedEvent>) {
+        self.pending
+            .extend(events.into_iter().filter_map(|event| event.timestamp));
+    }
+
+    pub fn pending_count(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Records the latency of all the events sent so far, the server having acknowledged a frame at `now`.
+    pub fn frame_acknowledged(&mut self, now: Instant, metrics: &mut impl LatencyMetrics) {
+        for timestamp in self.pending.drain(..) {
+            metrics.record_input_latency(now.saturating_duration_since(timestamp));
+        }
+    }
+}
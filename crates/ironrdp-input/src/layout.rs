@@ -0,0 +1,269 @@
+//! Keyboard layouts, for typing characters using scancodes
+//!
+//! Web front-ends usually only know the character typed by the user (`KeyboardEvent.key`), not the physical key
+//! which produced it. A [`KeyboardLayout`] maps such characters back to the key, and the modifiers, producing them
+//! with the keyboard layout of the server.
+
+use std::collections::BTreeMap;
+
+use smallvec::SmallVec;
+
+use crate::{Operation, Scancode};
+
+/// Left Shift key
+pub const SHIFT: Scancode = Scancode::from_u8(false, 0x2A);
+
+/// AltGr key (Right Alt)
+pub const ALT_GR: Scancode = Scancode::from_u8(true, 0x38);
+
+/// Modifier keys held down while pressing a key
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Modifiers {
+    pub shift: bool,
+    pub alt_gr: bool,
+}
+
+impl Modifiers {
+    pub const NONE: Self = Self {
+        shift: false,
+        alt_gr: false,
+    };
+
+    pub const SHIFT: Self = Self {
+        shift: true,
+        alt_gr: false,
+    };
+
+    pub const ALT_GR: Self = Self {
+        shift: false,
+        alt_gr: true,
+    };
+
+    pub const SHIFT_ALT_GR: Self = Self {
+        shift: true,
+        alt_gr: true,
+    };
+
+    fn scancodes(self) -> impl DoubleEndedIterator<Item = Scancode> {
+        [(self.shift, SHIFT), (self.alt_gr, ALT_GR)]
+            .into_iter()
+            .filter_map(|(held, scancode)| held.then_some(scancode))
+    }
+}
+
+/// Key, along with its modifiers, producing a character
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyStroke {
+    pub scancode: Scancode,
+    pub modifiers: Modifiers,
+}
+
+impl KeyStroke {
+    pub const fn new(scancode: Scancode, modifiers: Modifiers) -> Self {
+        Self { scancode, modifiers }
+    }
+
+    /// Presses the modifiers, then the key.
+    pub fn press(self) -> SmallVec<[Operation; 3]> {
+        self.modifiers
+            .scancodes()
+            .chain([self.scancode])
+            .map(Operation::KeyPressed)
+            .collect()
+    }
+
+    /// Releases the key, then the modifiers.
+    pub fn release(self) -> SmallVec<[Operation; 3]> {
+        [self.scancode]
+            .into_iter()
+            .chain(self.modifiers.scancodes().rev())
+            .map(Operation::KeyReleased)
+            .collect()
+    }
+
+    /// Presses and releases the key, along with its modifiers.
+    pub fn tap(self) -> SmallVec<[Operation; 6]> {
+        self.press().into_iter().chain(self.release()).collect()
+    }
+}
+
+/// Entry of a keyboard layout table: the character, and the key stroke producing it
+pub type LayoutEntry = (char, KeyStroke);
+
+/// Mapping of characters to the key strokes producing them
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct KeyboardLayout {
+    characters: BTreeMap<char, KeyStroke>,
+}
+
+impl KeyboardLayout {
+    /// Builds a layout from a table, later entries taking precedence for the same character
+    pub fn from_table(table: &[LayoutEntry]) -> Self {
+        Self {
+            characters: table.iter().copied().collect(),
+        }
+    }
+
+    /// US English (QWERTY) layout
+    pub fn us() -> Self {
+        Self::from_table(US)
+    }
+
+    /// Adds or replaces the key stroke producing `character`, returning the previous one.
+    pub fn insert(&mut self, character: char, key_stroke: KeyStroke) -> Option<KeyStroke> {
+        self.characters.insert(character, key_stroke)
+    }
+
+    /// Returns the key stroke producing `character`, if any.
+    pub fn key_stroke(&self, character: char) -> Option<KeyStroke> {
+        self.characters.get(&character).copied()
+    }
+
+    /// Returns the key stroke matching a [`KeyboardEvent.key`] value.
+    ///
+    /// Named keys (e.g. `Enter` or `ArrowLeft`) do not depend on the layout.
+    ///
+    /// [`KeyboardEvent.key`]: https://developer.mozilla.org/en-US/docs/Web/API/KeyboardEvent/key
+    pub fn key_value(&self, key: &str) -> Option<KeyStroke> {
+        let mut chars = key.chars();
+
+        match (chars.next(), chars.next()) {
+            (Some(character), None) => self.key_stroke(character),
+            _ => named_key_scancode(key).map(|scancode| KeyStroke::new(scancode, Modifiers::NONE)),
+        }
+    }
+
+    /// Returns the operations typing `text`.
+    ///
+    /// Characters missing from the layout are sent as Unicode keyboard events.
+    pub fn text_operations(&self, text: &str) -> Vec<Operation> {
+        let mut operations = Vec::with_capacity(text.len().saturating_mul(2));
+
+        for character in text.chars() {
+            match self.key_stroke(character) {
+                Some(key_stroke) => operations.extend(key_stroke.tap()),
+                None => operations.extend([
+                    Operation::UnicodeKeyPressed(character),
+                    Operation::UnicodeKeyReleased(character),
+                ]),
+            }
+        }
+
+        operations
+    }
+}
+
+/// Returns the scancode of a named [`KeyboardEvent.key`] value, such as `Enter` or `F5`.
+///
+/// [`KeyboardEvent.key`]: https://developer.mozilla.org/en-US/docs/Web/API/KeyboardEvent/key
+pub fn named_key_scancode(key: &str) -> Option<Scancode> {
+    let (extended, code) = match key {
+        "Escape" => (false, 0x01),
+        "Backspace" => (false, 0x0E),
+        "Tab" => (false, 0x0F),
+        "Enter" => (false, 0x1C),
+        "Control" => (false, 0x1D),
+        "Shift" => (false, 0x2A),
+        "Alt" => (false, 0x38),
+        "CapsLock" => (false, 0x3A),
+        "F1" => (false, 0x3B),
+        "F2" => (false, 0x3C),
+        "F3" => (false, 0x3D),
+        "F4" => (false, 0x3E),
+        "F5" => (false, 0x3F),
+        "F6" => (false, 0x40),
+        "F7" => (false, 0x41),
+        "F8" => (false, 0x42),
+        "F9" => (false, 0x43),
+        "F10" => (false, 0x44),
+        "NumLock" => (false, 0x45),
+        "ScrollLock" => (false, 0x46),
+        "F11" => (false, 0x57),
+        "F12" => (false, 0x58),
+        "PrintScreen" => (true, 0x37),
+        "AltGraph" => (true, 0x38),
+        "Home" => (true, 0x47),
+        "ArrowUp" => (true, 0x48),
+        "PageUp" => (true, 0x49),
+        "ArrowLeft" => (true, 0x4B),
+        "ArrowRight" => (true, 0x4D),
+        "End" => (true, 0x4F),
+        "ArrowDown" => (true, 0x50),
+        "PageDown" => (true, 0x51),
+        "Insert" => (true, 0x52),
+        "Delete" => (true, 0x53),
+        "Meta" => (true, 0x5B),
+        "ContextMenu" => (true, 0x5D),
+        _ => return None,
+    };
+
+    Some(Scancode::from_u8(extended, code))
+}
+
+const fn key(character: char, code: u8) -> LayoutEntry {
+    (
+        character,
+        KeyStroke::new(Scancode::from_u8(false, code), Modifiers::NONE),
+    )
+}
+
+const fn shifted(character: char, code: u8) -> LayoutEntry {
+    (
+        character,
+        KeyStroke::new(Scancode::from_u8(false, code), Modifiers::SHIFT),
+    )
+}
+
+#[rustfmt::skip]
+const US: &[LayoutEntry] = &[
+    key('`', 0x29), shifted('~', 0x29),
+    key('1', 0x02), shifted('!', 0x02),
+    key('2', 0x03), shifted('@', 0x03),
+    key('3', 0x04), shifted('#', 0x04),
+    key('4', 0x05), shifted('$', 0x05),
+    key('5', 0x06), shifted('%', 0x06),
+    key('6', 0x07), shifted('^', 0x07),
+    key('7', 0x08), shifted('&', 0x08),
+    key('8', 0x09), shifted('*', 0x09),
+    key('9', 0x0A), shifted('(', 0x0A),
+    key('0', 0x0B), shifted(')', 0x0B),
+    key('-', 0x0C), shifted('_', 0x0C),
+    key('=', 0x0D), shifted('+', 0x0D),
+    key('\t', 0x0F),
+    key('q', 0x10), shifted('Q', 0x10),
+    key('w', 0x11), shifted('W', 0x11),
+    key('e', 0x12), shifted('E', 0x12),
+    key('r', 0x13), shifted('R', 0x13),
+    key('t', 0x14), shifted('T', 0x14),
+    key('y', 0x15), shifted('Y', 0x15),
+    key('u', 0x16), shifted('U', 0x16),
+    key('i', 0x17), shifted('I', 0x17),
+    key('o', 0x18), shifted('O', 0x18),
+    key('p', 0x19), shifted('P', 0x19),
+    key('[', 0x1A), shifted('{', 0x1A),
+    key(']', 0x1B), shifted('}', 0x1B),
+    key('\\', 0x2B), shifted('|', 0x2B),
+    key('a', 0x1E), shifted('A', 0x1E),
+    key('s', 0x1F), shifted('S', 0x1F),
+    key('d', 0x20), shifted('D', 0x20),
+    key('f', 0x21), shifted('F', 0x21),
+    key('g', 0x22), shifted('G', 0x22),
+    key('h', 0x23), shifted('H', 0x23),
+    key('j', 0x24), shifted('J', 0x24),
+    key('k', 0x25), shifted('K', 0x25),
+    key('l', 0x26), shifted('L', 0x26),
+    key(';', 0x27), shifted(':', 0x27),
+    key('\'', 0x28), shifted('"', 0x28),
+    key('\n', 0x1C),
+    key('z', 0x2C), shifted('Z', 0x2C),
+    key('x', 0x2D), shifted('X', 0x2D),
+    key('c', 0x2E), shifted('C', 0x2E),
+    key('v', 0x2F), shifted('V', 0x2F),
+    key('b', 0x30), shifted('B', 0x30),
+    key('n', 0x31), shifted('N', 0x31),
+    key('m', 0x32), shifted('M', 0x32),
+    key(',', 0x33), shifted('<', 0x33),
+    key('.', 0x34), shifted('>', 0x34),
+    key('/', 0x35), shifted('?', 0x35),
+    key(' ', 0x39),
+];
@@ -2,12 +2,26 @@ use bitvec::array::BitArray;
 use bitvec::BitArr;
 use ironrdp_pdu::input::fast_path::{FastPathInputEvent, KeyboardFlags};
 use ironrdp_pdu::input::mouse::PointerFlags;
+use ironrdp_pdu::input::mouse_rel::PointerRelFlags;
 use ironrdp_pdu::input::mouse_x::PointerXFlags;
-use ironrdp_pdu::input::{MousePdu, MouseXPdu};
+use ironrdp_pdu::input::{MousePdu, MouseRelPdu, MouseXPdu};
+use ironrdp_pdu::rdp::capability_sets::InputFlags;
 use smallvec::SmallVec;
 use std::collections::BTreeSet;
 
+pub mod batch;
+pub mod gamepad;
+pub mod latency;
+pub mod layout;
+pub mod pen;
+pub mod recording;
+pub mod scancode;
+pub mod seat;
+pub mod shortcut;
+pub mod touch;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u8)]
 pub enum MouseButton {
     Left = 0,
@@ -61,6 +75,7 @@ impl MouseButton {
 
 /// Keyboard scan code.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Scancode {
     code: u8,
     extended: bool,
@@ -80,6 +95,15 @@ impl Scancode {
         Self { code, extended }
     }
 
+    fn from_idx(idx: usize) -> Self {
+        if idx >= 256 {
+            let extended_code = idx.checked_sub(256).expect("never underflow");
+            Self::from_u8(true, u8::try_from(extended_code).unwrap())
+        } else {
+            Self::from_u8(false, u8::try_from(idx).unwrap())
+        }
+    }
+
     pub fn as_idx(self) -> usize {
         if self.extended {
             usize::from(self.code).checked_add(256).expect("never overflow")
@@ -113,30 +137,333 @@ impl From<u16> for Scancode {
     }
 }
 
-/// Cursor position for a mouse device.
+/// Modifier keys, told apart by side.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ModifierKey {
+    LeftShift,
+    RightShift,
+    LeftCtrl,
+    RightCtrl,
+    LeftAlt,
+    RightAlt,
+    LeftMeta,
+    RightMeta,
+}
+
+impl ModifierKey {
+    pub const ALL: [Self; 8] = [
+        Self::LeftShift,
+        Self::RightShift,
+        Self::LeftCtrl,
+        Self::RightCtrl,
+        Self::LeftAlt,
+        Self::RightAlt,
+        Self::LeftMeta,
+        Self::RightMeta,
+    ];
+
+    pub const fn scancode(self) -> Scancode {
+        match self {
+            Self::LeftShift => Scancode::from_u8(false, 0x2A),
+            Self::RightShift => Scancode::from_u8(false, 0x36),
+            Self::LeftCtrl => Scancode::from_u8(false, 0x1D),
+            Self::RightCtrl => Scancode::from_u8(true, 0x1D),
+            Self::LeftAlt => Scancode::from_u8(false, 0x38),
+            Self::RightAlt => Scancode::from_u8(true, 0x38),
+            Self::LeftMeta => Scancode::from_u8(true, 0x5B),
+            Self::RightMeta => Scancode::from_u8(true, 0x5C),
+        }
+    }
+
+    pub fn from_scancode(scancode: Scancode) -> Option<Self> {
+        Self::ALL.into_iter().find(|key| key.scancode() == scancode)
+    }
+}
+
+/// Snapshot of the modifier keys held down.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct ModifierState {
+    pub left_shift: bool,
+    pub right_shift: bool,
+    pub left_ctrl: bool,
+    pub right_ctrl: bool,
+    pub left_alt: bool,
+    pub right_alt: bool,
+    pub left_meta: bool,
+    pub right_meta: bool,
+}
+
+impl ModifierState {
+    pub fn is_pressed(&self, key: ModifierKey) -> bool {
+        match key {
+            ModifierKey::LeftShift => self.left_shift,
+            ModifierKey::RightShift => self.right_shift,
+            ModifierKey::LeftCtrl => self.left_ctrl,
+            ModifierKey::RightCtrl => self.right_ctrl,
+            ModifierKey::LeftAlt => self.left_alt,
+            ModifierKey::RightAlt => self.right_alt,
+            ModifierKey::LeftMeta => self.left_meta,
+            ModifierKey::RightMeta => self.right_meta,
+        }
+    }
+
+    /// Returns true if either Shift key is held down.
+    pub fn shift(&self) -> bool {
+        self.left_shift || self.right_shift
+    }
+
+    /// Returns true if either Ctrl key is held down.
+    pub fn ctrl(&self) -> bool {
+        self.left_ctrl || self.right_ctrl
+    }
+
+    /// Returns true if either Alt key is held down.
+    pub fn alt(&self) -> bool {
+        self.left_alt || self.right_alt
+    }
+
+    /// Returns true if either Meta (Windows) key is held down.
+    pub fn meta(&self) -> bool {
+        self.left_meta || self.right_meta
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Keys pressed together, such as the Ctrl+Alt+Del secure attention sequence.
+///
+/// The keys are pressed in order, then released in reverse order.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct KeyCombo {
+    keys: SmallVec<[Scancode; 4]>,
+}
+
+impl KeyCombo {
+    const LEFT_CTRL: Scancode = ModifierKey::LeftCtrl.scancode();
+    const LEFT_SHIFT: Scancode = ModifierKey::LeftShift.scancode();
+    const LEFT_ALT: Scancode = ModifierKey::LeftAlt.scancode();
+    const LEFT_WIN: Scancode = ModifierKey::LeftMeta.scancode();
+
+    pub fn new(keys: impl IntoIterator<Item = Scancode>) -> Self {
+        Self {
+            keys: keys.into_iter().collect(),
+        }
+    }
+
+    /// Ctrl+Alt+Del
+    pub fn ctrl_alt_del() -> Self {
+        Self::new([Self::LEFT_CTRL, Self::LEFT_ALT, Scancode::from_u8(true, 0x53)])
+    }
+
+    /// Ctrl+Shift+Esc, opening the task manager
+    pub fn ctrl_shift_esc() -> Self {
+        Self::new([Self::LEFT_CTRL, Self::LEFT_SHIFT, Scancode::from_u8(false, 0x01)])
+    }
+
+    /// Win+L, locking the session
+    pub fn win_l() -> Self {
+        Self::new([Self::LEFT_WIN, Scancode::from_u8(false, 0x26)])
+    }
+
+    /// Alt+Tab
+    pub fn alt_tab() -> Self {
+        Self::new([Self::LEFT_ALT, Scancode::from_u8(false, 0x0F)])
+    }
+
+    pub fn keys(&self) -> &[Scancode] {
+        &self.keys
+    }
+
+    /// Returns the operations pressing, then releasing the keys.
+    pub fn operations(&self) -> SmallVec<[Operation; 8]> {
+        let presses = self.keys.iter().copied().map(Operation::KeyPressed);
+        let releases = self.keys.iter().rev().copied().map(Operation::KeyReleased);
+        presses.chain(releases).collect()
+    }
+}
+
+/// Cursor position for a mouse device.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MousePosition {
     pub x: u16,
     pub y: u16,
 }
 
+/// Relative movement of a mouse device, e.g. while the pointer is locked by the client.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MouseDelta {
+    pub x: i16,
+    pub y: i16,
+}
+
+/// How mouse movements and buttons are reported to the server.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum MouseMode {
+    /// Mouse events carry the absolute cursor position.
+    #[default]
+    Absolute,
+    /// Relative mouse events carry movement deltas. Requires the server to advertise
+    /// [`InputFlags::MOUSE_RELATIVE`].
+    Relative,
+}
+
+/// State of the lock keys.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct LockKeys {
+    pub scroll_lock: bool,
+    pub num_lock: bool,
+    pub caps_lock: bool,
+    pub kana_lock: bool,
+}
+
+impl LockKeys {
+    const CAPS_LOCK: Scancode = Scancode::from_u8(false, 0x3A);
+    const NUM_LOCK: Scancode = Scancode::from_u8(false, 0x45);
+    const SCROLL_LOCK: Scancode = Scancode::from_u8(false, 0x46);
+
+    /// Toggles the lock matching the pressed key, if any.
+    fn toggle(&mut self, scancode: Scancode) {
+        match scancode {
+            Self::CAPS_LOCK => self.caps_lock = !self.caps_lock,
+            Self::NUM_LOCK => self.num_lock = !self.num_lock,
+            Self::SCROLL_LOCK => self.scroll_lock = !self.scroll_lock,
+            _ => {}
+        }
+    }
+}
+
+/// How a [`Operation::KeyPressed`] of a key already held down, typically produced by the keyboard auto-repeat,
+/// is forwarded to the server.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum KeyRepeatPolicy {
+    /// The repeated press is dropped, leaving the auto-repeat to the server.
+    Ignore,
+    /// The key is released, then pressed again.
+    #[default]
+    ReleaseThenPress,
+    /// The press is sent again without release, which the server interprets as a repeat, like a physical keyboard
+    /// does.
+    ForwardRepeat,
+}
+
+/// How mouse wheel rotations are transformed before being sent to the server.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WheelSettings {
+    /// Inverts the direction of both axes, as with the natural scrolling of touchpads.
+    pub natural_scrolling: bool,
+    /// Factor applied to vertical rotations. A negative factor inverts the axis.
+    pub vertical_scale: f32,
+    /// Factor applied to horizontal rotations. A negative factor inverts the axis.
+    pub horizontal_scale: f32,
+}
+
+impl Default for WheelSettings {
+    fn default() -> Self {
+        Self {
+            natural_scrolling: false,
+            vertical_scale: 1.0,
+            horizontal_scale: 1.0,
+        }
+    }
+}
+
 /// Mouse wheel rotations.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct WheelRotations {
     pub is_vertical: bool,
     pub rotation_units: i16,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Operation {
     MouseButtonPressed(MouseButton),
     MouseButtonReleased(MouseButton),
     MouseMove(MousePosition),
+    RelativeMouseMove(MouseDelta),
     WheelRotations(WheelRotations),
     KeyPressed(Scancode),
     KeyReleased(Scancode),
     UnicodeKeyPressed(char),
     UnicodeKeyReleased(char),
+    /// Synchronizes the lock keys with the server, unless they already match the last state sent.
+    SyncLocks {
+        scroll_lock: bool,
+        num_lock: bool,
+        caps_lock: bool,
+        kana_lock: bool,
+    },
+    TouchDown(touch::TouchContact),
+    TouchMove(touch::TouchContact),
+    TouchUp(touch::TouchContact),
+    /// Moves the pen, bringing it in range if needed
+    PenMove(pen::PenContact),
+    PenDown(pen::PenContact),
+    /// Lifts the pen, which keeps hovering
+    PenUp(pen::PenContact),
+    /// Takes the pen out of range
+    PenLeave(pen::PenContact),
+    GamepadButtonPressed {
+        gamepad: u8,
+        buttons: gamepad::GamepadButtons,
+    },
+    GamepadButtonReleased {
+        gamepad: u8,
+        buttons: gamepad::GamepadButtons,
+    },
+    GamepadAxisMoved {
+        gamepad: u8,
+        axis: gamepad::GamepadAxis,
+        value: i16,
+    },
+}
+
+/// Keyboard and mouse state of a [`Database`], e.g. to carry it over a reconnection.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InputSnapshot {
+    pub pressed_keys: Vec<Scancode>,
+    pub pressed_unicode_keys: Vec<char>,
+    pub pressed_mouse_buttons: Vec<MouseButton>,
+    pub mouse_position: MousePosition,
+    pub lock_keys: Option<LockKeys>,
+}
+
+impl InputSnapshot {
+    /// Returns the operations bringing a fresh [`Database`], and the server, to this state.
+    pub fn operations(&self) -> Vec<Operation> {
+        let mut operations = Vec::new();
+
+        if let Some(lock_keys) = self.lock_keys {
+            operations.push(Operation::SyncLocks {
+                scroll_lock: lock_keys.scroll_lock,
+                num_lock: lock_keys.num_lock,
+                caps_lock: lock_keys.caps_lock,
+                kana_lock: lock_keys.kana_lock,
+            });
+        }
+
+        operations.push(Operation::MouseMove(self.mouse_position));
+        operations.extend(self.pressed_keys.iter().copied().map(Operation::KeyPressed));
+        operations.extend(
+            self.pressed_unicode_keys
+                .iter()
+                .copied()
+                .map(Operation::UnicodeKeyPressed),
+        );
+        operations.extend(
+            self.pressed_mouse_buttons
+                .iter()
+                .copied()
+                .map(Operation::MouseButtonPressed),
+        );
+
+        operations
+    }
 }
 
 pub type KeyboardState = BitArr!(for 512);
@@ -148,6 +475,15 @@ pub struct Database {
     keyboard: KeyboardState,
     mouse_buttons: MouseButtonsState,
     mouse_position: MousePosition,
+    mouse_mode: MouseMode,
+    relative_mouse_supported: bool,
+    lock_keys: Option<LockKeys>,
+    key_repeat_policy: KeyRepeatPolicy,
+    /// Width and height of the remote desktop, if known
+    desktop_size: Option<(u16, u16)>,
+    wheel_settings: WheelSettings,
+    /// Fractions of rotation units left over by the scaling, vertical then horizontal
+    wheel_remainders: (f32, f32),
 }
 
 impl Default for Database {
@@ -163,7 +499,123 @@ impl Database {
             mouse_buttons: BitArray::ZERO,
             mouse_position: MousePosition { x: 0, y: 0 },
             unicode_keyboard_state: BTreeSet::new(),
+            mouse_mode: MouseMode::Absolute,
+            relative_mouse_supported: false,
+            lock_keys: None,
+            key_repeat_policy: KeyRepeatPolicy::default(),
+            desktop_size: None,
+            wheel_settings: WheelSettings::default(),
+            wheel_remainders: (0.0, 0.0),
+        }
+    }
+
+    /// Restores the state exported by [`Database::to_snapshot`], without generating any input event.
+    ///
+    /// When the server side state was lost, as with a new connection, apply [`InputSnapshot::operations`] to a fresh
+    /// database instead.
+    pub fn from_snapshot(snapshot: &InputSnapshot) -> Self {
+        let mut database = Self::new();
+
+        for scancode in &snapshot.pressed_keys {
+            database.keyboard.set(scancode.as_idx(), true);
+        }
+
+        for button in &snapshot.pressed_mouse_buttons {
+            database.mouse_buttons.set(button.as_idx(), true);
+        }
+
+        database
+            .unicode_keyboard_state
+            .extend(snapshot.pressed_unicode_keys.iter().copied());
+        database.mouse_position = snapshot.mouse_position;
+        database.lock_keys = snapshot.lock_keys;
+
+        database
+    }
+
+    /// Exports the keyboard and mouse state.
+    ///
+    /// The mouse mode and the server input capabilities are specific to a connection, and not part of the snapshot.
+    pub fn to_snapshot(&self) -> InputSnapshot {
+        InputSnapshot {
+            pressed_keys: self.keyboard.iter_ones().map(Scancode::from_idx).collect(),
+            pressed_unicode_keys: self.unicode_keyboard_state.iter().copied().collect(),
+            pressed_mouse_buttons: self
+                .mouse_buttons
+                .iter_ones()
+                .map(|idx| MouseButton::from_idx(idx).expect("in-range index"))
+                .collect(),
+            mouse_position: self.mouse_position,
+            lock_keys: self.lock_keys,
+        }
+    }
+
+    /// Returns the state of the lock keys on the server side, as far as known.
+    ///
+    /// The state is unknown until a [`Operation::SyncLocks`] is applied.
+    pub fn lock_keys(&self) -> Option<LockKeys> {
+        self.lock_keys
+    }
+
+    /// Forgets the state of the lock keys, so that the next [`Operation::SyncLocks`] is always sent.
+    ///
+    /// Useful after reconnecting, since the server state is reset.
+    pub fn forget_lock_keys(&mut self) {
+        self.lock_keys = None;
+    }
+
+    /// Records the size of the remote desktop, e.g. after a dynamic resolution change.
+    ///
+    /// Mouse positions are then clamped to the desktop bounds, since some servers reject or misinterpret out-of-range
+    /// coordinates.
+    pub fn set_desktop_size(&mut self, width: u16, height: u16) {
+        self.desktop_size = Some((width, height));
+        self.mouse_position = self.clamp_position(self.mouse_position);
+    }
+
+    pub fn wheel_settings(&self) -> WheelSettings {
+        self.wheel_settings
+    }
+
+    pub fn set_wheel_settings(&mut self, settings: WheelSettings) {
+        self.wheel_settings = settings;
+        self.wheel_remainders = (0.0, 0.0);
+    }
+
+    pub fn key_repeat_policy(&self) -> KeyRepeatPolicy {
+        self.key_repeat_policy
+    }
+
+    pub fn set_key_repeat_policy(&mut self, policy: KeyRepeatPolicy) {
+        self.key_repeat_policy = policy;
+    }
+
+    /// Records the input capabilities advertised by the server.
+    ///
+    /// Falls back to the absolute mouse mode if relative mouse events are not supported.
+    pub fn set_server_input_flags(&mut self, flags: InputFlags) {
+        self.relative_mouse_supported = flags.contains(InputFlags::MOUSE_RELATIVE);
+
+        if !self.relative_mouse_supported {
+            self.mouse_mode = MouseMode::Absolute;
+        }
+    }
+
+    /// Switches between absolute and relative mouse events.
+    ///
+    /// Returns `false`, leaving the mode unchanged, when the server does not support relative mouse events.
+    pub fn set_mouse_mode(&mut self, mode: MouseMode) -> bool {
+        if mode == MouseMode::Relative && !self.relative_mouse_supported {
+            return false;
         }
+
+        self.mouse_mode = mode;
+
+        true
+    }
+
+    pub fn mouse_mode(&self) -> MouseMode {
+        self.mouse_mode
     }
 
     pub fn is_unicode_key_pressed(&self, character: char) -> bool {
@@ -178,6 +630,24 @@ impl Database {
             .unwrap_or(false)
     }
 
+    pub fn is_modifier_pressed(&self, key: ModifierKey) -> bool {
+        self.is_key_pressed(key.scancode())
+    }
+
+    /// Returns the modifier keys currently held down.
+    pub fn modifiers(&self) -> ModifierState {
+        ModifierState {
+            left_shift: self.is_modifier_pressed(ModifierKey::LeftShift),
+            right_shift: self.is_modifier_pressed(ModifierKey::RightShift),
+            left_ctrl: self.is_modifier_pressed(ModifierKey::LeftCtrl),
+            right_ctrl: self.is_modifier_pressed(ModifierKey::RightCtrl),
+            left_alt: self.is_modifier_pressed(ModifierKey::LeftAlt),
+            right_alt: self.is_modifier_pressed(ModifierKey::RightAlt),
+            left_meta: self.is_modifier_pressed(ModifierKey::LeftMeta),
+            right_meta: self.is_modifier_pressed(ModifierKey::RightMeta),
+        }
+    }
+
     pub fn is_mouse_button_pressed(&self, button: MouseButton) -> bool {
         self.mouse_buttons
             .get(button.as_idx())
@@ -200,7 +670,8 @@ impl Database {
 
     /// Apply a transaction (list of operations) and returns a list of RDP input events to send.
     ///
-    /// Operations that would cause no state change are ignored.
+    /// Operations that would cause no state change are ignored. Touch, pen and gamepad operations are ignored as well,
+    /// and handled by [`touch::TouchEncoder`], [`pen::PenEncoder`] and [`gamepad::GamepadEncoder`] instead.
     pub fn apply(&mut self, transaction: impl IntoIterator<Item = Operation>) -> SmallVec<[FastPathInputEvent; 2]> {
         let mut events = SmallVec::new();
 
@@ -210,45 +681,19 @@ impl Database {
                     let was_pressed = self.mouse_buttons.replace(button.as_idx(), true);
 
                     if !was_pressed {
-                        let event = match MouseButtonFlags::from(button) {
-                            MouseButtonFlags::Button(flags) => FastPathInputEvent::MouseEvent(MousePdu {
-                                flags: PointerFlags::DOWN | flags,
-                                number_of_wheel_rotation_units: 0,
-                                x_position: self.mouse_position.x,
-                                y_position: self.mouse_position.y,
-                            }),
-                            MouseButtonFlags::Pointer(flags) => FastPathInputEvent::MouseEventEx(MouseXPdu {
-                                flags: PointerXFlags::DOWN | flags,
-                                x_position: self.mouse_position.x,
-                                y_position: self.mouse_position.y,
-                            }),
-                        };
-
-                        events.push(event)
+                        events.push(self.mouse_button_event(button, true))
                     }
                 }
                 Operation::MouseButtonReleased(button) => {
                     let was_pressed = self.mouse_buttons.replace(button.as_idx(), false);
 
                     if was_pressed {
-                        let event = match MouseButtonFlags::from(button) {
-                            MouseButtonFlags::Button(flags) => FastPathInputEvent::MouseEvent(MousePdu {
-                                flags,
-                                number_of_wheel_rotation_units: 0,
-                                x_position: self.mouse_position.x,
-                                y_position: self.mouse_position.y,
-                            }),
-                            MouseButtonFlags::Pointer(flags) => FastPathInputEvent::MouseEventEx(MouseXPdu {
-                                flags,
-                                x_position: self.mouse_position.x,
-                                y_position: self.mouse_position.y,
-                            }),
-                        };
-
-                        events.push(event)
+                        events.push(self.mouse_button_event(button, false))
                     }
                 }
                 Operation::MouseMove(position) => {
+                    let position = self.clamp_position(position);
+
                     if position != self.mouse_position {
                         self.mouse_position = position;
                         events.push(FastPathInputEvent::MouseEvent(MousePdu {
@@ -259,19 +704,60 @@ impl Database {
                         }))
                     }
                 }
-                Operation::WheelRotations(rotations) => events.push(FastPathInputEvent::MouseEvent(MousePdu {
-                    flags: if rotations.is_vertical {
-                        PointerFlags::VERTICAL_WHEEL
-                    } else {
-                        PointerFlags::HORIZONTAL_WHEEL
-                    },
-                    number_of_wheel_rotation_units: rotations.rotation_units,
-                    x_position: self.mouse_position.x,
-                    y_position: self.mouse_position.y,
-                })),
+                Operation::RelativeMouseMove(delta) => {
+                    if delta.x == 0 && delta.y == 0 {
+                        continue;
+                    }
+
+                    match self.mouse_mode {
+                        MouseMode::Absolute => {
+                            let position = self.clamp_position(MousePosition {
+                                x: self.mouse_position.x.saturating_add_signed(delta.x),
+                                y: self.mouse_position.y.saturating_add_signed(delta.y),
+                            });
+
+                            if position != self.mouse_position {
+                                self.mouse_position = position;
+                                events.push(FastPathInputEvent::MouseEvent(MousePdu {
+                                    flags: PointerFlags::MOVE,
+                                    number_of_wheel_rotation_units: 0,
+                                    x_position: position.x,
+                                    y_position: position.y,
+                                }))
+                            }
+                        }
+                        MouseMode::Relative => events.push(FastPathInputEvent::MouseEventRel(MouseRelPdu {
+                            flags: PointerRelFlags::MOVE,
+                            x_delta: delta.x,
+                            y_delta: delta.y,
+                        })),
+                    }
+                }
+                Operation::WheelRotations(rotations) => {
+                    let Some(rotation_units) = self.scale_wheel_rotations(rotations) else {
+                        continue;
+                    };
+
+                    events.push(FastPathInputEvent::MouseEvent(MousePdu {
+                        flags: if rotations.is_vertical {
+                            PointerFlags::VERTICAL_WHEEL
+                        } else {
+                            PointerFlags::HORIZONTAL_WHEEL
+                        },
+                        number_of_wheel_rotation_units: rotation_units,
+                        x_position: self.mouse_position.x,
+                        y_position: self.mouse_position.y,
+                    }))
+                }
                 Operation::KeyPressed(scancode) => {
                     let was_pressed = self.keyboard.replace(scancode.as_idx(), true);
 
+                    if !was_pressed {
+                        if let Some(lock_keys) = &mut self.lock_keys {
+                            lock_keys.toggle(scancode);
+                        }
+                    }
+
                     let mut flags = KeyboardFlags::empty();
 
                     if scancode.extended {
@@ -279,10 +765,14 @@ impl Database {
                     };
 
                     if was_pressed {
-                        events.push(FastPathInputEvent::KeyboardEvent(
-                            flags | KeyboardFlags::RELEASE,
-                            scancode.code,
-                        ));
+                        match self.key_repeat_policy {
+                            KeyRepeatPolicy::Ignore => continue,
+                            KeyRepeatPolicy::ReleaseThenPress => events.push(FastPathInputEvent::KeyboardEvent(
+                                flags | KeyboardFlags::RELEASE,
+                                scancode.code,
+                            )),
+                            KeyRepeatPolicy::ForwardRepeat => {}
+                        }
                     }
 
                     events.push(FastPathInputEvent::KeyboardEvent(flags, scancode.code));
@@ -328,51 +818,116 @@ impl Database {
                         }
                     }
                 }
+                Operation::SyncLocks {
+                    scroll_lock,
+                    num_lock,
+                    caps_lock,
+                    kana_lock,
+                } => {
+                    let lock_keys = LockKeys {
+                        scroll_lock,
+                        num_lock,
+                        caps_lock,
+                        kana_lock,
+                    };
+
+                    if self.lock_keys != Some(lock_keys) {
+                        self.lock_keys = Some(lock_keys);
+                        events.push(synchronize_event(scroll_lock, num_lock, caps_lock, kana_lock));
+                    }
+                }
+                Operation::TouchDown(_)
+                | Operation::TouchMove(_)
+                | Operation::TouchUp(_)
+                | Operation::PenMove(_)
+                | Operation::PenDown(_)
+                | Operation::PenUp(_)
+                | Operation::PenLeave(_)
+                | Operation::GamepadButtonPressed { .. }
+                | Operation::GamepadButtonReleased { .. }
+                | Operation::GamepadAxisMoved { .. } => {}
             }
         }
 
         events
     }
 
+    /// Presses, then releases the keys of a combination. Returns a list of RDP input events to send.
+    ///
+    /// Keys of the combination which are already held down are neither pressed nor released, so that the keyboard
+    /// state is the same as before the combination.
+    pub fn apply_combo(&mut self, combo: &KeyCombo) -> SmallVec<[FastPathInputEvent; 2]> {
+        let keys: SmallVec<[Scancode; 4]> = combo
+            .keys()
+            .iter()
+            .copied()
+            .filter(|&scancode| !self.is_key_pressed(scancode))
+            .collect();
+
+        self.apply(KeyCombo { keys }.operations())
+    }
+
+    /// Like [`Database::apply`], but pairs each RDP input event with the timestamp of the operation producing it.
+    pub fn apply_timed(
+        &mut self,
+        transaction: impl IntoIterator<Item = impl Into<latency::TimedOperation>>,
+    ) -> SmallVec<[latency::TimedEvent; 2]> {
+        let mut events = SmallVec::new();
+
+        for operation in transaction {
+            let latency::TimedOperation { operation, timestamp } = operation.into();
+
+            events.extend(
+                self.apply([operation])
+                    .into_iter()
+                    .map(|event| latency::TimedEvent { event, timestamp }),
+            );
+        }
+
+        events
+    }
+
+    /// Releases the modifier keys only, leaving the other keys and the mouse buttons untouched. Returns a list of RDP
+    /// input events to send.
+    ///
+    /// Useful when the client window loses focus, since the key release events are then never received.
+    pub fn release_modifiers(&mut self) -> SmallVec<[FastPathInputEvent; 2]> {
+        self.apply(ModifierKey::ALL.map(|key| Operation::KeyReleased(key.scancode())))
+    }
+
     /// Releases all keys and buttons. Returns a list of RDP input events to send.
+    ///
+    /// The mouse buttons are released first, then the keys as with [`Database::release_keys`].
     pub fn release_all(&mut self) -> SmallVec<[FastPathInputEvent; 2]> {
         let mut events = SmallVec::new();
 
         for idx in self.mouse_buttons.iter_ones() {
             let button = MouseButton::from_idx(idx).expect("in-range index");
-
-            let event = match MouseButtonFlags::from(button) {
-                MouseButtonFlags::Button(flags) => FastPathInputEvent::MouseEvent(MousePdu {
-                    flags,
-                    number_of_wheel_rotation_units: 0,
-                    x_position: self.mouse_position.x,
-                    y_position: self.mouse_position.y,
-                }),
-                MouseButtonFlags::Pointer(flags) => FastPathInputEvent::MouseEventEx(MouseXPdu {
-                    flags,
-                    x_position: self.mouse_position.x,
-                    y_position: self.mouse_position.y,
-                }),
-            };
-
-            events.push(event)
+            events.push(self.mouse_button_event(button, false))
         }
 
-        for idx in self.keyboard.iter_ones() {
-            let (scancode, extended) = if idx >= 256 {
-                let extended_code = idx.checked_sub(256).expect("never underflow");
-                (u8::try_from(extended_code).unwrap(), true)
-            } else {
-                (u8::try_from(idx).unwrap(), false)
-            };
+        self.mouse_buttons = BitArray::ZERO;
+
+        events.extend(self.release_keys());
 
-            let mut flags = KeyboardFlags::RELEASE;
+        events
+    }
 
-            if extended {
-                flags |= KeyboardFlags::EXTENDED
-            };
+    /// Releases all keys, leaving the mouse buttons untouched. Returns a list of RDP input events to send.
+    ///
+    /// The modifier keys are released last, so that the server doesn't see the other keys as part of a shortcut. When
+    /// the state of the lock keys is known, a trailing synchronize event restores it.
+    pub fn release_keys(&mut self) -> SmallVec<[FastPathInputEvent; 2]> {
+        let mut events = SmallVec::new();
 
-            events.push(FastPathInputEvent::KeyboardEvent(flags, scancode));
+        let (modifiers, keys): (SmallVec<[Scancode; 8]>, SmallVec<[Scancode; 8]>) = self
+            .keyboard
+            .iter_ones()
+            .map(Scancode::from_idx)
+            .partition(|scancode| ModifierKey::from_scancode(*scancode).is_some());
+
+        for scancode in keys {
+            events.push(key_release_event(scancode));
         }
 
         for character in std::mem::take(&mut self.unicode_keyboard_state).into_iter() {
@@ -384,11 +939,103 @@ impl Database {
             }
         }
 
-        self.mouse_buttons = BitArray::ZERO;
+        for scancode in modifiers {
+            events.push(key_release_event(scancode));
+        }
+
         self.keyboard = BitArray::ZERO;
 
+        if let Some(lock_keys) = self.lock_keys {
+            events.push(synchronize_event(
+                lock_keys.scroll_lock,
+                lock_keys.num_lock,
+                lock_keys.caps_lock,
+                lock_keys.kana_lock,
+            ));
+        }
+
         events
     }
+
+    /// Applies the wheel settings. Returns `None` when the rotation is too small to be sent yet.
+    fn scale_wheel_rotations(&mut self, rotations: WheelRotations) -> Option<i16> {
+        /// Range of the 9-bit rotation field of mouse events
+        const ROTATION_RANGE: (f32, f32) = (-256.0, 255.0);
+
+        let (scale, remainder) = if rotations.is_vertical {
+            (self.wheel_settings.vertical_scale, &mut self.wheel_remainders.0)
+        } else {
+            (self.wheel_settings.horizontal_scale, &mut self.wheel_remainders.1)
+        };
+
+        let direction = if self.wheel_settings.natural_scrolling {
+            -1.0
+        } else {
+            1.0
+        };
+
+        let units = (f32::from(rotations.rotation_units) * scale * direction + *remainder)
+            .clamp(ROTATION_RANGE.0, ROTATION_RANGE.1);
+
+        let rounded = units.trunc();
+        *remainder = units - rounded;
+
+        // The value is within the rotation range
+        #[allow(clippy::cast_possible_truncation)]
+        let rotation_units = rounded as i16;
+
+        (rotation_units != 0).then_some(rotation_units)
+    }
+
+    fn clamp_position(&self, position: MousePosition) -> MousePosition {
+        match self.desktop_size {
+            Some((width, height)) => MousePosition {
+                x: position.x.min(width.saturating_sub(1)),
+                y: position.y.min(height.saturating_sub(1)),
+            },
+            None => position,
+        }
+    }
+
+    fn mouse_button_event(&self, button: MouseButton, pressed: bool) -> FastPathInputEvent {
+        if self.mouse_mode == MouseMode::Relative {
+            let mut flags = relative_button_flags(button);
+
+            if pressed {
+                flags |= PointerRelFlags::DOWN;
+            }
+
+            return FastPathInputEvent::MouseEventRel(MouseRelPdu {
+                flags,
+                x_delta: 0,
+                y_delta: 0,
+            });
+        }
+
+        match MouseButtonFlags::from(button) {
+            MouseButtonFlags::Button(flags) => FastPathInputEvent::MouseEvent(MousePdu {
+                flags: if pressed { PointerFlags::DOWN | flags } else { flags },
+                number_of_wheel_rotation_units: 0,
+                x_position: self.mouse_position.x,
+                y_position: self.mouse_position.y,
+            }),
+            MouseButtonFlags::Pointer(flags) => FastPathInputEvent::MouseEventEx(MouseXPdu {
+                flags: if pressed { PointerXFlags::DOWN | flags } else { flags },
+                x_position: self.mouse_position.x,
+                y_position: self.mouse_position.y,
+            }),
+        }
+    }
+}
+
+fn key_release_event(scancode: Scancode) -> FastPathInputEvent {
+    let mut flags = KeyboardFlags::RELEASE;
+
+    if scancode.extended {
+        flags |= KeyboardFlags::EXTENDED
+    };
+
+    FastPathInputEvent::KeyboardEvent(flags, scancode.code)
 }
 
 /// Returns the RDP input event to send in order to synchronize lock keys.
@@ -432,3 +1079,13 @@ impl From<MouseButton> for MouseButtonFlags {
         }
     }
 }
+
+fn relative_button_flags(button: MouseButton) -> PointerRelFlags {
+    match button {
+        MouseButton::Left => PointerRelFlags::BUTTON1,
+        MouseButton::Right => PointerRelFlags::BUTTON2,
+        MouseButton::Middle => PointerRelFlags::BUTTON3,
+        MouseButton::X1 => PointerRelFlags::XBUTTON1,
+        MouseButton::X2 => PointerRelFlags::XBUTTON2,
+    }
+}
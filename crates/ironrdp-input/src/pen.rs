@@ -0,0 +1,378 @@
+//! Pen input, as specified in MS-RDPEI
+//!
+//! Pen events are sent as [`PenEventPdu`]s over the [`CHANNEL_NAME`](crate::touch::CHANNEL_NAME) dynamic virtual
+//! channel, provided both sides support version 2 of the protocol.
+
+use std::collections::BTreeMap;
+
+use bitflags::bitflags;
+use ironrdp_core::{ensure_size, invalid_field_err, Encode, EncodeResult, WriteCursor};
+
+use crate::touch::{element_count, ContactFlags, VarInt};
+use crate::Operation;
+
+const EVENTID_PEN: u16 = 0x0008;
+
+bitflags! {
+    /// [2.2.3.7.1.1] RDPINPUT_PEN_CONTACT, penFlags field
+    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct PenFlags: u32 {
+        const BARREL_PRESSED = 0x0001;
+        const ERASER_PRESSED = 0x0002;
+        const INVERTED = 0x0004;
+    }
+}
+
+bitflags! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    struct FieldsPresent: u16 {
+        const PEN_FLAGS = 0x0001;
+        const PRESSURE = 0x0002;
+        const ROTATION = 0x0004;
+        const TILT_X = 0x0008;
+        const TILT_Y = 0x0010;
+    }
+}
+
+/// State of a pen, as reported by the operations
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PenContact {
+    /// Identifier of the pen, unique among the pens in range
+    pub device_id: u8,
+    pub x: i32,
+    pub y: i32,
+    pub flags: PenFlags,
+    /// Pressure, in the range 0 to 1024
+    pub pressure: Option<u32>,
+    /// Clockwise rotation, in degrees in the range 0 to 359
+    pub rotation: Option<u16>,
+    /// Tilt along the X axis, in degrees in the range -90 to 90
+    pub tilt_x: Option<i16>,
+    /// Tilt along the Y axis, in degrees in the range -90 to 90
+    pub tilt_y: Option<i16>,
+}
+
+impl PenContact {
+    pub const fn new(device_id: u8, x: i32, y: i32) -> Self {
+        Self {
+            device_id,
+            x,
+            y,
+            flags: PenFlags::empty(),
+            pressure: None,
+            rotation: None,
+            tilt_x: None,
+            tilt_y: None,
+        }
+    }
+}
+
+/// [2.2.3.7] RDPINPUT_PEN_EVENT_PDU
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PenEventPdu {
+    /// Milliseconds elapsed since the previous pen event was encoded
+    pub encode_time: u32,
+    pub frames: Vec<PenFrame>,
+}
+
+impl PenEventPdu {
+    const NAME: &'static str = "RDPINPUT_PEN_EVENT_PDU";
+
+    const FIXED_PART_SIZE: usize = 2 /* eventId */ + 4 /* pduLength */;
+}
+
+impl Encode for PenEventPdu {
+    fn encode(&self, dst: &mut WriteCursor<'_>) -> EncodeResult<()> {
+        ensure_size!(in: dst, size: self.size());
+
+        dst.write_u16(EVENTID_PEN);
+        dst.write_u32(u32::try_from(self.size()).map_err(|_| invalid_field_err!("pduLength", "too large"))?);
+        VarInt::FourByteUnsigned(self.encode_time).encode(dst)?;
+        VarInt::TwoByteUnsigned(element_count(self.frames.len())?).encode(dst)?;
+
+        for frame in &self.frames {
+            frame.encode(dst)?;
+        }
+
+        Ok(())
+    }
+
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    fn size(&self) -> usize {
+        let frames_size: usize = self.frames.iter().map(Encode::size).sum();
+
+        // LINTS: variable-length integers are at most 8 bytes long
+        #[allow(clippy::arithmetic_side_effects)]
+        let header_size = Self::FIXED_PART_SIZE
+            + VarInt::FourByteUnsigned(self.encode_time).size()
+            + VarInt::TwoByteUnsigned(u16::try_from(self.frames.len()).unwrap_or(u16::MAX)).size();
+
+        header_size.checked_add(frames_size).expect("never overflow")
+    }
+}
+
+/// [2.2.3.7.1] RDPINPUT_PEN_FRAME
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PenFrame {
+    /// Microseconds elapsed since the previous frame
+    pub frame_offset: u64,
+    pub contacts: Vec<PenContactData>,
+}
+
+impl PenFrame {
+    const NAME: &'static str = "RDPINPUT_PEN_FRAME";
+}
+
+impl Encode for PenFrame {
+    fn encode(&self, dst: &mut WriteCursor<'_>) -> EncodeResult<()> {
+        VarInt::TwoByteUnsigned(element_count(self.contacts.len())?).encode(dst)?;
+        VarInt::EightByteUnsigned(self.frame_offset).encode(dst)?;
+
+        for contact in &self.contacts {
+            contact.encode(dst)?;
+        }
+
+        Ok(())
+    }
+
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    fn size(&self) -> usize {
+        let contacts_size: usize = self.contacts.iter().map(Encode::size).sum();
+
+        // LINTS: variable-length integers are at most 8 bytes long
+        #[allow(clippy::arithmetic_side_effects)]
+        let header_size = VarInt::TwoByteUnsigned(u16::try_from(self.contacts.len()).unwrap_or(u16::MAX)).size()
+            + VarInt::EightByteUnsigned(self.frame_offset).size();
+
+        header_size.checked_add(contacts_size).expect("never overflow")
+    }
+}
+
+/// [2.2.3.7.1.1] RDPINPUT_PEN_CONTACT
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PenContactData {
+    pub device_id: u8,
+    pub x: i32,
+    pub y: i32,
+    pub contact_flags: ContactFlags,
+    pub pen_flags: Option<PenFlags>,
+    pub pressure: Option<u32>,
+    pub rotation: Option<u16>,
+    pub tilt_x: Option<i16>,
+    pub tilt_y: Option<i16>,
+}
+
+impl PenContactData {
+    const NAME: &'static str = "RDPINPUT_PEN_CONTACT";
+
+    const FIXED_PART_SIZE: usize = 1 /* deviceId */;
+
+    /// Builds the contact data of a pen, the pen flags being omitted when empty.
+    pub fn new(contact: PenContact, contact_flags: ContactFlags) -> Self {
+        Self {
+            device_id: contact.device_id,
+            x: contact.x,
+            y: contact.y,
+            contact_flags,
+            pen_flags: (!contact.flags.is_empty()).then_some(contact.flags),
+            pressure: contact.pressure,
+            rotation: contact.rotation,
+            tilt_x: contact.tilt_x,
+            tilt_y: contact.tilt_y,
+        }
+    }
+
+    fn fields_present(&self) -> FieldsPresent {
+        let mut fields_present = FieldsPresent::empty();
+        fields_present.set(FieldsPresent::PEN_FLAGS, self.pen_flags.is_some());
+        fields_present.set(FieldsPresent::PRESSURE, self.pressure.is_some());
+        fields_present.set(FieldsPresent::ROTATION, self.rotation.is_some());
+        fields_present.set(FieldsPresent::TILT_X, self.tilt_x.is_some());
+        fields_present.set(FieldsPresent::TILT_Y, self.tilt_y.is_some());
+        fields_present
+    }
+
+    fn var_ints(&self) -> impl Iterator<Item = VarInt> {
+        [
+            VarInt::TwoByteUnsigned(self.fields_present().bits()),
+            VarInt::FourByteSigned(self.x),
+            VarInt::FourByteSigned(self.y),
+            VarInt::FourByteUnsigned(self.contact_flags.bits()),
+        ]
+        .into_iter()
+        .chain(self.pen_flags.map(|flags| VarInt::FourByteUnsigned(flags.bits())))
+        .chain(self.pressure.map(VarInt::FourByteUnsigned))
+        .chain(self.rotation.map(VarInt::TwoByteUnsigned))
+        .chain(self.tilt_x.map(VarInt::TwoByteSigned))
+        .chain(self.tilt_y.map(VarInt::TwoByteSigned))
+    }
+}
+
+impl Encode for PenContactData {
+    fn encode(&self, dst: &mut WriteCursor<'_>) -> EncodeResult<()> {
+        ensure_size!(in: dst, size: self.size());
+
+        dst.write_u8(self.device_id);
+
+        for var_int in self.var_ints() {
+            var_int.encode(dst)?;
+        }
+
+        Ok(())
+    }
+
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    fn size(&self) -> usize {
+        Self::FIXED_PART_SIZE
+            .checked_add(self.var_ints().map(VarInt::size).sum())
+            .expect("never overflow")
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PenState {
+    Hovering,
+    Engaged,
+}
+
+impl PenState {
+    fn update_flags(self) -> ContactFlags {
+        match self {
+            PenState::Hovering => ContactFlags::UPDATE | ContactFlags::IN_RANGE,
+            PenState::Engaged => ContactFlags::UPDATE | ContactFlags::IN_RANGE | ContactFlags::IN_CONTACT,
+        }
+    }
+}
+
+/// Builds pen events out of pen operations, keeping track of the pens in range.
+///
+/// Each frame lists all the pens in range. A pen updated twice in the same transaction starts a new frame.
+#[derive(Debug, Clone, Default)]
+pub struct PenEncoder {
+    pens: BTreeMap<u8, (PenContact, PenState)>,
+}
+
+impl PenEncoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns whether the pen is in range, either hovering or touching the surface.
+    pub fn is_pen_in_range(&self, device_id: u8) -> bool {
+        self.pens.contains_key(&device_id)
+    }
+
+    /// Returns whether the pen is touching the surface.
+    pub fn is_pen_down(&self, device_id: u8) -> bool {
+        matches!(self.pens.get(&device_id), Some((_, PenState::Engaged)))
+    }
+
+    /// Applies a transaction (list of operations) and returns the pen event to send, if any.
+    ///
+    /// Operations other than pen ones, and those which would cause no state change, are ignored. The timing fields
+    /// of the returned event are left to zero.
+    pub fn apply(&mut self, transaction: impl IntoIterator<Item = Operation>) -> Option<PenEventPdu> {
+        let mut frames = Vec::new();
+        let mut frame = BTreeMap::new();
+
+        for operation in transaction {
+            let contact = match operation {
+                Operation::PenMove(contact)
+                | Operation::PenDown(contact)
+                | Operation::PenUp(contact)
+                | Operation::PenLeave(contact) => contact,
+                _ => continue,
+            };
+
+            if frame.contains_key(&contact.device_id) {
+                frames.push(self.close_frame(std::mem::take(&mut frame)));
+            }
+
+            let state = self.pens.get(&contact.device_id).map(|(_, state)| *state);
+
+            let flags = match (operation, state) {
+                (Operation::PenMove(_), state) => {
+                    let state = state.unwrap_or(PenState::Hovering);
+                    self.pens.insert(contact.device_id, (contact, state));
+                    state.update_flags()
+                }
+                (Operation::PenDown(_), Some(PenState::Engaged)) => {
+                    self.pens.insert(contact.device_id, (contact, PenState::Engaged));
+                    PenState::Engaged.update_flags()
+                }
+                (Operation::PenDown(_), _) => {
+                    self.pens.insert(contact.device_id, (contact, PenState::Engaged));
+                    ContactFlags::DOWN | ContactFlags::IN_RANGE | ContactFlags::IN_CONTACT
+                }
+                (Operation::PenUp(_), Some(PenState::Engaged)) => {
+                    self.pens.insert(contact.device_id, (contact, PenState::Hovering));
+                    ContactFlags::UP | ContactFlags::IN_RANGE
+                }
+                (Operation::PenLeave(_), Some(state)) => {
+                    self.pens.remove(&contact.device_id);
+
+                    match state {
+                        PenState::Hovering => ContactFlags::UPDATE,
+                        PenState::Engaged => ContactFlags::UP,
+                    }
+                }
+                _ => continue,
+            };
+
+            frame.insert(contact.device_id, PenContactData::new(contact, flags));
+        }
+
+        if !frame.is_empty() {
+            frames.push(self.close_frame(frame));
+        }
+
+        (!frames.is_empty()).then_some(PenEventPdu { encode_time: 0, frames })
+    }
+
+    /// Takes all the pens out of range, canceling the strokes in progress. Returns the pen event to send, if any.
+    pub fn cancel_all(&mut self) -> Option<PenEventPdu> {
+        let contacts: Vec<_> = std::mem::take(&mut self.pens)
+            .into_values()
+            .map(|(contact, state)| {
+                let flags = match state {
+                    PenState::Hovering => ContactFlags::UPDATE,
+                    PenState::Engaged => ContactFlags::UP | ContactFlags::CANCELED,
+                };
+
+                PenContactData::new(contact, flags)
+            })
+            .collect();
+
+        (!contacts.is_empty()).then(|| PenEventPdu {
+            encode_time: 0,
+            frames: vec![PenFrame {
+                frame_offset: 0,
+                contacts,
+            }],
+        })
+    }
+
+    fn close_frame(&self, mut frame: BTreeMap<u8, PenContactData>) -> PenFrame {
+        for (contact, state) in self.pens.values() {
+            frame
+                .entry(contact.device_id)
+                .or_insert_with(|| PenContactData::new(*contact, state.update_flags()));
+        }
+
+        PenFrame {
+            frame_offset: 0,
+            contacts: frame.into_values().collect(),
+        }
+    }
+}
@@ -0,0 +1,127 @@
+//! Recording and replay of input operations
+//!
+//! A [`Recorder`] captures operations along with the delay elapsed since the previous one. The resulting
+//! [`Recording`] can be stored (see the `serde` feature) and fed back later through a [`Replayer`], which paces the
+//! operations as they were recorded. Useful for automated UI testing and demo scenarios.
+
+use std::time::{Duration, Instant};
+
+use ironrdp_pdu::input::fast_path::FastPathInputEvent;
+use smallvec::SmallVec;
+
+use crate::{Database, Operation};
+
+/// An operation, along with the delay elapsed since the previous one
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RecordedOperation {
+    pub delay: Duration,
+    pub operation: Operation,
+}
+
+/// A sequence of recorded operations
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Recording {
+    pub operations: Vec<RecordedOperation>,
+}
+
+impl Recording {
+    /// Returns the time needed to replay the whole recording.
+    pub fn duration(&self) -> Duration {
+        self.operations
+            .iter()
+            .fold(Duration::ZERO, |total, operation| total.saturating_add(operation.delay))
+    }
+}
+
+/// Captures the operations applied to a [`Database`].
+#[derive(Debug, Clone, Default)]
+pub struct Recorder {
+    recording: Recording,
+    last_operation: Option<Instant>,
+}
+
+impl Recorder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records operations applied at `now`.
+    ///
+    /// The first recorded operation has no delay.
+    pub fn record(&mut self, operations: impl IntoIterator<Item = Operation>, now: Instant) {
+        for operation in operations {
+            let delay = self
+                .last_operation
+                .map(|last_operation| now.saturating_duration_since(last_operation))
+                .unwrap_or_default();
+
+            self.last_operation = Some(now);
+            self.recording.operations.push(RecordedOperation { delay, operation });
+        }
+    }
+
+    pub fn recording(&self) -> &Recording {
+        &self.recording
+    }
+
+    pub fn into_recording(self) -> Recording {
+        self.recording
+    }
+}
+
+/// Feeds recorded operations back through a [`Database`], with the recorded pacing.
+#[derive(Debug, Clone)]
+pub struct Replayer {
+    operations: std::vec::IntoIter<RecordedOperation>,
+    next: Option<RecordedOperation>,
+    last_operation: Instant,
+}
+
+impl Replayer {
+    /// Creates a replayer starting at `start`.
+    pub fn new(recording: Recording, start: Instant) -> Self {
+        let mut operations = recording.operations.into_iter();
+        let next = operations.next();
+
+        Self {
+            operations,
+            next,
+            last_operation: start,
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.next.is_none()
+    }
+
+    /// Returns the instant at which the next operation is due, if any.
+    pub fn deadline(&self) -> Option<Instant> {
+        let next = self.next.as_ref()?;
+        Some(
+            self.last_operation
+                .checked_add(next.delay)
+                .unwrap_or(self.last_operation),
+        )
+    }
+
+    /// Applies the operations due at `now`, and returns the RDP input events to send.
+    pub fn poll(&mut self, now: Instant, database: &mut Database) -> SmallVec<[FastPathInputEvent; 2]> {
+        let mut events = SmallVec::new();
+
+        while let Some(deadline) = self.deadline().filter(|deadline| *deadline <= now) {
+            let next = self
+                .next
+                .take()
+                .expect("deadline is only set when an operation is pending");
+
+            events.extend(database.apply([next.operation]));
+
+            self.last_operation = deadline;
+            self.next = self.operations.next();
+        }
+
+        events
+    }
+}
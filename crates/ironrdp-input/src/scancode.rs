@@ -0,0 +1,240 @@
+//! Conversions between RDP scancodes (set 1) and native key codes
+//!
+//! Supported key codes are Linux evdev key codes (`KEY_*` constants of `input-event-codes.h`), USB HID usages of the
+//! Keyboard/Keypad page (0x07), macOS virtual key codes (`kVK_*` constants of `Events.h`), and the [`code`] values of
+//! browser keyboard events.
+//!
+//! Keys which cannot be represented by a single scancode, such as Pause, are not mapped.
+//!
+//! [`code`]: https://developer.mozilla.org/en-US/docs/Web/API/KeyboardEvent/code
+
+use crate::Scancode;
+
+/// Converts a Linux evdev key code into a scancode.
+pub fn from_evdev(code: u16) -> Option<Scancode> {
+    find(|key| key.evdev == code)
+}
+
+/// Converts a scancode into a Linux evdev key code.
+pub fn to_evdev(scancode: Scancode) -> Option<u16> {
+    lookup(scancode).map(|key| key.evdev)
+}
+
+/// Converts a USB HID usage of the Keyboard/Keypad page into a scancode.
+pub fn from_usb_hid(usage: u16) -> Option<Scancode> {
+    find(|key| key.usb_hid == usage)
+}
+
+/// Converts a scancode into a USB HID usage of the Keyboard/Keypad page.
+pub fn to_usb_hid(scancode: Scancode) -> Option<u16> {
+    lookup(scancode).map(|key| key.usb_hid)
+}
+
+/// Converts a macOS virtual key code into a scancode.
+pub fn from_mac_keycode(code: u16) -> Option<Scancode> {
+    find(|key| key.mac == Some(code))
+}
+
+/// Converts a scancode into a macOS virtual key code.
+pub fn to_mac_keycode(scancode: Scancode) -> Option<u16> {
+    lookup(scancode).and_then(|key| key.mac)
+}
+
+/// Converts a [`KeyboardEvent.code`] value, such as `KeyA` or `NumpadEnter`, into a scancode.
+///
+/// The legacy values still reported by some browsers (e.g. `OSLeft` or `VolumeMute`) are supported as well.
+///
+/// [`KeyboardEvent.code`]: https://developer.mozilla.org/en-US/docs/Web/API/KeyboardEvent/code
+pub fn from_dom_code(code: &str) -> Option<Scancode> {
+    let code = match code {
+        "OSLeft" => "MetaLeft",
+        "OSRight" => "MetaRight",
+        "VolumeMute" => "AudioVolumeMute",
+        "VolumeUp" => "AudioVolumeUp",
+        "VolumeDown" => "AudioVolumeDown",
+        code => code,
+    };
+
+    find(|key| key.dom_code == code)
+}
+
+/// Converts a scancode into a [`KeyboardEvent.code`] value.
+///
+/// [`KeyboardEvent.code`]: https://developer.mozilla.org/en-US/docs/Web/API/KeyboardEvent/code
+pub fn to_dom_code(scancode: Scancode) -> Option<&'static str> {
+    lookup(scancode).map(|key| key.dom_code)
+}
+
+fn find(predicate: impl Fn(&KeyCodes) -> bool) -> Option<Scancode> {
+    KEYS.iter()
+        .find(|key| predicate(key))
+        .map(|key| Scancode::from_u16(key.scancode))
+}
+
+fn lookup(scancode: Scancode) -> Option<&'static KeyCodes> {
+    let scancode = scancode.as_u16();
+    KEYS.iter().find(|key| key.scancode == scancode)
+}
+
+struct KeyCodes {
+    /// Set 1 scancode, 0xE0 prefixed for extended keys
+    scancode: u16,
+    evdev: u16,
+    usb_hid: u16,
+    mac: Option<u16>,
+    dom_code: &'static str,
+}
+
+const fn key(scancode: u16, evdev: u16, usb_hid: u16, mac: u16, dom_code: &'static str) -> KeyCodes {
+    KeyCodes {
+        scancode,
+        evdev,
+        usb_hid,
+        mac: Some(mac),
+        dom_code,
+    }
+}
+
+const fn no_mac(scancode: u16, evdev: u16, usb_hid: u16, dom_code: &'static str) -> KeyCodes {
+    KeyCodes {
+        scancode,
+        evdev,
+        usb_hid,
+        mac: None,
+        dom_code,
+    }
+}
+
+#[rustfmt::skip]
+const KEYS: &[KeyCodes] = &[
+    //     scancode evdev  USB    macOS KeyboardEvent.code
+    key(   0x001E,  30,    0x04,  0x00, "KeyA"),
+    key(   0x0030,  48,    0x05,  0x0B, "KeyB"),
+    key(   0x002E,  46,    0x06,  0x08, "KeyC"),
+    key(   0x0020,  32,    0x07,  0x02, "KeyD"),
+    key(   0x0012,  18,    0x08,  0x0E, "KeyE"),
+    key(   0x0021,  33,    0x09,  0x03, "KeyF"),
+    key(   0x0022,  34,    0x0A,  0x05, "KeyG"),
+    key(   0x0023,  35,    0x0B,  0x04, "KeyH"),
+    key(   0x0017,  23,    0x0C,  0x22, "KeyI"),
+    key(   0x0024,  36,    0x0D,  0x26, "KeyJ"),
+    key(   0x0025,  37,    0x0E,  0x28, "KeyK"),
+    key(   0x0026,  38,    0x0F,  0x25, "KeyL"),
+    key(   0x0032,  50,    0x10,  0x2E, "KeyM"),
+    key(   0x0031,  49,    0x11,  0x2D, "KeyN"),
+    key(   0x0018,  24,    0x12,  0x1F, "KeyO"),
+    key(   0x0019,  25,    0x13,  0x23, "KeyP"),
+    key(   0x0010,  16,    0x14,  0x0C, "KeyQ"),
+    key(   0x0013,  19,    0x15,  0x0F, "KeyR"),
+    key(   0x001F,  31,    0x16,  0x01, "KeyS"),
+    key(   0x0014,  20,    0x17,  0x11, "KeyT"),
+    key(   0x0016,  22,    0x18,  0x20, "KeyU"),
+    key(   0x002F,  47,    0x19,  0x09, "KeyV"),
+    key(   0x0011,  17,    0x1A,  0x0D, "KeyW"),
+    key(   0x002D,  45,    0x1B,  0x07, "KeyX"),
+    key(   0x0015,  21,    0x1C,  0x10, "KeyY"),
+    key(   0x002C,  44,    0x1D,  0x06, "KeyZ"),
+    key(   0x0002,  2,     0x1E,  0x12, "Digit1"),
+    key(   0x0003,  3,     0x1F,  0x13, "Digit2"),
+    key(   0x0004,  4,     0x20,  0x14, "Digit3"),
+    key(   0x0005,  5,     0x21,  0x15, "Digit4"),
+    key(   0x0006,  6,     0x22,  0x17, "Digit5"),
+    key(   0x0007,  7,     0x23,  0x16, "Digit6"),
+    key(   0x0008,  8,     0x24,  0x1A, "Digit7"),
+    key(   0x0009,  9,     0x25,  0x1C, "Digit8"),
+    key(   0x000A,  10,    0x26,  0x19, "Digit9"),
+    key(   0x000B,  11,    0x27,  0x1D, "Digit0"),
+    key(   0x001C,  28,    0x28,  0x24, "Enter"),
+    key(   0x0001,  1,     0x29,  0x35, "Escape"),
+    key(   0x000E,  14,    0x2A,  0x33, "Backspace"),
+    key(   0x000F,  15,    0x2B,  0x30, "Tab"),
+    key(   0x0039,  57,    0x2C,  0x31, "Space"),
+    key(   0x000C,  12,    0x2D,  0x1B, "Minus"),
+    key(   0x000D,  13,    0x2E,  0x18, "Equal"),
+    key(   0x001A,  26,    0x2F,  0x21, "BracketLeft"),
+    key(   0x001B,  27,    0x30,  0x1E, "BracketRight"),
+    key(   0x002B,  43,    0x31,  0x2A, "Backslash"),
+    key(   0x0027,  39,    0x33,  0x29, "Semicolon"),
+    key(   0x0028,  40,    0x34,  0x27, "Quote"),
+    key(   0x0029,  41,    0x35,  0x32, "Backquote"),
+    key(   0x0033,  51,    0x36,  0x2B, "Comma"),
+    key(   0x0034,  52,    0x37,  0x2F, "Period"),
+    key(   0x0035,  53,    0x38,  0x2C, "Slash"),
+    key(   0x003A,  58,    0x39,  0x39, "CapsLock"),
+    key(   0x003B,  59,    0x3A,  0x7A, "F1"),
+    key(   0x003C,  60,    0x3B,  0x78, "F2"),
+    key(   0x003D,  61,    0x3C,  0x63, "F3"),
+    key(   0x003E,  62,    0x3D,  0x76, "F4"),
+    key(   0x003F,  63,    0x3E,  0x60, "F5"),
+    key(   0x0040,  64,    0x3F,  0x61, "F6"),
+    key(   0x0041,  65,    0x40,  0x62, "F7"),
+    key(   0x0042,  66,    0x41,  0x64, "F8"),
+    key(   0x0043,  67,    0x42,  0x65, "F9"),
+    key(   0x0044,  68,    0x43,  0x6D, "F10"),
+    key(   0x0057,  87,    0x44,  0x67, "F11"),
+    key(   0x0058,  88,    0x45,  0x6F, "F12"),
+    no_mac(0xE037,  99,    0x46,       "PrintScreen"),
+    no_mac(0x0046,  70,    0x47,       "ScrollLock"),
+    key(   0xE052,  110,   0x49,  0x72, "Insert"),
+    key(   0xE047,  102,   0x4A,  0x73, "Home"),
+    key(   0xE049,  104,   0x4B,  0x74, "PageUp"),
+    key(   0xE053,  111,   0x4C,  0x75, "Delete"),
+    key(   0xE04F,  107,   0x4D,  0x77, "End"),
+    key(   0xE051,  109,   0x4E,  0x79, "PageDown"),
+    key(   0xE04D,  106,   0x4F,  0x7C, "ArrowRight"),
+    key(   0xE04B,  105,   0x50,  0x7B, "ArrowLeft"),
+    key(   0xE050,  108,   0x51,  0x7D, "ArrowDown"),
+    key(   0xE048,  103,   0x52,  0x7E, "ArrowUp"),
+    key(   0x0045,  69,    0x53,  0x47, "NumLock"),
+    key(   0xE035,  98,    0x54,  0x4B, "NumpadDivide"),
+    key(   0x0037,  55,    0x55,  0x43, "NumpadMultiply"),
+    key(   0x004A,  74,    0x56,  0x4E, "NumpadSubtract"),
+    key(   0x004E,  78,    0x57,  0x45, "NumpadAdd"),
+    key(   0xE01C,  96,    0x58,  0x4C, "NumpadEnter"),
+    key(   0x004F,  79,    0x59,  0x53, "Numpad1"),
+    key(   0x0050,  80,    0x5A,  0x54, "Numpad2"),
+    key(   0x0051,  81,    0x5B,  0x55, "Numpad3"),
+    key(   0x004B,  75,    0x5C,  0x56, "Numpad4"),
+    key(   0x004C,  76,    0x5D,  0x57, "Numpad5"),
+    key(   0x004D,  77,    0x5E,  0x58, "Numpad6"),
+    key(   0x0047,  71,    0x5F,  0x59, "Numpad7"),
+    key(   0x0048,  72,    0x60,  0x5B, "Numpad8"),
+    key(   0x0049,  73,    0x61,  0x5C, "Numpad9"),
+    key(   0x0052,  82,    0x62,  0x52, "Numpad0"),
+    key(   0x0053,  83,    0x63,  0x41, "NumpadDecimal"),
+    key(   0x0056,  86,    0x64,  0x0A, "IntlBackslash"),
+    key(   0xE05D,  127,   0x65,  0x6E, "ContextMenu"),
+    no_mac(0xE05E,  116,   0x66,       "Power"),
+    key(   0x0059,  117,   0x67,  0x51, "NumpadEqual"),
+    key(   0x0064,  183,   0x68,  0x69, "F13"),
+    key(   0x0065,  184,   0x69,  0x6B, "F14"),
+    key(   0x0066,  185,   0x6A,  0x71, "F15"),
+    key(   0x0067,  186,   0x6B,  0x6A, "F16"),
+    key(   0x0068,  187,   0x6C,  0x40, "F17"),
+    key(   0x0069,  188,   0x6D,  0x4F, "F18"),
+    key(   0x006A,  189,   0x6E,  0x50, "F19"),
+    key(   0x006B,  190,   0x6F,  0x5A, "F20"),
+    no_mac(0x006C,  191,   0x70,       "F21"),
+    no_mac(0x006D,  192,   0x71,       "F22"),
+    no_mac(0x006E,  193,   0x72,       "F23"),
+    no_mac(0x0076,  194,   0x73,       "F24"),
+    key(   0xE020,  113,   0x7F,  0x4A, "AudioVolumeMute"),
+    key(   0xE030,  115,   0x80,  0x48, "AudioVolumeUp"),
+    key(   0xE02E,  114,   0x81,  0x49, "AudioVolumeDown"),
+    key(   0x007E,  121,   0x85,  0x5F, "NumpadComma"),
+    key(   0x0073,  89,    0x87,  0x5E, "IntlRo"),
+    no_mac(0x0070,  93,    0x88,       "KanaMode"),
+    key(   0x007D,  124,   0x89,  0x5D, "IntlYen"),
+    no_mac(0x0079,  92,    0x8A,       "Convert"),
+    no_mac(0x007B,  94,    0x8B,       "NonConvert"),
+    key(   0x0072,  122,   0x90,  0x68, "Lang1"),
+    key(   0x0071,  123,   0x91,  0x66, "Lang2"),
+    key(   0x001D,  29,    0xE0,  0x3B, "ControlLeft"),
+    key(   0x002A,  42,    0xE1,  0x38, "ShiftLeft"),
+    key(   0x0038,  56,    0xE2,  0x3A, "AltLeft"),
+    key(   0xE05B,  125,   0xE3,  0x37, "MetaLeft"),
+    key(   0xE01D,  97,    0xE4,  0x3E, "ControlRight"),
+    key(   0x0036,  54,    0xE5,  0x3C, "ShiftRight"),
+    key(   0xE038,  100,   0xE6,  0x3D, "AltRight"),
+    key(   0xE05C,  126,   0xE7,  0x36, "MetaRight"),
+];
@@ -0,0 +1,166 @@
+//! Multiple input seats driving a single session
+//!
+//! Collaborative clients may let several local users drive the same session. Each of them, a seat, keeps its own
+//! keyboard and mouse state, while the server only sees a single keyboard and a single mouse. [`MultiSeatDatabase`]
+//! merges the events of all the seats into one consistent stream.
+
+use std::collections::BTreeMap;
+
+use ironrdp_pdu::input::fast_path::FastPathInputEvent;
+use ironrdp_pdu::input::mouse::PointerFlags;
+use ironrdp_pdu::input::{MousePdu, MouseXPdu};
+use smallvec::SmallVec;
+
+use crate::{Database, MousePosition, Operation};
+
+/// Identifier of a seat
+pub type SeatId = u32;
+
+/// Maintains the keyboard and mouse state of several seats.
+///
+/// A key or button is pressed on the server when the first seat presses it, and released when the last seat holding
+/// it releases it. The cursor is shared: before a seat clicks or scrolls, the cursor is moved to the position of that
+/// seat. The lock keys are shared too.
+#[derive(Default)]
+pub struct MultiSeatDatabase {
+    seats: BTreeMap<SeatId, Database>,
+    /// Position of the cursor on the server side, as far as known
+    mouse_position: Option<MousePosition>,
+}
+
+impl MultiSeatDatabase {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the state of a seat, if it has applied any operation.
+    pub fn seat(&self, seat: SeatId) -> Option<&Database> {
+        self.seats.get(&seat)
+    }
+
+    pub fn seats(&self) -> impl Iterator<Item = SeatId> + '_ {
+        self.seats.keys().copied()
+    }
+
+    /// Applies a transaction (list of operations) on behalf of a seat, and returns a list of RDP input events to send.
+    pub fn apply(
+        &mut self,
+        seat: SeatId,
+        transaction: impl IntoIterator<Item = Operation>,
+    ) -> SmallVec<[FastPathInputEvent; 2]> {
+        let mut events = SmallVec::new();
+
+        for operation in transaction {
+            let held_by_others = self.held_by_others(seat, &operation);
+
+            if let Operation::SyncLocks { .. } = operation {
+                for (_, database) in self.seats.iter_mut().filter(|(id, _)| **id != seat) {
+                    database.apply([operation.clone()]);
+                }
+            }
+
+            let database = self.seats.entry(seat).or_default();
+            let produced = database.apply([operation.clone()]);
+            let seat_position = database.mouse_position();
+
+            if held_by_others {
+                continue;
+            }
+
+            if let Operation::MouseMove(_) = operation {
+                // The seat may not have moved while the shared cursor did
+                self.move_cursor(seat_position, &mut events);
+                continue;
+            }
+
+            for event in produced {
+                match &event {
+                    FastPathInputEvent::MouseEvent(pdu) if pdu.flags == PointerFlags::MOVE => {
+                        self.move_cursor(
+                            MousePosition {
+                                x: pdu.x_position,
+                                y: pdu.y_position,
+                            },
+                            &mut events,
+                        );
+                    }
+                    FastPathInputEvent::MouseEvent(MousePdu {
+                        x_position, y_position, ..
+                    })
+                    | FastPathInputEvent::MouseEventEx(MouseXPdu {
+                        x_position, y_position, ..
+                    }) => {
+                        self.move_cursor(
+                            MousePosition {
+                                x: *x_position,
+                                y: *y_position,
+                            },
+                            &mut events,
+                        );
+                        events.push(event);
+                    }
+                    _ => events.push(event),
+                }
+            }
+        }
+
+        events
+    }
+
+    /// Removes a seat, releasing the keys and buttons only it holds. Returns a list of RDP input events to send.
+    pub fn remove_seat(&mut self, seat: SeatId) -> SmallVec<[FastPathInputEvent; 2]> {
+        let Some(database) = self.seats.get(&seat) else {
+            return SmallVec::new();
+        };
+
+        let snapshot = database.to_snapshot();
+
+        let releases = snapshot
+            .pressed_mouse_buttons
+            .into_iter()
+            .map(Operation::MouseButtonReleased)
+            .chain(snapshot.pressed_keys.into_iter().map(Operation::KeyReleased))
+            .chain(
+                snapshot
+                    .pressed_unicode_keys
+                    .into_iter()
+                    .map(Operation::UnicodeKeyReleased),
+            );
+
+        let events = self.apply(seat, releases);
+        self.seats.remove(&seat);
+
+        events
+    }
+
+    fn held_by_others(&self, seat: SeatId, operation: &Operation) -> bool {
+        let mut others = self.seats.iter().filter(|(id, _)| **id != seat).map(|(_, db)| db);
+
+        match operation {
+            Operation::KeyPressed(scancode) | Operation::KeyReleased(scancode) => {
+                others.any(|db| db.is_key_pressed(*scancode))
+            }
+            Operation::UnicodeKeyPressed(character) | Operation::UnicodeKeyReleased(character) => {
+                others.any(|db| db.is_unicode_key_pressed(*character))
+            }
+            Operation::MouseButtonPressed(button) | Operation::MouseButtonReleased(button) => {
+                others.any(|db| db.is_mouse_button_pressed(*button))
+            }
+            _ => false,
+        }
+    }
+
+    fn move_cursor(&mut self, position: MousePosition, events: &mut SmallVec<[FastPathInputEvent; 2]>) {
+        if self.mouse_position == Some(position) {
+            return;
+        }
+
+        self.mouse_position = Some(position);
+        events.push(FastPathInputEvent::MouseEvent(MousePdu {
+            flags: PointerFlags::MOVE,
+            number_of_wheel_rotation_units: 0,
+            x_position: position.x,
+            y_position: position.y,
+        }));
+    }
+}
@@ -0,0 +1,142 @@
+//! Local handling of keyboard shortcuts
+//!
+//! Depending on the window state, some shortcuts such as Alt+Tab are better handled by the local system than by the
+//! remote session. A [`ShortcutFilter`] consults a [`ShortcutPolicy`] for each key press, and hands the presses to
+//! keep local back to the caller instead of forwarding them.
+
+use bitvec::array::BitArray;
+use ironrdp_pdu::input::fast_path::FastPathInputEvent;
+use smallvec::SmallVec;
+
+use crate::{Database, KeyboardState, ModifierKey, ModifierState, Operation, Scancode};
+
+/// Where a key press is handled
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ShortcutDecision {
+    /// The key press is sent to the remote session.
+    Forward,
+    /// The key press is left to the local system.
+    KeepLocal,
+}
+
+/// Decides where key presses are handled.
+pub trait ShortcutPolicy {
+    /// Decides where the press of `scancode` is handled, `modifiers` being the modifier keys held down on the remote
+    /// session.
+    fn decide(&self, modifiers: ModifierState, scancode: Scancode) -> ShortcutDecision;
+}
+
+/// Allows switching between policies at runtime, with `ShortcutFilter<Box<dyn ShortcutPolicy>>`.
+impl<P: ShortcutPolicy + ?Sized> ShortcutPolicy for Box<P> {
+    fn decide(&self, modifiers: ModifierState, scancode: Scancode) -> ShortcutDecision {
+        (**self).decide(modifiers, scancode)
+    }
+}
+
+/// Forwards all the key presses, e.g. while the client is fullscreen.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GrabAll;
+
+impl ShortcutPolicy for GrabAll {
+    fn decide(&self, _: ModifierState, _: Scancode) -> ShortcutDecision {
+        ShortcutDecision::Forward
+    }
+}
+
+/// Keeps the window management shortcuts local, e.g. while the client is windowed.
+///
+/// These are Alt+Tab, Alt+Esc, Ctrl+Esc, and the Windows keys along with all the shortcuts using them.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Windowed;
+
+impl Windowed {
+    const TAB: Scancode = Scancode::from_u8(false, 0x0F);
+    const ESCAPE: Scancode = Scancode::from_u8(false, 0x01);
+}
+
+impl ShortcutPolicy for Windowed {
+    fn decide(&self, modifiers: ModifierState, scancode: Scancode) -> ShortcutDecision {
+        let is_meta = [ModifierKey::LeftMeta, ModifierKey::RightMeta]
+            .into_iter()
+            .any(|key| key.scancode() == scancode);
+
+        let keep_local = is_meta
+            || modifiers.meta()
+            || (modifiers.alt() && (scancode == Self::TAB || scancode == Self::ESCAPE))
+            || (modifiers.ctrl() && scancode == Self::ESCAPE);
+
+        if keep_local {
+            ShortcutDecision::KeepLocal
+        } else {
+            ShortcutDecision::Forward
+        }
+    }
+}
+
+/// Outcome of [`ShortcutFilter::apply`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FilteredInput {
+    /// RDP input events to send
+    pub events: SmallVec<[FastPathInputEvent; 2]>,
+    /// Operations to handle locally
+    pub local: Vec<Operation>,
+}
+
+/// Applies operations to a [`Database`], except for the key presses the policy keeps local.
+///
+/// The release of a key kept local is kept local as well.
+pub struct ShortcutFilter<P> {
+    policy: P,
+    local_keys: KeyboardState,
+}
+
+impl<P: ShortcutPolicy> ShortcutFilter<P> {
+    pub fn new(policy: P) -> Self {
+        Self {
+            policy,
+            local_keys: BitArray::ZERO,
+        }
+    }
+
+    pub fn policy(&self) -> &P {
+        &self.policy
+    }
+
+    /// Replaces the policy, e.g. when the client window enters or leaves fullscreen.
+    ///
+    /// Keys already held down keep being handled where they were pressed.
+    pub fn set_policy(&mut self, policy: P) {
+        self.policy = policy;
+    }
+
+    pub fn apply(
+        &mut self,
+        database: &mut Database,
+        transaction: impl IntoIterator<Item = Operation>,
+    ) -> FilteredInput {
+        let mut input = FilteredInput::default();
+
+        for operation in transaction {
+            let keep_local = match operation {
+                Operation::KeyPressed(scancode) => {
+                    // Repeated presses are handled where the key was first pressed
+                    let keep_local = self.local_keys[scancode.as_idx()]
+                        || (!database.is_key_pressed(scancode)
+                            && self.policy.decide(database.modifiers(), scancode) == ShortcutDecision::KeepLocal);
+                    self.local_keys.set(scancode.as_idx(), keep_local);
+                    keep_local
+                }
+                Operation::KeyReleased(scancode) => self.local_keys.replace(scancode.as_idx(), false),
+                _ => false,
+            };
+
+            if keep_local {
+                input.local.push(operation);
+            } else {
+                input.events.extend(database.apply([operation]));
+            }
+        }
+
+        input
+    }
+}
@@ -0,0 +1,425 @@
+//! Touch input, as specified in MS-RDPEI
+//!
+//! Touch events are not sent as fast-path input events, but as [`TouchEventPdu`]s over the
+//! [`CHANNEL_NAME`] dynamic virtual channel, once the server has sent its ready PDU.
+
+use std::collections::BTreeMap;
+
+use bitflags::bitflags;
+use ironrdp_core::{ensure_size, invalid_field_err, Encode, EncodeResult, WriteCursor};
+
+use crate::Operation;
+
+pub const CHANNEL_NAME: &str = "Microsoft::Windows::RDS::Input";
+
+const EVENTID_TOUCH: u16 = 0x0003;
+
+/// Bounding box of a contact, relative to its position
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ContactRect {
+    pub left: i16,
+    pub top: i16,
+    pub right: i16,
+    pub bottom: i16,
+}
+
+/// State of a touch contact, as reported by the operations
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TouchContact {
+    /// Identifier of the contact, unique among the active contacts
+    pub id: u8,
+    pub x: i32,
+    pub y: i32,
+    /// Pressure, in the range 0 to 1024
+    pub pressure: Option<u32>,
+    pub rect: Option<ContactRect>,
+}
+
+impl TouchContact {
+    pub const fn new(id: u8, x: i32, y: i32) -> Self {
+        Self {
+            id,
+            x,
+            y,
+            pressure: None,
+            rect: None,
+        }
+    }
+}
+
+bitflags! {
+    /// [2.2.3.3.1.1] RDPINPUT_CONTACT_DATA, contactFlags field
+    ///
+    /// Also used by the pen contacts.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct ContactFlags: u32 {
+        const DOWN = 0x0001;
+        const UPDATE = 0x0002;
+        const UP = 0x0004;
+        const IN_RANGE = 0x0008;
+        const IN_CONTACT = 0x0010;
+        const CANCELED = 0x0020;
+    }
+}
+
+bitflags! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    struct FieldsPresent: u16 {
+        const CONTACT_RECT = 0x0001;
+        const ORIENTATION = 0x0002;
+        const PRESSURE = 0x0004;
+    }
+}
+
+/// [2.2.3.3] RDPINPUT_TOUCH_EVENT_PDU
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TouchEventPdu {
+    /// Milliseconds elapsed since the previous touch event was encoded
+    pub encode_time: u32,
+    pub frames: Vec<TouchFrame>,
+}
+
+impl TouchEventPdu {
+    const NAME: &'static str = "RDPINPUT_TOUCH_EVENT_PDU";
+
+    const FIXED_PART_SIZE: usize = 2 /* eventId */ + 4 /* pduLength */;
+}
+
+impl Encode for TouchEventPdu {
+    fn encode(&self, dst: &mut WriteCursor<'_>) -> EncodeResult<()> {
+        ensure_size!(in: dst, size: self.size());
+
+        dst.write_u16(EVENTID_TOUCH);
+        dst.write_u32(u32::try_from(self.size()).map_err(|_| invalid_field_err!("pduLength", "too large"))?);
+        VarInt::FourByteUnsigned(self.encode_time).encode(dst)?;
+        VarInt::TwoByteUnsigned(element_count(self.frames.len())?).encode(dst)?;
+
+        for frame in &self.frames {
+            frame.encode(dst)?;
+        }
+
+        Ok(())
+    }
+
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    fn size(&self) -> usize {
+        let frames_size: usize = self.frames.iter().map(Encode::size).sum();
+
+        // LINTS: variable-length integers are at most 8 bytes long
+        #[allow(clippy::arithmetic_side_effects)]
+        let header_size = Self::FIXED_PART_SIZE
+            + VarInt::FourByteUnsigned(self.encode_time).size()
+            + VarInt::TwoByteUnsigned(u16::try_from(self.frames.len()).unwrap_or(u16::MAX)).size();
+
+        header_size.checked_add(frames_size).expect("never overflow")
+    }
+}
+
+/// [2.2.3.3.1] RDPINPUT_TOUCH_FRAME
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TouchFrame {
+    /// Microseconds elapsed since the previous frame
+    pub frame_offset: u64,
+    pub contacts: Vec<TouchContactData>,
+}
+
+impl TouchFrame {
+    const NAME: &'static str = "RDPINPUT_TOUCH_FRAME";
+}
+
+impl Encode for TouchFrame {
+    fn encode(&self, dst: &mut WriteCursor<'_>) -> EncodeResult<()> {
+        VarInt::TwoByteUnsigned(element_count(self.contacts.len())?).encode(dst)?;
+        VarInt::EightByteUnsigned(self.frame_offset).encode(dst)?;
+
+        for contact in &self.contacts {
+            contact.encode(dst)?;
+        }
+
+        Ok(())
+    }
+
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    fn size(&self) -> usize {
+        let contacts_size: usize = self.contacts.iter().map(Encode::size).sum();
+
+        // LINTS: variable-length integers are at most 8 bytes long
+        #[allow(clippy::arithmetic_side_effects)]
+        let header_size = VarInt::TwoByteUnsigned(u16::try_from(self.contacts.len()).unwrap_or(u16::MAX)).size()
+            + VarInt::EightByteUnsigned(self.frame_offset).size();
+
+        header_size.checked_add(contacts_size).expect("never overflow")
+    }
+}
+
+/// [2.2.3.3.1.1] RDPINPUT_CONTACT_DATA
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TouchContactData {
+    pub contact_id: u8,
+    pub x: i32,
+    pub y: i32,
+    pub flags: ContactFlags,
+    pub rect: Option<ContactRect>,
+    /// Orientation, in degrees in the range 0 to 359
+    pub orientation: Option<u32>,
+    /// Pressure, in the range 0 to 1024
+    pub pressure: Option<u32>,
+}
+
+impl TouchContactData {
+    const NAME: &'static str = "RDPINPUT_CONTACT_DATA";
+
+    const FIXED_PART_SIZE: usize = 1 /* contactId */;
+
+    pub fn new(contact: TouchContact, flags: ContactFlags) -> Self {
+        Self {
+            contact_id: contact.id,
+            x: contact.x,
+            y: contact.y,
+            flags,
+            rect: contact.rect,
+            orientation: None,
+            pressure: contact.pressure,
+        }
+    }
+
+    fn fields_present(&self) -> FieldsPresent {
+        let mut fields_present = FieldsPresent::empty();
+        fields_present.set(FieldsPresent::CONTACT_RECT, self.rect.is_some());
+        fields_present.set(FieldsPresent::ORIENTATION, self.orientation.is_some());
+        fields_present.set(FieldsPresent::PRESSURE, self.pressure.is_some());
+        fields_present
+    }
+
+    fn var_ints(&self) -> impl Iterator<Item = VarInt> {
+        let rect = self.rect.into_iter().flat_map(|rect| {
+            [rect.left, rect.top, rect.right, rect.bottom]
+                .into_iter()
+                .map(VarInt::TwoByteSigned)
+        });
+
+        [
+            VarInt::TwoByteUnsigned(self.fields_present().bits()),
+            VarInt::FourByteSigned(self.x),
+            VarInt::FourByteSigned(self.y),
+            VarInt::FourByteUnsigned(self.flags.bits()),
+        ]
+        .into_iter()
+        .chain(rect)
+        .chain(self.orientation.map(VarInt::FourByteUnsigned))
+        .chain(self.pressure.map(VarInt::FourByteUnsigned))
+    }
+}
+
+impl Encode for TouchContactData {
+    fn encode(&self, dst: &mut WriteCursor<'_>) -> EncodeResult<()> {
+        ensure_size!(in: dst, size: self.size());
+
+        dst.write_u8(self.contact_id);
+
+        for var_int in self.var_ints() {
+            var_int.encode(dst)?;
+        }
+
+        Ok(())
+    }
+
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    fn size(&self) -> usize {
+        Self::FIXED_PART_SIZE
+            .checked_add(self.var_ints().map(VarInt::size).sum())
+            .expect("never overflow")
+    }
+}
+
+pub(crate) fn element_count(count: usize) -> EncodeResult<u16> {
+    u16::try_from(count).map_err(|_| invalid_field_err!("count", "too many elements"))
+}
+
+/// [2.2.2] Variable-length integers
+///
+/// The top bits of the first byte hold the number of additional bytes, followed by the sign for signed integers,
+/// the magnitude being written in big-endian order.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum VarInt {
+    TwoByteUnsigned(u16),
+    TwoByteSigned(i16),
+    FourByteUnsigned(u32),
+    FourByteSigned(i32),
+    EightByteUnsigned(u64),
+}
+
+impl VarInt {
+    /// Returns the magnitude, the sign, the maximum size and the number of bits holding the size.
+    fn parts(self) -> (u64, Option<bool>, usize, usize) {
+        match self {
+            VarInt::TwoByteUnsigned(value) => (u64::from(value), None, 2, 1),
+            VarInt::TwoByteSigned(value) => (u64::from(value.unsigned_abs()), Some(value < 0), 2, 1),
+            VarInt::FourByteUnsigned(value) => (u64::from(value), None, 4, 2),
+            VarInt::FourByteSigned(value) => (u64::from(value.unsigned_abs()), Some(value < 0), 4, 2),
+            VarInt::EightByteUnsigned(value) => (value, None, 8, 3),
+        }
+    }
+
+    fn magnitude_bits(self, size: usize) -> usize {
+        let (_, sign, _, size_bits) = self.parts();
+
+        // LINTS: size is in the range 1 to 8, and larger than the header
+        #[allow(clippy::arithmetic_side_effects)]
+        let bits = size * 8 - size_bits - usize::from(sign.is_some());
+
+        bits
+    }
+
+    pub(crate) fn size(self) -> usize {
+        let (magnitude, _, max_size, _) = self.parts();
+
+        (1..=max_size)
+            .find(|&size| magnitude >> self.magnitude_bits(size) == 0)
+            .unwrap_or(max_size)
+    }
+
+    pub(crate) fn encode(self, dst: &mut WriteCursor<'_>) -> EncodeResult<()> {
+        let (magnitude, sign, _, size_bits) = self.parts();
+        let size = self.size();
+        let magnitude_bits = self.magnitude_bits(size);
+
+        if magnitude >> magnitude_bits != 0 {
+            return Err(invalid_field_err!("value", "out of range"));
+        }
+
+        ensure_size!(in: dst, size: size);
+
+        // LINTS: size is in the range 1 to 8, and the shifts are below 64
+        #[allow(clippy::arithmetic_side_effects)]
+        let encoded = {
+            let size_value = u64::try_from(size - 1).expect("size fits in u64");
+            let mut encoded = magnitude | size_value << (size * 8 - size_bits);
+
+            if sign == Some(true) {
+                encoded |= 1 << magnitude_bits;
+            }
+
+            encoded
+        };
+
+        // LINTS: size is in the range 1 to 8
+        #[allow(clippy::arithmetic_side_effects)]
+        dst.write_slice(&encoded.to_be_bytes()[8 - size..]);
+
+        Ok(())
+    }
+}
+
+/// Builds touch events out of touch operations, keeping track of the active contacts.
+///
+/// Each frame lists all the active contacts, since the server expects the whole touch state. A contact updated twice
+/// in the same transaction starts a new frame.
+#[derive(Debug, Clone, Default)]
+pub struct TouchEncoder {
+    contacts: BTreeMap<u8, TouchContact>,
+}
+
+impl TouchEncoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_contact_active(&self, id: u8) -> bool {
+        self.contacts.contains_key(&id)
+    }
+
+    /// Applies a transaction (list of operations) and returns the touch event to send, if any.
+    ///
+    /// Operations other than touch ones, and those which would cause no state change, are ignored. The timing fields
+    /// of the returned event are left to zero.
+    pub fn apply(&mut self, transaction: impl IntoIterator<Item = Operation>) -> Option<TouchEventPdu> {
+        let mut frames = Vec::new();
+        let mut frame = BTreeMap::new();
+
+        for operation in transaction {
+            let contact = match operation {
+                Operation::TouchDown(contact) | Operation::TouchMove(contact) | Operation::TouchUp(contact) => contact,
+                _ => continue,
+            };
+
+            if frame.contains_key(&contact.id) {
+                frames.push(self.close_frame(std::mem::take(&mut frame)));
+            }
+
+            let flags = match operation {
+                Operation::TouchDown(_) => {
+                    let flags = if self.contacts.insert(contact.id, contact).is_some() {
+                        ContactFlags::UPDATE
+                    } else {
+                        ContactFlags::DOWN
+                    };
+
+                    flags | ContactFlags::IN_RANGE | ContactFlags::IN_CONTACT
+                }
+                Operation::TouchMove(_) => match self.contacts.get_mut(&contact.id) {
+                    Some(active) => {
+                        *active = contact;
+                        ContactFlags::UPDATE | ContactFlags::IN_RANGE | ContactFlags::IN_CONTACT
+                    }
+                    None => continue,
+                },
+                _ => match self.contacts.remove(&contact.id) {
+                    Some(_) => ContactFlags::UP,
+                    None => continue,
+                },
+            };
+
+            frame.insert(contact.id, TouchContactData::new(contact, flags));
+        }
+
+        if !frame.is_empty() {
+            frames.push(self.close_frame(frame));
+        }
+
+        (!frames.is_empty()).then_some(TouchEventPdu { encode_time: 0, frames })
+    }
+
+    /// Cancels all the active contacts. Returns the touch event to send, if any.
+    pub fn cancel_all(&mut self) -> Option<TouchEventPdu> {
+        let contacts: Vec<_> = std::mem::take(&mut self.contacts)
+            .into_values()
+            .map(|contact| TouchContactData::new(contact, ContactFlags::UP | ContactFlags::CANCELED))
+            .collect();
+
+        (!contacts.is_empty()).then(|| TouchEventPdu {
+            encode_time: 0,
+            frames: vec![TouchFrame {
+                frame_offset: 0,
+                contacts,
+            }],
+        })
+    }
+
+    fn close_frame(&self, mut frame: BTreeMap<u8, TouchContactData>) -> TouchFrame {
+        for contact in self.contacts.values() {
+            frame.entry(contact.id).or_insert_with(|| {
+                TouchContactData::new(
+                    *contact,
+                    ContactFlags::UPDATE | ContactFlags::IN_RANGE | ContactFlags::IN_CONTACT,
+                )
+            });
+        }
+
+        TouchFrame {
+            frame_offset: 0,
+            contacts: frame.into_values().collect(),
+        }
+    }
+}
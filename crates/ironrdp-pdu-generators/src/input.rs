@@ -0,0 +1,99 @@
+use ironrdp_pdu::input::fast_path::{FastPathInput, FastPathInputEvent, KeyboardFlags, SynchronizeFlags};
+use ironrdp_pdu::input::{
+    mouse, mouse_rel, mouse_x, scan_code, sync, unicode, InputEvent, InputEventPdu, MousePdu, MouseRelPdu, MouseXPdu,
+    ScanCodePdu, SyncPdu, UnicodePdu, UnusedPdu,
+};
+use proptest::collection::vec;
+use proptest::prelude::*;
+
+pub fn sync_pdu() -> impl Strategy<Value = SyncPdu> {
+    any::<u32>().prop_map(|bits| SyncPdu {
+        flags: sync::SyncToggleFlags::from_bits_truncate(bits),
+    })
+}
+
+pub fn scan_code_pdu() -> impl Strategy<Value = ScanCodePdu> {
+    (any::<u16>(), any::<u16>()).prop_map(|(flags, key_code)| ScanCodePdu {
+        flags: scan_code::KeyboardFlags::from_bits_truncate(flags),
+        key_code,
+    })
+}
+
+pub fn unicode_pdu() -> impl Strategy<Value = UnicodePdu> {
+    (any::<u16>(), any::<u16>()).prop_map(|(flags, unicode_code)| UnicodePdu {
+        flags: unicode::KeyboardFlags::from_bits_truncate(flags),
+        unicode_code,
+    })
+}
+
+/// The wheel rotation is a 9-bit two’s complement value, whose sign bit is `WHEEL_NEGATIVE`
+pub fn mouse_pdu() -> impl Strategy<Value = MousePdu> {
+    (any::<u16>(), -256i16..=255, any::<u16>(), any::<u16>()).prop_map(
+        |(flags, number_of_wheel_rotation_units, x_position, y_position)| {
+            let mut flags = mouse::PointerFlags::from_bits_truncate(flags);
+            flags.set(mouse::PointerFlags::WHEEL_NEGATIVE, number_of_wheel_rotation_units < 0);
+
+            MousePdu {
+                flags,
+                number_of_wheel_rotation_units,
+                x_position,
+                y_position,
+            }
+        },
+    )
+}
+
+pub fn mouse_x_pdu() -> impl Strategy<Value = MouseXPdu> {
+    (any::<u16>(), any::<u16>(), any::<u16>()).prop_map(|(flags, x_position, y_position)| MouseXPdu {
+        flags: mouse_x::PointerXFlags::from_bits_truncate(flags),
+        x_position,
+        y_position,
+    })
+}
+
+pub fn mouse_rel_pdu() -> impl Strategy<Value = MouseRelPdu> {
+    (any::<u16>(), any::<i16>(), any::<i16>()).prop_map(|(flags, x_delta, y_delta)| MouseRelPdu {
+        flags: mouse_rel::PointerRelFlags::from_bits_truncate(flags),
+        x_delta,
+        y_delta,
+    })
+}
+
+pub fn input_event() -> impl Strategy<Value = InputEvent> {
+    prop_oneof![
+        sync_pdu().prop_map(InputEvent::Sync),
+        Just(InputEvent::Unused(UnusedPdu)),
+        scan_code_pdu().prop_map(InputEvent::ScanCode),
+        unicode_pdu().prop_map(InputEvent::Unicode),
+        mouse_pdu().prop_map(InputEvent::Mouse),
+        mouse_x_pdu().prop_map(InputEvent::MouseX),
+        mouse_rel_pdu().prop_map(InputEvent::MouseRel),
+    ]
+}
+
+pub fn input_event_pdu() -> impl Strategy<Value = InputEventPdu> {
+    vec(input_event(), 0..16).prop_map(InputEventPdu)
+}
+
+pub fn fast_path_keyboard_flags() -> impl Strategy<Value = KeyboardFlags> {
+    any::<u8>().prop_map(KeyboardFlags::from_bits_truncate)
+}
+
+pub fn fast_path_input_event() -> impl Strategy<Value = FastPathInputEvent> {
+    prop_oneof![
+        (fast_path_keyboard_flags(), any::<u8>())
+            .prop_map(|(flags, code)| FastPathInputEvent::KeyboardEvent(flags, code)),
+        (fast_path_keyboard_flags(), any::<u16>())
+            .prop_map(|(flags, code)| FastPathInputEvent::UnicodeKeyboardEvent(flags, code)),
+        mouse_pdu().prop_map(FastPathInputEvent::MouseEvent),
+        mouse_x_pdu().prop_map(FastPathInputEvent::MouseEventEx),
+        mouse_rel_pdu().prop_map(FastPathInputEvent::MouseEventRel),
+        any::<u32>().prop_map(FastPathInputEvent::QoeEvent),
+        any::<u8>().prop_map(|bits| FastPathInputEvent::SyncEvent(SynchronizeFlags::from_bits_truncate(bits))),
+    ]
+}
+
+/// Fast-path input must contain at least one event
+pub fn fast_path_input() -> impl Strategy<Value = FastPathInput> {
+    vec(fast_path_input_event(), 1..32).prop_map(FastPathInput)
+}
@@ -1,6 +1,20 @@
+//! [`proptest`] strategies generating valid `ironrdp-pdu` PDUs.
+//!
+//! Each strategy only produces values which survive an encode/decode round-trip unchanged, so they can be used to
+//! write property tests such as `decode(encode(pdu)) == pdu`, or to seed structure-aware fuzzers.
+//!
+//! The strategies are organized in modules mirroring the ones of `ironrdp-pdu`.
+
 // No need to be as strict as in production libraries
 #![allow(clippy::arithmetic_side_effects)]
 #![allow(clippy::cast_lossless)]
 #![allow(clippy::cast_possible_truncation)]
 #![allow(clippy::cast_possible_wrap)]
 #![allow(clippy::cast_sign_loss)]
+
+pub mod input;
+pub mod mcs;
+pub mod nego;
+pub mod pcb;
+pub mod surface_commands;
+pub mod vc;
@@ -0,0 +1,98 @@
+use std::borrow::Cow;
+
+use ironrdp_pdu::mcs::{
+    AttachUserConfirm, AttachUserRequest, ChannelJoinConfirm, ChannelJoinRequest, DisconnectProviderUltimatum,
+    DisconnectReason, ErectDomainPdu, McsMessage, SendDataIndication, SendDataRequest, RESULT_ENUM_LENGTH,
+};
+use proptest::collection::vec;
+use proptest::prelude::*;
+
+/// User IDs are PER-encoded as an offset from the first dynamic channel ID
+pub fn user_id() -> impl Strategy<Value = u16> {
+    1001..=u16::MAX
+}
+
+pub fn result() -> impl Strategy<Value = u8> {
+    0..RESULT_ENUM_LENGTH
+}
+
+pub fn erect_domain_pdu() -> impl Strategy<Value = ErectDomainPdu> {
+    (any::<u32>(), any::<u32>()).prop_map(|(sub_height, sub_interval)| ErectDomainPdu {
+        sub_height,
+        sub_interval,
+    })
+}
+
+pub fn attach_user_request() -> impl Strategy<Value = AttachUserRequest> {
+    Just(AttachUserRequest)
+}
+
+pub fn attach_user_confirm() -> impl Strategy<Value = AttachUserConfirm> {
+    (result(), user_id()).prop_map(|(result, initiator_id)| AttachUserConfirm { result, initiator_id })
+}
+
+pub fn channel_join_request() -> impl Strategy<Value = ChannelJoinRequest> {
+    (user_id(), any::<u16>()).prop_map(|(initiator_id, channel_id)| ChannelJoinRequest {
+        initiator_id,
+        channel_id,
+    })
+}
+
+pub fn channel_join_confirm() -> impl Strategy<Value = ChannelJoinConfirm> {
+    (result(), user_id(), any::<u16>(), any::<u16>()).prop_map(
+        |(result, initiator_id, requested_channel_id, channel_id)| ChannelJoinConfirm {
+            result,
+            initiator_id,
+            requested_channel_id,
+            channel_id,
+        },
+    )
+}
+
+/// Spans both the short and long forms of the PER length
+pub fn user_data() -> impl Strategy<Value = Cow<'static, [u8]>> {
+    vec(any::<u8>(), 0..512).prop_map(Cow::Owned)
+}
+
+pub fn send_data_request() -> impl Strategy<Value = SendDataRequest<'static>> {
+    (user_id(), any::<u16>(), user_data()).prop_map(|(initiator_id, channel_id, user_data)| SendDataRequest {
+        initiator_id,
+        channel_id,
+        user_data,
+    })
+}
+
+pub fn send_data_indication() -> impl Strategy<Value = SendDataIndication<'static>> {
+    (user_id(), any::<u16>(), user_data()).prop_map(|(initiator_id, channel_id, user_data)| SendDataIndication {
+        initiator_id,
+        channel_id,
+        user_data,
+    })
+}
+
+pub fn disconnect_reason() -> impl Strategy<Value = DisconnectReason> {
+    prop_oneof![
+        Just(DisconnectReason::DomainDisconnected),
+        Just(DisconnectReason::ProviderInitiated),
+        Just(DisconnectReason::TokenPurged),
+        Just(DisconnectReason::UserRequested),
+        Just(DisconnectReason::ChannelPurged),
+    ]
+}
+
+pub fn disconnect_provider_ultimatum() -> impl Strategy<Value = DisconnectProviderUltimatum> {
+    disconnect_reason().prop_map(DisconnectProviderUltimatum::from_reason)
+}
+
+pub fn mcs_message() -> impl Strategy<Value = McsMessage<'static>> {
+    prop_oneof![
+        erect_domain_pdu().prop_map(McsMessage::ErectDomainRequest),
+        attach_user_request().prop_map(McsMessage::AttachUserRequest),
+        attach_user_confirm().prop_map(McsMessage::AttachUserConfirm),
+        channel_join_request().prop_map(McsMessage::ChannelJoinRequest),
+        channel_join_confirm().prop_map(McsMessage::ChannelJoinConfirm),
+        send_data_request().prop_map(McsMessage::SendDataRequest),
+        send_data_indication().prop_map(McsMessage::SendDataIndication),
+        disconnect_provider_ultimatum().prop_map(McsMessage::DisconnectProviderUltimatum),
+    ]
+}
@@ -0,0 +1,43 @@
+use ironrdp_pdu::nego::{
+    ConnectionConfirm, ConnectionRequest, FailureCode, NegoRequestData, RequestFlags, ResponseFlags, SecurityProtocol,
+};
+use proptest::prelude::*;
+
+pub fn security_protocol() -> impl Strategy<Value = SecurityProtocol> {
+    any::<u32>().prop_map(SecurityProtocol::from_bits_truncate)
+}
+
+/// Cookie and routing token values are terminated by CR LF, and can’t contain it
+pub fn nego_request_data() -> impl Strategy<Value = NegoRequestData> {
+    prop_oneof![
+        "[[:alnum:]@._-]{0,32}".prop_map(NegoRequestData::cookie),
+        "[[:alnum:]@._-]{0,32}".prop_map(NegoRequestData::routing_token),
+    ]
+}
+
+pub fn connection_request() -> impl Strategy<Value = ConnectionRequest> {
+    (
+        proptest::option::of(nego_request_data()),
+        // RDP_NEG_CORRELATION_INFO is not supported
+        any::<u8>()
+            .prop_map(|bits| RequestFlags::from_bits_truncate(bits).difference(RequestFlags::CORRELATION_INFO_PRESENT)),
+        security_protocol(),
+    )
+        .prop_map(|(nego_data, flags, protocol)| ConnectionRequest {
+            nego_data,
+            flags,
+            protocol,
+        })
+}
+
+pub fn connection_confirm() -> impl Strategy<Value = ConnectionConfirm> {
+    prop_oneof![
+        (any::<u8>(), security_protocol()).prop_map(|(flags, protocol)| ConnectionConfirm::Response {
+            flags: ResponseFlags::from_bits_truncate(flags),
+            protocol,
+        }),
+        any::<u32>().prop_map(|code| ConnectionConfirm::Failure {
+            code: FailureCode::from(code),
+        }),
+    ]
+}
@@ -0,0 +1,22 @@
+use ironrdp_pdu::pcb::{PcbVersion, PreconnectionBlob};
+use proptest::prelude::*;
+
+/// The PCB string is null-terminated, and can’t contain any null character
+pub fn v2_payload() -> impl Strategy<Value = String> {
+    "[^\\x00]{0,64}"
+}
+
+pub fn preconnection_blob() -> impl Strategy<Value = PreconnectionBlob> {
+    prop_oneof![
+        any::<u32>().prop_map(|id| PreconnectionBlob {
+            version: PcbVersion::V1,
+            id,
+            v2_payload: None,
+        }),
+        (any::<u32>(), v2_payload()).prop_map(|(id, payload)| PreconnectionBlob {
+            version: PcbVersion::V2,
+            id,
+            v2_payload: Some(payload),
+        }),
+    ]
+}
@@ -0,0 +1,19 @@
+use ironrdp_pdu::surface_commands::{FrameAction, FrameMarkerPdu, SurfaceCommand};
+use proptest::prelude::*;
+
+pub fn frame_action() -> impl Strategy<Value = FrameAction> {
+    prop_oneof![Just(FrameAction::Begin), Just(FrameAction::End)]
+}
+
+/// A missing frame ID is only tolerated when decoding, it is always encoded
+pub fn frame_marker_pdu() -> impl Strategy<Value = FrameMarkerPdu> {
+    (frame_action(), any::<u32>()).prop_map(|(frame_action, frame_id)| FrameMarkerPdu {
+        frame_action,
+        frame_id: Some(frame_id),
+    })
+}
+
+/// Surface bits commands borrow their bitmap data, only frame markers are generated
+pub fn surface_command() -> impl Strategy<Value = SurfaceCommand<'static>> {
+    frame_marker_pdu().prop_map(SurfaceCommand::FrameMarker)
+}
@@ -0,0 +1,9 @@
+use ironrdp_pdu::rdp::vc::{ChannelControlFlags, ChannelPduHeader};
+use proptest::prelude::*;
+
+pub fn channel_pdu_header() -> impl Strategy<Value = ChannelPduHeader> {
+    (any::<u32>(), any::<u32>()).prop_map(|(length, flags)| ChannelPduHeader {
+        length,
+        flags: ChannelControlFlags::from_bits_truncate(flags),
+    })
+}
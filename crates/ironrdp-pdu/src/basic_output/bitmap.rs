@@ -3,7 +3,8 @@ mod tests;
 
 pub mod rdp6;
 
-use std::fmt::{self, Debug};
+use alloc::vec::Vec;
+use core::fmt::{self, Debug};
 
 use bitflags::bitflags;
 
@@ -1,6 +1,8 @@
 #[cfg(test)]
 mod tests;
 
+use alloc::vec::Vec;
+
 use bit_field::BitField;
 use bitflags::bitflags;
 use num_derive::{FromPrimitive, ToPrimitive};
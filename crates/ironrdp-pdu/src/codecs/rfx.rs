@@ -1,13 +1,13 @@
+mod buffer;
 mod data_messages;
 mod header_messages;
 
-use std::io;
+use core::fmt;
 
-use byteorder::{LittleEndian, ReadBytesExt as _, WriteBytesExt as _};
 use num_derive::{FromPrimitive, ToPrimitive};
 use num_traits::{FromPrimitive as _, ToPrimitive as _};
-use thiserror::Error;
 
+use self::buffer::{ReadLeExt as _, WriteLeExt as _};
 use crate::PduBufferParsing;
 use ironrdp_core::{ensure_fixed_part_size, ReadCursor, WriteCursor};
 use ironrdp_core::{Decode, DecodeResult, Encode, EncodeResult};
@@ -40,7 +40,7 @@ impl<'a> PduBufferParsing<'a> for Headers {
 
     fn from_buffer_consume(buffer: &mut &'a [u8]) -> Result<Self, Self::Error> {
         let mut temp = *buffer;
-        let ty = temp.read_u16::<LittleEndian>()?;
+        let ty = temp.read_u16()?;
         let ty = BlockType::from_u16(ty).ok_or(RfxError::InvalidBlockType(ty))?;
 
         match ty {
@@ -81,7 +81,7 @@ impl BlockHeader {
     }
 
     fn from_buffer_consume_with_type(buffer: &mut &[u8], ty: BlockType) -> Result<Self, RfxError> {
-        let block_length = buffer.read_u32::<LittleEndian>()? as usize;
+        let block_length = buffer.read_u32()? as usize;
 
         let block_length_without_header = block_length
             .checked_sub(BLOCK_HEADER_SIZE)
@@ -114,8 +114,8 @@ impl BlockHeader {
     }
 
     fn to_buffer_consume(&self, buffer: &mut &mut [u8]) -> Result<(), RfxError> {
-        buffer.write_u16::<LittleEndian>(self.ty.to_u16().unwrap())?;
-        buffer.write_u32::<LittleEndian>((headers_length(self.ty) + self.data_length) as u32)?;
+        buffer.write_u16(self.ty.to_u16().unwrap())?;
+        buffer.write_u32((headers_length(self.ty) + self.data_length) as u32)?;
 
         Ok(())
     }
@@ -225,64 +225,80 @@ pub enum BlockType {
 
 impl BlockType {
     fn from_buffer(buffer: &mut &[u8]) -> Result<Self, RfxError> {
-        let ty = buffer.read_u16::<LittleEndian>()?;
+        let ty = buffer.read_u16()?;
         let ty = BlockType::from_u16(ty).ok_or(RfxError::InvalidBlockType(ty))?;
         Ok(ty)
     }
 }
 
-#[derive(Debug, Error)]
+#[derive(Debug)]
 pub enum RfxError {
-    #[error("IO error")]
-    IoError(#[from] io::Error),
-    #[error("got invalid block type: {0}")]
     InvalidBlockType(u16),
-    #[error("got unexpected Block type: expected ({expected:?}) != actual({actual:?})")]
     UnexpectedBlockType { expected: BlockType, actual: BlockType },
-    #[error("got unexpected Block type ({0:?}) while was expected header message")]
     InvalidHeaderBlockType(BlockType),
-    #[error("got invalid block length: {0}")]
     InvalidBlockLength(usize),
-    #[error("got invalid Sync magic number: {0}")]
     InvalidMagicNumber(u32),
-    #[error("got invalid Sync version: {0}")]
     InvalidSyncVersion(u16),
-    #[error("got invalid codecs number: {0}")]
     InvalidCodecsNumber(u8),
-    #[error("got invalid codec ID: {0}")]
     InvalidCodecId(u8),
-    #[error("got invalid codec version: {0}")]
     InvalidCodecVersion(u16),
-    #[error("got invalid channel ID: {0}")]
     InvalidChannelId(u8),
-    #[error("got invalid context ID: {0}")]
     InvalidContextId(u8),
-    #[error("got invalid context tile size: {0}")]
     InvalidTileSize(u16),
-    #[error("got invalid conversion transform: {0}")]
     InvalidColorConversionTransform(u16),
-    #[error("got invalid DWT: {0}")]
     InvalidDwt(u16),
-    #[error("got invalid entropy algorithm: {0}")]
     InvalidEntropyAlgorithm(u16),
-    #[error("got invalid quantization type: {0}")]
     InvalidQuantizationType(u16),
-    #[error("input buffer is shorter than the data length: {actual} < {expected}")]
     InvalidDataLength { expected: usize, actual: usize },
-    #[error("got invalid Region LRF: {0}")]
     InvalidLrf(bool),
-    #[error("got invalid Region type: {0}")]
     InvalidRegionType(u16),
-    #[error("got invalid number of tilesets: {0}")]
     InvalidNumberOfTilesets(u16),
-    #[error("got invalid ID of context: {0}")]
     InvalidIdOfContext(u16),
-    #[error("got invalid TileSet subtype: {0}")]
     InvalidSubtype(u16),
-    #[error("got invalid IT flag of TileSet: {0}")]
     InvalidItFlag(bool),
-    #[error("got invalid channel width: {0}")]
     InvalidChannelWidth(i16),
-    #[error("got invalid channel height: {0}")]
     InvalidChannelHeight(i16),
 }
+
+impl fmt::Display for RfxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidBlockType(value) => write!(f, "got invalid block type: {value}"),
+            Self::UnexpectedBlockType { expected, actual } => write!(
+                f,
+                "got unexpected Block type: expected ({expected:?}) != actual({actual:?})"
+            ),
+            Self::InvalidHeaderBlockType(value) => write!(
+                f,
+                "got unexpected Block type ({value:?}) while was expected header message"
+            ),
+            Self::InvalidBlockLength(value) => write!(f, "got invalid block length: {value}"),
+            Self::InvalidMagicNumber(value) => write!(f, "got invalid Sync magic number: {value}"),
+            Self::InvalidSyncVersion(value) => write!(f, "got invalid Sync version: {value}"),
+            Self::InvalidCodecsNumber(value) => write!(f, "got invalid codecs number: {value}"),
+            Self::InvalidCodecId(value) => write!(f, "got invalid codec ID: {value}"),
+            Self::InvalidCodecVersion(value) => write!(f, "got invalid codec version: {value}"),
+            Self::InvalidChannelId(value) => write!(f, "got invalid channel ID: {value}"),
+            Self::InvalidContextId(value) => write!(f, "got invalid context ID: {value}"),
+            Self::InvalidTileSize(value) => write!(f, "got invalid context tile size: {value}"),
+            Self::InvalidColorConversionTransform(value) => write!(f, "got invalid conversion transform: {value}"),
+            Self::InvalidDwt(value) => write!(f, "got invalid DWT: {value}"),
+            Self::InvalidEntropyAlgorithm(value) => write!(f, "got invalid entropy algorithm: {value}"),
+            Self::InvalidQuantizationType(value) => write!(f, "got invalid quantization type: {value}"),
+            Self::InvalidDataLength { expected, actual } => {
+                write!(f, "input buffer is shorter than the data length: {actual} < {expected}")
+            }
+            Self::InvalidLrf(value) => write!(f, "got invalid Region LRF: {value}"),
+            Self::InvalidRegionType(value) => write!(f, "got invalid Region type: {value}"),
+            Self::InvalidNumberOfTilesets(value) => write!(f, "got invalid number of tilesets: {value}"),
+            Self::InvalidIdOfContext(value) => write!(f, "got invalid ID of context: {value}"),
+            Self::InvalidSubtype(value) => write!(f, "got invalid TileSet subtype: {value}"),
+            Self::InvalidItFlag(value) => write!(f, "got invalid IT flag of TileSet: {value}"),
+            Self::InvalidChannelWidth(value) => write!(f, "got invalid channel width: {value}"),
+            Self::InvalidChannelHeight(value) => write!(f, "got invalid channel height: {value}"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for RfxError {}
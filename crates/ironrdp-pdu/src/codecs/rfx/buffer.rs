@@ -0,0 +1,83 @@
+//! Little-endian reads and writes over byte slices, consuming the slice like `std::io::Read` and `std::io::Write` do.
+
+use super::RfxError;
+
+pub(super) trait ReadLeExt {
+    fn read_u8(&mut self) -> Result<u8, RfxError>;
+    fn read_u16(&mut self) -> Result<u16, RfxError>;
+    fn read_i16(&mut self) -> Result<i16, RfxError>;
+    fn read_u32(&mut self) -> Result<u32, RfxError>;
+}
+
+impl ReadLeExt for &[u8] {
+    fn read_u8(&mut self) -> Result<u8, RfxError> {
+        read_array(self).map(u8::from_le_bytes)
+    }
+
+    fn read_u16(&mut self) -> Result<u16, RfxError> {
+        read_array(self).map(u16::from_le_bytes)
+    }
+
+    fn read_i16(&mut self) -> Result<i16, RfxError> {
+        read_array(self).map(i16::from_le_bytes)
+    }
+
+    fn read_u32(&mut self) -> Result<u32, RfxError> {
+        read_array(self).map(u32::from_le_bytes)
+    }
+}
+
+pub(super) trait WriteLeExt {
+    fn write_u8(&mut self, value: u8) -> Result<(), RfxError>;
+    fn write_u16(&mut self, value: u16) -> Result<(), RfxError>;
+    fn write_i16(&mut self, value: i16) -> Result<(), RfxError>;
+    fn write_u32(&mut self, value: u32) -> Result<(), RfxError>;
+    fn write_all(&mut self, data: &[u8]) -> Result<(), RfxError>;
+}
+
+impl WriteLeExt for &mut [u8] {
+    fn write_u8(&mut self, value: u8) -> Result<(), RfxError> {
+        self.write_all(&value.to_le_bytes())
+    }
+
+    fn write_u16(&mut self, value: u16) -> Result<(), RfxError> {
+        self.write_all(&value.to_le_bytes())
+    }
+
+    fn write_i16(&mut self, value: i16) -> Result<(), RfxError> {
+        self.write_all(&value.to_le_bytes())
+    }
+
+    fn write_u32(&mut self, value: u32) -> Result<(), RfxError> {
+        self.write_all(&value.to_le_bytes())
+    }
+
+    fn write_all(&mut self, data: &[u8]) -> Result<(), RfxError> {
+        if self.len() < data.len() {
+            return Err(RfxError::InvalidDataLength {
+                expected: data.len(),
+                actual: self.len(),
+            });
+        }
+
+        let (dst, rest) = core::mem::take(self).split_at_mut(data.len());
+        dst.copy_from_slice(data);
+        *self = rest;
+
+        Ok(())
+    }
+}
+
+fn read_array<const N: usize>(buffer: &mut &[u8]) -> Result<[u8; N], RfxError> {
+    if buffer.len() < N {
+        return Err(RfxError::InvalidDataLength {
+            expected: N,
+            actual: buffer.len(),
+        });
+    }
+
+    let (bytes, rest) = buffer.split_at(N);
+    *buffer = rest;
+
+    Ok(bytes.try_into().expect("N bytes"))
+}
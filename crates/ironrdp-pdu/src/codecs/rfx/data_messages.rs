@@ -1,11 +1,11 @@
-use std::io::Write;
+use alloc::vec::Vec;
 
 use bit_field::BitField;
 use bitflags::bitflags;
-use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
 use num_derive::{FromPrimitive, ToPrimitive};
 use num_traits::{FromPrimitive, ToPrimitive};
 
+use super::buffer::{ReadLeExt as _, WriteLeExt as _};
 use super::{BlockHeader, BlockType, CodecChannelHeader, RfxError, BLOCK_HEADER_SIZE, CODEC_CHANNEL_HEADER_SIZE};
 use crate::utils::SplitTo;
 use crate::PduBufferParsing;
@@ -40,12 +40,12 @@ impl ContextPdu {
             return Err(RfxError::InvalidContextId(id));
         }
 
-        let tile_size = buffer.read_u16::<LittleEndian>()?;
+        let tile_size = buffer.read_u16()?;
         if tile_size != TILE_SIZE {
             return Err(RfxError::InvalidTileSize(tile_size));
         }
 
-        let properties = buffer.read_u16::<LittleEndian>()?;
+        let properties = buffer.read_u16()?;
         let flags = OperatingMode::from_bits_truncate(properties.get_bits(0..3));
         let color_conversion_transform = properties.get_bits(3..5);
         if color_conversion_transform != COLOR_CONVERSION_ICT {
@@ -95,7 +95,7 @@ impl PduBufferParsing<'_> for ContextPdu {
         codec_header.to_buffer_consume_with_type(buffer, BlockType::Context)?;
 
         buffer.write_u8(CONTEXT_ID)?;
-        buffer.write_u16::<LittleEndian>(TILE_SIZE)?;
+        buffer.write_u16(TILE_SIZE)?;
 
         let mut properties: u16 = 0;
         properties.set_bits(0..3, self.flags.bits());
@@ -104,7 +104,7 @@ impl PduBufferParsing<'_> for ContextPdu {
         properties.set_bits(9..13, self.entropy_algorithm.to_u16().unwrap());
         properties.set_bits(13..15, SCALAR_QUANTIZATION);
         properties.set_bit(15, false); // reserved
-        buffer.write_u16::<LittleEndian>(properties)?;
+        buffer.write_u16(properties)?;
 
         Ok(())
     }
@@ -125,8 +125,8 @@ impl FrameBeginPdu {
         CodecChannelHeader::from_buffer_consume_with_type(buffer, BlockType::FrameBegin)?;
         let mut buffer = buffer.split_to(header.data_length);
 
-        let index = buffer.read_u32::<LittleEndian>()?;
-        let number_of_regions = buffer.read_i16::<LittleEndian>()?;
+        let index = buffer.read_u32()?;
+        let number_of_regions = buffer.read_i16()?;
 
         Ok(Self {
             index,
@@ -153,8 +153,8 @@ impl PduBufferParsing<'_> for FrameBeginPdu {
         header.to_buffer_consume(buffer)?;
         codec_header.to_buffer_consume_with_type(buffer, BlockType::FrameBegin)?;
 
-        buffer.write_u32::<LittleEndian>(self.index)?;
-        buffer.write_i16::<LittleEndian>(self.number_of_regions)?;
+        buffer.write_u32(self.index)?;
+        buffer.write_i16(self.number_of_regions)?;
 
         Ok(())
     }
@@ -214,7 +214,7 @@ impl PduBufferParsing<'_> for RegionPdu {
             return Err(RfxError::InvalidLrf(lrf));
         }
 
-        let number_of_rectangles = usize::from(buffer.read_u16::<LittleEndian>()?);
+        let number_of_rectangles = usize::from(buffer.read_u16()?);
         if buffer.len() < number_of_rectangles * RECTANGLE_SIZE {
             return Err(RfxError::InvalidDataLength {
                 expected: number_of_rectangles * RECTANGLE_SIZE,
@@ -226,12 +226,12 @@ impl PduBufferParsing<'_> for RegionPdu {
             .map(|_| RfxRectangle::from_buffer_consume(&mut buffer))
             .collect::<Result<Vec<_>, _>>()?;
 
-        let region_type = buffer.read_u16::<LittleEndian>()?;
+        let region_type = buffer.read_u16()?;
         if region_type != CBT_REGION {
             return Err(RfxError::InvalidRegionType(region_type));
         }
 
-        let number_of_tilesets = buffer.read_u16::<LittleEndian>()?;
+        let number_of_tilesets = buffer.read_u16()?;
         if number_of_tilesets != NUMBER_OF_TILESETS {
             return Err(RfxError::InvalidNumberOfTilesets(number_of_tilesets));
         }
@@ -253,13 +253,13 @@ impl PduBufferParsing<'_> for RegionPdu {
         region_flags.set_bit(0, LRF);
         buffer.write_u8(region_flags)?;
 
-        buffer.write_u16::<LittleEndian>(self.rectangles.len() as u16)?;
+        buffer.write_u16(self.rectangles.len() as u16)?;
         for rectangle in self.rectangles.iter() {
             rectangle.to_buffer_consume(buffer)?;
         }
 
-        buffer.write_u16::<LittleEndian>(CBT_REGION)?;
-        buffer.write_u16::<LittleEndian>(NUMBER_OF_TILESETS)?;
+        buffer.write_u16(CBT_REGION)?;
+        buffer.write_u16(NUMBER_OF_TILESETS)?;
 
         Ok(())
     }
@@ -291,17 +291,17 @@ impl<'a> PduBufferParsing<'a> for TileSetPdu<'a> {
         CodecChannelHeader::from_buffer_consume_with_type(buffer, BlockType::Extension)?;
         let mut buffer = buffer.split_to(header.data_length);
 
-        let subtype = buffer.read_u16::<LittleEndian>()?;
+        let subtype = buffer.read_u16()?;
         if subtype != CBT_TILESET {
             return Err(RfxError::InvalidSubtype(subtype));
         }
 
-        let id_of_context = buffer.read_u16::<LittleEndian>()?;
+        let id_of_context = buffer.read_u16()?;
         if id_of_context != IDX {
             return Err(RfxError::InvalidIdOfContext(id_of_context));
         }
 
-        let properties = buffer.read_u16::<LittleEndian>()?;
+        let properties = buffer.read_u16()?;
         let is_last = properties.get_bit(0);
         if is_last != IS_LAST_TILESET_FLAG {
             return Err(RfxError::InvalidItFlag(is_last));
@@ -338,8 +338,8 @@ impl<'a> PduBufferParsing<'a> for TileSetPdu<'a> {
             return Err(RfxError::InvalidTileSize(tile_size));
         }
 
-        let number_of_tiles = buffer.read_u16::<LittleEndian>()?;
-        let tiles_data_size = buffer.read_u32::<LittleEndian>()? as usize;
+        let number_of_tiles = buffer.read_u16()?;
+        let tiles_data_size = buffer.read_u32()? as usize;
 
         let expected_length = tiles_data_size + number_of_quants * QUANT_SIZE;
         if buffer.len() < expected_length {
@@ -374,8 +374,8 @@ impl<'a> PduBufferParsing<'a> for TileSetPdu<'a> {
         header.to_buffer_consume(buffer)?;
         codec_header.to_buffer_consume_with_type(buffer, BlockType::Extension)?;
 
-        buffer.write_u16::<LittleEndian>(CBT_TILESET)?;
-        buffer.write_u16::<LittleEndian>(IDX)?;
+        buffer.write_u16(CBT_TILESET)?;
+        buffer.write_u16(IDX)?;
 
         let mut properties: u16 = 0;
         properties.set_bit(0, IS_LAST_TILESET_FLAG);
@@ -384,14 +384,14 @@ impl<'a> PduBufferParsing<'a> for TileSetPdu<'a> {
         properties.set_bits(6..10, CLW_XFORM_DWT_53_A);
         properties.set_bits(10..14, self.entropy_algorithm.to_u16().unwrap());
         properties.set_bits(14..16, SCALAR_QUANTIZATION);
-        buffer.write_u16::<LittleEndian>(properties)?;
+        buffer.write_u16(properties)?;
 
         buffer.write_u8(self.quants.len() as u8)?;
         buffer.write_u8(TILE_SIZE as u8)?;
-        buffer.write_u16::<LittleEndian>(self.tiles.len() as u16)?;
+        buffer.write_u16(self.tiles.len() as u16)?;
 
         let tiles_data_size = self.tiles.iter().map(|t| t.buffer_length()).sum::<usize>() as u32;
-        buffer.write_u32::<LittleEndian>(tiles_data_size)?;
+        buffer.write_u32(tiles_data_size)?;
 
         for quant in self.quants.iter() {
             quant.to_buffer_consume(buffer)?;
@@ -425,19 +425,19 @@ impl PduBufferParsing<'_> for RfxRectangle {
     type Error = RfxError;
 
     fn from_buffer_consume(buffer: &mut &[u8]) -> Result<Self, Self::Error> {
-        let x = buffer.read_u16::<LittleEndian>()?;
-        let y = buffer.read_u16::<LittleEndian>()?;
-        let width = buffer.read_u16::<LittleEndian>()?;
-        let height = buffer.read_u16::<LittleEndian>()?;
+        let x = buffer.read_u16()?;
+        let y = buffer.read_u16()?;
+        let width = buffer.read_u16()?;
+        let height = buffer.read_u16()?;
 
         Ok(Self { x, y, width, height })
     }
 
     fn to_buffer_consume(&self, buffer: &mut &mut [u8]) -> Result<(), Self::Error> {
-        buffer.write_u16::<LittleEndian>(self.x)?;
-        buffer.write_u16::<LittleEndian>(self.y)?;
-        buffer.write_u16::<LittleEndian>(self.width)?;
-        buffer.write_u16::<LittleEndian>(self.height)?;
+        buffer.write_u16(self.x)?;
+        buffer.write_u16(self.y)?;
+        buffer.write_u16(self.width)?;
+        buffer.write_u16(self.height)?;
 
         Ok(())
     }
@@ -488,13 +488,13 @@ impl PduBufferParsing<'_> for Quant {
     type Error = RfxError;
 
     fn from_buffer_consume(buffer: &mut &[u8]) -> Result<Self, Self::Error> {
-        let level3 = buffer.read_u16::<LittleEndian>()?;
+        let level3 = buffer.read_u16()?;
         let ll3 = level3.get_bits(0..4) as u8;
         let lh3 = level3.get_bits(4..8) as u8;
         let hl3 = level3.get_bits(8..12) as u8;
         let hh3 = level3.get_bits(12..16) as u8;
 
-        let level2_with_lh1 = buffer.read_u16::<LittleEndian>()?;
+        let level2_with_lh1 = buffer.read_u16()?;
         let lh2 = level2_with_lh1.get_bits(0..4) as u8;
         let hl2 = level2_with_lh1.get_bits(4..8) as u8;
         let hh2 = level2_with_lh1.get_bits(8..12) as u8;
@@ -535,8 +535,8 @@ impl PduBufferParsing<'_> for Quant {
         level1.set_bits(0..4, self.hl1);
         level1.set_bits(4..8, self.hh1);
 
-        buffer.write_u16::<LittleEndian>(level3)?;
-        buffer.write_u16::<LittleEndian>(level2_with_lh1)?;
+        buffer.write_u16(level3)?;
+        buffer.write_u16(level2_with_lh1)?;
         buffer.write_u8(level1)?;
 
         Ok(())
@@ -572,12 +572,12 @@ impl<'a> PduBufferParsing<'a> for Tile<'a> {
         let cb_quant_index = buffer.read_u8()?;
         let cr_quant_index = buffer.read_u8()?;
 
-        let x = buffer.read_u16::<LittleEndian>()?;
-        let y = buffer.read_u16::<LittleEndian>()?;
+        let x = buffer.read_u16()?;
+        let y = buffer.read_u16()?;
 
-        let y_component_length = usize::from(buffer.read_u16::<LittleEndian>()?);
-        let cb_component_length = usize::from(buffer.read_u16::<LittleEndian>()?);
-        let cr_component_length = usize::from(buffer.read_u16::<LittleEndian>()?);
+        let y_component_length = usize::from(buffer.read_u16()?);
+        let cb_component_length = usize::from(buffer.read_u16()?);
+        let cr_component_length = usize::from(buffer.read_u16()?);
 
         if buffer.len() < y_component_length + cb_component_length + cr_component_length {
             return Err(RfxError::InvalidDataLength {
@@ -620,12 +620,12 @@ impl<'a> PduBufferParsing<'a> for Tile<'a> {
         buffer.write_u8(self.cb_quant_index)?;
         buffer.write_u8(self.cr_quant_index)?;
 
-        buffer.write_u16::<LittleEndian>(self.x)?;
-        buffer.write_u16::<LittleEndian>(self.y)?;
+        buffer.write_u16(self.x)?;
+        buffer.write_u16(self.y)?;
 
-        buffer.write_u16::<LittleEndian>(self.y_data.len() as u16)?;
-        buffer.write_u16::<LittleEndian>(self.cb_data.len() as u16)?;
-        buffer.write_u16::<LittleEndian>(self.cr_data.len() as u16)?;
+        buffer.write_u16(self.y_data.len() as u16)?;
+        buffer.write_u16(self.cb_data.len() as u16)?;
+        buffer.write_u16(self.cr_data.len() as u16)?;
 
         buffer.write_all(self.y_data)?;
         buffer.write_all(self.cb_data)?;
@@ -1,5 +1,6 @@
-use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use alloc::vec::Vec;
 
+use super::buffer::{ReadLeExt as _, WriteLeExt as _};
 use super::{BlockHeader, BlockType, RfxError, BLOCK_HEADER_SIZE};
 use crate::utils::SplitTo;
 use crate::PduBufferParsing;
@@ -20,11 +21,11 @@ impl SyncPdu {
     pub fn from_buffer_consume_with_header(buffer: &mut &[u8], header: BlockHeader) -> Result<Self, RfxError> {
         let mut buffer = buffer.split_to(header.data_length);
 
-        let magic = buffer.read_u32::<LittleEndian>()?;
+        let magic = buffer.read_u32()?;
         if magic != SYNC_MAGIC {
             return Err(RfxError::InvalidMagicNumber(magic));
         }
-        let version = buffer.read_u16::<LittleEndian>()?;
+        let version = buffer.read_u16()?;
         if version != SYNC_VERSION {
             Err(RfxError::InvalidSyncVersion(version))
         } else {
@@ -48,8 +49,8 @@ impl PduBufferParsing<'_> for SyncPdu {
         };
 
         header.to_buffer_consume(buffer)?;
-        buffer.write_u32::<LittleEndian>(SYNC_MAGIC)?;
-        buffer.write_u16::<LittleEndian>(SYNC_VERSION)?;
+        buffer.write_u32(SYNC_MAGIC)?;
+        buffer.write_u16(SYNC_VERSION)?;
 
         Ok(())
     }
@@ -211,10 +212,10 @@ impl PduBufferParsing<'_> for RfxChannel {
             return Err(RfxError::InvalidChannelId(id));
         }
 
-        let width = buffer.read_i16::<LittleEndian>()?;
+        let width = buffer.read_i16()?;
         let width = RfxChannelWidth::new(width);
 
-        let height = buffer.read_i16::<LittleEndian>()?;
+        let height = buffer.read_i16()?;
         let height = RfxChannelHeight::new(height);
 
         Ok(Self { width, height })
@@ -222,8 +223,8 @@ impl PduBufferParsing<'_> for RfxChannel {
 
     fn to_buffer_consume(&self, buffer: &mut &mut [u8]) -> Result<(), Self::Error> {
         buffer.write_u8(CHANNEL_ID)?;
-        buffer.write_i16::<LittleEndian>(self.width.get())?;
-        buffer.write_i16::<LittleEndian>(self.height.get())?;
+        buffer.write_i16(self.width.get())?;
+        buffer.write_i16(self.height.get())?;
 
         Ok(())
     }
@@ -245,7 +246,7 @@ impl PduBufferParsing<'_> for CodecVersion {
             return Err(RfxError::InvalidCodecId(id));
         }
 
-        let version = buffer.read_u16::<LittleEndian>()?;
+        let version = buffer.read_u16()?;
         if version != CODEC_VERSION {
             Err(RfxError::InvalidCodecVersion(version))
         } else {
@@ -255,7 +256,7 @@ impl PduBufferParsing<'_> for CodecVersion {
 
     fn to_buffer_consume(&self, buffer: &mut &mut [u8]) -> Result<(), Self::Error> {
         buffer.write_u8(CODEC_ID)?;
-        buffer.write_u16::<LittleEndian>(CODEC_VERSION)?;
+        buffer.write_u16(CODEC_VERSION)?;
 
         Ok(())
     }
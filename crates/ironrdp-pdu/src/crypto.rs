@@ -1,2 +1,3 @@
+pub(crate) mod hmac_md5;
 pub(crate) mod rc4;
 pub(crate) mod rsa;
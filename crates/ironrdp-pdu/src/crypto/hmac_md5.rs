@@ -0,0 +1,40 @@
+use md5::{Digest as _, Md5};
+
+const BLOCK_SIZE: usize = 64;
+const IPAD: u8 = 0x36;
+const OPAD: u8 = 0x5c;
+
+/// HMAC-MD5 ([RFC 2104](https://www.rfc-editor.org/rfc/rfc2104))
+pub(crate) fn hmac_md5(key: &[u8], data: &[u8]) -> [u8; 16] {
+    let mut block_key = [0u8; BLOCK_SIZE];
+
+    if key.len() > BLOCK_SIZE {
+        block_key[..16].copy_from_slice(&Md5::digest(key));
+    } else {
+        block_key[..key.len()].copy_from_slice(key);
+    }
+
+    let inner_key = block_key.map(|byte| byte ^ IPAD);
+    let outer_key = block_key.map(|byte| byte ^ OPAD);
+
+    let inner = Md5::new().chain_update(inner_key).chain_update(data).finalize();
+
+    Md5::new().chain_update(outer_key).chain_update(inner).finalize().into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rfc2104_test_vectors() {
+        assert_eq!(
+            hmac_md5(&[0x0b; 16], b"Hi There"),
+            [0x92, 0x94, 0x72, 0x7a, 0x36, 0x38, 0xbb, 0x1c, 0x13, 0xf4, 0x8e, 0xf8, 0x15, 0x8b, 0xfc, 0x9d]
+        );
+        assert_eq!(
+            hmac_md5(b"Jefe", b"what do ya want for nothing?"),
+            [0x75, 0x0c, 0x78, 0x3e, 0x6a, 0xb0, 0xb5, 0x03, 0xea, 0xa8, 0x6e, 0x31, 0x0a, 0x5d, 0xb7, 0x38]
+        );
+    }
+}
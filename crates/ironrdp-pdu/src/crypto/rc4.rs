@@ -1,4 +1,5 @@
-use std::{fmt, ops};
+use alloc::vec::Vec;
+use core::{fmt, ops};
 
 #[derive(Debug, Clone)]
 pub(crate) struct Rc4 {
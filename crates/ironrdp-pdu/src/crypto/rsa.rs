@@ -1,42 +1,36 @@
-use std::io;
+use alloc::borrow::ToOwned;
+use alloc::format;
+use alloc::vec::Vec;
 
 use der_parser::parse_der;
 use num_bigint::BigUint;
 
-pub(crate) fn encrypt_with_public_key(message: &[u8], public_key_der: &[u8]) -> io::Result<Vec<u8>> {
-    let (_, der_object) = parse_der(public_key_der).map_err(|err| {
-        io::Error::new(
-            io::ErrorKind::InvalidData,
-            format!("unable to parse public key from DER: {err:?}"),
-        )
-    })?;
+use crate::rdp::server_license::ServerLicenseError;
+
+pub(crate) fn encrypt_with_public_key(message: &[u8], public_key_der: &[u8]) -> Result<Vec<u8>, ServerLicenseError> {
+    let (_, der_object) = parse_der(public_key_der)
+        .map_err(|err| ServerLicenseError::InvalidPublicKey(format!("unable to parse public key from DER: {err:?}")))?;
 
     let der_object_sequence = der_object.as_sequence().map_err(|err| {
-        io::Error::new(
-            io::ErrorKind::InvalidData,
-            format!("unable to extract a sequence from the DER object: {err:?}"),
-        )
+        ServerLicenseError::InvalidPublicKey(format!("unable to extract a sequence from the DER object: {err:?}"))
     })?;
 
     if der_object_sequence.len() != 2 {
-        return Err(io::Error::new(
-            io::ErrorKind::InvalidData,
-            "DER object sequence is empty",
+        return Err(ServerLicenseError::InvalidPublicKey(
+            "DER object sequence is empty".to_owned(),
         ));
     }
 
     let n = der_object_sequence[0].as_slice().map_err(|err| {
-        io::Error::new(
-            io::ErrorKind::InvalidData,
-            format!("unable to extract a slice from public key modulus sequence: {err:?}"),
-        )
+        ServerLicenseError::InvalidPublicKey(format!(
+            "unable to extract a slice from public key modulus sequence: {err:?}"
+        ))
     })?;
 
     let e = der_object_sequence[1].as_slice().map_err(|err| {
-        io::Error::new(
-            io::ErrorKind::InvalidData,
-            format!("unable to extract a slice from public key exponent sequence: {err:?}"),
-        )
+        ServerLicenseError::InvalidPublicKey(format!(
+            "unable to extract a slice from public key exponent sequence: {err:?}"
+        ))
     })?;
 
     let n = BigUint::from_bytes_be(n);
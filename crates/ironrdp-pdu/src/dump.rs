@@ -0,0 +1,463 @@
+//! Human-readable dumps of PDUs, for debugging interoperability issues
+//!
+//! PDUs are rendered as a tree of labels, in the spirit of the packet details pane of Wireshark:
+//!
+//! ```text
+//! X.224 frame (11 bytes)
+//!     TPKT header
+//!         version: 3
+//!         length: 11
+//!     X.224 TPDU header
+//!         length indicator: 2
+//!         code: Data (0xF0)
+//!     MCS AttachUserConfirm (4 bytes)
+//!         AttachUserConfirm
+//!             AttachUserConfirm
+//!                 result: 0
+//!                 initiator_id: 1007
+//! ```
+//!
+//! Decoded PDUs are rendered from their `Debug` representation, while the parts which can't be decoded are
+//! hex-dumped along with the decoding error.
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::{self, Write as _};
+
+use ironrdp_core::{decode, Decode, DecodeError, Encode, ReadCursor};
+
+use crate::fast_path::{EncryptionFlags, FastPathHeader, FastPathUpdate, FastPathUpdatePdu, Fragmentation};
+use crate::input::fast_path::FastPathInput;
+use crate::mcs::{ConnectInitial, ConnectResponse, McsMessage, McsPdu as _};
+use crate::nego::{ConnectionConfirm, ConnectionRequest};
+use crate::tpdu::{TpduCode, TpduHeader};
+use crate::tpkt::TpktHeader;
+use crate::x224::{X224Pdu, X224};
+use crate::Action;
+
+/// Number of bytes on each line of a hex dump
+const HEX_DUMP_LINE_SIZE: usize = 16;
+
+/// Indentation of each level of the tree
+const INDENT: &str = "    ";
+
+/// Direction of a frame, needed to tell apart PDUs sharing the same framing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    ClientToServer,
+    ServerToClient,
+}
+
+/// Node of a dump tree
+///
+/// The `Display` implementation renders the whole tree, one node per line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DumpNode {
+    pub label: String,
+    pub children: Vec<DumpNode>,
+}
+
+impl DumpNode {
+    pub fn new(label: impl Into<String>) -> Self {
+        Self {
+            label: label.into(),
+            children: Vec::new(),
+        }
+    }
+
+    /// Leaf rendered as `name: value`
+    pub fn field(name: &str, value: impl fmt::Display) -> Self {
+        Self::new(format!("{name}: {value}"))
+    }
+
+    /// Node whose children are the lines of the hex dump of `data`
+    pub fn hex(label: &str, data: &[u8]) -> Self {
+        let mut node = Self::new(format!("{label} ({} bytes)", data.len()));
+        node.children = hex_dump_lines(data).map(Self::new).collect();
+        node
+    }
+
+    /// Node holding an error and the hex dump of the data which couldn't be decoded
+    pub fn undecodable(label: &str, error: &DecodeError, data: &[u8]) -> Self {
+        Self::new(format!("{label} (undecodable)"))
+            .with_child(Self::field("error", error))
+            .with_child(Self::hex("data", data))
+    }
+
+    /// Builds a tree from the pretty-printed `Debug` representation of a value
+    ///
+    /// Byte sequences are hex-dumped instead of being listed one element per line.
+    pub fn from_debug(value: &impl fmt::Debug) -> Self {
+        let debug = format!("{value:#?}");
+
+        // The stack holds the nodes being built, the bottom one being the root.
+        let mut stack = Vec::new();
+        let mut root = None;
+
+        for line in debug.lines() {
+            let content = line.trim_start();
+            let content = content.strip_suffix(',').unwrap_or(content);
+
+            if matches!(content, "}" | ")" | "]") {
+                let mut node = collapse_bytes(stack.pop().expect("a node was opened"));
+
+                // Render single-field tuples (e.g. `Some(5)` or bit flags) on a single line.
+                if content == ")" && node.children.len() == 1 && node.children[0].children.is_empty() {
+                    let field = node.children.remove(0);
+                    node.label = format!("{}({})", node.label, field.label);
+                }
+
+                match stack.last_mut() {
+                    // Unnamed lists and tuples are merged into their parent.
+                    Some(parent) if node.label.is_empty() => parent.children.append(&mut node.children),
+                    _ => attach(&mut stack, &mut root, node),
+                }
+            } else if let Some(label) = content
+                .strip_suffix(" {")
+                .or_else(|| content.strip_suffix('('))
+                .or_else(|| content.strip_suffix('['))
+            {
+                let label = label.trim_end();
+                stack.push(Self::new(label.strip_suffix(':').unwrap_or(label)));
+            } else {
+                attach(&mut stack, &mut root, Self::new(content));
+            }
+        }
+
+        root.unwrap_or_else(|| Self::new(debug))
+    }
+
+    /// Builds a tree from a decoded PDU, labelled with its name and size
+    pub fn from_pdu<T>(pdu: &T) -> Self
+    where
+        T: Encode + fmt::Debug,
+    {
+        let mut node = Self::new(format!("{} ({} bytes)", pdu.name(), pdu.size()));
+        node.push(Self::from_debug(pdu));
+        node
+    }
+
+    pub fn push(&mut self, child: DumpNode) {
+        self.children.push(child);
+    }
+
+    #[must_use]
+    pub fn with_child(mut self, child: DumpNode) -> Self {
+        self.push(child);
+        self
+    }
+
+    fn write(&self, f: &mut fmt::Formatter<'_>, depth: usize) -> fmt::Result {
+        for _ in 0..depth {
+            f.write_str(INDENT)?;
+        }
+        writeln!(f, "{}", self.label)?;
+
+        self.children.iter().try_for_each(|child| child.write(f, depth + 1))
+    }
+}
+
+impl fmt::Display for DumpNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write(f, 0)
+    }
+}
+
+fn attach(stack: &mut [DumpNode], root: &mut Option<DumpNode>, node: DumpNode) {
+    match stack.last_mut() {
+        Some(parent) => parent.push(node),
+        None => *root = Some(node),
+    }
+}
+
+/// Replaces the elements of a list of bytes by their hex dump
+fn collapse_bytes(node: DumpNode) -> DumpNode {
+    let bytes = node
+        .children
+        .iter()
+        .map(|child| {
+            if child.children.is_empty() {
+                child.label.parse::<u8>().ok()
+            } else {
+                None
+            }
+        })
+        .collect::<Option<Vec<u8>>>();
+
+    match bytes {
+        Some(bytes) if !bytes.is_empty() => DumpNode::hex(&node.label, &bytes),
+        _ => node,
+    }
+}
+
+/// Renders `data` like `hexdump -C` does: offset, 16 bytes in hexadecimal and their ASCII representation
+///
+/// ```text
+/// 0000  03 00 00 0c 02 f0 80 2e  00 00 03 ef              ............
+/// ```
+pub fn hex_dump(data: &[u8]) -> String {
+    let mut dump = String::new();
+
+    for line in hex_dump_lines(data) {
+        dump.push_str(&line);
+        dump.push('\n');
+    }
+
+    dump
+}
+
+fn hex_dump_lines(data: &[u8]) -> impl Iterator<Item = String> + '_ {
+    data.chunks(HEX_DUMP_LINE_SIZE).enumerate().map(|(idx, chunk)| {
+        let mut line = format!("{:04x} ", idx * HEX_DUMP_LINE_SIZE);
+
+        for column in 0..HEX_DUMP_LINE_SIZE {
+            if column == HEX_DUMP_LINE_SIZE / 2 {
+                line.push(' ');
+            }
+
+            match chunk.get(column) {
+                Some(byte) => write!(line, " {byte:02x}").expect("writing to a String can't fail"),
+                None => line.push_str("   "),
+            }
+        }
+
+        line.push_str("  ");
+        line.extend(chunk.iter().map(|&byte| {
+            if byte.is_ascii_graphic() || byte == b' ' {
+                char::from(byte)
+            } else {
+                '.'
+            }
+        }));
+
+        line
+    })
+}
+
+/// Dumps a whole frame, as found on the wire after the security layer: either a fast-path PDU or a TPKT-framed
+/// X.224 PDU
+///
+/// Bytes following the frame are dumped as trailing data.
+pub fn dump_frame(frame: &[u8], direction: Direction) -> DumpNode {
+    let Some(&first_byte) = frame.first() else {
+        return DumpNode::new("Empty frame");
+    };
+
+    let (mut node, frame_length) = match Action::from_fp_output_header(first_byte) {
+        Ok(Action::X224) => dump_x224_frame(frame, direction),
+        Ok(Action::FastPath) => match direction {
+            Direction::ClientToServer => dump_fast_path_input(frame),
+            Direction::ServerToClient => dump_fast_path_output(frame),
+        },
+        Err(action) => {
+            let node =
+                DumpNode::new(format!("Unknown frame (action {action:#04x})")).with_child(DumpNode::hex("data", frame));
+            (node, frame.len())
+        }
+    };
+
+    if let Some(trailing) = frame.get(frame_length..).filter(|trailing| !trailing.is_empty()) {
+        node.push(DumpNode::hex("Trailing data", trailing));
+    }
+
+    node
+}
+
+/// Returns the node and the length of the frame
+fn dump_x224_frame(frame: &[u8], direction: Direction) -> (DumpNode, usize) {
+    let mut src = ReadCursor::new(frame);
+
+    let tpkt = match TpktHeader::read(&mut src) {
+        Ok(tpkt) => tpkt,
+        Err(e) => return (DumpNode::undecodable("X.224 frame", &e, frame), frame.len()),
+    };
+
+    let frame_length = tpkt.packet_length().min(frame.len());
+    let frame = &frame[..frame_length];
+
+    let mut node = DumpNode::new(format!("X.224 frame ({} bytes)", tpkt.packet_length())).with_child(
+        DumpNode::new("TPKT header")
+            .with_child(DumpNode::field("version", TpktHeader::VERSION))
+            .with_child(DumpNode::field("length", tpkt.packet_length)),
+    );
+
+    if tpkt.packet_length() > frame.len() {
+        node.push(DumpNode::new(format!(
+            "Truncated frame: {} bytes are missing",
+            tpkt.packet_length() - frame.len()
+        )));
+    }
+
+    let tpdu = match TpduHeader::read(&mut src, &tpkt) {
+        Ok(tpdu) => tpdu,
+        Err(e) => {
+            node.push(DumpNode::undecodable("X.224 TPDU", &e, &frame[TpktHeader::SIZE..]));
+            return (node, frame_length);
+        }
+    };
+
+    node.push(
+        DumpNode::new("X.224 TPDU header")
+            .with_child(DumpNode::field("length indicator", tpdu.li))
+            .with_child(DumpNode::field("code", tpdu_code_name(tpdu.code))),
+    );
+
+    let body = match tpdu.code {
+        TpduCode::CONNECTION_REQUEST => dump_x224_pdu::<ConnectionRequest>(frame),
+        TpduCode::CONNECTION_CONFIRM => dump_x224_pdu::<ConnectionConfirm>(frame),
+        TpduCode::DATA => dump_x224_data(frame, TpktHeader::SIZE + tpdu.size(), direction),
+        _ => DumpNode::hex(
+            "X.224 user data",
+            frame.get(TpktHeader::SIZE + tpdu.size()..).unwrap_or_default(),
+        ),
+    };
+    node.push(body);
+
+    (node, frame_length)
+}
+
+fn dump_x224_pdu<'de, T>(frame: &'de [u8]) -> DumpNode
+where
+    T: X224Pdu<'de> + fmt::Debug,
+{
+    match decode::<X224<T>>(frame) {
+        Ok(pdu) => x224_pdu_node(T::X224_NAME, &pdu.0),
+        Err(e) => DumpNode::undecodable(T::X224_NAME, &e, frame),
+    }
+}
+
+fn x224_pdu_node<'de, T>(name: &str, pdu: &T) -> DumpNode
+where
+    T: X224Pdu<'de> + fmt::Debug,
+{
+    DumpNode::new(format!("{name} ({} bytes)", pdu.tpdu_user_data_size())).with_child(DumpNode::from_debug(pdu))
+}
+
+fn dump_x224_data(frame: &[u8], user_data_offset: usize, direction: Direction) -> DumpNode {
+    let user_data = frame.get(user_data_offset..).unwrap_or_default();
+
+    let mcs_error = match decode::<X224<McsMessage<'_>>>(frame) {
+        Ok(pdu) => return x224_pdu_node(&format!("MCS {}", pdu.0.name()), &pdu.0),
+        Err(e) => e,
+    };
+
+    // The MCS Connect Initial and Connect Response PDUs are BER-encoded, and not part of the DomainMCSPDU CHOICE.
+    let connect_pdu = match direction {
+        Direction::ClientToServer => try_dump::<ConnectInitial>(user_data),
+        Direction::ServerToClient => try_dump::<ConnectResponse>(user_data),
+    };
+
+    connect_pdu.unwrap_or_else(|| DumpNode::undecodable("MCS PDU", &mcs_error, user_data))
+}
+
+fn try_dump<'de, T>(data: &'de [u8]) -> Option<DumpNode>
+where
+    T: Decode<'de> + Encode + fmt::Debug,
+{
+    decode::<T>(data).ok().map(|pdu| DumpNode::from_pdu(&pdu))
+}
+
+fn dump_fast_path_input(frame: &[u8]) -> (DumpNode, usize) {
+    let mut src = ReadCursor::new(frame);
+
+    match FastPathInput::decode(&mut src) {
+        Ok(pdu) => {
+            let node = DumpNode::new(format!("Fast-path input frame ({} bytes)", src.pos()))
+                .with_child(DumpNode::from_pdu(&pdu));
+            (node, src.pos())
+        }
+        Err(e) => (DumpNode::undecodable("Fast-path input frame", &e, frame), frame.len()),
+    }
+}
+
+fn dump_fast_path_output(frame: &[u8]) -> (DumpNode, usize) {
+    let mut src = ReadCursor::new(frame);
+
+    let header = match FastPathHeader::decode(&mut src) {
+        Ok(header) => header,
+        Err(e) => return (DumpNode::undecodable("Fast-path output frame", &e, frame), frame.len()),
+    };
+
+    let frame_length = (src.pos() + header.data_length).min(frame.len());
+    let data = &frame[src.pos()..frame_length];
+
+    let mut node = DumpNode::new(format!(
+        "Fast-path output frame ({} bytes)",
+        src.pos() + header.data_length
+    ))
+    .with_child(
+        DumpNode::new("Fast-path header")
+            .with_child(DumpNode::field("flags", format_args!("{:?}", header.flags)))
+            .with_child(DumpNode::field("length", src.pos() + header.data_length)),
+    );
+
+    if header.flags.contains(EncryptionFlags::ENCRYPTED) {
+        node.push(DumpNode::hex("Encrypted data", data));
+        return (node, frame_length);
+    }
+
+    let mut updates = ReadCursor::new(data);
+    while !updates.is_empty() {
+        let start = updates.pos();
+
+        match FastPathUpdatePdu::decode(&mut updates) {
+            Ok(update) => node.push(dump_fast_path_update(&update)),
+            Err(e) => {
+                node.push(DumpNode::undecodable("Fast-path update", &e, &data[start..]));
+                break;
+            }
+        }
+    }
+
+    (node, frame_length)
+}
+
+fn dump_fast_path_update(update: &FastPathUpdatePdu<'_>) -> DumpNode {
+    let mut node = DumpNode::new(format!("Fast-path update ({} bytes)", update.size()))
+        .with_child(DumpNode::field("update code", format_args!("{:?}", update.update_code)))
+        .with_child(DumpNode::field(
+            "fragmentation",
+            format_args!("{:?}", update.fragmentation),
+        ));
+
+    if let Some(compression_flags) = update.compression_flags {
+        node.push(DumpNode::field(
+            "compression flags",
+            format_args!("{compression_flags:?}"),
+        ));
+    }
+
+    if let Some(compression_type) = update.compression_type {
+        node.push(DumpNode::field(
+            "compression type",
+            format_args!("{compression_type:?}"),
+        ));
+    }
+
+    // Only complete and uncompressed updates can be decoded on their own.
+    if update.fragmentation != Fragmentation::Single || update.compression_flags.is_some() {
+        node.push(DumpNode::hex("data", update.data));
+        return node;
+    }
+
+    match FastPathUpdate::decode_with_code(update.data, update.update_code) {
+        Ok(decoded) => node.push(DumpNode::from_pdu(&decoded)),
+        Err(e) => node.push(DumpNode::undecodable("data", &e, update.data)),
+    }
+
+    node
+}
+
+fn tpdu_code_name(code: TpduCode) -> String {
+    let name = match code {
+        TpduCode::CONNECTION_REQUEST => "Connection Request",
+        TpduCode::CONNECTION_CONFIRM => "Connection Confirm",
+        TpduCode::DISCONNECT_REQUEST => "Disconnect Request",
+        TpduCode::DATA => "Data",
+        TpduCode::ERROR => "Error",
+        _ => "Unknown",
+    };
+
+    format!("{name} ({:#04X})", u8::from(code))
+}
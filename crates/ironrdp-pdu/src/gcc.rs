@@ -1,9 +1,13 @@
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
 use std::io;
 
 use num_derive::{FromPrimitive, ToPrimitive};
 use num_traits::{FromPrimitive, ToPrimitive};
+#[cfg(feature = "std")]
 use thiserror::Error;
 
+#[cfg(feature = "std")]
 use crate::PduError;
 use ironrdp_core::{
     cast_length, ensure_fixed_part_size, ensure_size, invalid_field_err, DecodeErrorKind, DecodeResult, EncodeResult,
@@ -22,26 +26,30 @@ mod multi_transport_channel_data;
 mod network_data;
 mod security_data;
 
-pub use self::cluster_data::{ClientClusterData, ClusterDataError, RedirectionFlags, RedirectionVersion};
+#[cfg(feature = "std")]
+pub use self::cluster_data::ClusterDataError;
+pub use self::cluster_data::{ClientClusterData, RedirectionFlags, RedirectionVersion};
 pub use self::conference_create::{ConferenceCreateRequest, ConferenceCreateResponse};
 pub use self::core_data::client::{
     ClientColorDepth, ClientCoreData, ClientCoreOptionalData, ClientEarlyCapabilityFlags, ColorDepth, ConnectionType,
     HighColorDepth, KeyboardType, SecureAccessSequence, SupportedColorDepths, IME_FILE_NAME_SIZE,
 };
 pub use self::core_data::server::{ServerCoreData, ServerCoreOptionalData, ServerEarlyCapabilityFlags};
-pub use self::core_data::{CoreDataError, RdpVersion};
+#[cfg(feature = "std")]
+pub use self::core_data::CoreDataError;
+pub use self::core_data::RdpVersion;
 pub use self::message_channel_data::{ClientMessageChannelData, ServerMessageChannelData};
 pub use self::monitor_data::{
     ClientMonitorData, Monitor, MonitorFlags, MONITOR_COUNT_SIZE, MONITOR_FLAGS_SIZE, MONITOR_SIZE,
 };
 pub use self::monitor_extended_data::{ClientMonitorExtendedData, ExtendedMonitorInfo, MonitorOrientation};
 pub use self::multi_transport_channel_data::{MultiTransportChannelData, MultiTransportFlags};
-pub use self::network_data::{
-    ChannelDef, ChannelName, ChannelOptions, ClientNetworkData, NetworkDataError, ServerNetworkData,
-};
-pub use self::security_data::{
-    ClientSecurityData, EncryptionLevel, EncryptionMethod, SecurityDataError, ServerSecurityData,
-};
+#[cfg(feature = "std")]
+pub use self::network_data::NetworkDataError;
+pub use self::network_data::{ChannelDef, ChannelName, ChannelOptions, ClientNetworkData, ServerNetworkData};
+#[cfg(feature = "std")]
+pub use self::security_data::SecurityDataError;
+pub use self::security_data::{ClientSecurityData, EncryptionLevel, EncryptionMethod, ServerSecurityData};
 
 macro_rules! user_header_try {
     ($e:expr) => {
@@ -329,6 +337,7 @@ impl UserDataHeader {
     }
 }
 
+#[cfg(feature = "std")]
 #[derive(Debug, Error)]
 pub enum GccError {
     #[error("IO error")]
@@ -355,6 +364,7 @@ pub enum GccError {
     Pdu(PduError),
 }
 
+#[cfg(feature = "std")]
 impl From<PduError> for GccError {
     fn from(e: PduError) -> Self {
         Self::Pdu(e)
@@ -1,8 +1,10 @@
+#[cfg(feature = "std")]
 use std::io;
 
 use bitflags::bitflags;
 use num_derive::{FromPrimitive, ToPrimitive};
 use num_traits::{FromPrimitive as _, ToPrimitive as _};
+#[cfg(feature = "std")]
 use thiserror::Error;
 
 use ironrdp_core::{ensure_fixed_part_size, invalid_field_err, ReadCursor, WriteCursor};
@@ -87,6 +89,7 @@ pub enum RedirectionVersion {
     V6 = 5,
 }
 
+#[cfg(feature = "std")]
 #[derive(Debug, Error)]
 pub enum ClusterDataError {
     #[error("IO error")]
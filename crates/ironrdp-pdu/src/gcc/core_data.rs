@@ -1,10 +1,13 @@
 pub(crate) mod client;
 pub(crate) mod server;
 
+#[cfg(feature = "std")]
 use std::io;
 
+#[cfg(feature = "std")]
 use thiserror::Error;
 
+#[cfg(feature = "std")]
 use crate::PduError;
 
 const VERSION_SIZE: usize = 4;
@@ -43,6 +46,7 @@ impl RdpVersion {
     pub const V10_12: Self = Self(0x0008_0011);
 }
 
+#[cfg(feature = "std")]
 #[derive(Debug, Error)]
 pub enum CoreDataError {
     #[error("IO error")]
@@ -71,6 +75,7 @@ pub enum CoreDataError {
     Pdu(PduError),
 }
 
+#[cfg(feature = "std")]
 impl From<PduError> for CoreDataError {
     fn from(e: PduError) -> Self {
         Self::Pdu(e)
@@ -1,3 +1,5 @@
+use alloc::string::String;
+
 use bitflags::bitflags;
 use num_derive::{FromPrimitive, ToPrimitive};
 use num_traits::{FromPrimitive, ToPrimitive};
@@ -530,6 +532,7 @@ pub enum SecureAccessSequence {
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, FromPrimitive, ToPrimitive)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum KeyboardType {
     IbmPcXt = 1,
     OlivettiIco = 2,
@@ -1,5 +1,7 @@
+use alloc::vec::Vec;
+
 use bitflags::bitflags;
-use ironrdp_core::{cast_length, ensure_fixed_part_size, invalid_field_err, ReadCursor, WriteCursor};
+use ironrdp_core::{cast_length, decode_limits, ensure_fixed_part_size, invalid_field_err, ReadCursor, WriteCursor};
 
 use ironrdp_core::{Decode, DecodeResult, Encode, EncodeResult};
 
@@ -54,6 +56,13 @@ impl<'de> Decode<'de> for ClientMonitorData {
             return Err(invalid_field_err!("nMonitors", "too many monitors"));
         }
 
+        if monitor_count as usize > decode_limits().max_monitor_count {
+            return Err(invalid_field_err!(
+                "nMonitors",
+                "monitor count exceeds the decode limit"
+            ));
+        }
+
         let mut monitors = Vec::with_capacity(monitor_count as usize);
         for _ in 0..monitor_count {
             monitors.push(Monitor::decode(src)?);
@@ -64,6 +73,7 @@ impl<'de> Decode<'de> for ClientMonitorData {
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Monitor {
     pub left: i32,
     pub top: i32,
@@ -123,6 +133,7 @@ impl<'de> Decode<'de> for Monitor {
 
 bitflags! {
     #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct MonitorFlags: u32 {
         const PRIMARY = 1;
     }
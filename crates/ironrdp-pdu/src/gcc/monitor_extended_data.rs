@@ -1,4 +1,8 @@
-use ironrdp_core::{cast_length, ensure_fixed_part_size, ensure_size, invalid_field_err, ReadCursor, WriteCursor};
+use alloc::vec::Vec;
+
+use ironrdp_core::{
+    cast_length, decode_limits, ensure_fixed_part_size, ensure_size, invalid_field_err, ReadCursor, WriteCursor,
+};
 use num_derive::{FromPrimitive, ToPrimitive};
 use num_traits::{FromPrimitive, ToPrimitive};
 
@@ -64,6 +68,13 @@ impl<'de> Decode<'de> for ClientMonitorExtendedData {
             return Err(invalid_field_err!("monitorCount", "invalid monitor count"));
         }
 
+        if monitor_count > decode_limits().max_monitor_count {
+            return Err(invalid_field_err!(
+                "monitorCount",
+                "monitor count exceeds the decode limit"
+            ));
+        }
+
         let mut extended_monitors_info = Vec::with_capacity(monitor_count);
         for _ in 0..monitor_count {
             extended_monitors_info.push(ExtendedMonitorInfo::decode(src)?);
@@ -74,6 +85,7 @@ impl<'de> Decode<'de> for ClientMonitorExtendedData {
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ExtendedMonitorInfo {
     pub physical_width: u32,
     pub physical_height: u32,
@@ -86,6 +98,50 @@ impl ExtendedMonitorInfo {
     const NAME: &'static str = "ExtendedMonitorInfo";
 
     const FIXED_PART_SIZE: usize = MONITOR_SIZE;
+
+    /// DPI corresponding to a 100% scale factor
+    pub const DEFAULT_DPI: u32 = 96;
+
+    /// Creates the attributes of a monitor displayed at the given DPI
+    ///
+    /// The DPI is converted into a desktop scale factor, and the device scale factor is rounded to the closest
+    /// value accepted by the server (100, 140 or 180%).
+    pub fn from_dpi(physical_width: u32, physical_height: u32, orientation: MonitorOrientation, dpi: u32) -> Self {
+        let desktop_scale_factor = (dpi.saturating_mul(100) / Self::DEFAULT_DPI).clamp(100, 500);
+
+        let device_scale_factor = match desktop_scale_factor {
+            ..=119 => 100,
+            120..=159 => 140,
+            _ => 180,
+        };
+
+        Self {
+            physical_width,
+            physical_height,
+            orientation,
+            desktop_scale_factor,
+            device_scale_factor,
+        }
+    }
+
+    /// Returns the DPI corresponding to the desktop scale factor
+    ///
+    /// Returns `None` if the scale factor is outside the 100 to 500% range, as it is then ignored by the server.
+    pub fn dpi(&self) -> Option<u32> {
+        (100..=500)
+            .contains(&self.desktop_scale_factor)
+            .then(|| self.desktop_scale_factor * Self::DEFAULT_DPI / 100)
+    }
+
+    /// Returns the physical width and height of the monitor, in millimeters
+    ///
+    /// Returns `None` if any dimension is outside the 10 to 10,000 mm range, as they are then ignored by the server.
+    pub fn physical_dimensions(&self) -> Option<(u32, u32)> {
+        let valid = |dimension| (10..=10_000).contains(&dimension);
+
+        (valid(self.physical_width) && valid(self.physical_height))
+            .then_some((self.physical_width, self.physical_height))
+    }
 }
 
 impl Encode for ExtendedMonitorInfo {
@@ -132,6 +188,7 @@ impl<'de> Decode<'de> for ExtendedMonitorInfo {
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, FromPrimitive, ToPrimitive)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum MonitorOrientation {
     Landscape = 0,
     Portrait = 90,
@@ -1,11 +1,17 @@
-use ironrdp_core::{cast_length, ensure_fixed_part_size, ensure_size, invalid_field_err, ReadCursor, WriteCursor};
-use std::borrow::Cow;
-use std::{io, str};
+use alloc::borrow::Cow;
+use alloc::vec::Vec;
+use core::str;
+#[cfg(feature = "std")]
+use std::io;
 
 use bitflags::bitflags;
 use num_integer::Integer;
+#[cfg(feature = "std")]
 use thiserror::Error;
 
+use ironrdp_core::{
+    cast_length, decode_limits, ensure_fixed_part_size, ensure_size, invalid_field_err, ReadCursor, WriteCursor,
+};
 use ironrdp_core::{Decode, DecodeResult, Encode, EncodeResult};
 
 const CHANNELS_MAX: usize = 31;
@@ -136,6 +142,13 @@ impl<'de> Decode<'de> for ClientNetworkData {
             return Err(invalid_field_err!("channelCount", "invalid channel count"));
         }
 
+        if channel_count > decode_limits().max_channel_count {
+            return Err(invalid_field_err!(
+                "channelCount",
+                "channel count exceeds the decode limit"
+            ));
+        }
+
         let mut channels = Vec::with_capacity(channel_count);
         for _ in 0..channel_count {
             channels.push(ChannelDef::decode(src)?);
@@ -202,6 +215,13 @@ impl<'de> Decode<'de> for ServerNetworkData {
         let io_channel = src.read_u16();
         let channel_count = cast_length!("channelCount", src.read_u16())?;
 
+        if channel_count > decode_limits().max_channel_count {
+            return Err(invalid_field_err!(
+                "channelCount",
+                "channel count exceeds the decode limit"
+            ));
+        }
+
         ensure_size!(in: src, size: channel_count * 2);
         let mut channel_ids = Vec::with_capacity(channel_count);
         for _ in 0..channel_count {
@@ -284,6 +304,7 @@ bitflags! {
     }
 }
 
+#[cfg(feature = "std")]
 #[derive(Debug, Error)]
 pub enum NetworkDataError {
     #[error("IO error")]
@@ -1,8 +1,11 @@
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
 use std::io;
 
 use bitflags::bitflags;
 use num_derive::{FromPrimitive, ToPrimitive};
 use num_traits::{FromPrimitive, ToPrimitive};
+#[cfg(feature = "std")]
 use thiserror::Error;
 
 use ironrdp_core::{cast_length, ensure_fixed_part_size, ensure_size, invalid_field_err, ReadCursor, WriteCursor};
@@ -205,6 +208,7 @@ pub enum EncryptionLevel {
     Fips = 4,
 }
 
+#[cfg(feature = "std")]
 #[derive(Debug, Error)]
 pub enum SecurityDataError {
     #[error("IO error")]
@@ -1,3 +1,5 @@
+use alloc::vec::Vec;
+
 use bit_field::BitField;
 use bitflags::bitflags;
 use num_derive::{FromPrimitive, ToPrimitive};
@@ -51,9 +53,9 @@ impl Encode for FastPathInputHeader {
 
     fn size(&self) -> usize {
         let num_events_length = if self.num_events < 16 { 0 } else { 1 };
-        Self::FIXED_PART_SIZE
-            + per::sizeof_length(self.data_length as u16 + num_events_length as u16 + 1)
-            + num_events_length
+        // The length field counts itself, assume the short form to find out whether it fits in it.
+        let short_form_length = Self::FIXED_PART_SIZE + 1 + num_events_length + self.data_length;
+        Self::FIXED_PART_SIZE + per::sizeof_length(short_form_length as u16) + num_events_length
     }
 }
 
@@ -147,6 +149,9 @@ impl Encode for FastPathInputEvent {
             FastPathInputEvent::MouseEventEx(pdu) => {
                 pdu.encode(dst)?;
             }
+            FastPathInputEvent::MouseEventRel(pdu) => {
+                pdu.encode(dst)?;
+            }
             FastPathInputEvent::QoeEvent(stamp) => {
                 dst.write_u32(*stamp);
             }
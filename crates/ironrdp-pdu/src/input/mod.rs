@@ -1,7 +1,10 @@
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
 use std::io;
 
 use num_derive::{FromPrimitive, ToPrimitive};
 use num_traits::{FromPrimitive, ToPrimitive};
+#[cfg(feature = "std")]
 use thiserror::Error;
 
 use ironrdp_core::{ensure_fixed_part_size, ensure_size, invalid_field_err, ReadCursor, WriteCursor};
@@ -61,7 +64,7 @@ impl<'de> Decode<'de> for InputEventPdu {
         ensure_fixed_part_size!(in: src);
 
         let number_of_events = src.read_u16();
-        read_padding!(src, 2);
+        read_zeroed_padding!(src, 2)?;
 
         let events = (0..number_of_events)
             .map(|_| InputEvent::decode(src))
@@ -171,6 +174,7 @@ impl From<&InputEvent> for InputEventType {
     }
 }
 
+#[cfg(feature = "std")]
 #[derive(Debug, Error)]
 pub enum InputEventError {
     #[error("IO error")]
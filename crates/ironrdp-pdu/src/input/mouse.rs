@@ -57,8 +57,9 @@ impl<'de> Decode<'de> for MousePdu {
 
         let wheel_rotations_bits = flags_raw as u8; // truncate
 
+        // The rotation is a 9-bit two's complement value, whose sign bit is WHEEL_NEGATIVE.
         let number_of_wheel_rotation_units = if flags.contains(PointerFlags::WHEEL_NEGATIVE) {
-            -i16::from(wheel_rotations_bits)
+            i16::from(wheel_rotations_bits) - 0x100
         } else {
             i16::from(wheel_rotations_bits)
         };
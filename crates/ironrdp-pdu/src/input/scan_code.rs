@@ -41,7 +41,7 @@ impl<'de> Decode<'de> for ScanCodePdu {
 
         let flags = KeyboardFlags::from_bits_truncate(src.read_u16());
         let key_code = src.read_u16();
-        read_padding!(src, 2);
+        read_zeroed_padding!(src, 2)?;
 
         Ok(Self { flags, key_code })
     }
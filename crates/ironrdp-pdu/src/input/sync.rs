@@ -37,7 +37,7 @@ impl<'de> Decode<'de> for SyncPdu {
     fn decode(src: &mut ReadCursor<'de>) -> DecodeResult<Self> {
         ensure_fixed_part_size!(in: src);
 
-        read_padding!(src, 2);
+        read_zeroed_padding!(src, 2)?;
         let flags = SyncToggleFlags::from_bits_truncate(src.read_u32());
 
         Ok(Self { flags })
@@ -3,16 +3,22 @@
 #![allow(clippy::cast_possible_truncation)] // FIXME: remove
 #![allow(clippy::cast_possible_wrap)] // FIXME: remove
 #![allow(clippy::cast_sign_loss)] // FIXME: remove
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
 
 use core::fmt;
-use ironrdp_core::{unexpected_message_type_err, DecodeResult, EncodeResult, ReadCursor};
+use ironrdp_core::{
+    decode_limits, invalid_field_err, unexpected_message_type_err, DecodeResult, EncodeResult, ReadCursor,
+};
 
-use ironrdp_error::Source;
+use ironrdp_error::{ErrorCode, Source};
 
 #[macro_use]
 mod macros;
 
 pub mod codecs;
+pub mod dump;
 pub mod gcc;
 pub mod geometry;
 pub mod input;
@@ -63,8 +69,20 @@ impl PduErrorExt for PduError {
     }
 }
 
+#[cfg(feature = "std")]
 impl std::error::Error for PduErrorKind {}
 
+/// PDU error codes are in the `0x100` range.
+impl ErrorCode for PduErrorKind {
+    fn code(&self) -> u32 {
+        match self {
+            Self::Encode => 0x101,
+            Self::Decode => 0x102,
+            Self::Other { .. } => 0x1FF,
+        }
+    }
+}
+
 impl fmt::Display for PduErrorKind {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -115,6 +133,8 @@ pub struct PduInfo {
 }
 
 /// Finds next RDP PDU size by reading the next few bytes.
+///
+/// Fails if the PDU is larger than the maximum PDU size of the current [`DecodeLimits`](ironrdp_core::DecodeLimits).
 pub fn find_size(bytes: &[u8]) -> DecodeResult<Option<PduInfo>> {
     macro_rules! ensure_enough {
         ($bytes:expr, $len:expr) => {
@@ -137,7 +157,7 @@ pub fn find_size(bytes: &[u8]) -> DecodeResult<Option<PduInfo>> {
 
             Ok(Some(PduInfo {
                 action,
-                length: tpkt.packet_length(),
+                length: ensure_pdu_size("TPKT length", tpkt.packet_length())?,
             }))
         }
         Action::FastPath => {
@@ -155,12 +175,24 @@ pub fn find_size(bytes: &[u8]) -> DecodeResult<Option<PduInfo>> {
 
             Ok(Some(PduInfo {
                 action,
-                length: usize::from(fast_path_length),
+                length: ensure_pdu_size("fpOutputLength", usize::from(fast_path_length))?,
             }))
         }
     }
 }
 
+fn ensure_pdu_size(field: &'static str, length: usize) -> DecodeResult<usize> {
+    if length > decode_limits().max_pdu_size {
+        return Err(invalid_field_err!(
+            "find_size",
+            field,
+            "PDU size exceeds the decode limit"
+        ));
+    }
+
+    Ok(length)
+}
+
 pub trait PduHint: Send + Sync + fmt::Debug + 'static {
     /// Finds next PDU size by reading the next few bytes.
     ///
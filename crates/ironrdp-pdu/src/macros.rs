@@ -149,6 +149,16 @@ macro_rules! read_padding {
     };
 }
 
+/// Moves read cursor, checking that padding bytes are zeroed.
+///
+/// See `ironrdp_pdu::padding::read_zeroed`.
+#[macro_export]
+macro_rules! read_zeroed_padding {
+    ($src:expr, $n:expr) => {
+        $crate::padding::read_zeroed(ironrdp_core::function!(), $src, $n)
+    };
+}
+
 // FIXME: legacy macros below
 
 #[macro_export]
@@ -1,10 +1,12 @@
-use std::borrow::Cow;
+use alloc::borrow::Cow;
+use alloc::vec;
+use alloc::vec::Vec;
 
 use crate::gcc::{ChannelDef, ClientGccBlocks, ConferenceCreateRequest, ConferenceCreateResponse};
 use crate::tpdu::{TpduCode, TpduHeader};
 use crate::tpkt::TpktHeader;
 use crate::x224::{user_data_size, X224Pdu};
-use crate::{per, DecodeResult, EncodeResult, PduError};
+use crate::{per, DecodeResult, EncodeResult};
 use ironrdp_core::{
     cast_length, ensure_fixed_part_size, ensure_size, invalid_field_err, other_err, unexpected_message_type_err,
     IntoOwned, ReadCursor, WriteCursor,
@@ -244,7 +246,7 @@ fn read_mcspdu_header(src: &mut ReadCursor<'_>, ctx: &'static str) -> DecodeResu
 }
 
 fn peek_mcspdu_header(src: &mut ReadCursor<'_>, ctx: &'static str) -> DecodeResult<DomainMcsPdu> {
-    let choice = src.try_read_u8().map_err(|e| other_err!(ctx, source: e))?;
+    let choice = src.try_peek_u8().map_err(|e| other_err!(ctx, source: e))?;
 
     DomainMcsPdu::from_choice(choice)
         .ok_or_else(|| invalid_field_err(ctx, "domain-mcspdu", "unexpected application tag for CHOICE"))
@@ -933,17 +935,23 @@ impl DomainParameters {
     }
 }
 
+#[cfg(feature = "std")]
 pub use legacy::McsError;
 
 mod legacy {
+    #[cfg(feature = "std")]
     use std::io;
 
+    #[cfg(feature = "std")]
     use thiserror::Error;
 
     use super::*;
     use crate::ber;
     use crate::gcc::conference_create::{ConferenceCreateRequest, ConferenceCreateResponse};
+    #[cfg(feature = "std")]
     use crate::gcc::GccError;
+    #[cfg(feature = "std")]
+    use crate::PduError;
     use ironrdp_core::{Decode, DecodeResult, Encode, EncodeResult};
 
     // impl<'de> McsPdu<'de> for ConnectInitial {
@@ -1153,6 +1161,7 @@ mod legacy {
         }
     }
 
+    #[cfg(feature = "std")]
     #[derive(Debug, Error)]
     pub enum McsError {
         #[error("IO error")]
@@ -1171,12 +1180,14 @@ mod legacy {
         Pdu(PduError),
     }
 
+    #[cfg(feature = "std")]
     impl From<PduError> for McsError {
         fn from(e: PduError) -> Self {
             Self::Pdu(e)
         }
     }
 
+    #[cfg(feature = "std")]
     impl From<McsError> for io::Error {
         fn from(e: McsError) -> io::Error {
             io::Error::new(io::ErrorKind::Other, format!("MCS Connection Sequence error: {e}"))
@@ -4,7 +4,7 @@
 //! and message recipients should not assume padding has any particular
 //! value.
 
-use ironrdp_core::{ReadCursor, WriteCursor};
+use ironrdp_core::{tolerate_violation, DecodeResult, ReadCursor, WriteCursor};
 
 /// Writes zeroes using as few `write_u*` calls as possible.
 pub fn write(dst: &mut WriteCursor<'_>, mut n: usize) {
@@ -36,3 +36,14 @@ pub fn write(dst: &mut WriteCursor<'_>, mut n: usize) {
 pub fn read(src: &mut ReadCursor<'_>, n: usize) {
     src.advance(n);
 }
+
+/// Moves read cursor, checking that padding bytes are zeroed.
+///
+/// Non-zero padding is tolerated in lenient decoding mode, and rejected in strict decoding mode.
+pub fn read_zeroed(context: &'static str, src: &mut ReadCursor<'_>, n: usize) -> DecodeResult<()> {
+    if src.read_slice(n).iter().any(|&byte| byte != 0) {
+        tolerate_violation(context, "padding", "non-zero padding")?;
+    }
+
+    Ok(())
+}
@@ -1,6 +1,8 @@
 //! This module contains the RDP_PRECONNECTION_PDU_V1 and RDP_PRECONNECTION_PDU_V2 structures.
 
-use crate::Pdu;
+use alloc::string::String;
+
+use crate::{tpkt, Pdu, PduHint, X224_HINT};
 use ironrdp_core::{
     cast_length, ensure_fixed_part_size, ensure_size, invalid_field_err, invalid_field_err_with_source, DecodeResult,
     EncodeResult, ReadCursor, WriteCursor,
@@ -39,6 +41,53 @@ pub struct PreconnectionBlob {
 
 impl PreconnectionBlob {
     pub const FIXED_PART_SIZE: usize = 16;
+
+    /// Largest valid Preconnection PDU: a V2 PDU with the longest possible string
+    pub const MAX_SIZE: usize = Self::FIXED_PART_SIZE + 2 /* cchPCB */ + 0xFFFF * 2 /* wszPCB */;
+}
+
+/// Returns whether the first bytes received from the client are the ones of a Preconnection PDU
+///
+/// A Preconnection PDU starts with its little-endian size, which is only mistaken for a TPKT header (version 3
+/// followed by a reserved zero byte) for PDUs of exactly 0x10003 or 0x20003 bytes.
+/// Returns `None` if there are not enough bytes to decide.
+pub fn is_preconnection_blob(bytes: &[u8]) -> Option<bool> {
+    match bytes {
+        [tpkt::TpktHeader::VERSION, 0, ..] => Some(false),
+        [_, _, ..] => Some(true),
+        _ => None,
+    }
+}
+
+/// Matches the Preconnection PDU optionally sent by the client before the X.224 Connection Request
+#[derive(Clone, Copy, Debug)]
+pub struct PcbOrX224Hint;
+
+pub const PCB_OR_X224_HINT: PcbOrX224Hint = PcbOrX224Hint;
+
+impl PduHint for PcbOrX224Hint {
+    fn find_size(&self, bytes: &[u8]) -> DecodeResult<Option<(bool, usize)>> {
+        match is_preconnection_blob(bytes) {
+            Some(true) => {
+                let Some(size) = bytes.get(..4) else {
+                    return Ok(None);
+                };
+                let size: usize = cast_length!("cbSize", u32::from_le_bytes(size.try_into().expect("4 bytes")))?;
+
+                if !(PreconnectionBlob::FIXED_PART_SIZE..=PreconnectionBlob::MAX_SIZE).contains(&size) {
+                    return Err(invalid_field_err(
+                        PreconnectionBlob::NAME,
+                        "cbSize",
+                        "invalid Preconnection PDU size",
+                    ));
+                }
+
+                Ok(Some((true, size)))
+            }
+            Some(false) => X224_HINT.find_size(bytes),
+            None => Ok(None),
+        }
+    }
 }
 
 impl Pdu for PreconnectionBlob {
@@ -128,7 +177,7 @@ impl Encode for PreconnectionBlob {
 
         if let Some(v2_payload) = &self.v2_payload {
             // cchPCB
-            let utf16_character_count = v2_payload.chars().count() + 1; // +1 for null terminator
+            let utf16_character_count = v2_payload.encode_utf16().count() + 1; // +1 for null terminator
             dst.write_u16(cast_length!("cchPCB", utf16_character_count)?);
 
             // wszPCB
@@ -1,17 +1,28 @@
+#[cfg(feature = "std")]
 use std::io;
 
+#[cfg(feature = "std")]
 use thiserror::Error;
 
+#[cfg(feature = "std")]
 use crate::input::InputEventError;
+#[cfg(feature = "std")]
 use crate::rdp::capability_sets::CapabilitySetsError;
-use crate::rdp::client_info::{ClientInfo, ClientInfoError};
-use crate::rdp::headers::{BasicSecurityHeader, BasicSecurityHeaderFlags, ShareControlPduType, ShareDataPduType};
+use crate::rdp::client_info::ClientInfo;
+#[cfg(feature = "std")]
+use crate::rdp::client_info::ClientInfoError;
+use crate::rdp::headers::{BasicSecurityHeader, BasicSecurityHeaderFlags};
+#[cfg(feature = "std")]
+use crate::rdp::headers::{ShareControlPduType, ShareDataPduType};
+#[cfg(feature = "std")]
 use crate::rdp::server_license::ServerLicenseError;
+#[cfg(feature = "std")]
 use crate::PduError;
 use ironrdp_core::Encode;
 use ironrdp_core::{ensure_fixed_part_size, invalid_field_err, ReadCursor, WriteCursor};
 use ironrdp_core::{Decode, DecodeResult, EncodeResult};
 
+pub mod autodetect;
 pub mod capability_sets;
 pub mod client_info;
 pub mod finalization_messages;
@@ -20,6 +31,7 @@ pub mod refresh_rectangle;
 pub mod server_error_info;
 pub mod server_license;
 pub mod session_info;
+pub mod standard_security;
 pub mod suppress_output;
 pub mod vc;
 
@@ -72,6 +84,7 @@ impl<'de> Decode<'de> for ClientInfoPdu {
     }
 }
 
+#[cfg(feature = "std")]
 #[derive(Debug, Error)]
 pub enum RdpError {
     #[error("IO error")]
@@ -104,12 +117,14 @@ pub enum RdpError {
     Pdu(PduError),
 }
 
+#[cfg(feature = "std")]
 impl From<PduError> for RdpError {
     fn from(e: PduError) -> Self {
         Self::Pdu(e)
     }
 }
 
+#[cfg(feature = "std")]
 impl From<RdpError> for io::Error {
     fn from(e: RdpError) -> io::Error {
         io::Error::new(io::ErrorKind::Other, format!("RDP Connection Sequence error: {e}"))
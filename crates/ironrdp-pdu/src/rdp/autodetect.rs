@@ -0,0 +1,529 @@
+use alloc::format;
+use alloc::vec::Vec;
+
+use ironrdp_core::{
+    cast_length, ensure_fixed_part_size, ensure_size, invalid_field_err, unsupported_value_err, ReadCursor, WriteCursor,
+};
+use ironrdp_core::{Decode, DecodeResult, Encode, EncodeResult};
+
+use crate::rdp::headers::{BasicSecurityHeader, BasicSecurityHeaderFlags};
+
+const TYPE_ID_AUTODETECT_REQUEST: u8 = 0x00;
+const TYPE_ID_AUTODETECT_RESPONSE: u8 = 0x01;
+
+const RTT_REQUEST_CONTINUOUS: u16 = 0x0001;
+const RTT_REQUEST_CONNECT_TIME: u16 = 0x1001;
+const BW_START_CONTINUOUS: u16 = 0x0014;
+const BW_START_CONNECT_TIME: u16 = 0x1014;
+const BW_PAYLOAD: u16 = 0x0002;
+const BW_STOP_CONNECT_TIME: u16 = 0x002B;
+const BW_STOP_CONTINUOUS: u16 = 0x0429;
+const NETCHAR_RESULT_BASE_RTT_AVERAGE_RTT: u16 = 0x0840;
+const NETCHAR_RESULT_BANDWIDTH_AVERAGE_RTT: u16 = 0x0880;
+const NETCHAR_RESULT_ALL: u16 = 0x08C0;
+
+const RTT_RESPONSE: u16 = 0x0000;
+const BW_RESULTS_CONNECT_TIME: u16 = 0x0003;
+const BW_RESULTS_CONTINUOUS: u16 = 0x000B;
+const NETCHAR_SYNC: u16 = 0x0018;
+
+/// Phase of the connection during which a network auto-detection is performed
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum AutoDetectPhase {
+    /// Detection performed during the connection sequence, between the Secure Settings Exchange and Licensing phases
+    ConnectTime,
+    /// Detection performed at any time after the connection sequence is completed
+    Continuous,
+}
+
+/// Network characteristics computed by the server and sent to the client
+///
+/// At least one of `base_rtt` and `bandwidth` must be present.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct NetworkCharacteristics {
+    /// Lowest detected round-trip time, in milliseconds
+    pub base_rtt: Option<u32>,
+    /// Current bandwidth, in kilobits per second
+    pub bandwidth: Option<u32>,
+    /// Current average round-trip time, in milliseconds
+    pub average_rtt: u32,
+}
+
+/// [MS-RDPBCGR] 2.2.14.3 Auto-Detect Request PDU Data (TS_AUTODETECT_REQ)
+///
+/// Sent by the server, behind a security header with the `SEC_AUTODETECT_REQ` flag.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AutoDetectRequest {
+    /// [MS-RDPBCGR] 2.2.14.1.1 RTT Measure Request (TS_RTT_REQUEST)
+    RttRequest {
+        sequence_number: u16,
+        phase: AutoDetectPhase,
+    },
+    /// [MS-RDPBCGR] 2.2.14.1.2 Bandwidth Measure Start (TS_BANDWIDTH_MEASURE_START)
+    BandwidthMeasureStart {
+        sequence_number: u16,
+        phase: AutoDetectPhase,
+    },
+    /// [MS-RDPBCGR] 2.2.14.1.3 Bandwidth Measure Payload (TS_BANDWIDTH_MEASURE_PAYLOAD)
+    ///
+    /// Only used during the connect-time auto-detection.
+    BandwidthMeasurePayload { sequence_number: u16, payload: Vec<u8> },
+    /// [MS-RDPBCGR] 2.2.14.1.4 Bandwidth Measure Stop (TS_BANDWIDTH_MEASURE_STOP)
+    ///
+    /// A payload may only be carried during the connect-time auto-detection.
+    BandwidthMeasureStop {
+        sequence_number: u16,
+        phase: AutoDetectPhase,
+        payload: Vec<u8>,
+    },
+    /// [MS-RDPBCGR] 2.2.14.1.5 Network Characteristics Result (TS_NETCHAR_RESULT)
+    NetworkCharacteristicsResult {
+        sequence_number: u16,
+        characteristics: NetworkCharacteristics,
+    },
+}
+
+impl AutoDetectRequest {
+    const NAME: &'static str = "AutoDetectRequest";
+
+    const FIXED_PART_SIZE: usize = HEADER_SIZE;
+
+    pub fn sequence_number(&self) -> u16 {
+        match self {
+            Self::RttRequest { sequence_number, .. }
+            | Self::BandwidthMeasureStart { sequence_number, .. }
+            | Self::BandwidthMeasurePayload { sequence_number, .. }
+            | Self::BandwidthMeasureStop { sequence_number, .. }
+            | Self::NetworkCharacteristicsResult { sequence_number, .. } => *sequence_number,
+        }
+    }
+
+    fn request_type(&self) -> EncodeResult<u16> {
+        let request_type = match self {
+            Self::RttRequest { phase, .. } => match phase {
+                AutoDetectPhase::ConnectTime => RTT_REQUEST_CONNECT_TIME,
+                AutoDetectPhase::Continuous => RTT_REQUEST_CONTINUOUS,
+            },
+            Self::BandwidthMeasureStart { phase, .. } => match phase {
+                AutoDetectPhase::ConnectTime => BW_START_CONNECT_TIME,
+                AutoDetectPhase::Continuous => BW_START_CONTINUOUS,
+            },
+            Self::BandwidthMeasurePayload { .. } => BW_PAYLOAD,
+            Self::BandwidthMeasureStop { phase, .. } => match phase {
+                AutoDetectPhase::ConnectTime => BW_STOP_CONNECT_TIME,
+                AutoDetectPhase::Continuous => BW_STOP_CONTINUOUS,
+            },
+            Self::NetworkCharacteristicsResult { characteristics, .. } => {
+                match (characteristics.base_rtt, characteristics.bandwidth) {
+                    (Some(_), None) => NETCHAR_RESULT_BASE_RTT_AVERAGE_RTT,
+                    (None, Some(_)) => NETCHAR_RESULT_BANDWIDTH_AVERAGE_RTT,
+                    (Some(_), Some(_)) => NETCHAR_RESULT_ALL,
+                    (None, None) => {
+                        return Err(invalid_field_err!(
+                            "requestType",
+                            "either baseRTT or bandwidth must be present"
+                        ))
+                    }
+                }
+            }
+        };
+
+        Ok(request_type)
+    }
+
+    fn payload(&self) -> Option<&[u8]> {
+        match self {
+            Self::BandwidthMeasurePayload { payload, .. } => Some(payload),
+            Self::BandwidthMeasureStop {
+                phase: AutoDetectPhase::ConnectTime,
+                payload,
+                ..
+            } => Some(payload),
+            _ => None,
+        }
+    }
+}
+
+impl Encode for AutoDetectRequest {
+    fn encode(&self, dst: &mut WriteCursor<'_>) -> EncodeResult<()> {
+        ensure_size!(in: dst, size: self.size());
+
+        if let Self::BandwidthMeasureStop {
+            phase: AutoDetectPhase::Continuous,
+            payload,
+            ..
+        } = self
+        {
+            if !payload.is_empty() {
+                return Err(invalid_field_err!(
+                    "payload",
+                    "payload is only allowed during connect-time detection"
+                ));
+            }
+        }
+
+        let request_type = self.request_type()?;
+        let payload = self.payload();
+
+        // The header length covers all the fields, except the payload itself.
+        let header_length = self.size() - payload.map_or(0, <[u8]>::len);
+        dst.write_u8(cast_length!("headerLength", header_length)?);
+        dst.write_u8(TYPE_ID_AUTODETECT_REQUEST);
+        dst.write_u16(self.sequence_number());
+        dst.write_u16(request_type);
+
+        if let Some(payload) = payload {
+            dst.write_u16(cast_length!("payloadLength", payload.len())?);
+            dst.write_slice(payload);
+        }
+
+        if let Self::NetworkCharacteristicsResult { characteristics, .. } = self {
+            if let Some(base_rtt) = characteristics.base_rtt {
+                dst.write_u32(base_rtt);
+            }
+            if let Some(bandwidth) = characteristics.bandwidth {
+                dst.write_u32(bandwidth);
+            }
+            dst.write_u32(characteristics.average_rtt);
+        }
+
+        Ok(())
+    }
+
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    fn size(&self) -> usize {
+        let variable_part_size = match self {
+            Self::NetworkCharacteristicsResult { characteristics, .. } => {
+                characteristics.base_rtt.map_or(0, |_| 4 /* baseRTT */)
+                    + characteristics.bandwidth.map_or(0, |_| 4 /* bandwidth */)
+                    + 4 /* averageRTT */
+            }
+            _ => self
+                .payload()
+                .map_or(0, |payload| 2 /* payloadLength */ + payload.len()),
+        };
+
+        Self::FIXED_PART_SIZE + variable_part_size
+    }
+}
+
+impl<'de> Decode<'de> for AutoDetectRequest {
+    fn decode(src: &mut ReadCursor<'de>) -> DecodeResult<Self> {
+        ensure_fixed_part_size!(in: src);
+
+        let _header_length = src.read_u8();
+        let header_type_id = src.read_u8();
+        if header_type_id != TYPE_ID_AUTODETECT_REQUEST {
+            return Err(invalid_field_err!(
+                "headerTypeId",
+                "invalid auto-detect request type ID"
+            ));
+        }
+        let sequence_number = src.read_u16();
+        let request_type = src.read_u16();
+
+        let request = match request_type {
+            RTT_REQUEST_CONNECT_TIME | RTT_REQUEST_CONTINUOUS => Self::RttRequest {
+                sequence_number,
+                phase: phase_from_flag(request_type == RTT_REQUEST_CONNECT_TIME),
+            },
+            BW_START_CONNECT_TIME | BW_START_CONTINUOUS => Self::BandwidthMeasureStart {
+                sequence_number,
+                phase: phase_from_flag(request_type == BW_START_CONNECT_TIME),
+            },
+            BW_PAYLOAD => Self::BandwidthMeasurePayload {
+                sequence_number,
+                payload: read_payload(src)?,
+            },
+            BW_STOP_CONNECT_TIME => Self::BandwidthMeasureStop {
+                sequence_number,
+                phase: AutoDetectPhase::ConnectTime,
+                payload: read_payload(src)?,
+            },
+            BW_STOP_CONTINUOUS => Self::BandwidthMeasureStop {
+                sequence_number,
+                phase: AutoDetectPhase::Continuous,
+                payload: Vec::new(),
+            },
+            NETCHAR_RESULT_BASE_RTT_AVERAGE_RTT | NETCHAR_RESULT_BANDWIDTH_AVERAGE_RTT | NETCHAR_RESULT_ALL => {
+                let has_base_rtt = request_type != NETCHAR_RESULT_BANDWIDTH_AVERAGE_RTT;
+                let has_bandwidth = request_type != NETCHAR_RESULT_BASE_RTT_AVERAGE_RTT;
+
+                let size = if has_base_rtt && has_bandwidth { 12 } else { 8 };
+                ensure_size!(in: src, size: size);
+
+                let base_rtt = has_base_rtt.then(|| src.read_u32());
+                let bandwidth = has_bandwidth.then(|| src.read_u32());
+                let average_rtt = src.read_u32();
+
+                Self::NetworkCharacteristicsResult {
+                    sequence_number,
+                    characteristics: NetworkCharacteristics {
+                        base_rtt,
+                        bandwidth,
+                        average_rtt,
+                    },
+                }
+            }
+            _ => {
+                return Err(unsupported_value_err!(
+                    "requestType",
+                    format!("unsupported auto-detect request type: 0x{request_type:04X}")
+                ))
+            }
+        };
+
+        Ok(request)
+    }
+}
+
+/// [MS-RDPBCGR] 2.2.14.4 Auto-Detect Response PDU Data (TS_AUTODETECT_RSP)
+///
+/// Sent by the client, behind a security header with the `SEC_AUTODETECT_RSP` flag.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AutoDetectResponse {
+    /// [MS-RDPBCGR] 2.2.14.2.1 RTT Measure Response (TS_RTT_RESPONSE)
+    RttResponse { sequence_number: u16 },
+    /// [MS-RDPBCGR] 2.2.14.2.2 Bandwidth Measure Results (TS_BANDWIDTH_MEASURE_RESULTS)
+    BandwidthMeasureResults {
+        sequence_number: u16,
+        phase: AutoDetectPhase,
+        /// Time elapsed between the reception of the Bandwidth Measure Start and Stop messages, in milliseconds
+        time_delta: u32,
+        /// Number of bytes received between the Bandwidth Measure Start and Stop messages
+        byte_count: u32,
+    },
+    /// [MS-RDPBCGR] 2.2.14.2.3 Network Characteristics Sync (TS_NETCHAR_SYNC)
+    ///
+    /// Sent by a reconnecting client to report the previously detected characteristics.
+    NetworkCharacteristicsSync {
+        sequence_number: u16,
+        /// Bandwidth, in kilobits per second
+        bandwidth: u32,
+        /// Round-trip time, in milliseconds
+        rtt: u32,
+    },
+}
+
+impl AutoDetectResponse {
+    const NAME: &'static str = "AutoDetectResponse";
+
+    const FIXED_PART_SIZE: usize = HEADER_SIZE;
+
+    pub fn sequence_number(&self) -> u16 {
+        match self {
+            Self::RttResponse { sequence_number }
+            | Self::BandwidthMeasureResults { sequence_number, .. }
+            | Self::NetworkCharacteristicsSync { sequence_number, .. } => *sequence_number,
+        }
+    }
+}
+
+impl Encode for AutoDetectResponse {
+    fn encode(&self, dst: &mut WriteCursor<'_>) -> EncodeResult<()> {
+        ensure_size!(in: dst, size: self.size());
+
+        dst.write_u8(cast_length!("headerLength", self.size())?);
+        dst.write_u8(TYPE_ID_AUTODETECT_RESPONSE);
+        dst.write_u16(self.sequence_number());
+
+        match *self {
+            Self::RttResponse { .. } => {
+                dst.write_u16(RTT_RESPONSE);
+            }
+            Self::BandwidthMeasureResults {
+                phase,
+                time_delta,
+                byte_count,
+                ..
+            } => {
+                dst.write_u16(match phase {
+                    AutoDetectPhase::ConnectTime => BW_RESULTS_CONNECT_TIME,
+                    AutoDetectPhase::Continuous => BW_RESULTS_CONTINUOUS,
+                });
+                dst.write_u32(time_delta);
+                dst.write_u32(byte_count);
+            }
+            Self::NetworkCharacteristicsSync { bandwidth, rtt, .. } => {
+                dst.write_u16(NETCHAR_SYNC);
+                dst.write_u32(bandwidth);
+                dst.write_u32(rtt);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    fn size(&self) -> usize {
+        match self {
+            Self::RttResponse { .. } => Self::FIXED_PART_SIZE,
+            Self::BandwidthMeasureResults { .. } => {
+                Self::FIXED_PART_SIZE + 4 /* timeDelta */ + 4 /* byteCount */
+            }
+            Self::NetworkCharacteristicsSync { .. } => {
+                Self::FIXED_PART_SIZE + 4 /* bandwidth */ + 4 /* rtt */
+            }
+        }
+    }
+}
+
+impl<'de> Decode<'de> for AutoDetectResponse {
+    fn decode(src: &mut ReadCursor<'de>) -> DecodeResult<Self> {
+        ensure_fixed_part_size!(in: src);
+
+        let _header_length = src.read_u8();
+        let header_type_id = src.read_u8();
+        if header_type_id != TYPE_ID_AUTODETECT_RESPONSE {
+            return Err(invalid_field_err!(
+                "headerTypeId",
+                "invalid auto-detect response type ID"
+            ));
+        }
+        let sequence_number = src.read_u16();
+        let response_type = src.read_u16();
+
+        let response = match response_type {
+            RTT_RESPONSE => Self::RttResponse { sequence_number },
+            BW_RESULTS_CONNECT_TIME | BW_RESULTS_CONTINUOUS => {
+                ensure_size!(in: src, size: 4 /* timeDelta */ + 4 /* byteCount */);
+
+                Self::BandwidthMeasureResults {
+                    sequence_number,
+                    phase: phase_from_flag(response_type == BW_RESULTS_CONNECT_TIME),
+                    time_delta: src.read_u32(),
+                    byte_count: src.read_u32(),
+                }
+            }
+            NETCHAR_SYNC => {
+                ensure_size!(in: src, size: 4 /* bandwidth */ + 4 /* rtt */);
+
+                Self::NetworkCharacteristicsSync {
+                    sequence_number,
+                    bandwidth: src.read_u32(),
+                    rtt: src.read_u32(),
+                }
+            }
+            _ => {
+                return Err(unsupported_value_err!(
+                    "responseType",
+                    format!("unsupported auto-detect response type: 0x{response_type:04X}")
+                ))
+            }
+        };
+
+        Ok(response)
+    }
+}
+
+/// [MS-RDPBCGR] 2.2.14.3 Auto-Detect Request PDU (TS_AUTODETECT_REQ_PDU)
+///
+/// An [`AutoDetectRequest`] prefixed by a security header with the `SEC_AUTODETECT_REQ` flag.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AutoDetectRequestPdu(pub AutoDetectRequest);
+
+impl AutoDetectRequestPdu {
+    const NAME: &'static str = "AutoDetectRequestPdu";
+}
+
+impl Encode for AutoDetectRequestPdu {
+    fn encode(&self, dst: &mut WriteCursor<'_>) -> EncodeResult<()> {
+        ensure_size!(in: dst, size: self.size());
+
+        BasicSecurityHeader {
+            flags: BasicSecurityHeaderFlags::AUTODETECT_REQ,
+        }
+        .encode(dst)?;
+        self.0.encode(dst)
+    }
+
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    fn size(&self) -> usize {
+        BasicSecurityHeader::FIXED_PART_SIZE + self.0.size()
+    }
+}
+
+impl<'de> Decode<'de> for AutoDetectRequestPdu {
+    fn decode(src: &mut ReadCursor<'de>) -> DecodeResult<Self> {
+        let security_header = BasicSecurityHeader::decode(src)?;
+        if !security_header.flags.contains(BasicSecurityHeaderFlags::AUTODETECT_REQ) {
+            return Err(invalid_field_err!(
+                "securityHeader",
+                "SEC_AUTODETECT_REQ flag is missing"
+            ));
+        }
+
+        Ok(Self(AutoDetectRequest::decode(src)?))
+    }
+}
+
+/// [MS-RDPBCGR] 2.2.14.4 Auto-Detect Response PDU (TS_AUTODETECT_RSP_PDU)
+///
+/// An [`AutoDetectResponse`] prefixed by a security header with the `SEC_AUTODETECT_RSP` flag.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AutoDetectResponsePdu(pub AutoDetectResponse);
+
+impl AutoDetectResponsePdu {
+    const NAME: &'static str = "AutoDetectResponsePdu";
+}
+
+impl Encode for AutoDetectResponsePdu {
+    fn encode(&self, dst: &mut WriteCursor<'_>) -> EncodeResult<()> {
+        ensure_size!(in: dst, size: self.size());
+
+        BasicSecurityHeader {
+            flags: BasicSecurityHeaderFlags::AUTODETECT_RSP,
+        }
+        .encode(dst)?;
+        self.0.encode(dst)
+    }
+
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    fn size(&self) -> usize {
+        BasicSecurityHeader::FIXED_PART_SIZE + self.0.size()
+    }
+}
+
+impl<'de> Decode<'de> for AutoDetectResponsePdu {
+    fn decode(src: &mut ReadCursor<'de>) -> DecodeResult<Self> {
+        let security_header = BasicSecurityHeader::decode(src)?;
+        if !security_header.flags.contains(BasicSecurityHeaderFlags::AUTODETECT_RSP) {
+            return Err(invalid_field_err!(
+                "securityHeader",
+                "SEC_AUTODETECT_RSP flag is missing"
+            ));
+        }
+
+        Ok(Self(AutoDetectResponse::decode(src)?))
+    }
+}
+
+const HEADER_SIZE: usize = 1 /* headerLength */ + 1 /* headerTypeId */ + 2 /* sequenceNumber */ + 2 /* requestType */;
+
+fn phase_from_flag(is_connect_time: bool) -> AutoDetectPhase {
+    if is_connect_time {
+        AutoDetectPhase::ConnectTime
+    } else {
+        AutoDetectPhase::Continuous
+    }
+}
+
+fn read_payload(src: &mut ReadCursor<'_>) -> DecodeResult<Vec<u8>> {
+    ensure_size!(in: src, size: 2 /* payloadLength */);
+    let payload_length = usize::from(src.read_u16());
+
+    ensure_size!(in: src, size: payload_length);
+    Ok(src.read_slice(payload_length).to_vec())
+}
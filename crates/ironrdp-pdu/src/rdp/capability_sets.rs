@@ -1,12 +1,20 @@
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
 use std::io;
 
 use num_derive::{FromPrimitive, ToPrimitive};
 use num_traits::{FromPrimitive as _, ToPrimitive as _};
+#[cfg(feature = "std")]
 use thiserror::Error;
 
-use crate::{utils, PduError};
+use crate::utils;
+#[cfg(feature = "std")]
+use crate::PduError;
 use ironrdp_core::{
-    cast_length, ensure_fixed_part_size, ensure_size, invalid_field_err, unsupported_value_err, ReadCursor, WriteCursor,
+    cast_length, decode_limits, ensure_fixed_part_size, ensure_size, invalid_field_err, tolerate_violation,
+    unsupported_value_err, ReadCursor, WriteCursor,
 };
 use ironrdp_core::{decode, Decode, DecodeResult, Encode, EncodeResult};
 
@@ -14,6 +22,7 @@ mod bitmap;
 mod bitmap_cache;
 mod bitmap_codecs;
 mod brush;
+mod builder;
 mod frame_acknowledge;
 mod general;
 mod glyph_cache;
@@ -36,6 +45,9 @@ pub use self::bitmap_codecs::{
     RfxCapset, RfxClientCapsContainer, RfxICap, RfxICapFlags,
 };
 pub use self::brush::{Brush, SupportLevel};
+pub use self::builder::{
+    diff, CapabilitySetsBuilder, CapabilitySetsDiff, CapabilitySetsRole, CapabilitySetsValidationError,
+};
 pub use self::frame_acknowledge::FrameAcknowledge;
 pub use self::general::{General, GeneralExtraFlags, MajorPlatformType, MinorPlatformType, PROTOCOL_VER};
 pub use self::glyph_cache::{CacheDefinition, GlyphCache, GlyphSupportLevel, GLYPH_CACHE_NUM};
@@ -229,9 +241,24 @@ impl<'de> Decode<'de> for DemandActive {
         let capability_sets_count = src.read_u16() as usize;
         let _padding = src.read_u16();
 
+        if capability_sets_count > decode_limits().max_capability_sets {
+            return Err(invalid_field_err!(
+                "numberCapabilities",
+                "capability set count exceeds the decode limit"
+            ));
+        }
+
         let mut capability_sets = Vec::with_capacity(capability_sets_count);
 
         for _ in 0..capability_sets_count {
+            ensure_size!(in: src, size: CAPABILITY_SET_TYPE_FIELD_SIZE + CAPABILITY_SET_LENGTH_FIELD_SIZE);
+
+            if CapabilitySetType::from_u16(src.peek_u16()).is_none() {
+                tolerate_violation!("capabilitySetType", "unknown capability set type")?;
+                skip_capability_set(src)?;
+                continue;
+            }
+
             capability_sets.push(CapabilitySet::decode(src)?);
         }
 
@@ -242,6 +269,21 @@ impl<'de> Decode<'de> for DemandActive {
     }
 }
 
+fn skip_capability_set(src: &mut ReadCursor<'_>) -> DecodeResult<()> {
+    let _capability_set_type = src.read_u16();
+    let length = src.read_u16() as usize;
+
+    if length < CAPABILITY_SET_TYPE_FIELD_SIZE + CAPABILITY_SET_LENGTH_FIELD_SIZE {
+        return Err(invalid_field_err!("len", "invalid capability set length"));
+    }
+
+    let buffer_length = length - CAPABILITY_SET_TYPE_FIELD_SIZE - CAPABILITY_SET_LENGTH_FIELD_SIZE;
+    ensure_size!(in: src, size: buffer_length);
+    src.advance(buffer_length);
+
+    Ok(())
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum CapabilitySet {
     // mandatory
@@ -284,6 +326,41 @@ impl CapabilitySet {
     const NAME: &'static str = "CapabilitySet";
 
     const FIXED_PART_SIZE: usize = CAPABILITY_SET_TYPE_FIELD_SIZE + CAPABILITY_SET_LENGTH_FIELD_SIZE;
+
+    /// Returns the name of this kind of capability set
+    pub fn kind_name(&self) -> &'static str {
+        match self {
+            Self::General(_) => "General",
+            Self::Bitmap(_) => "Bitmap",
+            Self::Order(_) => "Order",
+            Self::BitmapCache(_) => "BitmapCache",
+            Self::BitmapCacheRev2(_) => "BitmapCacheRev2",
+            Self::Pointer(_) => "Pointer",
+            Self::Sound(_) => "Sound",
+            Self::Input(_) => "Input",
+            Self::Brush(_) => "Brush",
+            Self::GlyphCache(_) => "GlyphCache",
+            Self::OffscreenBitmapCache(_) => "OffscreenBitmapCache",
+            Self::VirtualChannel(_) => "VirtualChannel",
+            Self::Control(_) => "Control",
+            Self::WindowActivation(_) => "WindowActivation",
+            Self::Share(_) => "Share",
+            Self::Font(_) => "Font",
+            Self::BitmapCacheHostSupport(_) => "BitmapCacheHostSupport",
+            Self::DesktopComposition(_) => "DesktopComposition",
+            Self::MultiFragmentUpdate(_) => "MultiFragmentUpdate",
+            Self::LargePointer(_) => "LargePointer",
+            Self::SurfaceCommands(_) => "SurfaceCommands",
+            Self::BitmapCodecs(_) => "BitmapCodecs",
+            Self::FrameAcknowledge(_) => "FrameAcknowledge",
+            Self::ColorCache(_) => "ColorCache",
+            Self::DrawNineGridCache(_) => "DrawNineGridCache",
+            Self::DrawGdiPlus(_) => "DrawGdiPlus",
+            Self::Rail(_) => "Rail",
+            Self::WindowList(_) => "WindowList",
+            Self::BitmapCacheV3(_) => "BitmapCacheV3",
+        }
+    }
 }
 
 impl Encode for CapabilitySet {
@@ -594,12 +671,13 @@ enum CapabilitySetType {
     FrameAcknowledge = 0x1e,
 }
 
+#[cfg(feature = "std")]
 #[derive(Debug, Error)]
 pub enum CapabilitySetsError {
     #[error("IO error")]
     IOError(#[from] io::Error),
     #[error("UTF-8 error")]
-    Utf8Error(#[from] std::string::FromUtf8Error),
+    Utf8Error(#[from] alloc::string::FromUtf8Error),
     #[error("invalid type field")]
     InvalidType,
     #[error("invalid bitmap compression field")]
@@ -654,6 +732,7 @@ pub enum CapabilitySetsError {
     Pdu(PduError),
 }
 
+#[cfg(feature = "std")]
 impl From<PduError> for CapabilitySetsError {
     fn from(e: PduError) -> Self {
         Self::Pdu(e)
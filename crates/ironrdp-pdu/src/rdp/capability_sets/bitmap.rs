@@ -74,7 +74,7 @@ impl<'de> Decode<'de> for Bitmap {
         let _receive_8_bit_per_pixel = src.read_u16() != 0;
         let desktop_width = src.read_u16();
         let desktop_height = src.read_u16();
-        read_padding!(src, 2);
+        read_zeroed_padding!(src, 2)?;
         let desktop_resize_flag = src.read_u16() != 0;
 
         let is_bitmap_compress_flag_set = src.read_u16() != 0;
@@ -94,7 +94,7 @@ impl<'de> Decode<'de> for Bitmap {
         // https://github.com/FreeRDP/FreeRDP/blob/ba8cf8cf2158018fb7abbedb51ab245f369be813/libfreerdp/core/capabilities.c#L391
         let _ = src.read_u16();
 
-        read_padding!(src, 2);
+        read_zeroed_padding!(src, 2)?;
 
         Ok(Bitmap {
             pref_bits_per_pix,
@@ -0,0 +1,388 @@
+#[cfg(test)]
+mod tests;
+
+use alloc::vec::Vec;
+use core::fmt;
+use core::mem;
+
+use super::{
+    Bitmap, BitmapCache, BitmapCacheRev2, BitmapCodecs, Brush, CapabilitySet, CmdFlags, FrameAcknowledge, General,
+    GeneralExtraFlags, GlyphCache, Input, InputFlags, LargePointer, MultifragmentUpdate, OffscreenBitmapCache, Order,
+    Pointer, Sound, SurfaceCommands, VirtualChannel,
+};
+
+/// Side of the connection advertising the capability sets
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CapabilitySetsRole {
+    /// Capability sets sent in the Client Confirm Active PDU
+    Client,
+    /// Capability sets sent in the Server Demand Active PDU
+    Server,
+}
+
+impl CapabilitySetsRole {
+    fn mandatory_capability_sets(self) -> &'static [&'static str] {
+        match self {
+            // [2.2.1.13.2.1] Confirm Active PDU Data (TS_CONFIRM_ACTIVE_PDU)
+            //
+            // The Bitmap Cache capability set may be replaced by the Revision 2 one, which is checked separately.
+            Self::Client => &[
+                "General",
+                "Bitmap",
+                "Order",
+                "Pointer",
+                "Input",
+                "Brush",
+                "GlyphCache",
+                "OffscreenBitmapCache",
+                "VirtualChannel",
+                "Sound",
+            ],
+            // [2.2.1.13.1.1] Demand Active PDU Data (TS_DEMAND_ACTIVE_PDU)
+            Self::Server => &["General", "Bitmap", "Order", "Pointer", "Input", "VirtualChannel"],
+        }
+    }
+}
+
+/// Inconsistency found by [`CapabilitySetsBuilder::validate`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CapabilitySetsValidationError {
+    /// A capability set mandatory for the role is missing
+    MissingCapabilitySet(&'static str),
+    /// Fast-path input is advertised in the Input capability set, but fast-path output is not advertised in the
+    /// General capability set
+    FastPathInputWithoutFastPathOutput,
+    /// The Graphics Pipeline Extension is advertised, but no Surface Commands capability set is present
+    EgfxWithoutSurfaceCommands,
+    /// Bitmap codecs are advertised, but surface bits commands are not supported
+    BitmapCodecsWithoutSurfaceBits,
+    /// The Frame Acknowledge capability set is present, but frame marker commands are not supported
+    FrameAcknowledgeWithoutFrameMarker,
+}
+
+impl fmt::Display for CapabilitySetsValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingCapabilitySet(name) => write!(f, "missing mandatory {name} capability set"),
+            Self::FastPathInputWithoutFastPathOutput => {
+                f.write_str("fast-path input is advertised without fast-path output support")
+            }
+            Self::EgfxWithoutSurfaceCommands => {
+                f.write_str("EGFX is advertised without Surface Commands capability set")
+            }
+            Self::BitmapCodecsWithoutSurfaceBits => {
+                f.write_str("bitmap codecs are advertised without surface bits commands support")
+            }
+            Self::FrameAcknowledgeWithoutFrameMarker => {
+                f.write_str("frame acknowledgement is advertised without frame marker commands support")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for CapabilitySetsValidationError {}
+
+/// Fluent builder for the capability sets exchanged during the Capabilities Exchange phase
+///
+/// Setting a capability set replaces any previous capability set of the same kind.
+///
+/// # Example
+///
+/// ```
+/// use ironrdp_pdu::rdp::capability_sets::{CapabilitySetsBuilder, FrameAcknowledge, General};
+///
+/// let result = CapabilitySetsBuilder::server()
+///     .general(General::default())
+///     .frame_acknowledge(FrameAcknowledge {
+///         max_unacknowledged_frame_count: 2,
+///     })
+///     .build();
+///
+/// assert!(result.is_err()); // mandatory capability sets are missing
+/// ```
+#[derive(Debug, Clone)]
+pub struct CapabilitySetsBuilder {
+    role: CapabilitySetsRole,
+    egfx: bool,
+    capability_sets: Vec<CapabilitySet>,
+}
+
+impl CapabilitySetsBuilder {
+    /// Creates an empty builder for the given role
+    pub fn new(role: CapabilitySetsRole) -> Self {
+        Self {
+            role,
+            egfx: false,
+            capability_sets: Vec::new(),
+        }
+    }
+
+    /// Creates an empty builder for the Client Confirm Active PDU
+    pub fn client() -> Self {
+        Self::new(CapabilitySetsRole::Client)
+    }
+
+    /// Creates an empty builder for the Server Demand Active PDU
+    pub fn server() -> Self {
+        Self::new(CapabilitySetsRole::Server)
+    }
+
+    /// Declares whether the Graphics Pipeline Extension (MS-RDPEGFX) is advertised for this connection
+    #[must_use]
+    pub fn egfx(mut self, egfx: bool) -> Self {
+        self.egfx = egfx;
+        self
+    }
+
+    /// Sets a capability set, replacing any capability set of the same kind
+    #[must_use]
+    pub fn with(mut self, capability_set: CapabilitySet) -> Self {
+        self.insert(capability_set);
+        self
+    }
+
+    /// Sets all the given capability sets, replacing any capability set of the same kind
+    #[must_use]
+    pub fn with_all(mut self, capability_sets: impl IntoIterator<Item = CapabilitySet>) -> Self {
+        for capability_set in capability_sets {
+            self.insert(capability_set);
+        }
+        self
+    }
+
+    #[must_use]
+    pub fn general(self, general: General) -> Self {
+        self.with(CapabilitySet::General(general))
+    }
+
+    #[must_use]
+    pub fn bitmap(self, bitmap: Bitmap) -> Self {
+        self.with(CapabilitySet::Bitmap(bitmap))
+    }
+
+    #[must_use]
+    pub fn order(self, order: Order) -> Self {
+        self.with(CapabilitySet::Order(order))
+    }
+
+    #[must_use]
+    pub fn bitmap_cache(self, bitmap_cache: BitmapCache) -> Self {
+        self.with(CapabilitySet::BitmapCache(bitmap_cache))
+    }
+
+    #[must_use]
+    pub fn bitmap_cache_rev2(self, bitmap_cache: BitmapCacheRev2) -> Self {
+        self.with(CapabilitySet::BitmapCacheRev2(bitmap_cache))
+    }
+
+    #[must_use]
+    pub fn pointer(self, pointer: Pointer) -> Self {
+        self.with(CapabilitySet::Pointer(pointer))
+    }
+
+    #[must_use]
+    pub fn sound(self, sound: Sound) -> Self {
+        self.with(CapabilitySet::Sound(sound))
+    }
+
+    #[must_use]
+    pub fn input(self, input: Input) -> Self {
+        self.with(CapabilitySet::Input(input))
+    }
+
+    #[must_use]
+    pub fn brush(self, brush: Brush) -> Self {
+        self.with(CapabilitySet::Brush(brush))
+    }
+
+    #[must_use]
+    pub fn glyph_cache(self, glyph_cache: GlyphCache) -> Self {
+        self.with(CapabilitySet::GlyphCache(glyph_cache))
+    }
+
+    #[must_use]
+    pub fn offscreen_bitmap_cache(self, offscreen_bitmap_cache: OffscreenBitmapCache) -> Self {
+        self.with(CapabilitySet::OffscreenBitmapCache(offscreen_bitmap_cache))
+    }
+
+    #[must_use]
+    pub fn virtual_channel(self, virtual_channel: VirtualChannel) -> Self {
+        self.with(CapabilitySet::VirtualChannel(virtual_channel))
+    }
+
+    #[must_use]
+    pub fn multifragment_update(self, multifragment_update: MultifragmentUpdate) -> Self {
+        self.with(CapabilitySet::MultiFragmentUpdate(multifragment_update))
+    }
+
+    #[must_use]
+    pub fn large_pointer(self, large_pointer: LargePointer) -> Self {
+        self.with(CapabilitySet::LargePointer(large_pointer))
+    }
+
+    #[must_use]
+    pub fn surface_commands(self, surface_commands: SurfaceCommands) -> Self {
+        self.with(CapabilitySet::SurfaceCommands(surface_commands))
+    }
+
+    #[must_use]
+    pub fn bitmap_codecs(self, bitmap_codecs: BitmapCodecs) -> Self {
+        self.with(CapabilitySet::BitmapCodecs(bitmap_codecs))
+    }
+
+    #[must_use]
+    pub fn frame_acknowledge(self, frame_acknowledge: FrameAcknowledge) -> Self {
+        self.with(CapabilitySet::FrameAcknowledge(frame_acknowledge))
+    }
+
+    /// Returns the capability sets set so far
+    pub fn capability_sets(&self) -> &[CapabilitySet] {
+        &self.capability_sets
+    }
+
+    /// Checks the consistency of the capability sets
+    pub fn validate(&self) -> Result<(), CapabilitySetsValidationError> {
+        for &name in self.role.mandatory_capability_sets() {
+            if !self.capability_sets.iter().any(|c| c.kind_name() == name) {
+                return Err(CapabilitySetsValidationError::MissingCapabilitySet(name));
+            }
+        }
+
+        if self.role == CapabilitySetsRole::Client
+            && !self
+                .capability_sets
+                .iter()
+                .any(|c| matches!(c, CapabilitySet::BitmapCache(_) | CapabilitySet::BitmapCacheRev2(_)))
+        {
+            return Err(CapabilitySetsValidationError::MissingCapabilitySet("BitmapCache"));
+        }
+
+        let fast_path_input = self.find(|c| match c {
+            CapabilitySet::Input(input) => Some(
+                input
+                    .input_flags
+                    .intersects(InputFlags::FASTPATH_INPUT | InputFlags::FASTPATH_INPUT_2),
+            ),
+            _ => None,
+        });
+
+        let fast_path_output = self.find(|c| match c {
+            CapabilitySet::General(general) => Some(
+                general
+                    .extra_flags
+                    .contains(GeneralExtraFlags::FASTPATH_OUTPUT_SUPPORTED),
+            ),
+            _ => None,
+        });
+
+        if fast_path_input == Some(true) && fast_path_output != Some(true) {
+            return Err(CapabilitySetsValidationError::FastPathInputWithoutFastPathOutput);
+        }
+
+        let surface_commands = self.find(|c| match c {
+            CapabilitySet::SurfaceCommands(surface_commands) => Some(surface_commands.flags),
+            _ => None,
+        });
+
+        if self.egfx && surface_commands.is_none() {
+            return Err(CapabilitySetsValidationError::EgfxWithoutSurfaceCommands);
+        }
+
+        let surface_commands = surface_commands.unwrap_or(CmdFlags::empty());
+
+        let has_codecs = self
+            .find(|c| match c {
+                CapabilitySet::BitmapCodecs(codecs) => Some(!codecs.0.is_empty()),
+                _ => None,
+            })
+            .unwrap_or(false);
+
+        if has_codecs && !surface_commands.intersects(CmdFlags::SET_SURFACE_BITS | CmdFlags::STREAM_SURFACE_BITS) {
+            return Err(CapabilitySetsValidationError::BitmapCodecsWithoutSurfaceBits);
+        }
+
+        let has_frame_acknowledge = self
+            .capability_sets
+            .iter()
+            .any(|c| matches!(c, CapabilitySet::FrameAcknowledge(_)));
+
+        if has_frame_acknowledge && !surface_commands.contains(CmdFlags::FRAME_MARKER) {
+            return Err(CapabilitySetsValidationError::FrameAcknowledgeWithoutFrameMarker);
+        }
+
+        Ok(())
+    }
+
+    /// Validates and returns the capability sets
+    pub fn build(self) -> Result<Vec<CapabilitySet>, CapabilitySetsValidationError> {
+        self.validate()?;
+        Ok(self.capability_sets)
+    }
+
+    fn insert(&mut self, capability_set: CapabilitySet) {
+        match self
+            .capability_sets
+            .iter_mut()
+            .find(|c| c.is_same_kind(&capability_set))
+        {
+            Some(existing) => *existing = capability_set,
+            None => self.capability_sets.push(capability_set),
+        }
+    }
+
+    fn find<T>(&self, f: impl FnMut(&CapabilitySet) -> Option<T>) -> Option<T> {
+        self.capability_sets.iter().find_map(f)
+    }
+}
+
+/// Differences between two lists of capability sets, as returned by [`diff`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CapabilitySetsDiff<'a> {
+    /// Requested capability sets with no negotiated counterpart
+    pub missing: Vec<&'a CapabilitySet>,
+    /// Negotiated capability sets which were not requested
+    pub unexpected: Vec<&'a CapabilitySet>,
+    /// Capability sets which changed during the negotiation, as `(requested, negotiated)` pairs
+    pub changed: Vec<(&'a CapabilitySet, &'a CapabilitySet)>,
+}
+
+impl CapabilitySetsDiff<'_> {
+    /// Returns true if both lists hold the same capability sets
+    pub fn is_empty(&self) -> bool {
+        self.missing.is_empty() && self.unexpected.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Compares the requested capability sets with the negotiated ones
+///
+/// Capability sets are matched by kind, regardless of their order.
+pub fn diff<'a>(requested: &'a [CapabilitySet], negotiated: &'a [CapabilitySet]) -> CapabilitySetsDiff<'a> {
+    let mut missing = Vec::new();
+    let mut changed = Vec::new();
+
+    for requested in requested {
+        match negotiated.iter().find(|c| c.is_same_kind(requested)) {
+            Some(negotiated) if negotiated != requested => changed.push((requested, negotiated)),
+            Some(_) => {}
+            None => missing.push(requested),
+        }
+    }
+
+    let unexpected = negotiated
+        .iter()
+        .filter(|negotiated| !requested.iter().any(|c| c.is_same_kind(negotiated)))
+        .collect();
+
+    CapabilitySetsDiff {
+        missing,
+        unexpected,
+        changed,
+    }
+}
+
+impl CapabilitySet {
+    fn is_same_kind(&self, other: &CapabilitySet) -> bool {
+        mem::discriminant(self) == mem::discriminant(other)
+    }
+}
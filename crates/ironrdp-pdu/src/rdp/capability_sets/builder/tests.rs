@@ -0,0 +1,142 @@
+use super::*;
+use crate::rdp::capability_sets::{
+    BitmapDrawingFlags, OrderFlags, OrderSupportExFlags, SoundFlags, VirtualChannelFlags,
+};
+
+fn server_builder() -> CapabilitySetsBuilder {
+    CapabilitySetsBuilder::server()
+        .general(General {
+            extra_flags: GeneralExtraFlags::FASTPATH_OUTPUT_SUPPORTED,
+            ..Default::default()
+        })
+        .bitmap(Bitmap {
+            pref_bits_per_pix: 32,
+            desktop_width: 1024,
+            desktop_height: 768,
+            desktop_resize_flag: false,
+            drawing_flags: BitmapDrawingFlags::empty(),
+        })
+        .order(Order::new(OrderFlags::empty(), OrderSupportExFlags::empty(), 0, 0))
+        .pointer(Pointer {
+            color_pointer_cache_size: 32,
+            pointer_cache_size: 32,
+        })
+        .input(Input {
+            input_flags: InputFlags::SCANCODES | InputFlags::FASTPATH_INPUT_2,
+            keyboard_layout: 0,
+            keyboard_type: None,
+            keyboard_subtype: 0,
+            keyboard_function_key: 12,
+            keyboard_ime_filename: String::new(),
+        })
+        .virtual_channel(VirtualChannel {
+            flags: VirtualChannelFlags::NO_COMPRESSION,
+            chunk_size: None,
+        })
+}
+
+#[test]
+fn builds_consistent_capability_sets() {
+    let capability_sets = server_builder().build().unwrap();
+    assert_eq!(capability_sets.len(), 6);
+}
+
+#[test]
+fn setting_a_capability_set_replaces_the_previous_one() {
+    let capability_sets = server_builder()
+        .pointer(Pointer {
+            color_pointer_cache_size: 64,
+            pointer_cache_size: 64,
+        })
+        .build()
+        .unwrap();
+
+    let pointers = capability_sets
+        .iter()
+        .filter_map(|c| match c {
+            CapabilitySet::Pointer(pointer) => Some(pointer.pointer_cache_size),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(pointers, [64]);
+}
+
+#[test]
+fn missing_mandatory_capability_set_is_rejected() {
+    let error = CapabilitySetsBuilder::client()
+        .with_all(server_builder().capability_sets().iter().cloned())
+        .build()
+        .unwrap_err();
+
+    assert_eq!(error, CapabilitySetsValidationError::MissingCapabilitySet("Brush"));
+}
+
+#[test]
+fn fast_path_input_requires_fast_path_output() {
+    let error = server_builder().general(General::default()).build().unwrap_err();
+    assert_eq!(error, CapabilitySetsValidationError::FastPathInputWithoutFastPathOutput);
+}
+
+#[test]
+fn egfx_requires_surface_commands() {
+    let error = server_builder().egfx(true).build().unwrap_err();
+    assert_eq!(error, CapabilitySetsValidationError::EgfxWithoutSurfaceCommands);
+
+    server_builder()
+        .egfx(true)
+        .surface_commands(SurfaceCommands {
+            flags: CmdFlags::SET_SURFACE_BITS,
+        })
+        .build()
+        .unwrap();
+}
+
+#[test]
+fn frame_acknowledge_requires_frame_marker() {
+    let error = server_builder()
+        .surface_commands(SurfaceCommands {
+            flags: CmdFlags::SET_SURFACE_BITS,
+        })
+        .frame_acknowledge(FrameAcknowledge {
+            max_unacknowledged_frame_count: 2,
+        })
+        .build()
+        .unwrap_err();
+
+    assert_eq!(error, CapabilitySetsValidationError::FrameAcknowledgeWithoutFrameMarker);
+}
+
+#[test]
+fn diff_reports_missing_unexpected_and_changed_capability_sets() {
+    let requested = [
+        CapabilitySet::Pointer(Pointer {
+            color_pointer_cache_size: 32,
+            pointer_cache_size: 32,
+        }),
+        CapabilitySet::Sound(Sound {
+            flags: SoundFlags::empty(),
+        }),
+        CapabilitySet::MultiFragmentUpdate(MultifragmentUpdate { max_request_size: 1024 }),
+    ];
+
+    let negotiated = [
+        CapabilitySet::MultiFragmentUpdate(MultifragmentUpdate { max_request_size: 4096 }),
+        CapabilitySet::Pointer(Pointer {
+            color_pointer_cache_size: 32,
+            pointer_cache_size: 32,
+        }),
+        CapabilitySet::FrameAcknowledge(FrameAcknowledge {
+            max_unacknowledged_frame_count: 2,
+        }),
+    ];
+
+    let capabilities_diff = diff(&requested, &negotiated);
+
+    assert!(!capabilities_diff.is_empty());
+    assert_eq!(capabilities_diff.missing, [&requested[1]]);
+    assert_eq!(capabilities_diff.unexpected, [&negotiated[2]]);
+    assert_eq!(capabilities_diff.changed, [(&requested[2], &negotiated[0])]);
+
+    assert!(diff(&requested, &requested).is_empty());
+}
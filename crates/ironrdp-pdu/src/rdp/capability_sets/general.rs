@@ -1,7 +1,7 @@
 #[cfg(test)]
 mod tests;
 
-use std::fmt;
+use core::fmt;
 
 use bitflags::bitflags;
 
@@ -12,6 +12,7 @@ const GENERAL_LENGTH: usize = 20;
 pub const PROTOCOL_VER: u16 = 0x0200;
 
 #[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MajorPlatformType(u16);
 
 impl fmt::Debug for MajorPlatformType {
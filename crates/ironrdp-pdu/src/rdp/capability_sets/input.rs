@@ -1,6 +1,8 @@
 #[cfg(test)]
 mod tests;
 
+use alloc::string::String;
+
 use bitflags::bitflags;
 use num_traits::{FromPrimitive, ToPrimitive};
 
@@ -84,7 +86,7 @@ impl<'de> Decode<'de> for Input {
         ensure_fixed_part_size!(in: src);
 
         let input_flags = InputFlags::from_bits_truncate(src.read_u16());
-        read_padding!(src, 2);
+        read_zeroed_padding!(src, 2)?;
         let keyboard_layout = src.read_u32();
 
         let keyboard_type = KeyboardType::from_u32(src.read_u32());
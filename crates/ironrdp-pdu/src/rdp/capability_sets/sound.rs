@@ -50,7 +50,7 @@ impl<'de> Decode<'de> for Sound {
         ensure_fixed_part_size!(in: src);
 
         let flags = SoundFlags::from_bits_truncate(src.read_u16());
-        read_padding!(src, 2);
+        read_zeroed_padding!(src, 2)?;
 
         Ok(Sound { flags })
     }
@@ -1,13 +1,18 @@
+use alloc::string::String;
 use core::fmt;
+#[cfg(feature = "std")]
 use std::io;
 
 use bitflags::bitflags;
 use num_derive::{FromPrimitive, ToPrimitive};
 use num_traits::{FromPrimitive as _, ToPrimitive as _};
+#[cfg(feature = "std")]
 use thiserror::Error;
 
+use crate::utils;
 use crate::utils::CharacterSet;
-use crate::{utils, PduError};
+#[cfg(feature = "std")]
+use crate::PduError;
 use ironrdp_core::{ensure_fixed_part_size, ensure_size, invalid_field_err, ReadCursor, WriteCursor};
 use ironrdp_core::{Decode, DecodeResult, Encode, EncodeResult};
 
@@ -587,6 +592,7 @@ pub enum DayOfWeekOccurrence {
 
 bitflags! {
     #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct PerformanceFlags: u32 {
         const DISABLE_WALLPAPER = 0x0000_0001;
         const DISABLE_FULLWINDOWDRAG = 0x0000_0002;
@@ -671,12 +677,13 @@ pub enum CompressionType {
     Rdp61 = 3,
 }
 
+#[cfg(feature = "std")]
 #[derive(Debug, Error)]
 pub enum ClientInfoError {
     #[error("IO error")]
     IOError(#[from] io::Error),
     #[error("UTF-8 error")]
-    Utf8Error(#[from] std::string::FromUtf8Error),
+    Utf8Error(#[from] alloc::string::FromUtf8Error),
     #[error("invalid address family field")]
     InvalidAddressFamily,
     #[error("invalid flags field")]
@@ -689,6 +696,7 @@ pub enum ClientInfoError {
     Pdu(PduError),
 }
 
+#[cfg(feature = "std")]
 impl From<PduError> for ClientInfoError {
     fn from(e: PduError) -> Self {
         Self::Pdu(e)
@@ -704,7 +712,7 @@ fn string_len(value: &str, character_set: CharacterSet) -> u16 {
 
 pub mod builder {
     use super::*;
-    use std::marker::PhantomData;
+    use core::marker::PhantomData;
 
     pub struct ExtendedClientOptionalInfoBuilderStateSetTimeZone;
     pub struct ExtendedClientOptionalInfoBuilderStateSetSessionId;
@@ -1,9 +1,16 @@
+use alloc::vec::Vec;
+use core::mem;
+
 use bitflags::bitflags;
 use num_derive::{FromPrimitive, ToPrimitive};
 use num_traits::{FromPrimitive as _, ToPrimitive as _};
 
 use crate::gcc;
-use ironrdp_core::{cast_length, ensure_fixed_part_size, invalid_field_err, ReadCursor, WriteCursor};
+use crate::utils::{combine_u64, split_u64};
+use ironrdp_core::{
+    cast_length, decode_limits, ensure_fixed_part_size, ensure_size, invalid_field_err, tolerate_violation, ReadCursor,
+    WriteCursor,
+};
 use ironrdp_core::{Decode, DecodeResult, Encode, EncodeResult};
 
 const SYNCHRONIZE_PDU_SIZE: usize = 2 + 2;
@@ -11,6 +18,14 @@ const CONTROL_PDU_SIZE: usize = 2 + 2 + 4;
 const FONT_PDU_SIZE: usize = 2 * 4;
 const SYNCHRONIZE_MESSAGE_TYPE: u16 = 1;
 const MAX_MONITOR_COUNT: u32 = 64;
+const PERSISTENT_KEY_LIST_ENTRY_SIZE: usize = 4 /* Key1 */ + 4 /* Key2 */;
+
+/// Number of bitmap cache cells covered by the Persistent Key List PDU
+pub const PERSISTENT_KEY_LIST_CELLS: usize = 5;
+/// Maximum number of keys in a single Persistent Key List PDU
+pub const PERSISTENT_KEY_LIST_MAX_ENTRIES: usize = 169;
+/// Maximum number of keys over all the Persistent Key List PDUs
+pub const PERSISTENT_KEY_LIST_MAX_TOTAL_ENTRIES: usize = 262_144;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct SynchronizePdu {
@@ -218,6 +233,13 @@ impl<'de> Decode<'de> for MonitorLayoutPdu {
             return Err(invalid_field_err!("nMonitors", "invalid monitor count"));
         }
 
+        if monitor_count as usize > decode_limits().max_monitor_count {
+            return Err(invalid_field_err!(
+                "nMonitors",
+                "monitor count exceeds the decode limit"
+            ));
+        }
+
         let mut monitors = Vec::with_capacity(monitor_count as usize);
         for _ in 0..monitor_count {
             monitors.push(gcc::Monitor::decode(src)?);
@@ -227,6 +249,163 @@ impl<'de> Decode<'de> for MonitorLayoutPdu {
     }
 }
 
+/// 2.2.1.17.1 Persistent Key List PDU Data (TS_BITMAPCACHE_PERSISTENT_LIST_PDU)
+///
+/// Keys of the bitmaps cached by the client during previous connections. The client sends them during the connection
+/// finalization, after the Control (Request Control) PDU and before the Font List PDU, so that the server may use the
+/// cached bitmaps without sending them again.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PersistentKeyListPdu {
+    /// Keys sent in this PDU, for each bitmap cache cell
+    ///
+    /// Each 64-bit key holds `Key1` in its low 32 bits and `Key2` in its high 32 bits.
+    pub keys: [Vec<u64>; PERSISTENT_KEY_LIST_CELLS],
+    /// Total number of keys sent for each bitmap cache cell, over all the PDUs
+    pub total_entries: [u16; PERSISTENT_KEY_LIST_CELLS],
+    pub flags: PersistentKeyListFlags,
+}
+
+impl PersistentKeyListPdu {
+    const NAME: &'static str = "PersistentKeyListPdu";
+
+    const FIXED_PART_SIZE: usize = 2 * PERSISTENT_KEY_LIST_CELLS /* numEntriesCacheX */
+        + 2 * PERSISTENT_KEY_LIST_CELLS /* totalEntriesCacheX */
+        + 1 /* bBitMask */
+        + 1 /* Pad2 */
+        + 2 /* Pad3 */;
+
+    /// Splits the keys of each bitmap cache cell into as many PDUs as required
+    ///
+    /// A single PDU without keys is returned when there are no keys at all.
+    pub fn from_keys(keys: &[Vec<u64>]) -> EncodeResult<Vec<Self>> {
+        if keys.len() > PERSISTENT_KEY_LIST_CELLS {
+            return Err(invalid_field_err!("keys", "too many bitmap cache cells"));
+        }
+
+        let mut total_entries = [0; PERSISTENT_KEY_LIST_CELLS];
+        for (total, cell_keys) in total_entries.iter_mut().zip(keys) {
+            *total = cast_length!("totalEntries", cell_keys.len())?;
+        }
+
+        if keys.iter().map(Vec::len).sum::<usize>() > PERSISTENT_KEY_LIST_MAX_TOTAL_ENTRIES {
+            return Err(invalid_field_err!("totalEntries", "too many keys"));
+        }
+
+        let mut pdus = Vec::new();
+        let mut pdu = Self {
+            total_entries,
+            flags: PersistentKeyListFlags::FIRST,
+            ..Self::default()
+        };
+
+        let entries = keys
+            .iter()
+            .enumerate()
+            .flat_map(|(cell, cell_keys)| cell_keys.iter().map(move |key| (cell, *key)));
+
+        for (idx, (cell, key)) in entries.enumerate() {
+            if idx != 0 && idx % PERSISTENT_KEY_LIST_MAX_ENTRIES == 0 {
+                let next = Self {
+                    total_entries,
+                    ..Self::default()
+                };
+                pdus.push(mem::replace(&mut pdu, next));
+            }
+
+            pdu.keys[cell].push(key);
+        }
+
+        pdu.flags |= PersistentKeyListFlags::LAST;
+        pdus.push(pdu);
+
+        Ok(pdus)
+    }
+
+    fn entry_count(&self) -> usize {
+        self.keys.iter().map(Vec::len).sum()
+    }
+}
+
+impl Encode for PersistentKeyListPdu {
+    fn encode(&self, dst: &mut WriteCursor<'_>) -> EncodeResult<()> {
+        ensure_size!(in: dst, size: self.size());
+
+        for cell_keys in self.keys.iter() {
+            dst.write_u16(cast_length!("numEntries", cell_keys.len())?);
+        }
+
+        for total in self.total_entries {
+            dst.write_u16(total);
+        }
+
+        dst.write_u8(self.flags.bits());
+        write_padding!(dst, 3);
+
+        for key in self.keys.iter().flatten() {
+            let (key1, key2) = split_u64(*key);
+            dst.write_u32(key1);
+            dst.write_u32(key2);
+        }
+
+        Ok(())
+    }
+
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    fn size(&self) -> usize {
+        Self::FIXED_PART_SIZE + self.entry_count() * PERSISTENT_KEY_LIST_ENTRY_SIZE
+    }
+}
+
+impl<'de> Decode<'de> for PersistentKeyListPdu {
+    fn decode(src: &mut ReadCursor<'de>) -> DecodeResult<Self> {
+        ensure_fixed_part_size!(in: src);
+
+        let mut num_entries = [0; PERSISTENT_KEY_LIST_CELLS];
+        for count in num_entries.iter_mut() {
+            *count = usize::from(src.read_u16());
+        }
+
+        let mut total_entries = [0; PERSISTENT_KEY_LIST_CELLS];
+        for total in total_entries.iter_mut() {
+            *total = src.read_u16();
+        }
+
+        let flags = PersistentKeyListFlags::from_bits_truncate(src.read_u8());
+        read_padding!(src, 3);
+
+        if total_entries.iter().copied().map(usize::from).sum::<usize>() > PERSISTENT_KEY_LIST_MAX_TOTAL_ENTRIES {
+            return Err(invalid_field_err!("totalEntries", "too many keys"));
+        }
+
+        let entry_count = num_entries.iter().sum::<usize>();
+        if entry_count > PERSISTENT_KEY_LIST_MAX_ENTRIES {
+            tolerate_violation!("numEntries", "too many keys in a single PDU")?;
+        }
+
+        ensure_size!(in: src, size: entry_count * PERSISTENT_KEY_LIST_ENTRY_SIZE);
+
+        let mut keys = <[Vec<u64>; PERSISTENT_KEY_LIST_CELLS]>::default();
+        for (cell_keys, count) in keys.iter_mut().zip(num_entries) {
+            *cell_keys = (0..count)
+                .map(|_| {
+                    let key1 = src.read_u32();
+                    let key2 = src.read_u32();
+                    combine_u64(key1, key2)
+                })
+                .collect();
+        }
+
+        Ok(Self {
+            keys,
+            total_entries,
+            flags,
+        })
+    }
+}
+
 #[repr(u16)]
 #[derive(Debug, Clone, PartialEq, Eq, FromPrimitive, ToPrimitive)]
 pub enum ControlAction {
@@ -243,3 +422,11 @@ bitflags! {
         const LAST = 2;
     }
 }
+
+bitflags! {
+    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    pub struct PersistentKeyListFlags: u8 {
+        const FIRST = 0x01;
+        const LAST = 0x02;
+    }
+}
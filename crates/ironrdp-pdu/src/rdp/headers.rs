@@ -1,3 +1,5 @@
+use alloc::vec::Vec;
+
 use bitflags::bitflags;
 use num_derive::{FromPrimitive, ToPrimitive};
 use num_traits::{FromPrimitive, ToPrimitive};
@@ -6,14 +8,14 @@ use crate::codecs::rfx::FrameAcknowledgePdu;
 use crate::input::InputEventPdu;
 use crate::rdp::capability_sets::{ClientConfirmActive, ServerDemandActive};
 use crate::rdp::client_info;
-use crate::rdp::finalization_messages::{ControlPdu, FontPdu, MonitorLayoutPdu, SynchronizePdu};
+use crate::rdp::finalization_messages::{ControlPdu, FontPdu, MonitorLayoutPdu, PersistentKeyListPdu, SynchronizePdu};
 use crate::rdp::refresh_rectangle::RefreshRectanglePdu;
 use crate::rdp::server_error_info::ServerSetErrorInfoPdu;
 use crate::rdp::session_info::SaveSessionInfoPdu;
 use crate::rdp::suppress_output::SuppressOutputPdu;
 use ironrdp_core::{
-    cast_length, ensure_fixed_part_size, ensure_size, invalid_field_err, not_enough_bytes_err, other_err, ReadCursor,
-    WriteCursor,
+    cast_length, ensure_fixed_part_size, ensure_size, invalid_field_err, not_enough_bytes_err, other_err,
+    tolerate_violation, ReadCursor, WriteCursor,
 };
 use ironrdp_core::{Decode, DecodeResult, Encode, EncodeResult};
 
@@ -146,6 +148,8 @@ impl<'de> Decode<'de> for ShareControlHeader {
                     return Err(not_enough_bytes_err!(total_length, header_length));
                 }
 
+                tolerate_violation!("totalLength", "share control header length does not match its content")?;
+
                 let padding = total_length - header_length;
                 ensure_size!(in: src, size: padding);
                 read_padding!(src, padding);
@@ -167,7 +171,7 @@ pub enum ShareControlPdu {
 impl ShareControlPdu {
     const NAME: &'static str = "ShareControlPdu";
 
-    pub fn as_short_name(&self) -> &str {
+    pub fn as_short_name(&self) -> &'static str {
         match self {
             ShareControlPdu::ServerDemandActive(_) => "Server Demand Active PDU",
             ShareControlPdu::ClientConfirmActive(_) => "Client Confirm Active PDU",
@@ -284,7 +288,7 @@ impl<'de> Decode<'de> for ShareDataHeader {
     fn decode(src: &mut ReadCursor<'de>) -> DecodeResult<Self> {
         ensure_fixed_part_size!(in: src);
 
-        read_padding!(src, 1);
+        read_zeroed_padding!(src, 1)?;
         let stream_priority = StreamPriority::from_u8(src.read_u8())
             .ok_or_else(|| invalid_field_err!("streamPriority", "Invalid stream priority"))?;
         let _uncompressed_length = src.read_u16();
@@ -329,7 +333,7 @@ pub enum ShareDataPdu {
     Pointer(Vec<u8>),
     PlaySound(Vec<u8>),
     SetKeyboardIndicators(Vec<u8>),
-    BitmapCachePersistentList(Vec<u8>),
+    BitmapCachePersistentList(PersistentKeyListPdu),
     BitmapCacheErrorPdu(Vec<u8>),
     SetKeyboardImeStatus(Vec<u8>),
     OffscreenCacheErrorPdu(Vec<u8>),
@@ -342,7 +346,7 @@ pub enum ShareDataPdu {
 impl ShareDataPdu {
     const NAME: &'static str = "ShareDataPdu";
 
-    pub fn as_short_name(&self) -> &str {
+    pub fn as_short_name(&self) -> &'static str {
         match self {
             ShareDataPdu::Synchronize(_) => "Synchronize PDU",
             ShareDataPdu::Control(_) => "Control PDU",
@@ -427,9 +431,9 @@ impl ShareDataPdu {
             ShareDataPduType::SetKeyboardIndicators => {
                 Ok(ShareDataPdu::SetKeyboardIndicators(src.remaining().to_vec()))
             }
-            ShareDataPduType::BitmapCachePersistentList => {
-                Ok(ShareDataPdu::BitmapCachePersistentList(src.remaining().to_vec()))
-            }
+            ShareDataPduType::BitmapCachePersistentList => Ok(ShareDataPdu::BitmapCachePersistentList(
+                PersistentKeyListPdu::decode(src)?,
+            )),
             ShareDataPduType::BitmapCacheErrorPdu => Ok(ShareDataPdu::BitmapCacheErrorPdu(src.remaining().to_vec())),
             ShareDataPduType::SetKeyboardImeStatus => Ok(ShareDataPdu::SetKeyboardImeStatus(src.remaining().to_vec())),
             ShareDataPduType::OffscreenCacheErrorPdu => {
@@ -457,6 +461,7 @@ impl Encode for ShareDataPdu {
             ShareDataPdu::ShutdownRequest | ShareDataPdu::ShutdownDenied => Ok(()),
             ShareDataPdu::SuppressOutput(pdu) => pdu.encode(dst),
             ShareDataPdu::RefreshRectangle(pdu) => pdu.encode(dst),
+            ShareDataPdu::BitmapCachePersistentList(pdu) => pdu.encode(dst),
             _ => Err(other_err!("Encoding not implemented")),
         }
     }
@@ -478,11 +483,11 @@ impl Encode for ShareDataPdu {
             ShareDataPdu::ShutdownRequest | ShareDataPdu::ShutdownDenied => 0,
             ShareDataPdu::SuppressOutput(pdu) => pdu.size(),
             ShareDataPdu::RefreshRectangle(pdu) => pdu.size(),
+            ShareDataPdu::BitmapCachePersistentList(pdu) => pdu.size(),
             ShareDataPdu::Update(buffer)
             | ShareDataPdu::Pointer(buffer)
             | ShareDataPdu::PlaySound(buffer)
             | ShareDataPdu::SetKeyboardIndicators(buffer)
-            | ShareDataPdu::BitmapCachePersistentList(buffer)
             | ShareDataPdu::BitmapCacheErrorPdu(buffer)
             | ShareDataPdu::SetKeyboardImeStatus(buffer)
             | ShareDataPdu::OffscreenCacheErrorPdu(buffer)
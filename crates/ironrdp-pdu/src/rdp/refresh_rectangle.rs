@@ -1,3 +1,5 @@
+use alloc::vec::Vec;
+
 use crate::geometry::InclusiveRectangle;
 use ironrdp_core::{cast_length, ensure_fixed_part_size, ensure_size, ReadCursor, WriteCursor};
 use ironrdp_core::{Decode, DecodeResult, Encode, EncodeResult};
@@ -52,7 +54,7 @@ impl<'de> Decode<'de> for RefreshRectanglePdu {
         ensure_fixed_part_size!(in: src);
 
         let number_of_areas = src.read_u8();
-        read_padding!(src, 3);
+        read_zeroed_padding!(src, 3)?;
         let areas_to_refresh = (0..number_of_areas)
             .map(|_| InclusiveRectangle::decode(src))
             .collect::<Result<Vec<_>, _>>()?;
@@ -1,3 +1,6 @@
+use alloc::format;
+use alloc::string::String;
+
 use num_derive::{FromPrimitive, ToPrimitive};
 use num_traits::{FromPrimitive, ToPrimitive};
 
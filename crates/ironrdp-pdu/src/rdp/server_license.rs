@@ -1,21 +1,21 @@
-use std::io;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
 
 use bitflags::bitflags;
 use md5::Digest;
 use num_derive::{FromPrimitive, ToPrimitive};
 use num_traits::{FromPrimitive, ToPrimitive};
-use thiserror::Error;
 
 use crate::rdp::headers::{BasicSecurityHeader, BasicSecurityHeaderFlags, BASIC_SECURITY_HEADER_SIZE};
 use crate::PduError;
-use ironrdp_core::{
-    cast_length, ensure_fixed_part_size, invalid_field_err, unsupported_value_err, ReadCursor, WriteCursor,
-};
+use ironrdp_core::{cast_length, ensure_fixed_part_size, invalid_field_err, ReadCursor, WriteCursor};
 use ironrdp_core::{Decode, DecodeResult, Encode, EncodeResult};
 
 #[cfg(test)]
 mod tests;
 
+mod client_license_info;
 mod client_new_license_request;
 mod client_platform_challenge_response;
 mod licensing_error_message;
@@ -23,6 +23,7 @@ mod server_license_request;
 mod server_platform_challenge;
 mod server_upgrade_license;
 
+pub use self::client_license_info::ClientLicenseInfo;
 pub use self::client_new_license_request::{ClientNewLicenseRequest, PLATFORM_ID};
 pub use self::client_platform_challenge_response::{
     ClientHardwareIdentification, ClientPlatformChallengeResponse, PlatformChallengeResponseData,
@@ -176,85 +177,139 @@ impl BlobType {
     pub const CLIENT_MACHINE_NAME_BLOB: Self = Self(0x10);
 }
 
-#[derive(Debug, Error)]
+#[derive(Debug)]
 pub enum ServerLicenseError {
-    #[error("IO error: {0}")]
-    IOError(#[from] io::Error),
-    #[error("UTF-8 error: {0}")]
-    Utf8Error(#[from] std::string::FromUtf8Error),
-    #[error("invalid preamble field: {0}")]
+    #[cfg(feature = "std")]
+    IOError(std::io::Error),
+    Utf8Error(alloc::string::FromUtf8Error),
     InvalidPreamble(String),
-    #[error("invalid preamble message type field")]
     InvalidLicenseType,
-    #[error("invalid error code field")]
     InvalidErrorCode,
-    #[error("invalid state transition field")]
     InvalidStateTransition,
-    #[error("invalid blob type field")]
     InvalidBlobType,
-    #[error("unable to generate random number {0}")]
     RandomNumberGenerationError(String),
-    #[error("unable to retrieve public key from the certificate")]
     UnableToGetPublicKey,
-    #[error("unable to encrypt RSA public key")]
+    InvalidPublicKey(String),
     RsaKeyEncryptionError,
-    #[error("invalid License Request key exchange algorithm value")]
     InvalidKeyExchangeValue,
-    #[error("MAC checksum generated over decrypted data does not match the server's checksum")]
     InvalidMacData,
-    #[error("invalid platform challenge response data version")]
     InvalidChallengeResponseDataVersion,
-    #[error("invalid platform challenge response data client type")]
     InvalidChallengeResponseDataClientType,
-    #[error("invalid platform challenge response data license detail level")]
     InvalidChallengeResponseDataLicenseDetail,
-    #[error("invalid x509 certificate")]
+    InvalidPlatformChallengeResponse,
     InvalidX509Certificate {
         source: x509_cert::der::Error,
         cert_der: Vec<u8>,
     },
-    #[error("invalid certificate version")]
     InvalidCertificateVersion,
-    #[error("invalid x509 certificates amount")]
     InvalidX509CertificatesAmount,
-    #[error("invalid proprietary certificate signature algorithm ID")]
     InvalidPropCertSignatureAlgorithmId,
-    #[error("invalid proprietary certificate key algorithm ID")]
     InvalidPropCertKeyAlgorithmId,
-    #[error("invalid RSA public key magic")]
     InvalidRsaPublicKeyMagic,
-    #[error("invalid RSA public key length")]
     InvalidRsaPublicKeyLength,
-    #[error("invalid RSA public key data length")]
     InvalidRsaPublicKeyDataLength,
-    #[error("invalid RSA public key bit length")]
     InvalidRsaPublicKeyBitLength,
-    #[error("invalid License Header security flags")]
     InvalidSecurityFlags,
-    #[error("the server returned unexpected error: {0:?}")]
     UnexpectedError(LicensingErrorMessage),
-    #[error("got unexpected license message")]
     UnexpectedLicenseMessage,
-    #[error("the server has returned an unexpected error")]
     UnexpectedServerError(LicensingErrorMessage),
-    #[error("the server has returned STATUS_VALID_CLIENT (not an error)")]
     ValidClientStatus(LicensingErrorMessage),
-    #[error("invalid Key Exchange List field")]
     InvalidKeyExchangeAlgorithm,
-    #[error("received invalid company name length (Product Information): {0}")]
     InvalidCompanyNameLength(u32),
-    #[error("received invalid product ID length (Product Information): {0}")]
     InvalidProductIdLength(u32),
-    #[error("received invalid scope count field: {0}")]
     InvalidScopeCount(u32),
-    #[error("received invalid certificate length: {0}")]
     InvalidCertificateLength(u32),
-    #[error("blob too small")]
     BlobTooSmall,
-    #[error("PDU error: {0}")]
     Pdu(PduError),
 }
 
+impl fmt::Display for ServerLicenseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            #[cfg(feature = "std")]
+            Self::IOError(value) => write!(f, "IO error: {value}"),
+            Self::Utf8Error(value) => write!(f, "UTF-8 error: {value}"),
+            Self::InvalidPreamble(value) => write!(f, "invalid preamble field: {value}"),
+            Self::InvalidLicenseType => f.write_str("invalid preamble message type field"),
+            Self::InvalidErrorCode => f.write_str("invalid error code field"),
+            Self::InvalidStateTransition => f.write_str("invalid state transition field"),
+            Self::InvalidBlobType => f.write_str("invalid blob type field"),
+            Self::RandomNumberGenerationError(value) => write!(f, "unable to generate random number {value}"),
+            Self::UnableToGetPublicKey => f.write_str("unable to retrieve public key from the certificate"),
+            Self::InvalidPublicKey(value) => write!(f, "invalid server public key: {value}"),
+            Self::RsaKeyEncryptionError => f.write_str("unable to encrypt RSA public key"),
+            Self::InvalidKeyExchangeValue => f.write_str("invalid License Request key exchange algorithm value"),
+            Self::InvalidMacData => {
+                f.write_str("MAC checksum generated over decrypted data does not match the server's checksum")
+            }
+            Self::InvalidChallengeResponseDataVersion => {
+                f.write_str("invalid platform challenge response data version")
+            }
+            Self::InvalidChallengeResponseDataClientType => {
+                f.write_str("invalid platform challenge response data client type")
+            }
+            Self::InvalidChallengeResponseDataLicenseDetail => {
+                f.write_str("invalid platform challenge response data license detail level")
+            }
+            Self::InvalidPlatformChallengeResponse => {
+                f.write_str("the platform challenge response does not match the challenge")
+            }
+            Self::InvalidX509Certificate { .. } => f.write_str("invalid x509 certificate"),
+            Self::InvalidCertificateVersion => f.write_str("invalid certificate version"),
+            Self::InvalidX509CertificatesAmount => f.write_str("invalid x509 certificates amount"),
+            Self::InvalidPropCertSignatureAlgorithmId => {
+                f.write_str("invalid proprietary certificate signature algorithm ID")
+            }
+            Self::InvalidPropCertKeyAlgorithmId => f.write_str("invalid proprietary certificate key algorithm ID"),
+            Self::InvalidRsaPublicKeyMagic => f.write_str("invalid RSA public key magic"),
+            Self::InvalidRsaPublicKeyLength => f.write_str("invalid RSA public key length"),
+            Self::InvalidRsaPublicKeyDataLength => f.write_str("invalid RSA public key data length"),
+            Self::InvalidRsaPublicKeyBitLength => f.write_str("invalid RSA public key bit length"),
+            Self::InvalidSecurityFlags => f.write_str("invalid License Header security flags"),
+            Self::UnexpectedError(value) => write!(f, "the server returned unexpected error: {value:?}"),
+            Self::UnexpectedLicenseMessage => f.write_str("got unexpected license message"),
+            Self::UnexpectedServerError(_) => f.write_str("the server has returned an unexpected error"),
+            Self::ValidClientStatus(_) => f.write_str("the server has returned STATUS_VALID_CLIENT (not an error)"),
+            Self::InvalidKeyExchangeAlgorithm => f.write_str("invalid Key Exchange List field"),
+            Self::InvalidCompanyNameLength(value) => {
+                write!(f, "received invalid company name length (Product Information): {value}")
+            }
+            Self::InvalidProductIdLength(value) => {
+                write!(f, "received invalid product ID length (Product Information): {value}")
+            }
+            Self::InvalidScopeCount(value) => write!(f, "received invalid scope count field: {value}"),
+            Self::InvalidCertificateLength(value) => write!(f, "received invalid certificate length: {value}"),
+            Self::BlobTooSmall => f.write_str("blob too small"),
+            Self::Pdu(value) => write!(f, "PDU error: {value}"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ServerLicenseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::IOError(source) => Some(source),
+            Self::Utf8Error(source) => Some(source),
+            Self::InvalidX509Certificate { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<std::io::Error> for ServerLicenseError {
+    fn from(e: std::io::Error) -> Self {
+        Self::IOError(e)
+    }
+}
+
+impl From<alloc::string::FromUtf8Error> for ServerLicenseError {
+    fn from(e: alloc::string::FromUtf8Error) -> Self {
+        Self::Utf8Error(e)
+    }
+}
+
 impl From<PduError> for ServerLicenseError {
     fn from(e: PduError) -> Self {
         Self::Pdu(e)
@@ -340,6 +395,7 @@ fn compute_mac_data(mac_salt_key: &[u8], data: &[u8]) -> Vec<u8> {
 
 #[derive(Debug, PartialEq)]
 pub enum LicensePdu {
+    ClientLicenseInfo(ClientLicenseInfo),
     ClientNewLicenseRequest(ClientNewLicenseRequest),
     ClientPlatformChallengeResponse(ClientPlatformChallengeResponse),
     ServerLicenseRequest(ServerLicenseRequest),
@@ -358,10 +414,7 @@ impl<'de> Decode<'de> for LicensePdu {
             PreambleType::NewLicense | PreambleType::UpgradeLicense => {
                 Ok(ServerUpgradeLicense::decode(license_header, src)?.into())
             }
-            PreambleType::LicenseInfo => Err(unsupported_value_err!(
-                "LicensePdu::LicenseInfo",
-                "LicenseInfo is not supported".to_owned()
-            )),
+            PreambleType::LicenseInfo => Ok(ClientLicenseInfo::decode(license_header, src)?.into()),
             PreambleType::NewLicenseRequest => Ok(ClientNewLicenseRequest::decode(license_header, src)?.into()),
             PreambleType::PlatformChallengeResponse => {
                 Ok(ClientPlatformChallengeResponse::decode(license_header, src)?.into())
@@ -374,6 +427,7 @@ impl<'de> Decode<'de> for LicensePdu {
 impl Encode for LicensePdu {
     fn encode(&self, dst: &mut WriteCursor<'_>) -> EncodeResult<()> {
         match self {
+            Self::ClientLicenseInfo(ref pdu) => pdu.encode(dst),
             Self::ClientNewLicenseRequest(ref pdu) => pdu.encode(dst),
             Self::ClientPlatformChallengeResponse(ref pdu) => pdu.encode(dst),
             Self::ServerLicenseRequest(ref pdu) => pdu.encode(dst),
@@ -385,6 +439,7 @@ impl Encode for LicensePdu {
 
     fn name(&self) -> &'static str {
         match self {
+            Self::ClientLicenseInfo(pdu) => pdu.name(),
             Self::ClientNewLicenseRequest(pdu) => pdu.name(),
             Self::ClientPlatformChallengeResponse(pdu) => pdu.name(),
             Self::ServerLicenseRequest(pdu) => pdu.name(),
@@ -396,6 +451,7 @@ impl Encode for LicensePdu {
 
     fn size(&self) -> usize {
         match self {
+            Self::ClientLicenseInfo(pdu) => pdu.size(),
             Self::ClientNewLicenseRequest(pdu) => pdu.size(),
             Self::ClientPlatformChallengeResponse(pdu) => pdu.size(),
             Self::ServerLicenseRequest(pdu) => pdu.size(),
@@ -406,6 +462,12 @@ impl Encode for LicensePdu {
     }
 }
 
+impl From<ClientLicenseInfo> for LicensePdu {
+    fn from(pdu: ClientLicenseInfo) -> Self {
+        Self::ClientLicenseInfo(pdu)
+    }
+}
+
 impl From<ClientNewLicenseRequest> for LicensePdu {
     fn from(pdu: ClientNewLicenseRequest) -> Self {
         Self::ClientNewLicenseRequest(pdu)
@@ -0,0 +1,193 @@
+#[cfg(test)]
+mod tests;
+
+use alloc::vec::Vec;
+
+use super::client_new_license_request::{compute_encryption_data, decrypt_encryption_data};
+use super::client_platform_challenge_response::compute_hardware_id;
+use super::{
+    BasicSecurityHeader, BasicSecurityHeaderFlags, BlobHeader, BlobType, LicenseEncryptionData, LicenseHeader,
+    PreambleFlags, PreambleType, PreambleVersion, ServerLicenseError, ServerLicenseRequest, KEY_EXCHANGE_ALGORITHM_RSA,
+    MAC_SIZE, PLATFORM_ID, PREAMBLE_SIZE, RANDOM_NUMBER_SIZE,
+};
+use crate::crypto::rc4::Rc4;
+use crate::rdp::standard_security::RsaPrivateKey;
+use ironrdp_core::{ensure_size, invalid_field_err, ReadCursor, WriteCursor};
+use ironrdp_core::{Decode, DecodeResult, Encode, EncodeResult};
+
+const LICENSE_INFO_STATIC_FIELDS_SIZE: usize = 20;
+
+/// Client License Information (CLIENT_LICENSE_INFO), MS-RDPELE section 2.2.2.3
+///
+/// Sent instead of a [`ClientNewLicenseRequest`] to present a license previously issued to the client.
+///
+/// [`ClientNewLicenseRequest`]: super::ClientNewLicenseRequest
+#[derive(Debug, PartialEq, Eq)]
+pub struct ClientLicenseInfo {
+    pub license_header: LicenseHeader,
+    pub client_random: Vec<u8>,
+    pub encrypted_premaster_secret: Vec<u8>,
+    /// The license, as found in [`NewLicenseInformation::license_info`]
+    ///
+    /// [`NewLicenseInformation::license_info`]: super::NewLicenseInformation::license_info
+    pub license_info: Vec<u8>,
+    pub encrypted_hwid: Vec<u8>,
+    pub mac_data: Vec<u8>,
+}
+
+impl ClientLicenseInfo {
+    const NAME: &'static str = "ClientLicenseInfo";
+
+    /// The hardware identification is derived from `hostname`, like when the license was issued
+    pub fn from_server_license_request(
+        license_request: &ServerLicenseRequest,
+        client_random: &[u8],
+        premaster_secret: &[u8],
+        license_info: &[u8],
+        hostname: &str,
+    ) -> Result<(Self, LicenseEncryptionData), ServerLicenseError> {
+        let (encrypted_premaster_secret, encryption_data) =
+            compute_encryption_data(license_request, client_random, premaster_secret)?;
+
+        let hardware_id = compute_hardware_id(hostname);
+
+        let mut rc4 = Rc4::new(&encryption_data.license_key);
+        let encrypted_hwid = rc4.process(&hardware_id);
+
+        let mac_data = super::compute_mac_data(encryption_data.mac_salt_key.as_slice(), &hardware_id);
+
+        let license_header = LicenseHeader {
+            security_header: BasicSecurityHeader {
+                flags: BasicSecurityHeaderFlags::LICENSE_PKT,
+            },
+            preamble_message_type: PreambleType::LicenseInfo,
+            preamble_flags: PreambleFlags::empty(),
+            preamble_version: PreambleVersion::V3,
+            preamble_message_size: (PREAMBLE_SIZE
+                + LICENSE_INFO_STATIC_FIELDS_SIZE
+                + RANDOM_NUMBER_SIZE
+                + encrypted_premaster_secret.len()
+                + license_info.len()
+                + encrypted_hwid.len()
+                + MAC_SIZE) as u16,
+        };
+
+        Ok((
+            Self {
+                license_header,
+                client_random: Vec::from(client_random),
+                encrypted_premaster_secret,
+                license_info: Vec::from(license_info),
+                encrypted_hwid,
+                mac_data,
+            },
+            encryption_data,
+        ))
+    }
+
+    /// Server side: decrypts the premaster secret with the key of the server certificate, and derives the licensing
+    /// keys from it
+    pub fn encryption_data(&self, server_random: &[u8], server_key: &RsaPrivateKey) -> LicenseEncryptionData {
+        decrypt_encryption_data(
+            &self.encrypted_premaster_secret,
+            &self.client_random,
+            server_random,
+            server_key,
+        )
+    }
+
+    /// Server side: decrypts the hardware identification of the client, the license being bound to it
+    pub fn hardware_id(&self, encryption_data: &LicenseEncryptionData) -> Result<Vec<u8>, ServerLicenseError> {
+        let mut rc4 = Rc4::new(&encryption_data.license_key);
+        let hardware_id = rc4.process(&self.encrypted_hwid);
+
+        let mac_data = super::compute_mac_data(encryption_data.mac_salt_key.as_slice(), &hardware_id);
+        if mac_data != self.mac_data {
+            return Err(ServerLicenseError::InvalidMacData);
+        }
+
+        Ok(hardware_id)
+    }
+}
+
+impl ClientLicenseInfo {
+    pub fn encode(&self, dst: &mut WriteCursor<'_>) -> EncodeResult<()> {
+        ensure_size!(in: dst, size: self.size());
+
+        self.license_header.encode(dst)?;
+
+        dst.write_u32(KEY_EXCHANGE_ALGORITHM_RSA);
+        dst.write_u32(PLATFORM_ID);
+        dst.write_slice(&self.client_random);
+
+        BlobHeader::new(BlobType::RANDOM, self.encrypted_premaster_secret.len()).encode(dst)?;
+        dst.write_slice(&self.encrypted_premaster_secret);
+
+        BlobHeader::new(BlobType::DATA, self.license_info.len()).encode(dst)?;
+        dst.write_slice(&self.license_info);
+
+        BlobHeader::new(BlobType::ENCRYPTED_DATA, self.encrypted_hwid.len()).encode(dst)?;
+        dst.write_slice(&self.encrypted_hwid);
+
+        dst.write_slice(&self.mac_data);
+
+        Ok(())
+    }
+
+    pub fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    pub fn size(&self) -> usize {
+        self.license_header.size()
+            + LICENSE_INFO_STATIC_FIELDS_SIZE
+            + RANDOM_NUMBER_SIZE
+            + self.encrypted_premaster_secret.len()
+            + self.license_info.len()
+            + self.encrypted_hwid.len()
+            + MAC_SIZE
+    }
+}
+
+impl ClientLicenseInfo {
+    pub fn decode(license_header: LicenseHeader, src: &mut ReadCursor<'_>) -> DecodeResult<Self> {
+        if license_header.preamble_message_type != PreambleType::LicenseInfo {
+            return Err(invalid_field_err!("preambleMessageType", "unexpected preamble type"));
+        }
+
+        ensure_size!(in: src, size: LICENSE_INFO_STATIC_FIELDS_SIZE + RANDOM_NUMBER_SIZE);
+        let key_exchange_algorithm = src.read_u32();
+        if key_exchange_algorithm != KEY_EXCHANGE_ALGORITHM_RSA {
+            return Err(invalid_field_err!("keyExchangeAlgo", "invalid key exchange algorithm"));
+        }
+
+        let _platform_id = src.read_u32();
+        let client_random = src.read_slice(RANDOM_NUMBER_SIZE).into();
+
+        let encrypted_premaster_secret = decode_blob(src, BlobType::RANDOM)?;
+        let license_info = decode_blob(src, BlobType::DATA)?;
+        let encrypted_hwid = decode_blob(src, BlobType::ENCRYPTED_DATA)?;
+
+        ensure_size!(in: src, size: MAC_SIZE);
+        let mac_data = src.read_slice(MAC_SIZE).into();
+
+        Ok(Self {
+            license_header,
+            client_random,
+            encrypted_premaster_secret,
+            license_info,
+            encrypted_hwid,
+            mac_data,
+        })
+    }
+}
+
+fn decode_blob(src: &mut ReadCursor<'_>, expected_type: BlobType) -> DecodeResult<Vec<u8>> {
+    let blob_header = BlobHeader::decode(src)?;
+    if blob_header.blob_type != expected_type {
+        return Err(invalid_field_err!("blobType", "invalid blob type"));
+    }
+
+    ensure_size!(in: src, size: blob_header.length);
+    Ok(src.read_slice(blob_header.length).into())
+}
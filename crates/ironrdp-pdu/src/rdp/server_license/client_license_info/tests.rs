@@ -0,0 +1,64 @@
+use lazy_static::lazy_static;
+
+use super::*;
+use crate::rdp::server_license::LicensePdu;
+use ironrdp_core::{decode, encode_vec};
+
+const ENCRYPTED_PREMASTER_SECRET_SIZE: usize = 72;
+const LICENSE_INFO: [u8; 6] = [0x30, 0x82, 0x01, 0x0a, 0x02, 0x82];
+const ENCRYPTED_HWID_SIZE: usize = 20;
+
+lazy_static! {
+    pub static ref CLIENT_LICENSE_INFO: LicensePdu = ClientLicenseInfo {
+        license_header: LicenseHeader {
+            security_header: BasicSecurityHeader {
+                flags: BasicSecurityHeaderFlags::LICENSE_PKT,
+            },
+            preamble_message_type: PreambleType::LicenseInfo,
+            preamble_flags: PreambleFlags::empty(),
+            preamble_version: PreambleVersion::V3,
+            preamble_message_size: (PREAMBLE_SIZE
+                + LICENSE_INFO_STATIC_FIELDS_SIZE
+                + RANDOM_NUMBER_SIZE
+                + ENCRYPTED_PREMASTER_SECRET_SIZE
+                + LICENSE_INFO.len()
+                + ENCRYPTED_HWID_SIZE
+                + MAC_SIZE) as u16,
+        },
+        client_random: vec![0x33; RANDOM_NUMBER_SIZE],
+        encrypted_premaster_secret: vec![0x11; ENCRYPTED_PREMASTER_SECRET_SIZE],
+        license_info: Vec::from(LICENSE_INFO.as_ref()),
+        encrypted_hwid: vec![0x22; ENCRYPTED_HWID_SIZE],
+        mac_data: vec![0x44; MAC_SIZE],
+    }
+    .into();
+}
+
+#[test]
+fn client_license_info_round_trips() {
+    let buffer = encode_vec(&*CLIENT_LICENSE_INFO).unwrap();
+
+    assert_eq!(buffer.len(), CLIENT_LICENSE_INFO.size());
+    assert_eq!(*CLIENT_LICENSE_INFO, decode(&buffer).unwrap());
+}
+
+#[test]
+fn client_license_info_blobs_are_in_order() {
+    let buffer = encode_vec(&*CLIENT_LICENSE_INFO).unwrap();
+
+    // preamble: LICENSE_INFO, version 3
+    assert_eq!(buffer[4..6], [0x12, 0x03]);
+
+    let premaster_secret_blob = 8 + 8 + RANDOM_NUMBER_SIZE;
+    assert_eq!(
+        buffer[premaster_secret_blob..premaster_secret_blob + 4],
+        [0x02, 0x00, 72, 0x00]
+    );
+
+    let license_info_blob = premaster_secret_blob + 4 + ENCRYPTED_PREMASTER_SECRET_SIZE;
+    assert_eq!(buffer[license_info_blob..license_info_blob + 4], [0x01, 0x00, 6, 0x00]);
+    assert_eq!(buffer[license_info_blob + 4..license_info_blob + 10], LICENSE_INFO);
+
+    let hwid_blob = license_info_blob + 4 + LICENSE_INFO.len();
+    assert_eq!(buffer[hwid_blob..hwid_blob + 4], [0x09, 0x00, 20, 0x00]);
+}
@@ -1,7 +1,9 @@
 #[cfg(test)]
 mod tests;
 
-use std::io;
+use alloc::borrow::ToOwned;
+use alloc::string::String;
+use alloc::vec::Vec;
 
 use bitflags::bitflags;
 use md5::Digest;
@@ -9,9 +11,10 @@ use md5::Digest;
 use super::{
     BasicSecurityHeader, BasicSecurityHeaderFlags, BlobHeader, BlobType, LicenseEncryptionData, LicenseHeader,
     PreambleFlags, PreambleType, PreambleVersion, ServerLicenseError, ServerLicenseRequest, KEY_EXCHANGE_ALGORITHM_RSA,
-    PREAMBLE_SIZE, RANDOM_NUMBER_SIZE, UTF8_NULL_TERMINATOR_SIZE,
+    PREAMBLE_SIZE, PREMASTER_SECRET_SIZE, RANDOM_NUMBER_SIZE, UTF8_NULL_TERMINATOR_SIZE,
 };
 use crate::crypto::rsa::encrypt_with_public_key;
+use crate::rdp::standard_security::RsaPrivateKey;
 use crate::utils::{self, CharacterSet};
 use ironrdp_core::{ensure_size, invalid_field_err, ReadCursor, WriteCursor};
 use ironrdp_core::{Decode, DecodeResult, Encode, EncodeResult};
@@ -60,35 +63,8 @@ impl ClientNewLicenseRequest {
         client_username: &str,
         client_machine_name: &str,
     ) -> Result<(Self, LicenseEncryptionData), ServerLicenseError> {
-        let public_key = license_request.get_public_key()?
-            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData,
-                "attempted to retrieve the server public key from a server license request message that does not have a certificate"))?;
-
-        let encrypted_premaster_secret = encrypt_with_public_key(premaster_secret, &public_key)?;
-
-        let master_secret = compute_master_secret(
-            premaster_secret,
-            client_random,
-            license_request.server_random.as_slice(),
-        );
-        let session_key_blob = compute_session_key_blob(
-            master_secret.as_slice(),
-            client_random,
-            license_request.server_random.as_slice(),
-        );
-        let mac_salt_key = &session_key_blob[..16];
-
-        let mut md5 = md5::Md5::new();
-        md5.update(
-            [
-                &session_key_blob[16..32],
-                client_random,
-                license_request.server_random.as_slice(),
-            ]
-            .concat()
-            .as_slice(),
-        );
-        let license_key = md5.finalize().to_vec();
+        let (encrypted_premaster_secret, encryption_data) =
+            compute_encryption_data(license_request, client_random, premaster_secret)?;
 
         let license_header = LicenseHeader {
             security_header: BasicSecurityHeader {
@@ -115,13 +91,79 @@ impl ClientNewLicenseRequest {
                 client_username: client_username.to_owned(),
                 client_machine_name: client_machine_name.to_owned(),
             },
-            LicenseEncryptionData {
-                premaster_secret: Vec::from(premaster_secret),
-                mac_salt_key: Vec::from(mac_salt_key),
-                license_key,
-            },
+            encryption_data,
         ))
     }
+
+    /// Server side: decrypts the premaster secret with the key of the server certificate, and derives the licensing
+    /// keys from it
+    pub fn encryption_data(&self, server_random: &[u8], server_key: &RsaPrivateKey) -> LicenseEncryptionData {
+        decrypt_encryption_data(
+            &self.encrypted_premaster_secret,
+            &self.client_random,
+            server_random,
+            server_key,
+        )
+    }
+}
+
+/// Encrypts the premaster secret with the public key of the server, and derives the licensing keys from it
+pub(super) fn compute_encryption_data(
+    license_request: &ServerLicenseRequest,
+    client_random: &[u8],
+    premaster_secret: &[u8],
+) -> Result<(Vec<u8>, LicenseEncryptionData), ServerLicenseError> {
+    // The server license request message may not have a certificate.
+    let public_key = license_request
+        .get_public_key()?
+        .ok_or(ServerLicenseError::UnableToGetPublicKey)?;
+
+    let encrypted_premaster_secret = encrypt_with_public_key(premaster_secret, &public_key)?;
+
+    let encryption_data = derive_encryption_data(
+        premaster_secret,
+        client_random,
+        license_request.server_random.as_slice(),
+    );
+
+    Ok((encrypted_premaster_secret, encryption_data))
+}
+
+/// Decrypts the premaster secret sent by the client, and derives the licensing keys from it
+pub(super) fn decrypt_encryption_data(
+    encrypted_premaster_secret: &[u8],
+    client_random: &[u8],
+    server_random: &[u8],
+    server_key: &RsaPrivateKey,
+) -> LicenseEncryptionData {
+    let mut premaster_secret = server_key.decrypt(encrypted_premaster_secret);
+    premaster_secret.truncate(PREMASTER_SECRET_SIZE);
+
+    derive_encryption_data(&premaster_secret, client_random, server_random)
+}
+
+fn derive_encryption_data(
+    premaster_secret: &[u8],
+    client_random: &[u8],
+    server_random: &[u8],
+) -> LicenseEncryptionData {
+    let master_secret = compute_master_secret(premaster_secret, client_random, server_random);
+    let session_key_blob = compute_session_key_blob(master_secret.as_slice(), client_random, server_random);
+    let mac_salt_key = &session_key_blob[..16];
+
+    let mut md5 = md5::Md5::new();
+    md5.update(
+        [&session_key_blob[16..32], client_random, server_random]
+            .concat()
+            .as_slice(),
+    );
+    let license_key = md5.finalize().to_vec();
+
+    LicenseEncryptionData {
+        premaster_secret: Vec::from(premaster_secret),
+        mac_salt_key: Vec::from(mac_salt_key),
+        license_key,
+    }
 }
 
 impl ClientNewLicenseRequest {
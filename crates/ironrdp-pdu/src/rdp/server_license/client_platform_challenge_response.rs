@@ -1,9 +1,9 @@
 #[cfg(test)]
 mod test;
 
-use std::io::Write;
+use alloc::vec;
+use alloc::vec::Vec;
 
-use byteorder::{LittleEndian, WriteBytesExt as _};
 use md5::Digest;
 use num_derive::{FromPrimitive, ToPrimitive};
 use num_traits::{FromPrimitive as _, ToPrimitive as _};
@@ -52,19 +52,13 @@ impl ClientPlatformChallengeResponse {
         }
 
         let mut challenge_response_data = vec![0u8; RESPONSE_DATA_STATIC_FIELDS_SIZE];
-        challenge_response_data.write_u16::<LittleEndian>(RESPONSE_DATA_VERSION)?;
-        challenge_response_data.write_u16::<LittleEndian>(ClientType::Other.to_u16().unwrap())?;
-        challenge_response_data.write_u16::<LittleEndian>(LicenseDetailLevel::Detail.to_u16().unwrap())?;
-        challenge_response_data.write_u16::<LittleEndian>(decrypted_challenge.len() as u16)?;
-        challenge_response_data.write_all(&decrypted_challenge)?;
+        challenge_response_data.extend_from_slice(&RESPONSE_DATA_VERSION.to_le_bytes());
+        challenge_response_data.extend_from_slice(&ClientType::Other.to_u16().unwrap().to_le_bytes());
+        challenge_response_data.extend_from_slice(&LicenseDetailLevel::Detail.to_u16().unwrap().to_le_bytes());
+        challenge_response_data.extend_from_slice(&(decrypted_challenge.len() as u16).to_le_bytes());
+        challenge_response_data.extend_from_slice(&decrypted_challenge);
 
-        let mut hardware_id = Vec::with_capacity(CLIENT_HARDWARE_IDENTIFICATION_SIZE);
-        let mut md5 = md5::Md5::new();
-        md5.update(hostname.as_bytes());
-        let hardware_data = &md5.finalize();
-
-        hardware_id.write_u32::<LittleEndian>(PLATFORM_ID)?;
-        hardware_id.write_all(hardware_data)?;
+        let hardware_id = compute_hardware_id(hostname);
 
         let mut rc4 = Rc4::new(&encryption_data.license_key);
         let encrypted_hwid = rc4.process(&hardware_id);
@@ -100,6 +94,34 @@ impl ClientPlatformChallengeResponse {
     }
 }
 
+impl ClientPlatformChallengeResponse {
+    /// Server side: checks the response to `challenge`, and returns the decrypted hardware identification of the client
+    pub fn verify(
+        &self,
+        challenge: &[u8],
+        encryption_data: &LicenseEncryptionData,
+    ) -> Result<Vec<u8>, ServerLicenseError> {
+        let mut rc4 = Rc4::new(&encryption_data.license_key);
+        let mut challenge_response_data = rc4.process(&self.encrypted_challenge_response_data);
+
+        let mut rc4 = Rc4::new(&encryption_data.license_key);
+        let hardware_id = rc4.process(&self.encrypted_hwid);
+
+        // The challenge is echoed at the end of the response data.
+        if !challenge_response_data.ends_with(challenge) {
+            return Err(ServerLicenseError::InvalidPlatformChallengeResponse);
+        }
+
+        challenge_response_data.extend_from_slice(&hardware_id);
+        let mac_data = super::compute_mac_data(encryption_data.mac_salt_key.as_slice(), &challenge_response_data);
+        if mac_data != self.mac_data {
+            return Err(ServerLicenseError::InvalidMacData);
+        }
+
+        Ok(hardware_id)
+    }
+}
+
 impl ClientPlatformChallengeResponse {
     pub fn encode(&self, dst: &mut WriteCursor<'_>) -> EncodeResult<()> {
         ensure_size!(in: dst, size: self.size());
@@ -163,6 +185,21 @@ impl ClientPlatformChallengeResponse {
     }
 }
 
+/// Returns the encoded [`ClientHardwareIdentification`] of the client, derived from `hostname`
+///
+/// The server binds the licenses it issues to this identifier, which must hence be stable across connections.
+pub(super) fn compute_hardware_id(hostname: &str) -> Vec<u8> {
+    let mut hardware_id = Vec::with_capacity(CLIENT_HARDWARE_IDENTIFICATION_SIZE);
+    let mut md5 = md5::Md5::new();
+    md5.update(hostname.as_bytes());
+    let hardware_data = &md5.finalize();
+
+    hardware_id.extend_from_slice(&PLATFORM_ID.to_le_bytes());
+    hardware_id.extend_from_slice(hardware_data);
+
+    hardware_id
+}
+
 #[derive(Debug, PartialEq, FromPrimitive, ToPrimitive)]
 pub enum ClientType {
     Win32 = 0x0100,
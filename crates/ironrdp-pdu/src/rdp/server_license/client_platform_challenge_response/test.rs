@@ -189,8 +189,8 @@ fn challenge_response_creates_from_server_challenge_and_encryption_data_correctl
     md5.update(b"sample-hostname");
     let hardware_data = &md5.finalize();
 
-    hardware_id.write_u32::<LittleEndian>(PLATFORM_ID).unwrap();
-    hardware_id.write_all(hardware_data).unwrap();
+    hardware_id.extend_from_slice(&PLATFORM_ID.to_le_bytes());
+    hardware_id.extend_from_slice(hardware_data);
 
     let mut rc4 = Rc4::new(&encryption_data.license_key);
     let encrypted_hwid = rc4.process(&hardware_id);
@@ -1,6 +1,9 @@
 #[cfg(test)]
 mod test;
 
+use alloc::vec;
+use alloc::vec::Vec;
+
 use num_derive::{FromPrimitive, ToPrimitive};
 use num_traits::{FromPrimitive, ToPrimitive};
 
@@ -32,6 +35,10 @@ impl LicensingErrorMessage {
     const FIXED_PART_SIZE: usize = ERROR_CODE_SIZE + STATE_TRANSITION_SIZE;
 
     pub fn new_valid_client() -> EncodeResult<Self> {
+        Self::new(LicenseErrorCode::StatusValidClient, LicensingStateTransition::NoTransition)
+    }
+
+    pub fn new(error_code: LicenseErrorCode, state_transition: LicensingStateTransition) -> EncodeResult<Self> {
         let mut this = Self {
             license_header: LicenseHeader {
                 security_header: BasicSecurityHeader {
@@ -42,8 +49,8 @@ impl LicensingErrorMessage {
                 preamble_version: PreambleVersion::V3,
                 preamble_message_size: 0,
             },
-            error_code: LicenseErrorCode::StatusValidClient,
-            state_transition: LicensingStateTransition::NoTransition,
+            error_code,
+            state_transition,
             error_info: Vec::new(),
         };
         this.license_header.preamble_message_size = cast_length!(
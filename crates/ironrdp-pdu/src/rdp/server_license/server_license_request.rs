@@ -3,12 +3,18 @@ pub mod cert;
 #[cfg(test)]
 mod tests;
 
+use alloc::borrow::ToOwned;
+use alloc::string::String;
+use alloc::vec::Vec;
+
 use cert::{CertificateType, ProprietaryCertificate, X509CertificateChain};
 
 use super::{
-    BlobHeader, BlobType, LicenseHeader, PreambleType, ServerLicenseError, BLOB_LENGTH_SIZE, BLOB_TYPE_SIZE,
-    KEY_EXCHANGE_ALGORITHM_RSA, RANDOM_NUMBER_SIZE, UTF16_NULL_TERMINATOR_SIZE, UTF8_NULL_TERMINATOR_SIZE,
+    BasicSecurityHeader, BasicSecurityHeaderFlags, BlobHeader, BlobType, LicenseHeader, PreambleFlags, PreambleType,
+    PreambleVersion, ServerLicenseError, BLOB_LENGTH_SIZE, BLOB_TYPE_SIZE, KEY_EXCHANGE_ALGORITHM_RSA,
+    RANDOM_NUMBER_SIZE, UTF16_NULL_TERMINATOR_SIZE, UTF8_NULL_TERMINATOR_SIZE,
 };
+use crate::rdp::headers::BASIC_SECURITY_HEADER_SIZE;
 use crate::utils;
 use ironrdp_core::{cast_length, ensure_fixed_part_size, ensure_size, invalid_field_err, ReadCursor, WriteCursor};
 use ironrdp_core::{Decode, DecodeResult, Encode, EncodeResult};
@@ -40,6 +46,36 @@ pub struct ServerLicenseRequest {
 impl ServerLicenseRequest {
     const NAME: &'static str = "ServerLicenseRequest";
 
+    /// Server side: initiates the licensing exchange
+    pub fn new(
+        server_random: &[u8],
+        product_info: ProductInfo,
+        server_certificate: ServerCertificate,
+        scope_list: Vec<Scope>,
+    ) -> EncodeResult<Self> {
+        let mut this = Self {
+            license_header: LicenseHeader {
+                security_header: BasicSecurityHeader {
+                    flags: BasicSecurityHeaderFlags::LICENSE_PKT,
+                },
+                preamble_message_type: PreambleType::LicenseRequest,
+                preamble_flags: PreambleFlags::empty(),
+                preamble_version: PreambleVersion::V3,
+                preamble_message_size: 0,
+            },
+            server_random: Vec::from(server_random),
+            product_info,
+            server_certificate: Some(server_certificate),
+            scope_list,
+        };
+        this.license_header.preamble_message_size = cast_length!(
+            "ServerLicenseRequest",
+            "preamble_message_size",
+            this.size() - BASIC_SECURITY_HEADER_SIZE
+        )?;
+        Ok(this)
+    }
+
     pub fn get_public_key(&self) -> Result<Option<Vec<u8>>, ServerLicenseError> {
         self.server_certificate.as_ref().map(|c| c.get_public_key()).transpose()
     }
@@ -189,7 +225,7 @@ impl<'de> Decode<'de> for Scope {
         let mut blob_data = src.read_slice(blob_header.length).to_vec();
         blob_data.resize(blob_data.len() - UTF8_NULL_TERMINATOR_SIZE, 0);
 
-        if let Ok(data) = std::str::from_utf8(&blob_data) {
+        if let Ok(data) = core::str::from_utf8(&blob_data) {
             Ok(Self(String::from(data)))
         } else {
             Err(invalid_field_err!("scope", "scope is not utf8"))
@@ -216,10 +252,12 @@ impl ServerCertificate {
 
         match &self.certificate {
             CertificateType::Proprietary(certificate) => {
-                let public_exponent = certificate.public_key.public_exponent.to_le_bytes();
+                // The proprietary certificate holds little-endian integers, while DER ones are big-endian.
+                let public_exponent = certificate.public_key.public_exponent.to_be_bytes();
+                let modulus: Vec<u8> = certificate.public_key.modulus.iter().rev().copied().collect();
 
                 let rsa_public_key = pkcs1::RsaPublicKey {
-                    modulus: pkcs1::UintRef::new(&certificate.public_key.modulus).unwrap(),
+                    modulus: pkcs1::UintRef::new(&modulus).unwrap(),
                     public_exponent: pkcs1::UintRef::new(&public_exponent).unwrap(),
                 };
 
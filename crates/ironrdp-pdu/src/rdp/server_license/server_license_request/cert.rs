@@ -1,3 +1,5 @@
+use alloc::vec::Vec;
+
 use super::{BlobHeader, BlobType, KEY_EXCHANGE_ALGORITHM_RSA};
 use ironrdp_core::{cast_length, ensure_fixed_part_size, ensure_size, invalid_field_err, ReadCursor, WriteCursor};
 use ironrdp_core::{Decode, DecodeResult, Encode, EncodeResult};
@@ -1,8 +1,14 @@
 #[cfg(test)]
 mod test;
 
-use super::{BlobHeader, BlobType, LicenseHeader, PreambleType, BLOB_LENGTH_SIZE, BLOB_TYPE_SIZE, MAC_SIZE};
-use ironrdp_core::{ensure_size, invalid_field_err, ReadCursor, WriteCursor};
+use alloc::vec::Vec;
+
+use super::{
+    BasicSecurityHeader, BasicSecurityHeaderFlags, BlobHeader, BlobType, LicenseEncryptionData, LicenseHeader,
+    PreambleFlags, PreambleType, PreambleVersion, BLOB_LENGTH_SIZE, BLOB_TYPE_SIZE, MAC_SIZE, PREAMBLE_SIZE,
+};
+use crate::crypto::rc4::Rc4;
+use ironrdp_core::{cast_length, ensure_size, invalid_field_err, ReadCursor, WriteCursor};
 use ironrdp_core::{Decode, DecodeResult, Encode, EncodeResult};
 
 const CONNECT_FLAGS_FIELD_SIZE: usize = 4;
@@ -21,6 +27,33 @@ impl ServerPlatformChallenge {
     const NAME: &'static str = "ServerPlatformChallenge";
 
     const FIXED_PART_SIZE: usize = CONNECT_FLAGS_FIELD_SIZE + MAC_SIZE + BLOB_LENGTH_SIZE + BLOB_TYPE_SIZE;
+
+    /// Server side: encrypts a random `challenge`, to be echoed by the client
+    pub fn new(challenge: &[u8], encryption_data: &LicenseEncryptionData) -> EncodeResult<Self> {
+        let mut rc4 = Rc4::new(&encryption_data.license_key);
+        let encrypted_platform_challenge = rc4.process(challenge);
+
+        let mac_data = super::compute_mac_data(encryption_data.mac_salt_key.as_slice(), challenge);
+
+        let license_header = LicenseHeader {
+            security_header: BasicSecurityHeader {
+                flags: BasicSecurityHeaderFlags::LICENSE_PKT,
+            },
+            preamble_message_type: PreambleType::PlatformChallenge,
+            preamble_flags: PreambleFlags::empty(),
+            preamble_version: PreambleVersion::V3,
+            preamble_message_size: cast_length!(
+                "preambleMessageSize",
+                PREAMBLE_SIZE + Self::FIXED_PART_SIZE + encrypted_platform_challenge.len()
+            )?,
+        };
+
+        Ok(Self {
+            license_header,
+            encrypted_platform_challenge,
+            mac_data,
+        })
+    }
 }
 
 impl ServerPlatformChallenge {
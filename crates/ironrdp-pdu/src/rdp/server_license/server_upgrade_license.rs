@@ -1,15 +1,19 @@
 #[cfg(test)]
 mod tests;
 
+use alloc::string::String;
+use alloc::vec::Vec;
+
 use super::{
-    BlobHeader, BlobType, LicenseEncryptionData, LicenseHeader, PreambleType, ServerLicenseError, BLOB_LENGTH_SIZE,
-    BLOB_TYPE_SIZE, MAC_SIZE, UTF16_NULL_TERMINATOR_SIZE, UTF8_NULL_TERMINATOR_SIZE,
+    BasicSecurityHeader, BasicSecurityHeaderFlags, BlobHeader, BlobType, LicenseEncryptionData, LicenseHeader,
+    PreambleFlags, PreambleType, PreambleVersion, ServerLicenseError, BLOB_LENGTH_SIZE, BLOB_TYPE_SIZE, MAC_SIZE,
+    PREAMBLE_SIZE, UTF16_NULL_TERMINATOR_SIZE, UTF8_NULL_TERMINATOR_SIZE,
 };
 use crate::crypto::rc4::Rc4;
 use crate::utils;
 use crate::utils::CharacterSet;
 use ironrdp_core::{cast_length, ensure_fixed_part_size, ensure_size, invalid_field_err, ReadCursor, WriteCursor};
-use ironrdp_core::{Decode, DecodeResult, Encode, EncodeResult};
+use ironrdp_core::{decode, encode_vec, Decode, DecodeResult, Encode, EncodeResult};
 
 const NEW_LICENSE_INFO_STATIC_FIELDS_SIZE: usize = 20;
 
@@ -24,7 +28,53 @@ pub struct ServerUpgradeLicense {
 }
 
 impl ServerUpgradeLicense {
+    /// Server side: issues a new license to the client
+    pub fn new_license(
+        new_license_info: &NewLicenseInformation,
+        encryption_data: &LicenseEncryptionData,
+    ) -> EncodeResult<Self> {
+        let license_info = encode_vec(new_license_info)?;
+
+        let mut rc4 = Rc4::new(encryption_data.license_key.as_slice());
+        let encrypted_license_info = rc4.process(&license_info);
+
+        let mac_data = super::compute_mac_data(encryption_data.mac_salt_key.as_slice(), &license_info);
+
+        let license_header = LicenseHeader {
+            security_header: BasicSecurityHeader {
+                flags: BasicSecurityHeaderFlags::LICENSE_PKT,
+            },
+            preamble_message_type: PreambleType::NewLicense,
+            preamble_flags: PreambleFlags::empty(),
+            preamble_version: PreambleVersion::V3,
+            preamble_message_size: cast_length!(
+                "preambleMessageSize",
+                PREAMBLE_SIZE + BLOB_TYPE_SIZE + BLOB_LENGTH_SIZE + encrypted_license_info.len() + MAC_SIZE
+            )?,
+        };
+
+        Ok(Self {
+            license_header,
+            encrypted_license_info,
+            mac_data,
+        })
+    }
+
     pub fn verify_server_license(&self, encryption_data: &LicenseEncryptionData) -> Result<(), ServerLicenseError> {
+        self.decrypt_license_info(encryption_data).map(|_| ())
+    }
+
+    /// Returns the license issued by the server, which can be presented on subsequent connections
+    pub fn new_license_info(
+        &self,
+        encryption_data: &LicenseEncryptionData,
+    ) -> Result<NewLicenseInformation, ServerLicenseError> {
+        let decrypted_license_info = self.decrypt_license_info(encryption_data)?;
+
+        decode(&decrypted_license_info).map_err(|e| ServerLicenseError::from(crate::decode_err!(e)))
+    }
+
+    fn decrypt_license_info(&self, encryption_data: &LicenseEncryptionData) -> Result<Vec<u8>, ServerLicenseError> {
         let mut rc4 = Rc4::new(encryption_data.license_key.as_slice());
         let decrypted_license_info = rc4.process(self.encrypted_license_info.as_slice());
         let mac_data =
@@ -34,7 +84,7 @@ impl ServerUpgradeLicense {
             return Err(ServerLicenseError::InvalidMacData);
         }
 
-        Ok(())
+        Ok(decrypted_license_info)
     }
 }
 
@@ -91,7 +141,8 @@ impl ServerUpgradeLicense {
     }
 }
 
-#[derive(Debug, PartialEq, Eq)]
+/// New License Information (NEW_LICENSE_INFO), the license issued to the client by the license server
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct NewLicenseInformation {
     pub version: u32,
     pub scope: String,
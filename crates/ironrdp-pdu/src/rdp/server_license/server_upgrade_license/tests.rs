@@ -639,4 +639,7 @@ fn upgrade_license_verifies_correctly() {
     };
 
     upgrade_license.verify_server_license(&encryption_info).unwrap();
+
+    let new_license_info = upgrade_license.new_license_info(&encryption_info).unwrap();
+    assert_eq!(new_license_info.company_name, "Microsoft Corporation");
 }
@@ -1,9 +1,12 @@
+#[cfg(feature = "std")]
 use std::io;
 
 use num_derive::{FromPrimitive, ToPrimitive};
 use num_traits::{FromPrimitive as _, ToPrimitive as _};
+#[cfg(feature = "std")]
 use thiserror::Error;
 
+#[cfg(feature = "std")]
 use crate::PduError;
 use ironrdp_core::{ensure_fixed_part_size, ensure_size, invalid_field_err, ReadCursor, WriteCursor};
 use ironrdp_core::{Decode, DecodeResult, Encode, EncodeResult};
@@ -115,6 +118,7 @@ pub enum InfoData {
     LogonExtended(LogonInfoExtended),
 }
 
+#[cfg(feature = "std")]
 #[derive(Debug, Error)]
 pub enum SessionError {
     #[error("IO error")]
@@ -141,6 +145,7 @@ pub enum SessionError {
     Pdu(PduError),
 }
 
+#[cfg(feature = "std")]
 impl From<PduError> for SessionError {
     fn from(e: PduError) -> Self {
         Self::Pdu(e)
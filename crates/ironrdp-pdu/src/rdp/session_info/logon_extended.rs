@@ -5,6 +5,8 @@ use num_traits::{FromPrimitive, ToPrimitive};
 use ironrdp_core::{cast_length, ensure_fixed_part_size, ensure_size, invalid_field_err, ReadCursor, WriteCursor};
 use ironrdp_core::{Decode, DecodeResult, Encode, EncodeResult};
 
+use crate::crypto::hmac_md5::hmac_md5;
+
 const LOGON_EX_LENGTH_FIELD_SIZE: usize = 2;
 const LOGON_EX_FLAGS_FIELD_SIZE: usize = 4;
 const LOGON_EX_PADDING_SIZE: usize = 570;
@@ -96,6 +98,7 @@ impl<'de> Decode<'de> for LogonInfoExtended {
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ServerAutoReconnect {
     pub logon_id: u32,
     pub random_bits: [u8; AUTO_RECONNECT_RANDOM_BITS_SIZE],
@@ -105,6 +108,25 @@ impl ServerAutoReconnect {
     const NAME: &'static str = "ServerAutoReconnect";
 
     const FIXED_PART_SIZE: usize = AUTO_RECONNECT_PACKET_SIZE + LOGON_INFO_FIELD_DATA_SIZE;
+
+    /// Builds the client auto-reconnect cookie (ARC_CS_PRIVATE_PACKET) to send in the Client Info PDU
+    ///
+    /// The security verifier is the HMAC-MD5 of the client random, keyed with the random bits received from the
+    /// server. When Enhanced RDP Security is used (TLS, CredSSP), the client random is 32 zero bytes.
+    ///
+    /// [Doc](https://learn.microsoft.com/en-us/openspecs/windows_protocols/ms-rdpbcgr/15b0d1c9-2891-4adb-a45e-deb4aeeeab7c)
+    pub fn client_cookie(&self, client_random: &[u8]) -> [u8; AUTO_RECONNECT_PACKET_SIZE] {
+        let security_verifier = hmac_md5(&self.random_bits, client_random);
+
+        let mut cookie = [0u8; AUTO_RECONNECT_PACKET_SIZE];
+        let mut dst = WriteCursor::new(&mut cookie);
+        dst.write_u32(AUTO_RECONNECT_PACKET_SIZE as u32);
+        dst.write_u32(AUTO_RECONNECT_VERSION_1);
+        dst.write_u32(self.logon_id);
+        dst.write_array(security_verifier);
+
+        cookie
+    }
 }
 
 impl Encode for ServerAutoReconnect {
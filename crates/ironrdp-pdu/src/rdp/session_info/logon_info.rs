@@ -1,5 +1,9 @@
+use alloc::string::String;
+
 use crate::utils;
-use ironrdp_core::{cast_length, ensure_fixed_part_size, ensure_size, invalid_field_err, ReadCursor, WriteCursor};
+use ironrdp_core::{
+    cast_length, ensure_fixed_part_size, ensure_size, invalid_field_err, tolerate_violation, ReadCursor, WriteCursor,
+};
 use ironrdp_core::{Decode, DecodeResult, Encode, EncodeResult};
 
 const DOMAIN_NAME_SIZE_FIELD_SIZE: usize = 4;
@@ -67,7 +71,7 @@ impl<'de> Decode<'de> for LogonInfoVersion1 {
 
         let domain_name_size: usize = cast_length!("domainNameSize", src.read_u32())?;
         if domain_name_size > DOMAIN_NAME_SIZE_V1 {
-            return Err(invalid_field_err!("domainNameSize", "invalid domain name size"));
+            tolerate_violation!("domainNameSize", "invalid domain name size")?;
         }
 
         let domain_name =
@@ -75,7 +79,7 @@ impl<'de> Decode<'de> for LogonInfoVersion1 {
 
         let user_name_size: usize = cast_length!("userNameSize", src.read_u32())?;
         if user_name_size > USER_NAME_SIZE_V1 {
-            return Err(invalid_field_err!("userNameSize", "invalid user name size"));
+            tolerate_violation!("userNameSize", "invalid user name size")?;
         }
 
         let user_name = utils::decode_string(src.read_slice(USER_NAME_SIZE_V1), utils::CharacterSet::Unicode, false)?;
@@ -159,12 +163,12 @@ impl<'de> Decode<'de> for LogonInfoVersion2 {
         let session_id = src.read_u32();
         let domain_name_size: usize = cast_length!("domainNameSize", src.read_u32())?;
         if domain_name_size > DOMAIN_NAME_SIZE_V2 {
-            return Err(invalid_field_err!("domainNameSize", "invalid domain name size"));
+            tolerate_violation!("domainNameSize", "invalid domain name size")?;
         }
 
         let user_name_size: usize = cast_length!("userNameSize", src.read_u32())?;
         if user_name_size > USER_NAME_SIZE_V2 {
-            return Err(invalid_field_err!("userNameSize", "invalid user name size"));
+            tolerate_violation!("userNameSize", "invalid user name size")?;
         }
 
         read_padding!(src, LOGON_INFO_V2_PADDING_SIZE);
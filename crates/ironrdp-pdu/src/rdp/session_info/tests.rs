@@ -1,7 +1,7 @@
 use ironrdp_core::DecodeErrorKind;
 use lazy_static::lazy_static;
 
-use ironrdp_core::{decode, encode_vec, Encode};
+use ironrdp_core::{decode, encode_vec, with_decode_options, DecodeMode, DecodeOptions, Encode};
 
 use super::*;
 
@@ -381,21 +381,39 @@ fn from_buffer_parsing_with_invalid_session_info_type_fails() {
 }
 
 #[test]
-fn from_buffer_parsing_with_invalid_domain_size_fails() {
-    match decode::<LogonInfoVersion1>(LOGON_INFO_V1_WITH_INVALID_DOMAIN_SIZE_BUFFER.as_ref()) {
+fn from_buffer_parsing_with_invalid_domain_size_fails_in_strict_mode() {
+    let res = with_decode_options(DecodeOptions::new(DecodeMode::Strict), || {
+        decode::<LogonInfoVersion1>(LOGON_INFO_V1_WITH_INVALID_DOMAIN_SIZE_BUFFER.as_ref())
+    });
+
+    match res {
         Err(e) if matches!(e.kind(), DecodeErrorKind::InvalidField { .. }) => (),
         res => panic!("Expected InvalidDomainNameSize error, got: {res:?}"),
     };
 }
 
 #[test]
-fn from_buffer_parsing_with_invalid_user_name_size_fails() {
-    match decode::<LogonInfoVersion1>(LOGON_INFO_V1_WITH_INVALID_USER_NAME_SIZE_BUFFER.as_ref()) {
+fn from_buffer_parsing_with_invalid_domain_size_is_tolerated_in_lenient_mode() {
+    decode::<LogonInfoVersion1>(LOGON_INFO_V1_WITH_INVALID_DOMAIN_SIZE_BUFFER.as_ref()).unwrap();
+}
+
+#[test]
+fn from_buffer_parsing_with_invalid_user_name_size_fails_in_strict_mode() {
+    let res = with_decode_options(DecodeOptions::new(DecodeMode::Strict), || {
+        decode::<LogonInfoVersion1>(LOGON_INFO_V1_WITH_INVALID_USER_NAME_SIZE_BUFFER.as_ref())
+    });
+
+    match res {
         Err(e) if matches!(e.kind(), DecodeErrorKind::InvalidField { .. }) => (),
         res => panic!("Expected InvalidUserNameSize error, got: {res:?}"),
     };
 }
 
+#[test]
+fn from_buffer_parsing_with_invalid_user_name_size_is_tolerated_in_lenient_mode() {
+    decode::<LogonInfoVersion1>(LOGON_INFO_V1_WITH_INVALID_USER_NAME_SIZE_BUFFER.as_ref()).unwrap();
+}
+
 #[test]
 fn from_buffer_parsing_with_invalid_logon_version_fails() {
     match decode::<LogonInfoVersion2>(LOGON_INFO_V2_WITH_INVALID_LOGON_VERSION_BUFFER.as_ref()) {
@@ -435,3 +453,24 @@ fn from_buffer_parsing_with_invalid_logon_error_type_fails() {
         res => panic!("Expected InvalidLogonErrorType error, got: {res:?}"),
     };
 }
+
+#[test]
+fn client_auto_reconnect_cookie_is_built_from_server_random_bits() {
+    let auto_reconnect = ServerAutoReconnect {
+        logon_id: SESSION_ID,
+        random_bits: [
+            0xa8, 0x02, 0xe7, 0x25, 0xe2, 0x4c, 0x82, 0xb7, 0x52, 0xa5, 0x53, 0x50, 0x34, 0x98, 0xa1, 0xa8,
+        ],
+    };
+
+    assert_eq!(
+        auto_reconnect.client_cookie(&[0; 32]),
+        [
+            0x1c, 0x00, 0x00, 0x00, // cbLen
+            0x01, 0x00, 0x00, 0x00, // Version
+            0x02, 0x00, 0x00, 0x00, // LogonId
+            0x42, 0xb7, 0x9a, 0x48, 0x16, 0xea, 0xda, 0xd6, 0x34, 0xd6, 0x8c, 0x5e, 0xdc, 0xe8, 0x64,
+            0xb9, // SecurityVerifier
+        ]
+    );
+}
@@ -0,0 +1,434 @@
+//! Standard RDP Security ([MS-RDPBCGR] 5.3)
+//!
+//! Legacy security mechanism where the session is encrypted with RC4, using keys derived from random values
+//! exchanged during the connection sequence. It is only meant for clients unable to negotiate TLS.
+
+use alloc::vec::Vec;
+use core::fmt;
+
+use ironrdp_core::{cast_length, ensure_fixed_part_size, ensure_size, invalid_field_err, ReadCursor, WriteCursor};
+use ironrdp_core::{Decode, DecodeResult, Encode, EncodeResult};
+use md5::Digest as _;
+use num_bigint::BigUint;
+
+use crate::crypto::rc4::Rc4;
+use crate::gcc::EncryptionMethod;
+use crate::rdp::headers::{BasicSecurityHeader, BasicSecurityHeaderFlags};
+use crate::rdp::server_license::cert::{CertificateType, ProprietaryCertificate, RsaPublicKey, RSA_KEY_PADDING_LENGTH};
+use crate::rdp::server_license::ServerCertificate;
+
+pub const CLIENT_RANDOM_LEN: usize = 32;
+pub const SERVER_RANDOM_LEN: usize = 32;
+/// Size of the MAC signature following the basic security header of encrypted PDUs
+pub const DATA_SIGNATURE_LEN: usize = 8;
+
+/// Number of PDUs encrypted with a key before it is updated.
+const KEY_UPDATE_INTERVAL: u32 = 4096;
+
+const PAD1: [u8; 40] = [0x36; 40];
+const PAD2: [u8; 48] = [0x5c; 48];
+const SALT_40BIT: [u8; 3] = [0xd1, 0x26, 0x9e];
+const SALT_56BIT: [u8; 1] = [0xd1];
+
+/// [MS-RDPBCGR] 2.2.1.10.1 Security Exchange PDU Data (TS_SECURITY_PACKET)
+///
+/// Carries the client random, encrypted with the server public key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SecurityExchangePdu {
+    /// Encrypted client random, in little-endian byte order, including the 8 bytes of padding
+    pub encrypted_client_random: Vec<u8>,
+}
+
+impl SecurityExchangePdu {
+    const NAME: &'static str = "SecurityExchangePdu";
+
+    const FIXED_PART_SIZE: usize = BasicSecurityHeader::FIXED_PART_SIZE + 4 /* length */;
+}
+
+impl Encode for SecurityExchangePdu {
+    fn encode(&self, dst: &mut WriteCursor<'_>) -> EncodeResult<()> {
+        ensure_size!(in: dst, size: self.size());
+
+        BasicSecurityHeader {
+            flags: BasicSecurityHeaderFlags::EXCHANGE_PKT,
+        }
+        .encode(dst)?;
+        dst.write_u32(cast_length!("length", self.encrypted_client_random.len())?);
+        dst.write_slice(&self.encrypted_client_random);
+
+        Ok(())
+    }
+
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    fn size(&self) -> usize {
+        Self::FIXED_PART_SIZE + self.encrypted_client_random.len()
+    }
+}
+
+impl<'de> Decode<'de> for SecurityExchangePdu {
+    fn decode(src: &mut ReadCursor<'de>) -> DecodeResult<Self> {
+        ensure_fixed_part_size!(in: src);
+
+        let header = BasicSecurityHeader::decode(src)?;
+        if !header.flags.contains(BasicSecurityHeaderFlags::EXCHANGE_PKT) {
+            return Err(invalid_field_err!("securityHeader", "expected SEC_EXCHANGE_PKT flag"));
+        }
+
+        let length = cast_length!("length", src.read_u32())?;
+        ensure_size!(in: src, size: length);
+        let encrypted_client_random = src.read_slice(length).to_vec();
+
+        Ok(Self {
+            encrypted_client_random,
+        })
+    }
+}
+
+/// RSA key pair of the server
+///
+/// Standard RDP Security uses raw RSA operations on little-endian integers.
+#[derive(Clone)]
+pub struct RsaPrivateKey {
+    modulus: BigUint,
+    public_exponent: u32,
+    private_exponent: BigUint,
+}
+
+impl RsaPrivateKey {
+    /// Creates a key pair from its components, the modulus and private exponent being in big-endian byte order
+    pub fn from_components(modulus: &[u8], public_exponent: u32, private_exponent: &[u8]) -> Self {
+        Self {
+            modulus: BigUint::from_bytes_be(modulus),
+            public_exponent,
+            private_exponent: BigUint::from_bytes_be(private_exponent),
+        }
+    }
+
+    /// Returns the size of the modulus, in bytes
+    pub fn modulus_len(&self) -> usize {
+        usize::try_from(self.modulus.bits().div_ceil(8)).expect("modulus size fits in usize")
+    }
+
+    /// Returns the public key, as sent to the client in a proprietary certificate
+    pub fn public_key(&self) -> RsaPublicKey {
+        let mut modulus = self.modulus.to_bytes_le();
+        modulus.resize(self.modulus_len() + RSA_KEY_PADDING_LENGTH as usize, 0);
+
+        RsaPublicKey {
+            public_exponent: self.public_exponent,
+            modulus,
+        }
+    }
+
+    /// Decrypts a little-endian value encrypted with the public key, such as the encrypted client random
+    ///
+    /// The returned value is in little-endian byte order, and has the size of the modulus.
+    pub fn decrypt(&self, ciphertext: &[u8]) -> Vec<u8> {
+        self.raw_private(ciphertext)
+    }
+
+    fn raw_private(&self, input: &[u8]) -> Vec<u8> {
+        let input = BigUint::from_bytes_le(input);
+        let mut output = input.modpow(&self.private_exponent, &self.modulus).to_bytes_le();
+        output.resize(self.modulus_len(), 0);
+        output
+    }
+}
+
+impl fmt::Debug for RsaPrivateKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RsaPrivateKey")
+            .field("modulus_len", &self.modulus_len())
+            .field("public_exponent", &self.public_exponent)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Builds the proprietary certificate holding the public key of `server_key`, signed with `signing_key`
+///
+/// Clients verify the signature using the Terminal Services signing key, see [MS-RDPBCGR] 5.3.3.1.1.
+pub fn proprietary_server_certificate(
+    server_key: &RsaPrivateKey,
+    signing_key: &RsaPrivateKey,
+) -> EncodeResult<ServerCertificate> {
+    let public_key = server_key.public_key();
+    let signed_len =
+        4 /* dwVersion */ + 4 /* dwSigAlgId */ + 4 /* dwKeyAlgId */ + 4 /* blob header */ + public_key.size();
+
+    let mut certificate = ServerCertificate {
+        issued_permanently: false,
+        certificate: CertificateType::Proprietary(ProprietaryCertificate {
+            public_key,
+            signature: Vec::new(),
+        }),
+    };
+    let encoded = ironrdp_core::encode_vec(&certificate)?;
+
+    // [MS-RDPBCGR] 5.3.3.1.2 Signing a Proprietary Certificate
+    let hash = md5::Md5::digest(&encoded[..signed_len]);
+    let mut signature_data = hash.to_vec();
+    signature_data.push(0x00);
+    signature_data.extend_from_slice(&[0xff; 45]);
+    signature_data.push(0x01);
+
+    let mut signature = signing_key.raw_private(&signature_data);
+    signature.resize(signature.len() + RSA_KEY_PADDING_LENGTH as usize, 0);
+
+    if let CertificateType::Proprietary(proprietary) = &mut certificate.certificate {
+        proprietary.signature = signature;
+    }
+
+    Ok(certificate)
+}
+
+/// Session keys and RC4 state of a Standard RDP Security session
+///
+/// Keys are derived as described in [MS-RDPBCGR] 5.3.5.1 "Non-FIPS", and updated every 4096 PDUs (5.3.7).
+pub struct StandardSecurity {
+    mac_key: Vec<u8>,
+    method: EncryptionMethod,
+    encrypt: KeyState,
+    decrypt: KeyState,
+}
+
+impl StandardSecurity {
+    /// Derives the keys used by the server
+    ///
+    /// `method` must be one of the 40-bit, 56-bit or 128-bit encryption methods.
+    pub fn server(
+        client_random: &[u8; CLIENT_RANDOM_LEN],
+        server_random: &[u8; SERVER_RANDOM_LEN],
+        method: EncryptionMethod,
+    ) -> DecodeResult<Self> {
+        let keys = SessionKeyBlob::derive(client_random, server_random);
+        Self::new(keys.mac_key, keys.second_key, keys.third_key, method)
+    }
+
+    /// Derives the keys used by the client
+    ///
+    /// `method` must be one of the 40-bit, 56-bit or 128-bit encryption methods.
+    pub fn client(
+        client_random: &[u8; CLIENT_RANDOM_LEN],
+        server_random: &[u8; SERVER_RANDOM_LEN],
+        method: EncryptionMethod,
+    ) -> DecodeResult<Self> {
+        let keys = SessionKeyBlob::derive(client_random, server_random);
+        Self::new(keys.mac_key, keys.third_key, keys.second_key, method)
+    }
+
+    fn new(
+        mac_key: [u8; 16],
+        encrypt_key: [u8; 16],
+        decrypt_key: [u8; 16],
+        method: EncryptionMethod,
+    ) -> DecodeResult<Self> {
+        let key_len = if method == EncryptionMethod::BIT_128 {
+            16
+        } else if method == EncryptionMethod::BIT_56 || method == EncryptionMethod::BIT_40 {
+            8
+        } else {
+            return Err(invalid_field_err!("encryptionMethod", "unsupported encryption method"));
+        };
+
+        let mut mac_key = mac_key[..key_len].to_vec();
+        let mut encrypt_key = encrypt_key[..key_len].to_vec();
+        let mut decrypt_key = decrypt_key[..key_len].to_vec();
+        salt_key(&mut mac_key, method);
+        salt_key(&mut encrypt_key, method);
+        salt_key(&mut decrypt_key, method);
+
+        Ok(Self {
+            mac_key,
+            method,
+            encrypt: KeyState::new(encrypt_key),
+            decrypt: KeyState::new(decrypt_key),
+        })
+    }
+
+    pub fn method(&self) -> EncryptionMethod {
+        self.method
+    }
+
+    /// Signs and encrypts an outgoing PDU
+    ///
+    /// Returns the MAC signature, salted with the encryption count if `salted` is set (`SEC_SECURE_CHECKSUM`),
+    /// and the encrypted data.
+    pub fn encrypt(&mut self, data: &[u8], salted: bool) -> ([u8; DATA_SIGNATURE_LEN], Vec<u8>) {
+        let count = salted.then_some(self.encrypt.total_count);
+        let signature = mac_signature(&self.mac_key, data, count);
+        let encrypted = self.encrypt.process(data, self.method);
+
+        (signature, encrypted)
+    }
+
+    /// Decrypts an incoming PDU and verifies its MAC signature
+    pub fn decrypt(&mut self, data: &[u8], signature: &[u8], salted: bool) -> DecodeResult<Vec<u8>> {
+        let count = salted.then_some(self.decrypt.total_count);
+        let decrypted = self.decrypt.process(data, self.method);
+
+        if !signature_eq(&mac_signature(&self.mac_key, &decrypted, count), signature) {
+            return Err(invalid_field_err!("dataSignature", "invalid MAC signature"));
+        }
+
+        Ok(decrypted)
+    }
+}
+
+impl fmt::Debug for StandardSecurity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StandardSecurity")
+            .field("method", &self.method)
+            .finish_non_exhaustive()
+    }
+}
+
+struct SessionKeyBlob {
+    mac_key: [u8; 16],
+    second_key: [u8; 16],
+    third_key: [u8; 16],
+}
+
+impl SessionKeyBlob {
+    fn derive(client_random: &[u8], server_random: &[u8]) -> Self {
+        let pre_master_secret = [&client_random[..24], &server_random[..24]].concat();
+
+        let salted_hash = |salt: &[u8], input: &[u8]| -> [u8; 16] {
+            let sha = sha1::Sha1::new()
+                .chain_update(input)
+                .chain_update(salt)
+                .chain_update(client_random)
+                .chain_update(server_random)
+                .finalize();
+
+            md5::Md5::new().chain_update(salt).chain_update(sha).finalize().into()
+        };
+
+        let master_secret = [
+            salted_hash(&pre_master_secret, b"A"),
+            salted_hash(&pre_master_secret, b"BB"),
+            salted_hash(&pre_master_secret, b"CCC"),
+        ]
+        .concat();
+
+        let final_hash = |key: [u8; 16]| -> [u8; 16] {
+            md5::Md5::new()
+                .chain_update(key)
+                .chain_update(client_random)
+                .chain_update(server_random)
+                .finalize()
+                .into()
+        };
+
+        Self {
+            mac_key: salted_hash(&master_secret, b"X"),
+            second_key: final_hash(salted_hash(&master_secret, b"YY")),
+            third_key: final_hash(salted_hash(&master_secret, b"ZZZ")),
+        }
+    }
+}
+
+struct KeyState {
+    initial_key: Vec<u8>,
+    current_key: Vec<u8>,
+    rc4: Rc4,
+    /// Number of PDUs processed with the current key
+    use_count: u32,
+    /// Number of PDUs processed since the beginning of the session
+    total_count: u32,
+}
+
+impl KeyState {
+    fn new(key: Vec<u8>) -> Self {
+        Self {
+            rc4: Rc4::new(&key),
+            initial_key: key.clone(),
+            current_key: key,
+            use_count: 0,
+            total_count: 0,
+        }
+    }
+
+    fn process(&mut self, data: &[u8], method: EncryptionMethod) -> Vec<u8> {
+        if self.use_count >= KEY_UPDATE_INTERVAL {
+            self.update_key(method);
+        }
+
+        self.use_count += 1;
+        self.total_count = self.total_count.wrapping_add(1);
+
+        self.rc4.process(data)
+    }
+
+    /// [MS-RDPBCGR] 5.3.7 Session Key Updates
+    fn update_key(&mut self, method: EncryptionMethod) {
+        let key_len = self.current_key.len();
+
+        let sha = sha1::Sha1::new()
+            .chain_update(&self.initial_key)
+            .chain_update(PAD1)
+            .chain_update(&self.current_key)
+            .finalize();
+        let temp_key = md5::Md5::new()
+            .chain_update(&self.initial_key)
+            .chain_update(PAD2)
+            .chain_update(sha)
+            .finalize();
+
+        let mut new_key = Rc4::new(&temp_key[..key_len]).process(&temp_key[..key_len]);
+        salt_key(&mut new_key, method);
+
+        self.rc4 = Rc4::new(&new_key);
+        self.current_key = new_key;
+        self.use_count = 0;
+    }
+}
+
+fn salt_key(key: &mut [u8], method: EncryptionMethod) {
+    let salt: &[u8] = if method == EncryptionMethod::BIT_40 {
+        &SALT_40BIT
+    } else if method == EncryptionMethod::BIT_56 {
+        &SALT_56BIT
+    } else {
+        &[]
+    };
+
+    key[..salt.len()].copy_from_slice(salt);
+}
+
+/// [MS-RDPBCGR] 5.3.6.1 Non-FIPS, and 5.3.6.1.1 Salted MAC Generation
+fn mac_signature(mac_key: &[u8], data: &[u8], encryption_count: Option<u32>) -> [u8; DATA_SIGNATURE_LEN] {
+    let length = u32::try_from(data.len()).unwrap_or(u32::MAX);
+
+    let mut sha = sha1::Sha1::new()
+        .chain_update(mac_key)
+        .chain_update(PAD1)
+        .chain_update(length.to_le_bytes())
+        .chain_update(data);
+    if let Some(count) = encryption_count {
+        sha.update(count.to_le_bytes());
+    }
+    let sha = sha.finalize();
+
+    let md5 = md5::Md5::new()
+        .chain_update(mac_key)
+        .chain_update(PAD2)
+        .chain_update(sha)
+        .finalize();
+
+    let mut signature = [0; DATA_SIGNATURE_LEN];
+    signature.copy_from_slice(&md5[..DATA_SIGNATURE_LEN]);
+    signature
+}
+
+/// Compares the signatures in constant time, not to leak the position of the first mismatching byte
+fn signature_eq(expected: &[u8; DATA_SIGNATURE_LEN], received: &[u8]) -> bool {
+    received.len() == DATA_SIGNATURE_LEN
+        && expected
+            .iter()
+            .zip(received)
+            .fold(0, |diff, (expected, received)| diff | (expected ^ received))
+            == 0
+}
@@ -78,7 +78,7 @@ impl<'de> Decode<'de> for SuppressOutputPdu {
 
         let allow_display_updates = AllowDisplayUpdatesType::from_u8(src.read_u8())
             .ok_or_else(|| invalid_field_err!("allowDisplayUpdates", "invalid display update type"))?;
-        read_padding!(src, 3);
+        read_zeroed_padding!(src, 3)?;
         let desktop_rect = if allow_display_updates == AllowDisplayUpdatesType::AllowDisplayUpdates {
             Some(InclusiveRectangle::decode(src)?)
         } else {
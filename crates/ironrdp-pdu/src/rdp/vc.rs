@@ -3,11 +3,15 @@ pub mod dvc;
 #[cfg(test)]
 mod tests;
 
-use std::{io, str};
+use core::str;
+#[cfg(feature = "std")]
+use std::io;
 
 use bitflags::bitflags;
+#[cfg(feature = "std")]
 use thiserror::Error;
 
+#[cfg(feature = "std")]
 use crate::PduError;
 use ironrdp_core::{ensure_fixed_part_size, ReadCursor, WriteCursor};
 use ironrdp_core::{Decode, DecodeResult, Encode, EncodeResult};
@@ -78,12 +82,13 @@ bitflags! {
     }
 }
 
+#[cfg(feature = "std")]
 #[derive(Debug, Error)]
 pub enum ChannelError {
     #[error("IO error")]
     IOError(#[from] io::Error),
     #[error("from UTF-8 error")]
-    FromUtf8Error(#[from] std::string::FromUtf8Error),
+    FromUtf8Error(#[from] alloc::string::FromUtf8Error),
     #[error("invalid channel PDU header")]
     InvalidChannelPduHeader,
     #[error("invalid channel total data length")]
@@ -104,12 +109,14 @@ pub enum ChannelError {
     Pdu(PduError),
 }
 
+#[cfg(feature = "std")]
 impl From<PduError> for ChannelError {
     fn from(e: PduError) -> Self {
         Self::Pdu(e)
     }
 }
 
+#[cfg(feature = "std")]
 impl From<ChannelError> for io::Error {
     fn from(e: ChannelError) -> io::Error {
         io::Error::new(io::ErrorKind::Other, format!("Virtual channel error: {e}"))
@@ -12,13 +12,15 @@ pub use graphics_messages::{
 use num_derive::{FromPrimitive, ToPrimitive};
 use num_traits::{FromPrimitive as _, ToPrimitive as _};
 
-use ironrdp_core::{cast_length, ensure_fixed_part_size, ensure_size, invalid_field_err, ReadCursor, WriteCursor};
+use ironrdp_core::{
+    cast_length, ensure_fixed_part_size, ensure_size, invalid_field_err, IntoOwned, ReadCursor, WriteCursor,
+};
 use ironrdp_core::{Decode, DecodeResult, Encode, EncodeResult};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub enum ServerPdu {
-    WireToSurface1(WireToSurface1Pdu),
-    WireToSurface2(WireToSurface2Pdu),
+pub enum ServerPdu<'a> {
+    WireToSurface1(WireToSurface1Pdu<'a>),
+    WireToSurface2(WireToSurface2Pdu<'a>),
     DeleteEncodingContext(DeleteEncodingContextPdu),
     SolidFill(SolidFillPdu),
     SurfaceToSurface(SurfaceToSurfacePdu),
@@ -39,13 +41,42 @@ pub enum ServerPdu {
 
 const RDP_GFX_HEADER_SIZE: usize = 2 /* PduType */ + 2 /* flags */ + 4 /* bufferLen */;
 
-impl ServerPdu {
+impl_pdu_borrowing!(ServerPdu<'_>, OwnedServerPdu);
+
+impl IntoOwned for ServerPdu<'_> {
+    type Owned = OwnedServerPdu;
+
+    fn into_owned(self) -> Self::Owned {
+        match self {
+            ServerPdu::WireToSurface1(pdu) => ServerPdu::WireToSurface1(pdu.into_owned()),
+            ServerPdu::WireToSurface2(pdu) => ServerPdu::WireToSurface2(pdu.into_owned()),
+            ServerPdu::DeleteEncodingContext(pdu) => ServerPdu::DeleteEncodingContext(pdu),
+            ServerPdu::SolidFill(pdu) => ServerPdu::SolidFill(pdu),
+            ServerPdu::SurfaceToSurface(pdu) => ServerPdu::SurfaceToSurface(pdu),
+            ServerPdu::SurfaceToCache(pdu) => ServerPdu::SurfaceToCache(pdu),
+            ServerPdu::CacheToSurface(pdu) => ServerPdu::CacheToSurface(pdu),
+            ServerPdu::EvictCacheEntry(pdu) => ServerPdu::EvictCacheEntry(pdu),
+            ServerPdu::CreateSurface(pdu) => ServerPdu::CreateSurface(pdu),
+            ServerPdu::DeleteSurface(pdu) => ServerPdu::DeleteSurface(pdu),
+            ServerPdu::StartFrame(pdu) => ServerPdu::StartFrame(pdu),
+            ServerPdu::EndFrame(pdu) => ServerPdu::EndFrame(pdu),
+            ServerPdu::ResetGraphics(pdu) => ServerPdu::ResetGraphics(pdu),
+            ServerPdu::MapSurfaceToOutput(pdu) => ServerPdu::MapSurfaceToOutput(pdu),
+            ServerPdu::CapabilitiesConfirm(pdu) => ServerPdu::CapabilitiesConfirm(pdu),
+            ServerPdu::CacheImportReply(pdu) => ServerPdu::CacheImportReply(pdu),
+            ServerPdu::MapSurfaceToScaledOutput(pdu) => ServerPdu::MapSurfaceToScaledOutput(pdu),
+            ServerPdu::MapSurfaceToScaledWindow(pdu) => ServerPdu::MapSurfaceToScaledWindow(pdu),
+        }
+    }
+}
+
+impl ServerPdu<'_> {
     const NAME: &'static str = "GfxServerPdu";
 
     const FIXED_PART_SIZE: usize = RDP_GFX_HEADER_SIZE;
 }
 
-impl Encode for ServerPdu {
+impl Encode for ServerPdu<'_> {
     fn encode(&self, dst: &mut WriteCursor<'_>) -> EncodeResult<()> {
         ensure_size!(in: dst, size: self.size());
 
@@ -106,7 +137,7 @@ impl Encode for ServerPdu {
     }
 }
 
-impl<'a> Decode<'a> for ServerPdu {
+impl<'a> Decode<'a> for ServerPdu<'a> {
     fn decode(src: &mut ReadCursor<'a>) -> DecodeResult<Self> {
         ensure_fixed_part_size!(in: src);
 
@@ -260,8 +291,8 @@ pub enum ServerPduType {
     MapSurfaceToScaledWindow = 0x18,
 }
 
-impl<'a> From<&'a ServerPdu> for ServerPduType {
-    fn from(s: &'a ServerPdu) -> Self {
+impl<'a> From<&'a ServerPdu<'_>> for ServerPduType {
+    fn from(s: &'a ServerPdu<'_>) -> Self {
         match s {
             ServerPdu::WireToSurface1(_) => Self::WireToSurface1,
             ServerPdu::WireToSurface2(_) => Self::WireToSurface2,
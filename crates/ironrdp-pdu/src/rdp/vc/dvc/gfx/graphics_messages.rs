@@ -2,6 +2,8 @@ mod client;
 mod server;
 
 mod avc_messages;
+use alloc::vec::Vec;
+
 use bitflags::bitflags;
 use num_derive::{FromPrimitive, ToPrimitive};
 use num_traits::{FromPrimitive as _, ToPrimitive as _};
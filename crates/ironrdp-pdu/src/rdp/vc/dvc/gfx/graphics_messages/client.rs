@@ -1,3 +1,5 @@
+use alloc::vec::Vec;
+
 use super::CapabilitySet;
 use ironrdp_core::{cast_length, ensure_fixed_part_size, ensure_size, ReadCursor, WriteCursor};
 use ironrdp_core::{Decode, DecodeResult, Encode, EncodeResult};
@@ -1,4 +1,6 @@
-use std::fmt;
+use alloc::borrow::Cow;
+use alloc::vec::Vec;
+use core::fmt;
 
 use bit_field::BitField;
 use num_derive::{FromPrimitive, ToPrimitive};
@@ -7,7 +9,9 @@ use num_traits::{FromPrimitive, ToPrimitive};
 use super::{CapabilitySet, Color, Point, RDP_GFX_HEADER_SIZE};
 use crate::gcc::Monitor;
 use crate::geometry::InclusiveRectangle;
-use ironrdp_core::{cast_length, ensure_fixed_part_size, ensure_size, invalid_field_err, ReadCursor, WriteCursor};
+use ironrdp_core::{
+    cast_length, ensure_fixed_part_size, ensure_size, invalid_field_err, IntoOwned, ReadCursor, WriteCursor,
+};
 use ironrdp_core::{decode_cursor, Decode, DecodeResult, Encode, EncodeResult};
 
 pub(crate) const RESET_GRAPHICS_PDU_SIZE: usize = 340;
@@ -15,16 +19,31 @@ pub(crate) const RESET_GRAPHICS_PDU_SIZE: usize = 340;
 const MAX_RESET_GRAPHICS_WIDTH_HEIGHT: u32 = 32_766;
 const MONITOR_COUNT_MAX: u32 = 16;
 
+/// The bitmap data is borrowed from the decoded buffer, use [`IntoOwned::into_owned`] to detach it.
 #[derive(Clone, PartialEq, Eq)]
-pub struct WireToSurface1Pdu {
+pub struct WireToSurface1Pdu<'a> {
     pub surface_id: u16,
     pub codec_id: Codec1Type,
     pub pixel_format: PixelFormat,
     pub destination_rectangle: InclusiveRectangle,
-    pub bitmap_data: Vec<u8>,
+    pub bitmap_data: Cow<'a, [u8]>,
 }
 
-impl fmt::Debug for WireToSurface1Pdu {
+impl IntoOwned for WireToSurface1Pdu<'_> {
+    type Owned = WireToSurface1Pdu<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        WireToSurface1Pdu {
+            surface_id: self.surface_id,
+            codec_id: self.codec_id,
+            pixel_format: self.pixel_format,
+            destination_rectangle: self.destination_rectangle,
+            bitmap_data: Cow::Owned(self.bitmap_data.into_owned()),
+        }
+    }
+}
+
+impl fmt::Debug for WireToSurface1Pdu<'_> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("WireToSurface1Pdu")
             .field("surface_id", &self.surface_id)
@@ -36,13 +55,13 @@ impl fmt::Debug for WireToSurface1Pdu {
     }
 }
 
-impl WireToSurface1Pdu {
+impl WireToSurface1Pdu<'_> {
     const NAME: &'static str = "WireToSurface1Pdu";
 
     const FIXED_PART_SIZE: usize = 2 /* SurfaceId */ + 2 /* CodecId */ + 1 /* PixelFormat */ + InclusiveRectangle::FIXED_PART_SIZE /* Dest */ + 4 /* BitmapDataLen */;
 }
 
-impl Encode for WireToSurface1Pdu {
+impl Encode for WireToSurface1Pdu<'_> {
     fn encode(&self, dst: &mut WriteCursor<'_>) -> EncodeResult<()> {
         ensure_size!(in: dst, size: self.size());
 
@@ -64,7 +83,7 @@ impl Encode for WireToSurface1Pdu {
     }
 }
 
-impl<'a> Decode<'a> for WireToSurface1Pdu {
+impl<'a> Decode<'a> for WireToSurface1Pdu<'a> {
     fn decode(src: &mut ReadCursor<'a>) -> DecodeResult<Self> {
         ensure_fixed_part_size!(in: src);
 
@@ -77,7 +96,7 @@ impl<'a> Decode<'a> for WireToSurface1Pdu {
         let bitmap_data_length = cast_length!("BitmapDataLen", src.read_u32())?;
 
         ensure_size!(in: src, size: bitmap_data_length);
-        let bitmap_data = src.read_slice(bitmap_data_length).to_vec();
+        let bitmap_data = Cow::Borrowed(src.read_slice(bitmap_data_length));
 
         Ok(Self {
             surface_id,
@@ -89,16 +108,31 @@ impl<'a> Decode<'a> for WireToSurface1Pdu {
     }
 }
 
+/// The bitmap data is borrowed from the decoded buffer, use [`IntoOwned::into_owned`] to detach it.
 #[derive(Clone, PartialEq, Eq)]
-pub struct WireToSurface2Pdu {
+pub struct WireToSurface2Pdu<'a> {
     pub surface_id: u16,
     pub codec_id: Codec2Type,
     pub codec_context_id: u32,
     pub pixel_format: PixelFormat,
-    pub bitmap_data: Vec<u8>,
+    pub bitmap_data: Cow<'a, [u8]>,
+}
+
+impl IntoOwned for WireToSurface2Pdu<'_> {
+    type Owned = WireToSurface2Pdu<'static>;
+
+    fn into_owned(self) -> Self::Owned {
+        WireToSurface2Pdu {
+            surface_id: self.surface_id,
+            codec_id: self.codec_id,
+            codec_context_id: self.codec_context_id,
+            pixel_format: self.pixel_format,
+            bitmap_data: Cow::Owned(self.bitmap_data.into_owned()),
+        }
+    }
 }
 
-impl fmt::Debug for WireToSurface2Pdu {
+impl fmt::Debug for WireToSurface2Pdu<'_> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("WireToSurface2Pdu")
             .field("surface_id", &self.surface_id)
@@ -110,13 +144,13 @@ impl fmt::Debug for WireToSurface2Pdu {
     }
 }
 
-impl WireToSurface2Pdu {
+impl WireToSurface2Pdu<'_> {
     const NAME: &'static str = "WireToSurface2Pdu";
 
     const FIXED_PART_SIZE: usize = 2 /* SurfaceId */ + 2 /* CodecId */ + 4 /* ContextId */ + 1 /* PixelFormat */ + 4 /* BitmapDataLen */;
 }
 
-impl Encode for WireToSurface2Pdu {
+impl Encode for WireToSurface2Pdu<'_> {
     fn encode(&self, dst: &mut WriteCursor<'_>) -> EncodeResult<()> {
         ensure_size!(in: dst, size: self.size());
 
@@ -139,7 +173,7 @@ impl Encode for WireToSurface2Pdu {
     }
 }
 
-impl<'a> Decode<'a> for WireToSurface2Pdu {
+impl<'a> Decode<'a> for WireToSurface2Pdu<'a> {
     fn decode(src: &mut ReadCursor<'a>) -> DecodeResult<Self> {
         ensure_fixed_part_size!(in: src);
 
@@ -152,7 +186,7 @@ impl<'a> Decode<'a> for WireToSurface2Pdu {
         let bitmap_data_length = cast_length!("BitmapDataLen", src.read_u32())?;
 
         ensure_size!(in: src, size: bitmap_data_length);
-        let bitmap_data = src.read_slice(bitmap_data_length).to_vec();
+        let bitmap_data = Cow::Borrowed(src.read_slice(bitmap_data_length));
 
         Ok(Self {
             surface_id,
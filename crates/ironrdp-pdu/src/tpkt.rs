@@ -56,7 +56,7 @@ impl TpktHeader {
             return Err(unsupported_version_err!("TPKT version", version));
         }
 
-        read_padding!(src, 1);
+        read_zeroed_padding!(src, 1)?;
 
         let packet_length = src.read_u16_be();
 
@@ -1,4 +1,5 @@
-use std::string::FromUtf16Error;
+use alloc::string::{FromUtf16Error, String};
+use alloc::vec::Vec;
 
 pub fn read_utf16_string(utf16_payload: &[u8], utf16_size_hint: Option<usize>) -> Result<String, FromUtf16Error> {
     let mut trimmed_utf16: Vec<u16> = if let Some(size_hint) = utf16_size_hint {
@@ -1,8 +1,10 @@
-use byteorder::{LittleEndian, ReadBytesExt as _};
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Debug;
+use core::mem::size_of;
+use core::ops::Add;
+
 use num_derive::{FromPrimitive, ToPrimitive};
-use std::fmt::Debug;
-use std::mem::size_of;
-use std::ops::Add;
 
 use crate::{DecodeResult, EncodeResult};
 use ironrdp_core::{ensure_size, invalid_field_err, other_err, ReadCursor, WriteCursor};
@@ -30,15 +32,20 @@ pub fn to_utf16_bytes(value: &str) -> Vec<u8> {
         .collect::<Vec<u8>>()
 }
 
-pub fn from_utf16_bytes(mut value: &[u8]) -> String {
-    let mut value_u16 = vec![0x00; value.len() / 2];
-    value
-        .read_u16_into::<LittleEndian>(value_u16.as_mut())
-        .expect("read_u16_into cannot fail at this point");
+pub fn from_utf16_bytes(value: &[u8]) -> String {
+    let value_u16 = read_u16_le_vec(value);
 
     String::from_utf16_lossy(value_u16.as_ref())
 }
 
+/// Reads little-endian `u16`s, ignoring the trailing byte of an odd-sized buffer
+fn read_u16_le_vec(buffer: &[u8]) -> Vec<u16> {
+    buffer
+        .chunks_exact(2)
+        .map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]))
+        .collect()
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq, FromPrimitive, ToPrimitive)]
 pub enum CharacterSet {
     Ansi = 1,
@@ -91,14 +98,9 @@ pub fn read_string_from_cursor(
     let result = match character_set {
         CharacterSet::Unicode => {
             ensure_size!(ctx: "Decode string (UTF-16)", in: cursor, size: size);
-            let mut slice = cursor.read_slice(size);
-
-            let str_buffer = &mut slice;
-            let mut u16_buffer = vec![0u16; str_buffer.len() / 2];
+            let slice = cursor.read_slice(size);
 
-            str_buffer
-                .read_u16_into::<LittleEndian>(u16_buffer.as_mut())
-                .expect("BUG: str_buffer is always even for UTF16");
+            let u16_buffer = read_u16_le_vec(slice);
 
             String::from_utf16(&u16_buffer)
                 .map_err(|_| invalid_field_err!("UTF16 decode", "buffer", "Failed to decode UTF16 string"))?
@@ -245,7 +247,7 @@ impl<T> SplitTo for &mut [T] {
     fn split_to(&mut self, n: usize) -> Self {
         assert!(n <= self.len());
 
-        let (a, b) = std::mem::take(self).split_at_mut(n);
+        let (a, b) = core::mem::take(self).split_at_mut(n);
         *self = b;
 
         a
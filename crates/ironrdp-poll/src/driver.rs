@@ -0,0 +1,562 @@
+use core::mem;
+use std::collections::VecDeque;
+use std::time::Instant;
+
+use bytes::{Buf as _, Bytes, BytesMut};
+use ironrdp_connector::connection_activation::{ConnectionActivationSequence, ConnectionActivationState};
+use ironrdp_connector::credssp::{CredsspProcessGenerator, CredsspSequence, KerberosConfig};
+use ironrdp_connector::sspi::credssp::ClientState;
+use ironrdp_connector::sspi::generator::GeneratorState;
+use ironrdp_connector::sspi::network_client::NetworkClient;
+use ironrdp_connector::{
+    general_err, ClientConnector, ClientConnectorState, ConnectorError, ConnectorErrorKind, ConnectorResult,
+    DesktopSize, Sequence as _, ServerName, State as _, Written,
+};
+use ironrdp_core::WriteBuf;
+use ironrdp_graphics::image_processing::PixelFormat;
+use ironrdp_pdu::{Action, PduHint};
+use ironrdp_session::image::DecodedImage;
+use ironrdp_session::{fast_path, ActiveStage, ActiveStageOutput, GracefulDisconnectReason, SessionResult};
+
+use crate::{DriverError, DriverErrorExt as _, DriverResult};
+
+/// Event emitted by the [`Driver`], retrieved with [`Driver::poll_event`].
+#[derive(Debug)]
+pub enum DriverEvent {
+    /// The TLS upgrade must be performed on the transport, followed by a call to
+    /// [`Driver::mark_security_upgrade_as_done`].
+    SecurityUpgradeRequired,
+    /// The connection sequence is completed and the session is active.
+    Connected { desktop_size: DesktopSize },
+    /// The Deactivation-Reactivation Sequence is completed, the image has been resized.
+    Reactivated { desktop_size: DesktopSize },
+    /// Output of the active session, other than the response frames already queued for writing.
+    Session(ActiveStageOutput),
+    /// The server ended the session.
+    Terminated(GracefulDisconnectReason),
+}
+
+/// Sans-io driver wrapping the connection sequence and the active session.
+///
+/// The driver performs no I/O: bytes received from the transport are fed with [`Driver::handle_readable`],
+/// and bytes to send are drained with [`Driver::handle_writable`]. Any error returned while processing
+/// the received bytes is fatal, and leaves the driver terminated.
+pub struct Driver {
+    state: DriverState,
+    server_name: ServerName,
+    network_client: Option<Box<dyn NetworkClient>>,
+    kerberos_config: Option<KerberosConfig>,
+    connect_deadline: Option<Instant>,
+    pixel_format: PixelFormat,
+    input: BytesMut,
+    output: BytesMut,
+    buf: WriteBuf,
+    events: VecDeque<DriverEvent>,
+}
+
+#[derive(Default)]
+enum DriverState {
+    Connecting(Box<ClientConnector>),
+    SecurityUpgrade(Box<ClientConnector>),
+    Credssp {
+        connector: Box<ClientConnector>,
+        sequence: Box<CredsspSequence>,
+    },
+    Active(Box<ActiveSession>),
+    Reactivating {
+        session: Box<ActiveSession>,
+        activation: Box<ConnectionActivationSequence>,
+    },
+    #[default]
+    Terminated,
+}
+
+struct ActiveSession {
+    stage: ActiveStage,
+    image: DecodedImage,
+}
+
+impl Driver {
+    /// `server_name` must be the actual target server hostname (as opposed to the proxy).
+    pub fn new(connector: ClientConnector, server_name: ServerName) -> Self {
+        Self {
+            state: DriverState::Connecting(Box::new(connector)),
+            server_name,
+            network_client: None,
+            kerberos_config: None,
+            connect_deadline: None,
+            pixel_format: PixelFormat::RgbA32,
+            input: BytesMut::new(),
+            output: BytesMut::new(),
+            buf: WriteBuf::new(),
+            events: VecDeque::new(),
+        }
+    }
+
+    /// Network client used to reach the KDC during the CredSSP sequence.
+    ///
+    /// The requests are resolved in place, so the client is expected to be blocking.
+    /// Without a network client, a CredSSP sequence requiring network access fails.
+    #[must_use]
+    pub fn with_network_client(mut self, network_client: Box<dyn NetworkClient>) -> Self {
+        self.network_client = Some(network_client);
+        self
+    }
+
+    #[must_use]
+    pub fn with_kerberos_config(mut self, kerberos_config: KerberosConfig) -> Self {
+        self.kerberos_config = Some(kerberos_config);
+        self
+    }
+
+    /// Instant past which the connection sequence fails, reported by [`Driver::next_timeout`].
+    #[must_use]
+    pub fn with_connect_deadline(mut self, deadline: Instant) -> Self {
+        self.connect_deadline = Some(deadline);
+        self
+    }
+
+    /// Pixel format of the image of the active session, RGBA32 by default.
+    #[must_use]
+    pub fn with_pixel_format(mut self, pixel_format: PixelFormat) -> Self {
+        self.pixel_format = pixel_format;
+        self
+    }
+
+    /// Starts the connection sequence, queuing the first PDUs for writing.
+    pub fn start(&mut self) -> DriverResult<()> {
+        self.fatal(Self::advance)
+    }
+
+    /// Whether the driver is waiting for bytes from the transport.
+    pub fn wants_read(&self) -> bool {
+        match &self.state {
+            DriverState::Connecting(connector) => connector.next_pdu_hint().is_some(),
+            DriverState::Credssp { sequence, .. } => sequence.next_pdu_hint().is_some(),
+            DriverState::Active(_) | DriverState::Reactivating { .. } => true,
+            DriverState::SecurityUpgrade(_) | DriverState::Terminated => false,
+        }
+    }
+
+    /// Whether bytes are pending to be written to the transport.
+    pub fn wants_write(&self) -> bool {
+        !self.output.is_empty()
+    }
+
+    /// Feeds the bytes read from the transport, processing all the complete PDUs received so far.
+    pub fn handle_readable(&mut self, bytes: &[u8]) -> DriverResult<()> {
+        if matches!(self.state, DriverState::Terminated) {
+            return Err(DriverError::general("driver is terminated"));
+        }
+
+        self.input.extend_from_slice(bytes);
+
+        self.fatal(Self::process_input)
+    }
+
+    /// Copies the pending bytes into `out`, returning how many bytes were copied.
+    pub fn handle_writable(&mut self, out: &mut [u8]) -> usize {
+        let len = out.len().min(self.output.len());
+        out[..len].copy_from_slice(&self.output[..len]);
+        self.output.advance(len);
+        len
+    }
+
+    /// Bytes pending to be written to the transport, for transports writing from a borrowed buffer.
+    ///
+    /// [`Driver::consume_written`] must be called with the number of bytes actually written.
+    pub fn pending_write(&self) -> &[u8] {
+        &self.output
+    }
+
+    pub fn consume_written(&mut self, len: usize) {
+        self.output.advance(len);
+    }
+
+    /// Instant at which [`Driver::handle_timeout`] must be called, if any.
+    pub fn next_timeout(&self) -> Option<Instant> {
+        match self.state {
+            DriverState::Connecting(_) | DriverState::SecurityUpgrade(_) | DriverState::Credssp { .. } => {
+                self.connect_deadline
+            }
+            DriverState::Active(_) | DriverState::Reactivating { .. } | DriverState::Terminated => None,
+        }
+    }
+
+    pub fn handle_timeout(&mut self, now: Instant) -> DriverResult<()> {
+        match self.next_timeout() {
+            Some(deadline) if now >= deadline => {
+                self.state = DriverState::Terminated;
+                Err(DriverError::timeout("connection sequence"))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    pub fn poll_event(&mut self) -> Option<DriverEvent> {
+        self.events.pop_front()
+    }
+
+    /// Continues the connection sequence once the transport is upgraded to TLS.
+    ///
+    /// No bytes must have been fed since [`DriverEvent::SecurityUpgradeRequired`] was emitted.
+    pub fn mark_security_upgrade_as_done(&mut self, server_public_key: Vec<u8>) -> DriverResult<()> {
+        if !matches!(self.state, DriverState::SecurityUpgrade(_)) {
+            return Err(DriverError::general("no security upgrade is pending"));
+        }
+
+        let DriverState::SecurityUpgrade(mut connector) = mem::take(&mut self.state) else {
+            unreachable!()
+        };
+
+        if !self.input.is_empty() {
+            return Err(DriverError::general("received bytes before the security upgrade"));
+        }
+
+        trace!("Marked as upgraded");
+        connector.mark_security_upgrade_as_done();
+
+        if connector.should_perform_credssp() {
+            self.state = self.fatal(|driver| driver.begin_credssp(connector, server_public_key))?;
+        } else {
+            self.state = DriverState::Connecting(connector);
+        }
+
+        self.fatal(Self::advance)
+    }
+
+    pub fn is_active(&self) -> bool {
+        matches!(self.state, DriverState::Active(_))
+    }
+
+    pub fn is_terminated(&self) -> bool {
+        matches!(self.state, DriverState::Terminated)
+    }
+
+    /// Image of the active session, updated as the graphics PDUs are processed.
+    pub fn image(&self) -> Option<&DecodedImage> {
+        match &self.state {
+            DriverState::Active(session) | DriverState::Reactivating { session, .. } => Some(&session.image),
+            _ => None,
+        }
+    }
+
+    /// Gives access to the active stage, for sending input or shutting down the session.
+    ///
+    /// The response frames returned by `f` are queued for writing, and the other outputs are emitted as events.
+    pub fn with_active_stage<F>(&mut self, f: F) -> DriverResult<()>
+    where
+        F: FnOnce(&mut ActiveStage, &mut DecodedImage) -> SessionResult<Vec<ActiveStageOutput>>,
+    {
+        let DriverState::Active(session) = &mut self.state else {
+            return Err(DriverError::general("session is not active"));
+        };
+
+        let outputs = f(&mut session.stage, &mut session.image).map_err(DriverError::session)?;
+
+        self.handle_outputs(outputs);
+
+        Ok(())
+    }
+
+    /// Terminates the driver when `f` fails.
+    fn fatal<T>(&mut self, f: impl FnOnce(&mut Self) -> DriverResult<T>) -> DriverResult<T> {
+        let result = f(self);
+        if result.is_err() {
+            self.state = DriverState::Terminated;
+        }
+        result
+    }
+
+    /// Processes the complete PDUs buffered so far.
+    fn process_input(&mut self) -> DriverResult<()> {
+        self.advance()?;
+
+        while let Some((action, frame)) = self.next_frame()? {
+            trace!(length = frame.len(), "PDU received");
+            self.process_frame(action, &frame)?;
+            self.advance()?;
+        }
+
+        Ok(())
+    }
+
+    /// Splits the next complete PDU off the input buffer, if any.
+    fn next_frame(&mut self) -> DriverResult<Option<(Option<Action>, Bytes)>> {
+        loop {
+            let hint: &dyn PduHint = match &self.state {
+                DriverState::Connecting(connector) => match connector.next_pdu_hint() {
+                    Some(hint) => hint,
+                    None => return Ok(None),
+                },
+                DriverState::Credssp { sequence, .. } => match sequence.next_pdu_hint() {
+                    Some(hint) => hint,
+                    None => return Ok(None),
+                },
+                DriverState::Reactivating { activation, .. } => match activation.next_pdu_hint() {
+                    Some(hint) => hint,
+                    None => return Ok(None),
+                },
+                DriverState::Active(_) => {
+                    return match ironrdp_pdu::find_size(&self.input).map_err(DriverError::decode)? {
+                        Some(pdu_info) if pdu_info.length <= self.input.len() => {
+                            let frame = self.input.split_to(pdu_info.length).freeze();
+                            Ok(Some((Some(pdu_info.action), frame)))
+                        }
+                        _ => Ok(None),
+                    };
+                }
+                DriverState::SecurityUpgrade(_) | DriverState::Terminated => return Ok(None),
+            };
+
+            match hint.find_size(&self.input).map_err(DriverError::decode)? {
+                Some((matched, length)) if length <= self.input.len() => {
+                    let frame = self.input.split_to(length).freeze();
+                    if matched {
+                        return Ok(Some((None, frame)));
+                    }
+                    warn!("Received and lost an unexpected PDU");
+                }
+                _ => return Ok(None),
+            }
+        }
+    }
+
+    fn process_frame(&mut self, action: Option<Action>, frame: &[u8]) -> DriverResult<()> {
+        match &mut self.state {
+            DriverState::Connecting(connector) => {
+                debug!(connector.state = connector.state.name(), "Step");
+                self.buf.clear();
+                let written = connector.step(frame, &mut self.buf).map_err(DriverError::connector)?;
+                queue_written(&mut self.output, &self.buf, written);
+            }
+            DriverState::Credssp { sequence, .. } => {
+                let Some(ts_request) = sequence.decode_server_message(frame).map_err(DriverError::connector)? else {
+                    return Ok(());
+                };
+
+                let client_state = {
+                    let mut generator = sequence.process_ts_request(ts_request);
+                    resolve_generator(&mut generator, &mut self.network_client).map_err(DriverError::connector)?
+                }; // drop generator
+
+                self.buf.clear();
+                let written = sequence
+                    .handle_process_result(client_state, &mut self.buf)
+                    .map_err(DriverError::connector)?;
+                queue_written(&mut self.output, &self.buf, written);
+            }
+            DriverState::Reactivating { activation, .. } => {
+                self.buf.clear();
+                let written = activation.step(frame, &mut self.buf).map_err(DriverError::connector)?;
+                queue_written(&mut self.output, &self.buf, written);
+            }
+            DriverState::Active(session) => {
+                let action = action.expect("framed with find_size");
+                let outputs = session
+                    .stage
+                    .process(&mut session.image, action, frame)
+                    .map_err(DriverError::session)?;
+                self.handle_outputs(outputs);
+            }
+            DriverState::SecurityUpgrade(_) | DriverState::Terminated => {
+                return Err(DriverError::general("unexpected PDU"));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Runs the steps requiring no input, and moves on to the next state when the current one is completed.
+    fn advance(&mut self) -> DriverResult<()> {
+        loop {
+            match mem::take(&mut self.state) {
+                DriverState::Connecting(mut connector) => {
+                    if connector.should_perform_security_upgrade() {
+                        debug!("Security upgrade required");
+                        self.state = DriverState::SecurityUpgrade(connector);
+                        self.events.push_back(DriverEvent::SecurityUpgradeRequired);
+                        return Ok(());
+                    }
+
+                    if let ClientConnectorState::Connected { .. } = connector.state {
+                        let ClientConnectorState::Connected { result } = mem::take(&mut connector.state) else {
+                            unreachable!()
+                        };
+                        info!("Connected with success");
+                        let desktop_size = result.desktop_size;
+                        let image = DecodedImage::new(self.pixel_format, desktop_size.width, desktop_size.height);
+                        self.state = DriverState::Active(Box::new(ActiveSession {
+                            stage: ActiveStage::new(result),
+                            image,
+                        }));
+                        self.events.push_back(DriverEvent::Connected { desktop_size });
+                        return Ok(());
+                    }
+
+                    if connector.next_pdu_hint().is_some() {
+                        self.state = DriverState::Connecting(connector);
+                        return Ok(());
+                    }
+
+                    self.buf.clear();
+                    let written = connector.step_no_input(&mut self.buf).map_err(DriverError::connector)?;
+                    queue_written(&mut self.output, &self.buf, written);
+                    self.state = DriverState::Connecting(connector);
+                }
+                DriverState::Credssp {
+                    mut connector,
+                    sequence,
+                } => {
+                    if sequence.next_pdu_hint().is_some() {
+                        self.state = DriverState::Credssp { connector, sequence };
+                        return Ok(());
+                    }
+
+                    debug!("CredSSP sequence completed");
+                    connector.mark_credssp_as_done();
+                    self.state = DriverState::Connecting(connector);
+                }
+                DriverState::Reactivating {
+                    mut session,
+                    mut activation,
+                } => {
+                    if let ConnectionActivationState::Finalized {
+                        io_channel_id,
+                        user_channel_id,
+                        desktop_size,
+                        no_server_pointer,
+                        pointer_software_rendering,
+                        ..
+                    } = activation.state
+                    {
+                        debug!(?desktop_size, "Deactivation-Reactivation Sequence completed");
+                        session.image = DecodedImage::new(self.pixel_format, desktop_size.width, desktop_size.height);
+                        session.stage.set_fastpath_processor(
+                            fast_path::ProcessorBuilder {
+                                io_channel_id,
+                                user_channel_id,
+                                no_server_pointer,
+                                pointer_software_rendering,
+                            }
+                            .build(),
+                        );
+                        session.stage.set_no_server_pointer(no_server_pointer);
+                        self.state = DriverState::Active(session);
+                        self.events.push_back(DriverEvent::Reactivated { desktop_size });
+                        return Ok(());
+                    }
+
+                    if activation.next_pdu_hint().is_none() {
+                        self.buf.clear();
+                        let written = activation
+                            .step_no_input(&mut self.buf)
+                            .map_err(DriverError::connector)?;
+                        queue_written(&mut self.output, &self.buf, written);
+                        self.state = DriverState::Reactivating { session, activation };
+                    } else {
+                        self.state = DriverState::Reactivating { session, activation };
+                        return Ok(());
+                    }
+                }
+                state @ (DriverState::SecurityUpgrade(_) | DriverState::Active(_) | DriverState::Terminated) => {
+                    self.state = state;
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    fn begin_credssp(
+        &mut self,
+        connector: Box<ClientConnector>,
+        server_public_key: Vec<u8>,
+    ) -> DriverResult<DriverState> {
+        debug!("CredSSP procedure");
+
+        let selected_protocol = match connector.state {
+            ClientConnectorState::Credssp { selected_protocol, .. } => selected_protocol,
+            _ => {
+                return Err(DriverError::connector(general_err!(
+                    "invalid connector state for CredSSP sequence"
+                )))
+            }
+        };
+
+        let (mut sequence, ts_request) = CredsspSequence::init(
+            connector.config.credentials.clone(),
+            connector.config.domain.as_deref(),
+            selected_protocol,
+            self.server_name.clone(),
+            server_public_key,
+            self.kerberos_config.clone(),
+            connector.config.restricted_admin,
+        )
+        .map_err(DriverError::connector)?;
+
+        let client_state = {
+            let mut generator = sequence.process_ts_request(ts_request);
+            resolve_generator(&mut generator, &mut self.network_client).map_err(DriverError::connector)?
+        }; // drop generator
+
+        self.buf.clear();
+        let written = sequence
+            .handle_process_result(client_state, &mut self.buf)
+            .map_err(DriverError::connector)?;
+        queue_written(&mut self.output, &self.buf, written);
+
+        Ok(DriverState::Credssp {
+            connector,
+            sequence: Box::new(sequence),
+        })
+    }
+
+    fn handle_outputs(&mut self, outputs: Vec<ActiveStageOutput>) {
+        for output in outputs {
+            match output {
+                ActiveStageOutput::ResponseFrame(frame) => self.output.extend_from_slice(&frame),
+                ActiveStageOutput::DeactivateAll(activation) => {
+                    debug!("Received Server Deactivate All PDU, executing Deactivation-Reactivation Sequence");
+                    if let DriverState::Active(session) = mem::take(&mut self.state) {
+                        self.state = DriverState::Reactivating { session, activation };
+                    }
+                }
+                ActiveStageOutput::Terminate(reason) => {
+                    info!(%reason, "Session terminated");
+                    self.state = DriverState::Terminated;
+                    self.events.push_back(DriverEvent::Terminated(reason));
+                }
+                output => self.events.push_back(DriverEvent::Session(output)),
+            }
+        }
+    }
+}
+
+fn queue_written(output: &mut BytesMut, buf: &WriteBuf, written: Written) {
+    if let Some(response_len) = written.size() {
+        trace!(response_len, "Send response");
+        output.extend_from_slice(&buf[..response_len]);
+    }
+}
+
+/// Resolves the CredSSP generator in place, the network requests being sent with the blocking `network_client`.
+fn resolve_generator(
+    generator: &mut CredsspProcessGenerator<'_>,
+    network_client: &mut Option<Box<dyn NetworkClient>>,
+) -> ConnectorResult<ClientState> {
+    let mut state = generator.start();
+
+    loop {
+        match state {
+            GeneratorState::Suspended(request) => {
+                let Some(network_client) = network_client.as_deref_mut() else {
+                    return Err(general_err!("CredSSP requires a network client"));
+                };
+                state = generator.resume(network_client.send(&request));
+            }
+            GeneratorState::Completed(client_state) => {
+                break client_state.map_err(|e| ConnectorError::new("CredSSP", ConnectorErrorKind::Credssp(e)))
+            }
+        }
+    }
+}
@@ -0,0 +1,81 @@
+#![doc = include_str!("../README.md")]
+
+#[macro_use]
+extern crate tracing;
+
+mod driver;
+
+use core::fmt;
+
+use ironrdp_connector::ConnectorError;
+use ironrdp_session::SessionError;
+
+pub use self::driver::*;
+
+pub type DriverResult<T> = Result<T, DriverError>;
+
+#[non_exhaustive]
+#[derive(Debug)]
+pub enum DriverErrorKind {
+    Connector(ConnectorError),
+    Session(SessionError),
+    Decode(ironrdp_core::DecodeError),
+    Timeout,
+    General,
+}
+
+impl fmt::Display for DriverErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self {
+            DriverErrorKind::Connector(_) => write!(f, "connector error"),
+            DriverErrorKind::Session(_) => write!(f, "session error"),
+            DriverErrorKind::Decode(_) => write!(f, "decode error"),
+            DriverErrorKind::Timeout => write!(f, "timed out"),
+            DriverErrorKind::General => write!(f, "general error"),
+        }
+    }
+}
+
+impl std::error::Error for DriverErrorKind {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self {
+            DriverErrorKind::Connector(e) => Some(e),
+            DriverErrorKind::Session(e) => Some(e),
+            DriverErrorKind::Decode(e) => Some(e),
+            DriverErrorKind::Timeout => None,
+            DriverErrorKind::General => None,
+        }
+    }
+}
+
+pub type DriverError = ironrdp_error::Error<DriverErrorKind>;
+
+pub trait DriverErrorExt {
+    fn connector(error: ConnectorError) -> Self;
+    fn session(error: SessionError) -> Self;
+    fn decode(error: ironrdp_core::DecodeError) -> Self;
+    fn timeout(context: &'static str) -> Self;
+    fn general(context: &'static str) -> Self;
+}
+
+impl DriverErrorExt for DriverError {
+    fn connector(error: ConnectorError) -> Self {
+        Self::new("connection sequence", DriverErrorKind::Connector(error))
+    }
+
+    fn session(error: SessionError) -> Self {
+        Self::new("active session", DriverErrorKind::Session(error))
+    }
+
+    fn decode(error: ironrdp_core::DecodeError) -> Self {
+        Self::new("decode error", DriverErrorKind::Decode(error))
+    }
+
+    fn timeout(context: &'static str) -> Self {
+        Self::new(context, DriverErrorKind::Timeout)
+    }
+
+    fn general(context: &'static str) -> Self {
+        Self::new(context, DriverErrorKind::General)
+    }
+}
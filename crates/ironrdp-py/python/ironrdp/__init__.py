@@ -0,0 +1,3 @@
+from ._ironrdp import Config, Image, IronRdpError, MouseButton, Session, connect
+
+__all__ = ["Config", "Image", "IronRdpError", "MouseButton", "Session", "connect"]
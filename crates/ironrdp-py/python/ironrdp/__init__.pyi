@@ -0,0 +1,58 @@
+from enum import Enum
+from os import PathLike
+from typing import Awaitable, Optional, Tuple, Union
+
+class IronRdpError(Exception): ...
+
+class Config:
+    host: str
+    port: int
+    username: str
+    domain: Optional[str]
+    width: int
+    height: int
+    enable_credssp: bool
+    client_name: str
+    def __init__(
+        self,
+        host: str,
+        username: str,
+        password: str,
+        *,
+        port: int = 3389,
+        domain: Optional[str] = None,
+        width: int = 1280,
+        height: int = 1024,
+        enable_credssp: bool = True,
+        client_name: str = "ironrdp-py",
+    ) -> None: ...
+
+class Image:
+    width: int
+    height: int
+    data: bytes
+    def pixel(self, x: int, y: int) -> Tuple[int, int, int]: ...
+    def to_png(self) -> bytes: ...
+    def save_png(self, path: Union[str, PathLike[str]]) -> None: ...
+
+class MouseButton(Enum):
+    Left = 0
+    Middle = 1
+    Right = 2
+    X1 = 3
+    X2 = 4
+
+class Session:
+    is_connected: bool
+    def mouse_move(self, x: int, y: int) -> Awaitable[None]: ...
+    def click(self, x: int, y: int, button: MouseButton = MouseButton.Left) -> Awaitable[None]: ...
+    def mouse_button(self, button: MouseButton, pressed: bool) -> Awaitable[None]: ...
+    def wheel(self, rotation_units: int, vertical: bool = True) -> Awaitable[None]: ...
+    def key(self, scancode: int, pressed: Optional[bool] = None) -> Awaitable[None]: ...
+    def type_text(self, text: str) -> Awaitable[None]: ...
+    def release_all(self) -> Awaitable[None]: ...
+    def screenshot(self) -> Awaitable[Image]: ...
+    def wait_for_update(self) -> Awaitable[None]: ...
+    def disconnect(self) -> Awaitable[str]: ...
+
+def connect(config: Config) -> Awaitable[Session]: ...
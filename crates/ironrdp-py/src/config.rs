@@ -0,0 +1,116 @@
+use ironrdp::connector::{self, Credentials, DesktopSize};
+use ironrdp::pdu::gcc::KeyboardType;
+use ironrdp::pdu::rdp::capability_sets::MajorPlatformType;
+use ironrdp::pdu::rdp::client_info::PerformanceFlags;
+use ironrdp_core::DecodeMode;
+use pyo3::prelude::*;
+
+/// Parameters of an RDP connection
+#[pyclass(module = "ironrdp", frozen)]
+#[derive(Clone)]
+pub(crate) struct Config {
+    #[pyo3(get)]
+    pub(crate) host: String,
+    #[pyo3(get)]
+    pub(crate) port: u16,
+    #[pyo3(get)]
+    pub(crate) username: String,
+    password: String,
+    #[pyo3(get)]
+    pub(crate) domain: Option<String>,
+    #[pyo3(get)]
+    pub(crate) width: u16,
+    #[pyo3(get)]
+    pub(crate) height: u16,
+    #[pyo3(get)]
+    pub(crate) enable_credssp: bool,
+    #[pyo3(get)]
+    pub(crate) client_name: String,
+}
+
+#[pymethods]
+impl Config {
+    #[new]
+    #[pyo3(signature = (host, username, password, *, port = 3389, domain = None, width = 1280, height = 1024, enable_credssp = true, client_name = "ironrdp-py".to_owned()))]
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        host: String,
+        username: String,
+        password: String,
+        port: u16,
+        domain: Option<String>,
+        width: u16,
+        height: u16,
+        enable_credssp: bool,
+        client_name: String,
+    ) -> Self {
+        Self {
+            host,
+            port,
+            username,
+            password,
+            domain,
+            width,
+            height,
+            enable_credssp,
+            client_name,
+        }
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Config(host={:?}, port={}, username={:?}, domain={:?}, width={}, height={})",
+            self.host, self.port, self.username, self.domain, self.width, self.height
+        )
+    }
+}
+
+impl Config {
+    pub(crate) fn to_connector_config(&self) -> connector::Config {
+        connector::Config {
+            credentials: Credentials::UsernamePassword {
+                username: self.username.clone(),
+                password: self.password.clone(),
+            },
+            domain: self.domain.clone(),
+            enable_tls: true,
+            enable_credssp: self.enable_credssp,
+            keyboard_type: KeyboardType::IbmEnhanced,
+            keyboard_subtype: 0,
+            keyboard_layout: 0,
+            keyboard_functional_keys_count: 12,
+            ime_file_name: String::new(),
+            dig_product_id: String::new(),
+            desktop_size: DesktopSize {
+                width: self.width,
+                height: self.height,
+            },
+            desktop_scale_factor: 0,
+            bitmap: None,
+            client_build: 0,
+            client_name: self.client_name.clone(),
+            client_dir: "C:\\Windows\\System32\\mstscax.dll".to_owned(),
+
+            #[cfg(windows)]
+            platform: MajorPlatformType::WINDOWS,
+            #[cfg(target_os = "macos")]
+            platform: MajorPlatformType::MACINTOSH,
+            #[cfg(target_os = "linux")]
+            platform: MajorPlatformType::UNIX,
+            #[cfg(not(any(windows, target_os = "macos", target_os = "linux")))]
+            platform: MajorPlatformType::UNSPECIFIED,
+
+            // The pointer is rendered into the screenshots, as seen by a user
+            no_server_pointer: false,
+            pointer_software_rendering: true,
+            autologon: false,
+            restricted_admin: false,
+            auto_reconnect: None,
+            decode_mode: DecodeMode::Lenient,
+            decode_limits: Default::default(),
+            monitors: Vec::new(),
+            persistent_bitmap_cache: Vec::new(),
+            performance_flags: PerformanceFlags::default(),
+        }
+    }
+}
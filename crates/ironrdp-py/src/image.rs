@@ -0,0 +1,92 @@
+use std::path::PathBuf;
+
+use anyhow::Context as _;
+use ironrdp::session::image::DecodedImage;
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+
+use crate::to_py_err;
+
+/// Snapshot of the remote desktop, as RGBA pixels
+#[pyclass(module = "ironrdp", frozen)]
+pub(crate) struct Image {
+    #[pyo3(get)]
+    width: u16,
+    #[pyo3(get)]
+    height: u16,
+    data: Vec<u8>,
+}
+
+#[pymethods]
+impl Image {
+    /// Raw RGBA pixels, row by row
+    #[getter]
+    fn data<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new(py, &self.data)
+    }
+
+    /// Returns the `(r, g, b)` color of the pixel at the given coordinates
+    fn pixel(&self, x: u16, y: u16) -> PyResult<(u8, u8, u8)> {
+        let offset = self.offset(x, y).map_err(to_py_err)?;
+        let pixel = &self.data[offset..];
+        Ok((pixel[0], pixel[1], pixel[2]))
+    }
+
+    /// Encodes the image as PNG
+    fn to_png<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
+        let png = self.encode_png().map_err(to_py_err)?;
+        Ok(PyBytes::new(py, &png))
+    }
+
+    /// Saves the image to the disk, as PNG
+    fn save_png(&self, path: PathBuf) -> PyResult<()> {
+        let png = self.encode_png().map_err(to_py_err)?;
+        std::fs::write(path, png).map_err(PyErr::from)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Image(width={}, height={})", self.width, self.height)
+    }
+}
+
+impl Image {
+    pub(crate) fn from_decoded_image(image: &DecodedImage) -> Self {
+        Self {
+            width: image.width(),
+            height: image.height(),
+            data: image.data().to_vec(),
+        }
+    }
+
+    fn offset(&self, x: u16, y: u16) -> anyhow::Result<usize> {
+        anyhow::ensure!(
+            x < self.width && y < self.height,
+            "pixel ({x}, {y}) is out of the {}x{} image",
+            self.width,
+            self.height
+        );
+
+        // Can’t overflow: both coordinates are bounded by u16::MAX.
+        let offset = usize::from(y)
+            .checked_mul(usize::from(self.width))
+            .and_then(|offset| offset.checked_add(usize::from(x)))
+            .and_then(|offset| offset.checked_mul(4))
+            .expect("never overflow");
+
+        Ok(offset)
+    }
+
+    fn encode_png(&self) -> anyhow::Result<Vec<u8>> {
+        let mut png = Vec::new();
+
+        let mut encoder = png::Encoder::new(&mut png, u32::from(self.width), u32::from(self.height));
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+
+        let mut writer = encoder.write_header().context("write PNG header")?;
+        writer.write_image_data(&self.data).context("write PNG data")?;
+        writer.finish().context("finish PNG")?;
+
+        Ok(png)
+    }
+}
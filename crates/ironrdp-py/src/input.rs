@@ -0,0 +1,76 @@
+use ironrdp::input::{self, MousePosition, Operation, Scancode, WheelRotations};
+use pyo3::prelude::*;
+
+#[pyclass(module = "ironrdp", eq, eq_int)]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum MouseButton {
+    Left,
+    Middle,
+    Right,
+    /// Typically Browser Back button
+    X1,
+    /// Typically Browser Forward button
+    X2,
+}
+
+impl From<MouseButton> for input::MouseButton {
+    fn from(button: MouseButton) -> Self {
+        match button {
+            MouseButton::Left => Self::Left,
+            MouseButton::Middle => Self::Middle,
+            MouseButton::Right => Self::Right,
+            MouseButton::X1 => Self::X1,
+            MouseButton::X2 => Self::X2,
+        }
+    }
+}
+
+pub(crate) fn mouse_move(x: u16, y: u16) -> Vec<Operation> {
+    vec![Operation::MouseMove(MousePosition { x, y })]
+}
+
+pub(crate) fn click(x: u16, y: u16, button: MouseButton) -> Vec<Operation> {
+    vec![
+        Operation::MouseMove(MousePosition { x, y }),
+        Operation::MouseButtonPressed(button.into()),
+        Operation::MouseButtonReleased(button.into()),
+    ]
+}
+
+pub(crate) fn mouse_button(button: MouseButton, pressed: bool) -> Vec<Operation> {
+    if pressed {
+        vec![Operation::MouseButtonPressed(button.into())]
+    } else {
+        vec![Operation::MouseButtonReleased(button.into())]
+    }
+}
+
+pub(crate) fn wheel(rotation_units: i16, is_vertical: bool) -> Vec<Operation> {
+    vec![Operation::WheelRotations(WheelRotations {
+        is_vertical,
+        rotation_units,
+    })]
+}
+
+/// Presses then releases the key, unless `pressed` tells otherwise
+pub(crate) fn key(scancode: u16, pressed: Option<bool>) -> Vec<Operation> {
+    let scancode = Scancode::from_u16(scancode);
+
+    match pressed {
+        Some(true) => vec![Operation::KeyPressed(scancode)],
+        Some(false) => vec![Operation::KeyReleased(scancode)],
+        None => vec![Operation::KeyPressed(scancode), Operation::KeyReleased(scancode)],
+    }
+}
+
+/// Types the text using Unicode keyboard events, independently of the keyboard layout of the server
+pub(crate) fn type_text(text: &str) -> Vec<Operation> {
+    text.chars()
+        .flat_map(|character| {
+            [
+                Operation::UnicodeKeyPressed(character),
+                Operation::UnicodeKeyReleased(character),
+            ]
+        })
+        .collect()
+}
@@ -0,0 +1,33 @@
+#![doc = include_str!("../README.md")]
+
+#[macro_use]
+extern crate tracing;
+
+mod config;
+mod image;
+mod input;
+mod session;
+
+use pyo3::prelude::*;
+
+pyo3::create_exception!(
+    ironrdp,
+    IronRdpError,
+    pyo3::exceptions::PyException,
+    "Error raised when the connection or the session fails"
+);
+
+fn to_py_err(error: impl Into<anyhow::Error>) -> PyErr {
+    IronRdpError::new_err(format!("{:#}", error.into()))
+}
+
+#[pymodule]
+fn _ironrdp(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add("IronRdpError", m.py().get_type::<IronRdpError>())?;
+    m.add_class::<config::Config>()?;
+    m.add_class::<image::Image>()?;
+    m.add_class::<input::MouseButton>()?;
+    m.add_class::<session::Session>()?;
+    m.add_function(wrap_pyfunction!(session::connect, m)?)?;
+    Ok(())
+}
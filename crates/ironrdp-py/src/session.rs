@@ -0,0 +1,385 @@
+use std::thread;
+
+use anyhow::Context as _;
+use ironrdp::connector::connection_activation::ConnectionActivationState;
+use ironrdp::connector::{self, ConnectionResult};
+use ironrdp::graphics::image_processing::PixelFormat;
+use ironrdp::input::Operation;
+use ironrdp::session::image::DecodedImage;
+use ironrdp::session::{fast_path, ActiveStage, ActiveStageOutput, GracefulDisconnectReason};
+use ironrdp_core::WriteBuf;
+use ironrdp_tokio::single_sequence_step_read;
+use pyo3::prelude::*;
+use tokio::net::TcpStream;
+use tokio::sync::{mpsc, oneshot};
+
+use crate::config::Config;
+use crate::image::Image;
+use crate::input::{self, MouseButton};
+use crate::{to_py_err, IronRdpError};
+
+type UpgradedFramed = ironrdp_tokio::TokioFramed<ironrdp_tls::TlsStream<TcpStream>>;
+
+/*
+    Why a dedicated thread?
+
+    The active stage is not `Send` (the decoded pointers are reference counted), so it can’t be
+    moved across the worker threads of the runtime driving the Python awaitables. Instead, the
+    session is processed by a single-threaded runtime on its own thread, and the methods of the
+    `Session` object are sending commands to it.
+*/
+
+/// Connects to the server described by the configuration
+///
+/// Returns an awaitable resolving to a `Session`.
+#[pyfunction]
+pub(crate) fn connect(py: Python<'_>, config: Config) -> PyResult<Bound<'_, PyAny>> {
+    let (connected_tx, connected_rx) = oneshot::channel();
+
+    thread::Builder::new()
+        .name("ironrdp-session".to_owned())
+        .spawn(move || run(config, connected_tx))
+        .map_err(to_py_err)?;
+
+    pyo3_async_runtimes::tokio::future_into_py(py, async move {
+        connected_rx
+            .await
+            .map_err(|_| IronRdpError::new_err("session thread stopped unexpectedly"))?
+            .map_err(to_py_err)
+    })
+}
+
+/// Active RDP session, processed in the background
+///
+/// Graphics updates are applied to the remote desktop image as they are received, and
+/// the methods returning awaitables are resolved once the server has been sent the input.
+#[pyclass(module = "ironrdp", frozen)]
+pub(crate) struct Session {
+    commands: mpsc::UnboundedSender<Command>,
+}
+
+#[pymethods]
+impl Session {
+    /// Whether the session is still running
+    #[getter]
+    fn is_connected(&self) -> bool {
+        !self.commands.is_closed()
+    }
+
+    /// Moves the mouse cursor to the given position
+    fn mouse_move<'py>(&self, py: Python<'py>, x: u16, y: u16) -> PyResult<Bound<'py, PyAny>> {
+        self.input(py, input::mouse_move(x, y))
+    }
+
+    /// Moves the mouse cursor to the given position, then clicks
+    #[pyo3(signature = (x, y, button = MouseButton::Left))]
+    fn click<'py>(&self, py: Python<'py>, x: u16, y: u16, button: MouseButton) -> PyResult<Bound<'py, PyAny>> {
+        self.input(py, input::click(x, y, button))
+    }
+
+    /// Presses or releases a mouse button, at the current position of the cursor
+    fn mouse_button<'py>(&self, py: Python<'py>, button: MouseButton, pressed: bool) -> PyResult<Bound<'py, PyAny>> {
+        self.input(py, input::mouse_button(button, pressed))
+    }
+
+    /// Rotates the mouse wheel, in units of 1/120 of a notch
+    #[pyo3(signature = (rotation_units, vertical = true))]
+    fn wheel<'py>(&self, py: Python<'py>, rotation_units: i16, vertical: bool) -> PyResult<Bound<'py, PyAny>> {
+        self.input(py, input::wheel(rotation_units, vertical))
+    }
+
+    /// Presses then releases a key, identified by its scancode (0xE0 prefix for extended keys)
+    ///
+    /// With `pressed`, the key is only pressed or released.
+    #[pyo3(signature = (scancode, pressed = None))]
+    fn key<'py>(&self, py: Python<'py>, scancode: u16, pressed: Option<bool>) -> PyResult<Bound<'py, PyAny>> {
+        self.input(py, input::key(scancode, pressed))
+    }
+
+    /// Types the text, independently of the keyboard layout of the server
+    fn type_text<'py>(&self, py: Python<'py>, text: &str) -> PyResult<Bound<'py, PyAny>> {
+        self.input(py, input::type_text(text))
+    }
+
+    /// Releases all the keys and mouse buttons currently pressed
+    fn release_all<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        self.command(py, |done| Command::ReleaseAll { done })
+    }
+
+    /// Captures the remote desktop
+    ///
+    /// Returns an awaitable resolving to an `Image`.
+    fn screenshot<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        self.command(py, |image| Command::Screenshot { image })
+    }
+
+    /// Returns an awaitable resolved on the next graphics update
+    ///
+    /// Combine with `asyncio.wait_for` to wait for the remote desktop to settle.
+    fn wait_for_update<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        self.command(py, |updated| Command::WaitForUpdate { updated })
+    }
+
+    /// Gracefully disconnects from the server
+    ///
+    /// Returns an awaitable resolving to the disconnect reason.
+    fn disconnect<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        self.command(py, |reason| Command::Disconnect { reason })
+    }
+}
+
+impl Session {
+    fn input<'py>(&self, py: Python<'py>, operations: Vec<Operation>) -> PyResult<Bound<'py, PyAny>> {
+        self.command(py, |done| Command::Input { operations, done })
+    }
+
+    fn command<'py, T>(
+        &self,
+        py: Python<'py>,
+        command: impl FnOnce(oneshot::Sender<T>) -> Command,
+    ) -> PyResult<Bound<'py, PyAny>>
+    where
+        T: for<'a> IntoPyObject<'a> + Send + 'static,
+    {
+        let (reply_tx, reply_rx) = oneshot::channel();
+
+        self.commands
+            .send(command(reply_tx))
+            .map_err(|_| IronRdpError::new_err("session is terminated"))?;
+
+        pyo3_async_runtimes::tokio::future_into_py(py, async move {
+            reply_rx
+                .await
+                .map_err(|_| IronRdpError::new_err("session is terminated"))
+        })
+    }
+}
+
+enum Command {
+    Input {
+        operations: Vec<Operation>,
+        done: oneshot::Sender<()>,
+    },
+    ReleaseAll {
+        done: oneshot::Sender<()>,
+    },
+    Screenshot {
+        image: oneshot::Sender<Image>,
+    },
+    WaitForUpdate {
+        updated: oneshot::Sender<()>,
+    },
+    Disconnect {
+        reason: oneshot::Sender<String>,
+    },
+}
+
+fn run(config: Config, connected: oneshot::Sender<anyhow::Result<Session>>) {
+    let runtime = match tokio::runtime::Builder::new_current_thread().enable_all().build() {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            let _ = connected.send(Err(anyhow::Error::new(e).context("build runtime")));
+            return;
+        }
+    };
+
+    runtime.block_on(async move {
+        let (connection_result, framed) = match connect_impl(&config).await {
+            Ok(connected) => connected,
+            Err(e) => {
+                let _ = connected.send(Err(e));
+                return;
+            }
+        };
+
+        let (commands_tx, commands_rx) = mpsc::unbounded_channel();
+
+        if connected.send(Ok(Session { commands: commands_tx })).is_err() {
+            debug!("Connection cancelled");
+            return;
+        }
+
+        match active_session(framed, connection_result, commands_rx).await {
+            Ok(reason) => info!(%reason, "Session terminated"),
+            Err(e) => error!(error = format!("{e:#}"), "Session failed"),
+        }
+    });
+}
+
+async fn connect_impl(config: &Config) -> anyhow::Result<(ConnectionResult, UpgradedFramed)> {
+    let stream = TcpStream::connect((config.host.as_str(), config.port))
+        .await
+        .context("TCP connect")?;
+
+    let server_addr = stream.peer_addr().context("peer address")?;
+
+    let mut framed = ironrdp_tokio::TokioFramed::new(stream);
+
+    let mut connector = connector::ClientConnector::new(config.to_connector_config()).with_server_addr(server_addr);
+
+    let should_upgrade = ironrdp_tokio::connect_begin(&mut framed, &mut connector)
+        .await
+        .context("begin connection")?;
+
+    debug!("TLS upgrade");
+
+    // Ensure there is no leftover
+    let initial_stream = framed.into_inner_no_leftover();
+
+    let (upgraded_stream, server_public_key) = ironrdp_tls::upgrade(initial_stream, &config.host)
+        .await
+        .context("TLS upgrade")?;
+
+    let upgraded = ironrdp_tokio::mark_as_upgraded(should_upgrade, &mut connector);
+
+    let mut upgraded_framed = ironrdp_tokio::TokioFramed::new(upgraded_stream);
+
+    let connection_result = ironrdp_tokio::connect_finalize(
+        upgraded,
+        &mut upgraded_framed,
+        connector,
+        config.host.clone().into(),
+        server_public_key,
+        None,
+        None,
+    )
+    .await
+    .context("finalize connection")?;
+
+    debug!(?connection_result);
+
+    Ok((connection_result, upgraded_framed))
+}
+
+async fn active_session(
+    mut framed: UpgradedFramed,
+    connection_result: ConnectionResult,
+    mut commands: mpsc::UnboundedReceiver<Command>,
+) -> anyhow::Result<GracefulDisconnectReason> {
+    let mut image = DecodedImage::new(
+        PixelFormat::RgbA32,
+        connection_result.desktop_size.width,
+        connection_result.desktop_size.height,
+    );
+
+    let mut active_stage = ActiveStage::new(connection_result);
+    let mut input_database = ironrdp::input::Database::new();
+
+    let mut update_waiters = Vec::new();
+    let mut disconnect_waiters = Vec::new();
+    let mut shutting_down = false;
+
+    // The replies are ignored when sent to a Python awaitable which has been cancelled in the meantime.
+    let disconnect_reason = 'outer: loop {
+        let (outputs, done) = tokio::select! {
+            frame = framed.read_pdu() => {
+                let (action, payload) = frame.context("read frame")?;
+                trace!(?action, frame_length = payload.len(), "Frame received");
+
+                (active_stage.process(&mut image, action, &payload)?, None)
+            }
+            command = commands.recv(), if !shutting_down => {
+                match command {
+                    Some(Command::Input { operations, done }) => {
+                        let events = input_database.apply(operations);
+                        (active_stage.process_fastpath_input(&mut image, &events)?, Some(done))
+                    }
+                    Some(Command::ReleaseAll { done }) => {
+                        let events = input_database.release_all();
+                        (active_stage.process_fastpath_input(&mut image, &events)?, Some(done))
+                    }
+                    Some(Command::Screenshot { image: reply }) => {
+                        let _ = reply.send(Image::from_decoded_image(&image));
+                        (Vec::new(), None)
+                    }
+                    Some(Command::WaitForUpdate { updated }) => {
+                        update_waiters.push(updated);
+                        (Vec::new(), None)
+                    }
+                    Some(Command::Disconnect { reason }) => {
+                        disconnect_waiters.push(reason);
+                        shutting_down = true;
+                        (active_stage.graceful_shutdown()?, None)
+                    }
+                    None => {
+                        debug!("Session object dropped, disconnecting");
+                        shutting_down = true;
+                        (active_stage.graceful_shutdown()?, None)
+                    }
+                }
+            }
+        };
+
+        for out in outputs {
+            match out {
+                ActiveStageOutput::ResponseFrame(frame) => framed.write_all(&frame).await.context("write response")?,
+                ActiveStageOutput::GraphicsUpdate(_region) => {
+                    for updated in update_waiters.drain(..) {
+                        let _ = updated.send(());
+                    }
+                }
+                ActiveStageOutput::DeactivateAll(mut connection_activation) => {
+                    // Execute the Deactivation-Reactivation Sequence:
+                    // https://learn.microsoft.com/en-us/openspecs/windows_protocols/ms-rdpbcgr/dfc234ce-481a-4674-9a5d-2a7bafb14432
+                    debug!("Received Server Deactivate All PDU, executing Deactivation-Reactivation Sequence");
+                    let mut buf = WriteBuf::new();
+                    'activation_seq: loop {
+                        let written =
+                            single_sequence_step_read(&mut framed, &mut *connection_activation, &mut buf, None)
+                                .await
+                                .context("read deactivation-reactivation sequence step")?;
+
+                        if written.size().is_some() {
+                            framed
+                                .write_all(buf.filled())
+                                .await
+                                .context("write deactivation-reactivation sequence step")?;
+                        }
+
+                        if let ConnectionActivationState::Finalized {
+                            io_channel_id,
+                            user_channel_id,
+                            desktop_size,
+                            no_server_pointer,
+                            pointer_software_rendering,
+                            ..
+                        } = connection_activation.state
+                        {
+                            debug!(?desktop_size, "Deactivation-Reactivation Sequence completed");
+                            image = DecodedImage::new(PixelFormat::RgbA32, desktop_size.width, desktop_size.height);
+                            active_stage.set_fastpath_processor(
+                                fast_path::ProcessorBuilder {
+                                    io_channel_id,
+                                    user_channel_id,
+                                    no_server_pointer,
+                                    pointer_software_rendering,
+                                }
+                                .build(),
+                            );
+                            active_stage.set_no_server_pointer(no_server_pointer);
+                            break 'activation_seq;
+                        }
+                    }
+                }
+                ActiveStageOutput::Terminate(reason) => break 'outer reason,
+                ActiveStageOutput::PointerDefault
+                | ActiveStageOutput::PointerHidden
+                | ActiveStageOutput::PointerPosition { .. }
+                | ActiveStageOutput::PointerBitmap(_)
+                | ActiveStageOutput::MonitorLayout(_) => {
+                    // Not applicable, the pointer is rendered into the image.
+                }
+            }
+        }
+
+        if let Some(done) = done {
+            let _ = done.send(());
+        }
+    };
+
+    for reason in disconnect_waiters {
+        let _ = reason.send(disconnect_reason.description().to_owned());
+    }
+
+    Ok(disconnect_reason)
+}
@@ -0,0 +1,468 @@
+use core::fmt;
+use std::net::SocketAddr;
+
+use der::asn1::OctetString;
+
+use crate::{Capabilities, RDCleanPathErr, RDCleanPathPdu, ServerAddress, VERSION_1, VERSION_2};
+
+/// Inconsistency found when validating an RDCleanPath PDU
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RDCleanPathValidationError {
+    /// The version is older than [`VERSION_1`]
+    UnsupportedVersion(u64),
+    /// Capabilities are set on a PDU older than [`VERSION_2`]
+    CapabilitiesBeforeVersion2,
+    /// None of the destination, server address and error fields is set, so the PDU is neither a request, a response
+    /// nor an error
+    UnknownKind,
+    /// A field mandatory for the kind of PDU is missing
+    MissingField { kind: &'static str, field: &'static str },
+    /// A field is set which does not belong to the kind of PDU
+    UnexpectedField { kind: &'static str, field: &'static str },
+    /// A field could not be encoded
+    Encoding(der::Error),
+}
+
+impl fmt::Display for RDCleanPathValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnsupportedVersion(version) => write!(f, "unsupported version {version}"),
+            Self::CapabilitiesBeforeVersion2 => f.write_str("capabilities are only supported from version 2"),
+            Self::UnknownKind => f.write_str("neither a request, a response nor an error"),
+            Self::MissingField { kind, field } => write!(f, "{kind} is missing the {field} field"),
+            Self::UnexpectedField { kind, field } => write!(f, "unexpected {field} field in {kind}"),
+            Self::Encoding(e) => write!(f, "encoding error: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for RDCleanPathValidationError {}
+
+/// Kind of an RDCleanPath PDU, as found by [`RDCleanPathPdu::validate`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RDCleanPathKind {
+    Request,
+    Response,
+    Error,
+}
+
+impl RDCleanPathKind {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Request => "request",
+            Self::Response => "response",
+            Self::Error => "error",
+        }
+    }
+}
+
+impl RDCleanPathPdu {
+    pub fn builder() -> RDCleanPathPduBuilder {
+        RDCleanPathPduBuilder::new()
+    }
+
+    /// Checks that the fields set are consistent with each other, and returns the kind of PDU.
+    pub fn validate(&self) -> Result<RDCleanPathKind, RDCleanPathValidationError> {
+        if self.version < VERSION_1 {
+            return Err(RDCleanPathValidationError::UnsupportedVersion(self.version));
+        }
+
+        if self.capabilities.is_some() && self.version < VERSION_2 {
+            return Err(RDCleanPathValidationError::CapabilitiesBeforeVersion2);
+        }
+
+        let kind = if self.error.is_some() {
+            RDCleanPathKind::Error
+        } else if self.destination.is_some() {
+            RDCleanPathKind::Request
+        } else if self.server_addr.is_some() {
+            RDCleanPathKind::Response
+        } else {
+            return Err(RDCleanPathValidationError::UnknownKind);
+        };
+
+        let fields = [
+            ("error", self.error.is_some()),
+            ("destination", self.destination.is_some()),
+            ("proxy_auth", self.proxy_auth.is_some()),
+            ("server_auth", self.server_auth.is_some()),
+            ("preconnection_blob", self.preconnection_blob.is_some()),
+            ("x224_connection_pdu", self.x224_connection_pdu.is_some()),
+            ("server_cert_chain", self.server_cert_chain.is_some()),
+            ("ocsp_response", self.ocsp_response.is_some()),
+            ("server_addr", self.server_addr.is_some()),
+            ("client_cert_chain", self.client_cert_chain.is_some()),
+            ("kdc_proxy_url", self.kdc_proxy_url.is_some()),
+            ("server_addresses", self.server_addresses.is_some()),
+        ];
+
+        let (mandatory, optional): (&[&str], &[&str]) = match kind {
+            RDCleanPathKind::Request => (
+                &["destination", "proxy_auth", "x224_connection_pdu"],
+                &["server_auth", "preconnection_blob", "client_cert_chain"],
+            ),
+            RDCleanPathKind::Response => (
+                &["server_addr", "x224_connection_pdu", "server_cert_chain"],
+                &[
+                    "ocsp_response",
+                    "client_cert_chain",
+                    "kdc_proxy_url",
+                    "server_addresses",
+                ],
+            ),
+            RDCleanPathKind::Error => (&["error"], &[]),
+        };
+
+        for (field, is_set) in fields {
+            if is_set && !mandatory.contains(&field) && !optional.contains(&field) {
+                return Err(RDCleanPathValidationError::UnexpectedField {
+                    kind: kind.name(),
+                    field,
+                });
+            }
+
+            if !is_set && mandatory.contains(&field) {
+                return Err(RDCleanPathValidationError::MissingField {
+                    kind: kind.name(),
+                    field,
+                });
+            }
+        }
+
+        Ok(kind)
+    }
+}
+
+/// Fluent builder for RDCleanPath PDUs, validating the combination of fields on build
+///
+/// # Example
+///
+/// ```
+/// use ironrdp_rdcleanpath::RDCleanPathPdu;
+///
+/// let request = RDCleanPathPdu::builder()
+///     .destination("rdp.example.com:3389".to_owned())
+///     .proxy_auth("token".to_owned())
+///     .x224_connection_pdu(vec![0x03, 0x00, 0x00, 0x13])
+///     .build_request()
+///     .unwrap();
+///
+/// assert_eq!(request.destination(), "rdp.example.com:3389");
+/// ```
+#[derive(Debug, Clone)]
+pub struct RDCleanPathPduBuilder {
+    version: u64,
+    capabilities: Option<Capabilities>,
+    error: Option<RDCleanPathErr>,
+    destination: Option<String>,
+    proxy_auth: Option<String>,
+    server_auth: Option<String>,
+    preconnection_blob: Option<String>,
+    x224_connection_pdu: Option<Vec<u8>>,
+    server_cert_chain: Option<Vec<Vec<u8>>>,
+    ocsp_response: Option<Vec<u8>>,
+    server_addr: Option<String>,
+    client_cert_chain: Option<Vec<Vec<u8>>>,
+    kdc_proxy_url: Option<String>,
+    server_addresses: Option<Vec<ServerAddress>>,
+}
+
+impl Default for RDCleanPathPduBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RDCleanPathPduBuilder {
+    pub fn new() -> Self {
+        Self {
+            version: VERSION_1,
+            capabilities: None,
+            error: None,
+            destination: None,
+            proxy_auth: None,
+            server_auth: None,
+            preconnection_blob: None,
+            x224_connection_pdu: None,
+            server_cert_chain: None,
+            ocsp_response: None,
+            server_addr: None,
+            client_cert_chain: None,
+            kdc_proxy_url: None,
+            server_addresses: None,
+        }
+    }
+
+    #[must_use]
+    pub fn version(mut self, version: u64) -> Self {
+        self.version = version;
+        self
+    }
+
+    /// Advertises capabilities, bumping the PDU to [`VERSION_2`] if it was older.
+    #[must_use]
+    pub fn capabilities(mut self, capabilities: Capabilities) -> Self {
+        self.version = self.version.max(VERSION_2);
+        self.capabilities = Some(capabilities);
+        self
+    }
+
+    #[must_use]
+    pub fn error(mut self, error: RDCleanPathErr) -> Self {
+        self.error = Some(error);
+        self
+    }
+
+    #[must_use]
+    pub fn destination(mut self, destination: String) -> Self {
+        self.destination = Some(destination);
+        self
+    }
+
+    #[must_use]
+    pub fn proxy_auth(mut self, proxy_auth: String) -> Self {
+        self.proxy_auth = Some(proxy_auth);
+        self
+    }
+
+    #[must_use]
+    pub fn server_auth(mut self, server_auth: String) -> Self {
+        self.server_auth = Some(server_auth);
+        self
+    }
+
+    #[must_use]
+    pub fn preconnection_blob(mut self, preconnection_blob: String) -> Self {
+        self.preconnection_blob = Some(preconnection_blob);
+        self
+    }
+
+    /// Sets the X.224 Connection Request (in a request) or Connection Confirm (in a response)
+    #[must_use]
+    pub fn x224_connection_pdu(mut self, x224_pdu: Vec<u8>) -> Self {
+        self.x224_connection_pdu = Some(x224_pdu);
+        self
+    }
+
+    #[must_use]
+    pub fn server_cert_chain(mut self, x509_chain: impl IntoIterator<Item = Vec<u8>>) -> Self {
+        self.server_cert_chain = Some(x509_chain.into_iter().collect());
+        self
+    }
+
+    #[must_use]
+    pub fn ocsp_response(mut self, ocsp_response: Vec<u8>) -> Self {
+        self.ocsp_response = Some(ocsp_response);
+        self
+    }
+
+    #[must_use]
+    pub fn server_addr(mut self, server_addr: String) -> Self {
+        self.server_addr = Some(server_addr);
+        self
+    }
+
+    /// Sets the client certificate chain for mutual TLS, leaf certificate first.
+    ///
+    /// [`Capabilities::CLIENT_CERT_CHAIN`] is advertised along with the other capabilities on build.
+    #[must_use]
+    pub fn client_cert_chain(mut self, x509_chain: impl IntoIterator<Item = Vec<u8>>) -> Self {
+        self.client_cert_chain = Some(x509_chain.into_iter().collect());
+        self
+    }
+
+    #[must_use]
+    pub fn kdc_proxy_url(mut self, kdc_proxy_url: String) -> Self {
+        self.kdc_proxy_url = Some(kdc_proxy_url);
+        self
+    }
+
+    #[must_use]
+    pub fn server_addresses(mut self, addresses: impl IntoIterator<Item = ServerAddress>) -> Self {
+        self.server_addresses = Some(addresses.into_iter().collect());
+        self
+    }
+
+    /// Validates and returns the PDU, whatever its kind
+    pub fn build(mut self) -> Result<RDCleanPathPdu, RDCleanPathValidationError> {
+        if self.client_cert_chain.is_some() {
+            let capabilities = self.capabilities.unwrap_or_default() | Capabilities::CLIENT_CERT_CHAIN;
+            self = self.capabilities(capabilities);
+        }
+
+        let octet_string = |bytes: Vec<u8>| OctetString::new(bytes).map_err(RDCleanPathValidationError::Encoding);
+
+        let pdu = RDCleanPathPdu {
+            version: self.version,
+            error: self.error,
+            destination: self.destination,
+            proxy_auth: self.proxy_auth,
+            server_auth: self.server_auth,
+            preconnection_blob: self.preconnection_blob,
+            x224_connection_pdu: self.x224_connection_pdu.map(octet_string).transpose()?,
+            server_cert_chain: self
+                .server_cert_chain
+                .map(|chain| chain.into_iter().map(octet_string).collect())
+                .transpose()?,
+            ocsp_response: self.ocsp_response.map(octet_string).transpose()?,
+            server_addr: self.server_addr,
+            capabilities: self.capabilities.map(|capabilities| capabilities.bits()),
+            client_cert_chain: self
+                .client_cert_chain
+                .map(|chain| chain.into_iter().map(octet_string).collect())
+                .transpose()?,
+            kdc_proxy_url: self.kdc_proxy_url,
+            server_addresses: self.server_addresses,
+        };
+
+        pdu.validate()?;
+
+        Ok(pdu)
+    }
+
+    /// Validates and returns the PDU, which must be a request
+    pub fn build_request(self) -> Result<RDCleanPathRequest, RDCleanPathValidationError> {
+        RDCleanPathRequest::try_from(self.build()?)
+    }
+
+    /// Validates and returns the PDU, which must be a response
+    pub fn build_response(self) -> Result<RDCleanPathResponse, RDCleanPathValidationError> {
+        RDCleanPathResponse::try_from(self.build()?)
+    }
+}
+
+fn expect_kind(pdu: &RDCleanPathPdu, expected: RDCleanPathKind) -> Result<(), RDCleanPathValidationError> {
+    let kind = pdu.validate()?;
+
+    if kind == expected {
+        Ok(())
+    } else {
+        // The PDU is missing the field identifying the expected kind.
+        let field = match expected {
+            RDCleanPathKind::Request => "destination",
+            RDCleanPathKind::Response => "server_addr",
+            RDCleanPathKind::Error => "error",
+        };
+
+        Err(RDCleanPathValidationError::MissingField {
+            kind: expected.name(),
+            field,
+        })
+    }
+}
+
+/// Validated RDCleanPath request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RDCleanPathRequest(RDCleanPathPdu);
+
+impl RDCleanPathRequest {
+    pub fn destination(&self) -> &str {
+        self.0.destination.as_deref().expect("validated request")
+    }
+
+    pub fn proxy_auth(&self) -> &str {
+        self.0.proxy_auth.as_deref().expect("validated request")
+    }
+
+    pub fn server_auth(&self) -> Option<&str> {
+        self.0.server_auth.as_deref()
+    }
+
+    pub fn preconnection_blob(&self) -> Option<&str> {
+        self.0.preconnection_blob.as_deref()
+    }
+
+    pub fn x224_connection_request(&self) -> &[u8] {
+        self.0
+            .x224_connection_pdu
+            .as_ref()
+            .map(OctetString::as_bytes)
+            .expect("validated request")
+    }
+
+    pub fn capabilities(&self) -> Capabilities {
+        self.0.capabilities()
+    }
+
+    pub fn client_cert_chain(&self) -> impl Iterator<Item = &[u8]> {
+        self.0.client_cert_chain.iter().flatten().map(OctetString::as_bytes)
+    }
+
+    pub fn as_pdu(&self) -> &RDCleanPathPdu {
+        &self.0
+    }
+
+    pub fn into_pdu(self) -> RDCleanPathPdu {
+        self.0
+    }
+}
+
+impl TryFrom<RDCleanPathPdu> for RDCleanPathRequest {
+    type Error = RDCleanPathValidationError;
+
+    fn try_from(pdu: RDCleanPathPdu) -> Result<Self, Self::Error> {
+        expect_kind(&pdu, RDCleanPathKind::Request)?;
+        Ok(Self(pdu))
+    }
+}
+
+/// Validated RDCleanPath response
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RDCleanPathResponse(RDCleanPathPdu);
+
+impl RDCleanPathResponse {
+    pub fn server_addr(&self) -> &str {
+        self.0.server_addr.as_deref().expect("validated response")
+    }
+
+    pub fn x224_connection_response(&self) -> &[u8] {
+        self.0
+            .x224_connection_pdu
+            .as_ref()
+            .map(OctetString::as_bytes)
+            .expect("validated response")
+    }
+
+    pub fn server_cert_chain(&self) -> impl Iterator<Item = &[u8]> {
+        self.0.server_cert_chain.iter().flatten().map(OctetString::as_bytes)
+    }
+
+    pub fn ocsp_response(&self) -> Option<&[u8]> {
+        self.0.ocsp_response.as_ref().map(OctetString::as_bytes)
+    }
+
+    pub fn capabilities(&self) -> Capabilities {
+        self.0.capabilities()
+    }
+
+    pub fn client_cert_chain(&self) -> impl Iterator<Item = &[u8]> {
+        self.0.client_cert_chain.iter().flatten().map(OctetString::as_bytes)
+    }
+
+    /// URL of the KDC proxy to use for Kerberos authentication, if provided by the proxy
+    pub fn kdc_proxy_url(&self) -> Option<&str> {
+        self.0.kdc_proxy_url.as_deref()
+    }
+
+    /// Addresses of the RDP server, in the order to attempt them
+    pub fn server_addresses(&self) -> Vec<SocketAddr> {
+        self.0.server_addresses()
+    }
+
+    pub fn as_pdu(&self) -> &RDCleanPathPdu {
+        &self.0
+    }
+
+    pub fn into_pdu(self) -> RDCleanPathPdu {
+        self.0
+    }
+}
+
+impl TryFrom<RDCleanPathPdu> for RDCleanPathResponse {
+    type Error = RDCleanPathValidationError;
+
+    fn try_from(pdu: RDCleanPathPdu) -> Result<Self, Self::Error> {
+        expect_kind(&pdu, RDCleanPathKind::Response)?;
+        Ok(Self(pdu))
+    }
+}
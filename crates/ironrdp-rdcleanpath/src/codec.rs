@@ -0,0 +1,73 @@
+use std::io;
+
+use bytes::BytesMut;
+use tokio_util::codec::{Decoder, Encoder};
+
+use crate::{DetectionResult, RDCleanPathPdu};
+
+/// Codec framing RDCleanPath PDUs over a byte stream, for use with [`tokio_util::codec::Framed`].
+///
+/// The decoder buffers the input until a whole PDU is received, as found by [`RDCleanPathPdu::detect`].
+/// Once the RDCleanPath exchange is over, the framed stream may be turned back into the underlying one with
+/// [`tokio_util::codec::Framed::into_parts`], keeping the bytes read past the response.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RDCleanPathCodec;
+
+impl RDCleanPathCodec {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Decoder for RDCleanPathCodec {
+    type Item = RDCleanPathPdu;
+    type Error = io::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        let total_length = match RDCleanPathPdu::detect(src) {
+            DetectionResult::Detected { total_length, .. } => total_length,
+            DetectionResult::NotEnoughBytes {
+                total_length: Some(total_length),
+            } => {
+                src.reserve(total_length.saturating_sub(src.len()));
+                return Ok(None);
+            }
+            DetectionResult::NotEnoughBytes { total_length: None } => return Ok(None),
+            DetectionResult::Failed => {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, "not an RDCleanPath PDU"));
+            }
+        };
+
+        if let Some(missing) = total_length.checked_sub(src.len()).filter(|missing| *missing > 0) {
+            // Avoid reallocating for each chunk of a large PDU.
+            src.reserve(missing);
+            return Ok(None);
+        }
+
+        let pdu = src.split_to(total_length);
+
+        RDCleanPathPdu::from_der(&pdu)
+            .map(Some)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+impl Encoder<RDCleanPathPdu> for RDCleanPathCodec {
+    type Error = io::Error;
+
+    fn encode(&mut self, item: RDCleanPathPdu, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        self.encode(&item, dst)
+    }
+}
+
+impl Encoder<&RDCleanPathPdu> for RDCleanPathCodec {
+    type Error = io::Error;
+
+    fn encode(&mut self, item: &RDCleanPathPdu, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        let encoded = item
+            .to_der()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        dst.extend_from_slice(&encoded);
+        Ok(())
+    }
+}
@@ -0,0 +1,261 @@
+//! Client side of the RDCleanPath protocol
+//!
+//! [`RDCleanPathConnector`] wraps the connection initiation of a [`ClientConnector`] into the RDCleanPath exchange.
+//! As with the connector, no I/O is performed: the caller writes the request to the proxy, and feeds back the
+//! response.
+//!
+//! ```text
+//! request ──► RDCleanPath request to write to the proxy
+//! proxy response ─► process_response ──► server address, certificate chain and public key for the TLS upgrade
+//! ```
+
+use core::fmt;
+use std::net::SocketAddr;
+
+use der::Decode as _;
+use ironrdp_connector::{ClientConnector, ClientConnectorState, ConnectorError, Sequence as _, State as _};
+use ironrdp_core::WriteBuf;
+
+use crate::{Capabilities, RDCleanPath, RDCleanPathErr, RDCleanPathPdu};
+
+/// Error of the client side of the RDCleanPath exchange
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum RDCleanPathConnectorError {
+    /// The connector failed to produce the X.224 Connection Request or to process the Connection Confirm
+    Connector(ConnectorError),
+    /// The connector is not waiting to start or complete the connection initiation
+    UnexpectedConnectorState(&'static str),
+    /// A PDU could not be encoded or decoded
+    Der(der::Error),
+    /// The proxy did not send a response
+    UnexpectedPdu,
+    /// A field of the response is missing or invalid
+    InvalidResponse(&'static str),
+    /// The proxy reported an error
+    Proxy(RDCleanPathErr),
+}
+
+impl fmt::Display for RDCleanPathConnectorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Connector(_) => write!(f, "connector error"),
+            Self::UnexpectedConnectorState(state) => write!(f, "unexpected connector state: {state}"),
+            Self::Der(_) => write!(f, "RDCleanPath PDU encoding error"),
+            Self::UnexpectedPdu => write!(f, "received an unexpected RDCleanPath PDU (request)"),
+            Self::InvalidResponse(reason) => write!(f, "invalid RDCleanPath response: {reason}"),
+            Self::Proxy(error) => write!(f, "received an {error}"),
+        }
+    }
+}
+
+impl std::error::Error for RDCleanPathConnectorError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Connector(error) => Some(error),
+            Self::Der(error) => Some(error),
+            Self::Proxy(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<ConnectorError> for RDCleanPathConnectorError {
+    fn from(error: ConnectorError) -> Self {
+        Self::Connector(error)
+    }
+}
+
+impl From<der::Error> for RDCleanPathConnectorError {
+    fn from(error: der::Error) -> Self {
+        Self::Der(error)
+    }
+}
+
+/// What the TLS upgrade step needs, as established by the proxy
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RDCleanPathUpgrade {
+    /// Address of the RDP server, as resolved by the proxy
+    pub server_addr: SocketAddr,
+    /// Certificate chain of the RDP server, leaf certificate first (DER encoded)
+    pub server_cert_chain: Vec<Vec<u8>>,
+    /// Public key of the leaf certificate, for use by CredSSP
+    pub server_public_key: Vec<u8>,
+    /// OCSP response stapled by the RDP server, if forwarded by the proxy (DER encoded)
+    pub ocsp_response: Option<Vec<u8>>,
+    /// Client certificate chain presented by the proxy to the RDP server, if mutual TLS was performed (DER encoded)
+    pub client_cert_chain: Option<Vec<Vec<u8>>>,
+    /// URL of the KDC proxy to use for Kerberos authentication, if provided by the proxy
+    ///
+    /// Only provided when advertising [`Capabilities::KDC_PROXY_URL`].
+    pub kdc_proxy_url: Option<String>,
+    /// All the addresses of the RDP server, in the order to attempt them, `server_addr` only when not provided by the
+    /// proxy
+    ///
+    /// Only provided when advertising [`Capabilities::SERVER_ADDRESSES`]. Useful to fall back on another address
+    /// when reconnecting to a dual-stack or round-robin destination, using
+    /// [`RDCleanPathConnector::reconnection_attempts`].
+    pub server_addresses: Vec<SocketAddr>,
+}
+
+/// Drives the connection initiation of a [`ClientConnector`] through an RDCleanPath proxy
+#[derive(Debug, Clone)]
+pub struct RDCleanPathConnector {
+    destination: String,
+    proxy_auth: String,
+    preconnection_blob: Option<String>,
+    capabilities: Option<Capabilities>,
+    client_cert_chain: Option<Vec<Vec<u8>>>,
+}
+
+impl RDCleanPathConnector {
+    /// `destination` is the address of the RDP server, and `proxy_auth` the token authorizing the connection on the
+    /// proxy side.
+    pub fn new(destination: String, proxy_auth: String) -> Self {
+        Self {
+            destination,
+            proxy_auth,
+            preconnection_blob: None,
+            capabilities: None,
+            client_cert_chain: None,
+        }
+    }
+
+    #[must_use]
+    pub fn with_preconnection_blob(mut self, pcb: Option<String>) -> Self {
+        self.preconnection_blob = pcb;
+        self
+    }
+
+    /// Advertises capabilities to the proxy, which requires the proxy to support [`VERSION_2`](crate::VERSION_2).
+    #[must_use]
+    pub fn with_capabilities(mut self, capabilities: Capabilities) -> Self {
+        self.capabilities = Some(capabilities);
+        self
+    }
+
+    /// Asks the proxy to present this client certificate chain to the RDP server for mutual TLS, which requires the
+    /// proxy to support [`Capabilities::CLIENT_CERT_CHAIN`].
+    #[must_use]
+    pub fn with_client_cert_chain(mut self, x509_chain: Vec<Vec<u8>>) -> Self {
+        self.client_cert_chain = Some(x509_chain);
+        self
+    }
+
+    /// Returns the connectors to use when reconnecting, one per address of the RDP server, in the order to attempt them
+    ///
+    /// The proxy is the one connecting to the RDP server: falling back on another address means going through a new
+    /// RDCleanPath exchange, with this address as the destination. Attempting the next connector once an attempt fails
+    /// is left to the caller, which owns the connections to the proxy.
+    pub fn reconnection_attempts<'a>(&'a self, upgrade: &'a RDCleanPathUpgrade) -> impl Iterator<Item = Self> + 'a {
+        upgrade.server_addresses.iter().map(move |server_addr| Self {
+            destination: server_addr.to_string(),
+            ..self.clone()
+        })
+    }
+
+    /// Steps the connector to produce the X.224 Connection Request, and returns the RDCleanPath request to write to
+    /// the proxy.
+    pub fn request(&self, connector: &mut ClientConnector) -> Result<Vec<u8>, RDCleanPathConnectorError> {
+        let ClientConnectorState::ConnectionInitiationSendRequest = connector.state else {
+            return Err(RDCleanPathConnectorError::UnexpectedConnectorState(
+                connector.state.name(),
+            ));
+        };
+
+        let mut buf = WriteBuf::new();
+        connector.step_no_input(&mut buf)?;
+
+        let mut request = RDCleanPathPdu::new_request(
+            buf.filled().to_vec(),
+            self.destination.clone(),
+            self.proxy_auth.clone(),
+            self.preconnection_blob.clone(),
+        )?;
+
+        if let Some(capabilities) = self.capabilities {
+            request = request.with_capabilities(capabilities);
+        }
+
+        if let Some(client_cert_chain) = &self.client_cert_chain {
+            request = request.with_client_cert_chain(client_cert_chain.iter().cloned())?;
+        }
+
+        Ok(request.to_der()?)
+    }
+
+    /// Processes the RDCleanPath response of the proxy, and steps the connector with the X.224 Connection Confirm it
+    /// holds.
+    ///
+    /// Afterwards, the connector expects the security upgrade to be performed, which the proxy already did.
+    pub fn process_response(
+        &self,
+        connector: &mut ClientConnector,
+        response: &[u8],
+    ) -> Result<RDCleanPathUpgrade, RDCleanPathConnectorError> {
+        let ClientConnectorState::ConnectionInitiationWaitConfirm { .. } = connector.state else {
+            return Err(RDCleanPathConnectorError::UnexpectedConnectorState(
+                connector.state.name(),
+            ));
+        };
+
+        let response = RDCleanPathPdu::from_der(response)?;
+        let server_addresses = response.server_addresses();
+
+        let response = response
+            .into_enum()
+            .map_err(|_| RDCleanPathConnectorError::InvalidResponse("missing field"))?;
+
+        let RDCleanPath::Response {
+            x224_connection_response,
+            server_cert_chain,
+            ocsp_response,
+            server_addr,
+            client_cert_chain,
+            kdc_proxy_url,
+            ..
+        } = response
+        else {
+            return Err(match response {
+                RDCleanPath::Err(error) => RDCleanPathConnectorError::Proxy(error),
+                _ => RDCleanPathConnectorError::UnexpectedPdu,
+            });
+        };
+
+        let server_addr = server_addr
+            .parse()
+            .map_err(|_| RDCleanPathConnectorError::InvalidResponse("invalid server address"))?;
+
+        let server_cert = server_cert_chain
+            .first()
+            .ok_or(RDCleanPathConnectorError::InvalidResponse(
+                "empty server certificate chain",
+            ))?;
+
+        let server_public_key = x509_cert::Certificate::from_der(server_cert.as_bytes())
+            .map_err(|_| RDCleanPathConnectorError::InvalidResponse("invalid server certificate"))?
+            .tbs_certificate
+            .subject_public_key_info
+            .subject_public_key
+            .as_bytes()
+            .ok_or(RDCleanPathConnectorError::InvalidResponse(
+                "subject public key BIT STRING is not aligned",
+            ))?
+            .to_owned();
+
+        connector.attach_server_addr(server_addr);
+
+        let mut buf = WriteBuf::new();
+        connector.step(x224_connection_response.as_bytes(), &mut buf)?;
+
+        Ok(RDCleanPathUpgrade {
+            server_addr,
+            server_cert_chain: server_cert_chain.into_iter().map(|cert| cert.into_bytes()).collect(),
+            server_public_key,
+            ocsp_response: ocsp_response.map(|ocsp_response| ocsp_response.into_bytes()),
+            client_cert_chain: client_cert_chain.map(|chain| chain.into_iter().map(|cert| cert.into_bytes()).collect()),
+            kdc_proxy_url,
+            server_addresses,
+        })
+    }
+}
@@ -1,21 +1,141 @@
 use core::fmt;
 
+use bitflags::bitflags;
 use der::asn1::OctetString;
 
 // Re-export der crate for convenience
 #[rustfmt::skip] // do not re-order this pub use
 pub use der;
 
+mod builder;
+pub mod pcb;
+mod server_address;
+
+#[cfg(feature = "connector")]
+pub mod connector;
+
+#[cfg(feature = "proxy")]
+pub mod proxy;
+
+#[cfg(feature = "websocket")]
+pub mod websocket;
+
+#[cfg(feature = "codec")]
+mod codec;
+
+#[cfg(feature = "serde")]
+mod serde_octet_string;
+
+pub use self::builder::*;
+pub use self::server_address::*;
+
+#[cfg(feature = "codec")]
+pub use self::codec::*;
+
 pub const BASE_VERSION: u64 = 3389;
 pub const VERSION_1: u64 = BASE_VERSION + 1;
+/// Adds the capabilities field
+pub const VERSION_2: u64 = BASE_VERSION + 2;
+
+/// Highest version supported by this implementation
+pub const LATEST_VERSION: u64 = VERSION_2;
 
 pub const GENERAL_ERROR_CODE: u16 = 1;
 
+/// Error code of an [`RDCleanPathErr`]
+///
+/// Codes unknown to this implementation are kept as is, so that they can be forwarded or reported.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(from = "u16", into = "u16"))]
+pub enum RDCleanPathErrorCode {
+    /// Failure detailed by the other fields of the error, if any
+    General,
+    /// The destination could not be resolved by the proxy
+    DnsResolution,
+    /// The proxy could not connect to the RDP server
+    TcpConnect,
+    /// The TLS handshake between the proxy and the RDP server failed
+    TlsHandshake,
+    /// The proxy rejected the authorization token
+    AuthorizationRejected,
+    /// Code not matching any other variant
+    Unknown(u16),
+}
+
+impl RDCleanPathErrorCode {
+    const DNS_RESOLUTION: u16 = 2;
+    const TCP_CONNECT: u16 = 3;
+    const TLS_HANDSHAKE: u16 = 4;
+    const AUTHORIZATION_REJECTED: u16 = 5;
+}
+
+impl From<u16> for RDCleanPathErrorCode {
+    fn from(code: u16) -> Self {
+        match code {
+            GENERAL_ERROR_CODE => Self::General,
+            Self::DNS_RESOLUTION => Self::DnsResolution,
+            Self::TCP_CONNECT => Self::TcpConnect,
+            Self::TLS_HANDSHAKE => Self::TlsHandshake,
+            Self::AUTHORIZATION_REJECTED => Self::AuthorizationRejected,
+            code => Self::Unknown(code),
+        }
+    }
+}
+
+impl From<RDCleanPathErrorCode> for u16 {
+    fn from(code: RDCleanPathErrorCode) -> Self {
+        match code {
+            RDCleanPathErrorCode::General => GENERAL_ERROR_CODE,
+            RDCleanPathErrorCode::DnsResolution => RDCleanPathErrorCode::DNS_RESOLUTION,
+            RDCleanPathErrorCode::TcpConnect => RDCleanPathErrorCode::TCP_CONNECT,
+            RDCleanPathErrorCode::TlsHandshake => RDCleanPathErrorCode::TLS_HANDSHAKE,
+            RDCleanPathErrorCode::AuthorizationRejected => RDCleanPathErrorCode::AUTHORIZATION_REJECTED,
+            RDCleanPathErrorCode::Unknown(code) => code,
+        }
+    }
+}
+
+impl fmt::Display for RDCleanPathErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::General => write!(f, "general failure"),
+            Self::DnsResolution => write!(f, "DNS resolution failure"),
+            Self::TcpConnect => write!(f, "TCP connection failure"),
+            Self::TlsHandshake => write!(f, "TLS handshake failure"),
+            Self::AuthorizationRejected => write!(f, "authorization rejected"),
+            Self::Unknown(code) => write!(f, "unknown code {code}"),
+        }
+    }
+}
+
+// Encoded as a plain INTEGER.
+impl der::FixedTag for RDCleanPathErrorCode {
+    const TAG: der::Tag = der::Tag::Integer;
+}
+
+impl<'a> der::DecodeValue<'a> for RDCleanPathErrorCode {
+    fn decode_value<R: der::Reader<'a>>(reader: &mut R, header: der::Header) -> der::Result<Self> {
+        u16::decode_value(reader, header).map(Self::from)
+    }
+}
+
+impl der::EncodeValue for RDCleanPathErrorCode {
+    fn value_len(&self) -> der::Result<der::Length> {
+        u16::from(*self).value_len()
+    }
+
+    fn encode_value(&self, encoder: &mut impl der::Writer) -> der::Result<()> {
+        u16::from(*self).encode_value(encoder)
+    }
+}
+
 #[derive(Clone, Debug, Eq, PartialEq, der::Sequence)]
 #[asn1(tag_mode = "EXPLICIT")]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RDCleanPathErr {
     #[asn1(context_specific = "0")]
-    pub error_code: u16,
+    pub error_code: RDCleanPathErrorCode,
     #[asn1(context_specific = "1", optional = "true")]
     pub http_status_code: Option<u16>,
     #[asn1(context_specific = "2", optional = "true")]
@@ -24,9 +144,21 @@ pub struct RDCleanPathErr {
     pub tls_alert_code: Option<u8>,
 }
 
+impl RDCleanPathErr {
+    /// Error with the given code, without further details
+    pub fn new(error_code: RDCleanPathErrorCode) -> Self {
+        Self {
+            error_code,
+            http_status_code: None,
+            wsa_last_error: None,
+            tls_alert_code: None,
+        }
+    }
+}
+
 impl fmt::Display for RDCleanPathErr {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "RDCleanPath error (code {})", self.error_code)?;
+        write!(f, "RDCleanPath error ({})", self.error_code)?;
 
         if let Some(http_status_code) = self.http_status_code {
             write!(f, " [HTTP status = {http_status_code}]")?;
@@ -46,8 +178,47 @@ impl fmt::Display for RDCleanPathErr {
 
 impl std::error::Error for RDCleanPathErr {}
 
+bitflags! {
+    /// Optional features supported by a peer
+    ///
+    /// Unknown bits are retained, and left out by [`Capabilities::negotiate`].
+    #[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+    pub struct Capabilities: u32 {
+        /// The client understands the `ocsp_response` field of the response.
+        const OCSP_RESPONSE = 0x0000_0001;
+        /// The peer understands the `client_cert_chain` field.
+        const CLIENT_CERT_CHAIN = 0x0000_0002;
+        /// The client understands the `kdc_proxy_url` field of the response.
+        const KDC_PROXY_URL = 0x0000_0004;
+        /// The client understands the `server_addresses` field of the response.
+        const SERVER_ADDRESSES = 0x0000_0008;
+    }
+}
+
+impl Capabilities {
+    /// Capabilities supported by this implementation
+    pub const SUPPORTED: Self = Self::OCSP_RESPONSE
+        .union(Self::CLIENT_CERT_CHAIN)
+        .union(Self::KDC_PROXY_URL)
+        .union(Self::SERVER_ADDRESSES);
+
+    /// Returns the capabilities supported by both this implementation and the peer.
+    pub fn negotiate(peer: Self) -> Self {
+        peer & Self::SUPPORTED
+    }
+}
+
+/// Returns the version to use with a peer supporting versions up to `peer_version`, if any.
+///
+/// Both sides use the lowest of their highest supported versions, so that a new version can be rolled out to clients
+/// and proxies independently.
+pub fn negotiate_version(peer_version: u64) -> Option<u64> {
+    (peer_version >= VERSION_1).then(|| peer_version.min(LATEST_VERSION))
+}
+
 #[derive(Clone, Debug, Eq, PartialEq, der::Sequence)]
 #[asn1(tag_mode = "EXPLICIT")]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RDCleanPathPdu {
     /// RDCleanPathPdu packet version.
     #[asn1(context_specific = "0")]
@@ -79,19 +250,50 @@ pub struct RDCleanPathPdu {
     ///
     /// Both client and proxy will set this field.
     #[asn1(context_specific = "6", optional = "true")]
+    #[cfg_attr(feature = "serde", serde(default, with = "crate::serde_octet_string::option"))]
     pub x224_connection_pdu: Option<OctetString>,
     /// The RDP server TLS chain.
     ///
     /// Sent from proxy to client only.
     #[asn1(context_specific = "7", optional = "true")]
+    #[cfg_attr(feature = "serde", serde(default, with = "crate::serde_octet_string::option_vec"))]
     pub server_cert_chain: Option<Vec<OctetString>>,
-    // #[asn1(context_specific = "8", optional = "true")]
-    // pub ocsp_response: Option<String>,
+    /// The OCSP response stapled by the RDP server for its certificate, if any (DER encoded).
+    ///
+    /// Sent from proxy to client only.
+    #[asn1(context_specific = "8", optional = "true")]
+    #[cfg_attr(feature = "serde", serde(default, with = "crate::serde_octet_string::option"))]
+    pub ocsp_response: Option<OctetString>,
     /// IPv4 or IPv6 address of the server found by resolving the destination field on proxy side.
     ///
     /// Sent from proxy to client only.
     #[asn1(context_specific = "9", optional = "true")]
     pub server_addr: Option<String>,
+    /// Raw [`Capabilities`] of the sender.
+    ///
+    /// Since [`VERSION_2`]. In a response, only holds the capabilities also supported by the client.
+    #[asn1(context_specific = "10", optional = "true")]
+    pub capabilities: Option<u32>,
+    /// Client certificate chain for mutual TLS between the proxy and the RDP server, leaf certificate first (DER
+    /// encoded).
+    ///
+    /// In a request, the certificate the client asks the proxy to present, among the ones the proxy holds the private
+    /// key of. In a response, the certificate the proxy actually presented, so that the client can enforce its
+    /// pinning policy. Only exchanged between peers advertising [`Capabilities::CLIENT_CERT_CHAIN`].
+    #[asn1(context_specific = "11", optional = "true")]
+    #[cfg_attr(feature = "serde", serde(default, with = "crate::serde_octet_string::option_vec"))]
+    pub client_cert_chain: Option<Vec<OctetString>>,
+    /// URL of the KDC proxy (MS-KKDCP) to use for Kerberos authentication with the RDP server.
+    ///
+    /// Sent from proxy to client only, and only to clients advertising [`Capabilities::KDC_PROXY_URL`].
+    #[asn1(context_specific = "12", optional = "true")]
+    pub kdc_proxy_url: Option<String>,
+    /// All the addresses found by resolving the destination field on proxy side, with their priority.
+    ///
+    /// Sent from proxy to client only, and only to clients advertising [`Capabilities::SERVER_ADDRESSES`]. The
+    /// `server_addr` field still holds the address the proxy connected to.
+    #[asn1(context_specific = "13", optional = "true")]
+    pub server_addresses: Option<Vec<ServerAddress>>,
 }
 
 impl Default for RDCleanPathPdu {
@@ -105,15 +307,27 @@ impl Default for RDCleanPathPdu {
             preconnection_blob: None,
             x224_connection_pdu: None,
             server_cert_chain: None,
+            ocsp_response: None,
             server_addr: None,
+            capabilities: None,
+            client_cert_chain: None,
+            kdc_proxy_url: None,
+            server_addresses: None,
         }
     }
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum DetectionResult {
-    Detected { version: u64, total_length: usize },
-    NotEnoughBytes,
+    Detected {
+        version: u64,
+        total_length: usize,
+    },
+    /// More bytes are needed to detect the PDU
+    NotEnoughBytes {
+        /// Total length of the PDU, known once the outer SEQUENCE header is received
+        total_length: Option<usize>,
+    },
     Failed,
 }
 
@@ -134,7 +348,7 @@ impl RDCleanPathPdu {
         let header = match der::Header::decode(&mut slice_reader) {
             Ok(header) => header,
             Err(e) => match e.kind() {
-                der::ErrorKind::Incomplete { .. } => return DetectionResult::NotEnoughBytes,
+                der::ErrorKind::Incomplete { .. } => return DetectionResult::NotEnoughBytes { total_length: None },
                 _ => return DetectionResult::Failed,
             },
         };
@@ -151,19 +365,69 @@ impl RDCleanPathPdu {
         };
 
         match der::asn1::ContextSpecific::<u64>::decode_explicit(&mut slice_reader, der::TagNumber::N0) {
-            Ok(Some(version)) if version.value == VERSION_1 => DetectionResult::Detected {
-                version: VERSION_1,
+            // Versions newer than the ones known are detected as well, and left to the caller to negotiate.
+            Ok(Some(version)) if version.value >= VERSION_1 => DetectionResult::Detected {
+                version: version.value,
                 total_length,
             },
             Ok(Some(_)) => DetectionResult::Failed,
-            Ok(None) => DetectionResult::NotEnoughBytes,
+            Ok(None) => DetectionResult::NotEnoughBytes {
+                total_length: Some(total_length),
+            },
             Err(e) => match e.kind() {
-                der::ErrorKind::Incomplete { .. } => DetectionResult::NotEnoughBytes,
+                der::ErrorKind::Incomplete { .. } => DetectionResult::NotEnoughBytes {
+                    total_length: Some(total_length),
+                },
                 _ => DetectionResult::Failed,
             },
         }
     }
 
+    /// Decodes the RDCleanPath PDU at the start of `src`, returning it along with the number of bytes it spans
+    ///
+    /// Returns `Ok(None)` when more bytes are needed. The bytes following the PDU are left alone, so that the caller
+    /// can use its own buffer management.
+    pub fn decode_from_slice(src: &[u8]) -> der::Result<Option<(Self, usize)>> {
+        let total_length = match Self::detect(src) {
+            DetectionResult::Detected { total_length, .. } if total_length <= src.len() => total_length,
+            DetectionResult::Detected { .. } | DetectionResult::NotEnoughBytes { .. } => return Ok(None),
+            // Either not a DER sequence or an unsupported version.
+            DetectionResult::Failed => return Err(der::Tag::Sequence.value_error()),
+        };
+
+        Self::from_der(&src[..total_length]).map(|pdu| Some((pdu, total_length)))
+    }
+
+    /// Returns the capabilities of the sender, empty before [`VERSION_2`].
+    pub fn capabilities(&self) -> Capabilities {
+        self.capabilities
+            .map(Capabilities::from_bits_retain)
+            .unwrap_or_default()
+    }
+
+    /// Advertises capabilities, bumping the PDU to [`VERSION_2`] if it was older.
+    #[must_use]
+    pub fn with_capabilities(mut self, capabilities: Capabilities) -> Self {
+        self.version = self.version.max(VERSION_2);
+        self.capabilities = Some(capabilities.bits());
+        self
+    }
+
+    /// Sets the client certificate chain, advertising [`Capabilities::CLIENT_CERT_CHAIN`] along with the capabilities
+    /// already set.
+    pub fn with_client_cert_chain(mut self, x509_chain: impl IntoIterator<Item = Vec<u8>>) -> der::Result<Self> {
+        self.client_cert_chain = Some(
+            x509_chain
+                .into_iter()
+                .map(OctetString::new)
+                .collect::<der::Result<_>>()?,
+        );
+
+        let capabilities = self.capabilities() | Capabilities::CLIENT_CERT_CHAIN;
+
+        Ok(self.with_capabilities(capabilities))
+    }
+
     pub fn into_enum(self) -> Result<RDCleanPath, MissingRDCleanPathField> {
         RDCleanPath::try_from(self)
     }
@@ -172,7 +436,7 @@ impl RDCleanPathPdu {
         Self {
             version: VERSION_1,
             error: Some(RDCleanPathErr {
-                error_code: GENERAL_ERROR_CODE,
+                error_code: RDCleanPathErrorCode::General,
                 http_status_code: None,
                 wsa_last_error: None,
                 tls_alert_code: None,
@@ -185,7 +449,7 @@ impl RDCleanPathPdu {
         Self {
             version: VERSION_1,
             error: Some(RDCleanPathErr {
-                error_code: GENERAL_ERROR_CODE,
+                error_code: RDCleanPathErrorCode::General,
                 http_status_code: Some(status_code),
                 wsa_last_error: None,
                 tls_alert_code: None,
@@ -214,6 +478,7 @@ impl RDCleanPathPdu {
         server_addr: String,
         x224_pdu: Vec<u8>,
         x509_chain: impl IntoIterator<Item = Vec<u8>>,
+        ocsp_response: Option<Vec<u8>>,
     ) -> der::Result<Self> {
         Ok(Self {
             version: VERSION_1,
@@ -224,6 +489,7 @@ impl RDCleanPathPdu {
                     .map(OctetString::new)
                     .collect::<der::Result<_>>()?,
             ),
+            ocsp_response: ocsp_response.map(OctetString::new).transpose()?,
             server_addr: Some(server_addr),
             ..Self::default()
         })
@@ -233,7 +499,7 @@ impl RDCleanPathPdu {
         Self {
             version: VERSION_1,
             error: Some(RDCleanPathErr {
-                error_code: GENERAL_ERROR_CODE,
+                error_code: RDCleanPathErrorCode::General,
                 http_status_code: None,
                 wsa_last_error: None,
                 tls_alert_code: Some(alert_code),
@@ -246,7 +512,7 @@ impl RDCleanPathPdu {
         Self {
             version: VERSION_1,
             error: Some(RDCleanPathErr {
-                error_code: GENERAL_ERROR_CODE,
+                error_code: RDCleanPathErrorCode::General,
                 http_status_code: None,
                 wsa_last_error: Some(wsa_error_code),
                 tls_alert_code: None,
@@ -255,6 +521,44 @@ impl RDCleanPathPdu {
         }
     }
 
+    /// Error with the given code, without further details
+    pub fn new_error(error_code: RDCleanPathErrorCode) -> Self {
+        Self::from_error(RDCleanPathErr::new(error_code))
+    }
+
+    pub fn new_dns_resolution_error() -> Self {
+        Self::new_error(RDCleanPathErrorCode::DnsResolution)
+    }
+
+    pub fn new_tcp_connect_error(wsa_error_code: Option<u16>) -> Self {
+        Self::from_error(RDCleanPathErr {
+            wsa_last_error: wsa_error_code,
+            ..RDCleanPathErr::new(RDCleanPathErrorCode::TcpConnect)
+        })
+    }
+
+    pub fn new_tls_handshake_error(alert_code: Option<u8>) -> Self {
+        Self::from_error(RDCleanPathErr {
+            tls_alert_code: alert_code,
+            ..RDCleanPathErr::new(RDCleanPathErrorCode::TlsHandshake)
+        })
+    }
+
+    pub fn new_authorization_error(http_status_code: u16) -> Self {
+        Self::from_error(RDCleanPathErr {
+            http_status_code: Some(http_status_code),
+            ..RDCleanPathErr::new(RDCleanPathErrorCode::AuthorizationRejected)
+        })
+    }
+
+    fn from_error(error: RDCleanPathErr) -> Self {
+        Self {
+            version: VERSION_1,
+            error: Some(error),
+            ..Self::default()
+        }
+    }
+
     pub fn to_der(&self) -> der::Result<Vec<u8>> {
         der::Encode::to_der(self)
     }
@@ -269,11 +573,20 @@ pub enum RDCleanPath {
         server_auth: Option<String>,
         preconnection_blob: Option<String>,
         x224_connection_request: OctetString,
+        client_cert_chain: Option<Vec<OctetString>>,
+        /// Advertised capabilities, `None` before [`VERSION_2`].
+        capabilities: Option<Capabilities>,
     },
     Response {
         x224_connection_response: OctetString,
         server_cert_chain: Vec<OctetString>,
+        ocsp_response: Option<OctetString>,
         server_addr: String,
+        client_cert_chain: Option<Vec<OctetString>>,
+        kdc_proxy_url: Option<String>,
+        server_addresses: Option<Vec<ServerAddress>>,
+        /// Advertised capabilities, `None` before [`VERSION_2`].
+        capabilities: Option<Capabilities>,
     },
     Err(RDCleanPathErr),
 }
@@ -299,6 +612,8 @@ impl TryFrom<RDCleanPathPdu> for RDCleanPath {
     type Error = MissingRDCleanPathField;
 
     fn try_from(pdu: RDCleanPathPdu) -> Result<Self, Self::Error> {
+        let capabilities = pdu.capabilities.map(Capabilities::from_bits_retain);
+
         let rdcleanpath = if let Some(destination) = pdu.destination {
             Self::Request {
                 destination,
@@ -308,6 +623,8 @@ impl TryFrom<RDCleanPathPdu> for RDCleanPath {
                 x224_connection_request: pdu
                     .x224_connection_pdu
                     .ok_or(MissingRDCleanPathField("x224_connection_pdu"))?,
+                client_cert_chain: pdu.client_cert_chain,
+                capabilities,
             }
         } else if let Some(server_addr) = pdu.server_addr {
             Self::Response {
@@ -317,7 +634,12 @@ impl TryFrom<RDCleanPathPdu> for RDCleanPath {
                 server_cert_chain: pdu
                     .server_cert_chain
                     .ok_or(MissingRDCleanPathField("server_cert_chain"))?,
+                ocsp_response: pdu.ocsp_response,
                 server_addr,
+                client_cert_chain: pdu.client_cert_chain,
+                kdc_proxy_url: pdu.kdc_proxy_url,
+                server_addresses: pdu.server_addresses,
+                capabilities,
             }
         } else {
             Self::Err(pdu.error.ok_or(MissingRDCleanPathField("error"))?)
@@ -329,38 +651,72 @@ impl TryFrom<RDCleanPathPdu> for RDCleanPath {
 
 impl From<RDCleanPath> for RDCleanPathPdu {
     fn from(value: RDCleanPath) -> Self {
-        match value {
+        let (pdu, advertised) = match value {
             RDCleanPath::Request {
                 destination,
                 proxy_auth,
                 server_auth,
                 preconnection_blob,
                 x224_connection_request,
-            } => Self {
-                version: VERSION_1,
-                destination: Some(destination),
-                proxy_auth: Some(proxy_auth),
-                server_auth,
-                preconnection_blob,
-                x224_connection_pdu: Some(x224_connection_request),
-                ..Default::default()
-            },
+                client_cert_chain,
+                capabilities,
+            } => (
+                Self {
+                    version: VERSION_1,
+                    destination: Some(destination),
+                    proxy_auth: Some(proxy_auth),
+                    server_auth,
+                    preconnection_blob,
+                    x224_connection_pdu: Some(x224_connection_request),
+                    client_cert_chain,
+                    ..Default::default()
+                },
+                capabilities,
+            ),
             RDCleanPath::Response {
                 x224_connection_response,
                 server_cert_chain,
+                ocsp_response,
                 server_addr,
-            } => Self {
-                version: VERSION_1,
-                x224_connection_pdu: Some(x224_connection_response),
-                server_cert_chain: Some(server_cert_chain),
-                server_addr: Some(server_addr),
-                ..Default::default()
-            },
-            RDCleanPath::Err(error) => Self {
-                version: VERSION_1,
-                error: Some(error),
-                ..Default::default()
-            },
+                client_cert_chain,
+                kdc_proxy_url,
+                server_addresses,
+                capabilities,
+            } => (
+                Self {
+                    version: VERSION_1,
+                    x224_connection_pdu: Some(x224_connection_response),
+                    server_cert_chain: Some(server_cert_chain),
+                    ocsp_response,
+                    server_addr: Some(server_addr),
+                    client_cert_chain,
+                    kdc_proxy_url,
+                    server_addresses,
+                    ..Default::default()
+                },
+                capabilities,
+            ),
+            RDCleanPath::Err(error) => (
+                Self {
+                    version: VERSION_1,
+                    error: Some(error),
+                    ..Default::default()
+                },
+                None,
+            ),
+        };
+
+        // Capability-gated fields are only understood by peers advertising the matching capability.
+        let mut implied = Capabilities::empty();
+        implied.set(Capabilities::OCSP_RESPONSE, pdu.ocsp_response.is_some());
+        implied.set(Capabilities::CLIENT_CERT_CHAIN, pdu.client_cert_chain.is_some());
+        implied.set(Capabilities::KDC_PROXY_URL, pdu.kdc_proxy_url.is_some());
+        implied.set(Capabilities::SERVER_ADDRESSES, pdu.server_addresses.is_some());
+
+        if advertised.is_none() && implied.is_empty() {
+            pdu
+        } else {
+            pdu.with_capabilities(advertised.unwrap_or_default() | implied)
         }
     }
 }
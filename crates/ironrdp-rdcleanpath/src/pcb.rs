@@ -0,0 +1,174 @@
+//! Preconnection blob payloads
+//!
+//! The `preconnection_blob` field of a request holds the string of an RDP_PRECONNECTION_PDU_V2, which the proxy sends
+//! to the RDP server ahead of the X.224 Connection Request, with an Id of 0. [`PreconnectionBlob`] validates such
+//! strings, and formats the ones used by well-known RDP sources such as Hyper-V VMConnect.
+//!
+//! ```
+//! use ironrdp_rdcleanpath::pcb::PreconnectionBlob;
+//! use ironrdp_rdcleanpath::RDCleanPathPdu;
+//!
+//! let pcb = PreconnectionBlob::hyperv_vm("8b3b5f5e-5a4e-4f0b-a7e8-6d6e3c7d1f3a", true).unwrap();
+//! assert_eq!(pcb.as_str(), "8b3b5f5e-5a4e-4f0b-a7e8-6d6e3c7d1f3a;EnhancedMode=1");
+//!
+//! let request = RDCleanPathPdu::new_request(
+//!     vec![0x03, 0x00, 0x00, 0x13],
+//!     "hyperv-host.example.com:2179".to_owned(),
+//!     "token".to_owned(),
+//!     Some(pcb.into()),
+//! )
+//! .unwrap();
+//! ```
+
+use core::fmt;
+use core::str::FromStr;
+
+/// Validation error of a [`PreconnectionBlob`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PreconnectionBlobError {
+    /// The string does not fit in the PDU, `length` being its length in UTF-16 code units
+    TooLong { length: usize },
+    /// The string holds a null character, which would terminate it early
+    NullCharacter,
+    /// The Hyper-V virtual machine ID is not a GUID
+    InvalidVmId,
+}
+
+impl fmt::Display for PreconnectionBlobError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TooLong { length } => write!(
+                f,
+                "preconnection blob too long: {length} UTF-16 code units (max {})",
+                PreconnectionBlob::MAX_LENGTH
+            ),
+            Self::NullCharacter => f.write_str("preconnection blob holds a null character"),
+            Self::InvalidVmId => f.write_str("Hyper-V virtual machine ID is not a GUID"),
+        }
+    }
+}
+
+impl std::error::Error for PreconnectionBlobError {}
+
+/// Hyper-V virtual machine targeted by a [`PreconnectionBlob`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct HyperVTarget<'a> {
+    /// ID of the virtual machine (GUID)
+    pub vm_id: &'a str,
+    /// Whether the enhanced session mode is requested
+    pub enhanced_session: bool,
+}
+
+/// Validated string of an RDP_PRECONNECTION_PDU_V2
+///
+/// Converts into the `String` expected by [`RDCleanPathPdu::new_request`](crate::RDCleanPathPdu::new_request).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PreconnectionBlob(String);
+
+impl PreconnectionBlob {
+    /// Maximum length of the string, in UTF-16 code units
+    ///
+    /// The 16-bit cchPCB field also counts the null terminator.
+    pub const MAX_LENGTH: usize = 0xFFFE;
+
+    const ENHANCED_MODE_SUFFIX: &'static str = ";EnhancedMode=1";
+
+    /// Arbitrary string understood by the RDP source, such as a session token
+    pub fn new(payload: String) -> Result<Self, PreconnectionBlobError> {
+        if payload.contains('\0') {
+            return Err(PreconnectionBlobError::NullCharacter);
+        }
+
+        let length = payload.encode_utf16().count();
+
+        if length > Self::MAX_LENGTH {
+            return Err(PreconnectionBlobError::TooLong { length });
+        }
+
+        Ok(Self(payload))
+    }
+
+    /// Targets a Hyper-V virtual machine, the destination being the VMConnect endpoint of the host (port 2179)
+    ///
+    /// The ID may be enclosed in braces, which are removed.
+    pub fn hyperv_vm(vm_id: &str, enhanced_session: bool) -> Result<Self, PreconnectionBlobError> {
+        let vm_id = vm_id
+            .strip_prefix('{')
+            .and_then(|vm_id| vm_id.strip_suffix('}'))
+            .unwrap_or(vm_id);
+
+        if !is_guid(vm_id) {
+            return Err(PreconnectionBlobError::InvalidVmId);
+        }
+
+        let suffix = if enhanced_session {
+            Self::ENHANCED_MODE_SUFFIX
+        } else {
+            ""
+        };
+
+        Ok(Self(format!("{vm_id}{suffix}")))
+    }
+
+    /// Returns the Hyper-V virtual machine targeted, if the string is formatted as such
+    pub fn hyperv_target(&self) -> Option<HyperVTarget<'_>> {
+        let (vm_id, enhanced_session) = match self.0.strip_suffix(Self::ENHANCED_MODE_SUFFIX) {
+            Some(vm_id) => (vm_id, true),
+            None => (self.0.as_str(), false),
+        };
+
+        is_guid(vm_id).then_some(HyperVTarget {
+            vm_id,
+            enhanced_session,
+        })
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn into_string(self) -> String {
+        self.0
+    }
+}
+
+impl fmt::Display for PreconnectionBlob {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl FromStr for PreconnectionBlob {
+    type Err = PreconnectionBlobError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::new(s.to_owned())
+    }
+}
+
+impl TryFrom<String> for PreconnectionBlob {
+    type Error = PreconnectionBlobError;
+
+    fn try_from(payload: String) -> Result<Self, Self::Error> {
+        Self::new(payload)
+    }
+}
+
+impl From<PreconnectionBlob> for String {
+    fn from(pcb: PreconnectionBlob) -> Self {
+        pcb.0
+    }
+}
+
+/// Whether `s` is a GUID in its 8-4-4-4-12 hexadecimal form
+fn is_guid(s: &str) -> bool {
+    const GROUP_LENGTHS: [usize; 5] = [8, 4, 4, 4, 12];
+
+    let mut groups = s.split('-');
+
+    GROUP_LENGTHS.iter().all(|&length| {
+        groups
+            .next()
+            .is_some_and(|group| group.len() == length && group.bytes().all(|b| b.is_ascii_hexdigit()))
+    }) && groups.next().is_none()
+}
@@ -0,0 +1,553 @@
+//! Proxy side of the RDCleanPath protocol
+//!
+//! [`ProxySequence`] processes the RDCleanPath request of a client, and produces what must be written to the RDP
+//! server and back to the client. It never performs I/O by itself: connecting to the destination and upgrading the
+//! connection to TLS is left to the caller, who reports the outcome of each step.
+//!
+//! ```text
+//! client request ──► accept_request ──► (resolve the destination)
+//! addresses ───────► destination_resolved ──► order of the connection attempts (optional)
+//!                    (connect to the destination)
+//!                    server_connected ──► PCB and X.224 Connection Request to write to the server
+//! server response ─► process_x224_response ──► (TLS upgrade with the server)
+//!                    tls_upgraded ──► RDCleanPath response to write to the client
+//! ```
+//!
+//! The progression is exposed by [`ProxySequence::state`]. Any failure is reported as a [`ProxyError`], which converts
+//! into the RDCleanPath error PDU expected by the client, with the matching [`RDCleanPathErrorCode`].
+//!
+//! [`RDCleanPathErrorCode`]: crate::RDCleanPathErrorCode
+
+use core::fmt;
+use std::io;
+use std::net::SocketAddr;
+
+use der::asn1::OctetString;
+use ironrdp_core::{decode, encode_vec, DecodeResult};
+use ironrdp_pdu::pcb::{PcbVersion, PreconnectionBlob};
+use ironrdp_pdu::x224::X224;
+use ironrdp_pdu::{nego, PduHint};
+
+use crate::{
+    connection_attempt_order, negotiate_version, Capabilities, DetectionResult, RDCleanPath, RDCleanPathPdu, VERSION_1,
+    VERSION_2,
+};
+
+/// Finds the size of the RDCleanPath PDU sent by the client
+#[derive(Clone, Copy, Debug)]
+pub struct RDCleanPathHint;
+
+pub const RDCLEANPATH_HINT: RDCleanPathHint = RDCleanPathHint;
+
+impl PduHint for RDCleanPathHint {
+    fn find_size(&self, bytes: &[u8]) -> DecodeResult<Option<(bool, usize)>> {
+        match RDCleanPathPdu::detect(bytes) {
+            DetectionResult::Detected { total_length, .. } => Ok(Some((true, total_length))),
+            DetectionResult::NotEnoughBytes { .. } => Ok(None),
+            DetectionResult::Failed => Err(ironrdp_core::other_err!(
+                "RDCleanPathHint",
+                "detection failed (invalid PDU)"
+            )),
+        }
+    }
+}
+
+/// RDP server the client asks to be connected to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DestinationRequest {
+    /// Address of the RDP server, as sent by the client
+    pub destination: String,
+    /// Token to be validated by the proxy before connecting to the destination
+    pub proxy_auth: String,
+    pub server_auth: Option<String>,
+    /// Client certificate chain the client asks the proxy to present to the RDP server for mutual TLS, if any
+    ///
+    /// Only set when [`Capabilities::CLIENT_CERT_CHAIN`] is negotiated.
+    pub client_cert_chain: Option<Vec<Vec<u8>>>,
+}
+
+#[derive(Debug, Default)]
+pub enum ProxyState {
+    #[default]
+    Consumed,
+
+    WaitRequest,
+    ConnectServer {
+        preconnection_blob: Option<String>,
+        x224_connection_request: Vec<u8>,
+    },
+    WaitX224Response {
+        server_addr: SocketAddr,
+    },
+    TlsUpgrade {
+        server_addr: SocketAddr,
+        x224_connection_response: Vec<u8>,
+    },
+
+    Connected,
+    Failed,
+}
+
+impl ProxyState {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Consumed => "Consumed",
+            Self::WaitRequest => "WaitRequest",
+            Self::ConnectServer { .. } => "ConnectServer",
+            Self::WaitX224Response { .. } => "WaitX224Response",
+            Self::TlsUpgrade { .. } => "TlsUpgrade",
+            Self::Connected => "Connected",
+            Self::Failed => "Failed",
+        }
+    }
+
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, Self::Connected | Self::Failed)
+    }
+}
+
+/// Error reported to the client as an RDCleanPath error PDU
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ProxyError {
+    /// The client did not send a valid RDCleanPath request
+    BadRequest(&'static str),
+    /// The proxy authorization token is missing or invalid
+    Unauthorized,
+    /// The client is not allowed to connect to the requested destination
+    Forbidden,
+    /// The destination could not be resolved
+    DnsResolution(io::Error),
+    /// The connection to the RDP server failed
+    ServerConnection(io::Error),
+    /// The RDP server refused the security protocols requested by the client
+    NegotiationFailure(nego::FailureCode),
+    /// The RDP server did not send a valid X.224 Connection Confirm
+    InvalidServerResponse(&'static str),
+    /// The TLS upgrade with the RDP server failed, with the TLS alert received or sent if known
+    Tls { alert_code: Option<u8> },
+    /// A step was processed out of order
+    UnexpectedStep {
+        expected: &'static str,
+        state: &'static str,
+    },
+}
+
+impl ProxyError {
+    /// Error PDU to send back to the client
+    pub fn to_pdu(&self) -> RDCleanPathPdu {
+        match self {
+            Self::BadRequest(_) => RDCleanPathPdu::new_http_error(400),
+            Self::Unauthorized => RDCleanPathPdu::new_authorization_error(401),
+            Self::Forbidden => RDCleanPathPdu::new_authorization_error(403),
+            Self::DnsResolution(_) => RDCleanPathPdu::new_dns_resolution_error(),
+            Self::ServerConnection(error) => RDCleanPathPdu::new_tcp_connect_error(wsa_error_code(error)),
+            Self::Tls { alert_code } => RDCleanPathPdu::new_tls_handshake_error(*alert_code),
+            Self::NegotiationFailure(_) | Self::InvalidServerResponse(_) | Self::UnexpectedStep { .. } => {
+                RDCleanPathPdu::new_general_error()
+            }
+        }
+    }
+}
+
+impl fmt::Display for ProxyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BadRequest(reason) => write!(f, "bad RDCleanPath request: {reason}"),
+            Self::Unauthorized => write!(f, "unauthorized"),
+            Self::Forbidden => write!(f, "forbidden destination"),
+            Self::DnsResolution(_) => write!(f, "couldn’t resolve the destination"),
+            Self::ServerConnection(_) => write!(f, "couldn’t connect to the RDP server"),
+            Self::NegotiationFailure(code) => write!(f, "security protocol negotiation failed: {code}"),
+            Self::InvalidServerResponse(reason) => write!(f, "invalid X.224 response from the RDP server: {reason}"),
+            Self::Tls {
+                alert_code: Some(alert_code),
+            } => write!(f, "TLS upgrade failed (alert {alert_code})"),
+            Self::Tls { alert_code: None } => write!(f, "TLS upgrade failed"),
+            Self::UnexpectedStep { expected, state } => write!(f, "expected the {expected} state, but got {state}"),
+        }
+    }
+}
+
+impl std::error::Error for ProxyError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::DnsResolution(error) | Self::ServerConnection(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// Windows Sockets error code matching the connection failure
+fn wsa_error_code(error: &io::Error) -> Option<u16> {
+    #[cfg(windows)]
+    if let Some(code) = error.raw_os_error() {
+        return u16::try_from(code).ok();
+    }
+
+    let code = match error.kind() {
+        io::ErrorKind::AddrNotAvailable => 10049,  // WSAEADDRNOTAVAIL
+        io::ErrorKind::ConnectionAborted => 10053, // WSAECONNABORTED
+        io::ErrorKind::ConnectionReset => 10054,   // WSAECONNRESET
+        io::ErrorKind::NotConnected => 10057,      // WSAENOTCONN
+        io::ErrorKind::TimedOut => 10060,          // WSAETIMEDOUT
+        io::ErrorKind::ConnectionRefused => 10061, // WSAECONNREFUSED
+        _ => return None,
+    };
+
+    Some(code)
+}
+
+/// State machine driving the proxy side of an RDCleanPath connection
+#[derive(Debug)]
+pub struct ProxySequence {
+    state: ProxyState,
+    version: u64,
+    capabilities: Capabilities,
+    client_cert_chain: Option<Vec<Vec<u8>>>,
+    kdc_proxy_url: Option<String>,
+    server_addresses: Vec<SocketAddr>,
+}
+
+impl Default for ProxySequence {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ProxySequence {
+    pub fn new() -> Self {
+        Self {
+            state: ProxyState::WaitRequest,
+            version: VERSION_1,
+            capabilities: Capabilities::empty(),
+            client_cert_chain: None,
+            kdc_proxy_url: None,
+            server_addresses: Vec::new(),
+        }
+    }
+
+    /// Advertises the KDC proxy to use for Kerberos authentication with the RDP servers behind this proxy
+    ///
+    /// The URL is only sent to clients advertising [`Capabilities::KDC_PROXY_URL`].
+    #[must_use]
+    pub fn with_kdc_proxy_url(mut self, kdc_proxy_url: String) -> Self {
+        self.kdc_proxy_url = Some(kdc_proxy_url);
+        self
+    }
+
+    pub fn state(&self) -> &ProxyState {
+        &self.state
+    }
+
+    /// Version negotiated with the client, [`VERSION_1`] until the request is processed
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// Capabilities negotiated with the client, empty until the request is processed
+    pub fn capabilities(&self) -> Capabilities {
+        self.capabilities
+    }
+
+    /// Size hint of the next PDU to read, from the client or from the RDP server depending on the state
+    pub fn next_pdu_hint(&self) -> Option<&'static dyn PduHint> {
+        match self.state {
+            ProxyState::WaitRequest => Some(&RDCLEANPATH_HINT),
+            ProxyState::WaitX224Response { .. } => Some(&ironrdp_pdu::X224_HINT),
+            _ => None,
+        }
+    }
+
+    /// Processes the RDCleanPath request of the client
+    ///
+    /// The returned destination must be authorized and connected to by the caller, before calling
+    /// [`Self::server_connected`].
+    pub fn process_request(&mut self, request: &[u8]) -> Result<DestinationRequest, ProxyError> {
+        match core::mem::take(&mut self.state) {
+            ProxyState::WaitRequest => {
+                let result = decode_request(request).map(|(destination, version, capabilities, next_state)| {
+                    self.version = version;
+                    self.capabilities = capabilities;
+                    (destination, next_state)
+                });
+                self.apply(result)
+            }
+            state => self.unexpected_step("WaitRequest", state),
+        }
+    }
+
+    /// Processes the RDCleanPath request of the client, and authorizes it with `authorize`
+    ///
+    /// `authorize` typically validates the proxy authorization token against the destination, and returns
+    /// [`ProxyError::Unauthorized`] or [`ProxyError::Forbidden`] on failure. The authorized destination must be
+    /// connected to by the caller, before calling [`Self::server_connected`].
+    pub fn accept_request(
+        &mut self,
+        request: &[u8],
+        authorize: impl FnOnce(&DestinationRequest) -> Result<(), ProxyError>,
+    ) -> Result<DestinationRequest, ProxyError> {
+        let destination = self.process_request(request)?;
+
+        if let Err(e) = authorize(&destination) {
+            self.state = ProxyState::Failed;
+            return Err(e);
+        }
+
+        Ok(destination)
+    }
+
+    /// Reports the addresses the destination resolved to
+    ///
+    /// Returns them in the order in which the caller should attempt to connect, alternating between IPv6 and IPv4
+    /// addresses (see [`connection_attempt_order`]), before calling [`Self::server_connected`] with the first
+    /// successful one. They are sent in this order to clients advertising [`Capabilities::SERVER_ADDRESSES`].
+    pub fn destination_resolved(
+        &mut self,
+        addresses: impl IntoIterator<Item = SocketAddr>,
+    ) -> Result<Vec<SocketAddr>, ProxyError> {
+        if !matches!(self.state, ProxyState::ConnectServer { .. }) {
+            let state = core::mem::take(&mut self.state);
+            return self.unexpected_step("ConnectServer", state);
+        }
+
+        let addresses = connection_attempt_order(addresses);
+
+        if addresses.is_empty() {
+            self.state = ProxyState::Failed;
+            return Err(ProxyError::DnsResolution(io::Error::new(
+                io::ErrorKind::NotFound,
+                "destination resolved to no address",
+            )));
+        }
+
+        self.server_addresses = addresses.clone();
+
+        Ok(addresses)
+    }
+
+    /// Reports the successful TCP connection to the RDP server
+    ///
+    /// Returns the Preconnection PDU, if any, followed by the X.224 Connection Request of the client, to be written
+    /// as is to the RDP server.
+    pub fn server_connected(&mut self, server_addr: SocketAddr) -> Result<Vec<u8>, ProxyError> {
+        let (preconnection_blob, x224_connection_request) = match core::mem::take(&mut self.state) {
+            ProxyState::ConnectServer {
+                preconnection_blob,
+                x224_connection_request,
+            } => (preconnection_blob, x224_connection_request),
+            state => return self.unexpected_step("ConnectServer", state),
+        };
+
+        let result = match preconnection_blob {
+            Some(payload) => encode_vec(&PreconnectionBlob {
+                version: PcbVersion::V2,
+                id: 0,
+                v2_payload: Some(payload),
+            })
+            .map_err(|_| ProxyError::BadRequest("invalid preconnection blob")),
+            None => Ok(Vec::new()),
+        }
+        .map(|mut output| {
+            output.extend_from_slice(&x224_connection_request);
+            (output, ProxyState::WaitX224Response { server_addr })
+        });
+
+        self.apply(result)
+    }
+
+    /// Processes the X.224 Connection Confirm of the RDP server
+    ///
+    /// On success, the caller must upgrade the connection with the RDP server to TLS, then call
+    /// [`Self::tls_upgraded`].
+    pub fn process_x224_response(&mut self, response: &[u8]) -> Result<(), ProxyError> {
+        let server_addr = match core::mem::take(&mut self.state) {
+            ProxyState::WaitX224Response { server_addr } => server_addr,
+            state => return self.unexpected_step("WaitX224Response", state),
+        };
+
+        let result = match decode::<X224<nego::ConnectionConfirm>>(response) {
+            Ok(X224(nego::ConnectionConfirm::Response { protocol, .. }))
+                if protocol.intersects(
+                    nego::SecurityProtocol::SSL | nego::SecurityProtocol::HYBRID | nego::SecurityProtocol::HYBRID_EX,
+                ) =>
+            {
+                let next_state = ProxyState::TlsUpgrade {
+                    server_addr,
+                    x224_connection_response: response.to_vec(),
+                };
+
+                Ok(((), next_state))
+            }
+            Ok(X224(nego::ConnectionConfirm::Response { .. })) => Err(ProxyError::InvalidServerResponse(
+                "no TLS-based security protocol selected",
+            )),
+            Ok(X224(nego::ConnectionConfirm::Failure { code })) => Err(ProxyError::NegotiationFailure(code)),
+            Err(_) => Err(ProxyError::InvalidServerResponse("invalid X.224 Connection Confirm")),
+        };
+
+        self.apply(result)
+    }
+
+    /// Reports the client certificate chain presented to the RDP server, when the TLS upgrade involves mutual TLS
+    ///
+    /// Must be called before [`Self::tls_upgraded`]. The chain is only forwarded to clients advertising
+    /// [`Capabilities::CLIENT_CERT_CHAIN`].
+    pub fn client_cert_presented(
+        &mut self,
+        client_cert_chain: impl IntoIterator<Item = Vec<u8>>,
+    ) -> Result<(), ProxyError> {
+        if !matches!(self.state, ProxyState::TlsUpgrade { .. }) {
+            let state = core::mem::take(&mut self.state);
+            return self.unexpected_step("TlsUpgrade", state);
+        }
+
+        self.client_cert_chain = Some(client_cert_chain.into_iter().collect());
+
+        Ok(())
+    }
+
+    /// Reports the successful TLS upgrade with the RDP server
+    ///
+    /// Returns the RDCleanPath response to write to the client. Afterwards, the caller forwards the traffic between
+    /// the client and the RDP server as is.
+    ///
+    /// `ocsp_response` is the OCSP response stapled by the RDP server during the TLS handshake, if any. It is only
+    /// forwarded to clients advertising [`Capabilities::OCSP_RESPONSE`].
+    pub fn tls_upgraded(
+        &mut self,
+        server_cert_chain: impl IntoIterator<Item = Vec<u8>>,
+        ocsp_response: Option<Vec<u8>>,
+    ) -> Result<Vec<u8>, ProxyError> {
+        let (server_addr, x224_connection_response) = match core::mem::take(&mut self.state) {
+            ProxyState::TlsUpgrade {
+                server_addr,
+                x224_connection_response,
+            } => (server_addr, x224_connection_response),
+            state => return self.unexpected_step("TlsUpgrade", state),
+        };
+
+        let ocsp_response = ocsp_response.filter(|_| self.capabilities.contains(Capabilities::OCSP_RESPONSE));
+        let client_cert_chain = self
+            .client_cert_chain
+            .take()
+            .filter(|_| self.capabilities.contains(Capabilities::CLIENT_CERT_CHAIN));
+        let kdc_proxy_url = self
+            .kdc_proxy_url
+            .clone()
+            .filter(|_| self.capabilities.contains(Capabilities::KDC_PROXY_URL));
+        let server_addresses = Some(core::mem::take(&mut self.server_addresses))
+            .filter(|addresses| !addresses.is_empty() && self.capabilities.contains(Capabilities::SERVER_ADDRESSES));
+
+        let result = RDCleanPathPdu::new_response(
+            server_addr.to_string(),
+            x224_connection_response,
+            server_cert_chain,
+            ocsp_response,
+        )
+        .and_then(|response| match client_cert_chain {
+            Some(client_cert_chain) => response.with_client_cert_chain(client_cert_chain),
+            None => Ok(response),
+        })
+        .map(|response| RDCleanPathPdu {
+            kdc_proxy_url,
+            ..response
+        })
+        .map(|response| match server_addresses {
+            Some(server_addresses) => response.with_server_addresses(server_addresses),
+            None => response,
+        })
+        .map(|response| {
+            if self.version >= VERSION_2 {
+                response.with_capabilities(self.capabilities)
+            } else {
+                response
+            }
+        })
+        .and_then(|response| response.to_der())
+        .map(|response| (response, ProxyState::Connected))
+        .map_err(|_| ProxyError::InvalidServerResponse("certificate chain or OCSP response too large"));
+
+        self.apply(result)
+    }
+
+    /// Reports a failure of a step performed by the caller, such as authorization, connection or TLS upgrade
+    ///
+    /// Returns the RDCleanPath error PDU to write to the client.
+    pub fn fail(&mut self, error: &ProxyError) -> Vec<u8> {
+        self.state = ProxyState::Failed;
+
+        // An error PDU only holds small integers.
+        error.to_pdu().to_der().expect("error PDU encoding")
+    }
+
+    fn unexpected_step<T>(&mut self, expected: &'static str, state: ProxyState) -> Result<T, ProxyError> {
+        let error = ProxyError::UnexpectedStep {
+            expected,
+            state: state.name(),
+        };
+        self.state = state;
+        Err(error)
+    }
+
+    fn apply<T>(&mut self, result: Result<(T, ProxyState), ProxyError>) -> Result<T, ProxyError> {
+        match result {
+            Ok((output, next_state)) => {
+                self.state = next_state;
+                Ok(output)
+            }
+            Err(e) => {
+                self.state = ProxyState::Failed;
+                Err(e)
+            }
+        }
+    }
+}
+
+fn decode_request(request: &[u8]) -> Result<(DestinationRequest, u64, Capabilities, ProxyState), ProxyError> {
+    let pdu = RDCleanPathPdu::from_der(request).map_err(|_| ProxyError::BadRequest("invalid DER encoding"))?;
+
+    let version = negotiate_version(pdu.version).ok_or(ProxyError::BadRequest("unsupported version"))?;
+    let capabilities = Capabilities::negotiate(pdu.capabilities());
+
+    let RDCleanPath::Request {
+        destination,
+        proxy_auth,
+        server_auth,
+        preconnection_blob,
+        x224_connection_request,
+        client_cert_chain,
+        ..
+    } = pdu.into_enum().map_err(|_| ProxyError::BadRequest("missing field"))?
+    else {
+        return Err(ProxyError::BadRequest("not a request"));
+    };
+
+    // Rejected early rather than when sending it to the RDP server.
+    let preconnection_blob = preconnection_blob
+        .map(crate::pcb::PreconnectionBlob::new)
+        .transpose()
+        .map_err(|_| ProxyError::BadRequest("invalid preconnection blob"))?
+        .map(String::from);
+
+    let x224_connection_request = x224_connection_request.into_bytes();
+
+    decode::<X224<nego::ConnectionRequest>>(&x224_connection_request)
+        .map_err(|_| ProxyError::BadRequest("invalid X.224 Connection Request"))?;
+
+    let destination = DestinationRequest {
+        destination,
+        proxy_auth,
+        server_auth,
+        client_cert_chain: client_cert_chain
+            .filter(|_| capabilities.contains(Capabilities::CLIENT_CERT_CHAIN))
+            .map(|chain| chain.into_iter().map(OctetString::into_bytes).collect()),
+    };
+
+    let next_state = ProxyState::ConnectServer {
+        preconnection_blob,
+        x224_connection_request,
+    };
+
+    Ok((destination, version, capabilities, next_state))
+}
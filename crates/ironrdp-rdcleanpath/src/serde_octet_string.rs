@@ -0,0 +1,40 @@
+//! `serde` helpers for the [`OctetString`] fields, serialized as plain byte sequences
+
+use der::asn1::OctetString;
+use serde::de::Error as _;
+use serde::{Deserialize as _, Deserializer, Serialize as _, Serializer};
+
+pub(crate) mod option {
+    use super::*;
+
+    pub(crate) fn serialize<S: Serializer>(value: &Option<OctetString>, serializer: S) -> Result<S::Ok, S::Error> {
+        value.as_ref().map(OctetString::as_bytes).serialize(serializer)
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<OctetString>, D::Error> {
+        Option::<Vec<u8>>::deserialize(deserializer)?
+            .map(OctetString::new)
+            .transpose()
+            .map_err(D::Error::custom)
+    }
+}
+
+pub(crate) mod option_vec {
+    use super::*;
+
+    pub(crate) fn serialize<S: Serializer>(value: &Option<Vec<OctetString>>, serializer: S) -> Result<S::Ok, S::Error> {
+        value
+            .as_ref()
+            .map(|strings| strings.iter().map(OctetString::as_bytes).collect::<Vec<_>>())
+            .serialize(serializer)
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Vec<OctetString>>, D::Error> {
+        Option::<Vec<Vec<u8>>>::deserialize(deserializer)?
+            .map(|strings| strings.into_iter().map(OctetString::new).collect::<der::Result<_>>())
+            .transpose()
+            .map_err(D::Error::custom)
+    }
+}
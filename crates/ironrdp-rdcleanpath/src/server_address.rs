@@ -0,0 +1,84 @@
+use std::net::SocketAddr;
+
+use crate::RDCleanPathPdu;
+
+/// Address the destination resolved to on proxy side
+#[derive(Clone, Debug, Eq, PartialEq, der::Sequence)]
+#[asn1(tag_mode = "EXPLICIT")]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ServerAddress {
+    /// IPv4 or IPv6 address, with the port
+    #[asn1(context_specific = "0")]
+    pub address: String,
+    /// Addresses with lower values are attempted first.
+    #[asn1(context_specific = "1")]
+    pub priority: u32,
+}
+
+/// Orders the addresses of a dual-stack destination for connection attempts
+///
+/// As recommended by RFC 8305 (Happy Eyeballs), IPv6 and IPv4 addresses are alternated, starting with the family of
+/// the first address. The relative order of the addresses of a given family is kept.
+pub fn connection_attempt_order(addresses: impl IntoIterator<Item = SocketAddr>) -> Vec<SocketAddr> {
+    let addresses: Vec<SocketAddr> = addresses.into_iter().collect();
+
+    let Some(first) = addresses.first() else {
+        return addresses;
+    };
+
+    let (preferred, other): (Vec<SocketAddr>, Vec<SocketAddr>) = addresses
+        .iter()
+        .partition(|address| address.is_ipv6() == first.is_ipv6());
+
+    let mut preferred = preferred.into_iter();
+    let mut other = other.into_iter();
+    let mut ordered = Vec::with_capacity(addresses.len());
+
+    loop {
+        match (preferred.next(), other.next()) {
+            (None, None) => break,
+            (a, b) => ordered.extend(a.into_iter().chain(b)),
+        }
+    }
+
+    ordered
+}
+
+impl RDCleanPathPdu {
+    /// Sets the addresses the destination resolved to, prioritized in the given order
+    #[must_use]
+    pub fn with_server_addresses(mut self, addresses: impl IntoIterator<Item = SocketAddr>) -> Self {
+        self.server_addresses = Some(
+            addresses
+                .into_iter()
+                .zip(0..)
+                .map(|(address, priority)| ServerAddress {
+                    address: address.to_string(),
+                    priority,
+                })
+                .collect(),
+        );
+        self
+    }
+
+    /// Returns the addresses of the RDP server, in the order to attempt them
+    ///
+    /// Without the `server_addresses` field, only `server_addr` is returned. Invalid addresses are skipped.
+    pub fn server_addresses(&self) -> Vec<SocketAddr> {
+        match &self.server_addresses {
+            Some(addresses) => {
+                let mut addresses: Vec<&ServerAddress> = addresses.iter().collect();
+                addresses.sort_by_key(|address| address.priority);
+                addresses
+                    .into_iter()
+                    .filter_map(|address| address.address.parse().ok())
+                    .collect()
+            }
+            None => self
+                .server_addr
+                .iter()
+                .filter_map(|address| address.parse().ok())
+                .collect(),
+        }
+    }
+}
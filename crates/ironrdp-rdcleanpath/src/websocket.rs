@@ -0,0 +1,196 @@
+//! RDCleanPath over WebSocket
+//!
+//! Deployments commonly carry the RDCleanPath exchange, and the RDP traffic following it, over a WebSocket. The
+//! WebSocket implementation is left to the caller: [`RDCleanPathWebSocket`] works over any `Stream` and `Sink` of
+//! messages implementing [`WebSocketMessage`].
+//!
+//! Each PDU sent is a single binary message. Received PDUs are reassembled from the binary messages, which may split a
+//! PDU or hold bytes following it. Once the exchange is over, [`RDCleanPathWebSocket::into_passthrough`] turns the
+//! WebSocket into a byte stream for the RDP traffic, starting with the bytes received past the last PDU.
+
+use core::pin::Pin;
+use core::task::{ready, Context, Poll};
+use std::error::Error;
+use std::io;
+
+use futures_util::{AsyncRead, AsyncWrite, Sink, SinkExt as _, Stream, StreamExt as _};
+
+use crate::RDCleanPathPdu;
+
+/// Kind of a received WebSocket message
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WebSocketMessageKind {
+    Binary(Vec<u8>),
+    /// Text messages are not part of the protocol, and are reported as errors.
+    Text,
+    /// Ping and pong messages, which are ignored
+    Control,
+    Close,
+}
+
+/// Message type of a WebSocket implementation
+pub trait WebSocketMessage: Sized {
+    fn binary(data: Vec<u8>) -> Self;
+
+    fn into_kind(self) -> WebSocketMessageKind;
+}
+
+/// Transports only carrying binary messages
+impl WebSocketMessage for Vec<u8> {
+    fn binary(data: Vec<u8>) -> Self {
+        data
+    }
+
+    fn into_kind(self) -> WebSocketMessageKind {
+        WebSocketMessageKind::Binary(self)
+    }
+}
+
+/// Exchanges RDCleanPath PDUs over a WebSocket
+pub struct RDCleanPathWebSocket<S> {
+    socket: S,
+    buffer: Vec<u8>,
+}
+
+impl<S> RDCleanPathWebSocket<S> {
+    pub fn new(socket: S) -> Self {
+        Self {
+            socket,
+            buffer: Vec::new(),
+        }
+    }
+
+    /// Returns the WebSocket, along with the bytes received past the last PDU
+    pub fn into_inner(self) -> (S, Vec<u8>) {
+        (self.socket, self.buffer)
+    }
+
+    /// Turns the WebSocket into a byte stream for the RDP traffic following the RDCleanPath exchange
+    pub fn into_passthrough(self) -> WebSocketStream<S> {
+        WebSocketStream {
+            socket: self.socket,
+            pending: self.buffer,
+            offset: 0,
+        }
+    }
+}
+
+impl<S, M, E> RDCleanPathWebSocket<S>
+where
+    S: Stream<Item = Result<M, E>> + Sink<M, Error = E> + Unpin,
+    M: WebSocketMessage,
+    E: Into<Box<dyn Error + Send + Sync>>,
+{
+    /// Sends a PDU as a single binary message
+    pub async fn send(&mut self, pdu: &RDCleanPathPdu) -> io::Result<()> {
+        let encoded = pdu
+            .to_der()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        self.socket.send(M::binary(encoded)).await.map_err(io::Error::other)
+    }
+
+    /// Receives the next PDU, reading as many messages as needed
+    pub async fn receive(&mut self) -> io::Result<RDCleanPathPdu> {
+        loop {
+            let decoded = RDCleanPathPdu::decode_from_slice(&self.buffer)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+            if let Some((pdu, consumed)) = decoded {
+                self.buffer.drain(..consumed);
+                return Ok(pdu);
+            }
+
+            let message = self
+                .socket
+                .next()
+                .await
+                .ok_or_else(|| io::Error::from(io::ErrorKind::UnexpectedEof))?
+                .map_err(io::Error::other)?;
+
+            match message.into_kind() {
+                WebSocketMessageKind::Binary(data) => self.buffer.extend_from_slice(&data),
+                WebSocketMessageKind::Control => {}
+                WebSocketMessageKind::Text => return Err(unexpected_text()),
+                WebSocketMessageKind::Close => return Err(io::Error::from(io::ErrorKind::UnexpectedEof)),
+            }
+        }
+    }
+}
+
+/// Byte stream over the binary messages of a WebSocket
+///
+/// Each write is sent as a single binary message. A close message is read as the end of the stream.
+pub struct WebSocketStream<S> {
+    socket: S,
+    pending: Vec<u8>,
+    offset: usize,
+}
+
+impl<S> WebSocketStream<S> {
+    pub fn into_inner(self) -> S {
+        self.socket
+    }
+}
+
+impl<S, M, E> AsyncRead for WebSocketStream<S>
+where
+    S: Stream<Item = Result<M, E>> + Unpin,
+    M: WebSocketMessage,
+    E: Into<Box<dyn Error + Send + Sync>>,
+{
+    fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        loop {
+            let this = &mut *self;
+
+            if let Some(pending) = this.pending.get(this.offset..).filter(|pending| !pending.is_empty()) {
+                let read = pending.len().min(buf.len());
+                buf[..read].copy_from_slice(&pending[..read]);
+                this.offset += read;
+                return Poll::Ready(Ok(read));
+            }
+
+            match ready!(Pin::new(&mut this.socket).poll_next(cx)) {
+                Some(Ok(message)) => match message.into_kind() {
+                    WebSocketMessageKind::Binary(data) => {
+                        this.pending = data;
+                        this.offset = 0;
+                    }
+                    WebSocketMessageKind::Control => {}
+                    WebSocketMessageKind::Text => return Poll::Ready(Err(unexpected_text())),
+                    WebSocketMessageKind::Close => return Poll::Ready(Ok(0)),
+                },
+                Some(Err(e)) => return Poll::Ready(Err(io::Error::other(e))),
+                None => return Poll::Ready(Ok(0)),
+            }
+        }
+    }
+}
+
+impl<S, M, E> AsyncWrite for WebSocketStream<S>
+where
+    S: Stream<Item = Result<M, E>> + Sink<M, Error = E> + Unpin,
+    M: WebSocketMessage,
+    E: Into<Box<dyn Error + Send + Sync>>,
+{
+    fn poll_write(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        ready!(Pin::new(&mut self.socket).poll_ready(cx)).map_err(io::Error::other)?;
+
+        Pin::new(&mut self.socket)
+            .start_send(M::binary(buf.to_vec()))
+            .map_err(io::Error::other)?;
+
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.socket).poll_flush(cx).map_err(io::Error::other)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.socket).poll_close(cx).map_err(io::Error::other)
+    }
+}
+
+fn unexpected_text() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "unexpected WebSocket text message")
+}
@@ -11,23 +11,64 @@ use std::io::Read;
 use std::io::{Seek, SeekFrom, Write};
 use std::os::fd::{AsFd, AsRawFd};
 use std::os::unix::fs::MetadataExt;
+use std::process::{Command, Stdio};
 
 #[derive(Debug, Default)]
 pub struct NixRdpdrBackend {
     file_id: u32,
     file_base: String,
+    drives: std::collections::HashMap<u32, SharedDrive>,
+    local_printer: Option<String>,
     file_map: std::collections::HashMap<u32, std::fs::File>,
     file_path_map: std::collections::HashMap<u32, String>,
     file_dir_map: std::collections::HashMap<u32, OwningIter>,
+    print_job_map: std::collections::HashMap<u32, Vec<u8>>,
+}
+
+/// A local directory shared with the server
+#[derive(Debug, Clone)]
+struct SharedDrive {
+    path: String,
+    read_only: bool,
 }
 
 impl NixRdpdrBackend {
+    /// Creates a backend sharing `file_base` for all the drives which are not configured with [`Self::with_drive`].
     pub fn new(file_base: String) -> Self {
         Self {
             file_base,
             ..Default::default()
         }
     }
+
+    /// Shares `path` as the drive with the given device ID.
+    ///
+    /// When `read_only` is set, the server can not create, modify or delete any file of the drive.
+    #[must_use]
+    pub fn with_drive(mut self, device_id: u32, path: String, read_only: bool) -> Self {
+        let path = path.trim_end_matches('/').to_owned();
+        self.drives.insert(device_id, SharedDrive { path, read_only });
+        self
+    }
+
+    /// Prints the jobs on `local_printer`, instead of the default printer.
+    ///
+    /// The print jobs are sent as PostScript documents to `lpr`.
+    #[must_use]
+    pub fn with_local_printer(mut self, local_printer: String) -> Self {
+        self.local_printer = Some(local_printer);
+        self
+    }
+
+    fn drive_base(&self, device_id: u32) -> &str {
+        self.drives
+            .get(&device_id)
+            .map_or(self.file_base.as_str(), |drive| drive.path.as_str())
+    }
+
+    fn is_read_only(&self, device_id: u32) -> bool {
+        self.drives.get(&device_id).is_some_and(|drive| drive.read_only)
+    }
 }
 
 impl_as_any!(NixRdpdrBackend);
@@ -68,9 +109,24 @@ impl RdpdrBackend for NixRdpdrBackend {
             }
         }
     }
+    fn handle_printer_io_request(&mut self, req: ServerPrinterIoRequest) -> PduResult<Vec<SvcMessage>> {
+        match req {
+            ServerPrinterIoRequest::DeviceCreateRequest(req_inner) => create_print_job(self, req_inner),
+            ServerPrinterIoRequest::DeviceWriteRequest(req_inner) => write_print_job(self, req_inner),
+            ServerPrinterIoRequest::DeviceCloseRequest(req_inner) => close_print_job(self, req_inner),
+        }
+    }
 }
 
 pub(crate) fn write_device(backend: &mut NixRdpdrBackend, req_inner: DeviceWriteRequest) -> PduResult<Vec<SvcMessage>> {
+    if backend.is_read_only(req_inner.device_io_request.device_id) {
+        warn!("Attempt to write to a read-only drive");
+        let res = RdpdrPdu::DeviceWriteResponse(DeviceWriteResponse {
+            device_io_reply: DeviceIoResponse::new(req_inner.device_io_request, NtStatus::ACCESS_DENIED),
+            length: 0u32,
+        });
+        return Ok(vec![SvcMessage::from(res)]);
+    }
     return process_dependent_file(
         backend,
         req_inner.device_io_request,
@@ -373,11 +429,18 @@ pub(crate) fn set_information(
     backend: &mut NixRdpdrBackend,
     req_inner: ServerDriveSetInformationRequest,
 ) -> PduResult<Vec<SvcMessage>> {
+    if backend.is_read_only(req_inner.device_io_request.device_id) {
+        warn!("Attempt to modify a file of a read-only drive");
+        let res = RdpdrPdu::ClientDriveSetInformationResponse(
+            ClientDriveSetInformationResponse::new(&req_inner, NtStatus::ACCESS_DENIED).map_err(|e| encode_err!(e))?,
+        );
+        return Ok(vec![SvcMessage::from(res)]);
+    }
     match backend.file_path_map.get(&req_inner.device_io_request.file_id) {
         Some(file) => {
             match &req_inner.set_buffer {
                 FileInformationClass::Rename(info) => {
-                    let mut to = backend.file_base.clone();
+                    let mut to = backend.drive_base(req_inner.device_io_request.device_id).to_owned();
                     to.push_str(&info.file_name.replace('\\', "/"));
                     if let Err(error) = std::fs::rename(file, to) {
                         warn!(?error, "Rename file error");
@@ -548,7 +611,7 @@ pub(crate) fn query_directory(
             let mut find_file_name = None;
             if req_inner.initial_query > 0 {
                 if req_inner.path.ends_with('*') {
-                    let mut parent = backend.file_base.clone();
+                    let mut parent = backend.drive_base(req_inner.device_io_request.device_id).to_owned();
                     let query_path = req_inner.path.replace('\\', "/");
                     let len = query_path.len();
                     // path ends with *, so its len > 0
@@ -574,7 +637,7 @@ pub(crate) fn query_directory(
                         backend.file_dir_map.insert(req_inner.device_io_request.file_id, iter);
                     }
                 } else {
-                    let mut full_path = backend.file_base.clone();
+                    let mut full_path = backend.drive_base(req_inner.device_io_request.device_id).to_owned();
                     let query_path = req_inner.path.replace('\\', "/");
                     full_path.push_str(&query_path);
                     find_file_name = Some(full_path);
@@ -647,8 +710,19 @@ pub(crate) fn create_drive(
 ) -> PduResult<Vec<SvcMessage>> {
     let file_id = backend.file_id;
     backend.file_id += 1;
-    let mut path = String::from(backend.file_base.as_str());
+    let mut path = String::from(backend.drive_base(req_inner.device_io_request.device_id));
     path.push_str(&req_inner.path.replace('\\', "/"));
+    let read_only = backend.is_read_only(req_inner.device_io_request.device_id);
+    if read_only && modifies_drive(&req_inner, std::path::Path::new(&path).exists()) {
+        warn!("Attempt to modify a read-only drive, path:{}", path);
+        let io_response = DeviceIoResponse::new(req_inner.device_io_request, NtStatus::ACCESS_DENIED);
+        let res = RdpdrPdu::DeviceCreateResponse(DeviceCreateResponse {
+            device_io_reply: io_response,
+            file_id,
+            information: Information::empty(),
+        });
+        return Ok(vec![SvcMessage::from(res)]);
+    }
     // first process directory
     match std::fs::metadata(&path) {
         Ok(meta) => {
@@ -723,23 +797,28 @@ pub(crate) fn create_drive(
     }
 
     let mut fs = std::fs::OpenOptions::new();
-    if CreateDisposition::FILE_OPEN_IF == req_inner.create_disposition {
-        fs.create(true).write(true).read(true);
-    }
-    if CreateDisposition::FILE_CREATE == req_inner.create_disposition {
-        fs.create_new(true).write(true).read(true);
-    }
-    if CreateDisposition::FILE_SUPERSEDE == req_inner.create_disposition {
-        fs.create(true).write(true).append(true).read(true);
-    }
-    if CreateDisposition::FILE_OPEN == req_inner.create_disposition {
+    if read_only {
+        // The file exists and is not modified, as checked above.
         fs.read(true);
-    }
-    if CreateDisposition::FILE_OVERWRITE == req_inner.create_disposition {
-        fs.write(true).truncate(true).read(true);
-    }
-    if CreateDisposition::FILE_OVERWRITE_IF == req_inner.create_disposition {
-        fs.write(true).truncate(true).create(true).read(true);
+    } else {
+        if CreateDisposition::FILE_OPEN_IF == req_inner.create_disposition {
+            fs.create(true).write(true).read(true);
+        }
+        if CreateDisposition::FILE_CREATE == req_inner.create_disposition {
+            fs.create_new(true).write(true).read(true);
+        }
+        if CreateDisposition::FILE_SUPERSEDE == req_inner.create_disposition {
+            fs.create(true).write(true).append(true).read(true);
+        }
+        if CreateDisposition::FILE_OPEN == req_inner.create_disposition {
+            fs.read(true);
+        }
+        if CreateDisposition::FILE_OVERWRITE == req_inner.create_disposition {
+            fs.write(true).truncate(true).read(true);
+        }
+        if CreateDisposition::FILE_OVERWRITE_IF == req_inner.create_disposition {
+            fs.write(true).truncate(true).create(true).read(true);
+        }
     }
 
     match fs.open(&path) {
@@ -762,6 +841,116 @@ pub(crate) fn create_drive(
     }
 }
 
+/// Returns whether the request creates, modifies or deletes a file
+fn modifies_drive(req_inner: &DeviceCreateRequest, exists: bool) -> bool {
+    let write_access = DesiredAccess::FILE_WRITE_DATA_OR_FILE_ADD_FILE
+        | DesiredAccess::FILE_APPEND_DATA_OR_FILE_ADD_SUBDIRECTORY
+        | DesiredAccess::FILE_WRITE_EA
+        | DesiredAccess::FILE_DELETE_CHILD
+        | DesiredAccess::FILE_WRITE_ATTRIBUTES
+        | DesiredAccess::DELETE
+        | DesiredAccess::WRITE_DAC
+        | DesiredAccess::WRITE_OWNER
+        | DesiredAccess::GENERIC_ALL
+        | DesiredAccess::GENERIC_WRITE;
+
+    let modifying_disposition = match req_inner.create_disposition {
+        CreateDisposition::FILE_OPEN => false,
+        CreateDisposition::FILE_OPEN_IF => !exists,
+        _ => true,
+    };
+
+    req_inner.desired_access.intersects(write_access)
+        || req_inner.create_options.contains(CreateOptions::FILE_DELETE_ON_CLOSE)
+        || modifying_disposition
+}
+
+// in fact, index only needs to be different, so it is ok
+#[allow(clippy::arithmetic_side_effects)]
+pub(crate) fn create_print_job(
+    backend: &mut NixRdpdrBackend,
+    req_inner: DeviceCreateRequest,
+) -> PduResult<Vec<SvcMessage>> {
+    let file_id = backend.file_id;
+    backend.file_id += 1;
+    debug!("create print job file_id:{}", file_id);
+    backend.print_job_map.insert(file_id, Vec::new());
+    let res = RdpdrPdu::DeviceCreateResponse(DeviceCreateResponse {
+        device_io_reply: DeviceIoResponse::new(req_inner.device_io_request, NtStatus::SUCCESS),
+        file_id,
+        information: Information::empty(),
+    });
+    Ok(vec![SvcMessage::from(res)])
+}
+
+pub(crate) fn write_print_job(
+    backend: &mut NixRdpdrBackend,
+    req_inner: DeviceWriteRequest,
+) -> PduResult<Vec<SvcMessage>> {
+    let (status, length) = match backend.print_job_map.get_mut(&req_inner.device_io_request.file_id) {
+        Some(job) => {
+            // The document is written sequentially, the offset is ignored.
+            job.extend_from_slice(&req_inner.write_data);
+            (
+                NtStatus::SUCCESS,
+                u32::try_from(req_inner.write_data.len()).map_err(|e| encode_err!(e))?,
+            )
+        }
+        None => (NtStatus::NO_SUCH_FILE, 0u32),
+    };
+    let res = RdpdrPdu::DeviceWriteResponse(DeviceWriteResponse {
+        device_io_reply: DeviceIoResponse::new(req_inner.device_io_request, status),
+        length,
+    });
+    Ok(vec![SvcMessage::from(res)])
+}
+
+pub(crate) fn close_print_job(
+    backend: &mut NixRdpdrBackend,
+    req_inner: DeviceCloseRequest,
+) -> PduResult<Vec<SvcMessage>> {
+    let status = match backend.print_job_map.remove(&req_inner.device_io_request.file_id) {
+        Some(job) => match submit_print_job(backend.local_printer.as_deref(), job) {
+            Ok(()) => NtStatus::SUCCESS,
+            Err(error) => {
+                warn!(%error, "Print error");
+                NtStatus::UNSUCCESSFUL
+            }
+        },
+        None => NtStatus::NO_SUCH_FILE,
+    };
+    let res = RdpdrPdu::DeviceCloseResponse(DeviceCloseResponse {
+        device_io_response: DeviceIoResponse::new(req_inner.device_io_request, status),
+    });
+    Ok(vec![SvcMessage::from(res)])
+}
+
+/// Sends the document to `lpr`, without waiting for it to be printed
+fn submit_print_job(local_printer: Option<&str>, document: Vec<u8>) -> std::io::Result<()> {
+    let mut command = Command::new("lpr");
+    if let Some(local_printer) = local_printer {
+        command.arg("-P").arg(local_printer);
+    }
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::inherit())
+        .spawn()?;
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    std::thread::spawn(move || {
+        if let Err(error) = stdin.write_all(&document) {
+            warn!(%error, "Failed to send the document to lpr");
+        }
+        drop(stdin);
+        match child.wait() {
+            Ok(status) if !status.success() => warn!(%status, "lpr failed"),
+            Ok(_) => debug!("Document sent to lpr"),
+            Err(error) => warn!(%error, "Failed to wait for lpr"),
+        }
+    });
+    Ok(())
+}
+
 pub(crate) fn process_dependent_file(
     backend: &mut NixRdpdrBackend,
     request: DeviceIoRequest,
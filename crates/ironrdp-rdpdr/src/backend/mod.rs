@@ -5,7 +5,9 @@ use core::fmt;
 use ironrdp_core::AsAny;
 use ironrdp_pdu::PduResult;
 
-use crate::pdu::efs::{DeviceControlRequest, ServerDeviceAnnounceResponse, ServerDriveIoRequest};
+use crate::pdu::efs::{
+    DeviceControlRequest, ServerDeviceAnnounceResponse, ServerDriveIoRequest, ServerPrinterIoRequest,
+};
 use crate::pdu::esc::{ScardCall, ScardIoCtlCode};
 use ironrdp_svc::SvcMessage;
 
@@ -14,4 +16,5 @@ pub trait RdpdrBackend: AsAny + fmt::Debug + Send {
     fn handle_server_device_announce_response(&mut self, pdu: ServerDeviceAnnounceResponse) -> PduResult<()>;
     fn handle_scard_call(&mut self, req: DeviceControlRequest<ScardIoCtlCode>, call: ScardCall) -> PduResult<()>;
     fn handle_drive_io_request(&mut self, req: ServerDriveIoRequest) -> PduResult<Vec<SvcMessage>>;
+    fn handle_printer_io_request(&mut self, req: ServerPrinterIoRequest) -> PduResult<Vec<SvcMessage>>;
 }
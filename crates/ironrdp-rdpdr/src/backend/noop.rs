@@ -21,4 +21,10 @@ impl RdpdrBackend for NoopRdpdrBackend {
     fn handle_drive_io_request(&mut self, _req: crate::pdu::efs::ServerDriveIoRequest) -> PduResult<Vec<SvcMessage>> {
         Ok(Vec::new())
     }
+    fn handle_printer_io_request(
+        &mut self,
+        _req: crate::pdu::efs::ServerPrinterIoRequest,
+    ) -> PduResult<Vec<SvcMessage>> {
+        Ok(Vec::new())
+    }
 }
@@ -29,7 +29,7 @@ pub mod pdu;
 
 pub use self::backend::noop::NoopRdpdrBackend;
 pub use self::backend::RdpdrBackend;
-use crate::pdu::efs::ServerDriveIoRequest;
+use crate::pdu::efs::{ServerDriveIoRequest, ServerPrinterIoRequest};
 
 /// The RDPDR channel as specified in [\[MS-RDPEFS\]].
 ///
@@ -94,6 +94,16 @@ impl Rdpdr {
         self
     }
 
+    /// Adds printer redirection capability, and announces a printer to the server.
+    ///
+    /// The print jobs are handled by [`RdpdrBackend::handle_printer_io_request`].
+    #[must_use]
+    pub fn with_printer(mut self, device_id: u32, name: String) -> Self {
+        self.capabilities.add_printer();
+        self.device_list.add_printer(device_id, name);
+        self
+    }
+
     /// Users should call this method to announce a new drive to the server. It's the caller's responsibility
     /// to take the returned [`ClientDeviceListAnnounce`] and send it to the server.
     pub fn add_drive(&mut self, device_id: u32, name: String) -> ClientDeviceListAnnounce {
@@ -177,6 +187,13 @@ impl Rdpdr {
 
                 Ok(self.backend.handle_drive_io_request(req)?)
             }
+            DeviceType::Print => {
+                let req = ServerPrinterIoRequest::decode(dev_io_req, src).map_err(|e| decode_err!(e))?;
+
+                debug!(?req);
+
+                Ok(self.backend.handle_printer_io_request(req)?)
+            }
             _ => {
                 // This should never happen, as we only announce devices that we support.
                 warn!(?dev_io_req, "received packet for unsupported device type");
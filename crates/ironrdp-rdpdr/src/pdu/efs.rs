@@ -307,6 +307,10 @@ impl Capabilities {
         self.push(CapabilityMessage::new_drive());
     }
 
+    pub fn add_printer(&mut self) {
+        self.push(CapabilityMessage::new_printer());
+    }
+
     fn add_general(&mut self, special_type_device_cap: u32) {
         self.push(CapabilityMessage::new_general(special_type_device_cap));
     }
@@ -392,6 +396,14 @@ impl CapabilityMessage {
         }
     }
 
+    /// Creates a new `PRINTER_CAPS_SET`, as described in \[MS-RDPEFS\] 2.2.2.7.2.
+    pub fn new_printer() -> Self {
+        Self {
+            header: CapabilityHeader::new_printer(),
+            capability_data: CapabilityData::Printer,
+        }
+    }
+
     fn encode(&self, dst: &mut WriteCursor<'_>) -> EncodeResult<()> {
         ensure_size!(in: dst, size: self.size());
         self.header.encode(dst)?;
@@ -450,6 +462,14 @@ impl CapabilityHeader {
         }
     }
 
+    fn new_printer() -> Self {
+        Self {
+            cap_type: CapabilityType::Printer,
+            length: Self::SIZE as u16,
+            version: PRINT_CAPABILITY_VERSION_01,
+        }
+    }
+
     fn decode(src: &mut ReadCursor<'_>) -> DecodeResult<Self> {
         ensure_size!(in: src, size: Self::SIZE);
         let cap_type: CapabilityType = src.read_u16().try_into()?;
@@ -499,6 +519,8 @@ pub const GENERAL_CAPABILITY_VERSION_02: u32 = 0x0000_0002;
 pub const SMARTCARD_CAPABILITY_VERSION_01: u32 = 0x0000_0001;
 /// DRIVE_CAPABILITY_VERSION_02
 pub const DRIVE_CAPABILITY_VERSION_02: u32 = 0x0000_0002;
+/// PRINT_CAPABILITY_VERSION_01
+pub const PRINT_CAPABILITY_VERSION_01: u32 = 0x0000_0001;
 
 impl TryFrom<u16> for CapabilityType {
     type Error = DecodeError;
@@ -796,6 +818,10 @@ impl Devices {
         self.push(DeviceAnnounceHeader::new_drive(device_id, name));
     }
 
+    pub fn add_printer(&mut self, device_id: u32, name: String) {
+        self.push(DeviceAnnounceHeader::new_printer(device_id, name));
+    }
+
     /// Returns the [`DeviceType`] for the given device ID.
     pub fn for_device_type(&self, device_id: u32) -> DecodeResult<DeviceType> {
         if let Some(device_type) = self.0.iter().find(|d| d.device_id == device_id).map(|d| d.device_type) {
@@ -867,6 +893,45 @@ impl DeviceAnnounceHeader {
         }
     }
 
+    /// Creates the announce of a printer (DR_PRN_DEVICE_ANNOUNCE), as described in \[MS-RDPEPC\] 2.2.2.1.
+    ///
+    /// The printer is announced as the default printer, with a PostScript driver available on Windows servers:
+    /// the print jobs are therefore received as PostScript documents.
+    fn new_printer(device_id: u32, name: String) -> Self {
+        const DRIVER_NAME: &str = "MS Publisher Imagesetter";
+
+        fn to_utf16_null_terminated(value: &str) -> Vec<u8> {
+            value
+                .encode_utf16()
+                .chain(core::iter::once(0))
+                .flat_map(u16::to_le_bytes)
+                .collect()
+        }
+
+        let pnp_name = to_utf16_null_terminated("");
+        let driver_name = to_utf16_null_terminated(DRIVER_NAME);
+        let printer_name = to_utf16_null_terminated(&name);
+
+        let mut device_data =
+            Vec::with_capacity(size_of::<u32>() * 6 + pnp_name.len() + driver_name.len() + printer_name.len());
+        device_data.extend_from_slice(&PrinterAnnounceFlags::DEFAULT_PRINTER.bits().to_le_bytes());
+        device_data.extend_from_slice(&0u32.to_le_bytes()); // CodePage
+        device_data.extend_from_slice(&(pnp_name.len() as u32).to_le_bytes());
+        device_data.extend_from_slice(&(driver_name.len() as u32).to_le_bytes());
+        device_data.extend_from_slice(&(printer_name.len() as u32).to_le_bytes());
+        device_data.extend_from_slice(&0u32.to_le_bytes()); // CachedFieldsLen
+        device_data.extend_from_slice(&pnp_name);
+        device_data.extend_from_slice(&driver_name);
+        device_data.extend_from_slice(&printer_name);
+
+        Self {
+            device_type: DeviceType::Print,
+            device_id,
+            preferred_dos_name: PreferredDosName(format!("PRN{device_id}")),
+            device_data,
+        }
+    }
+
     fn encode(&self, dst: &mut WriteCursor<'_>) -> EncodeResult<()> {
         dst.write_u32(self.device_type.into());
         dst.write_u32(self.device_id);
@@ -885,6 +950,18 @@ impl DeviceAnnounceHeader {
     }
 }
 
+bitflags! {
+    /// Flags of the printer announce (DR_PRN_DEVICE_ANNOUNCE), as described in \[MS-RDPEPC\] 2.2.2.1
+    #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+    pub struct PrinterAnnounceFlags: u32 {
+        const ASCII = 0x0000_0001;
+        const DEFAULT_PRINTER = 0x0000_0002;
+        const NETWORK_PRINTER = 0x0000_0004;
+        const TS_PRINTER = 0x0000_0008;
+        const XPS_FORMAT = 0x0000_0010;
+    }
+}
+
 /// From ["PreferredDosName"](https://docs.microsoft.com/en-us/openspecs/windows_protocols/ms-rdpefs/32e34332-774b-4ead-8c9d-5d64720d6bf9):
 ///
 /// PreferredDosName (8 bytes): A string of ASCII characters (with a maximum length of eight characters) that represents the name of the device as it appears on the client. This field MUST be null-terminated, so the maximum device name is 7 characters long. The following characters are considered invalid for the PreferredDosName field:
@@ -1468,6 +1545,31 @@ impl ServerDriveIoRequest {
     }
 }
 
+/// I/O requests sent to a printer, as described in \[MS-RDPEPC\]
+///
+/// A print job is a file created on the printer: the document is written to it, and printed once it is closed.
+#[derive(Debug, PartialEq, Clone)]
+pub enum ServerPrinterIoRequest {
+    DeviceCreateRequest(DeviceCreateRequest),
+    DeviceWriteRequest(DeviceWriteRequest),
+    DeviceCloseRequest(DeviceCloseRequest),
+}
+
+impl ServerPrinterIoRequest {
+    pub fn decode(dev_io_req: DeviceIoRequest, src: &mut ReadCursor<'_>) -> DecodeResult<Self> {
+        match dev_io_req.major_function {
+            MajorFunction::Create => Ok(Self::DeviceCreateRequest(DeviceCreateRequest::decode(dev_io_req, src)?)),
+            MajorFunction::Write => Ok(Self::DeviceWriteRequest(DeviceWriteRequest::decode(dev_io_req, src)?)),
+            MajorFunction::Close => Ok(Self::DeviceCloseRequest(DeviceCloseRequest::decode(dev_io_req))),
+            major_function => Err(unsupported_value_err!(
+                "ServerPrinterIoRequest::decode",
+                "MajorFunction",
+                format!("{major_function:?}")
+            )),
+        }
+    }
+}
+
 impl From<DeviceCreateRequest> for ServerDriveIoRequest {
     fn from(req: DeviceCreateRequest) -> Self {
         Self::ServerCreateDriveRequest(req)
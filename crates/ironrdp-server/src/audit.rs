@@ -0,0 +1,172 @@
+use std::collections::HashSet;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+
+use ironrdp_acceptor::AcceptorHooks;
+use ironrdp_pdu::rdp::server_error_info::ErrorInfo;
+use ironrdp_pdu::{gcc, nego, pcb, rdp};
+
+/// Allow/deny list of virtual channel names
+///
+/// Names are compared case-insensitively, and apply to both static and dynamic virtual channels.
+/// Denied static channels are refused when the client joins them, and denied dynamic channels are never
+/// offered to the client.
+#[derive(Debug, Clone, Default)]
+pub struct ChannelFilter {
+    allowed: Option<HashSet<String>>,
+    denied: HashSet<String>,
+}
+
+impl ChannelFilter {
+    /// Allows all the channels, except the denied ones
+    pub fn allow_all() -> Self {
+        Self::default()
+    }
+
+    /// Only allows the given channels
+    pub fn allow_only<I, S>(names: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        Self {
+            allowed: Some(names.into_iter().map(|name| name.as_ref().to_lowercase()).collect()),
+            denied: HashSet::new(),
+        }
+    }
+
+    /// Denies the given channel, even if explicitly allowed
+    #[must_use]
+    pub fn deny(mut self, name: &str) -> Self {
+        self.denied.insert(name.to_lowercase());
+        self
+    }
+
+    pub fn is_allowed(&self, name: &str) -> bool {
+        let name = name.to_lowercase();
+
+        if self.denied.contains(&name) {
+            return false;
+        }
+
+        self.allowed.as_ref().map_or(true, |allowed| allowed.contains(&name))
+    }
+
+    /// Same as [`Self::is_allowed`], for a static virtual channel name
+    ///
+    /// Static channel names are truncated to 7 bytes on the wire: the names of the filter are truncated the same way
+    /// before being compared.
+    pub fn is_static_channel_allowed(&self, name: &str) -> bool {
+        let name = name.to_lowercase();
+        let matches = |names: &HashSet<String>| names.iter().any(|entry| truncate_static_name(entry) == name);
+
+        if matches(&self.denied) {
+            return false;
+        }
+
+        self.allowed.as_ref().map_or(true, matches)
+    }
+}
+
+fn truncate_static_name(name: &str) -> &str {
+    name.get(..gcc::ChannelName::SIZE - 1).unwrap_or(name)
+}
+
+/// Identity of the peer owning a channel
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PeerIdentity {
+    pub addr: SocketAddr,
+    /// User name sent in the Client Info PDU
+    ///
+    /// Static channels are joined before the Client Info PDU is received, the user name is thus only known
+    /// for the dynamic channels.
+    pub username: Option<String>,
+    pub domain: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChannelAuditEvent {
+    /// A static virtual channel was requested by the client
+    StaticChannelJoin {
+        peer: PeerIdentity,
+        channel_name: String,
+        allowed: bool,
+    },
+    /// The client answered the creation request of a dynamic virtual channel
+    DynamicChannelCreation {
+        peer: PeerIdentity,
+        channel_name: String,
+        channel_id: u32,
+        /// `CreationStatus` sent by the client, any other value than zero indicating a failure
+        creation_status: u32,
+    },
+    /// A dynamic virtual channel was not offered to the client because of the [`ChannelFilter`]
+    DynamicChannelDenied { peer: PeerIdentity, channel_name: String },
+}
+
+/// Receives the channel audit events of all the sessions served by the server
+pub trait ChannelAuditor: Send + Sync {
+    fn record(&self, event: ChannelAuditEvent);
+}
+
+/// Applies the [`ChannelFilter`] and records the channel joins, on top of the user-provided hooks
+pub(crate) struct AuditingHooks {
+    pub(crate) inner: Option<Box<dyn AcceptorHooks>>,
+    pub(crate) filter: ChannelFilter,
+    pub(crate) auditor: Option<Arc<dyn ChannelAuditor>>,
+    pub(crate) peer: Arc<Mutex<PeerIdentity>>,
+}
+
+impl AuditingHooks {
+    fn peer(&self) -> PeerIdentity {
+        self.peer.lock().expect("poisoned").clone()
+    }
+}
+
+impl AcceptorHooks for AuditingHooks {
+    fn on_preconnection_blob(&mut self, blob: &pcb::PreconnectionBlob) -> bool {
+        self.inner
+            .as_mut()
+            .map_or(true, |inner| inner.on_preconnection_blob(blob))
+    }
+
+    fn on_connection_request(&mut self, request: &nego::ConnectionRequest) -> Result<(), nego::FailureCode> {
+        match self.inner.as_mut() {
+            Some(inner) => inner.on_connection_request(request),
+            None => Ok(()),
+        }
+    }
+
+    fn on_client_info(&mut self, client_info: &rdp::ClientInfo) -> Result<(), ErrorInfo> {
+        if let Some(inner) = self.inner.as_mut() {
+            inner.on_client_info(client_info)?;
+        }
+
+        let mut peer = self.peer.lock().expect("poisoned");
+        peer.username = Some(client_info.credentials.username.clone());
+        peer.domain.clone_from(&client_info.credentials.domain);
+
+        Ok(())
+    }
+
+    fn on_channel_join(&mut self, channel: &gcc::ChannelDef) -> bool {
+        let channel_name = channel.name.as_str().unwrap_or_default();
+
+        let allowed = if self.filter.is_static_channel_allowed(channel_name) {
+            self.inner.as_mut().map_or(true, |inner| inner.on_channel_join(channel))
+        } else {
+            info!(channel_name, "Channel denied by filter");
+            false
+        };
+
+        if let Some(auditor) = self.auditor.as_deref() {
+            auditor.record(ChannelAuditEvent::StaticChannelJoin {
+                peer: self.peer(),
+                channel_name: channel_name.to_owned(),
+                allowed,
+            });
+        }
+
+        allowed
+    }
+}
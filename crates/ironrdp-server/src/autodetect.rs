@@ -0,0 +1,271 @@
+use std::time::{Duration, Instant};
+
+use ironrdp_pdu::rdp::autodetect::{AutoDetectPhase, AutoDetectRequest, AutoDetectResponse, NetworkCharacteristics};
+
+/// Maximum number of RTT requests awaiting a response.
+const MAX_PENDING_RTT_REQUESTS: usize = 8;
+
+/// Network auto-detection settings
+///
+/// See [MS-RDPBCGR] 1.3.1.5 "Network Characteristics Detection".
+#[derive(Debug, Clone)]
+pub struct AutoDetectConfig {
+    /// Measure the bandwidth during the connection sequence
+    pub connect_time: bool,
+    /// Interval between two continuous measurements, or `None` to only perform the connect-time detection
+    pub interval: Option<Duration>,
+    /// Controller picking the encoding quality from the detected network characteristics
+    pub quality_controller: QualityController,
+}
+
+impl Default for AutoDetectConfig {
+    fn default() -> Self {
+        Self {
+            connect_time: true,
+            interval: Some(Duration::from_secs(2)),
+            quality_controller: QualityController::default(),
+        }
+    }
+}
+
+/// Network characteristics detected for a client
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NetworkEstimate {
+    /// Smoothed bandwidth, in kilobits per second
+    pub bandwidth: Option<u32>,
+    /// Lowest round-trip time measured
+    pub base_rtt: Option<Duration>,
+    /// Smoothed round-trip time
+    pub average_rtt: Option<Duration>,
+}
+
+/// Quality of the encoded display updates
+///
+/// Lower qualities use a stronger quantization, trading image quality for a lower bitrate.
+/// This only affects codecs supporting it (RemoteFX).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum EncodingQuality {
+    Lowest,
+    Low,
+    Medium,
+    #[default]
+    High,
+}
+
+impl EncodingQuality {
+    #[must_use]
+    fn lower(self) -> Self {
+        match self {
+            Self::High => Self::Medium,
+            Self::Medium => Self::Low,
+            Self::Low | Self::Lowest => Self::Lowest,
+        }
+    }
+}
+
+/// Picks the encoding quality from the detected network characteristics
+#[derive(Debug, Clone)]
+pub struct QualityController {
+    /// Minimum bandwidth for the high quality, in kilobits per second
+    pub high_bandwidth: u32,
+    /// Minimum bandwidth for the medium quality, in kilobits per second
+    pub medium_bandwidth: u32,
+    /// Minimum bandwidth for the low quality, in kilobits per second
+    pub low_bandwidth: u32,
+    /// Average round-trip time above which the quality is lowered by one level
+    pub high_rtt: Duration,
+}
+
+impl Default for QualityController {
+    fn default() -> Self {
+        Self {
+            high_bandwidth: 20_000,
+            medium_bandwidth: 5_000,
+            low_bandwidth: 1_000,
+            high_rtt: Duration::from_millis(150),
+        }
+    }
+}
+
+impl QualityController {
+    /// Returns the encoding quality suited for the network, or `None` if the bandwidth is not known yet
+    pub fn quality(&self, estimate: &NetworkEstimate) -> Option<EncodingQuality> {
+        let bandwidth = estimate.bandwidth?;
+
+        let quality = if bandwidth >= self.high_bandwidth {
+            EncodingQuality::High
+        } else if bandwidth >= self.medium_bandwidth {
+            EncodingQuality::Medium
+        } else if bandwidth >= self.low_bandwidth {
+            EncodingQuality::Low
+        } else {
+            EncodingQuality::Lowest
+        };
+
+        if estimate.average_rtt.is_some_and(|rtt| rtt > self.high_rtt) {
+            Some(quality.lower())
+        } else {
+            Some(quality)
+        }
+    }
+}
+
+/// Bandwidth measure in progress, identified by the sequence numbers of its requests
+#[derive(Debug, Clone, Copy)]
+enum BandwidthMeasure {
+    /// The Bandwidth Measure Start request was sent
+    Started { start: u16 },
+    /// The Bandwidth Measure Stop request was sent, the results are expected
+    Stopped { start: u16, stop: u16 },
+}
+
+/// Server side of the continuous network auto-detection, for a single client
+#[derive(Debug)]
+pub struct AutoDetector {
+    sequence_number: u16,
+    rtt_requests: Vec<(u16, Instant)>,
+    bandwidth_measure: Option<BandwidthMeasure>,
+    estimate: NetworkEstimate,
+}
+
+impl AutoDetector {
+    /// Creates a detector, starting from the bandwidth detected during the connection sequence if any
+    pub fn new(initial_bandwidth: Option<u32>) -> Self {
+        Self {
+            sequence_number: 0,
+            rtt_requests: Vec::new(),
+            bandwidth_measure: None,
+            estimate: NetworkEstimate {
+                bandwidth: initial_bandwidth,
+                ..Default::default()
+            },
+        }
+    }
+
+    pub fn estimate(&self) -> &NetworkEstimate {
+        &self.estimate
+    }
+
+    /// Returns whether responses from the client are expected
+    pub fn is_waiting(&self) -> bool {
+        !self.rtt_requests.is_empty() || self.bandwidth_measure.is_some()
+    }
+
+    /// Returns the requests to send for the next measurement period
+    ///
+    /// Each period issues a RTT measure, and alternatively starts and stops a bandwidth measure,
+    /// so that the bandwidth is measured on the regular traffic sent in-between.
+    pub fn next_requests(&mut self, now: Instant) -> Vec<AutoDetectRequest> {
+        let mut requests = Vec::with_capacity(2);
+
+        if self.rtt_requests.len() >= MAX_PENDING_RTT_REQUESTS {
+            // The client is not answering, forget the oldest request.
+            self.rtt_requests.remove(0);
+        }
+        let sequence_number = self.next_sequence_number();
+        self.rtt_requests.push((sequence_number, now));
+        requests.push(AutoDetectRequest::RttRequest {
+            sequence_number,
+            phase: AutoDetectPhase::Continuous,
+        });
+
+        match self.bandwidth_measure {
+            Some(BandwidthMeasure::Started { start }) => {
+                let stop = self.next_sequence_number();
+                self.bandwidth_measure = Some(BandwidthMeasure::Stopped { start, stop });
+                requests.push(AutoDetectRequest::BandwidthMeasureStop {
+                    sequence_number: stop,
+                    phase: AutoDetectPhase::Continuous,
+                    payload: Vec::new(),
+                });
+            }
+            // The results of the previous measure never came, a new one replaces it.
+            Some(BandwidthMeasure::Stopped { .. }) | None => {
+                let start = self.next_sequence_number();
+                self.bandwidth_measure = Some(BandwidthMeasure::Started { start });
+                requests.push(AutoDetectRequest::BandwidthMeasureStart {
+                    sequence_number: start,
+                    phase: AutoDetectPhase::Continuous,
+                });
+            }
+        }
+
+        requests
+    }
+
+    /// Updates the estimate with a response from the client
+    ///
+    /// Returns the Network Characteristics Result to send back to the client, if any.
+    pub fn handle_response(&mut self, response: AutoDetectResponse, now: Instant) -> Option<AutoDetectRequest> {
+        match response {
+            AutoDetectResponse::RttResponse { sequence_number } => {
+                let Some(idx) = self.rtt_requests.iter().position(|(seq, _)| *seq == sequence_number) else {
+                    warn!(sequence_number, "Unexpected RTT response");
+                    return None;
+                };
+                let (_, sent_at) = self.rtt_requests.remove(idx);
+                let rtt = now.saturating_duration_since(sent_at);
+
+                self.estimate.base_rtt = Some(self.estimate.base_rtt.map_or(rtt, |base| base.min(rtt)));
+                self.estimate.average_rtt = Some(self.estimate.average_rtt.map_or(rtt, |avg| (avg * 7 + rtt) / 8));
+
+                None
+            }
+
+            AutoDetectResponse::BandwidthMeasureResults {
+                sequence_number,
+                time_delta,
+                byte_count,
+                ..
+            } => {
+                // The results answer the Stop request, the sequence number of the Start request is accepted as well.
+                let expected = matches!(
+                    self.bandwidth_measure,
+                    Some(BandwidthMeasure::Stopped { start, stop }) if sequence_number == start || sequence_number == stop
+                );
+                if !expected {
+                    warn!(sequence_number, "Unexpected bandwidth measure results");
+                    return None;
+                }
+                self.bandwidth_measure = None;
+
+                if time_delta == 0 {
+                    return None;
+                }
+
+                // Bytes per millisecond multiplied by 8 gives kilobits per second.
+                let bandwidth = u32::try_from(u64::from(byte_count) * 8 / u64::from(time_delta)).unwrap_or(u32::MAX);
+                let bandwidth = self.estimate.bandwidth.map_or(bandwidth, |avg| {
+                    u32::try_from((u64::from(avg) * 3 + u64::from(bandwidth)) / 4).unwrap_or(u32::MAX)
+                });
+                self.estimate.bandwidth = Some(bandwidth);
+
+                Some(AutoDetectRequest::NetworkCharacteristicsResult {
+                    sequence_number: self.next_sequence_number(),
+                    characteristics: NetworkCharacteristics {
+                        base_rtt: self.estimate.base_rtt.map(as_millis),
+                        bandwidth: Some(bandwidth),
+                        average_rtt: self.estimate.average_rtt.map(as_millis).unwrap_or_default(),
+                    },
+                })
+            }
+
+            AutoDetectResponse::NetworkCharacteristicsSync { bandwidth, rtt, .. } => {
+                self.estimate.bandwidth = Some(bandwidth);
+                self.estimate.average_rtt = Some(Duration::from_millis(u64::from(rtt)));
+
+                None
+            }
+        }
+    }
+
+    fn next_sequence_number(&mut self) -> u16 {
+        let sequence_number = self.sequence_number;
+        self.sequence_number = self.sequence_number.wrapping_add(1);
+        sequence_number
+    }
+}
+
+fn as_millis(duration: Duration) -> u32 {
+    u32::try_from(duration.as_millis()).unwrap_or(u32::MAX)
+}
@@ -1,11 +1,19 @@
 use std::net::SocketAddr;
+use std::sync::Arc;
 
 use anyhow::Result;
 use tokio_rustls::TlsAcceptor;
 
+use super::audit::{ChannelAuditor, ChannelFilter};
+use super::autodetect::AutoDetectConfig;
+use super::capture::CaptureFactory;
 use super::clipboard::CliprdrServerFactory;
 use super::display::{DesktopSize, RdpServerDisplay};
 use super::handler::{KeyboardEvent, MouseEvent, RdpServerInputHandler};
+use super::hooks::AcceptorHooksFactory;
+use super::input::InputSink;
+use super::limits::{ConnectionLimits, ConnectionPolicy, DecodeLimits};
+use super::scheduler::FrameSchedulerConfig;
 use super::server::*;
 use crate::{DisplayUpdate, RdpServerDisplayUpdates, SoundServerFactory};
 
@@ -21,14 +29,26 @@ pub struct WantsDisplay {
     addr: SocketAddr,
     security: RdpServerSecurity,
     handler: Box<dyn RdpServerInputHandler>,
+    input_sink: Option<Box<dyn InputSink>>,
 }
 pub struct BuilderDone {
     addr: SocketAddr,
     security: RdpServerSecurity,
     handler: Box<dyn RdpServerInputHandler>,
+    input_sink: Option<Box<dyn InputSink>>,
     display: Box<dyn RdpServerDisplay>,
     cliprdr_factory: Option<Box<dyn CliprdrServerFactory>>,
     sound_factory: Option<Box<dyn SoundServerFactory>>,
+    hooks_factory: Option<Box<dyn AcceptorHooksFactory>>,
+    capability_policy: CapabilityPolicy,
+    auto_detect: Option<AutoDetectConfig>,
+    frame_scheduler: Option<FrameSchedulerConfig>,
+    limits: ConnectionLimits,
+    decode_limits: DecodeLimits,
+    connection_policy: Option<Box<dyn ConnectionPolicy>>,
+    channel_filter: ChannelFilter,
+    channel_auditor: Option<Arc<dyn ChannelAuditor>>,
+    capture_factory: Option<Box<dyn CaptureFactory>>,
 }
 
 pub struct RdpServerBuilder<State> {
@@ -74,6 +94,18 @@ impl RdpServerBuilder<WantsSecurity> {
             },
         }
     }
+
+    /// Uses Standard RDP Security, for legacy clients unable to negotiate TLS
+    ///
+    /// Standard RDP Security is weak, and should be restricted to lab or embedded use cases.
+    pub fn with_standard_security(self, config: StandardSecurityConfig) -> RdpServerBuilder<WantsHandler> {
+        RdpServerBuilder {
+            state: WantsHandler {
+                addr: self.state.addr,
+                security: RdpServerSecurity::Standard(config),
+            },
+        }
+    }
 }
 
 impl RdpServerBuilder<WantsHandler> {
@@ -86,6 +118,21 @@ impl RdpServerBuilder<WantsHandler> {
                 addr: self.state.addr,
                 security: self.state.security,
                 handler: Box::new(handler),
+                input_sink: None,
+            },
+        }
+    }
+
+    pub fn with_input_sink<S>(self, sink: S) -> RdpServerBuilder<WantsDisplay>
+    where
+        S: InputSink + 'static,
+    {
+        RdpServerBuilder {
+            state: WantsDisplay {
+                addr: self.state.addr,
+                security: self.state.security,
+                handler: Box::new(NoopInputHandler),
+                input_sink: Some(Box::new(sink)),
             },
         }
     }
@@ -96,6 +143,7 @@ impl RdpServerBuilder<WantsHandler> {
                 addr: self.state.addr,
                 security: self.state.security,
                 handler: Box::new(NoopInputHandler),
+                input_sink: None,
             },
         }
     }
@@ -111,9 +159,20 @@ impl RdpServerBuilder<WantsDisplay> {
                 addr: self.state.addr,
                 security: self.state.security,
                 handler: self.state.handler,
+                input_sink: self.state.input_sink,
                 display: Box::new(display),
                 sound_factory: None,
                 cliprdr_factory: None,
+                hooks_factory: None,
+                capability_policy: CapabilityPolicy::default(),
+                auto_detect: None,
+                frame_scheduler: None,
+                limits: ConnectionLimits::default(),
+                decode_limits: DecodeLimits::default(),
+                connection_policy: None,
+                channel_filter: ChannelFilter::default(),
+                channel_auditor: None,
+                capture_factory: None,
             },
         }
     }
@@ -124,9 +183,20 @@ impl RdpServerBuilder<WantsDisplay> {
                 addr: self.state.addr,
                 security: self.state.security,
                 handler: self.state.handler,
+                input_sink: self.state.input_sink,
                 display: Box::new(NoopDisplay),
                 sound_factory: None,
                 cliprdr_factory: None,
+                hooks_factory: None,
+                capability_policy: CapabilityPolicy::default(),
+                auto_detect: None,
+                frame_scheduler: None,
+                limits: ConnectionLimits::default(),
+                decode_limits: DecodeLimits::default(),
+                connection_policy: None,
+                channel_filter: ChannelFilter::default(),
+                channel_auditor: None,
+                capture_factory: None,
             },
         }
     }
@@ -143,17 +213,95 @@ impl RdpServerBuilder<BuilderDone> {
         self
     }
 
+    pub fn with_acceptor_hooks_factory(mut self, hooks_factory: Option<Box<dyn AcceptorHooksFactory>>) -> Self {
+        self.state.hooks_factory = hooks_factory;
+        self
+    }
+
+    pub fn with_capability_policy(mut self, capability_policy: CapabilityPolicy) -> Self {
+        self.state.capability_policy = capability_policy;
+        self
+    }
+
+    pub fn with_auto_detect(mut self, auto_detect: AutoDetectConfig) -> Self {
+        self.state.auto_detect = Some(auto_detect);
+        self
+    }
+
+    pub fn with_frame_scheduler(mut self, frame_scheduler: FrameSchedulerConfig) -> Self {
+        self.state.frame_scheduler = Some(frame_scheduler);
+        self
+    }
+
+    pub fn with_connection_limits(mut self, limits: ConnectionLimits) -> Self {
+        self.state.limits = limits;
+        self
+    }
+
+    /// Bounds the resources claimed by the PDUs received from the clients
+    ///
+    /// The defaults are generous, and should be tightened when serving untrusted clients.
+    pub fn with_decode_limits(mut self, decode_limits: DecodeLimits) -> Self {
+        self.state.decode_limits = decode_limits;
+        self
+    }
+
+    pub fn with_connection_policy(mut self, policy: Option<Box<dyn ConnectionPolicy>>) -> Self {
+        self.state.connection_policy = policy;
+        self
+    }
+
+    pub fn with_channel_filter(mut self, channel_filter: ChannelFilter) -> Self {
+        self.state.channel_filter = channel_filter;
+        self
+    }
+
+    pub fn with_channel_auditor(mut self, auditor: Option<Arc<dyn ChannelAuditor>>) -> Self {
+        self.state.channel_auditor = auditor;
+        self
+    }
+
+    pub fn with_capture_factory(mut self, capture_factory: Option<Box<dyn CaptureFactory>>) -> Self {
+        self.state.capture_factory = capture_factory;
+        self
+    }
+
     pub fn build(self) -> RdpServer {
-        RdpServer::new(
+        let mut server = RdpServer::new(
             RdpServerOptions {
                 addr: self.state.addr,
                 security: self.state.security,
+                capability_policy: self.state.capability_policy,
+                auto_detect: self.state.auto_detect,
+                frame_scheduler: self.state.frame_scheduler,
+                limits: self.state.limits,
+                decode_limits: self.state.decode_limits,
+                channel_filter: self.state.channel_filter,
             },
             self.state.handler,
             self.state.display,
             self.state.sound_factory,
             self.state.cliprdr_factory,
-        )
+            self.state.hooks_factory,
+        );
+
+        if let Some(sink) = self.state.input_sink {
+            server.set_input_sink(sink);
+        }
+
+        if let Some(policy) = self.state.connection_policy {
+            server.set_connection_policy(policy);
+        }
+
+        if let Some(auditor) = self.state.channel_auditor {
+            server.set_channel_auditor(auditor);
+        }
+
+        if let Some(factory) = self.state.capture_factory {
+            server.set_capture_factory(factory);
+        }
+
+        server
     }
 }
 
@@ -1,19 +1,31 @@
-use ironrdp_pdu::rdp::capability_sets::{self, GeneralExtraFlags};
+use ironrdp_pdu::rdp::capability_sets::{
+    self, CapabilitySetsBuilder, CapabilitySetsValidationError, GeneralExtraFlags,
+};
 
 use crate::{DesktopSize, RdpServerOptions};
 
-pub(crate) fn capabilities(_opts: &RdpServerOptions, size: DesktopSize) -> Vec<capability_sets::CapabilitySet> {
-    vec![
-        capability_sets::CapabilitySet::General(general_capabilities()),
-        capability_sets::CapabilitySet::Bitmap(bitmap_capabilities(&size)),
-        capability_sets::CapabilitySet::Order(order_capabilities()),
-        capability_sets::CapabilitySet::SurfaceCommands(surface_capabilities()),
-        capability_sets::CapabilitySet::Pointer(pointer_capabilities()),
-        capability_sets::CapabilitySet::Input(input_capabilities()),
-        capability_sets::CapabilitySet::VirtualChannel(virtual_channel_capabilities()),
-        capability_sets::CapabilitySet::MultiFragmentUpdate(multifragment_update()),
-        capability_sets::CapabilitySet::BitmapCodecs(bitmap_codecs()),
-    ]
+pub(crate) fn capabilities(
+    opts: &RdpServerOptions,
+    size: DesktopSize,
+) -> Result<Vec<capability_sets::CapabilitySet>, CapabilitySetsValidationError> {
+    let mut builder = CapabilitySetsBuilder::server()
+        .general(general_capabilities())
+        .bitmap(bitmap_capabilities(&size))
+        .order(order_capabilities())
+        .surface_commands(surface_capabilities())
+        .pointer(pointer_capabilities())
+        .input(input_capabilities())
+        .virtual_channel(virtual_channel_capabilities())
+        .multifragment_update(multifragment_update())
+        .bitmap_codecs(bitmap_codecs());
+
+    if let Some(config) = opts.frame_scheduler.as_ref() {
+        builder = builder.frame_acknowledge(capability_sets::FrameAcknowledge {
+            max_unacknowledged_frame_count: config.max_unacknowledged_frames,
+        });
+    }
+
+    builder.build()
 }
 
 fn general_capabilities() -> capability_sets::General {
@@ -0,0 +1,317 @@
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::mem;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::mpsc;
+use std::task::{Context, Poll};
+
+use ironrdp_async::{CaptureEndpoint, CaptureSink, PcapngCapture};
+use ironrdp_core::decode;
+use ironrdp_pdu::x224::{X224Data, X224};
+use ironrdp_pdu::{mcs, Action};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+use crate::audit::ChannelFilter;
+
+/// Builds the writers receiving the session captures
+///
+/// Captures start once the security layer is established, and contain the plaintext PDUs exchanged
+/// in both directions, in the pcapng format (see [`PcapngCapture`]). They hold sensitive data (e.g.: credentials
+/// in the Client Info PDU), and must be stored accordingly.
+pub trait CaptureFactory: Send {
+    /// Returns the writer receiving the capture of a new session, or `None` to not capture it
+    ///
+    /// The writer is used from a blocking task.
+    fn build_writer(&self, peer_addr: SocketAddr) -> Option<Box<dyn Write + Send>>;
+
+    /// Channels whose contents are captured
+    ///
+    /// The PDUs sent on the other static virtual channels are recorded with their payload zeroed.
+    fn channel_filter(&self) -> ChannelFilter {
+        ChannelFilter::allow_all()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CaptureDirection {
+    ClientToServer,
+    ServerToClient,
+}
+
+/// Splits the bytes exchanged with the client into PDUs, and passes them to a [`CaptureSink`]
+///
+/// The payload of the PDUs sent on the static virtual channels rejected by the [`ChannelFilter`] is zeroed, keeping
+/// the headers and the length of the PDUs. The channels are registered on the sink once their IDs are known.
+#[derive(Debug)]
+pub struct CaptureSplitter {
+    filter: ChannelFilter,
+    requested_channels: Vec<String>,
+    channels: HashMap<u16, String>,
+    client_buffer: Vec<u8>,
+    server_buffer: Vec<u8>,
+}
+
+impl CaptureSplitter {
+    pub fn new(filter: ChannelFilter) -> Self {
+        Self {
+            filter,
+            requested_channels: Vec::new(),
+            channels: HashMap::new(),
+            client_buffer: Vec::new(),
+            server_buffer: Vec::new(),
+        }
+    }
+
+    /// Feeds bytes exchanged with the client, passing the complete PDUs to `sink`
+    ///
+    /// Fails if the bytes are not a sequence of TPKT or fast-path frames, or if the sink fails.
+    pub fn feed(&mut self, direction: CaptureDirection, bytes: &[u8], sink: &mut dyn CaptureSink) -> io::Result<()> {
+        let buffer = match direction {
+            CaptureDirection::ClientToServer => &mut self.client_buffer,
+            CaptureDirection::ServerToClient => &mut self.server_buffer,
+        };
+        buffer.extend_from_slice(bytes);
+
+        let mut pending = mem::take(buffer);
+        let mut consumed = 0;
+
+        loop {
+            let remaining = &pending[consumed..];
+
+            let length = match ironrdp_pdu::find_size(remaining) {
+                Ok(Some(info)) if info.length <= remaining.len() && info.length > 0 => info.length,
+                Ok(_) => break,
+                Err(error) => return Err(io::Error::new(io::ErrorKind::InvalidData, error)),
+            };
+
+            let mut frame = remaining[..length].to_vec();
+            if let Some(payload_len) = self.redacted_payload_len(direction, &frame, sink) {
+                let payload_start = frame.len().saturating_sub(payload_len);
+                frame[payload_start..].fill(0);
+            }
+
+            match direction {
+                CaptureDirection::ClientToServer => sink.received(&frame)?,
+                CaptureDirection::ServerToClient => sink.sent(&frame)?,
+            }
+
+            consumed += length;
+        }
+
+        pending.drain(..consumed);
+        match direction {
+            CaptureDirection::ClientToServer => self.client_buffer = pending,
+            CaptureDirection::ServerToClient => self.server_buffer = pending,
+        }
+
+        Ok(())
+    }
+
+    /// Returns the length of the payload to zero, if the frame is sent on a channel rejected by the filter
+    fn redacted_payload_len(
+        &mut self,
+        direction: CaptureDirection,
+        frame: &[u8],
+        sink: &mut dyn CaptureSink,
+    ) -> Option<usize> {
+        // Fast-path PDUs are always sent on the I/O channel.
+        if !matches!(
+            frame.first().map(|header| Action::from_fp_output_header(*header)),
+            Some(Ok(Action::X224))
+        ) {
+            return None;
+        }
+
+        // The channel names are sent in the MCS Connect Initial, and their IDs in the MCS Connect Response.
+        if self.channels.is_empty() {
+            self.learn_channels(direction, frame, sink);
+        }
+
+        let (channel_id, payload_len) = match decode::<X224<mcs::McsMessage<'_>>>(frame).ok()?.0 {
+            mcs::McsMessage::SendDataRequest(data) => (data.channel_id, data.user_data.len()),
+            mcs::McsMessage::SendDataIndication(data) => (data.channel_id, data.user_data.len()),
+            _ => return None,
+        };
+
+        let channel_name = self.channels.get(&channel_id)?;
+
+        (!self.filter.is_static_channel_allowed(channel_name)).then_some(payload_len)
+    }
+
+    fn learn_channels(&mut self, direction: CaptureDirection, frame: &[u8], sink: &mut dyn CaptureSink) {
+        let Ok(payload) = decode::<X224<X224Data<'_>>>(frame) else {
+            return;
+        };
+
+        match direction {
+            CaptureDirection::ClientToServer => {
+                if let Ok(connect_initial) = decode::<mcs::ConnectInitial>(payload.0.data.as_ref()) {
+                    self.requested_channels = connect_initial
+                        .conference_create_request
+                        .gcc_blocks
+                        .network
+                        .map(|network| network.channels)
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(|channel| channel.name.as_str().unwrap_or_default().to_owned())
+                        .collect();
+                }
+            }
+            CaptureDirection::ServerToClient => {
+                if let Ok(connect_response) = decode::<mcs::ConnectResponse>(payload.0.data.as_ref()) {
+                    let channel_ids = connect_response
+                        .conference_create_response
+                        .gcc_blocks
+                        .network
+                        .channel_ids;
+                    self.channels = channel_ids
+                        .into_iter()
+                        .zip(mem::take(&mut self.requested_channels))
+                        .collect();
+
+                    for (channel_id, channel_name) in &self.channels {
+                        sink.register_channel(*channel_id, channel_name);
+                    }
+                }
+            }
+        }
+    }
+}
+
+enum CaptureEvent {
+    Received(Vec<u8>),
+    Sent(Vec<u8>),
+    Channel(u16, String),
+}
+
+/// Forwards the captured frames to the task writing the capture
+struct ForwardingSink(mpsc::Sender<CaptureEvent>);
+
+impl ForwardingSink {
+    fn forward(&self, event: CaptureEvent) -> io::Result<()> {
+        self.0
+            .send(event)
+            .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "capture writer stopped"))
+    }
+}
+
+impl CaptureSink for ForwardingSink {
+    fn received(&mut self, frame: &[u8]) -> io::Result<()> {
+        self.forward(CaptureEvent::Received(frame.to_vec()))
+    }
+
+    fn sent(&mut self, frame: &[u8]) -> io::Result<()> {
+        self.forward(CaptureEvent::Sent(frame.to_vec()))
+    }
+
+    fn register_channel(&mut self, channel_id: u16, name: &str) {
+        let _ = self.forward(CaptureEvent::Channel(channel_id, name.to_owned()));
+    }
+}
+
+/// Captures the bytes exchanged with the client, written as pcapng by a blocking task
+pub(crate) struct CaptureTap {
+    /// `None` once the capture stopped
+    inner: Option<(CaptureSplitter, ForwardingSink)>,
+}
+
+impl CaptureTap {
+    pub(crate) fn start(factory: &dyn CaptureFactory, peer_addr: SocketAddr) -> Option<Self> {
+        let writer = factory.build_writer(peer_addr)?;
+        let (sender, receiver) = mpsc::channel::<CaptureEvent>();
+
+        debug!(?peer_addr, "Starting session capture");
+
+        tokio::task::spawn_blocking(move || {
+            let result = PcapngCapture::new(writer, CaptureEndpoint::Server).and_then(|mut capture| {
+                for event in receiver {
+                    match event {
+                        CaptureEvent::Received(frame) => capture.received(&frame)?,
+                        CaptureEvent::Sent(frame) => capture.sent(&frame)?,
+                        CaptureEvent::Channel(channel_id, name) => capture.register_channel(channel_id, &name),
+                    }
+                }
+
+                Ok(())
+            });
+
+            if let Err(error) = result {
+                warn!(%error, "Session capture failed");
+            }
+        });
+
+        Some(Self {
+            inner: Some((CaptureSplitter::new(factory.channel_filter()), ForwardingSink(sender))),
+        })
+    }
+
+    pub(crate) fn feed(&mut self, direction: CaptureDirection, bytes: &[u8]) {
+        let Some((splitter, sink)) = self.inner.as_mut() else {
+            return;
+        };
+
+        if let Err(error) = splitter.feed(direction, bytes, sink) {
+            // A failure of the writer is already reported by its task.
+            if error.kind() != io::ErrorKind::BrokenPipe {
+                warn!(%error, "Session capture stopped");
+            }
+            self.inner = None;
+        }
+    }
+}
+
+/// Stream recording the bytes exchanged with the client into a [`CaptureTap`]
+pub(crate) struct CaptureStream<S> {
+    inner: S,
+    tap: Option<CaptureTap>,
+}
+
+impl<S> CaptureStream<S> {
+    pub(crate) fn new(inner: S, tap: Option<CaptureTap>) -> Self {
+        Self { inner, tap }
+    }
+}
+
+impl<S> AsyncRead for CaptureStream<S>
+where
+    S: AsyncRead + Unpin,
+{
+    fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+        let this = &mut *self;
+        let filled = buf.filled().len();
+
+        let result = Pin::new(&mut this.inner).poll_read(cx, buf);
+
+        if let (Poll::Ready(Ok(())), Some(tap)) = (&result, this.tap.as_mut()) {
+            tap.feed(CaptureDirection::ClientToServer, &buf.filled()[filled..]);
+        }
+
+        result
+    }
+}
+
+impl<S> AsyncWrite for CaptureStream<S>
+where
+    S: AsyncWrite + Unpin,
+{
+    fn poll_write(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        let this = &mut *self;
+
+        let result = Pin::new(&mut this.inner).poll_write(cx, buf);
+
+        if let (Poll::Ready(Ok(written)), Some(tap)) = (&result, this.tap.as_mut()) {
+            tap.feed(CaptureDirection::ServerToClient, &buf[..*written]);
+        }
+
+        result
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
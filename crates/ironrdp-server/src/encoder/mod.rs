@@ -10,12 +10,14 @@ use ironrdp_pdu::fast_path::{EncryptionFlags, FastPathHeader, FastPathUpdatePdu,
 use ironrdp_pdu::geometry::ExclusiveRectangle;
 use ironrdp_pdu::pointer::{ColorPointerAttribute, Point16, PointerAttribute, PointerPositionAttribute};
 use ironrdp_pdu::rdp::capability_sets::{CmdFlags, EntropyBits};
-use ironrdp_pdu::surface_commands::{ExtendedBitmapDataPdu, SurfaceBitsPdu, SurfaceCommand};
+use ironrdp_pdu::surface_commands::{
+    ExtendedBitmapDataPdu, FrameAction, FrameMarkerPdu, SurfaceBitsPdu, SurfaceCommand,
+};
 
 use self::bitmap::BitmapEncoder;
 use self::rfx::RfxEncoder;
 use super::BitmapUpdate;
-use crate::{ColorPointer, PixelOrder, RGBAPointer};
+use crate::{ColorPointer, EncodingQuality, PixelOrder, RGBAPointer};
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 #[repr(u8)]
@@ -53,6 +55,13 @@ impl UpdateEncoder {
         }
     }
 
+    /// Adjusts the quality of the encoded bitmaps, when supported by the codec in use.
+    pub(crate) fn set_quality(&mut self, quality: EncodingQuality) {
+        if let Some((rfx, _)) = self.remotefx.as_mut() {
+            rfx.set_quality(quality);
+        }
+    }
+
     fn encode_pdu(&mut self, pdu: impl Encode) -> Result<usize> {
         loop {
             let mut cursor = WriteCursor::new(self.buffer.as_mut_slice());
@@ -125,6 +134,15 @@ impl UpdateEncoder {
         Ok(UpdateFragmenter::new(UpdateCode::PositionPointer, &self.buffer[..len]))
     }
 
+    pub(crate) fn frame_marker(&mut self, frame_action: FrameAction, frame_id: u32) -> Result<UpdateFragmenter<'_>> {
+        let cmd = SurfaceCommand::FrameMarker(FrameMarkerPdu {
+            frame_action,
+            frame_id: Some(frame_id),
+        });
+        let len = self.encode_pdu(cmd)?;
+        Ok(UpdateFragmenter::new(UpdateCode::SurfaceCommands, &self.buffer[..len]))
+    }
+
     pub(crate) fn bitmap(&mut self, bitmap: BitmapUpdate) -> Result<UpdateFragmenter<'_>> {
         let update = self.update;
 
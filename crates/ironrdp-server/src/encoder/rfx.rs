@@ -1,15 +1,13 @@
-use ironrdp_core::{cast_length, other_err, EncodeResult};
-use ironrdp_graphics::color_conversion::to_64x64_ycbcr_tile;
-use ironrdp_graphics::rfx_encode_component;
-use ironrdp_pdu::codecs::rfx::{self, OperatingMode, RfxChannel, RfxChannelHeight, RfxChannelWidth};
+use ironrdp_core::{other_err, EncodeResult};
+use ironrdp_graphics::rfx_encoder::{self, RfxSourceImage};
+use ironrdp_pdu::codecs::rfx;
 use ironrdp_pdu::rdp::capability_sets::EntropyBits;
-use ironrdp_pdu::PduBufferParsing;
 
-use crate::BitmapUpdate;
+use crate::{BitmapUpdate, EncodingQuality};
 
 #[derive(Debug)]
 pub(crate) struct RfxEncoder {
-    entropy_algorithm: rfx::EntropyAlgorithm,
+    encoder: rfx_encoder::RfxEncoder,
 }
 
 impl RfxEncoder {
@@ -18,133 +16,45 @@ impl RfxEncoder {
             EntropyBits::Rlgr1 => rfx::EntropyAlgorithm::Rlgr1,
             EntropyBits::Rlgr3 => rfx::EntropyAlgorithm::Rlgr3,
         };
-        Self { entropy_algorithm }
+        Self {
+            encoder: rfx_encoder::RfxEncoder::new(entropy_algorithm),
+        }
     }
 
-    // FIXME: rewrite to use WriteCursor
-    pub(crate) fn encode(&mut self, bitmap: &BitmapUpdate) -> EncodeResult<Vec<u8>> {
-        let width = bitmap.width.get();
-        let height = bitmap.height.get();
-        let entropy_algorithm = self.entropy_algorithm;
-
-        // header messages
-        // FIXME: skip if unnecessary?
-        let sync = rfx::SyncPdu;
-        let context = rfx::ContextPdu {
-            flags: OperatingMode::IMAGE_MODE,
-            entropy_algorithm,
-        };
-        let context = rfx::Headers::Context(context);
-        let channels = rfx::ChannelsPdu(vec![RfxChannel {
-            width: RfxChannelWidth::new(cast_length!("width", width)?),
-            height: RfxChannelHeight::new(cast_length!("height", height)?),
-        }]);
-        let channels = rfx::Headers::Channels(channels);
-        let version = rfx::CodecVersionsPdu;
-        let version = rfx::Headers::CodecVersions(version);
-
-        // data messages
-        let frame_begin = rfx::FrameBeginPdu {
-            index: 0,
-            number_of_regions: 1,
+    pub(crate) fn set_quality(&mut self, quality: EncodingQuality) {
+        let offset = match quality {
+            EncodingQuality::High => 0,
+            EncodingQuality::Medium => 2,
+            EncodingQuality::Low => 4,
+            EncodingQuality::Lowest => 6,
         };
-        let width = bitmap.width.get();
-        let height = bitmap.height.get();
-        let rectangles = vec![rfx::RfxRectangle {
-            x: 0,
-            y: 0,
-            width,
-            height,
-        }];
-        let region = rfx::RegionPdu { rectangles };
-        let quant = rfx::Quant::default();
-
-        let bpp = usize::from(bitmap.format.bytes_per_pixel());
-        let width = usize::from(bitmap.width.get());
-        let height = usize::from(bitmap.height.get());
-
-        let tiles_x = (width + 63) / 64;
-        let tiles_y = (height + 63) / 64;
-        let ntiles = tiles_x * tiles_y;
-        let mut tiles = Vec::with_capacity(ntiles);
-        let mut data = vec![0u8; 64 * 64 * 3 * ntiles];
-        let mut rest = data.as_mut_slice();
-
-        for tile_y in 0..tiles_y {
-            for tile_x in 0..tiles_x {
-                let x = tile_x * 64;
-                let y = tile_y * 64;
-                let tile_width = std::cmp::min(width - x, 64);
-                let tile_height = std::cmp::min(height - y, 64);
-
-                let input = &bitmap.data[y * bitmap.stride + x * bpp..];
 
-                let y = &mut [0i16; 4096];
-                let cb = &mut [0i16; 4096];
-                let cr = &mut [0i16; 4096];
-                to_64x64_ycbcr_tile(input, tile_width, tile_height, bitmap.stride, bitmap.format, y, cb, cr);
-
-                let (y_data, new_rest) = rest.split_at_mut(4096);
-                let (cb_data, new_rest) = new_rest.split_at_mut(4096);
-                let (cr_data, new_rest) = new_rest.split_at_mut(4096);
-                rest = new_rest;
-                let len = rfx_encode_component(y, y_data, &quant, entropy_algorithm)
-                    .map_err(|e| other_err!("rfxenc", source: e))?;
-                let y_data = &y_data[..len];
-                let len = rfx_encode_component(cb, cb_data, &quant, entropy_algorithm)
-                    .map_err(|e| other_err!("rfxenc", source: e))?;
-                let cb_data = &cb_data[..len];
-                let len = rfx_encode_component(cr, cr_data, &quant, entropy_algorithm)
-                    .map_err(|e| other_err!("rfxenc", source: e))?;
-                let cr_data = &cr_data[..len];
-
-                let tile = rfx::Tile {
-                    y_quant_index: 0,
-                    cb_quant_index: 0,
-                    cr_quant_index: 0,
-                    x: u16::try_from(tile_x).unwrap(),
-                    y: u16::try_from(tile_y).unwrap(),
-                    y_data,
-                    cb_data,
-                    cr_data,
-                };
-                tiles.push(tile);
-            }
-        }
+        // Quantization values range from 6 to 15, higher values giving a higher compression rate.
+        let quantize = |value: u8| value.saturating_add(offset).min(15);
+        let default = rfx::Quant::default();
+        self.encoder.set_quant(rfx::Quant {
+            ll3: quantize(default.ll3),
+            lh3: quantize(default.lh3),
+            hl3: quantize(default.hl3),
+            hh3: quantize(default.hh3),
+            lh2: quantize(default.lh2),
+            hl2: quantize(default.hl2),
+            hh2: quantize(default.hh2),
+            lh1: quantize(default.lh1),
+            hl1: quantize(default.hl1),
+            hh1: quantize(default.hh1),
+        });
+    }
 
-        let quants = vec![quant];
-        let tile_set = rfx::TileSetPdu {
-            entropy_algorithm,
-            quants,
-            tiles,
+    pub(crate) fn encode(&mut self, bitmap: &BitmapUpdate) -> EncodeResult<Vec<u8>> {
+        let image = RfxSourceImage {
+            data: &bitmap.data,
+            width: bitmap.width.get(),
+            height: bitmap.height.get(),
+            stride: bitmap.stride,
+            format: bitmap.format,
         };
-        let frame_end = rfx::FrameEndPdu;
-
-        macro_rules! encode {
-            ($($element:expr),+) => {
-                {
-                    let len: usize = 0 $( + $element.buffer_length() )+;
-                    let mut output = vec![0; len];
-                    let mut buffer = output.as_mut_slice();
-
-                    $(
-                        $element.to_buffer_consume(&mut buffer).map_err(|e| other_err!("rfxenc", source: e))?;
-                    )+
-
-                    Ok(output)
-                }
-            };
-        }
 
-        encode!(
-            sync,
-            context,
-            channels,
-            version,
-            frame_begin,
-            region,
-            tile_set,
-            frame_end
-        )
+        self.encoder.encode(&image).map_err(|e| other_err!("rfxenc", source: e))
     }
 }
@@ -0,0 +1,10 @@
+use std::net::SocketAddr;
+
+pub use ironrdp_acceptor::AcceptorHooks;
+
+/// Builds the [`AcceptorHooks`] consulted while accepting a new connection
+///
+/// The peer address is provided so that connections can be filtered by IP.
+pub trait AcceptorHooksFactory: Send {
+    fn build_hooks(&self, peer_addr: SocketAddr) -> Box<dyn AcceptorHooks>;
+}
@@ -0,0 +1,371 @@
+use ironrdp_ainput as ainput;
+use ironrdp_pdu::input::fast_path::{self, FastPathInputEvent, SynchronizeFlags};
+use ironrdp_pdu::input::mouse::PointerFlags;
+use ironrdp_pdu::input::mouse_rel::PointerRelFlags;
+use ironrdp_pdu::input::mouse_x::PointerXFlags;
+use ironrdp_pdu::input::{scan_code, unicode, InputEvent, MousePdu, MouseRelPdu, MouseXPdu};
+
+use crate::RdpServerInputHandler;
+
+/// Physical key, as reported by the client
+///
+/// Scancodes are independent from the keyboard layout of the client.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Scancode {
+    pub code: u8,
+    /// Whether the scancode is prefixed by `0xE0`
+    pub extended: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PointerButton {
+    Left,
+    Right,
+    Middle,
+    X1,
+    X2,
+}
+
+/// State of the lock keys on the client side
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct LockKeys {
+    pub scroll_lock: bool,
+    pub num_lock: bool,
+    pub caps_lock: bool,
+    pub kana_lock: bool,
+}
+
+/// Input event normalized from the various input PDUs a client may send
+///
+/// Slow-path, fast-path and advanced input (FreeRDP ainput channel) events are all
+/// translated into this single representation.
+///
+/// Keys are reported as scancodes, not as keysyms: the client does not send its keyboard layout along with the
+/// key events, so the sink is responsible for mapping them. Touch and pen events (MS-RDPEI) are not received by
+/// the server, and thus not reported either.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NormalizedInputEvent {
+    Key {
+        scancode: Scancode,
+        pressed: bool,
+    },
+    /// Text typed using an input method not producing scancodes
+    ///
+    /// UTF-16 surrogate pairs sent by the client are recombined.
+    Text(char),
+    /// Lock keys synchronization, typically sent when the client window gains focus
+    LockKeys(LockKeys),
+    /// Pointer moved to an absolute position, in desktop coordinates
+    PointerMove {
+        x: u16,
+        y: u16,
+    },
+    /// Pointer moved relatively to its previous position
+    PointerMoveRelative {
+        dx: i32,
+        dy: i32,
+    },
+    Button {
+        button: PointerButton,
+        pressed: bool,
+    },
+    /// Wheel rotation, in units of 1/120 of a notch (`WHEEL_DELTA`)
+    ///
+    /// A positive `dy` scrolls up (away from the user), and a positive `dx` scrolls to the right.
+    Wheel {
+        dx: i32,
+        dy: i32,
+    },
+}
+
+/// High-level input handler for an RDP server
+///
+/// Unlike [`RdpServerInputHandler`], the sink does not need to know about the PDUs the events
+/// originate from: each PDU is decoded into zero, one or several [`NormalizedInputEvent`]s.
+///
+/// # Example
+///
+/// ```
+/// use ironrdp_server::{InputSink, NormalizedInputEvent};
+///
+/// pub struct Sink;
+///
+/// impl InputSink for Sink {
+///     fn input(&mut self, event: NormalizedInputEvent) {
+///         match event {
+///             NormalizedInputEvent::Text(c) => println!("Typed {c}"),
+///             NormalizedInputEvent::PointerMove { x, y } => println!("Moved pointer to {x} {y}"),
+///             other => println!("unhandled event: {other:?}"),
+///         }
+///     }
+/// }
+/// ```
+pub trait InputSink: Send {
+    fn input(&mut self, event: NormalizedInputEvent);
+}
+
+/// Destination of the input events received by the server
+pub(crate) enum InputDispatcher {
+    Handler(Box<dyn RdpServerInputHandler>),
+    Sink {
+        sink: Box<dyn InputSink>,
+        decoder: InputDecoder,
+    },
+}
+
+impl InputDispatcher {
+    pub(crate) fn fastpath(&mut self, event: FastPathInputEvent) {
+        match self {
+            Self::Handler(handler) => match event {
+                FastPathInputEvent::KeyboardEvent(flags, key) => handler.keyboard((key, flags).into()),
+                FastPathInputEvent::UnicodeKeyboardEvent(flags, key) => handler.keyboard((key, flags).into()),
+                FastPathInputEvent::SyncEvent(flags) => handler.keyboard(flags.into()),
+                FastPathInputEvent::MouseEvent(mouse) => handler.mouse(mouse.into()),
+                FastPathInputEvent::MouseEventEx(mouse) => handler.mouse(mouse.into()),
+                FastPathInputEvent::MouseEventRel(mouse) => handler.mouse(mouse.into()),
+                FastPathInputEvent::QoeEvent(quality) => warn!("Received QoE: {}", quality),
+            },
+            Self::Sink { sink, decoder } => decoder.fastpath(event, sink.as_mut()),
+        }
+    }
+
+    pub(crate) fn slowpath(&mut self, event: InputEvent) {
+        match self {
+            Self::Handler(handler) => match event {
+                InputEvent::ScanCode(key) => handler.keyboard((key.key_code, key.flags).into()),
+                InputEvent::Unicode(key) => handler.keyboard((key.unicode_code, key.flags).into()),
+                InputEvent::Sync(sync) => handler.keyboard(sync.flags.into()),
+                InputEvent::Mouse(mouse) => handler.mouse(mouse.into()),
+                InputEvent::MouseX(mouse) => handler.mouse(mouse.into()),
+                InputEvent::MouseRel(mouse) => handler.mouse(mouse.into()),
+                InputEvent::Unused(_) => {}
+            },
+            Self::Sink { sink, decoder } => decoder.slowpath(event, sink.as_mut()),
+        }
+    }
+
+    pub(crate) fn ainput(&mut self, mouse: ainput::MousePdu) {
+        match self {
+            Self::Handler(handler) => handler.mouse(mouse.into()),
+            Self::Sink { sink, decoder } => decoder.ainput(&mouse, sink.as_mut()),
+        }
+    }
+}
+
+/// Server-side decoder translating input PDUs into [`NormalizedInputEvent`]s
+///
+/// The decoder is stateful: a UTF-16 surrogate pair is sent by the client as two Unicode keyboard events, and is
+/// reported as a single [`NormalizedInputEvent::Text`] once the low surrogate is received.
+#[derive(Debug, Default)]
+pub struct InputDecoder {
+    high_surrogate: Option<u16>,
+}
+
+impl InputDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn fastpath(&mut self, event: FastPathInputEvent, sink: &mut dyn InputSink) {
+        match event {
+            FastPathInputEvent::KeyboardEvent(flags, code) => sink.input(NormalizedInputEvent::Key {
+                scancode: Scancode {
+                    code,
+                    extended: flags.contains(fast_path::KeyboardFlags::EXTENDED),
+                },
+                pressed: !flags.contains(fast_path::KeyboardFlags::RELEASE),
+            }),
+            FastPathInputEvent::UnicodeKeyboardEvent(flags, code_unit) => {
+                self.unicode(code_unit, flags.contains(fast_path::KeyboardFlags::RELEASE), sink);
+            }
+            FastPathInputEvent::SyncEvent(flags) => sink.input(NormalizedInputEvent::LockKeys(lock_keys(flags))),
+            FastPathInputEvent::MouseEvent(mouse) => Self::mouse(&mouse, sink),
+            FastPathInputEvent::MouseEventEx(mouse) => Self::mouse_x(&mouse, sink),
+            FastPathInputEvent::MouseEventRel(mouse) => Self::mouse_rel(&mouse, sink),
+            FastPathInputEvent::QoeEvent(quality) => warn!("Received QoE: {}", quality),
+        }
+    }
+
+    pub fn slowpath(&mut self, event: InputEvent, sink: &mut dyn InputSink) {
+        match event {
+            InputEvent::ScanCode(key) => {
+                // Only the low byte is meaningful, the 0xE0 prefix being carried by the flags.
+                #[allow(clippy::cast_possible_truncation)] // we are actually truncating the value
+                let code = key.key_code as u8;
+
+                sink.input(NormalizedInputEvent::Key {
+                    scancode: Scancode {
+                        code,
+                        extended: key.flags.contains(scan_code::KeyboardFlags::EXTENDED),
+                    },
+                    pressed: !key.flags.contains(scan_code::KeyboardFlags::RELEASE),
+                });
+            }
+            InputEvent::Unicode(key) => {
+                self.unicode(
+                    key.unicode_code,
+                    key.flags.contains(unicode::KeyboardFlags::RELEASE),
+                    sink,
+                );
+            }
+            InputEvent::Sync(sync) => {
+                #[allow(clippy::cast_possible_truncation)] // only the four lowest bits are defined
+                let flags = SynchronizeFlags::from_bits_truncate(sync.flags.bits() as u8);
+
+                sink.input(NormalizedInputEvent::LockKeys(lock_keys(flags)));
+            }
+            InputEvent::Mouse(mouse) => Self::mouse(&mouse, sink),
+            InputEvent::MouseX(mouse) => Self::mouse_x(&mouse, sink),
+            InputEvent::MouseRel(mouse) => Self::mouse_rel(&mouse, sink),
+            InputEvent::Unused(_) => {}
+        }
+    }
+
+    fn unicode(&mut self, code_unit: u16, released: bool, sink: &mut dyn InputSink) {
+        // Text is emitted when the key is pressed, releases carry no additional information.
+        if released {
+            return;
+        }
+
+        let code_units = match self.high_surrogate.take() {
+            Some(high) => vec![high, code_unit],
+            None if (0xD800..0xDC00).contains(&code_unit) => {
+                self.high_surrogate = Some(code_unit);
+                return;
+            }
+            None => vec![code_unit],
+        };
+
+        for c in char::decode_utf16(code_units) {
+            match c {
+                Ok(c) => sink.input(NormalizedInputEvent::Text(c)),
+                Err(error) => warn!(%error, "Invalid unicode keyboard event"),
+            }
+        }
+    }
+
+    fn mouse(mouse: &MousePdu, sink: &mut dyn InputSink) {
+        let flags = mouse.flags;
+
+        if flags.contains(PointerFlags::VERTICAL_WHEEL) {
+            sink.input(NormalizedInputEvent::Wheel {
+                dx: 0,
+                dy: i32::from(mouse.number_of_wheel_rotation_units),
+            });
+            return;
+        }
+
+        if flags.contains(PointerFlags::HORIZONTAL_WHEEL) {
+            sink.input(NormalizedInputEvent::Wheel {
+                dx: i32::from(mouse.number_of_wheel_rotation_units),
+                dy: 0,
+            });
+            return;
+        }
+
+        if flags.contains(PointerFlags::MOVE) {
+            sink.input(NormalizedInputEvent::PointerMove {
+                x: mouse.x_position,
+                y: mouse.y_position,
+            });
+        }
+
+        let pressed = flags.contains(PointerFlags::DOWN);
+        for (flag, button) in [
+            (PointerFlags::LEFT_BUTTON, PointerButton::Left),
+            (PointerFlags::RIGHT_BUTTON, PointerButton::Right),
+            (PointerFlags::MIDDLE_BUTTON_OR_WHEEL, PointerButton::Middle),
+        ] {
+            if flags.contains(flag) {
+                sink.input(NormalizedInputEvent::Button { button, pressed });
+            }
+        }
+    }
+
+    fn mouse_x(mouse: &MouseXPdu, sink: &mut dyn InputSink) {
+        sink.input(NormalizedInputEvent::PointerMove {
+            x: mouse.x_position,
+            y: mouse.y_position,
+        });
+
+        let pressed = mouse.flags.contains(PointerXFlags::DOWN);
+        for (flag, button) in [
+            (PointerXFlags::BUTTON1, PointerButton::X1),
+            (PointerXFlags::BUTTON2, PointerButton::X2),
+        ] {
+            if mouse.flags.contains(flag) {
+                sink.input(NormalizedInputEvent::Button { button, pressed });
+            }
+        }
+    }
+
+    fn mouse_rel(mouse: &MouseRelPdu, sink: &mut dyn InputSink) {
+        if mouse.flags.contains(PointerRelFlags::MOVE) {
+            sink.input(NormalizedInputEvent::PointerMoveRelative {
+                dx: i32::from(mouse.x_delta),
+                dy: i32::from(mouse.y_delta),
+            });
+        }
+
+        let pressed = mouse.flags.contains(PointerRelFlags::DOWN);
+        for (flag, button) in [
+            (PointerRelFlags::BUTTON1, PointerButton::Left),
+            (PointerRelFlags::BUTTON2, PointerButton::Right),
+            (PointerRelFlags::BUTTON3, PointerButton::Middle),
+            (PointerRelFlags::XBUTTON1, PointerButton::X1),
+            (PointerRelFlags::XBUTTON2, PointerButton::X2),
+        ] {
+            if mouse.flags.contains(flag) {
+                sink.input(NormalizedInputEvent::Button { button, pressed });
+            }
+        }
+    }
+
+    pub fn ainput(&mut self, mouse: &ainput::MousePdu, sink: &mut dyn InputSink) {
+        use ainput::MouseEventFlags;
+
+        let flags = mouse.flags;
+
+        if flags.contains(MouseEventFlags::WHEEL) {
+            sink.input(NormalizedInputEvent::Wheel {
+                dx: mouse.x,
+                dy: mouse.y,
+            });
+            return;
+        }
+
+        if flags.contains(MouseEventFlags::REL) {
+            sink.input(NormalizedInputEvent::PointerMoveRelative {
+                dx: mouse.x,
+                dy: mouse.y,
+            });
+        } else if flags.contains(MouseEventFlags::MOVE) {
+            sink.input(NormalizedInputEvent::PointerMove {
+                x: u16::try_from(mouse.x).unwrap_or(0),
+                y: u16::try_from(mouse.y).unwrap_or(0),
+            });
+        }
+
+        let pressed = flags.contains(MouseEventFlags::DOWN);
+        for (flag, button) in [
+            (MouseEventFlags::BUTTON1, PointerButton::Left),
+            (MouseEventFlags::BUTTON2, PointerButton::Right),
+            (MouseEventFlags::BUTTON3, PointerButton::Middle),
+            (MouseEventFlags::XBUTTON1, PointerButton::X1),
+            (MouseEventFlags::XBUTTON2, PointerButton::X2),
+        ] {
+            if flags.contains(flag) {
+                sink.input(NormalizedInputEvent::Button { button, pressed });
+            }
+        }
+    }
+}
+
+fn lock_keys(flags: SynchronizeFlags) -> LockKeys {
+    LockKeys {
+        scroll_lock: flags.contains(SynchronizeFlags::SCROLL_LOCK),
+        num_lock: flags.contains(SynchronizeFlags::NUM_LOCK),
+        caps_lock: flags.contains(SynchronizeFlags::CAPS_LOCK),
+        kana_lock: flags.contains(SynchronizeFlags::KANA_LOCK),
+    }
+}
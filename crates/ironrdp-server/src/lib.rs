@@ -6,17 +6,34 @@ pub use tokio_rustls;
 #[macro_use]
 extern crate tracing;
 
+mod audit;
+mod autodetect;
 mod builder;
 mod capabilities;
+mod capture;
 mod clipboard;
 mod display;
 mod encoder;
 mod handler;
+mod hooks;
+mod input;
+mod limits;
+mod scheduler;
+mod security;
 mod server;
+mod shutdown;
 mod sound;
 
+pub use audit::*;
+pub use autodetect::*;
+pub use capture::*;
 pub use clipboard::*;
 pub use display::*;
 pub use handler::*;
+pub use hooks::*;
+pub use input::*;
+pub use limits::*;
+pub use scheduler::*;
 pub use server::*;
+pub use shutdown::*;
 pub use sound::*;
@@ -0,0 +1,200 @@
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::net::{IpAddr, SocketAddr};
+use std::time::{Duration, Instant};
+
+use anyhow::{Context as _, Result};
+pub use ironrdp_core::DecodeLimits;
+use ironrdp_core::DecodeOptions;
+use tokio::time;
+
+/// Limits protecting the server against connection floods
+#[derive(Debug, Clone)]
+pub struct ConnectionLimits {
+    /// Maximum number of sessions served concurrently
+    ///
+    /// The server serves one session at a time: a limit of one rejects the connections received while a session is
+    /// running, instead of queuing them.
+    pub max_sessions: Option<usize>,
+    /// Maximum number of connections waiting for the running session to end
+    ///
+    /// Queued connections are dropped once they waited longer than `handshake_timeout`.
+    pub max_queued_connections: usize,
+    /// Maximum number of connections accepted from a single IP address during `rate_limit_window`
+    pub max_connections_per_ip: Option<usize>,
+    pub rate_limit_window: Duration,
+    /// Maximum duration of the connection sequence, from the TCP connection to the client being accepted
+    pub handshake_timeout: Option<Duration>,
+}
+
+impl Default for ConnectionLimits {
+    fn default() -> Self {
+        Self {
+            max_sessions: None,
+            max_queued_connections: 4,
+            max_connections_per_ip: None,
+            rate_limit_window: Duration::from_secs(60),
+            handshake_timeout: Some(Duration::from_secs(30)),
+        }
+    }
+}
+
+/// Custom admission policy, consulted for each incoming connection
+///
+/// The policy is only consulted for connections satisfying the [`ConnectionLimits`].
+pub trait ConnectionPolicy: Send {
+    /// Returns whether the connection should be served, `sessions` being the number of sessions already held by the server
+    ///
+    /// Rejected connections are closed immediately.
+    fn admit(&mut self, peer_addr: SocketAddr, sessions: usize) -> bool;
+}
+
+/// Applies the [`ConnectionLimits`] and the [`ConnectionPolicy`] to the incoming connections
+pub struct ConnectionLimiter {
+    limits: ConnectionLimits,
+    policy: Option<Box<dyn ConnectionPolicy>>,
+    history: HashMap<IpAddr, VecDeque<Instant>>,
+}
+
+impl ConnectionLimiter {
+    pub fn new(limits: ConnectionLimits, policy: Option<Box<dyn ConnectionPolicy>>) -> Self {
+        Self {
+            limits,
+            policy,
+            history: HashMap::new(),
+        }
+    }
+
+    pub fn into_policy(self) -> Option<Box<dyn ConnectionPolicy>> {
+        self.policy
+    }
+
+    /// Returns whether the connection received at `now` should be served, `queued` being the number of connections
+    /// waiting for the running session to end, if any
+    pub fn admit(&mut self, peer_addr: SocketAddr, sessions: usize, queued: usize, now: Instant) -> bool {
+        if self.limits.max_sessions.is_some_and(|max| sessions >= max) {
+            warn!(?peer_addr, sessions, "Connection rejected: too many sessions");
+            return false;
+        }
+
+        if sessions > 0 && queued >= self.limits.max_queued_connections {
+            warn!(?peer_addr, queued, "Connection rejected: too many queued connections");
+            return false;
+        }
+
+        if let Some(max_connections) = self.limits.max_connections_per_ip {
+            let window = self.limits.rate_limit_window;
+
+            self.history.retain(|_, connections| {
+                while connections
+                    .front()
+                    .is_some_and(|at| now.saturating_duration_since(*at) > window)
+                {
+                    connections.pop_front();
+                }
+                !connections.is_empty()
+            });
+
+            let connections = self.history.entry(peer_addr.ip()).or_default();
+            if connections.len() >= max_connections {
+                warn!(?peer_addr, "Connection rejected: rate limit exceeded");
+                return false;
+            }
+            connections.push_back(now);
+        }
+
+        match self.policy.as_mut() {
+            Some(policy) if !policy.admit(peer_addr, sessions) => {
+                warn!(?peer_addr, "Connection rejected by policy");
+                false
+            }
+            _ => true,
+        }
+    }
+
+    /// Returns whether a connection queued at `queued_at` waited longer than the handshake timeout at `now`
+    pub fn is_expired(&self, queued_at: Instant, now: Instant) -> bool {
+        self.limits
+            .handshake_timeout
+            .is_some_and(|timeout| now.saturating_duration_since(queued_at) > timeout)
+    }
+}
+
+/// Connections waiting for the running session to end, bounded by [`ConnectionLimits::max_queued_connections`]
+pub struct ConnectionQueue<T> {
+    connections: VecDeque<(T, SocketAddr, Instant)>,
+}
+
+impl<T> Default for ConnectionQueue<T> {
+    fn default() -> Self {
+        Self {
+            connections: VecDeque::new(),
+        }
+    }
+}
+
+impl<T> ConnectionQueue<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.connections.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.connections.is_empty()
+    }
+
+    /// Queues a connection received at `now` while a session is running, returning whether it was admitted
+    ///
+    /// The connections which waited longer than the handshake timeout are dropped first.
+    pub fn push(
+        &mut self,
+        limiter: &mut ConnectionLimiter,
+        connection: T,
+        peer_addr: SocketAddr,
+        now: Instant,
+    ) -> bool {
+        self.connections.retain(|(_, queued_peer, queued_at)| {
+            let expired = limiter.is_expired(*queued_at, now);
+            if expired {
+                warn!(peer = ?queued_peer, "Queued connection dropped: handshake timed out");
+            }
+            !expired
+        });
+
+        let admitted = limiter.admit(peer_addr, 1, self.connections.len(), now);
+        if admitted {
+            self.connections.push_back((connection, peer_addr, now));
+        }
+
+        admitted
+    }
+
+    /// Returns the oldest connection which did not wait longer than the handshake timeout at `now`
+    pub fn pop(&mut self, limiter: &ConnectionLimiter, now: Instant) -> Option<(T, SocketAddr)> {
+        while let Some((connection, peer_addr, queued_at)) = self.connections.pop_front() {
+            if limiter.is_expired(queued_at, now) {
+                warn!(peer = ?peer_addr, "Queued connection dropped: handshake timed out");
+                continue;
+            }
+
+            return Some((connection, peer_addr));
+        }
+
+        None
+    }
+}
+
+/// Runs `f`, decoding the PDUs received from the client within `limits`
+pub(crate) fn with_decode_limits<R>(limits: DecodeLimits, f: impl FnOnce() -> R) -> R {
+    ironrdp_core::with_decode_options(DecodeOptions::default().with_limits(limits), f)
+}
+
+pub(crate) async fn with_handshake_timeout<F: Future>(timeout: Option<Duration>, future: F) -> Result<F::Output> {
+    match timeout {
+        Some(timeout) => time::timeout(timeout, future).await.context("handshake timed out"),
+        None => Ok(future.await),
+    }
+}
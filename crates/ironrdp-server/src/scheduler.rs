@@ -0,0 +1,212 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use crate::BitmapUpdate;
+
+/// Frame ID sent by clients to stop acknowledging frames, see [MS-RDPBCGR] 2.2.14.3.1
+const SUSPEND_FRAME_ACKNOWLEDGEMENT: u32 = 0xFFFF_FFFF;
+
+/// Per-client pacing of the display updates
+///
+/// Bitmap updates are grouped into frames. A new frame is only sent once the client acknowledged enough of the
+/// previous ones, and the link had the time to carry the previous frame given the measured bandwidth.
+/// Meanwhile, the updates are queued, and those covered by a newer update are skipped.
+///
+/// Frame acknowledgements require the client to support the frame markers and the Frame Acknowledge capability set,
+/// and the bandwidth is measured by the network auto-detection (see [`AutoDetectConfig`](crate::AutoDetectConfig)).
+#[derive(Debug, Clone)]
+pub struct FrameSchedulerConfig {
+    /// Maximum number of frames sent to the client without being acknowledged
+    pub max_unacknowledged_frames: u32,
+    /// Maximum number of frames sent per second
+    pub max_frame_rate: u32,
+    /// Duration after which an unacknowledged frame is considered lost
+    pub acknowledgement_timeout: Duration,
+}
+
+impl Default for FrameSchedulerConfig {
+    fn default() -> Self {
+        Self {
+            max_unacknowledged_frames: 2,
+            max_frame_rate: 30,
+            acknowledgement_timeout: Duration::from_secs(1),
+        }
+    }
+}
+
+/// Bitmap updates to send to the client as a single frame
+#[derive(Debug)]
+pub struct ScheduledFrame {
+    /// Frame ID, when the frame must be delimited by frame markers
+    pub id: Option<u32>,
+    pub updates: Vec<BitmapUpdate>,
+}
+
+#[derive(Debug)]
+struct SentFrame {
+    id: u32,
+    sent_at: Instant,
+}
+
+/// Paces the display updates sent to a client, see [`FrameSchedulerConfig`]
+#[derive(Debug)]
+pub struct FrameScheduler {
+    config: FrameSchedulerConfig,
+    frame_markers: bool,
+    acknowledgements: bool,
+    next_frame_id: u32,
+    unacknowledged: VecDeque<SentFrame>,
+    pending: Vec<BitmapUpdate>,
+    bandwidth: Option<u32>,
+    /// Earliest time at which the next frame may be sent
+    next_frame_at: Option<Instant>,
+}
+
+impl FrameScheduler {
+    /// `max_unacknowledged_frames` is the value advertised by the client, if it acknowledges the frames
+    pub fn new(mut config: FrameSchedulerConfig, frame_markers: bool, max_unacknowledged_frames: Option<u32>) -> Self {
+        let acknowledgements = frame_markers && max_unacknowledged_frames.is_some();
+
+        if let Some(max) = max_unacknowledged_frames.filter(|max| *max > 0) {
+            config.max_unacknowledged_frames = config.max_unacknowledged_frames.min(max);
+        }
+        config.max_unacknowledged_frames = config.max_unacknowledged_frames.max(1);
+
+        debug!(?config, frame_markers, acknowledgements, "Frame scheduler");
+
+        Self {
+            config,
+            frame_markers,
+            acknowledgements,
+            next_frame_id: 0,
+            unacknowledged: VecDeque::new(),
+            pending: Vec::new(),
+            bandwidth: None,
+            next_frame_at: None,
+        }
+    }
+
+    /// Queues an update, skipping the queued updates it covers
+    pub fn push(&mut self, update: BitmapUpdate) {
+        self.pending.retain(|pending| !covers(&update, pending));
+        self.pending.push(update);
+    }
+
+    /// Drops the queued updates, e.g.: when the desktop is resized
+    pub fn clear(&mut self) {
+        self.pending.clear();
+        self.unacknowledged.clear();
+        self.next_frame_at = None;
+    }
+
+    /// Updates the bandwidth measured for the client, in kilobits per second
+    pub fn set_bandwidth(&mut self, bandwidth: Option<u32>) {
+        self.bandwidth = bandwidth;
+    }
+
+    /// Handles a Frame Acknowledge PDU from the client
+    pub fn acknowledge(&mut self, frame_id: u32) {
+        if frame_id == SUSPEND_FRAME_ACKNOWLEDGEMENT {
+            debug!("Client suspended the frame acknowledgements");
+            self.acknowledgements = false;
+            self.unacknowledged.clear();
+            return;
+        }
+
+        // Acknowledging a frame implicitly acknowledges all the previous ones.
+        if let Some(position) = self.unacknowledged.iter().position(|frame| frame.id == frame_id) {
+            if let Some(frame) = self.unacknowledged.get(position) {
+                trace!(frame_id, latency = ?frame.sent_at.elapsed(), "Frame acknowledged");
+            }
+            self.unacknowledged.drain(..=position);
+        }
+    }
+
+    /// Returns the time at which [`FrameScheduler::poll_frame`] should be called again, if updates are queued
+    pub fn next_deadline(&self) -> Option<Instant> {
+        if self.pending.is_empty() {
+            return None;
+        }
+
+        let acknowledgement_deadline = self.is_waiting_acknowledgement().then(|| {
+            self.unacknowledged
+                .front()
+                .map(|frame| frame.sent_at + self.config.acknowledgement_timeout)
+        });
+
+        match (acknowledgement_deadline.flatten(), self.next_frame_at) {
+            (Some(a), Some(b)) => Some(a.max(b)),
+            (a, b) => a.or(b).or_else(|| Some(Instant::now())),
+        }
+    }
+
+    /// Returns the next frame to send, if the client is ready to receive it
+    pub fn poll_frame(&mut self, now: Instant) -> Option<ScheduledFrame> {
+        if self.pending.is_empty() {
+            return None;
+        }
+
+        let timeout = self.config.acknowledgement_timeout;
+        while self
+            .unacknowledged
+            .front()
+            .is_some_and(|frame| now.saturating_duration_since(frame.sent_at) >= timeout)
+        {
+            if let Some(frame) = self.unacknowledged.pop_front() {
+                debug!(frame_id = frame.id, "Frame acknowledgement timed out");
+            }
+        }
+
+        if self.is_waiting_acknowledgement() || self.next_frame_at.is_some_and(|at| now < at) {
+            return None;
+        }
+
+        let id = self.frame_markers.then(|| {
+            let id = self.next_frame_id;
+            // The suspend value is reserved.
+            self.next_frame_id = self.next_frame_id.wrapping_add(1) % SUSPEND_FRAME_ACKNOWLEDGEMENT;
+            id
+        });
+
+        Some(ScheduledFrame {
+            id,
+            updates: std::mem::take(&mut self.pending),
+        })
+    }
+
+    /// Records a sent frame of `size` bytes
+    pub fn frame_sent(&mut self, frame_id: Option<u32>, size: usize, now: Instant) {
+        if let (Some(id), true) = (frame_id, self.acknowledgements) {
+            self.unacknowledged.push_back(SentFrame { id, sent_at: now });
+        }
+
+        let frame_interval = Duration::from_secs(1) / self.config.max_frame_rate.max(1);
+
+        // Time needed by the link to carry the frame: bits divided by kilobits per second gives milliseconds.
+        let transmission_time = self
+            .bandwidth
+            .filter(|bandwidth| *bandwidth > 0)
+            .map(|bandwidth| {
+                let size = u64::try_from(size).unwrap_or(u64::MAX);
+                Duration::from_micros(size.saturating_mul(8_000) / u64::from(bandwidth))
+            })
+            .unwrap_or_default();
+
+        self.next_frame_at = Some(now + frame_interval.max(transmission_time));
+    }
+
+    fn is_waiting_acknowledgement(&self) -> bool {
+        self.acknowledgements
+            && self.unacknowledged.len() >= usize::try_from(self.config.max_unacknowledged_frames).unwrap_or(usize::MAX)
+    }
+}
+
+/// Returns whether `update` fully covers `other`
+fn covers(update: &BitmapUpdate, other: &BitmapUpdate) -> bool {
+    let right = u32::from(update.left) + u32::from(update.width.get());
+    let bottom = u32::from(update.top) + u32::from(update.height.get());
+    let other_right = u32::from(other.left) + u32::from(other.width.get());
+    let other_bottom = u32::from(other.top) + u32::from(other.height.get());
+
+    update.left <= other.left && update.top <= other.top && right >= other_right && bottom >= other_bottom
+}
@@ -0,0 +1,149 @@
+use std::collections::VecDeque;
+use std::io;
+
+use anyhow::Result;
+use ironrdp_acceptor::{Acceptor, AcceptorResult, SecurityLayer};
+use ironrdp_async::bytes::BytesMut;
+use ironrdp_async::{Framed, FramedRead, FramedWrite};
+use ironrdp_connector::Sequence as _;
+use ironrdp_core::WriteBuf;
+use ironrdp_pdu::Action;
+
+/// Frames exchanged with an accepted client
+///
+/// When Standard RDP Security is used, the frames are decrypted once read and encrypted before being written.
+/// Otherwise, they go through unchanged.
+pub(crate) struct SecureFramed<S> {
+    framed: Framed<S>,
+    security_layer: Option<SecurityLayer>,
+    /// Frames already read and decrypted, returned by the next reads
+    pending: VecDeque<(Action, BytesMut)>,
+}
+
+impl<S> SecureFramed<S> {
+    pub(crate) fn new(framed: Framed<S>, security_layer: Option<SecurityLayer>) -> Self {
+        Self {
+            framed,
+            security_layer,
+            pending: VecDeque::new(),
+        }
+    }
+}
+
+impl<S> SecureFramed<S>
+where
+    S: FramedRead,
+{
+    /// Reads a standard RDP PDU frame, decrypted if need be.
+    ///
+    /// # Cancel safety
+    ///
+    /// This method is cancel safe, as [`Framed::read_pdu`].
+    pub(crate) async fn read_pdu(&mut self) -> io::Result<(Action, BytesMut)> {
+        if let Some(frame) = self.pending.pop_front() {
+            return Ok(frame);
+        }
+
+        let (action, frame) = self.framed.read_pdu().await?;
+
+        let Some(layer) = self.security_layer.as_mut() else {
+            return Ok((action, frame));
+        };
+
+        let frame = layer.decrypt_frame(&frame).map_err(invalid_data)?;
+
+        Ok((action, BytesMut::from(frame.as_slice())))
+    }
+}
+
+impl<S> SecureFramed<S>
+where
+    S: FramedWrite,
+{
+    /// Writes one or more frames, encrypted if need be.
+    pub(crate) async fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
+        self.write_frames(buf, false).await
+    }
+
+    /// Writes slow-path frames whose user data already starts with a basic security header, such as the
+    /// auto-detect PDUs.
+    pub(crate) async fn write_all_with_security_header(&mut self, buf: &[u8]) -> io::Result<()> {
+        self.write_frames(buf, true).await
+    }
+
+    async fn write_frames(&mut self, buf: &[u8], has_security_header: bool) -> io::Result<()> {
+        let Some(layer) = self.security_layer.as_mut() else {
+            return self.framed.write_all(buf).await;
+        };
+
+        // Each frame is encrypted separately.
+        let mut frames = buf;
+        while !frames.is_empty() {
+            let length = ironrdp_pdu::find_size(frames)
+                .map_err(invalid_data)?
+                .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "incomplete frame"))?
+                .length;
+            let (frame, rest) = frames.split_at(length);
+            frames = rest;
+
+            let frame = layer.encrypt_frame(frame, has_security_header).map_err(invalid_data)?;
+            self.framed.write_all(&frame).await?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<S> SecureFramed<S>
+where
+    S: FramedRead + FramedWrite,
+{
+    /// Runs a deactivation-reactivation sequence
+    ///
+    /// The acceptor is driven with the frames read by [`Self::read_pdu`]: the keys of the security layer being updated
+    /// with each frame, all the frames must be decrypted in order. The frames unrelated to the sequence are returned by
+    /// the next reads.
+    pub(crate) async fn reactivate(&mut self, acceptor: &mut Acceptor) -> Result<AcceptorResult> {
+        let mut buf = WriteBuf::new();
+        let mut unmatched = VecDeque::new();
+
+        let result = loop {
+            if let Some(result) = acceptor.get_result() {
+                break result;
+            }
+
+            buf.clear();
+
+            let written = match acceptor.next_pdu_hint() {
+                Some(hint) => {
+                    let pdu = loop {
+                        let (action, frame) = self.read_pdu().await?;
+                        if hint.find_size(&frame)?.is_some_and(|(matched, _)| matched) {
+                            break frame;
+                        }
+                        unmatched.push_back((action, frame));
+                    };
+
+                    acceptor.step(&pdu, &mut buf)?
+                }
+                None => acceptor.step_no_input(&mut buf)?,
+            };
+
+            if written.size().is_some() {
+                self.write_all(buf.filled()).await?;
+            }
+        };
+
+        unmatched.append(&mut self.pending);
+        self.pending = unmatched;
+
+        Ok(result)
+    }
+}
+
+fn invalid_data<E>(error: E) -> io::Error
+where
+    E: std::error::Error + Send + Sync + 'static,
+{
+    io::Error::new(io::ErrorKind::InvalidData, error)
+}
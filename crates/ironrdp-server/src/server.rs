@@ -1,8 +1,10 @@
 use std::net::SocketAddr;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::{Duration, Instant};
 
 use anyhow::{anyhow, bail, Context, Result};
 use ironrdp_acceptor::{self, Acceptor, AcceptorResult, BeginResult, DesktopSize};
+pub use ironrdp_acceptor::{CapabilityPolicy, CodecKind, StandardSecurityConfig};
 use ironrdp_async::bytes;
 use ironrdp_cliprdr::backend::ClipboardMessage;
 use ironrdp_cliprdr::CliprdrServer;
@@ -10,52 +12,85 @@ use ironrdp_core::impl_as_any;
 use ironrdp_core::{decode, encode_vec};
 use ironrdp_displaycontrol::pdu::DisplayControlMonitorLayout;
 use ironrdp_displaycontrol::server::{DisplayControlHandler, DisplayControlServer};
-use ironrdp_pdu::input::fast_path::{FastPathInput, FastPathInputEvent};
+use ironrdp_pdu::input::fast_path::FastPathInput;
 use ironrdp_pdu::input::InputEventPdu;
 use ironrdp_pdu::mcs::{SendDataIndication, SendDataRequest};
+use ironrdp_pdu::rdp::autodetect::{AutoDetectRequest, AutoDetectRequestPdu, AutoDetectResponsePdu};
 use ironrdp_pdu::rdp::capability_sets::{BitmapCodecs, CapabilitySet, CmdFlags, GeneralExtraFlags};
-use ironrdp_pdu::rdp::headers::{ServerDeactivateAll, ShareControlPdu};
+use ironrdp_pdu::rdp::headers::{BasicSecurityHeaderFlags, ServerDeactivateAll, ShareControlPdu};
+use ironrdp_pdu::surface_commands::FrameAction;
 use ironrdp_pdu::x224::X224;
-use ironrdp_pdu::{self, decode_err, mcs, nego, rdp, Action, PduResult};
+use ironrdp_pdu::{self, decode_err, gcc, mcs, nego, rdp, Action, PduResult};
 use ironrdp_svc::{server_encode_svc_messages, StaticChannelId, StaticChannelSet, SvcProcessor};
-use ironrdp_tokio::{Framed, FramedRead, FramedWrite, TokioFramed};
+use ironrdp_tokio::{FramedRead, FramedWrite, TokioFramed};
 use rdpsnd::server::{RdpsndServer, RdpsndServerMessage};
 use tokio::io::{AsyncRead, AsyncWrite};
 use tokio::net::{TcpListener, TcpStream};
-use tokio::sync::{mpsc, Mutex};
-use tokio::task;
+use tokio::sync::{mpsc, oneshot, Mutex};
+use tokio::{task, time};
 use tokio_rustls::TlsAcceptor;
 use {ironrdp_dvc as dvc, ironrdp_rdpsnd as rdpsnd};
 
+use crate::audit::{AuditingHooks, ChannelAuditEvent, ChannelAuditor, ChannelFilter, PeerIdentity};
+use crate::autodetect::{AutoDetectConfig, AutoDetector};
+use crate::capture::{CaptureDirection, CaptureFactory, CaptureStream, CaptureTap};
 use crate::clipboard::CliprdrServerFactory;
 use crate::display::{DisplayUpdate, RdpServerDisplay};
-use crate::encoder::UpdateEncoder;
+use crate::encoder::{UpdateEncoder, UpdateFragmenter};
 use crate::handler::RdpServerInputHandler;
+use crate::hooks::AcceptorHooksFactory;
+use crate::input::{InputDecoder, InputDispatcher, InputSink};
+use crate::limits::{
+    with_decode_limits, with_handshake_timeout, ConnectionLimiter, ConnectionLimits, ConnectionPolicy, ConnectionQueue,
+    DecodeLimits,
+};
+use crate::scheduler::{FrameScheduler, FrameSchedulerConfig};
+use crate::security::SecureFramed;
+use crate::shutdown::{encode_disconnection, wait_teardown, ShutdownHandle, ShutdownRequest};
 use crate::{builder, capabilities, SoundServerFactory};
 
 #[derive(Clone)]
 pub struct RdpServerOptions {
     pub addr: SocketAddr,
     pub security: RdpServerSecurity,
+    /// Constraints on the negotiated capabilities
+    ///
+    /// Note that the server requires fast-path output: disabling fast-path prevents clients from connecting.
+    pub capability_policy: CapabilityPolicy,
+    /// Network auto-detection, used to adapt the encoding quality to each client
+    pub auto_detect: Option<AutoDetectConfig>,
+    /// Limits on the incoming connections
+    pub limits: ConnectionLimits,
+    /// Bounds on the resources claimed by the PDUs received from the clients
+    pub decode_limits: DecodeLimits,
+    /// Virtual channels the clients are allowed to use
+    pub channel_filter: ChannelFilter,
+    /// Per-client pacing of the display updates, sent as they come when disabled
+    pub frame_scheduler: Option<FrameSchedulerConfig>,
 }
 
 #[derive(Clone)]
 pub enum RdpServerSecurity {
     None,
     Tls(TlsAcceptor),
+    /// Standard RDP Security, for legacy clients unable to negotiate TLS
+    ///
+    /// RC4 and the RSA key exchange are not considered secure anymore, this should be restricted to lab or embedded
+    /// use cases.
+    Standard(StandardSecurityConfig),
 }
 
 impl RdpServerSecurity {
     pub fn flag(&self) -> nego::SecurityProtocol {
         match self {
-            RdpServerSecurity::None => nego::SecurityProtocol::empty(),
+            RdpServerSecurity::None | RdpServerSecurity::Standard(_) => nego::SecurityProtocol::empty(),
             RdpServerSecurity::Tls(_) => nego::SecurityProtocol::SSL,
         }
     }
 }
 
 struct AInputHandler {
-    handler: Arc<Mutex<Box<dyn RdpServerInputHandler>>>,
+    handler: Arc<Mutex<InputDispatcher>>,
 }
 
 impl_as_any!(AInputHandler);
@@ -82,7 +117,7 @@ impl dvc::DvcProcessor for AInputHandler {
             ClientPdu::Mouse(pdu) => {
                 let handler = Arc::clone(&self.handler);
                 task::spawn_blocking(move || {
-                    handler.blocking_lock().mouse(pdu.into());
+                    handler.blocking_lock().ainput(pdu);
                 });
             }
         }
@@ -115,7 +150,7 @@ impl DisplayControlHandler for DisplayControlBackend {
 /// A server is created to listen for connections.
 /// After the connection sequence is finalized using the provided security mechanism, the server can:
 ///  - receive display updates from a [`RdpServerDisplay`] and forward them to the client
-///  - receive input events from a client and forward them to an [`RdpServerInputHandler`]
+///  - receive input events from a client and forward them to an [`RdpServerInputHandler`] or an [`InputSink`]
 ///
 /// # Example
 ///
@@ -173,11 +208,18 @@ impl DisplayControlHandler for DisplayControlBackend {
 pub struct RdpServer {
     opts: RdpServerOptions,
     // FIXME: replace with a channel and poll/process the handler?
-    handler: Arc<Mutex<Box<dyn RdpServerInputHandler>>>,
+    handler: Arc<Mutex<InputDispatcher>>,
     display: Arc<Mutex<Box<dyn RdpServerDisplay>>>,
     static_channels: StaticChannelSet,
     sound_factory: Option<Box<dyn SoundServerFactory>>,
     cliprdr_factory: Option<Box<dyn CliprdrServerFactory>>,
+    hooks_factory: Option<Box<dyn AcceptorHooksFactory>>,
+    connection_policy: Option<Box<dyn ConnectionPolicy>>,
+    channel_auditor: Option<Arc<dyn ChannelAuditor>>,
+    capture_factory: Option<Box<dyn CaptureFactory>>,
+    shutdown_done: Option<oneshot::Sender<()>>,
+    auto_detector: Option<AutoDetector>,
+    frame_scheduler: Option<FrameScheduler>,
     ev_sender: mpsc::UnboundedSender<ServerEvent>,
     ev_receiver: mpsc::UnboundedReceiver<ServerEvent>,
 }
@@ -185,6 +227,7 @@ pub struct RdpServer {
 #[derive(Debug)]
 pub enum ServerEvent {
     Quit(String),
+    Shutdown(ShutdownRequest),
     Clipboard(ClipboardMessage),
     Rdpsnd(RdpsndServerMessage),
 }
@@ -213,6 +256,7 @@ impl RdpServer {
         display: Box<dyn RdpServerDisplay>,
         mut sound_factory: Option<Box<dyn SoundServerFactory>>,
         mut cliprdr_factory: Option<Box<dyn CliprdrServerFactory>>,
+        hooks_factory: Option<Box<dyn AcceptorHooksFactory>>,
     ) -> Self {
         let (ev_sender, ev_receiver) = ServerEvent::create_channel();
         if let Some(cliprdr) = cliprdr_factory.as_mut() {
@@ -223,11 +267,18 @@ impl RdpServer {
         }
         Self {
             opts,
-            handler: Arc::new(Mutex::new(handler)),
+            handler: Arc::new(Mutex::new(InputDispatcher::Handler(handler))),
             display: Arc::new(Mutex::new(display)),
             static_channels: StaticChannelSet::new(),
             sound_factory,
             cliprdr_factory,
+            hooks_factory,
+            connection_policy: None,
+            channel_auditor: None,
+            capture_factory: None,
+            shutdown_done: None,
+            auto_detector: None,
+            frame_scheduler: None,
             ev_sender,
             ev_receiver,
         }
@@ -237,11 +288,39 @@ impl RdpServer {
         builder::RdpServerBuilder::new()
     }
 
+    /// Replaces the input handler by a sink receiving normalized input events.
+    pub fn set_input_sink(&mut self, sink: Box<dyn InputSink>) {
+        self.handler = Arc::new(Mutex::new(InputDispatcher::Sink {
+            sink,
+            decoder: InputDecoder::default(),
+        }));
+    }
+
+    /// Installs a custom admission policy, consulted in addition to the [`ConnectionLimits`].
+    pub fn set_connection_policy(&mut self, policy: Box<dyn ConnectionPolicy>) {
+        self.connection_policy = Some(policy);
+    }
+
+    /// Installs an auditor recording the static channel joins and the dynamic channel creations.
+    pub fn set_channel_auditor(&mut self, auditor: Arc<dyn ChannelAuditor>) {
+        self.channel_auditor = Some(auditor);
+    }
+
+    /// Records the sessions using the writers built by the factory.
+    pub fn set_capture_factory(&mut self, factory: Box<dyn CaptureFactory>) {
+        self.capture_factory = Some(factory);
+    }
+
+    /// Returns a handle used to gracefully shut the server down
+    pub fn shutdown_handle(&self) -> ShutdownHandle {
+        ShutdownHandle::new(self.ev_sender.clone())
+    }
+
     pub fn event_sender(&self) -> &mpsc::UnboundedSender<ServerEvent> {
         &self.ev_sender
     }
 
-    fn attach_channels(&mut self, acceptor: &mut Acceptor) {
+    fn attach_channels(&mut self, acceptor: &mut Acceptor, peer: &Arc<StdMutex<PeerIdentity>>) {
         if let Some(cliprdr_factory) = self.cliprdr_factory.as_deref() {
             let backend = cliprdr_factory.build_cliprdr_backend();
 
@@ -256,40 +335,119 @@ impl RdpServer {
             acceptor.attach_static_channel(RdpsndServer::new(backend));
         }
 
+        let mut dvc = dvc::DrdynvcServer::new();
+
+        let ainput = AInputHandler {
+            handler: Arc::clone(&self.handler),
+        };
+        if self.allow_dynamic_channel(&ainput, peer) {
+            dvc = dvc.with_dynamic_channel(ainput);
+        }
+
         let dcs_backend = DisplayControlBackend::new(Arc::clone(&self.display));
-        let dvc = dvc::DrdynvcServer::new()
-            .with_dynamic_channel(AInputHandler {
-                handler: Arc::clone(&self.handler),
-            })
-            .with_dynamic_channel(DisplayControlServer::new(Box::new(dcs_backend)));
+        let display_control = DisplayControlServer::new(Box::new(dcs_backend));
+        if self.allow_dynamic_channel(&display_control, peer) {
+            dvc = dvc.with_dynamic_channel(display_control);
+        }
+
+        if let Some(auditor) = self.channel_auditor.clone() {
+            let peer = Arc::clone(peer);
+            dvc = dvc.with_creation_callback(move |channel_name, channel_id, creation_status| {
+                auditor.record(ChannelAuditEvent::DynamicChannelCreation {
+                    peer: peer.lock().expect("poisoned").clone(),
+                    channel_name: channel_name.to_owned(),
+                    channel_id,
+                    creation_status: creation_status.into(),
+                });
+            });
+        }
+
         acceptor.attach_static_channel(dvc);
     }
 
+    fn allow_dynamic_channel(&self, channel: &dyn dvc::DvcProcessor, peer: &Arc<StdMutex<PeerIdentity>>) -> bool {
+        let channel_name = channel.channel_name();
+
+        if self.opts.channel_filter.is_allowed(channel_name) {
+            return true;
+        }
+
+        info!(channel_name, "Dynamic channel denied by filter");
+
+        if let Some(auditor) = self.channel_auditor.as_deref() {
+            auditor.record(ChannelAuditEvent::DynamicChannelDenied {
+                peer: peer.lock().expect("poisoned").clone(),
+                channel_name: channel_name.to_owned(),
+            });
+        }
+
+        false
+    }
+
     pub async fn run_connection(&mut self, stream: TcpStream) -> Result<()> {
+        let peer_addr = stream.peer_addr()?;
         let framed = TokioFramed::new(stream);
 
         let size = self.display.lock().await.size().await;
-        let capabilities = capabilities::capabilities(&self.opts, size);
+        let capabilities = capabilities::capabilities(&self.opts, size).context("invalid server capability sets")?;
         let mut acceptor = Acceptor::new(self.opts.security.flag(), size, capabilities);
+        acceptor.set_capability_policy(self.opts.capability_policy.clone());
+        acceptor.set_decode_limits(self.opts.decode_limits);
+        acceptor.set_auto_detect(self.opts.auto_detect.as_ref().is_some_and(|config| config.connect_time));
+        if let RdpServerSecurity::Standard(config) = &self.opts.security {
+            acceptor.set_standard_security(config.clone());
+        }
 
-        self.attach_channels(&mut acceptor);
+        let peer = Arc::new(StdMutex::new(PeerIdentity {
+            addr: peer_addr,
+            username: None,
+            domain: None,
+        }));
 
-        let res = ironrdp_acceptor::accept_begin(framed, &mut acceptor)
-            .await
+        acceptor.set_hooks(Box::new(AuditingHooks {
+            inner: self
+                .hooks_factory
+                .as_deref()
+                .map(|hooks_factory| hooks_factory.build_hooks(peer_addr)),
+            filter: self.opts.channel_filter.clone(),
+            auditor: self.channel_auditor.clone(),
+            peer: Arc::clone(&peer),
+        }));
+
+        self.attach_channels(&mut acceptor, &peer);
+
+        let handshake_timeout = self.opts.limits.handshake_timeout;
+
+        let res = with_handshake_timeout(handshake_timeout, ironrdp_acceptor::accept_begin(framed, &mut acceptor))
+            .await?
             .context("accept_begin failed")?;
 
+        let mut capture = self
+            .capture_factory
+            .as_deref()
+            .and_then(|factory| CaptureTap::start(factory, peer_addr));
+
         match res {
             BeginResult::ShouldUpgrade(stream) => {
-                let framed = TokioFramed::new(match &self.opts.security {
-                    RdpServerSecurity::Tls(acceptor) => acceptor.accept(stream).await?,
-                    RdpServerSecurity::None => unreachable!(),
-                });
+                let stream = match &self.opts.security {
+                    RdpServerSecurity::Tls(acceptor) => {
+                        with_handshake_timeout(handshake_timeout, acceptor.accept(stream)).await??
+                    }
+                    RdpServerSecurity::None | RdpServerSecurity::Standard(_) => unreachable!(),
+                };
+                let framed = TokioFramed::new(CaptureStream::new(stream, capture));
 
-                self.accept_finalize(framed, acceptor).await?;
+                self.accept_finalize(framed, acceptor, &peer).await?;
             }
 
             BeginResult::Continue(framed) => {
-                self.accept_finalize(framed, acceptor).await?;
+                let (stream, leftover) = framed.into_inner();
+                if let Some(capture) = capture.as_mut() {
+                    capture.feed(CaptureDirection::ClientToServer, &leftover);
+                }
+                let framed = TokioFramed::new_with_leftover(CaptureStream::new(stream, capture), leftover);
+
+                self.accept_finalize(framed, acceptor, &peer).await?;
             }
         };
 
@@ -298,32 +456,77 @@ impl RdpServer {
 
     pub async fn run(&mut self) -> Result<()> {
         let listener = TcpListener::bind(self.opts.addr).await?;
+        let mut limiter = ConnectionLimiter::new(self.opts.limits.clone(), self.connection_policy.take());
+        let mut waiting = ConnectionQueue::new();
 
         debug!("Listening for connections");
         loop {
-            tokio::select! {
-                Some(event) = self.ev_receiver.recv() => {
-                    match event {
-                        ServerEvent::Quit(reason) => {
-                            debug!("Got quit event {reason}");
-                            break;
+            let stream = match waiting.pop(&limiter, Instant::now()) {
+                Some((stream, _)) => stream,
+                None => tokio::select! {
+                    Some(event) = self.ev_receiver.recv() => {
+                        match event {
+                            ServerEvent::Quit(reason) => {
+                                debug!("Got quit event {reason}");
+                                break;
+                            }
+                            ServerEvent::Shutdown(request) => {
+                                debug!(reason = ?request.reason, "Got shutdown event");
+                                self.shutdown_done = Some(request.done);
+                                break;
+                            }
+                            ev => {
+                                debug!("Unexpected event {:?}", ev);
+                                continue;
+                            }
                         }
-                        ev => {
-                            debug!("Unexpected event {:?}", ev);
+                    },
+                    Ok((stream, peer)) = listener.accept() => {
+                        debug!(?peer, "Received connection");
+                        if !limiter.admit(peer, 0, 0, Instant::now()) {
+                            continue;
                         }
+                        stream
                     }
+                    else => break,
                 },
-                Ok((stream, peer)) = listener.accept() => {
-                    debug!(?peer, "Received connection");
-                    if let Err(error) = self.run_connection(stream).await {
-                        error!(?error, "Connection error");
+            };
+
+            // Keep accepting connections while the session is running, so that connections exceeding the limits are
+            // closed right away instead of lingering in the listen backlog.
+            let result = {
+                let connection = self.run_connection(stream);
+                tokio::pin!(connection);
+
+                loop {
+                    tokio::select! {
+                        result = &mut connection => break result,
+                        Ok((stream, peer)) = listener.accept() => {
+                            debug!(?peer, "Received connection while a session is running");
+                            waiting.push(&mut limiter, stream, peer, Instant::now());
+                        }
                     }
-                    self.static_channels = StaticChannelSet::new();
                 }
-                else => break,
+            };
+
+            if let Err(error) = result {
+                error!(?error, "Connection error");
+            }
+            self.static_channels = StaticChannelSet::new();
+
+            if self.shutdown_done.is_some() {
+                // Connections waiting for their turn are dropped.
+                break;
             }
         }
 
+        self.connection_policy = limiter.into_policy();
+
+        if let Some(done) = self.shutdown_done.take() {
+            // The requester may have stopped waiting for the shutdown.
+            let _ = done.send(());
+        }
+
         Ok(())
     }
 
@@ -333,6 +536,11 @@ impl RdpServer {
             .and_then(|svc| svc.channel_processor_downcast_mut())
     }
 
+    fn get_svc_processor_with_id<T: SvcProcessor + 'static>(&mut self) -> Option<(StaticChannelId, &mut T)> {
+        let channel_id = self.get_channel_id_by_type::<T>()?;
+        Some((channel_id, self.get_svc_processor::<T>()?))
+    }
+
     pub fn get_channel_id_by_type<T: SvcProcessor + 'static>(&self) -> Option<StaticChannelId> {
         self.static_channels.get_channel_id_by_type::<T>()
     }
@@ -341,7 +549,7 @@ impl RdpServer {
         &mut self,
         action: Action,
         bytes: bytes::BytesMut,
-        framed: &mut Framed<S>,
+        framed: &mut SecureFramed<S>,
         io_channel_id: u16,
         user_channel_id: u16,
     ) -> Result<RunState>
@@ -350,7 +558,7 @@ impl RdpServer {
     {
         match action {
             Action::FastPath => {
-                let input = decode(&bytes)?;
+                let input = with_decode_limits(self.opts.decode_limits, || decode(&bytes))?;
                 self.handle_fastpath(input).await;
             }
 
@@ -372,7 +580,7 @@ impl RdpServer {
     async fn dispatch_display_update<S>(
         &mut self,
         update: DisplayUpdate,
-        framed: &mut Framed<S>,
+        framed: &mut SecureFramed<S>,
         user_channel_id: u16,
         io_channel_id: u16,
         buffer: &mut Vec<u8>,
@@ -382,10 +590,21 @@ impl RdpServer {
         S: FramedWrite + FramedRead,
     {
         let mut fragmenter = match update {
-            DisplayUpdate::Bitmap(bitmap) => encoder.bitmap(bitmap),
+            DisplayUpdate::Bitmap(bitmap) => {
+                if let Some(scheduler) = self.frame_scheduler.as_mut() {
+                    scheduler.push(bitmap);
+                    self.send_scheduled_frame(framed, buffer, encoder).await?;
+                    return Ok(RunState::Continue);
+                }
+
+                encoder.bitmap(bitmap)
+            }
             DisplayUpdate::PointerPosition(pos) => encoder.pointer_position(pos),
             DisplayUpdate::Resize(desktop_size) => {
                 debug!(?desktop_size, "Display resize");
+                if let Some(scheduler) = self.frame_scheduler.as_mut() {
+                    scheduler.clear();
+                }
                 let pdu = ShareControlPdu::ServerDeactivateAll(ServerDeactivateAll);
                 let pdu = rdp::headers::ShareControlHeader {
                     share_id: 0,
@@ -409,24 +628,64 @@ impl RdpServer {
         }
         .context("error during update encoding")?;
 
-        if fragmenter.size_hint() > buffer.len() {
-            buffer.resize(fragmenter.size_hint(), 0);
+        write_fragmented(framed, buffer, &mut fragmenter).await?;
+
+        Ok(RunState::Continue)
+    }
+
+    /// Sends the updates queued by the frame scheduler, if the client is ready to receive them
+    async fn send_scheduled_frame<S>(
+        &mut self,
+        framed: &mut SecureFramed<S>,
+        buffer: &mut Vec<u8>,
+        encoder: &mut UpdateEncoder,
+    ) -> Result<()>
+    where
+        S: FramedWrite,
+    {
+        let Some(frame) = self
+            .frame_scheduler
+            .as_mut()
+            .and_then(|scheduler| scheduler.poll_frame(Instant::now()))
+        else {
+            return Ok(());
+        };
+
+        let mut size = 0;
+
+        if let Some(id) = frame.id {
+            let mut fragmenter = encoder
+                .frame_marker(FrameAction::Begin, id)
+                .context("error during frame marker encoding")?;
+            size += write_fragmented(framed, buffer, &mut fragmenter).await?;
         }
 
-        while let Some(len) = fragmenter.next(buffer) {
-            framed
-                .write_all(&buffer[..len])
-                .await
-                .context("failed to write display update")?;
+        for bitmap in frame.updates {
+            let mut fragmenter = encoder.bitmap(bitmap).context("error during update encoding")?;
+            size += write_fragmented(framed, buffer, &mut fragmenter).await?;
         }
 
-        Ok(RunState::Continue)
+        if let Some(id) = frame.id {
+            let mut fragmenter = encoder
+                .frame_marker(FrameAction::End, id)
+                .context("error during frame marker encoding")?;
+            size += write_fragmented(framed, buffer, &mut fragmenter).await?;
+        }
+
+        trace!(frame_id = ?frame.id, size, "Frame sent");
+
+        if let Some(scheduler) = self.frame_scheduler.as_mut() {
+            scheduler.frame_sent(frame.id, size, Instant::now());
+        }
+
+        Ok(())
     }
 
     async fn dispatch_server_events<S>(
         &mut self,
         events: &mut Vec<ServerEvent>,
-        framed: &mut Framed<S>,
+        framed: &mut SecureFramed<S>,
+        io_channel_id: u16,
         user_channel_id: u16,
     ) -> Result<RunState>
     where
@@ -442,6 +701,11 @@ impl RdpServer {
                     debug!("Got quit event: {reason}");
                     return Ok(RunState::Disconnect);
                 }
+                ServerEvent::Shutdown(request) => {
+                    self.shutdown_client(framed, io_channel_id, user_channel_id, request)
+                        .await?;
+                    return Ok(RunState::Disconnect);
+                }
                 ServerEvent::Rdpsnd(s) => {
                     let Some(rdpsnd) = self.get_svc_processor::<RdpsndServer>() else {
                         warn!("No rdpsnd channel, dropping event");
@@ -497,7 +761,7 @@ impl RdpServer {
 
     async fn client_loop<S>(
         &mut self,
-        framed: &mut Framed<S>,
+        framed: &mut SecureFramed<S>,
         io_channel_id: u16,
         user_channel_id: u16,
         mut encoder: UpdateEncoder,
@@ -512,7 +776,12 @@ impl RdpServer {
         let mut events = Vec::with_capacity(100);
         let mut state = RunState::Continue;
 
+        let auto_detect_interval = self.opts.auto_detect.as_ref().and_then(|config| config.interval);
+        let mut auto_detect_timer = time::interval(auto_detect_interval.unwrap_or(Duration::from_secs(1)));
+
         while state == RunState::Continue {
+            let frame_deadline = self.frame_scheduler.as_ref().and_then(FrameScheduler::next_deadline);
+
             tokio::select! {
                 frame = framed.read_pdu() => {
                     let Ok((action, bytes)) = frame else {
@@ -521,12 +790,22 @@ impl RdpServer {
                         break;
                     };
                     state = self.dispatch_pdu(action, bytes, framed, io_channel_id, user_channel_id).await?;
+                    self.update_encoding_quality(&mut encoder);
+                    self.send_scheduled_frame(framed, &mut buffer, &mut encoder).await?;
                 },
 
+                _ = auto_detect_timer.tick(), if auto_detect_interval.is_some() => {
+                    self.send_auto_detect_requests(framed, io_channel_id, user_channel_id).await?;
+                }
+
                 Some(update) = display_updates.next_update() => {
                     state = self.dispatch_display_update(update, framed, user_channel_id, io_channel_id, &mut buffer, &mut encoder).await?;
                 }
 
+                _ = time::sleep_until(frame_deadline.unwrap_or_else(Instant::now).into()), if frame_deadline.is_some() => {
+                    self.send_scheduled_frame(framed, &mut buffer, &mut encoder).await?;
+                }
+
                 nevents = self.ev_receiver.recv_many(&mut events, 100) => {
                     if nevents == 0 {
                         debug!("No sever events.. stopping");
@@ -536,7 +815,7 @@ impl RdpServer {
                     while let Ok(ev) = self.ev_receiver.try_recv() {
                         events.push(ev);
                     }
-                    state = self.dispatch_server_events(&mut events, framed, user_channel_id).await?;
+                    state = self.dispatch_server_events(&mut events, framed, io_channel_id, user_channel_id).await?;
                 }
 
                 else => {
@@ -550,7 +829,112 @@ impl RdpServer {
         Ok(state)
     }
 
-    async fn client_accepted<S>(&mut self, framed: &mut Framed<S>, result: AcceptorResult) -> Result<RunState>
+    /// Closes the channels, then notifies the client of the disconnection reason
+    async fn shutdown_client<S>(
+        &mut self,
+        framed: &mut SecureFramed<S>,
+        io_channel_id: u16,
+        user_channel_id: u16,
+        request: ShutdownRequest,
+    ) -> Result<()>
+    where
+        S: FramedWrite + FramedRead,
+    {
+        info!(reason = %request.reason.description(), "Shutting down the session");
+
+        // Record the request first, so that the server stops even if the client is already gone.
+        self.shutdown_done = Some(request.done);
+
+        if let Some((channel_id, rdpsnd)) = self.get_svc_processor_with_id::<RdpsndServer>() {
+            let msgs = rdpsnd.close().context("failed to close rdpsnd channel")?;
+            let data = server_encode_svc_messages(msgs.into(), channel_id, user_channel_id)?;
+            framed.write_all(&data).await?;
+        }
+
+        if let Some((channel_id, drdynvc)) = self.get_svc_processor_with_id::<dvc::DrdynvcServer>() {
+            let msgs = drdynvc.close_all().context("failed to close dynamic channels")?;
+            let data = server_encode_svc_messages(msgs, channel_id, user_channel_id)?;
+            framed.write_all(&data).await?;
+        }
+
+        let teardown = async {
+            let state = self
+                .wait_channels_teardown(framed, io_channel_id, user_channel_id)
+                .await?;
+            Ok(state != RunState::Disconnect)
+        };
+        if !wait_teardown(teardown, request.timeout).await {
+            return Ok(());
+        }
+
+        let pdus = encode_disconnection(request.reason, io_channel_id, user_channel_id)?;
+        framed.write_all(&pdus).await?;
+
+        Ok(())
+    }
+
+    /// Processes the client PDUs until all the dynamic channels are closed
+    async fn wait_channels_teardown<S>(
+        &mut self,
+        framed: &mut SecureFramed<S>,
+        io_channel_id: u16,
+        user_channel_id: u16,
+    ) -> Result<RunState>
+    where
+        S: FramedWrite + FramedRead,
+    {
+        while self
+            .get_svc_processor::<dvc::DrdynvcServer>()
+            .is_some_and(|drdynvc| drdynvc.has_opened_channels())
+        {
+            let (action, bytes) = framed.read_pdu().await?;
+            if self
+                .dispatch_pdu(action, bytes, framed, io_channel_id, user_channel_id)
+                .await?
+                == RunState::Disconnect
+            {
+                return Ok(RunState::Disconnect);
+            }
+        }
+
+        Ok(RunState::Continue)
+    }
+
+    async fn send_auto_detect_requests<S>(
+        &mut self,
+        framed: &mut SecureFramed<S>,
+        io_channel_id: u16,
+        user_channel_id: u16,
+    ) -> Result<()>
+    where
+        S: FramedWrite,
+    {
+        let Some(auto_detector) = self.auto_detector.as_mut() else {
+            return Ok(());
+        };
+
+        for request in auto_detector.next_requests(Instant::now()) {
+            write_auto_detect_request(framed, request, io_channel_id, user_channel_id).await?;
+        }
+
+        Ok(())
+    }
+
+    fn update_encoding_quality(&mut self, encoder: &mut UpdateEncoder) {
+        let (Some(config), Some(auto_detector)) = (self.opts.auto_detect.as_ref(), self.auto_detector.as_ref()) else {
+            return;
+        };
+
+        if let Some(scheduler) = self.frame_scheduler.as_mut() {
+            scheduler.set_bandwidth(auto_detector.estimate().bandwidth);
+        }
+
+        if let Some(quality) = config.quality_controller.quality(auto_detector.estimate()) {
+            encoder.set_quality(quality);
+        }
+    }
+
+    async fn client_accepted<S>(&mut self, framed: &mut SecureFramed<S>, result: AcceptorResult) -> Result<RunState>
     where
         S: FramedWrite + FramedRead,
     {
@@ -580,6 +964,7 @@ impl RdpServer {
 
         let mut rfxcodec = None;
         let mut surface_flags = CmdFlags::empty();
+        let mut max_unacknowledged_frames = None;
         for c in result.capabilities {
             match c {
                 CapabilitySet::General(c) => {
@@ -591,6 +976,9 @@ impl RdpServer {
                 CapabilitySet::SurfaceCommands(c) => {
                     surface_flags = c.flags;
                 }
+                CapabilitySet::FrameAcknowledge(c) => {
+                    max_unacknowledged_frames = Some(c.max_unacknowledged_frame_count);
+                }
                 CapabilitySet::BitmapCodecs(BitmapCodecs(codecs)) => {
                     for codec in codecs {
                         match codec.property {
@@ -626,7 +1014,22 @@ impl RdpServer {
             }
         }
 
-        let encoder = UpdateEncoder::new(surface_flags, rfxcodec);
+        let mut encoder = UpdateEncoder::new(surface_flags, rfxcodec);
+
+        // The client must advertise support for the network characteristics detection.
+        let auto_detect_supported = result
+            .early_capability
+            .is_some_and(|c| c.contains(gcc::ClientEarlyCapabilityFlags::SUPPORT_NET_CHAR_AUTODETECT));
+        self.auto_detector = (self.opts.auto_detect.is_some() && auto_detect_supported)
+            .then(|| AutoDetector::new(result.auto_detected_bandwidth));
+        self.frame_scheduler = self.opts.frame_scheduler.clone().map(|config| {
+            FrameScheduler::new(
+                config,
+                surface_flags.contains(CmdFlags::FRAME_MARKER),
+                max_unacknowledged_frames,
+            )
+        });
+        self.update_encoding_quality(&mut encoder);
 
         let state = self
             .client_loop(framed, result.io_channel_id, result.user_channel_id, encoder)
@@ -638,7 +1041,7 @@ impl RdpServer {
 
     async fn handle_input_backlog<S>(
         &mut self,
-        framed: &mut Framed<S>,
+        framed: &mut SecureFramed<S>,
         io_channel_id: u16,
         user_channel_id: u16,
         frames: Vec<Vec<u8>>,
@@ -649,7 +1052,7 @@ impl RdpServer {
         for frame in frames {
             match Action::from_fp_output_header(frame[0]) {
                 Ok(Action::FastPath) => {
-                    let input = decode(&frame)?;
+                    let input = with_decode_limits(self.opts.decode_limits, || decode(&frame))?;
                     self.handle_fastpath(input).await;
                 }
 
@@ -667,42 +1070,15 @@ impl RdpServer {
     }
 
     async fn handle_fastpath(&mut self, input: FastPathInput) {
+        let mut handler = self.handler.lock().await;
         for event in input.0 {
-            let mut handler = self.handler.lock().await;
-            match event {
-                FastPathInputEvent::KeyboardEvent(flags, key) => {
-                    handler.keyboard((key, flags).into());
-                }
-
-                FastPathInputEvent::UnicodeKeyboardEvent(flags, key) => {
-                    handler.keyboard((key, flags).into());
-                }
-
-                FastPathInputEvent::SyncEvent(flags) => {
-                    handler.keyboard(flags.into());
-                }
-
-                FastPathInputEvent::MouseEvent(mouse) => {
-                    handler.mouse(mouse.into());
-                }
-
-                FastPathInputEvent::MouseEventEx(mouse) => {
-                    handler.mouse(mouse.into());
-                }
-
-                FastPathInputEvent::MouseEventRel(mouse) => {
-                    handler.mouse(mouse.into());
-                }
-
-                FastPathInputEvent::QoeEvent(quality) => {
-                    warn!("Received QoE: {}", quality);
-                }
-            }
+            handler.fastpath(event);
         }
     }
 
     async fn handle_io_channel_data(&mut self, data: SendDataRequest<'_>) -> Result<bool> {
-        let control: rdp::headers::ShareControlHeader = decode(data.user_data.as_ref())?;
+        let control: rdp::headers::ShareControlHeader =
+            with_decode_limits(self.opts.decode_limits, || decode(data.user_data.as_ref()))?;
 
         match control.share_control_pdu {
             rdp::headers::ShareControlPdu::Data(header) => match header.share_data_pdu {
@@ -714,6 +1090,12 @@ impl RdpServer {
                     return Ok(true);
                 }
 
+                rdp::headers::ShareDataPdu::FrameAcknowledge(pdu) => {
+                    if let Some(scheduler) = self.frame_scheduler.as_mut() {
+                        scheduler.acknowledge(pdu.frame_id);
+                    }
+                }
+
                 unexpected => {
                     warn!(?unexpected, "Unexpected share data pdu");
                 }
@@ -729,7 +1111,7 @@ impl RdpServer {
 
     async fn handle_x224<S>(
         &mut self,
-        framed: &mut Framed<S>,
+        framed: &mut SecureFramed<S>,
         io_channel_id: u16,
         user_channel_id: u16,
         frame: &[u8],
@@ -737,16 +1119,30 @@ impl RdpServer {
     where
         S: FramedWrite,
     {
-        let message = decode::<X224<mcs::McsMessage<'_>>>(frame)?;
+        let decode_limits = self.opts.decode_limits;
+
+        let message = with_decode_limits(decode_limits, || decode::<X224<mcs::McsMessage<'_>>>(frame))?;
         match message.0 {
             mcs::McsMessage::SendDataRequest(data) => {
                 debug!(?data, "McsMessage::SendDataRequest");
                 if data.channel_id == io_channel_id {
+                    if let Some(response) = self.decode_auto_detect_response(&data.user_data) {
+                        debug!(message = ?response, "Received");
+                        let request = self
+                            .auto_detector
+                            .as_mut()
+                            .and_then(|auto_detector| auto_detector.handle_response(response.0, Instant::now()));
+                        if let Some(request) = request {
+                            write_auto_detect_request(framed, request, io_channel_id, user_channel_id).await?;
+                        }
+                        return Ok(false);
+                    }
+
                     return self.handle_io_channel_data(data).await;
                 }
 
                 if let Some(svc) = self.static_channels.get_by_channel_id_mut(data.channel_id) {
-                    let response_pdus = svc.process(&data.user_data)?;
+                    let response_pdus = with_decode_limits(decode_limits, || svc.process(&data.user_data))?;
                     let response = server_encode_svc_messages(response_pdus, data.channel_id, user_channel_id)?;
                     framed.write_all(&response).await?;
                 } else {
@@ -768,70 +1164,62 @@ impl RdpServer {
         Ok(false)
     }
 
-    async fn handle_input_event(&mut self, input: InputEventPdu) {
-        for event in input.0 {
-            let mut handler = self.handler.lock().await;
-            match event {
-                ironrdp_pdu::input::InputEvent::ScanCode(key) => {
-                    handler.keyboard((key.key_code, key.flags).into());
-                }
-
-                ironrdp_pdu::input::InputEvent::Unicode(key) => {
-                    handler.keyboard((key.unicode_code, key.flags).into());
-                }
-
-                ironrdp_pdu::input::InputEvent::Sync(sync) => {
-                    handler.keyboard(sync.flags.into());
-                }
-
-                ironrdp_pdu::input::InputEvent::Mouse(mouse) => {
-                    handler.mouse(mouse.into());
-                }
+    fn decode_auto_detect_response(&self, user_data: &[u8]) -> Option<AutoDetectResponsePdu> {
+        // Auto-detect responses are the only PDUs prefixed by a security header on the I/O channel once connected.
+        // A Share Control Header starts with its total length instead, so the flag is only checked when expecting
+        // a response, and a response must then be decoded successfully.
+        if !self.auto_detector.as_ref().is_some_and(AutoDetector::is_waiting) {
+            return None;
+        }
 
-                ironrdp_pdu::input::InputEvent::MouseX(mouse) => {
-                    handler.mouse(mouse.into());
-                }
+        let flags =
+            BasicSecurityHeaderFlags::from_bits_truncate(u16::from_le_bytes([*user_data.first()?, *user_data.get(1)?]));
+        if !flags.contains(BasicSecurityHeaderFlags::AUTODETECT_RSP) {
+            return None;
+        }
 
-                ironrdp_pdu::input::InputEvent::MouseRel(mouse) => {
-                    handler.mouse(mouse.into());
-                }
+        decode(user_data).ok()
+    }
 
-                ironrdp_pdu::input::InputEvent::Unused(_) => {}
-            }
+    async fn handle_input_event(&mut self, input: InputEventPdu) {
+        let mut handler = self.handler.lock().await;
+        for event in input.0 {
+            handler.slowpath(event);
         }
     }
 
-    async fn accept_finalize<S>(&mut self, mut framed: TokioFramed<S>, mut acceptor: Acceptor) -> Result<()>
+    async fn accept_finalize<S>(
+        &mut self,
+        framed: TokioFramed<S>,
+        mut acceptor: Acceptor,
+        peer: &Arc<StdMutex<PeerIdentity>>,
+    ) -> Result<()>
     where
         S: AsyncRead + AsyncWrite + Sync + Send + Unpin,
     {
-        let mut other_pdus = None;
-
-        loop {
-            let (new_framed, result) = ironrdp_acceptor::accept_finalize(framed, &mut acceptor, other_pdus.as_mut())
-                .await
-                .context("failed to accept client during finalize")?;
-
-            let (stream, mut leftover) = new_framed.into_inner();
+        let (framed, mut result) = with_handshake_timeout(
+            self.opts.limits.handshake_timeout,
+            ironrdp_acceptor::accept_finalize(framed, &mut acceptor, None),
+        )
+        .await?
+        .context("failed to accept client during finalize")?;
 
-            if let Some(pdus) = other_pdus.take() {
-                let unmatched_frames = pdus.into_iter().flatten();
-                let previous_leftover = leftover.split();
-                leftover.extend(unmatched_frames);
-                leftover.extend_from_slice(&previous_leftover);
-            }
-
-            framed = TokioFramed::new_with_leftover(stream, leftover);
+        // From now on, the frames go through the security layer when Standard RDP Security is used.
+        let mut framed = SecureFramed::new(framed, result.security_layer.take());
 
+        loop {
             match self.client_accepted(&mut framed, result).await? {
                 RunState::Continue => {
                     unreachable!();
                 }
                 RunState::DeactivationReactivation { desktop_size } => {
-                    other_pdus = Some(Vec::new());
                     acceptor = Acceptor::new_deactivation_reactivation(acceptor, desktop_size);
-                    self.attach_channels(&mut acceptor);
-                    continue;
+                    self.attach_channels(&mut acceptor, peer);
+
+                    result =
+                        with_handshake_timeout(self.opts.limits.handshake_timeout, framed.reactivate(&mut acceptor))
+                            .await?
+                            .context("failed to accept client during reactivation")?;
                 }
                 RunState::Disconnect => break,
             }
@@ -840,3 +1228,49 @@ impl RdpServer {
         Ok(())
     }
 }
+
+async fn write_auto_detect_request<S>(
+    framed: &mut SecureFramed<S>,
+    request: AutoDetectRequest,
+    io_channel_id: u16,
+    user_channel_id: u16,
+) -> Result<()>
+where
+    S: FramedWrite,
+{
+    debug!(message = ?request, "Send");
+
+    let pdu = SendDataIndication {
+        initiator_id: user_channel_id,
+        channel_id: io_channel_id,
+        user_data: encode_vec(&AutoDetectRequestPdu(request))?.into(),
+    };
+    framed.write_all_with_security_header(&encode_vec(&X224(pdu))?).await?;
+
+    Ok(())
+}
+
+/// Writes all the fragments of an update, returning the number of bytes written
+async fn write_fragmented<S>(
+    framed: &mut SecureFramed<S>,
+    buffer: &mut Vec<u8>,
+    fragmenter: &mut UpdateFragmenter<'_>,
+) -> Result<usize>
+where
+    S: FramedWrite,
+{
+    if fragmenter.size_hint() > buffer.len() {
+        buffer.resize(fragmenter.size_hint(), 0);
+    }
+
+    let mut written = 0;
+    while let Some(len) = fragmenter.next(buffer) {
+        framed
+            .write_all(&buffer[..len])
+            .await
+            .context("failed to write display update")?;
+        written += len;
+    }
+
+    Ok(written)
+}
@@ -0,0 +1,116 @@
+use std::future::Future;
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use ironrdp_core::encode_vec;
+use ironrdp_pdu::mcs::{self, SendDataIndication};
+use ironrdp_pdu::rdp::client_info::CompressionType;
+use ironrdp_pdu::rdp::headers::{
+    CompressionFlags, ShareControlHeader, ShareControlPdu, ShareDataHeader, ShareDataPdu, StreamPriority,
+};
+use ironrdp_pdu::rdp::server_error_info::{ErrorInfo, ProtocolIndependentCode, ServerSetErrorInfoPdu};
+use ironrdp_pdu::x224::X224;
+use tokio::sync::{mpsc, oneshot};
+use tokio::time;
+
+use crate::ServerEvent;
+
+/// Request to gracefully shut the server down, sent using a [`ShutdownHandle`]
+#[derive(Debug)]
+pub struct ShutdownRequest {
+    pub(crate) reason: ErrorInfo,
+    pub(crate) timeout: Duration,
+    pub(crate) done: oneshot::Sender<()>,
+}
+
+/// Handle used to gracefully shut a running [`RdpServer`](crate::RdpServer) down
+///
+/// Contrary to [`ServerEvent::Quit`], the connected client is notified of the disconnection reason,
+/// and given a chance to close its channels before the connection is dropped.
+#[derive(Debug, Clone)]
+pub struct ShutdownHandle {
+    ev_sender: mpsc::UnboundedSender<ServerEvent>,
+}
+
+impl ShutdownHandle {
+    pub(crate) fn new(ev_sender: mpsc::UnboundedSender<ServerEvent>) -> Self {
+        Self { ev_sender }
+    }
+
+    /// Shuts the server down, disconnecting the client with the administrative disconnection reason
+    pub async fn shutdown(&self, timeout: Duration) -> Result<()> {
+        self.shutdown_with_reason(
+            ErrorInfo::ProtocolIndependentCode(ProtocolIndependentCode::RpcInitiatedDisconnect),
+            timeout,
+        )
+        .await
+    }
+
+    /// Shuts the server down, disconnecting the client with the given reason
+    ///
+    /// The client is sent a Set Error Info PDU followed by a Disconnect Provider Ultimatum,
+    /// once its channels are closed or `timeout` elapsed.
+    /// Resolves when the session is closed and the server stopped listening.
+    pub async fn shutdown_with_reason(&self, reason: ErrorInfo, timeout: Duration) -> Result<()> {
+        let (done, stopped) = oneshot::channel();
+
+        self.ev_sender
+            .send(ServerEvent::Shutdown(ShutdownRequest { reason, timeout, done }))
+            .map_err(|_| anyhow!("server is not running"))?;
+
+        stopped
+            .await
+            .map_err(|_| anyhow!("server stopped before completing the shutdown"))
+    }
+}
+
+/// Waits for the client to close its channels, for at most `timeout`
+///
+/// `teardown` resolves to whether the client is still connected once its channels are closed.
+/// Returns whether the disconnection PDUs should be sent, which is the case if the teardown timed out.
+pub async fn wait_teardown<F>(teardown: F, timeout: Duration) -> bool
+where
+    F: Future<Output = Result<bool>>,
+{
+    match time::timeout(timeout, teardown).await {
+        Ok(Ok(connected)) => {
+            if !connected {
+                debug!("Client disconnected during the channels teardown");
+            }
+            connected
+        }
+        Ok(Err(error)) => {
+            debug!(?error, "Channels teardown failed");
+            false
+        }
+        Err(_) => {
+            warn!("Timed out waiting for the channels teardown");
+            true
+        }
+    }
+}
+
+/// Encodes the Set Error Info PDU carrying `reason`, followed by the Disconnect Provider Ultimatum
+pub fn encode_disconnection(reason: ErrorInfo, io_channel_id: u16, user_channel_id: u16) -> Result<Vec<u8>> {
+    let set_error_info = ShareControlHeader {
+        share_id: 0,
+        pdu_source: io_channel_id,
+        share_control_pdu: ShareControlPdu::Data(ShareDataHeader {
+            share_data_pdu: ShareDataPdu::ServerSetErrorInfo(ServerSetErrorInfoPdu(reason)),
+            stream_priority: StreamPriority::Undefined,
+            compression_flags: CompressionFlags::empty(),
+            compression_type: CompressionType::K8,
+        }),
+    };
+    let pdu = SendDataIndication {
+        initiator_id: user_channel_id,
+        channel_id: io_channel_id,
+        user_data: encode_vec(&set_error_info)?.into(),
+    };
+    let mut pdus = encode_vec(&X224(pdu))?;
+
+    let ultimatum = mcs::DisconnectProviderUltimatum::from_reason(mcs::DisconnectReason::ProviderInitiated);
+    pdus.extend(encode_vec(&X224(ultimatum))?);
+
+    Ok(pdus)
+}
@@ -1,7 +1,226 @@
+use std::time::Duration;
+
+use ironrdp_rdpsnd::pdu::{AudioFormat, ClientAudioFormatPdu, WaveFormat};
 pub use ironrdp_rdpsnd::server::{RdpsndServerHandler, RdpsndServerMessage};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio::time;
 
-use crate::ServerEventSender;
+use crate::{ServerEvent, ServerEventSender};
 
 pub trait SoundServerFactory: ServerEventSender {
     fn build_backend(&self) -> Box<dyn RdpsndServerHandler>;
 }
+
+/// Chunk of audio samples produced by an [`AudioSource`]
+#[derive(Debug, Clone)]
+pub struct AudioFrame {
+    /// Samples encoded using the format passed to [`AudioSource::start`]
+    pub data: Vec<u8>,
+    /// Capture timestamp, in milliseconds
+    ///
+    /// When `None`, the timestamp is derived from the amount of audio data streamed so far.
+    pub timestamp: Option<u32>,
+}
+
+/// Audio source streamed to the client over the RDPSND channel
+///
+/// Frames are pulled using [`AudioSource::next_frame`].
+/// For push-based capture APIs, see [`ChannelAudioSource`].
+#[async_trait::async_trait]
+pub trait AudioSource: Send {
+    /// Audio formats this source is able to produce, in order of preference
+    fn formats(&self) -> &[AudioFormat];
+
+    /// Called once the format to use has been negotiated with the client
+    fn start(&mut self, format: &AudioFormat);
+
+    /// Returns the next audio frame, or `None` once the source is exhausted
+    ///
+    /// This method must be cancel safe: it is cancelled when no frame is produced before the underrun timeout.
+    async fn next_frame(&mut self) -> Option<AudioFrame>;
+}
+
+/// [`AudioSource`] fed with frames pushed through a channel
+pub struct ChannelAudioSource {
+    formats: Vec<AudioFormat>,
+    receiver: mpsc::Receiver<AudioFrame>,
+}
+
+impl ChannelAudioSource {
+    /// Creates a new source, and the sender to push frames with
+    pub fn new(formats: Vec<AudioFormat>, capacity: usize) -> (Self, mpsc::Sender<AudioFrame>) {
+        let (sender, receiver) = mpsc::channel(capacity);
+        (Self { formats, receiver }, sender)
+    }
+}
+
+#[async_trait::async_trait]
+impl AudioSource for ChannelAudioSource {
+    fn formats(&self) -> &[AudioFormat] {
+        &self.formats
+    }
+
+    fn start(&mut self, _format: &AudioFormat) {}
+
+    async fn next_frame(&mut self) -> Option<AudioFrame> {
+        self.receiver.recv().await
+    }
+}
+
+type AudioSourceBuilder = dyn Fn() -> Box<dyn AudioSource> + Send + Sync;
+
+/// [`SoundServerFactory`] streaming an [`AudioSource`] to each client
+///
+/// When the source does not produce any frame for longer than the underrun timeout, silence is
+/// sent instead (PCM only) so the client playback buffer does not run dry.
+pub struct AudioSourceSoundFactory {
+    build_source: Box<AudioSourceBuilder>,
+    underrun_timeout: Duration,
+    sender: Option<mpsc::UnboundedSender<ServerEvent>>,
+}
+
+impl AudioSourceSoundFactory {
+    const DEFAULT_UNDERRUN_TIMEOUT: Duration = Duration::from_millis(100);
+
+    /// Creates a factory building a new audio source for each connection
+    pub fn new<F>(build_source: F) -> Self
+    where
+        F: Fn() -> Box<dyn AudioSource> + Send + Sync + 'static,
+    {
+        Self {
+            build_source: Box::new(build_source),
+            underrun_timeout: Self::DEFAULT_UNDERRUN_TIMEOUT,
+            sender: None,
+        }
+    }
+
+    #[must_use]
+    pub fn with_underrun_timeout(mut self, underrun_timeout: Duration) -> Self {
+        self.underrun_timeout = underrun_timeout;
+        self
+    }
+}
+
+impl ServerEventSender for AudioSourceSoundFactory {
+    fn set_sender(&mut self, sender: mpsc::UnboundedSender<ServerEvent>) {
+        self.sender = Some(sender);
+    }
+}
+
+impl SoundServerFactory for AudioSourceSoundFactory {
+    fn build_backend(&self) -> Box<dyn RdpsndServerHandler> {
+        let source = (self.build_source)();
+
+        Box::new(AudioSourceHandler {
+            formats: source.formats().to_vec(),
+            source: Some(source),
+            underrun_timeout: self.underrun_timeout,
+            sender: self.sender.clone(),
+            task: None,
+        })
+    }
+}
+
+struct AudioSourceHandler {
+    formats: Vec<AudioFormat>,
+    source: Option<Box<dyn AudioSource>>,
+    underrun_timeout: Duration,
+    sender: Option<mpsc::UnboundedSender<ServerEvent>>,
+    task: Option<JoinHandle<()>>,
+}
+
+impl core::fmt::Debug for AudioSourceHandler {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("AudioSourceHandler")
+            .field("formats", &self.formats)
+            .field("underrun_timeout", &self.underrun_timeout)
+            .finish_non_exhaustive()
+    }
+}
+
+impl RdpsndServerHandler for AudioSourceHandler {
+    fn get_formats(&self) -> &[AudioFormat] {
+        &self.formats
+    }
+
+    fn start(&mut self, client_format: &ClientAudioFormatPdu) -> Option<u16> {
+        // The format number is an index into the list of formats sent by the client.
+        let Some((format_no, format)) = self.formats.iter().find_map(|format| {
+            client_format
+                .formats
+                .iter()
+                .position(|client| client == format)
+                .map(|idx| (idx, format.clone()))
+        }) else {
+            warn!("No audio format in common with the client");
+            return None;
+        };
+
+        let format_no = u16::try_from(format_no).ok()?;
+
+        let Some(sender) = self.sender.clone() else {
+            warn!("No server event sender, audio source not started");
+            return None;
+        };
+
+        let mut source = self.source.take()?;
+        let underrun_timeout = self.underrun_timeout;
+
+        self.task = Some(tokio::spawn(async move {
+            source.start(&format);
+
+            let mut streamed_len: u64 = 0;
+
+            loop {
+                let frame = match time::timeout(underrun_timeout, source.next_frame()).await {
+                    Ok(Some(frame)) => frame,
+                    Ok(None) => break,
+                    Err(_) => {
+                        trace!("Audio source underrun");
+
+                        if format.format != WaveFormat::PCM {
+                            continue;
+                        }
+
+                        AudioFrame {
+                            data: vec![0; duration_to_len(&format, underrun_timeout)],
+                            timestamp: None,
+                        }
+                    }
+                };
+
+                let timestamp = frame.timestamp.unwrap_or_else(|| len_to_ms(&format, streamed_len));
+                streamed_len = streamed_len.saturating_add(u64::try_from(frame.data.len()).unwrap_or(u64::MAX));
+
+                let message = ServerEvent::Rdpsnd(RdpsndServerMessage::Wave(frame.data, timestamp));
+                if sender.send(message).is_err() {
+                    break;
+                }
+            }
+
+            let _ = sender.send(ServerEvent::Rdpsnd(RdpsndServerMessage::Close));
+        }));
+
+        Some(format_no)
+    }
+
+    fn stop(&mut self) {
+        if let Some(task) = self.task.take() {
+            task.abort();
+        }
+    }
+}
+
+/// Number of bytes required to hold `duration` worth of samples, aligned on the block size
+fn duration_to_len(format: &AudioFormat, duration: Duration) -> usize {
+    let len = u128::from(format.n_avg_bytes_per_sec) * duration.as_millis() / 1000;
+    let block_align = u128::from(format.n_block_align.max(1));
+    usize::try_from(len - len % block_align).unwrap_or(0)
+}
+
+/// Playback position, in milliseconds, after `len` bytes of audio
+#[allow(clippy::cast_possible_truncation)] // RDPSND timestamps are wrapping around.
+fn len_to_ms(format: &AudioFormat, len: u64) -> u32 {
+    (u128::from(len) * 1000 / u128::from(format.n_avg_bytes_per_sec.max(1))) as u32
+}
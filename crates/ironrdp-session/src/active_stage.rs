@@ -2,15 +2,17 @@ use std::rc::Rc;
 
 use ironrdp_connector::connection_activation::ConnectionActivationSequence;
 use ironrdp_connector::ConnectionResult;
-use ironrdp_core::WriteBuf;
+use ironrdp_core::{DecodeLimits, DecodeMode, EncodeResult, WriteBuf};
 use ironrdp_displaycontrol::client::DisplayControlClient;
+use ironrdp_displaycontrol::pdu::MonitorLayoutEntry;
 use ironrdp_dvc::{DrdynvcClient, DvcProcessor, DynamicVirtualChannel};
 use ironrdp_graphics::pointer::DecodedPointer;
 use ironrdp_pdu::geometry::InclusiveRectangle;
 use ironrdp_pdu::input::fast_path::{FastPathInput, FastPathInputEvent};
 use ironrdp_pdu::rdp::headers::ShareDataPdu;
-use ironrdp_pdu::{mcs, Action};
-use ironrdp_svc::{SvcProcessor, SvcProcessorMessages};
+use ironrdp_pdu::rdp::session_info::ServerAutoReconnect;
+use ironrdp_pdu::{gcc, mcs, Action};
+use ironrdp_svc::{SvcMessage, SvcProcessor, SvcProcessorMessages};
 
 use crate::fast_path::UpdateKind;
 use crate::image::DecodedImage;
@@ -20,6 +22,8 @@ pub struct ActiveStage {
     x224_processor: x224::Processor,
     fast_path_processor: fast_path::Processor,
     no_server_pointer: bool,
+    decode_mode: DecodeMode,
+    decode_limits: DecodeLimits,
 }
 
 impl ActiveStage {
@@ -43,6 +47,8 @@ impl ActiveStage {
             x224_processor,
             fast_path_processor,
             no_server_pointer: connection_result.no_server_pointer,
+            decode_mode: connection_result.decode_mode,
+            decode_limits: connection_result.decode_limits,
         }
     }
 
@@ -103,6 +109,18 @@ impl ActiveStage {
         image: &mut DecodedImage,
         action: Action,
         frame: &[u8],
+    ) -> SessionResult<Vec<ActiveStageOutput>> {
+        ironrdp_core::with_decode_options(
+            ironrdp_connector::decode_options(self.decode_mode, self.decode_limits),
+            || self.process_impl(image, action, frame),
+        )
+    }
+
+    fn process_impl(
+        &mut self,
+        image: &mut DecodedImage,
+        action: Action,
+        frame: &[u8],
     ) -> SessionResult<Vec<ActiveStageOutput>> {
         let (mut stage_outputs, processor_updates) = match action {
             Action::FastPath => {
@@ -156,6 +174,14 @@ impl ActiveStage {
         self.no_server_pointer = no_server_pointer;
     }
 
+    /// Auto-reconnect cookie most recently received from the server, if any
+    ///
+    /// It can be set in the connector [`Config`](ironrdp_connector::Config) to reconnect to the same session
+    /// after a network failure.
+    pub fn auto_reconnect(&self) -> Option<&ServerAutoReconnect> {
+        self.x224_processor.auto_reconnect()
+    }
+
     /// Encodes client-side graceful shutdown request. Note that upon sending this request,
     /// client should wait for server's ShutdownDenied PDU before closing the connection.
     ///
@@ -217,18 +243,34 @@ impl ActiveStage {
         height: u32,
         scale_factor: Option<u32>,
         physical_dims: Option<(u32, u32)>,
+    ) -> Option<SessionResult<Vec<u8>>> {
+        self.encode_display_control(|display_control, channel_id| {
+            display_control.encode_single_primary_monitor(channel_id, width, height, scale_factor, physical_dims)
+        })
+    }
+
+    /// Fully encodes a monitor layout change for sending over the Display Control Virtual Channel.
+    ///
+    /// This is the multi-monitor counterpart of [`ActiveStage::encode_resize`]. Exactly one of the `monitors`
+    /// must be primary.
+    ///
+    /// If the Display Control Virtual Channel is not available, or not yet connected, this method
+    /// will return `None`.
+    pub fn encode_monitor_layout(&mut self, monitors: &[MonitorLayoutEntry]) -> Option<SessionResult<Vec<u8>>> {
+        self.encode_display_control(|display_control, channel_id| {
+            display_control.encode_monitor_layout(channel_id, monitors)
+        })
+    }
+
+    fn encode_display_control(
+        &mut self,
+        encode: impl FnOnce(&DisplayControlClient, u32) -> EncodeResult<Vec<SvcMessage>>,
     ) -> Option<SessionResult<Vec<u8>>> {
         if let Some(dvc) = self.get_dvc::<DisplayControlClient>() {
             if dvc.is_open() {
                 let display_control = dvc.channel_processor_downcast_ref::<DisplayControlClient>()?;
                 let channel_id = dvc.channel_id().unwrap(); // Safe to unwrap, as we checked if the channel is open
-                let svc_messages = match display_control.encode_single_primary_monitor(
-                    channel_id,
-                    width,
-                    height,
-                    scale_factor,
-                    physical_dims,
-                ) {
+                let svc_messages = match encode(display_control, channel_id) {
                     Ok(messages) => messages,
                     Err(e) => return Some(Err(SessionError::encode(e))),
                 };
@@ -237,10 +279,10 @@ impl ActiveStage {
                     self.process_svc_processor_messages(SvcProcessorMessages::<DrdynvcClient>::new(svc_messages)),
                 );
             } else {
-                debug!("Could not encode a resize: Display Control Virtual Channel is not yet connected");
+                debug!("Could not encode a monitor layout: Display Control Virtual Channel is not yet connected");
             }
         } else {
-            debug!("Could not encode a resize: Display Control Virtual Channel is not available");
+            debug!("Could not encode a monitor layout: Display Control Virtual Channel is not available");
         }
 
         None
@@ -257,6 +299,7 @@ pub enum ActiveStageOutput {
     PointerBitmap(Rc<DecodedPointer>),
     Terminate(GracefulDisconnectReason),
     DeactivateAll(Box<ConnectionActivationSequence>),
+    MonitorLayout(Vec<gcc::Monitor>),
 }
 
 impl TryFrom<x224::ProcessorOutput> for ActiveStageOutput {
@@ -275,6 +318,7 @@ impl TryFrom<x224::ProcessorOutput> for ActiveStageOutput {
                 Ok(Self::Terminate(reason))
             }
             x224::ProcessorOutput::DeactivateAll(cas) => Ok(Self::DeactivateAll(cas)),
+            x224::ProcessorOutput::MonitorLayout(monitors) => Ok(Self::MonitorLayout(monitors)),
         }
     }
 }
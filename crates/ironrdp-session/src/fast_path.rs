@@ -15,8 +15,10 @@ use ironrdp_pdu::surface_commands::{FrameAction, FrameMarkerPdu, SurfaceCommand}
 
 use crate::image::DecodedImage;
 use crate::pointer::PointerCache;
+#[cfg(feature = "rfx")]
+use crate::rfx;
 use crate::utils::CodecId;
-use crate::{rfx, SessionError, SessionErrorExt, SessionResult};
+use crate::{SessionError, SessionErrorExt, SessionErrorKind, SessionResult};
 
 #[derive(Debug)]
 pub enum UpdateKind {
@@ -30,6 +32,7 @@ pub enum UpdateKind {
 
 pub struct Processor {
     complete_data: CompleteData,
+    #[cfg(feature = "rfx")]
     rfx_handler: rfx::DecodingContext,
     marker_processor: FrameMarkerProcessor,
     bitmap_stream_decoder: BitmapStreamDecoder,
@@ -311,6 +314,7 @@ impl Processor {
         output: &mut WriteBuf,
         surface_commands: Vec<SurfaceCommand<'_>>,
     ) -> SessionResult<InclusiveRectangle> {
+        #[cfg_attr(not(feature = "rfx"), allow(unused_mut))] // only RemoteFX frames are accumulated
         let mut update_rectangle = InclusiveRectangle::empty();
 
         for command in surface_commands {
@@ -319,10 +323,11 @@ impl Processor {
                     trace!("Surface bits");
 
                     let codec_id = CodecId::from_u8(bits.extended_bitmap_data.codec_id).ok_or_else(|| {
-                        reason_err!(
+                        SessionError::new(
                             "Fast-Path",
-                            "unexpected codec ID: {:x}",
-                            bits.extended_bitmap_data.codec_id
+                            SessionErrorKind::UnsupportedCodec {
+                                codec_id: bits.extended_bitmap_data.codec_id,
+                            },
                         )
                     })?;
 
@@ -349,6 +354,7 @@ impl Processor {
                                 }
                             }
                         }
+                        #[cfg(feature = "rfx")]
                         CodecId::RemoteFx => {
                             let mut data = bits.extended_bitmap_data.data;
                             while !data.is_empty() {
@@ -356,6 +362,10 @@ impl Processor {
                                 update_rectangle = update_rectangle.union(&rectangle);
                             }
                         }
+                        #[cfg(not(feature = "rfx"))]
+                        CodecId::RemoteFx => {
+                            return Err(reason_err!("Fast-Path", "RemoteFX support is disabled (`rfx` feature)"));
+                        }
                     }
                 }
                 SurfaceCommand::FrameMarker(marker) => {
@@ -388,6 +398,7 @@ impl ProcessorBuilder {
     pub fn build(self) -> Processor {
         Processor {
             complete_data: CompleteData::new(),
+            #[cfg(feature = "rfx")]
             rfx_handler: rfx::DecodingContext::new(),
             marker_processor: FrameMarkerProcessor::new(self.user_channel_id, self.io_channel_id),
             bitmap_stream_decoder: BitmapStreamDecoder::default(),
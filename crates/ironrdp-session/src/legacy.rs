@@ -9,6 +9,16 @@ impl From<ironrdp_connector::ConnectorErrorKind> for crate::SessionErrorKind {
             ironrdp_connector::ConnectorErrorKind::AccessDenied => panic!("unexpected"),
             ironrdp_connector::ConnectorErrorKind::General => crate::SessionErrorKind::General,
             ironrdp_connector::ConnectorErrorKind::Custom => crate::SessionErrorKind::Custom,
+            ironrdp_connector::ConnectorErrorKind::Encode(e) => crate::SessionErrorKind::Encode(e),
+            ironrdp_connector::ConnectorErrorKind::Decode(e) => crate::SessionErrorKind::Decode(e),
+            ironrdp_connector::ConnectorErrorKind::Reason(reason) => crate::SessionErrorKind::Reason(reason),
+            ironrdp_connector::ConnectorErrorKind::Disconnected(reason) => {
+                crate::SessionErrorKind::Disconnected(reason)
+            }
+            ironrdp_connector::ConnectorErrorKind::ServerErrorInfo(info) => {
+                crate::SessionErrorKind::ServerErrorInfo(info)
+            }
+            ironrdp_connector::ConnectorErrorKind::UnexpectedPdu(name) => crate::SessionErrorKind::UnexpectedPdu(name),
             _ => crate::SessionErrorKind::General,
         }
     }
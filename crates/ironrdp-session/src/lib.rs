@@ -10,6 +10,7 @@ pub mod fast_path;
 pub mod image;
 pub mod legacy;
 pub mod pointer;
+#[cfg(feature = "rfx")]
 pub mod rfx; // FIXME: maybe this module should not be in this crate
 pub mod utils;
 pub mod x224;
@@ -18,6 +19,10 @@ mod active_stage;
 
 use core::fmt;
 
+use ironrdp_error::ErrorCode;
+use ironrdp_pdu::mcs::DisconnectReason;
+use ironrdp_pdu::rdp::server_error_info::{ErrorInfo, RdpSpecificCode};
+
 pub use active_stage::{ActiveStage, ActiveStageOutput, GracefulDisconnectReason};
 
 pub type SessionResult<T> = Result<T, SessionError>;
@@ -31,6 +36,73 @@ pub enum SessionErrorKind {
     Reason(String),
     General,
     Custom,
+    /// The server sent a Set Error Info PDU which is not part of a graceful disconnection
+    ServerErrorInfo(ErrorInfo),
+    /// The server sent an MCS Disconnect Provider Ultimatum
+    Disconnected(DisconnectReason),
+    /// Data was received on a channel which is not joined
+    UnexpectedChannel {
+        channel_id: u16,
+    },
+    /// No static virtual channel is registered for the processor
+    ChannelNotFound,
+    /// A PDU which is not handled in the active stage was received
+    UnexpectedPdu(&'static str),
+    /// Graphics were received with a codec which is not supported
+    UnsupportedCodec {
+        codec_id: u8,
+    },
+}
+
+impl SessionErrorKind {
+    /// Returns the error info code matching this error, if any.
+    ///
+    /// This is the code a server would send in a Set Error Info PDU before disconnecting because of this error,
+    /// and the code received by the client for [`SessionErrorKind::ServerErrorInfo`].
+    ///
+    /// | Error kind              | Error info                 |
+    /// |-------------------------|----------------------------|
+    /// | `ServerErrorInfo(info)` | `info`                     |
+    /// | `UnexpectedChannel`     | `ERRINFO_INVALIDCHANNELID` |
+    /// | `UnexpectedPdu`         | `ERRINFO_UNKNOWNPDUTYPE`   |
+    pub fn error_info(&self) -> Option<ErrorInfo> {
+        match self {
+            SessionErrorKind::ServerErrorInfo(info) => Some(*info),
+            SessionErrorKind::UnexpectedChannel { .. } => {
+                Some(ErrorInfo::RdpSpecificCode(RdpSpecificCode::InvalidChannelId))
+            }
+            SessionErrorKind::UnexpectedPdu(_) => Some(ErrorInfo::RdpSpecificCode(RdpSpecificCode::UnknownPduType)),
+            SessionErrorKind::Pdu(_)
+            | SessionErrorKind::Encode(_)
+            | SessionErrorKind::Decode(_)
+            | SessionErrorKind::Reason(_)
+            | SessionErrorKind::General
+            | SessionErrorKind::Custom
+            | SessionErrorKind::Disconnected(_)
+            | SessionErrorKind::ChannelNotFound
+            | SessionErrorKind::UnsupportedCodec { .. } => None,
+        }
+    }
+}
+
+/// Session error codes are in the `0x300` range.
+impl ErrorCode for SessionErrorKind {
+    fn code(&self) -> u32 {
+        match self {
+            SessionErrorKind::Pdu(_) => 0x301,
+            SessionErrorKind::Encode(_) => 0x302,
+            SessionErrorKind::Decode(_) => 0x303,
+            SessionErrorKind::Reason(_) => 0x304,
+            SessionErrorKind::General => 0x305,
+            SessionErrorKind::Custom => 0x306,
+            SessionErrorKind::ServerErrorInfo(_) => 0x307,
+            SessionErrorKind::Disconnected(_) => 0x308,
+            SessionErrorKind::UnexpectedChannel { .. } => 0x309,
+            SessionErrorKind::ChannelNotFound => 0x30A,
+            SessionErrorKind::UnexpectedPdu(_) => 0x30B,
+            SessionErrorKind::UnsupportedCodec { .. } => 0x30C,
+        }
+    }
 }
 
 impl fmt::Display for SessionErrorKind {
@@ -42,6 +114,14 @@ impl fmt::Display for SessionErrorKind {
             SessionErrorKind::Reason(description) => write!(f, "reason: {description}"),
             SessionErrorKind::General => write!(f, "general error"),
             SessionErrorKind::Custom => write!(f, "custom error"),
+            SessionErrorKind::ServerErrorInfo(info) => write!(f, "{}", info.description()),
+            SessionErrorKind::Disconnected(reason) => write!(f, "disconnected by server: {reason}"),
+            SessionErrorKind::UnexpectedChannel { channel_id } => {
+                write!(f, "unexpected channel received: ID {channel_id}")
+            }
+            SessionErrorKind::ChannelNotFound => write!(f, "channel not found"),
+            SessionErrorKind::UnexpectedPdu(name) => write!(f, "unhandled PDU: {name}"),
+            SessionErrorKind::UnsupportedCodec { codec_id } => write!(f, "unsupported codec ID: {codec_id:x}"),
         }
     }
 }
@@ -55,6 +135,12 @@ impl std::error::Error for SessionErrorKind {
             SessionErrorKind::Reason(_) => None,
             SessionErrorKind::General => None,
             SessionErrorKind::Custom => None,
+            SessionErrorKind::ServerErrorInfo(_) => None,
+            SessionErrorKind::Disconnected(_) => None,
+            SessionErrorKind::UnexpectedChannel { .. } => None,
+            SessionErrorKind::ChannelNotFound => None,
+            SessionErrorKind::UnexpectedPdu(_) => None,
+            SessionErrorKind::UnsupportedCodec { .. } => None,
         }
     }
 }
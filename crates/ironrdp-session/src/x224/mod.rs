@@ -1,15 +1,18 @@
 use ironrdp_connector::connection_activation::ConnectionActivationSequence;
 use ironrdp_connector::legacy::SendDataIndicationCtx;
+use ironrdp_connector::ConnectorErrorKind;
 use ironrdp_core::WriteBuf;
 use ironrdp_dvc::DynamicVirtualChannel;
 use ironrdp_dvc::{DrdynvcClient, DvcProcessor};
+use ironrdp_pdu::gcc;
 use ironrdp_pdu::mcs::{DisconnectProviderUltimatum, DisconnectReason, McsMessage};
 use ironrdp_pdu::rdp::headers::ShareDataPdu;
 use ironrdp_pdu::rdp::server_error_info::{ErrorInfo, ProtocolIndependentCode, ServerSetErrorInfoPdu};
+use ironrdp_pdu::rdp::session_info::{InfoData, LogonInfoExtended, ServerAutoReconnect};
 use ironrdp_pdu::x224::X224;
 use ironrdp_svc::{client_encode_svc_messages, StaticChannelSet, SvcMessage, SvcProcessor, SvcProcessorMessages};
 
-use crate::{SessionError, SessionErrorExt as _, SessionResult};
+use crate::{SessionError, SessionErrorExt as _, SessionErrorKind, SessionResult};
 
 /// X224 Processor output
 #[derive(Debug, Clone)]
@@ -23,6 +26,9 @@ pub enum ProcessorOutput {
     ///
     /// [Deactivation-Reactivation Sequence]: https://learn.microsoft.com/en-us/openspecs/windows_protocols/ms-rdpbcgr/dfc234ce-481a-4674-9a5d-2a7bafb14432
     DeactivateAll(Box<ConnectionActivationSequence>),
+    /// Received a [`ironrdp_pdu::rdp::finalization_messages::MonitorLayoutPdu`]. The server changed the monitor
+    /// layout of the session.
+    MonitorLayout(Vec<gcc::Monitor>),
 }
 
 pub struct Processor {
@@ -30,6 +36,7 @@ pub struct Processor {
     user_channel_id: u16,
     io_channel_id: u16,
     connection_activation: ConnectionActivationSequence,
+    auto_reconnect: Option<ServerAutoReconnect>,
 }
 
 impl Processor {
@@ -44,9 +51,15 @@ impl Processor {
             user_channel_id,
             io_channel_id,
             connection_activation,
+            auto_reconnect: None,
         }
     }
 
+    /// Auto-reconnect cookie most recently received from the server, if any
+    pub fn auto_reconnect(&self) -> Option<&ServerAutoReconnect> {
+        self.auto_reconnect.as_ref()
+    }
+
     pub fn get_svc_processor<T: SvcProcessor + 'static>(&self) -> Option<&T> {
         self.static_channels
             .get_by_type::<T>()
@@ -68,7 +81,7 @@ impl Processor {
         let channel_id = self
             .static_channels
             .get_channel_id_by_type::<C>()
-            .ok_or_else(|| reason_err!("SVC", "channel not found"))?;
+            .ok_or_else(|| SessionError::new("SVC", SessionErrorKind::ChannelNotFound))?;
 
         process_svc_messages(messages.into(), channel_id, self.user_channel_id)
     }
@@ -80,8 +93,18 @@ impl Processor {
     /// Processes a received PDU. Returns a vector of [`ProcessorOutput`] that must be processed
     /// in the returned order.
     pub fn process(&mut self, frame: &[u8]) -> SessionResult<Vec<ProcessorOutput>> {
-        let data_ctx: SendDataIndicationCtx<'_> =
-            ironrdp_connector::legacy::decode_send_data_indication(frame).map_err(crate::legacy::map_error)?;
+        let data_ctx: SendDataIndicationCtx<'_> = match ironrdp_connector::legacy::decode_send_data_indication(frame) {
+            Ok(data_ctx) => data_ctx,
+            Err(e) => {
+                return match e.kind {
+                    ConnectorErrorKind::Disconnected(reason) => {
+                        debug!("Received Disconnect Provider Ultimatum: {reason}");
+                        Ok(vec![ProcessorOutput::Disconnect(reason)])
+                    }
+                    _ => Err(crate::legacy::map_error(e)),
+                };
+            }
+        };
         let channel_id = data_ctx.channel_id;
 
         if channel_id == self.io_channel_id {
@@ -91,11 +114,14 @@ impl Processor {
             process_svc_messages(response_pdus, channel_id, data_ctx.initiator_id)
                 .map(|data| vec![ProcessorOutput::ResponseFrame(data)])
         } else {
-            Err(reason_err!("X224", "unexpected channel received: ID {channel_id}"))
+            Err(SessionError::new(
+                "X224",
+                SessionErrorKind::UnexpectedChannel { channel_id },
+            ))
         }
     }
 
-    fn process_io_channel(&self, data_ctx: SendDataIndicationCtx<'_>) -> SessionResult<Vec<ProcessorOutput>> {
+    fn process_io_channel(&mut self, data_ctx: SendDataIndicationCtx<'_>) -> SessionResult<Vec<ProcessorOutput>> {
         debug_assert_eq!(data_ctx.channel_id, self.io_channel_id);
 
         let io_channel = ironrdp_connector::legacy::decode_io_channel(data_ctx).map_err(crate::legacy::map_error)?;
@@ -105,8 +131,21 @@ impl Processor {
                 match ctx.pdu {
                     ShareDataPdu::SaveSessionInfo(session_info) => {
                         debug!("Got Session Save Info PDU: {session_info:?}");
+
+                        if let InfoData::LogonExtended(LogonInfoExtended {
+                            auto_reconnect: Some(auto_reconnect),
+                            ..
+                        }) = session_info.info_data
+                        {
+                            self.auto_reconnect = Some(auto_reconnect);
+                        }
+
                         Ok(Vec::new())
                     }
+                    ShareDataPdu::MonitorLayout(monitor_layout) => {
+                        debug!(monitors = ?monitor_layout.monitors, "Got Monitor Layout PDU");
+                        Ok(vec![ProcessorOutput::MonitorLayout(monitor_layout.monitors)])
+                    }
                     // FIXME: workaround fix to not terminate the session on "unhandled PDU: Set Keyboard Indicators PDU"
                     ShareDataPdu::SetKeyboardIndicators(data) => {
                         debug!("Got Keyboard Indicators PDU: {data:?}");
@@ -130,7 +169,10 @@ impl Processor {
 
                             Ok(vec![ProcessorOutput::Disconnect(reason)])
                         } else {
-                            Err(reason_err!("ServerSetErrorInfo", "{}", e.description()))
+                            Err(SessionError::new(
+                                "ServerSetErrorInfo",
+                                SessionErrorKind::ServerErrorInfo(e),
+                            ))
                         }
                     }
                     ShareDataPdu::ShutdownDenied => {
@@ -152,10 +194,9 @@ impl Processor {
                             ProcessorOutput::Disconnect(DisconnectReason::UserRequested),
                         ])
                     }
-                    _ => Err(reason_err!(
+                    _ => Err(SessionError::new(
                         "IO channel",
-                        "unhandled PDU: {:?}",
-                        ctx.pdu.as_short_name()
+                        SessionErrorKind::UnexpectedPdu(ctx.pdu.as_short_name()),
                     )),
                 }
             }
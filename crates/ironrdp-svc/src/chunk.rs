@@ -0,0 +1,325 @@
+use ironrdp_core::{
+    decode_cursor, decode_limits, encode_buf, invalid_field_err, tolerate_violation, DecodeLimits, DecodeResult,
+    EncodeResult, ReadCursor, WriteBuf, WriteBufPool,
+};
+use ironrdp_pdu::rdp::vc::ChannelControlFlags;
+
+use crate::{ChannelFlags, ChannelPduHeader, SvcMessage, VcCompressor, VcDecompressor, CHANNEL_CHUNK_LENGTH};
+
+/// The maximum chunk length a server may advertise in the Virtual Channel Capability Set
+///
+/// See also:
+/// - <https://docs.microsoft.com/en-us/openspecs/windows_protocols/ms-rdpbcgr/a8593178-80c0-4b80-876c-cb77e62cecfc>
+pub const MAX_CHANNEL_CHUNK_LENGTH: usize = 16256;
+
+/// The default maximum size of a de-chunkified PDU
+pub const DEFAULT_MAX_PDU_SIZE: usize = DecodeLimits::DEFAULT.max_pdu_size;
+
+/// Returns the chunk length to use given the `chunk_size` of the server Virtual Channel Capability Set
+///
+/// The length defaults to [`CHANNEL_CHUNK_LENGTH`] when the server does not advertise any, and is clamped to the
+/// range allowed by the specification otherwise.
+pub fn negotiated_chunk_length(chunk_size: Option<u32>) -> usize {
+    chunk_size
+        .and_then(|chunk_size| usize::try_from(chunk_size).ok())
+        .map_or(CHANNEL_CHUNK_LENGTH, |chunk_size| {
+            chunk_size.clamp(CHANNEL_CHUNK_LENGTH, MAX_CHANNEL_CHUNK_LENGTH)
+        })
+}
+
+/// ChunkProcessor is used to chunkify/de-chunkify static virtual channel PDUs.
+///
+/// It is shared by all the static virtual channels, so that channel processors always deal with complete PDUs.
+#[derive(Debug)]
+pub struct ChunkProcessor {
+    /// Buffer for de-chunkification of PDUs. Everything bigger than the chunk length (~1600 bytes) is
+    /// usually chunked when transferred over svc.
+    chunked_pdu: Vec<u8>,
+    /// Total length of the PDU being de-chunkified, as announced by its first chunk
+    expected_len: Option<usize>,
+    max_pdu_size: usize,
+}
+
+impl ChunkProcessor {
+    pub fn new() -> Self {
+        Self::with_max_pdu_size(DEFAULT_MAX_PDU_SIZE)
+    }
+
+    /// Creates a processor rejecting chunked PDUs larger than `max_pdu_size` bytes
+    pub fn with_max_pdu_size(max_pdu_size: usize) -> Self {
+        Self {
+            chunked_pdu: Vec::new(),
+            expected_len: None,
+            max_pdu_size,
+        }
+    }
+
+    pub fn max_pdu_size(&self) -> usize {
+        self.max_pdu_size
+    }
+
+    /// Takes a vector of PDUs and breaks them into chunks prefixed with a Channel PDU Header (`CHANNEL_PDU_HEADER`).
+    ///
+    /// Each chunk is at most `max_chunk_len` bytes long (not including the Channel PDU Header), which must be
+    /// between [`CHANNEL_CHUNK_LENGTH`] and [`MAX_CHANNEL_CHUNK_LENGTH`].
+    pub fn chunkify(messages: Vec<SvcMessage>, max_chunk_len: usize) -> EncodeResult<Vec<WriteBuf>> {
+        Self::chunkify_with_pool(messages, max_chunk_len, &mut WriteBufPool::new())
+    }
+
+    /// Same as [`ChunkProcessor::chunkify`], but the chunks and the scratch buffers are taken from `pool`.
+    ///
+    /// The returned chunks are owned by the caller, and may be returned to the pool once sent.
+    pub fn chunkify_with_pool(
+        messages: Vec<SvcMessage>,
+        max_chunk_len: usize,
+        pool: &mut WriteBufPool,
+    ) -> EncodeResult<Vec<WriteBuf>> {
+        Self::chunkify_impl(messages, max_chunk_len, None, pool)
+    }
+
+    /// Same as [`ChunkProcessor::chunkify_with_pool`], but the chunks are compressed using `compressor`.
+    ///
+    /// Messages sent [without compression](SvcMessage::without_compression) are left uncompressed.
+    pub fn chunkify_compressed(
+        messages: Vec<SvcMessage>,
+        max_chunk_len: usize,
+        compressor: &mut dyn VcCompressor,
+        pool: &mut WriteBufPool,
+    ) -> EncodeResult<Vec<WriteBuf>> {
+        Self::chunkify_impl(messages, max_chunk_len, Some(compressor), pool)
+    }
+
+    fn chunkify_impl(
+        messages: Vec<SvcMessage>,
+        max_chunk_len: usize,
+        mut compressor: Option<&mut dyn VcCompressor>,
+        pool: &mut WriteBufPool,
+    ) -> EncodeResult<Vec<WriteBuf>> {
+        if !(CHANNEL_CHUNK_LENGTH..=MAX_CHANNEL_CHUNK_LENGTH).contains(&max_chunk_len) {
+            return Err(invalid_field_err!("maxChunkLength", "chunk length out of range"));
+        }
+
+        let mut encoded_pdu = pool.checkout();
+        let mut results = Vec::new();
+        for message in messages {
+            encoded_pdu.clear();
+            let compressor = if message.compress {
+                compressor.as_deref_mut()
+            } else {
+                None
+            };
+            results.extend(Self::chunkify_one(
+                message,
+                max_chunk_len,
+                compressor,
+                &mut encoded_pdu,
+                pool,
+            )?);
+        }
+        pool.checkin(encoded_pdu);
+        Ok(results)
+    }
+
+    /// Dechunkify a payload received on the virtual channel.
+    ///
+    /// If the payload is not chunked, returns the payload as-is.
+    /// For chunked payloads, returns `Ok(None)` until the last chunk is received, at which point
+    /// it returns `Ok(Some(payload))`.
+    ///
+    /// Fails if the PDU is larger than the maximum PDU size of this processor or of the current
+    /// [`DecodeLimits`], or if the chunks exceed the announced PDU length. Compressed chunks are rejected, see
+    /// [`ChunkProcessor::dechunkify_with_decompressor`].
+    pub fn dechunkify(&mut self, payload: &[u8]) -> DecodeResult<Option<Vec<u8>>> {
+        self.dechunkify_impl(payload, None)
+    }
+
+    /// Same as [`ChunkProcessor::dechunkify`], but each chunk is decompressed using `decompressor`.
+    pub fn dechunkify_with_decompressor(
+        &mut self,
+        payload: &[u8],
+        decompressor: &mut dyn VcDecompressor,
+    ) -> DecodeResult<Option<Vec<u8>>> {
+        self.dechunkify_impl(payload, Some(decompressor))
+    }
+
+    fn dechunkify_impl(
+        &mut self,
+        payload: &[u8],
+        decompressor: Option<&mut dyn VcDecompressor>,
+    ) -> DecodeResult<Option<Vec<u8>>> {
+        let mut cursor = ReadCursor::new(payload);
+        let channel_header: ironrdp_pdu::rdp::vc::ChannelPduHeader = decode_cursor(&mut cursor)?;
+
+        let mut decompressed = Vec::new();
+        let data = match decompressor {
+            Some(decompressor) => {
+                let flags = ChannelFlags::from_bits_truncate(channel_header.flags.bits());
+                decompressor.decompress(flags, cursor.remaining(), &mut decompressed)?;
+                decompressed.as_slice()
+            }
+            None if channel_header.flags.contains(ChannelControlFlags::PACKET_COMPRESSED) => {
+                self.reset();
+                return Err(invalid_field_err!(
+                    "flags",
+                    "compressed chunk without bulk decompressor"
+                ));
+            }
+            None => cursor.remaining(),
+        };
+
+        let first = channel_header.flags.contains(ChannelControlFlags::FLAG_FIRST);
+        let last = channel_header.flags.contains(ChannelControlFlags::FLAG_LAST);
+
+        let expected_len = match self.expected_len {
+            Some(_) if first => {
+                tolerate_violation!(
+                    "flags",
+                    "first chunk received before the last chunk of the previous PDU"
+                )?;
+                self.start_pdu(channel_header.length)?
+            }
+            Some(expected_len) => expected_len,
+            None => {
+                if !first {
+                    tolerate_violation!("flags", "chunk received without a first chunk")?;
+                }
+                self.start_pdu(channel_header.length)?
+            }
+        };
+
+        if self.chunked_pdu.len().saturating_add(data.len()) > expected_len {
+            self.reset();
+            return Err(invalid_field_err!("length", "chunks exceed the announced PDU length"));
+        }
+
+        // Extend the chunked_pdu buffer with the payload
+        self.chunked_pdu.extend_from_slice(data);
+
+        // If this was an unchunked message, or the last in a series of chunks, return the payload
+        if last {
+            if self.chunked_pdu.len() != expected_len {
+                tolerate_violation!("length", "chunks are shorter than the announced PDU length")?;
+            }
+
+            self.expected_len = None;
+
+            // Take the chunked_pdu buffer and replace it with an empty one
+            return Ok(Some(core::mem::take(&mut self.chunked_pdu)));
+        }
+
+        // This was an intermediate chunk, return None
+        Ok(None)
+    }
+
+    /// Starts the de-chunkification of a new PDU, and returns its total length
+    fn start_pdu(&mut self, length: u32) -> DecodeResult<usize> {
+        self.reset();
+
+        let length = usize::try_from(length).map_err(|_| invalid_field_err!("length", "PDU too large"))?;
+
+        if length > self.max_pdu_size.min(decode_limits().max_pdu_size) {
+            return Err(invalid_field_err!("length", "PDU exceeds the maximum size"));
+        }
+
+        self.expected_len = Some(length);
+
+        Ok(length)
+    }
+
+    fn reset(&mut self) {
+        self.chunked_pdu.clear();
+        self.expected_len = None;
+    }
+
+    /// Takes a single PDU and breaks it into chunks prefixed with a [`ChannelPduHeader`].
+    ///
+    /// Each chunk is at most `max_chunk_len` bytes long (not including the Channel PDU Header).
+    ///
+    /// For example, if the PDU is 4000 bytes long and `max_chunk_len` is 1600, this function will
+    /// return 3 chunks, each 1600 bytes long, and the last chunk will be 800 bytes long.
+    ///
+    /// [[ Channel PDU Header | 1600 bytes of PDU data ] [ Channel PDU Header | 1600 bytes of PDU data ] [ Channel PDU Header | 800 bytes of PDU data ]]
+    fn chunkify_one(
+        message: SvcMessage,
+        max_chunk_len: usize,
+        mut compressor: Option<&mut (dyn VcCompressor + '_)>,
+        encoded_pdu: &mut WriteBuf,
+        pool: &mut WriteBufPool,
+    ) -> EncodeResult<Vec<WriteBuf>> {
+        encode_buf(message.pdu.as_ref(), encoded_pdu)?;
+
+        let mut chunks = Vec::new();
+        let mut compressed = Vec::new();
+
+        let total_len = encoded_pdu.filled_len();
+        let mut chunk_start_index: usize = 0;
+        let mut chunk_end_index = core::cmp::min(total_len, max_chunk_len);
+        loop {
+            // Take a buffer to hold this next chunk.
+            let mut chunk = pool.checkout();
+
+            // Set the first and last flags if this is the first and/or last chunk for this PDU.
+            let first = chunk_start_index == 0;
+            let last = chunk_end_index == total_len;
+
+            let data = &encoded_pdu[chunk_start_index..chunk_end_index];
+
+            // Compress this chunk, if it's worth it according to the compressor.
+            let compression_flags = match compressor.as_deref_mut() {
+                Some(compressor) => {
+                    compressed.clear();
+                    compressor.compress(data, &mut compressed)?
+                }
+                None => ChannelFlags::empty(),
+            };
+
+            let data = if compression_flags.contains(ChannelFlags::COMPRESSED) {
+                compressed.as_slice()
+            } else {
+                data
+            };
+
+            // Create the header for this chunk.
+            let header = {
+                let mut flags = ChannelFlags::empty();
+                if first {
+                    flags |= ChannelFlags::FIRST;
+                }
+                if last {
+                    flags |= ChannelFlags::LAST;
+                }
+
+                flags |= message.flags | compression_flags;
+
+                ChannelPduHeader {
+                    length: ironrdp_core::cast_int!(ChannelPduHeader::NAME, "length", total_len)?,
+                    flags,
+                }
+            };
+
+            // Encode the header for this chunk.
+            encode_buf(&header, &mut chunk)?;
+            // Append the piece of the encoded_pdu that belongs in this chunk.
+            chunk.write_slice(data);
+            // Push the chunk onto the results.
+            chunks.push(chunk);
+
+            // If this was the last chunk, we're done, return the results.
+            if last {
+                break;
+            }
+
+            // Otherwise, update the chunk start and end indices for the next iteration.
+            chunk_start_index = chunk_end_index;
+            chunk_end_index = core::cmp::min(total_len, chunk_end_index.saturating_add(max_chunk_len));
+        }
+
+        Ok(chunks)
+    }
+}
+
+impl Default for ChunkProcessor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
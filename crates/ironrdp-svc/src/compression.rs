@@ -0,0 +1,31 @@
+use core::fmt;
+
+use ironrdp_core::{assert_obj_safe, DecodeResult, EncodeResult};
+
+use crate::ChannelFlags;
+
+/// Bulk compressor for the virtual channel data, as specified in section 3.1.8 of MS-RDPBCGR
+///
+/// Chunks are compressed one by one, in the order they are sent, with a history shared by all the static virtual
+/// channels of the connection.
+pub trait VcCompressor: fmt::Debug + Send {
+    /// Compresses a chunk of virtual channel data into `dst`.
+    ///
+    /// Returns the flags to set in the Channel PDU Header, among [`ChannelFlags::COMPRESSED`],
+    /// [`ChannelFlags::AT_FRONT`], [`ChannelFlags::FLUSHED`] and the compression type. The chunk is sent as is,
+    /// ignoring `dst`, when [`ChannelFlags::COMPRESSED`] is not returned.
+    fn compress(&mut self, src: &[u8], dst: &mut Vec<u8>) -> EncodeResult<ChannelFlags>;
+}
+
+assert_obj_safe!(VcCompressor);
+
+/// Bulk decompressor for the virtual channel data, as specified in section 3.1.8 of MS-RDPBCGR
+pub trait VcDecompressor: fmt::Debug + Send {
+    /// Decompresses a chunk of virtual channel data into `dst`, `flags` being the ones of its Channel PDU Header.
+    ///
+    /// Called for every chunk received: uncompressed chunks are appended to `dst` as is, after resetting the
+    /// history if [`ChannelFlags::FLUSHED`] is set.
+    fn decompress(&mut self, flags: ChannelFlags, src: &[u8], dst: &mut Vec<u8>) -> DecodeResult<()>;
+}
+
+assert_obj_safe!(VcDecompressor);
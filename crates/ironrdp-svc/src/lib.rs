@@ -3,6 +3,9 @@
 
 extern crate alloc;
 
+#[macro_use]
+extern crate tracing;
+
 use ironrdp_pdu::x224::X224;
 // Re-export ironrdp_pdu crate for convenience
 #[rustfmt::skip] // do not re-order this pub use
@@ -16,12 +19,22 @@ use std::borrow::Cow;
 use std::marker::PhantomData;
 
 use bitflags::bitflags;
-use ironrdp_core::{assert_obj_safe, DecodeResult, EncodeResult, ReadCursor, WriteBuf, WriteCursor};
-use ironrdp_core::{decode_cursor, encode_buf, Encode};
+use ironrdp_core::{assert_obj_safe, EncodeResult, WriteBuf, WriteBufPool, WriteCursor};
+use ironrdp_core::{encode_buf, Encode};
 use ironrdp_pdu::gcc::{ChannelName, ChannelOptions};
 use ironrdp_pdu::{decode_err, mcs, PduResult};
 use pdu::gcc::ChannelDef;
-use pdu::rdp::vc::ChannelControlFlags;
+use wire_log::ChannelWireLog;
+
+mod chunk;
+mod compression;
+pub mod wire_log;
+
+#[cfg(feature = "testing")]
+pub mod testing;
+
+pub use self::chunk::{negotiated_chunk_length, ChunkProcessor, DEFAULT_MAX_PDU_SIZE, MAX_CHANNEL_CHUNK_LENGTH};
+pub use self::compression::{VcCompressor, VcDecompressor};
 
 /// The integer type representing a static virtual channel ID.
 pub type StaticChannelId = u16;
@@ -69,6 +82,7 @@ impl SvcEncode for Vec<u8> {}
 pub struct SvcMessage {
     pdu: Box<dyn SvcEncode>,
     flags: ChannelFlags,
+    compress: bool,
 }
 
 impl SvcMessage {
@@ -78,6 +92,15 @@ impl SvcMessage {
         self.flags |= flags;
         self
     }
+
+    /// Sends the message uncompressed, even when the channel data is compressed.
+    ///
+    /// Useful for payloads which are known to compress poorly, such as already compressed file data.
+    #[must_use]
+    pub fn without_compression(mut self) -> Self {
+        self.compress = false;
+        self
+    }
 }
 
 impl<T> From<T> for SvcMessage
@@ -88,6 +111,7 @@ where
         Self {
             pdu: Box::new(pdu),
             flags: ChannelFlags::empty(),
+            compress: true,
         }
     }
 }
@@ -103,18 +127,56 @@ pub enum CompressionCondition {
     Always,
 }
 
+impl CompressionCondition {
+    /// Reads the compression flag of the [`ChannelOptions`] sent along the [`ChannelDef`] structure
+    pub fn from_channel_options(options: ChannelOptions) -> Self {
+        if options.contains(ChannelOptions::COMPRESS) {
+            Self::Always
+        } else if options.contains(ChannelOptions::COMPRESS_RDP) {
+            Self::WhenRdpDataIsCompressed
+        } else {
+            Self::Never
+        }
+    }
+
+    pub fn to_channel_options(self) -> ChannelOptions {
+        match self {
+            Self::Never => ChannelOptions::empty(),
+            Self::WhenRdpDataIsCompressed => ChannelOptions::COMPRESS_RDP,
+            Self::Always => ChannelOptions::COMPRESS,
+        }
+    }
+
+    /// Returns whether the virtual channel data is compressed, given whether RDP data compression was negotiated
+    /// in the Client Info PDU (INFO_COMPRESSION)
+    pub fn is_compressed(self, rdp_compression: bool) -> bool {
+        match self {
+            Self::Never => false,
+            Self::WhenRdpDataIsCompressed => rdp_compression,
+            Self::Always => true,
+        }
+    }
+}
+
 /// A static virtual channel.
 #[derive(Debug)]
 pub struct StaticVirtualChannel {
     channel_processor: Box<dyn SvcProcessor>,
     chunk_processor: ChunkProcessor,
+    compression_condition: CompressionCondition,
+    wire_log: ChannelWireLog,
 }
 
 impl StaticVirtualChannel {
     pub fn new<T: SvcProcessor + 'static>(channel_processor: T) -> Self {
+        let chunk_processor = ChunkProcessor::with_max_pdu_size(channel_processor.max_pdu_size());
+        let compression_condition = channel_processor.compression_condition();
+
         Self {
             channel_processor: Box::new(channel_processor),
-            chunk_processor: ChunkProcessor::new(),
+            chunk_processor,
+            compression_condition,
+            wire_log: ChannelWireLog::new(),
         }
     }
 
@@ -123,18 +185,61 @@ impl StaticVirtualChannel {
     }
 
     pub fn compression_condition(&self) -> CompressionCondition {
-        self.channel_processor.compression_condition()
+        self.compression_condition
+    }
+
+    /// Overrides the compression condition of the channel processor.
+    ///
+    /// Must be called before the channel definitions are sent, in the Client Network Data.
+    pub fn set_compression_condition(&mut self, condition: CompressionCondition) {
+        self.compression_condition = condition;
     }
 
     pub fn start(&mut self) -> PduResult<Vec<SvcMessage>> {
-        self.channel_processor.start()
+        let channel_name = self.channel_name();
+        let channel = channel_name.as_str().unwrap_or("<invalid>");
+        let _span = debug_span!("svc", channel).entered();
+
+        let messages = self.channel_processor.start()?;
+        self.log_sent(channel, &messages);
+
+        Ok(messages)
     }
 
     /// Processes a payload received on the virtual channel. Returns a vector of PDUs to be sent back
     /// to the server. If no PDUs are to be sent, an empty vector is returned.
     pub fn process(&mut self, payload: &[u8]) -> PduResult<Vec<SvcMessage>> {
-        if let Some(payload) = self.dechunkify(payload).map_err(|e| decode_err!(e))? {
-            return self.channel_processor.process(&payload);
+        self.process_impl(payload, None)
+    }
+
+    /// Same as [`Self::process`], but compressed chunks are decompressed using `decompressor`.
+    pub fn process_with_decompressor(
+        &mut self,
+        payload: &[u8],
+        decompressor: &mut dyn VcDecompressor,
+    ) -> PduResult<Vec<SvcMessage>> {
+        self.process_impl(payload, Some(decompressor))
+    }
+
+    fn process_impl(
+        &mut self,
+        payload: &[u8],
+        decompressor: Option<&mut dyn VcDecompressor>,
+    ) -> PduResult<Vec<SvcMessage>> {
+        let channel_name = self.channel_name();
+        let channel = channel_name.as_str().unwrap_or("<invalid>");
+        let _span = debug_span!("svc", channel).entered();
+
+        let payload = match decompressor {
+            Some(decompressor) => self.chunk_processor.dechunkify_with_decompressor(payload, decompressor),
+            None => self.chunk_processor.dechunkify(payload),
+        };
+
+        if let Some(payload) = payload.map_err(|e| decode_err!(e))? {
+            self.wire_log.received(channel, &payload);
+            let messages = self.channel_processor.process(&payload)?;
+            self.log_sent(channel, &messages);
+            return Ok(messages);
         }
 
         Ok(Vec::new())
@@ -152,8 +257,10 @@ impl StaticVirtualChannel {
         self.channel_processor.as_any_mut().downcast_mut()
     }
 
-    fn dechunkify(&mut self, payload: &[u8]) -> DecodeResult<Option<Vec<u8>>> {
-        self.chunk_processor.dechunkify(payload)
+    fn log_sent(&mut self, channel: &str, messages: &[SvcMessage]) {
+        for message in messages {
+            self.wire_log.sent(channel, message.pdu.as_ref());
+        }
     }
 }
 
@@ -162,11 +269,12 @@ fn encode_svc_messages(
     channel_id: u16,
     initiator_id: u16,
     client: bool,
-) -> EncodeResult<Vec<u8>> {
-    let mut fully_encoded_responses = WriteBuf::new(); // TODO(perf): reuse this buffer using `clear` and `filled` as appropriate
+    pool: &mut WriteBufPool,
+) -> EncodeResult<WriteBuf> {
+    let mut fully_encoded_responses = pool.checkout();
 
     // For each response PDU, chunkify it and add appropriate static channel headers.
-    let chunks = StaticVirtualChannel::chunkify(messages)?;
+    let chunks = ChunkProcessor::chunkify_with_pool(messages, CHANNEL_CHUNK_LENGTH, pool)?;
 
     // SendData is [`McsPdu`], which is [`x224Pdu`], which is [`Encode`]. [`Encode`] for [`x224Pdu`]
     // also takes care of adding the Tpkt header, so therefore we can just call `encode_buf` on each of these and
@@ -184,6 +292,7 @@ fn encode_svc_messages(
                 user_data: Cow::Borrowed(chunk.filled()),
             };
             encode_buf(&X224(pdu), &mut fully_encoded_responses)?;
+            pool.checkin(chunk);
         }
     } else {
         for chunk in chunks {
@@ -193,10 +302,18 @@ fn encode_svc_messages(
                 user_data: Cow::Borrowed(chunk.filled()),
             };
             encode_buf(&X224(pdu), &mut fully_encoded_responses)?;
+            pool.checkin(chunk);
         }
     }
 
-    Ok(fully_encoded_responses.into_inner())
+    Ok(fully_encoded_responses)
+}
+
+fn into_filled_vec(buf: WriteBuf) -> Vec<u8> {
+    let filled_len = buf.filled_len();
+    let mut inner = buf.into_inner();
+    inner.truncate(filled_len);
+    inner
 }
 
 /// Encode a vector of [`SvcMessage`] in preparation for sending them on the `channel_id` channel.
@@ -210,7 +327,19 @@ pub fn client_encode_svc_messages(
     channel_id: u16,
     initiator_id: u16,
 ) -> EncodeResult<Vec<u8>> {
-    encode_svc_messages(messages, channel_id, initiator_id, true)
+    encode_svc_messages(messages, channel_id, initiator_id, true, &mut WriteBufPool::new()).map(into_filled_vec)
+}
+
+/// Same as [`client_encode_svc_messages`], but the buffers are taken from `pool`.
+///
+/// The encoded messages are in the filled region of the returned buffer, which may be returned to the pool once sent.
+pub fn client_encode_svc_messages_with_pool(
+    messages: Vec<SvcMessage>,
+    channel_id: u16,
+    initiator_id: u16,
+    pool: &mut WriteBufPool,
+) -> EncodeResult<WriteBuf> {
+    encode_svc_messages(messages, channel_id, initiator_id, true, pool)
 }
 
 /// Encode a vector of [`SvcMessage`] in preparation for sending them on the `channel_id` channel.
@@ -224,7 +353,19 @@ pub fn server_encode_svc_messages(
     channel_id: u16,
     initiator_id: u16,
 ) -> EncodeResult<Vec<u8>> {
-    encode_svc_messages(messages, channel_id, initiator_id, false)
+    encode_svc_messages(messages, channel_id, initiator_id, false, &mut WriteBufPool::new()).map(into_filled_vec)
+}
+
+/// Same as [`server_encode_svc_messages`], but the buffers are taken from `pool`.
+///
+/// The encoded messages are in the filled region of the returned buffer, which may be returned to the pool once sent.
+pub fn server_encode_svc_messages_with_pool(
+    messages: Vec<SvcMessage>,
+    channel_id: u16,
+    initiator_id: u16,
+    pool: &mut WriteBufPool,
+) -> EncodeResult<WriteBuf> {
+    encode_svc_messages(messages, channel_id, initiator_id, false, pool)
 }
 
 /// A type that is a Static Virtual Channel
@@ -242,6 +383,13 @@ pub trait SvcProcessor: ironrdp_core::AsAny + fmt::Debug + Send {
         CompressionCondition::Never
     }
 
+    /// Returns the maximum size of a de-chunkified PDU accepted on this channel.
+    ///
+    /// Chunked PDUs announcing a larger size are rejected before being reassembled.
+    fn max_pdu_size(&self) -> usize {
+        DEFAULT_MAX_PDU_SIZE
+    }
+
     /// Start a channel, after the connection is established and the channel is joined.
     ///
     /// Returns a list of PDUs to be sent back.
@@ -266,141 +414,9 @@ pub trait SvcServerProcessor: SvcProcessor {}
 
 assert_obj_safe!(SvcServerProcessor);
 
-/// ChunkProcessor is used to chunkify/de-chunkify static virtual channel PDUs.
-#[derive(Debug)]
-struct ChunkProcessor {
-    /// Buffer for de-chunkification of clipboard PDUs. Everything bigger than ~1600 bytes is
-    /// usually chunked when transferred over svc.
-    chunked_pdu: Vec<u8>,
-}
-
-impl ChunkProcessor {
-    fn new() -> Self {
-        Self {
-            chunked_pdu: Vec::new(),
-        }
-    }
-
-    /// Takes a vector of PDUs and breaks them into chunks prefixed with a Channel PDU Header (`CHANNEL_PDU_HEADER`).
-    ///
-    /// Each chunk is at most `max_chunk_len` bytes long (not including the Channel PDU Header).
-    fn chunkify(messages: Vec<SvcMessage>, max_chunk_len: usize) -> EncodeResult<Vec<WriteBuf>> {
-        let mut results = Vec::new();
-        for message in messages {
-            results.extend(Self::chunkify_one(message, max_chunk_len)?);
-        }
-        Ok(results)
-    }
-
-    /// Dechunkify a payload received on the virtual channel.
-    ///
-    /// If the payload is not chunked, returns the payload as-is.
-    /// For chunked payloads, returns `Ok(None)` until the last chunk is received, at which point
-    /// it returns `Ok(Some(payload))`.
-    fn dechunkify(&mut self, payload: &[u8]) -> DecodeResult<Option<Vec<u8>>> {
-        let mut cursor = ReadCursor::new(payload);
-        let last = Self::process_header(&mut cursor)?;
-
-        // Extend the chunked_pdu buffer with the payload
-        self.chunked_pdu.extend_from_slice(cursor.remaining());
-
-        // If this was an unchunked message, or the last in a series of chunks, return the payload
-        if last {
-            // Take the chunked_pdu buffer and replace it with an empty one
-            return Ok(Some(std::mem::take(&mut self.chunked_pdu)));
-        }
-
-        // This was an intermediate chunk, return None
-        Ok(None)
-    }
-
-    /// Returns whether this was the last chunk based on the flags in the channel header.
-    fn process_header(payload: &mut ReadCursor<'_>) -> DecodeResult<bool> {
-        let channel_header: ironrdp_pdu::rdp::vc::ChannelPduHeader = decode_cursor(payload)?;
-
-        Ok(channel_header.flags.contains(ChannelControlFlags::FLAG_LAST))
-    }
-
-    /// Takes a single PDU and breaks it into chunks prefixed with a [`ChannelPduHeader`].
-    ///
-    /// Each chunk is at most `max_chunk_len` bytes long (not including the Channel PDU Header).
-    ///
-    /// For example, if the PDU is 4000 bytes long and `max_chunk_len` is 1600, this function will
-    /// return 3 chunks, each 1600 bytes long, and the last chunk will be 800 bytes long.
-    ///
-    /// [[ Channel PDU Header | 1600 bytes of PDU data ] [ Channel PDU Header | 1600 bytes of PDU data ] [ Channel PDU Header | 800 bytes of PDU data ]]
-    fn chunkify_one(message: SvcMessage, max_chunk_len: usize) -> EncodeResult<Vec<WriteBuf>> {
-        let mut encoded_pdu = WriteBuf::new(); // TODO(perf): reuse this buffer using `clear` and `filled` as appropriate
-        encode_buf(message.pdu.as_ref(), &mut encoded_pdu)?;
-
-        let mut chunks = Vec::new();
-
-        let total_len = encoded_pdu.filled_len();
-        let mut chunk_start_index: usize = 0;
-        let mut chunk_end_index = std::cmp::min(total_len, max_chunk_len);
-        loop {
-            // Create a buffer to hold this next chunk.
-            // TODO(perf): Reuse this buffer using `clear` and `filled` as appropriate.
-            //             This one will be a bit trickier because we'll need to grow
-            //             the number of chunk buffers if we run out.
-            let mut chunk = WriteBuf::new();
-
-            // Set the first and last flags if this is the first and/or last chunk for this PDU.
-            let first = chunk_start_index == 0;
-            let last = chunk_end_index == total_len;
-
-            // Create the header for this chunk.
-            let header = {
-                let mut flags = ChannelFlags::empty();
-                if first {
-                    flags |= ChannelFlags::FIRST;
-                }
-                if last {
-                    flags |= ChannelFlags::LAST;
-                }
-
-                flags |= message.flags;
-
-                ChannelPduHeader {
-                    length: ironrdp_core::cast_int!(ChannelPduHeader::NAME, "length", total_len)?,
-                    flags,
-                }
-            };
-
-            // Encode the header for this chunk.
-            encode_buf(&header, &mut chunk)?;
-            // Append the piece of the encoded_pdu that belongs in this chunk.
-            chunk.write_slice(&encoded_pdu[chunk_start_index..chunk_end_index]);
-            // Push the chunk onto the results.
-            chunks.push(chunk);
-
-            // If this was the last chunk, we're done, return the results.
-            if last {
-                break;
-            }
-
-            // Otherwise, update the chunk start and end indices for the next iteration.
-            chunk_start_index = chunk_end_index;
-            chunk_end_index = std::cmp::min(total_len, chunk_end_index.saturating_add(max_chunk_len));
-        }
-
-        Ok(chunks)
-    }
-}
-
-impl Default for ChunkProcessor {
-    fn default() -> Self {
-        Self::new()
-    }
-}
-
 /// Builds the [`ChannelOptions`] bitfield to be used in the [`ChannelDef`] structure.
 pub fn make_channel_options(channel: &StaticVirtualChannel) -> ChannelOptions {
-    match channel.compression_condition() {
-        CompressionCondition::Never => ChannelOptions::empty(),
-        CompressionCondition::WhenRdpDataIsCompressed => ChannelOptions::COMPRESS_RDP,
-        CompressionCondition::Always => ChannelOptions::COMPRESS,
-    }
+    channel.compression_condition().to_channel_options()
 }
 
 /// Builds the [`ChannelDef`] structure containing information for this channel.
@@ -613,6 +629,8 @@ bitflags! {
         const AT_FRONT = 0x0040_0000;
         /// CHANNEL_PACKET_FLUSHED
         const FLUSHED = 0x0080_0000;
+        /// CompressionTypeMask, the bulk compression type of compressed chunks
+        const COMPRESSION_TYPE_MASK = 0x000F_0000;
     }
 }
 
@@ -0,0 +1,268 @@
+//! Utilities for testing virtual channel processors hermetically, without a live server or client.
+//!
+//! A [`MockPeer`] follows a script: it expects the channel under test to send some PDUs, and sends PDUs to it.
+//! The channel under test is reached through a [`MockEndpoint`], which carries the PDUs in memory exactly
+//! as they would be carried over the wire. [`SvcMockEndpoint`] is the endpoint for static virtual channels.
+//!
+//! ```ignore
+//! let mut channel = StaticVirtualChannel::new(MyProcessor::new());
+//!
+//! MockPeer::new()
+//!     .expect(&MyPdu::Ready)
+//!     .send(&MyPdu::Request)
+//!     .expect_with("a response", |pdu| pdu.starts_with(&[0x01]))
+//!     .run(&mut SvcMockEndpoint::new(&mut channel))?;
+//! ```
+
+use alloc::boxed::Box;
+use alloc::collections::VecDeque;
+use alloc::string::{String, ToString as _};
+use alloc::vec::Vec;
+use core::fmt;
+
+use ironrdp_core::{encode_vec, DecodeError, Encode, EncodeError};
+use ironrdp_pdu::PduError;
+
+use crate::{ChunkProcessor, StaticVirtualChannel, SvcMessage, CHANNEL_CHUNK_LENGTH};
+
+pub type MockResult<T> = Result<T, MockError>;
+
+#[derive(Debug)]
+pub enum MockError {
+    /// A PDU of the script could not be encoded.
+    Encode(EncodeError),
+    /// A PDU could not be carried over the mock transport.
+    Decode(DecodeError),
+    /// The channel under test returned an error.
+    Channel(PduError),
+    /// The peer expected a PDU, but the channel under test sent nothing.
+    MissingPdu { step: usize, expected: String },
+    /// The PDU sent by the channel under test is not the expected one.
+    UnexpectedPdu {
+        step: usize,
+        expected: String,
+        actual: Vec<u8>,
+    },
+    /// The channel under test sent PDUs which were not expected by the end of the script.
+    UnexpectedTrailingPdus { count: usize },
+}
+
+impl fmt::Display for MockError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MockError::Encode(_) => write!(f, "failed to encode a PDU of the script"),
+            MockError::Decode(_) => write!(f, "failed to carry a PDU over the mock transport"),
+            MockError::Channel(_) => write!(f, "channel error"),
+            MockError::MissingPdu { step, expected } => {
+                write!(f, "step {step}: expected {expected}, but nothing was sent")
+            }
+            MockError::UnexpectedPdu { step, expected, actual } => {
+                write!(
+                    f,
+                    "step {step}: expected {expected}, but got a {}-byte PDU",
+                    actual.len()
+                )
+            }
+            MockError::UnexpectedTrailingPdus { count } => write!(f, "{count} unexpected PDU(s) sent"),
+        }
+    }
+}
+
+impl std::error::Error for MockError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            MockError::Encode(e) => Some(e),
+            MockError::Decode(e) => Some(e),
+            MockError::Channel(e) => Some(e),
+            MockError::MissingPdu { .. } => None,
+            MockError::UnexpectedPdu { .. } => None,
+            MockError::UnexpectedTrailingPdus { .. } => None,
+        }
+    }
+}
+
+/// The channel under test, as seen by a [`MockPeer`].
+///
+/// The PDUs are complete: the endpoint takes care of the framing used by the channel (chunking, fragmentation…).
+pub trait MockEndpoint {
+    /// Starts the channel, returning the PDUs it sends right away.
+    fn start(&mut self) -> MockResult<Vec<Vec<u8>>>;
+
+    /// Delivers a PDU sent by the peer, returning the PDUs sent by the channel in response.
+    fn deliver(&mut self, pdu: &[u8]) -> MockResult<Vec<Vec<u8>>>;
+}
+
+enum Expectation {
+    Exact(Vec<u8>),
+    Predicate(Box<dyn FnMut(&[u8]) -> bool>),
+}
+
+enum MockStep {
+    Expect {
+        description: String,
+        expectation: Result<Expectation, EncodeError>,
+    },
+    Send(Result<Vec<u8>, EncodeError>),
+}
+
+/// Scripted peer of a channel under test.
+///
+/// The steps are performed in order by [`MockPeer::run`], which stops at the first deviation from the script.
+#[derive(Default)]
+pub struct MockPeer {
+    steps: Vec<MockStep>,
+}
+
+impl MockPeer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Expects the channel under test to send exactly `pdu`.
+    #[must_use]
+    pub fn expect<T>(mut self, pdu: &T) -> Self
+    where
+        T: Encode + ?Sized,
+    {
+        self.steps.push(MockStep::Expect {
+            description: pdu.name().to_string(),
+            expectation: encode_vec(pdu).map(Expectation::Exact),
+        });
+        self
+    }
+
+    /// Expects the channel under test to send a PDU for which `check` returns true.
+    #[must_use]
+    pub fn expect_with<F>(mut self, description: impl Into<String>, check: F) -> Self
+    where
+        F: FnMut(&[u8]) -> bool + 'static,
+    {
+        self.steps.push(MockStep::Expect {
+            description: description.into(),
+            expectation: Ok(Expectation::Predicate(Box::new(check))),
+        });
+        self
+    }
+
+    /// Sends `pdu` to the channel under test.
+    #[must_use]
+    pub fn send<T>(mut self, pdu: &T) -> Self
+    where
+        T: Encode + ?Sized,
+    {
+        self.steps.push(MockStep::Send(encode_vec(pdu)));
+        self
+    }
+
+    /// Starts the channel under test, and performs the steps of the script.
+    ///
+    /// The PDUs sent by the channel are queued, and checked in order by the expectations. All of them must have
+    /// been expected by the end of the script.
+    pub fn run(self, endpoint: &mut dyn MockEndpoint) -> MockResult<()> {
+        let mut sent: VecDeque<Vec<u8>> = endpoint.start()?.into();
+
+        for (step, mock_step) in (1..).zip(self.steps) {
+            match mock_step {
+                MockStep::Expect {
+                    description,
+                    expectation,
+                } => {
+                    let expectation = expectation.map_err(MockError::Encode)?;
+
+                    let Some(actual) = sent.pop_front() else {
+                        return Err(MockError::MissingPdu {
+                            step,
+                            expected: description,
+                        });
+                    };
+
+                    let is_expected = match expectation {
+                        Expectation::Exact(expected) => expected == actual,
+                        Expectation::Predicate(mut check) => check(&actual),
+                    };
+
+                    if !is_expected {
+                        return Err(MockError::UnexpectedPdu {
+                            step,
+                            expected: description,
+                            actual,
+                        });
+                    }
+                }
+                MockStep::Send(pdu) => {
+                    let pdu = pdu.map_err(MockError::Encode)?;
+                    sent.extend(endpoint.deliver(&pdu)?);
+                }
+            }
+        }
+
+        if sent.is_empty() {
+            Ok(())
+        } else {
+            Err(MockError::UnexpectedTrailingPdus { count: sent.len() })
+        }
+    }
+}
+
+/// In-memory transport to a static virtual channel under test.
+///
+/// The PDUs are chunked and reassembled as over the wire, so that large PDUs also exercise the chunking logic.
+pub struct SvcMockEndpoint<'a> {
+    channel: &'a mut StaticVirtualChannel,
+    chunk_length: usize,
+    peer_chunk_processor: ChunkProcessor,
+}
+
+impl<'a> SvcMockEndpoint<'a> {
+    pub fn new(channel: &'a mut StaticVirtualChannel) -> Self {
+        Self {
+            channel,
+            chunk_length: CHANNEL_CHUNK_LENGTH,
+            peer_chunk_processor: ChunkProcessor::new(),
+        }
+    }
+
+    /// Sets the maximum length of the chunks, [`CHANNEL_CHUNK_LENGTH`] by default.
+    #[must_use]
+    pub fn with_chunk_length(mut self, chunk_length: usize) -> Self {
+        self.chunk_length = chunk_length;
+        self
+    }
+
+    fn to_peer(&mut self, messages: Vec<SvcMessage>) -> MockResult<Vec<Vec<u8>>> {
+        let mut pdus = Vec::new();
+
+        for chunk in ChunkProcessor::chunkify(messages, self.chunk_length).map_err(MockError::Encode)? {
+            if let Some(pdu) = self
+                .peer_chunk_processor
+                .dechunkify(chunk.filled())
+                .map_err(MockError::Decode)?
+            {
+                pdus.push(pdu);
+            }
+        }
+
+        Ok(pdus)
+    }
+}
+
+impl MockEndpoint for SvcMockEndpoint<'_> {
+    fn start(&mut self) -> MockResult<Vec<Vec<u8>>> {
+        let messages = self.channel.start().map_err(MockError::Channel)?;
+        self.to_peer(messages)
+    }
+
+    fn deliver(&mut self, pdu: &[u8]) -> MockResult<Vec<Vec<u8>>> {
+        let chunks = ChunkProcessor::chunkify(vec![SvcMessage::from(pdu.to_vec())], self.chunk_length)
+            .map_err(MockError::Encode)?;
+
+        let mut pdus = Vec::new();
+
+        for chunk in chunks {
+            let messages = self.channel.process(chunk.filled()).map_err(MockError::Channel)?;
+            pdus.extend(self.to_peer(messages)?);
+        }
+
+        Ok(pdus)
+    }
+}
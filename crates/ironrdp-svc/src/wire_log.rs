@@ -0,0 +1,113 @@
+//! Opt-in logging of the PDUs exchanged over the virtual channels.
+//!
+//! Wire logging is disabled by default. Once enabled with [`set_wire_logging`], the PDUs received and sent
+//! by the static and dynamic virtual channels are logged with the [`WIRE_LOG_TARGET`] target, at the INFO level.
+//! Payloads are never logged unless [`WireLogging::Payload`] is explicitly selected.
+
+use core::fmt;
+use core::sync::atomic::{AtomicU8, Ordering};
+
+use ironrdp_core::Encode;
+
+/// Target of the wire logging events, for filtering them independently of the other events.
+pub const WIRE_LOG_TARGET: &str = "ironrdp::wire";
+
+/// Level of detail of the wire logging.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+#[repr(u8)]
+pub enum WireLogging {
+    /// Nothing is logged.
+    #[default]
+    Disabled = 0,
+    /// PDU names, sizes and sequence numbers are logged.
+    Metadata = 1,
+    /// The payloads are logged in addition to the metadata.
+    ///
+    /// Payloads may contain sensitive data (clipboard content, transferred files…).
+    Payload = 2,
+}
+
+static WIRE_LOGGING: AtomicU8 = AtomicU8::new(WireLogging::Disabled as u8);
+
+/// Sets the level of detail of the wire logging, for the whole process.
+pub fn set_wire_logging(level: WireLogging) {
+    WIRE_LOGGING.store(level as u8, Ordering::Relaxed);
+}
+
+/// Returns the level of detail of the wire logging.
+pub fn wire_logging() -> WireLogging {
+    match WIRE_LOGGING.load(Ordering::Relaxed) {
+        0 => WireLogging::Disabled,
+        1 => WireLogging::Metadata,
+        _ => WireLogging::Payload,
+    }
+}
+
+/// Wire logging state of a channel, numbering the PDUs received and sent over it.
+#[derive(Debug, Clone, Default)]
+pub struct ChannelWireLog {
+    received: u64,
+    sent: u64,
+}
+
+impl ChannelWireLog {
+    pub const fn new() -> Self {
+        Self { received: 0, sent: 0 }
+    }
+
+    /// Logs a complete PDU received on the channel.
+    pub fn received(&mut self, channel: &str, payload: &[u8]) {
+        let sequence = self.received;
+        self.received = self.received.wrapping_add(1);
+
+        match wire_logging() {
+            WireLogging::Disabled => {}
+            WireLogging::Metadata => {
+                info!(target: WIRE_LOG_TARGET, channel, sequence, size = payload.len(), "Received");
+            }
+            WireLogging::Payload => {
+                info!(target: WIRE_LOG_TARGET, channel, sequence, size = payload.len(), payload = %Hex(payload), "Received");
+            }
+        }
+    }
+
+    /// Logs a PDU sent on the channel.
+    pub fn sent<T>(&mut self, channel: &str, pdu: &T)
+    where
+        T: Encode + ?Sized,
+    {
+        let sequence = self.sent;
+        self.sent = self.sent.wrapping_add(1);
+
+        match wire_logging() {
+            WireLogging::Disabled => {}
+            WireLogging::Metadata => {
+                info!(target: WIRE_LOG_TARGET, channel, sequence, pdu = pdu.name(), size = pdu.size(), "Sent");
+            }
+            WireLogging::Payload => {
+                // The PDU is encoded a second time, which is acceptable since payloads are logged on request only.
+                let payload = ironrdp_core::encode_vec(pdu).unwrap_or_default();
+                info!(
+                    target: WIRE_LOG_TARGET,
+                    channel,
+                    sequence,
+                    pdu = pdu.name(),
+                    size = pdu.size(),
+                    payload = %Hex(&payload),
+                    "Sent"
+                );
+            }
+        }
+    }
+}
+
+struct Hex<'a>(&'a [u8]);
+
+impl fmt::Display for Hex<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for byte in self.0 {
+            write!(f, "{byte:02x}")?;
+        }
+        Ok(())
+    }
+}
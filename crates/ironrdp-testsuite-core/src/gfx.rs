@@ -12,6 +12,7 @@ lazy_static! {
         [&WIRE_TO_SURFACE_1_HEADER_BUFFER[..], &WIRE_TO_SURFACE_1_BUFFER[..],].concat();
     pub static ref HEADER_WITH_FRAME_ACKNOWLEDGE_BUFFER: Vec<u8> =
         [&FRAME_ACKNOWLEDGE_HEADER_BUFFER[..], &FRAME_ACKNOWLEDGE_BUFFER[..],].concat();
-    pub static ref HEADER_WITH_WIRE_TO_SURFACE_1: ServerPdu = ServerPdu::WireToSurface1(WIRE_TO_SURFACE_1.clone());
+    pub static ref HEADER_WITH_WIRE_TO_SURFACE_1: ServerPdu<'static> =
+        ServerPdu::WireToSurface1(WIRE_TO_SURFACE_1.clone());
     pub static ref HEADER_WITH_FRAME_ACKNOWLEDGE: ClientPdu = ClientPdu::FrameAcknowledge(FRAME_ACKNOWLEDGE.clone());
 }
@@ -227,7 +227,7 @@ pub const AVC_444_MESSAGE_CORRECT_LEN: [u8; 88] = [
 ];
 
 lazy_static! {
-    pub static ref WIRE_TO_SURFACE_1: WireToSurface1Pdu = WireToSurface1Pdu {
+    pub static ref WIRE_TO_SURFACE_1: WireToSurface1Pdu<'static> = WireToSurface1Pdu {
         surface_id: 0,
         codec_id: Codec1Type::ClearCodec,
         pixel_format: PixelFormat::XRgb,
@@ -237,15 +237,15 @@ lazy_static! {
             right: 939,
             bottom: 743
         },
-        bitmap_data: WIRE_TO_SURFACE_1_BUFFER[17..].to_vec(),
+        bitmap_data: WIRE_TO_SURFACE_1_BUFFER[17..].to_vec().into(),
     };
     pub static ref WIRE_TO_SURFACE_1_BITMAP_DATA: Vec<u8> = WIRE_TO_SURFACE_1_BUFFER[17..].to_vec();
-    pub static ref WIRE_TO_SURFACE_2: WireToSurface2Pdu = WireToSurface2Pdu {
+    pub static ref WIRE_TO_SURFACE_2: WireToSurface2Pdu<'static> = WireToSurface2Pdu {
         surface_id: 0,
         codec_id: Codec2Type::RemoteFxProgressive,
         codec_context_id: 4,
         pixel_format: PixelFormat::XRgb,
-        bitmap_data: WIRE_TO_SURFACE_2_BUFFER[13..].to_vec(),
+        bitmap_data: WIRE_TO_SURFACE_2_BUFFER[13..].to_vec().into(),
     };
     pub static ref WIRE_TO_SURFACE_2_BITMAP_DATA: Vec<u8> = WIRE_TO_SURFACE_2_BUFFER[13..].to_vec();
     pub static ref DELETE_ENCODING_CONTEXT: DeleteEncodingContextPdu = DeleteEncodingContextPdu {
@@ -0,0 +1,310 @@
+use ironrdp_acceptor::AcceptorHooks;
+use ironrdp_connector::sspi::{AuthIdentity, Username};
+use ironrdp_connector::{ConnectorErrorKind, Sequence as _};
+use ironrdp_core::impl_as_any;
+use ironrdp_pdu::gcc::{ChannelDef, ChannelName};
+use ironrdp_pdu::mcs::{DisconnectProviderUltimatum, DisconnectReason, SendDataIndication};
+use ironrdp_pdu::nego::{ConnectionConfirm, FailureCode};
+use ironrdp_pdu::rdp::client_info::{ClientInfo, CompressionType};
+use ironrdp_pdu::rdp::finalization_messages::{ControlAction, ControlPdu, FontPdu, SynchronizePdu};
+use ironrdp_pdu::rdp::headers::{
+    BasicSecurityHeader, BasicSecurityHeaderFlags, CompressionFlags, ShareControlHeader, ShareControlPdu,
+    ShareDataHeader, ShareDataPdu, StreamPriority,
+};
+use ironrdp_pdu::rdp::server_error_info::{ErrorInfo, ProtocolIndependentCode, ServerSetErrorInfoPdu};
+use ironrdp_pdu::rdp::ClientInfoPdu;
+use ironrdp_pdu::PduResult;
+use ironrdp_svc::{SvcMessage, SvcProcessor, SvcServerProcessor};
+use ironrdp_testsuite_core::capsets::CLIENT_DEMAND_ACTIVE;
+use ironrdp_testsuite_core::client_info::CLIENT_INFO_UNICODE;
+
+use super::*;
+
+/// Denies the connection at the configured points
+#[derive(Default)]
+struct DenyingHooks {
+    connection_request: Option<FailureCode>,
+    nla_account_name: Option<&'static str>,
+    client_info: Option<ErrorInfo>,
+    channel: Option<ChannelName>,
+}
+
+impl AcceptorHooks for DenyingHooks {
+    fn on_connection_request(&mut self, _: &ConnectionRequest) -> Result<(), FailureCode> {
+        self.connection_request.map_or(Ok(()), Err)
+    }
+
+    fn on_nla_complete(&mut self, identity: &AuthIdentity) -> bool {
+        self.nla_account_name != Some(identity.username.account_name())
+    }
+
+    fn on_client_info(&mut self, _: &ClientInfo) -> Result<(), ErrorInfo> {
+        self.client_info.map_or(Ok(()), Err)
+    }
+
+    fn on_channel_join(&mut self, channel: &ChannelDef) -> bool {
+        self.channel.as_ref() != Some(&channel.name)
+    }
+}
+
+#[derive(Debug)]
+struct Cliprdr;
+
+impl_as_any!(Cliprdr);
+
+impl SvcProcessor for Cliprdr {
+    fn channel_name(&self) -> ChannelName {
+        ChannelName::from_static(b"cliprdr\0")
+    }
+
+    fn process(&mut self, _: &[u8]) -> PduResult<Vec<SvcMessage>> {
+        Ok(Vec::new())
+    }
+}
+
+impl SvcServerProcessor for Cliprdr {}
+
+#[derive(Debug)]
+struct Rdpsnd;
+
+impl_as_any!(Rdpsnd);
+
+impl SvcProcessor for Rdpsnd {
+    fn channel_name(&self) -> ChannelName {
+        ChannelName::from_static(b"rdpsnd\0\0")
+    }
+
+    fn process(&mut self, _: &[u8]) -> PduResult<Vec<SvcMessage>> {
+        Ok(Vec::new())
+    }
+}
+
+impl SvcServerProcessor for Rdpsnd {}
+
+fn acceptor_with_hooks(hooks: DenyingHooks) -> Acceptor {
+    let mut acceptor = acceptor(SecurityProtocol::SSL);
+    acceptor.set_hooks(Box::new(hooks));
+    acceptor
+}
+
+/// Goes through the connection initiation, the basic settings exchange and the channel connection
+fn connect_channels(acceptor: &mut Acceptor) -> (u16, u16, Vec<mcs::ChannelJoinConfirm>) {
+    let responses = send(acceptor, &connection_request(SecurityProtocol::SSL));
+    assert_eq!(responses.len(), 1);
+
+    let responses = send(acceptor, &connect_initial());
+    let [response] = responses.as_slice() else {
+        panic!("expected a Connect Response, got {responses:?}");
+    };
+
+    join_channels(acceptor, &connect_response(response))
+}
+
+/// Goes through the connection initiation and the security upgrade, up to the CredSSP sequence
+fn reach_credssp(acceptor: &mut Acceptor) {
+    let mut output = WriteBuf::new();
+    acceptor
+        .step(&connection_request(SecurityProtocol::HYBRID_EX), &mut output)
+        .unwrap();
+    acceptor.step_no_input(&mut output).unwrap();
+    acceptor.mark_security_upgrade_as_done();
+    assert!(acceptor.should_perform_credssp());
+}
+
+fn authenticated_as(account_name: &str) -> AuthIdentity {
+    AuthIdentity {
+        username: Username::new(account_name, Some("DOMAIN")).unwrap(),
+        password: "password".to_owned().into(),
+    }
+}
+
+fn client_info(user_channel_id: u16, io_channel_id: u16) -> Vec<u8> {
+    send_data_request(
+        user_channel_id,
+        io_channel_id,
+        &ClientInfoPdu {
+            security_header: BasicSecurityHeader {
+                flags: BasicSecurityHeaderFlags::INFO_PKT,
+            },
+            client_info: CLIENT_INFO_UNICODE.clone(),
+        },
+    )
+}
+
+fn share_data(user_channel_id: u16, pdu: ShareDataPdu) -> ShareControlHeader {
+    ShareControlHeader {
+        share_control_pdu: ShareControlPdu::Data(ShareDataHeader {
+            share_data_pdu: pdu,
+            stream_priority: StreamPriority::Medium,
+            compression_flags: CompressionFlags::empty(),
+            compression_type: CompressionType::K8,
+        }),
+        pdu_source: user_channel_id,
+        share_id: 0,
+    }
+}
+
+fn assert_access_denied(mut acceptor: Acceptor) {
+    assert!(is_access_denied(&acceptor));
+
+    let error = acceptor.step_no_input(&mut WriteBuf::new()).unwrap_err();
+    assert!(matches!(error.kind, ConnectorErrorKind::AccessDenied));
+}
+
+#[test]
+fn connection_request_denied_by_hook() {
+    let mut acceptor = acceptor_with_hooks(DenyingHooks {
+        connection_request: Some(FailureCode::HYBRID_REQUIRED_BY_SERVER),
+        ..Default::default()
+    });
+
+    let responses = send(&mut acceptor, &connection_request(SecurityProtocol::SSL));
+
+    let failure = encode_vec(&X224(ConnectionConfirm::Failure {
+        code: FailureCode::HYBRID_REQUIRED_BY_SERVER,
+    }))
+    .unwrap();
+    assert_eq!(responses, [failure]);
+
+    assert_access_denied(acceptor);
+}
+
+#[test]
+fn nla_identity_allowed_by_hook() {
+    let mut acceptor = acceptor(SecurityProtocol::HYBRID_EX);
+    acceptor.set_hooks(Box::new(DenyingHooks {
+        nla_account_name: Some("mallory"),
+        ..Default::default()
+    }));
+
+    reach_credssp(&mut acceptor);
+
+    // The CredSSP sequence can't be stepped over.
+    let error = acceptor.step_no_input(&mut WriteBuf::new()).unwrap_err();
+    assert!(matches!(error.kind, ConnectorErrorKind::Reason(_)));
+    assert!(acceptor.should_perform_credssp());
+
+    assert!(acceptor.mark_credssp_as_done(&authenticated_as("alice")));
+
+    let responses = send(&mut acceptor, &connect_initial());
+    assert_eq!(responses.len(), 1, "expected a Connect Response");
+}
+
+#[test]
+fn nla_identity_denied_by_hook() {
+    let mut acceptor = acceptor(SecurityProtocol::HYBRID_EX);
+    acceptor.set_hooks(Box::new(DenyingHooks {
+        nla_account_name: Some("mallory"),
+        ..Default::default()
+    }));
+
+    reach_credssp(&mut acceptor);
+
+    assert!(!acceptor.mark_credssp_as_done(&authenticated_as("mallory")));
+
+    assert_access_denied(acceptor);
+}
+
+#[test]
+fn client_info_denied_by_hook() {
+    let error_info = ErrorInfo::ProtocolIndependentCode(ProtocolIndependentCode::ServerDeniedConnection);
+
+    let mut acceptor = acceptor_with_hooks(DenyingHooks {
+        client_info: Some(error_info),
+        ..Default::default()
+    });
+
+    let (user_channel_id, io_channel_id, _) = connect_channels(&mut acceptor);
+
+    let responses = send(&mut acceptor, &client_info(user_channel_id, io_channel_id));
+
+    let set_error_info = ShareControlHeader {
+        share_control_pdu: ShareControlPdu::Data(ShareDataHeader {
+            share_data_pdu: ShareDataPdu::ServerSetErrorInfo(ServerSetErrorInfoPdu(error_info)),
+            stream_priority: StreamPriority::Undefined,
+            compression_flags: CompressionFlags::empty(),
+            compression_type: CompressionType::K8,
+        }),
+        pdu_source: io_channel_id,
+        share_id: 0,
+    };
+    let set_error_info = encode_vec(&X224(SendDataIndication {
+        initiator_id: user_channel_id,
+        channel_id: io_channel_id,
+        user_data: Cow::Owned(encode_vec(&set_error_info).unwrap()),
+    }))
+    .unwrap();
+    let ultimatum = encode_vec(&X224(DisconnectProviderUltimatum::from_reason(
+        DisconnectReason::ProviderInitiated,
+    )))
+    .unwrap();
+    assert_eq!(responses, [set_error_info, ultimatum]);
+
+    assert_access_denied(acceptor);
+}
+
+#[test]
+fn channel_join_denied_by_hook() {
+    let mut acceptor = acceptor_with_hooks(DenyingHooks {
+        channel: Some(ChannelName::from_static(b"cliprdr\0")),
+        ..Default::default()
+    });
+    acceptor.attach_static_channel(Cliprdr);
+    acceptor.attach_static_channel(Rdpsnd);
+
+    let (user_channel_id, io_channel_id, confirms) = connect_channels(&mut acceptor);
+
+    // The `rdpdr`, `cliprdr` and `rdpsnd` channels, in the order of the Connect Initial.
+    let results: Vec<_> = confirms
+        .iter()
+        .map(|confirm| (confirm.requested_channel_id, confirm.result))
+        .collect();
+    assert_eq!(results, [(1004, 0), (1005, 6), (1006, 0)]);
+
+    // The connection goes on without the denied channel.
+    let responses = send(&mut acceptor, &client_info(user_channel_id, io_channel_id));
+    assert_eq!(responses.len(), 2, "expected a licensing PDU and a Demand Active PDU");
+
+    let confirm_active = ShareControlHeader {
+        share_control_pdu: ShareControlPdu::ClientConfirmActive(CLIENT_DEMAND_ACTIVE.clone()),
+        pdu_source: user_channel_id,
+        share_id: 0,
+    };
+
+    let finalization = [
+        ShareDataPdu::Synchronize(SynchronizePdu {
+            target_user_id: user_channel_id,
+        }),
+        ShareDataPdu::Control(ControlPdu {
+            action: ControlAction::Cooperate,
+            grant_id: 0,
+            control_id: 0,
+        }),
+        ShareDataPdu::Control(ControlPdu {
+            action: ControlAction::RequestControl,
+            grant_id: 0,
+            control_id: 0,
+        }),
+    ];
+
+    assert!(send(
+        &mut acceptor,
+        &send_data_request(user_channel_id, io_channel_id, &confirm_active)
+    )
+    .is_empty());
+    for pdu in finalization {
+        let pdu = share_data(user_channel_id, pdu);
+        assert!(send(&mut acceptor, &send_data_request(user_channel_id, io_channel_id, &pdu)).is_empty());
+    }
+
+    let font_list = share_data(user_channel_id, ShareDataPdu::FontList(FontPdu::default()));
+    let responses = send(
+        &mut acceptor,
+        &send_data_request(user_channel_id, io_channel_id, &font_list),
+    );
+    assert_eq!(responses.len(), 4, "expected the server finalization PDUs");
+
+    let result = acceptor.get_result().expect("accepted");
+    assert_eq!(result.static_channels.get_channel_id_by_type::<Cliprdr>(), None);
+    assert_eq!(result.static_channels.get_channel_id_by_type::<Rdpsnd>(), Some(1006));
+}
@@ -0,0 +1,152 @@
+use std::borrow::Cow;
+
+use ironrdp_acceptor::{Acceptor, AcceptorState, DesktopSize};
+use ironrdp_connector::{Sequence as _, State as _};
+use ironrdp_core::{decode, encode_vec, Encode, WriteBuf};
+use ironrdp_pdu::mcs::{self, ConnectResponse};
+use ironrdp_pdu::nego::{ConnectionRequest, RequestFlags, SecurityProtocol};
+use ironrdp_pdu::x224::{X224Data, X224};
+use ironrdp_testsuite_core::mcs::CONNECT_INITIAL;
+
+mod hooks;
+mod standard_security;
+
+const DESKTOP_SIZE: DesktopSize = DesktopSize {
+    width: 1024,
+    height: 768,
+};
+
+fn acceptor(security: SecurityProtocol) -> Acceptor {
+    Acceptor::new(security, DESKTOP_SIZE, Vec::new())
+}
+
+fn is_access_denied(acceptor: &Acceptor) -> bool {
+    matches!(
+        acceptor.state().as_any().downcast_ref::<AcceptorState>(),
+        Some(AcceptorState::AccessDenied)
+    )
+}
+
+/// Feeds a client frame to the acceptor, then steps it until it waits for the next client frame
+///
+/// Returns the frames sent by the acceptor.
+fn send(acceptor: &mut Acceptor, frame: &[u8]) -> Vec<Vec<u8>> {
+    let mut output = WriteBuf::new();
+    acceptor.step(frame, &mut output).unwrap();
+
+    while acceptor.next_pdu_hint().is_none() && !acceptor.state().is_terminal() && !is_access_denied(acceptor) {
+        acceptor.step_no_input(&mut output).unwrap();
+    }
+
+    split_frames(output.filled())
+}
+
+pub(crate) fn split_frames(mut frames: &[u8]) -> Vec<Vec<u8>> {
+    let mut split = Vec::new();
+
+    while !frames.is_empty() {
+        let length = ironrdp_pdu::find_size(frames).unwrap().unwrap().length;
+        let (frame, rest) = frames.split_at(length);
+        split.push(frame.to_vec());
+        frames = rest;
+    }
+
+    split
+}
+
+fn connection_request(protocol: SecurityProtocol) -> Vec<u8> {
+    encode_vec(&X224(ConnectionRequest {
+        nego_data: None,
+        flags: RequestFlags::empty(),
+        protocol,
+    }))
+    .unwrap()
+}
+
+/// MCS Connect Initial requesting the `rdpdr`, `cliprdr` and `rdpsnd` channels
+fn connect_initial() -> Vec<u8> {
+    x224_data(&*CONNECT_INITIAL)
+}
+
+fn connect_response(frame: &[u8]) -> ConnectResponse {
+    let data = decode::<X224<X224Data<'_>>>(frame).unwrap().0;
+    decode(data.data.as_ref()).unwrap()
+}
+
+fn x224_data(pdu: &impl Encode) -> Vec<u8> {
+    encode_vec(&X224(X224Data {
+        data: Cow::Owned(encode_vec(pdu).unwrap()),
+    }))
+    .unwrap()
+}
+
+fn send_data_request(user_channel_id: u16, channel_id: u16, pdu: &impl Encode) -> Vec<u8> {
+    send_data_request_raw(user_channel_id, channel_id, encode_vec(pdu).unwrap())
+}
+
+fn send_data_request_raw(user_channel_id: u16, channel_id: u16, user_data: Vec<u8>) -> Vec<u8> {
+    encode_vec(&X224(mcs::SendDataRequest {
+        initiator_id: user_channel_id,
+        channel_id,
+        user_data: Cow::Owned(user_data),
+    }))
+    .unwrap()
+}
+
+fn send_data_indication(frame: &[u8]) -> mcs::SendDataIndication<'_> {
+    decode::<X224<mcs::SendDataIndication<'_>>>(frame).unwrap().0
+}
+
+/// Goes through the MCS channel connection, joining the user, I/O and requested channels
+///
+/// Returns the user channel ID, the I/O channel ID, and the Channel Join Confirm PDUs of the requested channels.
+fn join_channels(
+    acceptor: &mut Acceptor,
+    connect_response: &ConnectResponse,
+) -> (u16, u16, Vec<mcs::ChannelJoinConfirm>) {
+    let network = &connect_response.conference_create_response.gcc_blocks.network;
+
+    let responses = send(
+        acceptor,
+        &encode_vec(&X224(mcs::ErectDomainPdu {
+            sub_height: 0,
+            sub_interval: 0,
+        }))
+        .unwrap(),
+    );
+    assert!(responses.is_empty());
+
+    let responses = send(acceptor, &encode_vec(&X224(mcs::AttachUserRequest)).unwrap());
+    let [attach_user_confirm] = responses.as_slice() else {
+        panic!("expected an Attach User Confirm, got {responses:?}");
+    };
+    let user_channel_id = decode::<X224<mcs::AttachUserConfirm>>(attach_user_confirm)
+        .unwrap()
+        .0
+        .initiator_id;
+
+    let mut confirms = Vec::new();
+    for channel_id in [user_channel_id, network.io_channel]
+        .into_iter()
+        .chain(network.channel_ids.iter().copied())
+    {
+        let responses = send(
+            acceptor,
+            &encode_vec(&X224(mcs::ChannelJoinRequest {
+                initiator_id: user_channel_id,
+                channel_id,
+            }))
+            .unwrap(),
+        );
+        let [confirm] = responses.as_slice() else {
+            panic!("expected a Channel Join Confirm, got {responses:?}");
+        };
+        confirms.push(decode::<X224<mcs::ChannelJoinConfirm>>(confirm).unwrap().0);
+    }
+
+    // The user and I/O channels are always joined.
+    let requested = confirms.split_off(2);
+    assert!(confirms.iter().all(|confirm| confirm.result == 0));
+
+    (user_channel_id, network.io_channel, requested)
+}
@@ -0,0 +1,103 @@
+use ironrdp_acceptor::StandardSecurityConfig;
+use ironrdp_pdu::gcc::EncryptionMethod;
+use ironrdp_pdu::nego::ConnectionConfirm;
+use ironrdp_pdu::rdp::headers::{BasicSecurityHeader, BasicSecurityHeaderFlags, ShareControlHeader, ShareControlPdu};
+use ironrdp_pdu::rdp::server_license::{LicenseErrorCode, LicensePdu};
+use ironrdp_pdu::rdp::standard_security::{SecurityExchangePdu, StandardSecurity, DATA_SIGNATURE_LEN};
+use ironrdp_pdu::rdp::ClientInfoPdu;
+use ironrdp_testsuite_core::client_info::CLIENT_INFO_UNICODE;
+
+use super::*;
+use crate::pdu::standard_security::{rsa_key, CLIENT_RANDOM, ENCRYPTED_CLIENT_RANDOM};
+
+#[test]
+fn standard_security_exchange_up_to_licensing() {
+    let mut acceptor = acceptor(SecurityProtocol::empty());
+    acceptor.set_standard_security(StandardSecurityConfig {
+        server_key: rsa_key(),
+        signing_key: rsa_key(),
+        encryption_methods: EncryptionMethod::BIT_128,
+    });
+
+    let responses = send(&mut acceptor, &connection_request(SecurityProtocol::empty()));
+    let [confirm] = responses.as_slice() else {
+        panic!("expected a Connection Confirm, got {responses:?}");
+    };
+    assert!(matches!(
+        decode::<X224<ConnectionConfirm>>(confirm).unwrap().0,
+        ConnectionConfirm::Response { protocol, .. } if protocol.is_empty()
+    ));
+
+    let responses = send(&mut acceptor, &connect_initial());
+    let [response] = responses.as_slice() else {
+        panic!("expected a Connect Response, got {responses:?}");
+    };
+    let response = connect_response(response);
+
+    let security = &response.conference_create_response.gcc_blocks.security;
+    assert_eq!(security.encryption_method, EncryptionMethod::BIT_128);
+    assert!(!security.server_cert.is_empty());
+    let server_random = security.server_random.expect("server random");
+
+    let (user_channel_id, io_channel_id, confirms) = join_channels(&mut acceptor, &response);
+    assert!(confirms.iter().all(|confirm| confirm.result == 0));
+
+    // The security exchange itself is not answered.
+    let exchange = SecurityExchangePdu {
+        encrypted_client_random: hex::decode(ENCRYPTED_CLIENT_RANDOM).unwrap(),
+    };
+    let responses = send(
+        &mut acceptor,
+        &send_data_request(user_channel_id, io_channel_id, &exchange),
+    );
+    assert!(responses.is_empty());
+
+    let mut client = StandardSecurity::client(&CLIENT_RANDOM, &server_random, EncryptionMethod::BIT_128).unwrap();
+
+    let client_info = encode_vec(&ClientInfoPdu {
+        security_header: BasicSecurityHeader {
+            flags: BasicSecurityHeaderFlags::INFO_PKT,
+        },
+        client_info: CLIENT_INFO_UNICODE.clone(),
+    })
+    .unwrap();
+    let (signature, encrypted) = client.encrypt(&client_info[4..], false);
+
+    let mut user_data = Vec::new();
+    let flags = BasicSecurityHeaderFlags::INFO_PKT | BasicSecurityHeaderFlags::ENCRYPT;
+    user_data.extend_from_slice(&flags.bits().to_le_bytes());
+    user_data.extend_from_slice(&[0, 0]);
+    user_data.extend_from_slice(&signature);
+    user_data.extend_from_slice(&encrypted);
+
+    let responses = send(
+        &mut acceptor,
+        &send_data_request_raw(user_channel_id, io_channel_id, user_data),
+    );
+    let [license, demand_active] = responses.as_slice() else {
+        panic!("expected a licensing PDU and a Demand Active PDU, got {responses:?}");
+    };
+
+    // The licensing PDU is sent in the clear.
+    let license = send_data_indication(license);
+    let LicensePdu::LicensingErrorMessage(message) = decode::<LicensePdu>(&license.user_data).unwrap() else {
+        panic!("expected a licensing error message");
+    };
+    assert_eq!(message.error_code, LicenseErrorCode::StatusValidClient);
+
+    // The Demand Active PDU is encrypted.
+    let demand_active = send_data_indication(demand_active);
+    let flags = BasicSecurityHeaderFlags::from_bits_truncate(u16::from_le_bytes([
+        demand_active.user_data[0],
+        demand_active.user_data[1],
+    ]));
+    assert_eq!(flags, BasicSecurityHeaderFlags::ENCRYPT);
+
+    let (signature, encrypted) = demand_active.user_data[4..].split_at(DATA_SIGNATURE_LEN);
+    let decrypted = client.decrypt(encrypted, signature, false).unwrap();
+    let header = decode::<ShareControlHeader>(&decrypted).unwrap();
+    assert!(matches!(
+        header.share_control_pdu,
+        ShareControlPdu::ServerDemandActive(_)
+    ));
+}
@@ -1,4 +1,6 @@
-use ironrdp_cliprdr_format::bitmap::{dib_to_png, dibv5_to_png, png_to_cf_dib, png_to_cf_dibv5};
+use ironrdp_cliprdr_format::bitmap::{
+    dib_to_png, dib_to_rgba, dibv5_to_png, png_to_cf_dib, png_to_cf_dibv5, rgba_to_cf_dib, RgbaImage,
+};
 use ironrdp_cliprdr_format::html::{cf_html_to_plain_html, plain_html_to_cf_html};
 
 #[test]
@@ -17,6 +19,46 @@ fn dibv5_to_png_conversion_1() {
     assert_eq!(converted, input);
 }
 
+#[test]
+fn dib_to_rgba_conversion_1() {
+    let input = include_bytes!("../../test_data/pdu/clipboard/cf_dib.pdu");
+    let image = dib_to_rgba(input).unwrap();
+    assert_eq!(
+        image.data.len(),
+        usize::from(image.width) * usize::from(image.height) * 4
+    );
+    let converted = rgba_to_cf_dib(&image).unwrap();
+    assert_eq!(converted, input);
+}
+
+#[test]
+fn rgba_to_dib_round_trip() {
+    let image = RgbaImage {
+        width: 2,
+        height: 2,
+        data: vec![
+            0xFF, 0x00, 0x00, 0xFF, // red
+            0x00, 0xFF, 0x00, 0xFF, // green
+            0x00, 0x00, 0xFF, 0xFF, // blue
+            0xFF, 0xFF, 0xFF, 0xFF, // white
+        ],
+    };
+
+    let dib = rgba_to_cf_dib(&image).unwrap();
+    assert_eq!(dib_to_rgba(&dib).unwrap(), image);
+}
+
+#[test]
+fn rgba_to_dib_invalid_size() {
+    let image = RgbaImage {
+        width: 2,
+        height: 2,
+        data: vec![0xFF; 12],
+    };
+
+    assert!(rgba_to_cf_dib(&image).is_err());
+}
+
 #[test]
 fn html_failure() {
     // Empty
@@ -0,0 +1,184 @@
+use ironrdp_connector::{
+    ClientConnector, Config, ConnectorErrorKind, Credentials, DesktopSize, Sequence as _, TlsInfo,
+};
+use ironrdp_core::{encode_vec, WriteBuf};
+use ironrdp_pdu::gcc::KeyboardType;
+use ironrdp_pdu::mcs::{DisconnectProviderUltimatum, DisconnectReason};
+use ironrdp_pdu::nego::{ConnectionConfirm, FailureCode, ResponseFlags, SecurityProtocol};
+use ironrdp_pdu::rdp::capability_sets::MajorPlatformType;
+use ironrdp_pdu::rdp::client_info::PerformanceFlags;
+use ironrdp_pdu::rdp::server_error_info::{ErrorInfo, ProtocolIndependentCode};
+use ironrdp_pdu::x224::X224;
+
+pub(crate) fn config() -> Config {
+    Config {
+        desktop_size: DesktopSize {
+            width: 1024,
+            height: 768,
+        },
+        desktop_scale_factor: 0,
+        enable_tls: true,
+        enable_credssp: false,
+        credentials: Credentials::UsernamePassword {
+            username: "user".to_owned(),
+            password: "password".to_owned(),
+        },
+        domain: None,
+        client_build: 0,
+        client_name: "client".to_owned(),
+        keyboard_type: KeyboardType::IbmEnhanced,
+        keyboard_subtype: 0,
+        keyboard_functional_keys_count: 12,
+        keyboard_layout: 0,
+        ime_file_name: String::new(),
+        bitmap: None,
+        dig_product_id: String::new(),
+        client_dir: String::new(),
+        platform: MajorPlatformType::UNSPECIFIED,
+        autologon: false,
+        restricted_admin: false,
+        auto_reconnect: None,
+        decode_mode: Default::default(),
+        decode_limits: Default::default(),
+        monitors: Vec::new(),
+        persistent_bitmap_cache: Vec::new(),
+        no_server_pointer: false,
+        pointer_software_rendering: false,
+        performance_flags: PerformanceFlags::default(),
+    }
+}
+
+fn connector_waiting_for_connection_confirm() -> ClientConnector {
+    let mut connector = ClientConnector::new(config());
+    connector.step_no_input(&mut WriteBuf::new()).unwrap();
+    connector
+}
+
+#[test]
+fn negotiation_failure_is_surfaced() {
+    let mut connector = connector_waiting_for_connection_confirm();
+
+    let confirm = encode_vec(&X224(ConnectionConfirm::Failure {
+        code: FailureCode::HYBRID_REQUIRED_BY_SERVER,
+    }))
+    .unwrap();
+
+    let error = connector.step(&confirm, &mut WriteBuf::new()).unwrap_err();
+
+    assert!(matches!(
+        error.kind,
+        ConnectorErrorKind::Negotiation(FailureCode::HYBRID_REQUIRED_BY_SERVER)
+    ));
+    assert!(error.kind.to_string().contains("Network Level Authentication"));
+}
+
+#[test]
+fn disconnect_provider_ultimatum_is_surfaced() {
+    let mut connector = connector_waiting_for_connection_confirm();
+
+    let ultimatum = encode_vec(&X224(DisconnectProviderUltimatum::from_reason(
+        DisconnectReason::ProviderInitiated,
+    )))
+    .unwrap();
+
+    let error = connector.step(&ultimatum, &mut WriteBuf::new()).unwrap_err();
+
+    assert!(matches!(
+        error.kind,
+        ConnectorErrorKind::Disconnected(DisconnectReason::ProviderInitiated)
+    ));
+}
+
+#[test]
+fn security_protocol_mismatch_is_surfaced() {
+    let mut connector = connector_waiting_for_connection_confirm();
+
+    let confirm = encode_vec(&X224(ConnectionConfirm::Response {
+        flags: ResponseFlags::empty(),
+        protocol: SecurityProtocol::HYBRID,
+    }))
+    .unwrap();
+
+    let error = connector.step(&confirm, &mut WriteBuf::new()).unwrap_err();
+
+    assert!(matches!(
+        error.kind,
+        ConnectorErrorKind::SecurityProtocolMismatch {
+            requested: SecurityProtocol::SSL,
+            selected: SecurityProtocol::HYBRID,
+        }
+    ));
+    assert_eq!(error.code(), 0x20C);
+    assert_eq!(error.kind.error_info(), None);
+}
+
+#[test]
+fn security_info_is_recorded() {
+    let mut connector = connector_waiting_for_connection_confirm();
+
+    let confirm = encode_vec(&X224(ConnectionConfirm::Response {
+        flags: ResponseFlags::empty(),
+        protocol: SecurityProtocol::SSL,
+    }))
+    .unwrap();
+
+    connector.step(&confirm, &mut WriteBuf::new()).unwrap();
+
+    let tls_info = TlsInfo {
+        protocol_version: Some("TLSv1_3".to_owned()),
+        cipher_suite: None,
+        server_certificate_chain: vec![vec![0x30, 0x00]],
+    };
+    connector.attach_tls_info(tls_info.clone());
+    connector.mark_security_upgrade_as_done();
+
+    assert_eq!(connector.security_info.security_protocol, SecurityProtocol::SSL);
+    assert!(!connector.security_info.nla);
+    assert_eq!(connector.security_info.tls, Some(tls_info));
+}
+
+#[test]
+fn error_kinds_map_to_error_info() {
+    assert_eq!(
+        ConnectorErrorKind::AccessDenied.error_info(),
+        Some(ErrorInfo::ProtocolIndependentCode(
+            ProtocolIndependentCode::ServerDeniedConnection
+        ))
+    );
+
+    let info = ErrorInfo::ProtocolIndependentCode(ProtocolIndependentCode::IdleTimeout);
+    assert_eq!(ConnectorErrorKind::ServerErrorInfo(info).error_info(), Some(info));
+}
+
+#[test]
+fn config_round_trips_through_serde() {
+    let config = config();
+
+    let serialized = serde_json::to_value(&config).unwrap();
+    let deserialized: Config = serde_json::from_value(serialized.clone()).unwrap();
+
+    assert_eq!(serde_json::to_value(&deserialized).unwrap(), serialized);
+    assert_eq!(deserialized.desktop_size, config.desktop_size);
+    assert_eq!(deserialized.keyboard_type, config.keyboard_type);
+}
+
+#[test]
+fn config_fields_added_later_are_optional() {
+    let mut serialized = serde_json::to_value(config()).unwrap();
+
+    let fields = serialized.as_object_mut().unwrap();
+    for field in [
+        "decode_mode",
+        "decode_limits",
+        "monitors",
+        "persistent_bitmap_cache",
+        "performance_flags",
+    ] {
+        fields.remove(field).unwrap();
+    }
+
+    let deserialized: Config = serde_json::from_value(serialized).unwrap();
+
+    assert!(deserialized.monitors.is_empty());
+    assert_eq!(deserialized.performance_flags, PerformanceFlags::default());
+}
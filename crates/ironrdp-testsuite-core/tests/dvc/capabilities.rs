@@ -4,16 +4,16 @@ const REQ_V1_ENCODED: [u8; 4] = [0x50, 0x00, 0x01, 0x00];
 const REQ_V2_ENCODED: [u8; 12] = [0x50, 0x00, 0x02, 0x00, 0x33, 0x33, 0x11, 0x11, 0x3d, 0x0a, 0xa7, 0x04];
 const RESP_V1_ENCODED: [u8; 4] = [0x50, 0x00, 0x01, 0x00];
 
-static REQ_V1_DECODED_SERVER: OnceLock<DrdynvcServerPdu> = OnceLock::new();
-static REQ_V2_DECODED_SERVER: OnceLock<DrdynvcServerPdu> = OnceLock::new();
-static RESP_V1_DECODED_CLIENT: OnceLock<DrdynvcClientPdu> = OnceLock::new();
+static REQ_V1_DECODED_SERVER: OnceLock<DrdynvcServerPdu<'static>> = OnceLock::new();
+static REQ_V2_DECODED_SERVER: OnceLock<DrdynvcServerPdu<'static>> = OnceLock::new();
+static RESP_V1_DECODED_CLIENT: OnceLock<DrdynvcClientPdu<'static>> = OnceLock::new();
 
-fn req_v1_decoded_server() -> &'static DrdynvcServerPdu {
+fn req_v1_decoded_server() -> &'static DrdynvcServerPdu<'static> {
     REQ_V1_DECODED_SERVER
         .get_or_init(|| DrdynvcServerPdu::Capabilities(CapabilitiesRequestPdu::new(CapsVersion::V1, None)))
 }
 
-fn req_v2_decoded_server() -> &'static DrdynvcServerPdu {
+fn req_v2_decoded_server() -> &'static DrdynvcServerPdu<'static> {
     REQ_V2_DECODED_SERVER.get_or_init(|| {
         DrdynvcServerPdu::Capabilities(CapabilitiesRequestPdu::new(
             CapsVersion::V2,
@@ -22,7 +22,7 @@ fn req_v2_decoded_server() -> &'static DrdynvcServerPdu {
     })
 }
 
-fn resp_v1_decoded_client() -> &'static DrdynvcClientPdu {
+fn resp_v1_decoded_client() -> &'static DrdynvcClientPdu<'static> {
     RESP_V1_DECODED_CLIENT.get_or_init(|| DrdynvcClientPdu::Capabilities(CapabilitiesResponsePdu::new(CapsVersion::V1)))
 }
 
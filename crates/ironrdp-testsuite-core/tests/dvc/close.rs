@@ -3,14 +3,14 @@ use super::*;
 const CHANNEL_ID: u32 = 0x0303;
 const ENCODED: [u8; 3] = [0x41, 0x03, 0x03];
 
-static DECODED_CLIENT: OnceLock<DrdynvcClientPdu> = OnceLock::new();
-static DECODED_SERVER: OnceLock<DrdynvcServerPdu> = OnceLock::new();
+static DECODED_CLIENT: OnceLock<DrdynvcClientPdu<'static>> = OnceLock::new();
+static DECODED_SERVER: OnceLock<DrdynvcServerPdu<'static>> = OnceLock::new();
 
-fn decoded_client() -> &'static DrdynvcClientPdu {
+fn decoded_client() -> &'static DrdynvcClientPdu<'static> {
     DECODED_CLIENT.get_or_init(|| DrdynvcClientPdu::Close(ClosePdu::new(CHANNEL_ID).with_cb_id_type(FieldType::U16)))
 }
 
-fn decoded_server() -> &'static DrdynvcServerPdu {
+fn decoded_server() -> &'static DrdynvcServerPdu<'static> {
     DECODED_SERVER.get_or_init(|| DrdynvcServerPdu::Close(ClosePdu::new(CHANNEL_ID).with_cb_id_type(FieldType::U16)))
 }
 
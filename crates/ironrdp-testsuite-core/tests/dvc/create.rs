@@ -4,15 +4,15 @@ const CHANNEL_ID: u32 = 0x0000_0003;
 const REQ_ENCODED: [u8; 10] = [0x10, 0x03, 0x74, 0x65, 0x73, 0x74, 0x64, 0x76, 0x63, 0x00];
 const RESP_ENCODED: [u8; 6] = [0x10, 0x03, 0x00, 0x00, 0x00, 0x00];
 
-static REQ_DECODED_SERVER: OnceLock<DrdynvcServerPdu> = OnceLock::new();
-static RESP_DECODED_CLIENT: OnceLock<DrdynvcClientPdu> = OnceLock::new();
+static REQ_DECODED_SERVER: OnceLock<DrdynvcServerPdu<'static>> = OnceLock::new();
+static RESP_DECODED_CLIENT: OnceLock<DrdynvcClientPdu<'static>> = OnceLock::new();
 
-fn req_decoded_server() -> &'static DrdynvcServerPdu {
+fn req_decoded_server() -> &'static DrdynvcServerPdu<'static> {
     REQ_DECODED_SERVER
         .get_or_init(|| DrdynvcServerPdu::Create(CreateRequestPdu::new(CHANNEL_ID, String::from("testdvc"))))
 }
 
-fn resp_decoded_client() -> &'static DrdynvcClientPdu {
+fn resp_decoded_client() -> &'static DrdynvcClientPdu<'static> {
     RESP_DECODED_CLIENT.get_or_init(|| DrdynvcClientPdu::Create(CreateResponsePdu::new(CHANNEL_ID, CreationStatus::OK)))
 }
 
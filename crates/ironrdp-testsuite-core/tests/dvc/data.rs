@@ -5,8 +5,8 @@ const PREFIX: [u8; 2] = [0x30, 0x03];
 const DATA: [u8; 12] = [0x71; 12];
 
 static ENCODED: OnceLock<Vec<u8>> = OnceLock::new();
-static DECODED_CLIENT: OnceLock<DrdynvcClientPdu> = OnceLock::new();
-static DECODED_SERVER: OnceLock<DrdynvcServerPdu> = OnceLock::new();
+static DECODED_CLIENT: OnceLock<DrdynvcClientPdu<'static>> = OnceLock::new();
+static DECODED_SERVER: OnceLock<DrdynvcServerPdu<'static>> = OnceLock::new();
 
 fn encoded() -> &'static Vec<u8> {
     ENCODED.get_or_init(|| {
@@ -16,11 +16,11 @@ fn encoded() -> &'static Vec<u8> {
     })
 }
 
-fn decoded_client() -> &'static DrdynvcClientPdu {
+fn decoded_client() -> &'static DrdynvcClientPdu<'static> {
     DECODED_CLIENT.get_or_init(|| DrdynvcClientPdu::Data(DrdynvcDataPdu::Data(DataPdu::new(CHANNEL_ID, DATA.to_vec()))))
 }
 
-fn decoded_server() -> &'static DrdynvcServerPdu {
+fn decoded_server() -> &'static DrdynvcServerPdu<'static> {
     DECODED_SERVER.get_or_init(|| DrdynvcServerPdu::Data(DrdynvcDataPdu::Data(DataPdu::new(CHANNEL_ID, DATA.to_vec()))))
 }
 
@@ -35,3 +35,17 @@ fn encodes_data() {
     test_encodes(decoded_client(), encoded());
     test_encodes(decoded_server(), encoded());
 }
+
+#[test]
+fn decodes_data_without_copying() {
+    let encoded = encoded();
+    let pdu = DrdynvcServerPdu::decode(&mut ReadCursor::new(encoded)).unwrap();
+
+    let DrdynvcServerPdu::Data(DrdynvcDataPdu::Data(data)) = &pdu else {
+        panic!("unexpected PDU: {pdu:?}");
+    };
+    assert!(matches!(data.data, Cow::Borrowed(_)));
+    assert_eq!(data.data.as_ptr(), encoded[PREFIX.len()..].as_ptr());
+
+    assert_eq!(pdu.into_owned(), *decoded_server());
+}
@@ -97,11 +97,11 @@ const EDGE_CASE_DATA: [u8; EDGE_CASE_LENGTH as usize] = [
 ];
 
 static ENCODED: OnceLock<Vec<u8>> = OnceLock::new();
-static DECODED_CLIENT: OnceLock<DrdynvcClientPdu> = OnceLock::new();
-static DECODED_SERVER: OnceLock<DrdynvcServerPdu> = OnceLock::new();
+static DECODED_CLIENT: OnceLock<DrdynvcClientPdu<'static>> = OnceLock::new();
+static DECODED_SERVER: OnceLock<DrdynvcServerPdu<'static>> = OnceLock::new();
 static EDGE_CASE_ENCODED: OnceLock<Vec<u8>> = OnceLock::new();
-static EDGE_CASE_DECODED_CLIENT: OnceLock<DrdynvcClientPdu> = OnceLock::new();
-static EDGE_CASE_DECODED_SERVER: OnceLock<DrdynvcServerPdu> = OnceLock::new();
+static EDGE_CASE_DECODED_CLIENT: OnceLock<DrdynvcClientPdu<'static>> = OnceLock::new();
+static EDGE_CASE_DECODED_SERVER: OnceLock<DrdynvcServerPdu<'static>> = OnceLock::new();
 
 fn encoded() -> &'static Vec<u8> {
     ENCODED.get_or_init(|| {
@@ -111,7 +111,7 @@ fn encoded() -> &'static Vec<u8> {
     })
 }
 
-fn decoded_client() -> &'static DrdynvcClientPdu {
+fn decoded_client() -> &'static DrdynvcClientPdu<'static> {
     DECODED_CLIENT.get_or_init(|| {
         DrdynvcClientPdu::Data(DrdynvcDataPdu::DataFirst(
             DataFirstPdu::new(CHANNEL_ID, LENGTH, DATA.to_vec())
@@ -121,7 +121,7 @@ fn decoded_client() -> &'static DrdynvcClientPdu {
     })
 }
 
-fn decoded_server() -> &'static DrdynvcServerPdu {
+fn decoded_server() -> &'static DrdynvcServerPdu<'static> {
     DECODED_SERVER.get_or_init(|| {
         DrdynvcServerPdu::Data(DrdynvcDataPdu::DataFirst(
             DataFirstPdu::new(CHANNEL_ID, LENGTH, DATA.to_vec())
@@ -139,7 +139,7 @@ fn edge_case_encoded() -> &'static Vec<u8> {
     })
 }
 
-fn edge_case_decoded_client() -> &'static DrdynvcClientPdu {
+fn edge_case_decoded_client() -> &'static DrdynvcClientPdu<'static> {
     EDGE_CASE_DECODED_CLIENT.get_or_init(|| {
         DrdynvcClientPdu::Data(DrdynvcDataPdu::DataFirst(
             DataFirstPdu::new(EDGE_CASE_CHANNEL_ID, EDGE_CASE_LENGTH, EDGE_CASE_DATA.to_vec())
@@ -149,7 +149,7 @@ fn edge_case_decoded_client() -> &'static DrdynvcClientPdu {
     })
 }
 
-fn edge_case_decoded_server() -> &'static DrdynvcServerPdu {
+fn edge_case_decoded_server() -> &'static DrdynvcServerPdu<'static> {
     EDGE_CASE_DECODED_SERVER.get_or_init(|| {
         DrdynvcServerPdu::Data(DrdynvcDataPdu::DataFirst(
             DataFirstPdu::new(EDGE_CASE_CHANNEL_ID, EDGE_CASE_LENGTH, EDGE_CASE_DATA.to_vec())
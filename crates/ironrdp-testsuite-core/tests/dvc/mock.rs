@@ -0,0 +1,74 @@
+use ironrdp_core::{impl_as_any, EncodeResult};
+use ironrdp_dvc::testing::{DvcMockEndpoint, MockError, MockPeer};
+use ironrdp_dvc::{DvcEncode, DvcMessage, DvcProcessor};
+use ironrdp_pdu::PduResult;
+
+use super::*;
+
+const CHANNEL_ID: u32 = 0x0303;
+
+struct Payload(Vec<u8>);
+
+impl Encode for Payload {
+    fn encode(&self, dst: &mut WriteCursor<'_>) -> EncodeResult<()> {
+        dst.write_slice(&self.0);
+        Ok(())
+    }
+
+    fn name(&self) -> &'static str {
+        "Payload"
+    }
+
+    fn size(&self) -> usize {
+        self.0.len()
+    }
+}
+
+impl DvcEncode for Payload {}
+
+/// Sends the channel ID when started, then echoes back the received PDUs.
+struct EchoProcessor;
+
+impl_as_any!(EchoProcessor);
+
+impl DvcProcessor for EchoProcessor {
+    fn channel_name(&self) -> &str {
+        "Echo"
+    }
+
+    fn start(&mut self, channel_id: u32) -> PduResult<Vec<DvcMessage>> {
+        Ok(vec![Box::new(Payload(channel_id.to_le_bytes().to_vec()))])
+    }
+
+    fn process(&mut self, _channel_id: u32, payload: &[u8]) -> PduResult<Vec<DvcMessage>> {
+        Ok(vec![Box::new(Payload(payload.to_vec()))])
+    }
+}
+
+#[test]
+fn mock_peer_drives_dvc_processor() {
+    let mut processor = EchoProcessor;
+    // Larger than a single DRDYNVC data PDU, hence fragmented over the mock transport.
+    let large_pdu = vec![0xAB; 4000];
+
+    MockPeer::new()
+        .expect(&CHANNEL_ID.to_le_bytes().to_vec())
+        .send(&vec![1, 2, 3])
+        .expect(&vec![1, 2, 3])
+        .send(&large_pdu)
+        .expect(&large_pdu)
+        .run(&mut DvcMockEndpoint::new(&mut processor, CHANNEL_ID))
+        .unwrap();
+}
+
+#[test]
+fn mock_peer_reports_unexpected_dvc_pdu() {
+    let mut processor = EchoProcessor;
+
+    let error = MockPeer::new()
+        .expect(&vec![0; 4])
+        .run(&mut DvcMockEndpoint::new(&mut processor, CHANNEL_ID))
+        .unwrap_err();
+
+    assert!(matches!(error, MockError::UnexpectedPdu { step: 1, .. }));
+}
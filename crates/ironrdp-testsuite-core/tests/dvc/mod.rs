@@ -1,5 +1,6 @@
 use ironrdp_core::Decode;
 use ironrdp_core::Encode;
+use ironrdp_core::IntoOwned;
 use ironrdp_core::{ReadCursor, WriteCursor};
 use ironrdp_dvc::pdu::ClosePdu;
 use ironrdp_dvc::pdu::DataPdu;
@@ -7,6 +8,7 @@ use ironrdp_dvc::pdu::{CapabilitiesRequestPdu, CapabilitiesResponsePdu, CapsVers
 use ironrdp_dvc::pdu::{CreateRequestPdu, CreateResponsePdu, CreationStatus};
 use ironrdp_dvc::pdu::{DataFirstPdu, FieldType};
 use ironrdp_dvc::pdu::{DrdynvcClientPdu, DrdynvcDataPdu, DrdynvcServerPdu};
+use std::borrow::Cow;
 use std::sync::OnceLock;
 
 // TODO: This likely generalizes to many tests and can thus be reused outside of this module.
@@ -28,3 +30,4 @@ mod close;
 mod create;
 mod data;
 mod data_first;
+mod mock;
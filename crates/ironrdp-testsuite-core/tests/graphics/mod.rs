@@ -1,5 +1,6 @@
 mod color_conversion;
 mod dwt;
 mod image_processing;
+mod rfx_encoder;
 mod rle;
 mod rlgr;
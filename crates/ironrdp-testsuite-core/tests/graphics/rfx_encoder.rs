@@ -0,0 +1,208 @@
+use ironrdp_graphics::image_processing::PixelFormat;
+use ironrdp_graphics::rfx_encoder::{RfxEncoder, RfxEncoderError, RfxSourceImage};
+use ironrdp_pdu::codecs::rfx::{EntropyAlgorithm, RfxRectangle};
+use ironrdp_pdu::geometry::InclusiveRectangle;
+use ironrdp_session::image::DecodedImage;
+use ironrdp_session::rfx::DecodingContext;
+use rstest::rstest;
+
+const WIDTH: u16 = 150;
+const HEIGHT: u16 = 90;
+const FORMAT_SIZE: usize = 4;
+
+// RemoteFX being lossy, decoded colors are compared with a tolerance.
+const MAX_COLOR_DIFFERENCE: u8 = 8;
+
+const SYNC_BLOCK_TYPE: [u8; 2] = [0xC0, 0xCC];
+const FRAME_BEGIN_BLOCK_TYPE: [u8; 2] = [0xC4, 0xCC];
+
+/// BGRX image made of smooth gradients, plus a sharp rectangle
+fn image(width: u16, height: u16, rectangle_color: [u8; 3]) -> Vec<u8> {
+    let (width, height) = (usize::from(width), usize::from(height));
+    let mut data = Vec::with_capacity(width * height * FORMAT_SIZE);
+
+    for y in 0..height {
+        for x in 0..width {
+            let [b, g, r] = if (width / 4..width / 2).contains(&x) && (height / 4..height / 2).contains(&y) {
+                rectangle_color
+            } else {
+                [
+                    (x * 255 / width) as u8,
+                    (y * 255 / height) as u8,
+                    ((x + y) * 255 / (width + height)) as u8,
+                ]
+            };
+            data.extend_from_slice(&[b, g, r, 0xFF]);
+        }
+    }
+
+    data
+}
+
+fn source(data: &[u8], width: u16, height: u16) -> RfxSourceImage<'_> {
+    RfxSourceImage {
+        data,
+        width,
+        height,
+        stride: usize::from(width) * FORMAT_SIZE,
+        format: PixelFormat::BgrX32,
+    }
+}
+
+fn decode_frames(width: u16, height: u16, frames: &[Vec<u8>]) -> (DecodedImage, Vec<u32>) {
+    let destination = InclusiveRectangle {
+        left: 0,
+        top: 0,
+        right: width - 1,
+        bottom: height - 1,
+    };
+    let mut image = DecodedImage::new(PixelFormat::BgrX32, width, height);
+    let mut context = DecodingContext::new();
+
+    let frame_ids = frames
+        .iter()
+        .map(|frame| {
+            let mut input = frame.as_slice();
+            let (frame_id, _) = context.decode(&mut image, &destination, &mut input).unwrap();
+            assert!(input.is_empty());
+            frame_id
+        })
+        .collect();
+
+    (image, frame_ids)
+}
+
+/// Largest difference between the colors of two BGRX pixels of the images, within `rectangle`
+fn max_color_difference(expected: &[u8], actual: &[u8], width: u16, rectangle: &InclusiveRectangle) -> u8 {
+    let mut max = 0;
+
+    for y in rectangle.top..=rectangle.bottom {
+        for x in rectangle.left..=rectangle.right {
+            let offset = (usize::from(y) * usize::from(width) + usize::from(x)) * FORMAT_SIZE;
+            for i in offset..offset + 3 {
+                max = max.max(expected[i].abs_diff(actual[i]));
+            }
+        }
+    }
+
+    max
+}
+
+fn whole(width: u16, height: u16) -> InclusiveRectangle {
+    InclusiveRectangle {
+        left: 0,
+        top: 0,
+        right: width - 1,
+        bottom: height - 1,
+    }
+}
+
+#[rstest]
+#[case(EntropyAlgorithm::Rlgr1)]
+#[case(EntropyAlgorithm::Rlgr3)]
+fn round_trip(#[case] entropy_algorithm: EntropyAlgorithm) {
+    let data = image(WIDTH, HEIGHT, [0x20, 0x40, 0xE0]);
+
+    let mut encoder = RfxEncoder::new(entropy_algorithm);
+    let frame = encoder.encode(&source(&data, WIDTH, HEIGHT)).unwrap();
+
+    let (decoded, frame_ids) = decode_frames(WIDTH, HEIGHT, &[frame]);
+
+    assert_eq!(frame_ids, [0]);
+    assert!(max_color_difference(&data, decoded.data(), WIDTH, &whole(WIDTH, HEIGHT)) <= MAX_COLOR_DIFFERENCE);
+}
+
+#[test]
+fn headers_sent_with_first_frame_and_size_change() {
+    let data = image(WIDTH, HEIGHT, [0x20, 0x40, 0xE0]);
+    let mut encoder = RfxEncoder::new(EntropyAlgorithm::Rlgr3);
+
+    let first = encoder.encode(&source(&data, WIDTH, HEIGHT)).unwrap();
+    let second = encoder.encode(&source(&data, WIDTH, HEIGHT)).unwrap();
+    let resized = encoder.encode(&source(&data, WIDTH, HEIGHT / 2)).unwrap();
+    encoder.reset();
+    let after_reset = encoder.encode(&source(&data, WIDTH, HEIGHT / 2)).unwrap();
+
+    assert_eq!(first[..2], SYNC_BLOCK_TYPE);
+    assert_eq!(second[..2], FRAME_BEGIN_BLOCK_TYPE);
+    assert_eq!(resized[..2], SYNC_BLOCK_TYPE);
+    assert_eq!(after_reset[..2], SYNC_BLOCK_TYPE);
+
+    let (decoded, frame_ids) = decode_frames(WIDTH, HEIGHT, &[first, second]);
+
+    assert_eq!(frame_ids, [0, 1]);
+    assert!(max_color_difference(&data, decoded.data(), WIDTH, &whole(WIDTH, HEIGHT)) <= MAX_COLOR_DIFFERENCE);
+}
+
+#[test]
+fn region_update() {
+    let first = image(WIDTH, HEIGHT, [0x20, 0x40, 0xE0]);
+    let second = image(WIDTH, HEIGHT, [0xE0, 0x40, 0x20]);
+    let mut encoder = RfxEncoder::new(EntropyAlgorithm::Rlgr3);
+
+    let full_frame = encoder.encode(&source(&first, WIDTH, HEIGHT)).unwrap();
+    let updated = RfxRectangle {
+        x: WIDTH / 4,
+        y: HEIGHT / 4,
+        width: WIDTH / 4,
+        height: HEIGHT / 4,
+    };
+    let region_frame = encoder
+        .encode_region(&source(&second, WIDTH, HEIGHT), &[updated.clone()])
+        .unwrap();
+
+    // Only the tiles intersecting the rectangle are sent.
+    assert!(region_frame.len() < full_frame.len() / 2);
+
+    let (decoded, _) = decode_frames(WIDTH, HEIGHT, &[full_frame, region_frame]);
+
+    let updated = InclusiveRectangle {
+        left: updated.x,
+        top: updated.y,
+        right: updated.x + updated.width - 1,
+        bottom: updated.y + updated.height - 1,
+    };
+    let untouched = InclusiveRectangle {
+        left: WIDTH / 2,
+        top: 0,
+        right: WIDTH - 1,
+        bottom: HEIGHT - 1,
+    };
+    assert!(max_color_difference(&second, decoded.data(), WIDTH, &updated) <= MAX_COLOR_DIFFERENCE);
+    assert!(max_color_difference(&first, decoded.data(), WIDTH, &untouched) <= MAX_COLOR_DIFFERENCE);
+}
+
+#[test]
+fn invalid_image() {
+    let data = image(WIDTH, HEIGHT, [0x20, 0x40, 0xE0]);
+    let mut encoder = RfxEncoder::new(EntropyAlgorithm::Rlgr3);
+
+    let empty = source(&data, 0, HEIGHT);
+    assert!(matches!(encoder.encode(&empty), Err(RfxEncoderError::InvalidImage(_))));
+
+    let truncated = source(&data[..data.len() - 1], WIDTH, HEIGHT);
+    assert!(matches!(
+        encoder.encode(&truncated),
+        Err(RfxEncoderError::InvalidImage(_))
+    ));
+
+    let short_stride = RfxSourceImage {
+        stride: usize::from(WIDTH),
+        ..source(&data, WIDTH, HEIGHT)
+    };
+    assert!(matches!(
+        encoder.encode(&short_stride),
+        Err(RfxEncoderError::InvalidImage(_))
+    ));
+
+    let out_of_bounds = RfxRectangle {
+        x: WIDTH - 10,
+        y: 0,
+        width: 20,
+        height: 10,
+    };
+    assert!(matches!(
+        encoder.encode_region(&source(&data, WIDTH, HEIGHT), &[out_of_bounds]),
+        Err(RfxEncoderError::InvalidRectangle(_))
+    ));
+}
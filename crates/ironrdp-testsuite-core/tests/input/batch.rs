@@ -0,0 +1,183 @@
+use std::time::{Duration, Instant};
+
+use ironrdp_input::batch::{InputBatcher, MAX_EVENTS_PER_PDU};
+use ironrdp_pdu::input::fast_path::{FastPathInputEvent, KeyboardFlags};
+use ironrdp_pdu::input::mouse::PointerFlags;
+use ironrdp_pdu::input::mouse_rel::PointerRelFlags;
+use ironrdp_pdu::input::{MousePdu, MouseRelPdu};
+
+const INTERVAL: Duration = Duration::from_millis(10);
+
+fn mouse_move(x: u16, y: u16) -> FastPathInputEvent {
+    FastPathInputEvent::MouseEvent(MousePdu {
+        flags: PointerFlags::MOVE,
+        number_of_wheel_rotation_units: 0,
+        x_position: x,
+        y_position: y,
+    })
+}
+
+fn relative_move(x_delta: i16, y_delta: i16) -> FastPathInputEvent {
+    FastPathInputEvent::MouseEventRel(MouseRelPdu {
+        flags: PointerRelFlags::MOVE,
+        x_delta,
+        y_delta,
+    })
+}
+
+fn left_button_down(x: u16, y: u16) -> FastPathInputEvent {
+    FastPathInputEvent::MouseEvent(MousePdu {
+        flags: PointerFlags::DOWN | PointerFlags::LEFT_BUTTON,
+        number_of_wheel_rotation_units: 0,
+        x_position: x,
+        y_position: y,
+    })
+}
+
+#[test]
+fn successive_moves_are_coalesced() {
+    let mut batcher = InputBatcher::new(INTERVAL);
+    let start = Instant::now();
+
+    batcher.push([mouse_move(1, 1), mouse_move(2, 2)], start);
+    batcher.push([mouse_move(3, 3)], start + Duration::from_millis(5));
+
+    assert_eq!(batcher.flush().expect("pending events").0, [mouse_move(3, 3)]);
+    assert!(batcher.is_empty());
+}
+
+#[test]
+fn moves_around_other_events_are_kept() {
+    let mut batcher = InputBatcher::new(INTERVAL);
+
+    batcher.push(
+        [
+            mouse_move(1, 1),
+            mouse_move(2, 2),
+            left_button_down(2, 2),
+            mouse_move(3, 3),
+            FastPathInputEvent::KeyboardEvent(KeyboardFlags::empty(), 0x1E),
+            mouse_move(4, 4),
+            mouse_move(5, 5),
+        ],
+        Instant::now(),
+    );
+
+    assert_eq!(
+        batcher.flush().expect("pending events").0,
+        [
+            mouse_move(2, 2),
+            left_button_down(2, 2),
+            mouse_move(3, 3),
+            FastPathInputEvent::KeyboardEvent(KeyboardFlags::empty(), 0x1E),
+            mouse_move(5, 5),
+        ]
+    );
+}
+
+#[test]
+fn relative_moves_are_summed() {
+    let mut batcher = InputBatcher::new(INTERVAL);
+
+    batcher.push(
+        [relative_move(1, -2), relative_move(3, 4), relative_move(i16::MAX, 0)],
+        Instant::now(),
+    );
+
+    assert_eq!(
+        batcher.flush().expect("pending events").0,
+        [relative_move(4, 2), relative_move(i16::MAX, 0)]
+    );
+}
+
+#[test]
+fn events_are_due_after_the_interval() {
+    let mut batcher = InputBatcher::new(INTERVAL);
+    let start = Instant::now();
+
+    assert_eq!(batcher.deadline(), None);
+    assert_eq!(batcher.poll(start), None);
+
+    batcher.push([mouse_move(1, 1)], start);
+    batcher.push([mouse_move(2, 2)], start + Duration::from_millis(8));
+    assert_eq!(batcher.deadline(), Some(start + INTERVAL));
+
+    assert_eq!(batcher.poll(start + Duration::from_millis(9)), None);
+
+    let pdu = batcher.poll(start + INTERVAL).expect("due events");
+    assert_eq!(pdu.0, [mouse_move(2, 2)]);
+    assert_eq!(batcher.deadline(), None);
+}
+
+#[test]
+fn full_batches_are_due_right_away() {
+    let mut batcher = InputBatcher::new(INTERVAL);
+    let start = Instant::now();
+
+    let events = (0..=MAX_EVENTS_PER_PDU).map(|i| {
+        let flags = if i % 2 == 0 {
+            KeyboardFlags::empty()
+        } else {
+            KeyboardFlags::RELEASE
+        };
+
+        FastPathInputEvent::KeyboardEvent(flags, 0x1E)
+    });
+
+    batcher.push(events, start);
+    assert_eq!(batcher.deadline(), Some(start));
+
+    assert_eq!(batcher.poll(start).expect("due events").0.len(), MAX_EVENTS_PER_PDU);
+    assert_eq!(batcher.deadline(), Some(start + INTERVAL));
+
+    assert_eq!(batcher.flush().expect("pending events").0.len(), 1);
+    assert_eq!(batcher.flush(), None);
+}
+
+fn key_events(count: usize) -> impl Iterator<Item = FastPathInputEvent> {
+    (0..count).map(|i| {
+        let flags = if i % 2 == 0 {
+            KeyboardFlags::empty()
+        } else {
+            KeyboardFlags::RELEASE
+        };
+
+        FastPathInputEvent::KeyboardEvent(flags, 0x1E)
+    })
+}
+
+#[test]
+fn max_events_limit() {
+    let mut batcher = InputBatcher::new(INTERVAL);
+    batcher.set_max_events(4);
+    let start = Instant::now();
+
+    batcher.push(key_events(3), start);
+    assert_eq!(batcher.deadline(), Some(start + INTERVAL));
+
+    batcher.push(key_events(7), start);
+    assert_eq!(batcher.deadline(), Some(start));
+
+    let pdus = batcher.flush_all();
+    assert_eq!(pdus.iter().map(|pdu| pdu.0.len()).collect::<Vec<_>>(), [4, 4, 2]);
+    assert!(batcher.is_empty());
+}
+
+#[test]
+fn max_pdu_size_limit() {
+    let mut batcher = InputBatcher::new(INTERVAL);
+    // Header, and three keyboard events of two bytes
+    batcher.set_max_pdu_size(4 + 3 * 2);
+
+    batcher.push(key_events(8), Instant::now());
+
+    let pdus = batcher.flush_encoded().unwrap();
+    assert_eq!(pdus.len(), 3);
+    assert!(pdus.iter().all(|pdu| pdu.len() <= 10));
+    assert_eq!(pdus[2].len(), 2 + 2 * 2);
+
+    // A PDU holds at least one event
+    batcher.set_max_pdu_size(1);
+    batcher.push(key_events(2), Instant::now());
+    assert_eq!(batcher.flush_all().len(), 2);
+}
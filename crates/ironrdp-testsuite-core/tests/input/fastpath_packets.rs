@@ -1,8 +1,10 @@
 use ironrdp_input::*;
 use ironrdp_pdu::input::fast_path::{FastPathInputEvent, KeyboardFlags, SynchronizeFlags};
 use ironrdp_pdu::input::mouse::PointerFlags;
+use ironrdp_pdu::input::mouse_rel::PointerRelFlags;
 use ironrdp_pdu::input::mouse_x::PointerXFlags;
-use ironrdp_pdu::input::{MousePdu, MouseXPdu};
+use ironrdp_pdu::input::{MousePdu, MouseRelPdu, MouseXPdu};
+use ironrdp_pdu::rdp::capability_sets::InputFlags;
 use rstest::rstest;
 
 enum MouseFlags {
@@ -167,6 +169,57 @@ fn keyboard_repeat() {
     assert_eq!(actual_inputs.as_slice(), expected_inputs.as_slice());
 }
 
+#[test]
+fn unicode_keyboard() {
+    let mut db = Database::default();
+
+    let ops = [
+        Operation::UnicodeKeyPressed('é'),
+        Operation::UnicodeKeyPressed('😀'),
+        Operation::UnicodeKeyPressed('é'),
+        Operation::UnicodeKeyReleased('é'),
+        Operation::UnicodeKeyReleased('é'),
+        Operation::UnicodeKeyReleased('😀'),
+    ];
+
+    // U+1F600 is sent as a surrogate pair.
+    let expected_inputs = [
+        FastPathInputEvent::UnicodeKeyboardEvent(KeyboardFlags::empty(), 0x00E9),
+        FastPathInputEvent::UnicodeKeyboardEvent(KeyboardFlags::empty(), 0xD83D),
+        FastPathInputEvent::UnicodeKeyboardEvent(KeyboardFlags::empty(), 0xDE00),
+        FastPathInputEvent::UnicodeKeyboardEvent(KeyboardFlags::RELEASE, 0x00E9),
+        FastPathInputEvent::UnicodeKeyboardEvent(KeyboardFlags::empty(), 0x00E9),
+        FastPathInputEvent::UnicodeKeyboardEvent(KeyboardFlags::RELEASE, 0x00E9),
+        FastPathInputEvent::UnicodeKeyboardEvent(KeyboardFlags::RELEASE, 0xD83D),
+        FastPathInputEvent::UnicodeKeyboardEvent(KeyboardFlags::RELEASE, 0xDE00),
+    ];
+
+    let actual_inputs = db.apply(ops);
+
+    assert_eq!(actual_inputs.as_slice(), expected_inputs.as_slice());
+    assert!(!db.is_unicode_key_pressed('é'));
+    assert!(!db.is_unicode_key_pressed('😀'));
+}
+
+#[test]
+fn unicode_keyboard_release_all() {
+    let mut db = Database::default();
+
+    let _ = db.apply([Operation::UnicodeKeyPressed('a'), Operation::UnicodeKeyPressed('😀')]);
+    assert!(db.is_unicode_key_pressed('😀'));
+
+    let expected_inputs = [
+        FastPathInputEvent::UnicodeKeyboardEvent(KeyboardFlags::RELEASE, 0x0061),
+        FastPathInputEvent::UnicodeKeyboardEvent(KeyboardFlags::RELEASE, 0xD83D),
+        FastPathInputEvent::UnicodeKeyboardEvent(KeyboardFlags::RELEASE, 0xDE00),
+    ];
+
+    let actual_inputs = db.release_all();
+
+    assert_eq!(actual_inputs.as_slice(), expected_inputs.as_slice());
+    assert!(!db.is_unicode_key_pressed('a'));
+}
+
 #[test]
 fn mouse_button_no_duplicate() {
     let mut db = Database::default();
@@ -328,3 +381,489 @@ fn wheel_rotations() {
 
     assert_eq!(actual_inputs.as_slice(), expected_inputs.as_slice());
 }
+
+#[test]
+fn relative_mouse_mode_requires_server_support() {
+    let mut db = Database::new();
+
+    assert!(!db.set_mouse_mode(MouseMode::Relative));
+    assert_eq!(db.mouse_mode(), MouseMode::Absolute);
+
+    db.set_server_input_flags(InputFlags::MOUSE_RELATIVE);
+    assert!(db.set_mouse_mode(MouseMode::Relative));
+    assert_eq!(db.mouse_mode(), MouseMode::Relative);
+
+    db.set_server_input_flags(InputFlags::empty());
+    assert_eq!(db.mouse_mode(), MouseMode::Absolute);
+}
+
+#[test]
+fn relative_mouse_mode() {
+    let mut db = Database::new();
+    db.set_server_input_flags(InputFlags::MOUSE_RELATIVE);
+    db.set_mouse_mode(MouseMode::Relative);
+
+    let ops = [
+        Operation::RelativeMouseMove(MouseDelta { x: 5, y: -3 }),
+        Operation::RelativeMouseMove(MouseDelta { x: 0, y: 0 }),
+        Operation::MouseButtonPressed(MouseButton::Left),
+        Operation::MouseButtonReleased(MouseButton::Left),
+        Operation::MouseButtonPressed(MouseButton::X1),
+    ];
+
+    let actual_inputs = db.apply(ops);
+
+    let expected_inputs = [
+        FastPathInputEvent::MouseEventRel(MouseRelPdu {
+            flags: PointerRelFlags::MOVE,
+            x_delta: 5,
+            y_delta: -3,
+        }),
+        FastPathInputEvent::MouseEventRel(MouseRelPdu {
+            flags: PointerRelFlags::DOWN | PointerRelFlags::BUTTON1,
+            x_delta: 0,
+            y_delta: 0,
+        }),
+        FastPathInputEvent::MouseEventRel(MouseRelPdu {
+            flags: PointerRelFlags::BUTTON1,
+            x_delta: 0,
+            y_delta: 0,
+        }),
+        FastPathInputEvent::MouseEventRel(MouseRelPdu {
+            flags: PointerRelFlags::DOWN | PointerRelFlags::XBUTTON1,
+            x_delta: 0,
+            y_delta: 0,
+        }),
+    ];
+
+    assert_eq!(actual_inputs.as_slice(), expected_inputs.as_slice());
+
+    let released = db.release_all();
+
+    assert_eq!(
+        released.as_slice(),
+        [FastPathInputEvent::MouseEventRel(MouseRelPdu {
+            flags: PointerRelFlags::XBUTTON1,
+            x_delta: 0,
+            y_delta: 0,
+        })]
+    );
+}
+
+#[test]
+fn relative_mouse_move_in_absolute_mode() {
+    let mut db = Database::new();
+
+    let ops = [
+        Operation::MouseMove(MousePosition { x: 10, y: 10 }),
+        Operation::RelativeMouseMove(MouseDelta { x: 5, y: -20 }),
+        Operation::RelativeMouseMove(MouseDelta { x: 0, y: -1 }),
+    ];
+
+    let actual_inputs = db.apply(ops);
+
+    let expected_inputs = [
+        FastPathInputEvent::MouseEvent(MousePdu {
+            flags: PointerFlags::MOVE,
+            number_of_wheel_rotation_units: 0,
+            x_position: 10,
+            y_position: 10,
+        }),
+        FastPathInputEvent::MouseEvent(MousePdu {
+            flags: PointerFlags::MOVE,
+            number_of_wheel_rotation_units: 0,
+            x_position: 15,
+            y_position: 0,
+        }),
+    ];
+
+    assert_eq!(actual_inputs.as_slice(), expected_inputs.as_slice());
+}
+
+#[test]
+fn sync_locks_only_when_needed() {
+    let mut db = Database::new();
+    assert_eq!(db.lock_keys(), None);
+
+    let sync = |caps_lock| Operation::SyncLocks {
+        scroll_lock: false,
+        num_lock: true,
+        caps_lock,
+        kana_lock: false,
+    };
+
+    let actual_inputs = db.apply([sync(false), sync(false)]);
+    assert_eq!(
+        actual_inputs.as_slice(),
+        [FastPathInputEvent::SyncEvent(SynchronizeFlags::NUM_LOCK)]
+    );
+
+    let actual_inputs = db.apply([sync(true)]);
+    assert_eq!(
+        actual_inputs.as_slice(),
+        [FastPathInputEvent::SyncEvent(
+            SynchronizeFlags::NUM_LOCK | SynchronizeFlags::CAPS_LOCK
+        )]
+    );
+
+    db.forget_lock_keys();
+    assert_eq!(db.apply([sync(true)]).len(), 1);
+}
+
+#[test]
+fn lock_key_presses_toggle_the_lock_state() {
+    let caps_lock = Scancode::from_u8(false, 0x3A);
+
+    let mut db = Database::new();
+    db.apply([Operation::SyncLocks {
+        scroll_lock: false,
+        num_lock: false,
+        caps_lock: false,
+        kana_lock: false,
+    }]);
+
+    // Repeated presses only toggle the lock once
+    db.apply([
+        Operation::KeyPressed(caps_lock),
+        Operation::KeyPressed(caps_lock),
+        Operation::KeyReleased(caps_lock),
+    ]);
+    assert_eq!(
+        db.lock_keys(),
+        Some(LockKeys {
+            caps_lock: true,
+            ..LockKeys::default()
+        })
+    );
+
+    let actual_inputs = db.apply([Operation::SyncLocks {
+        scroll_lock: false,
+        num_lock: false,
+        caps_lock: true,
+        kana_lock: false,
+    }]);
+    assert!(actual_inputs.is_empty());
+}
+
+#[test]
+fn ctrl_alt_del_combo() {
+    let mut db = Database::new();
+
+    let actual_inputs = db.apply_combo(&KeyCombo::ctrl_alt_del());
+
+    let expected_inputs = [
+        FastPathInputEvent::KeyboardEvent(KeyboardFlags::empty(), 0x1D),
+        FastPathInputEvent::KeyboardEvent(KeyboardFlags::empty(), 0x38),
+        FastPathInputEvent::KeyboardEvent(KeyboardFlags::EXTENDED, 0x53),
+        FastPathInputEvent::KeyboardEvent(KeyboardFlags::RELEASE | KeyboardFlags::EXTENDED, 0x53),
+        FastPathInputEvent::KeyboardEvent(KeyboardFlags::RELEASE, 0x38),
+        FastPathInputEvent::KeyboardEvent(KeyboardFlags::RELEASE, 0x1D),
+    ];
+    assert_eq!(actual_inputs.as_slice(), expected_inputs.as_slice());
+    assert!(db.keyboard_state().not_any());
+}
+
+#[test]
+fn combo_keeps_held_keys_pressed() {
+    let left_win = Scancode::from_u8(true, 0x5B);
+
+    let mut db = Database::new();
+    db.apply([Operation::KeyPressed(left_win)]);
+
+    let actual_inputs = db.apply_combo(&KeyCombo::win_l());
+
+    let expected_inputs = [
+        FastPathInputEvent::KeyboardEvent(KeyboardFlags::empty(), 0x26),
+        FastPathInputEvent::KeyboardEvent(KeyboardFlags::RELEASE, 0x26),
+    ];
+    assert_eq!(actual_inputs.as_slice(), expected_inputs.as_slice());
+    assert!(db.is_key_pressed(left_win));
+}
+
+#[test]
+fn combo_operations() {
+    let a = Scancode::from_u8(false, 0x1E);
+    let b = Scancode::from_u8(false, 0x30);
+
+    assert_eq!(
+        KeyCombo::new([a, b]).operations().as_slice(),
+        [
+            Operation::KeyPressed(a),
+            Operation::KeyPressed(b),
+            Operation::KeyReleased(b),
+            Operation::KeyReleased(a),
+        ]
+    );
+}
+
+#[test]
+fn modifiers_snapshot() {
+    let mut db = Database::new();
+    assert!(db.modifiers().is_empty());
+
+    db.apply([
+        Operation::KeyPressed(ModifierKey::RightCtrl.scancode()),
+        Operation::KeyPressed(ModifierKey::LeftShift.scancode()),
+        Operation::KeyPressed(Scancode::from_u8(false, 0x1E)),
+    ]);
+
+    let modifiers = db.modifiers();
+    assert_eq!(
+        modifiers,
+        ModifierState {
+            right_ctrl: true,
+            left_shift: true,
+            ..ModifierState::default()
+        }
+    );
+    assert!(modifiers.ctrl() && modifiers.shift());
+    assert!(!modifiers.alt() && !modifiers.meta());
+    assert!(db.is_modifier_pressed(ModifierKey::RightCtrl));
+    assert!(!db.is_modifier_pressed(ModifierKey::LeftCtrl));
+}
+
+#[test]
+fn release_modifiers_keeps_other_keys_and_buttons() {
+    let a = Scancode::from_u8(false, 0x1E);
+
+    let mut db = Database::new();
+    db.apply([
+        Operation::KeyPressed(ModifierKey::LeftAlt.scancode()),
+        Operation::KeyPressed(ModifierKey::RightMeta.scancode()),
+        Operation::KeyPressed(a),
+        Operation::MouseButtonPressed(MouseButton::Left),
+    ]);
+
+    let actual_inputs = db.release_modifiers();
+
+    let expected_inputs = [
+        FastPathInputEvent::KeyboardEvent(KeyboardFlags::RELEASE, 0x38),
+        FastPathInputEvent::KeyboardEvent(KeyboardFlags::RELEASE | KeyboardFlags::EXTENDED, 0x5C),
+    ];
+    assert_eq!(actual_inputs.as_slice(), expected_inputs.as_slice());
+    assert!(db.modifiers().is_empty());
+    assert!(db.is_key_pressed(a));
+    assert!(db.is_mouse_button_pressed(MouseButton::Left));
+}
+
+fn held_state_database() -> Database {
+    let mut db = Database::new();
+    db.apply([
+        Operation::SyncLocks {
+            scroll_lock: false,
+            num_lock: true,
+            caps_lock: false,
+            kana_lock: false,
+        },
+        Operation::MouseMove(MousePosition { x: 10, y: 20 }),
+        Operation::KeyPressed(ModifierKey::RightCtrl.scancode()),
+        Operation::UnicodeKeyPressed('é'),
+        Operation::MouseButtonPressed(MouseButton::Right),
+    ]);
+    db
+}
+
+#[test]
+fn snapshot_round_trip() {
+    let db = held_state_database();
+
+    let snapshot = db.to_snapshot();
+    assert_eq!(
+        snapshot,
+        InputSnapshot {
+            pressed_keys: vec![ModifierKey::RightCtrl.scancode()],
+            pressed_unicode_keys: vec!['é'],
+            pressed_mouse_buttons: vec![MouseButton::Right],
+            mouse_position: MousePosition { x: 10, y: 20 },
+            lock_keys: Some(LockKeys {
+                num_lock: true,
+                ..LockKeys::default()
+            }),
+        }
+    );
+
+    let restored = Database::from_snapshot(&snapshot);
+    assert_eq!(restored.to_snapshot(), snapshot);
+    assert!(restored.is_modifier_pressed(ModifierKey::RightCtrl));
+    assert!(restored.is_unicode_key_pressed('é'));
+    assert!(restored.is_mouse_button_pressed(MouseButton::Right));
+}
+
+#[test]
+fn snapshot_replay_on_new_connection() {
+    let snapshot = held_state_database().to_snapshot();
+
+    let mut db = Database::new();
+    let actual_inputs = db.apply(snapshot.operations());
+
+    let expected_inputs = [
+        FastPathInputEvent::SyncEvent(SynchronizeFlags::NUM_LOCK),
+        FastPathInputEvent::MouseEvent(MousePdu {
+            flags: PointerFlags::MOVE,
+            number_of_wheel_rotation_units: 0,
+            x_position: 10,
+            y_position: 20,
+        }),
+        FastPathInputEvent::KeyboardEvent(KeyboardFlags::EXTENDED, 0x1D),
+        FastPathInputEvent::UnicodeKeyboardEvent(KeyboardFlags::empty(), 0xE9),
+        FastPathInputEvent::MouseEvent(MousePdu {
+            flags: PointerFlags::DOWN | PointerFlags::RIGHT_BUTTON,
+            number_of_wheel_rotation_units: 0,
+            x_position: 10,
+            y_position: 20,
+        }),
+    ];
+    assert_eq!(actual_inputs.as_slice(), expected_inputs.as_slice());
+    assert_eq!(db.to_snapshot(), snapshot);
+}
+
+#[rstest]
+#[case::ignore(KeyRepeatPolicy::Ignore, &[
+    FastPathInputEvent::KeyboardEvent(KeyboardFlags::EXTENDED, 0x48),
+    FastPathInputEvent::KeyboardEvent(KeyboardFlags::RELEASE | KeyboardFlags::EXTENDED, 0x48),
+])]
+#[case::release_then_press(KeyRepeatPolicy::ReleaseThenPress, &[
+    FastPathInputEvent::KeyboardEvent(KeyboardFlags::EXTENDED, 0x48),
+    FastPathInputEvent::KeyboardEvent(KeyboardFlags::RELEASE | KeyboardFlags::EXTENDED, 0x48),
+    FastPathInputEvent::KeyboardEvent(KeyboardFlags::EXTENDED, 0x48),
+    FastPathInputEvent::KeyboardEvent(KeyboardFlags::RELEASE | KeyboardFlags::EXTENDED, 0x48),
+])]
+#[case::forward_repeat(KeyRepeatPolicy::ForwardRepeat, &[
+    FastPathInputEvent::KeyboardEvent(KeyboardFlags::EXTENDED, 0x48),
+    FastPathInputEvent::KeyboardEvent(KeyboardFlags::EXTENDED, 0x48),
+    FastPathInputEvent::KeyboardEvent(KeyboardFlags::RELEASE | KeyboardFlags::EXTENDED, 0x48),
+])]
+fn key_repeat_policy(#[case] policy: KeyRepeatPolicy, #[case] expected_inputs: &[FastPathInputEvent]) {
+    let up_arrow = Scancode::from_u8(true, 0x48);
+
+    let mut db = Database::new();
+    assert_eq!(db.key_repeat_policy(), KeyRepeatPolicy::ReleaseThenPress);
+    db.set_key_repeat_policy(policy);
+
+    let actual_inputs = db.apply([
+        Operation::KeyPressed(up_arrow),
+        Operation::KeyPressed(up_arrow),
+        Operation::KeyReleased(up_arrow),
+    ]);
+
+    assert_eq!(actual_inputs.as_slice(), expected_inputs);
+    assert!(!db.is_key_pressed(up_arrow));
+}
+
+#[test]
+fn mouse_positions_are_clamped_to_the_desktop() {
+    let mut db = Database::new();
+    db.apply([Operation::MouseMove(MousePosition { x: 1500, y: 900 })]);
+
+    // Shrinking the desktop moves the known position within the new bounds
+    db.set_desktop_size(1024, 768);
+    assert_eq!(db.mouse_position(), MousePosition { x: 1023, y: 767 });
+
+    let actual_inputs = db.apply([
+        Operation::MouseMove(MousePosition { x: 2000, y: 100 }),
+        Operation::RelativeMouseMove(MouseDelta { x: 0, y: 1000 }),
+    ]);
+
+    let expected_inputs = [
+        FastPathInputEvent::MouseEvent(MousePdu {
+            flags: PointerFlags::MOVE,
+            number_of_wheel_rotation_units: 0,
+            x_position: 1023,
+            y_position: 100,
+        }),
+        FastPathInputEvent::MouseEvent(MousePdu {
+            flags: PointerFlags::MOVE,
+            number_of_wheel_rotation_units: 0,
+            x_position: 1023,
+            y_position: 767,
+        }),
+    ];
+    assert_eq!(actual_inputs.as_slice(), expected_inputs.as_slice());
+}
+
+fn wheel_event(is_vertical: bool, rotation_units: i16) -> FastPathInputEvent {
+    FastPathInputEvent::MouseEvent(MousePdu {
+        flags: if is_vertical {
+            PointerFlags::VERTICAL_WHEEL
+        } else {
+            PointerFlags::HORIZONTAL_WHEEL
+        },
+        number_of_wheel_rotation_units: rotation_units,
+        x_position: 0,
+        y_position: 0,
+    })
+}
+
+fn wheel(is_vertical: bool, rotation_units: i16) -> Operation {
+    Operation::WheelRotations(WheelRotations {
+        is_vertical,
+        rotation_units,
+    })
+}
+
+#[test]
+fn natural_scrolling_and_scaling() {
+    let mut db = Database::new();
+    db.set_wheel_settings(WheelSettings {
+        natural_scrolling: true,
+        vertical_scale: 2.0,
+        horizontal_scale: -1.0,
+    });
+
+    let actual_inputs = db.apply([wheel(true, 120), wheel(false, 30)]);
+
+    // Scaled rotations are clamped to the range of the rotation field
+    let expected_inputs = [wheel_event(true, -240), wheel_event(false, 30)];
+    assert_eq!(actual_inputs.as_slice(), expected_inputs.as_slice());
+
+    assert_eq!(db.apply([wheel(true, -200)]).as_slice(), [wheel_event(true, 255)]);
+}
+
+#[test]
+fn fractional_wheel_rotations_are_accumulated() {
+    let mut db = Database::new();
+    db.set_wheel_settings(WheelSettings {
+        horizontal_scale: 0.4,
+        ..WheelSettings::default()
+    });
+
+    assert!(db.apply([wheel(false, 1), wheel(false, 1)]).is_empty());
+    assert_eq!(db.apply([wheel(false, 1)]).as_slice(), [wheel_event(false, 1)]);
+
+    // Axes are accumulated separately
+    assert_eq!(db.apply([wheel(true, 1)]).as_slice(), [wheel_event(true, 1)]);
+}
+
+#[test]
+fn release_keys_releases_modifiers_last() {
+    let mut db = Database::new();
+    db.apply([
+        Operation::SyncLocks {
+            scroll_lock: false,
+            num_lock: true,
+            caps_lock: false,
+            kana_lock: false,
+        },
+        Operation::MouseButtonPressed(MouseButton::Left),
+        Operation::KeyPressed(ModifierKey::LeftCtrl.scancode()),
+        Operation::KeyPressed(ModifierKey::RightAlt.scancode()),
+        Operation::KeyPressed(Scancode::from_u8(false, 0x2E)),
+        Operation::KeyPressed(Scancode::from_u8(true, 0x53)),
+    ]);
+
+    let actual_inputs = db.release_keys();
+
+    let expected_inputs = [
+        FastPathInputEvent::KeyboardEvent(KeyboardFlags::RELEASE, 0x2E),
+        FastPathInputEvent::KeyboardEvent(KeyboardFlags::RELEASE | KeyboardFlags::EXTENDED, 0x53),
+        FastPathInputEvent::KeyboardEvent(KeyboardFlags::RELEASE, 0x1D),
+        FastPathInputEvent::KeyboardEvent(KeyboardFlags::RELEASE | KeyboardFlags::EXTENDED, 0x38),
+        FastPathInputEvent::SyncEvent(SynchronizeFlags::NUM_LOCK),
+    ];
+    assert_eq!(actual_inputs.as_slice(), expected_inputs.as_slice());
+    assert!(db.keyboard_state().not_any());
+    assert!(db.is_mouse_button_pressed(MouseButton::Left));
+
+    assert_eq!(db.release_all().len(), 2);
+    assert!(!db.is_mouse_button_pressed(MouseButton::Left));
+}
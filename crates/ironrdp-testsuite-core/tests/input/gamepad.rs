@@ -0,0 +1,110 @@
+use ironrdp_core::encode_vec;
+use ironrdp_input::gamepad::{GamepadAxis, GamepadButtons, GamepadEncoder, GamepadState, GamepadStatePdu};
+use ironrdp_input::{Database, Operation};
+
+fn press(gamepad: u8, buttons: GamepadButtons) -> Operation {
+    Operation::GamepadButtonPressed { gamepad, buttons }
+}
+
+fn axis(gamepad: u8, axis: GamepadAxis, value: i16) -> Operation {
+    Operation::GamepadAxisMoved { gamepad, axis, value }
+}
+
+#[test]
+fn gamepad_state_encoding() {
+    let pdu = GamepadStatePdu {
+        gamepad: 1,
+        packet_number: 2,
+        state: GamepadState {
+            buttons: GamepadButtons::A | GamepadButtons::DPAD_UP,
+            left_trigger: 0xFF,
+            right_trigger: 0,
+            left_stick_x: -1,
+            left_stick_y: 0x1234,
+            right_stick_x: 0,
+            right_stick_y: i16::MIN,
+        },
+    };
+
+    assert_eq!(
+        encode_vec(&pdu).unwrap(),
+        [
+            0x01, // gamepad
+            0x02, 0x00, 0x00, 0x00, // dwPacketNumber
+            0x01, 0x10, // wButtons
+            0xFF, // bLeftTrigger
+            0x00, // bRightTrigger
+            0xFF, 0xFF, // sThumbLX
+            0x34, 0x12, // sThumbLY
+            0x00, 0x00, // sThumbRX
+            0x00, 0x80, // sThumbRY
+        ]
+    );
+}
+
+#[test]
+fn one_state_per_changed_gamepad() {
+    let mut encoder = GamepadEncoder::new();
+
+    let pdus = encoder.apply([
+        press(0, GamepadButtons::A),
+        axis(0, GamepadAxis::LeftStickX, 1000),
+        axis(2, GamepadAxis::RightTrigger, 300),
+        Operation::GamepadButtonReleased {
+            gamepad: 2,
+            buttons: GamepadButtons::B,
+        },
+    ]);
+
+    assert_eq!(
+        pdus.as_slice(),
+        [
+            GamepadStatePdu {
+                gamepad: 0,
+                packet_number: 1,
+                state: GamepadState {
+                    buttons: GamepadButtons::A,
+                    left_stick_x: 1000,
+                    ..GamepadState::default()
+                },
+            },
+            GamepadStatePdu {
+                gamepad: 2,
+                packet_number: 1,
+                state: GamepadState {
+                    right_trigger: 255,
+                    ..GamepadState::default()
+                },
+            },
+        ]
+    );
+
+    let pdus = encoder.apply([press(0, GamepadButtons::B)]);
+    assert_eq!(pdus[0].packet_number, 2);
+    assert_eq!(encoder.state(0).buttons, GamepadButtons::A | GamepadButtons::B);
+}
+
+#[test]
+fn ignored_gamepad_operations() {
+    let mut encoder = GamepadEncoder::new();
+    encoder.apply([press(0, GamepadButtons::A)]);
+
+    // No state change, and controller index out of range
+    assert!(encoder.apply([press(0, GamepadButtons::A)]).is_empty());
+    assert!(encoder.apply([press(4, GamepadButtons::A)]).is_empty());
+
+    let mut db = Database::new();
+    assert!(db.apply([press(0, GamepadButtons::X)]).is_empty());
+}
+
+#[test]
+fn reset_all_gamepads() {
+    let mut encoder = GamepadEncoder::new();
+    encoder.apply([press(0, GamepadButtons::A), axis(1, GamepadAxis::LeftStickY, -5)]);
+
+    let pdus = encoder.reset_all();
+
+    assert_eq!(pdus.len(), 2);
+    assert!(pdus.iter().all(|pdu| pdu.state == GamepadState::default()));
+    assert!(encoder.reset_all().is_empty());
+}
@@ -0,0 +1,87 @@
+use std::time::{Duration, Instant};
+
+use ironrdp_input::latency::{LatencyHistogram, LatencyMetrics, LatencyTracker, TimedEvent, TimedOperation};
+use ironrdp_input::{Database, Operation, Scancode};
+use ironrdp_pdu::input::fast_path::{FastPathInputEvent, KeyboardFlags};
+
+#[test]
+fn apply_timed_pairs_events_with_timestamps() {
+    let a = Scancode::from_u8(false, 0x1E);
+    let start = Instant::now();
+    let later = start + Duration::from_millis(5);
+
+    let mut db = Database::new();
+    let events = db.apply_timed([
+        TimedOperation::new(Operation::KeyPressed(a), start),
+        TimedOperation::from(Operation::KeyReleased(a)),
+        // Redundant operations produce no event
+        TimedOperation::new(Operation::KeyReleased(a), later),
+    ]);
+
+    assert_eq!(
+        events.as_slice(),
+        [
+            TimedEvent {
+                event: FastPathInputEvent::KeyboardEvent(KeyboardFlags::empty(), 0x1E),
+                timestamp: Some(start),
+            },
+            TimedEvent {
+                event: FastPathInputEvent::KeyboardEvent(KeyboardFlags::RELEASE, 0x1E),
+                timestamp: None,
+            },
+        ]
+    );
+}
+
+#[test]
+fn histogram_buckets() {
+    let mut histogram = LatencyHistogram::new();
+    assert_eq!(histogram.mean(), None);
+
+    for ms in [1, 3, 4, 40, 2000] {
+        histogram.record_input_latency(Duration::from_millis(ms));
+    }
+
+    assert_eq!(histogram.count(), 5);
+    assert_eq!(histogram.max(), Duration::from_millis(2000));
+    assert_eq!(histogram.mean(), Some(Duration::from_micros(409_600)));
+
+    let buckets: Vec<_> = histogram.buckets().filter(|(_, count)| *count != 0).collect();
+    assert_eq!(
+        buckets,
+        [
+            (Some(Duration::from_millis(1)), 1),
+            (Some(Duration::from_millis(5)), 2),
+            (Some(Duration::from_millis(50)), 1),
+            (None, 1),
+        ]
+    );
+
+    histogram.reset();
+    assert_eq!(histogram.count(), 0);
+}
+
+#[test]
+fn tracker_records_latency_on_frame_acknowledgement() {
+    let start = Instant::now();
+    let key_event = |timestamp| TimedEvent {
+        event: FastPathInputEvent::KeyboardEvent(KeyboardFlags::empty(), 0x1E),
+        timestamp,
+    };
+
+    let mut tracker = LatencyTracker::new();
+    tracker.sent(&[
+        key_event(Some(start)),
+        key_event(None),
+        key_event(Some(start + Duration::from_millis(10))),
+    ]);
+    assert_eq!(tracker.pending_count(), 2);
+
+    let mut histogram = LatencyHistogram::new();
+    tracker.frame_acknowledged(start + Duration::from_millis(30), &mut histogram);
+
+    assert_eq!(tracker.pending_count(), 0);
+    assert_eq!(histogram.count(), 2);
+    assert_eq!(histogram.max(), Duration::from_millis(30));
+    assert_eq!(histogram.mean(), Some(Duration::from_millis(25)));
+}
@@ -0,0 +1,98 @@
+use ironrdp_input::layout::{named_key_scancode, KeyStroke, KeyboardLayout, Modifiers, ALT_GR, SHIFT};
+use ironrdp_input::{Operation, Scancode};
+use rstest::rstest;
+
+#[rstest]
+#[case::lowercase('a', KeyStroke::new(Scancode::from_u8(false, 0x1E), Modifiers::NONE))]
+#[case::uppercase('A', KeyStroke::new(Scancode::from_u8(false, 0x1E), Modifiers::SHIFT))]
+#[case::digit('7', KeyStroke::new(Scancode::from_u8(false, 0x08), Modifiers::NONE))]
+#[case::symbol('@', KeyStroke::new(Scancode::from_u8(false, 0x03), Modifiers::SHIFT))]
+#[case::space(' ', KeyStroke::new(Scancode::from_u8(false, 0x39), Modifiers::NONE))]
+#[case::newline('\n', KeyStroke::new(Scancode::from_u8(false, 0x1C), Modifiers::NONE))]
+fn us_key_strokes(#[case] character: char, #[case] expected: KeyStroke) {
+    assert_eq!(KeyboardLayout::us().key_stroke(character), Some(expected));
+}
+
+#[test]
+fn us_layout_does_not_map_non_ascii_characters() {
+    let layout = KeyboardLayout::us();
+
+    assert_eq!(layout.key_stroke('é'), None);
+    assert_eq!(layout.key_stroke('€'), None);
+}
+
+#[test]
+fn modifiers_wrap_the_key() {
+    let a = Scancode::from_u8(false, 0x1E);
+    let key_stroke = KeyStroke::new(a, Modifiers::SHIFT_ALT_GR);
+
+    assert_eq!(
+        key_stroke.tap().into_vec(),
+        vec![
+            Operation::KeyPressed(SHIFT),
+            Operation::KeyPressed(ALT_GR),
+            Operation::KeyPressed(a),
+            Operation::KeyReleased(a),
+            Operation::KeyReleased(ALT_GR),
+            Operation::KeyReleased(SHIFT),
+        ]
+    );
+}
+
+#[rstest]
+#[case::character("a", Scancode::from_u8(false, 0x1E))]
+#[case::space(" ", Scancode::from_u8(false, 0x39))]
+#[case::enter("Enter", Scancode::from_u8(false, 0x1C))]
+#[case::arrow("ArrowLeft", Scancode::from_u8(true, 0x4B))]
+#[case::function("F12", Scancode::from_u8(false, 0x58))]
+fn key_values(#[case] key: &str, #[case] expected: Scancode) {
+    let key_stroke = KeyboardLayout::us().key_value(key).expect("known key");
+
+    assert_eq!(key_stroke.scancode, expected);
+}
+
+#[test]
+fn unknown_key_values() {
+    let layout = KeyboardLayout::us();
+
+    assert_eq!(layout.key_value("Unidentified"), None);
+    assert_eq!(layout.key_value(""), None);
+    assert_eq!(named_key_scancode("a"), None);
+}
+
+#[test]
+fn text_falls_back_to_unicode_events() {
+    let h = Scancode::from_u8(false, 0x23);
+
+    assert_eq!(
+        KeyboardLayout::us().text_operations("Hé"),
+        vec![
+            Operation::KeyPressed(SHIFT),
+            Operation::KeyPressed(h),
+            Operation::KeyReleased(h),
+            Operation::KeyReleased(SHIFT),
+            Operation::UnicodeKeyPressed('é'),
+            Operation::UnicodeKeyReleased('é'),
+        ]
+    );
+}
+
+#[test]
+fn custom_layout_table() {
+    // Subset of the French AZERTY layout
+    let table = [
+        ('a', KeyStroke::new(Scancode::from_u8(false, 0x10), Modifiers::NONE)),
+        ('é', KeyStroke::new(Scancode::from_u8(false, 0x03), Modifiers::NONE)),
+        ('€', KeyStroke::new(Scancode::from_u8(false, 0x12), Modifiers::ALT_GR)),
+    ];
+
+    let mut layout = KeyboardLayout::from_table(&table);
+
+    assert_eq!(layout.key_stroke('é'), Some(table[1].1));
+    assert_eq!(layout.key_value("€"), Some(table[2].1));
+    assert_eq!(layout.key_stroke('q'), None);
+
+    let q = KeyStroke::new(Scancode::from_u8(false, 0x1E), Modifiers::NONE);
+    assert_eq!(layout.insert('q', q), None);
+    assert_eq!(layout.key_stroke('q'), Some(q));
+}
@@ -1,2 +1,12 @@
+mod batch;
 mod fastpath_packets;
+mod gamepad;
+mod latency;
+mod layout;
+mod pen;
+mod recording;
+mod scancode;
+mod seat;
+mod shortcut;
 mod smoke;
+mod touch;
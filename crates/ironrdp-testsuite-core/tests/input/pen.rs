@@ -0,0 +1,157 @@
+use ironrdp_core::encode_vec;
+use ironrdp_input::pen::{PenContact, PenContactData, PenEncoder, PenEventPdu, PenFlags};
+use ironrdp_input::touch::ContactFlags;
+use ironrdp_input::{Database, Operation};
+
+const HOVERING: ContactFlags = ContactFlags::UPDATE.union(ContactFlags::IN_RANGE);
+const ENGAGED: ContactFlags = HOVERING.union(ContactFlags::IN_CONTACT);
+const DOWN: ContactFlags = ContactFlags::DOWN
+    .union(ContactFlags::IN_RANGE)
+    .union(ContactFlags::IN_CONTACT);
+
+fn pen_flags(pdu: Option<PenEventPdu>) -> Vec<Vec<(u8, ContactFlags)>> {
+    pdu.map(|pdu| {
+        pdu.frames
+            .iter()
+            .map(|frame| {
+                frame
+                    .contacts
+                    .iter()
+                    .map(|contact| (contact.device_id, contact.contact_flags))
+                    .collect()
+            })
+            .collect()
+    })
+    .unwrap_or_default()
+}
+
+#[test]
+fn pen_down_event_encoding() {
+    let mut encoder = PenEncoder::new();
+
+    let pdu = encoder
+        .apply([Operation::PenDown(PenContact::new(0, 10, 20))])
+        .expect("pen event");
+
+    assert_eq!(
+        encode_vec(&pdu).unwrap(),
+        [
+            0x08, 0x00, // eventId
+            0x0F, 0x00, 0x00, 0x00, // pduLength
+            0x00, // encodeTime
+            0x01, // frameCount
+            0x01, // contactCount
+            0x00, // frameOffset
+            0x00, // deviceId
+            0x00, // fieldsPresent
+            0x0A, // x
+            0x14, // y
+            0x19, // contactFlags
+        ]
+    );
+}
+
+#[test]
+fn pen_optional_fields_encoding() {
+    let contact = PenContact {
+        flags: PenFlags::BARREL_PRESSED,
+        pressure: Some(1024),
+        rotation: Some(90),
+        tilt_x: Some(-45),
+        tilt_y: Some(30),
+        ..PenContact::new(0, 10, 20)
+    };
+
+    assert_eq!(
+        encode_vec(&PenContactData::new(contact, DOWN)).unwrap(),
+        [
+            0x00, // deviceId
+            0x1F, // fieldsPresent
+            0x0A, // x
+            0x14, // y
+            0x19, // contactFlags
+            0x01, // penFlags
+            0x44, 0x00, // pressure
+            0x5A, // rotation
+            0x6D, // tiltX
+            0x1E, // tiltY
+        ]
+    );
+}
+
+#[test]
+fn pen_stroke() {
+    let mut encoder = PenEncoder::new();
+    let pen = PenContact::new(1, 10, 10);
+
+    assert_eq!(pen_flags(encoder.apply([Operation::PenMove(pen)])), [[(1, HOVERING)]]);
+    assert!(encoder.is_pen_in_range(1));
+    assert!(!encoder.is_pen_down(1));
+
+    assert_eq!(pen_flags(encoder.apply([Operation::PenDown(pen)])), [[(1, DOWN)]]);
+    assert!(encoder.is_pen_down(1));
+
+    assert_eq!(pen_flags(encoder.apply([Operation::PenMove(pen)])), [[(1, ENGAGED)]]);
+
+    assert_eq!(
+        pen_flags(encoder.apply([Operation::PenUp(pen)])),
+        [[(1, ContactFlags::UP | ContactFlags::IN_RANGE)]]
+    );
+    assert!(!encoder.is_pen_down(1));
+
+    assert_eq!(
+        pen_flags(encoder.apply([Operation::PenLeave(pen)])),
+        [[(1, ContactFlags::UPDATE)]]
+    );
+    assert!(!encoder.is_pen_in_range(1));
+}
+
+#[test]
+fn redundant_pen_operations_are_ignored() {
+    let mut encoder = PenEncoder::new();
+    let pen = PenContact::new(1, 10, 10);
+
+    assert_eq!(encoder.apply([Operation::PenUp(pen), Operation::PenLeave(pen)]), None);
+
+    encoder.apply([Operation::PenMove(pen)]);
+    assert_eq!(encoder.apply([Operation::PenUp(pen)]), None);
+}
+
+#[test]
+fn leaving_while_engaged_lifts_the_pen() {
+    let mut encoder = PenEncoder::new();
+    let pen = PenContact::new(1, 10, 10);
+
+    assert_eq!(
+        pen_flags(encoder.apply([Operation::PenDown(pen), Operation::PenLeave(pen)])),
+        [[(1, DOWN)], [(1, ContactFlags::UP)]]
+    );
+}
+
+#[test]
+fn cancel_all_pens() {
+    let mut encoder = PenEncoder::new();
+
+    encoder.apply([
+        Operation::PenDown(PenContact::new(1, 10, 10)),
+        Operation::PenMove(PenContact::new(2, 20, 20)),
+    ]);
+
+    assert_eq!(
+        pen_flags(encoder.cancel_all()),
+        [[
+            (1, ContactFlags::UP | ContactFlags::CANCELED),
+            (2, ContactFlags::UPDATE)
+        ]]
+    );
+    assert_eq!(encoder.cancel_all(), None);
+}
+
+#[test]
+fn database_ignores_pen_operations() {
+    let mut db = Database::new();
+
+    let events = db.apply([Operation::PenDown(PenContact::new(1, 10, 10))]);
+
+    assert!(events.is_empty());
+}
@@ -0,0 +1,80 @@
+use std::time::{Duration, Instant};
+
+use ironrdp_input::recording::{RecordedOperation, Recorder, Recording, Replayer};
+use ironrdp_input::{Database, MousePosition, Operation, Scancode};
+use ironrdp_pdu::input::fast_path::{FastPathInputEvent, KeyboardFlags};
+
+const A: Scancode = Scancode::from_u8(false, 0x1E);
+
+fn recording() -> Recording {
+    let start = Instant::now();
+
+    let mut recorder = Recorder::new();
+    recorder.record([Operation::KeyPressed(A)], start);
+    recorder.record(
+        [
+            Operation::KeyReleased(A),
+            Operation::MouseMove(MousePosition { x: 10, y: 20 }),
+        ],
+        start + Duration::from_millis(50),
+    );
+
+    recorder.into_recording()
+}
+
+#[test]
+fn recorder_captures_relative_timing() {
+    let recording = recording();
+
+    assert_eq!(
+        recording.operations,
+        [
+            RecordedOperation {
+                delay: Duration::ZERO,
+                operation: Operation::KeyPressed(A),
+            },
+            RecordedOperation {
+                delay: Duration::from_millis(50),
+                operation: Operation::KeyReleased(A),
+            },
+            RecordedOperation {
+                delay: Duration::ZERO,
+                operation: Operation::MouseMove(MousePosition { x: 10, y: 20 }),
+            },
+        ]
+    );
+    assert_eq!(recording.duration(), Duration::from_millis(50));
+}
+
+#[test]
+fn replayer_paces_operations() {
+    let start = Instant::now();
+    let mut db = Database::new();
+    let mut replayer = Replayer::new(recording(), start);
+
+    assert_eq!(replayer.deadline(), Some(start));
+    assert_eq!(
+        replayer.poll(start, &mut db).as_slice(),
+        [FastPathInputEvent::KeyboardEvent(KeyboardFlags::empty(), 0x1E)]
+    );
+
+    assert_eq!(replayer.deadline(), Some(start + Duration::from_millis(50)));
+    assert!(replayer.poll(start + Duration::from_millis(49), &mut db).is_empty());
+
+    // Late polls catch up with all the operations due
+    let events = replayer.poll(start + Duration::from_millis(70), &mut db);
+    assert_eq!(events.len(), 2);
+    assert!(replayer.is_finished());
+    assert_eq!(replayer.deadline(), None);
+    assert_eq!(db.mouse_position(), MousePosition { x: 10, y: 20 });
+}
+
+#[test]
+fn recording_round_trips_through_serde() {
+    let recording = recording();
+
+    let serialized = serde_json::to_string(&recording).unwrap();
+    let deserialized: Recording = serde_json::from_str(&serialized).unwrap();
+
+    assert_eq!(deserialized, recording);
+}
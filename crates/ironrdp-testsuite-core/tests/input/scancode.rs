@@ -0,0 +1,80 @@
+use ironrdp_input::scancode::{
+    from_dom_code, from_evdev, from_mac_keycode, from_usb_hid, to_dom_code, to_evdev, to_mac_keycode, to_usb_hid,
+};
+use ironrdp_input::Scancode;
+use rstest::rstest;
+
+#[rstest]
+#[case::letter(Scancode::from_u8(false, 0x1E), 30, 0x04, Some(0x00))]
+#[case::enter(Scancode::from_u8(false, 0x1C), 28, 0x28, Some(0x24))]
+#[case::keypad_enter(Scancode::from_u8(true, 0x1C), 96, 0x58, Some(0x4C))]
+#[case::left_arrow(Scancode::from_u8(true, 0x4B), 105, 0x50, Some(0x7B))]
+#[case::right_meta(Scancode::from_u8(true, 0x5C), 126, 0xE7, Some(0x36))]
+#[case::print_screen(Scancode::from_u8(true, 0x37), 99, 0x46, None)]
+fn conversions(#[case] scancode: Scancode, #[case] evdev: u16, #[case] usb_hid: u16, #[case] mac: Option<u16>) {
+    assert_eq!(to_evdev(scancode), Some(evdev));
+    assert_eq!(from_evdev(evdev), Some(scancode));
+
+    assert_eq!(to_usb_hid(scancode), Some(usb_hid));
+    assert_eq!(from_usb_hid(usb_hid), Some(scancode));
+
+    assert_eq!(to_mac_keycode(scancode), mac);
+
+    if let Some(mac) = mac {
+        assert_eq!(from_mac_keycode(mac), Some(scancode));
+    }
+}
+
+#[rstest]
+#[case::letter("KeyA", Scancode::from_u8(false, 0x1E))]
+#[case::left_control("ControlLeft", Scancode::from_u8(false, 0x1D))]
+#[case::right_control("ControlRight", Scancode::from_u8(true, 0x1D))]
+#[case::keypad_enter("NumpadEnter", Scancode::from_u8(true, 0x1C))]
+#[case::left_meta("MetaLeft", Scancode::from_u8(true, 0x5B))]
+fn dom_code_conversions(#[case] code: &str, #[case] scancode: Scancode) {
+    assert_eq!(from_dom_code(code), Some(scancode));
+    assert_eq!(to_dom_code(scancode), Some(code));
+}
+
+#[rstest]
+#[case("OSLeft", "MetaLeft")]
+#[case("OSRight", "MetaRight")]
+#[case("VolumeMute", "AudioVolumeMute")]
+fn legacy_dom_codes(#[case] legacy: &str, #[case] code: &str) {
+    assert_eq!(from_dom_code(legacy), from_dom_code(code));
+    assert!(from_dom_code(legacy).is_some());
+}
+
+#[test]
+fn unmapped_codes() {
+    assert_eq!(from_evdev(0), None);
+    assert_eq!(from_usb_hid(0x01), None);
+    assert_eq!(from_mac_keycode(0xFF), None);
+    assert_eq!(from_dom_code("Unidentified"), None);
+    assert_eq!(to_evdev(Scancode::from_u8(true, 0x01)), None);
+}
+
+#[test]
+fn mappings_are_bijective() {
+    for code in 0..=u16::from(u8::MAX) {
+        if let Some(scancode) = from_evdev(code) {
+            assert_eq!(to_evdev(scancode), Some(code));
+        }
+
+        if let Some(scancode) = from_usb_hid(code) {
+            assert_eq!(to_usb_hid(scancode), Some(code));
+        }
+
+        if let Some(scancode) = from_mac_keycode(code) {
+            assert_eq!(to_mac_keycode(scancode), Some(code));
+        }
+    }
+
+    for code in (0..=u8::MAX).flat_map(|code| [Scancode::from_u8(false, code), Scancode::from_u8(true, code)]) {
+        if let Some(evdev) = to_evdev(code) {
+            assert_eq!(from_evdev(evdev), Some(code));
+            assert_eq!(from_usb_hid(to_usb_hid(code).unwrap()), Some(code));
+            assert_eq!(from_dom_code(to_dom_code(code).unwrap()), Some(code));
+        }
+    }
+}
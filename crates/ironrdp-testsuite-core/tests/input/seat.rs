@@ -0,0 +1,95 @@
+use ironrdp_input::seat::MultiSeatDatabase;
+use ironrdp_input::{MouseButton, MousePosition, Operation, Scancode};
+use ironrdp_pdu::input::fast_path::{FastPathInputEvent, KeyboardFlags};
+use ironrdp_pdu::input::mouse::PointerFlags;
+use ironrdp_pdu::input::MousePdu;
+
+const SHIFT: Scancode = Scancode::from_u8(false, 0x2A);
+
+fn mouse_event(flags: PointerFlags, x: u16, y: u16) -> FastPathInputEvent {
+    FastPathInputEvent::MouseEvent(MousePdu {
+        flags,
+        number_of_wheel_rotation_units: 0,
+        x_position: x,
+        y_position: y,
+    })
+}
+
+#[test]
+fn shared_keys_are_released_by_the_last_seat() {
+    let mut db = MultiSeatDatabase::new();
+
+    assert_eq!(
+        db.apply(1, [Operation::KeyPressed(SHIFT)]).as_slice(),
+        [FastPathInputEvent::KeyboardEvent(KeyboardFlags::empty(), 0x2A)]
+    );
+    assert!(db.apply(2, [Operation::KeyPressed(SHIFT)]).is_empty());
+    assert!(db.apply(1, [Operation::KeyReleased(SHIFT)]).is_empty());
+    assert_eq!(
+        db.apply(2, [Operation::KeyReleased(SHIFT)]).as_slice(),
+        [FastPathInputEvent::KeyboardEvent(KeyboardFlags::RELEASE, 0x2A)]
+    );
+}
+
+#[test]
+fn cursor_follows_the_acting_seat() {
+    let mut db = MultiSeatDatabase::new();
+
+    db.apply(1, [Operation::MouseMove(MousePosition { x: 10, y: 10 })]);
+    db.apply(2, [Operation::MouseMove(MousePosition { x: 50, y: 50 })]);
+
+    // Seat 1 clicks where its own cursor is
+    assert_eq!(
+        db.apply(1, [Operation::MouseButtonPressed(MouseButton::Left)])
+            .as_slice(),
+        [
+            mouse_event(PointerFlags::MOVE, 10, 10),
+            mouse_event(PointerFlags::DOWN | PointerFlags::LEFT_BUTTON, 10, 10),
+        ]
+    );
+
+    // Seat 2 did not move, but the shared cursor did
+    assert_eq!(
+        db.apply(2, [Operation::MouseMove(MousePosition { x: 50, y: 50 })])
+            .as_slice(),
+        [mouse_event(PointerFlags::MOVE, 50, 50)]
+    );
+    assert!(db
+        .apply(2, [Operation::MouseMove(MousePosition { x: 50, y: 50 })])
+        .is_empty());
+}
+
+#[test]
+fn removing_a_seat_releases_what_only_it_holds() {
+    let a = Scancode::from_u8(false, 0x1E);
+
+    let mut db = MultiSeatDatabase::new();
+    db.apply(1, [Operation::KeyPressed(SHIFT), Operation::KeyPressed(a)]);
+    db.apply(2, [Operation::KeyPressed(SHIFT)]);
+
+    assert_eq!(
+        db.remove_seat(1).as_slice(),
+        [FastPathInputEvent::KeyboardEvent(KeyboardFlags::RELEASE, 0x1E)]
+    );
+    assert_eq!(db.seats().collect::<Vec<_>>(), [2]);
+    assert!(db.seat(2).unwrap().is_key_pressed(SHIFT));
+    assert!(db.remove_seat(1).is_empty());
+}
+
+#[test]
+fn lock_keys_are_shared() {
+    let sync = |caps_lock| Operation::SyncLocks {
+        scroll_lock: false,
+        num_lock: false,
+        caps_lock,
+        kana_lock: false,
+    };
+
+    let mut db = MultiSeatDatabase::new();
+    db.apply(2, [Operation::MouseMove(MousePosition { x: 1, y: 1 })]);
+
+    assert_eq!(db.apply(1, [sync(true)]).len(), 1);
+    assert!(db.apply(2, [sync(true)]).is_empty());
+    assert_eq!(db.apply(2, [sync(false)]).len(), 1);
+    assert_eq!(db.apply(1, [sync(true)]).len(), 1);
+}
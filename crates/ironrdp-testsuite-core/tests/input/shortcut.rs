@@ -0,0 +1,74 @@
+use ironrdp_input::shortcut::{GrabAll, ShortcutDecision, ShortcutFilter, ShortcutPolicy, Windowed};
+use ironrdp_input::{Database, ModifierKey, ModifierState, Operation, Scancode};
+use ironrdp_pdu::input::fast_path::{FastPathInputEvent, KeyboardFlags};
+use rstest::rstest;
+
+const ALT: Scancode = ModifierKey::LeftAlt.scancode();
+const CTRL: Scancode = ModifierKey::LeftCtrl.scancode();
+const META: Scancode = ModifierKey::LeftMeta.scancode();
+const TAB: Scancode = Scancode::from_u8(false, 0x0F);
+const ESCAPE: Scancode = Scancode::from_u8(false, 0x01);
+const A: Scancode = Scancode::from_u8(false, 0x1E);
+
+fn modifiers(keys: &[ModifierKey]) -> ModifierState {
+    let mut db = Database::new();
+    db.apply(keys.iter().map(|key| Operation::KeyPressed(key.scancode())));
+    db.modifiers()
+}
+
+#[rstest]
+#[case::alt_tab(&[ModifierKey::LeftAlt], TAB, ShortcutDecision::KeepLocal)]
+#[case::alt_escape(&[ModifierKey::RightAlt], ESCAPE, ShortcutDecision::KeepLocal)]
+#[case::ctrl_escape(&[ModifierKey::LeftCtrl], ESCAPE, ShortcutDecision::KeepLocal)]
+#[case::meta(&[], META, ShortcutDecision::KeepLocal)]
+#[case::meta_combination(&[ModifierKey::RightMeta], A, ShortcutDecision::KeepLocal)]
+#[case::tab(&[], TAB, ShortcutDecision::Forward)]
+#[case::ctrl_tab(&[ModifierKey::LeftCtrl], TAB, ShortcutDecision::Forward)]
+#[case::alt(&[], ALT, ShortcutDecision::Forward)]
+fn windowed_policy(#[case] held: &[ModifierKey], #[case] scancode: Scancode, #[case] expected: ShortcutDecision) {
+    assert_eq!(Windowed.decide(modifiers(held), scancode), expected);
+    assert_eq!(GrabAll.decide(modifiers(held), scancode), ShortcutDecision::Forward);
+}
+
+#[test]
+fn kept_local_presses_are_handed_back() {
+    let mut db = Database::new();
+    let mut filter = ShortcutFilter::new(Windowed);
+
+    let input = filter.apply(
+        &mut db,
+        [
+            Operation::KeyPressed(ALT),
+            Operation::KeyPressed(TAB),
+            Operation::KeyPressed(TAB),
+        ],
+    );
+    assert_eq!(
+        input.events.as_slice(),
+        [FastPathInputEvent::KeyboardEvent(KeyboardFlags::empty(), 0x38)]
+    );
+    assert_eq!(input.local, [Operation::KeyPressed(TAB), Operation::KeyPressed(TAB)]);
+    assert!(!db.is_key_pressed(TAB));
+
+    // The release follows the press, even though Alt is not held anymore
+    let input = filter.apply(&mut db, [Operation::KeyReleased(ALT), Operation::KeyReleased(TAB)]);
+    assert_eq!(
+        input.events.as_slice(),
+        [FastPathInputEvent::KeyboardEvent(KeyboardFlags::RELEASE, 0x38)]
+    );
+    assert_eq!(input.local, [Operation::KeyReleased(TAB)]);
+}
+
+#[test]
+fn keys_held_before_a_policy_change_stay_remote() {
+    let mut db = Database::new();
+    let mut filter: ShortcutFilter<Box<dyn ShortcutPolicy>> = ShortcutFilter::new(Box::new(GrabAll));
+
+    filter.apply(&mut db, [Operation::KeyPressed(CTRL), Operation::KeyPressed(ESCAPE)]);
+
+    filter.set_policy(Box::new(Windowed));
+    let input = filter.apply(&mut db, [Operation::KeyPressed(ESCAPE), Operation::KeyReleased(ESCAPE)]);
+
+    assert!(input.local.is_empty());
+    assert_eq!(input.events.len(), 3);
+}
@@ -0,0 +1,197 @@
+use ironrdp_core::encode_vec;
+use ironrdp_input::touch::{
+    ContactFlags, ContactRect, TouchContact, TouchContactData, TouchEncoder, TouchEventPdu, TouchFrame,
+};
+use ironrdp_input::{Database, Operation};
+
+const ACTIVE: ContactFlags = ContactFlags::IN_RANGE.union(ContactFlags::IN_CONTACT);
+
+fn contact_flags(frame: &TouchFrame) -> Vec<(u8, ContactFlags)> {
+    frame
+        .contacts
+        .iter()
+        .map(|contact| (contact.contact_id, contact.flags))
+        .collect()
+}
+
+#[test]
+fn touch_down_event_encoding() {
+    let mut encoder = TouchEncoder::new();
+
+    let pdu = encoder
+        .apply([Operation::TouchDown(TouchContact::new(1, 100, 200))])
+        .expect("touch event");
+
+    assert_eq!(
+        encode_vec(&pdu).unwrap(),
+        [
+            0x03, 0x00, // eventId
+            0x11, 0x00, 0x00, 0x00, // pduLength
+            0x00, // encodeTime
+            0x01, // frameCount
+            0x01, // contactCount
+            0x00, // frameOffset
+            0x01, // contactId
+            0x00, // fieldsPresent
+            0x40, 0x64, // x
+            0x40, 0xC8, // y
+            0x19, // contactFlags
+        ]
+    );
+}
+
+#[test]
+fn contact_optional_fields_encoding() {
+    let contact = TouchContactData {
+        contact_id: 2,
+        x: -5,
+        y: 0,
+        flags: ContactFlags::UPDATE | ACTIVE,
+        rect: Some(ContactRect {
+            left: -10,
+            top: -10,
+            right: 10,
+            bottom: 10,
+        }),
+        orientation: None,
+        pressure: Some(512),
+    };
+
+    assert_eq!(
+        encode_vec(&contact).unwrap(),
+        [
+            0x02, // contactId
+            0x05, // fieldsPresent
+            0x25, // x
+            0x00, // y
+            0x1A, // contactFlags
+            0x4A, 0x4A, 0x0A, 0x0A, // contactRect
+            0x42, 0x00, // pressure
+        ]
+    );
+}
+
+#[test]
+fn frame_offset_encoding() {
+    let frame = TouchFrame {
+        frame_offset: 1_000_000,
+        contacts: Vec::new(),
+    };
+
+    assert_eq!(encode_vec(&frame).unwrap(), [0x00, 0x4F, 0x42, 0x40]);
+}
+
+#[test]
+fn out_of_range_values_are_rejected() {
+    let pdu = TouchEventPdu {
+        encode_time: u32::MAX,
+        frames: Vec::new(),
+    };
+
+    encode_vec(&pdu).unwrap_err();
+}
+
+#[test]
+fn unknown_contacts_are_ignored() {
+    let mut encoder = TouchEncoder::new();
+
+    let pdu = encoder.apply([
+        Operation::TouchMove(TouchContact::new(1, 10, 10)),
+        Operation::TouchUp(TouchContact::new(2, 10, 10)),
+        Operation::KeyPressed(ironrdp_input::Scancode::from_u8(false, 0x1E)),
+    ]);
+
+    assert_eq!(pdu, None);
+}
+
+#[test]
+fn frames_list_all_active_contacts() {
+    let mut encoder = TouchEncoder::new();
+
+    let pdu = encoder
+        .apply([
+            Operation::TouchDown(TouchContact::new(1, 10, 10)),
+            Operation::TouchDown(TouchContact::new(2, 20, 20)),
+        ])
+        .expect("touch event");
+    assert_eq!(pdu.frames.len(), 1);
+    assert_eq!(
+        contact_flags(&pdu.frames[0]),
+        [(1, ContactFlags::DOWN | ACTIVE), (2, ContactFlags::DOWN | ACTIVE)]
+    );
+
+    let pdu = encoder
+        .apply([Operation::TouchMove(TouchContact::new(1, 15, 15))])
+        .expect("touch event");
+    assert_eq!(
+        contact_flags(&pdu.frames[0]),
+        [(1, ContactFlags::UPDATE | ACTIVE), (2, ContactFlags::UPDATE | ACTIVE)]
+    );
+    assert_eq!(pdu.frames[0].contacts[0].x, 15);
+    assert_eq!(pdu.frames[0].contacts[1].x, 20);
+
+    let pdu = encoder
+        .apply([Operation::TouchUp(TouchContact::new(1, 15, 15))])
+        .expect("touch event");
+    assert_eq!(
+        contact_flags(&pdu.frames[0]),
+        [(1, ContactFlags::UP), (2, ContactFlags::UPDATE | ACTIVE)]
+    );
+
+    assert!(!encoder.is_contact_active(1));
+    assert!(encoder.is_contact_active(2));
+}
+
+#[test]
+fn repeated_contact_starts_a_new_frame() {
+    let mut encoder = TouchEncoder::new();
+
+    let pdu = encoder
+        .apply([
+            Operation::TouchDown(TouchContact::new(1, 10, 10)),
+            Operation::TouchMove(TouchContact::new(1, 20, 20)),
+            Operation::TouchUp(TouchContact::new(1, 20, 20)),
+        ])
+        .expect("touch event");
+
+    let flags: Vec<_> = pdu.frames.iter().map(contact_flags).collect();
+    assert_eq!(
+        flags,
+        [
+            vec![(1, ContactFlags::DOWN | ACTIVE)],
+            vec![(1, ContactFlags::UPDATE | ACTIVE)],
+            vec![(1, ContactFlags::UP)],
+        ]
+    );
+}
+
+#[test]
+fn cancel_all() {
+    let mut encoder = TouchEncoder::new();
+
+    encoder.apply([
+        Operation::TouchDown(TouchContact::new(3, 10, 10)),
+        Operation::TouchDown(TouchContact::new(4, 20, 20)),
+    ]);
+
+    let pdu = encoder.cancel_all().expect("touch event");
+    assert_eq!(
+        contact_flags(&pdu.frames[0]),
+        [
+            (3, ContactFlags::UP | ContactFlags::CANCELED),
+            (4, ContactFlags::UP | ContactFlags::CANCELED)
+        ]
+    );
+
+    assert!(!encoder.is_contact_active(3));
+    assert_eq!(encoder.cancel_all(), None);
+}
+
+#[test]
+fn database_ignores_touch_operations() {
+    let mut db = Database::new();
+
+    let events = db.apply([Operation::TouchDown(TouchContact::new(1, 10, 10))]);
+
+    assert!(events.is_empty());
+}
@@ -11,7 +11,9 @@
 //! Cargo will run all tests from a single binary in parallel, but
 //! binaries themselves are run sequentally.
 
+mod acceptor;
 mod clipboard;
+mod connector;
 mod displaycontrol;
 mod dvc;
 mod fuzz_regression;
@@ -23,5 +25,7 @@ mod rdcleanpath;
 mod rdpsnd;
 mod server_name;
 mod session;
+mod svc;
+mod transcript;
 
 mod now_proto;
@@ -166,3 +166,30 @@ fn pcb_v2_string_too_big() {
         "#]]
     .assert_debug_eq(&e);
 }
+
+#[test]
+fn hint_matches_preconnection_pdu() {
+    use ironrdp_pdu::PduHint as _;
+
+    assert_eq!(PCB_OR_X224_HINT.find_size(&[0x20, 0x00]).unwrap(), None);
+    assert_eq!(
+        PCB_OR_X224_HINT
+            .find_size(&PRECONNECTION_PDU_V2_LARGE_PAYLOAD_SIZE_BUF)
+            .unwrap(),
+        Some((true, 32))
+    );
+    PCB_OR_X224_HINT
+        .find_size(&PRECONNECTION_PDU_V1_NULL_SIZE_BUF)
+        .unwrap_err();
+}
+
+#[test]
+fn hint_matches_connection_request() {
+    use ironrdp_pdu::PduHint as _;
+
+    // TPKT header of a X.224 Connection Request
+    let tpkt = [0x03, 0x00, 0x00, 0x2b, 0x26, 0xe0];
+
+    assert_eq!(is_preconnection_blob(&tpkt), Some(false));
+    assert_eq!(PCB_OR_X224_HINT.find_size(&tpkt).unwrap(), Some((true, 0x2b)));
+}
@@ -0,0 +1,113 @@
+use ironrdp_core::{decode, encode_vec};
+use ironrdp_pdu::rdp::autodetect::{
+    AutoDetectPhase, AutoDetectRequest, AutoDetectRequestPdu, AutoDetectResponse, AutoDetectResponsePdu,
+    NetworkCharacteristics,
+};
+use rstest::rstest;
+
+#[rstest]
+#[case::rtt_request_connect_time(
+    &[0x06, 0x00, 0x01, 0x00, 0x01, 0x10],
+    AutoDetectRequest::RttRequest { sequence_number: 1, phase: AutoDetectPhase::ConnectTime },
+)]
+#[case::bandwidth_measure_start_continuous(
+    &[0x06, 0x00, 0x02, 0x00, 0x14, 0x00],
+    AutoDetectRequest::BandwidthMeasureStart { sequence_number: 2, phase: AutoDetectPhase::Continuous },
+)]
+#[case::bandwidth_measure_payload(
+    &[0x08, 0x00, 0x03, 0x00, 0x02, 0x00, 0x03, 0x00, 0xAA, 0xBB, 0xCC],
+    AutoDetectRequest::BandwidthMeasurePayload { sequence_number: 3, payload: vec![0xAA, 0xBB, 0xCC] },
+)]
+#[case::bandwidth_measure_stop_connect_time(
+    &[0x08, 0x00, 0x04, 0x00, 0x2B, 0x00, 0x01, 0x00, 0xAA],
+    AutoDetectRequest::BandwidthMeasureStop {
+        sequence_number: 4,
+        phase: AutoDetectPhase::ConnectTime,
+        payload: vec![0xAA],
+    },
+)]
+#[case::bandwidth_measure_stop_continuous(
+    &[0x06, 0x00, 0x05, 0x00, 0x29, 0x04],
+    AutoDetectRequest::BandwidthMeasureStop {
+        sequence_number: 5,
+        phase: AutoDetectPhase::Continuous,
+        payload: Vec::new(),
+    },
+)]
+#[case::network_characteristics_result(
+    &[
+        0x12, 0x00, 0x06, 0x00, 0xC0, 0x08, 0x0A, 0x00, 0x00, 0x00, 0x00, 0x10, 0x00, 0x00, 0x14, 0x00, 0x00, 0x00,
+    ],
+    AutoDetectRequest::NetworkCharacteristicsResult {
+        sequence_number: 6,
+        characteristics: NetworkCharacteristics { base_rtt: Some(10), bandwidth: Some(4096), average_rtt: 20 },
+    },
+)]
+#[case::network_characteristics_result_without_bandwidth(
+    &[0x0E, 0x00, 0x07, 0x00, 0x40, 0x08, 0x0A, 0x00, 0x00, 0x00, 0x14, 0x00, 0x00, 0x00],
+    AutoDetectRequest::NetworkCharacteristicsResult {
+        sequence_number: 7,
+        characteristics: NetworkCharacteristics { base_rtt: Some(10), bandwidth: None, average_rtt: 20 },
+    },
+)]
+fn auto_detect_request_roundtrip(#[case] buf: &[u8], #[case] request: AutoDetectRequest) {
+    assert_eq!(request, decode::<AutoDetectRequest>(buf).unwrap());
+    assert_eq!(buf, encode_vec(&request).unwrap().as_slice());
+}
+
+#[rstest]
+#[case::rtt_response(
+    &[0x06, 0x01, 0x01, 0x00, 0x00, 0x00],
+    AutoDetectResponse::RttResponse { sequence_number: 1 },
+)]
+#[case::bandwidth_measure_results_continuous(
+    &[0x0E, 0x01, 0x02, 0x00, 0x0B, 0x00, 0x64, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00],
+    AutoDetectResponse::BandwidthMeasureResults {
+        sequence_number: 2,
+        phase: AutoDetectPhase::Continuous,
+        time_delta: 100,
+        byte_count: 65536,
+    },
+)]
+#[case::network_characteristics_sync(
+    &[0x0E, 0x01, 0x03, 0x00, 0x18, 0x00, 0x00, 0x10, 0x00, 0x00, 0x14, 0x00, 0x00, 0x00],
+    AutoDetectResponse::NetworkCharacteristicsSync { sequence_number: 3, bandwidth: 4096, rtt: 20 },
+)]
+fn auto_detect_response_roundtrip(#[case] buf: &[u8], #[case] response: AutoDetectResponse) {
+    assert_eq!(response, decode::<AutoDetectResponse>(buf).unwrap());
+    assert_eq!(buf, encode_vec(&response).unwrap().as_slice());
+}
+
+#[test]
+fn network_characteristics_result_requires_rtt_or_bandwidth() {
+    let request = AutoDetectRequest::NetworkCharacteristicsResult {
+        sequence_number: 0,
+        characteristics: NetworkCharacteristics {
+            base_rtt: None,
+            bandwidth: None,
+            average_rtt: 20,
+        },
+    };
+
+    encode_vec(&request).unwrap_err();
+}
+
+#[test]
+fn auto_detect_pdus_carry_security_header() {
+    let request = AutoDetectRequestPdu(AutoDetectRequest::RttRequest {
+        sequence_number: 1,
+        phase: AutoDetectPhase::Continuous,
+    });
+    let buf = encode_vec(&request).unwrap();
+    assert_eq!(buf, [0x00, 0x10, 0x00, 0x00, 0x06, 0x00, 0x01, 0x00, 0x01, 0x00]);
+    assert_eq!(request, decode::<AutoDetectRequestPdu>(&buf).unwrap());
+
+    let buf = [0x00, 0x20, 0x00, 0x00, 0x06, 0x01, 0x01, 0x00, 0x00, 0x00];
+    assert_eq!(
+        AutoDetectResponsePdu(AutoDetectResponse::RttResponse { sequence_number: 1 }),
+        decode::<AutoDetectResponsePdu>(&buf).unwrap()
+    );
+
+    // A request is not a valid response.
+    decode::<AutoDetectResponsePdu>(&encode_vec(&request).unwrap()).unwrap_err();
+}
@@ -0,0 +1,114 @@
+use ironrdp_cliprdr::pdu::{ClipboardPdu, FormatDataResponse};
+use ironrdp_core::{decode, decode_limits, encode_vec, with_decode_options, DecodeLimits, DecodeOptions};
+use ironrdp_pdu::gcc::{
+    ChannelDef, ChannelName, ChannelOptions, ClientMonitorData, ClientNetworkData, Monitor, MonitorFlags,
+};
+use ironrdp_pdu::rdp::capability_sets::{CapabilitySet, DemandActive};
+
+fn with_limits<R>(limits: DecodeLimits, f: impl FnOnce() -> R) -> R {
+    with_decode_options(DecodeOptions::default().with_limits(limits), f)
+}
+
+fn channels(count: usize) -> ClientNetworkData {
+    ClientNetworkData {
+        channels: (0..count)
+            .map(|_| ChannelDef {
+                name: ChannelName::from_static(b"cliprdr\0"),
+                options: ChannelOptions::INITIALIZED,
+            })
+            .collect(),
+    }
+}
+
+fn monitors(count: i32) -> ClientMonitorData {
+    ClientMonitorData {
+        monitors: (0..count)
+            .map(|i| Monitor {
+                left: i * 1024,
+                top: 0,
+                right: i * 1024 + 1023,
+                bottom: 767,
+                flags: if i == 0 {
+                    MonitorFlags::PRIMARY
+                } else {
+                    MonitorFlags::empty()
+                },
+            })
+            .collect(),
+    }
+}
+
+#[test]
+fn default_limits_apply() {
+    assert_eq!(decode_limits(), DecodeLimits::default());
+}
+
+#[test]
+fn capability_sets_exceeding_the_limit_are_rejected() {
+    let demand_active = DemandActive {
+        source_descriptor: "RDP".to_owned(),
+        capability_sets: vec![CapabilitySet::Control(vec![0; 8]), CapabilitySet::Share(vec![0; 4])],
+    };
+    let buf = encode_vec(&demand_active).unwrap();
+
+    let limits = DecodeLimits {
+        max_capability_sets: 1,
+        ..DecodeLimits::default()
+    };
+
+    with_limits(limits, || decode::<DemandActive>(&buf)).unwrap_err();
+    assert_eq!(decode::<DemandActive>(&buf).unwrap(), demand_active);
+}
+
+#[test]
+fn channels_exceeding_the_limit_are_rejected() {
+    let buf = encode_vec(&channels(3)).unwrap();
+
+    let limits = DecodeLimits {
+        max_channel_count: 2,
+        ..DecodeLimits::default()
+    };
+
+    with_limits(limits, || decode::<ClientNetworkData>(&buf)).unwrap_err();
+    assert_eq!(decode::<ClientNetworkData>(&buf).unwrap(), channels(3));
+}
+
+#[test]
+fn monitors_exceeding_the_limit_are_rejected() {
+    let buf = encode_vec(&monitors(2)).unwrap();
+
+    let limits = DecodeLimits {
+        max_monitor_count: 1,
+        ..DecodeLimits::default()
+    };
+
+    with_limits(limits, || decode::<ClientMonitorData>(&buf)).unwrap_err();
+    assert_eq!(decode::<ClientMonitorData>(&buf).unwrap(), monitors(2));
+}
+
+#[test]
+fn pdu_exceeding_the_limit_is_rejected() {
+    let tpkt_header = [0x03, 0x00, 0x04, 0x00];
+
+    let limits = DecodeLimits {
+        max_pdu_size: 512,
+        ..DecodeLimits::default()
+    };
+
+    with_limits(limits, || ironrdp_pdu::find_size(&tpkt_header)).unwrap_err();
+    assert_eq!(ironrdp_pdu::find_size(&tpkt_header).unwrap().unwrap().length, 1024);
+}
+
+#[test]
+fn clipboard_data_exceeding_the_limit_is_rejected() {
+    let pdu = ClipboardPdu::FormatDataResponse(FormatDataResponse::new_data(vec![0xAB; 64]));
+    let buf = encode_vec(&pdu).unwrap();
+
+    let limits = DecodeLimits {
+        max_clipboard_data_size: 32,
+        ..DecodeLimits::default()
+    };
+
+    with_limits(limits, || decode::<ClipboardPdu<'_>>(&buf)).unwrap_err();
+    decode::<ClipboardPdu<'_>>(&buf).unwrap();
+}
@@ -0,0 +1,107 @@
+use std::sync::{Arc, Mutex};
+
+use ironrdp_core::{decode, decode_mode, encode_vec, with_decode_options, DecodeMode, DecodeOptions, DecodeWarning};
+use ironrdp_pdu::rdp::capability_sets::{CapabilitySet, DemandActive};
+use ironrdp_pdu::rdp::suppress_output::SuppressOutputPdu;
+
+const SUPPRESS_OUTPUT_WITH_DIRTY_PADDING: [u8; 4] = [
+    0x00, // allowDisplayUpdates (SUPPRESS_DISPLAY_UPDATES)
+    0x00, 0x2a, 0x00, // pad3Octets
+];
+
+const UNKNOWN_CAPABILITY_SET: [u8; 8] = [
+    0xff, 0x7f, // capabilitySetType
+    0x08, 0x00, // lengthCapability
+    0x01, 0x02, 0x03, 0x04, // capabilityData
+];
+
+fn demand_active_with_unknown_capability_set() -> Vec<u8> {
+    let demand_active = DemandActive {
+        source_descriptor: "RDP".to_owned(),
+        capability_sets: vec![CapabilitySet::Control(vec![0; 8])],
+    };
+
+    let mut buf = encode_vec(&demand_active).unwrap();
+
+    // numberCapabilities follows the lengthSourceDescriptor, lengthCombinedCapabilities and sourceDescriptor fields.
+    let number_capabilities_offset = 2 + 2 + demand_active.source_descriptor.len() + 1;
+    buf[number_capabilities_offset] += 1;
+    buf.extend_from_slice(&UNKNOWN_CAPABILITY_SET);
+
+    buf
+}
+
+fn collect_warnings<R>(mode: DecodeMode, f: impl FnOnce() -> R) -> (R, Vec<DecodeWarning>) {
+    let warnings = Arc::new(Mutex::new(Vec::new()));
+
+    let options = DecodeOptions::new(mode).with_warning_callback({
+        let warnings = Arc::clone(&warnings);
+        move |warning| warnings.lock().unwrap().push(*warning)
+    });
+
+    let result = with_decode_options(options, f);
+    let warnings = warnings.lock().unwrap().clone();
+
+    (result, warnings)
+}
+
+#[test]
+fn lenient_is_the_default_mode() {
+    assert_eq!(decode_mode(), DecodeMode::Lenient);
+    decode::<SuppressOutputPdu>(&SUPPRESS_OUTPUT_WITH_DIRTY_PADDING).unwrap();
+}
+
+#[test]
+fn lenient_tolerates_non_zero_padding() {
+    let (result, warnings) = collect_warnings(DecodeMode::Lenient, || {
+        decode::<SuppressOutputPdu>(&SUPPRESS_OUTPUT_WITH_DIRTY_PADDING)
+    });
+
+    assert_eq!(result.unwrap(), SuppressOutputPdu { desktop_rect: None });
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].field, "padding");
+}
+
+#[test]
+fn strict_rejects_non_zero_padding() {
+    let (result, warnings) = collect_warnings(DecodeMode::Strict, || {
+        decode::<SuppressOutputPdu>(&SUPPRESS_OUTPUT_WITH_DIRTY_PADDING)
+    });
+
+    result.unwrap_err();
+    assert!(warnings.is_empty());
+}
+
+#[test]
+fn lenient_skips_unknown_capability_sets() {
+    let buf = demand_active_with_unknown_capability_set();
+
+    let (result, warnings) = collect_warnings(DecodeMode::Lenient, || decode::<DemandActive>(&buf));
+
+    let demand_active = result.unwrap();
+    assert_eq!(demand_active.capability_sets, vec![CapabilitySet::Control(vec![0; 8])]);
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].field, "capabilitySetType");
+}
+
+#[test]
+fn strict_rejects_unknown_capability_sets() {
+    let buf = demand_active_with_unknown_capability_set();
+
+    let (result, _) = collect_warnings(DecodeMode::Strict, || decode::<DemandActive>(&buf));
+
+    result.unwrap_err();
+}
+
+#[test]
+fn previous_options_are_restored() {
+    with_decode_options(DecodeOptions::new(DecodeMode::Strict), || {
+        with_decode_options(DecodeOptions::new(DecodeMode::Lenient), || {
+            assert_eq!(decode_mode(), DecodeMode::Lenient);
+        });
+
+        assert_eq!(decode_mode(), DecodeMode::Strict);
+    });
+
+    assert_eq!(decode_mode(), DecodeMode::Lenient);
+}
@@ -0,0 +1,108 @@
+use expect_test::expect;
+use ironrdp_core::encode_vec;
+use ironrdp_pdu::dump::{dump_frame, hex_dump, Direction};
+use ironrdp_pdu::mcs::{McsMessage, SendDataIndication};
+use ironrdp_pdu::x224::X224;
+
+#[test]
+fn hex_dump_with_partial_line() {
+    let dump = hex_dump(b"\x03\x00\x00\x0cIronRDP dump\xff\x00");
+
+    expect![[r#"
+        0000  03 00 00 0c 49 72 6f 6e  52 44 50 20 64 75 6d 70  ....IronRDP dump
+        0010  ff 00                                             ..
+    "#]]
+    .assert_eq(&dump);
+}
+
+#[test]
+fn x224_mcs_frame() {
+    let frame = encode_vec(&X224(McsMessage::SendDataIndication(SendDataIndication {
+        initiator_id: 1002,
+        channel_id: 1003,
+        user_data: b"\x06\x00\x17\x00clipboard".as_slice().into(),
+    })))
+    .unwrap();
+
+    expect![[r#"
+        X.224 frame (27 bytes)
+            TPKT header
+                version: 3
+                length: 27
+            X.224 TPDU header
+                length indicator: 2
+                code: Data (0xF0)
+            MCS SendDataIndication (20 bytes)
+                SendDataIndication
+                    SendDataIndication
+                        initiator_id: 1002
+                        channel_id: 1003
+                        user_data (13 bytes)
+                            0000  06 00 17 00 63 6c 69 70  62 6f 61 72 64           ....clipboard
+    "#]]
+    .assert_eq(&dump_frame(&frame, Direction::ServerToClient).to_string());
+}
+
+#[test]
+fn fast_path_input_frame() {
+    // Scancode and Unicode keyboard events
+    let frame = [0x08, 0x07, 0x00, 0x1e, 0x80, 0x41, 0x00];
+
+    expect![[r#"
+        Fast-path input frame (7 bytes)
+            FastPathInput (7 bytes)
+                FastPathInput
+                    KeyboardEvent
+                        KeyboardFlags(0x0)
+                        30
+                    UnicodeKeyboardEvent
+                        KeyboardFlags(0x0)
+                        65
+    "#]]
+    .assert_eq(&dump_frame(&frame, Direction::ClientToServer).to_string());
+}
+
+#[test]
+fn fast_path_output_frame() {
+    // Hidden pointer update, followed by an update with an invalid code
+    let frame = [0x00, 0x09, 0x05, 0x00, 0x00, 0x0f, 0x02, 0x00, 0xaa];
+
+    expect![[r#"
+        Fast-path output frame (9 bytes)
+            Fast-path header
+                flags: EncryptionFlags(0x0)
+                length: 9
+            Fast-path update (3 bytes)
+                update code: HiddenPointer
+                fragmentation: Single
+                TS_FP_UPDATE data (0 bytes)
+                    Pointer(SetHidden)
+            Fast-path update (undecodable)
+                error: [<ironrdp_pdu::basic_output::fast_path::FastPathUpdatePdu<'_> as ironrdp_core::decode::Decode<'_>>::decode] invalid `updateHeader`: Invalid update code
+                data (4 bytes)
+                    0000  0f 02 00 aa                                       ....
+    "#]].assert_eq(&dump_frame(&frame, Direction::ServerToClient).to_string());
+}
+
+#[test]
+fn undecodable_mcs_pdu_and_trailing_data() {
+    // X.224 Data TPDU carrying an unknown DomainMCSPDU, followed by the start of the next frame
+    let frame = [0x03, 0x00, 0x00, 0x09, 0x02, 0xf0, 0x80, 0xfc, 0x01, 0x03, 0x00];
+
+    expect![[r#"
+        X.224 frame (9 bytes)
+            TPKT header
+                version: 3
+                length: 9
+            X.224 TPDU header
+                length indicator: 2
+                code: Data (0xF0)
+            MCS PDU (undecodable)
+                error: [McsMessage] invalid `domain-mcspdu`: unexpected application tag for CHOICE
+                data (2 bytes)
+                    0000  fc 01                                             ..
+            Trailing data (2 bytes)
+                0000  03 00                                             ..
+    "#]]
+    .assert_eq(&dump_frame(&frame, Direction::ClientToServer).to_string());
+}
@@ -576,6 +576,30 @@ fn buffer_length_is_correct_for_client_monitor_extended_data_with_monitors() {
     assert_eq!(expected_buffer_len, len);
 }
 
+#[test]
+fn extended_monitor_info_from_dpi_computes_scale_factors() {
+    let info = ExtendedMonitorInfo::from_dpi(520, 290, MonitorOrientation::Landscape, 144);
+
+    assert_eq!(info.desktop_scale_factor, 150);
+    assert_eq!(info.device_scale_factor, 140);
+    assert_eq!(info.dpi(), Some(144));
+    assert_eq!(info.physical_dimensions(), Some((520, 290)));
+}
+
+#[test]
+fn extended_monitor_info_ignores_out_of_range_attributes() {
+    let info = ExtendedMonitorInfo {
+        physical_width: 5,
+        physical_height: 290,
+        orientation: MonitorOrientation::Portrait,
+        desktop_scale_factor: 0,
+        device_scale_factor: 0,
+    };
+
+    assert_eq!(info.dpi(), None);
+    assert_eq!(info.physical_dimensions(), None);
+}
+
 #[test]
 fn from_buffer_correctly_parses_server_multi_transport_channel_data() {
     let buffer =
@@ -0,0 +1,151 @@
+use ironrdp_core::{decode, encode_vec};
+use ironrdp_pdu::rdp::server_license::{
+    ClientLicenseInfo, ClientNewLicenseRequest, ClientPlatformChallengeResponse, LicenseErrorCode, LicensePdu,
+    LicensingErrorMessage, LicensingStateTransition, NewLicenseInformation, ProductInfo, Scope, ServerLicenseError,
+    ServerLicenseRequest, ServerPlatformChallenge, ServerUpgradeLicense, PREMASTER_SECRET_SIZE,
+};
+use ironrdp_pdu::rdp::standard_security::proprietary_server_certificate;
+
+use super::standard_security::rsa_key;
+
+const CLIENT_RANDOM: [u8; 32] = [0x11; 32];
+const SERVER_RANDOM: [u8; 32] = [0x42; 32];
+const PREMASTER_SECRET: [u8; PREMASTER_SECRET_SIZE] = [0x33; PREMASTER_SECRET_SIZE];
+const CHALLENGE: &[u8] = b"platform challenge";
+const HOSTNAME: &str = "client";
+
+fn round_trip(pdu: LicensePdu) -> LicensePdu {
+    let decoded = decode::<LicensePdu>(&encode_vec(&pdu).unwrap()).unwrap();
+    assert_eq!(encode_vec(&decoded).unwrap(), encode_vec(&pdu).unwrap());
+    decoded
+}
+
+fn server_license_request() -> ServerLicenseRequest {
+    let key = rsa_key();
+
+    let request = ServerLicenseRequest::new(
+        &SERVER_RANDOM,
+        ProductInfo {
+            version: 0x0006_0000,
+            company_name: "IronRDP".to_owned(),
+            product_id: "A02".to_owned(),
+        },
+        proprietary_server_certificate(&key, &key).unwrap(),
+        vec![Scope("ironrdp.test".to_owned())],
+    )
+    .unwrap();
+
+    let LicensePdu::ServerLicenseRequest(request) = round_trip(request.into()) else {
+        panic!("expected a server license request");
+    };
+
+    request
+}
+
+#[test]
+fn new_license_exchange() {
+    let key = rsa_key();
+    let request = server_license_request();
+
+    let (new_license_request, client_encryption_data) = ClientNewLicenseRequest::from_server_license_request(
+        &request,
+        &CLIENT_RANDOM,
+        &PREMASTER_SECRET,
+        "user",
+        HOSTNAME,
+    )
+    .unwrap();
+
+    let server_encryption_data = new_license_request.encryption_data(&SERVER_RANDOM, &key);
+    assert_eq!(server_encryption_data, client_encryption_data);
+
+    let challenge = ServerPlatformChallenge::new(CHALLENGE, &server_encryption_data).unwrap();
+    let LicensePdu::ServerPlatformChallenge(challenge) = round_trip(challenge.into()) else {
+        panic!("expected a platform challenge");
+    };
+
+    let response =
+        ClientPlatformChallengeResponse::from_server_platform_challenge(&challenge, HOSTNAME, &client_encryption_data)
+            .unwrap();
+    response.verify(CHALLENGE, &server_encryption_data).unwrap();
+
+    let new_license_info = NewLicenseInformation {
+        version: 0x0006_0000,
+        scope: "ironrdp.test".to_owned(),
+        company_name: "IronRDP".to_owned(),
+        product_id: "A02".to_owned(),
+        license_info: b"license".to_vec(),
+    };
+    let upgrade_license = ServerUpgradeLicense::new_license(&new_license_info, &server_encryption_data).unwrap();
+    let LicensePdu::ServerUpgradeLicense(upgrade_license) = round_trip(upgrade_license.into()) else {
+        panic!("expected a new license");
+    };
+
+    assert_eq!(
+        upgrade_license.new_license_info(&client_encryption_data).unwrap(),
+        new_license_info
+    );
+}
+
+#[test]
+fn license_info_is_bound_to_the_hardware_id() {
+    let key = rsa_key();
+    let request = server_license_request();
+
+    let (new_license_request, encryption_data) = ClientNewLicenseRequest::from_server_license_request(
+        &request,
+        &CLIENT_RANDOM,
+        &PREMASTER_SECRET,
+        "user",
+        HOSTNAME,
+    )
+    .unwrap();
+    let challenge = ServerPlatformChallenge::new(CHALLENGE, &encryption_data).unwrap();
+    let response =
+        ClientPlatformChallengeResponse::from_server_platform_challenge(&challenge, HOSTNAME, &encryption_data).unwrap();
+    let issued_hardware_id = response
+        .verify(CHALLENGE, &new_license_request.encryption_data(&SERVER_RANDOM, &key))
+        .unwrap();
+
+    let (license_info, _) =
+        ClientLicenseInfo::from_server_license_request(&request, &CLIENT_RANDOM, &[0x44; 48], b"license", HOSTNAME)
+            .unwrap();
+    let encryption_data = license_info.encryption_data(&SERVER_RANDOM, &key);
+
+    assert_eq!(license_info.license_info, b"license");
+    assert_eq!(license_info.hardware_id(&encryption_data).unwrap(), issued_hardware_id);
+}
+
+#[test]
+fn wrong_challenge_response_is_rejected() {
+    let request = server_license_request();
+
+    let (_, encryption_data) = ClientNewLicenseRequest::from_server_license_request(
+        &request,
+        &CLIENT_RANDOM,
+        &PREMASTER_SECRET,
+        "user",
+        HOSTNAME,
+    )
+    .unwrap();
+    let challenge = ServerPlatformChallenge::new(CHALLENGE, &encryption_data).unwrap();
+    let response =
+        ClientPlatformChallengeResponse::from_server_platform_challenge(&challenge, HOSTNAME, &encryption_data).unwrap();
+
+    assert!(matches!(
+        response.verify(b"another challenge", &encryption_data),
+        Err(ServerLicenseError::InvalidPlatformChallengeResponse)
+    ));
+}
+
+#[test]
+fn licensing_error_message_round_trip() {
+    let message = LicensingErrorMessage::new(LicenseErrorCode::NoLicense, LicensingStateTransition::TotalAbort).unwrap();
+
+    let LicensePdu::LicensingErrorMessage(decoded) = round_trip(message.into()) else {
+        panic!("expected a licensing error message");
+    };
+
+    assert_eq!(decoded.error_code, LicenseErrorCode::NoLicense);
+    assert_eq!(decoded.state_transition, LicensingStateTransition::TotalAbort);
+}
@@ -1,8 +1,15 @@
+mod autodetect;
+mod decode_limits;
+mod decode_mode;
+mod dump;
 mod gcc;
 mod gfx;
 mod input;
+mod licensing;
 mod mcs;
 mod pointer;
 mod rdp;
 mod rfx;
+mod round_trip;
+pub(crate) mod standard_security;
 mod x224;
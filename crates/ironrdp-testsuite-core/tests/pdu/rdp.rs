@@ -1,4 +1,9 @@
 use ironrdp_core::{decode, encode_vec, Encode};
+use ironrdp_pdu::rdp::client_info::CompressionType;
+use ironrdp_pdu::rdp::finalization_messages::{
+    PersistentKeyListFlags, PersistentKeyListPdu, PERSISTENT_KEY_LIST_MAX_ENTRIES,
+};
+use ironrdp_pdu::rdp::headers::{CompressionFlags, ShareDataHeader, ShareDataPdu, StreamPriority};
 use ironrdp_testsuite_core::capsets::*;
 use ironrdp_testsuite_core::client_info::*;
 use ironrdp_testsuite_core::rdp::*;
@@ -447,3 +452,84 @@ fn buffer_length_is_correct_for_client_demand_active() {
 
     assert_eq!(expected_buffer_len, len);
 }
+
+const PERSISTENT_KEY_LIST_BUFFER: [u8; 32] = [
+    0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // numEntriesCache0-4
+    0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // totalEntriesCache0-4
+    0x03, 0x00, 0x00, 0x00, // bBitMask, Pad2, Pad3
+    0x88, 0x77, 0x66, 0x55, 0x44, 0x33, 0x22, 0x11, // Key1, Key2
+];
+
+fn persistent_key_list() -> PersistentKeyListPdu {
+    PersistentKeyListPdu {
+        keys: [
+            vec![0x1122_3344_5566_7788],
+            Vec::new(),
+            Vec::new(),
+            Vec::new(),
+            Vec::new(),
+        ],
+        total_entries: [1, 0, 0, 0, 0],
+        flags: PersistentKeyListFlags::FIRST | PersistentKeyListFlags::LAST,
+    }
+}
+
+#[test]
+fn persistent_key_list_round_trip() {
+    let pdu = persistent_key_list();
+
+    assert_eq!(pdu, decode(PERSISTENT_KEY_LIST_BUFFER.as_slice()).unwrap());
+    assert_eq!(encode_vec(&pdu).unwrap(), PERSISTENT_KEY_LIST_BUFFER);
+    assert_eq!(pdu.size(), PERSISTENT_KEY_LIST_BUFFER.len());
+}
+
+#[test]
+fn persistent_key_list_share_data_round_trip() {
+    let pdu = ShareDataHeader {
+        share_data_pdu: ShareDataPdu::BitmapCachePersistentList(persistent_key_list()),
+        stream_priority: StreamPriority::Medium,
+        compression_flags: CompressionFlags::empty(),
+        compression_type: CompressionType::K8,
+    };
+
+    let buf = encode_vec(&pdu).unwrap();
+
+    assert_eq!(buf.len(), pdu.size());
+    assert_eq!(pdu, decode(buf.as_slice()).unwrap());
+}
+
+#[test]
+fn persistent_key_list_is_split_into_several_pdus() {
+    let keys = vec![(0..100).collect::<Vec<u64>>(), (100..200).collect()];
+
+    let pdus = PersistentKeyListPdu::from_keys(&keys).unwrap();
+
+    assert_eq!(pdus.len(), 2);
+    assert_eq!(pdus[0].flags, PersistentKeyListFlags::FIRST);
+    assert_eq!(pdus[1].flags, PersistentKeyListFlags::LAST);
+    assert_eq!(
+        pdus[0].keys[0].len() + pdus[0].keys[1].len(),
+        PERSISTENT_KEY_LIST_MAX_ENTRIES
+    );
+    assert!(pdus.iter().all(|pdu| pdu.total_entries == [100, 100, 0, 0, 0]));
+
+    let mut merged: Vec<Vec<u64>> = vec![Vec::new(), Vec::new()];
+    for pdu in pdus {
+        merged[0].extend(&pdu.keys[0]);
+        merged[1].extend(&pdu.keys[1]);
+    }
+    assert_eq!(merged, keys);
+}
+
+#[test]
+fn persistent_key_list_without_keys_is_a_single_pdu() {
+    let pdus = PersistentKeyListPdu::from_keys(&[]).unwrap();
+
+    assert_eq!(
+        pdus,
+        [PersistentKeyListPdu {
+            flags: PersistentKeyListFlags::FIRST | PersistentKeyListFlags::LAST,
+            ..PersistentKeyListPdu::default()
+        }]
+    );
+}
@@ -0,0 +1,85 @@
+//! Property tests checking that every generated PDU survives an encode/decode round-trip unchanged
+
+use ironrdp_core::{decode, encode_vec};
+use ironrdp_pdu::input::fast_path::FastPathInput;
+use ironrdp_pdu::input::InputEventPdu;
+use ironrdp_pdu::mcs::McsMessage;
+use ironrdp_pdu::nego::{ConnectionConfirm, ConnectionRequest};
+use ironrdp_pdu::pcb::PreconnectionBlob;
+use ironrdp_pdu::rdp::vc::ChannelPduHeader;
+use ironrdp_pdu::surface_commands::SurfaceCommand;
+use ironrdp_pdu::x224::X224;
+use ironrdp_pdu_generators::{input, mcs, nego, pcb, surface_commands, vc};
+use proptest::prelude::*;
+
+#[test]
+fn connection_request() {
+    proptest!(|(pdu in nego::connection_request())| {
+        let encoded = encode_vec(&X224(pdu.clone())).unwrap();
+        let decoded = decode::<X224<ConnectionRequest>>(&encoded).unwrap().0;
+        prop_assert_eq!(decoded, pdu);
+    });
+}
+
+#[test]
+fn connection_confirm() {
+    proptest!(|(pdu in nego::connection_confirm())| {
+        let encoded = encode_vec(&X224(pdu.clone())).unwrap();
+        let decoded = decode::<X224<ConnectionConfirm>>(&encoded).unwrap().0;
+        prop_assert_eq!(decoded, pdu);
+    });
+}
+
+#[test]
+fn mcs_message() {
+    proptest!(|(pdu in mcs::mcs_message())| {
+        let encoded = encode_vec(&X224(pdu.clone())).unwrap();
+        let decoded = decode::<X224<McsMessage<'_>>>(&encoded).unwrap().0;
+        prop_assert_eq!(decoded, pdu);
+    });
+}
+
+#[test]
+fn preconnection_blob() {
+    proptest!(|(pdu in pcb::preconnection_blob())| {
+        let encoded = encode_vec(&pdu).unwrap();
+        let decoded = decode::<PreconnectionBlob>(&encoded).unwrap();
+        prop_assert_eq!(decoded, pdu);
+    });
+}
+
+#[test]
+fn input_event_pdu() {
+    proptest!(|(pdu in input::input_event_pdu())| {
+        let encoded = encode_vec(&pdu).unwrap();
+        let decoded = decode::<InputEventPdu>(&encoded).unwrap();
+        prop_assert_eq!(decoded, pdu);
+    });
+}
+
+#[test]
+fn fast_path_input() {
+    proptest!(|(pdu in input::fast_path_input())| {
+        let encoded = encode_vec(&pdu).unwrap();
+        let decoded = decode::<FastPathInput>(&encoded).unwrap();
+        prop_assert_eq!(decoded, pdu);
+    });
+}
+
+#[test]
+fn surface_command() {
+    proptest!(|(pdu in surface_commands::surface_command())| {
+        let encoded = encode_vec(&pdu).unwrap();
+        let decoded = decode::<SurfaceCommand<'_>>(&encoded).unwrap();
+        prop_assert_eq!(decoded, pdu);
+    });
+}
+
+#[test]
+fn channel_pdu_header() {
+    proptest!(|(pdu in vc::channel_pdu_header())| {
+        let encoded = encode_vec(&pdu).unwrap();
+        let decoded = decode::<ChannelPduHeader>(&encoded).unwrap();
+        prop_assert_eq!(decoded, pdu);
+    });
+}
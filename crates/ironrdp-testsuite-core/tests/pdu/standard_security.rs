@@ -0,0 +1,146 @@
+use ironrdp_core::{decode, encode_vec};
+use ironrdp_pdu::gcc::EncryptionMethod;
+use ironrdp_pdu::rdp::server_license::cert::CertificateType;
+use ironrdp_pdu::rdp::server_license::ServerCertificate;
+use ironrdp_pdu::rdp::standard_security::{
+    proprietary_server_certificate, RsaPrivateKey, SecurityExchangePdu, StandardSecurity, DATA_SIGNATURE_LEN,
+};
+use rstest::rstest;
+
+const MODULUS: &str = "bbe8b0f07364dc27c4f2a74926288c596f449a323de12537ba547554a9d55529\
+                       e06d2a0c3d6044d31f33aef282c4a05dd980e829c893e3b2b48419ecf7d63e4d";
+const PRIVATE_EXPONENT: &str = "ad7ca157ee82114cda65da130c1ae5b170ac5adcc60ac74cd34844e77cc18c94\
+                                bc53ce58a16c3e9e82a10c649bc018e48680793b59a292f9faf1c7e900bdcc95";
+
+pub(crate) const CLIENT_RANDOM: [u8; 32] = [
+    0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f, 0x10, 0x11, 0x12, 0x13,
+    0x14, 0x15, 0x16, 0x17, 0x18, 0x19, 0x1a, 0x1b, 0x1c, 0x1d, 0x1e, 0x1f, 0x20,
+];
+const SERVER_RANDOM: [u8; 32] = [0x42; 32];
+/// [`CLIENT_RANDOM`] encrypted with [`rsa_key`], as sent in the Security Exchange PDU
+pub(crate) const ENCRYPTED_CLIENT_RANDOM: &str = "5cf953ca3321c49aaf324f3cf682dfd4bd4778b373b713c40ed3d051a64172db\
+                                                  f8eda11a7580ca6739f1506693d374f94f3dbd73429b7358ad7ca11bb96e681a\
+                                                  0000000000000000";
+
+pub(crate) fn rsa_key() -> RsaPrivateKey {
+    RsaPrivateKey::from_components(
+        &hex::decode(MODULUS).unwrap(),
+        65537,
+        &hex::decode(PRIVATE_EXPONENT).unwrap(),
+    )
+}
+
+#[test]
+fn security_exchange_pdu_roundtrip() {
+    let encoded = [
+        0x01, 0x00, 0x00, 0x00, // flags
+        0x04, 0x00, 0x00, 0x00, // length
+        0xaa, 0xbb, 0x00, 0x00, // encryptedClientRandom
+    ];
+    let expected = SecurityExchangePdu {
+        encrypted_client_random: vec![0xaa, 0xbb, 0x00, 0x00],
+    };
+
+    assert_eq!(decode::<SecurityExchangePdu>(&encoded).unwrap(), expected);
+    assert_eq!(encode_vec(&expected).unwrap(), encoded);
+}
+
+#[test]
+fn security_exchange_pdu_without_exchange_flag_is_rejected() {
+    let encoded = [0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00];
+
+    decode::<SecurityExchangePdu>(&encoded).unwrap_err();
+}
+
+#[test]
+fn client_random_decryption() {
+    let encrypted = hex::decode(ENCRYPTED_CLIENT_RANDOM).unwrap();
+
+    let decrypted = rsa_key().decrypt(&encrypted);
+
+    assert_eq!(decrypted.len(), 64);
+    assert_eq!(decrypted[..32], CLIENT_RANDOM);
+    assert!(decrypted[32..].iter().all(|b| *b == 0));
+}
+
+#[test]
+fn proprietary_certificate_signature() {
+    let key = rsa_key();
+
+    let certificate = proprietary_server_certificate(&key, &key).unwrap();
+    let certificate = decode::<ServerCertificate>(&encode_vec(&certificate).unwrap()).unwrap();
+
+    let CertificateType::Proprietary(certificate) = certificate.certificate else {
+        panic!("expected a proprietary certificate");
+    };
+    assert_eq!(certificate.public_key, key.public_key());
+    assert_eq!(
+        hex::encode(&certificate.signature),
+        "959b6cff20f34648eeb22e8d4afd1ce213fb37034266838aa408af63251c4359\
+         d8903b2a94cab16ace17d2f0205c46ba6c705032da02edd2c9ab826f2762e12c\
+         0000000000000000",
+    );
+}
+
+#[rstest]
+#[case::bit_40(EncryptionMethod::BIT_40)]
+#[case::bit_56(EncryptionMethod::BIT_56)]
+#[case::bit_128(EncryptionMethod::BIT_128)]
+fn encryption_roundtrip(#[case] method: EncryptionMethod) {
+    let mut server = StandardSecurity::server(&CLIENT_RANDOM, &SERVER_RANDOM, method).unwrap();
+    let mut client = StandardSecurity::client(&CLIENT_RANDOM, &SERVER_RANDOM, method).unwrap();
+
+    // Goes through a couple of key updates in both directions.
+    for i in 0..10_000u32 {
+        let data = i.to_le_bytes();
+        let salted = i % 2 == 0;
+
+        let (signature, encrypted) = server.encrypt(&data, salted);
+        assert_ne!(encrypted, data);
+        assert_eq!(client.decrypt(&encrypted, &signature, salted).unwrap(), data);
+
+        let (signature, encrypted) = client.encrypt(&data, salted);
+        assert_eq!(server.decrypt(&encrypted, &signature, salted).unwrap(), data);
+    }
+}
+
+#[test]
+fn tampered_data_is_rejected() {
+    let mut server = StandardSecurity::server(&CLIENT_RANDOM, &SERVER_RANDOM, EncryptionMethod::BIT_128).unwrap();
+    let mut client = StandardSecurity::client(&CLIENT_RANDOM, &SERVER_RANDOM, EncryptionMethod::BIT_128).unwrap();
+
+    let (signature, mut encrypted) = client.encrypt(b"hello", false);
+    encrypted[0] ^= 0xff;
+
+    server.decrypt(&encrypted, &signature, false).unwrap_err();
+}
+
+#[test]
+fn tampered_signature_is_rejected() {
+    for i in 0..DATA_SIGNATURE_LEN {
+        let mut server = StandardSecurity::server(&CLIENT_RANDOM, &SERVER_RANDOM, EncryptionMethod::BIT_128).unwrap();
+        let mut client = StandardSecurity::client(&CLIENT_RANDOM, &SERVER_RANDOM, EncryptionMethod::BIT_128).unwrap();
+
+        let (mut signature, encrypted) = client.encrypt(b"hello", false);
+        signature[i] ^= 0x01;
+
+        server.decrypt(&encrypted, &signature, false).unwrap_err();
+    }
+}
+
+#[test]
+fn truncated_signature_is_rejected() {
+    let mut server = StandardSecurity::server(&CLIENT_RANDOM, &SERVER_RANDOM, EncryptionMethod::BIT_128).unwrap();
+    let mut client = StandardSecurity::client(&CLIENT_RANDOM, &SERVER_RANDOM, EncryptionMethod::BIT_128).unwrap();
+
+    let (signature, encrypted) = client.encrypt(b"hello", false);
+
+    server
+        .decrypt(&encrypted, &signature[..DATA_SIGNATURE_LEN - 1], false)
+        .unwrap_err();
+}
+
+#[test]
+fn fips_is_not_supported() {
+    StandardSecurity::server(&CLIENT_RANDOM, &SERVER_RANDOM, EncryptionMethod::FIPS).unwrap_err();
+}
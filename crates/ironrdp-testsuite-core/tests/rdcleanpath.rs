@@ -1,4 +1,7 @@
-use ironrdp_rdcleanpath::{DetectionResult, RDCleanPathPdu, VERSION_1};
+use ironrdp_rdcleanpath::{
+    negotiate_version, Capabilities, DetectionResult, RDCleanPath, RDCleanPathErr, RDCleanPathErrorCode,
+    RDCleanPathPdu, BASE_VERSION, VERSION_1, VERSION_2,
+};
 use rstest::rstest;
 
 fn request() -> RDCleanPathPdu {
@@ -26,6 +29,7 @@ fn response_success() -> RDCleanPathPdu {
             vec![0xDE, 0xAD, 0xBE, 0xFF],
             vec![0xDE, 0xAD, 0xBE, 0xFF],
         ],
+        None,
     )
     .unwrap()
 }
@@ -36,6 +40,40 @@ const RESPONSE_SUCCESS_DER: &[u8] = &[
     0xC, 0xC, 0x31, 0x39, 0x32, 0x2E, 0x31, 0x36, 0x38, 0x2E, 0x37, 0x2E, 0x39, 0x35,
 ];
 
+fn response_success_with_ocsp() -> RDCleanPathPdu {
+    RDCleanPathPdu::new_response(
+        "192.168.7.95".to_owned(),
+        vec![0xDE, 0xAD, 0xBE, 0xFF],
+        [vec![0xDE, 0xAD, 0xBE, 0xFF]],
+        Some(vec![0x30, 0x03, 0x0A, 0x01, 0x00]),
+    )
+    .unwrap()
+}
+
+const RESPONSE_SUCCESS_WITH_OCSP_DER: &[u8] = &[
+    0x30, 0x31, 0xA0, 0x4, 0x2, 0x2, 0xD, 0x3E, 0xA6, 0x6, 0x4, 0x4, 0xDE, 0xAD, 0xBE, 0xFF, 0xA7, 0x8, 0x30, 0x6, 0x4,
+    0x4, 0xDE, 0xAD, 0xBE, 0xFF, 0xA8, 0x7, 0x4, 0x5, 0x30, 0x3, 0xA, 0x1, 0x0, 0xA9, 0xE, 0xC, 0xC, 0x31, 0x39, 0x32,
+    0x2E, 0x31, 0x36, 0x38, 0x2E, 0x37, 0x2E, 0x39, 0x35,
+];
+
+fn response_success_with_client_cert() -> RDCleanPathPdu {
+    RDCleanPathPdu::new_response(
+        "192.168.7.95".to_owned(),
+        vec![0xDE, 0xAD, 0xBE, 0xFF],
+        [vec![0xDE, 0xAD, 0xBE, 0xFF]],
+        None,
+    )
+    .unwrap()
+    .with_client_cert_chain([vec![0xCA, 0xFE]])
+    .unwrap()
+}
+
+const RESPONSE_SUCCESS_WITH_CLIENT_CERT_DER: &[u8] = &[
+    0x30, 0x35, 0xA0, 0x4, 0x2, 0x2, 0xD, 0x3F, 0xA6, 0x6, 0x4, 0x4, 0xDE, 0xAD, 0xBE, 0xFF, 0xA7, 0x8, 0x30, 0x6, 0x4,
+    0x4, 0xDE, 0xAD, 0xBE, 0xFF, 0xA9, 0xE, 0xC, 0xC, 0x31, 0x39, 0x32, 0x2E, 0x31, 0x36, 0x38, 0x2E, 0x37, 0x2E, 0x39,
+    0x35, 0xAA, 0x3, 0x2, 0x1, 0x2, 0xAB, 0x6, 0x30, 0x4, 0x4, 0x2, 0xCA, 0xFE,
+];
+
 fn response_http_error() -> RDCleanPathPdu {
     RDCleanPathPdu::new_http_error(500)
 }
@@ -54,11 +92,34 @@ const RESPONSE_TLS_ERROR_DER: &[u8] = &[
     0x02, 0x01, 0x30,
 ];
 
+fn response_dns_resolution_error() -> RDCleanPathPdu {
+    RDCleanPathPdu::new_dns_resolution_error()
+}
+
+const RESPONSE_DNS_RESOLUTION_ERROR_DER: &[u8] = &[
+    0x30, 0xF, 0xA0, 0x4, 0x2, 0x2, 0xD, 0x3E, 0xA1, 0x7, 0x30, 0x5, 0xA0, 0x3, 0x2, 0x1, 0x2,
+];
+
+fn response_unknown_error() -> RDCleanPathPdu {
+    RDCleanPathPdu::new_error(RDCleanPathErrorCode::Unknown(0x1234))
+}
+
+const RESPONSE_UNKNOWN_ERROR_DER: &[u8] = &[
+    0x30, 0x10, 0xA0, 0x4, 0x2, 0x2, 0xD, 0x3E, 0xA1, 0x8, 0x30, 0x6, 0xA0, 0x4, 0x2, 0x2, 0x12, 0x34,
+];
+
 #[rstest]
 #[case(request())]
 #[case(response_success())]
+#[case(response_success_with_ocsp())]
+#[case(response_success_with_client_cert())]
 #[case(response_http_error())]
 #[case(response_tls_error())]
+#[case(response_dns_resolution_error())]
+#[case(response_unknown_error())]
+#[case(RDCleanPathPdu::new_tcp_connect_error(Some(10061)))]
+#[case(RDCleanPathPdu::new_tls_handshake_error(None))]
+#[case(RDCleanPathPdu::new_authorization_error(401))]
 fn smoke(#[case] message: RDCleanPathPdu) {
     let encoded = message.to_der().unwrap();
     let decoded = RDCleanPathPdu::from_der(&encoded).unwrap();
@@ -83,8 +144,12 @@ macro_rules! assert_serialization {
 #[rstest]
 #[case(request(), REQUEST_DER)]
 #[case(response_success(), RESPONSE_SUCCESS_DER)]
+#[case(response_success_with_ocsp(), RESPONSE_SUCCESS_WITH_OCSP_DER)]
+#[case(response_success_with_client_cert(), RESPONSE_SUCCESS_WITH_CLIENT_CERT_DER)]
 #[case(response_http_error(), RESPONSE_HTTP_ERROR_DER)]
 #[case(response_tls_error(), RESPONSE_TLS_ERROR_DER)]
+#[case(response_dns_resolution_error(), RESPONSE_DNS_RESOLUTION_ERROR_DER)]
+#[case(response_unknown_error(), RESPONSE_UNKNOWN_ERROR_DER)]
 fn serialization(#[case] message: RDCleanPathPdu, #[case] expected_der: &[u8]) {
     let encoded = message.to_der().unwrap();
     assert_serialization!(encoded, expected_der);
@@ -93,6 +158,7 @@ fn serialization(#[case] message: RDCleanPathPdu, #[case] expected_der: &[u8]) {
 #[rstest]
 #[case(REQUEST_DER)]
 #[case(RESPONSE_SUCCESS_DER)]
+#[case(RESPONSE_SUCCESS_WITH_OCSP_DER)]
 #[case(RESPONSE_HTTP_ERROR_DER)]
 #[case(RESPONSE_TLS_ERROR_DER)]
 fn detect(#[case] der: &[u8]) {
@@ -110,16 +176,1348 @@ fn detect(#[case] der: &[u8]) {
     assert_eq!(detected_length, der.len());
 }
 
+#[rstest]
+#[case(&[], None)]
+#[case(&[0x30], None)]
+#[case(&[0x30, 0x15], Some(0x17))]
+#[case(&[0x30, 0x15, 0xA0], Some(0x17))]
+#[case(&[0x30, 0x32, 0xA0, 0x4], Some(0x34))]
+#[case(&[0x30, 0x32, 0xA0, 0x4, 0x2], Some(0x34))]
+#[case(&[0x30, 0x32, 0xA0, 0x4, 0x2, 0x2], Some(0x34))]
+#[case(&[0x30, 0x32, 0xA0, 0x4, 0x2, 0x2, 0xD], Some(0x34))]
+#[case(&[0x30, 0x82, 0x01], None)]
+#[case(&[0x30, 0x82, 0x01, 0x00], Some(0x104))]
+fn detect_not_enough(#[case] payload: &[u8], #[case] total_length: Option<usize>) {
+    let result = RDCleanPathPdu::detect(payload);
+    assert_eq!(result, DetectionResult::NotEnoughBytes { total_length });
+}
+
+#[test]
+fn decode_from_slice() {
+    let mut src = REQUEST_DER.to_vec();
+    src.extend_from_slice(RESPONSE_SUCCESS_DER);
+
+    let (pdu, consumed) = RDCleanPathPdu::decode_from_slice(&src).unwrap().unwrap();
+    assert_eq!(pdu, request());
+    assert_eq!(consumed, REQUEST_DER.len());
+
+    let (pdu, consumed) = RDCleanPathPdu::decode_from_slice(&src[consumed..]).unwrap().unwrap();
+    assert_eq!(pdu, response_success());
+    assert_eq!(consumed, RESPONSE_SUCCESS_DER.len());
+}
+
 #[rstest]
 #[case(&[])]
-#[case(&[0x30])]
-#[case(&[0x30, 0x15])]
-#[case(&[0x30, 0x15, 0xA0])]
 #[case(&[0x30, 0x32, 0xA0, 0x4])]
-#[case(&[0x30, 0x32, 0xA0, 0x4, 0x2])]
-#[case(&[0x30, 0x32, 0xA0, 0x4, 0x2, 0x2])]
-#[case(&[0x30, 0x32, 0xA0, 0x4, 0x2, 0x2, 0xD])]
-fn detect_not_enough(#[case] payload: &[u8]) {
-    let result = RDCleanPathPdu::detect(payload);
-    assert_eq!(result, DetectionResult::NotEnoughBytes);
+#[case(&REQUEST_DER[..REQUEST_DER.len() - 1])]
+fn decode_from_slice_not_enough(#[case] src: &[u8]) {
+    assert_eq!(RDCleanPathPdu::decode_from_slice(src).unwrap(), None);
+}
+
+#[rstest]
+#[case(&[0x03, 0x00, 0x00, 0x13])]
+#[case(&[0x30, 0x05, 0xA0, 0x03, 0x02, 0x01, 0x01])]
+fn decode_from_slice_invalid(#[case] src: &[u8]) {
+    RDCleanPathPdu::decode_from_slice(src).unwrap_err();
+}
+
+#[rstest]
+#[case(1, RDCleanPathErrorCode::General)]
+#[case(2, RDCleanPathErrorCode::DnsResolution)]
+#[case(3, RDCleanPathErrorCode::TcpConnect)]
+#[case(4, RDCleanPathErrorCode::TlsHandshake)]
+#[case(5, RDCleanPathErrorCode::AuthorizationRejected)]
+#[case(0, RDCleanPathErrorCode::Unknown(0))]
+#[case(0xFFFF, RDCleanPathErrorCode::Unknown(0xFFFF))]
+fn error_code(#[case] raw: u16, #[case] expected: RDCleanPathErrorCode) {
+    assert_eq!(RDCleanPathErrorCode::from(raw), expected);
+    assert_eq!(u16::from(expected), raw);
+}
+
+#[test]
+fn error_code_decoding() {
+    let decoded = RDCleanPathPdu::from_der(RESPONSE_UNKNOWN_ERROR_DER).unwrap();
+
+    assert_eq!(
+        decoded.error,
+        Some(RDCleanPathErr::new(RDCleanPathErrorCode::Unknown(0x1234)))
+    );
+    assert_eq!(
+        decoded.error.unwrap().to_string(),
+        "RDCleanPath error (unknown code 4660)"
+    );
+}
+
+#[test]
+fn detect_newer_version() {
+    let der = RDCleanPathPdu {
+        version: BASE_VERSION + 7,
+        ..request()
+    }
+    .to_der()
+    .unwrap();
+
+    assert_eq!(
+        RDCleanPathPdu::detect(&der),
+        DetectionResult::Detected {
+            version: BASE_VERSION + 7,
+            total_length: der.len(),
+        }
+    );
+}
+
+#[test]
+fn detect_older_version() {
+    let der = RDCleanPathPdu {
+        version: BASE_VERSION,
+        ..request()
+    }
+    .to_der()
+    .unwrap();
+
+    assert_eq!(RDCleanPathPdu::detect(&der), DetectionResult::Failed);
+}
+
+#[rstest]
+#[case(BASE_VERSION, None)]
+#[case(VERSION_1, Some(VERSION_1))]
+#[case(VERSION_2, Some(VERSION_2))]
+#[case(VERSION_2 + 1, Some(VERSION_2))]
+fn version_negotiation(#[case] peer_version: u64, #[case] expected: Option<u64>) {
+    assert_eq!(negotiate_version(peer_version), expected);
+}
+
+#[test]
+fn capabilities() {
+    assert_eq!(request().capabilities(), Capabilities::empty());
+
+    let unknown = Capabilities::from_bits_retain(0x8000_0000);
+    let message = request().with_capabilities(Capabilities::OCSP_RESPONSE | unknown);
+    assert_eq!(message.version, VERSION_2);
+
+    let decoded = RDCleanPathPdu::from_der(&message.to_der().unwrap()).unwrap();
+    assert_eq!(decoded.capabilities(), Capabilities::OCSP_RESPONSE | unknown);
+    assert_eq!(
+        Capabilities::negotiate(decoded.capabilities()),
+        Capabilities::OCSP_RESPONSE
+    );
+}
+
+#[rstest]
+#[case(request())]
+#[case(request().with_capabilities(Capabilities::SUPPORTED))]
+#[case(request().with_capabilities(Capabilities::empty()))]
+#[case(response_success())]
+#[case(response_success_with_ocsp().with_capabilities(Capabilities::OCSP_RESPONSE))]
+#[case(response_success_with_client_cert())]
+#[case(response_http_error())]
+fn pdu_round_trips_through_enum(#[case] message: RDCleanPathPdu) {
+    assert_eq!(message.clone().into_enum().unwrap().into_pdu(), message);
+}
+
+#[test]
+fn enum_advertises_capabilities_of_present_fields() {
+    let mut message = response_success().into_enum().unwrap();
+    let RDCleanPath::Response {
+        client_cert_chain,
+        kdc_proxy_url,
+        ..
+    } = &mut message
+    else {
+        panic!("expected a response");
+    };
+    *client_cert_chain = Some(Vec::new());
+    *kdc_proxy_url = Some("https://gateway.example.com/KdcProxy".to_owned());
+
+    let pdu = message.into_pdu();
+    assert_eq!(pdu.version, VERSION_2);
+    assert_eq!(
+        pdu.capabilities(),
+        Capabilities::CLIENT_CERT_CHAIN | Capabilities::KDC_PROXY_URL
+    );
+
+    let pdu = response_success_with_ocsp().into_enum().unwrap().into_pdu();
+    assert_eq!(pdu.version, VERSION_2);
+    assert_eq!(pdu.capabilities(), Capabilities::OCSP_RESPONSE);
+}
+
+#[test]
+fn enum_keeps_advertised_capabilities() {
+    let message = request()
+        .with_capabilities(Capabilities::KDC_PROXY_URL)
+        .into_enum()
+        .unwrap();
+    let RDCleanPath::Request { capabilities, .. } = &message else {
+        panic!("expected a request");
+    };
+    assert_eq!(*capabilities, Some(Capabilities::KDC_PROXY_URL));
+
+    let pdu = message.into_pdu();
+    assert_eq!(pdu.version, VERSION_2);
+    assert_eq!(pdu.capabilities(), Capabilities::KDC_PROXY_URL);
+}
+
+#[rstest]
+#[case(request())]
+#[case(response_success_with_ocsp())]
+#[case(response_success_with_client_cert())]
+#[case(response_unknown_error())]
+#[case(RDCleanPathPdu::new_tcp_connect_error(Some(10061)))]
+fn pdu_round_trips_through_serde(#[case] message: RDCleanPathPdu) {
+    let serialized = serde_json::to_value(&message).unwrap();
+    let deserialized: RDCleanPathPdu = serde_json::from_value(serialized).unwrap();
+    assert_eq!(deserialized, message);
+}
+
+#[test]
+fn pdu_serde_representation() {
+    let serialized = serde_json::to_value(RDCleanPathPdu::new_tcp_connect_error(Some(10061))).unwrap();
+    assert_eq!(serialized["error"]["error_code"], 3);
+    assert_eq!(serialized["error"]["wsa_last_error"], 10061);
+
+    let serialized = serde_json::to_value(response_success_with_ocsp()).unwrap();
+    assert_eq!(
+        serialized["ocsp_response"],
+        serde_json::json!([0x30, 0x03, 0x0A, 0x01, 0x00])
+    );
+    assert_eq!(
+        serialized["server_cert_chain"],
+        serde_json::json!([[0xDE, 0xAD, 0xBE, 0xFF]])
+    );
+
+    // Absent optional fields may be omitted.
+    let deserialized: RDCleanPathPdu = serde_json::from_str(r#"{"version":3390,"error":{"error_code":1}}"#).unwrap();
+    assert_eq!(deserialized, RDCleanPathPdu::new_general_error());
+}
+
+mod builder {
+    use ironrdp_rdcleanpath::{
+        Capabilities, RDCleanPathErr, RDCleanPathErrorCode, RDCleanPathKind, RDCleanPathPdu, RDCleanPathRequest,
+        RDCleanPathResponse, RDCleanPathValidationError, BASE_VERSION, VERSION_2,
+    };
+
+    use rstest::rstest;
+
+    use super::{request, response_http_error, response_success, response_success_with_ocsp};
+
+    #[test]
+    fn build_request() {
+        let built = RDCleanPathPdu::builder()
+            .destination("destination".to_owned())
+            .proxy_auth("proxy auth".to_owned())
+            .preconnection_blob("PCB".to_owned())
+            .x224_connection_pdu(vec![0xDE, 0xAD, 0xBE, 0xFF])
+            .build_request()
+            .unwrap();
+
+        assert_eq!(built.destination(), "destination");
+        assert_eq!(built.proxy_auth(), "proxy auth");
+        assert_eq!(built.server_auth(), None);
+        assert_eq!(built.preconnection_blob(), Some("PCB"));
+        assert_eq!(built.x224_connection_request(), [0xDE, 0xAD, 0xBE, 0xFF]);
+        assert_eq!(built.into_pdu(), request());
+    }
+
+    #[test]
+    fn build_response() {
+        let built = RDCleanPathPdu::builder()
+            .server_addr("192.168.7.95".to_owned())
+            .x224_connection_pdu(vec![0xDE, 0xAD, 0xBE, 0xFF])
+            .server_cert_chain([vec![0xDE, 0xAD, 0xBE, 0xFF]])
+            .ocsp_response(vec![0x30, 0x03, 0x0A, 0x01, 0x00])
+            .build_response()
+            .unwrap();
+
+        assert_eq!(built.server_addr(), "192.168.7.95");
+        assert_eq!(built.x224_connection_response(), [0xDE, 0xAD, 0xBE, 0xFF]);
+        assert_eq!(
+            built.server_cert_chain().collect::<Vec<_>>(),
+            [[0xDE, 0xAD, 0xBE, 0xFF]]
+        );
+        assert_eq!(built.ocsp_response(), Some(&[0x30, 0x03, 0x0A, 0x01, 0x00][..]));
+        assert_eq!(built.into_pdu(), response_success_with_ocsp());
+    }
+
+    #[test]
+    fn build_error() {
+        let error = RDCleanPathErr {
+            http_status_code: Some(500),
+            ..RDCleanPathErr::new(RDCleanPathErrorCode::General)
+        };
+
+        let built = RDCleanPathPdu::builder().error(error).build().unwrap();
+        assert_eq!(built, response_http_error());
+    }
+
+    #[test]
+    fn build_with_capabilities() {
+        let built = RDCleanPathPdu::builder()
+            .server_addr("192.168.7.95".to_owned())
+            .x224_connection_pdu(vec![])
+            .server_cert_chain([])
+            .capabilities(Capabilities::OCSP_RESPONSE)
+            .build_response()
+            .unwrap();
+
+        assert_eq!(built.as_pdu().version, VERSION_2);
+        assert_eq!(built.capabilities(), Capabilities::OCSP_RESPONSE);
+    }
+
+    #[test]
+    fn build_with_client_cert_chain() {
+        let built = RDCleanPathPdu::builder()
+            .destination("destination".to_owned())
+            .proxy_auth("proxy auth".to_owned())
+            .x224_connection_pdu(vec![])
+            .capabilities(Capabilities::OCSP_RESPONSE)
+            .client_cert_chain([vec![0xCA, 0xFE], vec![0xBE, 0xEF]])
+            .build_request()
+            .unwrap();
+
+        assert_eq!(built.as_pdu().version, VERSION_2);
+        assert_eq!(
+            built.capabilities(),
+            Capabilities::OCSP_RESPONSE | Capabilities::CLIENT_CERT_CHAIN
+        );
+        assert_eq!(
+            built.client_cert_chain().collect::<Vec<_>>(),
+            [[0xCA, 0xFE], [0xBE, 0xEF]]
+        );
+    }
+
+    #[test]
+    fn build_with_kdc_proxy_url() {
+        let built = RDCleanPathPdu::builder()
+            .server_addr("192.168.7.95".to_owned())
+            .x224_connection_pdu(vec![])
+            .server_cert_chain([])
+            .capabilities(Capabilities::KDC_PROXY_URL)
+            .kdc_proxy_url("https://gateway.example.com/KdcProxy".to_owned())
+            .build_response()
+            .unwrap();
+
+        assert_eq!(built.kdc_proxy_url(), Some("https://gateway.example.com/KdcProxy"));
+
+        let encoded = built.as_pdu().to_der().unwrap();
+        assert_eq!(&RDCleanPathPdu::from_der(&encoded).unwrap(), built.as_pdu());
+
+        let error = RDCleanPathPdu {
+            kdc_proxy_url: Some("https://gateway.example.com/KdcProxy".to_owned()),
+            ..request()
+        }
+        .validate()
+        .unwrap_err();
+        assert_eq!(
+            error,
+            RDCleanPathValidationError::UnexpectedField {
+                kind: "request",
+                field: "kdc_proxy_url"
+            }
+        );
+    }
+
+    #[rstest]
+    #[case(request(), Ok(RDCleanPathKind::Request))]
+    #[case(response_success(), Ok(RDCleanPathKind::Response))]
+    #[case(response_http_error(), Ok(RDCleanPathKind::Error))]
+    #[case(RDCleanPathPdu::default(), Err(RDCleanPathValidationError::UnknownKind))]
+    #[case(
+        RDCleanPathPdu { version: BASE_VERSION, ..request() },
+        Err(RDCleanPathValidationError::UnsupportedVersion(BASE_VERSION))
+    )]
+    #[case(
+        RDCleanPathPdu { capabilities: Some(0), ..request() },
+        Err(RDCleanPathValidationError::CapabilitiesBeforeVersion2)
+    )]
+    #[case(
+        RDCleanPathPdu { proxy_auth: None, ..request() },
+        Err(RDCleanPathValidationError::MissingField { kind: "request", field: "proxy_auth" })
+    )]
+    #[case(
+        RDCleanPathPdu { server_addr: Some("192.168.7.95".to_owned()), ..request() },
+        Err(RDCleanPathValidationError::UnexpectedField { kind: "request", field: "server_addr" })
+    )]
+    #[case(
+        RDCleanPathPdu { server_cert_chain: None, ..response_success() },
+        Err(RDCleanPathValidationError::MissingField { kind: "response", field: "server_cert_chain" })
+    )]
+    #[case(
+        RDCleanPathPdu { proxy_auth: Some("proxy auth".to_owned()), ..response_success() },
+        Err(RDCleanPathValidationError::UnexpectedField { kind: "response", field: "proxy_auth" })
+    )]
+    #[case(
+        RDCleanPathPdu { server_addr: Some("192.168.7.95".to_owned()), ..response_http_error() },
+        Err(RDCleanPathValidationError::UnexpectedField { kind: "error", field: "server_addr" })
+    )]
+    fn validate(#[case] pdu: RDCleanPathPdu, #[case] expected: Result<RDCleanPathKind, RDCleanPathValidationError>) {
+        assert_eq!(pdu.validate(), expected);
+    }
+
+    #[test]
+    fn typed_view_of_another_kind() {
+        assert_eq!(
+            RDCleanPathResponse::try_from(request()),
+            Err(RDCleanPathValidationError::MissingField {
+                kind: "response",
+                field: "server_addr"
+            })
+        );
+        assert_eq!(
+            RDCleanPathRequest::try_from(response_http_error()),
+            Err(RDCleanPathValidationError::MissingField {
+                kind: "request",
+                field: "destination"
+            })
+        );
+    }
+}
+
+mod codec {
+    use bytes::BytesMut;
+    use ironrdp_rdcleanpath::RDCleanPathCodec;
+    use tokio_util::codec::{Decoder as _, Encoder as _};
+
+    use super::{request, response_success, REQUEST_DER, RESPONSE_SUCCESS_DER};
+
+    #[test]
+    fn decode_in_chunks() {
+        let mut codec = RDCleanPathCodec::new();
+        let mut src = BytesMut::new();
+
+        let (first, second) = REQUEST_DER.split_at(10);
+
+        src.extend_from_slice(&first[..1]);
+        assert_eq!(codec.decode(&mut src).unwrap(), None);
+
+        src.extend_from_slice(&first[1..]);
+        assert_eq!(codec.decode(&mut src).unwrap(), None);
+
+        src.extend_from_slice(second);
+        src.extend_from_slice(&[0x03, 0x00]);
+        assert_eq!(codec.decode(&mut src).unwrap(), Some(request()));
+
+        // The bytes following the PDU are left untouched.
+        assert_eq!(&src[..], [0x03, 0x00]);
+    }
+
+    #[test]
+    fn decode_invalid() {
+        let mut codec = RDCleanPathCodec::new();
+        let mut src = BytesMut::from(&[0x03, 0x00, 0x00, 0x13][..]);
+
+        assert!(codec.decode(&mut src).is_err());
+    }
+
+    #[test]
+    fn encode() {
+        let mut codec = RDCleanPathCodec::new();
+        let mut dst = BytesMut::new();
+
+        codec.encode(request(), &mut dst).unwrap();
+        codec.encode(&response_success(), &mut dst).unwrap();
+
+        assert_eq!(&dst[..REQUEST_DER.len()], REQUEST_DER);
+        assert_eq!(&dst[REQUEST_DER.len()..], RESPONSE_SUCCESS_DER);
+    }
+}
+
+mod pcb {
+    use ironrdp_rdcleanpath::pcb::{HyperVTarget, PreconnectionBlob, PreconnectionBlobError};
+    use ironrdp_rdcleanpath::RDCleanPathPdu;
+    use rstest::rstest;
+
+    const VM_ID: &str = "8b3b5f5e-5a4e-4f0b-a7e8-6d6e3c7d1f3a";
+
+    #[rstest]
+    #[case(VM_ID, false, "8b3b5f5e-5a4e-4f0b-a7e8-6d6e3c7d1f3a")]
+    #[case(VM_ID, true, "8b3b5f5e-5a4e-4f0b-a7e8-6d6e3c7d1f3a;EnhancedMode=1")]
+    #[case(
+        "{8B3B5F5E-5A4E-4F0B-A7E8-6D6E3C7D1F3A}",
+        false,
+        "8B3B5F5E-5A4E-4F0B-A7E8-6D6E3C7D1F3A"
+    )]
+    fn hyperv_vm(#[case] vm_id: &str, #[case] enhanced_session: bool, #[case] expected: &str) {
+        let pcb = PreconnectionBlob::hyperv_vm(vm_id, enhanced_session).unwrap();
+        assert_eq!(pcb.as_str(), expected);
+
+        let target = pcb.hyperv_target().unwrap();
+        assert_eq!(target.vm_id, vm_id.trim_start_matches('{').trim_end_matches('}'));
+        assert_eq!(target.enhanced_session, enhanced_session);
+    }
+
+    #[rstest]
+    #[case("")]
+    #[case("8b3b5f5e-5a4e-4f0b-a7e8")]
+    #[case("8b3b5f5e-5a4e-4f0b-a7e8-6d6e3c7d1f3a-00")]
+    #[case("8b3b5f5e5a4e4f0ba7e86d6e3c7d1f3a")]
+    #[case("zb3b5f5e-5a4e-4f0b-a7e8-6d6e3c7d1f3a")]
+    #[case("{8b3b5f5e-5a4e-4f0b-a7e8-6d6e3c7d1f3a")]
+    fn invalid_vm_id(#[case] vm_id: &str) {
+        assert_eq!(
+            PreconnectionBlob::hyperv_vm(vm_id, false),
+            Err(PreconnectionBlobError::InvalidVmId)
+        );
+    }
+
+    #[test]
+    fn arbitrary_payload() {
+        let pcb: PreconnectionBlob = "session token".parse().unwrap();
+        assert_eq!(pcb.hyperv_target(), None);
+        assert_eq!(String::from(pcb), "session token");
+
+        let pcb: PreconnectionBlob = format!("{VM_ID};EnhancedMode=1").parse().unwrap();
+        assert_eq!(
+            pcb.hyperv_target(),
+            Some(HyperVTarget {
+                vm_id: VM_ID,
+                enhanced_session: true
+            })
+        );
+    }
+
+    #[test]
+    fn invalid_payload() {
+        assert_eq!(
+            PreconnectionBlob::new("a\0b".to_owned()),
+            Err(PreconnectionBlobError::NullCharacter)
+        );
+
+        assert!(PreconnectionBlob::new("a".repeat(PreconnectionBlob::MAX_LENGTH)).is_ok());
+        assert_eq!(
+            PreconnectionBlob::new("\u{1F600}".repeat(PreconnectionBlob::MAX_LENGTH / 2 + 1)),
+            Err(PreconnectionBlobError::TooLong {
+                length: PreconnectionBlob::MAX_LENGTH + 2
+            })
+        );
+    }
+
+    #[test]
+    fn request() {
+        let pcb = PreconnectionBlob::hyperv_vm(VM_ID, false).unwrap();
+
+        let request = RDCleanPathPdu::new_request(
+            vec![0xDE, 0xAD, 0xBE, 0xFF],
+            "hyperv-host:2179".to_owned(),
+            "proxy auth".to_owned(),
+            Some(pcb.into()),
+        )
+        .unwrap();
+
+        assert_eq!(request.preconnection_blob.as_deref(), Some(VM_ID));
+    }
+}
+
+mod websocket {
+    use core::pin::Pin;
+    use core::task::{Context, Poll};
+    use std::collections::VecDeque;
+    use std::io;
+
+    use futures_executor::block_on;
+    use futures_util::{AsyncReadExt as _, AsyncWriteExt as _, Sink, Stream};
+    use ironrdp_rdcleanpath::websocket::{RDCleanPathWebSocket, WebSocketMessage, WebSocketMessageKind};
+
+    use super::{request, response_success, REQUEST_DER, RESPONSE_SUCCESS_DER};
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    enum Message {
+        Binary(Vec<u8>),
+        Text(String),
+        Ping,
+        Close,
+    }
+
+    impl WebSocketMessage for Message {
+        fn binary(data: Vec<u8>) -> Self {
+            Self::Binary(data)
+        }
+
+        fn into_kind(self) -> WebSocketMessageKind {
+            match self {
+                Self::Binary(data) => WebSocketMessageKind::Binary(data),
+                Self::Text(_) => WebSocketMessageKind::Text,
+                Self::Ping => WebSocketMessageKind::Control,
+                Self::Close => WebSocketMessageKind::Close,
+            }
+        }
+    }
+
+    #[derive(Default)]
+    struct MockWebSocket {
+        incoming: VecDeque<Message>,
+        outgoing: Vec<Message>,
+    }
+
+    impl MockWebSocket {
+        fn new(incoming: impl IntoIterator<Item = Message>) -> Self {
+            Self {
+                incoming: incoming.into_iter().collect(),
+                outgoing: Vec::new(),
+            }
+        }
+    }
+
+    impl Stream for MockWebSocket {
+        type Item = io::Result<Message>;
+
+        fn poll_next(mut self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+            Poll::Ready(self.incoming.pop_front().map(Ok))
+        }
+    }
+
+    impl Sink<Message> for MockWebSocket {
+        type Error = io::Error;
+
+        fn poll_ready(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn start_send(mut self: Pin<&mut Self>, item: Message) -> io::Result<()> {
+            self.outgoing.push(item);
+            Ok(())
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_close(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    #[test]
+    fn send() {
+        let mut ws = RDCleanPathWebSocket::new(MockWebSocket::default());
+
+        block_on(ws.send(&request())).unwrap();
+
+        let (socket, _) = ws.into_inner();
+        assert_eq!(socket.outgoing, [Message::Binary(REQUEST_DER.to_vec())]);
+    }
+
+    #[test]
+    fn receive_then_passthrough() {
+        let (first, second) = RESPONSE_SUCCESS_DER.split_at(7);
+        let mut second = second.to_vec();
+        second.extend_from_slice(&[0x03, 0x00]);
+
+        let mut ws = RDCleanPathWebSocket::new(MockWebSocket::new([
+            Message::Binary(first.to_vec()),
+            Message::Ping,
+            Message::Binary(second),
+            Message::Binary(vec![0x00, 0x13]),
+            Message::Close,
+        ]));
+
+        assert_eq!(block_on(ws.receive()).unwrap(), response_success());
+
+        let mut stream = ws.into_passthrough();
+
+        let mut received = Vec::new();
+        block_on(stream.read_to_end(&mut received)).unwrap();
+        assert_eq!(received, [0x03, 0x00, 0x00, 0x13]);
+
+        block_on(stream.write_all(&[0xDE, 0xAD])).unwrap();
+        assert_eq!(stream.into_inner().outgoing, [Message::Binary(vec![0xDE, 0xAD])]);
+    }
+
+    #[test]
+    fn receive_several_pdus_from_one_message() {
+        let mut ws = RDCleanPathWebSocket::new(MockWebSocket::new([Message::Binary(
+            [REQUEST_DER, RESPONSE_SUCCESS_DER].concat(),
+        )]));
+
+        assert_eq!(block_on(ws.receive()).unwrap(), request());
+        assert_eq!(block_on(ws.receive()).unwrap(), response_success());
+    }
+
+    #[test]
+    fn receive_errors() {
+        let mut ws = RDCleanPathWebSocket::new(MockWebSocket::new([Message::Text("hello".to_owned())]));
+        assert_eq!(block_on(ws.receive()).unwrap_err().kind(), io::ErrorKind::InvalidData);
+
+        let mut ws = RDCleanPathWebSocket::new(MockWebSocket::new([
+            Message::Binary(REQUEST_DER[..10].to_vec()),
+            Message::Close,
+        ]));
+        assert_eq!(block_on(ws.receive()).unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+
+        let mut ws = RDCleanPathWebSocket::new(MockWebSocket::new([Message::Binary(vec![0x03, 0x00, 0x00, 0x13])]));
+        assert_eq!(block_on(ws.receive()).unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+}
+
+mod server_address {
+    use std::net::SocketAddr;
+
+    use ironrdp_rdcleanpath::{connection_attempt_order, RDCleanPathPdu, ServerAddress};
+    use rstest::rstest;
+
+    fn addresses(addresses: &[&str]) -> Vec<SocketAddr> {
+        addresses.iter().map(|address| address.parse().unwrap()).collect()
+    }
+
+    #[rstest]
+    #[case(&[], &[])]
+    #[case(&["10.0.0.1:3389", "10.0.0.2:3389"], &["10.0.0.1:3389", "10.0.0.2:3389"])]
+    #[case(
+        &["[2001:db8::1]:3389", "[2001:db8::2]:3389", "10.0.0.1:3389", "10.0.0.2:3389", "10.0.0.3:3389"],
+        &["[2001:db8::1]:3389", "10.0.0.1:3389", "[2001:db8::2]:3389", "10.0.0.2:3389", "10.0.0.3:3389"]
+    )]
+    #[case(
+        &["10.0.0.1:3389", "10.0.0.2:3389", "[2001:db8::1]:3389"],
+        &["10.0.0.1:3389", "[2001:db8::1]:3389", "10.0.0.2:3389"]
+    )]
+    fn attempt_order(#[case] resolved: &[&str], #[case] expected: &[&str]) {
+        assert_eq!(connection_attempt_order(addresses(resolved)), addresses(expected));
+    }
+
+    #[test]
+    fn round_trip() {
+        let pdu = RDCleanPathPdu::new_response(
+            "10.0.0.1:3389".to_owned(),
+            vec![0xDE, 0xAD, 0xBE, 0xFF],
+            [vec![0xDE, 0xAD, 0xBE, 0xFF]],
+            None,
+        )
+        .unwrap()
+        .with_server_addresses(addresses(&["10.0.0.1:3389", "[2001:db8::1]:3389"]));
+
+        assert_eq!(
+            pdu.server_addresses.as_deref(),
+            Some(
+                &[
+                    ServerAddress {
+                        address: "10.0.0.1:3389".to_owned(),
+                        priority: 0,
+                    },
+                    ServerAddress {
+                        address: "[2001:db8::1]:3389".to_owned(),
+                        priority: 1,
+                    },
+                ][..]
+            )
+        );
+
+        let encoded = pdu.to_der().unwrap();
+        assert_eq!(RDCleanPathPdu::from_der(&encoded).unwrap(), pdu);
+    }
+
+    #[test]
+    fn sorted_by_priority() {
+        let pdu = RDCleanPathPdu {
+            server_addr: Some("10.0.0.1:3389".to_owned()),
+            server_addresses: Some(vec![
+                ServerAddress {
+                    address: "10.0.0.2:3389".to_owned(),
+                    priority: 10,
+                },
+                ServerAddress {
+                    address: "not an address".to_owned(),
+                    priority: 0,
+                },
+                ServerAddress {
+                    address: "[2001:db8::1]:3389".to_owned(),
+                    priority: 5,
+                },
+            ]),
+            ..RDCleanPathPdu::default()
+        };
+
+        assert_eq!(
+            pdu.server_addresses(),
+            addresses(&["[2001:db8::1]:3389", "10.0.0.2:3389"])
+        );
+    }
+
+    #[test]
+    fn fallback_on_server_addr() {
+        let pdu = RDCleanPathPdu {
+            server_addr: Some("10.0.0.1:3389".to_owned()),
+            ..RDCleanPathPdu::default()
+        };
+
+        assert_eq!(pdu.server_addresses(), addresses(&["10.0.0.1:3389"]));
+    }
+}
+
+mod connector {
+    use ironrdp_connector::{ClientConnector, ClientConnectorState};
+    use ironrdp_core::encode_vec;
+    use ironrdp_pdu::nego;
+    use ironrdp_pdu::x224::X224;
+    use ironrdp_rdcleanpath::connector::{RDCleanPathConnector, RDCleanPathConnectorError, RDCleanPathUpgrade};
+    use ironrdp_rdcleanpath::{Capabilities, RDCleanPath, RDCleanPathPdu};
+
+    fn rdcleanpath_connector() -> RDCleanPathConnector {
+        RDCleanPathConnector::new("destination".to_owned(), "proxy auth".to_owned())
+            .with_preconnection_blob(Some("PCB".to_owned()))
+    }
+
+    fn connector_waiting_for_response() -> ClientConnector {
+        let mut connector = ClientConnector::new(crate::connector::config());
+        rdcleanpath_connector().request(&mut connector).unwrap();
+        connector
+    }
+
+    fn response(server_cert_chain: Vec<Vec<u8>>) -> Vec<u8> {
+        let confirm = encode_vec(&X224(nego::ConnectionConfirm::Response {
+            flags: nego::ResponseFlags::empty(),
+            protocol: nego::SecurityProtocol::SSL,
+        }))
+        .unwrap();
+
+        RDCleanPathPdu::new_response("192.168.7.95:3389".to_owned(), confirm, server_cert_chain, None)
+            .unwrap()
+            .to_der()
+            .unwrap()
+    }
+
+    #[test]
+    fn request() {
+        let mut connector = ClientConnector::new(crate::connector::config());
+
+        let request = rdcleanpath_connector().request(&mut connector).unwrap();
+        assert!(matches!(
+            connector.state,
+            ClientConnectorState::ConnectionInitiationWaitConfirm { .. }
+        ));
+
+        let request = RDCleanPathPdu::from_der(&request).unwrap().into_enum().unwrap();
+        let RDCleanPath::Request {
+            destination,
+            proxy_auth,
+            preconnection_blob,
+            x224_connection_request,
+            ..
+        } = request
+        else {
+            panic!("unexpected RDCleanPath PDU: {request:?}");
+        };
+
+        assert_eq!(destination, "destination");
+        assert_eq!(proxy_auth, "proxy auth");
+        assert_eq!(preconnection_blob.as_deref(), Some("PCB"));
+        ironrdp_core::decode::<X224<nego::ConnectionRequest>>(x224_connection_request.as_bytes()).unwrap();
+    }
+
+    #[test]
+    fn request_with_client_cert_chain() {
+        let mut connector = ClientConnector::new(crate::connector::config());
+
+        let request = rdcleanpath_connector()
+            .with_client_cert_chain(vec![vec![0xCA, 0xFE]])
+            .request(&mut connector)
+            .unwrap();
+
+        let request = RDCleanPathPdu::from_der(&request).unwrap();
+        assert!(request.capabilities().contains(Capabilities::CLIENT_CERT_CHAIN));
+        assert_eq!(request.client_cert_chain.unwrap()[0].as_bytes(), [0xCA, 0xFE]);
+    }
+
+    #[test]
+    fn reconnection_attempts() {
+        let upgrade = RDCleanPathUpgrade {
+            server_addr: "10.0.0.1:3389".parse().unwrap(),
+            server_cert_chain: Vec::new(),
+            server_public_key: Vec::new(),
+            ocsp_response: None,
+            client_cert_chain: None,
+            kdc_proxy_url: None,
+            server_addresses: vec!["10.0.0.1:3389".parse().unwrap(), "[2001:db8::1]:3389".parse().unwrap()],
+        };
+
+        let destinations: Vec<_> = rdcleanpath_connector()
+            .reconnection_attempts(&upgrade)
+            .map(|rdcleanpath_connector| {
+                let mut connector = ClientConnector::new(crate::connector::config());
+                let request = rdcleanpath_connector.request(&mut connector).unwrap();
+
+                let request = RDCleanPathPdu::from_der(&request).unwrap().into_enum().unwrap();
+                let RDCleanPath::Request {
+                    destination,
+                    proxy_auth,
+                    preconnection_blob,
+                    ..
+                } = request
+                else {
+                    panic!("unexpected RDCleanPath PDU: {request:?}");
+                };
+                assert_eq!(proxy_auth, "proxy auth");
+                assert_eq!(preconnection_blob.as_deref(), Some("PCB"));
+
+                destination
+            })
+            .collect();
+
+        assert_eq!(destinations, ["10.0.0.1:3389", "[2001:db8::1]:3389"]);
+    }
+
+    #[test]
+    fn proxy_error() {
+        let mut connector = connector_waiting_for_response();
+
+        let response = RDCleanPathPdu::new_authorization_error(403).to_der().unwrap();
+        let error = rdcleanpath_connector()
+            .process_response(&mut connector, &response)
+            .unwrap_err();
+
+        assert!(matches!(
+            error,
+            RDCleanPathConnectorError::Proxy(error) if error.http_status_code == Some(403)
+        ));
+
+        // The connector is left untouched.
+        assert!(matches!(
+            connector.state,
+            ClientConnectorState::ConnectionInitiationWaitConfirm { .. }
+        ));
+    }
+
+    #[test]
+    fn invalid_certificate() {
+        let mut connector = connector_waiting_for_response();
+
+        let error = rdcleanpath_connector()
+            .process_response(&mut connector, &response(vec![vec![0xDE, 0xAD, 0xBE, 0xFF]]))
+            .unwrap_err();
+
+        assert!(matches!(
+            error,
+            RDCleanPathConnectorError::InvalidResponse("invalid server certificate")
+        ));
+    }
+
+    #[test]
+    fn empty_certificate_chain() {
+        let mut connector = connector_waiting_for_response();
+
+        let error = rdcleanpath_connector()
+            .process_response(&mut connector, &response(Vec::new()))
+            .unwrap_err();
+
+        assert!(matches!(
+            error,
+            RDCleanPathConnectorError::InvalidResponse("empty server certificate chain")
+        ));
+    }
+
+    #[test]
+    fn unexpected_connector_state() {
+        let mut connector = ClientConnector::new(crate::connector::config());
+
+        let error = rdcleanpath_connector()
+            .process_response(&mut connector, &response(Vec::new()))
+            .unwrap_err();
+
+        assert!(matches!(
+            error,
+            RDCleanPathConnectorError::UnexpectedConnectorState("ConnectionInitiationSendRequest")
+        ));
+    }
+}
+
+mod proxy {
+    use std::io;
+    use std::net::SocketAddr;
+
+    use ironrdp_core::encode_vec;
+    use ironrdp_pdu::nego;
+    use ironrdp_pdu::x224::X224;
+    use ironrdp_rdcleanpath::proxy::{DestinationRequest, ProxyError, ProxySequence, ProxyState};
+    use ironrdp_rdcleanpath::{Capabilities, RDCleanPath, RDCleanPathPdu, RDCleanPathResponse, VERSION_1, VERSION_2};
+    use rstest::rstest;
+
+    const SERVER_ADDR: &str = "192.168.7.95:3389";
+    const OCSP_RESPONSE: &[u8] = &[0x30, 0x03, 0x0A, 0x01, 0x00];
+
+    fn x224_connection_request() -> Vec<u8> {
+        encode_vec(&X224(nego::ConnectionRequest {
+            nego_data: None,
+            flags: nego::RequestFlags::empty(),
+            protocol: nego::SecurityProtocol::SSL | nego::SecurityProtocol::HYBRID,
+        }))
+        .unwrap()
+    }
+
+    fn x224_connection_confirm(confirm: nego::ConnectionConfirm) -> Vec<u8> {
+        encode_vec(&X224(confirm)).unwrap()
+    }
+
+    fn client_request(pcb: Option<&str>) -> Vec<u8> {
+        RDCleanPathPdu::new_request(
+            x224_connection_request(),
+            "destination".to_owned(),
+            "proxy auth".to_owned(),
+            pcb.map(str::to_owned),
+        )
+        .unwrap()
+        .to_der()
+        .unwrap()
+    }
+
+    fn connected_proxy(pcb: Option<&str>) -> (ProxySequence, Vec<u8>) {
+        let mut proxy = ProxySequence::new();
+
+        let destination = proxy.process_request(&client_request(pcb)).unwrap();
+        assert_eq!(
+            destination,
+            DestinationRequest {
+                destination: "destination".to_owned(),
+                proxy_auth: "proxy auth".to_owned(),
+                server_auth: None,
+                client_cert_chain: None,
+            }
+        );
+
+        let to_server = proxy.server_connected(SERVER_ADDR.parse().unwrap()).unwrap();
+
+        (proxy, to_server)
+    }
+
+    #[test]
+    fn successful_connection() {
+        let (mut proxy, to_server) = connected_proxy(None);
+        assert_eq!(to_server, x224_connection_request());
+
+        let confirm = x224_connection_confirm(nego::ConnectionConfirm::Response {
+            flags: nego::ResponseFlags::empty(),
+            protocol: nego::SecurityProtocol::HYBRID,
+        });
+        proxy.process_x224_response(&confirm).unwrap();
+
+        let response = proxy.tls_upgraded([vec![0xDE, 0xAD, 0xBE, 0xFF]], None).unwrap();
+        assert!(matches!(proxy.state(), ProxyState::Connected));
+
+        let response = RDCleanPathPdu::from_der(&response).unwrap().into_enum().unwrap();
+        let RDCleanPath::Response {
+            x224_connection_response,
+            server_cert_chain,
+            ocsp_response,
+            server_addr,
+            client_cert_chain,
+            kdc_proxy_url,
+            server_addresses,
+            capabilities,
+        } = response
+        else {
+            panic!("unexpected RDCleanPath PDU: {response:?}");
+        };
+
+        assert_eq!(x224_connection_response.as_bytes(), confirm);
+        assert_eq!(server_cert_chain.len(), 1);
+        assert_eq!(server_cert_chain[0].as_bytes(), [0xDE, 0xAD, 0xBE, 0xFF]);
+        assert_eq!(ocsp_response, None);
+        assert_eq!(server_addr, SERVER_ADDR);
+        assert_eq!(client_cert_chain, None);
+        assert_eq!(kdc_proxy_url, None);
+        assert_eq!(server_addresses, None);
+        assert_eq!(capabilities, None);
+    }
+
+    #[rstest]
+    #[case(Capabilities::SERVER_ADDRESSES, &["[2001:db8::1]:3389", "192.168.7.95:3389", "[2001:db8::2]:3389"][..])]
+    #[case(Capabilities::empty(), &[SERVER_ADDR][..])]
+    fn server_addresses(#[case] capabilities: Capabilities, #[case] expected_server_addresses: &[&str]) {
+        let request = RDCleanPathPdu::new_request(
+            x224_connection_request(),
+            "destination".to_owned(),
+            "proxy auth".to_owned(),
+            None,
+        )
+        .unwrap()
+        .with_capabilities(capabilities);
+
+        let mut proxy = ProxySequence::new();
+        proxy.process_request(&request.to_der().unwrap()).unwrap();
+
+        let resolved: Vec<SocketAddr> = ["[2001:db8::1]:3389", "[2001:db8::2]:3389", "192.168.7.95:3389"]
+            .iter()
+            .map(|address| address.parse().unwrap())
+            .collect();
+        let attempt_order = proxy.destination_resolved(resolved).unwrap();
+        assert_eq!(attempt_order[1], SERVER_ADDR.parse().unwrap());
+
+        proxy.server_connected(attempt_order[1]).unwrap();
+        let confirm = x224_connection_confirm(nego::ConnectionConfirm::Response {
+            flags: nego::ResponseFlags::empty(),
+            protocol: nego::SecurityProtocol::HYBRID,
+        });
+        proxy.process_x224_response(&confirm).unwrap();
+
+        let response = proxy.tls_upgraded([], None).unwrap();
+        let response = RDCleanPathResponse::try_from(RDCleanPathPdu::from_der(&response).unwrap()).unwrap();
+
+        assert_eq!(response.server_addr(), SERVER_ADDR);
+        assert_eq!(
+            response.server_addresses(),
+            expected_server_addresses
+                .iter()
+                .map(|address| address.parse().unwrap())
+                .collect::<Vec<SocketAddr>>()
+        );
+    }
+
+    #[test]
+    fn destination_resolved_to_no_address() {
+        let mut proxy = ProxySequence::new();
+        proxy.process_request(&client_request(None)).unwrap();
+
+        let error = proxy.destination_resolved([]).unwrap_err();
+        assert!(matches!(error, ProxyError::DnsResolution(_)));
+        assert!(matches!(proxy.state(), ProxyState::Failed));
+    }
+
+    #[rstest]
+    #[case(Capabilities::KDC_PROXY_URL, Some("https://gateway.example.com/KdcProxy"))]
+    #[case(Capabilities::empty(), None)]
+    fn kdc_proxy_url(#[case] capabilities: Capabilities, #[case] expected_kdc_proxy_url: Option<&str>) {
+        let request = RDCleanPathPdu::new_request(
+            x224_connection_request(),
+            "destination".to_owned(),
+            "proxy auth".to_owned(),
+            None,
+        )
+        .unwrap()
+        .with_capabilities(capabilities);
+
+        let mut proxy = ProxySequence::new().with_kdc_proxy_url("https://gateway.example.com/KdcProxy".to_owned());
+        proxy.process_request(&request.to_der().unwrap()).unwrap();
+        proxy.server_connected(SERVER_ADDR.parse().unwrap()).unwrap();
+        let confirm = x224_connection_confirm(nego::ConnectionConfirm::Response {
+            flags: nego::ResponseFlags::empty(),
+            protocol: nego::SecurityProtocol::HYBRID,
+        });
+        proxy.process_x224_response(&confirm).unwrap();
+
+        let response = proxy.tls_upgraded([], None).unwrap();
+        let response = RDCleanPathResponse::try_from(RDCleanPathPdu::from_der(&response).unwrap()).unwrap();
+
+        assert_eq!(response.kdc_proxy_url(), expected_kdc_proxy_url);
+    }
+
+    #[rstest]
+    #[case(true, Some(&[0xCA, 0xFE][..]))]
+    #[case(false, None)]
+    fn client_cert_chain(#[case] client_supports_it: bool, #[case] expected_client_cert: Option<&[u8]>) {
+        let mut request = RDCleanPathPdu::new_request(
+            x224_connection_request(),
+            "destination".to_owned(),
+            "proxy auth".to_owned(),
+            None,
+        )
+        .unwrap();
+
+        if client_supports_it {
+            request = request.with_client_cert_chain([vec![0xCA, 0xFE]]).unwrap();
+        }
+
+        let mut proxy = ProxySequence::new();
+        let destination = proxy.process_request(&request.to_der().unwrap()).unwrap();
+        assert_eq!(
+            destination.client_cert_chain,
+            expected_client_cert.map(|cert| vec![cert.to_vec()])
+        );
+
+        proxy.server_connected(SERVER_ADDR.parse().unwrap()).unwrap();
+        let confirm = x224_connection_confirm(nego::ConnectionConfirm::Response {
+            flags: nego::ResponseFlags::empty(),
+            protocol: nego::SecurityProtocol::HYBRID,
+        });
+        proxy.process_x224_response(&confirm).unwrap();
+
+        proxy.client_cert_presented([vec![0xCA, 0xFE]]).unwrap();
+        let response = proxy.tls_upgraded([], None).unwrap();
+        let response = RDCleanPathPdu::from_der(&response).unwrap();
+
+        assert_eq!(
+            response
+                .client_cert_chain
+                .as_ref()
+                .map(|chain| chain.iter().map(|cert| cert.as_bytes()).collect::<Vec<_>>()),
+            expected_client_cert.map(|cert| vec![cert])
+        );
+    }
+
+    #[test]
+    fn client_cert_presented_out_of_order() {
+        let (mut proxy, _) = connected_proxy(None);
+
+        let error = proxy.client_cert_presented([]).unwrap_err();
+        assert!(matches!(
+            error,
+            ProxyError::UnexpectedStep {
+                expected: "TlsUpgrade",
+                ..
+            }
+        ));
+        assert!(matches!(proxy.state(), ProxyState::WaitX224Response { .. }));
+    }
+
+    #[rstest]
+    #[case(None, VERSION_1, None)]
+    #[case(Some(Capabilities::empty()), VERSION_2, None)]
+    #[case(Some(Capabilities::OCSP_RESPONSE), VERSION_2, Some(OCSP_RESPONSE))]
+    fn version_and_capabilities_negotiation(
+        #[case] capabilities: Option<Capabilities>,
+        #[case] expected_version: u64,
+        #[case] expected_ocsp_response: Option<&[u8]>,
+    ) {
+        let mut request = RDCleanPathPdu::new_request(
+            x224_connection_request(),
+            "destination".to_owned(),
+            "proxy auth".to_owned(),
+            None,
+        )
+        .unwrap();
+
+        if let Some(capabilities) = capabilities {
+            request = request.with_capabilities(capabilities);
+        }
+
+        let mut proxy = ProxySequence::new();
+        proxy.process_request(&request.to_der().unwrap()).unwrap();
+        assert_eq!(proxy.version(), expected_version);
+        assert_eq!(proxy.capabilities(), capabilities.unwrap_or_default());
+
+        proxy.server_connected(SERVER_ADDR.parse().unwrap()).unwrap();
+        let confirm = x224_connection_confirm(nego::ConnectionConfirm::Response {
+            flags: nego::ResponseFlags::empty(),
+            protocol: nego::SecurityProtocol::HYBRID,
+        });
+        proxy.process_x224_response(&confirm).unwrap();
+
+        let response = proxy.tls_upgraded([], Some(OCSP_RESPONSE.to_vec())).unwrap();
+        let response = RDCleanPathPdu::from_der(&response).unwrap();
+
+        assert_eq!(response.version, expected_version);
+        assert_eq!(response.capabilities(), capabilities.unwrap_or_default());
+        assert_eq!(
+            response
+                .ocsp_response
+                .as_ref()
+                .map(|ocsp_response| ocsp_response.as_bytes()),
+            expected_ocsp_response
+        );
+    }
+
+    #[test]
+    fn preconnection_blob_is_sent_first() {
+        let (_, to_server) = connected_proxy(Some("PCB"));
+
+        assert!(to_server.ends_with(&x224_connection_request()));
+
+        let pcb_size = to_server.len() - x224_connection_request().len();
+        let pcb = ironrdp_core::decode::<ironrdp_pdu::pcb::PreconnectionBlob>(&to_server[..pcb_size]).unwrap();
+        assert_eq!(pcb.v2_payload.as_deref(), Some("PCB"));
+    }
+
+    #[test]
+    fn invalid_preconnection_blob_is_rejected() {
+        let mut proxy = ProxySequence::new();
+
+        let error = proxy.process_request(&client_request(Some("PCB\0"))).unwrap_err();
+        assert!(matches!(error, ProxyError::BadRequest("invalid preconnection blob")));
+        assert!(matches!(proxy.state(), ProxyState::Failed));
+    }
+
+    #[test]
+    fn response_is_rejected_as_request() {
+        let mut proxy = ProxySequence::new();
+
+        let response = RDCleanPathPdu::new_response(SERVER_ADDR.to_owned(), vec![], [], None)
+            .unwrap()
+            .to_der()
+            .unwrap();
+
+        let error = proxy.process_request(&response).unwrap_err();
+        assert!(matches!(error, ProxyError::BadRequest(_)));
+        assert!(matches!(proxy.state(), ProxyState::Failed));
+        assert_eq!(error.to_pdu(), RDCleanPathPdu::new_http_error(400));
+    }
+
+    #[test]
+    fn negotiation_failure() {
+        let (mut proxy, _) = connected_proxy(None);
+
+        let confirm = x224_connection_confirm(nego::ConnectionConfirm::Failure {
+            code: nego::FailureCode::SSL_NOT_ALLOWED_BY_SERVER,
+        });
+
+        let error = proxy.process_x224_response(&confirm).unwrap_err();
+        assert!(matches!(
+            error,
+            ProxyError::NegotiationFailure(nego::FailureCode::SSL_NOT_ALLOWED_BY_SERVER)
+        ));
+        assert_eq!(error.to_pdu(), RDCleanPathPdu::new_general_error());
+    }
+
+    #[test]
+    fn caller_reported_failures() {
+        let mut proxy = ProxySequence::new();
+        proxy.process_request(&client_request(None)).unwrap();
+
+        let error = ProxyError::ServerConnection(io::Error::from(io::ErrorKind::ConnectionRefused));
+        let error_pdu = RDCleanPathPdu::from_der(&proxy.fail(&error)).unwrap();
+        assert!(matches!(proxy.state(), ProxyState::Failed));
+        assert_eq!(error_pdu, RDCleanPathPdu::new_tcp_connect_error(Some(10061)));
+
+        assert_eq!(
+            ProxyError::Tls { alert_code: Some(48) }.to_pdu(),
+            RDCleanPathPdu::new_tls_handshake_error(Some(48))
+        );
+        assert_eq!(
+            ProxyError::Forbidden.to_pdu(),
+            RDCleanPathPdu::new_authorization_error(403)
+        );
+        assert_eq!(
+            ProxyError::DnsResolution(io::Error::from(io::ErrorKind::NotFound)).to_pdu(),
+            RDCleanPathPdu::new_dns_resolution_error()
+        );
+    }
+
+    #[test]
+    fn accept_request() {
+        let mut proxy = ProxySequence::new();
+
+        let destination = proxy
+            .accept_request(&client_request(None), |destination| {
+                assert_eq!(destination.proxy_auth, "proxy auth");
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(destination.destination, "destination");
+        assert!(matches!(proxy.state(), ProxyState::ConnectServer { .. }));
+    }
+
+    #[test]
+    fn accept_request_unauthorized() {
+        let mut proxy = ProxySequence::new();
+
+        let error = proxy
+            .accept_request(&client_request(None), |_| Err(ProxyError::Unauthorized))
+            .unwrap_err();
+        assert!(matches!(error, ProxyError::Unauthorized));
+        assert!(matches!(proxy.state(), ProxyState::Failed));
+        assert_eq!(error.to_pdu(), RDCleanPathPdu::new_authorization_error(401));
+    }
+
+    #[test]
+    fn out_of_order_step() {
+        let mut proxy = ProxySequence::new();
+        let server_addr: SocketAddr = SERVER_ADDR.parse().unwrap();
+
+        let error = proxy.server_connected(server_addr).unwrap_err();
+        assert!(matches!(
+            error,
+            ProxyError::UnexpectedStep {
+                expected: "ConnectServer",
+                state: "WaitRequest"
+            }
+        ));
+
+        // The state is left untouched.
+        proxy.process_request(&client_request(None)).unwrap();
+    }
 }
@@ -1,4 +1,6 @@
 use ironrdp_graphics::image_processing::PixelFormat;
+use ironrdp_graphics::rfx_encoder::{RfxEncoder, RfxSourceImage};
+use ironrdp_pdu::codecs::rfx::EntropyAlgorithm;
 use ironrdp_pdu::geometry::InclusiveRectangle;
 use ironrdp_session::image::DecodedImage;
 use ironrdp_session::rfx::DecodingContext;
@@ -31,6 +33,45 @@ fn decode_decodes_valid_sequence_of_messages() {
     assert_eq!(expected, image.data());
 }
 
+#[test]
+fn encode_round_trips_reference_image() {
+    let destination = InclusiveRectangle {
+        left: 0,
+        top: 0,
+        right: u16::try_from(IMAGE_WIDTH).unwrap() - 1,
+        bottom: u16::try_from(IMAGE_HEIGHT).unwrap() - 1,
+    };
+    let source = RfxSourceImage {
+        data: DECODED_IMAGE.as_ref(),
+        width: IMAGE_WIDTH.try_into().unwrap(),
+        height: IMAGE_HEIGHT.try_into().unwrap(),
+        stride: IMAGE_WIDTH * FORMAT_SIZE,
+        format: PixelFormat::BgrX32,
+    };
+
+    let mut encoder = RfxEncoder::new(EntropyAlgorithm::Rlgr3);
+    let encoded = encoder.encode(&source).unwrap();
+
+    let mut image = DecodedImage::new(
+        PixelFormat::BgrX32,
+        IMAGE_WIDTH.try_into().unwrap(),
+        IMAGE_HEIGHT.try_into().unwrap(),
+    );
+    DecodingContext::default()
+        .decode(&mut image, &destination, &mut encoded.as_slice())
+        .unwrap();
+
+    // RemoteFX being lossy, colors are only compared with a tolerance.
+    for (expected, actual) in DECODED_IMAGE
+        .chunks_exact(FORMAT_SIZE)
+        .zip(image.data().chunks_exact(FORMAT_SIZE))
+    {
+        for (expected, actual) in expected[..3].iter().zip(&actual[..3]) {
+            assert!(expected.abs_diff(*actual) <= 8, "{expected:?} != {actual:?}");
+        }
+    }
+}
+
 const ENCODED_MESSAGES: [u8; 2970] = [
     /* HEADERS as in 4.2.2 */
     0xc0, 0xcc, 0x0c, 0x00, 0x00, 0x00, 0xca, 0xac, 0xcc, 0xca, 0x00, 0x01, 0xc3, 0xcc, 0x0d, 0x00, 0x00, 0x00, 0x01,
@@ -0,0 +1,348 @@
+use ironrdp_core::{
+    impl_as_any, with_decode_options, DecodeLimits, DecodeMode, DecodeOptions, DecodeResult, EncodeResult, WriteBufPool,
+};
+use ironrdp_pdu::gcc::{ChannelName, ChannelOptions};
+use ironrdp_pdu::PduResult;
+use ironrdp_svc::testing::{MockError, MockPeer, SvcMockEndpoint};
+use ironrdp_svc::wire_log::{set_wire_logging, wire_logging, ChannelWireLog, WireLogging};
+use ironrdp_svc::{
+    client_encode_svc_messages, client_encode_svc_messages_with_pool, make_channel_options, negotiated_chunk_length,
+    ChannelFlags, ChunkProcessor, CompressionCondition, StaticVirtualChannel, SvcMessage, SvcProcessor, VcCompressor,
+    VcDecompressor, CHANNEL_CHUNK_LENGTH, MAX_CHANNEL_CHUNK_LENGTH,
+};
+
+const CHANNEL_FLAG_FIRST: u32 = 0x0000_0001;
+const CHANNEL_FLAG_LAST: u32 = 0x0000_0002;
+
+fn chunk(length: u32, flags: u32, data: &[u8]) -> Vec<u8> {
+    let mut chunk = Vec::new();
+    chunk.extend_from_slice(&length.to_le_bytes());
+    chunk.extend_from_slice(&flags.to_le_bytes());
+    chunk.extend_from_slice(data);
+    chunk
+}
+
+fn header(chunk: &[u8]) -> (u32, u32) {
+    (
+        u32::from_le_bytes(chunk[0..4].try_into().unwrap()),
+        u32::from_le_bytes(chunk[4..8].try_into().unwrap()),
+    )
+}
+
+#[test]
+fn chunkify_sets_first_and_last_flags() {
+    let pdu = vec![0xAB; 4000];
+
+    let chunks = ChunkProcessor::chunkify(vec![SvcMessage::from(pdu)], CHANNEL_CHUNK_LENGTH).unwrap();
+
+    let headers = chunks.iter().map(|chunk| header(chunk.filled())).collect::<Vec<_>>();
+    assert_eq!(
+        headers,
+        [(4000, CHANNEL_FLAG_FIRST), (4000, 0), (4000, CHANNEL_FLAG_LAST)]
+    );
+
+    let lengths = chunks.iter().map(|chunk| chunk.filled_len() - 8).collect::<Vec<_>>();
+    assert_eq!(lengths, [1600, 1600, 800]);
+}
+
+#[test]
+fn chunkify_uses_the_negotiated_chunk_length() {
+    let pdu = vec![0xAB; 4000];
+    let chunk_length = negotiated_chunk_length(Some(4096));
+
+    let chunks = ChunkProcessor::chunkify(vec![SvcMessage::from(pdu)], chunk_length).unwrap();
+
+    let headers = chunks.iter().map(|chunk| header(chunk.filled())).collect::<Vec<_>>();
+    assert_eq!(headers, [(4000, CHANNEL_FLAG_FIRST | CHANNEL_FLAG_LAST)]);
+}
+
+#[test]
+fn chunkify_rejects_invalid_chunk_length() {
+    assert!(ChunkProcessor::chunkify(vec![SvcMessage::from(vec![0; 16])], 0).is_err());
+    assert!(ChunkProcessor::chunkify(vec![SvcMessage::from(vec![0; 16])], MAX_CHANNEL_CHUNK_LENGTH + 1).is_err());
+}
+
+#[test]
+fn encode_with_pool_reuses_buffers() {
+    let messages = || vec![SvcMessage::from(vec![0xAB; 4000]), SvcMessage::from(vec![0xCD; 16])];
+    let expected = client_encode_svc_messages(messages(), 1004, 1007).unwrap();
+
+    let mut pool = WriteBufPool::new();
+
+    let encoded = client_encode_svc_messages_with_pool(messages(), 1004, 1007, &mut pool).unwrap();
+    assert_eq!(encoded.filled(), expected);
+    // The scratch buffer and the four chunks are back in the pool.
+    assert_eq!(pool.available(), 5);
+    pool.checkin(encoded);
+
+    let encoded = client_encode_svc_messages_with_pool(messages(), 1004, 1007, &mut pool).unwrap();
+    assert_eq!(encoded.filled(), expected);
+    assert_eq!(pool.available(), 5);
+}
+
+#[test]
+fn negotiated_chunk_length_is_clamped() {
+    assert_eq!(negotiated_chunk_length(None), CHANNEL_CHUNK_LENGTH);
+    assert_eq!(negotiated_chunk_length(Some(0)), CHANNEL_CHUNK_LENGTH);
+    assert_eq!(negotiated_chunk_length(Some(8192)), 8192);
+    assert_eq!(negotiated_chunk_length(Some(u32::MAX)), MAX_CHANNEL_CHUNK_LENGTH);
+}
+
+#[test]
+fn dechunkify_reassembles_chunkified_pdu() {
+    let pdu = (0..4000).map(|i| (i % 256) as u8).collect::<Vec<_>>();
+    let chunks = ChunkProcessor::chunkify(vec![SvcMessage::from(pdu.clone())], CHANNEL_CHUNK_LENGTH).unwrap();
+
+    let mut processor = ChunkProcessor::new();
+
+    let (last, intermediates) = chunks.split_last().unwrap();
+    for chunk in intermediates {
+        assert_eq!(processor.dechunkify(chunk.filled()).unwrap(), None);
+    }
+    assert_eq!(processor.dechunkify(last.filled()).unwrap(), Some(pdu));
+}
+
+#[test]
+fn dechunkify_rejects_pdu_larger_than_the_maximum_size() {
+    let mut processor = ChunkProcessor::with_max_pdu_size(1024);
+
+    processor
+        .dechunkify(&chunk(2048, CHANNEL_FLAG_FIRST, &[0; 1024]))
+        .unwrap_err();
+
+    // The processor is usable again afterwards.
+    let pdu = processor
+        .dechunkify(&chunk(4, CHANNEL_FLAG_FIRST | CHANNEL_FLAG_LAST, &[1, 2, 3, 4]))
+        .unwrap();
+    assert_eq!(pdu, Some(vec![1, 2, 3, 4]));
+}
+
+#[test]
+fn dechunkify_honors_the_decode_limits() {
+    let mut processor = ChunkProcessor::new();
+
+    let limits = DecodeLimits {
+        max_pdu_size: 1024,
+        ..DecodeLimits::default()
+    };
+
+    with_decode_options(DecodeOptions::default().with_limits(limits), || {
+        processor
+            .dechunkify(&chunk(2048, CHANNEL_FLAG_FIRST, &[0; 1024]))
+            .unwrap_err();
+    });
+}
+
+#[test]
+fn dechunkify_rejects_chunks_exceeding_the_announced_length() {
+    let mut processor = ChunkProcessor::new();
+
+    assert_eq!(
+        processor.dechunkify(&chunk(6, CHANNEL_FLAG_FIRST, &[0; 4])).unwrap(),
+        None
+    );
+    processor.dechunkify(&chunk(6, CHANNEL_FLAG_LAST, &[0; 4])).unwrap_err();
+}
+
+#[test]
+fn dechunkify_tolerates_missing_first_flag_in_lenient_mode() {
+    let mut processor = ChunkProcessor::new();
+
+    let pdu = processor.dechunkify(&chunk(2, CHANNEL_FLAG_LAST, &[1, 2])).unwrap();
+    assert_eq!(pdu, Some(vec![1, 2]));
+
+    with_decode_options(DecodeOptions::new(DecodeMode::Strict), || {
+        processor.dechunkify(&chunk(2, CHANNEL_FLAG_LAST, &[1, 2])).unwrap_err();
+    });
+}
+
+const CHANNEL_PACKET_COMPRESSED: u32 = 0x0020_0000;
+
+/// Stands for a bulk compressor, by reversing the chunks.
+#[derive(Debug)]
+struct ReversingCompressor;
+
+impl VcCompressor for ReversingCompressor {
+    fn compress(&mut self, src: &[u8], dst: &mut Vec<u8>) -> EncodeResult<ChannelFlags> {
+        dst.extend(src.iter().rev());
+        Ok(ChannelFlags::COMPRESSED)
+    }
+}
+
+impl VcDecompressor for ReversingCompressor {
+    fn decompress(&mut self, flags: ChannelFlags, src: &[u8], dst: &mut Vec<u8>) -> DecodeResult<()> {
+        if flags.contains(ChannelFlags::COMPRESSED) {
+            dst.extend(src.iter().rev());
+        } else {
+            dst.extend_from_slice(src);
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn compressed_chunks_round_trip() {
+    let pdu = (0..4000).map(|i| (i % 256) as u8).collect::<Vec<_>>();
+    let chunks = ChunkProcessor::chunkify_compressed(
+        vec![SvcMessage::from(pdu.clone())],
+        CHANNEL_CHUNK_LENGTH,
+        &mut ReversingCompressor,
+        &mut WriteBufPool::new(),
+    )
+    .unwrap();
+
+    for chunk in &chunks {
+        let (length, flags) = header(chunk.filled());
+        assert_eq!(length, 4000);
+        assert_ne!(flags & CHANNEL_PACKET_COMPRESSED, 0);
+    }
+    assert_eq!(chunks[0].filled()[8], pdu[CHANNEL_CHUNK_LENGTH - 1]);
+
+    let mut processor = ChunkProcessor::new();
+
+    let (last, intermediates) = chunks.split_last().unwrap();
+    for chunk in intermediates {
+        let pdu = processor
+            .dechunkify_with_decompressor(chunk.filled(), &mut ReversingCompressor)
+            .unwrap();
+        assert_eq!(pdu, None);
+    }
+    let reassembled = processor
+        .dechunkify_with_decompressor(last.filled(), &mut ReversingCompressor)
+        .unwrap();
+    assert_eq!(reassembled, Some(pdu));
+}
+
+#[test]
+fn messages_can_be_sent_without_compression() {
+    let chunks = ChunkProcessor::chunkify_compressed(
+        vec![SvcMessage::from(vec![1, 2, 3]).without_compression()],
+        CHANNEL_CHUNK_LENGTH,
+        &mut ReversingCompressor,
+        &mut WriteBufPool::new(),
+    )
+    .unwrap();
+
+    assert_eq!(chunks.len(), 1);
+    assert_eq!(
+        chunks[0].filled(),
+        chunk(3, CHANNEL_FLAG_FIRST | CHANNEL_FLAG_LAST, &[1, 2, 3])
+    );
+}
+
+#[test]
+fn dechunkify_rejects_compressed_chunks_without_decompressor() {
+    let mut processor = ChunkProcessor::new();
+
+    processor
+        .dechunkify(&chunk(
+            3,
+            CHANNEL_FLAG_FIRST | CHANNEL_FLAG_LAST | CHANNEL_PACKET_COMPRESSED,
+            &[3, 2, 1],
+        ))
+        .unwrap_err();
+}
+
+#[test]
+fn compression_condition_follows_the_channel_options() {
+    for condition in [
+        CompressionCondition::Never,
+        CompressionCondition::WhenRdpDataIsCompressed,
+        CompressionCondition::Always,
+    ] {
+        let options = condition.to_channel_options() | ChannelOptions::INITIALIZED;
+        assert_eq!(CompressionCondition::from_channel_options(options), condition);
+    }
+
+    assert!(!CompressionCondition::Never.is_compressed(true));
+    assert!(!CompressionCondition::WhenRdpDataIsCompressed.is_compressed(false));
+    assert!(CompressionCondition::WhenRdpDataIsCompressed.is_compressed(true));
+    assert!(CompressionCondition::Always.is_compressed(false));
+}
+
+#[test]
+fn compression_condition_can_be_overridden_per_channel() {
+    let mut channel = StaticVirtualChannel::new(EchoProcessor);
+    assert_eq!(make_channel_options(&channel), ChannelOptions::empty());
+
+    channel.set_compression_condition(CompressionCondition::WhenRdpDataIsCompressed);
+    assert_eq!(make_channel_options(&channel), ChannelOptions::COMPRESS_RDP);
+}
+
+#[test]
+fn wire_logging_is_disabled_by_default_and_can_be_toggled() {
+    assert_eq!(wire_logging(), WireLogging::Disabled);
+
+    set_wire_logging(WireLogging::Payload);
+    assert_eq!(wire_logging(), WireLogging::Payload);
+
+    let mut wire_log = ChannelWireLog::new();
+    wire_log.received("rdpsnd", &[1, 2, 3]);
+    wire_log.sent("rdpsnd", &vec![4u8, 5, 6]);
+
+    set_wire_logging(WireLogging::Disabled);
+    assert_eq!(wire_logging(), WireLogging::Disabled);
+}
+
+/// Sends a greeting when started, then echoes back the received PDUs.
+#[derive(Debug)]
+struct EchoProcessor;
+
+impl_as_any!(EchoProcessor);
+
+impl SvcProcessor for EchoProcessor {
+    fn channel_name(&self) -> ChannelName {
+        ChannelName::from_static(b"echo\0\0\0\0")
+    }
+
+    fn start(&mut self) -> PduResult<Vec<SvcMessage>> {
+        Ok(vec![SvcMessage::from(b"hello".to_vec())])
+    }
+
+    fn process(&mut self, payload: &[u8]) -> PduResult<Vec<SvcMessage>> {
+        Ok(vec![SvcMessage::from(payload.to_vec())])
+    }
+}
+
+#[test]
+fn mock_peer_follows_the_script() {
+    let mut channel = StaticVirtualChannel::new(EchoProcessor);
+    let large_pdu = vec![0xAB; 4000];
+
+    MockPeer::new()
+        .expect(&b"hello".to_vec())
+        .send(&vec![1, 2, 3])
+        .expect(&vec![1, 2, 3])
+        // Chunked over the mock transport.
+        .send(&large_pdu)
+        .expect_with("the large PDU", move |pdu| pdu == large_pdu)
+        .run(&mut SvcMockEndpoint::new(&mut channel))
+        .unwrap();
+}
+
+#[test]
+fn mock_peer_reports_deviations_from_the_script() {
+    let mut channel = StaticVirtualChannel::new(EchoProcessor);
+    let error = MockPeer::new()
+        .expect(&b"hello".to_vec())
+        .send(&vec![1, 2, 3])
+        .expect(&vec![3, 2, 1])
+        .run(&mut SvcMockEndpoint::new(&mut channel))
+        .unwrap_err();
+    assert!(matches!(error, MockError::UnexpectedPdu { step: 3, .. }));
+
+    let mut channel = StaticVirtualChannel::new(EchoProcessor);
+    let error = MockPeer::new()
+        .expect(&b"hello".to_vec())
+        .expect(&b"hello".to_vec())
+        .run(&mut SvcMockEndpoint::new(&mut channel))
+        .unwrap_err();
+    assert!(matches!(error, MockError::MissingPdu { step: 2, .. }));
+
+    let mut channel = StaticVirtualChannel::new(EchoProcessor);
+    let error = MockPeer::new()
+        .send(&vec![1, 2, 3])
+        .run(&mut SvcMockEndpoint::new(&mut channel))
+        .unwrap_err();
+    assert!(matches!(error, MockError::UnexpectedTrailingPdus { count: 2 }));
+}
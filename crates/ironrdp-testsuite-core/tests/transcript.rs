@@ -0,0 +1,176 @@
+use std::collections::VecDeque;
+
+use ironrdp_acceptor::{Acceptor, DesktopSize};
+use ironrdp_connector::{ClientConnector, Sequence, State as _};
+use ironrdp_core::WriteBuf;
+use ironrdp_pdu::nego::SecurityProtocol;
+use ironrdp_transcript::{ReplayErrorKind, Replayer, Role, Transcript, TranscriptRecorder};
+
+use crate::acceptor::split_frames;
+use crate::connector::config;
+
+const TRANSCRIPT: &str = "\
+# Connection Request and Connection Confirm
+C 0300002a25e00000000000436f6f6b69653a206d737473686173683d757365720d0a010008000b000000
+S 030000130ed000001234 000201080001000000
+";
+
+fn acceptor() -> Acceptor {
+    Acceptor::new(
+        SecurityProtocol::SSL,
+        DesktopSize {
+            width: 1024,
+            height: 768,
+        },
+        Vec::new(),
+    )
+}
+
+/// Connects a client connector to an acceptor, returning the transcripts recorded by the client and the server
+///
+/// As for a connection over the network, the frames sent in one step are recorded as a single entry, and the
+/// frames received are recorded one by one.
+fn record_connection() -> (Transcript, Transcript) {
+    let client_recorder = TranscriptRecorder::new(Role::Client);
+    let server_recorder = TranscriptRecorder::new(Role::Server);
+
+    let mut client = ClientConnector::new(config());
+    let mut server = acceptor();
+
+    let mut to_client = VecDeque::new();
+    let mut to_server = VecDeque::new();
+
+    while !client.state().is_terminal() || !server.state().is_terminal() {
+        let client_progress = advance(&mut client, &client_recorder, &mut to_client, &mut to_server);
+        let server_progress = advance(&mut server, &server_recorder, &mut to_server, &mut to_client);
+
+        assert!(client_progress || server_progress, "the connection is stuck");
+    }
+
+    (client_recorder.transcript(), server_recorder.transcript())
+}
+
+/// Performs a single step of `sequence`, unless it is waiting for a frame not sent yet
+fn advance(
+    sequence: &mut dyn Sequence,
+    recorder: &TranscriptRecorder,
+    received: &mut VecDeque<Vec<u8>>,
+    sent: &mut VecDeque<Vec<u8>>,
+) -> bool {
+    if sequence.state().is_terminal() {
+        return false;
+    }
+
+    let mut buf = WriteBuf::new();
+
+    let written = match sequence.next_pdu_hint() {
+        Some(hint) => {
+            let Some(frame) = received.pop_front() else {
+                return false;
+            };
+            recorder.record_received(&frame);
+
+            if !hint.find_size(&frame).unwrap().is_some_and(|(matched, _)| matched) {
+                // Same as when reading from the network: the unexpected PDU is lost.
+                return true;
+            }
+
+            sequence.step(&frame, &mut buf).unwrap()
+        }
+        None => sequence.step_no_input(&mut buf).unwrap(),
+    };
+
+    if let Some(length) = written.size() {
+        let frames = &buf.filled()[..length];
+        recorder.record_sent(frames);
+        sent.extend(split_frames(frames));
+    }
+
+    true
+}
+
+#[test]
+fn client_connector_replays_recorded_connection() {
+    let (transcript, _) = record_connection();
+    assert!(!transcript.is_empty());
+
+    let mut replayer = Replayer::new(&transcript, Role::Client);
+    let mut connector = ClientConnector::new(config());
+
+    replayer.run_to_completion(&mut connector).unwrap();
+
+    assert!(replayer.is_finished());
+}
+
+#[test]
+fn acceptor_replays_recorded_connection() {
+    let (_, transcript) = record_connection();
+    assert!(!transcript.is_empty());
+
+    let mut replayer = Replayer::new(&transcript, Role::Server);
+    let mut acceptor = acceptor();
+
+    replayer.run_to_completion(&mut acceptor).unwrap();
+
+    assert!(replayer.is_finished());
+    assert!(acceptor.get_result().is_some());
+}
+
+#[test]
+fn mismatched_sent_frame_is_reported() {
+    const INDEX: usize = 2;
+    const OFFSET: usize = 10;
+
+    let (recorded, _) = record_connection();
+
+    // The Connect Initial PDU, sent after the Connection Request and the Connection Confirm.
+    assert_eq!(recorded.entries()[INDEX].sender, Role::Client);
+
+    let mut transcript = Transcript::new();
+    for (index, entry) in recorded.entries().iter().enumerate() {
+        let mut frame = entry.frame.clone();
+        if index == INDEX {
+            frame[OFFSET] ^= 0xff;
+        }
+        transcript.push(entry.sender, frame);
+    }
+
+    let mut replayer = Replayer::new(&transcript, Role::Client);
+    let error = replayer
+        .run_to_completion(&mut ClientConnector::new(config()))
+        .unwrap_err();
+
+    assert!(matches!(
+        error.kind(),
+        ReplayErrorKind::Mismatch {
+            index: INDEX,
+            offset: OFFSET
+        }
+    ));
+    assert_eq!(replayer.position(), INDEX);
+}
+
+#[test]
+fn transcript_text_round_trip() {
+    let transcript: Transcript = TRANSCRIPT.parse().unwrap();
+
+    assert_eq!(transcript.len(), 2);
+    assert_eq!(transcript.entries()[0].sender, Role::Client);
+    assert_eq!(transcript.entries()[1].sender, Role::Server);
+    assert_eq!(
+        transcript.entries()[1].frame,
+        hex::decode("030000130ed000001234000201080001000000").unwrap()
+    );
+
+    let (recorded, _) = record_connection();
+    for transcript in [transcript, recorded] {
+        assert_eq!(transcript.to_string().parse::<Transcript>().unwrap(), transcript);
+    }
+}
+
+#[test]
+fn invalid_transcript_line_is_reported() {
+    let error = "# comment\nC 0300\nX 0300\n".parse::<Transcript>().unwrap_err();
+
+    assert_eq!(error.line_number, 3);
+}
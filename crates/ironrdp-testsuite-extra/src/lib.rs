@@ -0,0 +1 @@
+//! Test fixtures shared by the extra tier integration tests
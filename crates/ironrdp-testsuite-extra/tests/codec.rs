@@ -0,0 +1,96 @@
+use std::io;
+
+use bytes::BytesMut;
+use ironrdp_pdu::Action;
+use ironrdp_tokio::RdpCodec;
+use rstest::rstest;
+use tokio_util::codec::Decoder as _;
+
+/// TPKT header followed by an X.224 Data TPDU and 4 bytes of user data
+const TPKT_PDU: [u8; 11] = [0x03, 0x00, 0x00, 0x0B, 0x02, 0xF0, 0x80, 0xDE, 0xAD, 0xBE, 0xEF];
+
+/// Fast-path header with a 1-byte length, followed by 3 bytes of data
+const FAST_PATH_PDU: [u8; 5] = [0x00, 0x05, 0x01, 0x02, 0x03];
+
+/// Fast-path PDU of 300 bytes, with a 2-byte length
+fn long_fast_path_pdu() -> Vec<u8> {
+    let mut pdu = vec![0x00, 0x81, 0x2C];
+    pdu.resize(300, 0xAB);
+    pdu
+}
+
+#[test]
+fn partial_tpkt() {
+    let mut codec = RdpCodec::new();
+    let mut src = BytesMut::new();
+
+    for chunk in [&TPKT_PDU[..1], &TPKT_PDU[1..4], &TPKT_PDU[4..10]] {
+        src.extend_from_slice(chunk);
+        assert!(codec.decode(&mut src).unwrap().is_none());
+    }
+    assert!(src.capacity() >= TPKT_PDU.len());
+
+    src.extend_from_slice(&TPKT_PDU[10..]);
+    let (action, pdu) = codec.decode(&mut src).unwrap().unwrap();
+
+    assert_eq!(action, Action::X224);
+    assert_eq!(pdu, TPKT_PDU[..]);
+    assert!(src.is_empty());
+}
+
+#[test]
+fn partial_fast_path_with_2_byte_length() {
+    let expected = long_fast_path_pdu();
+    let mut codec = RdpCodec::new();
+    let mut src = BytesMut::new();
+
+    // The length is only known once both of its bytes are received.
+    for chunk in [&expected[..1], &expected[1..2], &expected[2..3], &expected[3..299]] {
+        src.extend_from_slice(chunk);
+        assert!(codec.decode(&mut src).unwrap().is_none());
+    }
+    assert!(src.capacity() >= expected.len());
+
+    src.extend_from_slice(&expected[299..]);
+    let (action, pdu) = codec.decode(&mut src).unwrap().unwrap();
+
+    assert_eq!(action, Action::FastPath);
+    assert_eq!(pdu, expected);
+    assert!(src.is_empty());
+}
+
+#[test]
+fn two_pdus_in_one_buffer() {
+    let mut codec = RdpCodec::new();
+    let mut src = BytesMut::new();
+    src.extend_from_slice(&TPKT_PDU);
+    src.extend_from_slice(&FAST_PATH_PDU);
+    src.extend_from_slice(&TPKT_PDU[..2]);
+
+    let (action, pdu) = codec.decode(&mut src).unwrap().unwrap();
+    assert_eq!(action, Action::X224);
+    assert_eq!(pdu, TPKT_PDU[..]);
+
+    let (action, pdu) = codec.decode(&mut src).unwrap().unwrap();
+    assert_eq!(action, Action::FastPath);
+    assert_eq!(pdu, FAST_PATH_PDU[..]);
+
+    // The beginning of the next PDU is kept for the next call.
+    assert!(codec.decode(&mut src).unwrap().is_none());
+    assert_eq!(src, TPKT_PDU[..2]);
+}
+
+#[rstest]
+// Unknown action in the fast-path output header.
+#[case(&[0x01, 0x05, 0x00, 0x00, 0x00])]
+#[case(&[0x02, 0x05, 0x00, 0x00, 0x00])]
+// Zero-length fast-path PDU.
+#[case(&[0x00, 0x00])]
+#[case(&[0x00, 0x80, 0x00])]
+fn invalid_data(#[case] bytes: &[u8]) {
+    let mut src = BytesMut::from(bytes);
+
+    let error = RdpCodec::new().decode(&mut src).unwrap_err();
+
+    assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+}
@@ -0,0 +1,12 @@
+#![allow(unused_crate_dependencies)] // false positives because there is both a library and a binary
+
+//! Integration Tests (IT)
+//!
+//! Integration tests for the extra tier crates, all contained in this single crate and organized in modules,
+//! as in `ironrdp-testsuite-core`.
+//!
+//! Keeping this suite separate allows the core test suite to build without any library from the extra tier.
+
+mod codec;
+mod poll;
+mod server;
@@ -0,0 +1,217 @@
+use std::time::{Duration, Instant};
+
+use ironrdp_acceptor::Acceptor;
+use ironrdp_connector::{ClientConnector, Config, Credentials, DesktopSize, Sequence as _, ServerName, State as _};
+use ironrdp_core::{encode_vec, WriteBuf};
+use ironrdp_pdu::gcc::KeyboardType;
+use ironrdp_pdu::mcs::{DisconnectProviderUltimatum, DisconnectReason};
+use ironrdp_pdu::nego::SecurityProtocol;
+use ironrdp_pdu::rdp::capability_sets::MajorPlatformType;
+use ironrdp_pdu::rdp::client_info::PerformanceFlags;
+use ironrdp_pdu::x224::X224;
+use ironrdp_poll::{Driver, DriverErrorKind, DriverEvent};
+use ironrdp_session::GracefulDisconnectReason;
+
+const DESKTOP_SIZE: DesktopSize = DesktopSize {
+    width: 1024,
+    height: 768,
+};
+
+/// Size of the reads and writes on the in-memory transport, small enough to split most PDUs
+const CHUNK_SIZE: usize = 7;
+
+fn config() -> Config {
+    Config {
+        desktop_size: DESKTOP_SIZE,
+        desktop_scale_factor: 0,
+        enable_tls: true,
+        enable_credssp: false,
+        credentials: Credentials::UsernamePassword {
+            username: "user".to_owned(),
+            password: "password".to_owned(),
+        },
+        domain: None,
+        client_build: 0,
+        client_name: "client".to_owned(),
+        keyboard_type: KeyboardType::IbmEnhanced,
+        keyboard_subtype: 0,
+        keyboard_functional_keys_count: 12,
+        keyboard_layout: 0,
+        ime_file_name: String::new(),
+        bitmap: None,
+        dig_product_id: String::new(),
+        client_dir: String::new(),
+        platform: MajorPlatformType::UNSPECIFIED,
+        autologon: false,
+        restricted_admin: false,
+        auto_reconnect: None,
+        decode_mode: Default::default(),
+        decode_limits: Default::default(),
+        monitors: Vec::new(),
+        persistent_bitmap_cache: Vec::new(),
+        no_server_pointer: false,
+        pointer_software_rendering: false,
+        performance_flags: PerformanceFlags::default(),
+    }
+}
+
+fn driver() -> Driver {
+    Driver::new(ClientConnector::new(config()), ServerName::new("server"))
+}
+
+/// In-memory transport between the [`Driver`] and an [`Acceptor`]
+///
+/// The security upgrade is a no-op: the bytes are carried as is.
+struct Transport {
+    acceptor: Acceptor,
+    to_client: Vec<u8>,
+    to_server: Vec<u8>,
+}
+
+impl Transport {
+    fn new() -> Self {
+        Self {
+            acceptor: Acceptor::new(SecurityProtocol::SSL, DESKTOP_SIZE, Vec::new()),
+            to_client: Vec::new(),
+            to_server: Vec::new(),
+        }
+    }
+
+    /// Carries the bytes in both directions, returning whether any progress was made
+    fn pump(&mut self, driver: &mut Driver) -> bool {
+        let mut progress = false;
+
+        while driver.wants_write() {
+            let mut chunk = [0; CHUNK_SIZE];
+            let len = driver.handle_writable(&mut chunk);
+            self.to_server.extend_from_slice(&chunk[..len]);
+            progress = true;
+        }
+
+        progress |= self.step_acceptor();
+
+        if driver.wants_read() && !self.to_client.is_empty() {
+            for chunk in self.to_client.chunks(CHUNK_SIZE) {
+                driver.handle_readable(chunk).unwrap();
+            }
+            self.to_client.clear();
+            progress = true;
+        }
+
+        while let Some(event) = driver.poll_event() {
+            match event {
+                DriverEvent::SecurityUpgradeRequired => driver.mark_security_upgrade_as_done(Vec::new()).unwrap(),
+                DriverEvent::Connected { desktop_size } => assert_eq!(desktop_size, DESKTOP_SIZE),
+                event => panic!("unexpected event: {event:?}"),
+            }
+            progress = true;
+        }
+
+        progress
+    }
+
+    fn step_acceptor(&mut self) -> bool {
+        let mut progress = false;
+
+        while !self.acceptor.state().is_terminal() {
+            let mut buf = WriteBuf::new();
+
+            let written = match self.acceptor.next_pdu_hint() {
+                Some(hint) => {
+                    let Some((matched, length)) = hint.find_size(&self.to_server).unwrap() else {
+                        break;
+                    };
+                    if length > self.to_server.len() {
+                        break;
+                    }
+                    let frame: Vec<u8> = self.to_server.drain(..length).collect();
+                    assert!(matched, "unexpected PDU");
+
+                    self.acceptor.step(&frame, &mut buf).unwrap()
+                }
+                None => self.acceptor.step_no_input(&mut buf).unwrap(),
+            };
+
+            if let Some(length) = written.size() {
+                self.to_client.extend_from_slice(&buf.filled()[..length]);
+            }
+            progress = true;
+        }
+
+        progress
+    }
+
+    fn run_until_connected(&mut self, driver: &mut Driver) {
+        driver.start().unwrap();
+
+        while !driver.is_active() || !self.acceptor.state().is_terminal() {
+            assert!(self.pump(driver), "the connection is stuck");
+        }
+    }
+}
+
+#[test]
+fn connection_sequence() {
+    let mut driver = driver();
+    let mut transport = Transport::new();
+
+    transport.run_until_connected(&mut driver);
+
+    assert!(transport.acceptor.get_result().is_some());
+    assert!(!driver.wants_write());
+    assert_eq!(driver.next_timeout(), None);
+
+    let image = driver.image().unwrap();
+    assert_eq!(
+        (image.width(), image.height()),
+        (DESKTOP_SIZE.width, DESKTOP_SIZE.height)
+    );
+}
+
+#[test]
+fn server_disconnection() {
+    let mut driver = driver();
+    let mut transport = Transport::new();
+    transport.run_until_connected(&mut driver);
+
+    let ultimatum = DisconnectProviderUltimatum::from_reason(DisconnectReason::ProviderInitiated);
+    driver.handle_readable(&encode_vec(&X224(ultimatum)).unwrap()).unwrap();
+
+    assert!(matches!(
+        driver.poll_event(),
+        Some(DriverEvent::Terminated(GracefulDisconnectReason::ServerInitiated))
+    ));
+    assert!(driver.is_terminated());
+    driver.handle_readable(&[0x03]).unwrap_err();
+}
+
+#[test]
+fn invalid_data_terminates_the_driver() {
+    let mut driver = driver();
+    let mut transport = Transport::new();
+    transport.run_until_connected(&mut driver);
+
+    // Unknown action in the fast-path output header.
+    driver.handle_readable(&[0x01, 0x05, 0x00, 0x00, 0x00]).unwrap_err();
+
+    assert!(driver.is_terminated());
+    assert!(driver.image().is_none());
+}
+
+#[test]
+fn connect_deadline() {
+    let deadline = Instant::now() + Duration::from_secs(10);
+    let mut driver = driver().with_connect_deadline(deadline);
+    driver.start().unwrap();
+
+    assert!(driver.wants_write());
+    assert_eq!(driver.next_timeout(), Some(deadline));
+
+    driver.handle_timeout(deadline - Duration::from_secs(1)).unwrap();
+    assert!(!driver.is_terminated());
+
+    let error = driver.handle_timeout(deadline).unwrap_err();
+    assert!(matches!(error.kind(), DriverErrorKind::Timeout));
+    assert!(driver.is_terminated());
+    assert_eq!(driver.next_timeout(), None);
+}
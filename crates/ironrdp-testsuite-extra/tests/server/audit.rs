@@ -0,0 +1,77 @@
+use ironrdp_server::ChannelFilter;
+use rstest::rstest;
+
+#[test]
+fn allow_all() {
+    let filter = ChannelFilter::allow_all();
+
+    assert!(filter.is_allowed("cliprdr"));
+    assert!(filter.is_allowed("Microsoft::Windows::RDS::DisplayControl"));
+    assert!(filter.is_static_channel_allowed("rdpsnd"));
+}
+
+#[test]
+fn allow_only() {
+    let filter = ChannelFilter::allow_only(["cliprdr", "Microsoft::Windows::RDS::DisplayControl"]);
+
+    assert!(filter.is_allowed("cliprdr"));
+    assert!(filter.is_allowed("Microsoft::Windows::RDS::DisplayControl"));
+    assert!(!filter.is_allowed("rdpdr"));
+    assert!(!filter.is_static_channel_allowed("rdpdr"));
+}
+
+#[test]
+fn deny_takes_precedence_over_allow() {
+    let filter = ChannelFilter::allow_only(["cliprdr", "rdpdr"]).deny("cliprdr");
+
+    assert!(!filter.is_allowed("cliprdr"));
+    assert!(!filter.is_static_channel_allowed("cliprdr"));
+    assert!(filter.is_allowed("rdpdr"));
+    assert!(!filter.is_allowed("rdpsnd"));
+}
+
+#[test]
+fn deny_on_top_of_allow_all() {
+    let filter = ChannelFilter::allow_all().deny("rdpdr");
+
+    assert!(!filter.is_allowed("rdpdr"));
+    assert!(!filter.is_static_channel_allowed("rdpdr"));
+    assert!(filter.is_allowed("cliprdr"));
+}
+
+#[rstest]
+#[case("cliprdr")]
+#[case("CLIPRDR")]
+#[case("ClipRdr")]
+fn names_are_case_insensitive(#[case] name: &str) {
+    assert!(ChannelFilter::allow_only(["CliprDR"]).is_allowed(name));
+    assert!(ChannelFilter::allow_only(["CliprDR"]).is_static_channel_allowed(name));
+    assert!(!ChannelFilter::allow_all().deny("CliprDR").is_allowed(name));
+    assert!(!ChannelFilter::allow_all()
+        .deny("CliprDR")
+        .is_static_channel_allowed(name));
+}
+
+#[test]
+fn static_names_are_truncated() {
+    // Static channel names are at most 7 bytes long, followed by the null terminator.
+    let filter = ChannelFilter::allow_only(["drdynvc_extended"]);
+    assert!(filter.is_static_channel_allowed("drdynvc"));
+    assert!(filter.is_static_channel_allowed("DRDYNVC"));
+    assert!(!filter.is_static_channel_allowed("drdynv"));
+    assert!(!filter.is_allowed("drdynvc"));
+
+    let filter = ChannelFilter::allow_all().deny("RDPSND_LONG");
+    assert!(!filter.is_static_channel_allowed("rdpsnd_"));
+    assert!(filter.is_static_channel_allowed("rdpsnd"));
+    assert!(filter.is_allowed("rdpsnd_"));
+}
+
+#[test]
+fn short_static_names_are_matched_exactly() {
+    let filter = ChannelFilter::allow_only(["rdpdr"]);
+
+    assert!(filter.is_static_channel_allowed("rdpdr"));
+    assert!(!filter.is_static_channel_allowed("rdpd"));
+    assert!(!filter.is_static_channel_allowed("rdpdr2"));
+}
@@ -0,0 +1,191 @@
+use std::time::{Duration, Instant};
+
+use ironrdp_pdu::rdp::autodetect::{AutoDetectPhase, AutoDetectRequest, AutoDetectResponse, NetworkCharacteristics};
+use ironrdp_server::{AutoDetector, EncodingQuality, NetworkEstimate, QualityController};
+use rstest::rstest;
+
+const MS: Duration = Duration::from_millis(1);
+
+fn rtt_response(request: &AutoDetectRequest) -> AutoDetectResponse {
+    assert!(matches!(request, AutoDetectRequest::RttRequest { .. }));
+
+    AutoDetectResponse::RttResponse {
+        sequence_number: request.sequence_number(),
+    }
+}
+
+fn bandwidth_results(sequence_number: u16, time_delta: u32, byte_count: u32) -> AutoDetectResponse {
+    AutoDetectResponse::BandwidthMeasureResults {
+        sequence_number,
+        phase: AutoDetectPhase::Continuous,
+        time_delta,
+        byte_count,
+    }
+}
+
+/// Runs a full bandwidth measure, returning the sequence number of the Stop request
+fn measure(detector: &mut AutoDetector, now: Instant) -> u16 {
+    let requests = detector.next_requests(now);
+    assert!(matches!(requests[1], AutoDetectRequest::BandwidthMeasureStart { .. }));
+
+    let requests = detector.next_requests(now);
+    let AutoDetectRequest::BandwidthMeasureStop { sequence_number, .. } = requests[1] else {
+        panic!("expected a bandwidth measure stop: {requests:?}");
+    };
+
+    sequence_number
+}
+
+#[test]
+fn rtt_smoothing() {
+    let mut detector = AutoDetector::new(None);
+    let t0 = Instant::now();
+
+    let requests = detector.next_requests(t0);
+    detector.handle_response(rtt_response(&requests[0]), t0 + 80 * MS);
+    assert_eq!(detector.estimate().base_rtt, Some(80 * MS));
+    assert_eq!(detector.estimate().average_rtt, Some(80 * MS));
+
+    let t1 = t0 + 1000 * MS;
+    let requests = detector.next_requests(t1);
+    detector.handle_response(rtt_response(&requests[0]), t1 + 160 * MS);
+    assert_eq!(detector.estimate().base_rtt, Some(80 * MS));
+    // (80 * 7 + 160) / 8
+    assert_eq!(detector.estimate().average_rtt, Some(90 * MS));
+
+    let t2 = t1 + 1000 * MS;
+    let requests = detector.next_requests(t2);
+    detector.handle_response(rtt_response(&requests[0]), t2 + 40 * MS);
+    assert_eq!(detector.estimate().base_rtt, Some(40 * MS));
+    // (90 * 7 + 40) / 8
+    assert_eq!(detector.estimate().average_rtt, Some(Duration::from_micros(83_750)));
+}
+
+#[test]
+fn unexpected_rtt_response_is_ignored() {
+    let mut detector = AutoDetector::new(None);
+    let t0 = Instant::now();
+
+    let requests = detector.next_requests(t0);
+    let response = rtt_response(&requests[0]);
+    detector.handle_response(response.clone(), t0 + 80 * MS);
+
+    // Answered twice.
+    detector.handle_response(response, t0 + 200 * MS);
+    assert_eq!(detector.estimate().average_rtt, Some(80 * MS));
+}
+
+#[test]
+fn bandwidth_formula() {
+    let mut detector = AutoDetector::new(None);
+    let now = Instant::now();
+
+    let stop = measure(&mut detector, now);
+    assert!(detector.is_waiting());
+
+    // 125 000 bytes in 100 ms: 1250 bytes/ms, 10 000 kbit/s.
+    let result = detector.handle_response(bandwidth_results(stop, 100, 125_000), now);
+    let Some(AutoDetectRequest::NetworkCharacteristicsResult { characteristics, .. }) = result else {
+        panic!("expected a network characteristics result: {result:?}");
+    };
+    assert_eq!(
+        characteristics,
+        NetworkCharacteristics {
+            base_rtt: None,
+            bandwidth: Some(10_000),
+            average_rtt: 0,
+        }
+    );
+    assert_eq!(detector.estimate().bandwidth, Some(10_000));
+
+    // 20 000 kbit/s, smoothed with the previous estimate: (10 000 * 3 + 20 000) / 4
+    let stop = measure(&mut detector, now);
+    detector.handle_response(bandwidth_results(stop, 100, 250_000), now);
+    assert_eq!(detector.estimate().bandwidth, Some(12_500));
+}
+
+#[test]
+fn bandwidth_smoothed_with_initial_estimate() {
+    let mut detector = AutoDetector::new(Some(2_000));
+    let now = Instant::now();
+
+    let stop = measure(&mut detector, now);
+    detector.handle_response(bandwidth_results(stop, 10, 12_500), now);
+
+    // (2 000 * 3 + 10 000) / 4
+    assert_eq!(detector.estimate().bandwidth, Some(4_000));
+}
+
+#[test]
+fn bandwidth_results_of_another_measure_are_ignored() {
+    let mut detector = AutoDetector::new(None);
+    let now = Instant::now();
+
+    // Not stopped yet.
+    let requests = detector.next_requests(now);
+    let start = requests[1].sequence_number();
+    assert!(detector
+        .handle_response(bandwidth_results(start, 100, 125_000), now)
+        .is_none());
+
+    let requests = detector.next_requests(now);
+    let stop = requests[1].sequence_number();
+
+    // Unknown sequence number.
+    assert!(detector
+        .handle_response(bandwidth_results(stop.wrapping_add(1), 100, 125_000), now)
+        .is_none());
+    assert_eq!(detector.estimate().bandwidth, None);
+
+    assert!(detector
+        .handle_response(bandwidth_results(stop, 100, 125_000), now)
+        .is_some());
+    assert_eq!(detector.estimate().bandwidth, Some(10_000));
+
+    // Already answered.
+    assert!(detector
+        .handle_response(bandwidth_results(stop, 100, 500_000), now)
+        .is_none());
+    assert_eq!(detector.estimate().bandwidth, Some(10_000));
+}
+
+#[test]
+fn bandwidth_results_of_a_replaced_measure_are_ignored() {
+    let mut detector = AutoDetector::new(None);
+    let now = Instant::now();
+
+    let stale = measure(&mut detector, now);
+    // No results, the next period starts a new measure.
+    let requests = detector.next_requests(now);
+    assert!(matches!(requests[1], AutoDetectRequest::BandwidthMeasureStart { .. }));
+
+    assert!(detector
+        .handle_response(bandwidth_results(stale, 100, 125_000), now)
+        .is_none());
+    assert_eq!(detector.estimate().bandwidth, None);
+}
+
+fn estimate(bandwidth: Option<u32>, average_rtt_ms: u64) -> NetworkEstimate {
+    NetworkEstimate {
+        bandwidth,
+        base_rtt: None,
+        average_rtt: Some(Duration::from_millis(average_rtt_ms)),
+    }
+}
+
+#[rstest]
+#[case(estimate(None, 10), None)]
+#[case(estimate(Some(20_000), 10), Some(EncodingQuality::High))]
+#[case(estimate(Some(19_999), 10), Some(EncodingQuality::Medium))]
+#[case(estimate(Some(5_000), 10), Some(EncodingQuality::Medium))]
+#[case(estimate(Some(4_999), 10), Some(EncodingQuality::Low))]
+#[case(estimate(Some(1_000), 10), Some(EncodingQuality::Low))]
+#[case(estimate(Some(999), 10), Some(EncodingQuality::Lowest))]
+// The quality is lowered by one level above the RTT threshold.
+#[case(estimate(Some(20_000), 150), Some(EncodingQuality::High))]
+#[case(estimate(Some(20_000), 151), Some(EncodingQuality::Medium))]
+#[case(estimate(Some(1_000), 151), Some(EncodingQuality::Lowest))]
+#[case(estimate(Some(999), 151), Some(EncodingQuality::Lowest))]
+fn quality_thresholds(#[case] estimate: NetworkEstimate, #[case] expected: Option<EncodingQuality>) {
+    assert_eq!(QualityController::default().quality(&estimate), expected);
+}
@@ -0,0 +1,279 @@
+use std::borrow::Cow;
+
+use ironrdp_async::{CaptureEndpoint, CaptureSink, PcapngCapture};
+use ironrdp_core::encode_vec;
+use ironrdp_pdu::gcc::{ChannelDef, ChannelName, ChannelOptions, ClientNetworkData};
+use ironrdp_pdu::mcs::{McsMessage, SendDataIndication, SendDataRequest};
+use ironrdp_pdu::x224::{X224Data, X224};
+use ironrdp_server::{CaptureDirection, CaptureSplitter, ChannelFilter};
+use ironrdp_testsuite_core::mcs::{CONNECT_INITIAL, CONNECT_RESPONSE};
+
+const IO_CHANNEL_ID: u16 = 1003;
+const RDPDR_CHANNEL_ID: u16 = 1004;
+const CLIPRDR_CHANNEL_ID: u16 = 1005;
+
+const FAST_PATH_FRAME: [u8; 4] = [0x00, 0x04, 0xAB, 0xCD];
+
+#[derive(Default)]
+struct Frames {
+    frames: Vec<(CaptureDirection, Vec<u8>)>,
+    channels: Vec<(u16, String)>,
+}
+
+impl CaptureSink for Frames {
+    fn received(&mut self, frame: &[u8]) -> std::io::Result<()> {
+        self.frames.push((CaptureDirection::ClientToServer, frame.to_vec()));
+        Ok(())
+    }
+
+    fn sent(&mut self, frame: &[u8]) -> std::io::Result<()> {
+        self.frames.push((CaptureDirection::ServerToClient, frame.to_vec()));
+        Ok(())
+    }
+
+    fn register_channel(&mut self, channel_id: u16, name: &str) {
+        self.channels.push((channel_id, name.to_owned()));
+    }
+}
+
+fn x224_data(data: Vec<u8>) -> Vec<u8> {
+    encode_vec(&X224(X224Data { data: Cow::Owned(data) })).unwrap()
+}
+
+fn connect_initial() -> Vec<u8> {
+    let mut connect_initial = CONNECT_INITIAL.clone();
+    connect_initial.conference_create_request.gcc_blocks.network = Some(ClientNetworkData {
+        channels: ["rdpdr", "cliprdr"]
+            .into_iter()
+            .map(|name| ChannelDef {
+                name: ChannelName::from_utf8(name).unwrap(),
+                options: ChannelOptions::INITIALIZED,
+            })
+            .collect(),
+    });
+
+    x224_data(encode_vec(&connect_initial).unwrap())
+}
+
+fn connect_response() -> Vec<u8> {
+    let mut connect_response = CONNECT_RESPONSE.clone();
+    let network = &mut connect_response.conference_create_response.gcc_blocks.network;
+    network.io_channel = IO_CHANNEL_ID;
+    network.channel_ids = vec![RDPDR_CHANNEL_ID, CLIPRDR_CHANNEL_ID];
+
+    x224_data(encode_vec(&connect_response).unwrap())
+}
+
+fn send_data_request(channel_id: u16, user_data: &[u8]) -> Vec<u8> {
+    encode_vec(&X224(McsMessage::SendDataRequest(SendDataRequest {
+        initiator_id: 1007,
+        channel_id,
+        user_data: Cow::Borrowed(user_data),
+    })))
+    .unwrap()
+}
+
+fn send_data_indication(channel_id: u16, user_data: &[u8]) -> Vec<u8> {
+    encode_vec(&X224(McsMessage::SendDataIndication(SendDataIndication {
+        initiator_id: 1007,
+        channel_id,
+        user_data: Cow::Borrowed(user_data),
+    })))
+    .unwrap()
+}
+
+/// Frames of a session, in the order they are exchanged
+fn session() -> Vec<(CaptureDirection, Vec<u8>)> {
+    vec![
+        (CaptureDirection::ClientToServer, connect_initial()),
+        (CaptureDirection::ServerToClient, connect_response()),
+        (
+            CaptureDirection::ClientToServer,
+            send_data_request(IO_CHANNEL_ID, b"io"),
+        ),
+        (
+            CaptureDirection::ClientToServer,
+            send_data_request(RDPDR_CHANNEL_ID, b"device"),
+        ),
+        (
+            CaptureDirection::ServerToClient,
+            send_data_indication(RDPDR_CHANNEL_ID, b"announce"),
+        ),
+        (
+            CaptureDirection::ClientToServer,
+            send_data_request(CLIPRDR_CHANNEL_ID, b"clipboard"),
+        ),
+        (CaptureDirection::ServerToClient, FAST_PATH_FRAME.to_vec()),
+    ]
+}
+
+/// Feeds the frames in small chunks, so that they are split across several reads
+fn feed(splitter: &mut CaptureSplitter, frames: &[(CaptureDirection, Vec<u8>)], sink: &mut dyn CaptureSink) {
+    for (direction, frame) in frames {
+        for chunk in frame.chunks(7) {
+            splitter.feed(*direction, chunk, sink).unwrap();
+        }
+    }
+}
+
+/// Packet of a pcapng capture
+struct Packet {
+    from_client: bool,
+    payload: Vec<u8>,
+    comment: String,
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> usize {
+    usize::try_from(u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())).unwrap()
+}
+
+fn read_u16(bytes: &[u8], offset: usize) -> usize {
+    usize::from(u16::from_le_bytes(bytes[offset..offset + 2].try_into().unwrap()))
+}
+
+/// Reads the Enhanced Packet Blocks of a pcapng capture, stripping the synthesized IPv4 and TCP headers
+fn read_pcapng(mut capture: &[u8]) -> Vec<Packet> {
+    const ENHANCED_PACKET_BLOCK: usize = 6;
+    const IP_AND_TCP_HEADERS_LEN: usize = 40;
+    const OPT_COMMENT: usize = 1;
+
+    let mut packets = Vec::new();
+
+    while !capture.is_empty() {
+        let block_type = read_u32(capture, 0);
+        let block_len = read_u32(capture, 4);
+        let body = &capture[8..block_len - 4];
+
+        if block_type == ENHANCED_PACKET_BLOCK {
+            let captured_len = read_u32(body, 12);
+            let packet = &body[20..20 + captured_len];
+            let source_port = u16::from_be_bytes([packet[20], packet[21]]);
+
+            let mut options = &body[(20 + captured_len).next_multiple_of(4)..];
+            let mut comment = String::new();
+            while read_u16(options, 0) != 0 {
+                let option_len = read_u16(options, 2);
+                if read_u16(options, 0) == OPT_COMMENT {
+                    comment = String::from_utf8(options[4..4 + option_len].to_vec()).unwrap();
+                }
+                options = &options[(4 + option_len).next_multiple_of(4)..];
+            }
+
+            packets.push(Packet {
+                from_client: source_port != 3389,
+                payload: packet[IP_AND_TCP_HEADERS_LEN..].to_vec(),
+                comment,
+            });
+        }
+
+        capture = &capture[block_len..];
+    }
+
+    packets
+}
+
+#[test]
+fn pcapng_round_trip() {
+    let frames = session();
+
+    let mut capture = PcapngCapture::new(Vec::new(), CaptureEndpoint::Server).unwrap();
+    let mut splitter = CaptureSplitter::new(ChannelFilter::allow_all());
+    feed(&mut splitter, &frames, &mut capture);
+
+    let packets = read_pcapng(&capture.into_inner());
+
+    assert_eq!(packets.len(), frames.len());
+    for (packet, (direction, frame)) in packets.iter().zip(&frames) {
+        assert_eq!(packet.from_client, *direction == CaptureDirection::ClientToServer);
+        assert_eq!(&packet.payload, frame);
+    }
+
+    // The channel names learned from the MCS connection sequence annotate the packets.
+    assert!(
+        packets[3].comment.ends_with("MCS channel 1004 (rdpdr)"),
+        "{}",
+        packets[3].comment
+    );
+    assert!(packets[6].comment.ends_with("fast-path"), "{}", packets[6].comment);
+}
+
+#[test]
+fn partial_frame_is_kept_until_complete() {
+    let frame = send_data_request(IO_CHANNEL_ID, b"io");
+    let (head, tail) = frame.split_at(5);
+
+    let mut sink = Frames::default();
+    let mut splitter = CaptureSplitter::new(ChannelFilter::allow_all());
+
+    splitter
+        .feed(CaptureDirection::ClientToServer, head, &mut sink)
+        .unwrap();
+    // The other direction is buffered separately.
+    splitter
+        .feed(CaptureDirection::ServerToClient, &FAST_PATH_FRAME, &mut sink)
+        .unwrap();
+    assert_eq!(
+        sink.frames,
+        [(CaptureDirection::ServerToClient, FAST_PATH_FRAME.to_vec())]
+    );
+
+    splitter
+        .feed(CaptureDirection::ClientToServer, tail, &mut sink)
+        .unwrap();
+    assert_eq!(sink.frames[1], (CaptureDirection::ClientToServer, frame));
+}
+
+#[test]
+fn invalid_frame_is_an_error() {
+    let mut splitter = CaptureSplitter::new(ChannelFilter::allow_all());
+
+    // Neither a TPKT nor a fast-path header.
+    let result = splitter.feed(CaptureDirection::ClientToServer, &[0x01, 0x00], &mut Frames::default());
+    assert_eq!(result.unwrap_err().kind(), std::io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn denied_channels_are_redacted() {
+    let frames = session();
+
+    let mut sink = Frames::default();
+    let mut splitter = CaptureSplitter::new(ChannelFilter::allow_only(["CLIPRDR"]));
+    feed(&mut splitter, &frames, &mut sink);
+
+    assert_eq!(sink.frames.len(), frames.len());
+
+    let redacted = [
+        (3, send_data_request(RDPDR_CHANNEL_ID, &[0; 6])),
+        (4, send_data_indication(RDPDR_CHANNEL_ID, &[0; 8])),
+    ];
+
+    for (index, (captured, (direction, frame))) in sink.frames.iter().zip(&frames).enumerate() {
+        let expected = redacted
+            .iter()
+            .find(|(redacted_index, _)| *redacted_index == index)
+            .map_or(frame, |(_, redacted_frame)| redacted_frame);
+
+        assert_eq!(captured, &(*direction, expected.clone()), "frame {index}");
+    }
+
+    sink.channels.sort();
+    assert_eq!(
+        sink.channels,
+        [
+            (RDPDR_CHANNEL_ID, "rdpdr".to_owned()),
+            (CLIPRDR_CHANNEL_ID, "cliprdr".to_owned())
+        ]
+    );
+}
+
+#[test]
+fn denied_channel_overrides_allowed() {
+    let frames = session();
+
+    let mut sink = Frames::default();
+    let mut splitter = CaptureSplitter::new(ChannelFilter::allow_only(["rdpdr", "cliprdr"]).deny("cliprdr"));
+    feed(&mut splitter, &frames, &mut sink);
+
+    assert_eq!(sink.frames[3].1, frames[3].1);
+    assert_eq!(sink.frames[5].1, send_data_request(CLIPRDR_CHANNEL_ID, &[0; 9]));
+}
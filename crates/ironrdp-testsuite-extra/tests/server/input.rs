@@ -0,0 +1,208 @@
+use ironrdp_ainput::{MouseEventFlags, MousePdu as AInputMousePdu};
+use ironrdp_core::decode;
+use ironrdp_pdu::input::fast_path::{FastPathInputEvent, KeyboardFlags};
+use ironrdp_pdu::input::mouse::PointerFlags;
+use ironrdp_pdu::input::mouse_rel::PointerRelFlags;
+use ironrdp_pdu::input::mouse_x::PointerXFlags;
+use ironrdp_pdu::input::{InputEvent, MousePdu, MouseRelPdu, MouseXPdu};
+use ironrdp_server::{InputDecoder, InputSink, NormalizedInputEvent, PointerButton};
+use rstest::rstest;
+
+#[derive(Default)]
+struct Events(Vec<NormalizedInputEvent>);
+
+impl InputSink for Events {
+    fn input(&mut self, event: NormalizedInputEvent) {
+        self.0.push(event);
+    }
+}
+
+fn decode_fastpath(events: impl IntoIterator<Item = FastPathInputEvent>) -> Vec<NormalizedInputEvent> {
+    let mut decoder = InputDecoder::new();
+    let mut sink = Events::default();
+
+    for event in events {
+        decoder.fastpath(event, &mut sink);
+    }
+
+    sink.0
+}
+
+fn unicode(code_unit: u16, flags: KeyboardFlags) -> FastPathInputEvent {
+    FastPathInputEvent::UnicodeKeyboardEvent(flags, code_unit)
+}
+
+fn button(button: PointerButton, pressed: bool) -> NormalizedInputEvent {
+    NormalizedInputEvent::Button { button, pressed }
+}
+
+#[test]
+fn surrogate_pair_is_recombined() {
+    // U+1F600, encoded as D83D DE00.
+    let events = decode_fastpath([
+        unicode(0xD83D, KeyboardFlags::empty()),
+        unicode(0xDE00, KeyboardFlags::empty()),
+        unicode(0xD83D, KeyboardFlags::RELEASE),
+        unicode(0xDE00, KeyboardFlags::RELEASE),
+        unicode(u16::from(b'a'), KeyboardFlags::empty()),
+    ]);
+
+    assert_eq!(
+        events,
+        [NormalizedInputEvent::Text('\u{1F600}'), NormalizedInputEvent::Text('a')]
+    );
+}
+
+#[test]
+fn unpaired_high_surrogate_is_dropped() {
+    let events = decode_fastpath([
+        unicode(0xD83D, KeyboardFlags::empty()),
+        unicode(u16::from(b'a'), KeyboardFlags::empty()),
+    ]);
+
+    assert_eq!(events, [NormalizedInputEvent::Text('a')]);
+}
+
+#[test]
+fn slowpath_unicode_surrogate_pair() {
+    use ironrdp_pdu::input::unicode::{KeyboardFlags, UnicodePdu};
+
+    let mut decoder = InputDecoder::new();
+    let mut sink = Events::default();
+
+    for unicode_code in [0xD83D, 0xDE00] {
+        decoder.slowpath(
+            InputEvent::Unicode(UnicodePdu {
+                flags: KeyboardFlags::empty(),
+                unicode_code,
+            }),
+            &mut sink,
+        );
+    }
+
+    assert_eq!(sink.0, [NormalizedInputEvent::Text('\u{1F600}')]);
+}
+
+#[rstest]
+// VERTICAL_WHEEL, 120 notch units.
+#[case(0x0278, NormalizedInputEvent::Wheel { dx: 0, dy: 120 })]
+// VERTICAL_WHEEL | WHEEL_NEGATIVE, the 9-bit two's complement value 0x188 is -120.
+#[case(0x0388, NormalizedInputEvent::Wheel { dx: 0, dy: -120 })]
+// HORIZONTAL_WHEEL | WHEEL_NEGATIVE
+#[case(0x0588, NormalizedInputEvent::Wheel { dx: -120, dy: 0 })]
+// HORIZONTAL_WHEEL | WHEEL_NEGATIVE, the lowest value.
+#[case(0x0500, NormalizedInputEvent::Wheel { dx: -256, dy: 0 })]
+fn wheel_sign(#[case] flags: u16, #[case] expected: NormalizedInputEvent) {
+    let mut encoded = flags.to_le_bytes().to_vec();
+    encoded.extend_from_slice(&[0x10, 0x00, 0x20, 0x00]);
+    let mouse = decode::<MousePdu>(&encoded).unwrap();
+
+    assert_eq!(decode_fastpath([FastPathInputEvent::MouseEvent(mouse)]), [expected]);
+}
+
+#[rstest]
+#[case(PointerFlags::DOWN | PointerFlags::LEFT_BUTTON, [button(PointerButton::Left, true)])]
+#[case(PointerFlags::RIGHT_BUTTON, [button(PointerButton::Right, false)])]
+#[case(PointerFlags::DOWN | PointerFlags::MIDDLE_BUTTON_OR_WHEEL, [button(PointerButton::Middle, true)])]
+fn mouse_buttons(#[case] flags: PointerFlags, #[case] expected: [NormalizedInputEvent; 1]) {
+    let events = decode_fastpath([FastPathInputEvent::MouseEvent(MousePdu {
+        flags: flags | PointerFlags::MOVE,
+        number_of_wheel_rotation_units: 0,
+        x_position: 16,
+        y_position: 32,
+    })]);
+
+    let mut all_expected = vec![NormalizedInputEvent::PointerMove { x: 16, y: 32 }];
+    all_expected.extend(expected);
+    assert_eq!(events, all_expected);
+}
+
+#[rstest]
+#[case(PointerXFlags::DOWN | PointerXFlags::BUTTON1, button(PointerButton::X1, true))]
+#[case(PointerXFlags::BUTTON2, button(PointerButton::X2, false))]
+fn mouse_ex_buttons(#[case] flags: PointerXFlags, #[case] expected: NormalizedInputEvent) {
+    let events = decode_fastpath([FastPathInputEvent::MouseEventEx(MouseXPdu {
+        flags,
+        x_position: 16,
+        y_position: 32,
+    })]);
+
+    assert_eq!(events, [NormalizedInputEvent::PointerMove { x: 16, y: 32 }, expected]);
+}
+
+#[rstest]
+#[case(PointerRelFlags::DOWN | PointerRelFlags::BUTTON1, button(PointerButton::Left, true))]
+#[case(PointerRelFlags::BUTTON2, button(PointerButton::Right, false))]
+#[case(PointerRelFlags::DOWN | PointerRelFlags::BUTTON3, button(PointerButton::Middle, true))]
+#[case(PointerRelFlags::DOWN | PointerRelFlags::XBUTTON1, button(PointerButton::X1, true))]
+#[case(PointerRelFlags::XBUTTON2, button(PointerButton::X2, false))]
+fn mouse_rel_buttons(#[case] flags: PointerRelFlags, #[case] expected: NormalizedInputEvent) {
+    let events = decode_fastpath([FastPathInputEvent::MouseEventRel(MouseRelPdu {
+        flags: flags | PointerRelFlags::MOVE,
+        x_delta: -3,
+        y_delta: 4,
+    })]);
+
+    assert_eq!(
+        events,
+        [NormalizedInputEvent::PointerMoveRelative { dx: -3, dy: 4 }, expected]
+    );
+}
+
+#[rstest]
+#[case(MouseEventFlags::DOWN | MouseEventFlags::BUTTON1, button(PointerButton::Left, true))]
+#[case(MouseEventFlags::BUTTON2, button(PointerButton::Right, false))]
+#[case(MouseEventFlags::DOWN | MouseEventFlags::BUTTON3, button(PointerButton::Middle, true))]
+#[case(MouseEventFlags::DOWN | MouseEventFlags::XBUTTON1, button(PointerButton::X1, true))]
+#[case(MouseEventFlags::XBUTTON2, button(PointerButton::X2, false))]
+fn ainput_buttons(#[case] flags: MouseEventFlags, #[case] expected: NormalizedInputEvent) {
+    let mut decoder = InputDecoder::new();
+    let mut sink = Events::default();
+
+    decoder.ainput(
+        &AInputMousePdu {
+            time: 0,
+            flags: flags | MouseEventFlags::MOVE,
+            x: 16,
+            y: 32,
+        },
+        &mut sink,
+    );
+    decoder.ainput(
+        &AInputMousePdu {
+            time: 0,
+            flags: flags | MouseEventFlags::REL,
+            x: -3,
+            y: 4,
+        },
+        &mut sink,
+    );
+
+    assert_eq!(
+        sink.0,
+        [
+            NormalizedInputEvent::PointerMove { x: 16, y: 32 },
+            expected,
+            NormalizedInputEvent::PointerMoveRelative { dx: -3, dy: 4 },
+            expected,
+        ]
+    );
+}
+
+#[test]
+fn ainput_wheel() {
+    let mut decoder = InputDecoder::new();
+    let mut sink = Events::default();
+
+    decoder.ainput(
+        &AInputMousePdu {
+            time: 0,
+            flags: MouseEventFlags::WHEEL,
+            x: 0,
+            y: -120,
+        },
+        &mut sink,
+    );
+
+    assert_eq!(sink.0, [NormalizedInputEvent::Wheel { dx: 0, dy: -120 }]);
+}
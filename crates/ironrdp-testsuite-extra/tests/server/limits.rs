@@ -0,0 +1,171 @@
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+
+use ironrdp_server::{ConnectionLimiter, ConnectionLimits, ConnectionPolicy, ConnectionQueue};
+
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+fn peer(ip: [u8; 4], port: u16) -> SocketAddr {
+    SocketAddr::from((ip, port))
+}
+
+fn limiter(limits: ConnectionLimits) -> ConnectionLimiter {
+    ConnectionLimiter::new(limits, None)
+}
+
+fn limits() -> ConnectionLimits {
+    ConnectionLimits {
+        max_sessions: None,
+        max_queued_connections: 2,
+        max_connections_per_ip: None,
+        rate_limit_window: Duration::from_secs(60),
+        handshake_timeout: Some(TIMEOUT),
+    }
+}
+
+#[test]
+fn per_ip_rate_limit() {
+    let mut limiter = limiter(ConnectionLimits {
+        max_connections_per_ip: Some(2),
+        ..limits()
+    });
+    let now = Instant::now();
+
+    assert!(limiter.admit(peer([10, 0, 0, 1], 1000), 0, 0, now));
+    assert!(limiter.admit(peer([10, 0, 0, 1], 1001), 0, 0, now + Duration::from_secs(10)));
+    assert!(!limiter.admit(peer([10, 0, 0, 1], 1002), 0, 0, now + Duration::from_secs(20)));
+
+    // Other addresses have their own budget.
+    assert!(limiter.admit(peer([10, 0, 0, 2], 1000), 0, 0, now + Duration::from_secs(20)));
+
+    // The first connection leaves the window, the second one is still in it.
+    assert!(limiter.admit(peer([10, 0, 0, 1], 1003), 0, 0, now + Duration::from_secs(61)));
+    assert!(!limiter.admit(peer([10, 0, 0, 1], 1004), 0, 0, now + Duration::from_secs(62)));
+    assert!(limiter.admit(peer([10, 0, 0, 1], 1005), 0, 0, now + Duration::from_secs(71)));
+}
+
+#[test]
+fn rejected_connections_do_not_count_against_rate_limit() {
+    let mut limiter = limiter(ConnectionLimits {
+        max_connections_per_ip: Some(1),
+        ..limits()
+    });
+    let now = Instant::now();
+
+    assert!(limiter.admit(peer([10, 0, 0, 1], 1000), 0, 0, now));
+    for port in 1001..1010 {
+        assert!(!limiter.admit(peer([10, 0, 0, 1], port), 0, 0, now + Duration::from_secs(30)));
+    }
+    assert!(limiter.admit(peer([10, 0, 0, 1], 1010), 0, 0, now + Duration::from_secs(61)));
+}
+
+#[test]
+fn session_cap() {
+    let mut limiter = limiter(ConnectionLimits {
+        max_sessions: Some(1),
+        ..limits()
+    });
+    let now = Instant::now();
+
+    assert!(limiter.admit(peer([10, 0, 0, 1], 1000), 0, 0, now));
+    assert!(!limiter.admit(peer([10, 0, 0, 2], 1000), 1, 0, now));
+
+    let mut queue = ConnectionQueue::new();
+    assert!(!queue.push(&mut limiter, (), peer([10, 0, 0, 2], 1000), now));
+    assert!(queue.is_empty());
+}
+
+#[test]
+fn bounded_queue() {
+    let mut limiter = limiter(limits());
+    let mut queue = ConnectionQueue::new();
+    let now = Instant::now();
+
+    assert!(queue.push(&mut limiter, 1, peer([10, 0, 0, 1], 1000), now));
+    assert!(queue.push(&mut limiter, 2, peer([10, 0, 0, 2], 1000), now));
+    assert!(!queue.push(&mut limiter, 3, peer([10, 0, 0, 3], 1000), now));
+    assert_eq!(queue.len(), 2);
+
+    assert_eq!(queue.pop(&limiter, now), Some((1, peer([10, 0, 0, 1], 1000))));
+    assert!(queue.push(&mut limiter, 4, peer([10, 0, 0, 4], 1000), now));
+    assert_eq!(queue.pop(&limiter, now), Some((2, peer([10, 0, 0, 2], 1000))));
+    assert_eq!(queue.pop(&limiter, now), Some((4, peer([10, 0, 0, 4], 1000))));
+    assert_eq!(queue.pop(&limiter, now), None);
+}
+
+#[test]
+fn queue_limit_only_applies_while_a_session_is_running() {
+    let mut limiter = limiter(ConnectionLimits {
+        max_queued_connections: 0,
+        ..limits()
+    });
+    let now = Instant::now();
+
+    assert!(limiter.admit(peer([10, 0, 0, 1], 1000), 0, 0, now));
+    assert!(!limiter.admit(peer([10, 0, 0, 2], 1000), 1, 0, now));
+}
+
+#[test]
+fn stale_connections_are_dropped_on_pop() {
+    let mut limiter = limiter(limits());
+    let mut queue = ConnectionQueue::new();
+    let now = Instant::now();
+
+    assert!(queue.push(&mut limiter, 1, peer([10, 0, 0, 1], 1000), now));
+    assert!(queue.push(
+        &mut limiter,
+        2,
+        peer([10, 0, 0, 2], 1000),
+        now + Duration::from_secs(10)
+    ));
+
+    let popped_at = now + TIMEOUT + Duration::from_secs(1);
+    assert_eq!(queue.pop(&limiter, popped_at), Some((2, peer([10, 0, 0, 2], 1000))));
+    assert!(queue.is_empty());
+}
+
+#[test]
+fn stale_connections_are_dropped_on_push() {
+    let mut limiter = limiter(limits());
+    let mut queue = ConnectionQueue::new();
+    let now = Instant::now();
+
+    assert!(queue.push(&mut limiter, 1, peer([10, 0, 0, 1], 1000), now));
+    assert!(queue.push(&mut limiter, 2, peer([10, 0, 0, 2], 1000), now));
+
+    // The queue is full until the queued connections time out.
+    assert!(!queue.push(&mut limiter, 3, peer([10, 0, 0, 3], 1000), now + TIMEOUT));
+    assert!(queue.push(
+        &mut limiter,
+        4,
+        peer([10, 0, 0, 4], 1000),
+        now + TIMEOUT + Duration::from_secs(1)
+    ));
+    assert_eq!(queue.len(), 1);
+}
+
+#[test]
+fn no_handshake_timeout() {
+    let limiter = limiter(ConnectionLimits {
+        handshake_timeout: None,
+        ..limits()
+    });
+    let now = Instant::now();
+
+    assert!(!limiter.is_expired(now, now + Duration::from_secs(3600)));
+}
+
+struct RejectAll;
+
+impl ConnectionPolicy for RejectAll {
+    fn admit(&mut self, _: SocketAddr, _: usize) -> bool {
+        false
+    }
+}
+
+#[test]
+fn policy_is_consulted_last() {
+    let mut limiter = ConnectionLimiter::new(limits(), Some(Box::new(RejectAll)));
+
+    assert!(!limiter.admit(peer([10, 0, 0, 1], 1000), 0, 0, Instant::now()));
+}
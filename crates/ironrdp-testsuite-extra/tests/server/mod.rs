@@ -0,0 +1,8 @@
+mod audit;
+mod autodetect;
+mod capture;
+mod input;
+mod limits;
+mod scheduler;
+mod shutdown;
+mod sound;
@@ -0,0 +1,223 @@
+use std::num::NonZeroU16;
+use std::time::{Duration, Instant};
+
+use ironrdp_server::{BitmapUpdate, FrameScheduler, FrameSchedulerConfig, PixelFormat, PixelOrder};
+
+const MS: Duration = Duration::from_millis(1);
+
+fn update(left: u16, top: u16, width: u16, height: u16) -> BitmapUpdate {
+    BitmapUpdate {
+        top,
+        left,
+        width: NonZeroU16::new(width).unwrap(),
+        height: NonZeroU16::new(height).unwrap(),
+        format: PixelFormat::BgrA32,
+        order: PixelOrder::TopToBottom,
+        data: Vec::new(),
+        stride: 0,
+    }
+}
+
+fn config(max_frame_rate: u32) -> FrameSchedulerConfig {
+    FrameSchedulerConfig {
+        max_unacknowledged_frames: 2,
+        max_frame_rate,
+        acknowledgement_timeout: Duration::from_secs(1),
+    }
+}
+
+/// Queues an update and sends the resulting frame, if any, returning its ID
+fn send(scheduler: &mut FrameScheduler, now: Instant) -> Option<Option<u32>> {
+    scheduler.push(update(0, 0, 64, 64));
+
+    let frame = scheduler.poll_frame(now)?;
+    scheduler.frame_sent(frame.id, 0, now);
+
+    Some(frame.id)
+}
+
+#[test]
+fn acknowledgement_window() {
+    let mut scheduler = FrameScheduler::new(config(1000), true, Some(16));
+    let t0 = Instant::now();
+
+    assert_eq!(send(&mut scheduler, t0), Some(Some(0)));
+    assert_eq!(send(&mut scheduler, t0 + 100 * MS), Some(Some(1)));
+
+    // Two frames in flight, the update stays queued.
+    assert_eq!(send(&mut scheduler, t0 + 200 * MS), None);
+
+    scheduler.acknowledge(0);
+    let frame = scheduler.poll_frame(t0 + 200 * MS).unwrap();
+    assert_eq!(frame.id, Some(2));
+    scheduler.frame_sent(frame.id, 0, t0 + 200 * MS);
+
+    assert_eq!(send(&mut scheduler, t0 + 300 * MS), None);
+
+    // Acknowledging frame 2 implicitly acknowledges frame 1, draining the window.
+    scheduler.acknowledge(2);
+    let frame = scheduler.poll_frame(t0 + 300 * MS).unwrap();
+    assert_eq!(frame.id, Some(3));
+    scheduler.frame_sent(frame.id, 0, t0 + 300 * MS);
+
+    assert_eq!(send(&mut scheduler, t0 + 400 * MS), Some(Some(4)));
+}
+
+#[test]
+fn acknowledgement_window_advertised_by_client() {
+    let mut scheduler = FrameScheduler::new(config(1000), true, Some(1));
+    let t0 = Instant::now();
+
+    assert_eq!(send(&mut scheduler, t0), Some(Some(0)));
+    assert_eq!(send(&mut scheduler, t0 + 100 * MS), None);
+
+    scheduler.acknowledge(0);
+    assert_eq!(scheduler.poll_frame(t0 + 100 * MS).unwrap().id, Some(1));
+}
+
+#[test]
+fn unknown_acknowledgement_is_ignored() {
+    let mut scheduler = FrameScheduler::new(config(1000), true, Some(16));
+    let t0 = Instant::now();
+
+    send(&mut scheduler, t0);
+    send(&mut scheduler, t0 + 100 * MS);
+
+    scheduler.acknowledge(42);
+    assert_eq!(send(&mut scheduler, t0 + 200 * MS), None);
+}
+
+#[test]
+fn suspended_acknowledgements() {
+    let mut scheduler = FrameScheduler::new(config(1000), true, Some(16));
+    let t0 = Instant::now();
+
+    send(&mut scheduler, t0);
+    send(&mut scheduler, t0 + 100 * MS);
+    assert_eq!(send(&mut scheduler, t0 + 200 * MS), None);
+
+    scheduler.acknowledge(0xFFFF_FFFF);
+
+    // Frames are still delimited by frame markers, but not waited for anymore.
+    let frame = scheduler.poll_frame(t0 + 200 * MS).unwrap();
+    assert_eq!(frame.id, Some(2));
+    scheduler.frame_sent(frame.id, 0, t0 + 200 * MS);
+
+    for (i, id) in (3..10).enumerate() {
+        let now = t0 + 300 * MS + u32::try_from(i).unwrap() * 100 * MS;
+        assert_eq!(send(&mut scheduler, now), Some(Some(id)));
+    }
+}
+
+#[test]
+fn acknowledgement_timeout() {
+    let mut scheduler = FrameScheduler::new(config(1000), true, Some(16));
+    let t0 = Instant::now();
+
+    send(&mut scheduler, t0);
+    send(&mut scheduler, t0 + 10 * MS);
+    assert_eq!(send(&mut scheduler, t0 + 500 * MS), None);
+
+    // The oldest frame is considered lost once the timeout elapsed.
+    assert_eq!(scheduler.next_deadline(), Some(t0 + 1000 * MS));
+    assert!(scheduler.poll_frame(t0 + 999 * MS).is_none());
+
+    let frame = scheduler.poll_frame(t0 + 1000 * MS).unwrap();
+    assert_eq!(frame.id, Some(2));
+    scheduler.frame_sent(frame.id, 0, t0 + 1000 * MS);
+
+    // Frame 1 is still in flight, along with frame 2.
+    assert_eq!(send(&mut scheduler, t0 + 1005 * MS), None);
+    assert!(scheduler.poll_frame(t0 + 1010 * MS).is_some());
+}
+
+#[test]
+fn frame_rate_pacing() {
+    let mut scheduler = FrameScheduler::new(config(10), false, None);
+    let t0 = Instant::now();
+
+    assert_eq!(send(&mut scheduler, t0), Some(None));
+
+    // 10 frames per second, one every 100 ms.
+    assert_eq!(send(&mut scheduler, t0 + 99 * MS), None);
+    assert_eq!(scheduler.next_deadline(), Some(t0 + 100 * MS));
+    assert!(scheduler.poll_frame(t0 + 100 * MS).is_some());
+}
+
+#[test]
+fn bandwidth_pacing() {
+    let mut scheduler = FrameScheduler::new(config(1000), false, None);
+    scheduler.set_bandwidth(Some(1_000));
+    let t0 = Instant::now();
+
+    scheduler.push(update(0, 0, 64, 64));
+    let frame = scheduler.poll_frame(t0).unwrap();
+    // 50 000 bytes at 1 000 kbit/s take 400 ms.
+    scheduler.frame_sent(frame.id, 50_000, t0);
+
+    scheduler.push(update(0, 0, 64, 64));
+    assert_eq!(scheduler.next_deadline(), Some(t0 + 400 * MS));
+    assert!(scheduler.poll_frame(t0 + 399 * MS).is_none());
+    assert!(scheduler.poll_frame(t0 + 400 * MS).is_some());
+}
+
+#[test]
+fn unknown_bandwidth_falls_back_to_frame_rate() {
+    let mut scheduler = FrameScheduler::new(config(10), false, None);
+    scheduler.set_bandwidth(Some(0));
+    let t0 = Instant::now();
+
+    scheduler.push(update(0, 0, 64, 64));
+    let frame = scheduler.poll_frame(t0).unwrap();
+    scheduler.frame_sent(frame.id, 50_000, t0);
+
+    scheduler.push(update(0, 0, 64, 64));
+    assert_eq!(scheduler.next_deadline(), Some(t0 + 100 * MS));
+}
+
+fn bounds(update: &BitmapUpdate) -> (u16, u16, u16, u16) {
+    (update.left, update.top, update.width.get(), update.height.get())
+}
+
+#[test]
+fn covered_updates_are_skipped() {
+    let mut scheduler = FrameScheduler::new(config(1000), false, None);
+
+    scheduler.push(update(0, 0, 64, 64));
+    scheduler.push(update(16, 16, 16, 16));
+    // Overlapping, but not covering.
+    scheduler.push(update(32, 32, 64, 64));
+    // Covers the first two only.
+    scheduler.push(update(0, 0, 64, 64));
+    // Covered by a queued update, but newer: kept.
+    scheduler.push(update(8, 8, 8, 8));
+
+    let frame = scheduler.poll_frame(Instant::now()).unwrap();
+    let updates: Vec<_> = frame.updates.iter().map(bounds).collect();
+    assert_eq!(updates, [(32, 32, 64, 64), (0, 0, 64, 64), (8, 8, 8, 8)]);
+}
+
+#[test]
+fn covering_update_skips_everything() {
+    let mut scheduler = FrameScheduler::new(config(1000), false, None);
+
+    scheduler.push(update(0, 0, 64, 64));
+    scheduler.push(update(64, 64, 64, 64));
+    scheduler.push(update(0, 0, 128, 128));
+
+    let frame = scheduler.poll_frame(Instant::now()).unwrap();
+    let updates: Vec<_> = frame.updates.iter().map(bounds).collect();
+    assert_eq!(updates, [(0, 0, 128, 128)]);
+}
+
+#[test]
+fn nothing_queued() {
+    let mut scheduler = FrameScheduler::new(config(1000), false, None);
+
+    assert_eq!(scheduler.next_deadline(), None);
+    assert!(scheduler.poll_frame(Instant::now()).is_none());
+
+    scheduler.push(update(0, 0, 64, 64));
+    scheduler.clear();
+    assert!(scheduler.poll_frame(Instant::now()).is_none());
+}
@@ -0,0 +1,72 @@
+use std::future;
+use std::time::Duration;
+
+use anyhow::anyhow;
+use ironrdp_core::decode;
+use ironrdp_pdu::mcs::{DisconnectProviderUltimatum, DisconnectReason, SendDataIndication};
+use ironrdp_pdu::rdp::headers::{ShareControlHeader, ShareControlPdu, ShareDataPdu};
+use ironrdp_pdu::rdp::server_error_info::{ErrorInfo, ProtocolIndependentCode, ServerSetErrorInfoPdu};
+use ironrdp_pdu::x224::X224;
+use ironrdp_server::{encode_disconnection, wait_teardown};
+use tokio::time::{self, Instant};
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+const IO_CHANNEL_ID: u16 = 1003;
+const USER_CHANNEL_ID: u16 = 1007;
+
+#[test]
+fn disconnection_pdus() {
+    let reason = ErrorInfo::ProtocolIndependentCode(ProtocolIndependentCode::RpcInitiatedDisconnect);
+    let pdus = encode_disconnection(reason, IO_CHANNEL_ID, USER_CHANNEL_ID).unwrap();
+
+    let length = ironrdp_pdu::find_size(&pdus).unwrap().unwrap().length;
+    let (set_error_info, ultimatum) = pdus.split_at(length);
+
+    let indication = decode::<X224<SendDataIndication<'_>>>(set_error_info).unwrap().0;
+    assert_eq!(indication.initiator_id, USER_CHANNEL_ID);
+    assert_eq!(indication.channel_id, IO_CHANNEL_ID);
+
+    let header = decode::<ShareControlHeader>(&indication.user_data).unwrap();
+    assert_eq!(header.pdu_source, IO_CHANNEL_ID);
+    let ShareControlPdu::Data(data) = &header.share_control_pdu else {
+        panic!("unexpected share control PDU: {:?}", header.share_control_pdu);
+    };
+    assert_eq!(
+        data.share_data_pdu,
+        ShareDataPdu::ServerSetErrorInfo(ServerSetErrorInfoPdu(reason))
+    );
+
+    let ultimatum = decode::<X224<DisconnectProviderUltimatum>>(ultimatum).unwrap().0;
+    assert_eq!(ultimatum.reason, DisconnectReason::ProviderInitiated);
+}
+
+#[tokio::test(start_paused = true)]
+async fn teardown_times_out() {
+    let start = Instant::now();
+
+    assert!(wait_teardown(future::pending(), TIMEOUT).await);
+    assert_eq!(start.elapsed(), TIMEOUT);
+}
+
+#[tokio::test(start_paused = true)]
+async fn teardown_completes_before_timeout() {
+    let start = Instant::now();
+
+    let teardown = async {
+        time::sleep(Duration::from_secs(1)).await;
+        Ok(true)
+    };
+    assert!(wait_teardown(teardown, TIMEOUT).await);
+    assert_eq!(start.elapsed(), Duration::from_secs(1));
+}
+
+#[tokio::test(start_paused = true)]
+async fn client_disconnected_during_teardown() {
+    assert!(!wait_teardown(async { Ok(false) }, TIMEOUT).await);
+}
+
+#[tokio::test(start_paused = true)]
+async fn teardown_failed() {
+    assert!(!wait_teardown(async { Err(anyhow!("connection reset")) }, TIMEOUT).await);
+}
@@ -0,0 +1,182 @@
+use std::sync::Mutex;
+use std::time::Duration;
+
+use ironrdp_rdpsnd::pdu::{AudioFormat, AudioFormatFlags, ClientAudioFormatPdu, Version, WaveFormat};
+use ironrdp_server::{
+    AudioFrame, AudioSourceSoundFactory, ChannelAudioSource, RdpsndServerHandler, RdpsndServerMessage, ServerEvent,
+    ServerEventSender as _, SoundServerFactory as _,
+};
+use tokio::sync::mpsc;
+
+/// 44.1 kHz, 16-bit stereo: 176 400 bytes per second, in blocks of 4 bytes
+fn pcm() -> AudioFormat {
+    AudioFormat {
+        format: WaveFormat::PCM,
+        n_channels: 2,
+        n_samples_per_sec: 44_100,
+        n_avg_bytes_per_sec: 176_400,
+        n_block_align: 4,
+        bits_per_sample: 16,
+        data: None,
+    }
+}
+
+/// Format the server does not produce
+fn mono() -> AudioFormat {
+    AudioFormat {
+        format: WaveFormat::PCM,
+        n_channels: 1,
+        n_samples_per_sec: 8_000,
+        n_avg_bytes_per_sec: 16_000,
+        n_block_align: 2,
+        bits_per_sample: 16,
+        data: None,
+    }
+}
+
+fn adpcm() -> AudioFormat {
+    AudioFormat {
+        format: WaveFormat::ADPCM,
+        n_channels: 2,
+        n_samples_per_sec: 22_050,
+        n_avg_bytes_per_sec: 22_311,
+        n_block_align: 1024,
+        bits_per_sample: 4,
+        data: None,
+    }
+}
+
+struct Stream {
+    handler: Box<dyn RdpsndServerHandler>,
+    frames: mpsc::Sender<AudioFrame>,
+    events: mpsc::UnboundedReceiver<ServerEvent>,
+}
+
+/// Streams a [`ChannelAudioSource`] producing `format`
+fn start(format: AudioFormat, underrun_timeout: Duration) -> Stream {
+    let (source, frames) = ChannelAudioSource::new(vec![format.clone()], 8);
+    let source = Mutex::new(Some(source));
+
+    let mut factory = AudioSourceSoundFactory::new(move || Box::new(source.lock().unwrap().take().unwrap()))
+        .with_underrun_timeout(underrun_timeout);
+    let (sender, events) = ServerEvent::create_channel();
+    factory.set_sender(sender);
+
+    let mut handler = factory.build_backend();
+    let format_no = handler.start(&ClientAudioFormatPdu {
+        version: Version::V8,
+        flags: AudioFormatFlags::ALIVE,
+        formats: vec![mono(), format],
+        volume_left: 0xFFFF,
+        volume_right: 0xFFFF,
+        pitch: 0x10000,
+        dgram_port: 0,
+    });
+    assert_eq!(format_no, Some(1));
+
+    Stream {
+        handler,
+        frames,
+        events,
+    }
+}
+
+async fn next_wave(events: &mut mpsc::UnboundedReceiver<ServerEvent>) -> (Vec<u8>, u32) {
+    match events.recv().await {
+        Some(ServerEvent::Rdpsnd(RdpsndServerMessage::Wave(data, timestamp))) => (data, timestamp),
+        _ => panic!("expected a wave"),
+    }
+}
+
+fn frame(len: usize, timestamp: Option<u32>) -> AudioFrame {
+    AudioFrame {
+        data: vec![0x42; len],
+        timestamp,
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn underrun_sends_block_aligned_silence() {
+    let Stream {
+        handler: _handler,
+        frames,
+        mut events,
+    } = start(pcm(), Duration::from_millis(7));
+
+    // 7 ms at 176 400 bytes per second is 1234.8 bytes, truncated to a multiple of the block size.
+    let (data, timestamp) = next_wave(&mut events).await;
+    assert_eq!(data, vec![0; 1232]);
+    assert_eq!(timestamp, 0);
+
+    // 1232 bytes are 6.98 ms of audio.
+    let (data, timestamp) = next_wave(&mut events).await;
+    assert_eq!(data.len(), 1232);
+    assert_eq!(timestamp, 6);
+
+    // The source resumes.
+    frames.send(frame(176_400, None)).await.unwrap();
+    let (data, timestamp) = next_wave(&mut events).await;
+    assert_eq!(data.len(), 176_400);
+    assert_eq!(timestamp, 13);
+
+    drop(frames);
+    assert!(matches!(
+        events.recv().await,
+        Some(ServerEvent::Rdpsnd(RdpsndServerMessage::Close))
+    ));
+}
+
+#[tokio::test(start_paused = true)]
+async fn underrun_without_silence_for_compressed_formats() {
+    let Stream {
+        handler: _handler,
+        frames,
+        mut events,
+    } = start(adpcm(), Duration::from_millis(7));
+
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    frames.send(frame(1024, Some(1000))).await.unwrap();
+
+    // The underruns did not produce any data.
+    assert_eq!(next_wave(&mut events).await, (vec![0x42; 1024], 1000));
+}
+
+#[tokio::test(start_paused = true)]
+async fn timestamp_wraps_around() {
+    let format = AudioFormat {
+        n_avg_bytes_per_sec: 1,
+        ..pcm()
+    };
+    let Stream {
+        handler: _handler,
+        frames,
+        mut events,
+    } = start(format, Duration::from_secs(3600));
+
+    // Enough audio for the position, in milliseconds, to go past `u32::MAX`: 4 294 968 s.
+    frames.send(frame(4_294_968, None)).await.unwrap();
+    frames.send(frame(4, None)).await.unwrap();
+
+    let (_, timestamp) = next_wave(&mut events).await;
+    assert_eq!(timestamp, 0);
+
+    // 4 294 968 000 ms modulo 2^32
+    let (_, timestamp) = next_wave(&mut events).await;
+    assert_eq!(timestamp, 704);
+}
+
+#[tokio::test(start_paused = true)]
+async fn stop_drops_the_source() {
+    let Stream {
+        mut handler,
+        frames,
+        mut events,
+    } = start(pcm(), Duration::from_millis(7));
+
+    next_wave(&mut events).await;
+    handler.stop();
+
+    tokio::time::timeout(Duration::from_secs(1), frames.closed())
+        .await
+        .expect("source dropped");
+}
@@ -22,7 +22,34 @@ compile_error!("a TLS backend must be selected by enabling a single feature out
 
 // The whole public API of this crate.
 #[cfg(any(feature = "stub", feature = "native-tls", feature = "rustls"))]
-pub use impl_::{upgrade, TlsStream};
+pub use impl_::{cipher_suite, peer_certificate, peer_certificate_chain, protocol_version, upgrade_with, TlsStream};
+
+/// Policy for verifying the certificate of the server
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CertificateVerification {
+    /// Any certificate is accepted
+    ///
+    /// RDP servers typically use self-signed certificates. With CredSSP, the server is still authenticated,
+    /// as its public key is bound to the authentication exchange.
+    #[default]
+    Disabled,
+    /// The certificate is verified by the platform, using the certificate store of the system
+    ///
+    /// Only supported by the `native-tls` backend, which relies on Schannel on Windows and on Secure Transport
+    /// on macOS: the policy of the system (trusted roots, revocation checks, FIPS mode…) applies.
+    System,
+}
+
+/// Upgrades the stream to TLS, accepting any server certificate
+///
+/// Returns the TLS stream and the public key of the server.
+#[cfg(any(feature = "stub", feature = "native-tls", feature = "rustls"))]
+pub async fn upgrade<S>(stream: S, server_name: &str) -> std::io::Result<(TlsStream<S>, Vec<u8>)>
+where
+    S: Unpin + tokio::io::AsyncRead + tokio::io::AsyncWrite,
+{
+    upgrade_with(stream, server_name, CertificateVerification::Disabled).await
+}
 
 #[cfg(any(feature = "native-tls", feature = "rustls"))]
 pub(crate) fn extract_tls_server_public_key(cert: &[u8]) -> std::io::Result<Vec<u8>> {
@@ -2,15 +2,21 @@ use std::io;
 
 use tokio::io::{AsyncRead, AsyncWrite, AsyncWriteExt as _};
 
+use crate::CertificateVerification;
+
 pub type TlsStream<S> = tokio_native_tls::TlsStream<S>;
 
-pub async fn upgrade<S>(stream: S, server_name: &str) -> io::Result<(TlsStream<S>, Vec<u8>)>
+pub async fn upgrade_with<S>(
+    stream: S,
+    server_name: &str,
+    verification: CertificateVerification,
+) -> io::Result<(TlsStream<S>, Vec<u8>)>
 where
     S: Unpin + AsyncRead + AsyncWrite,
 {
     let mut tls_stream = {
         let connector = tokio_native_tls::native_tls::TlsConnector::builder()
-            .danger_accept_invalid_certs(true)
+            .danger_accept_invalid_certs(verification == CertificateVerification::Disabled)
             .use_sni(false)
             .build()
             .map(tokio_native_tls::TlsConnector::from)
@@ -25,14 +31,45 @@ where
     tls_stream.flush().await?;
 
     let server_public_key = {
-        let cert = tls_stream
-            .get_ref()
-            .peer_certificate()
-            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?
-            .ok_or_else(|| io::Error::new(io::ErrorKind::Other, "peer certificate is missing"))?;
-        let cert = cert.to_der().map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+        let cert = peer_certificate(&tls_stream)?;
         crate::extract_tls_server_public_key(&cert)?
     };
 
     Ok((tls_stream, server_public_key))
 }
+
+/// Returns the DER-encoded certificate of the server
+pub fn peer_certificate<S>(tls_stream: &TlsStream<S>) -> io::Result<Vec<u8>>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let cert = tls_stream
+        .get_ref()
+        .peer_certificate()
+        .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?
+        .ok_or_else(|| io::Error::new(io::ErrorKind::Other, "peer certificate is missing"))?;
+
+    cert.to_der().map_err(|e| io::Error::new(io::ErrorKind::Other, e))
+}
+
+/// Returns the DER-encoded certificates presented by the server, starting with the certificate of the server
+///
+/// native-tls only exposes the certificate of the server itself.
+pub fn peer_certificate_chain<S>(tls_stream: &TlsStream<S>) -> io::Result<Vec<Vec<u8>>>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    Ok(vec![peer_certificate(tls_stream)?])
+}
+
+/// Returns the negotiated protocol version, which is not exposed by native-tls
+pub fn protocol_version<S>(tls_stream: &TlsStream<S>) -> Option<String> {
+    let _ = tls_stream;
+    None
+}
+
+/// Returns the negotiated cipher suite, which is not exposed by native-tls
+pub fn cipher_suite<S>(tls_stream: &TlsStream<S>) -> Option<String> {
+    let _ = tls_stream;
+    None
+}
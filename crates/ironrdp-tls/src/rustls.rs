@@ -3,12 +3,25 @@ use std::io;
 use tokio::io::{AsyncRead, AsyncWrite, AsyncWriteExt as _};
 use tokio_rustls::rustls::{self, pki_types::ServerName};
 
+use crate::CertificateVerification;
+
 pub type TlsStream<S> = tokio_rustls::client::TlsStream<S>;
 
-pub async fn upgrade<S>(stream: S, server_name: &str) -> io::Result<(TlsStream<S>, Vec<u8>)>
+pub async fn upgrade_with<S>(
+    stream: S,
+    server_name: &str,
+    verification: CertificateVerification,
+) -> io::Result<(TlsStream<S>, Vec<u8>)>
 where
     S: Unpin + AsyncRead + AsyncWrite,
 {
+    if verification != CertificateVerification::Disabled {
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "the certificate can only be verified by the system with the native-tls backend",
+        ));
+    }
+
     let mut tls_stream = {
         let mut config = rustls::client::ClientConfig::builder()
             .dangerous()
@@ -35,18 +48,48 @@ where
     tls_stream.flush().await?;
 
     let server_public_key = {
-        let cert = tls_stream
-            .get_ref()
-            .1
-            .peer_certificates()
-            .and_then(|certificates| certificates.first())
-            .ok_or_else(|| io::Error::new(io::ErrorKind::Other, "peer certificate is missing"))?;
-        crate::extract_tls_server_public_key(cert)?
+        let cert = peer_certificate(&tls_stream)?;
+        crate::extract_tls_server_public_key(&cert)?
     };
 
     Ok((tls_stream, server_public_key))
 }
 
+/// Returns the DER-encoded certificate of the server
+pub fn peer_certificate<S>(tls_stream: &TlsStream<S>) -> io::Result<Vec<u8>> {
+    let cert = tls_stream
+        .get_ref()
+        .1
+        .peer_certificates()
+        .and_then(|certificates| certificates.first())
+        .ok_or_else(|| io::Error::new(io::ErrorKind::Other, "peer certificate is missing"))?;
+
+    Ok(cert.to_vec())
+}
+
+/// Returns the DER-encoded certificates presented by the server, starting with the certificate of the server
+pub fn peer_certificate_chain<S>(tls_stream: &TlsStream<S>) -> io::Result<Vec<Vec<u8>>> {
+    let certificates = tls_stream
+        .get_ref()
+        .1
+        .peer_certificates()
+        .ok_or_else(|| io::Error::other("peer certificate is missing"))?;
+
+    Ok(certificates.iter().map(|cert| cert.to_vec()).collect())
+}
+
+/// Returns the negotiated protocol version, e.g.: `TLSv1_3`
+pub fn protocol_version<S>(tls_stream: &TlsStream<S>) -> Option<String> {
+    let version = tls_stream.get_ref().1.protocol_version()?;
+    Some(format!("{version:?}"))
+}
+
+/// Returns the negotiated cipher suite, e.g.: `TLS13_AES_256_GCM_SHA384`
+pub fn cipher_suite<S>(tls_stream: &TlsStream<S>) -> Option<String> {
+    let suite = tls_stream.get_ref().1.negotiated_cipher_suite()?;
+    Some(format!("{:?}", suite.suite()))
+}
+
 mod danger {
     use tokio_rustls::rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
     use tokio_rustls::rustls::pki_types;
@@ -4,6 +4,8 @@ use std::task::{Context, Poll};
 
 use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
 
+use crate::CertificateVerification;
+
 #[derive(Debug)]
 pub struct TlsStream<S> {
     _marker: PhantomData<S>,
@@ -29,11 +31,35 @@ impl<S> AsyncWrite for TlsStream<S> {
     }
 }
 
-pub async fn upgrade<S>(stream: S, server_name: &str) -> io::Result<(TlsStream<S>, Vec<u8>)>
+pub async fn upgrade_with<S>(
+    stream: S,
+    server_name: &str,
+    verification: CertificateVerification,
+) -> io::Result<(TlsStream<S>, Vec<u8>)>
 where
     S: Unpin + AsyncRead + AsyncWrite,
 {
     // Do nothing and fail
-    let _ = (stream, server_name);
+    let _ = (stream, server_name, verification);
+    Err(io::Error::other("no TLS backend enabled for this build"))
+}
+
+pub fn peer_certificate<S>(tls_stream: &TlsStream<S>) -> io::Result<Vec<u8>> {
+    let _ = tls_stream;
     Err(io::Error::other("no TLS backend enabled for this build"))
 }
+
+pub fn peer_certificate_chain<S>(tls_stream: &TlsStream<S>) -> io::Result<Vec<Vec<u8>>> {
+    let _ = tls_stream;
+    Err(io::Error::other("no TLS backend enabled for this build"))
+}
+
+pub fn protocol_version<S>(tls_stream: &TlsStream<S>) -> Option<String> {
+    let _ = tls_stream;
+    None
+}
+
+pub fn cipher_suite<S>(tls_stream: &TlsStream<S>) -> Option<String> {
+    let _ = tls_stream;
+    None
+}
@@ -0,0 +1,73 @@
+use std::io;
+
+use bytes::{Bytes, BytesMut};
+use ironrdp_pdu::Action;
+use tokio_util::codec::{Decoder, Encoder};
+
+/// Codec splitting a byte stream into whole RDP PDUs, for use with [`tokio_util::codec::Framed`].
+///
+/// Both X.224 (TPKT) and fast-path PDUs are recognized, as with [`ironrdp_pdu::find_size`].
+/// This makes it possible to compose IronRDP with middleware operating on PDU streams (proxies, traffic shapers…).
+///
+/// Only the traffic exchanged once the security upgrade is performed may be framed this way:
+/// the CredSSP messages are not TPKT-framed, and must be handled by the connector instead.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RdpCodec;
+
+impl RdpCodec {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Decoder for RdpCodec {
+    type Item = (Action, BytesMut);
+    type Error = io::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        let Some(pdu_info) = ironrdp_pdu::find_size(src).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?
+        else {
+            return Ok(None);
+        };
+
+        // An empty frame would be yielded over and over, without consuming the stream.
+        if pdu_info.length == 0 {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "zero-length PDU"));
+        }
+
+        if let Some(missing) = pdu_info.length.checked_sub(src.len()).filter(|missing| *missing > 0) {
+            // Avoid reallocating for each chunk of a large PDU.
+            src.reserve(missing);
+            return Ok(None);
+        }
+
+        Ok(Some((pdu_info.action, src.split_to(pdu_info.length))))
+    }
+}
+
+impl Encoder<Bytes> for RdpCodec {
+    type Error = io::Error;
+
+    fn encode(&mut self, item: Bytes, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        dst.extend_from_slice(&item);
+        Ok(())
+    }
+}
+
+impl Encoder<Vec<u8>> for RdpCodec {
+    type Error = io::Error;
+
+    fn encode(&mut self, item: Vec<u8>, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        dst.extend_from_slice(&item);
+        Ok(())
+    }
+}
+
+impl Encoder<&[u8]> for RdpCodec {
+    type Error = io::Error;
+
+    fn encode(&mut self, item: &[u8], dst: &mut BytesMut) -> Result<(), Self::Error> {
+        dst.extend_from_slice(item);
+        Ok(())
+    }
+}
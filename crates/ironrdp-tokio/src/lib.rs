@@ -1,6 +1,12 @@
 #[rustfmt::skip] // do not re-order this pub use
 pub use ironrdp_async::*;
 
+#[cfg(feature = "codec")]
+mod codec;
+
+#[cfg(feature = "codec")]
+pub use self::codec::*;
+
 use std::io;
 use std::pin::Pin;
 
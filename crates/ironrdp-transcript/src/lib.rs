@@ -0,0 +1,101 @@
+#![doc = include_str!("../README.md")]
+
+#[macro_use]
+extern crate tracing;
+
+mod replay;
+mod transcript;
+
+use core::fmt;
+
+use ironrdp_connector::ConnectorError;
+use ironrdp_session::SessionError;
+
+pub use self::replay::*;
+pub use self::transcript::*;
+
+pub type ReplayResult<T> = Result<T, ReplayError>;
+
+#[non_exhaustive]
+#[derive(Debug)]
+pub enum ReplayErrorKind {
+    Connector(ConnectorError),
+    Session(SessionError),
+    Decode(ironrdp_core::DecodeError),
+    /// The transcript ended while the state machine was expecting a frame.
+    EndOfTranscript,
+    /// The state machine did not behave as recorded at the given entry of the transcript.
+    Diverged {
+        index: usize,
+    },
+    /// The frame sent by the state machine differs from the recorded one, starting at the given offset.
+    Mismatch {
+        index: usize,
+        offset: usize,
+    },
+}
+
+impl fmt::Display for ReplayErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self {
+            ReplayErrorKind::Connector(_) => write!(f, "connector error"),
+            ReplayErrorKind::Session(_) => write!(f, "session error"),
+            ReplayErrorKind::Decode(_) => write!(f, "decode error"),
+            ReplayErrorKind::EndOfTranscript => write!(f, "end of transcript"),
+            ReplayErrorKind::Diverged { index } => write!(f, "diverged from the transcript at entry #{index}"),
+            ReplayErrorKind::Mismatch { index, offset } => {
+                write!(f, "frame differs from entry #{index} at offset {offset}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ReplayErrorKind {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self {
+            ReplayErrorKind::Connector(e) => Some(e),
+            ReplayErrorKind::Session(e) => Some(e),
+            ReplayErrorKind::Decode(e) => Some(e),
+            ReplayErrorKind::EndOfTranscript => None,
+            ReplayErrorKind::Diverged { .. } => None,
+            ReplayErrorKind::Mismatch { .. } => None,
+        }
+    }
+}
+
+pub type ReplayError = ironrdp_error::Error<ReplayErrorKind>;
+
+pub trait ReplayErrorExt {
+    fn connector(error: ConnectorError) -> Self;
+    fn session(error: SessionError) -> Self;
+    fn decode(error: ironrdp_core::DecodeError) -> Self;
+    fn end_of_transcript(context: &'static str) -> Self;
+    fn diverged(context: &'static str, index: usize) -> Self;
+    fn mismatch(index: usize, offset: usize) -> Self;
+}
+
+impl ReplayErrorExt for ReplayError {
+    fn connector(error: ConnectorError) -> Self {
+        Self::new("connector", ReplayErrorKind::Connector(error))
+    }
+
+    fn session(error: SessionError) -> Self {
+        Self::new("session", ReplayErrorKind::Session(error))
+    }
+
+    fn decode(error: ironrdp_core::DecodeError) -> Self {
+        Self::new("decode error", ReplayErrorKind::Decode(error))
+    }
+
+    fn end_of_transcript(context: &'static str) -> Self {
+        Self::new(context, ReplayErrorKind::EndOfTranscript)
+    }
+
+    fn diverged(context: &'static str, index: usize) -> Self {
+        Self::new(context, ReplayErrorKind::Diverged { index })
+    }
+
+    fn mismatch(index: usize, offset: usize) -> Self {
+        Self::new("sent frame", ReplayErrorKind::Mismatch { index, offset })
+    }
+}
@@ -0,0 +1,265 @@
+use core::mem;
+
+use ironrdp_connector::Sequence;
+use ironrdp_core::WriteBuf;
+use ironrdp_session::image::DecodedImage;
+use ironrdp_session::{ActiveStage, ActiveStageOutput};
+
+use crate::{ReplayError, ReplayErrorExt as _, ReplayResult, Role, Transcript, TranscriptEntry};
+
+/// How the frames sent by the state machine under test are checked against the transcript.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputCheck {
+    /// The sent frames must be identical to the recorded ones.
+    #[default]
+    Exact,
+    /// The recorded sent frames are skipped, for state machines whose output is not deterministic.
+    Ignore,
+}
+
+/// Replays a transcript against the state machines of one side of the connection.
+///
+/// The frames sent by the peer are fed to the state machine under test, and the frames sent by the state
+/// machine are checked against the recorded ones, so that any divergence from the recorded behavior is reported
+/// with the index of the entry at which it occurred.
+pub struct Replayer<'a> {
+    entries: &'a [TranscriptEntry],
+    position: usize,
+    role: Role,
+    output_check: OutputCheck,
+    buf: WriteBuf,
+}
+
+impl<'a> Replayer<'a> {
+    /// Creates a replayer for the state machines of `role`.
+    pub fn new(transcript: &'a Transcript, role: Role) -> Self {
+        Self {
+            entries: transcript.entries(),
+            position: 0,
+            role,
+            output_check: OutputCheck::default(),
+            buf: WriteBuf::new(),
+        }
+    }
+
+    #[must_use]
+    pub fn with_output_check(mut self, output_check: OutputCheck) -> Self {
+        self.output_check = output_check;
+        self
+    }
+
+    /// Returns the index of the next entry to be replayed.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    pub fn remaining(&self) -> &'a [TranscriptEntry] {
+        &self.entries[self.position..]
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.position == self.entries.len()
+    }
+
+    /// Returns the next frame received by the state machine under test.
+    pub fn next_received(&mut self) -> ReplayResult<&'a [u8]> {
+        if self.output_check == OutputCheck::Ignore {
+            self.skip_sent();
+        }
+
+        let entries = self.entries;
+        let entry = entries
+            .get(self.position)
+            .ok_or_else(|| ReplayError::end_of_transcript("next received frame"))?;
+
+        if entry.sender == self.role {
+            return Err(ReplayError::diverged(
+                "expected a received frame, but the transcript records a sent one",
+                self.position,
+            ));
+        }
+
+        self.advance();
+
+        Ok(&entry.frame)
+    }
+
+    /// Checks that `frame` is the next frame sent by the state machine under test.
+    pub fn expect_sent(&mut self, frame: &[u8]) -> ReplayResult<()> {
+        match self.entries.get(self.position) {
+            Some(entry) if entry.sender == self.role => {
+                if self.output_check == OutputCheck::Exact && entry.frame != frame {
+                    let offset = entry
+                        .frame
+                        .iter()
+                        .zip(frame)
+                        .position(|(recorded, sent)| recorded != sent)
+                        .unwrap_or_else(|| entry.frame.len().min(frame.len()));
+
+                    return Err(ReplayError::mismatch(self.position, offset));
+                }
+
+                self.advance();
+
+                Ok(())
+            }
+            _ if self.output_check == OutputCheck::Ignore => Ok(()),
+            Some(_) => Err(ReplayError::diverged(
+                "expected a sent frame, but the transcript records a received one",
+                self.position,
+            )),
+            None => Err(ReplayError::end_of_transcript("next sent frame")),
+        }
+    }
+
+    /// Skips the consecutive frames sent by the state machine under test at the current position, returning
+    /// how many were skipped.
+    ///
+    /// This is useful for the frames sent in reaction to an external event, such as a user input.
+    pub fn skip_sent(&mut self) -> usize {
+        let skipped = self
+            .remaining()
+            .iter()
+            .take_while(|entry| entry.sender == self.role)
+            .count();
+        self.position = self
+            .position
+            .checked_add(skipped)
+            .expect("skipped entries are in bounds");
+        skipped
+    }
+
+    /// Performs a single step of `sequence`, feeding it the next received frame if it is waiting for one.
+    pub fn step<S>(&mut self, sequence: &mut S) -> ReplayResult<()>
+    where
+        S: Sequence + ?Sized,
+    {
+        self.buf.clear();
+
+        let written = if let Some(hint) = sequence.next_pdu_hint() {
+            let frame = loop {
+                let index = self.position;
+                let frame = self.next_received()?;
+
+                match hint.find_size(frame).map_err(ReplayError::decode)? {
+                    Some((true, length)) if length == frame.len() => break frame,
+                    Some((false, length)) if length == frame.len() => {
+                        // Same as when reading from the network: the unexpected PDU is lost.
+                        warn!(index, "Skipped a frame not matching the PDU hint");
+                    }
+                    _ => {
+                        return Err(ReplayError::diverged(
+                            "recorded frame does not match the PDU hint of the state machine",
+                            index,
+                        ))
+                    }
+                }
+            };
+
+            trace!(
+                state = sequence.state().name(),
+                length = frame.len(),
+                "Replay received frame"
+            );
+
+            sequence.step(frame, &mut self.buf)
+        } else {
+            sequence.step_no_input(&mut self.buf)
+        }
+        .map_err(ReplayError::connector)?;
+
+        if let Some(length) = written.size() {
+            let buf = mem::take(&mut self.buf);
+            let result = self.expect_sent(&buf.filled()[..length]);
+            self.buf = buf;
+            result?;
+        }
+
+        Ok(())
+    }
+
+    /// Steps `sequence` until `stop` returns true.
+    ///
+    /// An error is returned if `sequence` reaches a terminal state before.
+    pub fn run_until<S>(&mut self, sequence: &mut S, mut stop: impl FnMut(&S) -> bool) -> ReplayResult<()>
+    where
+        S: Sequence + ?Sized,
+    {
+        while !stop(sequence) {
+            if sequence.state().is_terminal() {
+                return Err(ReplayError::diverged(
+                    "sequence reached a terminal state before the stop condition",
+                    self.position,
+                ));
+            }
+
+            self.step(sequence)?;
+        }
+
+        Ok(())
+    }
+
+    /// Steps `sequence` until it reaches a terminal state.
+    pub fn run_to_completion<S>(&mut self, sequence: &mut S) -> ReplayResult<()>
+    where
+        S: Sequence + ?Sized,
+    {
+        while !sequence.state().is_terminal() {
+            self.step(sequence)?;
+        }
+
+        Ok(())
+    }
+
+    /// Processes the received frames with `active_stage`, checking the response frames against the transcript.
+    ///
+    /// Stops at the end of the transcript, before a frame sent spontaneously (e.g.: user input), or after
+    /// a frame deactivating or terminating the session. The outputs other than the response frames are returned,
+    /// so that a [`ActiveStageOutput::DeactivateAll`] can be handled with [`Replayer::run_to_completion`].
+    pub fn process_session(
+        &mut self,
+        active_stage: &mut ActiveStage,
+        image: &mut DecodedImage,
+    ) -> ReplayResult<Vec<ActiveStageOutput>> {
+        let mut outputs = Vec::new();
+
+        while let Some(entry) = self.entries.get(self.position) {
+            if entry.sender == self.role {
+                break;
+            }
+
+            let frame = self.next_received()?;
+
+            let action = ironrdp_pdu::find_size(frame)
+                .map_err(ReplayError::decode)?
+                .map(|info| info.action)
+                .ok_or_else(|| ReplayError::diverged("incomplete frame", self.position.saturating_sub(1)))?;
+
+            let mut stop = false;
+
+            for output in active_stage
+                .process(image, action, frame)
+                .map_err(ReplayError::session)?
+            {
+                match output {
+                    ActiveStageOutput::ResponseFrame(frame) => self.expect_sent(&frame)?,
+                    ActiveStageOutput::DeactivateAll(_) | ActiveStageOutput::Terminate(_) => {
+                        stop = true;
+                        outputs.push(output);
+                    }
+                    output => outputs.push(output),
+                }
+            }
+
+            if stop {
+                break;
+            }
+        }
+
+        Ok(outputs)
+    }
+
+    fn advance(&mut self) {
+        self.position = self.position.checked_add(1).expect("position is in bounds");
+    }
+}
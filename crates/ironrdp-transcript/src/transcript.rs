@@ -0,0 +1,180 @@
+use core::fmt;
+use core::str::FromStr;
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// Side of a connection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    Client,
+    Server,
+}
+
+impl Role {
+    #[must_use]
+    pub fn peer(self) -> Self {
+        match self {
+            Role::Client => Role::Server,
+            Role::Server => Role::Client,
+        }
+    }
+}
+
+/// A frame of a transcript.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TranscriptEntry {
+    /// Side of the connection which sent the frame.
+    pub sender: Role,
+    pub frame: Vec<u8>,
+}
+
+/// Frames exchanged over a connection, in the order they were sent or received.
+///
+/// A transcript is formatted as text with [`fmt::Display`], and parsed back with [`FromStr`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Transcript {
+    entries: Vec<TranscriptEntry>,
+}
+
+impl Transcript {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, sender: Role, frame: impl Into<Vec<u8>>) {
+        self.entries.push(TranscriptEntry {
+            sender,
+            frame: frame.into(),
+        });
+    }
+
+    pub fn entries(&self) -> &[TranscriptEntry] {
+        &self.entries
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl fmt::Display for Transcript {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for entry in &self.entries {
+            let prefix = match entry.sender {
+                Role::Client => 'C',
+                Role::Server => 'S',
+            };
+
+            writeln!(f, "{prefix} {}", hex::encode(&entry.frame))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for Transcript {
+    type Err = ParseTranscriptError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut transcript = Transcript::new();
+
+        for (line_number, line) in (1..).zip(s.lines()) {
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let error = |reason| ParseTranscriptError { line_number, reason };
+
+            let (prefix, frame) = line
+                .split_once(char::is_whitespace)
+                .ok_or_else(|| error("missing frame"))?;
+
+            let sender = match prefix {
+                "C" => Role::Client,
+                "S" => Role::Server,
+                _ => return Err(error("invalid sender, expected `C` or `S`")),
+            };
+
+            // Whitespaces are allowed inside the frame, for readability.
+            let frame: String = frame.chars().filter(|c| !c.is_whitespace()).collect();
+            let frame = hex::decode(frame).map_err(|_| error("invalid hexadecimal frame"))?;
+
+            transcript.push(sender, frame);
+        }
+
+        Ok(transcript)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseTranscriptError {
+    /// Line number, starting at 1.
+    pub line_number: usize,
+    pub reason: &'static str,
+}
+
+impl fmt::Display for ParseTranscriptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line_number, self.reason)
+    }
+}
+
+impl std::error::Error for ParseTranscriptError {}
+
+/// Records the frames received and sent by one side of a connection.
+///
+/// The recorder is cheaply clonable, and all the clones record into the same transcript. This way, a clone
+/// can be handed over to the connection while the original is kept for retrieving the transcript.
+#[derive(Debug, Clone)]
+pub struct TranscriptRecorder {
+    role: Role,
+    transcript: Arc<Mutex<Transcript>>,
+}
+
+impl TranscriptRecorder {
+    /// Creates a recorder for the frames exchanged by `role`.
+    pub fn new(role: Role) -> Self {
+        Self {
+            role,
+            transcript: Arc::new(Mutex::new(Transcript::new())),
+        }
+    }
+
+    pub fn record_received(&self, frame: &[u8]) {
+        self.record(self.role.peer(), frame);
+    }
+
+    pub fn record_sent(&self, frame: &[u8]) {
+        self.record(self.role, frame);
+    }
+
+    /// Returns the frames recorded so far.
+    pub fn transcript(&self) -> Transcript {
+        self.transcript.lock().unwrap_or_else(PoisonError::into_inner).clone()
+    }
+
+    fn record(&self, sender: Role, frame: &[u8]) {
+        self.transcript
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(sender, frame);
+    }
+}
+
+#[cfg(feature = "capture")]
+impl ironrdp_async::CaptureSink for TranscriptRecorder {
+    fn received(&mut self, frame: &[u8]) -> std::io::Result<()> {
+        self.record_received(frame);
+        Ok(())
+    }
+
+    fn sent(&mut self, frame: &[u8]) -> std::io::Result<()> {
+        self.record_sent(frame);
+        Ok(())
+    }
+}
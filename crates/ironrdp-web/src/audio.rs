@@ -0,0 +1,325 @@
+//! Remote audio playback using the Web Audio API
+//!
+//! The rdpsnd channel processor requires a `Send` handler, but browser objects can’t leave the thread they were
+//! created on. [`WebAudioSink`] is therefore only forwarding the audio messages to a [`WebAudioPlayer`] task running
+//! on the session thread, which owns the `AudioContext`.
+//!
+//! Samples are played by an `AudioWorklet` processor implementing a jitter buffer: playback only starts once enough
+//! audio is buffered to absorb the network jitter, and starts over after an underrun. When too much audio is
+//! buffered, the oldest samples are dropped to keep the latency bounded.
+
+use std::borrow::Cow;
+use std::time::Duration;
+
+use anyhow::Context as _;
+use futures_channel::mpsc;
+use futures_util::StreamExt as _;
+use ironrdp::rdpsnd::client::RdpsndClientHandler;
+use ironrdp::rdpsnd::pdu::{AudioFormat, PitchPdu, VolumePdu, WaveFormat};
+use js_sys::{Array, Float32Array, Object, Reflect};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::{spawn_local, JsFuture};
+use web_sys::{
+    AudioContext, AudioContextOptions, AudioWorkletNode, AudioWorkletNodeOptions, Blob, BlobPropertyBag, GainNode, Url,
+};
+
+const PROCESSOR_NAME: &str = "ironrdp-audio-processor";
+
+const PROCESSOR_SOURCE: &str = r#"
+class IronRdpAudioProcessor extends AudioWorkletProcessor {
+    constructor(options) {
+        super();
+        const { channels, targetFrames, maxFrames } = options.processorOptions;
+        this.channels = channels;
+        this.targetFrames = targetFrames;
+        this.maxFrames = maxFrames;
+        this.queue = [];
+        this.offset = 0;
+        this.bufferedFrames = 0;
+        this.playing = false;
+        this.port.onmessage = (event) => this.enqueue(event.data);
+    }
+
+    enqueue(samples) {
+        this.queue.push(samples);
+        this.bufferedFrames += samples.length / this.channels;
+
+        // Drop the oldest samples instead of letting the latency grow.
+        while (this.bufferedFrames > this.maxFrames && this.queue.length > 1) {
+            const dropped = this.queue.shift();
+            this.bufferedFrames -= (dropped.length - this.offset) / this.channels;
+            this.offset = 0;
+        }
+    }
+
+    process(inputs, outputs) {
+        const output = outputs[0];
+
+        if (!this.playing) {
+            if (this.bufferedFrames < this.targetFrames) {
+                // Outputs are zero-filled, silence is played until enough samples are buffered.
+                return true;
+            }
+            this.playing = true;
+        }
+
+        for (let frame = 0; frame < output[0].length; frame++) {
+            if (this.queue.length === 0) {
+                // Underrun, buffer again before resuming the playback.
+                this.playing = false;
+                break;
+            }
+
+            const samples = this.queue[0];
+            for (let channel = 0; channel < output.length; channel++) {
+                output[channel][frame] = samples[this.offset + channel];
+            }
+
+            this.offset += this.channels;
+            this.bufferedFrames -= 1;
+
+            if (this.offset >= samples.length) {
+                this.queue.shift();
+                this.offset = 0;
+            }
+        }
+
+        return true;
+    }
+}
+
+registerProcessor("ironrdp-audio-processor", IronRdpAudioProcessor);
+"#;
+
+/// The buffered audio is never allowed to exceed this multiple of the jitter buffer duration
+const MAX_BUFFERED_FACTOR: u32 = 4;
+
+#[derive(Debug)]
+enum AudioMessage {
+    Wave { format: AudioFormat, data: Vec<u8> },
+    Volume(VolumePdu),
+    Close,
+}
+
+/// rdpsnd handler forwarding the audio to the [`WebAudioPlayer`]
+#[derive(Debug)]
+pub(crate) struct WebAudioSink {
+    tx: mpsc::UnboundedSender<AudioMessage>,
+}
+
+impl WebAudioSink {
+    fn send(&self, message: AudioMessage) {
+        if self.tx.unbounded_send(message).is_err() {
+            debug!("Audio player is gone, dropping audio message");
+        }
+    }
+}
+
+impl RdpsndClientHandler for WebAudioSink {
+    fn wave(&mut self, format: &AudioFormat, _ts: u32, data: Cow<'_, [u8]>) {
+        self.send(AudioMessage::Wave {
+            format: format.clone(),
+            data: data.into_owned(),
+        });
+    }
+
+    fn set_volume(&mut self, volume: VolumePdu) {
+        self.send(AudioMessage::Volume(volume));
+    }
+
+    fn set_pitch(&mut self, _pitch: PitchPdu) {}
+
+    fn close(&mut self) {
+        self.send(AudioMessage::Close);
+    }
+}
+
+/// Plays the audio received by the [`WebAudioSink`]
+pub(crate) struct WebAudioPlayer {
+    jitter_buffer: Duration,
+    volume: f32,
+    output: Option<AudioOutput>,
+    /// Formats which can’t be played, to only report them once
+    unsupported_formats: Vec<AudioFormat>,
+}
+
+impl WebAudioPlayer {
+    /// Spawns the player on the current thread, and returns the associated sink
+    pub(crate) fn spawn(jitter_buffer: Duration) -> WebAudioSink {
+        let (tx, rx) = mpsc::unbounded();
+
+        let player = Self {
+            jitter_buffer,
+            volume: 1.0,
+            output: None,
+            unsupported_formats: Vec::new(),
+        };
+
+        spawn_local(player.run(rx));
+
+        WebAudioSink { tx }
+    }
+
+    async fn run(mut self, mut rx: mpsc::UnboundedReceiver<AudioMessage>) {
+        while let Some(message) = rx.next().await {
+            match message {
+                AudioMessage::Wave { format, data } => self.play(format, &data).await,
+                AudioMessage::Volume(volume) => {
+                    self.volume = (f32::from(volume.volume_left) + f32::from(volume.volume_right)) / 2.0 / 65535.0;
+
+                    if let Some(output) = &self.output {
+                        output.gain.gain().set_value(self.volume);
+                    }
+                }
+                AudioMessage::Close => self.close(),
+            }
+        }
+
+        self.close();
+        debug!("Audio player stopped");
+    }
+
+    async fn play(&mut self, format: AudioFormat, data: &[u8]) {
+        if self.unsupported_formats.contains(&format) {
+            return;
+        }
+
+        if self.output.as_ref().map(|output| &output.format) != Some(&format) {
+            self.close();
+
+            match AudioOutput::open(format.clone(), self.jitter_buffer, self.volume).await {
+                Ok(output) => self.output = Some(output),
+                Err(error) => {
+                    error!(?format, "Failed to open the audio output: {error:#}");
+                    self.unsupported_formats.push(format);
+                    return;
+                }
+            }
+        }
+
+        if let Some(output) = &self.output {
+            if let Err(error) = output.write(data) {
+                error!("Failed to play audio: {error:?}");
+            }
+        }
+    }
+
+    fn close(&mut self) {
+        if let Some(output) = self.output.take() {
+            output.close();
+        }
+    }
+}
+
+struct AudioOutput {
+    format: AudioFormat,
+    context: AudioContext,
+    node: AudioWorkletNode,
+    gain: GainNode,
+}
+
+impl AudioOutput {
+    async fn open(format: AudioFormat, jitter_buffer: Duration, volume: f32) -> anyhow::Result<Self> {
+        if format.format != WaveFormat::PCM {
+            anyhow::bail!("only PCM formats supported");
+        }
+
+        if !matches!(format.bits_per_sample, 8 | 16) {
+            anyhow::bail!("only PCM 8/16 bits formats supported");
+        }
+
+        let context_options = AudioContextOptions::new();
+        // Let the browser resample the audio to the output device rate.
+        context_options.set_sample_rate(format.n_samples_per_sec as f32);
+
+        let context = AudioContext::new_with_context_options(&context_options).map_err(js_error)?;
+
+        let url = processor_url()?;
+        let added = JsFuture::from(
+            context
+                .audio_worklet()
+                .map_err(js_error)?
+                .add_module(&url)
+                .map_err(js_error)?,
+        )
+        .await;
+        let _ = Url::revoke_object_url(&url);
+        added.map_err(js_error).context("failed to load the audio processor")?;
+
+        let target_frames = jitter_buffer.as_secs_f64() * f64::from(format.n_samples_per_sec);
+
+        let processor_options = Object::new();
+        Reflect::set(&processor_options, &"channels".into(), &format.n_channels.into()).map_err(js_error)?;
+        Reflect::set(&processor_options, &"targetFrames".into(), &target_frames.into()).map_err(js_error)?;
+        Reflect::set(
+            &processor_options,
+            &"maxFrames".into(),
+            &(target_frames * f64::from(MAX_BUFFERED_FACTOR)).into(),
+        )
+        .map_err(js_error)?;
+
+        let node_options = AudioWorkletNodeOptions::new();
+        node_options.set_number_of_inputs(0);
+        node_options.set_output_channel_count(&Array::of1(&format.n_channels.into()));
+        node_options.set_processor_options(Some(&processor_options));
+
+        let node = AudioWorkletNode::new_with_options(&context, PROCESSOR_NAME, &node_options).map_err(js_error)?;
+
+        let gain = context.create_gain().map_err(js_error)?;
+        gain.gain().set_value(volume);
+
+        node.connect_with_audio_node(&gain).map_err(js_error)?;
+        gain.connect_with_audio_node(&context.destination()).map_err(js_error)?;
+
+        // Browsers may keep the context suspended until the user interacts with the page.
+        if let Ok(resumed) = context.resume() {
+            let _ = JsFuture::from(resumed).await;
+        }
+
+        debug!(?format, state = ?context.state(), "Audio output opened");
+
+        Ok(Self {
+            format,
+            context,
+            node,
+            gain,
+        })
+    }
+
+    fn write(&self, data: &[u8]) -> Result<(), JsValue> {
+        let samples: Vec<f32> = match self.format.bits_per_sample {
+            8 => data.iter().map(|&sample| (f32::from(sample) - 128.0) / 128.0).collect(),
+            _ => data
+                .chunks_exact(2)
+                .map(|sample| f32::from(i16::from_le_bytes([sample[0], sample[1]])) / 32768.0)
+                .collect(),
+        };
+
+        self.node.port()?.post_message(&Float32Array::from(samples.as_slice()))
+    }
+
+    fn close(self) {
+        let _ = self.node.disconnect();
+        let _ = self.gain.disconnect();
+
+        if let Ok(closed) = self.context.close() {
+            spawn_local(async move {
+                let _ = JsFuture::from(closed).await;
+            });
+        }
+    }
+}
+
+fn processor_url() -> anyhow::Result<String> {
+    let options = BlobPropertyBag::new();
+    options.set_type("text/javascript");
+
+    let blob =
+        Blob::new_with_str_sequence_and_options(&Array::of1(&PROCESSOR_SOURCE.into()), &options).map_err(js_error)?;
+
+    Url::create_object_url_with_blob(&blob).map_err(js_error)
+}
+
+fn js_error(error: JsValue) -> anyhow::Error {
+    anyhow::anyhow!("{error:?}")
+}
@@ -2,7 +2,103 @@ use std::num::NonZeroU32;
 
 use ironrdp::pdu::geometry::{InclusiveRectangle, Rectangle as _};
 use softbuffer::{NoDisplayHandle, NoWindowHandle};
-use web_sys::HtmlCanvasElement;
+use wasm_bindgen::prelude::*;
+use web_sys::{HtmlCanvasElement, OffscreenCanvas};
+
+use crate::webgl::WebGlCanvas;
+use crate::worker::FrameTransport;
+
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RendererKind {
+    /// Copies the updated regions to a 2D canvas
+    Canvas2d,
+    /// Uploads the updated regions to a WebGL 2 texture, falling back to the 2D canvas if WebGL 2 is not available
+    WebGl,
+}
+
+/// Canvas the desktop is rendered to
+///
+/// An `OffscreenCanvas` is typically obtained using `transferControlToOffscreen`, so the rendering
+/// happens in a worker while the canvas is displayed by the main thread.
+#[derive(Clone)]
+pub(crate) enum RenderCanvas {
+    Element(HtmlCanvasElement),
+    Offscreen(OffscreenCanvas),
+}
+
+impl RenderCanvas {
+    pub(crate) fn set_size(&self, width: u32, height: u32) {
+        match self {
+            RenderCanvas::Element(canvas) => {
+                canvas.set_width(width);
+                canvas.set_height(height);
+            }
+            RenderCanvas::Offscreen(canvas) => {
+                canvas.set_width(width);
+                canvas.set_height(height);
+            }
+        }
+    }
+
+    pub(crate) fn get_context(&self, context_id: &str) -> anyhow::Result<Option<js_sys::Object>> {
+        match self {
+            RenderCanvas::Element(canvas) => canvas.get_context(context_id),
+            RenderCanvas::Offscreen(canvas) => canvas.get_context(context_id),
+        }
+        .map_err(|e| anyhow::anyhow!("failed to get {context_id} context: {e:?}"))
+    }
+}
+
+/// Where the desktop is rendered
+#[derive(Clone)]
+pub(crate) enum RenderTarget {
+    Canvas(RenderCanvas),
+    /// Updated regions are posted to the main thread, when running in a worker without `OffscreenCanvas`
+    MainThread,
+}
+
+pub(crate) enum Renderer {
+    Canvas2d(Canvas),
+    WebGl(WebGlCanvas),
+    MainThread(FrameTransport),
+}
+
+impl Renderer {
+    pub(crate) fn new(target: RenderTarget, kind: RendererKind, width: u32, height: u32) -> anyhow::Result<Self> {
+        let render_canvas = match target {
+            RenderTarget::Canvas(render_canvas) => render_canvas,
+            RenderTarget::MainThread => return FrameTransport::new(width, height).map(Self::MainThread),
+        };
+
+        if kind == RendererKind::WebGl {
+            match WebGlCanvas::new(&render_canvas, width, height) {
+                Ok(canvas) => return Ok(Self::WebGl(canvas)),
+                Err(error) => warn!(%error, "Falling back to the 2D canvas renderer"),
+            }
+        }
+
+        Canvas::new(render_canvas, width, height).map(Self::Canvas2d)
+    }
+
+    pub(crate) fn draw(&mut self, buffer: &[u8], region: InclusiveRectangle) -> anyhow::Result<()> {
+        match self {
+            Renderer::Canvas2d(canvas) => canvas.draw(buffer, region),
+            Renderer::WebGl(canvas) => canvas.draw(buffer, region),
+            Renderer::MainThread(transport) => transport.draw(buffer, region),
+        }
+    }
+
+    /// Displays the regions drawn since the last call
+    pub(crate) fn present(&mut self) {
+        match self {
+            // Regions are presented as they are drawn.
+            Renderer::Canvas2d(_) => {}
+            Renderer::WebGl(canvas) => canvas.present(),
+            Renderer::MainThread(transport) => transport.present(),
+        }
+    }
+}
 
 pub(crate) struct Canvas {
     width: u32,
@@ -10,19 +106,22 @@ pub(crate) struct Canvas {
 }
 
 impl Canvas {
-    pub(crate) fn new(render_canvas: HtmlCanvasElement, width: u32, height: u32) -> anyhow::Result<Self> {
-        render_canvas.set_width(width);
-        render_canvas.set_height(height);
+    pub(crate) fn new(render_canvas: RenderCanvas, width: u32, height: u32) -> anyhow::Result<Self> {
+        render_canvas.set_size(width, height);
 
         #[cfg(target_arch = "wasm32")]
         let mut surface = {
             use softbuffer::SurfaceExtWeb as _;
-            softbuffer::Surface::from_canvas(render_canvas).expect("surface")
+            match render_canvas {
+                RenderCanvas::Element(canvas) => softbuffer::Surface::from_canvas(canvas),
+                RenderCanvas::Offscreen(canvas) => softbuffer::Surface::from_offscreen_canvas(canvas),
+            }
+            .expect("surface")
         };
 
         #[cfg(not(target_arch = "wasm32"))]
         let mut surface = {
-            fn stub(_: HtmlCanvasElement) -> softbuffer::Surface<NoDisplayHandle, NoWindowHandle> {
+            fn stub(_: RenderCanvas) -> softbuffer::Surface<NoDisplayHandle, NoWindowHandle> {
                 unimplemented!()
             }
 
@@ -0,0 +1,294 @@
+//! Clipboard integration based on the browser [Async Clipboard API]
+//!
+//! Browsers only grant access to the system clipboard to a document having the focus, and reading it is usually
+//! subject to the user permission ("clipboard-read"). Because of that:
+//!
+//! - The local clipboard is read each time the document gains the focus, which is typically when the user comes back
+//!   from another application where something was copied.
+//! - Remote clipboard updates received while the document doesn't have the focus are kept, and only the latest one is
+//!   written once the focus is regained.
+//!
+//! [Async Clipboard API]: https://developer.mozilla.org/en-US/docs/Web/API/Clipboard_API
+
+use core::cell::RefCell;
+use std::rc::Rc;
+
+use anyhow::Context as _;
+use js_sys::{Array, Function, Object, Promise, Reflect, Uint8Array};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::{spawn_local, JsFuture};
+use web_sys::{Blob, BlobPropertyBag, Window};
+
+use super::transaction::{ClipboardContent, ClipboardContentValue};
+use super::{
+    ClipboardTransaction, WasmClipboardBackendMessage, WasmClipboardMessageProxy, MIME_HTML, MIME_PNG, MIME_TEXT,
+};
+
+/// MIME types read from the local clipboard, other types are ignored
+const SUPPORTED_MIME_TYPES: &[&str] = &[MIME_TEXT, MIME_HTML, MIME_PNG];
+
+pub(crate) struct AsyncClipboard {
+    state: Rc<RefCell<State>>,
+    window: Window,
+    on_focus: Closure<dyn FnMut()>,
+}
+
+struct State {
+    proxy: WasmClipboardMessageProxy,
+    /// Remote clipboard content to write once the document has the focus
+    pending_write: Option<ClipboardTransaction>,
+    /// Last content read from or written to the local clipboard, to avoid announcing the same content again
+    last_content: Option<ClipboardTransaction>,
+    /// Whether the user was already warned that the clipboard access is denied
+    denial_reported: bool,
+}
+
+impl AsyncClipboard {
+    /// Fails if the Async Clipboard API is not available, e.g.: outside of a secure context or in a worker
+    pub(crate) fn new(proxy: WasmClipboardMessageProxy) -> anyhow::Result<Self> {
+        let window = web_sys::window().context("no window available")?;
+        let clipboard = Reflect::get(&window.navigator(), &"clipboard".into())
+            .ok()
+            .filter(|clipboard| !clipboard.is_undefined())
+            .context("Async Clipboard API is not available")?;
+
+        for method in ["read", "readText", "write"] {
+            if !Reflect::get(&clipboard, &method.into()).is_ok_and(|value| value.is_function()) {
+                anyhow::bail!("Async Clipboard API does not support `{method}`");
+            }
+        }
+
+        let state = Rc::new(RefCell::new(State {
+            proxy,
+            pending_write: None,
+            last_content: None,
+            denial_reported: false,
+        }));
+
+        let on_focus = Closure::<dyn FnMut()>::new({
+            let state = Rc::clone(&state);
+            move || {
+                let pending_write = state.borrow_mut().pending_write.take();
+                if let Some(transaction) = pending_write {
+                    spawn_local(write(Rc::clone(&state), transaction));
+                }
+
+                spawn_local(read(Rc::clone(&state), false));
+            }
+        });
+
+        window
+            .add_event_listener_with_callback("focus", on_focus.as_ref().unchecked_ref())
+            .map_err(|e| anyhow::anyhow!("failed to register the focus listener: {e:?}"))?;
+
+        Ok(Self {
+            state,
+            window,
+            on_focus,
+        })
+    }
+
+    /// Writes the remote clipboard content to the local clipboard, deferring it until the document has the focus
+    pub(crate) fn write(&self, transaction: ClipboardTransaction) {
+        if self.has_focus() {
+            spawn_local(write(Rc::clone(&self.state), transaction));
+        } else {
+            debug!("Document doesn't have the focus, deferring clipboard write");
+            self.state.borrow_mut().pending_write = Some(transaction);
+        }
+    }
+
+    /// Announces the local clipboard content to the remote
+    ///
+    /// When the local clipboard can't be read, an empty format list is announced instead, as the remote expects a
+    /// response.
+    pub(crate) fn announce_local_content(&self) {
+        if self.has_focus() {
+            spawn_local(read(Rc::clone(&self.state), true));
+        } else {
+            debug!("Document doesn't have the focus, announcing an empty clipboard");
+            self.state
+                .borrow()
+                .proxy
+                .send_backend_message(WasmClipboardBackendMessage::LocalClipboardChanged(
+                    ClipboardTransaction::new(),
+                ));
+        }
+    }
+
+    fn has_focus(&self) -> bool {
+        self.window
+            .document()
+            .and_then(|document| document.has_focus().ok())
+            .unwrap_or(false)
+    }
+}
+
+impl Drop for AsyncClipboard {
+    fn drop(&mut self) {
+        let _ = self
+            .window
+            .remove_event_listener_with_callback("focus", self.on_focus.as_ref().unchecked_ref());
+    }
+}
+
+async fn read(state: Rc<RefCell<State>>, force: bool) {
+    let transaction = match read_clipboard().await {
+        Ok(transaction) => transaction,
+        Err(error) => {
+            report_error(&state, "read", &error);
+
+            if !force {
+                return;
+            }
+
+            ClipboardTransaction::new()
+        }
+    };
+
+    let mut state = state.borrow_mut();
+
+    if !force && state.last_content.as_ref() == Some(&transaction) {
+        return;
+    }
+
+    state.last_content = Some(transaction.clone());
+    state
+        .proxy
+        .send_backend_message(WasmClipboardBackendMessage::LocalClipboardChanged(transaction));
+}
+
+async fn write(state: Rc<RefCell<State>>, transaction: ClipboardTransaction) {
+    match write_clipboard(&transaction).await {
+        Ok(()) => {
+            // Reading back our own write on the next focus must not be announced to the remote.
+            state.borrow_mut().last_content = Some(transaction);
+        }
+        Err(error) => report_error(&state, "write", &error),
+    }
+}
+
+fn report_error(state: &RefCell<State>, operation: &str, error: &JsValue) {
+    let name = Reflect::get(error, &"name".into())
+        .ok()
+        .and_then(|name| name.as_string());
+
+    if name.as_deref() == Some("NotAllowedError") {
+        let mut state = state.borrow_mut();
+
+        if state.denial_reported {
+            debug!(operation, "Clipboard access denied");
+        } else {
+            warn!(
+                operation,
+                "Clipboard access denied, the clipboard permissions must be granted to the page"
+            );
+            state.denial_reported = true;
+        }
+    } else {
+        error!(operation, "Clipboard access failed: {error:?}");
+    }
+}
+
+async fn read_clipboard() -> Result<ClipboardTransaction, JsValue> {
+    let clipboard = clipboard()?;
+
+    // Some browsers (e.g.: Firefox) only allow to read the text without prompting the user.
+    if permission_state("clipboard-read").await.as_deref() == Some("denied") {
+        let text = JsFuture::from(call_method(&clipboard, "readText", &[])?).await?;
+        return Ok(text
+            .as_string()
+            .filter(|text| !text.is_empty())
+            .map(|text| ClipboardContent::new_text(MIME_TEXT, &text))
+            .into_iter()
+            .collect());
+    }
+
+    let items = JsFuture::from(call_method(&clipboard, "read", &[])?).await?;
+    let mut transaction = ClipboardTransaction::new();
+
+    for item in Array::from(&items).iter() {
+        let types = Array::from(&Reflect::get(&item, &"types".into())?);
+
+        for mime_type in types.iter().filter_map(|mime_type| mime_type.as_string()) {
+            if !SUPPORTED_MIME_TYPES.contains(&mime_type.as_str()) {
+                continue;
+            }
+
+            let blob: Blob = JsFuture::from(call_method(&item, "getType", &[mime_type.as_str().into()])?)
+                .await?
+                .dyn_into()?;
+
+            let content = if mime_type == MIME_PNG {
+                let buffer = JsFuture::from(blob.array_buffer()).await?;
+                ClipboardContent::new_binary(&mime_type, &Uint8Array::new(&buffer).to_vec())
+            } else {
+                let text = JsFuture::from(blob.text()).await?.as_string().unwrap_or_default();
+                ClipboardContent::new_text(&mime_type, &text)
+            };
+
+            transaction.add_content(content);
+        }
+    }
+
+    Ok(transaction)
+}
+
+async fn write_clipboard(transaction: &ClipboardTransaction) -> Result<(), JsValue> {
+    let clipboard = clipboard()?;
+
+    let blobs = Object::new();
+    for content in transaction.contents() {
+        let options = BlobPropertyBag::new();
+        options.set_type(content.mime_type());
+
+        let blob = match content.value() {
+            ClipboardContentValue::Text(text) => {
+                Blob::new_with_str_sequence_and_options(&Array::of1(&text.into()), &options)?
+            }
+            ClipboardContentValue::Binary(binary) => Blob::new_with_u8_array_sequence_and_options(
+                &Array::of1(&Uint8Array::from(binary.as_slice())),
+                &options,
+            )?,
+        };
+
+        Reflect::set(&blobs, &content.mime_type().into(), &blob)?;
+    }
+
+    let clipboard_item: Function = Reflect::get(&js_sys::global(), &"ClipboardItem".into())?.dyn_into()?;
+    let item = Reflect::construct(&clipboard_item, &Array::of1(&blobs))?;
+
+    JsFuture::from(call_method(&clipboard, "write", &[Array::of1(&item).into()])?).await?;
+
+    Ok(())
+}
+
+/// Returns the state of the permission (`granted`, `denied` or `prompt`), if the browser knows about it
+async fn permission_state(name: &str) -> Option<String> {
+    let permissions = Reflect::get(&js_sys::global(), &"navigator".into())
+        .and_then(|navigator| Reflect::get(&navigator, &"permissions".into()))
+        .ok()
+        .filter(|permissions| !permissions.is_undefined())?;
+
+    let descriptor = Object::new();
+    Reflect::set(&descriptor, &"name".into(), &name.into()).ok()?;
+
+    // Unknown permission names are rejected with a `TypeError`.
+    let status = JsFuture::from(call_method(&permissions, "query", &[descriptor.into()]).ok()?)
+        .await
+        .ok()?;
+
+    Reflect::get(&status, &"state".into()).ok()?.as_string()
+}
+
+fn clipboard() -> Result<JsValue, JsValue> {
+    let navigator = Reflect::get(&js_sys::global(), &"navigator".into())?;
+    Reflect::get(&navigator, &"clipboard".into())
+}
+
+fn call_method(target: &JsValue, name: &str, args: &[JsValue]) -> Result<Promise, JsValue> {
+    let method: Function = Reflect::get(target, &name.into())?.dyn_into()?;
+    method
+        .apply(target, &args.iter().collect::<Array>())?
+        .dyn_into::<Promise>()
+}
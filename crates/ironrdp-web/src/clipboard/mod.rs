@@ -10,7 +10,11 @@
 //! target application in which the user performs the paste operation, either one could be
 //! requested: when pasting into notepad, which does not support "text/html", "text/plain"
 //! will be requested, and when pasting into WordPad, "text/html" will be requested.
+//!
+//! The system clipboard is either accessed by the JS code, through callbacks (see [`JsClipboardCallbacks`]), or
+//! directly by the backend, using the browser Async Clipboard API (see [`AsyncClipboard`]).
 
+mod async_clipboard;
 mod transaction;
 
 use std::collections::HashMap;
@@ -27,8 +31,11 @@ use ironrdp_core::{impl_as_any, IntoOwned};
 use transaction::{ClipboardContent, ClipboardContentValue};
 use wasm_bindgen::prelude::*;
 
+use crate::events::{EventDispatcher, SessionEvent, SessionEventType};
 use crate::session::RdpInputEvent;
 
+#[rustfmt::skip]
+pub(crate) use async_clipboard::AsyncClipboard;
 #[rustfmt::skip]
 pub(crate) use transaction::ClipboardTransaction;
 
@@ -123,7 +130,16 @@ pub(crate) struct WasmClipboard {
     remote_formats_to_read: Vec<ClipboardFormatId>,
 
     proxy: WasmClipboardMessageProxy,
-    js_callbacks: JsClipboardCallbacks,
+    integration: ClipboardIntegration,
+    events: EventDispatcher,
+}
+
+/// How the system clipboard is accessed
+pub(crate) enum ClipboardIntegration {
+    /// The JS code accesses the clipboard, and is notified using callbacks
+    Callbacks(JsClipboardCallbacks),
+    /// The backend accesses the clipboard using the browser Async Clipboard API
+    AsyncClipboard(AsyncClipboard),
 }
 
 /// Callbacks, required to interact with JS code from within the backend.
@@ -134,12 +150,17 @@ pub(crate) struct JsClipboardCallbacks {
 }
 
 impl WasmClipboard {
-    pub(crate) fn new(message_proxy: WasmClipboardMessageProxy, js_callbacks: JsClipboardCallbacks) -> Self {
+    pub(crate) fn new(
+        message_proxy: WasmClipboardMessageProxy,
+        integration: ClipboardIntegration,
+        events: EventDispatcher,
+    ) -> Self {
         Self {
             local_clipboard: None,
             remote_clipboard: ClipboardTransaction::new(),
             proxy: message_proxy,
-            js_callbacks,
+            integration,
+            events,
 
             remote_mapping: HashMap::new(),
             remote_formats_to_read: Vec::new(),
@@ -433,11 +454,21 @@ impl WasmClipboard {
             if transaction.is_empty() {
                 return Ok(());
             }
+
+            if self.events.has_subscribers(SessionEventType::Clipboard) {
+                self.events.emit(SessionEvent::clipboard_changed(transaction.clone()));
+            }
+
             // Set clipboard when all formats were read
-            self.js_callbacks
-                .on_remote_clipboard_changed
-                .call1(&JsValue::NULL, &JsValue::from(transaction))
-                .expect("Failed to call JS callback");
+            match &self.integration {
+                ClipboardIntegration::Callbacks(js_callbacks) => {
+                    js_callbacks
+                        .on_remote_clipboard_changed
+                        .call1(&JsValue::NULL, &JsValue::from(transaction))
+                        .expect("Failed to call JS callback");
+                }
+                ClipboardIntegration::AsyncClipboard(async_clipboard) => async_clipboard.write(transaction),
+            }
         }
 
         Ok(())
@@ -495,20 +526,29 @@ impl WasmClipboard {
                 }
             }
             WasmClipboardBackendMessage::FormatListReceived => {
-                if let Some(callback) = self.js_callbacks.on_remote_received_format_list.as_mut() {
+                if let ClipboardIntegration::Callbacks(JsClipboardCallbacks {
+                    on_remote_received_format_list: Some(callback),
+                    ..
+                }) = &self.integration
+                {
                     callback.call0(&JsValue::NULL).expect("Failed to call JS callback");
                 }
             }
-            WasmClipboardBackendMessage::ForceClipboardUpdate => {
-                if let Some(callback) = self.js_callbacks.on_force_clipboard_update.as_mut() {
+            WasmClipboardBackendMessage::ForceClipboardUpdate => match &self.integration {
+                ClipboardIntegration::Callbacks(JsClipboardCallbacks {
+                    on_force_clipboard_update: Some(callback),
+                    ..
+                }) => {
                     callback.call0(&JsValue::NULL).expect("Failed to call JS callback");
-                } else {
+                }
+                ClipboardIntegration::AsyncClipboard(async_clipboard) => async_clipboard.announce_local_content(),
+                ClipboardIntegration::Callbacks(_) => {
                     // If no initial clipboard callback was set, send empty format list instead
                     return self.process_event(WasmClipboardBackendMessage::LocalClipboardChanged(
                         ClipboardTransaction::new(),
                     ));
                 }
-            }
+            },
         };
 
         Ok(())
@@ -517,7 +557,7 @@ impl WasmClipboard {
 
 /// CLIPRDR backend implementation for web. This object could be instantiated via [`WasmClipboard`]
 /// to pass it to CLIPRDR SVC constructor.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub(crate) struct WasmClipboardBackend {
     proxy: WasmClipboardMessageProxy,
 }
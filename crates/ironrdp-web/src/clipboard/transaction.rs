@@ -2,7 +2,7 @@ use wasm_bindgen::prelude::*;
 
 /// Object which represents complete clipboard transaction with multiple MIME types.
 #[wasm_bindgen]
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
 pub struct ClipboardTransaction {
     contents: Vec<ClipboardContent>,
 }
@@ -49,7 +49,7 @@ impl FromIterator<ClipboardContent> for ClipboardTransaction {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ClipboardContentValue {
     Text(String),
     Binary(Vec<u8>),
@@ -66,7 +66,7 @@ impl ClipboardContentValue {
 
 /// Object which represents single clipboard format represented standard MIME type.
 #[wasm_bindgen]
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ClipboardContent {
     mime_type: String,
     value: ClipboardContentValue,
@@ -0,0 +1,749 @@
+//! Drive redirection backed by an in-memory staging area
+//!
+//! Browsers don’t give access to the local file system, so the drive announced to the remote is backed by files
+//! held in memory. The JS code moves files in and out of the session through this staging area:
+//!
+//! - Files uploaded by the user (e.g.: using a file picker or drag and drop) are added to the staging area, and are
+//!   then visible from the remote session.
+//! - Files written by the remote are reported once closed (see `SessionBuilder::drive_file_written_callback`), so the
+//!   JS code can prompt the user to download them.
+//!
+//! Paths exchanged with the JS code are relative to the drive root, and use `/` as the separator. Like on Windows,
+//! paths are case-insensitive.
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use futures_channel::mpsc;
+use ironrdp::pdu::{encode_err, PduResult};
+use ironrdp::rdpdr::pdu::efs::*;
+use ironrdp::rdpdr::pdu::esc::{ScardCall, ScardIoCtlCode};
+use ironrdp::rdpdr::pdu::RdpdrPdu;
+use ironrdp::rdpdr::RdpdrBackend;
+use ironrdp::svc::SvcMessage;
+use ironrdp_core::impl_as_any;
+
+use crate::session::RdpInputEvent;
+
+/// Capacity reported to the remote
+const DRIVE_CAPACITY: u64 = 1024 * 1024 * 1024;
+
+const BYTES_PER_SECTOR: u32 = 512;
+const SECTORS_PER_ALLOC_UNIT: u32 = 8;
+const ALLOC_UNIT_SIZE: u64 = 4096;
+
+/// Files staged for the remote session, shared between the rdpdr backend and the JS-facing session
+#[derive(Debug, Default)]
+pub(crate) struct StagingArea {
+    /// Entries indexed by their lowercase path, the drive root is implicit
+    entries: BTreeMap<String, Entry>,
+}
+
+#[derive(Debug)]
+struct Entry {
+    /// Path as created, with `\` separators and without leading separator
+    path: String,
+    kind: EntryKind,
+    last_write_time: i64,
+}
+
+#[derive(Debug)]
+enum EntryKind {
+    File(Vec<u8>),
+    Directory,
+}
+
+impl StagingArea {
+    pub(crate) fn new_shared() -> Arc<Mutex<Self>> {
+        Arc::new(Mutex::new(Self::default()))
+    }
+
+    /// Adds a file, creating the parent directories as needed and replacing any existing file
+    pub(crate) fn upload_file(&mut self, path: &str, data: Vec<u8>) -> anyhow::Result<()> {
+        let path = normalize_path(path);
+        anyhow::ensure!(!path.is_empty(), "empty file path");
+
+        let components: Vec<&str> = path.split('\\').collect();
+        for depth in 1..components.len() {
+            let parent = components[..depth].join("\\");
+
+            match self.entries.get(&key(&parent)) {
+                Some(Entry {
+                    kind: EntryKind::File(_),
+                    ..
+                }) => anyhow::bail!("{parent} is a file"),
+                Some(_) => {}
+                None => self.insert(parent, EntryKind::Directory),
+            }
+        }
+
+        if let Some(Entry {
+            kind: EntryKind::Directory,
+            ..
+        }) = self.entries.get(&key(&path))
+        {
+            anyhow::bail!("{path} is a directory");
+        }
+
+        self.insert(path, EntryKind::File(data));
+
+        Ok(())
+    }
+
+    /// Returns the content of a file
+    pub(crate) fn file(&self, path: &str) -> Option<&[u8]> {
+        match &self.entries.get(&key(&normalize_path(path)))?.kind {
+            EntryKind::File(data) => Some(data),
+            EntryKind::Directory => None,
+        }
+    }
+
+    /// Removes a file or a directory, including its content
+    pub(crate) fn remove(&mut self, path: &str) -> bool {
+        let path = key(&normalize_path(path));
+        let prefix = format!("{path}\\");
+
+        self.entries.retain(|key, _| !key.starts_with(&prefix));
+        self.entries.remove(&path).is_some()
+    }
+
+    /// Paths of all the files, using `/` as the separator
+    pub(crate) fn files(&self) -> Vec<String> {
+        self.entries
+            .values()
+            .filter(|entry| matches!(entry.kind, EntryKind::File(_)))
+            .map(|entry| entry.path.replace('\\', "/"))
+            .collect()
+    }
+
+    /// Moves an entry, including the content of a directory
+    fn rename(&mut self, from: &str, to: &str) {
+        let from_key = key(from);
+        let prefix = format!("{from_key}\\");
+
+        let moved: Vec<String> = self
+            .entries
+            .keys()
+            .filter(|key| **key == from_key || key.starts_with(&prefix))
+            .cloned()
+            .collect();
+
+        for old_key in moved {
+            if let Some(mut entry) = self.entries.remove(&old_key) {
+                entry.path = format!("{to}{}", entry.path.get(from.len()..).unwrap_or_default());
+                self.entries.insert(key(&entry.path), entry);
+            }
+        }
+    }
+
+    fn insert(&mut self, path: String, kind: EntryKind) {
+        self.entries.insert(
+            key(&path),
+            Entry {
+                path,
+                kind,
+                last_write_time: now_as_filetime(),
+            },
+        );
+    }
+
+    fn exists(&self, path: &str) -> bool {
+        path.is_empty() || self.entries.contains_key(&key(path))
+    }
+
+    fn is_directory(&self, path: &str) -> bool {
+        path.is_empty()
+            || matches!(
+                self.entries.get(&key(path)),
+                Some(Entry {
+                    kind: EntryKind::Directory,
+                    ..
+                })
+            )
+    }
+
+    /// Paths of the direct children of a directory
+    fn children(&self, path: &str) -> VecDeque<String> {
+        let parent = key(path);
+
+        self.entries
+            .iter()
+            .filter(|(key, _)| parent_of(key) == parent)
+            .map(|(_, entry)| entry.path.clone())
+            .collect()
+    }
+
+    fn used_space(&self) -> u64 {
+        self.entries
+            .values()
+            .map(|entry| match &entry.kind {
+                EntryKind::File(data) => u64::try_from(data.len()).unwrap_or(u64::MAX),
+                EntryKind::Directory => 0,
+            })
+            .fold(0, u64::saturating_add)
+    }
+}
+
+/// Open file or directory
+#[derive(Debug)]
+struct Handle {
+    path: String,
+    modified: bool,
+    delete_on_close: bool,
+    /// Remaining entries of an ongoing directory query
+    listing: Option<VecDeque<String>>,
+}
+
+/// rdpdr backend exposing the [`StagingArea`] as a drive
+#[derive(Debug)]
+pub(crate) struct WebDriveBackend {
+    staging_area: Arc<Mutex<StagingArea>>,
+    handles: HashMap<u32, Handle>,
+    next_file_id: u32,
+    events_tx: mpsc::UnboundedSender<RdpInputEvent>,
+}
+
+impl WebDriveBackend {
+    pub(crate) fn new(staging_area: Arc<Mutex<StagingArea>>, events_tx: mpsc::UnboundedSender<RdpInputEvent>) -> Self {
+        Self {
+            staging_area,
+            handles: HashMap::new(),
+            next_file_id: 0,
+            events_tx,
+        }
+    }
+
+    fn staging_area(&self) -> MutexGuard<'_, StagingArea> {
+        self.staging_area.lock().expect("staging area lock poisoned")
+    }
+
+    fn create(&mut self, req: DeviceCreateRequest) -> PduResult<Vec<SvcMessage>> {
+        let path = normalize_path(&req.path);
+        let directory_requested = req.create_options.contains(CreateOptions::FILE_DIRECTORY_FILE);
+        let file_requested = req.create_options.contains(CreateOptions::FILE_NON_DIRECTORY_FILE);
+
+        let result = {
+            let mut staging_area = self.staging_area.lock().expect("staging area lock poisoned");
+            let exists = staging_area.exists(&path);
+            let is_directory = staging_area.is_directory(&path);
+
+            if exists {
+                if req.create_disposition == CreateDisposition::FILE_CREATE {
+                    Err(NtStatus::OBJECT_NAME_COLLISION)
+                } else if directory_requested && !is_directory {
+                    Err(NtStatus::NOT_A_DIRECTORY)
+                } else if file_requested && is_directory {
+                    Err(NtStatus::ACCESS_DENIED)
+                } else {
+                    let overwrite = matches!(
+                        req.create_disposition,
+                        CreateDisposition::FILE_SUPERSEDE
+                            | CreateDisposition::FILE_OVERWRITE
+                            | CreateDisposition::FILE_OVERWRITE_IF
+                    );
+
+                    if overwrite && !is_directory {
+                        staging_area.insert(path.clone(), EntryKind::File(Vec::new()));
+                    }
+
+                    Ok(overwrite)
+                }
+            } else if matches!(
+                req.create_disposition,
+                CreateDisposition::FILE_OPEN | CreateDisposition::FILE_OVERWRITE
+            ) || !staging_area.is_directory(parent_of(&path))
+            {
+                Err(NtStatus::NO_SUCH_FILE)
+            } else {
+                let kind = if directory_requested {
+                    EntryKind::Directory
+                } else {
+                    EntryKind::File(Vec::new())
+                };
+                staging_area.insert(path.clone(), kind);
+
+                Ok(!directory_requested)
+            }
+        };
+
+        let file_id = self.next_file_id;
+        self.next_file_id = self.next_file_id.wrapping_add(1);
+
+        let (status, information) = match result {
+            Ok(modified) => {
+                debug!(file_id, %path, "Open drive file");
+
+                self.handles.insert(
+                    file_id,
+                    Handle {
+                        path,
+                        modified,
+                        delete_on_close: req.create_options.contains(CreateOptions::FILE_DELETE_ON_CLOSE),
+                        listing: None,
+                    },
+                );
+
+                let information = match req.create_disposition {
+                    CreateDisposition::FILE_OPEN_IF => Information::FILE_OPENED,
+                    CreateDisposition::FILE_OVERWRITE_IF => Information::FILE_OVERWRITTEN,
+                    _ => Information::FILE_SUPERSEDED,
+                };
+
+                (NtStatus::SUCCESS, information)
+            }
+            Err(status) => {
+                debug!(%path, ?status, "Failed to open drive file");
+                (status, Information::empty())
+            }
+        };
+
+        Ok(vec![SvcMessage::from(RdpdrPdu::DeviceCreateResponse(
+            DeviceCreateResponse {
+                device_io_reply: DeviceIoResponse::new(req.device_io_request, status),
+                file_id,
+                information,
+            },
+        ))])
+    }
+
+    fn close(&mut self, req: DeviceCloseRequest) -> PduResult<Vec<SvcMessage>> {
+        if let Some(handle) = self.handles.remove(&req.device_io_request.file_id) {
+            if handle.delete_on_close {
+                self.staging_area().remove(&handle.path);
+            } else if handle.modified && !self.staging_area().is_directory(&handle.path) {
+                let path = handle.path.replace('\\', "/");
+
+                if self
+                    .events_tx
+                    .unbounded_send(RdpInputEvent::DriveFileWritten(path))
+                    .is_err()
+                {
+                    warn!("Session is gone, drive file write not reported");
+                }
+            }
+        }
+
+        Ok(vec![SvcMessage::from(RdpdrPdu::DeviceCloseResponse(
+            DeviceCloseResponse {
+                device_io_response: DeviceIoResponse::new(req.device_io_request, NtStatus::SUCCESS),
+            },
+        ))])
+    }
+
+    fn read(&mut self, req: DeviceReadRequest) -> PduResult<Vec<SvcMessage>> {
+        let read_data = self.handles.get(&req.device_io_request.file_id).and_then(|handle| {
+            let staging_area = self.staging_area();
+            let data = staging_area.file(&handle.path)?;
+
+            let start = usize::try_from(req.offset).unwrap_or(usize::MAX).min(data.len());
+            let length = usize::try_from(req.length).unwrap_or(usize::MAX);
+            let end = start.saturating_add(length).min(data.len());
+
+            data.get(start..end).map(<[u8]>::to_vec)
+        });
+
+        let (status, read_data) = match read_data {
+            Some(read_data) => (NtStatus::SUCCESS, read_data),
+            None => (NtStatus::NO_SUCH_FILE, Vec::new()),
+        };
+
+        Ok(vec![SvcMessage::from(RdpdrPdu::DeviceReadResponse(
+            DeviceReadResponse {
+                device_io_reply: DeviceIoResponse::new(req.device_io_request, status),
+                read_data,
+            },
+        ))])
+    }
+
+    fn write(&mut self, req: DeviceWriteRequest) -> PduResult<Vec<SvcMessage>> {
+        let length = u32::try_from(req.write_data.len()).map_err(|e| encode_err!(e))?;
+
+        let written = match self.handles.get_mut(&req.device_io_request.file_id) {
+            Some(handle) => {
+                handle.modified = true;
+
+                let mut staging_area = self.staging_area.lock().expect("staging area lock poisoned");
+
+                match staging_area.entries.get_mut(&key(&handle.path)) {
+                    Some(Entry {
+                        kind: EntryKind::File(data),
+                        last_write_time,
+                        ..
+                    }) => {
+                        let start = usize::try_from(req.offset).ok();
+                        let end = start.and_then(|start| start.checked_add(req.write_data.len()));
+
+                        match (start, end) {
+                            (Some(start), Some(end)) => {
+                                if data.len() < end {
+                                    data.resize(end, 0);
+                                }
+                                data[start..end].copy_from_slice(&req.write_data);
+                                *last_write_time = now_as_filetime();
+
+                                Ok(())
+                            }
+                            _ => Err(NtStatus::UNSUCCESSFUL),
+                        }
+                    }
+                    _ => Err(NtStatus::NO_SUCH_FILE),
+                }
+            }
+            None => Err(NtStatus::NO_SUCH_FILE),
+        };
+
+        let (status, length) = match written {
+            Ok(()) => (NtStatus::SUCCESS, length),
+            Err(status) => (status, 0),
+        };
+
+        Ok(vec![SvcMessage::from(RdpdrPdu::DeviceWriteResponse(
+            DeviceWriteResponse {
+                device_io_reply: DeviceIoResponse::new(req.device_io_request, status),
+                length,
+            },
+        ))])
+    }
+
+    fn query_information(&mut self, req: ServerDriveQueryInformationRequest) -> PduResult<Vec<SvcMessage>> {
+        let info = self.handles.get(&req.device_io_request.file_id).and_then(|handle| {
+            let staging_area = self.staging_area();
+            let (attributes, size, last_write_time) = describe(&staging_area, &handle.path)?;
+
+            if req.file_info_class_lvl == FileInformationClassLevel::FILE_BASIC_INFORMATION {
+                Some(FileInformationClass::Basic(FileBasicInformation {
+                    creation_time: last_write_time,
+                    last_access_time: last_write_time,
+                    last_write_time,
+                    change_time: last_write_time,
+                    file_attributes: attributes,
+                }))
+            } else if req.file_info_class_lvl == FileInformationClassLevel::FILE_STANDARD_INFORMATION {
+                Some(FileInformationClass::Standard(FileStandardInformation {
+                    allocation_size: size,
+                    end_of_file: size,
+                    number_of_links: 1,
+                    delete_pending: if handle.delete_on_close {
+                        Boolean::True
+                    } else {
+                        Boolean::False
+                    },
+                    directory: if attributes.contains(FileAttributes::FILE_ATTRIBUTE_DIRECTORY) {
+                        Boolean::True
+                    } else {
+                        Boolean::False
+                    },
+                }))
+            } else if req.file_info_class_lvl == FileInformationClassLevel::FILE_ATTRIBUTE_TAG_INFORMATION {
+                Some(FileInformationClass::AttributeTag(FileAttributeTagInformation {
+                    file_attributes: attributes,
+                    reparse_tag: 0,
+                }))
+            } else {
+                warn!(class = ?req.file_info_class_lvl, "Unsupported file information class");
+                None
+            }
+        });
+
+        let status = if info.is_some() {
+            NtStatus::SUCCESS
+        } else {
+            NtStatus::UNSUCCESSFUL
+        };
+
+        Ok(vec![SvcMessage::from(RdpdrPdu::ClientDriveQueryInformationResponse(
+            ClientDriveQueryInformationResponse {
+                device_io_response: DeviceIoResponse::new(req.device_io_request, status),
+                buffer: info,
+            },
+        ))])
+    }
+
+    fn query_volume_information(
+        &mut self,
+        req: ServerDriveQueryVolumeInformationRequest,
+    ) -> PduResult<Vec<SvcMessage>> {
+        let total_alloc_units = i64::try_from(DRIVE_CAPACITY / ALLOC_UNIT_SIZE).map_err(|e| encode_err!(e))?;
+        let used_alloc_units =
+            i64::try_from(self.staging_area().used_space().div_ceil(ALLOC_UNIT_SIZE)).map_err(|e| encode_err!(e))?;
+        let available_alloc_units = total_alloc_units.saturating_sub(used_alloc_units).max(0);
+
+        let info = if req.fs_info_class_lvl == FileSystemInformationClassLevel::FILE_FS_FULL_SIZE_INFORMATION {
+            Some(FileSystemInformationClass::FileFsFullSizeInformation(
+                FileFsFullSizeInformation {
+                    total_alloc_units,
+                    caller_available_alloc_units: available_alloc_units,
+                    actual_available_alloc_units: available_alloc_units,
+                    sectors_per_alloc_unit: SECTORS_PER_ALLOC_UNIT,
+                    bytes_per_sector: BYTES_PER_SECTOR,
+                },
+            ))
+        } else if req.fs_info_class_lvl == FileSystemInformationClassLevel::FILE_FS_SIZE_INFORMATION {
+            Some(FileSystemInformationClass::FileFsSizeInformation(
+                FileFsSizeInformation {
+                    total_alloc_units,
+                    available_alloc_units,
+                    sectors_per_alloc_unit: SECTORS_PER_ALLOC_UNIT,
+                    bytes_per_sector: BYTES_PER_SECTOR,
+                },
+            ))
+        } else if req.fs_info_class_lvl == FileSystemInformationClassLevel::FILE_FS_ATTRIBUTE_INFORMATION {
+            Some(FileSystemInformationClass::FileFsAttributeInformation(
+                FileFsAttributeInformation {
+                    file_system_attributes: FileSystemAttributes::FILE_CASE_PRESERVED_NAMES
+                        | FileSystemAttributes::FILE_UNICODE_ON_DISK,
+                    max_component_name_len: 260,
+                    file_system_name: "FAT32".to_owned(),
+                },
+            ))
+        } else if req.fs_info_class_lvl == FileSystemInformationClassLevel::FILE_FS_VOLUME_INFORMATION {
+            Some(FileSystemInformationClass::FileFsVolumeInformation(
+                FileFsVolumeInformation {
+                    volume_creation_time: 0,
+                    volume_serial_number: 0,
+                    supports_objects: Boolean::False,
+                    volume_label: "IRON_RDP".to_owned(),
+                },
+            ))
+        } else {
+            warn!(class = ?req.fs_info_class_lvl, "Unsupported volume information class");
+            None
+        };
+
+        let status = if info.is_some() {
+            NtStatus::SUCCESS
+        } else {
+            NtStatus::UNSUCCESSFUL
+        };
+
+        Ok(vec![SvcMessage::from(
+            RdpdrPdu::ClientDriveQueryVolumeInformationResponse(ClientDriveQueryVolumeInformationResponse {
+                device_io_reply: DeviceIoResponse::new(req.device_io_request, status),
+                buffer: info,
+            }),
+        )])
+    }
+
+    fn query_directory(&mut self, req: ServerDriveQueryDirectoryRequest) -> PduResult<Vec<SvcMessage>> {
+        let staging_area = self.staging_area.lock().expect("staging area lock poisoned");
+
+        let Some(handle) = self.handles.get_mut(&req.device_io_request.file_id) else {
+            return Ok(make_query_dir_resp(req.device_io_request, NtStatus::NO_SUCH_FILE, None));
+        };
+
+        if req.initial_query > 0 {
+            let path = normalize_path(&req.path);
+
+            handle.listing = Some(match path.strip_suffix('*') {
+                Some(directory) => staging_area.children(directory.trim_end_matches('\\')),
+                None if staging_area.exists(&path) && !path.is_empty() => VecDeque::from([path]),
+                None => VecDeque::new(),
+            });
+        }
+
+        let next = handle.listing.as_mut().and_then(VecDeque::pop_front);
+
+        let Some(path) = next else {
+            let status = if req.initial_query > 0 {
+                NtStatus::NO_SUCH_FILE
+            } else {
+                NtStatus::NO_MORE_FILES
+            };
+
+            return Ok(make_query_dir_resp(req.device_io_request, status, None));
+        };
+
+        if req.file_info_class_lvl != FileInformationClassLevel::FILE_BOTH_DIRECTORY_INFORMATION {
+            warn!(class = ?req.file_info_class_lvl, "Unsupported file class for query directory");
+            return Ok(make_query_dir_resp(
+                req.device_io_request,
+                NtStatus::NOT_SUPPORTED,
+                None,
+            ));
+        }
+
+        let Some((attributes, size, last_write_time)) = describe(&staging_area, &path) else {
+            return Ok(make_query_dir_resp(req.device_io_request, NtStatus::NO_SUCH_FILE, None));
+        };
+
+        let info = FileBothDirectoryInformation::new(
+            last_write_time,
+            last_write_time,
+            last_write_time,
+            last_write_time,
+            size,
+            attributes,
+            file_name(&path).to_owned(),
+        );
+
+        Ok(make_query_dir_resp(
+            req.device_io_request,
+            NtStatus::SUCCESS,
+            Some(FileInformationClass::BothDirectory(info)),
+        ))
+    }
+
+    fn set_information(&mut self, req: ServerDriveSetInformationRequest) -> PduResult<Vec<SvcMessage>> {
+        let status = match self.handles.get_mut(&req.device_io_request.file_id) {
+            Some(handle) => {
+                let mut staging_area = self.staging_area.lock().expect("staging area lock poisoned");
+
+                match &req.set_buffer {
+                    FileInformationClass::Rename(info) => {
+                        let to = normalize_path(&info.file_name);
+
+                        if staging_area.exists(&to) && info.replace_if_exists == Boolean::False {
+                            NtStatus::OBJECT_NAME_COLLISION
+                        } else if !staging_area.is_directory(parent_of(&to)) {
+                            NtStatus::NO_SUCH_FILE
+                        } else {
+                            staging_area.rename(&handle.path, &to);
+                            handle.path = to;
+                            NtStatus::SUCCESS
+                        }
+                    }
+                    FileInformationClass::Disposition(info) => {
+                        if staging_area.is_directory(&handle.path) && !staging_area.children(&handle.path).is_empty() {
+                            NtStatus::DIRECTORY_NOT_EMPTY
+                        } else {
+                            handle.delete_on_close = info.delete_pending != 0;
+                            NtStatus::SUCCESS
+                        }
+                    }
+                    FileInformationClass::EndOfFile(info) => {
+                        match (
+                            staging_area.entries.get_mut(&key(&handle.path)),
+                            usize::try_from(info.end_of_file),
+                        ) {
+                            (
+                                Some(Entry {
+                                    kind: EntryKind::File(data),
+                                    last_write_time,
+                                    ..
+                                }),
+                                Ok(end_of_file),
+                            ) => {
+                                data.resize(end_of_file, 0);
+                                *last_write_time = now_as_filetime();
+                                handle.modified = true;
+                                NtStatus::SUCCESS
+                            }
+                            _ => NtStatus::UNSUCCESSFUL,
+                        }
+                    }
+                    // Timestamps and attributes are not stored, and the memory is allocated on write.
+                    _ => NtStatus::SUCCESS,
+                }
+            }
+            None => NtStatus::NO_SUCH_FILE,
+        };
+
+        Ok(vec![SvcMessage::from(RdpdrPdu::ClientDriveSetInformationResponse(
+            ClientDriveSetInformationResponse::new(&req, status).map_err(|e| encode_err!(e))?,
+        ))])
+    }
+}
+
+impl_as_any!(WebDriveBackend);
+
+impl RdpdrBackend for WebDriveBackend {
+    fn handle_server_device_announce_response(&mut self, _pdu: ServerDeviceAnnounceResponse) -> PduResult<()> {
+        Ok(())
+    }
+
+    fn handle_scard_call(&mut self, _req: DeviceControlRequest<ScardIoCtlCode>, _call: ScardCall) -> PduResult<()> {
+        Ok(())
+    }
+
+    fn handle_drive_io_request(&mut self, req: ServerDriveIoRequest) -> PduResult<Vec<SvcMessage>> {
+        trace!(?req, "Drive I/O request");
+
+        match req {
+            ServerDriveIoRequest::ServerCreateDriveRequest(req) => self.create(req),
+            ServerDriveIoRequest::DeviceCloseRequest(req) => self.close(req),
+            ServerDriveIoRequest::DeviceReadRequest(req) => self.read(req),
+            ServerDriveIoRequest::DeviceWriteRequest(req) => self.write(req),
+            ServerDriveIoRequest::ServerDriveQueryInformationRequest(req) => self.query_information(req),
+            ServerDriveIoRequest::ServerDriveQueryVolumeInformationRequest(req) => self.query_volume_information(req),
+            ServerDriveIoRequest::ServerDriveQueryDirectoryRequest(req) => self.query_directory(req),
+            ServerDriveIoRequest::ServerDriveSetInformationRequest(req) => self.set_information(req),
+            ServerDriveIoRequest::DeviceControlRequest(req) => Ok(vec![SvcMessage::from(
+                RdpdrPdu::DeviceControlResponse(DeviceControlResponse {
+                    device_io_reply: DeviceIoResponse::new(req.header, NtStatus::SUCCESS),
+                    output_buffer: None,
+                }),
+            )]),
+            // The staging area is only modified by the user, change notifications are not needed.
+            ServerDriveIoRequest::ServerDriveNotifyChangeDirectoryRequest(_) => Ok(Vec::new()),
+            // TODO: locks are not supported
+            ServerDriveIoRequest::ServerDriveLockControlRequest(_) => Ok(Vec::new()),
+        }
+    }
+
+    fn handle_printer_io_request(&mut self, _req: ServerPrinterIoRequest) -> PduResult<Vec<SvcMessage>> {
+        // No printer is announced to the server.
+        Ok(Vec::new())
+    }
+}
+
+fn make_query_dir_resp(
+    device_io_request: DeviceIoRequest,
+    status: NtStatus,
+    buffer: Option<FileInformationClass>,
+) -> Vec<SvcMessage> {
+    vec![SvcMessage::from(RdpdrPdu::ClientDriveQueryDirectoryResponse(
+        ClientDriveQueryDirectoryResponse {
+            device_io_reply: DeviceIoResponse::new(device_io_request, status),
+            buffer,
+        },
+    ))]
+}
+
+/// Returns the attributes, size and last write time of an entry
+fn describe(staging_area: &StagingArea, path: &str) -> Option<(FileAttributes, i64, i64)> {
+    if path.is_empty() {
+        return Some((FileAttributes::FILE_ATTRIBUTE_DIRECTORY, 0, 0));
+    }
+
+    let entry = staging_area.entries.get(&key(path))?;
+
+    Some(match &entry.kind {
+        EntryKind::File(data) => (
+            FileAttributes::FILE_ATTRIBUTE_ARCHIVE,
+            i64::try_from(data.len()).ok()?,
+            entry.last_write_time,
+        ),
+        EntryKind::Directory => (FileAttributes::FILE_ATTRIBUTE_DIRECTORY, 0, entry.last_write_time),
+    })
+}
+
+/// Converts a path to the staging area representation: `\` separators, no leading or trailing separator
+fn normalize_path(path: &str) -> String {
+    path.split(['\\', '/'])
+        .filter(|component| !component.is_empty() && *component != ".")
+        .collect::<Vec<_>>()
+        .join("\\")
+}
+
+fn key(path: &str) -> String {
+    path.to_lowercase()
+}
+
+fn parent_of(path: &str) -> &str {
+    path.rsplit_once('\\').map_or("", |(parent, _)| parent)
+}
+
+fn file_name(path: &str) -> &str {
+    path.rsplit_once('\\').map_or(path, |(_, name)| name)
+}
+
+/// Current time, as a Windows file time (100-nanosecond intervals since January 1, 1601)
+fn now_as_filetime() -> i64 {
+    const UNIX_EPOCH_AS_FILETIME: i64 = 116_444_736_000_000_000;
+
+    // The current time in milliseconds is far from overflowing.
+    #[allow(clippy::cast_possible_truncation, clippy::arithmetic_side_effects)]
+    let filetime = js_sys::Date::now() as i64 * 10_000 + UNIX_EPOCH_AS_FILETIME;
+
+    filetime
+}
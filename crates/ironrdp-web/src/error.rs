@@ -2,7 +2,7 @@ use ironrdp::connector::{self, sspi, ConnectorErrorKind};
 use wasm_bindgen::prelude::*;
 
 #[wasm_bindgen]
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug)]
 pub enum IronRdpErrorKind {
     /// Catch-all error kind
     General,
@@ -29,6 +29,10 @@ impl IronRdpError {
         self.kind = kind;
         self
     }
+
+    pub(crate) fn message(&self) -> String {
+        format!("{:#}", self.source)
+    }
 }
 
 #[wasm_bindgen]
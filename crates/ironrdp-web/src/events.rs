@@ -0,0 +1,370 @@
+//! Typed session events
+//!
+//! Instead of registering one ad-hoc callback per notification, the JS code may subscribe to the session events it
+//! is interested in. Each subscriber receives a [`SessionEvent`], whose payload accessors return `undefined` unless
+//! they match the event type.
+//!
+//! The existing callbacks (cursor style, clipboard, …) are still called, events are emitted in addition to them.
+
+use core::cell::RefCell;
+use std::rc::Rc;
+
+use wasm_bindgen::prelude::*;
+
+#[cfg(feature = "clipboard")]
+use crate::clipboard::ClipboardTransaction;
+use crate::error::IronRdpErrorKind;
+use crate::pointer::Zoom;
+use crate::DesktopSize;
+
+/// Minimum delay between two [`SessionEventType::Stats`] events
+pub(crate) const STATS_INTERVAL_MS: f64 = 1000.0;
+
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SessionEventType {
+    /// The connection state changed, see [`SessionEvent::connection_state`]
+    ConnectionState,
+    /// The remote desktop was resized, see [`SessionEvent::desktop_size`]
+    Resize,
+    /// The remote clipboard changed, see [`SessionEvent::clipboard`] (never emitted without the `clipboard` feature)
+    Clipboard,
+    /// The pointer shape changed, see [`SessionEvent::pointer_kind`]
+    PointerShape,
+    /// Periodic session statistics, see [`SessionEvent::stats`]
+    Stats,
+    /// The session failed, see [`SessionEvent::error_kind`]
+    Error,
+    /// The local canvas was pinch-zoomed, see [`SessionEvent::zoom_scale`]
+    Zoom,
+}
+
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConnectionState {
+    Connecting,
+    Connected,
+    /// The connection was lost, and is being reopened
+    Reconnecting,
+    Disconnected,
+}
+
+#[wasm_bindgen]
+#[derive(Clone, Debug)]
+pub struct SessionStats {
+    /// Frames received since the previous stats event
+    pub frames: u32,
+    /// Bytes received since the previous stats event
+    pub bytes: u32,
+    /// Duration covered by these stats, in milliseconds
+    pub elapsed_ms: f64,
+}
+
+#[derive(Clone, Debug)]
+enum SessionEventPayload {
+    ConnectionState(ConnectionState),
+    Resize(DesktopSize),
+    #[cfg(feature = "clipboard")]
+    Clipboard(ClipboardTransaction),
+    PointerShape {
+        kind: &'static str,
+        url: Option<String>,
+        hotspot_x: u16,
+        hotspot_y: u16,
+    },
+    Stats(SessionStats),
+    Error {
+        kind: IronRdpErrorKind,
+        message: String,
+    },
+    Zoom(Zoom),
+}
+
+#[wasm_bindgen]
+#[derive(Clone, Debug)]
+pub struct SessionEvent {
+    payload: SessionEventPayload,
+}
+
+impl SessionEvent {
+    pub(crate) fn connection_state_changed(state: ConnectionState) -> Self {
+        Self {
+            payload: SessionEventPayload::ConnectionState(state),
+        }
+    }
+
+    pub(crate) fn resized(desktop_size: DesktopSize) -> Self {
+        Self {
+            payload: SessionEventPayload::Resize(desktop_size),
+        }
+    }
+
+    #[cfg(feature = "clipboard")]
+    pub(crate) fn clipboard_changed(transaction: ClipboardTransaction) -> Self {
+        Self {
+            payload: SessionEventPayload::Clipboard(transaction),
+        }
+    }
+
+    pub(crate) fn pointer_shape_changed(
+        kind: &'static str,
+        url: Option<String>,
+        hotspot_x: u16,
+        hotspot_y: u16,
+    ) -> Self {
+        Self {
+            payload: SessionEventPayload::PointerShape {
+                kind,
+                url,
+                hotspot_x,
+                hotspot_y,
+            },
+        }
+    }
+
+    pub(crate) fn stats(stats: SessionStats) -> Self {
+        Self {
+            payload: SessionEventPayload::Stats(stats),
+        }
+    }
+
+    pub(crate) fn error(kind: IronRdpErrorKind, message: String) -> Self {
+        Self {
+            payload: SessionEventPayload::Error { kind, message },
+        }
+    }
+
+    pub(crate) fn zoomed(zoom: Zoom) -> Self {
+        Self {
+            payload: SessionEventPayload::Zoom(zoom),
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl SessionEvent {
+    pub fn event_type(&self) -> SessionEventType {
+        match self.payload {
+            SessionEventPayload::ConnectionState(_) => SessionEventType::ConnectionState,
+            SessionEventPayload::Resize(_) => SessionEventType::Resize,
+            #[cfg(feature = "clipboard")]
+            SessionEventPayload::Clipboard(_) => SessionEventType::Clipboard,
+            SessionEventPayload::PointerShape { .. } => SessionEventType::PointerShape,
+            SessionEventPayload::Stats(_) => SessionEventType::Stats,
+            SessionEventPayload::Error { .. } => SessionEventType::Error,
+            SessionEventPayload::Zoom(_) => SessionEventType::Zoom,
+        }
+    }
+
+    pub fn connection_state(&self) -> Option<ConnectionState> {
+        match self.payload {
+            SessionEventPayload::ConnectionState(state) => Some(state),
+            _ => None,
+        }
+    }
+
+    pub fn desktop_size(&self) -> Option<DesktopSize> {
+        match &self.payload {
+            SessionEventPayload::Resize(desktop_size) => Some(desktop_size.clone()),
+            _ => None,
+        }
+    }
+
+    #[cfg(feature = "clipboard")]
+    pub fn clipboard(&self) -> Option<ClipboardTransaction> {
+        match &self.payload {
+            SessionEventPayload::Clipboard(transaction) => Some(transaction.clone()),
+            _ => None,
+        }
+    }
+
+    /// `default`, `hidden` or `url`
+    pub fn pointer_kind(&self) -> Option<String> {
+        match &self.payload {
+            SessionEventPayload::PointerShape { kind, .. } => Some((*kind).to_owned()),
+            _ => None,
+        }
+    }
+
+    /// Data URL of the pointer image, for the `url` pointer kind
+    pub fn pointer_url(&self) -> Option<String> {
+        match &self.payload {
+            SessionEventPayload::PointerShape { url, .. } => url.clone(),
+            _ => None,
+        }
+    }
+
+    pub fn pointer_hotspot_x(&self) -> Option<u16> {
+        match self.payload {
+            SessionEventPayload::PointerShape { hotspot_x, .. } => Some(hotspot_x),
+            _ => None,
+        }
+    }
+
+    pub fn pointer_hotspot_y(&self) -> Option<u16> {
+        match self.payload {
+            SessionEventPayload::PointerShape { hotspot_y, .. } => Some(hotspot_y),
+            _ => None,
+        }
+    }
+
+    pub fn stats(&self) -> Option<SessionStats> {
+        match &self.payload {
+            SessionEventPayload::Stats(stats) => Some(stats.clone()),
+            _ => None,
+        }
+    }
+
+    pub fn error_kind(&self) -> Option<IronRdpErrorKind> {
+        match self.payload {
+            SessionEventPayload::Error { kind, .. } => Some(kind),
+            _ => None,
+        }
+    }
+
+    pub fn error_message(&self) -> Option<String> {
+        match &self.payload {
+            SessionEventPayload::Error { message, .. } => Some(message.clone()),
+            _ => None,
+        }
+    }
+
+    /// Scale of the canvas, between 1 and 4
+    ///
+    /// The canvas should be transformed using `transform-origin: 0 0` and
+    /// `transform: scale(zoom_scale) translate(-zoom_offset_x px, -zoom_offset_y px)`, in canvas pixels.
+    pub fn zoom_scale(&self) -> Option<f64> {
+        match self.payload {
+            SessionEventPayload::Zoom(zoom) => Some(zoom.scale),
+            _ => None,
+        }
+    }
+
+    /// Left edge of the visible desktop region, in desktop pixels
+    pub fn zoom_offset_x(&self) -> Option<f64> {
+        match self.payload {
+            SessionEventPayload::Zoom(zoom) => Some(zoom.offset_x),
+            _ => None,
+        }
+    }
+
+    /// Top edge of the visible desktop region, in desktop pixels
+    pub fn zoom_offset_y(&self) -> Option<f64> {
+        match self.payload {
+            SessionEventPayload::Zoom(zoom) => Some(zoom.offset_y),
+            _ => None,
+        }
+    }
+}
+
+struct Subscription {
+    id: u32,
+    event_type: SessionEventType,
+    callback: js_sys::Function,
+}
+
+#[derive(Default)]
+struct EventDispatcherInner {
+    next_id: u32,
+    subscriptions: Vec<Subscription>,
+}
+
+/// Subscriptions shared by the session builder, the session and the clipboard backend
+#[derive(Clone, Default)]
+pub(crate) struct EventDispatcher(Rc<RefCell<EventDispatcherInner>>);
+
+impl EventDispatcher {
+    /// Returns the subscription ID, to be passed to [`EventDispatcher::unsubscribe`]
+    pub(crate) fn subscribe(&self, event_type: SessionEventType, callback: js_sys::Function) -> u32 {
+        let mut inner = self.0.borrow_mut();
+
+        let id = inner.next_id;
+        inner.next_id = inner.next_id.wrapping_add(1);
+
+        inner.subscriptions.push(Subscription {
+            id,
+            event_type,
+            callback,
+        });
+
+        id
+    }
+
+    /// Returns `false` if there is no such subscription
+    pub(crate) fn unsubscribe(&self, id: u32) -> bool {
+        let mut inner = self.0.borrow_mut();
+        let count = inner.subscriptions.len();
+        inner.subscriptions.retain(|subscription| subscription.id != id);
+        inner.subscriptions.len() != count
+    }
+
+    pub(crate) fn has_subscribers(&self, event_type: SessionEventType) -> bool {
+        self.0
+            .borrow()
+            .subscriptions
+            .iter()
+            .any(|subscription| subscription.event_type == event_type)
+    }
+
+    pub(crate) fn emit(&self, event: SessionEvent) {
+        let event_type = event.event_type();
+
+        // Subscribers may (un)subscribe from their callback, so the borrow must not be held while calling them.
+        let callbacks: Vec<js_sys::Function> = self
+            .0
+            .borrow()
+            .subscriptions
+            .iter()
+            .filter(|subscription| subscription.event_type == event_type)
+            .map(|subscription| subscription.callback.clone())
+            .collect();
+
+        for callback in callbacks {
+            if let Err(error) = callback.call1(&JsValue::NULL, &JsValue::from(event.clone())) {
+                error!(?event_type, "Session event callback failed: {error:?}");
+            }
+        }
+    }
+}
+
+/// Accumulates the received traffic for the [`SessionEventType::Stats`] events
+pub(crate) struct StatsCollector {
+    frames: u32,
+    bytes: u32,
+    since: f64,
+}
+
+impl StatsCollector {
+    pub(crate) fn new() -> Self {
+        Self {
+            frames: 0,
+            bytes: 0,
+            since: js_sys::Date::now(),
+        }
+    }
+
+    /// Records a received frame, and returns the stats once per [`STATS_INTERVAL_MS`]
+    pub(crate) fn record_frame(&mut self, length: usize) -> Option<SessionStats> {
+        self.frames = self.frames.saturating_add(1);
+        self.bytes = self.bytes.saturating_add(u32::try_from(length).unwrap_or(u32::MAX));
+
+        let now = js_sys::Date::now();
+        let elapsed_ms = now - self.since;
+
+        if elapsed_ms < STATS_INTERVAL_MS {
+            return None;
+        }
+
+        let stats = SessionStats {
+            frames: self.frames,
+            bytes: self.bytes,
+            elapsed_ms,
+        };
+
+        self.frames = 0;
+        self.bytes = 0;
+        self.since = now;
+
+        Some(stats)
+    }
+}
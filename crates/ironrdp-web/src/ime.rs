@@ -0,0 +1,55 @@
+//! IME composition handling
+//!
+//! Input method editors (used for CJK text entry) compose text using several key strokes, and the composed text is
+//! only known once the composition ends. The browser reports it using `composition*` events, while the key strokes
+//! typed in the candidate window are still dispatched as `keydown` events, flagged with `isComposing` or the special
+//! 229 key code (Safari dispatches the committing `keydown` after `compositionend`, without `isComposing`). These key
+//! events must not reach the remote, otherwise the raw key strokes would be typed in addition to the composed text.
+//!
+//! The composed text is sent using Unicode keyboard events, one character at a time.
+
+use ironrdp::input::Operation;
+use smallvec::SmallVec;
+
+/// `keyCode` of the key events processed by the IME
+const IME_PROCESS_KEY_CODE: u32 = 229;
+
+pub(crate) type ImeOperations = SmallVec<[Operation; 4]>;
+
+#[derive(Default)]
+pub(crate) struct ImeComposition {
+    composing: bool,
+}
+
+impl ImeComposition {
+    pub(crate) fn start(&mut self) {
+        self.composing = true;
+    }
+
+    pub(crate) fn update(&mut self) {
+        // Some browsers resume a composition without dispatching `compositionstart` again.
+        self.composing = true;
+    }
+
+    /// Returns the operations typing the composed text, which is empty when the composition was cancelled
+    pub(crate) fn end(&mut self, data: &str) -> ImeOperations {
+        if !self.composing {
+            debug!("Composition ended without being started");
+        }
+        self.composing = false;
+
+        data.chars()
+            .flat_map(|character| {
+                [
+                    Operation::UnicodeKeyPressed(character),
+                    Operation::UnicodeKeyReleased(character),
+                ]
+            })
+            .collect()
+    }
+
+    /// Returns `true` when the key event belongs to the composition, and must not be sent to the remote
+    pub(crate) fn is_composition_key_event(&self, is_composing: bool, key_code: u32) -> bool {
+        self.composing || is_composing || key_code == IME_PROCESS_KEY_CODE
+    }
+}
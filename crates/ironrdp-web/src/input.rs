@@ -56,6 +56,12 @@ impl DeviceEvent {
     }
 }
 
+/// Returns the scancode of the key identified by a `KeyboardEvent.code` value, if any.
+#[wasm_bindgen]
+pub fn ironrdp_scancode_from_code(code: &str) -> Option<u16> {
+    ironrdp::input::scancode::from_dom_code(code).map(Scancode::as_u16)
+}
+
 #[wasm_bindgen]
 pub struct InputTransaction(pub(crate) SmallVec<[Operation; 3]>);
 
@@ -10,13 +10,24 @@ extern crate time as _;
 #[macro_use]
 extern crate tracing;
 
+#[cfg(feature = "audio")]
+mod audio;
 mod canvas;
+#[cfg(feature = "clipboard")]
 mod clipboard;
+#[cfg(feature = "rdpdr")]
+mod drive;
 mod error;
+mod events;
 mod image;
+mod ime;
 mod input;
 mod network_client;
+mod pointer;
 mod session;
+mod transport;
+mod webgl;
+mod worker;
 
 use wasm_bindgen::prelude::*;
 
@@ -36,6 +47,14 @@ pub fn ironrdp_init(log_level: &str) {
     }
 }
 
+/// Whether this build of the module uses SIMD instructions to decode graphics
+///
+/// See the crate README to pick the SIMD build at runtime, depending on the browser support.
+#[wasm_bindgen]
+pub fn ironrdp_simd_acceleration() -> bool {
+    ironrdp::graphics::SIMD_ACCELERATION
+}
+
 fn set_logger_once(level: tracing::Level) {
     use tracing_subscriber::filter::LevelFilter;
     use tracing_subscriber::fmt::time::UtcTime;
@@ -59,7 +78,7 @@ fn set_logger_once(level: tracing::Level) {
 }
 
 #[wasm_bindgen]
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 pub struct DesktopSize {
     pub width: u16,
     pub height: u16,
@@ -0,0 +1,331 @@
+//! Pointer events (mouse, pen and touch) handling
+//!
+//! DOM `PointerEvent`s are translated into mouse operations. The touch input virtual channel (MS-RDPEI) is not
+//! supported yet, so touch contacts are emulated using the mouse:
+//!
+//! - a single contact moves the pointer and holds the left button, like a mouse drag;
+//! - two contacts pinch-zoom and pan the local canvas, without sending any input to the remote.
+//!
+//! Event positions are expressed in the canvas space (i.e. desktop pixels when the canvas is not zoomed), and are
+//! remapped to desktop coordinates using the current [`Zoom`].
+
+use ironrdp::input::{MouseButton, MousePosition, Operation};
+use smallvec::SmallVec;
+use wasm_bindgen::prelude::*;
+
+use crate::session::f64_to_u16_saturating_cast;
+
+/// Maximum scale of the pinch-zoom
+const MAX_ZOOM: f64 = 4.0;
+
+/// Contacts closer than this distance, in canvas pixels, can’t be used to compute a zoom factor
+const MIN_PINCH_DISTANCE: f64 = 10.0;
+
+pub(crate) type PointerOperations = SmallVec<[Operation; 3]>;
+
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PointerEventKind {
+    /// `pointerdown`
+    Down,
+    /// `pointermove`
+    Move,
+    /// `pointerup`
+    Up,
+    /// `pointercancel`
+    Cancel,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum PointerType {
+    Mouse,
+    Pen,
+    Touch,
+}
+
+impl PointerType {
+    fn from_web(pointer_type: &str) -> Self {
+        match pointer_type {
+            "pen" => Self::Pen,
+            "touch" => Self::Touch,
+            // The pointer type is an empty string when the browser can’t detect it.
+            _ => Self::Mouse,
+        }
+    }
+}
+
+#[wasm_bindgen]
+#[derive(Clone, Debug)]
+pub struct PointerInputEvent {
+    kind: PointerEventKind,
+    pointer_type: PointerType,
+    pointer_id: i32,
+    button: i16,
+    x: f64,
+    y: f64,
+}
+
+#[wasm_bindgen]
+impl PointerInputEvent {
+    /// `pointer_type`, `pointer_id` and `button` are the `PointerEvent` properties of the same name, and `x`/`y` the
+    /// position of the pointer in the canvas space.
+    pub fn new(kind: PointerEventKind, pointer_type: &str, pointer_id: i32, button: i16, x: f64, y: f64) -> Self {
+        Self {
+            kind,
+            pointer_type: PointerType::from_web(pointer_type),
+            pointer_id,
+            button,
+            x,
+            y,
+        }
+    }
+}
+
+/// Transform applied to the local canvas
+///
+/// The canvas shows the desktop region starting at (`offset_x`, `offset_y`), scaled by `scale`.
+/// In CSS terms: `transform-origin: 0 0; transform: scale(scale) translate(-offset_x px, -offset_y px)`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct Zoom {
+    pub(crate) scale: f64,
+    pub(crate) offset_x: f64,
+    pub(crate) offset_y: f64,
+}
+
+impl Zoom {
+    const NONE: Self = Self {
+        scale: 1.0,
+        offset_x: 0.0,
+        offset_y: 0.0,
+    };
+
+    fn to_desktop(self, x: f64, y: f64) -> (f64, f64) {
+        (self.offset_x + x / self.scale, self.offset_y + y / self.scale)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Contact {
+    pointer_id: i32,
+    x: f64,
+    y: f64,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Pinch {
+    distance: f64,
+    /// Desktop point under the center of the contacts when the pinch started
+    anchor_x: f64,
+    anchor_y: f64,
+    zoom: Zoom,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum TouchMode {
+    /// No contact
+    Idle,
+    /// A single contact emulates the mouse
+    Mouse(i32),
+    /// Two contacts or more are zooming the canvas, emulation resumes once all contacts are lifted
+    Gesture,
+}
+
+/// Translates pointer events into mouse operations, and tracks the pinch-zoom
+pub(crate) struct PointerTracker {
+    desktop_width: f64,
+    desktop_height: f64,
+    contacts: Vec<Contact>,
+    touch_mode: TouchMode,
+    pinch: Option<Pinch>,
+    zoom: Zoom,
+}
+
+impl PointerTracker {
+    pub(crate) fn new(desktop_width: u16, desktop_height: u16) -> Self {
+        Self {
+            desktop_width: f64::from(desktop_width),
+            desktop_height: f64::from(desktop_height),
+            contacts: Vec::new(),
+            touch_mode: TouchMode::Idle,
+            pinch: None,
+            zoom: Zoom::NONE,
+        }
+    }
+
+    pub(crate) fn zoom(&self) -> Zoom {
+        self.zoom
+    }
+
+    pub(crate) fn set_desktop_size(&mut self, desktop_width: u16, desktop_height: u16) {
+        self.desktop_width = f64::from(desktop_width);
+        self.desktop_height = f64::from(desktop_height);
+        self.zoom = self.clamp_zoom(self.zoom);
+    }
+
+    pub(crate) fn reset_zoom(&mut self) {
+        self.zoom = Zoom::NONE;
+        self.pinch = None;
+    }
+
+    pub(crate) fn process(&mut self, event: &PointerInputEvent) -> PointerOperations {
+        match event.pointer_type {
+            PointerType::Mouse | PointerType::Pen => self.process_mouse(event),
+            PointerType::Touch => self.process_touch(event),
+        }
+    }
+
+    fn process_mouse(&self, event: &PointerInputEvent) -> PointerOperations {
+        let mut operations = PointerOperations::new();
+        operations.push(Operation::MouseMove(self.desktop_position(event.x, event.y)));
+
+        // The pen tip is reported as the left button, and the barrel button as the right one.
+        let button = u8::try_from(event.button).ok().and_then(MouseButton::from_web_button);
+
+        match (event.kind, button) {
+            (PointerEventKind::Down, Some(button)) => operations.push(Operation::MouseButtonPressed(button)),
+            (PointerEventKind::Up, Some(button)) => operations.push(Operation::MouseButtonReleased(button)),
+            (PointerEventKind::Cancel, _) => operations.push(Operation::MouseButtonReleased(MouseButton::Left)),
+            _ => {}
+        }
+
+        operations
+    }
+
+    fn process_touch(&mut self, event: &PointerInputEvent) -> PointerOperations {
+        let mut operations = PointerOperations::new();
+
+        match event.kind {
+            PointerEventKind::Down => {
+                self.contacts.retain(|contact| contact.pointer_id != event.pointer_id);
+                self.contacts.push(Contact {
+                    pointer_id: event.pointer_id,
+                    x: event.x,
+                    y: event.y,
+                });
+
+                match self.touch_mode {
+                    TouchMode::Idle => {
+                        self.touch_mode = TouchMode::Mouse(event.pointer_id);
+                        operations.push(Operation::MouseMove(self.desktop_position(event.x, event.y)));
+                        operations.push(Operation::MouseButtonPressed(MouseButton::Left));
+                    }
+                    TouchMode::Mouse(_) => {
+                        // A second finger turns the drag into a gesture.
+                        self.touch_mode = TouchMode::Gesture;
+                        operations.push(Operation::MouseButtonReleased(MouseButton::Left));
+                        self.start_pinch();
+                    }
+                    TouchMode::Gesture => self.start_pinch(),
+                }
+            }
+            PointerEventKind::Move => {
+                if let Some(contact) = self
+                    .contacts
+                    .iter_mut()
+                    .find(|contact| contact.pointer_id == event.pointer_id)
+                {
+                    contact.x = event.x;
+                    contact.y = event.y;
+                }
+
+                match self.touch_mode {
+                    TouchMode::Mouse(pointer_id) if pointer_id == event.pointer_id => {
+                        operations.push(Operation::MouseMove(self.desktop_position(event.x, event.y)));
+                    }
+                    TouchMode::Gesture => self.update_pinch(),
+                    _ => {}
+                }
+            }
+            PointerEventKind::Up | PointerEventKind::Cancel => {
+                self.contacts.retain(|contact| contact.pointer_id != event.pointer_id);
+
+                if self.touch_mode == TouchMode::Mouse(event.pointer_id) {
+                    if event.kind == PointerEventKind::Up {
+                        operations.push(Operation::MouseMove(self.desktop_position(event.x, event.y)));
+                    }
+                    operations.push(Operation::MouseButtonReleased(MouseButton::Left));
+                }
+
+                if self.contacts.is_empty() {
+                    self.touch_mode = TouchMode::Idle;
+                    self.pinch = None;
+                } else {
+                    // The remaining contacts keep zooming from their current positions.
+                    self.start_pinch();
+                }
+            }
+        }
+
+        operations
+    }
+
+    fn start_pinch(&mut self) {
+        self.pinch = match self.contacts.as_slice() {
+            [first, second, ..] => {
+                let (center_x, center_y) = center(first, second);
+                let (anchor_x, anchor_y) = self.zoom.to_desktop(center_x, center_y);
+
+                Some(Pinch {
+                    distance: distance(first, second),
+                    anchor_x,
+                    anchor_y,
+                    zoom: self.zoom,
+                })
+            }
+            _ => None,
+        };
+    }
+
+    fn update_pinch(&mut self) {
+        let (Some(pinch), [first, second, ..]) = (self.pinch, self.contacts.as_slice()) else {
+            return;
+        };
+
+        let scale = if pinch.distance < MIN_PINCH_DISTANCE {
+            pinch.zoom.scale
+        } else {
+            pinch.zoom.scale * distance(first, second) / pinch.distance
+        };
+
+        // Keep the desktop point which was under the fingers under their current center.
+        let (center_x, center_y) = center(first, second);
+
+        self.zoom = self.clamp_zoom(Zoom {
+            scale,
+            offset_x: pinch.anchor_x - center_x / scale,
+            offset_y: pinch.anchor_y - center_y / scale,
+        });
+    }
+
+    /// Keeps the scale in bounds, and the visible region inside the desktop
+    fn clamp_zoom(&self, zoom: Zoom) -> Zoom {
+        let scale = zoom.scale.clamp(1.0, MAX_ZOOM);
+
+        Zoom {
+            scale,
+            offset_x: zoom
+                .offset_x
+                .clamp(0.0, self.desktop_width - self.desktop_width / scale),
+            offset_y: zoom
+                .offset_y
+                .clamp(0.0, self.desktop_height - self.desktop_height / scale),
+        }
+    }
+
+    fn desktop_position(&self, x: f64, y: f64) -> MousePosition {
+        let (x, y) = self.zoom.to_desktop(x, y);
+
+        MousePosition {
+            x: f64_to_u16_saturating_cast((x.min(self.desktop_width - 1.0)).round()),
+            y: f64_to_u16_saturating_cast((y.min(self.desktop_height - 1.0)).round()),
+        }
+    }
+}
+
+fn center(first: &Contact, second: &Contact) -> (f64, f64) {
+    ((first.x + second.x) / 2.0, (first.y + second.y) / 2.0)
+}
+
+fn distance(first: &Contact, second: &Contact) -> f64 {
+    (first.x - second.x).hypot(first.y - second.y)
+}
@@ -4,6 +4,8 @@
 use core::cell::RefCell;
 use std::borrow::Cow;
 use std::rc::Rc;
+#[cfg(feature = "rdpdr")]
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
 use anyhow::Context as _;
@@ -11,9 +13,9 @@ use base64::Engine as _;
 use futures_channel::mpsc;
 use futures_util::io::{ReadHalf, WriteHalf};
 use futures_util::{select, AsyncWriteExt as _, FutureExt as _, StreamExt as _};
-use gloo_net::websocket;
-use gloo_net::websocket::futures::WebSocket;
+#[cfg(feature = "clipboard")]
 use ironrdp::cliprdr::backend::ClipboardMessage;
+#[cfg(feature = "clipboard")]
 use ironrdp::cliprdr::CliprdrClient;
 use ironrdp::connector::connection_activation::ConnectionActivationState;
 use ironrdp::connector::credssp::KerberosConfig;
@@ -21,27 +23,53 @@ use ironrdp::connector::{self, ClientConnector, Credentials};
 use ironrdp::graphics::image_processing::PixelFormat;
 use ironrdp::pdu::input::fast_path::FastPathInputEvent;
 use ironrdp::pdu::rdp::client_info::PerformanceFlags;
+use ironrdp::pdu::rdp::session_info::ServerAutoReconnect;
+#[cfg(feature = "rdpdr")]
+use ironrdp::rdpdr::Rdpdr;
+#[cfg(feature = "rdpdr")]
+use ironrdp::rdpsnd::client::NoopRdpsndBackend;
+#[cfg(any(feature = "audio", feature = "rdpdr"))]
+use ironrdp::rdpsnd::client::Rdpsnd;
 use ironrdp::session::image::DecodedImage;
 use ironrdp::session::{fast_path, ActiveStage, ActiveStageOutput, GracefulDisconnectReason};
-use ironrdp_core::WriteBuf;
+use ironrdp_core::{DecodeMode, WriteBuf};
 use ironrdp_futures::single_sequence_step_read;
 use rgb::AsPixels as _;
 use tap::prelude::*;
 use wasm_bindgen::prelude::*;
 use wasm_bindgen_futures::spawn_local;
-use web_sys::HtmlCanvasElement;
-
-use crate::canvas::Canvas;
-use crate::clipboard::{ClipboardTransaction, WasmClipboard, WasmClipboardBackend, WasmClipboardBackendMessage};
+use web_sys::{HtmlCanvasElement, OffscreenCanvas};
+
+#[cfg(feature = "audio")]
+use crate::audio::WebAudioPlayer;
+use crate::canvas::{RenderCanvas, RenderTarget, Renderer, RendererKind};
+#[cfg(feature = "clipboard")]
+use crate::clipboard::{self, ClipboardTransaction, WasmClipboard, WasmClipboardBackend, WasmClipboardBackendMessage};
+#[cfg(feature = "rdpdr")]
+use crate::drive::{StagingArea, WebDriveBackend};
 use crate::error::{IronRdpError, IronRdpErrorKind};
+use crate::events::{ConnectionState, EventDispatcher, SessionEvent, SessionEventType, StatsCollector};
 use crate::image::extract_partial_image;
+use crate::ime::ImeComposition;
 use crate::input::InputTransaction;
 use crate::network_client::WasmNetworkClient;
-use crate::{clipboard, DesktopSize};
+use crate::pointer::{PointerInputEvent, PointerTracker};
+use crate::transport::{self, Datagrams, Transport};
+use crate::DesktopSize;
 
 const DEFAULT_WIDTH: u16 = 1280;
 const DEFAULT_HEIGHT: u16 = 720;
 
+/// Audio buffered before starting the playback, to absorb the network jitter
+#[cfg(feature = "audio")]
+const AUDIO_JITTER_BUFFER: Duration = Duration::from_millis(100);
+
+#[cfg(feature = "rdpdr")]
+const DRIVE_DEVICE_ID: u32 = 1;
+
+/// Delay between two reconnection attempts, see [`SessionBuilder::reconnect_grace_period`]
+const RECONNECT_RETRY_DELAY: Duration = Duration::from_secs(2);
+
 #[wasm_bindgen]
 #[derive(Clone, Default)]
 pub struct SessionBuilder(Rc<RefCell<SessionBuilderInner>>);
@@ -58,12 +86,26 @@ struct SessionBuilderInner {
     client_name: String,
     desktop_size: DesktopSize,
 
-    render_canvas: Option<HtmlCanvasElement>,
+    render_target: Option<RenderTarget>,
+    renderer: RendererKind,
     set_cursor_style_callback: Option<js_sys::Function>,
     set_cursor_style_callback_context: Option<JsValue>,
+    #[cfg(feature = "clipboard")]
     remote_clipboard_changed_callback: Option<js_sys::Function>,
+    #[cfg(feature = "clipboard")]
     remote_received_format_list_callback: Option<js_sys::Function>,
+    #[cfg(feature = "clipboard")]
     force_clipboard_update_callback: Option<js_sys::Function>,
+    #[cfg(feature = "clipboard")]
+    async_clipboard: bool,
+    #[cfg(feature = "audio")]
+    audio: bool,
+    #[cfg(feature = "rdpdr")]
+    drive_name: Option<String>,
+    #[cfg(feature = "rdpdr")]
+    drive_file_written_callback: Option<js_sys::Function>,
+    reconnect_grace_period: Option<Duration>,
+    events: EventDispatcher,
 }
 
 impl Default for SessionBuilderInner {
@@ -83,12 +125,26 @@ impl Default for SessionBuilderInner {
                 height: DEFAULT_HEIGHT,
             },
 
-            render_canvas: None,
+            render_target: None,
+            renderer: RendererKind::Canvas2d,
             set_cursor_style_callback: None,
             set_cursor_style_callback_context: None,
+            #[cfg(feature = "clipboard")]
             remote_clipboard_changed_callback: None,
+            #[cfg(feature = "clipboard")]
             remote_received_format_list_callback: None,
+            #[cfg(feature = "clipboard")]
             force_clipboard_update_callback: None,
+            #[cfg(feature = "clipboard")]
+            async_clipboard: false,
+            #[cfg(feature = "audio")]
+            audio: false,
+            #[cfg(feature = "rdpdr")]
+            drive_name: None,
+            #[cfg(feature = "rdpdr")]
+            drive_file_written_callback: None,
+            reconnect_grace_period: None,
+            events: EventDispatcher::default(),
         }
     }
 }
@@ -128,6 +184,8 @@ impl SessionBuilder {
     }
 
     /// Required
+    ///
+    /// The scheme selects the transport: WebSocket for `ws://` and `wss://`, WebTransport for `https://`.
     pub fn proxy_address(&self, address: String) -> SessionBuilder {
         self.0.borrow_mut().proxy_address = Some(address);
         self.clone()
@@ -159,7 +217,21 @@ impl SessionBuilder {
 
     /// Optional
     pub fn render_canvas(&self, canvas: HtmlCanvasElement) -> SessionBuilder {
-        self.0.borrow_mut().render_canvas = Some(canvas);
+        self.0.borrow_mut().render_target = Some(RenderTarget::Canvas(RenderCanvas::Element(canvas)));
+        self.clone()
+    }
+
+    /// Optional, replaces `render_canvas`
+    ///
+    /// Renders to an `OffscreenCanvas`, e.g.: obtained using `transferControlToOffscreen` when running in a worker.
+    pub fn offscreen_render_canvas(&self, canvas: OffscreenCanvas) -> SessionBuilder {
+        self.0.borrow_mut().render_target = Some(RenderTarget::Canvas(RenderCanvas::Offscreen(canvas)));
+        self.clone()
+    }
+
+    /// Optional, defaults to the 2D canvas renderer
+    pub fn renderer(&self, renderer: RendererKind) -> SessionBuilder {
+        self.0.borrow_mut().renderer = renderer;
         self.clone()
     }
 
@@ -191,25 +263,120 @@ impl SessionBuilder {
         self.clone()
     }
 
-    /// Optional
+    /// Optional, requires the `clipboard` feature
+    #[cfg(feature = "clipboard")]
     pub fn remote_clipboard_changed_callback(&self, callback: js_sys::Function) -> SessionBuilder {
         self.0.borrow_mut().remote_clipboard_changed_callback = Some(callback);
         self.clone()
     }
 
-    /// Optional
+    /// Optional, requires the `clipboard` feature
+    #[cfg(feature = "clipboard")]
     pub fn remote_received_format_list_callback(&self, callback: js_sys::Function) -> SessionBuilder {
         self.0.borrow_mut().remote_received_format_list_callback = Some(callback);
         self.clone()
     }
 
-    /// Optional
+    /// Optional, requires the `clipboard` feature
+    #[cfg(feature = "clipboard")]
     pub fn force_clipboard_update_callback(&self, callback: js_sys::Function) -> SessionBuilder {
         self.0.borrow_mut().force_clipboard_update_callback = Some(callback);
         self.clone()
     }
 
+    /// Optional, replaces the clipboard callbacks, requires the `clipboard` feature
+    ///
+    /// Accesses the system clipboard directly using the browser Async Clipboard API (text, HTML and PNG images).
+    /// Browsers only allow it in a secure context, for a focused document, and reading may require the
+    /// "clipboard-read" permission to be granted by the user. The local clipboard is announced to the remote each
+    /// time the page gains the focus, and remote clipboard updates are written once the page has the focus.
+    ///
+    /// When the API is unavailable, the clipboard callbacks are used if set, otherwise the clipboard is disabled.
+    #[cfg(feature = "clipboard")]
+    pub fn async_clipboard(&self, enabled: bool) -> SessionBuilder {
+        self.0.borrow_mut().async_clipboard = enabled;
+        self.clone()
+    }
+
+    /// Optional, disabled by default, requires the `audio` feature
+    ///
+    /// Plays the remote audio using the Web Audio API. Browsers may refuse to start the playback until the user
+    /// interacted with the page, so the session should preferably be connected from a user gesture handler.
+    #[cfg(feature = "audio")]
+    pub fn enable_audio(&self, enabled: bool) -> SessionBuilder {
+        self.0.borrow_mut().audio = enabled;
+        self.clone()
+    }
+
+    /// Optional, requires the `rdpdr` feature
+    ///
+    /// Redirects a drive with the given name, backed by an in-memory staging area. Files are moved in and out of the
+    /// remote session using `Session::drive_upload_file` and `Session::drive_download_file`.
+    #[cfg(feature = "rdpdr")]
+    pub fn drive(&self, name: String) -> SessionBuilder {
+        self.0.borrow_mut().drive_name = Some(name);
+        self.clone()
+    }
+
+    /// Optional, requires the `rdpdr` feature
+    ///
+    /// # Callback signature:
+    /// ```typescript
+    /// function callback(path: string, data: Uint8Array): void
+    /// ```
+    ///
+    /// Called when the remote closes a file of the redirected drive after writing it, e.g.: to prompt the user to
+    /// download it. The path is relative to the drive root, and uses `/` as the separator.
+    #[cfg(feature = "rdpdr")]
+    pub fn drive_file_written_callback(&self, callback: js_sys::Function) -> SessionBuilder {
+        self.0.borrow_mut().drive_file_written_callback = Some(callback);
+        self.clone()
+    }
+
+    /// Optional
+    ///
+    /// Keeps the session alive for the given grace period (in milliseconds) when the connection to the proxy is lost,
+    /// and reconnects to the same remote session over a new WebSocket (or WebTransport session) in the meantime.
+    /// The auto-reconnect cookie sent by the server is used, so the user is not prompted to log in again.
+    /// `Reconnecting` and `Connected` connection states are emitted around the reconnection.
+    ///
+    /// The auth token is reused, so it must remain valid for the whole session. Disabled by default (0).
+    pub fn reconnect_grace_period(&self, grace_period_ms: u32) -> SessionBuilder {
+        self.0.borrow_mut().reconnect_grace_period =
+            (grace_period_ms != 0).then(|| Duration::from_millis(u64::from(grace_period_ms)));
+        self.clone()
+    }
+
+    /// Optional
+    ///
+    /// Subscribes to the session events of the given type, the callback is called with a `SessionEvent`.
+    /// Subscribing before connecting allows to observe the `Connecting` state and the connection errors.
+    /// These subscriptions last for the whole session, see `Session.subscribe` for cancellable ones.
+    pub fn subscribe(&self, event_type: SessionEventType, callback: js_sys::Function) -> SessionBuilder {
+        self.0.borrow().events.subscribe(event_type, callback);
+        self.clone()
+    }
+
     pub async fn connect(&self) -> Result<Session, IronRdpError> {
+        let events = self.0.borrow().events.clone();
+
+        events.emit(SessionEvent::connection_state_changed(ConnectionState::Connecting));
+
+        match self.h_connect(events.clone()).await {
+            Ok(session) => {
+                events.emit(SessionEvent::connection_state_changed(ConnectionState::Connected));
+                Ok(session)
+            }
+            Err(error) => {
+                emit_failure(&events, &error);
+                Err(error)
+            }
+        }
+    }
+}
+
+impl SessionBuilder {
+    async fn h_connect(&self, events: EventDispatcher) -> Result<Session, IronRdpError> {
         let (
             username,
             destination,
@@ -221,12 +388,11 @@ impl SessionBuilder {
             kdc_proxy_url,
             client_name,
             desktop_size,
-            render_canvas,
+            render_target,
+            renderer,
             set_cursor_style_callback,
             set_cursor_style_callback_context,
-            remote_clipboard_changed_callback,
-            remote_received_format_list_callback,
-            force_clipboard_update_callback,
+            reconnect_grace_period,
         );
 
         {
@@ -243,7 +409,8 @@ impl SessionBuilder {
             client_name = inner.client_name.clone();
             desktop_size = inner.desktop_size.clone();
 
-            render_canvas = inner.render_canvas.clone().context("render_canvas missing")?;
+            render_target = inner.render_target.clone().context("render_canvas missing")?;
+            renderer = inner.renderer;
 
             set_cursor_style_callback = inner
                 .set_cursor_style_callback
@@ -253,9 +420,7 @@ impl SessionBuilder {
                 .set_cursor_style_callback_context
                 .clone()
                 .context("set_cursor_style_callback_context missing")?;
-            remote_clipboard_changed_callback = inner.remote_clipboard_changed_callback.clone();
-            remote_received_format_list_callback = inner.remote_received_format_list_callback.clone();
-            force_clipboard_update_callback = inner.force_clipboard_update_callback.clone();
+            reconnect_grace_period = inner.reconnect_grace_period;
         }
 
         info!("Connect to RDP host");
@@ -264,57 +429,51 @@ impl SessionBuilder {
 
         let (input_events_tx, input_events_rx) = mpsc::unbounded();
 
-        let clipboard = remote_clipboard_changed_callback.clone().map(|callback| {
-            WasmClipboard::new(
-                clipboard::WasmClipboardMessageProxy::new(input_events_tx.clone()),
-                clipboard::JsClipboardCallbacks {
-                    on_remote_clipboard_changed: callback,
-                    on_remote_received_format_list: remote_received_format_list_callback,
-                    on_force_clipboard_update: force_clipboard_update_callback,
-                },
-            )
-        });
+        #[cfg(feature = "clipboard")]
+        let clipboard = self.h_clipboard(&input_events_tx, &events);
+
+        let channels = StaticChannels {
+            #[cfg(feature = "clipboard")]
+            clipboard_backend: clipboard.as_ref().map(WasmClipboard::backend),
+            #[cfg(feature = "audio")]
+            audio: self.0.borrow().audio,
+            #[cfg(feature = "rdpdr")]
+            drive: self.0.borrow().drive_name.clone().map(|name| RedirectedDrive {
+                name,
+                staging_area: StagingArea::new_shared(),
+                input_events_tx: input_events_tx.clone(),
+            }),
+        };
 
-        let ws = WebSocket::open(&proxy_address).context("Couldn’t open WebSocket")?;
+        let reconnection = reconnect_grace_period.map(|grace_period| Reconnection {
+            grace_period,
+            config: config.clone(),
+            proxy_address: proxy_address.clone(),
+            auth_token: auth_token.clone(),
+            destination: destination.clone(),
+            pcb: pcb.clone(),
+            kdc_proxy_url: kdc_proxy_url.clone(),
+            channels: channels.clone(),
+        });
 
-        // NOTE: ideally, when the WebSocket can’t be opened, the above call should fail with details on why is that
-        // (e.g., the proxy hostname could not be resolved, proxy service is not running), but errors are neved
-        // bubbled up in practice, so instead we poll the WebSocket state until we know its connected (i.e., the
-        // WebSocket handshake is a success and user data can be exchanged).
-        loop {
-            match ws.state() {
-                websocket::State::Closing | websocket::State::Closed => {
-                    return Err(IronRdpError::from(anyhow::anyhow!(
-                        "Failed to connect to {proxy_address} (WebSocket is `{:?}`)",
-                        ws.state()
-                    ))
-                    .with_kind(IronRdpErrorKind::ProxyConnect));
-                }
-                websocket::State::Connecting => {
-                    trace!("WebSocket is connecting to proxy at {proxy_address}...");
-                    gloo_timers::future::sleep(Duration::from_millis(50)).await;
-                }
-                websocket::State::Open => {
-                    debug!("WebSocket connected to {proxy_address} with success");
-                    break;
-                }
-            }
-        }
+        let transport = transport::open(&proxy_address).await?;
 
-        let (connection_result, ws) = connect(
-            ws,
+        let (connection_result, transport) = connect(
+            transport,
             config,
             auth_token,
             destination,
             pcb,
             kdc_proxy_url,
-            clipboard.as_ref().map(|clip| clip.backend()),
+            &channels,
         )
         .await?;
 
         info!("Connected!");
 
-        let (rdp_reader, rdp_writer) = futures_util::AsyncReadExt::split(ws);
+        let datagrams = transport.datagrams();
+
+        let (rdp_reader, rdp_writer) = futures_util::AsyncReadExt::split(transport);
 
         let (writer_tx, writer_rx) = mpsc::unbounded();
 
@@ -322,28 +481,96 @@ impl SessionBuilder {
 
         Ok(Session {
             desktop_size: connection_result.desktop_size,
-            input_database: RefCell::new(ironrdp::input::Database::new()),
-            writer_tx,
+            input_database: RefCell::new({
+                let mut input_database = ironrdp::input::Database::new();
+                input_database.set_desktop_size(
+                    connection_result.desktop_size.width,
+                    connection_result.desktop_size.height,
+                );
+                input_database
+            }),
+            pointer_tracker: RefCell::new(PointerTracker::new(
+                connection_result.desktop_size.width,
+                connection_result.desktop_size.height,
+            )),
+            ime_composition: RefCell::new(ImeComposition::default()),
+            writer_tx: RefCell::new(writer_tx),
             input_events_tx,
 
-            render_canvas,
+            render_target,
+            renderer,
             set_cursor_style_callback,
             set_cursor_style_callback_context,
 
             input_events_rx: RefCell::new(Some(input_events_rx)),
             rdp_reader: RefCell::new(Some(rdp_reader)),
             connection_result: RefCell::new(Some(connection_result)),
+            #[cfg(feature = "clipboard")]
             clipboard: RefCell::new(Some(clipboard)),
+            #[cfg(feature = "rdpdr")]
+            drive_staging_area: channels.drive.map(|drive| drive.staging_area),
+            #[cfg(feature = "rdpdr")]
+            drive_file_written_callback: self.0.borrow().drive_file_written_callback.clone(),
+            reconnection,
+            events,
+            datagrams: RefCell::new(datagrams),
         })
     }
+
+    #[cfg(feature = "clipboard")]
+    fn h_clipboard(
+        &self,
+        input_events_tx: &mpsc::UnboundedSender<RdpInputEvent>,
+        events: &EventDispatcher,
+    ) -> Option<WasmClipboard> {
+        let inner = self.0.borrow();
+
+        let clipboard_proxy = clipboard::WasmClipboardMessageProxy::new(input_events_tx.clone());
+
+        let async_clipboard = if inner.async_clipboard {
+            match clipboard::AsyncClipboard::new(clipboard_proxy.clone()) {
+                Ok(async_clipboard) => Some(async_clipboard),
+                Err(error) => {
+                    warn!("Async Clipboard API integration unavailable: {error:#}");
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        let clipboard_integration = match async_clipboard {
+            Some(async_clipboard) => Some(clipboard::ClipboardIntegration::AsyncClipboard(async_clipboard)),
+            None => inner.remote_clipboard_changed_callback.clone().map(|callback| {
+                clipboard::ClipboardIntegration::Callbacks(clipboard::JsClipboardCallbacks {
+                    on_remote_clipboard_changed: callback,
+                    on_remote_received_format_list: inner.remote_received_format_list_callback.clone(),
+                    on_force_clipboard_update: inner.force_clipboard_update_callback.clone(),
+                })
+            }),
+        };
+
+        clipboard_integration.map(|integration| WasmClipboard::new(clipboard_proxy, integration, events.clone()))
+    }
+
+    /// Posts the updated regions to the main thread instead of rendering them, see [`crate::worker`]
+    pub(crate) fn post_frames_to_main_thread(&self) -> SessionBuilder {
+        self.0.borrow_mut().render_target = Some(RenderTarget::MainThread);
+        self.clone()
+    }
 }
 
 pub(crate) type FastPathInputEvents = smallvec::SmallVec<[FastPathInputEvent; 2]>;
 
 #[derive(Debug)]
 pub(crate) enum RdpInputEvent {
+    #[cfg(feature = "clipboard")]
     Cliprdr(ClipboardMessage),
+    #[cfg(feature = "clipboard")]
     ClipboardBackend(WasmClipboardBackendMessage),
+    /// A file of the redirected drive was written by the remote
+    #[cfg(feature = "rdpdr")]
+    DriveFileWritten(String),
     FastPath(FastPathInputEvents),
     TerminateSession,
 }
@@ -358,6 +585,72 @@ enum CursorStyle {
     },
 }
 
+/// Parameters required to reopen the connection, see [`SessionBuilder::reconnect_grace_period`]
+struct Reconnection {
+    grace_period: Duration,
+    config: connector::Config,
+    proxy_address: String,
+    auth_token: String,
+    destination: String,
+    pcb: Option<String>,
+    kdc_proxy_url: Option<String>,
+    channels: StaticChannels,
+}
+
+/// Static virtual channels attached to each connection, depending on the enabled cargo features
+#[derive(Clone)]
+struct StaticChannels {
+    #[cfg(feature = "clipboard")]
+    clipboard_backend: Option<WasmClipboardBackend>,
+    #[cfg(feature = "audio")]
+    audio: bool,
+    #[cfg(feature = "rdpdr")]
+    drive: Option<RedirectedDrive>,
+}
+
+#[cfg(feature = "rdpdr")]
+#[derive(Clone)]
+struct RedirectedDrive {
+    name: String,
+    staging_area: Arc<Mutex<StagingArea>>,
+    /// Notified when the remote writes a file
+    input_events_tx: mpsc::UnboundedSender<RdpInputEvent>,
+}
+
+impl StaticChannels {
+    #[cfg_attr(
+        not(any(feature = "clipboard", feature = "audio", feature = "rdpdr")),
+        allow(unused_variables, clippy::unused_self)
+    )] // nothing to attach in a minimal build
+    fn attach(&self, connector: &mut ClientConnector) {
+        #[cfg(feature = "clipboard")]
+        if let Some(clipboard_backend) = &self.clipboard_backend {
+            connector.attach_static_channel(CliprdrClient::new(Box::new(clipboard_backend.clone())));
+        }
+
+        #[cfg(feature = "audio")]
+        if self.audio {
+            let audio_sink = WebAudioPlayer::spawn(AUDIO_JITTER_BUFFER);
+            connector.attach_static_channel(Rdpsnd::new(Box::new(audio_sink)));
+        }
+
+        #[cfg(feature = "rdpdr")]
+        if let Some(drive) = &self.drive {
+            // The rdpsnd channel is required for the rdpdr channel to work.
+            if connector.static_channels.get_by_type::<Rdpsnd>().is_none() {
+                connector.attach_static_channel(Rdpsnd::new(Box::new(NoopRdpsndBackend)));
+            }
+
+            let drive_backend = WebDriveBackend::new(Arc::clone(&drive.staging_area), drive.input_events_tx.clone());
+
+            connector.attach_static_channel(
+                Rdpdr::new(Box::new(drive_backend), "IronRDP".to_owned())
+                    .with_drives(Some(vec![(DRIVE_DEVICE_ID, drive.name.clone())])),
+            );
+        }
+    }
+}
+
 #[wasm_bindgen]
 pub struct SessionTerminationInfo {
     reason: GracefulDisconnectReason,
@@ -374,23 +667,62 @@ impl SessionTerminationInfo {
 pub struct Session {
     desktop_size: connector::DesktopSize,
     input_database: RefCell<ironrdp::input::Database>,
-    writer_tx: mpsc::UnboundedSender<Vec<u8>>,
+    pointer_tracker: RefCell<PointerTracker>,
+    ime_composition: RefCell<ImeComposition>,
+    // Replaced when the connection is reopened
+    writer_tx: RefCell<mpsc::UnboundedSender<Vec<u8>>>,
     input_events_tx: mpsc::UnboundedSender<RdpInputEvent>,
 
-    render_canvas: HtmlCanvasElement,
+    render_target: RenderTarget,
+    renderer: RendererKind,
     set_cursor_style_callback: js_sys::Function,
     set_cursor_style_callback_context: JsValue,
 
     // Consumed when `run` is called
     input_events_rx: RefCell<Option<mpsc::UnboundedReceiver<RdpInputEvent>>>,
     connection_result: RefCell<Option<connector::ConnectionResult>>,
-    rdp_reader: RefCell<Option<ReadHalf<WebSocket>>>,
+    rdp_reader: RefCell<Option<ReadHalf<Box<dyn Transport>>>>,
+    #[cfg(feature = "clipboard")]
     clipboard: RefCell<Option<Option<WasmClipboard>>>,
+
+    #[cfg(feature = "rdpdr")]
+    drive_staging_area: Option<Arc<Mutex<StagingArea>>>,
+    #[cfg(feature = "rdpdr")]
+    drive_file_written_callback: Option<js_sys::Function>,
+
+    reconnection: Option<Reconnection>,
+    events: EventDispatcher,
+    datagrams: RefCell<Option<Datagrams>>,
 }
 
 #[wasm_bindgen]
 impl Session {
     pub async fn run(&self) -> Result<SessionTerminationInfo, IronRdpError> {
+        let result = self.h_run().await;
+
+        if let Err(error) = &result {
+            emit_failure(&self.events, error);
+        }
+
+        self.events
+            .emit(SessionEvent::connection_state_changed(ConnectionState::Disconnected));
+
+        result
+    }
+
+    /// Subscribes to the session events of the given type, and returns the subscription ID
+    ///
+    /// The callback is called with a `SessionEvent`.
+    pub fn subscribe(&self, event_type: SessionEventType, callback: js_sys::Function) -> u32 {
+        self.events.subscribe(event_type, callback)
+    }
+
+    /// Cancels a subscription, returns `false` if there is no such subscription
+    pub fn unsubscribe(&self, subscription_id: u32) -> bool {
+        self.events.unsubscribe(subscription_id)
+    }
+
+    async fn h_run(&self) -> Result<SessionTerminationInfo, IronRdpError> {
         let rdp_reader = self
             .rdp_reader
             .borrow_mut()
@@ -409,14 +741,16 @@ impl Session {
             .take()
             .expect("run called only once");
 
+        #[cfg(feature = "clipboard")]
         let mut clipboard = self.clipboard.borrow_mut().take().expect("run called only once");
 
         let mut framed = ironrdp_futures::LocalFuturesFramed::new(rdp_reader);
 
         debug!("Initialize canvas");
 
-        let mut gui = Canvas::new(
-            self.render_canvas.clone(),
+        let mut gui = Renderer::new(
+            self.render_target.clone(),
+            self.renderer,
             u32::from(connection_result.desktop_size.width),
             u32::from(connection_result.desktop_size.height),
         )
@@ -434,18 +768,36 @@ impl Session {
 
         let mut active_stage = ActiveStage::new(connection_result);
 
+        let mut stats = StatsCollector::new();
+
         let disconnect_reason = 'outer: loop {
+            let mut connection_lost = false;
+
             let outputs = select! {
                 frame = framed.read_pdu().fuse() => {
-                    let (action, payload) = frame.context("read frame")?;
-                    trace!(?action, frame_length = payload.len(), "Frame received");
+                    match frame {
+                        Ok((action, payload)) => {
+                            trace!(?action, frame_length = payload.len(), "Frame received");
+
+                            if let Some(stats) = stats.record_frame(payload.len()) {
+                                self.events.emit(SessionEvent::stats(stats));
+                            }
 
-                    active_stage.process(&mut image, action, &payload)?
+                            active_stage.process(&mut image, action, &payload)?
+                        }
+                        Err(error) if self.reconnection.is_some() => {
+                            warn!("Connection lost: {error}");
+                            connection_lost = true;
+                            Vec::new()
+                        }
+                        Err(error) => return Err(anyhow::Error::new(error).context("read frame").into()),
+                    }
                 }
                 input_events = input_events.next() => {
                     let event = input_events.context("read next input events")?;
 
                     match event {
+                        #[cfg(feature = "clipboard")]
                         RdpInputEvent::Cliprdr(message) => {
                             if let Some(cliprdr) = active_stage.get_svc_processor::<CliprdrClient>() {
                                 if let Some(svc_messages) = match message {
@@ -478,6 +830,7 @@ impl Session {
                                 Vec::new()
                             }
                         }
+                        #[cfg(feature = "clipboard")]
                         RdpInputEvent::ClipboardBackend(event) => {
                             if let Some(clipboard) = &mut clipboard {
                                 clipboard.process_event(event)?;
@@ -485,6 +838,12 @@ impl Session {
                             // No RDP output frames for backend event processing
                             Vec::new()
                         }
+                        #[cfg(feature = "rdpdr")]
+                        RdpInputEvent::DriveFileWritten(path) => {
+                            self.notify_drive_file_written(&path)?;
+                            // The file was already written by the drive backend
+                            Vec::new()
+                        }
                         RdpInputEvent::FastPath(events) => {
                             active_stage.process_fastpath_input(&mut image, &events)
                                 .context("fast path input events processing")?
@@ -497,10 +856,43 @@ impl Session {
                 }
             };
 
+            if connection_lost {
+                let Some((connection_result, rdp_reader)) = self
+                    .h_reconnect(&mut input_events, active_stage.auto_reconnect().cloned())
+                    .await?
+                else {
+                    break 'outer GracefulDisconnectReason::UserInitiated;
+                };
+
+                let desktop_size = connection_result.desktop_size;
+
+                framed = ironrdp_futures::LocalFuturesFramed::new(rdp_reader);
+                image = DecodedImage::new(PixelFormat::RgbA32, desktop_size.width, desktop_size.height);
+                active_stage = ActiveStage::new(connection_result);
+
+                // The keys and buttons pressed before the connection was lost are released on the remote.
+                let mut input_database = ironrdp::input::Database::new();
+                input_database.set_desktop_size(desktop_size.width, desktop_size.height);
+                *self.input_database.borrow_mut() = input_database;
+                self.pointer_tracker
+                    .borrow_mut()
+                    .set_desktop_size(desktop_size.width, desktop_size.height);
+
+                self.events
+                    .emit(SessionEvent::connection_state_changed(ConnectionState::Connected));
+                self.events.emit(SessionEvent::resized(DesktopSize {
+                    width: desktop_size.width,
+                    height: desktop_size.height,
+                }));
+
+                continue;
+            }
+
             for out in outputs {
                 match out {
                     ActiveStageOutput::ResponseFrame(frame) => {
                         self.writer_tx
+                            .borrow()
                             .unbounded_send(frame)
                             .context("Send frame to writer task")?;
                     }
@@ -621,6 +1013,7 @@ impl Session {
 
                             if written.size().is_some() {
                                 self.writer_tx
+                                    .borrow()
                                     .unbounded_send(buf.filled().to_vec())
                                     .context("Send frame to writer task")?;
                             }
@@ -631,6 +1024,7 @@ impl Session {
                                 desktop_size,
                                 no_server_pointer,
                                 pointer_software_rendering,
+                                ..
                             } = box_connection_activation.state
                             {
                                 debug!("Deactivation-Reactivation Sequence completed");
@@ -647,13 +1041,28 @@ impl Session {
                                     .build(),
                                 );
                                 active_stage.set_no_server_pointer(no_server_pointer);
+                                self.input_database
+                                    .borrow_mut()
+                                    .set_desktop_size(desktop_size.width, desktop_size.height);
+                                self.pointer_tracker
+                                    .borrow_mut()
+                                    .set_desktop_size(desktop_size.width, desktop_size.height);
+                                self.events.emit(SessionEvent::resized(DesktopSize {
+                                    width: desktop_size.width,
+                                    height: desktop_size.height,
+                                }));
                                 break 'activation_seq;
                             }
                         }
                     }
+                    ActiveStageOutput::MonitorLayout(monitors) => {
+                        debug!(?monitors, "Server changed the monitor layout");
+                    }
                     ActiveStageOutput::Terminate(reason) => break 'outer reason,
                 }
             }
+
+            gui.present();
         };
 
         info!(%disconnect_reason, "RPD session terminated");
@@ -663,6 +1072,94 @@ impl Session {
         })
     }
 
+    /// Reopens the connection until it succeeds or the grace period expires
+    ///
+    /// Returns `None` if the session is terminated in the meantime. Input events received while disconnected are
+    /// dropped, since the remote can’t process them.
+    async fn h_reconnect(
+        &self,
+        input_events: &mut mpsc::UnboundedReceiver<RdpInputEvent>,
+        auto_reconnect: Option<ServerAutoReconnect>,
+    ) -> Result<Option<(connector::ConnectionResult, ReadHalf<Box<dyn Transport>>)>, IronRdpError> {
+        let reconnection = self.reconnection.as_ref().context("reconnection is disabled")?;
+
+        if auto_reconnect.is_none() {
+            debug!("No auto-reconnect cookie received from the server, the credentials are used instead");
+        }
+
+        self.events
+            .emit(SessionEvent::connection_state_changed(ConnectionState::Reconnecting));
+
+        let reconnect = async {
+            let deadline = js_sys::Date::now() + reconnection.grace_period.as_secs_f64() * 1000.0;
+
+            let mut config = reconnection.config.clone();
+            config.auto_reconnect = auto_reconnect;
+
+            loop {
+                match self.h_reconnect_once(reconnection, config.clone()).await {
+                    Ok(reconnected) => break Ok(reconnected),
+                    Err(error) if js_sys::Date::now() + RECONNECT_RETRY_DELAY.as_secs_f64() * 1000.0 < deadline => {
+                        warn!("Reconnection attempt failed: {}", error.message());
+                        gloo_timers::future::sleep(RECONNECT_RETRY_DELAY).await;
+                    }
+                    Err(error) => break Err(error),
+                }
+            }
+        }
+        .fuse();
+
+        futures_util::pin_mut!(reconnect);
+
+        loop {
+            select! {
+                reconnected = reconnect => {
+                    let reconnected = reconnected?;
+                    info!("Reconnected!");
+                    return Ok(Some(reconnected));
+                }
+                input_event = input_events.next() => {
+                    match input_event.context("read next input events")? {
+                        RdpInputEvent::TerminateSession => return Ok(None),
+                        input_event => trace!(?input_event, "Input event dropped while reconnecting"),
+                    }
+                }
+            }
+        }
+    }
+
+    async fn h_reconnect_once(
+        &self,
+        reconnection: &Reconnection,
+        config: connector::Config,
+    ) -> Result<(connector::ConnectionResult, ReadHalf<Box<dyn Transport>>), IronRdpError> {
+        let transport = transport::open(&reconnection.proxy_address).await?;
+
+        let (connection_result, transport) = connect(
+            transport,
+            config,
+            reconnection.auth_token.clone(),
+            reconnection.destination.clone(),
+            reconnection.pcb.clone(),
+            reconnection.kdc_proxy_url.clone(),
+            &reconnection.channels,
+        )
+        .await?;
+
+        *self.datagrams.borrow_mut() = transport.datagrams();
+
+        let (rdp_reader, rdp_writer) = futures_util::AsyncReadExt::split(transport);
+
+        let (writer_tx, writer_rx) = mpsc::unbounded();
+
+        spawn_local(writer_task(writer_rx, rdp_writer));
+
+        // The previous writer task ends once its sender is dropped.
+        *self.writer_tx.borrow_mut() = writer_tx;
+
+        Ok((connection_result, rdp_reader))
+    }
+
     pub fn desktop_size(&self) -> DesktopSize {
         DesktopSize {
             width: self.desktop_size.width,
@@ -675,6 +1172,66 @@ impl Session {
         self.h_send_inputs(inputs)
     }
 
+    /// Handles a DOM `PointerEvent` (mouse, pen or touch)
+    ///
+    /// A single touch contact emulates the mouse, and two contacts pinch-zoom the canvas. The zoom is reported
+    /// using `Zoom` session events, and the positions of the following events are remapped accordingly.
+    pub fn apply_pointer_event(&self, event: PointerInputEvent) -> Result<(), IronRdpError> {
+        let (operations, zoom) = {
+            let mut pointer_tracker = self.pointer_tracker.borrow_mut();
+            let previous_zoom = pointer_tracker.zoom();
+            let operations = pointer_tracker.process(&event);
+            let zoom = pointer_tracker.zoom();
+            (operations, (zoom != previous_zoom).then_some(zoom))
+        };
+
+        if let Some(zoom) = zoom {
+            self.events.emit(SessionEvent::zoomed(zoom));
+        }
+
+        let inputs = self.input_database.borrow_mut().apply(operations);
+        self.h_send_inputs(inputs)
+    }
+
+    /// Cancels the pinch-zoom of the canvas
+    pub fn reset_zoom(&self) {
+        let zoom = {
+            let mut pointer_tracker = self.pointer_tracker.borrow_mut();
+            pointer_tracker.reset_zoom();
+            pointer_tracker.zoom()
+        };
+
+        self.events.emit(SessionEvent::zoomed(zoom));
+    }
+
+    /// Handles the DOM `compositionstart` event
+    pub fn composition_start(&self) {
+        self.ime_composition.borrow_mut().start();
+    }
+
+    /// Handles the DOM `compositionupdate` event
+    ///
+    /// The text being composed is displayed by the IME candidate window, it is only sent once committed.
+    pub fn composition_update(&self) {
+        self.ime_composition.borrow_mut().update();
+    }
+
+    /// Handles the DOM `compositionend` event, typing the composed text on the remote
+    pub fn composition_end(&self, data: &str) -> Result<(), IronRdpError> {
+        let operations = self.ime_composition.borrow_mut().end(data);
+        let inputs = self.input_database.borrow_mut().apply(operations);
+        self.h_send_inputs(inputs)
+    }
+
+    /// Whether a `keydown` or `keyup` event belongs to an IME composition, and must not be applied
+    ///
+    /// `is_composing` and `key_code` are the `KeyboardEvent` properties of the same name.
+    pub fn is_composition_key_event(&self, is_composing: bool, key_code: u32) -> bool {
+        self.ime_composition
+            .borrow()
+            .is_composition_key_event(is_composing, key_code)
+    }
+
     pub fn release_all_inputs(&self) -> Result<(), IronRdpError> {
         let inputs = self.input_database.borrow_mut().release_all();
         self.h_send_inputs(inputs)
@@ -699,18 +1256,16 @@ impl Session {
         caps_lock: bool,
         kana_lock: bool,
     ) -> Result<(), IronRdpError> {
-        use ironrdp::pdu::input::fast_path::FastPathInput;
-
-        let event = ironrdp::input::synchronize_event(scroll_lock, num_lock, caps_lock, kana_lock);
-        let fastpath_input = FastPathInput(vec![event]);
-
-        let frame = ironrdp::core::encode_vec(&fastpath_input).context("FastPathInput encoding")?;
-
-        self.writer_tx
-            .unbounded_send(frame)
-            .context("Send frame to writer task")?;
-
-        Ok(())
+        let inputs = self
+            .input_database
+            .borrow_mut()
+            .apply([ironrdp::input::Operation::SyncLocks {
+                scroll_lock,
+                num_lock,
+                caps_lock,
+                kana_lock,
+            }]);
+        self.h_send_inputs(inputs)
     }
 
     pub fn shutdown(&self) -> Result<(), IronRdpError> {
@@ -721,6 +1276,7 @@ impl Session {
         Ok(())
     }
 
+    #[cfg(feature = "clipboard")]
     pub async fn on_clipboard_paste(&self, content: ClipboardTransaction) -> Result<(), IronRdpError> {
         self.input_events_tx
             .unbounded_send(RdpInputEvent::ClipboardBackend(
@@ -731,6 +1287,112 @@ impl Session {
         Ok(())
     }
 
+    /// Adds a file to the redirected drive, replacing any existing file
+    ///
+    /// The path is relative to the drive root, and uses `/` as the separator. Missing directories are created.
+    #[cfg(feature = "rdpdr")]
+    pub fn drive_upload_file(&self, path: String, data: Vec<u8>) -> Result<(), IronRdpError> {
+        self.drive_staging_area()?
+            .lock()
+            .expect("staging area lock poisoned")
+            .upload_file(&path, data)
+            .context("upload file")?;
+
+        Ok(())
+    }
+
+    /// Returns the content of a file of the redirected drive
+    #[cfg(feature = "rdpdr")]
+    pub fn drive_download_file(&self, path: String) -> Result<Vec<u8>, IronRdpError> {
+        let data = self
+            .drive_staging_area()?
+            .lock()
+            .expect("staging area lock poisoned")
+            .file(&path)
+            .map(<[u8]>::to_vec)
+            .with_context(|| format!("no such file: {path}"))?;
+
+        Ok(data)
+    }
+
+    /// Removes a file or a directory, including its content, from the redirected drive
+    #[cfg(feature = "rdpdr")]
+    pub fn drive_remove_file(&self, path: String) -> Result<bool, IronRdpError> {
+        Ok(self
+            .drive_staging_area()?
+            .lock()
+            .expect("staging area lock poisoned")
+            .remove(&path))
+    }
+
+    /// Lists the paths of the files of the redirected drive
+    #[cfg(feature = "rdpdr")]
+    pub fn drive_files(&self) -> Result<js_sys::Array, IronRdpError> {
+        Ok(self
+            .drive_staging_area()?
+            .lock()
+            .expect("staging area lock poisoned")
+            .files()
+            .into_iter()
+            .map(JsValue::from)
+            .collect())
+    }
+
+    /// Whether the transport supports unreliable datagrams (WebTransport only)
+    pub fn supports_datagrams(&self) -> bool {
+        self.datagrams.borrow().is_some()
+    }
+
+    /// Sends an unreliable datagram to the proxy, which may be dropped on the way
+    pub async fn send_datagram(&self, data: Vec<u8>) -> Result<(), IronRdpError> {
+        self.datagrams()?.send(&data).await.context("send datagram")?;
+        Ok(())
+    }
+
+    /// Receives the next datagram from the proxy, returns `undefined` once the session is closed
+    pub async fn receive_datagram(&self) -> Result<Option<js_sys::Uint8Array>, IronRdpError> {
+        let datagram = self.datagrams()?.recv().await.context("receive datagram")?;
+        Ok(datagram.as_deref().map(js_sys::Uint8Array::from))
+    }
+
+    // Cloned so that the borrow is not held across awaits, the transport being replaced on reconnection.
+    fn datagrams(&self) -> anyhow::Result<Datagrams> {
+        self.datagrams
+            .borrow()
+            .clone()
+            .context("the transport does not support datagrams")
+    }
+
+    #[cfg(feature = "rdpdr")]
+    fn drive_staging_area(&self) -> anyhow::Result<&Mutex<StagingArea>> {
+        self.drive_staging_area
+            .as_deref()
+            .context("drive redirection is not enabled")
+    }
+
+    #[cfg(feature = "rdpdr")]
+    fn notify_drive_file_written(&self, path: &str) -> Result<(), IronRdpError> {
+        let (Some(callback), Ok(staging_area)) = (&self.drive_file_written_callback, self.drive_staging_area()) else {
+            return Ok(());
+        };
+
+        // The file may have been removed or renamed in the meantime.
+        let Some(data) = staging_area
+            .lock()
+            .expect("staging area lock poisoned")
+            .file(path)
+            .map(js_sys::Uint8Array::from)
+        else {
+            return Ok(());
+        };
+
+        callback
+            .call2(&JsValue::NULL, &JsValue::from_str(path), &data)
+            .map_err(|e| anyhow::Error::msg(format!("drive file written callback failed: {e:?}")))?;
+
+        Ok(())
+    }
+
     fn set_cursor_style(&self, style: CursorStyle) -> Result<(), IronRdpError> {
         let (kind, data, hotspot_x, hotspot_y) = match style {
             CursorStyle::Default => ("default", None, None, None),
@@ -742,6 +1404,13 @@ impl Session {
             } => ("url", Some(data), Some(hotspot_x), Some(hotspot_y)),
         };
 
+        self.events.emit(SessionEvent::pointer_shape_changed(
+            kind,
+            data.clone(),
+            hotspot_x.unwrap_or_default(),
+            hotspot_y.unwrap_or_default(),
+        ));
+
         let args = js_sys::Array::from_iter([
             JsValue::from_str(kind),
             JsValue::from(data),
@@ -765,6 +1434,10 @@ impl Session {
     }
 }
 
+fn emit_failure(events: &EventDispatcher, error: &IronRdpError) {
+    events.emit(SessionEvent::error(error.kind(), error.message()));
+}
+
 fn build_config(
     username: String,
     password: String,
@@ -791,6 +1464,7 @@ fn build_config(
         bitmap: Some(connector::BitmapConfig {
             color_depth: 16,
             lossy_compression: true,
+            remotefx: cfg!(feature = "rfx"),
         }),
         #[allow(clippy::arithmetic_side_effects)] // fine unless we end up with an insanely big version
         client_build: semver::Version::parse(env!("CARGO_PKG_VERSION"))
@@ -805,18 +1479,24 @@ fn build_config(
         platform: ironrdp::pdu::rdp::capability_sets::MajorPlatformType::UNSPECIFIED,
         no_server_pointer: false,
         autologon: false,
+        restricted_admin: false,
+        auto_reconnect: None,
+        decode_mode: DecodeMode::Lenient,
+        decode_limits: Default::default(),
+        monitors: Vec::new(),
+        persistent_bitmap_cache: Vec::new(),
         pointer_software_rendering: false,
         performance_flags: PerformanceFlags::default(),
         desktop_scale_factor: 0,
     }
 }
 
-async fn writer_task(rx: mpsc::UnboundedReceiver<Vec<u8>>, rdp_writer: WriteHalf<WebSocket>) {
+async fn writer_task(rx: mpsc::UnboundedReceiver<Vec<u8>>, rdp_writer: WriteHalf<Box<dyn Transport>>) {
     debug!("writer task started");
 
     async fn inner(
         mut rx: mpsc::UnboundedReceiver<Vec<u8>>,
-        mut rdp_writer: WriteHalf<WebSocket>,
+        mut rdp_writer: WriteHalf<Box<dyn Transport>>,
     ) -> anyhow::Result<()> {
         while let Some(frame) = rx.next().await {
             rdp_writer.write_all(&frame).await.context("Couldn’t write frame")?;
@@ -833,21 +1513,19 @@ async fn writer_task(rx: mpsc::UnboundedReceiver<Vec<u8>>, rdp_writer: WriteHalf
 }
 
 async fn connect(
-    ws: WebSocket,
+    transport: Box<dyn Transport>,
     config: connector::Config,
     proxy_auth_token: String,
     destination: String,
     pcb: Option<String>,
     kdc_proxy_url: Option<String>,
-    clipboard_backend: Option<WasmClipboardBackend>,
-) -> Result<(connector::ConnectionResult, WebSocket), IronRdpError> {
-    let mut framed = ironrdp_futures::LocalFuturesFramed::new(ws);
+    channels: &StaticChannels,
+) -> Result<(connector::ConnectionResult, Box<dyn Transport>), IronRdpError> {
+    let mut framed = ironrdp_futures::LocalFuturesFramed::new(transport);
 
     let mut connector = ClientConnector::new(config);
 
-    if let Some(clipboard_backend) = clipboard_backend {
-        connector.attach_static_channel(CliprdrClient::new(Box::new(clipboard_backend)));
-    }
+    channels.attach(&mut connector);
 
     let (upgraded, server_public_key) =
         connect_rdcleanpath(&mut framed, &mut connector, destination.clone(), proxy_auth_token, pcb).await?;
@@ -870,9 +1548,9 @@ async fn connect(
     )
     .await?;
 
-    let ws = framed.into_inner_no_leftover();
+    let transport = framed.into_inner_no_leftover();
 
-    Ok((connection_result, ws))
+    Ok((connection_result, transport))
 }
 
 async fn connect_rdcleanpath<S>(
@@ -885,9 +1563,6 @@ async fn connect_rdcleanpath<S>(
 where
     S: ironrdp_futures::FramedRead + ironrdp_futures::FramedWrite,
 {
-    use ironrdp::connector::Sequence as _;
-    use x509_cert::der::Decode as _;
-
     #[derive(Clone, Copy, Debug)]
     struct RDCleanPathHint;
 
@@ -897,7 +1572,7 @@ where
         fn find_size(&self, bytes: &[u8]) -> ironrdp::core::DecodeResult<Option<(bool, usize)>> {
             match ironrdp_rdcleanpath::RDCleanPathPdu::detect(bytes) {
                 ironrdp_rdcleanpath::DetectionResult::Detected { total_length, .. } => Ok(Some((true, total_length))),
-                ironrdp_rdcleanpath::DetectionResult::NotEnoughBytes => Ok(None),
+                ironrdp_rdcleanpath::DetectionResult::NotEnoughBytes { .. } => Ok(None),
                 ironrdp_rdcleanpath::DetectionResult::Failed => Err(ironrdp::core::other_err!(
                     "RDCleanPathHint",
                     "detection failed (invalid PDU)"
@@ -906,29 +1581,15 @@ where
         }
     }
 
-    let mut buf = WriteBuf::new();
-
     info!("Begin connection procedure");
 
+    let rdcleanpath = ironrdp_rdcleanpath::connector::RDCleanPathConnector::new(destination, proxy_auth_token)
+        .with_preconnection_blob(pcb);
+
     {
         // RDCleanPath request
 
-        let connector::ClientConnectorState::ConnectionInitiationSendRequest = connector.state else {
-            return Err(anyhow::Error::msg("invalid connector state (send request)").into());
-        };
-
-        debug_assert!(connector.next_pdu_hint().is_none());
-
-        let written = connector.step_no_input(&mut buf)?;
-        let x224_pdu_len = written.size().expect("written size");
-        debug_assert_eq!(x224_pdu_len, buf.filled_len());
-        let x224_pdu = buf.filled().to_vec();
-
-        let rdcleanpath_req =
-            ironrdp_rdcleanpath::RDCleanPathPdu::new_request(x224_pdu, destination, proxy_auth_token, pcb)
-                .context("new RDCleanPath request")?;
-        debug!(message = ?rdcleanpath_req, "Send RDCleanPath request");
-        let rdcleanpath_req = rdcleanpath_req.to_der().context("RDCleanPath request encode")?;
+        let rdcleanpath_req = rdcleanpath.request(connector).context("RDCleanPath request")?;
 
         framed
             .write_all(&rdcleanpath_req)
@@ -944,61 +1605,15 @@ where
             .await
             .context("read RDCleanPath request")?;
 
-        let rdcleanpath_res =
-            ironrdp_rdcleanpath::RDCleanPathPdu::from_der(&rdcleanpath_res).context("RDCleanPath response decode")?;
-
-        debug!(message = ?rdcleanpath_res, "Received RDCleanPath PDU");
-
-        let (x224_connection_response, server_cert_chain, server_addr) =
-            match rdcleanpath_res.into_enum().context("invalid RDCleanPath PDU")? {
-                ironrdp_rdcleanpath::RDCleanPath::Request { .. } => {
-                    return Err(anyhow::Error::msg("received an unexpected RDCleanPath type (request)").into());
-                }
-                ironrdp_rdcleanpath::RDCleanPath::Response {
-                    x224_connection_response,
-                    server_cert_chain,
-                    server_addr,
-                } => (x224_connection_response, server_cert_chain, server_addr),
-                ironrdp_rdcleanpath::RDCleanPath::Err(error) => {
-                    return Err(
-                        IronRdpError::from(anyhow::anyhow!("received an RDCleanPath error: {error}"))
-                            .with_kind(IronRdpErrorKind::RDCleanPath),
-                    );
-                }
-            };
-
-        let server_addr = server_addr
-            .parse()
-            .context("failed to parse server address sent by proxy")?;
-
-        connector.attach_server_addr(server_addr);
-
-        let connector::ClientConnectorState::ConnectionInitiationWaitConfirm { .. } = connector.state else {
-            return Err(anyhow::Error::msg("invalid connector state (wait confirm)").into());
+        let upgrade = match rdcleanpath.process_response(connector, &rdcleanpath_res) {
+            Ok(upgrade) => upgrade,
+            Err(error @ ironrdp_rdcleanpath::connector::RDCleanPathConnectorError::Proxy(_)) => {
+                return Err(IronRdpError::from(anyhow::Error::new(error)).with_kind(IronRdpErrorKind::RDCleanPath));
+            }
+            Err(error) => return Err(anyhow::Error::new(error).context("RDCleanPath response").into()),
         };
 
-        debug_assert!(connector.next_pdu_hint().is_some());
-
-        buf.clear();
-        let written = connector.step(x224_connection_response.as_bytes(), &mut buf)?;
-
-        debug_assert!(written.is_nothing());
-
-        let server_cert = server_cert_chain
-            .into_iter()
-            .next()
-            .context("server cert chain missing from rdcleanpath response")?;
-
-        let cert = x509_cert::Certificate::from_der(server_cert.as_bytes())
-            .context("failed to decode x509 certificate sent by proxy")?;
-
-        let server_public_key = cert
-            .tbs_certificate
-            .subject_public_key_info
-            .subject_public_key
-            .as_bytes()
-            .context("subject public key BIT STRING is not aligned")?
-            .to_owned();
+        debug!(server_addr = %upgrade.server_addr, "Received RDCleanPath response");
 
         let should_upgrade = ironrdp_futures::skip_connect_begin(connector);
 
@@ -1006,12 +1621,12 @@ where
 
         let upgraded = ironrdp_futures::mark_as_upgraded(should_upgrade, connector);
 
-        Ok((upgraded, server_public_key))
+        Ok((upgraded, upgrade.server_public_key))
     }
 }
 
 #[allow(clippy::cast_sign_loss)]
 #[allow(clippy::cast_possible_truncation)]
-fn f64_to_u16_saturating_cast(value: f64) -> u16 {
+pub(crate) fn f64_to_u16_saturating_cast(value: f64) -> u16 {
     value as u16
 }
@@ -0,0 +1,361 @@
+//! Transports used to reach the RDCleanPath proxy
+//!
+//! The proxy address scheme selects the transport:
+//!
+//! - `ws://` and `wss://`: WebSocket
+//! - `https://`: [WebTransport] (HTTP/3), using a bidirectional stream for the RDP traffic. Unlike WebSocket,
+//!   WebTransport also supports unreliable datagrams, which are exposed by the session.
+//!
+//! [WebTransport]: https://developer.mozilla.org/en-US/docs/Web/API/WebTransport_API
+
+use core::future::Future as _;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+use std::io;
+use std::time::Duration;
+
+use anyhow::Context as _;
+use futures_channel::{mpsc, oneshot};
+use futures_util::io::IntoAsyncRead;
+use futures_util::{ready, AsyncRead, AsyncWrite, StreamExt as _, TryStreamExt as _};
+use gloo_net::websocket;
+use gloo_net::websocket::futures::WebSocket;
+use js_sys::{Array, Function, Promise, Reflect, Uint8Array};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::{spawn_local, JsFuture};
+
+use crate::error::{IronRdpError, IronRdpErrorKind};
+
+/// Number of writes queued for the writer task before `poll_write` applies backpressure
+const WRITE_QUEUE_SIZE: usize = 16;
+
+/// Reliable and ordered byte stream to the RDCleanPath proxy
+pub(crate) trait Transport: AsyncRead + AsyncWrite + Unpin {
+    /// Unreliable datagrams, when supported by the transport
+    fn datagrams(&self) -> Option<Datagrams> {
+        None
+    }
+}
+
+impl Transport for WebSocket {}
+
+/// Opens the transport matching the scheme of the proxy address
+pub(crate) async fn open(proxy_address: &str) -> Result<Box<dyn Transport>, IronRdpError> {
+    let scheme = url::Url::parse(proxy_address)
+        .map(|url| url.scheme().to_owned())
+        .unwrap_or_default();
+
+    let transport: Box<dyn Transport> = match scheme.as_str() {
+        "https" => Box::new(
+            WebTransportStream::open(proxy_address)
+                .await
+                .map_err(|e| IronRdpError::from(e).with_kind(IronRdpErrorKind::ProxyConnect))?,
+        ),
+        _ => Box::new(open_websocket(proxy_address).await?),
+    };
+
+    Ok(transport)
+}
+
+async fn open_websocket(proxy_address: &str) -> Result<WebSocket, IronRdpError> {
+    let ws = WebSocket::open(proxy_address).context("Couldn’t open WebSocket")?;
+
+    // NOTE: ideally, when the WebSocket can’t be opened, the above call should fail with details on why is that
+    // (e.g., the proxy hostname could not be resolved, proxy service is not running), but errors are neved
+    // bubbled up in practice, so instead we poll the WebSocket state until we know its connected (i.e., the
+    // WebSocket handshake is a success and user data can be exchanged).
+    loop {
+        match ws.state() {
+            websocket::State::Closing | websocket::State::Closed => {
+                return Err(IronRdpError::from(anyhow::anyhow!(
+                    "Failed to connect to {proxy_address} (WebSocket is `{:?}`)",
+                    ws.state()
+                ))
+                .with_kind(IronRdpErrorKind::ProxyConnect));
+            }
+            websocket::State::Connecting => {
+                trace!("WebSocket is connecting to proxy at {proxy_address}...");
+                gloo_timers::future::sleep(Duration::from_millis(50)).await;
+            }
+            websocket::State::Open => {
+                debug!("WebSocket connected to {proxy_address} with success");
+                break;
+            }
+        }
+    }
+
+    Ok(ws)
+}
+
+/// Bidirectional stream of a WebTransport session
+///
+/// The stream reader and writer are driven by tasks, so the stream can be used through `AsyncRead` and `AsyncWrite`.
+/// Flushing waits for the writer task to complete the queued writes.
+pub(crate) struct WebTransportStream {
+    transport: JsValue,
+    reader: IntoAsyncRead<mpsc::UnboundedReceiver<io::Result<Vec<u8>>>>,
+    writer_tx: mpsc::Sender<WriteRequest>,
+    pending_flush: Option<oneshot::Receiver<io::Result<()>>>,
+    datagrams: Datagrams,
+}
+
+enum WriteRequest {
+    Write(Vec<u8>),
+    /// Answered once the writes queued before it are completed
+    Flush(oneshot::Sender<io::Result<()>>),
+}
+
+impl WebTransportStream {
+    async fn open(url: &str) -> anyhow::Result<Self> {
+        let constructor: Function = Reflect::get(&js_sys::global(), &"WebTransport".into())
+            .ok()
+            .and_then(|constructor| constructor.dyn_into().ok())
+            .context("WebTransport API is not available")?;
+
+        let transport = Reflect::construct(&constructor, &Array::of1(&url.into())).map_err(js_error)?;
+
+        let ready: Promise = Reflect::get(&transport, &"ready".into())
+            .and_then(JsCast::dyn_into)
+            .map_err(js_error)?;
+        JsFuture::from(ready)
+            .await
+            .map_err(js_error)
+            .with_context(|| format!("failed to connect to {url}"))?;
+
+        debug!("WebTransport session established with {url}");
+
+        let stream = call_async(&transport, "createBidirectionalStream", &[])
+            .map_err(js_error)?
+            .await
+            .map_err(js_error)
+            .context("failed to create the bidirectional stream")?;
+
+        let reader = call(
+            &Reflect::get(&stream, &"readable".into()).map_err(js_error)?,
+            "getReader",
+            &[],
+        )
+        .map_err(js_error)?;
+        let writer = call(
+            &Reflect::get(&stream, &"writable".into()).map_err(js_error)?,
+            "getWriter",
+            &[],
+        )
+        .map_err(js_error)?;
+
+        let (reader_tx, reader_rx) = mpsc::unbounded();
+        let (writer_tx, writer_rx) = mpsc::channel(WRITE_QUEUE_SIZE);
+
+        spawn_local(read_task(reader, reader_tx));
+        spawn_local(write_task(writer, writer_rx));
+
+        let datagrams = Reflect::get(&transport, &"datagrams".into()).map_err(js_error)?;
+
+        Ok(Self {
+            datagrams: Datagrams {
+                readable: Reflect::get(&datagrams, &"readable".into()).map_err(js_error)?,
+                writable: Reflect::get(&datagrams, &"writable".into()).map_err(js_error)?,
+            },
+            transport,
+            reader: reader_rx.into_async_read(),
+            writer_tx,
+            pending_flush: None,
+        })
+    }
+}
+
+impl Transport for WebTransportStream {
+    fn datagrams(&self) -> Option<Datagrams> {
+        Some(self.datagrams.clone())
+    }
+}
+
+impl AsyncRead for WebTransportStream {
+    fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.reader).poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for WebTransportStream {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+
+        // The writer task closes the channel on error, so write failures are reported by the next write or flush.
+        ready!(this.writer_tx.poll_ready(cx)).map_err(|_| broken_pipe())?;
+        this.writer_tx
+            .start_send(WriteRequest::Write(buf.to_vec()))
+            .map_err(|_| broken_pipe())?;
+
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+
+        if this.pending_flush.is_none() {
+            ready!(this.writer_tx.poll_ready(cx)).map_err(|_| broken_pipe())?;
+
+            let (tx, rx) = oneshot::channel();
+            this.writer_tx
+                .start_send(WriteRequest::Flush(tx))
+                .map_err(|_| broken_pipe())?;
+            this.pending_flush = Some(rx);
+        }
+
+        let pending_flush = this.pending_flush.as_mut().expect("flush request sent above");
+        let result = ready!(Pin::new(pending_flush).poll(cx));
+        this.pending_flush = None;
+
+        Poll::Ready(result.unwrap_or_else(|_| Err(broken_pipe())))
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let flushed = ready!(self.as_mut().poll_flush(cx));
+        self.writer_tx.close_channel();
+        Poll::Ready(flushed)
+    }
+}
+
+impl Drop for WebTransportStream {
+    fn drop(&mut self) {
+        let _ = call(&self.transport, "close", &[]);
+    }
+}
+
+async fn read_task(reader: JsValue, tx: mpsc::UnboundedSender<io::Result<Vec<u8>>>) {
+    loop {
+        let chunk = match call_async(&reader, "read", &[]) {
+            Ok(read) => read.await,
+            Err(error) => Err(error),
+        };
+
+        let chunk = match chunk {
+            Ok(chunk) => chunk,
+            Err(error) => {
+                let _ = tx.unbounded_send(Err(io::Error::new(
+                    io::ErrorKind::ConnectionReset,
+                    format!("{error:?}"),
+                )));
+                break;
+            }
+        };
+
+        if Reflect::get(&chunk, &"done".into())
+            .ok()
+            .and_then(|done| done.as_bool())
+            == Some(true)
+        {
+            break;
+        }
+
+        let Ok(value) = Reflect::get(&chunk, &"value".into()) else {
+            continue;
+        };
+
+        if tx.unbounded_send(Ok(Uint8Array::new(&value).to_vec())).is_err() {
+            let _ = call(&reader, "cancel", &[]);
+            break;
+        }
+    }
+
+    debug!("WebTransport read task ended");
+}
+
+async fn write_task(writer: JsValue, mut rx: mpsc::Receiver<WriteRequest>) {
+    while let Some(request) = rx.next().await {
+        let frame = match request {
+            WriteRequest::Write(frame) => frame,
+            WriteRequest::Flush(done) => {
+                let _ = done.send(Ok(()));
+                continue;
+            }
+        };
+
+        let written = match call_async(&writer, "write", &[Uint8Array::from(frame.as_slice()).into()]) {
+            Ok(write) => write.await,
+            Err(error) => Err(error),
+        };
+
+        if let Err(error) = written {
+            error!("WebTransport write failed: {error:?}");
+
+            // Report the failure to the flushes already queued, further writes failing with `BrokenPipe`.
+            rx.close();
+            while let Some(request) = rx.next().await {
+                if let WriteRequest::Flush(done) = request {
+                    let _ = done.send(Err(io::Error::new(io::ErrorKind::BrokenPipe, format!("{error:?}"))));
+                }
+            }
+
+            return;
+        }
+    }
+
+    let _ = call(&writer, "close", &[]);
+    debug!("WebTransport write task ended");
+}
+
+/// Unreliable and unordered datagrams of a WebTransport session
+#[derive(Clone)]
+pub(crate) struct Datagrams {
+    readable: JsValue,
+    writable: JsValue,
+}
+
+impl Datagrams {
+    /// Sends a datagram, which may be dropped
+    pub(crate) async fn send(&self, data: &[u8]) -> anyhow::Result<()> {
+        let writer = call(&self.writable, "getWriter", &[]).map_err(js_error)?;
+
+        let written = match call_async(&writer, "write", &[Uint8Array::from(data).into()]) {
+            Ok(write) => write.await.map(|_| ()),
+            Err(error) => Err(error),
+        };
+        let _ = call(&writer, "releaseLock", &[]);
+
+        written.map_err(js_error)
+    }
+
+    /// Receives the next datagram, returns `None` once the session is closed
+    pub(crate) async fn recv(&self) -> anyhow::Result<Option<Vec<u8>>> {
+        let reader = call(&self.readable, "getReader", &[]).map_err(js_error)?;
+
+        let chunk = match call_async(&reader, "read", &[]) {
+            Ok(read) => read.await,
+            Err(error) => Err(error),
+        };
+        let _ = call(&reader, "releaseLock", &[]);
+
+        let chunk = chunk.map_err(js_error)?;
+
+        if Reflect::get(&chunk, &"done".into())
+            .ok()
+            .and_then(|done| done.as_bool())
+            == Some(true)
+        {
+            return Ok(None);
+        }
+
+        let value = Reflect::get(&chunk, &"value".into()).map_err(js_error)?;
+
+        Ok(Some(Uint8Array::new(&value).to_vec()))
+    }
+}
+
+fn call(target: &JsValue, name: &str, args: &[JsValue]) -> Result<JsValue, JsValue> {
+    let method: Function = Reflect::get(target, &name.into())?.dyn_into()?;
+    method.apply(target, &args.iter().collect::<Array>())
+}
+
+fn call_async(target: &JsValue, name: &str, args: &[JsValue]) -> Result<JsFuture, JsValue> {
+    let promise: Promise = call(target, name, args)?.dyn_into()?;
+    Ok(JsFuture::from(promise))
+}
+
+fn broken_pipe() -> io::Error {
+    io::Error::from(io::ErrorKind::BrokenPipe)
+}
+
+fn js_error(error: JsValue) -> anyhow::Error {
+    anyhow::anyhow!("{error:?}")
+}
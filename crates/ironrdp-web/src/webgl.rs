@@ -0,0 +1,169 @@
+use anyhow::Context as _;
+use ironrdp::pdu::geometry::{InclusiveRectangle, Rectangle as _};
+use wasm_bindgen::JsCast as _;
+use web_sys::{WebGl2RenderingContext as Gl, WebGlProgram, WebGlShader, WebGlTexture};
+
+use crate::canvas::RenderCanvas;
+
+// Draws a triangle covering the whole viewport, generated from the vertex ID (no vertex buffer required).
+const VERTEX_SHADER: &str = r"#version 300 es
+out vec2 v_uv;
+
+void main() {
+    vec2 position = vec2(float((gl_VertexID << 1) & 2), float(gl_VertexID & 2));
+    // The first texture row is the top of the desktop.
+    v_uv = vec2(position.x, 1.0 - position.y);
+    gl_Position = vec4(position * 2.0 - 1.0, 0.0, 1.0);
+}
+";
+
+const FRAGMENT_SHADER: &str = r"#version 300 es
+precision mediump float;
+
+uniform sampler2D u_desktop;
+in vec2 v_uv;
+out vec4 color;
+
+void main() {
+    color = vec4(texture(u_desktop, v_uv).rgb, 1.0);
+}
+";
+
+/// Renders the desktop using a WebGL 2 texture
+///
+/// Updated regions are uploaded to the texture as they are received, and the texture is drawn once
+/// all the updates of a frame were uploaded (see [`WebGlCanvas::present`]), instead of blitting each
+/// region separately.
+pub(crate) struct WebGlCanvas {
+    gl: Gl,
+    program: WebGlProgram,
+    texture: WebGlTexture,
+    width: i32,
+    height: i32,
+    dirty: bool,
+}
+
+impl WebGlCanvas {
+    /// Fails if WebGL 2 is not available, in which case the 2D canvas should be used instead
+    pub(crate) fn new(render_canvas: &RenderCanvas, width: u32, height: u32) -> anyhow::Result<Self> {
+        render_canvas.set_size(width, height);
+
+        let gl = render_canvas
+            .get_context("webgl2")?
+            .context("WebGL 2 is not supported")?
+            .dyn_into::<Gl>()
+            .map_err(|_| anyhow::anyhow!("unexpected WebGL 2 context type"))?;
+
+        let width = i32::try_from(width).context("canvas width")?;
+        let height = i32::try_from(height).context("canvas height")?;
+
+        let program = link_program(&gl)?;
+
+        let texture = gl.create_texture().context("failed to create texture")?;
+        gl.bind_texture(Gl::TEXTURE_2D, Some(&texture));
+        gl.tex_storage_2d(Gl::TEXTURE_2D, 1, Gl::RGBA8, width, height);
+        for (parameter, value) in [
+            (Gl::TEXTURE_MIN_FILTER, Gl::NEAREST),
+            (Gl::TEXTURE_MAG_FILTER, Gl::NEAREST),
+            (Gl::TEXTURE_WRAP_S, Gl::CLAMP_TO_EDGE),
+            (Gl::TEXTURE_WRAP_T, Gl::CLAMP_TO_EDGE),
+        ] {
+            #[allow(clippy::cast_possible_wrap)] // WebGL constants are small positive values.
+            gl.tex_parameteri(Gl::TEXTURE_2D, parameter, value as i32);
+        }
+        gl.pixel_storei(Gl::UNPACK_ALIGNMENT, 1);
+
+        gl.use_program(Some(&program));
+        gl.uniform1i(gl.get_uniform_location(&program, "u_desktop").as_ref(), 0);
+        gl.viewport(0, 0, width, height);
+
+        Ok(Self {
+            gl,
+            program,
+            texture,
+            width,
+            height,
+            dirty: false,
+        })
+    }
+
+    /// Uploads an updated region, `buffer` holding its RGBA pixels
+    pub(crate) fn draw(&mut self, buffer: &[u8], region: InclusiveRectangle) -> anyhow::Result<()> {
+        self.gl.bind_texture(Gl::TEXTURE_2D, Some(&self.texture));
+        self.gl
+            .tex_sub_image_2d_with_i32_and_i32_and_u32_and_type_and_opt_u8_array(
+                Gl::TEXTURE_2D,
+                0,
+                i32::from(region.left),
+                i32::from(region.top),
+                i32::from(region.width()),
+                i32::from(region.height()),
+                Gl::RGBA,
+                Gl::UNSIGNED_BYTE,
+                Some(buffer),
+            )
+            .map_err(|e| anyhow::anyhow!("texture upload failed: {e:?}"))?;
+
+        self.dirty = true;
+
+        Ok(())
+    }
+
+    /// Draws the desktop texture, if updated since the last call
+    pub(crate) fn present(&mut self) {
+        if !self.dirty {
+            return;
+        }
+
+        if self.gl.is_context_lost() {
+            warn!("WebGL context lost, skipping frame");
+            return;
+        }
+
+        self.gl.use_program(Some(&self.program));
+        self.gl.active_texture(Gl::TEXTURE0);
+        self.gl.bind_texture(Gl::TEXTURE_2D, Some(&self.texture));
+        self.gl.viewport(0, 0, self.width, self.height);
+        self.gl.draw_arrays(Gl::TRIANGLES, 0, 3);
+
+        self.dirty = false;
+    }
+}
+
+fn link_program(gl: &Gl) -> anyhow::Result<WebGlProgram> {
+    let vertex_shader = compile_shader(gl, Gl::VERTEX_SHADER, VERTEX_SHADER)?;
+    let fragment_shader = compile_shader(gl, Gl::FRAGMENT_SHADER, FRAGMENT_SHADER)?;
+
+    let program = gl.create_program().context("failed to create program")?;
+    gl.attach_shader(&program, &vertex_shader);
+    gl.attach_shader(&program, &fragment_shader);
+    gl.link_program(&program);
+
+    if !gl
+        .get_program_parameter(&program, Gl::LINK_STATUS)
+        .as_bool()
+        .unwrap_or(false)
+    {
+        let log = gl.get_program_info_log(&program).unwrap_or_default();
+        anyhow::bail!("failed to link program: {log}");
+    }
+
+    Ok(program)
+}
+
+fn compile_shader(gl: &Gl, kind: u32, source: &str) -> anyhow::Result<WebGlShader> {
+    let shader = gl.create_shader(kind).context("failed to create shader")?;
+    gl.shader_source(&shader, source);
+    gl.compile_shader(&shader);
+
+    if !gl
+        .get_shader_parameter(&shader, Gl::COMPILE_STATUS)
+        .as_bool()
+        .unwrap_or(false)
+    {
+        let log = gl.get_shader_info_log(&shader).unwrap_or_default();
+        anyhow::bail!("failed to compile shader: {log}");
+    }
+
+    Ok(shader)
+}
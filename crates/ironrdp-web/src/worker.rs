@@ -0,0 +1,456 @@
+//! Runs the session in a dedicated worker, driven by messages posted by the main thread.
+//!
+//! PDU processing and decoding then happen off the main thread, which is left free for input and UI.
+//! See the crate README for the message protocol.
+
+use core::cell::RefCell;
+use std::rc::Rc;
+
+use anyhow::Context as _;
+use ironrdp::pdu::geometry::{InclusiveRectangle, Rectangle as _};
+use js_sys::{Array, Object, Reflect, SharedArrayBuffer, Uint8Array};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::{DedicatedWorkerGlobalScope, MessageEvent, OffscreenCanvas};
+
+use crate::canvas::RendererKind;
+use crate::error::IronRdpError;
+use crate::events::{ConnectionState, SessionEvent, SessionEventType};
+use crate::input::{DeviceEvent, InputTransaction};
+use crate::pointer::{PointerEventKind, PointerInputEvent};
+use crate::session::{Session, SessionBuilder};
+use crate::DesktopSize;
+
+/// Entry point of the worker script, to call once the WASM module is initialized
+#[wasm_bindgen]
+pub fn ironrdp_worker_init(log_level: &str) -> Result<(), IronRdpError> {
+    crate::ironrdp_init(log_level);
+
+    let scope = worker_scope()?;
+    let session: Rc<RefCell<Option<Rc<Session>>>> = Rc::default();
+
+    let on_message = Closure::<dyn FnMut(MessageEvent)>::new(move |event: MessageEvent| {
+        if let Err(error) = handle_message(&session, &event.data()) {
+            error!("Failed to handle worker message: {}", error.backtrace());
+            post_error(&error);
+        }
+    });
+    scope.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+    on_message.forget();
+
+    debug!("IronRDP worker is ready");
+
+    Ok(())
+}
+
+fn handle_message(session: &Rc<RefCell<Option<Rc<Session>>>>, data: &JsValue) -> Result<(), IronRdpError> {
+    let kind = get_string(data, "type").context("message type missing")?;
+
+    if kind == "connect" {
+        let builder = session_builder(data)?;
+        spawn_local(run_session(Rc::clone(session), builder));
+        return Ok(());
+    }
+
+    let session = session.borrow().clone().context("no session running")?;
+
+    match kind.as_str() {
+        "input" => {
+            let events = Reflect::get(data, &"events".into())
+                .ok()
+                .map(Array::from)
+                .context("input events missing")?;
+
+            let mut transaction = InputTransaction::new();
+            for event in events.iter() {
+                transaction.add_event(device_event(&event)?);
+            }
+
+            session.apply_inputs(transaction)?;
+        }
+        "pointer" => session.apply_pointer_event(pointer_event(data)?)?,
+        "resetZoom" => session.reset_zoom(),
+        "releaseAllInputs" => session.release_all_inputs()?,
+        "synchronizeLockKeys" => session.synchronize_lock_keys(
+            get_bool(data, "scrollLock"),
+            get_bool(data, "numLock"),
+            get_bool(data, "capsLock"),
+            get_bool(data, "kanaLock"),
+        )?,
+        "shutdown" => session.shutdown()?,
+        unknown => return Err(anyhow::anyhow!("unknown message type: {unknown}").into()),
+    }
+
+    Ok(())
+}
+
+async fn run_session(state: Rc<RefCell<Option<Rc<Session>>>>, builder: SessionBuilder) {
+    let session = match builder.connect().await {
+        Ok(session) => Rc::new(session),
+        Err(error) => {
+            post_error(&error);
+            return;
+        }
+    };
+
+    *state.borrow_mut() = Some(Rc::clone(&session));
+
+    let desktop_size = session.desktop_size();
+    post_message(
+        "connected",
+        &[
+            ("desktopWidth", desktop_size.width.into()),
+            ("desktopHeight", desktop_size.height.into()),
+        ],
+    );
+
+    match session.run().await {
+        Ok(info) => post_message("terminated", &[("reason", info.reason().into())]),
+        Err(error) => post_error(&error),
+    }
+
+    state.borrow_mut().take();
+}
+
+fn session_builder(data: &JsValue) -> anyhow::Result<SessionBuilder> {
+    let required = |key: &str| get_string(data, key).with_context(|| format!("{key} missing"));
+
+    let builder = SessionBuilder::new()
+        .username(required("username")?)
+        .password(required("password")?)
+        .destination(required("destination")?)
+        .proxy_address(required("proxyAddress")?)
+        .auth_token(required("authToken")?)
+        .server_domain(get_string(data, "serverDomain").unwrap_or_default())
+        .kdc_proxy_url(get_string(data, "kdcProxyUrl"))
+        .set_cursor_style_callback(cursor_style_callback())
+        .set_cursor_style_callback_context(JsValue::NULL)
+        .subscribe(SessionEventType::Zoom, zoom_callback())
+        .subscribe(SessionEventType::ConnectionState, connection_state_callback());
+
+    if let Some(pcb) = get_string(data, "pcb") {
+        builder.pcb(pcb);
+    }
+
+    if let Some(grace_period_ms) = get_u32(data, "reconnectGracePeriod") {
+        builder.reconnect_grace_period(grace_period_ms);
+    }
+
+    if let (Some(width), Some(height)) = (get_u16(data, "desktopWidth"), get_u16(data, "desktopHeight")) {
+        builder.desktop_size(DesktopSize { width, height });
+    }
+
+    match get_string(data, "renderer").as_deref() {
+        Some("webgl") => {
+            builder.renderer(RendererKind::WebGl);
+        }
+        Some("canvas2d") | None => {}
+        Some(unknown) => anyhow::bail!("unknown renderer: {unknown}"),
+    }
+
+    // Without canvas, the updated regions are posted to the main thread.
+    match Reflect::get(data, &"canvas".into())
+        .ok()
+        .filter(|canvas| !canvas.is_undefined())
+    {
+        Some(canvas) => {
+            let canvas = canvas
+                .dyn_into::<OffscreenCanvas>()
+                .map_err(|_| anyhow::anyhow!("canvas is not an OffscreenCanvas"))?;
+            builder.offscreen_render_canvas(canvas);
+        }
+        None => {
+            builder.post_frames_to_main_thread();
+        }
+    }
+
+    Ok(builder)
+}
+
+fn device_event(event: &JsValue) -> anyhow::Result<DeviceEvent> {
+    let kind = get_string(event, "kind").context("input event kind missing")?;
+    let number = |key: &str| get_u16(event, key).with_context(|| format!("{key} missing in {kind} input event"));
+
+    let event = match kind.as_str() {
+        "mouseButtonPressed" => DeviceEvent::new_mouse_button_pressed(u8::try_from(number("button")?)?),
+        "mouseButtonReleased" => DeviceEvent::new_mouse_button_released(u8::try_from(number("button")?)?),
+        "mouseMove" => DeviceEvent::new_mouse_move(number("x")?, number("y")?),
+        "wheelRotations" => {
+            let rotation_units = get_f64(event, "rotationUnits").context("rotationUnits missing")?;
+            DeviceEvent::new_wheel_rotations(get_bool(event, "vertical"), f64_to_i16_saturating_cast(rotation_units))
+        }
+        "keyPressed" => DeviceEvent::new_key_pressed(number("scancode")?),
+        "keyReleased" => DeviceEvent::new_key_released(number("scancode")?),
+        "unicodePressed" => DeviceEvent::new_unicode_pressed(get_char(event)?),
+        "unicodeReleased" => DeviceEvent::new_unicode_released(get_char(event)?),
+        unknown => anyhow::bail!("unknown input event kind: {unknown}"),
+    };
+
+    Ok(event)
+}
+
+fn pointer_event(data: &JsValue) -> anyhow::Result<PointerInputEvent> {
+    let kind = match get_string(data, "kind").context("pointer event kind missing")?.as_str() {
+        "down" => PointerEventKind::Down,
+        "move" => PointerEventKind::Move,
+        "up" => PointerEventKind::Up,
+        "cancel" => PointerEventKind::Cancel,
+        unknown => anyhow::bail!("unknown pointer event kind: {unknown}"),
+    };
+    let number = |key: &str| get_f64(data, key).with_context(|| format!("{key} missing in pointer event"));
+
+    Ok(PointerInputEvent::new(
+        kind,
+        &get_string(data, "pointerType").unwrap_or_default(),
+        f64_to_i32_saturating_cast(number("pointerId")?),
+        f64_to_i16_saturating_cast(get_f64(data, "button").unwrap_or(-1.0)),
+        number("x")?,
+        number("y")?,
+    ))
+}
+
+/// Posts the updated regions of the desktop to the main thread
+///
+/// When the page is cross-origin isolated, the desktop is shared with the main thread through a
+/// `SharedArrayBuffer` (announced once with a `framebuffer` message), and `frame` messages only carry
+/// the updated regions. Otherwise, the pixels of each region are transferred along with it.
+pub(crate) struct FrameTransport {
+    width: usize,
+    framebuffer: Option<Uint8Array>,
+    regions: Array,
+    transfer: Array,
+}
+
+impl FrameTransport {
+    pub(crate) fn new(width: u32, height: u32) -> anyhow::Result<Self> {
+        let scope = worker_scope()?;
+
+        let cross_origin_isolated = Reflect::get(&scope, &"crossOriginIsolated".into())
+            .ok()
+            .and_then(|value| value.as_bool())
+            .unwrap_or(false);
+
+        let framebuffer = if cross_origin_isolated {
+            let length = width
+                .checked_mul(height)
+                .and_then(|pixels| pixels.checked_mul(4))
+                .context("desktop too large")?;
+            let buffer = SharedArrayBuffer::new(length);
+
+            post_message(
+                "framebuffer",
+                &[
+                    ("buffer", buffer.clone().into()),
+                    ("width", width.into()),
+                    ("height", height.into()),
+                ],
+            );
+
+            Some(Uint8Array::new(&buffer))
+        } else {
+            debug!("Not cross-origin isolated, transferring the frames without SharedArrayBuffer");
+            None
+        };
+
+        Ok(Self {
+            width: usize::try_from(width)?,
+            framebuffer,
+            regions: Array::new(),
+            transfer: Array::new(),
+        })
+    }
+
+    #[allow(clippy::arithmetic_side_effects)] // The region is within the desktop, whose size fits in the framebuffer.
+    pub(crate) fn draw(&mut self, buffer: &[u8], region: InclusiveRectangle) -> anyhow::Result<()> {
+        let message = message_object(&[
+            ("x", region.left.into()),
+            ("y", region.top.into()),
+            ("width", region.width().into()),
+            ("height", region.height().into()),
+        ]);
+
+        match &self.framebuffer {
+            Some(framebuffer) => {
+                let stride = usize::from(region.width()) * 4;
+
+                for (row, pixels) in buffer.chunks_exact(stride).enumerate() {
+                    let offset = ((usize::from(region.top) + row) * self.width + usize::from(region.left)) * 4;
+                    let begin = u32::try_from(offset)?;
+                    let end = u32::try_from(offset + stride)?;
+                    framebuffer.subarray(begin, end).copy_from(pixels);
+                }
+            }
+            None => {
+                let pixels = Uint8Array::from(buffer).buffer();
+                set_field(&message, "pixels", &pixels);
+                self.transfer.push(&pixels);
+            }
+        }
+
+        self.regions.push(&message);
+
+        Ok(())
+    }
+
+    /// Posts the regions drawn since the last call, if any
+    pub(crate) fn present(&mut self) {
+        if self.regions.length() == 0 {
+            return;
+        }
+
+        let message = message_object(&[("type", "frame".into()), ("regions", self.regions.clone().into())]);
+
+        if let Err(error) = worker_scope().and_then(|scope| {
+            scope
+                .post_message_with_transfer(&message, &self.transfer)
+                .map_err(|e| anyhow::anyhow!("{e:?}"))
+        }) {
+            error!(%error, "Failed to post frame");
+        }
+
+        self.regions = Array::new();
+        self.transfer = Array::new();
+    }
+}
+
+fn cursor_style_callback() -> js_sys::Function {
+    let callback = Closure::<dyn Fn(JsValue, JsValue, JsValue, JsValue)>::new(
+        |kind: JsValue, data: JsValue, hotspot_x: JsValue, hotspot_y: JsValue| {
+            post_message(
+                "cursor",
+                &[
+                    ("kind", kind),
+                    ("data", data),
+                    ("hotspotX", hotspot_x),
+                    ("hotspotY", hotspot_y),
+                ],
+            );
+        },
+    );
+
+    callback.into_js_value().unchecked_into()
+}
+
+fn zoom_callback() -> js_sys::Function {
+    let callback = Closure::<dyn Fn(SessionEvent)>::new(|event: SessionEvent| {
+        post_message(
+            "zoom",
+            &[
+                ("scale", event.zoom_scale().into()),
+                ("offsetX", event.zoom_offset_x().into()),
+                ("offsetY", event.zoom_offset_y().into()),
+            ],
+        );
+    });
+
+    callback.into_js_value().unchecked_into()
+}
+
+fn connection_state_callback() -> js_sys::Function {
+    let callback = Closure::<dyn Fn(SessionEvent)>::new(|event: SessionEvent| {
+        let state = match event.connection_state() {
+            Some(ConnectionState::Connecting) => "connecting",
+            Some(ConnectionState::Connected) => "connected",
+            Some(ConnectionState::Reconnecting) => "reconnecting",
+            Some(ConnectionState::Disconnected) => "disconnected",
+            None => return,
+        };
+
+        post_message("connectionState", &[("state", state.into())]);
+    });
+
+    callback.into_js_value().unchecked_into()
+}
+
+fn worker_scope() -> anyhow::Result<DedicatedWorkerGlobalScope> {
+    js_sys::global()
+        .dyn_into::<DedicatedWorkerGlobalScope>()
+        .map_err(|_| anyhow::anyhow!("not running in a dedicated worker"))
+}
+
+fn post_message(kind: &str, fields: &[(&str, JsValue)]) {
+    let message = message_object(fields);
+    set_field(&message, "type", &kind.into());
+
+    if let Err(error) =
+        worker_scope().and_then(|scope| scope.post_message(&message).map_err(|e| anyhow::anyhow!("{e:?}")))
+    {
+        error!(%error, kind, "Failed to post message");
+    }
+}
+
+fn post_error(error: &IronRdpError) {
+    post_message(
+        "error",
+        &[
+            ("kind", (error.kind() as u32).into()),
+            ("backtrace", error.backtrace().into()),
+        ],
+    );
+}
+
+fn message_object(fields: &[(&str, JsValue)]) -> Object {
+    let object = Object::new();
+    for (key, value) in fields {
+        set_field(&object, key, value);
+    }
+    object
+}
+
+fn set_field(object: &Object, key: &str, value: &JsValue) {
+    // Setting a property on a plain object can't fail.
+    let _ = Reflect::set(object, &key.into(), value);
+}
+
+fn get_string(object: &JsValue, key: &str) -> Option<String> {
+    Reflect::get(object, &key.into()).ok()?.as_string()
+}
+
+fn get_f64(object: &JsValue, key: &str) -> Option<f64> {
+    Reflect::get(object, &key.into()).ok()?.as_f64()
+}
+
+fn get_u16(object: &JsValue, key: &str) -> Option<u16> {
+    let value = get_f64(object, key)?;
+
+    if value.fract() != 0.0 || !(0.0..=f64::from(u16::MAX)).contains(&value) {
+        return None;
+    }
+
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)] // Range checked above.
+    Some(value as u16)
+}
+
+fn get_u32(object: &JsValue, key: &str) -> Option<u32> {
+    let value = get_f64(object, key)?;
+
+    if value.fract() != 0.0 || !(0.0..=f64::from(u32::MAX)).contains(&value) {
+        return None;
+    }
+
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)] // Range checked above.
+    Some(value as u32)
+}
+
+fn get_bool(object: &JsValue, key: &str) -> bool {
+    Reflect::get(object, &key.into())
+        .ok()
+        .and_then(|value| value.as_bool())
+        .unwrap_or(false)
+}
+
+fn get_char(event: &JsValue) -> anyhow::Result<char> {
+    get_string(event, "char")
+        .and_then(|s| s.chars().next())
+        .context("char missing in unicode input event")
+}
+
+#[allow(clippy::cast_possible_truncation)]
+fn f64_to_i16_saturating_cast(value: f64) -> i16 {
+    value as i16
+}
+
+#[allow(clippy::cast_possible_truncation)]
+fn f64_to_i32_saturating_cast(value: f64) -> i32 {
+    value as i32
+}
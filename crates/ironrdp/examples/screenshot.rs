@@ -30,6 +30,7 @@ use connector::Credentials;
 use ironrdp::connector;
 use ironrdp::connector::sspi::network_client::reqwest_network_client::ReqwestNetworkClient;
 use ironrdp::connector::ConnectionResult;
+use ironrdp::core::DecodeMode;
 use ironrdp::pdu::gcc::KeyboardType;
 use ironrdp::pdu::rdp::capability_sets::MajorPlatformType;
 use ironrdp::session::image::DecodedImage;
@@ -224,6 +225,12 @@ fn build_config(username: String, password: String, domain: Option<String>) -> c
         // Disable custom pointers (there is no user interaction anyway)
         no_server_pointer: true,
         autologon: false,
+        restricted_admin: false,
+        auto_reconnect: None,
+        decode_mode: DecodeMode::Lenient,
+        decode_limits: Default::default(),
+        monitors: Vec::new(),
+        persistent_bitmap_cache: Vec::new(),
         pointer_software_rendering: true,
         performance_flags: PerformanceFlags::default(),
         desktop_scale_factor: 0,
@@ -0,0 +1,180 @@
+// <auto-generated/> by Diplomat
+
+#pragma warning disable 0105
+using System;
+using System.Runtime.InteropServices;
+
+using Devolutions.IronRdp.Diplomat;
+#pragma warning restore 0105
+
+namespace Devolutions.IronRdp;
+
+#nullable enable
+
+public partial class ClipboardEvent: IDisposable
+{
+    private unsafe Raw.ClipboardEvent* _inner;
+
+    public ClipboardEventType EventType
+    {
+        get
+        {
+            return GetEventType();
+        }
+    }
+
+    public ClipboardFormatId? FormatDataRequest
+    {
+        get
+        {
+            return GetFormatDataRequest();
+        }
+    }
+
+    public FormatDataResponse? FormatDataResponse
+    {
+        get
+        {
+            return GetFormatDataResponse();
+        }
+    }
+
+    public ClipboardFormatIterator? RemoteCopy
+    {
+        get
+        {
+            return GetRemoteCopy();
+        }
+    }
+
+    /// <summary>
+    /// Creates a managed <c>ClipboardEvent</c> from a raw handle.
+    /// </summary>
+    /// <remarks>
+    /// Safety: you should not build two managed objects using the same raw handle (may causes use-after-free and double-free).
+    /// <br/>
+    /// This constructor assumes the raw struct is allocated on Rust side.
+    /// If implemented, the custom Drop implementation on Rust side WILL run on destruction.
+    /// </remarks>
+    public unsafe ClipboardEvent(Raw.ClipboardEvent* handle)
+    {
+        _inner = handle;
+    }
+
+    /// <returns>
+    /// A <c>ClipboardEventType</c> allocated on C# side.
+    /// </returns>
+    public ClipboardEventType GetEventType()
+    {
+        unsafe
+        {
+            if (_inner == null)
+            {
+                throw new ObjectDisposedException("ClipboardEvent");
+            }
+            Raw.ClipboardEventType retVal = Raw.ClipboardEvent.GetEventType(_inner);
+            return (ClipboardEventType)retVal;
+        }
+    }
+
+    /// <summary>
+    /// Formats available on the remote clipboard, to be requested with `ActiveStage::initiate_clipboard_paste`
+    /// </summary>
+    /// <returns>
+    /// A <c>ClipboardFormatIterator</c> allocated on Rust side.
+    /// </returns>
+    public ClipboardFormatIterator? GetRemoteCopy()
+    {
+        unsafe
+        {
+            if (_inner == null)
+            {
+                throw new ObjectDisposedException("ClipboardEvent");
+            }
+            Raw.ClipboardFormatIterator* retVal = Raw.ClipboardEvent.GetRemoteCopy(_inner);
+            if (retVal == null)
+            {
+                return null;
+            }
+            return new ClipboardFormatIterator(retVal);
+        }
+    }
+
+    /// <summary>
+    /// Format requested by the server, to be answered with `ActiveStage::submit_clipboard_format_data`
+    /// </summary>
+    /// <returns>
+    /// A <c>ClipboardFormatId</c> allocated on Rust side.
+    /// </returns>
+    public ClipboardFormatId? GetFormatDataRequest()
+    {
+        unsafe
+        {
+            if (_inner == null)
+            {
+                throw new ObjectDisposedException("ClipboardEvent");
+            }
+            Raw.ClipboardFormatId* retVal = Raw.ClipboardEvent.GetFormatDataRequest(_inner);
+            if (retVal == null)
+            {
+                return null;
+            }
+            return new ClipboardFormatId(retVal);
+        }
+    }
+
+    /// <summary>
+    /// Data sent by the server in response to `ActiveStage::initiate_clipboard_paste`
+    /// </summary>
+    /// <returns>
+    /// A <c>FormatDataResponse</c> allocated on Rust side.
+    /// </returns>
+    public FormatDataResponse? GetFormatDataResponse()
+    {
+        unsafe
+        {
+            if (_inner == null)
+            {
+                throw new ObjectDisposedException("ClipboardEvent");
+            }
+            Raw.FormatDataResponse* retVal = Raw.ClipboardEvent.GetFormatDataResponse(_inner);
+            if (retVal == null)
+            {
+                return null;
+            }
+            return new FormatDataResponse(retVal);
+        }
+    }
+
+    /// <summary>
+    /// Returns the underlying raw handle.
+    /// </summary>
+    public unsafe Raw.ClipboardEvent* AsFFI()
+    {
+        return _inner;
+    }
+
+    /// <summary>
+    /// Destroys the underlying object immediately.
+    /// </summary>
+    public void Dispose()
+    {
+        unsafe
+        {
+            if (_inner == null)
+            {
+                return;
+            }
+
+            Raw.ClipboardEvent.Destroy(_inner);
+            _inner = null;
+
+            GC.SuppressFinalize(this);
+        }
+    }
+
+    ~ClipboardEvent()
+    {
+        Dispose();
+    }
+}
@@ -0,0 +1,21 @@
+// <auto-generated/> by Diplomat
+
+#pragma warning disable 0105
+using System;
+using System.Runtime.InteropServices;
+
+using Devolutions.IronRdp.Diplomat;
+#pragma warning restore 0105
+
+namespace Devolutions.IronRdp;
+
+#nullable enable
+
+public enum ClipboardEventType
+{
+    RequestFormatList = 0,
+    FormatListReceived = 1,
+    RemoteCopy = 2,
+    FormatDataRequest = 3,
+    FormatDataResponse = 4,
+}
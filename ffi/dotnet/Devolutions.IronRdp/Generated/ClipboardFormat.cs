@@ -0,0 +1,129 @@
+// <auto-generated/> by Diplomat
+
+#pragma warning disable 0105
+using System;
+using System.Runtime.InteropServices;
+
+using Devolutions.IronRdp.Diplomat;
+#pragma warning restore 0105
+
+namespace Devolutions.IronRdp;
+
+#nullable enable
+
+public partial class ClipboardFormat: IDisposable
+{
+    private unsafe Raw.ClipboardFormat* _inner;
+
+    public ClipboardFormatId Id
+    {
+        get
+        {
+            return GetId();
+        }
+    }
+
+    public string Name
+    {
+        get
+        {
+            return GetName();
+        }
+    }
+
+    /// <summary>
+    /// Creates a managed <c>ClipboardFormat</c> from a raw handle.
+    /// </summary>
+    /// <remarks>
+    /// Safety: you should not build two managed objects using the same raw handle (may causes use-after-free and double-free).
+    /// <br/>
+    /// This constructor assumes the raw struct is allocated on Rust side.
+    /// If implemented, the custom Drop implementation on Rust side WILL run on destruction.
+    /// </remarks>
+    public unsafe ClipboardFormat(Raw.ClipboardFormat* handle)
+    {
+        _inner = handle;
+    }
+
+    /// <returns>
+    /// A <c>ClipboardFormatId</c> allocated on Rust side.
+    /// </returns>
+    public ClipboardFormatId GetId()
+    {
+        unsafe
+        {
+            if (_inner == null)
+            {
+                throw new ObjectDisposedException("ClipboardFormat");
+            }
+            Raw.ClipboardFormatId* retVal = Raw.ClipboardFormat.GetId(_inner);
+            return new ClipboardFormatId(retVal);
+        }
+    }
+
+    /// <summary>
+    /// Writes the name of the format, if any
+    /// </summary>
+    public void GetName(DiplomatWriteable writeable)
+    {
+        unsafe
+        {
+            if (_inner == null)
+            {
+                throw new ObjectDisposedException("ClipboardFormat");
+            }
+            Raw.ClipboardFormat.GetName(_inner, &writeable);
+        }
+    }
+
+    /// <summary>
+    /// Writes the name of the format, if any
+    /// </summary>
+    public string GetName()
+    {
+        unsafe
+        {
+            if (_inner == null)
+            {
+                throw new ObjectDisposedException("ClipboardFormat");
+            }
+            DiplomatWriteable writeable = new DiplomatWriteable();
+            Raw.ClipboardFormat.GetName(_inner, &writeable);
+            string retVal = writeable.ToUnicode();
+            writeable.Dispose();
+            return retVal;
+        }
+    }
+
+    /// <summary>
+    /// Returns the underlying raw handle.
+    /// </summary>
+    public unsafe Raw.ClipboardFormat* AsFFI()
+    {
+        return _inner;
+    }
+
+    /// <summary>
+    /// Destroys the underlying object immediately.
+    /// </summary>
+    public void Dispose()
+    {
+        unsafe
+        {
+            if (_inner == null)
+            {
+                return;
+            }
+
+            Raw.ClipboardFormat.Destroy(_inner);
+            _inner = null;
+
+            GC.SuppressFinalize(this);
+        }
+    }
+
+    ~ClipboardFormat()
+    {
+        Dispose();
+    }
+}
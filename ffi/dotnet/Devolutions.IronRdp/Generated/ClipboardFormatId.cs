@@ -15,6 +15,14 @@ public partial class ClipboardFormatId: IDisposable
 {
     private unsafe Raw.ClipboardFormatId* _inner;
 
+    public uint Value
+    {
+        get
+        {
+            return GetValue();
+        }
+    }
+
     /// <summary>
     /// Creates a managed <c>ClipboardFormatId</c> from a raw handle.
     /// </summary>
@@ -29,6 +37,31 @@ public partial class ClipboardFormatId: IDisposable
         _inner = handle;
     }
 
+    /// <returns>
+    /// A <c>ClipboardFormatId</c> allocated on Rust side.
+    /// </returns>
+    public static ClipboardFormatId New(uint value)
+    {
+        unsafe
+        {
+            Raw.ClipboardFormatId* retVal = Raw.ClipboardFormatId.New(value);
+            return new ClipboardFormatId(retVal);
+        }
+    }
+
+    public uint GetValue()
+    {
+        unsafe
+        {
+            if (_inner == null)
+            {
+                throw new ObjectDisposedException("ClipboardFormatId");
+            }
+            uint retVal = Raw.ClipboardFormatId.GetValue(_inner);
+            return retVal;
+        }
+    }
+
     /// <summary>
     /// Returns the underlying raw handle.
     /// </summary>
@@ -29,6 +29,90 @@ public partial class ClipboardFormatIterator: IDisposable
         _inner = handle;
     }
 
+    /// <returns>
+    /// A <c>ClipboardFormatIterator</c> allocated on Rust side.
+    /// </returns>
+    public static ClipboardFormatIterator New()
+    {
+        unsafe
+        {
+            Raw.ClipboardFormatIterator* retVal = Raw.ClipboardFormatIterator.New();
+            return new ClipboardFormatIterator(retVal);
+        }
+    }
+
+    /// <summary>
+    /// Adds a format to the list, `name` being ignored when empty
+    /// </summary>
+    public void Push(ClipboardFormatId formatId, string name)
+    {
+        unsafe
+        {
+            if (_inner == null)
+            {
+                throw new ObjectDisposedException("ClipboardFormatIterator");
+            }
+            byte[] nameBuf = DiplomatUtils.StringToUtf8(name);
+            nuint nameBufLength = (nuint)nameBuf.Length;
+            Raw.ClipboardFormatId* formatIdRaw;
+            formatIdRaw = formatId.AsFFI();
+            if (formatIdRaw == null)
+            {
+                throw new ObjectDisposedException("ClipboardFormatId");
+            }
+            fixed (byte* nameBufPtr = nameBuf)
+            {
+                Raw.ClipboardFormatIterator.Push(_inner, formatIdRaw, nameBufPtr, nameBufLength);
+            }
+        }
+    }
+
+    public nuint Len()
+    {
+        unsafe
+        {
+            if (_inner == null)
+            {
+                throw new ObjectDisposedException("ClipboardFormatIterator");
+            }
+            nuint retVal = Raw.ClipboardFormatIterator.Len(_inner);
+            return retVal;
+        }
+    }
+
+    public bool IsEmpty()
+    {
+        unsafe
+        {
+            if (_inner == null)
+            {
+                throw new ObjectDisposedException("ClipboardFormatIterator");
+            }
+            bool retVal = Raw.ClipboardFormatIterator.IsEmpty(_inner);
+            return retVal;
+        }
+    }
+
+    /// <returns>
+    /// A <c>ClipboardFormat</c> allocated on Rust side.
+    /// </returns>
+    public ClipboardFormat? Next()
+    {
+        unsafe
+        {
+            if (_inner == null)
+            {
+                throw new ObjectDisposedException("ClipboardFormatIterator");
+            }
+            Raw.ClipboardFormat* retVal = Raw.ClipboardFormatIterator.Next(_inner);
+            if (retVal == null)
+            {
+                return null;
+            }
+            return new ClipboardFormat(retVal);
+        }
+    }
+
     /// <summary>
     /// Returns the underlying raw handle.
     /// </summary>
@@ -151,6 +151,14 @@ public partial class ConfigBuilder: IDisposable
         }
     }
 
+    public bool RestrictedAdmin
+    {
+        set
+        {
+            SetRestrictedAdmin(value);
+        }
+    }
+
     /// <summary>
     /// Creates a managed <c>ConfigBuilder</c> from a raw handle.
     /// </summary>
@@ -442,6 +450,18 @@ public partial class ConfigBuilder: IDisposable
         }
     }
 
+    public void SetRestrictedAdmin(bool restrictedAdmin)
+    {
+        unsafe
+        {
+            if (_inner == null)
+            {
+                throw new ObjectDisposedException("ConfigBuilder");
+            }
+            Raw.ConfigBuilder.SetRestrictedAdmin(_inner, restrictedAdmin);
+        }
+    }
+
     public void SetPointerSoftwareRendering(bool pointerSoftwareRendering)
     {
         unsafe
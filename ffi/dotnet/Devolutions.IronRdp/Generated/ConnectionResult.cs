@@ -31,6 +31,14 @@ public partial class ConnectionResult: IDisposable
         }
     }
 
+    public bool Nla
+    {
+        get
+        {
+            return GetNla();
+        }
+    }
+
     public bool NoServerPointer
     {
         get
@@ -47,6 +55,14 @@ public partial class ConnectionResult: IDisposable
         }
     }
 
+    public SecurityProtocol SecurityProtocol
+    {
+        get
+        {
+            return GetSecurityProtocol();
+        }
+    }
+
     public ushort UserChannelId
     {
         get
@@ -167,6 +183,50 @@ public partial class ConnectionResult: IDisposable
         }
     }
 
+    /// <exception cref="IronRdpException"></exception>
+    /// <returns>
+    /// A <c>SecurityProtocol</c> allocated on Rust side.
+    /// </returns>
+    public SecurityProtocol GetSecurityProtocol()
+    {
+        unsafe
+        {
+            if (_inner == null)
+            {
+                throw new ObjectDisposedException("ConnectionResult");
+            }
+            Raw.ConnectorResultFfiResultBoxSecurityProtocolBoxIronRdpError result = Raw.ConnectionResult.GetSecurityProtocol(_inner);
+            if (!result.isOk)
+            {
+                throw new IronRdpException(new IronRdpError(result.Err));
+            }
+            Raw.SecurityProtocol* retVal = result.Ok;
+            return new SecurityProtocol(retVal);
+        }
+    }
+
+    /// <summary>
+    /// Whether the user was authenticated with CredSSP (Network Level Authentication)
+    /// </summary>
+    /// <exception cref="IronRdpException"></exception>
+    public bool GetNla()
+    {
+        unsafe
+        {
+            if (_inner == null)
+            {
+                throw new ObjectDisposedException("ConnectionResult");
+            }
+            Raw.ConnectorResultFfiResultBoolBoxIronRdpError result = Raw.ConnectionResult.GetNla(_inner);
+            if (!result.isOk)
+            {
+                throw new IronRdpException(new IronRdpError(result.Err));
+            }
+            bool retVal = result.Ok;
+            return retVal;
+        }
+    }
+
     /// <summary>
     /// Returns the underlying raw handle.
     /// </summary>
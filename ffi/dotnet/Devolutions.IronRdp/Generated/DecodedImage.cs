@@ -31,6 +31,22 @@ public partial class DecodedImage: IDisposable
         }
     }
 
+    public PixelFormat PixelFormat
+    {
+        get
+        {
+            return GetPixelFormat();
+        }
+    }
+
+    public nuint Stride
+    {
+        get
+        {
+            return GetStride();
+        }
+    }
+
     public ushort Width
     {
         get
@@ -109,6 +125,69 @@ public partial class DecodedImage: IDisposable
         }
     }
 
+    /// <returns>
+    /// A <c>PixelFormat</c> allocated on C# side.
+    /// </returns>
+    public PixelFormat GetPixelFormat()
+    {
+        unsafe
+        {
+            if (_inner == null)
+            {
+                throw new ObjectDisposedException("DecodedImage");
+            }
+            Raw.PixelFormat retVal = Raw.DecodedImage.GetPixelFormat(_inner);
+            return (PixelFormat)retVal;
+        }
+    }
+
+    /// <summary>
+    /// Number of bytes per row of the image data
+    /// </summary>
+    public nuint GetStride()
+    {
+        unsafe
+        {
+            if (_inner == null)
+            {
+                throw new ObjectDisposedException("DecodedImage");
+            }
+            nuint retVal = Raw.DecodedImage.GetStride(_inner);
+            return retVal;
+        }
+    }
+
+    /// <summary>
+    /// Copies the pixels of the given region (e.g.: the rectangle of a graphics update) into `buffer`,
+    /// row after row without padding.
+    /// </summary>
+    /// <exception cref="IronRdpException"></exception>
+    public void CopyRegion(InclusiveRectangle region, byte[] buffer)
+    {
+        unsafe
+        {
+            if (_inner == null)
+            {
+                throw new ObjectDisposedException("DecodedImage");
+            }
+            nuint bufferLength = (nuint)buffer.Length;
+            Raw.InclusiveRectangle* regionRaw;
+            regionRaw = region.AsFFI();
+            if (regionRaw == null)
+            {
+                throw new ObjectDisposedException("InclusiveRectangle");
+            }
+            fixed (byte* bufferPtr = buffer)
+            {
+                Raw.SessionImageFfiResultVoidBoxIronRdpError result = Raw.DecodedImage.CopyRegion(_inner, regionRaw, bufferPtr, bufferLength);
+                if (!result.isOk)
+                {
+                    throw new IronRdpException(new IronRdpError(result.Err));
+                }
+            }
+        }
+    }
+
     /// <summary>
     /// Returns the underlying raw handle.
     /// </summary>
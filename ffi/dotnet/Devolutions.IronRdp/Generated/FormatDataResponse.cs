@@ -15,6 +15,14 @@ public partial class FormatDataResponse: IDisposable
 {
     private unsafe Raw.FormatDataResponse* _inner;
 
+    public BytesSlice? Data
+    {
+        get
+        {
+            return GetData();
+        }
+    }
+
     /// <summary>
     /// Creates a managed <c>FormatDataResponse</c> from a raw handle.
     /// </summary>
@@ -29,6 +37,73 @@ public partial class FormatDataResponse: IDisposable
         _inner = handle;
     }
 
+    /// <returns>
+    /// A <c>FormatDataResponse</c> allocated on Rust side.
+    /// </returns>
+    public static FormatDataResponse NewData(byte[] data)
+    {
+        unsafe
+        {
+            nuint dataLength = (nuint)data.Length;
+            fixed (byte* dataPtr = data)
+            {
+                Raw.FormatDataResponse* retVal = Raw.FormatDataResponse.NewData(dataPtr, dataLength);
+                return new FormatDataResponse(retVal);
+            }
+        }
+    }
+
+    /// <returns>
+    /// A <c>FormatDataResponse</c> allocated on Rust side.
+    /// </returns>
+    public static FormatDataResponse NewError()
+    {
+        unsafe
+        {
+            Raw.FormatDataResponse* retVal = Raw.FormatDataResponse.NewError();
+            return new FormatDataResponse(retVal);
+        }
+    }
+
+    /// <summary>
+    /// Returns `false` once the response has been submitted
+    /// </summary>
+    public bool IsError()
+    {
+        unsafe
+        {
+            if (_inner == null)
+            {
+                throw new ObjectDisposedException("FormatDataResponse");
+            }
+            bool retVal = Raw.FormatDataResponse.IsError(_inner);
+            return retVal;
+        }
+    }
+
+    /// <summary>
+    /// Returns the data of the response, or nothing once the response has been submitted
+    /// </summary>
+    /// <returns>
+    /// A <c>BytesSlice</c> allocated on Rust side.
+    /// </returns>
+    public BytesSlice? GetData()
+    {
+        unsafe
+        {
+            if (_inner == null)
+            {
+                throw new ObjectDisposedException("FormatDataResponse");
+            }
+            Raw.BytesSlice* retVal = Raw.FormatDataResponse.GetData(_inner);
+            if (retVal == null)
+            {
+                return null;
+            }
+            return new BytesSlice(retVal);
+        }
+    }
+
     /// <summary>
     /// Returns the underlying raw handle.
     /// </summary>
@@ -15,6 +15,22 @@ public partial class GracefulDisconnectReason: IDisposable
 {
     private unsafe Raw.GracefulDisconnectReason* _inner;
 
+    public string Description
+    {
+        get
+        {
+            return GetDescription();
+        }
+    }
+
+    public GracefulDisconnectReasonType ReasonType
+    {
+        get
+        {
+            return GetReasonType();
+        }
+    }
+
     /// <summary>
     /// Creates a managed <c>GracefulDisconnectReason</c> from a raw handle.
     /// </summary>
@@ -29,6 +45,56 @@ public partial class GracefulDisconnectReason: IDisposable
         _inner = handle;
     }
 
+    /// <returns>
+    /// A <c>GracefulDisconnectReasonType</c> allocated on C# side.
+    /// </returns>
+    public GracefulDisconnectReasonType GetReasonType()
+    {
+        unsafe
+        {
+            if (_inner == null)
+            {
+                throw new ObjectDisposedException("GracefulDisconnectReason");
+            }
+            Raw.GracefulDisconnectReasonType retVal = Raw.GracefulDisconnectReason.GetReasonType(_inner);
+            return (GracefulDisconnectReasonType)retVal;
+        }
+    }
+
+    /// <summary>
+    /// Returns a GUI-friendly description of the disconnect reason.
+    /// </summary>
+    public void GetDescription(DiplomatWriteable writeable)
+    {
+        unsafe
+        {
+            if (_inner == null)
+            {
+                throw new ObjectDisposedException("GracefulDisconnectReason");
+            }
+            Raw.GracefulDisconnectReason.GetDescription(_inner, &writeable);
+        }
+    }
+
+    /// <summary>
+    /// Returns a GUI-friendly description of the disconnect reason.
+    /// </summary>
+    public string GetDescription()
+    {
+        unsafe
+        {
+            if (_inner == null)
+            {
+                throw new ObjectDisposedException("GracefulDisconnectReason");
+            }
+            DiplomatWriteable writeable = new DiplomatWriteable();
+            Raw.GracefulDisconnectReason.GetDescription(_inner, &writeable);
+            string retVal = writeable.ToUnicode();
+            writeable.Dispose();
+            return retVal;
+        }
+    }
+
     /// <summary>
     /// Returns the underlying raw handle.
     /// </summary>
@@ -0,0 +1,19 @@
+// <auto-generated/> by Diplomat
+
+#pragma warning disable 0105
+using System;
+using System.Runtime.InteropServices;
+
+using Devolutions.IronRdp.Diplomat;
+#pragma warning restore 0105
+
+namespace Devolutions.IronRdp;
+
+#nullable enable
+
+public enum GracefulDisconnectReasonType
+{
+    UserInitiated = 0,
+    ServerInitiated = 1,
+    Other = 2,
+}
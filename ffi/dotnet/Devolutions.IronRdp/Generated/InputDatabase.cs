@@ -63,6 +63,40 @@ public partial class InputDatabase: IDisposable
         }
     }
 
+    /// <summary>
+    /// Releases all the keys and mouse buttons currently pressed, e.g.: when the window loses the focus.
+    /// </summary>
+    /// <returns>
+    /// A <c>FastPathInputEventIterator</c> allocated on Rust side.
+    /// </returns>
+    public FastPathInputEventIterator ReleaseAll()
+    {
+        unsafe
+        {
+            if (_inner == null)
+            {
+                throw new ObjectDisposedException("InputDatabase");
+            }
+            Raw.FastPathInputEventIterator* retVal = Raw.InputDatabase.ReleaseAll(_inner);
+            return new FastPathInputEventIterator(retVal);
+        }
+    }
+
+    /// <summary>
+    /// Synchronizes the state of the lock keys with the server, e.g.: when the window gains the focus.
+    /// </summary>
+    /// <returns>
+    /// A <c>FastPathInputEventIterator</c> allocated on Rust side.
+    /// </returns>
+    public static FastPathInputEventIterator SynchronizeEvent(bool scrollLock, bool numLock, bool capsLock, bool kanaLock)
+    {
+        unsafe
+        {
+            Raw.FastPathInputEventIterator* retVal = Raw.InputDatabase.SynchronizeEvent(scrollLock, numLock, capsLock, kanaLock);
+            return new FastPathInputEventIterator(retVal);
+        }
+    }
+
     /// <summary>
     /// Returns the underlying raw handle.
     /// </summary>
@@ -0,0 +1,114 @@
+// <auto-generated/> by Diplomat
+
+#pragma warning disable 0105
+using System;
+using System.Runtime.InteropServices;
+
+using Devolutions.IronRdp.Diplomat;
+#pragma warning restore 0105
+
+namespace Devolutions.IronRdp;
+
+#nullable enable
+
+/// <summary>
+/// Platform-independent clipboard backend, forwarding the clipboard events to the native client
+/// </summary>
+public partial class PollingCliprdr: IDisposable
+{
+    private unsafe Raw.PollingCliprdr* _inner;
+
+    /// <summary>
+    /// Creates a managed <c>PollingCliprdr</c> from a raw handle.
+    /// </summary>
+    /// <remarks>
+    /// Safety: you should not build two managed objects using the same raw handle (may causes use-after-free and double-free).
+    /// <br/>
+    /// This constructor assumes the raw struct is allocated on Rust side.
+    /// If implemented, the custom Drop implementation on Rust side WILL run on destruction.
+    /// </remarks>
+    public unsafe PollingCliprdr(Raw.PollingCliprdr* handle)
+    {
+        _inner = handle;
+    }
+
+    /// <returns>
+    /// A <c>PollingCliprdr</c> allocated on Rust side.
+    /// </returns>
+    public static PollingCliprdr New()
+    {
+        unsafe
+        {
+            Raw.PollingCliprdr* retVal = Raw.PollingCliprdr.New();
+            return new PollingCliprdr(retVal);
+        }
+    }
+
+    /// <returns>
+    /// A <c>ClipboardEvent</c> allocated on Rust side.
+    /// </returns>
+    public ClipboardEvent? NextClipboardEvent()
+    {
+        unsafe
+        {
+            if (_inner == null)
+            {
+                throw new ObjectDisposedException("PollingCliprdr");
+            }
+            Raw.ClipboardEvent* retVal = Raw.PollingCliprdr.NextClipboardEvent(_inner);
+            if (retVal == null)
+            {
+                return null;
+            }
+            return new ClipboardEvent(retVal);
+        }
+    }
+
+    /// <returns>
+    /// A <c>CliprdrBackendFactory</c> allocated on Rust side.
+    /// </returns>
+    public CliprdrBackendFactory BackendFactory()
+    {
+        unsafe
+        {
+            if (_inner == null)
+            {
+                throw new ObjectDisposedException("PollingCliprdr");
+            }
+            Raw.CliprdrBackendFactory* retVal = Raw.PollingCliprdr.BackendFactory(_inner);
+            return new CliprdrBackendFactory(retVal);
+        }
+    }
+
+    /// <summary>
+    /// Returns the underlying raw handle.
+    /// </summary>
+    public unsafe Raw.PollingCliprdr* AsFFI()
+    {
+        return _inner;
+    }
+
+    /// <summary>
+    /// Destroys the underlying object immediately.
+    /// </summary>
+    public void Dispose()
+    {
+        unsafe
+        {
+            if (_inner == null)
+            {
+                return;
+            }
+
+            Raw.PollingCliprdr.Destroy(_inner);
+            _inner = null;
+
+            GC.SuppressFinalize(this);
+        }
+    }
+
+    ~PollingCliprdr()
+    {
+        Dispose();
+    }
+}
@@ -0,0 +1,42 @@
+// <auto-generated/> by Diplomat
+
+#pragma warning disable 0105
+using System;
+using System.Runtime.InteropServices;
+
+using Devolutions.IronRdp.Diplomat;
+#pragma warning restore 0105
+
+namespace Devolutions.IronRdp.Raw;
+
+#nullable enable
+
+[StructLayout(LayoutKind.Sequential)]
+public partial struct ClipboardEvent
+{
+    private const string NativeLib = "DevolutionsIronRdp";
+
+    [DllImport(NativeLib, CallingConvention = CallingConvention.Cdecl, EntryPoint = "ClipboardEvent_get_event_type", ExactSpelling = true)]
+    public static unsafe extern ClipboardEventType GetEventType(ClipboardEvent* self);
+
+    /// <summary>
+    /// Formats available on the remote clipboard, to be requested with `ActiveStage::initiate_clipboard_paste`
+    /// </summary>
+    [DllImport(NativeLib, CallingConvention = CallingConvention.Cdecl, EntryPoint = "ClipboardEvent_get_remote_copy", ExactSpelling = true)]
+    public static unsafe extern ClipboardFormatIterator* GetRemoteCopy(ClipboardEvent* self);
+
+    /// <summary>
+    /// Format requested by the server, to be answered with `ActiveStage::submit_clipboard_format_data`
+    /// </summary>
+    [DllImport(NativeLib, CallingConvention = CallingConvention.Cdecl, EntryPoint = "ClipboardEvent_get_format_data_request", ExactSpelling = true)]
+    public static unsafe extern ClipboardFormatId* GetFormatDataRequest(ClipboardEvent* self);
+
+    /// <summary>
+    /// Data sent by the server in response to `ActiveStage::initiate_clipboard_paste`
+    /// </summary>
+    [DllImport(NativeLib, CallingConvention = CallingConvention.Cdecl, EntryPoint = "ClipboardEvent_get_format_data_response", ExactSpelling = true)]
+    public static unsafe extern FormatDataResponse* GetFormatDataResponse(ClipboardEvent* self);
+
+    [DllImport(NativeLib, CallingConvention = CallingConvention.Cdecl, EntryPoint = "ClipboardEvent_destroy", ExactSpelling = true)]
+    public static unsafe extern void Destroy(ClipboardEvent* self);
+}
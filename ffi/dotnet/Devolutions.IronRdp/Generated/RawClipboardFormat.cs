@@ -0,0 +1,30 @@
+// <auto-generated/> by Diplomat
+
+#pragma warning disable 0105
+using System;
+using System.Runtime.InteropServices;
+
+using Devolutions.IronRdp.Diplomat;
+#pragma warning restore 0105
+
+namespace Devolutions.IronRdp.Raw;
+
+#nullable enable
+
+[StructLayout(LayoutKind.Sequential)]
+public partial struct ClipboardFormat
+{
+    private const string NativeLib = "DevolutionsIronRdp";
+
+    [DllImport(NativeLib, CallingConvention = CallingConvention.Cdecl, EntryPoint = "ClipboardFormat_get_id", ExactSpelling = true)]
+    public static unsafe extern ClipboardFormatId* GetId(ClipboardFormat* self);
+
+    /// <summary>
+    /// Writes the name of the format, if any
+    /// </summary>
+    [DllImport(NativeLib, CallingConvention = CallingConvention.Cdecl, EntryPoint = "ClipboardFormat_get_name", ExactSpelling = true)]
+    public static unsafe extern void GetName(ClipboardFormat* self, DiplomatWriteable* writeable);
+
+    [DllImport(NativeLib, CallingConvention = CallingConvention.Cdecl, EntryPoint = "ClipboardFormat_destroy", ExactSpelling = true)]
+    public static unsafe extern void Destroy(ClipboardFormat* self);
+}
@@ -16,6 +16,12 @@ public partial struct ClipboardFormatId
 {
     private const string NativeLib = "DevolutionsIronRdp";
 
+    [DllImport(NativeLib, CallingConvention = CallingConvention.Cdecl, EntryPoint = "ClipboardFormatId_new", ExactSpelling = true)]
+    public static unsafe extern ClipboardFormatId* New(uint value);
+
+    [DllImport(NativeLib, CallingConvention = CallingConvention.Cdecl, EntryPoint = "ClipboardFormatId_get_value", ExactSpelling = true)]
+    public static unsafe extern uint GetValue(ClipboardFormatId* self);
+
     [DllImport(NativeLib, CallingConvention = CallingConvention.Cdecl, EntryPoint = "ClipboardFormatId_destroy", ExactSpelling = true)]
     public static unsafe extern void Destroy(ClipboardFormatId* self);
 }
@@ -16,6 +16,25 @@ public partial struct ClipboardFormatIterator
 {
     private const string NativeLib = "DevolutionsIronRdp";
 
+    [DllImport(NativeLib, CallingConvention = CallingConvention.Cdecl, EntryPoint = "ClipboardFormatIterator_new", ExactSpelling = true)]
+    public static unsafe extern ClipboardFormatIterator* New();
+
+    /// <summary>
+    /// Adds a format to the list, `name` being ignored when empty
+    /// </summary>
+    [DllImport(NativeLib, CallingConvention = CallingConvention.Cdecl, EntryPoint = "ClipboardFormatIterator_push", ExactSpelling = true)]
+    public static unsafe extern void Push(ClipboardFormatIterator* self, ClipboardFormatId* formatId, byte* name, nuint nameSz);
+
+    [DllImport(NativeLib, CallingConvention = CallingConvention.Cdecl, EntryPoint = "ClipboardFormatIterator_len", ExactSpelling = true)]
+    public static unsafe extern nuint Len(ClipboardFormatIterator* self);
+
+    [DllImport(NativeLib, CallingConvention = CallingConvention.Cdecl, EntryPoint = "ClipboardFormatIterator_is_empty", ExactSpelling = true)]
+    [return: MarshalAs(UnmanagedType.U1)]
+    public static unsafe extern bool IsEmpty(ClipboardFormatIterator* self);
+
+    [DllImport(NativeLib, CallingConvention = CallingConvention.Cdecl, EntryPoint = "ClipboardFormatIterator_next", ExactSpelling = true)]
+    public static unsafe extern ClipboardFormat* Next(ClipboardFormatIterator* self);
+
     [DllImport(NativeLib, CallingConvention = CallingConvention.Cdecl, EntryPoint = "ClipboardFormatIterator_destroy", ExactSpelling = true)]
     public static unsafe extern void Destroy(ClipboardFormatIterator* self);
 }
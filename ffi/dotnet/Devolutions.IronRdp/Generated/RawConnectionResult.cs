@@ -31,6 +31,15 @@ public partial struct ConnectionResult
     [DllImport(NativeLib, CallingConvention = CallingConvention.Cdecl, EntryPoint = "ConnectionResult_get_pointer_software_rendering", ExactSpelling = true)]
     public static unsafe extern ConnectorResultFfiResultBoolBoxIronRdpError GetPointerSoftwareRendering(ConnectionResult* self);
 
+    [DllImport(NativeLib, CallingConvention = CallingConvention.Cdecl, EntryPoint = "ConnectionResult_get_security_protocol", ExactSpelling = true)]
+    public static unsafe extern ConnectorResultFfiResultBoxSecurityProtocolBoxIronRdpError GetSecurityProtocol(ConnectionResult* self);
+
+    /// <summary>
+    /// Whether the user was authenticated with CredSSP (Network Level Authentication)
+    /// </summary>
+    [DllImport(NativeLib, CallingConvention = CallingConvention.Cdecl, EntryPoint = "ConnectionResult_get_nla", ExactSpelling = true)]
+    public static unsafe extern ConnectorResultFfiResultBoolBoxIronRdpError GetNla(ConnectionResult* self);
+
     [DllImport(NativeLib, CallingConvention = CallingConvention.Cdecl, EntryPoint = "ConnectionResult_destroy", ExactSpelling = true)]
     public static unsafe extern void Destroy(ConnectionResult* self);
 }
@@ -0,0 +1,46 @@
+// <auto-generated/> by Diplomat
+
+#pragma warning disable 0105
+using System;
+using System.Runtime.InteropServices;
+
+using Devolutions.IronRdp.Diplomat;
+#pragma warning restore 0105
+
+namespace Devolutions.IronRdp.Raw;
+
+#nullable enable
+
+[StructLayout(LayoutKind.Sequential)]
+public partial struct ConnectorResultFfiResultBoxSecurityProtocolBoxIronRdpError
+{
+    [StructLayout(LayoutKind.Explicit)]
+    private unsafe struct InnerUnion
+    {
+        [FieldOffset(0)]
+        internal SecurityProtocol* ok;
+        [FieldOffset(0)]
+        internal IronRdpError* err;
+    }
+
+    private InnerUnion _inner;
+
+    [MarshalAs(UnmanagedType.U1)]
+    public bool isOk;
+
+    public unsafe SecurityProtocol* Ok
+    {
+        get
+        {
+            return _inner.ok;
+        }
+    }
+
+    public unsafe IronRdpError* Err
+    {
+        get
+        {
+            return _inner.err;
+        }
+    }
+}
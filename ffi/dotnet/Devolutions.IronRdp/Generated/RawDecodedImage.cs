@@ -28,6 +28,22 @@ public partial struct DecodedImage
     [DllImport(NativeLib, CallingConvention = CallingConvention.Cdecl, EntryPoint = "DecodedImage_get_height", ExactSpelling = true)]
     public static unsafe extern ushort GetHeight(DecodedImage* self);
 
+    [DllImport(NativeLib, CallingConvention = CallingConvention.Cdecl, EntryPoint = "DecodedImage_get_pixel_format", ExactSpelling = true)]
+    public static unsafe extern PixelFormat GetPixelFormat(DecodedImage* self);
+
+    /// <summary>
+    /// Number of bytes per row of the image data
+    /// </summary>
+    [DllImport(NativeLib, CallingConvention = CallingConvention.Cdecl, EntryPoint = "DecodedImage_get_stride", ExactSpelling = true)]
+    public static unsafe extern nuint GetStride(DecodedImage* self);
+
+    /// <summary>
+    /// Copies the pixels of the given region (e.g.: the rectangle of a graphics update) into `buffer`,
+    /// row after row without padding.
+    /// </summary>
+    [DllImport(NativeLib, CallingConvention = CallingConvention.Cdecl, EntryPoint = "DecodedImage_copy_region", ExactSpelling = true)]
+    public static unsafe extern SessionImageFfiResultVoidBoxIronRdpError CopyRegion(DecodedImage* self, InclusiveRectangle* region, byte* buffer, nuint bufferSz);
+
     [DllImport(NativeLib, CallingConvention = CallingConvention.Cdecl, EntryPoint = "DecodedImage_destroy", ExactSpelling = true)]
     public static unsafe extern void Destroy(DecodedImage* self);
 }
@@ -16,6 +16,25 @@ public partial struct FormatDataResponse
 {
     private const string NativeLib = "DevolutionsIronRdp";
 
+    [DllImport(NativeLib, CallingConvention = CallingConvention.Cdecl, EntryPoint = "FormatDataResponse_new_data", ExactSpelling = true)]
+    public static unsafe extern FormatDataResponse* NewData(byte* data, nuint dataSz);
+
+    [DllImport(NativeLib, CallingConvention = CallingConvention.Cdecl, EntryPoint = "FormatDataResponse_new_error", ExactSpelling = true)]
+    public static unsafe extern FormatDataResponse* NewError();
+
+    /// <summary>
+    /// Returns `false` once the response has been submitted
+    /// </summary>
+    [DllImport(NativeLib, CallingConvention = CallingConvention.Cdecl, EntryPoint = "FormatDataResponse_is_error", ExactSpelling = true)]
+    [return: MarshalAs(UnmanagedType.U1)]
+    public static unsafe extern bool IsError(FormatDataResponse* self);
+
+    /// <summary>
+    /// Returns the data of the response, or nothing once the response has been submitted
+    /// </summary>
+    [DllImport(NativeLib, CallingConvention = CallingConvention.Cdecl, EntryPoint = "FormatDataResponse_get_data", ExactSpelling = true)]
+    public static unsafe extern BytesSlice* GetData(FormatDataResponse* self);
+
     [DllImport(NativeLib, CallingConvention = CallingConvention.Cdecl, EntryPoint = "FormatDataResponse_destroy", ExactSpelling = true)]
     public static unsafe extern void Destroy(FormatDataResponse* self);
 }
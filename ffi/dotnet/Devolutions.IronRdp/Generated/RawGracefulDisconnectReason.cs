@@ -16,6 +16,15 @@ public partial struct GracefulDisconnectReason
 {
     private const string NativeLib = "DevolutionsIronRdp";
 
+    [DllImport(NativeLib, CallingConvention = CallingConvention.Cdecl, EntryPoint = "GracefulDisconnectReason_get_reason_type", ExactSpelling = true)]
+    public static unsafe extern GracefulDisconnectReasonType GetReasonType(GracefulDisconnectReason* self);
+
+    /// <summary>
+    /// Returns a GUI-friendly description of the disconnect reason.
+    /// </summary>
+    [DllImport(NativeLib, CallingConvention = CallingConvention.Cdecl, EntryPoint = "GracefulDisconnectReason_get_description", ExactSpelling = true)]
+    public static unsafe extern void GetDescription(GracefulDisconnectReason* self, DiplomatWriteable* writeable);
+
     [DllImport(NativeLib, CallingConvention = CallingConvention.Cdecl, EntryPoint = "GracefulDisconnectReason_destroy", ExactSpelling = true)]
     public static unsafe extern void Destroy(GracefulDisconnectReason* self);
 }
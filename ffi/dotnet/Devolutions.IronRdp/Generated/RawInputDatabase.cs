@@ -22,6 +22,18 @@ public partial struct InputDatabase
     [DllImport(NativeLib, CallingConvention = CallingConvention.Cdecl, EntryPoint = "InputDatabase_apply", ExactSpelling = true)]
     public static unsafe extern FastPathInputEventIterator* Apply(InputDatabase* self, Operation* operation);
 
+    /// <summary>
+    /// Releases all the keys and mouse buttons currently pressed, e.g.: when the window loses the focus.
+    /// </summary>
+    [DllImport(NativeLib, CallingConvention = CallingConvention.Cdecl, EntryPoint = "InputDatabase_release_all", ExactSpelling = true)]
+    public static unsafe extern FastPathInputEventIterator* ReleaseAll(InputDatabase* self);
+
+    /// <summary>
+    /// Synchronizes the state of the lock keys with the server, e.g.: when the window gains the focus.
+    /// </summary>
+    [DllImport(NativeLib, CallingConvention = CallingConvention.Cdecl, EntryPoint = "InputDatabase_synchronize_event", ExactSpelling = true)]
+    public static unsafe extern FastPathInputEventIterator* SynchronizeEvent([MarshalAs(UnmanagedType.U1)] bool scrollLock, [MarshalAs(UnmanagedType.U1)] bool numLock, [MarshalAs(UnmanagedType.U1)] bool capsLock, [MarshalAs(UnmanagedType.U1)] bool kanaLock);
+
     [DllImport(NativeLib, CallingConvention = CallingConvention.Cdecl, EntryPoint = "InputDatabase_destroy", ExactSpelling = true)]
     public static unsafe extern void Destroy(InputDatabase* self);
 }
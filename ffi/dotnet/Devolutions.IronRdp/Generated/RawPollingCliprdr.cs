@@ -0,0 +1,33 @@
+// <auto-generated/> by Diplomat
+
+#pragma warning disable 0105
+using System;
+using System.Runtime.InteropServices;
+
+using Devolutions.IronRdp.Diplomat;
+#pragma warning restore 0105
+
+namespace Devolutions.IronRdp.Raw;
+
+#nullable enable
+
+/// <summary>
+/// Platform-independent clipboard backend, forwarding the clipboard events to the native client
+/// </summary>
+[StructLayout(LayoutKind.Sequential)]
+public partial struct PollingCliprdr
+{
+    private const string NativeLib = "DevolutionsIronRdp";
+
+    [DllImport(NativeLib, CallingConvention = CallingConvention.Cdecl, EntryPoint = "PollingCliprdr_new", ExactSpelling = true)]
+    public static unsafe extern PollingCliprdr* New();
+
+    [DllImport(NativeLib, CallingConvention = CallingConvention.Cdecl, EntryPoint = "PollingCliprdr_next_clipboard_event", ExactSpelling = true)]
+    public static unsafe extern ClipboardEvent* NextClipboardEvent(PollingCliprdr* self);
+
+    [DllImport(NativeLib, CallingConvention = CallingConvention.Cdecl, EntryPoint = "PollingCliprdr_backend_factory", ExactSpelling = true)]
+    public static unsafe extern CliprdrBackendFactory* BackendFactory(PollingCliprdr* self);
+
+    [DllImport(NativeLib, CallingConvention = CallingConvention.Cdecl, EntryPoint = "PollingCliprdr_destroy", ExactSpelling = true)]
+    public static unsafe extern void Destroy(PollingCliprdr* self);
+}
@@ -17,4 +17,5 @@ public enum IronRdpLibExceptionType
     CannotResolveDns,
     ConnectionFailed,
     EndOfFile,
+    SessionTerminated,
 }
\ No newline at end of file
@@ -0,0 +1,367 @@
+using System.Net.Security;
+using System.Threading.Channels;
+
+namespace Devolutions.IronRdp;
+
+/// <summary>
+/// Active RDP session, processing the PDUs received from the server in the background.
+/// </summary>
+/// <remarks>
+/// The graphics updates are published through <see cref="ReadUpdatesAsync"/>, and buffered until read.
+/// The event handlers are invoked from the background task processing the session.
+/// </remarks>
+public sealed class RdpSession : IAsyncDisposable
+{
+    private readonly Framed<SslStream> _framed;
+    private readonly ActiveStage _activeStage;
+    private readonly InputDatabase _inputDatabase = InputDatabase.New();
+    private readonly PollingCliprdr? _cliprdr;
+    private readonly Channel<FrameUpdate> _updates = Channel.CreateUnbounded<FrameUpdate>();
+
+    // ActiveStage and DecodedImage are not thread-safe: the session is processed under this lock.
+    private readonly SemaphoreSlim _lock = new(1, 1);
+
+    private DecodedImage _image;
+    private Task? _processing;
+    private volatile bool _terminated;
+
+    public event EventHandler<ClipboardEventArgs>? ClipboardEvent;
+
+    public event EventHandler<DisconnectedEventArgs>? Disconnected;
+
+    public ushort Width => _image.GetWidth();
+
+    public ushort Height => _image.GetHeight();
+
+    private RdpSession(ConnectionResult connectionResult, Framed<SslStream> framed, PollingCliprdr? cliprdr)
+    {
+        var desktopSize = connectionResult.GetDesktopSize();
+        _image = DecodedImage.New(PixelFormat.RgbA32, desktopSize.GetWidth(), desktopSize.GetHeight());
+        _activeStage = ActiveStage.New(connectionResult);
+        _framed = framed;
+        _cliprdr = cliprdr;
+    }
+
+    /// <summary>
+    /// Connects to the server, then starts processing the session in the background.
+    /// </summary>
+    /// <param name="enableClipboard">Whether the clipboard is redirected, raising <see cref="ClipboardEvent"/>.</param>
+    public static async Task<RdpSession> ConnectAsync(Config config, string serverName, bool enableClipboard = false,
+        int port = 3389, CancellationToken cancellationToken = default)
+    {
+        var cliprdr = enableClipboard ? PollingCliprdr.New() : null;
+
+        var (result, framed) = await Connection.Connect(config, serverName, cliprdr?.BackendFactory(), port)
+            .WaitAsync(cancellationToken);
+
+        var session = new RdpSession(result, framed, cliprdr);
+        session._processing = Task.Run(session.ProcessAsync);
+
+        return session;
+    }
+
+    /// <summary>
+    /// Enumerates the regions of the remote desktop updated by the server, until the session ends.
+    /// </summary>
+    public IAsyncEnumerable<FrameUpdate> ReadUpdatesAsync(CancellationToken cancellationToken = default)
+    {
+        return _updates.Reader.ReadAllAsync(cancellationToken);
+    }
+
+    /// <summary>
+    /// Copies the whole remote desktop, in the pixel format of the session.
+    /// </summary>
+    public async Task<FrameUpdate> CaptureAsync(CancellationToken cancellationToken = default)
+    {
+        await _lock.WaitAsync(cancellationToken);
+        try
+        {
+            var data = _image.GetData();
+            var pixels = new byte[data.GetSize()];
+            data.Fill(pixels);
+            return new FrameUpdate(0, 0, _image.GetWidth(), _image.GetHeight(), pixels);
+        }
+        finally
+        {
+            _lock.Release();
+        }
+    }
+
+    public Task SendInputAsync(params Operation[] operations)
+    {
+        return WithActiveStageAsync(() =>
+        {
+            var outputs = new List<ActiveStageOutputIterator>();
+            foreach (var operation in operations)
+            {
+                var fastpath = _inputDatabase.Apply(operation);
+                outputs.Add(_activeStage.ProcessFastpathInput(_image, fastpath));
+            }
+
+            return outputs;
+        });
+    }
+
+    /// <summary>
+    /// Releases all the keys and mouse buttons currently pressed.
+    /// </summary>
+    public Task ReleaseAllAsync()
+    {
+        return WithActiveStageAsync(() =>
+        {
+            var fastpath = _inputDatabase.ReleaseAll();
+            return new List<ActiveStageOutputIterator> { _activeStage.ProcessFastpathInput(_image, fastpath) };
+        });
+    }
+
+    /// <summary>
+    /// Requests the server to resize the remote desktop, using the Display Control virtual channel.
+    /// </summary>
+    /// <returns>False if the server does not support resizing, in which case a reconnection is required.</returns>
+    public async Task<bool> ResizeAsync(uint width, uint height)
+    {
+        var supported = true;
+
+        await WithActiveStageAsync(() =>
+        {
+            var output = _activeStage.EncodedResize(width, height);
+            supported = output != null;
+            return output == null
+                ? new List<ActiveStageOutputIterator>()
+                : new List<ActiveStageOutputIterator> { output };
+        });
+
+        return supported;
+    }
+
+    /// <summary>
+    /// Advertises the formats available on the local clipboard.
+    /// </summary>
+    public Task InitiateClipboardCopyAsync(ClipboardFormatIterator formats)
+    {
+        return WriteClipboardFrameAsync(() => _activeStage.InitiateClipboardCopy(formats));
+    }
+
+    /// <summary>
+    /// Requests the data of a format available on the remote clipboard, received through <see cref="ClipboardEvent"/>.
+    /// </summary>
+    public Task InitiateClipboardPasteAsync(uint formatId)
+    {
+        return WriteClipboardFrameAsync(() => _activeStage.InitiateClipboardPaste(ClipboardFormatId.New(formatId)));
+    }
+
+    /// <summary>
+    /// Answers a <see cref="ClipboardEventType.FormatDataRequest"/>, with null if the data is not available.
+    /// </summary>
+    public Task SubmitClipboardFormatDataAsync(byte[]? data)
+    {
+        var response = data == null ? FormatDataResponse.NewError() : FormatDataResponse.NewData(data);
+        return WriteClipboardFrameAsync(() => _activeStage.SubmitClipboardFormatData(response));
+    }
+
+    /// <summary>
+    /// Gracefully disconnects from the server, then waits for the session to end.
+    /// </summary>
+    public async Task DisconnectAsync(CancellationToken cancellationToken = default)
+    {
+        if (!_terminated)
+        {
+            await WithActiveStageAsync(() =>
+                new List<ActiveStageOutputIterator> { _activeStage.GracefulShutdown() });
+        }
+
+        if (_processing != null)
+        {
+            await _processing.WaitAsync(cancellationToken);
+        }
+    }
+
+    public async ValueTask DisposeAsync()
+    {
+        try
+        {
+            await DisconnectAsync().WaitAsync(TimeSpan.FromSeconds(5));
+        }
+        catch (Exception)
+        {
+            // The connection is closed below regardless.
+        }
+
+        var (stream, _) = _framed.GetInner();
+        await stream.DisposeAsync();
+    }
+
+    private async Task ProcessAsync()
+    {
+        GracefulDisconnectReason? reason = null;
+        Exception? error = null;
+
+        try
+        {
+            while (reason == null)
+            {
+                var (action, payload) = await _framed.ReadPdu();
+
+                var clipboardEvents = new List<ClipboardEventArgs>();
+
+                await _lock.WaitAsync();
+                try
+                {
+                    var outputs = _activeStage.Process(_image, action, payload);
+                    reason = await HandleOutputsAsync(outputs);
+
+                    while (_cliprdr?.NextClipboardEvent() is { } clipboardEvent)
+                    {
+                        clipboardEvents.Add(ClipboardEventArgs.FromEvent(clipboardEvent));
+                    }
+                }
+                finally
+                {
+                    _lock.Release();
+                }
+
+                // Raised outside of the lock, so that the handlers can call back into the session.
+                foreach (var clipboardEvent in clipboardEvents)
+                {
+                    ClipboardEvent?.Invoke(this, clipboardEvent);
+                }
+            }
+        }
+        catch (Exception e)
+        {
+            error = e;
+        }
+
+        _terminated = true;
+        _updates.Writer.TryComplete(error);
+
+        Disconnected?.Invoke(this, reason != null
+            ? new DisconnectedEventArgs(reason.GetReasonType(), reason.GetDescription(), null)
+            : new DisconnectedEventArgs(null, error?.Message ?? "session ended", error));
+    }
+
+    private async Task WithActiveStageAsync(Func<List<ActiveStageOutputIterator>> produceOutputs)
+    {
+        if (_terminated)
+        {
+            throw new IronRdpLibException(IronRdpLibExceptionType.SessionTerminated, "Session is terminated");
+        }
+
+        await _lock.WaitAsync();
+        try
+        {
+            foreach (var outputs in produceOutputs())
+            {
+                await HandleOutputsAsync(outputs);
+            }
+        }
+        finally
+        {
+            _lock.Release();
+        }
+    }
+
+    private async Task WriteClipboardFrameAsync(Func<VecU8> produceFrame)
+    {
+        if (_cliprdr == null)
+        {
+            throw new InvalidOperationException("Clipboard is not enabled for this session");
+        }
+
+        if (_terminated)
+        {
+            throw new IronRdpLibException(IronRdpLibExceptionType.SessionTerminated, "Session is terminated");
+        }
+
+        await _lock.WaitAsync();
+        try
+        {
+            await _framed.Write(Utils.VecU8ToByte(produceFrame()));
+        }
+        finally
+        {
+            _lock.Release();
+        }
+    }
+
+    /// <summary>
+    /// Must be called with the lock held.
+    /// </summary>
+    /// <returns>The disconnect reason, once the server terminated the session.</returns>
+    private async Task<GracefulDisconnectReason?> HandleOutputsAsync(ActiveStageOutputIterator outputIterator)
+    {
+        while (!outputIterator.IsEmpty())
+        {
+            var output = outputIterator.Next()!; // outputIterator.Next() is not null since outputIterator.IsEmpty() is false
+
+            switch (output.GetEnumType())
+            {
+                case ActiveStageOutputType.ResponseFrame:
+                {
+                    var responseFrame = output.GetResponseFrame();
+                    var responseFrameBytes = new byte[responseFrame.GetSize()];
+                    responseFrame.Fill(responseFrameBytes);
+                    await _framed.Write(responseFrameBytes);
+                    break;
+                }
+                case ActiveStageOutputType.GraphicsUpdate:
+                {
+                    var region = output.GetGraphicsUpdate();
+                    var bytesPerPixel = (int)_image.GetStride() / _image.GetWidth();
+                    var pixels = new byte[region.GetWidth() * region.GetHeight() * bytesPerPixel];
+                    _image.CopyRegion(region, pixels);
+                    _updates.Writer.TryWrite(new FrameUpdate(region.GetLeft(), region.GetTop(), region.GetWidth(),
+                        region.GetHeight(), pixels));
+                    break;
+                }
+                case ActiveStageOutputType.DeactivateAll:
+                {
+                    await ReactivateAsync(output.GetDeactivateAll());
+                    break;
+                }
+                case ActiveStageOutputType.Terminate:
+                {
+                    return output.GetTerminate();
+                }
+                default:
+                {
+                    // The pointer is rendered into the image, and the monitor layout is not tracked.
+                    break;
+                }
+            }
+        }
+
+        return null;
+    }
+
+    /// <summary>
+    /// Executes the Deactivation-Reactivation Sequence, which may change the size of the remote desktop.
+    /// </summary>
+    private async Task ReactivateAsync(ConnectionActivationSequence activationSequence)
+    {
+        var writeBuf = WriteBuf.New();
+        while (true)
+        {
+            await Connection.SingleSequenceStep(activationSequence, writeBuf, _framed);
+
+            if (activationSequence.GetState().GetType() != ConnectionActivationStateType.Finalized)
+            {
+                continue;
+            }
+
+            var finalized = activationSequence.GetState().GetFinalized();
+            var desktopSize = finalized.GetDesktopSize();
+
+            _image = DecodedImage.New(PixelFormat.RgbA32, desktopSize.GetWidth(), desktopSize.GetHeight());
+            _activeStage.SetFastpathProcessor(
+                finalized.GetIoChannelId(),
+                finalized.GetUserChannelId(),
+                finalized.GetNoServerPointer(),
+                finalized.GetPointerSoftwareRendering()
+            );
+            _activeStage.SetNoServerPointer(finalized.GetNoServerPointer());
+
+            break;
+        }
+    }
+}
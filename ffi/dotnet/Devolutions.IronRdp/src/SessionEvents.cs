@@ -0,0 +1,91 @@
+namespace Devolutions.IronRdp;
+
+/// <summary>
+/// Region of the remote desktop updated by the server.
+/// </summary>
+/// <param name="Left">Left coordinate of the region.</param>
+/// <param name="Top">Top coordinate of the region.</param>
+/// <param name="Width">Width of the region, in pixels.</param>
+/// <param name="Height">Height of the region, in pixels.</param>
+/// <param name="Pixels">Tightly packed pixels of the region, in the pixel format of the session.</param>
+public record FrameUpdate(ushort Left, ushort Top, ushort Width, ushort Height, byte[] Pixels);
+
+/// <summary>
+/// Format available on the remote clipboard.
+/// </summary>
+public record RemoteClipboardFormat(uint Id, string Name);
+
+public class ClipboardEventArgs : EventArgs
+{
+    public ClipboardEventType EventType { get; }
+
+    /// <summary>
+    /// Formats available on the remote clipboard, set for <see cref="ClipboardEventType.RemoteCopy"/>.
+    /// </summary>
+    public IReadOnlyList<RemoteClipboardFormat> RemoteFormats { get; }
+
+    /// <summary>
+    /// Format requested by the server, set for <see cref="ClipboardEventType.FormatDataRequest"/>.
+    /// To be answered with <see cref="RdpSession.SubmitClipboardFormatDataAsync"/>.
+    /// </summary>
+    public uint? RequestedFormatId { get; }
+
+    /// <summary>
+    /// Data sent by the server, set for <see cref="ClipboardEventType.FormatDataResponse"/> unless the request failed.
+    /// </summary>
+    public byte[]? Data { get; }
+
+    public ClipboardEventArgs(ClipboardEventType eventType, IReadOnlyList<RemoteClipboardFormat> remoteFormats,
+        uint? requestedFormatId, byte[]? data)
+    {
+        EventType = eventType;
+        RemoteFormats = remoteFormats;
+        RequestedFormatId = requestedFormatId;
+        Data = data;
+    }
+
+    internal static ClipboardEventArgs FromEvent(ClipboardEvent clipboardEvent)
+    {
+        var remoteFormats = new List<RemoteClipboardFormat>();
+        var formats = clipboardEvent.GetRemoteCopy();
+        while (formats != null && !formats.IsEmpty())
+        {
+            var format = formats.Next()!; // formats.Next() is not null since formats.IsEmpty() is false
+            remoteFormats.Add(new RemoteClipboardFormat(format.GetId().GetValue(), format.GetName()));
+        }
+
+        byte[]? data = null;
+        var response = clipboardEvent.GetFormatDataResponse();
+        var responseData = response?.GetData();
+        if (responseData != null)
+        {
+            data = new byte[responseData.GetSize()];
+            responseData.Fill(data);
+        }
+
+        return new ClipboardEventArgs(clipboardEvent.GetEventType(), remoteFormats,
+            clipboardEvent.GetFormatDataRequest()?.GetValue(), data);
+    }
+}
+
+public class DisconnectedEventArgs : EventArgs
+{
+    /// <summary>
+    /// Reason of a graceful disconnection, null when the session failed.
+    /// </summary>
+    public GracefulDisconnectReasonType? ReasonType { get; }
+
+    public string Description { get; }
+
+    /// <summary>
+    /// Error which caused the session to end, null on graceful disconnection.
+    /// </summary>
+    public Exception? Error { get; }
+
+    public DisconnectedEventArgs(GracefulDisconnectReasonType? reasonType, string description, Exception? error)
+    {
+        ReasonType = reasonType;
+        Description = description;
+        Error = error;
+    }
+}
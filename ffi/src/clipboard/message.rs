@@ -1,5 +1,10 @@
 #[diplomat::bridge]
 pub mod ffi {
+    use std::fmt::Write as _;
+
+    use diplomat_runtime::DiplomatWriteable;
+
+    use crate::utils::ffi::BytesSlice;
 
     #[diplomat::opaque]
     pub struct ClipboardMessage(pub ironrdp::cliprdr::backend::ClipboardMessage);
@@ -57,9 +62,88 @@ pub mod ffi {
     #[diplomat::opaque]
     pub struct ClipboardFormatIterator(pub Vec<ironrdp::cliprdr::pdu::ClipboardFormat>);
 
+    impl ClipboardFormatIterator {
+        pub fn new() -> Box<ClipboardFormatIterator> {
+            Box::new(ClipboardFormatIterator(Vec::new()))
+        }
+
+        /// Adds a format to the list, `name` being ignored when empty
+        pub fn push(&mut self, format_id: &ClipboardFormatId, name: &str) {
+            let format = ironrdp::cliprdr::pdu::ClipboardFormat::new(format_id.0)
+                .with_name(ironrdp::cliprdr::pdu::ClipboardFormatName::new(name.to_owned()));
+            self.0.push(format);
+        }
+
+        pub fn len(&self) -> usize {
+            self.0.len()
+        }
+
+        pub fn is_empty(&self) -> bool {
+            self.0.is_empty()
+        }
+
+        pub fn next(&mut self) -> Option<Box<ClipboardFormat>> {
+            self.0.pop().map(ClipboardFormat).map(Box::new)
+        }
+    }
+
+    #[diplomat::opaque]
+    pub struct ClipboardFormat(pub ironrdp::cliprdr::pdu::ClipboardFormat);
+
+    impl ClipboardFormat {
+        pub fn get_id(&self) -> Box<ClipboardFormatId> {
+            Box::new(ClipboardFormatId(self.0.id()))
+        }
+
+        /// Writes the name of the format, if any
+        pub fn get_name(&self, writeable: &mut DiplomatWriteable) {
+            if let Some(name) = self.0.name() {
+                let _ = write!(writeable, "{}", name.value());
+            }
+            writeable.flush();
+        }
+    }
+
     #[diplomat::opaque]
     pub struct FormatDataResponse(pub Option<ironrdp::cliprdr::pdu::OwnedFormatDataResponse>);
 
+    impl FormatDataResponse {
+        pub fn new_data(data: &[u8]) -> Box<FormatDataResponse> {
+            Box::new(FormatDataResponse(Some(
+                ironrdp::cliprdr::pdu::OwnedFormatDataResponse::new_data(data.to_vec()),
+            )))
+        }
+
+        pub fn new_error() -> Box<FormatDataResponse> {
+            Box::new(FormatDataResponse(Some(
+                ironrdp::cliprdr::pdu::OwnedFormatDataResponse::new_error(),
+            )))
+        }
+
+        /// Returns `false` once the response has been submitted
+        pub fn is_error(&self) -> bool {
+            self.0.as_ref().is_some_and(|response| response.is_error())
+        }
+
+        /// Returns the data of the response, or nothing once the response has been submitted
+        pub fn get_data<'a>(&'a self) -> Option<Box<BytesSlice<'a>>> {
+            self.0
+                .as_ref()
+                .map(|response| BytesSlice(response.data()))
+                .map(Box::new)
+        }
+    }
+
     #[diplomat::opaque]
     pub struct ClipboardFormatId(pub ironrdp::cliprdr::pdu::ClipboardFormatId);
+
+    impl ClipboardFormatId {
+        pub fn new(value: u32) -> Box<ClipboardFormatId> {
+            Box::new(ClipboardFormatId(ironrdp::cliprdr::pdu::ClipboardFormatId::new(value)))
+        }
+
+        pub fn get_value(&self) -> u32 {
+            self.0.value()
+        }
+    }
 }
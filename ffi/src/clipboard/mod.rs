@@ -2,6 +2,7 @@ use tracing::error;
 
 pub mod message;
 
+pub mod polling;
 pub mod windows;
 
 #[diplomat::bridge]
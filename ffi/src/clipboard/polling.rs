@@ -0,0 +1,203 @@
+use std::sync::mpsc;
+
+use ironrdp::cliprdr::backend::{CliprdrBackend, CliprdrBackendFactory};
+use ironrdp::cliprdr::pdu::{
+    ClipboardFormat, ClipboardFormatId, ClipboardGeneralCapabilityFlags, FileContentsRequest, FileContentsResponse,
+    FormatDataRequest, FormatDataResponse, LockDataId, OwnedFormatDataResponse,
+};
+use ironrdp_core::{impl_as_any, IntoOwned};
+use tracing::error;
+
+/*
+    Why polling instead of callbacks?
+
+    Diplomat does not support passing callbacks across the FFI boundary. The backend methods are
+    called by the `Cliprdr` processor while the active stage processes the received frames, so the
+    events are queued, and the native client polls them after each call to `ActiveStage::process`.
+*/
+#[diplomat::bridge]
+pub mod ffi {
+    use crate::clipboard::ffi::CliprdrBackendFactory;
+    use crate::clipboard::message::ffi::{ClipboardFormatId, ClipboardFormatIterator, FormatDataResponse};
+
+    use super::{ClipboardEventInner, PollingCliprdrInner};
+
+    /// Platform-independent clipboard backend, forwarding the clipboard events to the native client
+    #[diplomat::opaque]
+    pub struct PollingCliprdr(PollingCliprdrInner);
+
+    impl PollingCliprdr {
+        pub fn new() -> Box<PollingCliprdr> {
+            Box::new(PollingCliprdr(PollingCliprdrInner::new()))
+        }
+
+        pub fn next_clipboard_event(&self) -> Option<Box<ClipboardEvent>> {
+            self.0.receiver.try_recv().ok().map(ClipboardEvent).map(Box::new)
+        }
+
+        pub fn backend_factory(&self) -> Box<CliprdrBackendFactory> {
+            Box::new(CliprdrBackendFactory(Box::new(self.0.backend_factory())))
+        }
+    }
+
+    #[diplomat::opaque]
+    pub struct ClipboardEvent(ClipboardEventInner);
+
+    pub enum ClipboardEventType {
+        RequestFormatList,
+        FormatListReceived,
+        RemoteCopy,
+        FormatDataRequest,
+        FormatDataResponse,
+    }
+
+    impl ClipboardEvent {
+        pub fn get_event_type(&self) -> ClipboardEventType {
+            match &self.0 {
+                ClipboardEventInner::RequestFormatList => ClipboardEventType::RequestFormatList,
+                ClipboardEventInner::FormatListReceived => ClipboardEventType::FormatListReceived,
+                ClipboardEventInner::RemoteCopy(_) => ClipboardEventType::RemoteCopy,
+                ClipboardEventInner::FormatDataRequest(_) => ClipboardEventType::FormatDataRequest,
+                ClipboardEventInner::FormatDataResponse(_) => ClipboardEventType::FormatDataResponse,
+            }
+        }
+
+        /// Formats available on the remote clipboard, to be requested with `ActiveStage::initiate_clipboard_paste`
+        pub fn get_remote_copy(&self) -> Option<Box<ClipboardFormatIterator>> {
+            match &self.0 {
+                ClipboardEventInner::RemoteCopy(formats) => Some(formats.clone()),
+                _ => None,
+            }
+            .map(ClipboardFormatIterator)
+            .map(Box::new)
+        }
+
+        /// Format requested by the server, to be answered with `ActiveStage::submit_clipboard_format_data`
+        pub fn get_format_data_request(&self) -> Option<Box<ClipboardFormatId>> {
+            match &self.0 {
+                ClipboardEventInner::FormatDataRequest(format_id) => Some(*format_id),
+                _ => None,
+            }
+            .map(ClipboardFormatId)
+            .map(Box::new)
+        }
+
+        /// Data sent by the server in response to `ActiveStage::initiate_clipboard_paste`
+        pub fn get_format_data_response(&self) -> Option<Box<FormatDataResponse>> {
+            match &self.0 {
+                ClipboardEventInner::FormatDataResponse(response) => Some(response.clone()),
+                _ => None,
+            }
+            .map(Some)
+            .map(FormatDataResponse)
+            .map(Box::new)
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ClipboardEventInner {
+    /// The server requests the formats available on the local clipboard
+    RequestFormatList,
+    /// The server acknowledged the formats available on the local clipboard
+    FormatListReceived,
+    RemoteCopy(Vec<ClipboardFormat>),
+    FormatDataRequest(ClipboardFormatId),
+    FormatDataResponse(OwnedFormatDataResponse),
+}
+
+pub struct PollingCliprdrInner {
+    sender: mpsc::Sender<ClipboardEventInner>,
+    receiver: mpsc::Receiver<ClipboardEventInner>,
+}
+
+impl PollingCliprdrInner {
+    fn new() -> Self {
+        let (sender, receiver) = mpsc::channel();
+        Self { sender, receiver }
+    }
+
+    fn backend_factory(&self) -> PollingCliprdrBackendFactory {
+        PollingCliprdrBackendFactory {
+            sender: self.sender.clone(),
+        }
+    }
+}
+
+struct PollingCliprdrBackendFactory {
+    sender: mpsc::Sender<ClipboardEventInner>,
+}
+
+impl CliprdrBackendFactory for PollingCliprdrBackendFactory {
+    fn build_cliprdr_backend(&self) -> Box<dyn CliprdrBackend> {
+        Box::new(PollingCliprdrBackend {
+            sender: self.sender.clone(),
+        })
+    }
+}
+
+#[derive(Debug)]
+struct PollingCliprdrBackend {
+    sender: mpsc::Sender<ClipboardEventInner>,
+}
+
+impl PollingCliprdrBackend {
+    fn send_event(&self, event: ClipboardEventInner) {
+        if let Err(err) = self.sender.send(event) {
+            error!("Failed to send clipboard event: {:?}", err);
+        }
+    }
+}
+
+impl_as_any!(PollingCliprdrBackend);
+
+impl CliprdrBackend for PollingCliprdrBackend {
+    fn temporary_directory(&self) -> &str {
+        ".cliprdr"
+    }
+
+    fn client_capabilities(&self) -> ClipboardGeneralCapabilityFlags {
+        // No additional capabilities yet
+        ClipboardGeneralCapabilityFlags::empty()
+    }
+
+    fn on_request_format_list(&mut self) {
+        self.send_event(ClipboardEventInner::RequestFormatList);
+    }
+
+    fn on_format_list_received(&mut self) {
+        self.send_event(ClipboardEventInner::FormatListReceived);
+    }
+
+    fn on_process_negotiated_capabilities(&mut self, _: ClipboardGeneralCapabilityFlags) {
+        // No additional capabilities yet
+    }
+
+    fn on_remote_copy(&mut self, available_formats: &[ClipboardFormat]) {
+        self.send_event(ClipboardEventInner::RemoteCopy(available_formats.to_vec()));
+    }
+
+    fn on_format_data_request(&mut self, request: FormatDataRequest) {
+        self.send_event(ClipboardEventInner::FormatDataRequest(request.format));
+    }
+
+    fn on_format_data_response(&mut self, response: FormatDataResponse<'_>) {
+        self.send_event(ClipboardEventInner::FormatDataResponse(response.into_owned()));
+    }
+
+    fn on_file_contents_request(&mut self, _request: FileContentsRequest) {
+        // File transfer not implemented yet
+    }
+
+    fn on_file_contents_response(&mut self, _response: FileContentsResponse<'_>) {
+        // File transfer not implemented yet
+    }
+
+    fn on_lock(&mut self, _data_id: LockDataId) {
+        // File transfer not implemented yet
+    }
+
+    fn on_unlock(&mut self, _data_id: LockDataId) {
+        // File transfer not implemented yet
+    }
+}
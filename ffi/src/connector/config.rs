@@ -38,6 +38,7 @@ pub mod ffi {
         pub platform: Option<MajorPlatformType>,
         pub no_server_pointer: Option<bool>,
         pub autologon: Option<bool>,
+        pub restricted_admin: Option<bool>,
         pub pointer_software_rendering: Option<bool>,
         pub performance_flags: Option<ironrdp::pdu::rdp::client_info::PerformanceFlags>,
     }
@@ -146,6 +147,10 @@ pub mod ffi {
             self.autologon = Some(autologon);
         }
 
+        pub fn set_restricted_admin(&mut self, restricted_admin: bool) {
+            self.restricted_admin = Some(restricted_admin);
+        }
+
         pub fn set_pointer_software_rendering(&mut self, pointer_software_rendering: bool) {
             self.pointer_software_rendering = Some(pointer_software_rendering);
         }
@@ -191,6 +196,12 @@ pub mod ffi {
 
                 no_server_pointer: self.no_server_pointer.unwrap_or(false),
                 autologon: self.autologon.unwrap_or(false),
+                restricted_admin: self.restricted_admin.unwrap_or(false),
+                auto_reconnect: None,
+                decode_mode: ironrdp::core::DecodeMode::Lenient,
+                decode_limits: ironrdp::core::DecodeLimits::default(),
+                monitors: Vec::new(),
+                persistent_bitmap_cache: Vec::new(),
                 pointer_software_rendering: self.pointer_software_rendering.unwrap_or(false),
                 performance_flags: self.performance_flags.ok_or("performance flag is missing")?,
                 desktop_scale_factor: 0,
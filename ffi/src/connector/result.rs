@@ -3,6 +3,7 @@ pub mod ffi {
     use crate::{
         connector::config::ffi::DesktopSize,
         error::{ffi::IronRdpError, ValueConsumedError},
+        pdu::ffi::SecurityProtocol,
         utils::ffi::OptionalUsize,
     };
 
@@ -74,5 +75,25 @@ pub mod ffi {
                 .ok_or_else(|| ValueConsumedError::for_item("ConnectionResult"))?
                 .pointer_software_rendering)
         }
+
+        pub fn get_security_protocol(&self) -> Result<Box<SecurityProtocol>, Box<IronRdpError>> {
+            Ok(Box::new(SecurityProtocol(
+                self.0
+                    .as_ref()
+                    .ok_or_else(|| ValueConsumedError::for_item("ConnectionResult"))?
+                    .security_info
+                    .security_protocol,
+            )))
+        }
+
+        /// Whether the user was authenticated with CredSSP (Network Level Authentication)
+        pub fn get_nla(&self) -> Result<bool, Box<IronRdpError>> {
+            Ok(self
+                .0
+                .as_ref()
+                .ok_or_else(|| ValueConsumedError::for_item("ConnectionResult"))?
+                .security_info
+                .nla)
+        }
     }
 }
@@ -72,6 +72,7 @@ pub mod ffi {
                         server_name.into(),
                         server_public_key.to_owned(),
                         kerbero_configs.map(|config| config.0.clone()),
+                        connector.config.restricted_admin,
                     )?;
 
                     Ok(Box::new(CredsspSequenceInitResult {
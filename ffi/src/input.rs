@@ -14,6 +14,23 @@ pub mod ffi {
             let res = self.0.apply(std::iter::once(operation.0.clone()));
             Box::new(res.to_vec().into())
         }
+
+        /// Releases all the keys and mouse buttons currently pressed, e.g.: when the window loses the focus.
+        pub fn release_all(&mut self) -> Box<FastPathInputEventIterator> {
+            let res = self.0.release_all();
+            Box::new(res.to_vec().into())
+        }
+
+        /// Synchronizes the state of the lock keys with the server, e.g.: when the window gains the focus.
+        pub fn synchronize_event(
+            scroll_lock: bool,
+            num_lock: bool,
+            caps_lock: bool,
+            kana_lock: bool,
+        ) -> Box<FastPathInputEventIterator> {
+            let event = ironrdp::input::synchronize_event(scroll_lock, num_lock, caps_lock, kana_lock);
+            Box::new(vec![event].into())
+        }
     }
 
     #[diplomat::opaque]
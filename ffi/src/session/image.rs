@@ -1,5 +1,9 @@
 #[diplomat::bridge]
 pub mod ffi {
+    use ironrdp::pdu::geometry::Rectangle as _;
+
+    use crate::error::ffi::IronRdpError;
+    use crate::pdu::ffi::InclusiveRectangle;
     use crate::utils::ffi::BytesSlice;
 
     #[diplomat::opaque]
@@ -26,6 +30,60 @@ pub mod ffi {
         pub fn get_height(&self) -> u16 {
             self.0.height()
         }
+
+        pub fn get_pixel_format(&self) -> PixelFormat {
+            self.0.pixel_format().into()
+        }
+
+        /// Number of bytes per row of the image data
+        pub fn get_stride(&self) -> usize {
+            usize::from(self.0.width())
+                .checked_mul(usize::from(self.0.pixel_format().bytes_per_pixel()))
+                .expect("image row length fits in usize")
+        }
+
+        /// Copies the pixels of the given region (e.g.: the rectangle of a graphics update) into `buffer`,
+        /// row after row without padding.
+        pub fn copy_region(&self, region: &InclusiveRectangle, buffer: &mut [u8]) -> Result<(), Box<IronRdpError>> {
+            let region = &region.0;
+
+            if region.left > region.right || region.top > region.bottom {
+                return Err("invalid region".into());
+            }
+
+            if region.right >= self.0.width() || region.bottom >= self.0.height() {
+                return Err("region is out of the image bounds".into());
+            }
+
+            let bytes_per_pixel = usize::from(self.0.pixel_format().bytes_per_pixel());
+            let row_start = usize::from(region.left)
+                .checked_mul(bytes_per_pixel)
+                .ok_or("region is too large")?;
+            let row_len = usize::from(region.width())
+                .checked_mul(bytes_per_pixel)
+                .ok_or("region is too large")?;
+            let row_end = row_start.checked_add(row_len).ok_or("region is too large")?;
+            let region_len = row_len
+                .checked_mul(usize::from(region.height()))
+                .ok_or("region is too large")?;
+
+            if buffer.len() < region_len {
+                return Err("buffer is too small".into());
+            }
+
+            let rows = self
+                .0
+                .data()
+                .chunks_exact(self.get_stride())
+                .skip(usize::from(region.top))
+                .take(usize::from(region.height()));
+
+            for (row, dst) in rows.zip(buffer.chunks_exact_mut(row_len)) {
+                dst.copy_from_slice(&row[row_start..row_end]);
+            }
+
+            Ok(())
+        }
     }
 
     #[diplomat::enum_convert(ironrdp::graphics::image_processing::PixelFormat)]
@@ -2,6 +2,9 @@ pub mod image;
 
 #[diplomat::bridge]
 pub mod ffi {
+    use std::fmt::Write as _;
+
+    use diplomat_runtime::DiplomatWriteable;
 
     use crate::{
         clipboard::message::ffi::{ClipboardFormatId, ClipboardFormatIterator, FormatDataResponse},
@@ -178,6 +181,7 @@ pub mod ffi {
         PointerBitmap,
         Terminate,
         DeactivateAll,
+        MonitorLayout,
     }
 
     impl ActiveStageOutput {
@@ -191,6 +195,7 @@ pub mod ffi {
                 ironrdp::session::ActiveStageOutput::PointerBitmap { .. } => ActiveStageOutputType::PointerBitmap,
                 ironrdp::session::ActiveStageOutput::Terminate { .. } => ActiveStageOutputType::Terminate,
                 ironrdp::session::ActiveStageOutput::DeactivateAll { .. } => ActiveStageOutputType::DeactivateAll,
+                ironrdp::session::ActiveStageOutput::MonitorLayout { .. } => ActiveStageOutputType::MonitorLayout,
             }
         }
 
@@ -260,4 +265,30 @@ pub mod ffi {
 
     #[diplomat::opaque]
     pub struct GracefulDisconnectReason(pub ironrdp::session::GracefulDisconnectReason);
+
+    pub enum GracefulDisconnectReasonType {
+        UserInitiated,
+        ServerInitiated,
+        Other,
+    }
+
+    impl GracefulDisconnectReason {
+        pub fn get_reason_type(&self) -> GracefulDisconnectReasonType {
+            match self.0 {
+                ironrdp::session::GracefulDisconnectReason::UserInitiated => {
+                    GracefulDisconnectReasonType::UserInitiated
+                }
+                ironrdp::session::GracefulDisconnectReason::ServerInitiated => {
+                    GracefulDisconnectReasonType::ServerInitiated
+                }
+                ironrdp::session::GracefulDisconnectReason::Other(_) => GracefulDisconnectReasonType::Other,
+            }
+        }
+
+        /// Returns a GUI-friendly description of the disconnect reason.
+        pub fn get_description(&self, writeable: &mut DiplomatWriteable) {
+            let _ = write!(writeable, "{}", self.0.description());
+            writeable.flush();
+        }
+    }
 }
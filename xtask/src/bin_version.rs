@@ -10,3 +10,4 @@ pub const WASM_PACK: CargoPackage = CargoPackage::new("wasm-pack", "0.12.1");
 pub const TYPOS_CLI: CargoPackage = CargoPackage::new("typos-cli", "1.16.23").with_binary_name("typos");
 
 pub const WABT_VERSION: &str = "1.0.33";
+pub const WASMTIME_VERSION: &str = "14.0.4";
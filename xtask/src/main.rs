@@ -34,6 +34,18 @@ pub const CARGO: &str = env!("CARGO");
 
 pub const WASM_PACKAGES: &[&str] = &["ironrdp-web"];
 
+/// Optional features of `ironrdp-web`, checked in every combination
+pub const WEB_OPTIONAL_FEATURES: &[&str] = &["rfx", "clipboard", "audio", "rdpdr"];
+
+/// Packages which must build without `std` (`--no-default-features --features alloc`)
+pub const NO_STD_PACKAGES: &[&str] = &["ironrdp-core", "ironrdp-pdu"];
+
+/// Target without `std`, so that a dependency pulling `std` in fails the no-std check
+pub const NO_STD_TARGET: &str = "thumbv7em-none-eabihf";
+
+/// Target of the SIMD equivalence tests, run with wasmtime
+pub const SIMD_TEST_TARGET: &str = "wasm32-wasi";
+
 pub const FUZZ_TARGETS: &[&str] = &[
     "pdu_decoding",
     "rle_decompression",
@@ -4,4 +4,7 @@ pub use xshell::{cmd, Shell};
 pub use crate::bin_install::{cargo_install, is_installed};
 pub use crate::bin_version::*;
 pub use crate::section::Section;
-pub use crate::{is_verbose, list_files, CARGO, FUZZ_TARGETS, WASM_PACKAGES};
+pub use crate::{
+    is_verbose, list_files, CARGO, FUZZ_TARGETS, NO_STD_PACKAGES, NO_STD_TARGET, SIMD_TEST_TARGET, WASM_PACKAGES,
+    WEB_OPTIONAL_FEATURES,
+};
@@ -30,15 +30,89 @@ pub fn check(sh: &Shell) -> anyhow::Result<()> {
         }
     }
 
+    check_web_feature_matrix(sh)?;
+    check_no_std(sh)?;
+    test_simd(sh)?;
+
     println!("All good!");
 
     Ok(())
 }
 
+/// Checks every combination of the optional `ironrdp-web` features (see the feature matrix in its README)
+fn check_web_feature_matrix(sh: &Shell) -> anyhow::Result<()> {
+    let mut combinations = vec![Vec::new()];
+
+    for feature in WEB_OPTIONAL_FEATURES {
+        let with_feature: Vec<Vec<&str>> = combinations
+            .iter()
+            .map(|combination| {
+                let mut combination = combination.clone();
+                combination.push(*feature);
+                combination
+            })
+            .collect();
+
+        combinations.extend(with_feature);
+    }
+
+    for combination in combinations {
+        let features = combination.join(",");
+
+        println!("Check ironrdp-web with features [{features}]");
+
+        cmd!(
+            sh,
+            "{CARGO} check --locked --target wasm32-unknown-unknown --package ironrdp-web --no-default-features --features={features}"
+        )
+        .run()?;
+    }
+
+    Ok(())
+}
+
+/// Checks that the protocol crates build without `std`, as needed by restricted WASM environments
+///
+/// The check runs on a target without `std`: `wasm32-unknown-unknown` ships one, and would not catch a dependency
+/// pulling it in.
+fn check_no_std(sh: &Shell) -> anyhow::Result<()> {
+    for package in NO_STD_PACKAGES {
+        println!("Check {package} without std");
+
+        cmd!(
+            sh,
+            "{CARGO} check --locked --target {NO_STD_TARGET} --package {package} --no-default-features --features=alloc"
+        )
+        .run()?;
+    }
+
+    Ok(())
+}
+
+/// Runs the scalar vs SIMD equivalence tests of the codec hot loops
+///
+/// The tests are built for `wasm32-wasi` with the `simd128` target feature, and run with wasmtime.
+fn test_simd(sh: &Shell) -> anyhow::Result<()> {
+    println!("Test ironrdp-graphics with simd128");
+
+    // A separate target directory avoids invalidating the other builds, since RUSTFLAGS is changed.
+    cmd!(
+        sh,
+        "{CARGO} test --locked --target {SIMD_TEST_TARGET} --target-dir ./target/simd128 --package ironrdp-graphics --lib -- simd"
+    )
+    .env("RUSTFLAGS", "-C target-feature=+simd128")
+    .env("CARGO_TARGET_WASM32_WASI_RUNNER", "wasmtime")
+    .run()?;
+
+    Ok(())
+}
+
 pub fn install(sh: &Shell) -> anyhow::Result<()> {
     let _s = Section::new("WASM-INSTALL");
 
     cmd!(sh, "rustup target add wasm32-unknown-unknown").run()?;
+    cmd!(sh, "rustup target add {NO_STD_TARGET}").run()?;
+    cmd!(sh, "rustup target add {SIMD_TEST_TARGET}").run()?;
 
     match cmd!(sh, "wasm2wat --version").read() {
         Ok(version) => println!("Found wasm2wat {version}"),
@@ -48,6 +122,14 @@ pub fn install(sh: &Shell) -> anyhow::Result<()> {
         }
     }
 
+    match cmd!(sh, "wasmtime --version").read() {
+        Ok(version) => println!("Found {version}"),
+        Err(e) => {
+            trace!("{e}");
+            install_wasmtime(sh)?;
+        }
+    }
+
     Ok(())
 }
 
@@ -101,3 +183,57 @@ fn install_wasm2wat(sh: &Shell) -> anyhow::Result<()> {
 
     Ok(())
 }
+
+fn install_wasmtime(sh: &Shell) -> anyhow::Result<()> {
+    println!("Installing wasmtime in local root...");
+
+    let _guard = sh.push_dir(crate::LOCAL_CARGO_ROOT);
+
+    let (platform_suffix, extension) = if cfg!(target_os = "windows") {
+        ("x86_64-windows", "zip")
+    } else if cfg!(all(target_os = "macos", target_arch = "aarch64")) {
+        ("aarch64-macos", "tar.xz")
+    } else if cfg!(target_os = "macos") {
+        ("x86_64-macos", "tar.xz")
+    } else {
+        ("x86_64-linux", "tar.xz")
+    };
+
+    let url = format!(
+        "https://github.com/bytecodealliance/wasmtime/releases/download/v{WASMTIME_VERSION}/wasmtime-v{WASMTIME_VERSION}-{platform_suffix}.{extension}"
+    );
+    let archive = format!("wasmtime.{extension}");
+
+    cmd!(sh, "curl --location --remote-header-name {url} --output {archive}").run()?;
+
+    if is_verbose() {
+        list_files(sh, ".")?;
+    }
+
+    sh.create_dir("wasmtime")?;
+    // bsdtar, shipped with Windows, also extracts zip archives.
+    cmd!(sh, "tar xf {archive} -C ./wasmtime --strip-components 1").run()?;
+
+    if is_verbose() {
+        list_files(sh, "./wasmtime")?;
+    }
+
+    trace!("Copy wasmtime to local bin");
+
+    if cfg!(target_os = "windows") {
+        sh.copy_file("./wasmtime/wasmtime.exe", "./bin/wasmtime.exe")?;
+    } else {
+        sh.copy_file("./wasmtime/wasmtime", "./bin/wasmtime")?;
+    }
+
+    trace!("Clean artifacts");
+
+    sh.remove_path(&archive)?;
+    sh.remove_path("./wasmtime")?;
+
+    if is_verbose() {
+        list_files(sh, ".")?;
+    }
+
+    Ok(())
+}